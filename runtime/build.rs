@@ -0,0 +1,5 @@
+fn main() {
+    //applications loaded by `rusty-run` resolve the runtime's helpers (e.g. `__rusty_lint_to_string`)
+    //against the executable, so its symbols are exported
+    println!("cargo:rustc-link-arg-bins=-rdynamic");
+}
@@ -0,0 +1,81 @@
+//! executes the tasks bound to a core in parallel to the other tasks
+//!
+//! the tasks are grouped by their core, every group is executed by its own scheduler on a thread
+//! pinned to the core. The tasks of different cores access the application's globals at the same
+//! time, the compiler reports the globals shared between tasks unless they are declared in a
+//! `VAR_GLOBAL {atomic}` block
+use std::collections::BTreeMap;
+
+use crate::application::Task;
+
+/// groups the tasks by their core, the tasks without core are grouped under `None`
+pub fn group_by_core(tasks: Vec<Task>) -> BTreeMap<Option<u32>, Vec<Task>> {
+    let mut groups: BTreeMap<Option<u32>, Vec<Task>> = BTreeMap::new();
+    for task in tasks {
+        groups.entry(task.core).or_default().push(task);
+    }
+    groups
+}
+
+/// restricts the calling thread to the given core
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(core: u32) -> Result<(), String> {
+    if core as usize >= libc::CPU_SETSIZE as usize {
+        return Err(format!("Cannot execute tasks on unknown core {}", core));
+    }
+    let result = unsafe {
+        let mut cores: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core as usize, &mut cores);
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &cores)
+    };
+    if result != 0 {
+        return Err(format!(
+            "Cannot execute tasks on core {}: {}",
+            core,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+/// restricts the calling thread to the given core
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(core: u32) -> Result<(), String> {
+    Err(format!(
+        "Cannot execute tasks on core {}, tasks can only be bound to cores on Linux",
+        core
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::group_by_core;
+    use crate::application::Task;
+
+    extern "C" fn noop() {}
+
+    #[test]
+    fn tasks_are_grouped_by_their_core() {
+        let tasks = vec![
+            Task::new("motion", 1, None, noop).with_core(1),
+            Task::new("hmi", 2, None, noop),
+            Task::new("drives", 1, None, noop).with_core(1),
+            Task::new("io", 0, None, noop).with_core(0),
+        ];
+
+        let groups = group_by_core(tasks);
+        let groups = groups
+            .iter()
+            .map(|(core, tasks)| (*core, tasks.iter().map(Task::get_name).collect()))
+            .collect::<Vec<(Option<u32>, Vec<&str>)>>();
+
+        assert_eq!(
+            groups,
+            vec![
+                (None, vec!["hmi"]),
+                (Some(0), vec!["io"]),
+                (Some(1), vec!["motion", "drives"]),
+            ]
+        );
+    }
+}
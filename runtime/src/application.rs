@@ -0,0 +1,293 @@
+use std::{
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_void},
+    time::Duration,
+};
+
+use crate::{
+    symbols::{Symbol, SymbolNode, SymbolTable},
+    telemetry::Telemetry,
+};
+
+/// the descriptor of a task as generated by the compiler (`__rusty_task`)
+#[repr(C)]
+struct TaskDescriptor {
+    name: *const c_char,
+    priority: i32,
+    /// the interval in milliseconds
+    interval: i64,
+    entry: extern "C" fn(),
+    /// the number of the external event executing the task, -1 for cyclic tasks
+    event: i32,
+    /// the core executing the task, -1 if the task is not bound to a core
+    core: i32,
+}
+
+/// the descriptor of a retained variable as generated by the compiler (`__rusty_retain_entry`)
+#[repr(C)]
+struct RetainDescriptor {
+    address: *mut u8,
+    size: i64,
+}
+
+/// a node of the symbol trie as generated by the compiler (`__rusty_symbol`)
+#[repr(C)]
+struct SymbolDescriptor {
+    name: *const c_char,
+    type_name: *const c_char,
+    offset: u64,
+    size: u64,
+    first_child: i32,
+    child_count: i32,
+}
+
+const TASKS: &str = "__rusty_tasks";
+const TASK_COUNT: &str = "__rusty_task_count";
+const RETAIN: &str = "__rusty_retain";
+const RETAIN_COUNT: &str = "__rusty_retain_count";
+const SYMBOLS: &str = "__rusty_symbols";
+const SYMBOL_COUNT: &str = "__rusty_symbol_count";
+const TELEMETRY: &str = "__rusty_telemetry";
+const TELEMETRY_INTERVAL: &str = "__rusty_telemetry_interval";
+
+/// a task of the application, executing its programs in order
+pub struct Task {
+    name: CString,
+    pub priority: i32,
+    /// the task's cycle time, a task without interval is executed in every scheduler tick
+    pub interval: Option<Duration>,
+    /// the external event executing the task instead of its interval (see `events`)
+    pub event: Option<u32>,
+    /// the core executing the task (see `affinity`), tasks without core run on the main thread
+    pub core: Option<u32>,
+    entry: extern "C" fn(),
+}
+
+impl Task {
+    pub fn new(
+        name: &str,
+        priority: i32,
+        interval: Option<Duration>,
+        entry: extern "C" fn(),
+    ) -> Self {
+        Task {
+            name: CString::new(name).unwrap_or_default(),
+            priority,
+            interval,
+            event: None,
+            core: None,
+            entry,
+        }
+    }
+
+    /// creates a task executed whenever the given external event was raised
+    pub fn new_event_task(name: &str, priority: i32, event: u32, entry: extern "C" fn()) -> Self {
+        Task {
+            event: Some(event),
+            ..Task::new(name, priority, None, entry)
+        }
+    }
+
+    /// binds the task to the given core
+    pub fn with_core(mut self, core: u32) -> Self {
+        self.core = Some(core);
+        self
+    }
+
+    pub fn get_name(&self) -> &str {
+        self.name.to_str().unwrap_or_default()
+    }
+
+    pub(crate) fn get_c_name(&self) -> &CStr {
+        &self.name
+    }
+
+    pub fn execute(&self) {
+        (self.entry)()
+    }
+}
+
+/// an application loaded from a shared object
+pub struct Application {
+    handle: *mut c_void,
+    tasks: Vec<Task>,
+    retained: Vec<(*mut u8, usize)>,
+    symbols: SymbolTable,
+    telemetry: Option<Telemetry>,
+}
+
+impl Application {
+    /// loads the shared object at the given path and reads its task, retain and symbol tables
+    pub fn load(path: &str) -> Result<Application, String> {
+        let file = CString::new(path).map_err(|err| err.to_string())?;
+        let handle = unsafe { libc::dlopen(file.as_ptr(), libc::RTLD_NOW) };
+        if handle.is_null() {
+            return Err(format!("Cannot load {}: {}", path, get_dl_error()));
+        }
+        let mut application = Application {
+            handle,
+            tasks: vec![],
+            retained: vec![],
+            symbols: SymbolTable::default(),
+            telemetry: None,
+        };
+
+        let tasks = unsafe { application.get_table::<TaskDescriptor>(TASKS, TASK_COUNT) }
+            .ok_or_else(|| {
+                format!(
+                    "{} contains no task table, compile it with --task-conf",
+                    path
+                )
+            })?;
+        application.tasks = tasks
+            .iter()
+            .map(|it| {
+                let name = unsafe { CStr::from_ptr(it.name) }.to_string_lossy();
+                let task = if let Ok(event) = u32::try_from(it.event) {
+                    Task::new_event_task(&name, it.priority, event, it.entry)
+                } else {
+                    let interval = if it.interval > 0 {
+                        Some(Duration::from_millis(it.interval as u64))
+                    } else {
+                        None
+                    };
+                    Task::new(&name, it.priority, interval, it.entry)
+                };
+                match u32::try_from(it.core) {
+                    Ok(core) => task.with_core(core),
+                    Err(_) => task,
+                }
+            })
+            .collect();
+
+        let retained = unsafe { application.get_table::<RetainDescriptor>(RETAIN, RETAIN_COUNT) }
+            .unwrap_or_default();
+        application.retained = retained
+            .iter()
+            .map(|it| (it.address, it.size as usize))
+            .collect();
+
+        let symbols = unsafe { application.get_table::<SymbolDescriptor>(SYMBOLS, SYMBOL_COUNT) }
+            .unwrap_or_default();
+        application.symbols = SymbolTable::new(
+            symbols
+                .iter()
+                .map(|it| SymbolNode {
+                    name: unsafe { CStr::from_ptr(it.name) }
+                        .to_string_lossy()
+                        .to_string(),
+                    type_name: unsafe { CStr::from_ptr(it.type_name) }
+                        .to_string_lossy()
+                        .to_string(),
+                    offset: it.offset,
+                    size: it.size,
+                    first_child: it.first_child.max(0) as usize,
+                    child_count: it.child_count.max(0) as usize,
+                })
+                .collect(),
+        );
+
+        if let (Some(serialize), Some(interval)) = (
+            application.get_symbol(TELEMETRY),
+            application.get_symbol(TELEMETRY_INTERVAL),
+        ) {
+            let serialize: extern "C" fn(*mut u8, i32) -> i32 =
+                unsafe { std::mem::transmute(serialize) };
+            let interval = unsafe { *(interval as *const i64) };
+            let interval = (interval > 0).then(|| Duration::from_millis(interval as u64));
+            application.telemetry = Some(Telemetry::new(serialize, interval));
+        }
+        Ok(application)
+    }
+
+    /// returns the table and its length stored in the given symbols
+    ///
+    /// # Safety
+    /// the symbols must hold an array of `T` and its length as generated by the compiler
+    unsafe fn get_table<T>(&self, table: &str, count: &str) -> Option<&[T]> {
+        let table = self.get_symbol(table)? as *const T;
+        let count = *(self.get_symbol(count)? as *const i32);
+        Some(std::slice::from_raw_parts(table, count.max(0) as usize))
+    }
+
+    fn get_symbol(&self, name: &str) -> Option<*mut c_void> {
+        let name = CString::new(name).ok()?;
+        let symbol = unsafe { libc::dlsym(self.handle, name.as_ptr()) };
+        (!symbol.is_null()).then(|| symbol)
+    }
+
+    /// removes the tasks from the application, they stay executable while the application is loaded
+    pub fn take_tasks(&mut self) -> Vec<Task> {
+        std::mem::take(&mut self.tasks)
+    }
+
+    /// removes the telemetry publisher from the application, `None` if the application was
+    /// compiled without a `telemetry` section
+    pub fn take_telemetry(&mut self) -> Option<Telemetry> {
+        self.telemetry.take()
+    }
+
+    pub fn get_symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    /// returns the memory of the variable at the given path (e.g. `prg.fb1.sub.out`)
+    pub fn read_symbol(&self, path: &str) -> Result<Vec<u8>, String> {
+        let symbol = self.find_symbol(path)?;
+        Ok(
+            unsafe { std::slice::from_raw_parts(symbol.address as *const u8, symbol.size) }
+                .to_vec(),
+        )
+    }
+
+    /// overwrites the memory of the variable at the given path, the value has to match its size
+    pub fn write_symbol(&mut self, path: &str, value: &[u8]) -> Result<(), String> {
+        let symbol = self.find_symbol(path)?;
+        if value.len() != symbol.size {
+            return Err(format!(
+                "Cannot write {} bytes to '{}' of type {} ({} bytes)",
+                value.len(),
+                path,
+                symbol.type_name,
+                symbol.size
+            ));
+        }
+        unsafe {
+            std::ptr::copy_nonoverlapping(value.as_ptr(), symbol.address as *mut u8, symbol.size)
+        };
+        Ok(())
+    }
+
+    fn find_symbol(&self, path: &str) -> Result<Symbol, String> {
+        self.symbols
+            .find_symbol(path)
+            .ok_or_else(|| format!("Unknown symbol '{}'", path))
+    }
+
+    /// returns the memory of all retained variables
+    pub fn get_retained(&mut self) -> Vec<&mut [u8]> {
+        self.retained
+            .iter()
+            .map(|(address, size)| unsafe { std::slice::from_raw_parts_mut(*address, *size) })
+            .collect()
+    }
+}
+
+impl Drop for Application {
+    fn drop(&mut self) {
+        unsafe {
+            libc::dlclose(self.handle);
+        }
+    }
+}
+
+fn get_dl_error() -> String {
+    let error = unsafe { libc::dlerror() };
+    if error.is_null() {
+        "unknown error".to_string()
+    } else {
+        unsafe { CStr::from_ptr(error) }
+            .to_string_lossy()
+            .to_string()
+    }
+}
@@ -0,0 +1,111 @@
+//! calculates the checksums of byte buffers for the applications' checksum functions
+//! (e.g. `CRC16`, `CRC32`)
+//!
+//! the compiler passes the address of the buffer's first byte and the number of bytes:
+//! - `CRC16` is the CRC-16/MODBUS (polynomial `16#A001` reflected, initial value `16#FFFF`)
+//! - `CRC32` is the CRC-32 of ethernet and zip (polynomial `16#EDB88320` reflected, initial and
+//!   final value `16#FFFFFFFF`)
+//! - `CHECKSUM_SUM` adds the bytes modulo 256, `CHECKSUM_XOR` combines them by XOR
+use std::slice;
+
+/// the helpers by their symbol name, so they can be mapped into a JIT
+pub fn get_helpers() -> Vec<(&'static str, usize)> {
+    vec![
+        ("__rusty_crc16", __rusty_crc16 as *const () as usize),
+        ("__rusty_crc32", __rusty_crc32 as *const () as usize),
+        (
+            "__rusty_checksum_sum",
+            __rusty_checksum_sum as *const () as usize,
+        ),
+        (
+            "__rusty_checksum_xor",
+            __rusty_checksum_xor as *const () as usize,
+        ),
+    ]
+}
+
+/// returns the given buffer, an empty one if it is null
+unsafe fn bytes<'a>(data: *const u8, length: u32) -> &'a [u8] {
+    if data.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(data, length as usize)
+    }
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0xFFFF, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u16, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(0xFFFF_FFFF, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// # Safety
+/// `data` is null or points to `length` bytes
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_crc16(data: *const u8, length: u32) -> u16 {
+    crc16(bytes(data, length))
+}
+
+/// # Safety
+/// `data` is null or points to `length` bytes
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_crc32(data: *const u8, length: u32) -> u32 {
+    crc32(bytes(data, length))
+}
+
+/// # Safety
+/// `data` is null or points to `length` bytes
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_checksum_sum(data: *const u8, length: u32) -> u8 {
+    bytes(data, length)
+        .iter()
+        .fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// # Safety
+/// `data` is null or points to `length` bytes
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_checksum_xor(data: *const u8, length: u32) -> u8 {
+    bytes(data, length).iter().fold(0, |sum, byte| sum ^ byte)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crcs_match_their_check_values() {
+        assert_eq!(crc16(b"123456789"), 0x4B37);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc16(&[]), 0xFFFF);
+        assert_eq!(crc32(&[]), 0);
+    }
+
+    #[test]
+    fn checksums_wrap_around() {
+        let data = [0xF0, 0x20, 0x01];
+        unsafe {
+            assert_eq!(__rusty_checksum_sum(data.as_ptr(), 3), 0x11);
+            assert_eq!(__rusty_checksum_xor(data.as_ptr(), 3), 0xD1);
+            assert_eq!(__rusty_checksum_sum(std::ptr::null(), 3), 0);
+        }
+    }
+}
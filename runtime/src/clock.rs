@@ -0,0 +1,66 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// the time base of the scheduler
+pub trait Clock {
+    /// the time passed since the clock was started
+    fn now(&self) -> Duration;
+
+    /// blocks until the clock reaches the given time
+    fn sleep_until(&self, time: Duration);
+}
+
+/// a clock based on the operating system's monotonic clock
+pub struct MonotonicClock {
+    start: Instant,
+}
+
+impl MonotonicClock {
+    pub fn start() -> Self {
+        MonotonicClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep_until(&self, time: Duration) {
+        if let Some(remaining) = time.checked_sub(self.now()) {
+            std::thread::sleep(remaining);
+        }
+    }
+}
+
+/// the time the application first asked for the time, see `__rusty_time`
+static APPLICATION_START: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// the helpers by their symbol name, so they can be mapped into a JIT
+pub fn get_helpers() -> Vec<(&'static str, usize)> {
+    vec![("__rusty_time", __rusty_time as *const () as usize)]
+}
+
+/// the time passed since the application first asked for it in nanoseconds (a `TIME`), the
+/// application measures the elapsed times of its SFC steps with it
+#[no_mangle]
+pub extern "C" fn __rusty_time() -> i64 {
+    let mut start = APPLICATION_START.lock().unwrap();
+    start.get_or_insert_with(Instant::now).elapsed().as_nanos() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn application_time_is_monotonic() {
+        let first = __rusty_time();
+        std::thread::sleep(Duration::from_millis(1));
+        let second = __rusty_time();
+        assert!(first >= 0);
+        assert!(second - first >= 1_000_000);
+    }
+}
@@ -0,0 +1,881 @@
+//! communicates over TCP, UDP and serial lines for the applications' communication function
+//! blocks (e.g. `TCP_CONNECT`, `TCP_SEND`, `TCP_RECEIVE`)
+//!
+//! the function blocks never block a cycle: a TCP connection is established in the background and
+//! reported as `busy` until it is `done`, a send writes what the connection accepts right away and
+//! a receive returns the bytes that arrived until the current cycle. A connection is identified by
+//! its handle (greater than 0) and closed by `COMM_CLOSE`. A failure sets `error` and reports the
+//! OS's error number in `error_id`, an unknown handle or argument is reported as `-1` and a TCP
+//! connection closed by its peer as `-2`.
+//!
+//! the ST declarations of the function blocks are generated from their descriptions by
+//! `get_declarations`, they have to match the instance structs below.
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    mem::MaybeUninit,
+    net::{TcpStream, UdpSocket},
+    os::unix::{fs::OpenOptionsExt, io::AsRawFd},
+    slice,
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex,
+    },
+    thread,
+};
+
+/// the `error_id` of an unknown handle or argument
+const INVALID_ARGUMENT: i32 = -1;
+/// the `error_id` of a TCP connection closed by its peer
+const CONNECTION_CLOSED: i32 = -2;
+
+enum Connection {
+    /// a TCP connection that is established in the background
+    Connecting(Receiver<io::Result<TcpStream>>),
+    Tcp(TcpStream),
+    Udp(UdpSocket),
+    Serial(File),
+}
+
+/// the open connections, a connection's handle is its index + 1
+static CONNECTIONS: Mutex<Vec<Option<Connection>>> = Mutex::new(Vec::new());
+
+/// the instance of a `TCP_CONNECT` function block
+#[repr(C)]
+pub struct TcpConnect {
+    pub execute: bool,
+    /// the host name or address as a null-terminated `STRING[255]`
+    pub host: [u8; 256],
+    pub port: u16,
+    pub done: bool,
+    pub busy: bool,
+    pub error: bool,
+    pub error_id: i32,
+    pub handle: i32,
+    edge: bool,
+}
+
+/// the instance of a `UDP_OPEN` function block
+#[repr(C)]
+pub struct UdpOpen {
+    pub execute: bool,
+    /// the local port to receive on, 0 for any port
+    pub port: u16,
+    pub done: bool,
+    pub error: bool,
+    pub error_id: i32,
+    pub handle: i32,
+    edge: bool,
+}
+
+/// the instance of a `SERIAL_OPEN` function block
+#[repr(C)]
+pub struct SerialOpen {
+    pub execute: bool,
+    /// the path of the serial device (e.g. `/dev/ttyUSB0`) as a null-terminated `STRING[255]`
+    pub device: [u8; 256],
+    pub baud_rate: u32,
+    pub done: bool,
+    pub error: bool,
+    pub error_id: i32,
+    pub handle: i32,
+    edge: bool,
+}
+
+/// the instance of a `TCP_SEND` or a `SERIAL_SEND` function block
+#[repr(C)]
+pub struct Send {
+    pub execute: bool,
+    pub handle: i32,
+    /// the address of the bytes to send
+    pub buffer: u64,
+    pub size: u32,
+    pub done: bool,
+    pub error: bool,
+    pub error_id: i32,
+    /// the number of bytes sent, less than `size` if the connection accepted less
+    pub count: u32,
+    edge: bool,
+}
+
+/// the instance of a `UDP_SEND` function block
+#[repr(C)]
+pub struct UdpSend {
+    pub execute: bool,
+    pub handle: i32,
+    /// the receiver's address as a null-terminated `STRING[255]`
+    pub host: [u8; 256],
+    pub port: u16,
+    /// the address of the bytes to send
+    pub buffer: u64,
+    pub size: u32,
+    pub done: bool,
+    pub error: bool,
+    pub error_id: i32,
+    pub count: u32,
+    edge: bool,
+}
+
+/// the instance of a `TCP_RECEIVE`, a `UDP_RECEIVE` or a `SERIAL_RECEIVE` function block
+#[repr(C)]
+pub struct Receive {
+    /// receives in every cycle while set
+    pub enable: bool,
+    pub handle: i32,
+    /// the address of the buffer to receive into
+    pub buffer: u64,
+    pub size: u32,
+    /// true if bytes were received in this cycle
+    pub done: bool,
+    pub error: bool,
+    pub error_id: i32,
+    /// the number of bytes received in this cycle
+    pub count: u32,
+}
+
+/// the instance of a `COMM_CLOSE` function block
+#[repr(C)]
+pub struct Close {
+    pub execute: bool,
+    pub handle: i32,
+    pub done: bool,
+    pub error: bool,
+    pub error_id: i32,
+    edge: bool,
+}
+
+/// the description of a function block's ST declaration
+struct Declaration {
+    name: &'static str,
+    inputs: &'static [(&'static str, &'static str)],
+    outputs: &'static [(&'static str, &'static str)],
+    /// true if the function block acts on a rising edge of `execute` and keeps the last value
+    edge_triggered: bool,
+}
+
+const SEND_INPUTS: &[(&str, &str)] = &[
+    ("execute", "BOOL"),
+    ("handle", "DINT"),
+    ("buffer", "LWORD"),
+    ("size", "UDINT"),
+];
+const RECEIVE_INPUTS: &[(&str, &str)] = &[
+    ("enable", "BOOL"),
+    ("handle", "DINT"),
+    ("buffer", "LWORD"),
+    ("size", "UDINT"),
+];
+const OPEN_OUTPUTS: &[(&str, &str)] = &[
+    ("done", "BOOL"),
+    ("error", "BOOL"),
+    ("error_id", "DINT"),
+    ("handle", "DINT"),
+];
+const TRANSFER_OUTPUTS: &[(&str, &str)] = &[
+    ("done", "BOOL"),
+    ("error", "BOOL"),
+    ("error_id", "DINT"),
+    ("count", "UDINT"),
+];
+
+const DECLARATIONS: &[Declaration] = &[
+    Declaration {
+        name: "TCP_CONNECT",
+        inputs: &[
+            ("execute", "BOOL"),
+            ("host", "STRING[255]"),
+            ("port", "UINT"),
+        ],
+        outputs: &[
+            ("done", "BOOL"),
+            ("busy", "BOOL"),
+            ("error", "BOOL"),
+            ("error_id", "DINT"),
+            ("handle", "DINT"),
+        ],
+        edge_triggered: true,
+    },
+    Declaration {
+        name: "TCP_SEND",
+        inputs: SEND_INPUTS,
+        outputs: TRANSFER_OUTPUTS,
+        edge_triggered: true,
+    },
+    Declaration {
+        name: "TCP_RECEIVE",
+        inputs: RECEIVE_INPUTS,
+        outputs: TRANSFER_OUTPUTS,
+        edge_triggered: false,
+    },
+    Declaration {
+        name: "UDP_OPEN",
+        inputs: &[("execute", "BOOL"), ("port", "UINT")],
+        outputs: OPEN_OUTPUTS,
+        edge_triggered: true,
+    },
+    Declaration {
+        name: "UDP_SEND",
+        inputs: &[
+            ("execute", "BOOL"),
+            ("handle", "DINT"),
+            ("host", "STRING[255]"),
+            ("port", "UINT"),
+            ("buffer", "LWORD"),
+            ("size", "UDINT"),
+        ],
+        outputs: TRANSFER_OUTPUTS,
+        edge_triggered: true,
+    },
+    Declaration {
+        name: "UDP_RECEIVE",
+        inputs: RECEIVE_INPUTS,
+        outputs: TRANSFER_OUTPUTS,
+        edge_triggered: false,
+    },
+    Declaration {
+        name: "SERIAL_OPEN",
+        inputs: &[
+            ("execute", "BOOL"),
+            ("device", "STRING[255]"),
+            ("baud_rate", "UDINT"),
+        ],
+        outputs: OPEN_OUTPUTS,
+        edge_triggered: true,
+    },
+    Declaration {
+        name: "SERIAL_SEND",
+        inputs: SEND_INPUTS,
+        outputs: TRANSFER_OUTPUTS,
+        edge_triggered: true,
+    },
+    Declaration {
+        name: "SERIAL_RECEIVE",
+        inputs: RECEIVE_INPUTS,
+        outputs: TRANSFER_OUTPUTS,
+        edge_triggered: false,
+    },
+    Declaration {
+        name: "COMM_CLOSE",
+        inputs: &[("execute", "BOOL"), ("handle", "DINT")],
+        outputs: &[("done", "BOOL"), ("error", "BOOL"), ("error_id", "DINT")],
+        edge_triggered: true,
+    },
+];
+
+/// the function blocks by their symbol name, so they can be mapped into a JIT
+pub fn get_helpers() -> Vec<(&'static str, usize)> {
+    vec![
+        ("TCP_CONNECT", TCP_CONNECT as *const () as usize),
+        ("TCP_SEND", TCP_SEND as *const () as usize),
+        ("TCP_RECEIVE", TCP_RECEIVE as *const () as usize),
+        ("UDP_OPEN", UDP_OPEN as *const () as usize),
+        ("UDP_SEND", UDP_SEND as *const () as usize),
+        ("UDP_RECEIVE", UDP_RECEIVE as *const () as usize),
+        ("SERIAL_OPEN", SERIAL_OPEN as *const () as usize),
+        ("SERIAL_SEND", SERIAL_SEND as *const () as usize),
+        ("SERIAL_RECEIVE", SERIAL_RECEIVE as *const () as usize),
+        ("COMM_CLOSE", COMM_CLOSE as *const () as usize),
+    ]
+}
+
+/// generates the ST declarations of the communication function blocks, they are implemented
+/// externally by this module
+pub fn get_declarations() -> String {
+    let mut declarations = String::new();
+    for declaration in DECLARATIONS {
+        declarations.push_str(&format!("FUNCTION_BLOCK {}\n", declaration.name));
+        for (block, variables) in [
+            ("VAR_INPUT", declaration.inputs),
+            ("VAR_OUTPUT", declaration.outputs),
+        ] {
+            declarations.push_str(&format!("{}\n", block));
+            for (name, type_name) in variables {
+                declarations.push_str(&format!("    {} : {};\n", name, type_name));
+            }
+            declarations.push_str("END_VAR\n");
+        }
+        if declaration.edge_triggered {
+            declarations.push_str("VAR\n    edge : BOOL;\nEND_VAR\n");
+        }
+        declarations.push_str("END_FUNCTION_BLOCK\n\n");
+    }
+    declarations
+}
+
+/// returns true on a rising edge of `execute`
+fn is_triggered(execute: bool, edge: &mut bool) -> bool {
+    let triggered = execute && !*edge;
+    *edge = execute;
+    triggered
+}
+
+/// returns the `error_id` of the given error
+fn get_error_id(error: &io::Error) -> i32 {
+    error.raw_os_error().unwrap_or_else(|| {
+        if error.kind() == io::ErrorKind::ConnectionAborted {
+            CONNECTION_CLOSED
+        } else {
+            INVALID_ARGUMENT
+        }
+    })
+}
+
+fn invalid_argument() -> io::Error {
+    io::Error::from(io::ErrorKind::InvalidInput)
+}
+
+/// returns the text of a null-terminated `STRING`
+fn to_string(text: &[u8]) -> String {
+    let length = text.iter().position(|it| *it == 0).unwrap_or(text.len());
+    String::from_utf8_lossy(&text[..length]).to_string()
+}
+
+/// returns the buffer at the given address, an empty one if it is null
+unsafe fn buffer<'a>(address: u64, size: u32) -> &'a mut [u8] {
+    if address == 0 {
+        &mut []
+    } else {
+        slice::from_raw_parts_mut(address as *mut u8, size as usize)
+    }
+}
+
+/// registers the given connection and returns its handle
+fn register(connection: Connection) -> i32 {
+    let mut connections = CONNECTIONS.lock().unwrap();
+    let index = match connections.iter().position(Option::is_none) {
+        Some(index) => {
+            connections[index] = Some(connection);
+            index
+        }
+        None => {
+            connections.push(Some(connection));
+            connections.len() - 1
+        }
+    };
+    index as i32 + 1
+}
+
+/// calls the given function with the connection of the given handle
+fn with_connection<T>(
+    handle: i32,
+    access: impl FnOnce(&mut Connection) -> io::Result<T>,
+) -> io::Result<T> {
+    let mut connections = CONNECTIONS.lock().unwrap();
+    match connections.get_mut((handle as usize).wrapping_sub(1)) {
+        Some(Some(connection)) if handle > 0 => access(connection),
+        _ => Err(invalid_argument()),
+    }
+}
+
+fn close(handle: i32) -> io::Result<()> {
+    let mut connections = CONNECTIONS.lock().unwrap();
+    match connections.get_mut((handle as usize).wrapping_sub(1)) {
+        Some(connection) if handle > 0 && connection.is_some() => {
+            *connection = None;
+            Ok(())
+        }
+        _ => Err(invalid_argument()),
+    }
+}
+
+/// returns the number of bytes accepted by the given stream without blocking
+fn write_available(stream: &mut impl Write, buffer: &[u8]) -> io::Result<usize> {
+    match stream.write(buffer) {
+        Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(0),
+        result => result,
+    }
+}
+
+/// returns the number of bytes received by the given stream without blocking
+fn read_available(stream: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    match stream.read(buffer) {
+        Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(0),
+        result => result,
+    }
+}
+
+fn connect_tcp(host: String, port: u16) -> io::Result<TcpStream> {
+    let stream = TcpStream::connect((host.as_str(), port))?;
+    stream.set_nonblocking(true)?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+/// polls the TCP connection of the given handle, returns true once it is established
+fn poll_connecting(handle: i32) -> io::Result<bool> {
+    let result = with_connection(handle, |connection| match connection {
+        Connection::Connecting(receiver) => match receiver.try_recv() {
+            Ok(Ok(stream)) => {
+                *connection = Connection::Tcp(stream);
+                Ok(true)
+            }
+            Ok(Err(error)) => Err(error),
+            Err(TryRecvError::Empty) => Ok(false),
+            Err(TryRecvError::Disconnected) => Err(io::Error::from(io::ErrorKind::Other)),
+        },
+        _ => Ok(true),
+    });
+    if result.is_err() {
+        let _ = close(handle);
+    }
+    result
+}
+
+fn open_serial(device: &str, baud_rate: u32) -> io::Result<File> {
+    let speed = match baud_rate {
+        1200 => libc::B1200,
+        2400 => libc::B2400,
+        4800 => libc::B4800,
+        9600 => libc::B9600,
+        19200 => libc::B19200,
+        38400 => libc::B38400,
+        57600 => libc::B57600,
+        115200 => libc::B115200,
+        230400 => libc::B230400,
+        _ => return Err(invalid_argument()),
+    };
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY | libc::O_NONBLOCK)
+        .open(device)?;
+    //configure a raw line with 8 data bits, no parity and one stop bit
+    unsafe {
+        let mut settings = MaybeUninit::<libc::termios>::uninit();
+        if libc::tcgetattr(file.as_raw_fd(), settings.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut settings = settings.assume_init();
+        libc::cfmakeraw(&mut settings);
+        settings.c_cflag |= libc::CLOCAL | libc::CREAD;
+        if libc::cfsetspeed(&mut settings, speed) != 0
+            || libc::tcsetattr(file.as_raw_fd(), libc::TCSANOW, &settings) != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(file)
+}
+
+/// # Safety
+/// `instance` has to point to a `TCP_CONNECT` instance
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn TCP_CONNECT(instance: *mut TcpConnect) {
+    let instance = &mut *instance;
+    if is_triggered(instance.execute, &mut instance.edge) {
+        let (sender, receiver) = mpsc::channel();
+        let host = to_string(&instance.host);
+        let port = instance.port;
+        thread::spawn(move || sender.send(connect_tcp(host, port)));
+        instance.handle = register(Connection::Connecting(receiver));
+        instance.done = false;
+        instance.busy = true;
+        instance.error = false;
+        instance.error_id = 0;
+    }
+    if instance.busy {
+        match poll_connecting(instance.handle) {
+            Ok(connected) => {
+                instance.done = connected;
+                instance.busy = !connected;
+            }
+            Err(error) => {
+                instance.busy = false;
+                instance.error = true;
+                instance.error_id = get_error_id(&error);
+                instance.handle = 0;
+            }
+        }
+    }
+}
+
+/// # Safety
+/// `instance` has to point to a `UDP_OPEN` instance
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn UDP_OPEN(instance: *mut UdpOpen) {
+    let instance = &mut *instance;
+    if is_triggered(instance.execute, &mut instance.edge) {
+        let result = UdpSocket::bind(("0.0.0.0", instance.port)).and_then(|socket| {
+            socket.set_nonblocking(true)?;
+            Ok(register(Connection::Udp(socket)))
+        });
+        instance.done = result.is_ok();
+        instance.error = result.is_err();
+        instance.error_id = result.as_ref().err().map(get_error_id).unwrap_or_default();
+        instance.handle = result.unwrap_or_default();
+    }
+}
+
+/// # Safety
+/// `instance` has to point to a `SERIAL_OPEN` instance
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn SERIAL_OPEN(instance: *mut SerialOpen) {
+    let instance = &mut *instance;
+    if is_triggered(instance.execute, &mut instance.edge) {
+        let result = open_serial(&to_string(&instance.device), instance.baud_rate)
+            .map(|file| register(Connection::Serial(file)));
+        instance.done = result.is_ok();
+        instance.error = result.is_err();
+        instance.error_id = result.as_ref().err().map(get_error_id).unwrap_or_default();
+        instance.handle = result.unwrap_or_default();
+    }
+}
+
+/// # Safety
+/// `instance` has to point to a `TCP_SEND` instance, its `buffer` to at least `size` bytes
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn TCP_SEND(instance: *mut Send) {
+    let instance = &mut *instance;
+    if is_triggered(instance.execute, &mut instance.edge) {
+        let buffer = buffer(instance.buffer, instance.size);
+        let result = with_connection(instance.handle, |connection| match connection {
+            Connection::Tcp(stream) => write_available(stream, buffer),
+            _ => Err(invalid_argument()),
+        });
+        report_send(instance, result);
+    }
+}
+
+/// # Safety
+/// `instance` has to point to a `SERIAL_SEND` instance, its `buffer` to at least `size` bytes
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn SERIAL_SEND(instance: *mut Send) {
+    let instance = &mut *instance;
+    if is_triggered(instance.execute, &mut instance.edge) {
+        let buffer = buffer(instance.buffer, instance.size);
+        let result = with_connection(instance.handle, |connection| match connection {
+            Connection::Serial(file) => write_available(file, buffer),
+            _ => Err(invalid_argument()),
+        });
+        report_send(instance, result);
+    }
+}
+
+fn report_send(instance: &mut Send, result: io::Result<usize>) {
+    instance.done = result.is_ok();
+    instance.error = result.is_err();
+    instance.error_id = result.as_ref().err().map(get_error_id).unwrap_or_default();
+    instance.count = result.unwrap_or_default() as u32;
+}
+
+/// # Safety
+/// `instance` has to point to a `UDP_SEND` instance, its `buffer` to at least `size` bytes
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn UDP_SEND(instance: *mut UdpSend) {
+    let instance = &mut *instance;
+    if is_triggered(instance.execute, &mut instance.edge) {
+        let buffer = buffer(instance.buffer, instance.size);
+        let host = to_string(&instance.host);
+        let port = instance.port;
+        let result = with_connection(instance.handle, |connection| match connection {
+            Connection::Udp(socket) => match socket.send_to(buffer, (host.as_str(), port)) {
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(0),
+                result => result,
+            },
+            _ => Err(invalid_argument()),
+        });
+        instance.done = result.is_ok();
+        instance.error = result.is_err();
+        instance.error_id = result.as_ref().err().map(get_error_id).unwrap_or_default();
+        instance.count = result.unwrap_or_default() as u32;
+    }
+}
+
+/// # Safety
+/// `instance` has to point to a `TCP_RECEIVE` instance, its `buffer` to at least `size` bytes
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn TCP_RECEIVE(instance: *mut Receive) {
+    receive(&mut *instance, |connection, buffer| match connection {
+        Connection::Tcp(stream) => match stream.read(buffer) {
+            Ok(0) if !buffer.is_empty() => Err(io::Error::from(io::ErrorKind::ConnectionAborted)),
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(0),
+            result => result,
+        },
+        _ => Err(invalid_argument()),
+    })
+}
+
+/// # Safety
+/// `instance` has to point to a `UDP_RECEIVE` instance, its `buffer` to at least `size` bytes
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn UDP_RECEIVE(instance: *mut Receive) {
+    receive(&mut *instance, |connection, buffer| match connection {
+        Connection::Udp(socket) => match socket.recv(buffer) {
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(0),
+            result => result,
+        },
+        _ => Err(invalid_argument()),
+    })
+}
+
+/// # Safety
+/// `instance` has to point to a `SERIAL_RECEIVE` instance, its `buffer` to at least `size` bytes
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn SERIAL_RECEIVE(instance: *mut Receive) {
+    receive(&mut *instance, |connection, buffer| match connection {
+        Connection::Serial(file) => read_available(file, buffer),
+        _ => Err(invalid_argument()),
+    })
+}
+
+/// receives into the instance's buffer while it is enabled
+unsafe fn receive(
+    instance: &mut Receive,
+    read: impl FnOnce(&mut Connection, &mut [u8]) -> io::Result<usize>,
+) {
+    if instance.enable {
+        let buffer = buffer(instance.buffer, instance.size);
+        let result = with_connection(instance.handle, |connection| read(connection, buffer));
+        instance.done = matches!(result, Ok(count) if count > 0);
+        instance.error = result.is_err();
+        instance.error_id = result.as_ref().err().map(get_error_id).unwrap_or_default();
+        instance.count = result.unwrap_or_default() as u32;
+    } else {
+        instance.done = false;
+        instance.count = 0;
+    }
+}
+
+/// # Safety
+/// `instance` has to point to a `COMM_CLOSE` instance
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn COMM_CLOSE(instance: *mut Close) {
+    let instance = &mut *instance;
+    if is_triggered(instance.execute, &mut instance.edge) {
+        let result = close(instance.handle);
+        instance.done = result.is_ok();
+        instance.error = result.is_err();
+        instance.error_id = result.as_ref().err().map(get_error_id).unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{SocketAddr, TcpListener},
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+
+    fn to_host(text: &str) -> [u8; 256] {
+        let mut host = [0; 256];
+        host[..text.len()].copy_from_slice(text.as_bytes());
+        host
+    }
+
+    /// calls the given function block every millisecond until `is_done` returns true
+    fn poll<T>(instance: &mut T, call: unsafe extern "C" fn(*mut T), is_done: fn(&T) -> bool) {
+        let start = Instant::now();
+        while !is_done(instance) && start.elapsed() < Duration::from_secs(5) {
+            unsafe { call(instance) };
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn receive_instance(handle: i32, buffer: &mut [u8]) -> Receive {
+        Receive {
+            enable: true,
+            handle,
+            buffer: buffer.as_mut_ptr() as u64,
+            size: buffer.len() as u32,
+            done: false,
+            error: false,
+            error_id: 0,
+            count: 0,
+        }
+    }
+
+    fn close_handle(handle: i32) -> Close {
+        let mut instance = Close {
+            execute: true,
+            handle,
+            done: false,
+            error: false,
+            error_id: 0,
+            edge: false,
+        };
+        unsafe { COMM_CLOSE(&mut instance) };
+        instance
+    }
+
+    #[test]
+    fn tcp_connections_are_established_without_blocking_a_cycle() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut connect = TcpConnect {
+            execute: true,
+            host: to_host("127.0.0.1"),
+            port: listener.local_addr().unwrap().port(),
+            done: false,
+            busy: false,
+            error: false,
+            error_id: 0,
+            handle: 0,
+            edge: false,
+        };
+        unsafe { TCP_CONNECT(&mut connect) };
+        assert!(connect.busy || connect.done);
+        poll(&mut connect, TCP_CONNECT, |it| !it.busy);
+        assert!(connect.done && !connect.error && connect.handle > 0);
+        let (mut peer, _) = listener.accept().unwrap();
+
+        let mut data = *b"ping";
+        let mut send = Send {
+            execute: true,
+            handle: connect.handle,
+            buffer: data.as_mut_ptr() as u64,
+            size: 4,
+            done: false,
+            error: false,
+            error_id: 0,
+            count: 0,
+            edge: false,
+        };
+        unsafe { TCP_SEND(&mut send) };
+        assert_eq!((send.done, send.count), (true, 4));
+        let mut received = [0; 4];
+        peer.read_exact(&mut received).unwrap();
+        assert_eq!(&received, b"ping");
+
+        //nothing is received until the peer sends
+        let mut buffer = [0u8; 16];
+        let mut receive = receive_instance(connect.handle, &mut buffer);
+        unsafe { TCP_RECEIVE(&mut receive) };
+        assert_eq!(
+            (receive.done, receive.error, receive.count),
+            (false, false, 0)
+        );
+        peer.write_all(b"pong").unwrap();
+        poll(&mut receive, TCP_RECEIVE, |it| it.done);
+        assert_eq!(&buffer[..receive.count as usize], b"pong");
+
+        //a connection closed by the peer is reported
+        drop(peer);
+        let mut receive = receive_instance(connect.handle, &mut buffer);
+        poll(&mut receive, TCP_RECEIVE, |it| it.error);
+        assert_eq!(receive.error_id, CONNECTION_CLOSED);
+        assert!(close_handle(connect.handle).done);
+        assert!(close_handle(connect.handle).error);
+    }
+
+    #[test]
+    fn refused_tcp_connections_are_reported() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut connect = TcpConnect {
+            execute: true,
+            host: to_host("127.0.0.1"),
+            port,
+            done: false,
+            busy: false,
+            error: false,
+            error_id: 0,
+            handle: 0,
+            edge: false,
+        };
+        poll(&mut connect, TCP_CONNECT, |it| it.error || it.done);
+        assert!(connect.error && connect.error_id > 0 && connect.handle == 0);
+    }
+
+    #[test]
+    fn udp_datagrams_are_sent_and_received() {
+        let peer = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut open = UdpOpen {
+            execute: true,
+            port: 0,
+            done: false,
+            error: false,
+            error_id: 0,
+            handle: 0,
+            edge: false,
+        };
+        unsafe { UDP_OPEN(&mut open) };
+        assert!(open.done && open.handle > 0);
+
+        let mut data = *b"hello";
+        let mut send = UdpSend {
+            execute: true,
+            handle: open.handle,
+            host: to_host("127.0.0.1"),
+            port: peer.local_addr().unwrap().port(),
+            buffer: data.as_mut_ptr() as u64,
+            size: 5,
+            done: false,
+            error: false,
+            error_id: 0,
+            count: 0,
+            edge: false,
+        };
+        unsafe { UDP_SEND(&mut send) };
+        assert_eq!((send.done, send.count), (true, 5));
+        let mut received = [0; 16];
+        let (count, sender) = peer.recv_from(&mut received).unwrap();
+        assert_eq!(&received[..count], b"hello");
+
+        let mut buffer = [0u8; 16];
+        let mut receive = receive_instance(open.handle, &mut buffer);
+        unsafe { UDP_RECEIVE(&mut receive) };
+        assert!(!receive.done && !receive.error);
+        let sender = SocketAddr::from(([127, 0, 0, 1], sender.port()));
+        peer.send_to(b"world", sender).unwrap();
+        poll(&mut receive, UDP_RECEIVE, |it| it.done);
+        assert_eq!(&buffer[..receive.count as usize], b"world");
+
+        //a connection of another kind is rejected
+        let mut receive = receive_instance(open.handle, &mut buffer);
+        unsafe { TCP_RECEIVE(&mut receive) };
+        assert!(receive.error && receive.error_id == INVALID_ARGUMENT);
+        assert!(close_handle(open.handle).done);
+    }
+
+    #[test]
+    fn serial_lines_report_unknown_baud_rates_and_devices() {
+        let mut open = SerialOpen {
+            execute: true,
+            device: to_host("/dev/null"),
+            baud_rate: 12345,
+            done: false,
+            error: false,
+            error_id: 0,
+            handle: 0,
+            edge: false,
+        };
+        unsafe { SERIAL_OPEN(&mut open) };
+        assert!(open.error && open.error_id == INVALID_ARGUMENT);
+
+        open.execute = false;
+        unsafe { SERIAL_OPEN(&mut open) };
+        open.execute = true;
+        open.device = to_host("/dev/rusty_missing_serial_device");
+        open.baud_rate = 9600;
+        unsafe { SERIAL_OPEN(&mut open) };
+        assert!(open.error && open.error_id > 0 && open.handle == 0);
+    }
+
+    #[test]
+    fn declarations_describe_every_function_block() {
+        let declarations = get_declarations();
+        for (name, _) in get_helpers() {
+            assert!(declarations.contains(&format!("FUNCTION_BLOCK {}\n", name)));
+        }
+        assert!(declarations.contains(
+            "FUNCTION_BLOCK UDP_OPEN\nVAR_INPUT\n    execute : BOOL;\n    port : UINT;\nEND_VAR\n"
+        ));
+        assert!(declarations.contains(
+            "    count : UDINT;\nEND_VAR\nEND_FUNCTION_BLOCK\n\nFUNCTION_BLOCK COMM_CLOSE"
+        ));
+    }
+}
@@ -0,0 +1,63 @@
+//! the external events executing the event tasks of the application
+//!
+//! an event is raised by an interrupt handler or a driver with `raise`, or by the application
+//! itself with `RAISE_EVENT`. The scheduler executes the tasks of a raised event in its next
+//! cycle, an event raised several times before is executed once. The pending events are kept in
+//! an atomic bit set, so they may be raised from any thread or signal handler.
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// the number of external events, they are numbered from 0
+pub const EVENT_COUNT: u32 = 64;
+
+static PENDING: AtomicU64 = AtomicU64::new(0);
+
+/// the helpers by their symbol name, so they can be mapped into a JIT
+pub fn get_helpers() -> Vec<(&'static str, usize)> {
+    vec![("RAISE_EVENT", RAISE_EVENT as *const () as usize)]
+}
+
+/// marks the given event as pending, unknown events are ignored
+pub fn raise(event: u32) {
+    if event < EVENT_COUNT {
+        PENDING.fetch_or(1 << event, Ordering::SeqCst);
+    }
+}
+
+/// returns true if the given event was raised since the last call and clears it
+pub fn take(event: u32) -> bool {
+    if event >= EVENT_COUNT {
+        return false;
+    }
+    let mask = 1 << event;
+    PENDING.fetch_and(!mask, Ordering::SeqCst) & mask != 0
+}
+
+/// raises the given event for the application (`RAISE_EVENT(3)`)
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn RAISE_EVENT(event: i32) {
+    if let Ok(event) = u32::try_from(event) {
+        raise(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raised_events_are_taken_once() {
+        assert!(!take(40));
+        raise(40);
+        RAISE_EVENT(40);
+        assert!(take(40));
+        assert!(!take(40));
+    }
+
+    #[test]
+    fn unknown_events_are_ignored() {
+        raise(EVENT_COUNT);
+        RAISE_EVENT(-1);
+        assert!(!take(EVENT_COUNT));
+    }
+}
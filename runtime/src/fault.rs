@@ -0,0 +1,89 @@
+//! reports crashes of the application (invalid memory accesses, divisions by zero, ...) together
+//! with the task that was executing, and turns SIGINT/SIGTERM into a stop request. The executing
+//! task is remembered per thread, the fault is reported by the faulting thread
+use std::{
+    cell::Cell,
+    ffi::CStr,
+    os::raw::{c_char, c_int},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// the exit code of the runtime after a fault
+pub const FAULT_EXIT_CODE: c_int = 70;
+
+thread_local! {
+    static CURRENT_TASK: Cell<*const c_char> = Cell::new(std::ptr::null());
+}
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+const FAULT_SIGNALS: &[(c_int, &str)] = &[
+    (libc::SIGSEGV, "segmentation fault"),
+    (libc::SIGBUS, "bus error"),
+    (libc::SIGFPE, "arithmetic fault"),
+    (libc::SIGILL, "illegal instruction"),
+];
+
+/// installs the fault handler and the stop handler
+pub fn install_handlers() {
+    for (signal, _) in FAULT_SIGNALS {
+        install(*signal, on_fault as usize);
+    }
+    install(libc::SIGINT, on_stop as usize);
+    install(libc::SIGTERM, on_stop as usize);
+}
+
+fn install(signal: c_int, handler: usize) {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(signal, &action, std::ptr::null_mut());
+    }
+}
+
+/// remembers the task executed from now on, it is reported if the application faults
+pub fn set_current_task(name: Option<&CStr>) {
+    let name = name.map_or(std::ptr::null(), CStr::as_ptr);
+    CURRENT_TASK.with(|it| it.set(name));
+}
+
+/// stops the runtime as if SIGINT was received
+pub fn request_stop() {
+    STOP_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// returns true once SIGINT or SIGTERM was received
+pub fn is_stop_requested() -> bool {
+    STOP_REQUESTED.load(Ordering::SeqCst)
+}
+
+extern "C" fn on_stop(_: c_int) {
+    STOP_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn on_fault(signal: c_int) {
+    //only async-signal-safe functions may be used here, so the message is written piece by piece
+    let description = FAULT_SIGNALS
+        .iter()
+        .find(|(it, _)| *it == signal)
+        .map_or("fault", |(_, description)| description);
+    write_stderr(b"rusty-run: ");
+    write_stderr(description.as_bytes());
+    let task = CURRENT_TASK.try_with(Cell::get).unwrap_or(std::ptr::null());
+    if !task.is_null() {
+        write_stderr(b" in task ");
+        write_stderr(unsafe { CStr::from_ptr(task) }.to_bytes());
+    }
+    write_stderr(b"\n");
+    unsafe { libc::_exit(FAULT_EXIT_CODE) }
+}
+
+fn write_stderr(message: &[u8]) {
+    unsafe {
+        libc::write(
+            libc::STDERR_FILENO,
+            message.as_ptr() as *const libc::c_void,
+            message.len(),
+        );
+    }
+}
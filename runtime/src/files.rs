@@ -0,0 +1,296 @@
+//! opens, reads, writes and closes files for the applications' `FILE_OPEN`, `FILE_READ`,
+//! `FILE_WRITE` and `FILE_CLOSE` function blocks, e.g. to persist recipes and parameters
+//!
+//! the function blocks are implemented here and called with their instance. They act on a rising
+//! edge of their `execute` input and identify an open file by its handle (greater than 0). A
+//! failure sets `error` and reports the OS's error number in `error_id`, an unknown handle or
+//! mode is reported as `-1`. Files are accessed synchronously within the calling cycle.
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    slice,
+    sync::Mutex,
+};
+
+/// the `error_id` of an unknown handle or mode
+const INVALID_ARGUMENT: i32 = -1;
+
+/// the open files, a file's handle is its index + 1
+static FILES: Mutex<Vec<Option<File>>> = Mutex::new(Vec::new());
+
+/// the instance of a `FILE_OPEN` function block
+#[repr(C)]
+pub struct FileOpen {
+    pub execute: bool,
+    /// the path as a null-terminated `STRING[255]`
+    pub path: [u8; 256],
+    /// `FILE_MODE_READ` (0), `FILE_MODE_WRITE` (1) or `FILE_MODE_APPEND` (2)
+    pub mode: i32,
+    pub done: bool,
+    pub error: bool,
+    pub error_id: i32,
+    pub handle: i32,
+    edge: bool,
+}
+
+/// the instance of a `FILE_READ` or a `FILE_WRITE` function block
+#[repr(C)]
+pub struct FileTransfer {
+    pub execute: bool,
+    pub handle: i32,
+    /// the address of the buffer read into or written from
+    pub buffer: u64,
+    /// the size of the buffer in bytes
+    pub size: u32,
+    pub done: bool,
+    pub error: bool,
+    pub error_id: i32,
+    /// the number of bytes read or written, 0 if a read reached the end of the file
+    pub count: u32,
+    edge: bool,
+}
+
+/// the instance of a `FILE_CLOSE` function block
+#[repr(C)]
+pub struct FileClose {
+    pub execute: bool,
+    pub handle: i32,
+    pub done: bool,
+    pub error: bool,
+    pub error_id: i32,
+    edge: bool,
+}
+
+/// the function blocks by their symbol name, so they can be mapped into a JIT
+pub fn get_helpers() -> Vec<(&'static str, usize)> {
+    vec![
+        ("FILE_OPEN", FILE_OPEN as *const () as usize),
+        ("FILE_READ", FILE_READ as *const () as usize),
+        ("FILE_WRITE", FILE_WRITE as *const () as usize),
+        ("FILE_CLOSE", FILE_CLOSE as *const () as usize),
+    ]
+}
+
+/// returns true on a rising edge of `execute`
+fn is_triggered(execute: bool, edge: &mut bool) -> bool {
+    let triggered = execute && !*edge;
+    *edge = execute;
+    triggered
+}
+
+/// returns the `error_id` of the given error
+fn get_error_id(error: &io::Error) -> i32 {
+    error.raw_os_error().unwrap_or(INVALID_ARGUMENT)
+}
+
+fn invalid_argument() -> io::Error {
+    io::Error::from(io::ErrorKind::InvalidInput)
+}
+
+fn open(path: &[u8], mode: i32) -> io::Result<i32> {
+    let length = path.iter().position(|it| *it == 0).unwrap_or(path.len());
+    let path = String::from_utf8_lossy(&path[..length]).to_string();
+    let mut options = OpenOptions::new();
+    match mode {
+        0 => options.read(true),
+        1 => options.write(true).create(true).truncate(true),
+        2 => options.append(true).create(true),
+        _ => return Err(invalid_argument()),
+    };
+    let file = options.open(path)?;
+    let mut files = FILES.lock().unwrap();
+    let index = match files.iter().position(Option::is_none) {
+        Some(index) => {
+            files[index] = Some(file);
+            index
+        }
+        None => {
+            files.push(Some(file));
+            files.len() - 1
+        }
+    };
+    Ok(index as i32 + 1)
+}
+
+/// calls the given function with the open file of the given handle
+fn with_file<T>(handle: i32, access: impl FnOnce(&mut File) -> io::Result<T>) -> io::Result<T> {
+    let mut files = FILES.lock().unwrap();
+    match files.get_mut((handle as usize).wrapping_sub(1)) {
+        Some(Some(file)) if handle > 0 => access(file),
+        _ => Err(invalid_argument()),
+    }
+}
+
+fn close(handle: i32) -> io::Result<()> {
+    let mut files = FILES.lock().unwrap();
+    match files.get_mut((handle as usize).wrapping_sub(1)) {
+        Some(file) if handle > 0 && file.is_some() => {
+            *file = None;
+            Ok(())
+        }
+        _ => Err(invalid_argument()),
+    }
+}
+
+/// returns the buffer at the given address, an empty one if it is null
+unsafe fn buffer<'a>(address: u64, size: u32) -> &'a mut [u8] {
+    if address == 0 {
+        &mut []
+    } else {
+        slice::from_raw_parts_mut(address as *mut u8, size as usize)
+    }
+}
+
+/// # Safety
+/// `instance` has to point to a `FILE_OPEN` instance
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn FILE_OPEN(instance: *mut FileOpen) {
+    let instance = &mut *instance;
+    if is_triggered(instance.execute, &mut instance.edge) {
+        let result = open(&instance.path, instance.mode);
+        instance.done = result.is_ok();
+        instance.error = result.is_err();
+        instance.error_id = result.as_ref().err().map(get_error_id).unwrap_or_default();
+        instance.handle = result.unwrap_or_default();
+    }
+}
+
+/// # Safety
+/// `instance` has to point to a `FILE_READ` instance, its `buffer` to at least `size` bytes
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn FILE_READ(instance: *mut FileTransfer) {
+    let instance = &mut *instance;
+    if is_triggered(instance.execute, &mut instance.edge) {
+        let buffer = buffer(instance.buffer, instance.size);
+        let result = with_file(instance.handle, |file| file.read(buffer));
+        report_transfer(instance, result);
+    }
+}
+
+/// # Safety
+/// `instance` has to point to a `FILE_WRITE` instance, its `buffer` to at least `size` bytes
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn FILE_WRITE(instance: *mut FileTransfer) {
+    let instance = &mut *instance;
+    if is_triggered(instance.execute, &mut instance.edge) {
+        let buffer = buffer(instance.buffer, instance.size);
+        let result = with_file(instance.handle, |file| {
+            file.write_all(buffer)?;
+            file.flush()?;
+            Ok(buffer.len())
+        });
+        report_transfer(instance, result);
+    }
+}
+
+fn report_transfer(instance: &mut FileTransfer, result: io::Result<usize>) {
+    instance.done = result.is_ok();
+    instance.error = result.is_err();
+    instance.error_id = result.as_ref().err().map(get_error_id).unwrap_or_default();
+    instance.count = result.unwrap_or_default() as u32;
+}
+
+/// # Safety
+/// `instance` has to point to a `FILE_CLOSE` instance
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn FILE_CLOSE(instance: *mut FileClose) {
+    let instance = &mut *instance;
+    if is_triggered(instance.execute, &mut instance.edge) {
+        let result = close(instance.handle);
+        instance.done = result.is_ok();
+        instance.error = result.is_err();
+        instance.error_id = result.as_ref().err().map(get_error_id).unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_instance(path: &str, mode: i32) -> FileOpen {
+        let mut instance = FileOpen {
+            execute: true,
+            path: [0; 256],
+            mode,
+            done: false,
+            error: false,
+            error_id: 0,
+            handle: 0,
+            edge: false,
+        };
+        instance.path[..path.len()].copy_from_slice(path.as_bytes());
+        unsafe { FILE_OPEN(&mut instance) };
+        instance
+    }
+
+    fn transfer_instance(handle: i32, buffer: &mut [u8]) -> FileTransfer {
+        FileTransfer {
+            execute: true,
+            handle,
+            buffer: buffer.as_mut_ptr() as u64,
+            size: buffer.len() as u32,
+            done: false,
+            error: false,
+            error_id: 0,
+            count: 0,
+            edge: false,
+        }
+    }
+
+    fn close_handle(handle: i32) -> FileClose {
+        let mut instance = FileClose {
+            execute: true,
+            handle,
+            done: false,
+            error: false,
+            error_id: 0,
+            edge: false,
+        };
+        unsafe { FILE_CLOSE(&mut instance) };
+        instance
+    }
+
+    #[test]
+    fn files_are_written_and_read_on_a_rising_edge() {
+        let path = std::env::temp_dir().join(format!("rusty_files_{}.dat", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let writer = open_instance(path, 1);
+        assert!(writer.done && !writer.error && writer.handle > 0);
+        let mut data = *b"recipe";
+        let mut write = transfer_instance(writer.handle, &mut data);
+        unsafe { FILE_WRITE(&mut write) };
+        assert_eq!((write.done, write.count), (true, 6));
+        //nothing is written again until the next rising edge
+        unsafe { FILE_WRITE(&mut write) };
+        write.execute = false;
+        unsafe { FILE_WRITE(&mut write) };
+        write.execute = true;
+        unsafe { FILE_WRITE(&mut write) };
+        assert!(close_handle(writer.handle).done);
+
+        let reader = open_instance(path, 0);
+        let mut read_data = [0u8; 20];
+        let mut read = transfer_instance(reader.handle, &mut read_data);
+        unsafe { FILE_READ(&mut read) };
+        assert_eq!((read.done, read.count), (true, 12));
+        assert_eq!(&read_data[..12], b"reciperecipe");
+        assert!(close_handle(reader.handle).done);
+        std::fs::remove_file(path).unwrap();
+
+        //closed handles, unknown modes and missing files are reported
+        let closed = close_handle(reader.handle);
+        assert!(closed.error && closed.error_id == INVALID_ARGUMENT);
+        let mut read = transfer_instance(reader.handle, &mut read_data);
+        unsafe { FILE_READ(&mut read) };
+        assert!(read.error && read.error_id == INVALID_ARGUMENT && read.count == 0);
+        let unknown_mode = open_instance(path, 3);
+        assert!(unknown_mode.error && unknown_mode.handle == 0);
+        let missing = open_instance(path, 0);
+        assert!(missing.error && missing.error_id > 0);
+    }
+}
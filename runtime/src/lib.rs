@@ -0,0 +1,51 @@
+//! A reference runtime for applications compiled by RuSTy
+//!
+//! The runtime loads an application compiled as a shared object with a task configuration
+//! (`rustyc --shared --task-conf tasks.json ...`) and executes its tasks cyclically:
+//! - `application` reads the task table and the retain table generated by the compiler
+//! - `scheduler` releases every task according to its interval and priority, event tasks once
+//!   their event was raised
+//! - `affinity` executes the tasks bound to a core by a scheduler on a thread pinned to the core
+//! - `events` keeps the external events raised for the event tasks (e.g. by interrupt handlers)
+//! - `clock` provides the monotonic time base of the scheduler and of the application's SFC steps
+//! - `retain` saves and restores the retained variables across restarts
+//! - `symbols` resolves the instance paths of the application's variables (e.g. `prg.fb.out`), read
+//!   and written with `Application::read_symbol` and `Application::write_symbol`
+//! - `fault` reports crashes of the application together with the faulting task
+//! - `strings` converts numbers to strings and back for the application (e.g. `INT_TO_STRING`)
+//! - `checksums` calculates the checksums of byte buffers for the application (e.g. `CRC16`)
+//! - `random` generates random numbers and GUIDs for the application (e.g. `RAND`)
+//! - `telemetry` publishes the variables of the task configuration's `telemetry` section as JSON
+//!   (e.g. to an MQTT broker)
+//! - `files` implements the application's file access function blocks (e.g. `FILE_OPEN`)
+//! - `communication` implements the TCP, UDP and serial function blocks (e.g. `TCP_CONNECT`), it
+//!   is only available with the `communication` feature
+pub mod affinity;
+pub mod application;
+pub mod checksums;
+pub mod clock;
+#[cfg(feature = "communication")]
+pub mod communication;
+pub mod events;
+pub mod fault;
+pub mod files;
+pub mod random;
+pub mod retain;
+pub mod scheduler;
+pub mod strings;
+pub mod symbols;
+pub mod telemetry;
+
+/// the helpers called by the application by their symbol name, so they can be mapped into a JIT
+pub fn get_helpers() -> Vec<(&'static str, usize)> {
+    let mut helpers = strings::get_helpers();
+    helpers.extend(clock::get_helpers());
+    helpers.extend(events::get_helpers());
+    helpers.extend(checksums::get_helpers());
+    helpers.extend(random::get_helpers());
+    helpers.extend(files::get_helpers());
+    helpers.extend(telemetry::get_helpers());
+    #[cfg(feature = "communication")]
+    helpers.extend(communication::get_helpers());
+    helpers
+}
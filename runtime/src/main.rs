@@ -0,0 +1,185 @@
+//! `rusty-run` executes an application compiled by RuSTy
+//!
+//! ```sh
+//! rustyc --shared --task-conf tasks.json app.st -o app.so
+//! rusty-run app.so --retain app.retain
+//! ```
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use clap::Parser;
+use rusty_runtime::{
+    affinity,
+    application::{Application, Task},
+    clock::MonotonicClock,
+    fault, retain,
+    scheduler::Scheduler,
+    telemetry::Publisher,
+};
+
+#[derive(Parser, Debug)]
+#[clap(
+    name = "rusty-run",
+    version,
+    about = "Executes the tasks of an application compiled by RuSTy"
+)]
+struct RunParameters {
+    #[clap(
+        name = "application",
+        help = "The application compiled as a shared object with a task configuration"
+    )]
+    application: String,
+
+    #[clap(
+        long,
+        name = "retain",
+        help = "Restore the retained variables from <retain> and save them there when stopped"
+    )]
+    retain: Option<String>,
+
+    #[clap(
+        long,
+        name = "tick",
+        help = "The cycle time in milliseconds of the tasks without interval",
+        default_value = "10"
+    )]
+    tick: u64,
+
+    #[clap(
+        long,
+        name = "cycles",
+        help = "Stop after the given number of scheduler cycles instead of running until interrupted"
+    )]
+    cycles: Option<u64>,
+
+    #[clap(
+        long,
+        name = "telemetry",
+        help = "Append the telemetry published by the application to <telemetry> as JSON lines, - writes them to stdout"
+    )]
+    telemetry: Option<String>,
+}
+
+/// writes every published object as a line, e.g. to be piped into an MQTT client
+struct LinePublisher(Box<dyn Write>);
+
+impl Publisher for LinePublisher {
+    fn publish(&mut self, message: &str) {
+        if let Err(err) = writeln!(self.0, "{}", message).and_then(|_| self.0.flush()) {
+            eprintln!("Cannot publish telemetry: {}", err);
+        }
+    }
+}
+
+fn main() {
+    let parameters = RunParameters::parse();
+    if let Err(message) = run(parameters) {
+        eprintln!("Error: {}", message);
+        std::process::exit(1);
+    }
+}
+
+fn run(parameters: RunParameters) -> Result<(), String> {
+    //the application calls the runtime's helpers, they are exported by the build script but have
+    //to be linked although the runtime does not call them itself
+    std::hint::black_box(rusty_runtime::get_helpers());
+    let mut application = Application::load(&parameters.application)?;
+    if let Some(path) = &parameters.retain {
+        let restored = retain::restore(path, &mut application.get_retained())
+            .map_err(|err| format!("Cannot restore {}: {}", path, err))?;
+        if !restored {
+            println!(
+                "{} does not match the application, starting with initial values",
+                path
+            );
+        }
+    }
+
+    fault::install_handlers();
+    let tick = Duration::from_millis(parameters.tick);
+    let mut groups = affinity::group_by_core(application.take_tasks());
+    let mut scheduler = Scheduler::new(
+        MonotonicClock::start(),
+        groups.remove(&None).unwrap_or_default(),
+        tick,
+    );
+    let cores = groups
+        .into_iter()
+        .map(|(core, tasks)| run_on_core(core.unwrap_or_default(), tasks, tick, parameters.cycles))
+        .collect::<Vec<_>>();
+    match (application.take_telemetry(), &parameters.telemetry) {
+        (Some(mut telemetry), Some(path)) => {
+            let output: Box<dyn Write> = if path == "-" {
+                Box::new(io::stdout())
+            } else {
+                Box::new(
+                    OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .map_err(|err| format!("Cannot open {}: {}", path, err))?,
+                )
+            };
+            let mut publisher = LinePublisher(output);
+            scheduler.run_with(parameters.cycles, |now| {
+                telemetry.publish_if_due(now, &mut publisher)
+            });
+        }
+        (None, Some(_)) => {
+            return Err(format!(
+                "{} publishes no telemetry, add a telemetry section to its task configuration",
+                parameters.application
+            ))
+        }
+        _ => scheduler.run(parameters.cycles),
+    }
+    let mut overruns = scheduler
+        .get_overruns()
+        .into_iter()
+        .map(|(task, overruns)| (task.to_string(), overruns))
+        .collect::<Vec<_>>();
+    for core in cores {
+        overruns.extend(
+            core.join()
+                .map_err(|_| "A task thread panicked".to_string())??,
+        );
+    }
+    for (task, overruns) in overruns {
+        if overruns > 0 {
+            println!("task {} missed {} releases", task, overruns);
+        }
+    }
+
+    if let Some(path) = &parameters.retain {
+        retain::save(path, &application.get_retained())
+            .map_err(|err| format!("Cannot save {}: {}", path, err))?;
+    }
+    Ok(())
+}
+
+/// executes the given tasks on a thread pinned to the given core and returns their overruns, the
+/// runtime is stopped if the thread cannot be pinned
+fn run_on_core(
+    core: u32,
+    tasks: Vec<Task>,
+    tick: Duration,
+    cycles: Option<u64>,
+) -> JoinHandle<Result<Vec<(String, u64)>, String>> {
+    thread::spawn(move || {
+        if let Err(message) = affinity::pin_current_thread(core) {
+            fault::request_stop();
+            return Err(message);
+        }
+        let mut scheduler = Scheduler::new(MonotonicClock::start(), tasks, tick);
+        scheduler.run(cycles);
+        Ok(scheduler
+            .get_overruns()
+            .into_iter()
+            .map(|(task, overruns)| (task.to_string(), overruns))
+            .collect())
+    })
+}
@@ -0,0 +1,129 @@
+//! generates random numbers and GUIDs for the applications' `RAND`, `SRAND` and `GUID` functions
+//!
+//! the numbers are drawn from a splitmix64 generator shared by all tasks. It is seeded randomly
+//! on its first use unless the application calls `SRAND` or the host calls `seed` before, a
+//! seeded generator produces the same numbers and GUIDs in every run (e.g. in a simulation).
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    os::raw::c_char,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Once,
+    },
+};
+
+use crate::strings;
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+static STATE: AtomicU64 = AtomicU64::new(0);
+static SEEDED: Once = Once::new();
+
+/// the helpers by their symbol name, so they can be mapped into a JIT
+pub fn get_helpers() -> Vec<(&'static str, usize)> {
+    vec![
+        ("__rusty_rand", __rusty_rand as *const () as usize),
+        ("__rusty_srand", __rusty_srand as *const () as usize),
+        ("__rusty_guid", __rusty_guid as *const () as usize),
+    ]
+}
+
+/// seeds the generator, the following numbers and GUIDs only depend on the seed
+pub fn seed(seed: u64) {
+    SEEDED.call_once(|| {});
+    STATE.store(seed, Ordering::Relaxed);
+}
+
+fn next() -> u64 {
+    SEEDED.call_once(|| {
+        STATE.store(
+            RandomState::new().build_hasher().finish(),
+            Ordering::Relaxed,
+        )
+    });
+    let mut z = STATE
+        .fetch_add(GOLDEN_GAMMA, Ordering::Relaxed)
+        .wrapping_add(GOLDEN_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// returns a random GUID (version 4) formatted like `5f0c6f7e-2b1a-4c3d-9e8f-0a1b2c3d4e5f`
+fn guid() -> String {
+    let high = next();
+    let low = next();
+    // the version (4) and the variant (RFC 4122) replace the random bits
+    let high = (high & !0xf000) | 0x4000;
+    let low = (low & !(0xc << 60)) | (0x8 << 60);
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        high & 0xffff,
+        low >> 48,
+        low & 0xffff_ffff_ffff
+    )
+}
+
+/// returns a random number in [0, 1)
+#[no_mangle]
+pub extern "C" fn __rusty_rand() -> f64 {
+    (next() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[no_mangle]
+pub extern "C" fn __rusty_srand(value: u64) {
+    seed(value)
+}
+
+/// # Safety
+/// `destination` has to point to a string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_guid(destination: *mut c_char, length: i32) {
+    strings::write(&guid(), destination as *mut u8, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the generator is shared, so a single test checks the seeded sequences
+    #[test]
+    fn seeded_numbers_and_guids_are_reproducible() {
+        seed(42);
+        let numbers = (0..100).map(|_| __rusty_rand()).collect::<Vec<_>>();
+        let first_guid = guid();
+        assert!(numbers.iter().all(|it| (0.0..1.0).contains(it)));
+        assert!(numbers.windows(2).any(|it| it[0] != it[1]));
+
+        __rusty_srand(42);
+        assert_eq!(
+            (0..100).map(|_| __rusty_rand()).collect::<Vec<_>>(),
+            numbers
+        );
+        assert_eq!(guid(), first_guid);
+
+        let second = guid();
+        assert_ne!(second, first_guid);
+        assert_eq!(second.len(), 36);
+        assert_eq!(&second[14..15], "4");
+        assert!(matches!(&second[19..20], "8" | "9" | "a" | "b"));
+        assert_eq!(
+            second
+                .char_indices()
+                .filter(|(_, it)| *it == '-')
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>(),
+            vec![8, 13, 18, 23]
+        );
+
+        let mut text = [0xffu8; 10];
+        unsafe { __rusty_guid(text.as_mut_ptr() as *mut c_char, 10) };
+        assert_eq!(text[9], 0);
+        assert!(text[..9]
+            .iter()
+            .all(|it| it.is_ascii_hexdigit() || *it == b'-'));
+    }
+}
@@ -0,0 +1,114 @@
+//! saves and restores the retained variables of an application
+//!
+//! the file starts with `MAGIC` followed by the number of variables and, for every variable, its
+//! size and content. A file is only restored if the number and sizes of its variables match the
+//! application, so a changed layout starts with the variables' initial values.
+use std::{
+    fs,
+    io::{self, Read, Write},
+};
+
+const MAGIC: &[u8] = b"RUSTYRET";
+
+/// writes the given variables into the given file
+pub fn save(path: &str, variables: &[&mut [u8]]) -> io::Result<()> {
+    let mut content = MAGIC.to_vec();
+    content.extend((variables.len() as u64).to_le_bytes());
+    for variable in variables {
+        content.extend((variable.len() as u64).to_le_bytes());
+        content.extend(variable.iter());
+    }
+    //write to a temporary file first so a power loss never leaves a partial file behind
+    let temporary = format!("{}.tmp", path);
+    fs::File::create(&temporary)?.write_all(&content)?;
+    fs::rename(temporary, path)
+}
+
+/// restores the given variables from the given file, returns false if the file does not exist or
+/// does not match the variables
+pub fn restore(path: &str, variables: &mut [&mut [u8]]) -> io::Result<bool> {
+    let mut content = vec![];
+    match fs::File::open(path) {
+        Ok(mut file) => file.read_to_end(&mut content)?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    };
+
+    let mut reader = content.as_slice();
+    if read_bytes(&mut reader, MAGIC.len()) != Some(MAGIC)
+        || read_u64(&mut reader) != Some(variables.len() as u64)
+    {
+        return Ok(false);
+    }
+    let mut stored = vec![];
+    for variable in variables.iter() {
+        match read_u64(&mut reader) {
+            Some(size) if size == variable.len() as u64 => {
+                stored.push(read_bytes(&mut reader, variable.len()))
+            }
+            _ => return Ok(false),
+        }
+    }
+    if stored.iter().any(Option::is_none) {
+        return Ok(false);
+    }
+    for (variable, value) in variables.iter_mut().zip(stored.into_iter().flatten()) {
+        variable.copy_from_slice(value);
+    }
+    Ok(true)
+}
+
+fn read_bytes<'a>(reader: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if reader.len() < len {
+        return None;
+    }
+    let (bytes, rest) = reader.split_at(len);
+    *reader = rest;
+    Some(bytes)
+}
+
+fn read_u64(reader: &mut &[u8]) -> Option<u64> {
+    read_bytes(reader, 8)
+        .and_then(|it| it.try_into().ok())
+        .map(u64::from_le_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{restore, save};
+
+    fn temp_file(name: &str) -> String {
+        let mut path = std::env::temp_dir();
+        path.push(name);
+        path.into_os_string().into_string().unwrap()
+    }
+
+    #[test]
+    fn retained_variables_are_restored() {
+        let path = temp_file("retain_roundtrip.bin");
+        let mut counter = [1, 2, 3, 4];
+        let mut total = [5, 6];
+        save(&path, &[&mut counter, &mut total]).unwrap();
+
+        let mut counter = [0; 4];
+        let mut total = [0; 2];
+        assert!(restore(&path, &mut [&mut counter, &mut total]).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(counter, [1, 2, 3, 4]);
+        assert_eq!(total, [5, 6]);
+    }
+
+    #[test]
+    fn changed_layouts_are_not_restored() {
+        let path = temp_file("retain_changed.bin");
+        save(&path, &[&mut [1, 2, 3, 4]]).unwrap();
+
+        let mut counter = [0; 8];
+        assert!(!restore(&path, &mut [&mut counter]).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(counter, [0; 8]);
+        assert!(!restore(&temp_file("retain_missing.bin"), &mut [&mut counter]).unwrap());
+    }
+}
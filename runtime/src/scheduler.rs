@@ -0,0 +1,204 @@
+//! a cyclic, non-preemptive scheduler: in every tick the due tasks are executed in the order of
+//! their priority (a lower value is a higher priority, as in IEC 61131-3). An event task is due
+//! once its event was raised (see `events`), it is executed in the scheduler's next cycle
+use std::time::Duration;
+
+use crate::{application::Task, clock::Clock, events, fault};
+
+struct ScheduledTask {
+    task: Task,
+    next_release: Duration,
+    /// the number of releases missed because the task did not finish in time
+    overruns: u64,
+}
+
+pub struct Scheduler<C: Clock> {
+    clock: C,
+    tasks: Vec<ScheduledTask>,
+    /// the interval of the tasks without interval
+    tick: Duration,
+}
+
+impl<C: Clock> Scheduler<C> {
+    pub fn new(clock: C, mut tasks: Vec<Task>, tick: Duration) -> Self {
+        tasks.sort_by_key(|it| it.priority);
+        Scheduler {
+            tasks: tasks
+                .into_iter()
+                .map(|task| ScheduledTask {
+                    task,
+                    next_release: Duration::ZERO,
+                    overruns: 0,
+                })
+                .collect(),
+            clock,
+            tick,
+        }
+    }
+
+    /// executes the due tasks and returns the names of the executed tasks
+    pub fn run_cycle(&mut self) -> Vec<&str> {
+        let mut executed = vec![];
+        for scheduled in self.tasks.iter_mut() {
+            if let Some(event) = scheduled.task.event {
+                if events::take(event) {
+                    execute(&scheduled.task);
+                    executed.push(scheduled.task.get_name());
+                }
+                continue;
+            }
+            let now = self.clock.now();
+            if now < scheduled.next_release {
+                continue;
+            }
+            execute(&scheduled.task);
+
+            let interval = scheduled.task.interval.unwrap_or(self.tick);
+            scheduled.next_release += interval;
+            let now = self.clock.now();
+            if scheduled.next_release <= now {
+                //skip the missed releases instead of executing the task back to back
+                let missed = ((now - scheduled.next_release).as_nanos() / interval.as_nanos()) + 1;
+                scheduled.overruns += missed as u64;
+                scheduled.next_release += interval * missed as u32;
+            }
+            executed.push(scheduled.task.get_name());
+        }
+        executed
+    }
+
+    /// waits until the next cyclic task is due, or for one tick if there are only event tasks (or
+    /// no tasks at all)
+    pub fn wait_for_next_release(&self) {
+        let next = self
+            .tasks
+            .iter()
+            .filter(|it| it.task.event.is_none())
+            .map(|it| it.next_release)
+            .min();
+        match next {
+            Some(next) => self.clock.sleep_until(next),
+            //the raised events are polled in every tick
+            None => self.clock.sleep_until(self.clock.now() + self.tick),
+        }
+    }
+
+    /// runs the given number of cycles, or until a stop is requested if no number is given
+    pub fn run(&mut self, cycles: Option<u64>) {
+        self.run_with(cycles, |_| {})
+    }
+
+    /// runs like `run` and calls `after_cycle` with the current time after every cycle
+    pub fn run_with<F: FnMut(Duration)>(&mut self, cycles: Option<u64>, mut after_cycle: F) {
+        let mut cycle = 0;
+        while cycles.map_or(true, |it| cycle < it) && !fault::is_stop_requested() {
+            self.run_cycle();
+            after_cycle(self.clock.now());
+            self.wait_for_next_release();
+            cycle += 1;
+        }
+    }
+
+    /// returns the name and the number of overruns of every task
+    pub fn get_overruns(&self) -> Vec<(&str, u64)> {
+        self.tasks
+            .iter()
+            .map(|it| (it.task.get_name(), it.overruns))
+            .collect()
+    }
+}
+
+/// executes the given task, it is reported if the application faults
+fn execute(task: &Task) {
+    fault::set_current_task(Some(task.get_c_name()));
+    task.execute();
+    fault::set_current_task(None);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, time::Duration};
+
+    use super::Scheduler;
+    use crate::{application::Task, clock::Clock, events};
+
+    /// a clock advanced by the test, sleeping moves it to the requested time
+    struct ManualClock {
+        now: Cell<Duration>,
+    }
+
+    impl Clock for &ManualClock {
+        fn now(&self) -> Duration {
+            self.now.get()
+        }
+
+        fn sleep_until(&self, time: Duration) {
+            if time > self.now.get() {
+                self.now.set(time);
+            }
+        }
+    }
+
+    extern "C" fn noop() {}
+
+    fn millis(value: u64) -> Duration {
+        Duration::from_millis(value)
+    }
+
+    #[test]
+    fn tasks_are_released_by_interval_and_priority() {
+        let clock = ManualClock {
+            now: Cell::new(Duration::ZERO),
+        };
+        let tasks = vec![
+            Task::new("slow", 2, Some(millis(20)), noop),
+            Task::new("fast", 1, Some(millis(10)), noop),
+        ];
+        let mut scheduler = Scheduler::new(&clock, tasks, millis(10));
+
+        assert_eq!(scheduler.run_cycle(), vec!["fast", "slow"]);
+        scheduler.wait_for_next_release();
+        assert_eq!(clock.now.get(), millis(10));
+        assert_eq!(scheduler.run_cycle(), vec!["fast"]);
+        scheduler.wait_for_next_release();
+        assert_eq!(scheduler.run_cycle(), vec!["fast", "slow"]);
+    }
+
+    #[test]
+    fn event_tasks_are_executed_once_their_event_was_raised() {
+        let clock = ManualClock {
+            now: Cell::new(Duration::ZERO),
+        };
+        let tasks = vec![
+            Task::new("cyclic", 2, Some(millis(10)), noop),
+            Task::new_event_task("alarm", 1, 12, noop),
+        ];
+        let mut scheduler = Scheduler::new(&clock, tasks, millis(10));
+
+        assert_eq!(scheduler.run_cycle(), vec!["cyclic"]);
+        events::raise(12);
+        events::raise(12);
+        //the event task's priority is higher, it is released although the cyclic task is not
+        assert_eq!(scheduler.run_cycle(), vec!["alarm"]);
+        scheduler.wait_for_next_release();
+        assert_eq!(clock.now.get(), millis(10));
+        assert_eq!(scheduler.run_cycle(), vec!["cyclic"]);
+    }
+
+    #[test]
+    fn missed_releases_are_counted_as_overruns() {
+        let clock = ManualClock {
+            now: Cell::new(Duration::ZERO),
+        };
+        let tasks = vec![Task::new("fast", 1, Some(millis(10)), noop)];
+        let mut scheduler = Scheduler::new(&clock, tasks, millis(10));
+
+        scheduler.run_cycle();
+        clock.now.set(millis(35));
+        scheduler.run_cycle();
+
+        assert_eq!(scheduler.get_overruns(), vec![("fast", 2)]);
+        scheduler.wait_for_next_release();
+        assert_eq!(clock.now.get(), millis(40));
+    }
+}
@@ -0,0 +1,699 @@
+//! converts numbers to strings and back for the applications' conversion functions
+//! (e.g. `INT_TO_STRING`, `STRING_TO_REAL`)
+//!
+//! the compiler passes integers as LINT (ULINT if unsigned) and strings as the address of their
+//! first character with their length including the terminator. Numbers are formatted and parsed
+//! independently of the locale, a string that is not a number converts to 0 and sets the `error`
+//! flag (if one is given). DTs (milliseconds since 1970-01-01 UTC) and TIMEs (nanoseconds) are
+//! formatted like their literals (e.g. `DT#2021-05-02-14:20:10.250`, `T#1h30m`).
+use std::os::raw::c_char;
+
+/// the helpers by their symbol name, so they can be mapped into a JIT
+pub fn get_helpers() -> Vec<(&'static str, usize)> {
+    vec![
+        (
+            "__rusty_lint_to_string",
+            __rusty_lint_to_string as *const () as usize,
+        ),
+        (
+            "__rusty_ulint_to_string",
+            __rusty_ulint_to_string as *const () as usize,
+        ),
+        (
+            "__rusty_real_to_string",
+            __rusty_real_to_string as *const () as usize,
+        ),
+        (
+            "__rusty_lreal_to_string",
+            __rusty_lreal_to_string as *const () as usize,
+        ),
+        (
+            "__rusty_lint_to_wstring",
+            __rusty_lint_to_wstring as *const () as usize,
+        ),
+        (
+            "__rusty_ulint_to_wstring",
+            __rusty_ulint_to_wstring as *const () as usize,
+        ),
+        (
+            "__rusty_real_to_wstring",
+            __rusty_real_to_wstring as *const () as usize,
+        ),
+        (
+            "__rusty_lreal_to_wstring",
+            __rusty_lreal_to_wstring as *const () as usize,
+        ),
+        (
+            "__rusty_string_to_lint",
+            __rusty_string_to_lint as *const () as usize,
+        ),
+        (
+            "__rusty_string_to_ulint",
+            __rusty_string_to_ulint as *const () as usize,
+        ),
+        (
+            "__rusty_string_to_lreal",
+            __rusty_string_to_lreal as *const () as usize,
+        ),
+        (
+            "__rusty_wstring_to_lint",
+            __rusty_wstring_to_lint as *const () as usize,
+        ),
+        (
+            "__rusty_wstring_to_ulint",
+            __rusty_wstring_to_ulint as *const () as usize,
+        ),
+        (
+            "__rusty_wstring_to_lreal",
+            __rusty_wstring_to_lreal as *const () as usize,
+        ),
+        (
+            "__rusty_dt_to_string",
+            __rusty_dt_to_string as *const () as usize,
+        ),
+        (
+            "__rusty_time_to_string",
+            __rusty_time_to_string as *const () as usize,
+        ),
+        (
+            "__rusty_dt_to_wstring",
+            __rusty_dt_to_wstring as *const () as usize,
+        ),
+        (
+            "__rusty_time_to_wstring",
+            __rusty_time_to_wstring as *const () as usize,
+        ),
+        (
+            "__rusty_string_to_dt",
+            __rusty_string_to_dt as *const () as usize,
+        ),
+        (
+            "__rusty_string_to_time",
+            __rusty_string_to_time as *const () as usize,
+        ),
+        (
+            "__rusty_wstring_to_dt",
+            __rusty_wstring_to_dt as *const () as usize,
+        ),
+        (
+            "__rusty_wstring_to_time",
+            __rusty_wstring_to_time as *const () as usize,
+        ),
+    ]
+}
+
+/// formats a real with the shortest representation that parses to the same value, very big and
+/// very small values use an exponent (e.g. `1.5e-7`)
+pub(crate) fn format_real<T: std::fmt::Display + std::fmt::LowerExp>(
+    value: T,
+    magnitude: f64,
+) -> String {
+    if magnitude != 0.0 && !(1e-5..1e15).contains(&magnitude) && magnitude.is_finite() {
+        format!("{:e}", value)
+    } else {
+        format!("{}", value)
+    }
+}
+
+/// parses an integer with an optional sign, `_` separators and a `2#`, `8#` or `16#` base
+fn parse_integer(text: &str) -> Option<i128> {
+    let text = text.trim().replace('_', "");
+    let (negative, text) = match text.as_bytes().first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text.as_str()),
+    };
+    let (radix, digits) = match text.split_once('#') {
+        Some(("2", digits)) => (2, digits),
+        Some(("8", digits)) => (8, digits),
+        Some(("16", digits)) => (16, digits),
+        Some(_) => return None,
+        None => (10, text),
+    };
+    //the sign is handled above, a second one is not a number
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+    let value = i128::from_str_radix(digits, radix).ok()?;
+    Some(if negative { -value } else { value })
+}
+
+fn parse_real(text: &str) -> Option<f64> {
+    text.trim().replace('_', "").parse().ok()
+}
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+/// the units of a TIME with their length in nanoseconds, from the longest to the shortest
+const TIME_UNITS: [(&str, u64); 7] = [
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+/// returns the days since 1970-01-01 of the given date in the gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// returns the year, month and day of the given days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// removes one of the given prefixes (e.g. `DT#`) ignoring its case
+fn strip_prefix<'a>(text: &'a str, prefixes: &[&str]) -> &'a str {
+    prefixes
+        .iter()
+        .find(|it| {
+            text.get(..it.len())
+                .map_or(false, |start| start.eq_ignore_ascii_case(it))
+        })
+        .map_or(text, |it| &text[it.len()..])
+}
+
+/// parses a number of decimal digits
+fn parse_digits(text: &str) -> Option<i64> {
+    if text.is_empty() || !text.bytes().all(|it| it.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// formats the milliseconds since 1970-01-01 like a DT literal, the milliseconds are omitted if
+/// they are 0
+fn format_date_and_time(millis: i64) -> String {
+    let (year, month, day) = civil_from_days(millis.div_euclid(MILLIS_PER_DAY));
+    let millis = millis.rem_euclid(MILLIS_PER_DAY);
+    let seconds = millis / 1000;
+    let mut text = format!(
+        "DT#{:04}-{:02}-{:02}-{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    if millis % 1000 != 0 {
+        text.push_str(&format!(".{:03}", millis % 1000));
+    }
+    text
+}
+
+/// parses a DT like its literal (`DT#2021-05-02-14:20:10.25`) or an ISO-8601 date and time
+/// (`2021-05-02T14:20:10Z`) into the milliseconds since 1970-01-01
+fn parse_date_and_time(text: &str) -> Option<i64> {
+    let text = strip_prefix(text.trim(), &["DATE_AND_TIME#", "DT#"]);
+    let text = text.strip_suffix(['Z', 'z']).unwrap_or(text);
+    let (date, time) = match text.find(['T', 't', ' ']) {
+        Some(separator) => (&text[..separator], &text[separator + 1..]),
+        None => {
+            let (separator, _) = text.match_indices('-').nth(2)?;
+            (&text[..separator], &text[separator + 1..])
+        }
+    };
+
+    let mut date = date.split('-').map(parse_digits);
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let mut time = time.split(':');
+    let (hour, minute, seconds) = (time.next()?, time.next()?, time.next()?);
+    if date.next().is_some() || time.next().is_some() {
+        return None;
+    }
+    let (hour, minute) = (parse_digits(hour)?, parse_digits(minute)?);
+    let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, "0"));
+    let seconds = parse_digits(seconds)?;
+    // the fraction is given in seconds, only milliseconds are stored
+    parse_digits(fraction)?;
+    let millis = format!("{:0<3}", fraction)[..3].parse::<i64>().ok()?;
+
+    let days = days_from_civil(year, month, day);
+    if !(1..=12).contains(&month)
+        || civil_from_days(days) != (year, month, day)
+        || hour > 23
+        || minute > 59
+        || seconds > 59
+    {
+        return None;
+    }
+    Some(days * MILLIS_PER_DAY + ((hour * 60 + minute) * 60 + seconds) * 1000 + millis)
+}
+
+/// formats the nanoseconds like a TIME literal (e.g. `T#1h30m`, `T#-250ms`)
+fn format_time(nanos: i64) -> String {
+    let mut text = String::from(if nanos < 0 { "T#-" } else { "T#" });
+    let mut rest = nanos.unsigned_abs();
+    for (unit, length) in TIME_UNITS {
+        if rest >= length {
+            text.push_str(&format!("{}{}", rest / length, unit));
+            rest %= length;
+        }
+    }
+    if nanos == 0 {
+        text.push_str("0s");
+    }
+    text
+}
+
+/// parses a TIME like its literal (e.g. `T#1h30m`, `-2.5s`) into nanoseconds. The units have to
+/// be given from the longest to the shortest, only the last one may have a fraction
+fn parse_time(text: &str) -> Option<i64> {
+    let text = strip_prefix(text.trim(), &["TIME#", "LTIME#", "T#", "LT#"]).replace('_', "");
+    let (negative, mut text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text.strip_prefix('+').unwrap_or(&text)),
+    };
+
+    let mut nanos: i128 = 0;
+    let mut next_unit = 0;
+    while !text.is_empty() {
+        let value_end = text.find(|it: char| it.is_ascii_alphabetic())?;
+        let unit_end = text[value_end..]
+            .find(|it: char| !it.is_ascii_alphabetic())
+            .map_or(text.len(), |it| value_end + it);
+        let (value, unit) = (&text[..value_end], &text[value_end..unit_end]);
+        text = &text[unit_end..];
+
+        let position = TIME_UNITS[next_unit..]
+            .iter()
+            .position(|(it, _)| it.eq_ignore_ascii_case(unit))?;
+        let length = TIME_UNITS[next_unit + position].1;
+        next_unit += position + 1;
+
+        let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+        if !fraction.is_empty() && !text.is_empty() {
+            return None;
+        }
+        nanos += parse_digits(whole)? as i128 * length as i128;
+        if !fraction.is_empty() {
+            parse_digits(fraction)?;
+            let fraction: f64 = format!("0.{}", fraction).parse().ok()?;
+            nanos += (fraction * length as f64).round() as i128;
+        }
+    }
+    if next_unit == 0 {
+        return None;
+    }
+    i64::try_from(if negative { -nanos } else { nanos }).ok()
+}
+
+/// writes the text into the string at `destination` which holds `length` characters including
+/// the terminator, the text is truncated if it does not fit
+pub(crate) unsafe fn write<T: From<u8>>(text: &str, destination: *mut T, length: i32) {
+    if destination.is_null() || length < 1 {
+        return;
+    }
+    let count = text.len().min(length as usize - 1);
+    for (i, byte) in text.bytes().take(count).enumerate() {
+        destination.add(i).write(T::from(byte));
+    }
+    destination.add(count).write(T::from(0));
+}
+
+/// reads the string at `source` up to its terminator or its `length`
+unsafe fn read<T: Copy + Into<u32>>(source: *const T, length: i32) -> String {
+    let mut text = String::new();
+    if source.is_null() {
+        return text;
+    }
+    for i in 0..length.max(0) as usize {
+        match (*source.add(i)).into() {
+            0 => break,
+            it => text.push(char::from_u32(it).unwrap_or(char::REPLACEMENT_CHARACTER)),
+        }
+    }
+    text
+}
+
+/// sets the optional error flag and returns the parsed value or 0
+unsafe fn report<T: Default>(value: Option<T>, error: *mut bool) -> T {
+    if !error.is_null() {
+        error.write(value.is_none());
+    }
+    value.unwrap_or_default()
+}
+
+/// returns the integer if it fits into a signed integer of the given bits
+fn signed(value: i128, bits: i32) -> Option<i64> {
+    let max = (1_i128 << (bits.clamp(1, 64) - 1)) - 1;
+    if (-max - 1..=max).contains(&value) {
+        Some(value as i64)
+    } else {
+        None
+    }
+}
+
+/// returns the integer if it fits into an unsigned integer of the given bits
+fn unsigned(value: i128, bits: i32) -> Option<u64> {
+    let max = (1_i128 << bits.clamp(1, 64)) - 1;
+    if (0..=max).contains(&value) {
+        Some(value as u64)
+    } else {
+        None
+    }
+}
+
+/// # Safety
+/// `destination` has to point to a string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_lint_to_string(value: i64, destination: *mut c_char, length: i32) {
+    write(&value.to_string(), destination as *mut u8, length)
+}
+
+/// # Safety
+/// `destination` has to point to a string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_ulint_to_string(
+    value: u64,
+    destination: *mut c_char,
+    length: i32,
+) {
+    write(&value.to_string(), destination as *mut u8, length)
+}
+
+/// # Safety
+/// `destination` has to point to a string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_real_to_string(value: f32, destination: *mut c_char, length: i32) {
+    let text = format_real(value, value.abs() as f64);
+    write(&text, destination as *mut u8, length)
+}
+
+/// # Safety
+/// `destination` has to point to a string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_lreal_to_string(
+    value: f64,
+    destination: *mut c_char,
+    length: i32,
+) {
+    write(
+        &format_real(value, value.abs()),
+        destination as *mut u8,
+        length,
+    )
+}
+
+/// # Safety
+/// `destination` has to point to a wide string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_lint_to_wstring(value: i64, destination: *mut u16, length: i32) {
+    write(&value.to_string(), destination, length)
+}
+
+/// # Safety
+/// `destination` has to point to a wide string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_ulint_to_wstring(value: u64, destination: *mut u16, length: i32) {
+    write(&value.to_string(), destination, length)
+}
+
+/// # Safety
+/// `destination` has to point to a wide string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_real_to_wstring(value: f32, destination: *mut u16, length: i32) {
+    write(&format_real(value, value.abs() as f64), destination, length)
+}
+
+/// # Safety
+/// `destination` has to point to a wide string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_lreal_to_wstring(value: f64, destination: *mut u16, length: i32) {
+    write(&format_real(value, value.abs()), destination, length)
+}
+
+/// parses a signed integer that has to fit into the given number of bits
+///
+/// # Safety
+/// `source` has to point to a string of `length` characters, `error` is null or points to a BOOL
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_string_to_lint(
+    source: *const c_char,
+    length: i32,
+    bits: i32,
+    error: *mut bool,
+) -> i64 {
+    let value = parse_integer(&read(source as *const u8, length)).and_then(|it| signed(it, bits));
+    report(value, error)
+}
+
+/// parses an unsigned integer that has to fit into the given number of bits
+///
+/// # Safety
+/// `source` has to point to a string of `length` characters, `error` is null or points to a BOOL
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_string_to_ulint(
+    source: *const c_char,
+    length: i32,
+    bits: i32,
+    error: *mut bool,
+) -> u64 {
+    let value = parse_integer(&read(source as *const u8, length)).and_then(|it| unsigned(it, bits));
+    report(value, error)
+}
+
+/// # Safety
+/// `source` has to point to a string of `length` characters, `error` is null or points to a BOOL
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_string_to_lreal(
+    source: *const c_char,
+    length: i32,
+    error: *mut bool,
+) -> f64 {
+    report(parse_real(&read(source as *const u8, length)), error)
+}
+
+/// parses a signed integer that has to fit into the given number of bits
+///
+/// # Safety
+/// `source` has to point to a wide string of `length` characters, `error` is null or points to a
+/// BOOL
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_wstring_to_lint(
+    source: *const u16,
+    length: i32,
+    bits: i32,
+    error: *mut bool,
+) -> i64 {
+    let value = parse_integer(&read(source, length)).and_then(|it| signed(it, bits));
+    report(value, error)
+}
+
+/// parses an unsigned integer that has to fit into the given number of bits
+///
+/// # Safety
+/// `source` has to point to a wide string of `length` characters, `error` is null or points to a
+/// BOOL
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_wstring_to_ulint(
+    source: *const u16,
+    length: i32,
+    bits: i32,
+    error: *mut bool,
+) -> u64 {
+    let value = parse_integer(&read(source, length)).and_then(|it| unsigned(it, bits));
+    report(value, error)
+}
+
+/// # Safety
+/// `source` has to point to a wide string of `length` characters, `error` is null or points to a
+/// BOOL
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_wstring_to_lreal(
+    source: *const u16,
+    length: i32,
+    error: *mut bool,
+) -> f64 {
+    report(parse_real(&read(source, length)), error)
+}
+
+/// # Safety
+/// `destination` has to point to a string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_dt_to_string(value: i64, destination: *mut c_char, length: i32) {
+    write(&format_date_and_time(value), destination as *mut u8, length)
+}
+
+/// # Safety
+/// `destination` has to point to a string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_time_to_string(value: i64, destination: *mut c_char, length: i32) {
+    write(&format_time(value), destination as *mut u8, length)
+}
+
+/// # Safety
+/// `destination` has to point to a wide string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_dt_to_wstring(value: i64, destination: *mut u16, length: i32) {
+    write(&format_date_and_time(value), destination, length)
+}
+
+/// # Safety
+/// `destination` has to point to a wide string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_time_to_wstring(value: i64, destination: *mut u16, length: i32) {
+    write(&format_time(value), destination, length)
+}
+
+/// # Safety
+/// `source` has to point to a string of `length` characters, `error` is null or points to a BOOL
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_string_to_dt(
+    source: *const c_char,
+    length: i32,
+    error: *mut bool,
+) -> i64 {
+    report(
+        parse_date_and_time(&read(source as *const u8, length)),
+        error,
+    )
+}
+
+/// # Safety
+/// `source` has to point to a string of `length` characters, `error` is null or points to a BOOL
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_string_to_time(
+    source: *const c_char,
+    length: i32,
+    error: *mut bool,
+) -> i64 {
+    report(parse_time(&read(source as *const u8, length)), error)
+}
+
+/// # Safety
+/// `source` has to point to a wide string of `length` characters, `error` is null or points to a
+/// BOOL
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_wstring_to_dt(
+    source: *const u16,
+    length: i32,
+    error: *mut bool,
+) -> i64 {
+    report(parse_date_and_time(&read(source, length)), error)
+}
+
+/// # Safety
+/// `source` has to point to a wide string of `length` characters, `error` is null or points to a
+/// BOOL
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_wstring_to_time(
+    source: *const u16,
+    length: i32,
+    error: *mut bool,
+) -> i64 {
+    report(parse_time(&read(source, length)), error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_formatted_without_locale() {
+        assert_eq!(format_real(1.5_f64, 1.5), "1.5");
+        assert_eq!(format_real(-0.1_f32, 0.1), "-0.1");
+        assert_eq!(format_real(1e20_f64, 1e20), "1e20");
+        assert_eq!(format_real(2.5e-7_f64, 2.5e-7), "2.5e-7");
+        assert_eq!(format_real(0.0_f64, 0.0), "0");
+    }
+
+    #[test]
+    fn integers_are_parsed_with_bases_and_separators() {
+        assert_eq!(parse_integer(" 42 "), Some(42));
+        assert_eq!(parse_integer("-1_000"), Some(-1000));
+        assert_eq!(parse_integer("16#FF"), Some(255));
+        assert_eq!(parse_integer("2#1010"), Some(10));
+        assert_eq!(parse_integer("12a"), None);
+        assert_eq!(parse_integer("--1"), None);
+        assert_eq!(parse_integer("3#12"), None);
+        assert_eq!(parse_integer(""), None);
+    }
+
+    #[test]
+    fn integers_have_to_fit_into_the_target() {
+        assert_eq!(signed(32767, 16), Some(32767));
+        assert_eq!(signed(-32768, 16), Some(-32768));
+        assert_eq!(signed(32768, 16), None);
+        assert_eq!(unsigned(255, 8), Some(255));
+        assert_eq!(unsigned(-1, 8), None);
+        assert_eq!(unsigned(u64::MAX as i128, 64), Some(u64::MAX));
+    }
+
+    #[test]
+    fn strings_are_truncated_and_terminated() {
+        let mut destination = [b'x'; 4];
+        unsafe { write("12345", destination.as_mut_ptr(), 4) };
+        assert_eq!(&destination, b"123\0");
+
+        let mut error = false;
+        let source = b"7\0garbage";
+        let value = unsafe { __rusty_string_to_lint(source.as_ptr() as _, 9, 16, &mut error) };
+        assert_eq!((value, error), (7, false));
+        let value = unsafe { __rusty_string_to_lint(b"x\0".as_ptr() as _, 2, 16, &mut error) };
+        assert_eq!((value, error), (0, true));
+    }
+
+    #[test]
+    fn date_and_times_are_formatted_like_literals() {
+        assert_eq!(format_date_and_time(0), "DT#1970-01-01-00:00:00");
+        assert_eq!(
+            format_date_and_time(1_619_965_210_250),
+            "DT#2021-05-02-14:20:10.250"
+        );
+        assert_eq!(format_date_and_time(-1), "DT#1969-12-31-23:59:59.999");
+    }
+
+    #[test]
+    fn date_and_times_are_parsed_from_literals_and_iso_8601() {
+        assert_eq!(
+            parse_date_and_time("DT#2021-05-02-14:20:10.25"),
+            Some(1_619_965_210_250)
+        );
+        assert_eq!(
+            parse_date_and_time("2021-05-02T14:20:10Z"),
+            Some(1_619_965_210_000)
+        );
+        assert_eq!(
+            parse_date_and_time("date_and_time#1970-01-01-00:00:00"),
+            Some(0)
+        );
+        assert_eq!(parse_date_and_time("2021-02-29-00:00:00"), None);
+        assert_eq!(parse_date_and_time("2021-05-02-24:00:00"), None);
+        assert_eq!(parse_date_and_time("2021-05-02"), None);
+    }
+
+    #[test]
+    fn times_are_formatted_and_parsed_like_literals() {
+        assert_eq!(format_time(0), "T#0s");
+        assert_eq!(format_time(5_400_000_000_000), "T#1h30m");
+        assert_eq!(format_time(-250_000_001), "T#-250ms1ns");
+        assert_eq!(parse_time("T#1h30m"), Some(5_400_000_000_000));
+        assert_eq!(parse_time("time#-2.5s"), Some(-2_500_000_000));
+        assert_eq!(parse_time("1d_2h"), Some(93_600_000_000_000));
+        assert_eq!(parse_time(&format_time(-250_000_001)), Some(-250_000_001));
+        assert_eq!(parse_time("T#30m1h"), None);
+        assert_eq!(parse_time("T#1.5h30m"), None);
+        assert_eq!(parse_time("T#"), None);
+        assert_eq!(parse_time("T#5x"), None);
+    }
+}
@@ -0,0 +1,155 @@
+//! resolves the instance paths of an application's variables (e.g. `prg.fb1.sub.out`) to their
+//! address, size and type
+//!
+//! the compiler generates the paths as a trie (`__rusty_symbols`): the first node is the root, the
+//! children of every node are stored next to each other ordered by their lower case name. The
+//! offset of a global variable or program is its address, the offset of a member is relative to
+//! its parent.
+
+/// a node of the symbol trie
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolNode {
+    pub name: String,
+    pub type_name: String,
+    pub offset: u64,
+    pub size: u64,
+    pub first_child: usize,
+    pub child_count: usize,
+}
+
+/// a variable of the application
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub address: usize,
+    pub size: usize,
+    /// the type as declared, e.g. `INT`, `STRING[80]` or `ARRAY[1..5] OF INT`
+    pub type_name: String,
+}
+
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    nodes: Vec<SymbolNode>,
+}
+
+impl SymbolTable {
+    pub fn new(nodes: Vec<SymbolNode>) -> Self {
+        SymbolTable { nodes }
+    }
+
+    /// returns the variable at the given path, the names are compared case-insensitively
+    pub fn find_symbol(&self, path: &str) -> Option<Symbol> {
+        let mut node = self.nodes.first()?;
+        let mut address = 0_u64;
+        for name in path.split('.') {
+            node = self.find_child(node, name.trim())?;
+            address = address.wrapping_add(node.offset);
+        }
+        Some(Symbol {
+            address: address as usize,
+            size: node.size as usize,
+            type_name: node.type_name.clone(),
+        })
+    }
+
+    /// returns the members of the given path, the global variables and programs for an empty path
+    pub fn get_children(&self, path: &str) -> Vec<&str> {
+        let mut node = match self.nodes.first() {
+            Some(node) => node,
+            None => return vec![],
+        };
+        for name in path.split('.').filter(|_| !path.is_empty()) {
+            node = match self.find_child(node, name.trim()) {
+                Some(child) => child,
+                None => return vec![],
+            };
+        }
+        self.get_child_nodes(node)
+            .iter()
+            .map(|it| it.name.as_str())
+            .collect()
+    }
+
+    fn find_child(&self, node: &SymbolNode, name: &str) -> Option<&SymbolNode> {
+        let children = self.get_child_nodes(node);
+        let name = name.to_lowercase();
+        children
+            .binary_search_by(|it| it.name.to_lowercase().cmp(&name))
+            .ok()
+            .map(|it| &children[it])
+    }
+
+    fn get_child_nodes(&self, node: &SymbolNode) -> &[SymbolNode] {
+        self.nodes
+            .get(node.first_child..node.first_child + node.child_count)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Symbol, SymbolNode, SymbolTable};
+
+    fn node(name: &str, offset: u64, size: u64, children: (usize, usize)) -> SymbolNode {
+        SymbolNode {
+            name: name.to_string(),
+            type_name: if children.1 > 0 { "motor" } else { "INT" }.to_string(),
+            offset,
+            size,
+            first_child: children.0,
+            child_count: children.1,
+        }
+    }
+
+    /// `counter : INT` at 0x1000, `prg` at 0x2000 with the motors `left` and `right`
+    fn create_table() -> SymbolTable {
+        SymbolTable::new(vec![
+            node("", 0, 0, (1, 2)),
+            node("counter", 0x1000, 2, (0, 0)),
+            node("prg", 0x2000, 8, (3, 2)),
+            node("left", 0, 4, (5, 2)),
+            node("right", 4, 4, (5, 2)),
+            node("position", 2, 2, (0, 0)),
+            node("speed", 0, 2, (0, 0)),
+        ])
+    }
+
+    #[test]
+    fn paths_are_resolved_to_their_address() {
+        let table = create_table();
+
+        assert_eq!(
+            table.find_symbol("counter"),
+            Some(Symbol {
+                address: 0x1000,
+                size: 2,
+                type_name: "INT".to_string()
+            })
+        );
+        assert_eq!(
+            table.find_symbol("PRG.Right").map(|it| it.address),
+            Some(0x2004)
+        );
+        assert_eq!(
+            table.find_symbol("prg.right.position").map(|it| it.address),
+            Some(0x2006)
+        );
+        assert_eq!(
+            table.find_symbol("prg.left.speed").map(|it| it.address),
+            Some(0x2000)
+        );
+        assert_eq!(table.find_symbol("prg.center"), None);
+        assert_eq!(table.find_symbol("counter.speed"), None);
+        assert_eq!(table.find_symbol(""), None);
+        assert_eq!(SymbolTable::default().find_symbol("counter"), None);
+    }
+
+    #[test]
+    fn children_of_paths_are_listed() {
+        let table = create_table();
+
+        assert_eq!(table.get_children(""), vec!["counter", "prg"]);
+        assert_eq!(table.get_children("prg.left"), vec!["position", "speed"]);
+        assert!(table.get_children("counter").is_empty());
+        assert!(table.get_children("unknown").is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+//! publishes the variables listed in the `telemetry` section of the task configuration
+//!
+//! the compiler generates `__rusty_telemetry`, which serializes the variables into a JSON object
+//! keyed by their instance path (e.g. `{"prg.speed":12,"prg.name":"left"}`) using the helpers of
+//! this module. Every helper appends to the buffer as far as it fits and returns the length of the
+//! whole text, so a truncated object is serialized again into a larger buffer. The serialized
+//! objects are handed to a `Publisher` (e.g. a bridge to an MQTT broker) once per interval.
+use std::{ffi::CStr, os::raw::c_char, time::Duration};
+
+use crate::strings::format_real;
+
+/// the helpers by their symbol name, so they can be mapped into a JIT
+pub fn get_helpers() -> Vec<(&'static str, usize)> {
+    vec![
+        ("__rusty_json_text", __rusty_json_text as *const () as usize),
+        ("__rusty_json_lint", __rusty_json_lint as *const () as usize),
+        (
+            "__rusty_json_ulint",
+            __rusty_json_ulint as *const () as usize,
+        ),
+        (
+            "__rusty_json_lreal",
+            __rusty_json_lreal as *const () as usize,
+        ),
+        ("__rusty_json_bool", __rusty_json_bool as *const () as usize),
+        (
+            "__rusty_json_string",
+            __rusty_json_string as *const () as usize,
+        ),
+        (
+            "__rusty_json_wstring",
+            __rusty_json_wstring as *const () as usize,
+        ),
+    ]
+}
+
+/// receives the serialized variables, e.g. to send them to an MQTT broker
+pub trait Publisher {
+    fn publish(&mut self, message: &str);
+}
+
+/// the telemetry publisher generated by the compiler and its interval
+pub struct Telemetry {
+    serialize: extern "C" fn(*mut u8, i32) -> i32,
+    /// the publishing cycle, without interval the variables are published after every cycle
+    pub interval: Option<Duration>,
+    next_publication: Duration,
+    buffer: Vec<u8>,
+}
+
+impl Telemetry {
+    pub fn new(serialize: extern "C" fn(*mut u8, i32) -> i32, interval: Option<Duration>) -> Self {
+        Telemetry {
+            serialize,
+            interval,
+            next_publication: Duration::ZERO,
+            buffer: vec![0; 256],
+        }
+    }
+
+    /// returns the current values of the variables as a JSON object
+    pub fn serialize(&mut self) -> String {
+        loop {
+            let length = (self.serialize)(self.buffer.as_mut_ptr(), self.buffer.len() as i32);
+            let length = length.max(0) as usize;
+            if length <= self.buffer.len() {
+                return String::from_utf8_lossy(&self.buffer[..length]).to_string();
+            }
+            self.buffer.resize(length, 0);
+        }
+    }
+
+    /// publishes the variables if the interval elapsed at the given time
+    pub fn publish_if_due(&mut self, now: Duration, publisher: &mut dyn Publisher) {
+        if now < self.next_publication {
+            return;
+        }
+        publisher.publish(&self.serialize());
+        if let Some(interval) = self.interval {
+            //publications missed while the tasks were executing are skipped
+            while self.next_publication <= now {
+                self.next_publication += interval;
+            }
+        }
+    }
+}
+
+/// appends the given bytes as far as they fit into the buffer and returns the new length
+unsafe fn append(buffer: *mut u8, size: i32, position: i32, text: &[u8]) -> i32 {
+    let position = position.max(0) as usize;
+    let size = size.max(0) as usize;
+    if position < size {
+        let count = text.len().min(size - position);
+        std::ptr::copy_nonoverlapping(text.as_ptr(), buffer.add(position), count);
+    }
+    (position + text.len()) as i32
+}
+
+/// quotes the given text and escapes it as a JSON string
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            it if (it as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", it as u32)),
+            it => quoted.push(it),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// # Safety
+/// `buffer` has to point to `size` bytes, `text` has to be zero-terminated
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_json_text(
+    buffer: *mut u8,
+    size: i32,
+    position: i32,
+    text: *const c_char,
+) -> i32 {
+    append(buffer, size, position, CStr::from_ptr(text).to_bytes())
+}
+
+/// # Safety
+/// `buffer` has to point to `size` bytes
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_json_lint(
+    buffer: *mut u8,
+    size: i32,
+    position: i32,
+    value: i64,
+) -> i32 {
+    append(buffer, size, position, value.to_string().as_bytes())
+}
+
+/// # Safety
+/// `buffer` has to point to `size` bytes
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_json_ulint(
+    buffer: *mut u8,
+    size: i32,
+    position: i32,
+    value: u64,
+) -> i32 {
+    append(buffer, size, position, value.to_string().as_bytes())
+}
+
+/// publishes `null` for infinite values and NaN, JSON cannot represent them
+///
+/// # Safety
+/// `buffer` has to point to `size` bytes
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_json_lreal(
+    buffer: *mut u8,
+    size: i32,
+    position: i32,
+    value: f64,
+) -> i32 {
+    let text = if value.is_finite() {
+        format_real(value, value.abs())
+    } else {
+        "null".to_string()
+    };
+    append(buffer, size, position, text.as_bytes())
+}
+
+/// # Safety
+/// `buffer` has to point to `size` bytes
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_json_bool(
+    buffer: *mut u8,
+    size: i32,
+    position: i32,
+    value: i8,
+) -> i32 {
+    let text: &[u8] = if value != 0 { b"true" } else { b"false" };
+    append(buffer, size, position, text)
+}
+
+/// # Safety
+/// `buffer` has to point to `size` bytes, `value` to a string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_json_string(
+    buffer: *mut u8,
+    size: i32,
+    position: i32,
+    value: *const c_char,
+    length: i32,
+) -> i32 {
+    let value = std::slice::from_raw_parts(value as *const u8, length.max(0) as usize);
+    let end = value.iter().position(|it| *it == 0).unwrap_or(value.len());
+    let text = quote(&String::from_utf8_lossy(&value[..end]));
+    append(buffer, size, position, text.as_bytes())
+}
+
+/// # Safety
+/// `buffer` has to point to `size` bytes, `value` to a wide string of `length` characters
+#[no_mangle]
+pub unsafe extern "C" fn __rusty_json_wstring(
+    buffer: *mut u8,
+    size: i32,
+    position: i32,
+    value: *const u16,
+    length: i32,
+) -> i32 {
+    let value = std::slice::from_raw_parts(value, length.max(0) as usize);
+    let end = value.iter().position(|it| *it == 0).unwrap_or(value.len());
+    let text = quote(&String::from_utf16_lossy(&value[..end]));
+    append(buffer, size, position, text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// serializes `{"prg.speed":-12,"prg.name":"a \"b\"","prg.on":true}` like a generated publisher
+    extern "C" fn serialize(buffer: *mut u8, size: i32) -> i32 {
+        let name = b"a \"b\"\0\0\0";
+        unsafe {
+            let position = __rusty_json_text(buffer, size, 0, b"{\"prg.speed\":\0".as_ptr() as _);
+            let position = __rusty_json_lint(buffer, size, position, -12);
+            let position =
+                __rusty_json_text(buffer, size, position, b",\"prg.name\":\0".as_ptr() as _);
+            let position = __rusty_json_string(
+                buffer,
+                size,
+                position,
+                name.as_ptr() as _,
+                name.len() as i32,
+            );
+            let position =
+                __rusty_json_text(buffer, size, position, b",\"prg.on\":\0".as_ptr() as _);
+            let position = __rusty_json_bool(buffer, size, position, 1);
+            __rusty_json_text(buffer, size, position, b"}\0".as_ptr() as _)
+        }
+    }
+
+    struct Messages(Vec<String>);
+
+    impl Publisher for Messages {
+        fn publish(&mut self, message: &str) {
+            self.0.push(message.to_string())
+        }
+    }
+
+    #[test]
+    fn values_are_serialized_as_json() {
+        let mut telemetry = Telemetry::new(serialize, None);
+        //the buffer grows if the object does not fit
+        telemetry.buffer = vec![0; 4];
+
+        assert_eq!(
+            telemetry.serialize(),
+            r#"{"prg.speed":-12,"prg.name":"a \"b\"","prg.on":true}"#
+        );
+    }
+
+    #[test]
+    fn special_values_are_escaped() {
+        let mut buffer = [0_u8; 32];
+        let wide = "tab\there".encode_utf16().collect::<Vec<_>>();
+        let length = unsafe {
+            let position =
+                __rusty_json_wstring(buffer.as_mut_ptr(), 32, 0, wide.as_ptr(), wide.len() as i32);
+            __rusty_json_lreal(buffer.as_mut_ptr(), 32, position, f64::NAN)
+        };
+
+        assert_eq!(&buffer[..length as usize], br#""tab\there"null"#);
+    }
+
+    #[test]
+    fn values_are_published_once_per_interval() {
+        let mut telemetry = Telemetry::new(serialize, Some(Duration::from_millis(100)));
+        let mut messages = Messages(vec![]);
+        for now in [0, 50, 100, 350] {
+            telemetry.publish_if_due(Duration::from_millis(now), &mut messages);
+        }
+
+        assert_eq!(messages.0.len(), 3);
+        assert_eq!(telemetry.next_publication, Duration::from_millis(400));
+    }
+}
@@ -0,0 +1,316 @@
+//! the versioned ABI metadata embedded into every compiled artifact
+//!
+//! the metadata is stored as a json record in a custom section (see `get_section_name`), every
+//! record is framed by `ABI_MAGIC` and a terminating 0-byte so it can be found in objects, shared
+//! libraries and linked binaries alike. A controller loading an artifact can compare its metadata
+//! against the metadata it expects and refuse to load incompatible logic.
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use inkwell::{
+    context::Context,
+    memory_buffer::MemoryBuffer,
+    module::Module,
+    targets::{
+        CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine, TargetTriple,
+    },
+    OptimizationLevel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{ast::SourceRange, diagnostics::Diagnostic, dialect::Dialect};
+
+/// the version of the metadata's layout, increased whenever the record changes incompatibly
+pub const ABI_VERSION: u32 = 1;
+/// the marker preceding every metadata record
+pub const ABI_MAGIC: &[u8] = b"RUSTY_ABI\0";
+/// the name of the global holding the metadata record
+pub const ABI_SYMBOL: &str = "__rusty_abi_metadata";
+
+const BITCODE_MAGIC: &[u8] = b"BC\xC0\xDE";
+
+/// describes the application compiled into an artifact
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbiMetadata {
+    pub abi_version: u32,
+    pub compiler_version: String,
+    pub dialect: Dialect,
+    pub target: String,
+    /// a hash over the memory layout of all structs and POU instances
+    pub layout_hash: String,
+    /// the POUs exported by the artifact
+    pub exports: Vec<String>,
+    /// the memory layout of all structs and POU instances
+    pub layouts: Vec<InstanceLayout>,
+    /// the variables declared in `RETAIN` blocks
+    pub retained: Vec<RetainedVariable>,
+}
+
+/// the memory layout of a struct or a POU's instance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceLayout {
+    pub name: String,
+    pub size: u64,
+    pub members: Vec<MemberLayout>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemberLayout {
+    pub name: String,
+    pub type_name: String,
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// a variable that keeps its value across restarts, located at `offset` inside `container`
+/// (the symbol of a global variable or of a program's instance)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetainedVariable {
+    pub name: String,
+    pub type_name: String,
+    pub container: String,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl AbiMetadata {
+    pub fn new(dialect: Dialect, target: &str, layout_hash: u64, exports: Vec<String>) -> Self {
+        AbiMetadata {
+            abi_version: ABI_VERSION,
+            compiler_version: env!("CARGO_PKG_VERSION").to_string(),
+            dialect,
+            target: target.to_string(),
+            layout_hash: format!("{:016x}", layout_hash),
+            exports,
+            layouts: vec![],
+            retained: vec![],
+        }
+    }
+
+    pub fn with_layouts(
+        mut self,
+        layouts: Vec<InstanceLayout>,
+        retained: Vec<RetainedVariable>,
+    ) -> Self {
+        self.layouts = layouts;
+        self.retained = retained;
+        self
+    }
+
+    /// returns true if an artifact described by this metadata can replace an artifact described
+    /// by the given metadata without changing the memory layout expected by its users
+    pub fn is_compatible_with(&self, expected: &AbiMetadata) -> bool {
+        self.abi_version == expected.abi_version
+            && self.target == expected.target
+            && self.layout_hash == expected.layout_hash
+            && expected.exports.iter().all(|it| self.exports.contains(it))
+    }
+
+    /// returns the framed record stored in the artifact
+    pub fn to_record(&self) -> Result<Vec<u8>, Diagnostic> {
+        let json = serde_json::to_string(self).map_err(|err| {
+            Diagnostic::codegen_error(
+                format!("Cannot serialize the ABI metadata: {}", err).as_str(),
+                SourceRange::undefined(),
+            )
+        })?;
+        let mut record = ABI_MAGIC.to_vec();
+        record.extend_from_slice(json.as_bytes());
+        record.push(0);
+        Ok(record)
+    }
+}
+
+impl Display for AbiMetadata {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "abi version: {}", self.abi_version)?;
+        writeln!(f, "compiler version: {}", self.compiler_version)?;
+        writeln!(f, "dialect: {}", self.dialect)?;
+        writeln!(f, "target: {}", self.target)?;
+        writeln!(f, "layout hash: {}", self.layout_hash)?;
+
+        writeln!(f, "\nexported POUs:")?;
+        for export in &self.exports {
+            writeln!(f, "  {}", export)?;
+        }
+
+        writeln!(f, "\ninstance layouts:")?;
+        for layout in &self.layouts {
+            writeln!(f, "  {} ({} bytes)", layout.name, layout.size)?;
+            for member in &layout.members {
+                writeln!(
+                    f,
+                    "    {:>6}  {} : {} ({} bytes)",
+                    member.offset, member.name, member.type_name, member.size
+                )?;
+            }
+        }
+
+        write!(f, "\nretained area:")?;
+        if self.retained.is_empty() {
+            write!(f, "\n  (empty)")?;
+        }
+        for variable in &self.retained {
+            write!(
+                f,
+                "\n  {} : {} ({}+{}, {} bytes)",
+                variable.name,
+                variable.type_name,
+                variable.container,
+                variable.offset,
+                variable.size
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// returns the section holding the metadata for the given target-triple
+pub fn get_section_name(triple: &str) -> &'static str {
+    if triple.contains("apple") || triple.contains("darwin") {
+        "__DATA,__rusty_abi"
+    } else {
+        ".rusty_abi"
+    }
+}
+
+/// calculates a stable hash (FNV-1a) over the given layout descriptions
+pub fn hash_layouts<'a>(layouts: impl Iterator<Item = &'a str>) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in layouts.flat_map(|it| it.bytes().chain(std::iter::once(b'\n'))) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// returns all metadata records found in the given binary content
+pub fn find_metadata(content: &[u8]) -> Vec<AbiMetadata> {
+    let mut records = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.windows(ABI_MAGIC.len()).position(|it| it == ABI_MAGIC) {
+        rest = &rest[start + ABI_MAGIC.len()..];
+        let end = rest.iter().position(|it| *it == 0).unwrap_or(rest.len());
+        if let Ok(metadata) = serde_json::from_slice(&rest[..end]) {
+            records.push(metadata);
+        }
+        rest = &rest[end..];
+    }
+    records
+}
+
+/// reads the metadata embedded in the given object, library, bitcode or IR file
+pub fn read_metadata(path: &str) -> Result<Vec<AbiMetadata>, Diagnostic> {
+    let content =
+        std::fs::read(path).map_err(|err| Diagnostic::io_read_error(path, &err.to_string()))?;
+    if content.starts_with(BITCODE_MAGIC) || path.ends_with(".ll") {
+        //the record is not stored verbatim in bitcode and IR, so the module is emitted first
+        let context = Context::create();
+        let module = load_module(&context, path)?;
+        let object = emit_object(&module, path)?;
+        return Ok(find_metadata(object.as_slice()));
+    }
+    Ok(find_metadata(&content))
+}
+
+fn load_module<'ctx>(context: &'ctx Context, path: &str) -> Result<Module<'ctx>, Diagnostic> {
+    if path.ends_with(".ll") {
+        MemoryBuffer::create_from_file(Path::new(path))
+            .and_then(|buffer| context.create_module_from_ir(buffer))
+    } else {
+        Module::parse_bitcode_from_path(path, context)
+    }
+    .map_err(|err| Diagnostic::io_read_error(path, &err.to_string()))
+}
+
+fn emit_object(module: &Module, path: &str) -> Result<MemoryBuffer, Diagnostic> {
+    create_target_machine(&TargetMachine::get_default_triple())?
+        .write_to_memory_buffer(module, FileType::Object)
+        .map_err(|err| Diagnostic::llvm_error(path, &err))
+}
+
+/// creates a machine for the given target-triple, used to lay out and emit the metadata
+pub(crate) fn create_target_machine(triple: &TargetTriple) -> Result<TargetMachine, Diagnostic> {
+    create_configured_target_machine(
+        triple,
+        GENERIC_CPU,
+        "",
+        OptimizationLevel::None,
+        RelocMode::Default,
+    )
+}
+
+/// the CPU of the target machines unless another one is given with `--cpu`
+pub(crate) const GENERIC_CPU: &str = "generic";
+
+/// creates a machine generating code for the given CPU with the given features (e.g.
+/// `+neon,-fp16`) of the given target-triple
+pub(crate) fn create_configured_target_machine(
+    triple: &TargetTriple,
+    cpu: &str,
+    features: &str,
+    optimization: OptimizationLevel,
+    reloc: RelocMode,
+) -> Result<TargetMachine, Diagnostic> {
+    Target::initialize_all(&InitializationConfig::default());
+    let target = Target::from_triple(triple).map_err(|it| {
+        Diagnostic::codegen_error(
+            &format!("Invalid target-tripple '{:}' - {:?}", triple, it),
+            SourceRange::undefined(),
+        )
+    })?;
+    target
+        .create_target_machine(
+            triple,
+            cpu,
+            features,
+            optimization,
+            reloc,
+            CodeModel::Default,
+        )
+        .ok_or_else(|| {
+            Diagnostic::codegen_error("Cannot create target machine.", SourceRange::undefined())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_metadata, hash_layouts, AbiMetadata};
+    use crate::dialect::Dialect;
+
+    fn metadata(layout_hash: u64, exports: &[&str]) -> AbiMetadata {
+        AbiMetadata::new(
+            Dialect::Codesys,
+            "x86_64-unknown-linux-gnu",
+            layout_hash,
+            exports.iter().map(|it| it.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn records_are_found_in_binary_content() {
+        let first = metadata(1, &["prg"]);
+        let second = metadata(2, &["foo", "bar"]);
+        let mut content = vec![0x7f, b'E', b'L', b'F', 0, 0];
+        content.extend(first.to_record().unwrap());
+        content.extend([1, 2, 3]);
+        content.extend(second.to_record().unwrap());
+
+        assert_eq!(find_metadata(&content), vec![first, second]);
+        assert!(find_metadata(b"no metadata").is_empty());
+    }
+
+    #[test]
+    fn layout_changes_break_compatibility() {
+        let deployed = metadata(hash_layouts(["prg = { i16, i32 }"].into_iter()), &["prg"]);
+        let extended = metadata(
+            hash_layouts(["prg = { i16, i32 }"].into_iter()),
+            &["prg", "foo"],
+        );
+        let changed = metadata(hash_layouts(["prg = { i32, i32 }"].into_iter()), &["prg"]);
+
+        assert!(extended.is_compatible_with(&deployed));
+        assert!(!deployed.is_compatible_with(&extended));
+        assert!(!changed.is_compatible_with(&deployed));
+    }
+}
@@ -2,11 +2,13 @@
 use crate::{lexer::IdProvider, typesystem::DataTypeInformation};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     fmt::{Debug, Display, Formatter, Result},
     iter,
     ops::Range,
     unimplemented,
 };
+mod namespaces;
 mod pre_processor;
 
 pub type AstId = usize;
@@ -30,6 +32,73 @@ pub struct Pou {
     pub poly_mode: Option<PolymorphismMode>,
     pub generics: Vec<GenericBinding>,
     pub linkage: LinkageType,
+    /// the name of the class this class `EXTENDS`
+    pub super_class: Option<String>,
+    /// the names of the interfaces this class or function block `IMPLEMENTS`
+    pub interfaces: Vec<String>,
+    /// the namespaces imported by the POU's `USING` directives
+    pub usings: Vec<String>,
+    /// overrides the optimization of the POU's implementations
+    /// (`{attribute 'optimize' := 'size'}`)
+    pub optimization: Option<OptimizationHint>,
+    /// the POU's floating point arithmetic may ignore strict IEEE semantics
+    /// (`{attribute 'fast_math'}`)
+    pub fast_math: bool,
+    /// the calls of the function are replaced by the expression it returns
+    /// (`{attribute 'inline'}`)
+    pub inline: bool,
+    /// the calling convention of an external function (`{external 'stdcall'}`)
+    pub calling_convention: Option<CallingConvention>,
+}
+
+/// the optimization of a POU requested by `{attribute 'optimize' := '...'}`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OptimizationHint {
+    /// the POU is not optimized, so its code stays verifiable against the source
+    None,
+    /// the POU is optimized for size
+    Size,
+    /// the POU is optimized for speed
+    Speed,
+}
+
+impl OptimizationHint {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "none" => Some(OptimizationHint::None),
+            "size" => Some(OptimizationHint::Size),
+            "speed" => Some(OptimizationHint::Speed),
+            _ => None,
+        }
+    }
+}
+
+/// the calling convention of an external function requested by `{external '...'}`, its aggregate
+/// parameters are passed as pointers to copies (`byval`) and its aggregate result is returned
+/// through a pointer passed by the caller (`sret`), as C compilers do
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CallingConvention {
+    /// the target's C calling convention (`cdecl` on x86)
+    C,
+    /// the callee cleans up the stack (`__stdcall`, the convention of the 32-bit Windows API)
+    Stdcall,
+}
+
+impl CallingConvention {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "c" => Some(CallingConvention::C),
+            "stdcall" => Some(CallingConvention::Stdcall),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            CallingConvention::C => "C",
+            CallingConvention::Stdcall => "stdcall",
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -172,6 +241,29 @@ impl DirectAccessType {
             DirectAccessType::Template => unimplemented!("Should not test for template width"),
         }
     }
+
+    /// Returns the bytes of the process image occupied by a variable located at the given address
+    /// (e.g. `[4]` for `%QW4`), `None` for templates and malformed addresses. Byte, word and
+    /// double word addresses are byte offsets. A `BOOL` takes a byte, so bits are stored
+    /// unpacked and numbered consecutively: `%IX1.2` is the bit 10 stored in the byte 10
+    pub fn get_image_range(&self, address: &[i128]) -> Option<Range<u64>> {
+        let offset = match (self, address) {
+            (DirectAccessType::Template, _) => return None,
+            (DirectAccessType::Bit, [bit]) => *bit,
+            (DirectAccessType::Bit, [byte, bit]) if (0..8).contains(bit) => {
+                byte.checked_mul(8)?.checked_add(*bit)?
+            }
+            (DirectAccessType::Bit, _) => return None,
+            (_, [byte]) => *byte,
+            _ => return None,
+        };
+        let width = match self {
+            DirectAccessType::Bit => 1,
+            _ => self.get_bit_width() / 8,
+        };
+        let offset = u64::try_from(offset).ok()?;
+        Some(offset..offset.checked_add(width)?)
+    }
 }
 
 impl Debug for Pou {
@@ -184,6 +276,24 @@ impl Debug for Pou {
         if !self.generics.is_empty() {
             str.field("generics", &self.generics);
         }
+        if self.super_class.is_some() {
+            str.field("super_class", &self.super_class);
+        }
+        if !self.interfaces.is_empty() {
+            str.field("interfaces", &self.interfaces);
+        }
+        if !self.usings.is_empty() {
+            str.field("usings", &self.usings);
+        }
+        if self.optimization.is_some() {
+            str.field("optimization", &self.optimization);
+        }
+        if self.fast_math {
+            str.field("fast_math", &self.fast_math);
+        }
+        if self.inline {
+            str.field("inline", &self.inline);
+        }
         str.finish()
     }
 }
@@ -196,6 +306,39 @@ impl Pou {
     pub fn calc_return_name(pou_name: &str) -> &str {
         pou_name.split('.').last().unwrap_or_default()
     }
+
+    /// returns the expression the given implementation of this function returns if its calls can
+    /// be replaced by it, otherwise the reason why they cannot
+    ///
+    /// the implementation must consist of the single assignment of the function's result and the
+    /// function may only declare `VAR_INPUT` parameters passed by value
+    pub fn get_inline_expression<'i>(
+        &self,
+        implementation: &'i Implementation,
+    ) -> Result<&'i AstStatement, &'static str> {
+        if self.pou_type != PouType::Function {
+            return Err("it is not a FUNCTION");
+        }
+        if !self.generics.is_empty() {
+            return Err("it is generic");
+        }
+        if self
+            .variable_blocks
+            .iter()
+            .any(|it| it.variable_block_type != VariableBlockType::Input(ArgumentProperty::ByVal))
+        {
+            return Err("it declares variables other than VAR_INPUT parameters passed by value");
+        }
+        match implementation.statements.as_slice() {
+            [AstStatement::Assignment { left, right, .. }]
+                if matches!(left.as_ref(), AstStatement::Reference { name, .. }
+                    if name.eq_ignore_ascii_case(self.get_return_name())) =>
+            {
+                Ok(right)
+            }
+            _ => Err("its body is not a single assignment of its result"),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -209,6 +352,9 @@ pub struct Implementation {
     pub overriding: bool,
     pub generic: bool,
     pub access: Option<AccessModifier>,
+    /// the maximum number of iterations of the loops annotated with
+    /// `{attribute 'loop_bound' := 'n'}`, by the loop's id
+    pub loop_bounds: HashMap<AstId, u32>,
 }
 
 #[derive(Debug, Copy, PartialEq, Clone)]
@@ -216,6 +362,9 @@ pub enum LinkageType {
     Internal,
     External,
     BuiltIn,
+    /// an internal definition that may be replaced by another definition at link time
+    /// (`{attribute 'weak'}`)
+    Weak,
 }
 
 #[derive(Debug, PartialEq)]
@@ -234,6 +383,7 @@ pub enum PouType {
     Action,
     Class,
     Method { owner_class: String },
+    Interface,
 }
 
 impl PouType {
@@ -253,13 +403,55 @@ pub struct CompilationUnit {
     pub units: Vec<Pou>,
     pub implementations: Vec<Implementation>,
     pub types: Vec<UserTypeDeclaration>,
+    /// POUs declared `{prototype}`, they have to be implemented by exactly one POU of the
+    /// application
+    pub prototypes: Vec<Pou>,
+    /// the `NAMESPACE` blocks of this unit, the names of the declarations within are already
+    /// qualified with the name of their namespace
+    pub namespaces: Vec<Namespace>,
+    /// the instance specific addresses of the unit's `VAR_CONFIG` blocks
+    pub var_config: Vec<ConfigVariable>,
+    /// the comments of the unit in the order of their appearance in the source
+    pub comments: Vec<Comment>,
+}
+
+/// a comment or a pragma unknown to the compiler, kept to print the unit back to source (see
+/// `formatter`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    /// the comment including its delimiters (e.g. `(* ... *)` or `// ...`)
+    pub text: String,
+    pub location: SourceRange,
+}
+
+/// an entry of a `VAR_CONFIG` block, binding the template address (`AT %I*`) of an instance's
+/// variable to an address (e.g. `main.fb.sensor AT %IX1.2 : BOOL;`)
+#[derive(Debug, PartialEq)]
+pub struct ConfigVariable {
+    /// the path to the configured variable, array elements are written as `fbs[1,2]`
+    pub reference: String,
+    pub data_type: DataTypeDeclaration,
+    /// the configured `AstStatement::HardwareAccess`
+    pub address: AstStatement,
+    pub location: SourceRange,
+}
+
+/// a `NAMESPACE ... END_NAMESPACE` block
+#[derive(Debug, PartialEq)]
+pub struct Namespace {
+    /// the fully qualified name of the namespace (e.g. `plant.conveyor`)
+    pub name: String,
+    /// the namespaces imported by the block's `USING` directives
+    pub usings: Vec<String>,
+    pub location: SourceRange,
 }
 
 impl CompilationUnit {
     /// imports all elements of the other CompilationUnit into this CompilationUnit
     ///
-    /// this will import all global_vars, units, implementations and types. The imported
-    /// structs are moved from the other unit into this unit
+    /// this will import all global_vars, units, implementations, types, prototypes, namespaces,
+    /// config variables and comments.
+    /// The imported structs are moved from the other unit into this unit
     /// # Arguments
     /// `other` the other CompilationUnit to import the elements from.
     pub fn import(&mut self, other: CompilationUnit) {
@@ -267,6 +459,10 @@ impl CompilationUnit {
         self.units.extend(other.units);
         self.implementations.extend(other.implementations);
         self.types.extend(other.types);
+        self.prototypes.extend(other.prototypes);
+        self.namespaces.extend(other.namespaces);
+        self.var_config.extend(other.var_config);
+        self.comments.extend(other.comments);
     }
 }
 
@@ -291,6 +487,20 @@ pub struct VariableBlock {
     pub access: AccessModifier,
     pub constant: bool,
     pub retain: bool,
+    /// true if the variables of this block also keep their value across downloads of the
+    /// application (`PERSISTENT`), persistent variables are retained as well
+    pub persistent: bool,
+    /// true if the variables of this block are declared `{atomic}` (safe to be shared between tasks)
+    pub atomic: bool,
+    /// true if the variables of this block are declared `{shared}` (may be written by more than
+    /// one program)
+    pub shared: bool,
+    /// true if the initial values of this block's variables are loaded from the configuration
+    /// constants (`{attribute 'config_const'}`)
+    pub config_const: bool,
+    /// the position of this block's variables in the initialization order
+    /// (`{attribute 'init_priority' := 'n'}`), lower priorities are initialized first
+    pub init_priority: Option<u32>,
     pub variables: Vec<Variable>,
     pub variable_block_type: VariableBlockType,
     pub linkage: LinkageType,
@@ -482,10 +692,14 @@ pub enum DataType {
         name: Option<String>,
         bounds: AstStatement,
         referenced_type: Box<DataTypeDeclaration>,
+        /// `ARRAY[*] OF ...`, the bounds are taken from the argument passed to the parameter
+        is_variable_length: bool,
     },
     PointerType {
         name: Option<String>,
         referenced_type: Box<DataTypeDeclaration>,
+        /// `REFERENCE TO`, the pointer is dereferenced implicitly and bound with `REF=`
+        auto_deref: bool,
     },
     StringType {
         name: Option<String>,
@@ -500,6 +714,12 @@ pub enum DataType {
         generic_symbol: String,
         nature: TypeNature,
     },
+    /// `FIXED(integer_bits, fraction_bits)`, a signed number stored as a scaled integer
+    FixedPointType {
+        name: Option<String>,
+        integer_bits: u32,
+        fraction_bits: u32,
+    },
 }
 
 impl DataType {
@@ -510,7 +730,8 @@ impl DataType {
             | DataType::SubRangeType { name, .. }
             | DataType::ArrayType { name, .. }
             | DataType::PointerType { name, .. }
-            | DataType::StringType { name, .. } => *name = Some(new_name),
+            | DataType::StringType { name, .. }
+            | DataType::FixedPointType { name, .. } => *name = Some(new_name),
             DataType::GenericType { name, .. } => *name = new_name,
             DataType::VarArgs { .. } => {} //No names on varargs
         }
@@ -523,7 +744,8 @@ impl DataType {
             | DataType::ArrayType { name, .. }
             | DataType::PointerType { name, .. }
             | DataType::StringType { name, .. }
-            | DataType::SubRangeType { name, .. } => name.as_ref().map(|x| x.as_str()),
+            | DataType::SubRangeType { name, .. }
+            | DataType::FixedPointType { name, .. } => name.as_ref().map(|x| x.as_str()),
             DataType::GenericType { name, .. } => Some(name.as_str()),
             DataType::VarArgs { .. } => None,
         }
@@ -726,6 +948,12 @@ pub enum AstStatement {
         right: Box<AstStatement>,
         id: AstId,
     },
+    // ReferenceAssignment (left REF= right)
+    ReferenceAssignment {
+        left: Box<AstStatement>,
+        right: Box<AstStatement>,
+        id: AstId,
+    },
     //Call Statement
     CallStatement {
         operator: Box<AstStatement>,
@@ -784,6 +1012,17 @@ pub enum AstStatement {
         location: SourceRange,
         id: AstId,
     },
+    // Jumps (see `LanguageExtension::Jumps`)
+    JumpStatement {
+        label: String,
+        location: SourceRange,
+        id: AstId,
+    },
+    LabelStatement {
+        name: String,
+        location: SourceRange,
+        id: AstId,
+    },
     LiteralNull {
         location: SourceRange,
         id: AstId,
@@ -919,6 +1158,11 @@ impl Debug for AstStatement {
                 .field("left", left)
                 .field("right", right)
                 .finish(),
+            AstStatement::ReferenceAssignment { left, right, .. } => f
+                .debug_struct("ReferenceAssignment")
+                .field("left", left)
+                .field("right", right)
+                .finish(),
             AstStatement::CallStatement {
                 operator,
                 parameters,
@@ -1020,6 +1264,14 @@ impl Debug for AstStatement {
             AstStatement::ReturnStatement { .. } => f.debug_struct("ReturnStatement").finish(),
             AstStatement::ContinueStatement { .. } => f.debug_struct("ContinueStatement").finish(),
             AstStatement::ExitStatement { .. } => f.debug_struct("ExitStatement").finish(),
+            AstStatement::JumpStatement { label, .. } => f
+                .debug_struct("JumpStatement")
+                .field("label", label)
+                .finish(),
+            AstStatement::LabelStatement { name, .. } => f
+                .debug_struct("LabelStatement")
+                .field("name", name)
+                .finish(),
             AstStatement::CastStatement {
                 target, type_name, ..
             } => f
@@ -1093,6 +1345,11 @@ impl AstStatement {
                 let right_loc = right.get_location();
                 SourceRange::new(left_loc.range.start..right_loc.range.end)
             }
+            AstStatement::ReferenceAssignment { left, right, .. } => {
+                let left_loc = left.get_location();
+                let right_loc = right.get_location();
+                SourceRange::new(left_loc.range.start..right_loc.range.end)
+            }
             AstStatement::CallStatement { location, .. } => location.clone(),
             AstStatement::IfStatement { location, .. } => location.clone(),
             AstStatement::ForLoopStatement { location, .. } => location.clone(),
@@ -1114,6 +1371,8 @@ impl AstStatement {
             AstStatement::ReturnStatement { location, .. } => location.clone(),
             AstStatement::ContinueStatement { location, .. } => location.clone(),
             AstStatement::ExitStatement { location, .. } => location.clone(),
+            AstStatement::JumpStatement { location, .. } => location.clone(),
+            AstStatement::LabelStatement { location, .. } => location.clone(),
             AstStatement::CastStatement { location, .. } => location.clone(),
         }
     }
@@ -1144,6 +1403,7 @@ impl AstStatement {
             AstStatement::RangeStatement { id, .. } => *id,
             AstStatement::Assignment { id, .. } => *id,
             AstStatement::OutputAssignment { id, .. } => *id,
+            AstStatement::ReferenceAssignment { id, .. } => *id,
             AstStatement::CallStatement { id, .. } => *id,
             AstStatement::IfStatement { id, .. } => *id,
             AstStatement::ForLoopStatement { id, .. } => *id,
@@ -1154,6 +1414,8 @@ impl AstStatement {
             AstStatement::ReturnStatement { id, .. } => *id,
             AstStatement::ContinueStatement { id, .. } => *id,
             AstStatement::ExitStatement { id, .. } => *id,
+            AstStatement::JumpStatement { id, .. } => *id,
+            AstStatement::LabelStatement { id, .. } => *id,
             AstStatement::CastStatement { id, .. } => *id,
         }
     }
@@ -1195,8 +1457,17 @@ impl Display for Operator {
             Operator::Multiplication => "*",
             Operator::Division => "/",
             Operator::Equal => "=",
+            Operator::NotEqual => "<>",
             Operator::Modulo => "MOD",
-            _ => unimplemented!(),
+            Operator::Less => "<",
+            Operator::Greater => ">",
+            Operator::LessOrEqual => "<=",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Not => "NOT",
+            Operator::And => "AND",
+            Operator::Or => "OR",
+            Operator::Xor => "XOR",
+            Operator::Address => "&",
         };
         f.write_str(symbol)
     }
@@ -1258,6 +1529,61 @@ pub fn flatten_expression_list(condition: &AstStatement) -> Vec<&AstStatement> {
     }
 }
 
+/// returns the argument passed explicitly to the parameter of the given name (`name := value`)
+/// or implicitly at the given position
+pub fn get_call_argument<'a>(
+    arguments: &[&'a AstStatement],
+    name: &str,
+    position: usize,
+) -> Option<&'a AstStatement> {
+    let is_explicit = |it: &AstStatement| {
+        matches!(
+            it,
+            AstStatement::Assignment { .. } | AstStatement::OutputAssignment { .. }
+        )
+    };
+    arguments
+        .iter()
+        .find_map(|it| match it {
+            AstStatement::Assignment { left, right, .. }
+            | AstStatement::OutputAssignment { left, right, .. } => match left.as_ref() {
+                AstStatement::Reference {
+                    name: left_name, ..
+                } if left_name.eq_ignore_ascii_case(name) => Some(right.as_ref()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .or_else(|| {
+            arguments
+                .get(position)
+                .copied()
+                .filter(|it| !is_explicit(it))
+        })
+}
+
+/// returns the statement lists nested in the given control statement (e.g. the blocks of an IF)
+pub fn get_nested_bodies(statement: &AstStatement) -> Vec<&[AstStatement]> {
+    match statement {
+        AstStatement::IfStatement {
+            blocks, else_block, ..
+        }
+        | AstStatement::CaseStatement {
+            case_blocks: blocks,
+            else_block,
+            ..
+        } => blocks
+            .iter()
+            .map(|it| it.body.as_slice())
+            .chain(std::iter::once(else_block.as_slice()))
+            .collect(),
+        AstStatement::ForLoopStatement { body, .. }
+        | AstStatement::WhileLoopStatement { body, .. }
+        | AstStatement::RepeatLoopStatement { body, .. } => vec![body.as_slice()],
+        _ => vec![],
+    }
+}
+
 /// helper function that creates a call-statement
 pub fn create_call_to(
     function_name: String,
@@ -1348,6 +1674,20 @@ pub fn create_cast_statement(
 pub fn pre_process(unit: &mut CompilationUnit, id_provider: IdProvider) {
     pre_processor::pre_process(unit, id_provider)
 }
+
+/// qualifies the references to the declarations of namespaces in the given units, the units may
+/// reference each other's declarations. This has to happen before the units are pre-processed
+/// and indexed
+pub fn resolve_namespaces(units: Vec<&mut CompilationUnit>) {
+    namespaces::resolve_namespaces(units, vec![])
+}
+
+/// qualifies the references of the given units like `resolve_namespaces`, the declarations of the
+/// already resolved units are visible to them, but their references are left untouched (e.g. the
+/// unchanged documents of an editor)
+pub fn resolve_namespaces_with(units: Vec<&mut CompilationUnit>, resolved: Vec<&CompilationUnit>) {
+    namespaces::resolve_namespaces(units, resolved)
+}
 impl Operator {
     /// returns true, if this operator results in a bool value
     pub(crate) fn is_bool_type(&self) -> bool {
@@ -1375,4 +1715,26 @@ impl Operator {
                 | Operator::GreaterOrEqual
         )
     }
+
+    /// returns true, if this operator is an arithmetic operator
+    /// (+, -, *, /, MOD)
+    pub(crate) fn is_arithmetic_operator(&self) -> bool {
+        matches!(
+            self,
+            Operator::Plus
+                | Operator::Minus
+                | Operator::Multiplication
+                | Operator::Division
+                | Operator::Modulo
+        )
+    }
+
+    /// returns true, if this operator is a bit operator
+    /// (NOT, AND, OR, XOR)
+    pub(crate) fn is_bit_operator(&self) -> bool {
+        matches!(
+            self,
+            Operator::Not | Operator::And | Operator::Or | Operator::Xor
+        )
+    }
 }
@@ -0,0 +1,628 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+//! namespaces qualify the names of their declarations with the namespace's name (e.g.
+//! `plant.conveyor.Motor`). The parser qualifies the declarations, the references to them are
+//! qualified once all units are parsed, because a namespace may be referenced from (and extended
+//! in) any unit. A name is looked up in the namespace of the referencing declaration and its
+//! enclosing namespaces, then in the namespaces imported by `USING` directives and finally in the
+//! global namespace. Variables of the referencing POU shadow the declarations of namespaces.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    AstStatement, CompilationUnit, ConditionalBlock, DataType, DataTypeDeclaration, Pou, PouType,
+    SourceRange, Variable,
+};
+
+fn qualify(namespace: &str, name: &str) -> String {
+    if namespace.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", namespace, name)
+    }
+}
+
+impl CompilationUnit {
+    /// qualifies the names of the unit's declarations with the given namespace
+    pub fn qualify_names(&mut self, namespace: &str) {
+        for pou in self.units.iter_mut().chain(self.prototypes.iter_mut()) {
+            pou.name = qualify(namespace, &pou.name);
+            qualify_pou_type(namespace, &mut pou.pou_type);
+            pou.variable_blocks
+                .iter_mut()
+                .flat_map(|it| it.variables.iter_mut())
+                .for_each(|it| qualify_scope(namespace, &mut it.data_type));
+            if let Some(return_type) = pou.return_type.as_mut() {
+                qualify_scope(namespace, return_type);
+            }
+        }
+        for implementation in self.implementations.iter_mut() {
+            implementation.name = qualify(namespace, &implementation.name);
+            implementation.type_name = qualify(namespace, &implementation.type_name);
+            qualify_pou_type(namespace, &mut implementation.pou_type);
+        }
+        for user_type in self.types.iter_mut() {
+            if let Some(name) = user_type.data_type.get_name() {
+                let name = qualify(namespace, name);
+                user_type.data_type.set_name(name);
+            }
+            if let Some(scope) = user_type.scope.as_mut() {
+                *scope = qualify(namespace, scope);
+            }
+        }
+        for variable in self
+            .global_vars
+            .iter_mut()
+            .flat_map(|it| it.variables.iter_mut())
+        {
+            variable.name = qualify(namespace, &variable.name);
+        }
+        for nested in self.namespaces.iter_mut() {
+            nested.name = qualify(namespace, &nested.name);
+        }
+    }
+}
+
+fn qualify_pou_type(namespace: &str, pou_type: &mut PouType) {
+    if let PouType::Method { owner_class } = pou_type {
+        *owner_class = qualify(namespace, owner_class);
+    }
+}
+
+/// qualifies the scope of the types declared inline in a POU
+fn qualify_scope(namespace: &str, declaration: &mut DataTypeDeclaration) {
+    if let DataTypeDeclaration::DataTypeDefinition {
+        data_type, scope, ..
+    } = declaration
+    {
+        if let Some(scope) = scope.as_mut() {
+            *scope = qualify(namespace, scope);
+        }
+        match data_type {
+            DataType::ArrayType {
+                referenced_type, ..
+            }
+            | DataType::PointerType {
+                referenced_type, ..
+            } => qualify_scope(namespace, referenced_type),
+            DataType::StructType { variables, .. } => variables
+                .iter_mut()
+                .for_each(|it| qualify_scope(namespace, &mut it.data_type)),
+            _ => {}
+        }
+    }
+}
+
+pub fn resolve_namespaces(mut units: Vec<&mut CompilationUnit>, resolved: Vec<&CompilationUnit>) {
+    let declaring_units = units
+        .iter()
+        .map(|it| &**it)
+        .chain(resolved)
+        .collect::<Vec<_>>();
+    let namespaces = Namespaces::new(&declaring_units);
+    //without namespaces there is nothing to qualify
+    if namespaces.names.is_empty() {
+        return;
+    }
+    for unit in units.iter_mut() {
+        namespaces.resolve_unit(unit);
+    }
+}
+
+/// the names visible to a declaration
+struct Scope {
+    /// the namespace the declaration is located in
+    namespace: String,
+    /// the namespaces imported by the `USING` directives of the declaration and its namespaces
+    usings: Vec<String>,
+    /// the lower case names of the variables shadowing the declarations of namespaces
+    locals: HashSet<String>,
+}
+
+impl Scope {
+    fn is_local(&self, name: &str) -> bool {
+        self.locals.contains(&name.to_lowercase())
+    }
+}
+
+/// the POUs' names visible to their bodies
+struct PouNames {
+    usings: Vec<String>,
+    variables: HashSet<String>,
+    super_class: Option<String>,
+}
+
+/// the namespaces and the declarations of all units
+struct Namespaces {
+    /// the lower case names of the declared namespaces
+    names: HashSet<String>,
+    /// the `USING` directives of the namespaces by the namespace's lower case name
+    usings: HashMap<String, Vec<String>>,
+    /// the qualified names of the POUs, types and global variables declared in namespaces by
+    /// their lower case name
+    declarations: HashMap<String, String>,
+    /// the names visible to the POUs by the POU's lower case name
+    pous: HashMap<String, PouNames>,
+}
+
+impl Namespaces {
+    fn new(units: &[&CompilationUnit]) -> Namespaces {
+        let mut namespaces = Namespaces {
+            names: HashSet::new(),
+            usings: HashMap::new(),
+            declarations: HashMap::new(),
+            pous: HashMap::new(),
+        };
+        //the `USING` directives outside of namespaces only apply to their own file
+        for namespace in units
+            .iter()
+            .flat_map(|it| it.namespaces.iter())
+            .filter(|it| !it.name.is_empty())
+        {
+            namespaces.names.insert(namespace.name.to_lowercase());
+            namespaces
+                .usings
+                .entry(namespace.name.to_lowercase())
+                .or_default()
+                .extend(namespace.usings.iter().cloned());
+        }
+
+        let mut declarations = vec![];
+        for unit in units.iter() {
+            for pou in unit.units.iter().chain(unit.prototypes.iter()) {
+                namespaces.pous.insert(
+                    pou.name.to_lowercase(),
+                    PouNames {
+                        usings: pou.usings.clone(),
+                        variables: pou
+                            .variable_blocks
+                            .iter()
+                            .flat_map(|it| it.variables.iter())
+                            .map(|it| it.name.to_lowercase())
+                            .collect(),
+                        super_class: pou.super_class.clone(),
+                    },
+                );
+                if !matches!(pou.pou_type, PouType::Method { .. }) {
+                    declarations.push(pou.name.as_str());
+                }
+            }
+            declarations.extend(unit.types.iter().filter_map(|it| it.data_type.get_name()));
+            declarations.extend(
+                unit.global_vars
+                    .iter()
+                    .flat_map(|it| it.variables.iter())
+                    .map(|it| it.name.as_str()),
+            );
+        }
+        for name in declarations {
+            if !namespaces.get_namespace(name).is_empty() {
+                namespaces
+                    .declarations
+                    .insert(name.to_lowercase(), name.to_string());
+            }
+        }
+        namespaces
+    }
+
+    /// returns the namespace the given qualified name is declared in, `""` for the global namespace
+    fn get_namespace<'n>(&self, name: &'n str) -> &'n str {
+        let mut end = name.len();
+        while let Some(position) = name[..end].rfind('.') {
+            if self.names.contains(&name[..position].to_lowercase()) {
+                return &name[..position];
+            }
+            end = position;
+        }
+        ""
+    }
+
+    /// returns the namespaces imported into the given namespace, including the ones imported
+    /// into its enclosing namespaces
+    fn get_usings(&self, namespace: &str) -> Vec<String> {
+        let mut usings = vec![];
+        let mut namespace = namespace;
+        loop {
+            if let Some(it) = self.usings.get(&namespace.to_lowercase()) {
+                usings.extend(it.iter().cloned());
+            }
+            if namespace.is_empty() {
+                return usings;
+            }
+            namespace = namespace.rfind('.').map_or("", |it| &namespace[..it]);
+        }
+    }
+
+    /// returns the qualified name of the declaration the given name refers to from within the
+    /// given namespace, None if it does not refer to a declaration of a namespace
+    fn find_declaration(&self, namespace: &str, usings: &[String], name: &str) -> Option<&str> {
+        let mut namespace = namespace;
+        loop {
+            if let Some(it) = self
+                .declarations
+                .get(&qualify(namespace, name).to_lowercase())
+            {
+                return Some(it.as_str());
+            }
+            if namespace.is_empty() {
+                break;
+            }
+            namespace = namespace.rfind('.').map_or("", |it| &namespace[..it]);
+        }
+        usings
+            .iter()
+            .find_map(|it| self.declarations.get(&qualify(it, name).to_lowercase()))
+            .map(String::as_str)
+    }
+
+    /// creates the scope of the declaration with the given qualified name, methods and actions
+    /// see the names of their POU, classes the names of their super classes
+    fn create_scope(&self, name: &str, file_usings: &[String]) -> Scope {
+        let namespace = self.get_namespace(name);
+        let mut usings = vec![];
+        let mut locals = HashSet::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![name.to_string()];
+        while let Some(current) = pending.pop() {
+            if !visited.insert(current.to_lowercase()) {
+                continue;
+            }
+            if let Some(pou) = self.pous.get(&current.to_lowercase()) {
+                usings.extend(pou.usings.iter().cloned());
+                locals.extend(pou.variables.iter().cloned());
+                if let Some(super_class) = &pou.super_class {
+                    let super_class = self
+                        .find_declaration(self.get_namespace(&current), &pou.usings, super_class)
+                        .unwrap_or(super_class);
+                    pending.push(super_class.to_string());
+                }
+            }
+            //the POU owning the method or action
+            if let Some(position) = current.rfind('.') {
+                pending.push(current[..position].to_string());
+            }
+        }
+        usings.extend(self.get_usings(namespace));
+        usings.extend(file_usings.iter().cloned());
+        Scope {
+            namespace: namespace.to_string(),
+            usings,
+            locals,
+        }
+    }
+
+    fn resolve_name(&self, scope: &Scope, name: &mut String) {
+        if let Some(qualified) = self.find_declaration(&scope.namespace, &scope.usings, name) {
+            *name = qualified.to_string();
+        }
+    }
+
+    fn resolve_unit(&self, unit: &mut CompilationUnit) {
+        let file_usings = unit
+            .namespaces
+            .iter()
+            .filter(|it| it.name.is_empty())
+            .flat_map(|it| it.usings.iter().cloned())
+            .collect::<Vec<_>>();
+        for pou in unit.units.iter_mut().chain(unit.prototypes.iter_mut()) {
+            let scope = self.create_scope(&pou.name, &file_usings);
+            if let Some(super_class) = pou.super_class.as_mut() {
+                self.resolve_name(&scope, super_class);
+            }
+            for interface in pou.interfaces.iter_mut() {
+                self.resolve_name(&scope, interface);
+            }
+            for variable in pou
+                .variable_blocks
+                .iter_mut()
+                .flat_map(|it| it.variables.iter_mut())
+            {
+                self.resolve_variable(&scope, variable);
+            }
+            if let Some(return_type) = pou.return_type.as_mut() {
+                self.resolve_data_type_declaration(&scope, return_type);
+            }
+        }
+
+        for implementation in unit.implementations.iter_mut() {
+            let mut scope = self.create_scope(&implementation.type_name, &file_usings);
+            //the return variable is named after the function
+            scope
+                .locals
+                .insert(Pou::calc_return_name(&implementation.name).to_lowercase());
+            for statement in implementation.statements.iter_mut() {
+                self.resolve_statement(&scope, statement);
+            }
+        }
+
+        for user_type in unit.types.iter_mut() {
+            let scope = self.create_scope(
+                user_type
+                    .data_type
+                    .get_name()
+                    .or(user_type.scope.as_deref())
+                    .unwrap_or_default(),
+                &file_usings,
+            );
+            self.resolve_data_type(&scope, &mut user_type.data_type);
+            if let Some(initializer) = user_type.initializer.as_mut() {
+                self.resolve_expression(&scope, initializer);
+            }
+        }
+
+        for variable in unit
+            .global_vars
+            .iter_mut()
+            .flat_map(|it| it.variables.iter_mut())
+        {
+            let scope = self.create_scope(&variable.name, &file_usings);
+            self.resolve_variable(&scope, variable);
+        }
+    }
+
+    fn resolve_variable(&self, scope: &Scope, variable: &mut Variable) {
+        self.resolve_data_type_declaration(scope, &mut variable.data_type);
+        if let Some(initializer) = variable.initializer.as_mut() {
+            self.resolve_expression(scope, initializer);
+        }
+    }
+
+    fn resolve_data_type_declaration(&self, scope: &Scope, declaration: &mut DataTypeDeclaration) {
+        match declaration {
+            DataTypeDeclaration::DataTypeReference {
+                referenced_type, ..
+            } => self.resolve_name(scope, referenced_type),
+            DataTypeDeclaration::DataTypeDefinition { data_type, .. } => {
+                self.resolve_data_type(scope, data_type)
+            }
+        }
+    }
+
+    fn resolve_data_type(&self, scope: &Scope, data_type: &mut DataType) {
+        match data_type {
+            DataType::StructType { variables, .. } => {
+                for variable in variables.iter_mut() {
+                    self.resolve_variable(scope, variable);
+                }
+            }
+            DataType::SubRangeType {
+                referenced_type,
+                bounds,
+                ..
+            } => {
+                self.resolve_name(scope, referenced_type);
+                if let Some(bounds) = bounds.as_mut() {
+                    self.resolve_expression(scope, bounds);
+                }
+            }
+            DataType::ArrayType {
+                bounds,
+                referenced_type,
+                ..
+            } => {
+                self.resolve_expression(scope, bounds);
+                self.resolve_data_type_declaration(scope, referenced_type);
+            }
+            DataType::PointerType {
+                referenced_type, ..
+            }
+            | DataType::VarArgs {
+                referenced_type: Some(referenced_type),
+            } => self.resolve_data_type_declaration(scope, referenced_type),
+            DataType::StringType {
+                size: Some(size), ..
+            } => self.resolve_expression(scope, size),
+            _ => {}
+        }
+    }
+
+    /// resolves the references of the given statement, the left side of an assignment is a
+    /// variable only on the level of statements (in expressions it names a parameter or member)
+    fn resolve_statement(&self, scope: &Scope, statement: &mut AstStatement) {
+        match statement {
+            AstStatement::Assignment { left, right, .. }
+            | AstStatement::OutputAssignment { left, right, .. }
+            | AstStatement::ReferenceAssignment { left, right, .. } => {
+                self.resolve_expression(scope, left);
+                self.resolve_expression(scope, right);
+            }
+            AstStatement::IfStatement {
+                blocks, else_block, ..
+            } => {
+                self.resolve_conditional_blocks(scope, blocks);
+                self.resolve_statements(scope, else_block);
+            }
+            AstStatement::CaseStatement {
+                selector,
+                case_blocks,
+                else_block,
+                ..
+            } => {
+                self.resolve_expression(scope, selector);
+                self.resolve_conditional_blocks(scope, case_blocks);
+                self.resolve_statements(scope, else_block);
+            }
+            AstStatement::ForLoopStatement {
+                counter,
+                start,
+                end,
+                by_step,
+                body,
+                ..
+            } => {
+                self.resolve_expression(scope, counter);
+                self.resolve_expression(scope, start);
+                self.resolve_expression(scope, end);
+                if let Some(by_step) = by_step.as_mut() {
+                    self.resolve_expression(scope, by_step);
+                }
+                self.resolve_statements(scope, body);
+            }
+            AstStatement::WhileLoopStatement {
+                condition, body, ..
+            }
+            | AstStatement::RepeatLoopStatement {
+                condition, body, ..
+            } => {
+                self.resolve_expression(scope, condition);
+                self.resolve_statements(scope, body);
+            }
+            _ => self.resolve_expression(scope, statement),
+        }
+    }
+
+    fn resolve_conditional_blocks(&self, scope: &Scope, blocks: &mut [ConditionalBlock]) {
+        for block in blocks.iter_mut() {
+            self.resolve_expression(scope, &mut block.condition);
+            self.resolve_statements(scope, &mut block.body);
+        }
+    }
+
+    fn resolve_statements(&self, scope: &Scope, statements: &mut [AstStatement]) {
+        for statement in statements.iter_mut() {
+            self.resolve_statement(scope, statement);
+        }
+    }
+
+    fn resolve_expression(&self, scope: &Scope, expression: &mut AstStatement) {
+        let resolved = match expression {
+            AstStatement::Reference { name, .. } => {
+                if !scope.is_local(name) {
+                    self.resolve_name(scope, name);
+                }
+                None
+            }
+            AstStatement::QualifiedReference { elements, .. } => {
+                self.resolve_qualified_reference(scope, elements);
+                //`plant.Main` collapsed into a single reference
+                if elements.len() == 1 {
+                    elements.pop()
+                } else {
+                    None
+                }
+            }
+            AstStatement::CastStatement {
+                target, type_name, ..
+            } => {
+                self.resolve_name(scope, type_name);
+                self.resolve_expression(scope, target);
+                None
+            }
+            AstStatement::ArrayAccess {
+                reference, access, ..
+            } => {
+                self.resolve_expression(scope, reference);
+                self.resolve_expression(scope, access);
+                None
+            }
+            AstStatement::BinaryExpression { left, right, .. }
+            | AstStatement::RangeStatement {
+                start: left,
+                end: right,
+                ..
+            } => {
+                self.resolve_expression(scope, left);
+                self.resolve_expression(scope, right);
+                None
+            }
+            AstStatement::MultipliedStatement { element: value, .. }
+            | AstStatement::UnaryExpression { value, .. }
+            | AstStatement::PointerAccess {
+                reference: value, ..
+            }
+            | AstStatement::CaseCondition {
+                condition: value, ..
+            } => {
+                self.resolve_expression(scope, value);
+                None
+            }
+            AstStatement::LiteralArray {
+                elements: Some(value),
+                ..
+            } => {
+                self.resolve_expression(scope, value);
+                None
+            }
+            AstStatement::ExpressionList { expressions, .. } => {
+                for expression in expressions.iter_mut() {
+                    self.resolve_expression(scope, expression);
+                }
+                None
+            }
+            //the left side names a parameter or the member of a struct
+            AstStatement::Assignment { right, .. }
+            | AstStatement::OutputAssignment { right, .. } => {
+                self.resolve_expression(scope, right);
+                None
+            }
+            AstStatement::CallStatement {
+                operator,
+                parameters,
+                ..
+            } => {
+                self.resolve_expression(scope, operator);
+                if let Some(parameters) = parameters.as_mut() {
+                    self.resolve_expression(scope, parameters);
+                }
+                None
+            }
+            _ => None,
+        };
+        if let Some(resolved) = resolved {
+            *expression = resolved;
+        }
+    }
+
+    /// collapses the leading segments naming a declaration of a namespace into a single
+    /// reference, e.g. `plant.conveyor.Main.speed` into `plant.conveyor.Main` and `speed`
+    fn resolve_qualified_reference(&self, scope: &Scope, elements: &mut Vec<AstStatement>) {
+        let mut names = vec![];
+        for element in elements.iter() {
+            match element {
+                AstStatement::Reference { name, .. } => names.push(name.as_str()),
+                //the array access ends the names, e.g. `plant.table[1].x`
+                AstStatement::ArrayAccess { reference, .. } => {
+                    if let AstStatement::Reference { name, .. } = reference.as_ref() {
+                        names.push(name.as_str());
+                    }
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        let resolved = match names.first() {
+            Some(first) if !scope.is_local(first) => (1..=names.len()).find_map(|length| {
+                self.find_declaration(&scope.namespace, &scope.usings, &names[..length].join("."))
+                    .map(|it| (length, it.to_string()))
+            }),
+            _ => None,
+        };
+        if let Some((length, qualified)) = resolved {
+            let start = elements[0].get_location().get_start();
+            elements.drain(..length - 1);
+            match &mut elements[0] {
+                AstStatement::Reference { name, location, .. } => {
+                    *name = qualified;
+                    *location = SourceRange::new(start..location.get_end());
+                }
+                AstStatement::ArrayAccess { reference, .. } => {
+                    if let AstStatement::Reference { name, .. } = reference.as_mut() {
+                        *name = qualified;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (position, element) in elements.iter_mut().enumerate() {
+            match element {
+                //the first reference was looked up above, the following ones name members
+                AstStatement::Reference { .. } => {}
+                AstStatement::ArrayAccess { access, .. } => self.resolve_expression(scope, access),
+                _ if position == 0 => self.resolve_expression(scope, element),
+                _ => {}
+            }
+        }
+    }
+}
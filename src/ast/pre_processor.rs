@@ -323,6 +323,9 @@ fn replace_generic_type_name(dt: &mut DataTypeDeclaration, generics: &HashMap<St
             | DataType::PointerType {
                 referenced_type, ..
             } => replace_generic_type_name(referenced_type.as_mut(), generics),
+            DataType::VarArgs {
+                referenced_type: Some(referenced_type),
+            } => replace_generic_type_name(referenced_type.as_mut(), generics),
             _ => {}
         },
         DataTypeDeclaration::DataTypeReference {
@@ -1,15 +1,135 @@
-use std::collections::HashMap;
+//! the builtin functions and function blocks of the language
+//!
+//! the builtins are kept in a registry by their name together with the name of their provider.
+//! The compiler's builtins (e.g. `ADR`, `SEL` or `INT_TO_STRING`) are provided by the
+//! [`STANDARD_PROVIDER`], embedders (e.g. the vendor of a runtime) extend the registry with their
+//! system API before compiling. A builtin is declared by its ST signature and is either generated
+//! in place by a codegen callback ([`register_builtin`]) or bound to the external function or
+//! function block of the same name the runtime provides ([`register_extern_builtin`]). The
+//! provider only labels a builtin, builtins are called by their plain name, so a name can only be
+//! registered once.
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::RwLock,
+};
 
-use inkwell::values::{BasicValue, BasicValueEnum};
+use inkwell::{
+    values::{BasicValue, BasicValueEnum},
+    FloatPredicate,
+};
 use lazy_static::lazy_static;
 
 use crate::{
-    ast::{AstStatement, CompilationUnit, LinkageType, SourceRange},
-    codegen::generators::expression_generator::ExpressionCodeGenerator,
-    diagnostics::Diagnostic,
+    ast::{self, CompilationUnit, LinkageType, PouType},
+    codegen::generators::expression_generator,
+    diagnostics::{DefaultDiagnosticAssessor, Diagnostic, DiagnosticAssessor, Severity},
     lexer::{self, IdProvider},
     parser,
+    resolver::TypeAnnotator,
+    typesystem::{DINT_TYPE, LREAL_TYPE, REAL_TYPE},
 };
+pub use crate::{
+    ast::{AstStatement, SourceRange},
+    codegen::generators::expression_generator::ExpressionCodeGenerator,
+};
+
+/// the provider of the compiler's builtins
+pub const STANDARD_PROVIDER: &str = "std";
+
+/// declares `<T>_TO_STRING`, `<T>_TO_WSTRING`, `STRING_TO_<T>` and `WSTRING_TO_<T>` for each of
+/// the given number types, parsing a string reports invalid numbers in its `error` output
+macro_rules! string_conversions {
+    ($($number:literal),*) => {
+        HashMap::from([$(
+            (
+                concat!($number, "_TO_STRING"),
+                BuiltIn {
+                    vendor: false,
+                    annotation: None,
+                    decl: concat!("FUNCTION ", $number, "_TO_STRING : STRING
+                    VAR_INPUT
+                        in : ", $number, ";
+                    END_VAR
+                    END_FUNCTION
+                    "),
+                    code: |generator, params, location| {
+                        generator.generate_number_to_string(
+                            concat!($number, "_TO_STRING"),
+                            params,
+                            location,
+                        )
+                    }
+                },
+            ),
+            (
+                concat!($number, "_TO_WSTRING"),
+                BuiltIn {
+                    vendor: false,
+                    annotation: None,
+                    decl: concat!("FUNCTION ", $number, "_TO_WSTRING : WSTRING
+                    VAR_INPUT
+                        in : ", $number, ";
+                    END_VAR
+                    END_FUNCTION
+                    "),
+                    code: |generator, params, location| {
+                        generator.generate_number_to_string(
+                            concat!($number, "_TO_WSTRING"),
+                            params,
+                            location,
+                        )
+                    }
+                },
+            ),
+            (
+                concat!("STRING_TO_", $number),
+                BuiltIn {
+                    vendor: false,
+                    annotation: None,
+                    decl: concat!("FUNCTION STRING_TO_", $number, " : ", $number, "
+                    VAR_INPUT
+                        in : STRING;
+                    END_VAR
+                    VAR_OUTPUT
+                        error : BOOL;
+                    END_VAR
+                    END_FUNCTION
+                    "),
+                    code: |generator, params, location| {
+                        generator.generate_string_to_number(
+                            concat!("STRING_TO_", $number),
+                            params,
+                            location,
+                        )
+                    }
+                },
+            ),
+            (
+                concat!("WSTRING_TO_", $number),
+                BuiltIn {
+                    vendor: false,
+                    annotation: None,
+                    decl: concat!("FUNCTION WSTRING_TO_", $number, " : ", $number, "
+                    VAR_INPUT
+                        in : WSTRING;
+                    END_VAR
+                    VAR_OUTPUT
+                        error : BOOL;
+                    END_VAR
+                    END_FUNCTION
+                    "),
+                    code: |generator, params, location| {
+                        generator.generate_string_to_number(
+                            concat!("WSTRING_TO_", $number),
+                            params,
+                            location,
+                        )
+                    }
+                },
+            ),
+        )*])
+    };
+}
 
 // Defines a set of functions that are always included in a compiled application
 lazy_static! {
@@ -17,6 +137,8 @@ lazy_static! {
         (
             "ADR",
             BuiltIn {
+                vendor: true,
+                annotation: None,
                 decl: "FUNCTION ADR<T: ANY> : LWORD
                 VAR_INPUT
                     in : T;
@@ -40,6 +162,8 @@ lazy_static! {
         (
             "REF",
             BuiltIn {
+                vendor: false,
+                annotation: None,
                 decl: "FUNCTION REF<T: ANY> : REF_TO T
                 VAR_INPUT
                     in : T;
@@ -59,17 +183,786 @@ lazy_static! {
                     }
                 }
             },
+        ),
+        (
+            "ALMOST_EQUAL",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION ALMOST_EQUAL<T: ANY_REAL> : BOOL
+                VAR_INPUT
+                    a : T;
+                    b : T;
+                    epsilon : T;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    if let [a, b, epsilon] = params {
+                        // compare as LREAL, so parameters of different types can be mixed
+                        let llvm = generator.llvm;
+                        let lreal_type = llvm.context.f64_type();
+                        let mut values = Vec::with_capacity(3);
+                        for param in [a, b, epsilon] {
+                            let value = match generator.generate_expression(param)? {
+                                BasicValueEnum::FloatValue(value) => {
+                                    llvm.builder.build_float_cast(value, lreal_type, "")
+                                }
+                                BasicValueEnum::IntValue(value)
+                                    if generator.get_type_info_for(param).is_signed_int() =>
+                                {
+                                    llvm.builder.build_signed_int_to_float(value, lreal_type, "")
+                                }
+                                BasicValueEnum::IntValue(value) => {
+                                    llvm.builder.build_unsigned_int_to_float(value, lreal_type, "")
+                                }
+                                _ => {
+                                    return Err(Diagnostic::codegen_error(
+                                        "Expected numeric parameters for ALMOST_EQUAL",
+                                        param.get_location(),
+                                    ))
+                                }
+                            };
+                            values.push(value);
+                        }
+                        let (a, b, epsilon) = (values[0], values[1], values[2]);
+
+                        // |a - b| <= epsilon  <=>  a - b <= epsilon AND b - a <= epsilon
+                        let a_minus_b = llvm.builder.build_float_sub(a, b, "");
+                        let b_minus_a = llvm.builder.build_float_sub(b, a, "");
+                        let lower = llvm.builder.build_float_compare(
+                            FloatPredicate::OLE,
+                            a_minus_b,
+                            epsilon,
+                            "",
+                        );
+                        let upper = llvm.builder.build_float_compare(
+                            FloatPredicate::OLE,
+                            b_minus_a,
+                            epsilon,
+                            "",
+                        );
+                        Ok(llvm.builder.build_and(lower, upper, "").as_basic_value_enum())
+                    } else {
+                        Err(Diagnostic::codegen_error(
+                            "Expected exactly three parameters for ALMOST_EQUAL",
+                            location,
+                        ))
+                    }
+                }
+            },
+        ),
+        (
+            "SEL",
+            BuiltIn {
+                vendor: false,
+                annotation: None,
+                decl: "FUNCTION SEL<T: ANY> : T
+                VAR_INPUT
+                    G : BOOL;
+                    IN0 : T;
+                    IN1 : T;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    if let [g, in0, in1] = params {
+                        // both values are evaluated and converted to T by the resolver's hints,
+                        // so no branches are needed
+                        let llvm = generator.llvm;
+                        let g = generator.generate_expression(g)?.into_int_value();
+                        let in0 = generator.generate_expression(in0)?;
+                        let in1 = generator.generate_expression(in1)?;
+                        if in0.get_type() != in1.get_type() {
+                            return Err(Diagnostic::codegen_error(
+                                "Expected IN0 and IN1 of the same type for SEL",
+                                location,
+                            ));
+                        }
+                        Ok(llvm.builder.build_select(
+                            expression_generator::to_i1(g, &llvm.builder),
+                            in1,
+                            in0,
+                            "",
+                        ))
+                    } else {
+                        Err(Diagnostic::codegen_error(
+                            "Expected exactly three parameters for SEL",
+                            location,
+                        ))
+                    }
+                }
+            },
+        ),
+        (
+            "CONCAT",
+            BuiltIn {
+                vendor: false,
+                annotation: Some(|annotator, operator, params| {
+                    annotator.annotate_concat_result("CONCAT", operator, params)
+                }),
+                decl: "FUNCTION CONCAT<T: ANY_STRING> : T
+                VAR_INPUT
+                    args : T...;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    if params.is_empty() {
+                        Err(Diagnostic::codegen_error(
+                            "Expected at least one parameter for CONCAT",
+                            location,
+                        ))
+                    } else {
+                        generator
+                            .generate_string_concatenation(params)
+                            .map(|it| it.as_basic_value_enum())
+                    }
+                }
+            },
+        ),
+        // the checksums of a byte buffer given by its address (e.g. ADR(frame)) and its length
+        (
+            "CRC16",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION CRC16 : WORD
+                VAR_INPUT
+                    data : LWORD;
+                    length : UDINT;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_checksum("CRC16", params, location)
+                }
+            },
+        ),
+        (
+            "CRC32",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION CRC32 : DWORD
+                VAR_INPUT
+                    data : LWORD;
+                    length : UDINT;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_checksum("CRC32", params, location)
+                }
+            },
+        ),
+        (
+            "CHECKSUM_SUM",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION CHECKSUM_SUM : BYTE
+                VAR_INPUT
+                    data : LWORD;
+                    length : UDINT;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_checksum("CHECKSUM_SUM", params, location)
+                }
+            },
+        ),
+        (
+            "CHECKSUM_XOR",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION CHECKSUM_XOR : BYTE
+                VAR_INPUT
+                    data : LWORD;
+                    length : UDINT;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_checksum("CHECKSUM_XOR", params, location)
+                }
+            },
+        ),
+        // copies between variables, strings and byte arrays without accessing them beyond their end
+        (
+            "MEM_COPY",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION MEM_COPY<T: ANY, U: ANY> : UDINT
+                VAR_INPUT
+                    destination : T;
+                    source : U;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_memory_copy(params, location)
+                }
+            },
+        ),
+        (
+            "STRING_TO_BYTES",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION STRING_TO_BYTES<T: ANY> : UDINT
+                VAR_INPUT
+                    in : STRING;
+                    bytes : T;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_string_to_bytes(params, location)
+                }
+            },
+        ),
+        (
+            "BYTES_TO_STRING",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION BYTES_TO_STRING<T: ANY> : STRING
+                VAR_INPUT
+                    in : T;
+                    length : UDINT;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_bytes_to_string(params, location)
+                }
+            },
+        ),
+        // the memory functions of SysMem-like libraries, the variables are passed directly or by
+        // their address (e.g. ADR(frame))
+        (
+            "LOWER_BOUND",
+            BuiltIn {
+                vendor: false,
+                annotation: None,
+                decl: "FUNCTION LOWER_BOUND<T: ANY> : DINT
+                VAR_INPUT
+                    arr : T;
+                    dim : DINT;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_array_bound("LOWER_BOUND", params, location)
+                }
+            },
+        ),
+        (
+            "UPPER_BOUND",
+            BuiltIn {
+                vendor: false,
+                annotation: None,
+                decl: "FUNCTION UPPER_BOUND<T: ANY> : DINT
+                VAR_INPUT
+                    arr : T;
+                    dim : DINT;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_array_bound("UPPER_BOUND", params, location)
+                }
+            },
+        ),
+        (
+            "MEMCPY",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION MEMCPY<T: ANY, U: ANY> : UDINT
+                VAR_INPUT
+                    destination : T;
+                    source : U;
+                    size : UDINT;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_memcpy(params, location)
+                }
+            },
+        ),
+        (
+            "MEMSET",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION MEMSET<T: ANY> : UDINT
+                VAR_INPUT
+                    destination : T;
+                    value : BYTE;
+                    size : UDINT;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_memset(params, location)
+                }
+            },
+        ),
+        (
+            "MEMCMP",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION MEMCMP<T: ANY, U: ANY> : DINT
+                VAR_INPUT
+                    left : T;
+                    right : U;
+                    size : UDINT;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_memcmp(params, location)
+                }
+            },
+        ),
+        // array and matrix arithmetic generated as loops LLVM may vectorize
+        (
+            "ARRAY_ADD",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION ARRAY_ADD<T: ANY, U: ANY, V: ANY> : UDINT
+                VAR_INPUT
+                    destination : T;
+                    left : U;
+                    right : V;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_array_operation("ARRAY_ADD", params, location)
+                }
+            },
+        ),
+        (
+            "ARRAY_MUL",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION ARRAY_MUL<T: ANY, U: ANY, V: ANY> : UDINT
+                VAR_INPUT
+                    destination : T;
+                    left : U;
+                    right : V;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_array_operation("ARRAY_MUL", params, location)
+                }
+            },
+        ),
+        (
+            "ARRAY_DOT",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION ARRAY_DOT<T: ANY, U: ANY> : LREAL
+                VAR_INPUT
+                    left : T;
+                    right : U;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_array_dot(params, location)
+                }
+            },
+        ),
+        (
+            "MATRIX_MUL",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION MATRIX_MUL<T: ANY, U: ANY, V: ANY> : UDINT
+                VAR_INPUT
+                    destination : T;
+                    left : U;
+                    right : V;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_matrix_multiplication(params, location)
+                }
+            },
+        ),
+        (
+            "TRANSPOSE",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION TRANSPOSE<T: ANY, U: ANY> : UDINT
+                VAR_INPUT
+                    destination : T;
+                    source : U;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_transpose(params, location)
+                }
+            },
+        ),
+        // integer arithmetic clamped to the range of the type instead of wrapping around
+        (
+            "ADD_SAT",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION ADD_SAT<T: ANY_INT> : T
+                VAR_INPUT
+                    in1 : T;
+                    in2 : T;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_saturating_arithmetic("ADD_SAT", params, location)
+                }
+            },
+        ),
+        (
+            "SUB_SAT",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION SUB_SAT<T: ANY_INT> : T
+                VAR_INPUT
+                    in1 : T;
+                    in2 : T;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_saturating_arithmetic("SUB_SAT", params, location)
+                }
+            },
+        ),
+        (
+            "MUL_SAT",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION MUL_SAT<T: ANY_INT> : T
+                VAR_INPUT
+                    in1 : T;
+                    in2 : T;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_saturating_arithmetic("MUL_SAT", params, location)
+                }
+            },
+        ),
+        // fixed-point numbers are converted into FIXED implicitly, these convert them back
+        (
+            "FIXED_TO_REAL",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION FIXED_TO_REAL<T: ANY> : REAL
+                VAR_INPUT
+                    in : T;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_fixed_point_conversion(
+                        "FIXED_TO_REAL",
+                        REAL_TYPE,
+                        params,
+                        location,
+                    )
+                }
+            },
+        ),
+        (
+            "FIXED_TO_LREAL",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION FIXED_TO_LREAL<T: ANY> : LREAL
+                VAR_INPUT
+                    in : T;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_fixed_point_conversion(
+                        "FIXED_TO_LREAL",
+                        LREAL_TYPE,
+                        params,
+                        location,
+                    )
+                }
+            },
+        ),
+        (
+            "FIXED_TO_DINT",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION FIXED_TO_DINT<T: ANY> : DINT
+                VAR_INPUT
+                    in : T;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_fixed_point_conversion(
+                        "FIXED_TO_DINT",
+                        DINT_TYPE,
+                        params,
+                        location,
+                    )
+                }
+            },
+        ),
+        // random numbers and GUIDs, e.g. for test stimuli and message ids
+        (
+            "RAND",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION RAND : LREAL
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_random("RAND", params, location)
+                }
+            },
+        ),
+        (
+            "SRAND",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION SRAND
+                VAR_INPUT
+                    seed : ULINT;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_random("SRAND", params, location)
+                }
+            },
+        ),
+        (
+            "GUID",
+            BuiltIn {
+                vendor: true,
+                annotation: None,
+                decl: "FUNCTION GUID : STRING
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_random("GUID", params, location)
+                }
+            },
+        ),
+        // the compile-time queries of the symbols defined on the command line (see `defines`)
+        (
+            "__DEFINED",
+            BuiltIn {
+                vendor: false,
+                annotation: None,
+                decl: "FUNCTION __DEFINED : BOOL
+                VAR_INPUT
+                    name : STRING;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_define_query("__DEFINED", params, location)
+                }
+            },
+        ),
+        (
+            "__OPTION",
+            BuiltIn {
+                vendor: false,
+                annotation: None,
+                decl: "FUNCTION __OPTION : STRING
+                VAR_INPUT
+                    name : STRING;
+                END_VAR
+                END_FUNCTION
+                ",
+                code: |generator, params, location| {
+                    generator.generate_define_query("__OPTION", params, location)
+                }
+            },
         )
     ]);
+
+    // the conversions between numbers and strings (e.g. INT_TO_STRING, STRING_TO_REAL, DT_TO_STRING)
+    static ref STRING_CONVERSIONS: HashMap<&'static str, BuiltIn> = string_conversions!(
+        "SINT", "USINT", "INT", "UINT", "DINT", "UDINT", "LINT", "ULINT", "BYTE", "WORD", "DWORD",
+        "LWORD", "REAL", "LREAL", "DT", "TIME"
+    );
+
+    // the registered builtins by their upper case name, initially the compiler's builtins. The
+    // builtins are declared in the order of their names, so the ids of their AST are reproducible
+    static ref REGISTRY: RwLock<BTreeMap<String, Registration>> = RwLock::new(
+        BUILTIN
+            .iter()
+            .chain(STRING_CONVERSIONS.iter())
+            .map(|(name, builtin)| {
+                let registration = Registration {
+                    provider: STANDARD_PROVIDER.into(),
+                    decl: builtin.decl,
+                    builtin: Some(builtin),
+                    vendor: builtin.vendor,
+                };
+                (name.to_string(), registration)
+            })
+            .collect()
+    );
+
+    // the upper case names of the runtime library's POUs, none of them is defined by IEC 61131-3
+    static ref RUNTIME_LIBRARY_POUS: Vec<String> = parse_runtime_library(IdProvider::default())
+        .units
+        .iter()
+        .map(|it| it.name.to_uppercase())
+        .collect();
+}
+
+/// a registered builtin and the name of its provider
+struct Registration {
+    provider: String,
+    decl: &'static str,
+    /// generates the calls of the builtin in place, a builtin without is bound to the external
+    /// function or function block of the same name
+    builtin: Option<&'static BuiltIn>,
+    /// the builtin is not defined by IEC 61131-3, the builtins of embedders never are
+    vendor: bool,
 }
 
+/// the function blocks implemented by the runtime of hosted targets, they act on a rising edge of
+/// `execute` and are not available on freestanding targets. `__rusty_time` is the clock measuring
+/// the elapsed times of the steps of sequential function charts, `RAISE_EVENT` releases the tasks
+/// executed by the given external event
+const RUNTIME_LIBRARY: &str = "
+FUNCTION __rusty_time : TIME
+END_FUNCTION
+
+FUNCTION RAISE_EVENT
+VAR_INPUT
+    event : DINT;
+END_VAR
+END_FUNCTION
+
+TYPE FILE_MODE : (FILE_MODE_READ := 0, FILE_MODE_WRITE := 1, FILE_MODE_APPEND := 2); END_TYPE
+
+FUNCTION_BLOCK FILE_OPEN
+VAR_INPUT
+    execute : BOOL;
+    path : STRING[255];
+    mode : FILE_MODE;
+END_VAR
+VAR_OUTPUT
+    done : BOOL;
+    error : BOOL;
+    error_id : DINT;
+    handle : DINT;
+END_VAR
+VAR
+    edge : BOOL;
+END_VAR
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK FILE_READ
+VAR_INPUT
+    execute : BOOL;
+    handle : DINT;
+    buffer : LWORD;
+    size : UDINT;
+END_VAR
+VAR_OUTPUT
+    done : BOOL;
+    error : BOOL;
+    error_id : DINT;
+    count : UDINT;
+END_VAR
+VAR
+    edge : BOOL;
+END_VAR
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK FILE_WRITE
+VAR_INPUT
+    execute : BOOL;
+    handle : DINT;
+    buffer : LWORD;
+    size : UDINT;
+END_VAR
+VAR_OUTPUT
+    done : BOOL;
+    error : BOOL;
+    error_id : DINT;
+    count : UDINT;
+END_VAR
+VAR
+    edge : BOOL;
+END_VAR
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK FILE_CLOSE
+VAR_INPUT
+    execute : BOOL;
+    handle : DINT;
+END_VAR
+VAR_OUTPUT
+    done : BOOL;
+    error : BOOL;
+    error_id : DINT;
+END_VAR
+VAR
+    edge : BOOL;
+END_VAR
+END_FUNCTION_BLOCK
+";
+
+/// generates the code of a call to a builtin from the call's parameters
+pub type CodegenCallback = for<'ink, 'b> fn(
+    &'b ExpressionCodeGenerator<'ink, 'b>,
+    &[&AstStatement],
+    SourceRange,
+) -> Result<BasicValueEnum<'ink>, Diagnostic>;
+
+/// annotates a call to a builtin whose result cannot be declared by its signature (e.g. the
+/// length of the string returned by `CONCAT`) from the call's operator and parameters
+pub(crate) type AnnotationCallback =
+    for<'i> fn(&mut TypeAnnotator<'i>, &AstStatement, &[&AstStatement]);
+
 pub struct BuiltIn {
+    /// the builtin is not defined by IEC 61131-3 (e.g. `ADR`), calling it is reported in dialects
+    /// that do not accept vendor builtins
+    vendor: bool,
+    /// annotates the result of a call instead of the return type of the signature
+    annotation: Option<AnnotationCallback>,
     decl: &'static str,
-    code: for<'ink, 'b> fn(
-        &'b ExpressionCodeGenerator<'ink, 'b>,
-        &[&AstStatement],
-        SourceRange,
-    ) -> Result<BasicValueEnum<'ink>, Diagnostic>,
+    code: CodegenCallback,
 }
 
 impl BuiltIn {
@@ -81,18 +974,148 @@ impl BuiltIn {
     ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
         (self.code)(generator, params, location)
     }
+
+    /// returns the callback annotating the calls of this builtin if its result depends on the
+    /// call (e.g. `CONCAT`)
+    pub(crate) fn get_annotation(&self) -> Option<AnnotationCallback> {
+        self.annotation
+    }
 }
 
-pub fn parse_built_ins(id_provider: IdProvider) -> CompilationUnit {
-    let src = BUILTIN
+/// registers a builtin function of the given provider whose calls are generated by the given
+/// callback, `decl` is the function's declaration (e.g. `FUNCTION ACME_TICKS : LINT END_FUNCTION`).
+/// Registered builtins are kept until the process exits
+pub fn register_builtin(
+    provider: &str,
+    name: &str,
+    decl: &'static str,
+    code: CodegenCallback,
+) -> Result<(), Diagnostic> {
+    let builtin: &'static BuiltIn = Box::leak(Box::new(BuiltIn {
+        vendor: true,
+        annotation: None,
+        decl,
+        code,
+    }));
+    register(provider, name, decl, Some(builtin))
+}
+
+/// registers a builtin function or function block of the given provider that is called like the
+/// external POU of the same name, `decl` is its declaration. The runtime has to provide the POU
+pub fn register_extern_builtin(
+    provider: &str,
+    name: &str,
+    decl: &'static str,
+) -> Result<(), Diagnostic> {
+    register(provider, name, decl, None)
+}
+
+fn register(
+    provider: &str,
+    name: &str,
+    decl: &'static str,
+    builtin: Option<&'static BuiltIn>,
+) -> Result<(), Diagnostic> {
+    //the declaration has to declare exactly the named POU, builtins generated in place are
+    //functions
+    let (unit, diagnostics) = parser::parse(
+        lexer::lex_with_ids(decl, IdProvider::default()),
+        LinkageType::External,
+    );
+    let assessor = DefaultDiagnosticAssessor::default();
+    let reason = if let Some(error) = diagnostics
+        .iter()
+        .find(|it| matches!(assessor.assess((*it).clone()).severity, Severity::Error))
+    {
+        Some(error.get_message().to_string())
+    } else if !matches!(unit.units.as_slice(), [pou] if pou.name.eq_ignore_ascii_case(name)) {
+        Some(format!("it does not declare the POU '{}' only", name))
+    } else if builtin.is_some() && unit.units[0].pou_type != PouType::Function {
+        Some("builtins generated by a callback have to be functions".into())
+    } else {
+        None
+    };
+    if let Some(reason) = reason {
+        return Err(Diagnostic::invalid_builtin_declaration(name, &reason));
+    }
+
+    let mut registry = REGISTRY.write().unwrap_or_else(|it| it.into_inner());
+    let key = name.to_uppercase();
+    if let Some(existing) = registry.get(&key) {
+        return Err(Diagnostic::duplicate_builtin(name, &existing.provider));
+    }
+    let registration = Registration {
+        provider: provider.into(),
+        decl,
+        builtin,
+        vendor: true,
+        annotation: None,
+    };
+    registry.insert(key, registration);
+    Ok(())
+}
+
+/// returns the sorted upper case names of the builtins of the given provider
+pub fn get_builtin_names(provider: &str) -> Vec<String> {
+    let registry = REGISTRY.read().unwrap_or_else(|it| it.into_inner());
+    registry
         .iter()
-        .map(|(_, it)| it.decl)
-        .collect::<Vec<&str>>()
-        .join(" ");
-    parser::parse(lexer::lex_with_ids(&src, id_provider), LinkageType::BuiltIn).0
+        .filter(|(_, it)| it.provider == provider)
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// parses the declarations of the registered builtins in the order of their names, the builtins
+/// bound to external POUs are declared as external
+pub fn parse_built_ins(id_provider: IdProvider) -> CompilationUnit {
+    let registry = REGISTRY.read().unwrap_or_else(|it| it.into_inner());
+    let declarations = |is_extern: bool| {
+        registry
+            .values()
+            .filter(|it| it.builtin.is_none() == is_extern)
+            .map(|it| it.decl)
+            .collect::<Vec<&str>>()
+            .join(" ")
+    };
+    let mut unit = parser::parse(
+        lexer::lex_with_ids(&declarations(false), id_provider.clone()),
+        LinkageType::BuiltIn,
+    )
+    .0;
+    let (mut extern_unit, _) = parser::parse(
+        lexer::lex_with_ids(&declarations(true), id_provider.clone()),
+        LinkageType::External,
+    );
+    ast::pre_process(&mut extern_unit, id_provider);
+    unit.import(extern_unit);
+    unit
+}
+
+/// parses the declarations of the function blocks implemented by the runtime (e.g. `FILE_OPEN`),
+/// including the communication function blocks if the `communication` feature is enabled
+pub fn parse_runtime_library(id_provider: IdProvider) -> CompilationUnit {
+    #[allow(unused_mut)]
+    let mut library = RUNTIME_LIBRARY.to_string();
+    #[cfg(feature = "communication")]
+    library.push_str(&rusty_runtime::communication::get_declarations());
+    let (mut unit, _) = parser::parse(
+        lexer::lex_with_ids(&library, id_provider.clone()),
+        LinkageType::External,
+    );
+    ast::pre_process(&mut unit, id_provider);
+    unit
 }
 
-/// Returns the requested functio from the builtin index or None
+/// Returns the requested function from the builtin registry if it is generated in place
 pub fn get_builtin(name: &str) -> Option<&'static BuiltIn> {
-    BUILTIN.get(name.to_uppercase().as_str())
+    let registry = REGISTRY.read().unwrap_or_else(|it| it.into_inner());
+    registry.get(&name.to_uppercase()).and_then(|it| it.builtin)
+}
+
+/// returns true if the builtin or the POU of the runtime library with the given name is not
+/// defined by IEC 61131-3 (e.g. `ADR` or `FILE_OPEN`)
+pub fn is_vendor_extension(name: &str) -> bool {
+    let name = name.to_uppercase();
+    let registry = REGISTRY.read().unwrap_or_else(|it| it.into_inner());
+    registry.get(&name).map_or(false, |it| it.vendor) || RUNTIME_LIBRARY_POUS.contains(&name)
 }
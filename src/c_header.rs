@@ -0,0 +1,466 @@
+//! generates a C header declaring the instance structs, the program instances and the signatures of
+//! the exported POUs, so C code can call them and link against the generated object. The structs
+//! mirror the llvm types generated for the POUs, the header checks them against the layouts
+//! calculated for the compilation's target
+
+use std::{collections::HashMap, fmt::Write, path::Path};
+
+use crate::{
+    abi_metadata::AbiMetadata,
+    index::{self, Index, PouIndexEntry, VariableIndexEntry},
+    typesystem::{DataTypeInformation, StringEncoding},
+};
+
+const C_KEYWORDS: &[&str] = &[
+    "auto", "bool", "break", "case", "char", "const", "continue", "default", "do", "double",
+    "else", "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
+    "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef",
+    "union", "unsigned", "void", "volatile", "while",
+];
+
+/// returns the include guard of the given header, derived from its file name (`motor.h` becomes
+/// `MOTOR_H`)
+pub fn get_include_guard(header: &str) -> String {
+    let guard: String = Path::new(header)
+        .file_name()
+        .map(|it| it.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .map(|it| {
+            if it.is_ascii_alphanumeric() {
+                it.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if guard.starts_with(|it: char| it.is_ascii_digit()) {
+        format!("_{}", guard)
+    } else {
+        guard
+    }
+}
+
+/// generates the header of the application described by the given metadata, POUs that cannot be
+/// called from C (e.g. functions passing a STRING by value) are listed in a comment
+pub fn generate_header(index: &Index, metadata: &AbiMetadata, guard: &str) -> String {
+    let mut generator = HeaderGenerator {
+        index,
+        forward_declarations: String::new(),
+        definitions: String::new(),
+        structs: HashMap::new(),
+    };
+    let mut declarations = String::new();
+    for export in &metadata.exports {
+        match generator.declare_pou(export) {
+            Ok(Some(declaration)) => declarations.push_str(&declaration),
+            Ok(None) => {}
+            Err(reason) => {
+                let _ = writeln!(
+                    declarations,
+                    "/* {} cannot be called from C, {} */",
+                    export, reason
+                );
+            }
+        }
+    }
+
+    //the structs are only generated for the target the header was generated for
+    let mut assertions = String::new();
+    for layout in &metadata.layouts {
+        if let Some(c_name) = generator.structs.get(&layout.name.to_lowercase()) {
+            let _ = writeln!(
+                assertions,
+                "_Static_assert(sizeof({}) == {}, \"unexpected size of {}\");",
+                c_name, layout.size, layout.name
+            );
+            for member in &layout.members {
+                let _ = writeln!(
+                    assertions,
+                    "_Static_assert(offsetof({}, {}) == {}, \"unexpected offset of {}.{}\");",
+                    c_name,
+                    get_identifier(&member.name),
+                    member.offset,
+                    layout.name,
+                    member.name
+                );
+            }
+        }
+    }
+
+    format!(
+        "/* generated by rusty {} for {} */\n\
+        #ifndef {guard}\n\
+        #define {guard}\n\n\
+        #include <stdbool.h>\n\
+        #include <stddef.h>\n\
+        #include <stdint.h>\n\n\
+        #ifdef __cplusplus\n\
+        extern \"C\" {{\n\
+        #endif\n\n\
+        {}\n\
+        {}\
+        {}\
+        #ifndef __cplusplus\n\
+        {}\
+        #endif\n\n\
+        #ifdef __cplusplus\n\
+        }}\n\
+        #endif\n\n\
+        #endif /* {guard} */\n",
+        env!("CARGO_PKG_VERSION"),
+        metadata.target,
+        generator.forward_declarations,
+        generator.definitions,
+        declarations,
+        assertions,
+        guard = guard
+    )
+}
+
+/// appends an underscore to names reserved by C
+fn get_identifier(name: &str) -> String {
+    if C_KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+struct HeaderGenerator<'idx> {
+    index: &'idx Index,
+    /// the typedefs of all declared structs, allowing the structs to point at each other
+    forward_declarations: String,
+    /// the struct definitions, every struct follows the structs it contains
+    definitions: String,
+    /// the C names of the declared structs by their lowercase type name
+    structs: HashMap<String, String>,
+}
+
+impl<'idx> HeaderGenerator<'idx> {
+    /// declares the given POU's instance and signature, returns `None` for POUs that are not
+    /// called by their name (e.g. methods and actions)
+    fn declare_pou(&mut self, name: &str) -> Result<Option<String>, String> {
+        let pou = match self.index.find_pou(name) {
+            Some(pou) if !name.contains('.') => pou,
+            _ => return Ok(None),
+        };
+        let declaration = match pou {
+            PouIndexEntry::Program { .. } => {
+                let instance = self.declare_struct(name)?;
+                format!(
+                    "extern {} {}_instance;\nvoid {}({} *instance);\n\n",
+                    instance, name, name, instance
+                )
+            }
+            PouIndexEntry::FunctionBlock { .. } => {
+                let instance = self.declare_struct(name)?;
+                format!(
+                    "/* the initial values of a {} instance */\n\
+                    extern const {} {};\n\
+                    void {}({} *instance);\n\n",
+                    name,
+                    instance,
+                    index::get_initializer_name(name),
+                    name,
+                    instance
+                )
+            }
+            PouIndexEntry::Class { .. } => {
+                self.declare_struct(name)?;
+                return Ok(None);
+            }
+            PouIndexEntry::Function { .. } => self.declare_function(name)?,
+            _ => return Ok(None),
+        };
+        Ok(Some(declaration))
+    }
+
+    /// declares the function's prototype, the parameters and the return value are passed by value
+    /// and must therefore be scalars (or pointers) to be passed the same way by C
+    fn declare_function(&mut self, name: &str) -> Result<String, String> {
+        if self
+            .index
+            .find_effective_type_info(name)
+            .map_or(false, |it| it.is_variadic())
+        {
+            return Err("it takes a variable number of arguments".to_string());
+        }
+        let mut parameters = self
+            .index
+            .get_container_members(name)
+            .into_iter()
+            .filter(|it| it.is_parameter())
+            .collect::<Vec<_>>();
+        parameters.sort_by_key(|it| it.get_location_in_parent());
+        let parameters = parameters
+            .into_iter()
+            .map(|it| self.declare_scalar(it.get_type_name(), &get_identifier(it.get_name())))
+            .collect::<Result<Vec<_>, _>>()?;
+        let parameters = if parameters.is_empty() {
+            "void".to_string()
+        } else {
+            parameters.join(", ")
+        };
+        let declarator = format!("{}({})", name, parameters);
+        let prototype = match self.index.find_return_variable(name) {
+            Some(variable) => self.declare_return(variable, &declarator)?,
+            None => format!("void {}", declarator),
+        };
+        Ok(format!("{};\n\n", prototype))
+    }
+
+    fn declare_return(
+        &mut self,
+        variable: &VariableIndexEntry,
+        declarator: &str,
+    ) -> Result<String, String> {
+        self.declare_scalar(variable.get_type_name(), declarator)
+            .map_err(|_| format!("it returns {} by value", variable.get_type_name()))
+    }
+
+    fn declare_scalar(&mut self, type_name: &str, declarator: &str) -> Result<String, String> {
+        match self.index.find_effective_type_info(type_name) {
+            Some(
+                DataTypeInformation::Struct { .. }
+                | DataTypeInformation::Array { .. }
+                | DataTypeInformation::String { .. },
+            ) => Err(format!("it passes {} by value", type_name)),
+            _ => self.declare(type_name, declarator),
+        }
+    }
+
+    /// returns the C declaration of `declarator` with the given type, e.g. `int16_t values[3]`
+    fn declare(&mut self, type_name: &str, declarator: &str) -> Result<String, String> {
+        let data_type = self
+            .index
+            .find_effective_type(type_name)
+            .ok_or_else(|| format!("its type {} is unknown", type_name))?;
+        let information = data_type.get_type_information();
+        let c_type = match information {
+            DataTypeInformation::Struct { .. } => self.declare_struct(data_type.get_name())?,
+            DataTypeInformation::Array {
+                inner_type_name,
+                dimensions,
+                ..
+            } => {
+                let mut declarator = declarator.to_string();
+                for dimension in dimensions {
+                    let length = dimension.get_length(self.index)?;
+                    let _ = write!(declarator, "[{}]", length);
+                }
+                return self.declare(inner_type_name, &declarator);
+            }
+            DataTypeInformation::String { size, encoding } => {
+                let length = size.as_int_value(self.index)?;
+                let character = if *encoding == StringEncoding::Utf8 {
+                    "char"
+                } else {
+                    "uint16_t"
+                };
+                return Ok(format!("{} {}[{}]", character, declarator, length));
+            }
+            DataTypeInformation::Pointer {
+                inner_type_name, ..
+            } => {
+                //a pointer to an array binds weaker than the array's dimensions
+                let points_to_array = matches!(
+                    self.index.find_effective_type_info(inner_type_name),
+                    Some(DataTypeInformation::Array { .. } | DataTypeInformation::String { .. })
+                );
+                let declarator = if points_to_array {
+                    format!("(*{})", declarator)
+                } else {
+                    format!("*{}", declarator)
+                };
+                return self.declare(inner_type_name, &declarator);
+            }
+            DataTypeInformation::Enum {
+                referenced_type, ..
+            }
+            | DataTypeInformation::SubRange {
+                referenced_type, ..
+            } => return self.declare(referenced_type, declarator),
+            _ if information.is_bool() => "bool".to_string(),
+            DataTypeInformation::Integer { signed, size, .. } => get_integer_type(*signed, *size)?,
+            DataTypeInformation::FixedPoint { size, .. } => get_integer_type(true, *size)?,
+            DataTypeInformation::Float { size, .. } => {
+                if *size == 32 { "float" } else { "double" }.to_string()
+            }
+            DataTypeInformation::Void => "void".to_string(),
+            DataTypeInformation::Alias { .. } | DataTypeInformation::Generic { .. } => {
+                return Err(format!("its type {} cannot be declared in C", type_name))
+            }
+        };
+        Ok(format!("{} {}", c_type, declarator))
+    }
+
+    /// defines the struct with the given name (and the structs it contains) unless it was already
+    /// defined and returns its C name
+    fn declare_struct(&mut self, type_name: &str) -> Result<String, String> {
+        let key = type_name.to_lowercase();
+        if let Some(c_name) = self.structs.get(&key) {
+            return Ok(c_name.clone());
+        }
+        let data_type = self
+            .index
+            .find_type(type_name)
+            .ok_or_else(|| format!("its type {} is unknown", type_name))?;
+        //instance structs are named after the llvm struct (`<pou>_interface`)
+        let c_name = data_type.get_type_information().get_name().to_string();
+        let _ = writeln!(
+            self.forward_declarations,
+            "typedef struct {} {};",
+            c_name, c_name
+        );
+        //register the struct before its members, members may point at their container
+        self.structs.insert(key, c_name.clone());
+
+        let members = self
+            .index
+            .get_container_members(data_type.get_name())
+            .into_iter()
+            .filter(|it| !it.is_temp() && !it.is_return())
+            .map(|it| {
+                self.declare(it.get_type_name(), &get_identifier(it.get_name()))
+                    .map(|member| format!("    {};\n", member))
+            })
+            .collect::<Result<String, _>>()?;
+        let _ = writeln!(self.definitions, "struct {} {{\n{}}};\n", c_name, members);
+        Ok(c_name)
+    }
+}
+
+fn get_integer_type(signed: bool, size: u32) -> Result<String, String> {
+    match size {
+        8 | 16 | 32 | 64 => Ok(format!("{}int{}_t", if signed { "" } else { "u" }, size)),
+        _ => Err(format!("integers of {} bits cannot be declared in C", size)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_header, get_include_guard};
+    use crate::{
+        abi_metadata::{AbiMetadata, InstanceLayout, MemberLayout},
+        dialect::Dialect,
+        test_utils::tests::index,
+    };
+
+    fn metadata(exports: &[&str]) -> AbiMetadata {
+        AbiMetadata::new(
+            Dialect::default(),
+            "x86_64-unknown-linux-gnu",
+            0,
+            exports.iter().map(|it| it.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn include_guards_are_derived_from_the_file_name() {
+        assert_eq!(get_include_guard("include/motor.h"), "MOTOR_H");
+        assert_eq!(get_include_guard("2nd-axis.h"), "_2ND_AXIS_H");
+    }
+
+    #[test]
+    fn programs_and_function_blocks_are_declared_with_their_instance_structs() {
+        let (_, index) = index(
+            "
+            FUNCTION_BLOCK Motor
+            VAR_INPUT speed : INT; END_VAR
+            VAR_IN_OUT limit : INT; END_VAR
+            VAR_TEMP scratch : INT; END_VAR
+            END_FUNCTION_BLOCK
+
+            PROGRAM mainProg
+            VAR
+                drive : Motor;
+                enabled : BOOL;
+                name : STRING[10];
+                positions : ARRAY[1..3] OF LREAL;
+            END_VAR
+            END_PROGRAM
+            ",
+        );
+
+        let header = generate_header(&index, &metadata(&["Motor", "mainProg"]), "APP_H");
+
+        assert!(header.contains("#ifndef APP_H\n#define APP_H\n"));
+        assert!(header.contains("typedef struct Motor_interface Motor_interface;"));
+        assert!(header
+            .contains("struct Motor_interface {\n    int16_t speed;\n    int16_t *limit;\n};"));
+        assert!(header.contains(
+            "struct mainProg_interface {\n    Motor_interface drive;\n    bool enabled;\n    \
+            char name[11];\n    double positions[3];\n};"
+        ));
+        //the contained struct is defined first
+        assert!(
+            header.find("struct Motor_interface {").unwrap()
+                < header.find("struct mainProg_interface {").unwrap()
+        );
+        assert!(header.contains("extern const Motor_interface Motor__init;"));
+        assert!(header.contains("void Motor(Motor_interface *instance);"));
+        assert!(header.contains("extern mainProg_interface mainProg_instance;"));
+        assert!(header.contains("void mainProg(mainProg_interface *instance);"));
+    }
+
+    #[test]
+    fn functions_are_declared_if_c_passes_their_arguments_the_same_way() {
+        let (_, index) = index(
+            "
+            FUNCTION scale : REAL
+            VAR_INPUT value : DINT; factor : REAL; END_VAR
+            VAR_IN_OUT count : UDINT; END_VAR
+            END_FUNCTION
+
+            FUNCTION reset
+            END_FUNCTION
+
+            FUNCTION greet : STRING
+            VAR_INPUT name : STRING; END_VAR
+            END_FUNCTION
+            ",
+        );
+
+        let header = generate_header(&index, &metadata(&["greet", "reset", "scale"]), "APP_H");
+
+        assert!(header.contains("float scale(int32_t value, float factor, uint32_t *count);"));
+        assert!(header.contains("void reset(void);"));
+        assert!(header.contains("/* greet cannot be called from C, it passes STRING by value */"));
+    }
+
+    #[test]
+    fn the_layouts_of_the_target_are_asserted() {
+        let (_, index) = index(
+            "
+            PROGRAM mainProg
+            VAR double : INT; END_VAR
+            END_PROGRAM
+            ",
+        );
+        let metadata = metadata(&["mainProg"]).with_layouts(
+            vec![InstanceLayout {
+                name: "mainProg".to_string(),
+                size: 2,
+                members: vec![MemberLayout {
+                    name: "double".to_string(),
+                    type_name: "INT".to_string(),
+                    offset: 0,
+                    size: 2,
+                }],
+            }],
+            vec![],
+        );
+
+        let header = generate_header(&index, &metadata, "APP_H");
+
+        assert!(header.contains("    int16_t double_;\n"));
+        assert!(header.contains(
+            "_Static_assert(sizeof(mainProg_interface) == 2, \"unexpected size of mainProg\");"
+        ));
+        assert!(header.contains(
+            "_Static_assert(offsetof(mainProg_interface, double_) == 0, \
+            \"unexpected offset of mainProg.double\");"
+        ));
+    }
+}
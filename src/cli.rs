@@ -3,7 +3,11 @@ use clap::{ArgGroup, Parser};
 use encoding_rs::Encoding;
 use std::{ffi::OsStr, path::Path};
 
-use crate::{ConfigFormat, ErrorFormat, FormatOption};
+use crate::{
+    dialect::Dialect, documentation::DocFormat, formatter::KeywordCase, profile::Profile,
+    ConfigFormat, Conformance, ErrorFormat, FormatOption, OptimizationLevel, OutputType,
+    RuntimeCheck,
+};
 
 // => Set the default output format here:
 const DEFAULT_FORMAT: FormatOption = FormatOption::Static;
@@ -64,6 +68,14 @@ pub struct CompileParameters {
     )]
     pub output_bit_code: bool,
 
+    #[clap(
+        long = "output-type",
+        group = "format",
+        arg_enum,
+        help = "Emit an object file (obj), link a shared library (shared) or an executable (static)"
+    )]
+    pub output_type: Option<OutputType>,
+
     #[clap(short = 'c', help = "Do not link after compiling object code")]
     pub skip_linking: bool,
 
@@ -74,6 +86,20 @@ pub struct CompileParameters {
     )]
     pub target: Option<String>,
 
+    #[clap(
+        long,
+        name = "cpu",
+        help = "The CPU to generate code for (e.g. cortex-a53), generic if not set"
+    )]
+    pub cpu: Option<String>,
+
+    #[clap(
+        long = "target-features",
+        name = "target-features",
+        help = "The CPU features to enable (+) or disable (-), e.g. +neon,-fp16"
+    )]
+    pub target_features: Option<String>,
+
     #[clap(
         long,
         name = "encoding",
@@ -102,9 +128,96 @@ pub struct CompileParameters {
     #[clap(name = "library", long, short = 'l', help = "Library name to link")]
     pub libraries: Vec<String>,
 
+    #[clap(
+        name = "lib",
+        long = "lib",
+        help = "A library package (.plclib) to compile against and link, see `rustyc package`"
+    )]
+    pub library_packages: Vec<String>,
+
+    #[clap(
+        long = "lib-lock",
+        name = "lib-lock",
+        help = "Verify the library packages against the versions and POU signatures recorded in <lib-lock>, libraries not recorded yet are recorded"
+    )]
+    pub library_lock: Option<String>,
+
     #[clap(long, name = "sysroot", help = "Path to system root, used for linking")]
     pub sysroot: Option<String>,
 
+    #[clap(
+        long = "map",
+        name = "map-file",
+        help = "Write a map file listing the sections and symbols of the linked output with their addresses and sizes"
+    )]
+    pub map_file: Option<String>,
+
+    #[clap(
+        long,
+        name = "lto",
+        help = "Optimize the compiled units together while linking (link-time optimization), objects hold LLVM bitcode"
+    )]
+    pub lto: bool,
+
+    #[clap(
+        long,
+        name = "linker-script",
+        help = "Write a GNU ld script fragment declaring the sections of the generated code and variables"
+    )]
+    pub linker_script: Option<String>,
+
+    #[clap(
+        long,
+        help = "Write a C header declaring the instance structs and signatures of the compiled POUs"
+    )]
+    pub header: Option<String>,
+
+    #[clap(
+        long,
+        name = "wcet-annotations",
+        help = "Annotate the generated code for WCET analyzers and write the bounds of the loops annotated with {attribute 'loop_bound'} to <wcet-annotations> in AIS format"
+    )]
+    pub wcet_annotations: Option<String>,
+
+    #[clap(
+        long,
+        name = "init-order",
+        help = "Write the order in which the global variables and program instances are initialized to <init-order>"
+    )]
+    pub init_order: Option<String>,
+
+    #[clap(
+        long,
+        name = "max-code-size",
+        help = "Fail the build if the generated code exceeds the given number of bytes (e.g. 65536, 64K or 1M)",
+        parse(try_from_str = parse_size)
+    )]
+    pub max_code_size: Option<u64>,
+
+    #[clap(
+        long,
+        name = "max-ram",
+        help = "Fail the build if the global variables and program instances exceed the given number of bytes (e.g. 65536, 64K or 1M)",
+        parse(try_from_str = parse_size)
+    )]
+    pub max_ram: Option<u64>,
+
+    #[clap(
+        long,
+        name = "profile",
+        help = "Reject the constructs the given language profile does not allow, safety rejects pointers, dynamic memory, recursion, variadics and unbounded loops",
+        arg_enum
+    )]
+    pub profile: Option<Profile>,
+
+    #[clap(
+        long,
+        name = "profile-report",
+        requires = "profile",
+        help = "Write the conformance of the sources to the selected profile to <profile-report>"
+    )]
+    pub profile_report: Option<String>,
+
     #[clap(
         name = "include",
         long,
@@ -123,15 +236,103 @@ pub struct CompileParameters {
     ) ]
     pub hardware_config: Option<String>,
 
+    #[clap(
+        name = "task-conf",
+        long,
+        help = "Task configuration used to detect global variables shared between tasks and to generate the task table executed by the runtime.
+    Format is detected by extenstion.
+    Supported formats : json, toml",
+    parse(try_from_str = validate_config)
+    ) ]
+    pub task_config: Option<String>,
+
+    #[clap(
+        name = "config-const",
+        long,
+        help = "Values of the global variables declared in {attribute 'config_const'} blocks, replacing their initial values.
+    Format is detected by extenstion.
+    Supported formats : json, toml",
+    parse(try_from_str = validate_config)
+    ) ]
+    pub config_constants: Option<String>,
+
+    #[clap(
+        name = "define",
+        long,
+        short = 'D',
+        help = "Define a symbol queried by __DEFINED('NAME') and __OPTION('NAME'), given as NAME or NAME=VALUE"
+    )]
+    pub defines: Vec<String>,
+
+    #[clap(
+        long,
+        help = "Emit reflection tables describing the members of structs and POU instances"
+    )]
+    pub reflection: bool,
+
+    #[clap(
+        short = 'g',
+        long,
+        help = "Emit DWARF debug information, so the application can be debugged at the source level"
+    )]
+    pub debug: bool,
+
+    #[clap(
+        long = "llvm-arg",
+        name = "llvm-arg",
+        allow_hyphen_values = true,
+        number_of_values = 1,
+        help = "Pass an option to LLVM's code generation (e.g. --llvm-arg=-unroll-threshold=50), the options of the RUSTY_LLVM_ARGS environment variable are passed first"
+    )]
+    pub llvm_args: Vec<String>,
+
+    #[clap(
+        long,
+        help = "Keep calls in tail position from being replaced by jumps, so every call shows up in backtraces"
+    )]
+    pub disable_tail_calls: bool,
+
+    #[clap(
+        long = "ffast-math",
+        help = "Compute REAL and LREAL operations without strict IEEE semantics (reassociation, no NaNs or infinities, fused multiply-add)"
+    )]
+    pub fast_math: bool,
+
+    #[clap(
+        long = "check",
+        name = "check",
+        arg_enum,
+        number_of_values = 1,
+        help = "Generate the given runtime check into the application and may be repeated, bounds checks the indexes of array accesses and calls the application's CheckBounds function or traps for an index out of bounds"
+    )]
+    pub checks: Vec<RuntimeCheck>,
+
+    #[clap(
+        short = 'j',
+        long,
+        default_value = "1",
+        help = "Generate the POUs on the given number of threads, 0 uses every core"
+    )]
+    pub jobs: usize,
+
+    #[clap(
+        name = "project",
+        long,
+        help = "Project file selecting the ST dialect of the sources, `rustyc build` builds the whole project.
+    Format is detected by extenstion.
+    Supported formats : json, toml",
+    parse(try_from_str = validate_config)
+    ) ]
+    pub project: Option<String>,
+
     #[clap(
         name = "optimization",
         long,
         short = 'O',
-        help = "Optimization level",
-        arg_enum,
-        default_value = "default"
+        help = "Optimization level, also given as -O0 to -O3 or -Os. Defaults to default, IR (--ir) and bitcode (--bc) are not optimized by default",
+        arg_enum
     )]
-    pub optimization: crate::OptimizationLevel,
+    pub optimization: Option<crate::OptimizationLevel>,
 
     #[clap(
         name = "error-format",
@@ -140,13 +341,655 @@ pub struct CompileParameters {
         arg_enum,
         default_value = "rich"
     )]
-    pub error_format: ErrorFormat,
+    pub error_format: ErrorFormat,
+
+    #[clap(
+        name = "conformance",
+        long,
+        help = "Set the IEC 61131-3 conformance level, legacy reports violations tolerated by older code as warnings. Defaults to the level of the project's dialect (legacy without a dialect)",
+        arg_enum
+    )]
+    pub conformance: Option<Conformance>,
+}
+
+/// the parameters of `rusty migrate`, which converts vendor specific ST into portable IEC 61131-3 ST
+#[derive(Parser, Debug)]
+#[clap(
+    name = "migrate",
+    about = "Converts vendor specific constructs into portable IEC61131-3 Structured Text"
+)]
+pub struct MigrateParameters {
+    #[clap(
+        short,
+        long,
+        name = "output-dir",
+        help = "Write the migrated sources and the change report to <output-dir>",
+        default_value = "migrated"
+    )]
+    pub output: String,
+
+    #[clap(
+        long,
+        name = "dialect",
+        help = "The dialect the input-files are written in",
+        arg_enum,
+        default_value = "legacy"
+    )]
+    pub dialect: Dialect,
+
+    #[clap(
+        long,
+        name = "encoding",
+        help = "The file encoding used to read the input-files, as defined by the Encoding Standard",
+        parse(try_from_str = parse_encoding),
+    )]
+    pub encoding: Option<&'static Encoding>,
+
+    #[clap(
+        name = "input-files",
+        help = "Read input from <input-files>, may be a glob expression like 'src/**/*' or a sequence of files",
+        required = true,
+        min_values = 1
+    )]
+    pub input: Vec<String>,
+}
+
+impl MigrateParameters {
+    /// parses the arguments following the `migrate` command
+    pub fn parse(args: Vec<String>) -> Result<MigrateParameters, ParameterError> {
+        MigrateParameters::try_parse_from(args)
+    }
+}
+
+/// the parameters of `rusty format`, which prints ST sources in a canonical layout
+#[derive(Parser, Debug)]
+#[clap(
+    name = "format",
+    about = "Prints Structured Text sources in a canonical layout"
+)]
+pub struct FormatParameters {
+    #[clap(
+        long,
+        name = "indent",
+        help = "The number of spaces a nested declaration or statement is indented by",
+        default_value = "4"
+    )]
+    pub indent: usize,
+
+    #[clap(
+        long,
+        name = "keyword-case",
+        help = "The case the keywords are printed in",
+        arg_enum,
+        default_value = "upper"
+    )]
+    pub keyword_case: KeywordCase,
+
+    #[clap(
+        long,
+        name = "line-width",
+        help = "The width beyond which the arguments of a call are printed on lines of their own",
+        default_value = "100"
+    )]
+    pub line_width: usize,
+
+    #[clap(
+        long,
+        help = "Report the input-files that are not formatted instead of rewriting them"
+    )]
+    pub check: bool,
+
+    #[clap(
+        long,
+        name = "dialect",
+        help = "The dialect the input-files are written in",
+        arg_enum,
+        default_value = "legacy"
+    )]
+    pub dialect: Dialect,
+
+    #[clap(
+        long,
+        name = "encoding",
+        help = "The file encoding used to read the input-files, as defined by the Encoding Standard",
+        parse(try_from_str = parse_encoding),
+    )]
+    pub encoding: Option<&'static Encoding>,
+
+    #[clap(
+        name = "input-files",
+        help = "Read input from <input-files>, may be a glob expression like 'src/**/*' or a sequence of files",
+        required = true,
+        min_values = 1
+    )]
+    pub input: Vec<String>,
+}
+
+impl FormatParameters {
+    /// parses the arguments following the `format` command
+    pub fn parse(args: Vec<String>) -> Result<FormatParameters, ParameterError> {
+        FormatParameters::try_parse_from(args)
+    }
+}
+
+/// the parameters of `rusty doc`, which generates the API documentation of ST sources from their
+/// documentation comments
+#[derive(Parser, Debug)]
+#[clap(
+    name = "doc",
+    about = "Generates the API documentation of the POUs and data types from their (** *) and /// comments"
+)]
+pub struct DocParameters {
+    #[clap(
+        long,
+        name = "format",
+        help = "The format the documentation is written in",
+        arg_enum,
+        default_value = "markdown"
+    )]
+    pub format: DocFormat,
+
+    #[clap(
+        short,
+        long,
+        name = "output-dir",
+        help = "The directory the documentation (index.md or index.html) is written to",
+        default_value = "doc"
+    )]
+    pub output: String,
+
+    #[clap(
+        long,
+        name = "dialect",
+        help = "The dialect the input-files are written in",
+        arg_enum,
+        default_value = "legacy"
+    )]
+    pub dialect: Dialect,
+
+    #[clap(
+        long,
+        name = "encoding",
+        help = "The file encoding used to read the input-files, as defined by the Encoding Standard",
+        parse(try_from_str = parse_encoding),
+    )]
+    pub encoding: Option<&'static Encoding>,
+
+    #[clap(
+        name = "input-files",
+        help = "Read input from <input-files>, may be a glob expression like 'src/**/*' or a sequence of files",
+        required = true,
+        min_values = 1
+    )]
+    pub input: Vec<String>,
+}
+
+impl DocParameters {
+    /// parses the arguments following the `doc` command
+    pub fn parse(args: Vec<String>) -> Result<DocParameters, ParameterError> {
+        DocParameters::try_parse_from(args)
+    }
+}
+
+/// the parameters of `rusty package`, which compiles ST sources into a library package (`.plclib`)
+/// bundling the object with the declarations and the documentation of the sources
+#[derive(Parser, Debug)]
+#[clap(
+    name = "package",
+    about = "Compiles a library into a .plclib package holding its object, declarations and documentation"
+)]
+pub struct PackageParameters {
+    #[clap(
+        long,
+        name = "name",
+        help = "The name of the library, the name of the first input-file if omitted"
+    )]
+    pub name: Option<String>,
+
+    #[clap(
+        long = "library-version",
+        name = "library-version",
+        help = "The semantic version of the library (major.minor.patch)",
+        default_value = "0.1.0"
+    )]
+    pub version: crate::library_package::Version,
+
+    #[clap(
+        short,
+        long,
+        name = "output-file",
+        help = "Write the package to <output-file>, <name>.plclib if omitted"
+    )]
+    pub output: Option<String>,
+
+    #[clap(
+        long,
+        name = "target-triple",
+        help = "A target-tripple supported by LLVM"
+    )]
+    pub target: Option<String>,
+
+    #[clap(
+        long,
+        short = 'O',
+        help = "Optimization level, also given as -O0 to -O3 or -Os",
+        arg_enum,
+        default_value = "default"
+    )]
+    pub optimization: crate::OptimizationLevel,
+
+    #[clap(
+        name = "include",
+        long,
+        short = 'i',
+        help = "Include source files for external functions"
+    )]
+    pub includes: Vec<String>,
+
+    #[clap(
+        long,
+        name = "dialect",
+        help = "The dialect the input-files are written in",
+        arg_enum,
+        default_value = "legacy"
+    )]
+    pub dialect: Dialect,
+
+    #[clap(
+        long,
+        name = "encoding",
+        help = "The file encoding used to read the input-files, as defined by the Encoding Standard",
+        parse(try_from_str = parse_encoding),
+    )]
+    pub encoding: Option<&'static Encoding>,
+
+    #[clap(
+        name = "input-files",
+        help = "Read input from <input-files>, may be a glob expression like 'src/**/*' or a sequence of files",
+        required = true,
+        min_values = 1
+    )]
+    pub input: Vec<String>,
+}
+
+impl PackageParameters {
+    /// parses the arguments following the `package` command
+    pub fn parse(args: Vec<String>) -> Result<PackageParameters, ParameterError> {
+        PackageParameters::try_parse_from(args)
+    }
+
+    /// returns the name of the library, the name of the first input-file if not set
+    pub fn library_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            self.input
+                .first()
+                .and_then(|it| Path::new(it).file_stem())
+                .and_then(OsStr::to_str)
+                .unwrap_or(DEFAULT_OUTPUT_NAME)
+                .to_string()
+        })
+    }
+
+    /// returns the file the package is written to, `<name>.plclib` if not set
+    pub fn output_name(&self) -> String {
+        self.output.clone().unwrap_or_else(|| {
+            format!(
+                "{}.{}",
+                self.library_name(),
+                crate::library_package::EXTENSION
+            )
+        })
+    }
+}
+
+/// the parameters of `rusty inspect`, which prints the exported POUs, the instance layouts, the
+/// retained area and the ABI metadata embedded in a compiled artifact
+#[derive(Parser, Debug)]
+#[clap(
+    name = "inspect",
+    about = "Prints the exported POUs, instance layouts, retained area and ABI metadata of a compiled object, library or bitcode file"
+)]
+pub struct InspectParameters {
+    #[clap(name = "artifact", help = "The compiled artifact to inspect")]
+    pub input: String,
+}
+
+impl InspectParameters {
+    /// parses the arguments following the `inspect` command
+    pub fn parse(args: Vec<String>) -> Result<InspectParameters, ParameterError> {
+        InspectParameters::try_parse_from(args)
+    }
+}
+
+/// the parameters of `rusty symbolicate`, which translates the addresses of a crash into the POUs
+/// and source lines they belong to
+#[derive(Parser, Debug)]
+#[clap(
+    name = "symbolicate",
+    about = "Prints the POU, ST line and surrounding source of crash addresses for post-mortem analysis"
+)]
+pub struct SymbolicateParameters {
+    #[clap(
+        long = "map",
+        name = "map-file",
+        help = "The map file written when linking the application (see --map)"
+    )]
+    pub map_file: String,
+
+    #[clap(
+        long = "debug-info",
+        name = "binary",
+        help = "The application compiled with -g, its DWARF line table locates the addresses in the sources"
+    )]
+    pub debug_info: Option<String>,
+
+    #[clap(
+        long,
+        name = "backtrace-file",
+        help = "A backtrace or crash log, every hexadecimal address (e.g. 0x4011a2) in it is symbolicated"
+    )]
+    pub backtrace: Option<String>,
+
+    #[clap(
+        long = "load-address",
+        name = "load-address",
+        help = "The address the application was loaded at, subtracted from the addresses of position independent code",
+        parse(try_from_str = parse_address)
+    )]
+    pub load_address: Option<u64>,
+
+    #[clap(
+        long,
+        name = "lines",
+        help = "The number of source lines shown before and after the crashing line",
+        default_value = "2"
+    )]
+    pub context: usize,
+
+    #[clap(
+        name = "addresses",
+        help = "The crash addresses, e.g. 0x4011a2",
+        required_unless_present = "backtrace-file",
+        parse(try_from_str = parse_address)
+    )]
+    pub addresses: Vec<u64>,
+}
+
+impl SymbolicateParameters {
+    /// parses the arguments following the `symbolicate` command
+    pub fn parse(args: Vec<String>) -> Result<SymbolicateParameters, ParameterError> {
+        SymbolicateParameters::try_parse_from(args)
+    }
+}
+
+/// the parameters of `rusty run`, which executes an application's tasks in the JIT
+#[derive(Parser, Debug)]
+#[clap(
+    name = "run",
+    about = "Runs the tasks of an application in simulated time, optionally reloading changed sources between cycles"
+)]
+pub struct RunParameters {
+    #[clap(
+        name = "task-conf",
+        long,
+        help = "Task configuration defining the tasks to run.
+    Format is detected by extenstion.
+    Supported formats : json, toml",
+        parse(try_from_str = validate_config)
+    )]
+    pub task_config: String,
+
+    #[clap(
+        long,
+        name = "tick",
+        help = "The simulated time in milliseconds advanced by every cycle",
+        default_value = "10"
+    )]
+    pub tick: u64,
+
+    #[clap(
+        long,
+        name = "cycles",
+        help = "Stop after the given number of cycles instead of running until interrupted"
+    )]
+    pub cycles: Option<u64>,
+
+    #[clap(
+        long,
+        help = "Recompile changed sources between cycles, global variables and program instances keep their values if their layout did not change"
+    )]
+    pub watch: bool,
+
+    #[clap(
+        long = "debug",
+        name = "port",
+        help = "Wait for a debugger connecting to <port> using the Debug Adapter Protocol before running"
+    )]
+    pub debug_port: Option<u16>,
+
+    #[clap(
+        long,
+        help = "Report the tasks exceeding their interval and their most expensive POUs"
+    )]
+    pub budget: bool,
+
+    #[clap(
+        long,
+        name = "seed",
+        help = "Seed RAND and GUID, so the random numbers are the same in every run"
+    )]
+    pub seed: Option<u64>,
+
+    #[clap(
+        long = "restore-state",
+        name = "restore-file",
+        help = "Continue from a snapshot written by --save-state"
+    )]
+    pub restore_state: Option<String>,
+
+    #[clap(
+        long = "save-state",
+        name = "save-file",
+        help = "Write a snapshot of the global variables, program instances and the simulated time to <save-file> once the cycles are run"
+    )]
+    pub save_state: Option<String>,
+
+    #[clap(
+        long = "save-every",
+        name = "save-interval",
+        requires = "save-file",
+        help = "Additionally write the snapshot every <save-interval> cycles"
+    )]
+    pub save_every: Option<u64>,
+
+    #[clap(
+        long = "force",
+        name = "symbol := value",
+        number_of_values = 1,
+        help = "Hold a symbol at a value, e.g. --force 'prg.start := TRUE', may be repeated"
+    )]
+    pub forces: Vec<String>,
+
+    #[clap(
+        long,
+        name = "dialect",
+        help = "The dialect the input-files are written in",
+        arg_enum,
+        default_value = "legacy"
+    )]
+    pub dialect: Dialect,
+
+    #[clap(
+        long,
+        name = "encoding",
+        help = "The file encoding used to read the input-files, as defined by the Encoding Standard",
+        parse(try_from_str = parse_encoding),
+    )]
+    pub encoding: Option<&'static Encoding>,
+
+    #[clap(
+        name = "include",
+        long,
+        short = 'i',
+        help = "Include source files for external functions"
+    )]
+    pub includes: Vec<String>,
+
+    #[clap(
+        name = "input-files",
+        help = "Read input from <input-files>, may be a glob expression like 'src/**/*' or a sequence of files",
+        required = true,
+        min_values = 1
+    )]
+    pub input: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+#[clap(
+    name = "test",
+    about = "Runs the test functions and acceptance test scenarios of an application in the JIT"
+)]
+pub struct TestParameters {
+    #[clap(
+        long = "scenario",
+        name = "scenario-file",
+        help = "A CSV file describing the inputs and expected outputs per cycle, may be repeated",
+        number_of_values = 1
+    )]
+    pub scenarios: Vec<String>,
+
+    #[clap(
+        long,
+        name = "cases",
+        help = "The number of cases generated for every test function",
+        default_value = "100"
+    )]
+    pub cases: usize,
+
+    #[clap(
+        long,
+        name = "seed",
+        help = "The seed of the generated cases, reported with a failing test function"
+    )]
+    pub seed: Option<u64>,
+
+    #[clap(
+        name = "task-conf",
+        long,
+        help = "Task configuration defining the tasks to run.
+    Format is detected by extenstion.
+    Supported formats : json, toml",
+        parse(try_from_str = validate_config)
+    )]
+    pub task_config: String,
+
+    #[clap(
+        long,
+        name = "tick",
+        help = "The simulated time in milliseconds advanced by every cycle",
+        default_value = "10"
+    )]
+    pub tick: u64,
+
+    #[clap(
+        long,
+        name = "dialect",
+        help = "The dialect the input-files are written in",
+        arg_enum,
+        default_value = "legacy"
+    )]
+    pub dialect: Dialect,
+
+    #[clap(
+        long,
+        name = "encoding",
+        help = "The file encoding used to read the input-files, as defined by the Encoding Standard",
+        parse(try_from_str = parse_encoding),
+    )]
+    pub encoding: Option<&'static Encoding>,
+
+    #[clap(
+        name = "include",
+        long,
+        short = 'i',
+        help = "Include source files for external functions"
+    )]
+    pub includes: Vec<String>,
+
+    #[clap(
+        name = "input-files",
+        help = "Read input from <input-files>, may be a glob expression like 'src/**/*' or a sequence of files",
+        required = true,
+        min_values = 1
+    )]
+    pub input: Vec<String>,
+}
+
+/// the parameters of `rusty build`, which compiles and links the sources listed in a project file
+#[derive(Parser, Debug)]
+#[clap(
+    name = "build",
+    about = "Compiles the sources listed in a project file and links them into one output"
+)]
+pub struct BuildParameters {
+    #[clap(
+        name = "project-file",
+        help = "The project file listing the sources, includes, libraries, output and options, plc.json or rusty.toml of the current directory if omitted.
+    Format is detected by extenstion.
+    Supported formats : json, toml",
+        parse(try_from_str = validate_config)
+    )]
+    pub project: Option<String>,
+}
+
+impl BuildParameters {
+    /// parses the arguments following the `build` command
+    pub fn parse(args: Vec<String>) -> Result<BuildParameters, ParameterError> {
+        BuildParameters::try_parse_from(args)
+    }
+}
+
+impl RunParameters {
+    /// parses the arguments following the `run` command
+    pub fn parse(args: Vec<String>) -> Result<RunParameters, ParameterError> {
+        RunParameters::try_parse_from(args)
+    }
+}
+
+impl TestParameters {
+    /// parses the arguments following the `test` command
+    pub fn parse(args: Vec<String>) -> Result<TestParameters, ParameterError> {
+        TestParameters::try_parse_from(args)
+    }
 }
 
 fn parse_encoding(encoding: &str) -> Result<&'static Encoding, String> {
     Encoding::for_label(encoding.as_bytes()).ok_or(format!("Unknown encoding {}", encoding))
 }
 
+/// parses a hexadecimal address, the `0x` prefix is optional
+pub fn parse_address(address: &str) -> Result<u64, String> {
+    let digits = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .unwrap_or(address);
+    u64::from_str_radix(digits, 16).map_err(|_| format!("Invalid address {}", address))
+}
+
+/// parses a number of bytes, optionally followed by `K` or `M` for kibi- or mebibytes
+fn parse_size(size: &str) -> Result<u64, String> {
+    let (digits, factor) = match size.trim().to_uppercase() {
+        it if it.ends_with('K') => (it.trim_end_matches('K').to_string(), 1024),
+        it if it.ends_with('M') => (it.trim_end_matches('M').to_string(), 1024 * 1024),
+        it => (it, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|it| it.checked_mul(factor))
+        .ok_or(format!("Invalid size {}", size))
+}
+
 fn validate_config(config_name: &str) -> Result<String, String> {
     if get_config_format(config_name).is_some() {
         Ok(config_name.to_string())
@@ -186,11 +1029,18 @@ impl CompileParameters {
             Some(FormatOption::Static)
         } else if self.output_reloc_code {
             Some(FormatOption::Relocatable)
+        } else if let Some(output_type) = self.output_type {
+            Some(output_type.into())
         } else {
             None
         }
     }
 
+    /// returns true if the generated object is not linked, with `-c` or `--output-type obj`
+    pub fn is_linking_skipped(&self) -> bool {
+        self.skip_linking || self.output_type == Some(OutputType::Obj)
+    }
+
     /// return the selected output format, or the default if none.
     pub fn output_format_or_default(&self) -> FormatOption {
         // structop makes sure only one or zero format flags are
@@ -198,6 +1048,16 @@ impl CompileParameters {
         self.output_format().unwrap_or(DEFAULT_FORMAT)
     }
 
+    /// returns the selected optimization level. Without a level IR and bitcode are emitted as
+    /// generated, the other formats are optimized at `default`
+    pub fn optimization_or_default(&self) -> OptimizationLevel {
+        self.optimization
+            .unwrap_or(match self.output_format_or_default() {
+                FormatOption::IR | FormatOption::Bitcode => OptimizationLevel::None,
+                _ => OptimizationLevel::Default,
+            })
+    }
+
     /// return the output filename with the correct ending
     pub fn output_name(&self) -> Option<String> {
         let out_format = self.output_format_or_default();
@@ -207,7 +1067,7 @@ impl CompileParameters {
             let ending = match out_format {
                 FormatOption::Bitcode => ".bc",
                 FormatOption::Relocatable => ".o",
-                FormatOption::Static if self.skip_linking => ".o",
+                FormatOption::Static if self.is_linking_skipped() => ".o",
                 FormatOption::Static => "",
                 FormatOption::Shared | FormatOption::PIC => ".so",
                 FormatOption::IR => ".ir",
@@ -229,8 +1089,14 @@ impl CompileParameters {
 
 #[cfg(test)]
 mod cli_tests {
-    use super::CompileParameters;
-    use crate::{ConfigFormat, ErrorFormat, FormatOption, OptimizationLevel};
+    use super::{
+        BuildParameters, CompileParameters, DocParameters, FormatParameters, InspectParameters,
+        MigrateParameters, PackageParameters, RunParameters, SymbolicateParameters, TestParameters,
+    };
+    use crate::{
+        dialect::Dialect, documentation::DocFormat, formatter::KeywordCase, profile::Profile,
+        ConfigFormat, Conformance, ErrorFormat, FormatOption, OptimizationLevel, RuntimeCheck,
+    };
     use clap::ErrorKind;
     use pretty_assertions::assert_eq;
 
@@ -276,6 +1142,10 @@ mod cli_tests {
             vec_of_strings!["input.st", "--ir", "--relocatable"],
             ErrorKind::ArgumentConflict,
         );
+        expect_argument_error(
+            vec_of_strings!["input.st", "--shared", "--output-type", "obj"],
+            ErrorKind::ArgumentConflict,
+        );
     }
 
     #[test]
@@ -351,39 +1221,72 @@ mod cli_tests {
     #[test]
     fn test_optimization_levels() {
         let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(
+            parameters.optimization_or_default(),
+            OptimizationLevel::Default
+        );
+        //IR and bitcode are not optimized unless a level is given
+        for format in ["--ir", "--bc"] {
+            let parameters = CompileParameters::parse(vec_of_strings!("alpha.st", format)).unwrap();
+            assert_eq!(
+                parameters.optimization_or_default(),
+                OptimizationLevel::None
+            );
+        }
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--ir", "-O2")).unwrap();
+        assert_eq!(
+            parameters.optimization_or_default(),
+            OptimizationLevel::Default
+        );
 
-        assert_eq!(parameters.optimization, OptimizationLevel::Default);
         let parameters = CompileParameters::parse(vec_of_strings!("alpha.st", "-Onone")).unwrap();
 
-        assert_eq!(parameters.optimization, OptimizationLevel::None);
+        assert_eq!(parameters.optimization, Some(OptimizationLevel::None));
         let parameters =
             CompileParameters::parse(vec_of_strings!("alpha.st", "--optimization", "none"))
                 .unwrap();
-        assert_eq!(parameters.optimization, OptimizationLevel::None);
+        assert_eq!(parameters.optimization, Some(OptimizationLevel::None));
 
         let parameters = CompileParameters::parse(vec_of_strings!("alpha.st", "-Oless")).unwrap();
 
-        assert_eq!(parameters.optimization, OptimizationLevel::Less);
+        assert_eq!(parameters.optimization, Some(OptimizationLevel::Less));
         let parameters =
             CompileParameters::parse(vec_of_strings!("alpha.st", "--optimization", "less"))
                 .unwrap();
-        assert_eq!(parameters.optimization, OptimizationLevel::Less);
+        assert_eq!(parameters.optimization, Some(OptimizationLevel::Less));
         let parameters =
             CompileParameters::parse(vec_of_strings!("alpha.st", "-Odefault")).unwrap();
 
-        assert_eq!(parameters.optimization, OptimizationLevel::Default);
+        assert_eq!(parameters.optimization, Some(OptimizationLevel::Default));
         let parameters =
             CompileParameters::parse(vec_of_strings!("alpha.st", "--optimization", "default"))
                 .unwrap();
-        assert_eq!(parameters.optimization, OptimizationLevel::Default);
+        assert_eq!(parameters.optimization, Some(OptimizationLevel::Default));
         let parameters =
             CompileParameters::parse(vec_of_strings!("alpha.st", "-Oaggressive")).unwrap();
 
-        assert_eq!(parameters.optimization, OptimizationLevel::Aggressive);
+        assert_eq!(parameters.optimization, Some(OptimizationLevel::Aggressive));
         let parameters =
             CompileParameters::parse(vec_of_strings!("alpha.st", "--optimization", "aggressive"))
                 .unwrap();
-        assert_eq!(parameters.optimization, OptimizationLevel::Aggressive);
+        assert_eq!(parameters.optimization, Some(OptimizationLevel::Aggressive));
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--optimization", "size"))
+                .unwrap();
+        assert_eq!(parameters.optimization, Some(OptimizationLevel::Size));
+
+        //clang's levels
+        for (flag, level) in [
+            ("-O0", OptimizationLevel::None),
+            ("-O1", OptimizationLevel::Less),
+            ("-O2", OptimizationLevel::Default),
+            ("-O3", OptimizationLevel::Aggressive),
+            ("-Os", OptimizationLevel::Size),
+        ] {
+            let parameters = CompileParameters::parse(vec_of_strings!("alpha.st", flag)).unwrap();
+            assert_eq!(parameters.optimization, Some(level));
+        }
     }
 
     #[test]
@@ -412,6 +1315,29 @@ mod cli_tests {
         assert_eq!(parameters.output_format_or_default(), super::DEFAULT_FORMAT);
     }
 
+    #[test]
+    fn output_type_selects_the_format() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--output-type", "obj")).unwrap();
+        assert_eq!(parameters.output_format_or_default(), FormatOption::Static);
+        assert!(parameters.is_linking_skipped());
+        assert_eq!(parameters.output_name().unwrap(), "alpha.o".to_string());
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("bravo.st", "--output-type", "shared"))
+                .unwrap();
+        assert_eq!(parameters.output_format_or_default(), FormatOption::Shared);
+        assert!(!parameters.is_linking_skipped());
+        assert_eq!(parameters.output_name().unwrap(), "bravo.so".to_string());
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("charlie.st", "--output-type", "static"))
+                .unwrap();
+        assert_eq!(parameters.output_format_or_default(), FormatOption::Static);
+        assert!(!parameters.is_linking_skipped());
+        assert_eq!(parameters.output_name().unwrap(), "charlie".to_string());
+    }
+
     #[test]
     fn encoding_resolution() {
         let parameters =
@@ -549,6 +1475,165 @@ mod cli_tests {
         assert_eq!(parameters.sysroot, Some("path/to/sysroot".to_string()));
     }
 
+    #[test]
+    fn map_file_added() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--map", "app.map")).unwrap();
+        assert_eq!(parameters.map_file, Some("app.map".to_string()));
+
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st")).unwrap();
+        assert_eq!(parameters.map_file, None);
+    }
+
+    #[test]
+    fn linker_script_added() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--linker-script", "rusty.ld"))
+                .unwrap();
+        assert_eq!(parameters.linker_script, Some("rusty.ld".to_string()));
+    }
+
+    #[test]
+    fn header_added() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--header", "app.h")).unwrap();
+        assert_eq!(parameters.header, Some("app.h".to_string()));
+    }
+
+    #[test]
+    fn llvm_options_added() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--llvm-arg=-unroll-threshold=50",
+            "--llvm-arg",
+            "-enable-misched",
+            "--disable-tail-calls"
+        ))
+        .unwrap();
+        assert_eq!(
+            parameters.llvm_args,
+            vec_of_strings!("-unroll-threshold=50", "-enable-misched")
+        );
+        assert_eq!(parameters.input, vec_of_strings!("input.st"));
+        assert!(parameters.disable_tail_calls);
+    }
+
+    #[test]
+    fn fast_math_added() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st")).unwrap();
+        assert!(!parameters.fast_math);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--ffast-math")).unwrap();
+        assert!(parameters.fast_math);
+    }
+
+    #[test]
+    fn jobs_added() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st")).unwrap();
+        assert_eq!(parameters.jobs, 1);
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st", "-j", "4")).unwrap();
+        assert_eq!(parameters.jobs, 4);
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st", "--jobs=0")).unwrap();
+        assert_eq!(parameters.jobs, 0);
+    }
+
+    #[test]
+    fn wcet_annotations_added() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--wcet-annotations", "app.ais"))
+                .unwrap();
+        assert_eq!(parameters.wcet_annotations, Some("app.ais".to_string()));
+    }
+
+    #[test]
+    fn init_order_added() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--init-order", "init.txt"))
+                .unwrap();
+        assert_eq!(parameters.init_order, Some("init.txt".to_string()));
+    }
+
+    #[test]
+    fn cpu_and_target_features_added() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--target",
+            "armv7a-none-eabihf",
+            "--cpu",
+            "cortex-a9",
+            "--target-features",
+            "+neon,-fp16"
+        ))
+        .unwrap();
+        assert_eq!(parameters.target, Some("armv7a-none-eabihf".to_string()));
+        assert_eq!(parameters.cpu, Some("cortex-a9".to_string()));
+        assert_eq!(parameters.target_features, Some("+neon,-fp16".to_string()));
+    }
+
+    #[test]
+    fn checks_added() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st")).unwrap();
+        assert!(parameters.checks.is_empty());
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--check", "bounds")).unwrap();
+        assert_eq!(parameters.checks, vec![RuntimeCheck::Bounds]);
+        assert!(CompileParameters::parse(vec_of_strings!("input.st", "--check", "bound")).is_err());
+    }
+
+    #[test]
+    fn lto_added() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--lto", "-O", "aggressive"))
+                .unwrap();
+        assert!(parameters.lto);
+        assert_eq!(parameters.optimization, OptimizationLevel::Aggressive);
+
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st")).unwrap();
+        assert!(!parameters.lto);
+    }
+
+    #[test]
+    fn profile_added() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--profile",
+            "safety",
+            "--profile-report",
+            "conformance.txt"
+        ))
+        .unwrap();
+        assert_eq!(parameters.profile, Some(Profile::Safety));
+        assert_eq!(
+            parameters.profile_report,
+            Some("conformance.txt".to_string())
+        );
+
+        assert!(CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--profile-report",
+            "conformance.txt"
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn size_budgets_added() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--max-code-size",
+            "64K",
+            "--max-ram",
+            "4096"
+        ))
+        .unwrap();
+        assert_eq!(parameters.max_code_size, Some(65536));
+        assert_eq!(parameters.max_ram, Some(4096));
+
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st")).unwrap();
+        assert_eq!(parameters.max_code_size, None);
+        assert!(CompileParameters::parse(vec_of_strings!("input.st", "--max-ram", "4G")).is_err());
+    }
+
     #[test]
     fn include_files_added() {
         let parameters = CompileParameters::parse(vec_of_strings!(
@@ -592,6 +1677,357 @@ mod cli_tests {
         );
     }
 
+    #[test]
+    fn task_config_option_set() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("foo", "--task-conf=tasks.toml")).unwrap();
+        assert_eq!(parameters.task_config, Some("tasks.toml".to_string()));
+
+        expect_argument_error(
+            vec_of_strings!("foo", "--task-conf=tasks.xml"),
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn config_const_option_set() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("foo", "--config-const=variant_a.toml"))
+                .unwrap();
+        assert_eq!(
+            parameters.config_constants,
+            Some("variant_a.toml".to_string())
+        );
+
+        expect_argument_error(
+            vec_of_strings!("foo", "--config-const=variant_a.ini"),
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn define_option_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "foo",
+            "-D",
+            "SIMULATION",
+            "--define=AXES=4"
+        ))
+        .unwrap();
+        assert_eq!(parameters.defines, vec_of_strings!("SIMULATION", "AXES=4"));
+    }
+
+    #[test]
+    fn reflection_option_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!("foo")).unwrap();
+        assert!(!parameters.reflection);
+        let parameters = CompileParameters::parse(vec_of_strings!("foo", "--reflection")).unwrap();
+        assert!(parameters.reflection);
+    }
+
+    #[test]
+    fn debug_option_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!("foo")).unwrap();
+        assert!(!parameters.debug);
+        let parameters = CompileParameters::parse(vec_of_strings!("foo", "--debug")).unwrap();
+        assert!(parameters.debug);
+        let parameters = CompileParameters::parse(vec_of_strings!("foo", "-g")).unwrap();
+        assert!(parameters.debug);
+    }
+
+    #[test]
+    fn project_option_set() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("foo", "--project=plc.json")).unwrap();
+        assert_eq!(parameters.project, Some("plc.json".to_string()));
+
+        expect_argument_error(
+            vec_of_strings!("foo", "--project=plc.xml"),
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn migrate_parameters_parsed() {
+        let params = MigrateParameters::parse(vec_of_strings!("a.st", "b.st")).unwrap();
+        assert_eq!(params.input, vec!["a.st", "b.st"]);
+        assert_eq!(params.output, "migrated");
+        assert_eq!(params.dialect, Dialect::Legacy);
+
+        let params =
+            MigrateParameters::parse(vec_of_strings!("a.st", "--dialect=scl", "-o", "out"))
+                .unwrap();
+        assert_eq!(params.output, "out");
+        assert_eq!(params.dialect, Dialect::Scl);
+
+        assert!(MigrateParameters::parse(vec_of_strings!()).is_err());
+    }
+
+    #[test]
+    fn format_parameters_parsed() {
+        let params = FormatParameters::parse(vec_of_strings!("a.st")).unwrap();
+        assert_eq!(params.input, vec!["a.st"]);
+        assert_eq!(params.indent, 4);
+        assert_eq!(params.keyword_case, KeywordCase::Upper);
+        assert_eq!(params.line_width, 100);
+        assert!(!params.check);
+
+        let params = FormatParameters::parse(vec_of_strings!(
+            "a.st",
+            "--indent",
+            "2",
+            "--keyword-case=lower",
+            "--line-width",
+            "80",
+            "--check"
+        ))
+        .unwrap();
+        assert_eq!(params.indent, 2);
+        assert_eq!(params.keyword_case, KeywordCase::Lower);
+        assert_eq!(params.line_width, 80);
+        assert!(params.check);
+
+        assert!(FormatParameters::parse(vec_of_strings!()).is_err());
+    }
+
+    #[test]
+    fn doc_parameters_parsed() {
+        let params = DocParameters::parse(vec_of_strings!("a.st", "b.st")).unwrap();
+        assert_eq!(params.input, vec!["a.st", "b.st"]);
+        assert_eq!(params.format, DocFormat::Markdown);
+        assert_eq!(params.output, "doc");
+
+        let params =
+            DocParameters::parse(vec_of_strings!("a.st", "--format=html", "-o", "api")).unwrap();
+        assert_eq!(params.format, DocFormat::Html);
+        assert_eq!(params.output, "api");
+
+        assert!(DocParameters::parse(vec_of_strings!()).is_err());
+    }
+
+    #[test]
+    fn package_parameters_parsed() {
+        let params =
+            PackageParameters::parse(vec_of_strings!("src/motion.st", "src/axis.st")).unwrap();
+        assert_eq!(params.input, vec!["src/motion.st", "src/axis.st"]);
+        assert_eq!(params.library_name(), "motion");
+        assert_eq!(params.output_name(), "motion.plclib");
+
+        let params = PackageParameters::parse(vec_of_strings!(
+            "src/motion.st",
+            "--name",
+            "drives",
+            "--target",
+            "armv7a-none-eabihf"
+        ))
+        .unwrap();
+        assert_eq!(params.library_name(), "drives");
+        assert_eq!(params.output_name(), "drives.plclib");
+        assert_eq!(params.target, Some("armv7a-none-eabihf".to_string()));
+        assert_eq!(params.version.to_string(), "0.1.0");
+
+        let params = PackageParameters::parse(vec_of_strings!(
+            "src/motion.st",
+            "--library-version",
+            "1.2.3"
+        ))
+        .unwrap();
+        assert_eq!(params.version.to_string(), "1.2.3");
+        assert!(PackageParameters::parse(vec_of_strings!(
+            "src/motion.st",
+            "--library-version",
+            "1.2"
+        ))
+        .is_err());
+
+        assert!(PackageParameters::parse(vec_of_strings!()).is_err());
+    }
+
+    #[test]
+    fn library_packages_added() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--lib", "motion.plclib"))
+                .unwrap();
+        assert_eq!(
+            parameters.library_packages,
+            vec!["motion.plclib".to_string()]
+        );
+        assert_eq!(parameters.library_lock, None);
+
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--lib",
+            "motion.plclib",
+            "--lib-lock",
+            "plc.lock"
+        ))
+        .unwrap();
+        assert_eq!(parameters.library_lock, Some("plc.lock".to_string()));
+    }
+
+    #[test]
+    fn inspect_parameters_parsed() {
+        let params = InspectParameters::parse(vec_of_strings!("app.o")).unwrap();
+        assert_eq!(params.input, "app.o");
+
+        assert!(InspectParameters::parse(vec_of_strings!()).is_err());
+    }
+
+    #[test]
+    fn symbolicate_parameters_parsed() {
+        let params = SymbolicateParameters::parse(vec_of_strings!(
+            "--map",
+            "app.map",
+            "--debug-info",
+            "app",
+            "0x4011a2",
+            "4011b0"
+        ))
+        .unwrap();
+        assert_eq!(params.map_file, "app.map");
+        assert_eq!(params.debug_info, Some("app".to_string()));
+        assert_eq!(params.addresses, vec![0x4011a2, 0x4011b0]);
+        assert_eq!(params.context, 2);
+
+        let params = SymbolicateParameters::parse(vec_of_strings!(
+            "--map",
+            "app.map",
+            "--backtrace",
+            "crash.log",
+            "--load-address",
+            "0x7f0000000000"
+        ))
+        .unwrap();
+        assert_eq!(params.backtrace, Some("crash.log".to_string()));
+        assert_eq!(params.load_address, Some(0x7f0000000000));
+
+        //either addresses or a backtrace are required
+        assert!(SymbolicateParameters::parse(vec_of_strings!("--map", "app.map")).is_err());
+        assert!(SymbolicateParameters::parse(vec_of_strings!("--map", "app.map", "0xzz")).is_err());
+    }
+
+    #[test]
+    fn build_parameters_parsed() {
+        let params = BuildParameters::parse(vec_of_strings!("plc.json")).unwrap();
+        assert_eq!(params.project, Some("plc.json".to_string()));
+
+        let params = BuildParameters::parse(vec_of_strings!()).unwrap();
+        assert_eq!(params.project, None);
+
+        assert!(BuildParameters::parse(vec_of_strings!("plc.xml")).is_err());
+    }
+
+    #[test]
+    fn run_parameters_parsed() {
+        let params = RunParameters::parse(vec_of_strings!(
+            "--task-conf",
+            "tasks.json",
+            "--watch",
+            "--cycles",
+            "100",
+            "app.st"
+        ))
+        .unwrap();
+        assert_eq!(params.task_config, "tasks.json");
+        assert_eq!(params.tick, 10);
+        assert_eq!(params.cycles, Some(100));
+        assert!(params.watch);
+        assert_eq!(params.debug_port, None);
+        assert!(!params.budget);
+        assert_eq!(params.restore_state, None);
+        assert_eq!(params.save_state, None);
+        assert_eq!(params.seed, None);
+        assert_eq!(params.input, vec!["app.st"]);
+
+        let params = RunParameters::parse(vec_of_strings!(
+            "--task-conf",
+            "tasks.json",
+            "--debug",
+            "4711",
+            "--budget",
+            "--seed",
+            "7",
+            "app.st"
+        ))
+        .unwrap();
+        assert_eq!(params.debug_port, Some(4711));
+        assert!(params.budget);
+        assert_eq!(params.seed, Some(7));
+
+        let params = RunParameters::parse(vec_of_strings!(
+            "--task-conf",
+            "tasks.json",
+            "--force",
+            "prg.start := TRUE",
+            "--force",
+            "limit := 5",
+            "app.st"
+        ))
+        .unwrap();
+        assert_eq!(params.forces, vec!["prg.start := TRUE", "limit := 5"]);
+        assert_eq!(params.input, vec!["app.st"]);
+
+        let params = RunParameters::parse(vec_of_strings!(
+            "--task-conf",
+            "tasks.json",
+            "--restore-state",
+            "fault.json",
+            "--save-state",
+            "state.json",
+            "--save-every",
+            "1000",
+            "app.st"
+        ))
+        .unwrap();
+        assert_eq!(params.restore_state, Some("fault.json".to_string()));
+        assert_eq!(params.save_state, Some("state.json".to_string()));
+        assert_eq!(params.save_every, Some(1000));
+        assert!(RunParameters::parse(vec_of_strings!(
+            "--task-conf",
+            "tasks.json",
+            "--save-every",
+            "1000",
+            "app.st"
+        ))
+        .is_err());
+
+        assert!(RunParameters::parse(vec_of_strings!("app.st")).is_err());
+    }
+
+    #[test]
+    fn test_parameters_parsed() {
+        let params = TestParameters::parse(vec_of_strings!(
+            "--task-conf",
+            "tasks.json",
+            "--scenario",
+            "start.csv",
+            "--scenario",
+            "stop.csv",
+            "app.st"
+        ))
+        .unwrap();
+        assert_eq!(params.scenarios, vec!["start.csv", "stop.csv"]);
+        assert_eq!(params.tick, 10);
+        assert_eq!(params.input, vec!["app.st"]);
+
+        assert_eq!(params.cases, 100);
+        assert_eq!(params.seed, None);
+
+        let params = TestParameters::parse(vec_of_strings!(
+            "--task-conf",
+            "tasks.json",
+            "--cases",
+            "1000",
+            "--seed",
+            "42",
+            "app.st"
+        ))
+        .unwrap();
+        assert!(params.scenarios.is_empty());
+        assert_eq!(params.cases, 1000);
+        assert_eq!(params.seed, Some(42));
+    }
+
     #[test]
     fn error_format_default_set() {
         // make sure the default error format is set
@@ -611,4 +2047,13 @@ mod cli_tests {
             ErrorKind::InvalidValue,
         );
     }
+
+    #[test]
+    fn conformance_set() {
+        let params = CompileParameters::parse(vec_of_strings!("input.st")).unwrap();
+        assert_eq!(params.conformance, None);
+        let params =
+            CompileParameters::parse(vec_of_strings!("input.st", "--conformance=strict")).unwrap();
+        assert_eq!(params.conformance, Some(Conformance::Strict));
+    }
 }
@@ -3,26 +3,39 @@
 /// module to generate llvm intermediate representation for a CompilationUnit
 use self::{
     generators::{
-        data_type_generator,
+        abi_generator, bounds_check, checksums, data_type_generator,
+        debug_generator::DebugHooks,
+        debug_info::DebugInfo,
+        fast_math,
         llvm::{GlobalValueExt, Llvm},
         pou_generator::{self, PouGenerator},
-        variable_generator,
+        random, reflection_generator, saturation, section_generator, string_conversions,
+        symbol_generator, task_generator, telemetry_generator, variable_generator,
+        vtable_generator, wasm_generator,
     },
     llvm_index::LlvmTypedIndex,
 };
 use crate::{
+    abi_metadata::{self, AbiMetadata},
     diagnostics::Diagnostic,
+    dialect::Dialect,
+    linker_script::SectionLayout,
     resolver::{AstAnnotations, StringLiterals},
+    simulation::symbols::SourceFile,
+    task_configuration::{TaskConfiguration, Telemetry},
 };
 
 use super::ast::*;
 use super::index::*;
+use inkwell::attributes::{Attribute, AttributeLoc};
 use inkwell::module::Module;
+use inkwell::targets::{RelocMode, TargetTriple};
 use inkwell::{context::Context, types::BasicType};
 
 pub(crate) mod generators;
 mod llvm_index;
 mod llvm_typesystem;
+pub(crate) mod parallel;
 #[cfg(test)]
 mod tests;
 
@@ -33,13 +46,61 @@ pub struct CodeGen<'ink> {
     pub context: &'ink Context,
     /// the module represents a llvm compilation unit
     pub module: Module<'ink>,
+    /// the DWARF debug information of the generated POUs, if requested
+    debug_info: Option<DebugInfo<'ink>>,
+    /// annotate the generated POUs for WCET analyzers
+    timing_annotations: bool,
+    /// compute the float operations of all POUs with fast-math semantics
+    fast_math: bool,
+    /// check the indexes of array accesses at runtime
+    check_bounds: bool,
+    /// the CPU and its features the module is generated for (see `set_target`)
+    target_cpu: String,
+    target_features: String,
 }
 
 impl<'ink> CodeGen<'ink> {
     /// constructs a new code-generator that generates CompilationUnits into a module with the given module_name
     pub fn new(context: &'ink Context, module_name: &str) -> CodeGen<'ink> {
         let module = context.create_module(module_name);
-        CodeGen { context, module }
+        CodeGen {
+            context,
+            module,
+            debug_info: None,
+            timing_annotations: false,
+            fast_math: false,
+            check_bounds: false,
+            target_cpu: abi_metadata::GENERIC_CPU.to_string(),
+            target_features: String::new(),
+        }
+    }
+
+    /// emits DWARF debug information for the POUs generated by `generate_with_debug_info`,
+    /// `main_file` is the file of the debug information's compile unit
+    pub fn with_debug_info(mut self, main_file: &SourceFile) -> Self {
+        self.debug_info = Some(DebugInfo::new(self.context, &self.module, main_file));
+        self
+    }
+
+    /// annotates the statements and bounded loops of the generated POUs with metadata for WCET
+    /// analyzers (see `timing_generator`)
+    pub fn with_timing_annotations(mut self) -> Self {
+        self.timing_annotations = true;
+        self
+    }
+
+    /// computes the float operations of all generated POUs with fast-math semantics, not only
+    /// the ones of the POUs marked with `{attribute 'fast_math'}` (see `fast_math`)
+    pub fn with_fast_math(mut self) -> Self {
+        self.fast_math = true;
+        self
+    }
+
+    /// checks the indexes of the array accesses of the generated POUs against the array's bounds
+    /// (see `bounds_check`)
+    pub fn with_bounds_check(mut self) -> Self {
+        self.check_bounds = true;
+        self
     }
 
     pub fn generate_llvm_index(
@@ -51,8 +112,12 @@ impl<'ink> CodeGen<'ink> {
         let llvm = Llvm::new(self.context, self.context.create_builder());
         let mut index = LlvmTypedIndex::default();
         //Generate types index, and any global variables associated with them.
-        let llvm_type_index =
-            data_type_generator::generate_data_types(&llvm, global_index, annotations)?;
+        let llvm_type_index = data_type_generator::generate_data_types(
+            &self.module,
+            &llvm,
+            global_index,
+            annotations,
+        )?;
         index.merge(llvm_type_index);
 
         //Generate global variables
@@ -85,6 +150,31 @@ impl<'ink> CodeGen<'ink> {
         )?;
         index.merge(llvm_values_index);
 
+        //Generate the vtables of polymorphic classes and of the pous implementing interfaces
+        vtable_generator::generate_vtables(&self.module, &llvm, global_index, &index)?;
+
+        //Declare the runtime's helpers used by string conversions, checksums and random numbers
+        let llvm_helpers_index =
+            string_conversions::declare_helpers(&self.module, &llvm, global_index, annotations)?;
+        index.merge(llvm_helpers_index);
+        let llvm_helpers_index =
+            checksums::declare_helpers(&self.module, &llvm, global_index, annotations)?;
+        index.merge(llvm_helpers_index);
+        let llvm_helpers_index =
+            random::declare_helpers(&self.module, &llvm, global_index, annotations)?;
+        index.merge(llvm_helpers_index);
+        let llvm_helpers_index =
+            saturation::declare_helpers(&self.module, &llvm, global_index, annotations)?;
+        index.merge(llvm_helpers_index);
+        if self.fast_math || global_index.has_fast_math() {
+            let llvm_helpers_index = fast_math::declare_helpers(&self.module, &llvm)?;
+            index.merge(llvm_helpers_index);
+        }
+        if self.check_bounds {
+            let llvm_helpers_index = bounds_check::declare_helpers(&self.module, &llvm)?;
+            index.merge(llvm_helpers_index);
+        }
+
         //Generate constants for string-literal
         //generate literals but first sort, so we get reproducable builds
         let mut utf08s = literals.utf08.into_iter().collect::<Vec<String>>();
@@ -138,19 +228,274 @@ impl<'ink> CodeGen<'ink> {
         //generate all pous
         let llvm = Llvm::new(self.context, self.context.create_builder());
         let pou_generator = PouGenerator::new(llvm, global_index, annotations, llvm_index);
+        self.generate_implementations(unit, pou_generator, global_index)
+    }
+
+    /// generates the given CompilationUnit like `generate` and instruments its POUs with calls to
+    /// the debugger's hooks, `file` is the index of the unit's source file
+    pub fn generate_with_debug_hooks(
+        &self,
+        unit: &CompilationUnit,
+        file: u32,
+        annotations: &AstAnnotations,
+        global_index: &Index,
+        llvm_index: &LlvmTypedIndex,
+    ) -> Result<String, Diagnostic> {
+        let llvm = Llvm::new(self.context, self.context.create_builder());
+        let debug_hooks = DebugHooks::declare(&self.module, &llvm, file);
+        let pou_generator = PouGenerator::new(llvm, global_index, annotations, llvm_index)
+            .with_debug_hooks(debug_hooks);
+        self.generate_implementations(unit, pou_generator, global_index)
+    }
+
+    /// generates the given CompilationUnit like `generate` and describes its POUs and variables in
+    /// the debug information, `source` is the unit's source file
+    pub fn generate_with_debug_info(
+        &self,
+        unit: &CompilationUnit,
+        source: &SourceFile,
+        annotations: &AstAnnotations,
+        global_index: &Index,
+        llvm_index: &LlvmTypedIndex<'ink>,
+    ) -> Result<String, Diagnostic> {
+        let debug_info = match &self.debug_info {
+            Some(debug_info) => debug_info,
+            None => return self.generate(unit, annotations, global_index, llvm_index),
+        };
+        let debug_file = debug_info.create_file(source);
+        debug_file.declare_global_variables(unit, global_index, llvm_index);
+        let llvm = Llvm::new(self.context, self.context.create_builder());
+        let pou_generator = PouGenerator::new(llvm, global_index, annotations, llvm_index)
+            .with_debug_file(&debug_file);
+        self.generate_implementations(unit, pou_generator, global_index)
+    }
+
+    /// generates the POUs of all given units like `generate` on `jobs` threads (see `parallel`),
+    /// `literals` are the string literals passed to `generate_llvm_index`
+    pub fn generate_in_parallel(
+        &self,
+        units: &[CompilationUnit],
+        annotations: &AstAnnotations,
+        literals: &StringLiterals,
+        global_index: &Index,
+        jobs: usize,
+    ) -> Result<(), Diagnostic> {
+        let implementations = units
+            .iter()
+            .flat_map(|it| it.implementations.iter())
+            .filter(|it| is_generated(it, global_index))
+            .collect::<Vec<_>>();
+        parallel::generate_partitions(
+            self,
+            parallel::partition(&implementations, jobs),
+            annotations,
+            literals,
+            global_index,
+        )
+    }
+
+    /// keeps the generated functions from replacing calls in tail position by jumps, so every
+    /// call shows up in backtraces and stack usage analyses
+    pub fn disable_tail_calls(&self) {
+        let attribute = self
+            .context
+            .create_string_attribute("disable-tail-calls", "true");
+        for function in self
+            .module
+            .get_functions()
+            .filter(|it| it.count_basic_blocks() > 0)
+        {
+            function.add_attribute(AttributeLoc::Function, attribute);
+        }
+    }
+
+    /// excludes the implemented functions from optimization (`optnone`, which requires
+    /// `noinline`), except for the POUs optimized for size by `{attribute 'optimize' := 'size'}`
+    pub fn disable_optimization(&self) {
+        let optsize = Attribute::get_named_enum_kind_id("optsize");
+        for function in self.module.get_functions().filter(|it| {
+            it.count_basic_blocks() > 0
+                && it
+                    .get_enum_attribute(AttributeLoc::Function, optsize)
+                    .is_none()
+        }) {
+            for name in ["optnone", "noinline"] {
+                let attribute = self
+                    .context
+                    .create_enum_attribute(Attribute::get_named_enum_kind_id(name), 0);
+                function.add_attribute(AttributeLoc::Function, attribute);
+            }
+        }
+    }
+
+    /// resolves the debug information, has to be called once all units are generated
+    pub fn finalize_debug_info(&self) {
+        if let Some(debug_info) = &self.debug_info {
+            debug_info.finalize();
+        }
+    }
 
+    fn generate_implementations(
+        &self,
+        unit: &CompilationUnit,
+        pou_generator: PouGenerator,
+        global_index: &Index,
+    ) -> Result<String, Diagnostic> {
+        let pou_generator = self.configure(pou_generator);
         //Generate the POU stubs in the first go to make sure they can be referenced.
-        for implementation in &unit.implementations {
-            //Don't generate external or generic functions
-            if let Some(entry) = global_index.find_pou(implementation.name.as_str()) {
-                if !entry.is_generic() && entry.get_linkage() != &LinkageType::External {
-                    pou_generator.generate_implementation(implementation)?;
+        for implementation in unit
+            .implementations
+            .iter()
+            .filter(|it| is_generated(it, global_index))
+        {
+            pou_generator.generate_implementation(implementation)?;
+        }
+
+        Ok(self.module.print_to_string().to_string())
+    }
+
+    /// applies the options of this code-generator to the given POU generator
+    fn configure<'cg>(&self, pou_generator: PouGenerator<'ink, 'cg>) -> PouGenerator<'ink, 'cg> {
+        let mut pou_generator = pou_generator;
+        if self.timing_annotations {
+            pou_generator = pou_generator.with_timing_annotations();
+        }
+        if self.fast_math {
+            pou_generator = pou_generator.with_fast_math();
+        }
+        if self.check_bounds {
+            pou_generator = pou_generator.with_bounds_check();
+        }
+        pou_generator
+    }
+
+    /// generates reflection tables describing the members of all structs and POU instances
+    pub fn generate_reflection(&self, global_index: &Index) -> Result<(), Diagnostic> {
+        let llvm = Llvm::new(self.context, self.context.create_builder());
+        reflection_generator::generate_reflection(&self.module, &llvm, global_index)
+    }
+
+    /// embeds the ABI metadata of the application compiled for the given dialect and target
+    pub fn generate_abi_metadata(
+        &self,
+        global_index: &Index,
+        dialect: Dialect,
+        target: &TargetTriple,
+    ) -> Result<AbiMetadata, Diagnostic> {
+        let llvm = Llvm::new(self.context, self.context.create_builder());
+        abi_generator::generate_abi_metadata(&self.module, &llvm, global_index, dialect, target)
+    }
+
+    /// generates the task table and the retain table consumed by the runtime's scheduler
+    pub fn generate_task_table(
+        &self,
+        global_index: &Index,
+        tasks: &TaskConfiguration,
+    ) -> Result<(), Diagnostic> {
+        let llvm = Llvm::new(self.context, self.context.create_builder());
+        task_generator::generate_task_table(&self.module, &llvm, global_index, tasks)
+    }
+
+    /// generates the trie of instance paths accessed by the runtime's `read_symbol` and `write_symbol`
+    pub fn generate_symbol_table(&self, global_index: &Index) -> Result<(), Diagnostic> {
+        let llvm = Llvm::new(self.context, self.context.create_builder());
+        symbol_generator::generate_symbol_table(&self.module, &llvm, global_index)
+    }
+
+    /// generates the publisher serializing the telemetry's variables to JSON for the runtime
+    pub fn generate_telemetry(
+        &self,
+        global_index: &Index,
+        telemetry: &Telemetry,
+    ) -> Result<(), Diagnostic> {
+        let llvm = Llvm::new(self.context, self.context.create_builder());
+        telemetry_generator::generate_telemetry(&self.module, &llvm, global_index, telemetry)
+    }
+
+    /// sets the given target and its data layout, so the module is optimized with the target's
+    /// pointer size and alignments. The given CPU and features are used when the module is
+    /// compiled to an object and recorded on the generated functions for IR and bitcode outputs
+    pub fn set_target(
+        &mut self,
+        target: &TargetTriple,
+        cpu: Option<&str>,
+        features: Option<&str>,
+    ) -> Result<(), Diagnostic> {
+        self.target_cpu = cpu.unwrap_or(abi_metadata::GENERIC_CPU).to_string();
+        self.target_features = features.unwrap_or_default().to_string();
+        let machine = abi_metadata::create_configured_target_machine(
+            target,
+            &self.target_cpu,
+            &self.target_features,
+            inkwell::OptimizationLevel::None,
+            RelocMode::Default,
+        )?;
+        self.module.set_triple(target);
+        self.module
+            .set_data_layout(&machine.get_target_data().get_data_layout());
+
+        for (name, value) in [("target-cpu", cpu), ("target-features", features)] {
+            if let Some(value) = value {
+                let attribute = self.context.create_string_attribute(name, value);
+                for function in self
+                    .module
+                    .get_functions()
+                    .filter(|it| it.count_basic_blocks() > 0)
+                {
+                    function.add_attribute(AttributeLoc::Function, attribute);
                 }
             }
         }
+        Ok(())
+    }
 
-        Ok(self.module.print_to_string().to_string())
+    /// the CPU the module is generated for
+    pub fn get_target_cpu(&self) -> &str {
+        &self.target_cpu
+    }
+
+    /// the enabled and disabled features of the CPU the module is generated for
+    pub fn get_target_features(&self) -> &str {
+        &self.target_features
+    }
+
+    /// prepares the module for WebAssembly targets, the POUs are exported and freestanding
+    /// targets get their own memory functions (see `wasm_generator`)
+    pub fn prepare_for_wasm(
+        &self,
+        global_index: &Index,
+        target: &TargetTriple,
+        freestanding: bool,
+    ) -> Result<(), Diagnostic> {
+        let llvm = Llvm::new(self.context, self.context.create_builder());
+        wasm_generator::export_pous(&self.module, &llvm, global_index);
+        if freestanding {
+            wasm_generator::generate_memory_functions(&self.module, &llvm, target)?;
+        }
+        Ok(())
     }
+
+    /// places the hardware-bound, retained and persistent global variables into their linker
+    /// sections
+    pub fn assign_sections(
+        &self,
+        global_index: &Index,
+        target: &TargetTriple,
+    ) -> Result<Vec<SectionLayout>, Diagnostic> {
+        section_generator::assign_sections(&self.module, global_index, target)
+    }
+}
+
+/// returns true if the given implementation is generated, external and generic functions and
+/// abstract methods are not
+fn is_generated(implementation: &Implementation, global_index: &Index) -> bool {
+    global_index
+        .find_pou(implementation.name.as_str())
+        .map_or(false, |entry| {
+            !entry.is_generic()
+                && !entry.is_abstract()
+                && entry.get_linkage() != &LinkageType::External
+        })
 }
 
 #[cfg(test)]
@@ -1,7 +1,26 @@
+pub mod abi_generator;
+pub mod bounds_check;
+pub mod checksums;
 pub mod data_type_generator;
 pub mod date_time_util;
+pub mod debug_generator;
+pub mod debug_info;
 pub mod expression_generator;
+pub mod fast_math;
 pub mod llvm;
 pub mod pou_generator;
+pub mod process_image_generator;
+pub mod random;
+pub mod reflection_generator;
+pub mod saturation;
+pub mod section_generator;
 pub mod statement_generator;
+pub mod string_conversions;
+pub mod symbol_generator;
+pub mod task_generator;
+pub mod telemetry_generator;
+pub mod timing_generator;
 pub mod variable_generator;
+pub mod vectorization;
+pub mod vtable_generator;
+pub mod wasm_generator;
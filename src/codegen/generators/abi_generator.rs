@@ -0,0 +1,190 @@
+//! embeds the versioned ABI metadata (see `crate::abi_metadata`) into a custom section of the
+//! generated module, the instance layouts are calculated for the compilation's target
+use inkwell::{
+    module::{Linkage, Module},
+    targets::{TargetData, TargetTriple},
+    types::BasicType,
+    AddressSpace,
+};
+
+use super::{
+    llvm::{GlobalValueExt, Llvm},
+    reflection_generator,
+};
+use crate::{
+    abi_metadata::{self, AbiMetadata, InstanceLayout, MemberLayout, RetainedVariable},
+    ast::LinkageType,
+    diagnostics::Diagnostic,
+    dialect::Dialect,
+    index::{Index, PouIndexEntry},
+};
+
+pub fn generate_abi_metadata<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    dialect: Dialect,
+    triple: &TargetTriple,
+) -> Result<AbiMetadata, Diagnostic> {
+    let target = triple.as_str().to_string_lossy();
+    let target_data = abi_metadata::create_target_machine(triple)?.get_target_data();
+    let layouts = get_instance_layouts(module, index, &target_data);
+    let retained = get_retained_variables(module, index, &target_data, &layouts);
+    let metadata = AbiMetadata::new(
+        dialect,
+        &target,
+        abi_metadata::hash_layouts(
+            get_layout_descriptions(module, index)
+                .iter()
+                .map(String::as_str),
+        ),
+        get_exports(index),
+    )
+    .with_layouts(layouts, retained);
+    let record = metadata.to_record()?;
+
+    let context = llvm.context;
+    let global = module.add_global(
+        context.i8_type().array_type(record.len() as u32),
+        None,
+        abi_metadata::ABI_SYMBOL,
+    );
+    global.set_linkage(Linkage::Internal);
+    global.set_section(abi_metadata::get_section_name(&target));
+    global.set_alignment(1);
+    global
+        .make_constant()
+        .set_initializer(&llvm.create_llvm_const_vec_string(record.as_slice())?);
+
+    //the record is never referenced by the application, keep the optimizer from removing it
+    let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    let used = module.add_global(i8_ptr_type.array_type(1), None, "llvm.used");
+    used.set_linkage(Linkage::Appending);
+    used.set_section("llvm.metadata");
+    used.set_initializer(
+        &i8_ptr_type.const_array(&[global.as_pointer_value().const_cast(i8_ptr_type)]),
+    );
+
+    Ok(metadata)
+}
+
+/// describes the llvm layout of every reflected struct, sorted so the declaration order of the
+/// types does not change the layout's hash
+fn get_layout_descriptions(module: &Module, index: &Index) -> Vec<String> {
+    let mut layouts = reflection_generator::get_reflected_types(index)
+        .into_iter()
+        .filter_map(|data_type| {
+            module
+                .get_struct_type(data_type.get_type_information().get_name())
+                .map(|struct_type| {
+                    let fields = struct_type
+                        .get_field_types()
+                        .iter()
+                        .map(|it| it.print_to_string().to_string())
+                        .collect::<Vec<_>>();
+                    format!("{} = {{ {} }}", data_type.get_name(), fields.join(", "))
+                })
+        })
+        .collect::<Vec<_>>();
+    layouts.sort();
+    layouts
+}
+
+/// calculates the offset and size of the members of every reflected struct for the target
+pub(crate) fn get_instance_layouts(
+    module: &Module,
+    index: &Index,
+    target_data: &TargetData,
+) -> Vec<InstanceLayout> {
+    reflection_generator::get_reflected_types(index)
+        .into_iter()
+        .filter_map(|data_type| {
+            let struct_type =
+                module.get_struct_type(data_type.get_type_information().get_name())?;
+            let members = index
+                .get_container_members(data_type.get_name())
+                .into_iter()
+                .filter(|it| !it.is_temp() && !it.is_return())
+                .enumerate()
+                .filter_map(|(element, member)| {
+                    let field_type = struct_type.get_field_type_at_index(element as u32)?;
+                    Some(MemberLayout {
+                        name: member.get_name().to_string(),
+                        type_name: member.get_type_name().to_string(),
+                        offset: target_data.offset_of_element(&struct_type, element as u32)?,
+                        size: target_data.get_abi_size(&field_type),
+                    })
+                })
+                .collect();
+            Some(InstanceLayout {
+                name: data_type.get_name().to_string(),
+                size: target_data.get_abi_size(&struct_type),
+                members,
+            })
+        })
+        .collect()
+}
+
+/// collects the global variables and the members of POU instances declared in `RETAIN` blocks
+fn get_retained_variables(
+    module: &Module,
+    index: &Index,
+    target_data: &TargetData,
+    layouts: &[InstanceLayout],
+) -> Vec<RetainedVariable> {
+    let globals = index
+        .get_globals()
+        .values()
+        .filter(|it| it.is_retain() && !it.is_external())
+        .filter_map(|variable| {
+            let global = module.get_global(variable.get_name())?;
+            Some(RetainedVariable {
+                name: variable.get_name().to_string(),
+                type_name: variable.get_type_name().to_string(),
+                container: variable.get_name().to_string(),
+                offset: 0,
+                size: target_data
+                    .get_abi_size(&global.as_pointer_value().get_type().get_element_type()),
+            })
+        });
+    let members = layouts.iter().flat_map(|layout| {
+        layout
+            .members
+            .iter()
+            .filter(|member| {
+                index
+                    .find_member(&layout.name, &member.name)
+                    .map_or(false, |it| it.is_retain())
+            })
+            .map(|member| RetainedVariable {
+                name: format!("{}.{}", layout.name, member.name),
+                type_name: member.type_name.clone(),
+                container: layout.name.clone(),
+                offset: member.offset,
+                size: member.size,
+            })
+    });
+    globals.chain(members).collect()
+}
+
+/// returns the names of the POUs implemented by this application
+fn get_exports(index: &Index) -> Vec<String> {
+    let mut exports = index
+        .get_pous()
+        .values()
+        .filter(|it| {
+            matches!(it.get_linkage(), LinkageType::Internal | LinkageType::Weak)
+                && !it.is_generic()
+        })
+        //interfaces and their method prototypes are not implemented
+        .filter(|it| {
+            !matches!(
+                index.find_pou(it.get_container()),
+                Some(PouIndexEntry::Interface { .. })
+            )
+        })
+        .map(|it| it.get_name().to_string())
+        .collect::<Vec<_>>();
+    exports.sort();
+    exports
+}
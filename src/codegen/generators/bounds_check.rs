@@ -0,0 +1,100 @@
+//! runtime checks of the indexes of array accesses (`--check bounds`)
+//!
+//! every array access with an index that is not constant checks the index against the bounds of
+//! the accessed dimension before the element is addressed. An index out of bounds is passed to
+//! the application's `CheckBounds` function, which may report the violation (e.g. log it or stop
+//! the tasks) and returns the index accessed instead. Without such a function the application
+//! traps, before the access overwrites the memory next to the array (e.g. the process image).
+use inkwell::{
+    module::Module,
+    types::BasicType,
+    values::{FunctionValue, IntValue},
+    IntPredicate,
+};
+
+use super::llvm::Llvm;
+use crate::{ast::SourceRange, codegen::llvm_index::LlvmTypedIndex, diagnostics::Diagnostic};
+
+/// the function handling an index out of bounds:
+/// `FUNCTION CheckBounds : DINT VAR_INPUT index, lower, upper : DINT; END_VAR`
+pub const BOUNDS_CHECK_FN: &str = "CheckBounds";
+
+/// the intrinsic aborting the application if it declares no `CheckBounds` function
+const TRAP: &str = "llvm.trap";
+
+/// declares the trap intrinsic called for an index out of bounds
+/// Returns a new LLVM index containing the declared intrinsic
+pub fn declare_helpers<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+) -> Result<LlvmTypedIndex<'ink>, Diagnostic> {
+    let mut llvm_index = LlvmTypedIndex::default();
+    let trap = module.get_function(TRAP).unwrap_or_else(|| {
+        module.add_function(TRAP, llvm.context.void_type().fn_type(&[], false), None)
+    });
+    llvm_index.associate_implementation(TRAP, trap)?;
+    Ok(llvm_index)
+}
+
+/// checks the given `DINT` index into a dimension with the given `DINT` bounds and returns the
+/// index to access, the builder is positioned behind the check
+pub fn generate_bounds_check<'ink>(
+    llvm: &Llvm<'ink>,
+    llvm_index: &LlvmTypedIndex<'ink>,
+    function: FunctionValue<'ink>,
+    index: IntValue<'ink>,
+    (lower, upper): (IntValue<'ink>, IntValue<'ink>),
+    location: &SourceRange,
+) -> Result<IntValue<'ink>, Diagnostic> {
+    let builder = &llvm.builder;
+    let i32_type = llvm.i32_type();
+    let in_bounds = builder.build_and(
+        builder.build_int_compare(IntPredicate::SGE, index, lower, ""),
+        builder.build_int_compare(IntPredicate::SLE, index, upper, ""),
+        "in_bounds",
+    );
+    let checked_block = builder
+        .get_insert_block()
+        .ok_or_else(|| Diagnostic::missing_function(location.clone()))?;
+    let out_of_bounds_block = llvm.context.append_basic_block(function, "out_of_bounds");
+    let continue_block = llvm.context.append_basic_block(function, "in_bounds");
+    builder.build_conditional_branch(in_bounds, continue_block, out_of_bounds_block);
+
+    builder.position_at_end(out_of_bounds_block);
+    let handler = match llvm_index.find_associated_implementation(BOUNDS_CHECK_FN) {
+        Some(handler) => handler,
+        None => {
+            let trap = llvm_index
+                .find_associated_implementation(TRAP)
+                .ok_or_else(|| Diagnostic::missing_function(location.clone()))?;
+            builder.build_call(trap, &[], "");
+            builder.build_unreachable();
+            builder.position_at_end(continue_block);
+            return Ok(index);
+        }
+    };
+    let handler_type = handler.get_type();
+    let dint_type = i32_type.as_basic_type_enum();
+    if handler_type.get_return_type() != Some(dint_type)
+        || handler_type.get_param_types() != vec![dint_type; 3]
+    {
+        return Err(Diagnostic::invalid_bounds_check_function(
+            BOUNDS_CHECK_FN,
+            location.clone(),
+        ));
+    }
+    let handled_index = builder
+        .build_call(handler, &[index.into(), lower.into(), upper.into()], "")
+        .try_as_basic_value()
+        .unwrap_left()
+        .into_int_value();
+    builder.build_unconditional_branch(continue_block);
+
+    builder.position_at_end(continue_block);
+    let checked_index = builder.build_phi(i32_type, "checked_index");
+    checked_index.add_incoming(&[
+        (&index, checked_block),
+        (&handled_index, out_of_bounds_block),
+    ]);
+    Ok(checked_index.as_basic_value().into_int_value())
+}
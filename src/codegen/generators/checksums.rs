@@ -0,0 +1,72 @@
+//! declares the runtime's helpers behind the checksum builtins (e.g. `CRC16`, `CRC32`)
+//!
+//! every helper takes the address of a byte buffer and its length in bytes
+//! (`iN(i8* data, i32 length)`) and returns the checksum. The helpers are provided by the runtime
+//! (`rusty-runtime`) and are only declared if the application calls one of the builtins.
+use inkwell::{
+    context::Context,
+    module::{Linkage, Module},
+    types::FunctionType,
+    AddressSpace,
+};
+
+use super::llvm::Llvm;
+use crate::{
+    codegen::llvm_index::LlvmTypedIndex, diagnostics::Diagnostic, index::Index,
+    resolver::AstAnnotations,
+};
+
+/// the checksum builtins with the runtime's helper implementing them and the bits of their result
+const CHECKSUMS: [(&str, &str, u32); 4] = [
+    ("CRC16", "__rusty_crc16", 16),
+    ("CRC32", "__rusty_crc32", 32),
+    ("CHECKSUM_SUM", "__rusty_checksum_sum", 8),
+    ("CHECKSUM_XOR", "__rusty_checksum_xor", 8),
+];
+
+/// returns the name of the runtime's helper implementing the given checksum builtin or None if
+/// the function does not calculate a checksum
+pub fn find_checksum_helper(function_name: &str) -> Option<&'static str> {
+    CHECKSUMS
+        .iter()
+        .find(|(name, ..)| name.eq_ignore_ascii_case(function_name))
+        .map(|(_, helper, _)| *helper)
+}
+
+/// returns the signature of the helper calculating a checksum of the given bits
+fn get_helper_type<'ink>(context: &'ink Context, bits: u32) -> FunctionType<'ink> {
+    context.custom_width_int_type(bits).fn_type(
+        &[
+            context.i8_type().ptr_type(AddressSpace::Generic).into(),
+            context.i32_type().into(),
+        ],
+        false,
+    )
+}
+
+/// declares the helpers of the checksum builtins called by the application
+/// Returns a new LLVM index containing the declared helpers
+pub fn declare_helpers<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    annotations: &AstAnnotations,
+) -> Result<LlvmTypedIndex<'ink>, Diagnostic> {
+    let mut llvm_index = LlvmTypedIndex::default();
+    let called = annotations
+        .get_called_functions()
+        .filter(|it| index.get_builtin_function(it).is_some())
+        .collect::<Vec<_>>();
+    for (name, helper, bits) in CHECKSUMS {
+        let is_called = called.iter().any(|it| it.eq_ignore_ascii_case(name));
+        if is_called && module.get_function(helper).is_none() {
+            let function = module.add_function(
+                helper,
+                get_helper_type(llvm.context, bits),
+                Some(Linkage::External),
+            );
+            llvm_index.associate_implementation(helper, function)?;
+        }
+    }
+    Ok(llvm_index)
+}
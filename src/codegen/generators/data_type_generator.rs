@@ -1,4 +1,7 @@
-use std::convert::TryInto;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+};
 
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 /// the data_type_generator generates user defined data-types
@@ -8,9 +11,9 @@ use std::convert::TryInto;
 /// - Alias types
 /// - sized Strings
 use crate::ast::SourceRange;
-use crate::index::{Index, VariableIndexEntry, VariableType};
+use crate::index::{Index, VariableIndexEntry, VariableType, VTABLE_MEMBER};
 use crate::resolver::AstAnnotations;
-use crate::typesystem::{Dimension, StringEncoding, StructSource};
+use crate::typesystem::{Dimension, StringEncoding, StructSource, VTABLE_POINTER_TYPE};
 use crate::Diagnostic;
 use crate::{ast::AstStatement, typesystem::DataTypeInformation};
 use crate::{
@@ -21,12 +24,20 @@ use crate::{
     typesystem::DataType,
 };
 use inkwell::{
+    module::Module,
     types::{ArrayType, BasicType, BasicTypeEnum},
-    values::{BasicValue, BasicValueEnum},
+    values::{
+        ArrayValue, BasicValue, BasicValueEnum, FloatValue, IntValue, PointerValue, StructValue,
+        VectorValue,
+    },
     AddressSpace,
 };
 
-use super::{expression_generator::ExpressionCodeGenerator, llvm::Llvm};
+use super::{
+    expression_generator::ExpressionCodeGenerator,
+    llvm::Llvm,
+    vtable_generator::{self, get_class_vtable_name},
+};
 
 pub struct DataTypeGenerator<'ink, 'b> {
     llvm: &'b Llvm<'ink>,
@@ -43,6 +54,7 @@ pub struct DataTypeGenerator<'ink, 'b> {
 /// - Array type for arrays
 /// - array type for sized Strings
 pub fn generate_data_types<'ink>(
+    module: &Module<'ink>,
     llvm: &Llvm<'ink>,
     index: &Index,
     annotations: &AstAnnotations,
@@ -59,8 +71,7 @@ pub fn generate_data_types<'ink>(
         .get_types()
         .iter()
         .filter(|(_, it)| !it.get_type_information().is_generic(generator.index))
-        .map(|(a, b)| (a.as_str(), b))
-        .collect::<Vec<(&str, &DataType)>>();
+        .map(|(a, b)| (a.as_str(), b));
 
     let pou_types = generator
         .index
@@ -68,8 +79,11 @@ pub fn generate_data_types<'ink>(
         .values()
         .filter(|pou| !pou.is_generic() && !pou.is_action()) //actions dont get an own datatype, they use the one from their parent
         .map(|pou| pou.get_instance_struct_type(generator.index))
-        .map(|it| (it.get_name(), it))
-        .collect::<Vec<(&str, &DataType)>>();
+        .map(|it| (it.get_name(), it));
+
+    // user types and pou types depend on each other (e.g. a struct containing a function block
+    // instance), so they are sorted together
+    let types = sort_by_dependencies(types.chain(pou_types).collect(), generator.index);
 
     // first create all STUBs for struct types (empty structs)
     // and associate them in the llvm index
@@ -78,31 +92,20 @@ pub fn generate_data_types<'ink>(
             name: struct_name, ..
         } = user_type.get_type_information()
         {
-            generator
-                .types_index
-                .associate_type(name, llvm.create_struct_stub(struct_name).into())?;
-        }
-    }
-    // pou_types will always be struct
-    for (name, user_type) in &pou_types {
-        if let DataTypeInformation::Struct {
-            name: struct_name, ..
-        } = user_type.get_type_information()
-        {
-            generator
-                .types_index
-                .associate_pou_type(name, llvm.create_struct_stub(struct_name).into())?;
+            generator.associate_type(
+                name,
+                user_type,
+                llvm.create_struct_stub(struct_name).into(),
+            )?;
         }
     }
+    // the instances of polymorphic classes are initialized with a pointer to their vtable
+    vtable_generator::declare_class_vtables(module, llvm, index, &mut generator.types_index)?;
+
     // now create all other types (enum's, arrays, etc.)
     for (name, user_type) in &types {
         let gen_type = generator.create_type(name, user_type)?;
-        generator.types_index.associate_type(name, gen_type)?
-    }
-
-    for (name, user_type) in &pou_types {
-        let gen_type = generator.create_type(name, user_type)?;
-        generator.types_index.associate_pou_type(name, gen_type)?
+        generator.associate_type(name, user_type, gen_type)?
     }
 
     // now since all types should be available in the llvm index, we can think about constructing and associating
@@ -115,19 +118,100 @@ pub fn generate_data_types<'ink>(
                 .associate_initial_value(name, init_value)?;
         }
     }
-    for (name, user_type) in &pou_types {
-        generator.expand_opaque_types(user_type)?;
-        if let Some(init_value) = generator.generate_initial_value(user_type)? {
-            generator
-                .types_index
-                .associate_initial_value(name, init_value)?;
+
+    Ok(generator.types_index)
+}
+
+/// orders the given types so every type follows the types it depends on (e.g. the members of a
+/// struct come before the struct), independent types keep their order of declaration
+fn sort_by_dependencies<'idx>(
+    types: Vec<(&'idx str, &'idx DataType)>,
+    index: &Index,
+) -> Vec<(&'idx str, &'idx DataType)> {
+    let by_name = types
+        .iter()
+        .map(|(name, data_type)| (name.to_lowercase(), (*name, *data_type)))
+        .collect::<HashMap<_, _>>();
+    let mut visited = HashSet::new();
+    let mut sorted = Vec::with_capacity(types.len());
+    for (name, data_type) in &types {
+        visit_dependencies_first(name, data_type, &by_name, index, &mut visited, &mut sorted);
+    }
+    sorted
+}
+
+fn visit_dependencies_first<'idx>(
+    name: &'idx str,
+    data_type: &'idx DataType,
+    by_name: &HashMap<String, (&'idx str, &'idx DataType)>,
+    index: &Index,
+    visited: &mut HashSet<String>,
+    sorted: &mut Vec<(&'idx str, &'idx DataType)>,
+) {
+    //cyclic types are reported by the validation, visiting every type once stops here
+    if !visited.insert(name.to_lowercase()) {
+        return;
+    }
+    for dependency in get_dependencies(data_type, index) {
+        if let Some((dependency_name, dependency_type)) = by_name.get(&dependency.to_lowercase()) {
+            visit_dependencies_first(
+                dependency_name,
+                dependency_type,
+                by_name,
+                index,
+                visited,
+                sorted,
+            );
         }
     }
+    sorted.push((name, data_type));
+}
 
-    Ok(generator.types_index)
+/// returns the names of the types that need to be generated before the given type, pointers only
+/// need the stub of their inner type
+fn get_dependencies(data_type: &DataType, index: &Index) -> Vec<String> {
+    match data_type.get_type_information() {
+        DataTypeInformation::Struct { .. } => index
+            .get_container_members(data_type.get_name())
+            .into_iter()
+            .filter(|it| !it.is_temp())
+            .map(|it| it.get_type_name().to_string())
+            .collect(),
+        DataTypeInformation::Array {
+            inner_type_name, ..
+        } => vec![inner_type_name.clone()],
+        DataTypeInformation::SubRange {
+            referenced_type, ..
+        }
+        | DataTypeInformation::Alias {
+            referenced_type, ..
+        }
+        | DataTypeInformation::Enum {
+            referenced_type, ..
+        } => vec![referenced_type.clone()],
+        _ => vec![],
+    }
 }
 
 impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
+    /// associates the given llvm type with the given user type or instance type of a pou
+    fn associate_type(
+        &mut self,
+        name: &str,
+        data_type: &DataType,
+        llvm_type: BasicTypeEnum<'ink>,
+    ) -> Result<(), Diagnostic> {
+        if let DataTypeInformation::Struct {
+            source: StructSource::Pou(..),
+            ..
+        } = data_type.get_type_information()
+        {
+            self.types_index.associate_pou_type(name, llvm_type)
+        } else {
+            self.types_index.associate_type(name, llvm_type)
+        }
+    }
+
     /// generates the members of an opaque struct and associates its initial values
     fn expand_opaque_types(&mut self, data_type: &DataType) -> Result<(), Diagnostic> {
         let information = data_type.get_type_information();
@@ -144,7 +228,7 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
                 StructSource::Pou(..) => self
                     .types_index
                     .get_associated_pou_type(data_type.get_name()),
-                StructSource::OriginalDeclaration => {
+                StructSource::OriginalDeclaration | StructSource::Internal(_) => {
                     self.types_index.get_associated_type(data_type.get_name())
                 }
             }
@@ -169,7 +253,7 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
                 StructSource::Pou(..) => self
                     .types_index
                     .get_associated_pou_type(data_type.get_name()),
-                StructSource::OriginalDeclaration => {
+                StructSource::OriginalDeclaration | StructSource::Internal(_) => {
                     self.types_index.get_associated_type(data_type.get_name())
                 }
             },
@@ -183,7 +267,8 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
                 .and_then(|inner_type| self.create_type(inner_type_name, inner_type))
                 .and_then(|inner_type| self.create_nested_array_type(inner_type, dimensions))
                 .map(|it| it.as_basic_type_enum()),
-            DataTypeInformation::Integer { size, .. } => {
+            DataTypeInformation::Integer { size, .. }
+            | DataTypeInformation::FixedPoint { size, .. } => {
                 get_llvm_int_type(self.llvm.context, *size, name).map(|it| it.into())
             }
             DataTypeInformation::Enum {
@@ -260,6 +345,11 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
                     .iter()
                     .filter(|it| it.get_variable_type() != VariableType::Temp)
                     .map(|it| {
+                        if it.get_name() == VTABLE_MEMBER {
+                            return self
+                                .get_vtable_pointer(it)
+                                .map(|v| (it.get_qualified_name(), v));
+                        }
                         self.generate_initial_value_for_variable(it)
                             .and_then(|v| match v {
                                 Some(v) => Ok((it.get_qualified_name(), v)),
@@ -282,7 +372,7 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
                     StructSource::Pou(..) => self
                         .types_index
                         .get_associated_pou_type(data_type.get_name()),
-                    StructSource::OriginalDeclaration => {
+                    StructSource::OriginalDeclaration | StructSource::Internal(_) => {
                         self.types_index.get_associated_type(data_type.get_name())
                     }
                 }?
@@ -294,11 +384,16 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
                         .as_basic_value_enum(),
                 ))
             }
-            DataTypeInformation::Array { .. } => self.generate_array_initializer(
+            DataTypeInformation::Array {
+                inner_type_name, ..
+            } => match self.generate_array_initializer(
                 data_type,
                 |stmt| matches!(stmt, AstStatement::LiteralArray { .. }),
                 "LiteralArray",
-            ),
+            )? {
+                Some(initial_value) => Ok(Some(initial_value)),
+                None => self.generate_array_default(data_type, inner_type_name),
+            },
             DataTypeInformation::String { .. } => self.generate_array_initializer(
                 data_type,
                 |stmt| matches!(stmt, AstStatement::LiteralString { .. }),
@@ -315,6 +410,35 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
         }
     }
 
+    /// returns a pointer to the vtable of the class declaring the given vtable member, used to
+    /// initialize the class' instances
+    fn get_vtable_pointer(
+        &self,
+        vtable_member: &VariableIndexEntry,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let class_name = vtable_member
+            .get_qualified_name()
+            .rsplit_once('.')
+            .map_or("", |(class_name, _)| class_name);
+        let vtable = self
+            .types_index
+            .find_global_value(&get_class_vtable_name(class_name))
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!("Cannot find the vtable of '{}'", class_name),
+                    SourceRange::undefined(),
+                )
+            })?;
+        let pointer_type = self
+            .types_index
+            .get_associated_type(VTABLE_POINTER_TYPE)?
+            .into_pointer_type();
+        Ok(vtable
+            .as_pointer_value()
+            .const_cast(pointer_type)
+            .as_basic_value_enum())
+    }
+
     /// generates and returns an optional inital value at the given declared variable
     /// if no initial value is defined, it returns the initial value of the variable's
     /// datatype or Ok(None) if the type also has no declared default value
@@ -416,6 +540,32 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
         }
     }
 
+    /// generates the default of an array without initializer: every element is initialized
+    /// with the initial value of the array's inner type. Returns Ok(None) if the inner type
+    /// declares no initial value (so the array's default is zero)
+    fn generate_array_default(
+        &mut self,
+        data_type: &DataType,
+        inner_type_name: &str,
+    ) -> Result<Option<BasicValueEnum<'ink>>, Diagnostic> {
+        if !self.index.has_declared_initial_value(inner_type_name) {
+            return Ok(None);
+        }
+        let inner_type = self.index.get_type(inner_type_name)?;
+        let element = match self.generate_initial_value(inner_type)? {
+            Some(element) => element,
+            None => return Ok(None),
+        };
+        let array_type = self
+            .types_index
+            .get_associated_type(data_type.get_name())?
+            .into_array_type();
+        let elements = vec![element; array_type.len() as usize];
+        Ok(Some(
+            get_const_array(array_type.get_element_type(), &elements).as_basic_value_enum(),
+        ))
+    }
+
     /// creates the llvm types for a multi-dimensional array
     ///
     /// an array with multiple dimensions will be flattened into a long
@@ -471,3 +621,54 @@ pub fn get_default_for(basic_type: BasicTypeEnum) -> BasicValueEnum {
         BasicTypeEnum::VectorType(t) => t.const_zero().into(),
     }
 }
+
+/// creates a constant array of the given element type holding the given elements
+pub fn get_const_array<'ink>(
+    element_type: BasicTypeEnum<'ink>,
+    elements: &[BasicValueEnum<'ink>],
+) -> ArrayValue<'ink> {
+    match element_type {
+        BasicTypeEnum::ArrayType(t) => t.const_array(
+            elements
+                .iter()
+                .map(|it| it.into_array_value())
+                .collect::<Vec<ArrayValue>>()
+                .as_slice(),
+        ),
+        BasicTypeEnum::FloatType(t) => t.const_array(
+            elements
+                .iter()
+                .map(|it| it.into_float_value())
+                .collect::<Vec<FloatValue>>()
+                .as_slice(),
+        ),
+        BasicTypeEnum::IntType(t) => t.const_array(
+            elements
+                .iter()
+                .map(|it| it.into_int_value())
+                .collect::<Vec<IntValue>>()
+                .as_slice(),
+        ),
+        BasicTypeEnum::PointerType(t) => t.const_array(
+            elements
+                .iter()
+                .map(|it| it.into_pointer_value())
+                .collect::<Vec<PointerValue>>()
+                .as_slice(),
+        ),
+        BasicTypeEnum::StructType(t) => t.const_array(
+            elements
+                .iter()
+                .map(|it| it.into_struct_value())
+                .collect::<Vec<StructValue>>()
+                .as_slice(),
+        ),
+        BasicTypeEnum::VectorType(t) => t.const_array(
+            elements
+                .iter()
+                .map(|it| it.into_vector_value())
+                .collect::<Vec<VectorValue>>()
+                .as_slice(),
+        ),
+    }
+}
@@ -0,0 +1,99 @@
+//! instruments the generated code with calls to the hooks of the simulation's debugger
+//!
+//! every POU calls `__rusty_debug_enter(name, instance)` when it is entered and
+//! `__rusty_debug_leave()` before it returns, every statement is preceded by a call to
+//! `__rusty_debug_statement(file, offset)` passing the index of the statement's source file and
+//! the statement's offset in that file. The hooks are declared as external functions, they are
+//! resolved by the debugger when the module is loaded into the JIT.
+use inkwell::{
+    module::{Linkage, Module},
+    values::{BasicValueEnum, FunctionValue},
+    AddressSpace,
+};
+
+use super::llvm::Llvm;
+use crate::ast::SourceRange;
+
+pub const DEBUG_ENTER: &str = "__rusty_debug_enter";
+pub const DEBUG_STATEMENT: &str = "__rusty_debug_statement";
+pub const DEBUG_LEAVE: &str = "__rusty_debug_leave";
+
+/// the declared hooks and the index of the source file the generated code belongs to
+#[derive(Clone, Copy)]
+pub struct DebugHooks<'ink> {
+    enter: FunctionValue<'ink>,
+    statement: FunctionValue<'ink>,
+    leave: FunctionValue<'ink>,
+    file: u32,
+}
+
+impl<'ink> DebugHooks<'ink> {
+    /// declares the hooks in the given module (or reuses the existing declarations)
+    pub fn declare(module: &Module<'ink>, llvm: &Llvm<'ink>, file: u32) -> DebugHooks<'ink> {
+        let context = llvm.context;
+        let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+        let void_type = context.void_type();
+        let declare = |name: &str, function_type| {
+            module.get_function(name).unwrap_or_else(|| {
+                module.add_function(name, function_type, Some(Linkage::External))
+            })
+        };
+        DebugHooks {
+            enter: declare(
+                DEBUG_ENTER,
+                void_type.fn_type(&[i8_ptr_type.into(), i8_ptr_type.into()], false),
+            ),
+            statement: declare(
+                DEBUG_STATEMENT,
+                void_type.fn_type(
+                    &[context.i32_type().into(), context.i32_type().into()],
+                    false,
+                ),
+            ),
+            leave: declare(DEBUG_LEAVE, void_type.fn_type(&[], false)),
+            file,
+        }
+    }
+
+    /// reports the entered POU and its instance (if the POU has one) to the debugger
+    pub fn generate_enter(
+        &self,
+        llvm: &Llvm<'ink>,
+        pou_name: &str,
+        instance: Option<BasicValueEnum<'ink>>,
+    ) {
+        let i8_ptr_type = llvm.context.i8_type().ptr_type(AddressSpace::Generic);
+        let name = llvm
+            .builder
+            .build_global_string_ptr(pou_name, "__debug_pou_name")
+            .as_pointer_value();
+        let instance = match instance {
+            Some(BasicValueEnum::PointerValue(instance)) => {
+                llvm.builder.build_pointer_cast(instance, i8_ptr_type, "")
+            }
+            _ => i8_ptr_type.const_null(),
+        };
+        llvm.builder
+            .build_call(self.enter, &[name.into(), instance.into()], "");
+    }
+
+    /// reports the location of the statement about to be executed to the debugger
+    pub fn generate_statement(&self, llvm: &Llvm<'ink>, location: &SourceRange) {
+        let i32_type = llvm.context.i32_type();
+        llvm.builder.build_call(
+            self.statement,
+            &[
+                i32_type.const_int(self.file as u64, false).into(),
+                i32_type
+                    .const_int(location.get_start() as u64, false)
+                    .into(),
+            ],
+            "",
+        );
+    }
+
+    /// reports the return of the current POU to the debugger
+    pub fn generate_leave(&self, llvm: &Llvm<'ink>) {
+        llvm.builder.build_call(self.leave, &[], "");
+    }
+}
@@ -0,0 +1,667 @@
+//! generates the DWARF debug information of the compiled POUs, so the object code can be stepped
+//! through at the source level by debuggers like GDB or LLDB
+//!
+//! every POU is described by a subprogram, the instructions generated for a statement carry the
+//! statement's line and column and every variable is declared with its type: the locals and
+//! parameters of functions, the members of POU instances as well as global variables and program
+//! instances. Types are described like their C counterparts, the members of structs and POU
+//! instances are aligned to their natural alignment. Strings and arrays are named as declared in
+//! ST (e.g. `STRING[80]` or `ARRAY[1..5] OF INT`) and enums are described with the names of
+//! their elements. The date and time types keep their names, so the pretty printers generated
+//! alongside a debug build (see `crate::pretty_printers`) can show their values as ST literals
+//! (e.g. `T#5s`).
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use inkwell::{
+    context::Context,
+    debug_info::{
+        AsDIScope, DICompileUnit, DIFile, DIFlags, DIFlagsConstants, DIType, DWARFEmissionKind,
+        DWARFSourceLanguage, DebugInfoBuilder,
+    },
+    module::{FlagBehavior, Module},
+    values::{FunctionValue, GlobalValue, PointerValue},
+    AddressSpace,
+};
+use llvm_sys::{
+    debuginfo::{
+        LLVMCreateDIBuilder, LLVMDIBuilderCreateEnumerationType, LLVMDIBuilderCreateEnumerator,
+        LLVMDIBuilderFinalize, LLVMDisposeDIBuilder,
+    },
+    prelude::{LLVMDIBuilderRef, LLVMMetadataRef},
+};
+
+use super::llvm::Llvm;
+use crate::{
+    ast::{CompilationUnit, Implementation, LinkageType, PouType},
+    codegen::llvm_index::LlvmTypedIndex,
+    index::{Index, PouIndexEntry, VariableIndexEntry},
+    simulation::symbols::SourceFile,
+    typesystem::{
+        DataType, DataTypeInformation, StringEncoding, BYTE_TYPE, CHAR_TYPE, STRING_TYPE,
+        WCHAR_TYPE, WSTRING_TYPE,
+    },
+};
+
+const PRODUCER: &str = "RuSTy Structured Text Compiler";
+const POINTER_SIZE_IN_BITS: u64 = 64;
+
+//the DWARF encodings of the basic types
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_UNSIGNED: u32 = 0x07;
+const DW_ATE_UNSIGNED_CHAR: u32 = 0x08;
+const DW_ATE_UTF: u32 = 0x10;
+
+/// the debug information of a module and the types described so far
+pub struct DebugInfo<'ink> {
+    context: &'ink Context,
+    builder: DebugInfoBuilder<'ink>,
+    compile_unit: DICompileUnit<'ink>,
+    /// the described types and their size in bits by their lower case name
+    types: RefCell<HashMap<String, (DIType<'ink>, u64)>>,
+    /// the types currently being described, to break up recursive types
+    pending: RefCell<HashSet<String>>,
+    /// an intrinsic without any effect, called by statements that generate no other instruction
+    /// so they can still be located
+    marker: FunctionValue<'ink>,
+    /// creates the descriptions of enums, which cannot be created through `builder`
+    enumeration_builder: LLVMDIBuilderRef,
+}
+
+/// the debug information of the POUs and variables of a source file
+pub struct DebugFile<'ink, 'd> {
+    debug_info: &'d DebugInfo<'ink>,
+    file: DIFile<'ink>,
+    source: &'d SourceFile,
+}
+
+impl<'ink> DebugInfo<'ink> {
+    /// creates the debug information of the given module, `main_file` is the compile unit's file
+    pub fn new(context: &'ink Context, module: &Module<'ink>, main_file: &SourceFile) -> Self {
+        let i32_type = context.i32_type();
+        module.add_basic_value_flag(
+            "Debug Info Version",
+            FlagBehavior::Warning,
+            i32_type.const_int(3, false),
+        );
+        module.add_basic_value_flag(
+            "Dwarf Version",
+            FlagBehavior::Warning,
+            i32_type.const_int(4, false),
+        );
+        let (directory, file_name) = split_path(&main_file.path);
+        let (builder, compile_unit) = module.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::C,
+            file_name,
+            directory,
+            PRODUCER,
+            false,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+        let marker = module.add_function(
+            "llvm.donothing",
+            context.void_type().fn_type(&[], false),
+            None,
+        );
+        DebugInfo {
+            context,
+            builder,
+            compile_unit,
+            types: RefCell::new(HashMap::new()),
+            pending: RefCell::new(HashSet::new()),
+            marker,
+            enumeration_builder: unsafe { LLVMCreateDIBuilder(module.as_mut_ptr()) },
+        }
+    }
+
+    /// creates the debug information of the given source file
+    pub fn create_file<'d>(&'d self, source: &'d SourceFile) -> DebugFile<'ink, 'd> {
+        let (directory, file_name) = split_path(&source.path);
+        DebugFile {
+            debug_info: self,
+            file: self.builder.create_file(file_name, directory),
+            source,
+        }
+    }
+
+    /// resolves the described POUs, variables and types, has to be called before the module is
+    /// emitted
+    pub fn finalize(&self) {
+        self.builder.finalize();
+        unsafe { LLVMDIBuilderFinalize(self.enumeration_builder) }
+    }
+
+    /// returns the description of the given type or None if it cannot be described (e.g.
+    /// generics or a recursive reference to a type)
+    fn get_type(&self, type_name: &str, index: &Index) -> Option<DIType<'ink>> {
+        self.get_sized_type(type_name, index)
+            .map(|(di_type, _)| di_type)
+    }
+
+    /// returns the description of the given type with its size in bits, the size of a typedef
+    /// is the size of the type it refers to
+    fn get_sized_type(&self, type_name: &str, index: &Index) -> Option<(DIType<'ink>, u64)> {
+        let key = type_name.to_lowercase();
+        if let Some(described) = self.types.borrow().get(&key) {
+            return Some(*described);
+        }
+        if !self.pending.borrow_mut().insert(key.clone()) {
+            return None;
+        }
+        let described = index
+            .get_type(type_name)
+            .ok()
+            .and_then(|it| self.create_type(it, index));
+        self.pending.borrow_mut().remove(&key);
+        if let Some(described) = described {
+            self.types.borrow_mut().insert(key, described);
+        }
+        described
+    }
+
+    fn create_type(&self, data_type: &DataType, index: &Index) -> Option<(DIType<'ink>, u64)> {
+        let name = data_type.get_name();
+        match data_type.get_type_information() {
+            DataTypeInformation::Integer {
+                signed,
+                size,
+                semantic_size,
+                ..
+            } => {
+                let encoding = if *semantic_size == Some(1) {
+                    DW_ATE_BOOLEAN
+                } else if name.eq_ignore_ascii_case(CHAR_TYPE) {
+                    DW_ATE_UNSIGNED_CHAR
+                } else if name.eq_ignore_ascii_case(WCHAR_TYPE) {
+                    DW_ATE_UTF
+                } else if *signed {
+                    DW_ATE_SIGNED
+                } else {
+                    DW_ATE_UNSIGNED
+                };
+                self.create_basic_type(name, *size as u64, encoding)
+            }
+            DataTypeInformation::Float { size, .. } => {
+                self.create_basic_type(name, *size as u64, DW_ATE_FLOAT)
+            }
+            // the scale cannot be described, debuggers show the stored integer
+            DataTypeInformation::FixedPoint { size, .. } => {
+                self.create_basic_type(name, *size as u64, DW_ATE_SIGNED)
+            }
+            DataTypeInformation::String { size, encoding } => {
+                let (character, _) = match encoding {
+                    StringEncoding::Utf8 => {
+                        self.create_basic_type(CHAR_TYPE, 8, DW_ATE_UNSIGNED_CHAR)?
+                    }
+                    StringEncoding::Utf16 => self.create_basic_type(WCHAR_TYPE, 16, DW_ATE_UTF)?,
+                };
+                let length = size.as_int_value(index).ok()?;
+                let size_in_bits = length as u64 * character.get_size_in_bits();
+                let characters = self
+                    .builder
+                    .create_array_type(
+                        character,
+                        size_in_bits,
+                        get_alignment_in_bits(character) as u32,
+                        &[0..length],
+                    )
+                    .as_type();
+                Some(self.create_typedef(
+                    characters,
+                    size_in_bits,
+                    &get_declared_type_name(name, index),
+                ))
+            }
+            DataTypeInformation::Array {
+                inner_type_name,
+                dimensions,
+                ..
+            } => {
+                let (inner_type, inner_size) = self.get_sized_type(inner_type_name, index)?;
+                //the subscripts are given by their first index and their exclusive end
+                let subscripts = dimensions
+                    .iter()
+                    .map(|it| {
+                        let range = it.get_range_inclusive(index).ok()?;
+                        Some(*range.start()..*range.end() + 1)
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                let size_in_bits = subscripts
+                    .iter()
+                    .fold(inner_size, |size, it| size * (it.end - it.start) as u64);
+                let elements = self
+                    .builder
+                    .create_array_type(
+                        inner_type,
+                        size_in_bits,
+                        get_alignment_in_bits(inner_type) as u32,
+                        &subscripts,
+                    )
+                    .as_type();
+                Some(self.create_typedef(
+                    elements,
+                    size_in_bits,
+                    &get_declared_type_name(name, index),
+                ))
+            }
+            DataTypeInformation::Pointer {
+                inner_type_name, ..
+            } => {
+                //a pointer to a type that is being described points to bytes
+                let pointee = self
+                    .get_type(inner_type_name, index)
+                    .or_else(|| self.get_type(BYTE_TYPE, index))?;
+                Some((
+                    self.builder
+                        .create_pointer_type(
+                            name,
+                            pointee,
+                            POINTER_SIZE_IN_BITS,
+                            POINTER_SIZE_IN_BITS as u32,
+                            AddressSpace::Generic,
+                        )
+                        .as_type(),
+                    POINTER_SIZE_IN_BITS,
+                ))
+            }
+            DataTypeInformation::Struct { name, .. } => {
+                let file = self.compile_unit.get_file();
+                let scope = self.compile_unit.as_debug_info_scope();
+                let mut members = index
+                    .get_container_members(name)
+                    .into_iter()
+                    .filter(|it| !it.is_temp() && !it.is_return())
+                    .collect::<Vec<_>>();
+                members.sort_by_key(|it| it.get_location_in_parent());
+                let mut elements = Vec::new();
+                let mut size_in_bits = 0;
+                let mut alignment_in_bits = 8;
+                for member in members {
+                    let (member_type, member_size) =
+                        self.get_sized_type(member.get_type_name(), index)?;
+                    let member_alignment = get_alignment_in_bits(member_type);
+                    let offset = align_to(size_in_bits, member_alignment);
+                    elements.push(
+                        self.builder
+                            .create_member_type(
+                                scope,
+                                member.get_name(),
+                                file,
+                                0,
+                                member_size,
+                                member_alignment as u32,
+                                offset,
+                                DIFlags::PUBLIC,
+                                member_type,
+                            )
+                            .as_type(),
+                    );
+                    size_in_bits = offset + member_size;
+                    alignment_in_bits = alignment_in_bits.max(member_alignment);
+                }
+                let size_in_bits = align_to(size_in_bits, alignment_in_bits);
+                Some((
+                    self.builder
+                        .create_struct_type(
+                            scope,
+                            name,
+                            file,
+                            0,
+                            size_in_bits,
+                            alignment_in_bits as u32,
+                            DIFlags::PUBLIC,
+                            None,
+                            &elements,
+                            0,
+                            None,
+                            name,
+                        )
+                        .as_type(),
+                    size_in_bits,
+                ))
+            }
+            DataTypeInformation::Enum {
+                referenced_type,
+                elements,
+                ..
+            } => {
+                let (referenced_type, size_in_bits) =
+                    self.get_sized_type(referenced_type, index)?;
+                let values = elements
+                    .iter()
+                    .filter_map(|element| {
+                        index
+                            .find_enum_element(name, element)
+                            .and_then(|it| it.initial_value)
+                            .and_then(|it| {
+                                index
+                                    .get_const_expressions()
+                                    .get_constant_int_statement_value(&it)
+                                    .ok()
+                            })
+                            .map(|value| (element.as_str(), value as i64))
+                    })
+                    .collect::<Vec<_>>();
+                Some((
+                    self.create_enumeration_type(name, referenced_type, size_in_bits, &values),
+                    size_in_bits,
+                ))
+            }
+            DataTypeInformation::SubRange {
+                referenced_type, ..
+            }
+            | DataTypeInformation::Alias {
+                referenced_type, ..
+            } => {
+                let (referenced_type, size_in_bits) =
+                    self.get_sized_type(referenced_type, index)?;
+                Some(self.create_typedef(referenced_type, size_in_bits, name))
+            }
+            DataTypeInformation::Generic { .. } | DataTypeInformation::Void => None,
+        }
+    }
+
+    fn create_basic_type(
+        &self,
+        name: &str,
+        size_in_bits: u64,
+        encoding: u32,
+    ) -> Option<(DIType<'ink>, u64)> {
+        self.builder
+            .create_basic_type(name, size_in_bits, encoding, DIFlags::PUBLIC)
+            .ok()
+            .map(|it| (it.as_type(), size_in_bits))
+    }
+
+    /// describes the given type under another name, a typedef has no size of its own so the
+    /// given size of the referenced type is returned with it
+    fn create_typedef(
+        &self,
+        referenced_type: DIType<'ink>,
+        size_in_bits: u64,
+        name: &str,
+    ) -> (DIType<'ink>, u64) {
+        let typedef = self.builder.create_typedef(
+            referenced_type,
+            name,
+            self.compile_unit.get_file(),
+            0,
+            self.compile_unit.as_debug_info_scope(),
+            get_alignment_in_bits(referenced_type) as u32,
+        );
+        (typedef.as_type(), size_in_bits)
+    }
+
+    /// describes an enum with the names and values of its elements, so debuggers show the
+    /// element's name instead of its value. Inkwell cannot describe enumerations, they are
+    /// created through llvm's C api
+    fn create_enumeration_type(
+        &self,
+        name: &str,
+        referenced_type: DIType<'ink>,
+        size_in_bits: u64,
+        elements: &[(&str, i64)],
+    ) -> DIType<'ink> {
+        unsafe {
+            let mut enumerators = elements
+                .iter()
+                .map(|(element, value)| {
+                    LLVMDIBuilderCreateEnumerator(
+                        self.enumeration_builder,
+                        element.as_ptr() as *const _,
+                        element.len(),
+                        *value,
+                        0,
+                    )
+                })
+                .collect::<Vec<LLVMMetadataRef>>();
+            let enumeration = LLVMDIBuilderCreateEnumerationType(
+                self.enumeration_builder,
+                self.compile_unit.as_debug_info_scope().as_mut_ptr(),
+                name.as_ptr() as *const _,
+                name.len(),
+                self.compile_unit.get_file().as_mut_ptr(),
+                0,
+                size_in_bits,
+                get_alignment_in_bits(referenced_type) as u32,
+                enumerators.as_mut_ptr(),
+                enumerators.len() as u32,
+                referenced_type.as_mut_ptr(),
+            );
+            //a DIType only wraps the reference to its metadata
+            std::mem::transmute::<LLVMMetadataRef, DIType<'ink>>(enumeration)
+        }
+    }
+}
+
+impl<'ink> Drop for DebugInfo<'ink> {
+    fn drop(&mut self) {
+        unsafe { LLVMDisposeDIBuilder(self.enumeration_builder) }
+    }
+}
+
+impl<'ink, 'd> DebugFile<'ink, 'd> {
+    /// describes the function generated for the given implementation, the following instructions
+    /// are located at the implementation's start
+    pub fn register_function(
+        &self,
+        llvm: &Llvm<'ink>,
+        function: FunctionValue<'ink>,
+        implementation: &Implementation,
+        index: &Index,
+    ) {
+        let debug_info = self.debug_info;
+        let return_type = index
+            .find_return_type(&implementation.type_name)
+            .and_then(|it| debug_info.get_type(it.get_name(), index));
+        let subroutine_type =
+            debug_info
+                .builder
+                .create_subroutine_type(self.file, return_type, &[], DIFlags::PUBLIC);
+        let line = self.source.get_line(implementation.location.get_start());
+        let subprogram = debug_info.builder.create_function(
+            self.file.as_debug_info_scope(),
+            &implementation.name,
+            None,
+            self.file,
+            line,
+            subroutine_type,
+            false,
+            true,
+            line,
+            DIFlags::PUBLIC,
+            false,
+        );
+        function.set_subprogram(subprogram);
+        self.set_location(llvm, function, implementation.location.get_start());
+    }
+
+    /// locates the following instructions of the given function at the given offset of the file
+    pub fn set_location(&self, llvm: &Llvm<'ink>, function: FunctionValue<'ink>, offset: usize) {
+        if let Some(subprogram) = function.get_subprogram() {
+            let location = self.debug_info.builder.create_debug_location(
+                self.debug_info.context,
+                self.source.get_line(offset),
+                self.source.get_column(offset),
+                subprogram.as_debug_info_scope(),
+                None,
+            );
+            llvm.builder
+                .set_current_debug_location(self.debug_info.context, location);
+        }
+    }
+
+    /// generates an instruction without any effect at the current location, for statements that
+    /// generate no other instruction
+    pub fn generate_marker(&self, llvm: &Llvm<'ink>) {
+        llvm.builder.build_call(self.debug_info.marker, &[], "");
+    }
+
+    /// declares the given local variable or POU member stored at `storage`
+    pub fn declare_variable(
+        &self,
+        llvm: &Llvm<'ink>,
+        function: FunctionValue<'ink>,
+        variable: &VariableIndexEntry,
+        storage: PointerValue<'ink>,
+        index: &Index,
+    ) {
+        let debug_info = self.debug_info;
+        let subprogram = function.get_subprogram();
+        let variable_type = debug_info.get_type(variable.get_type_name(), index);
+        let block = llvm.builder.get_insert_block();
+        if let (Some(subprogram), Some(variable_type), Some(block)) =
+            (subprogram, variable_type, block)
+        {
+            let scope = subprogram.as_debug_info_scope();
+            let line = self.source.get_line(variable.source_location.get_start());
+            let local_variable = debug_info.builder.create_auto_variable(
+                scope,
+                variable.get_name(),
+                self.file,
+                line,
+                variable_type,
+                true,
+                DIFlags::ZERO,
+                get_alignment_in_bits(variable_type) as u32,
+            );
+            let location =
+                debug_info
+                    .builder
+                    .create_debug_location(debug_info.context, line, 0, scope, None);
+            debug_info.builder.insert_declare_at_end(
+                storage,
+                Some(local_variable),
+                None,
+                location,
+                block,
+            );
+        }
+    }
+
+    /// declares the global variables and program instances of the given unit
+    pub fn declare_global_variables(
+        &self,
+        unit: &CompilationUnit,
+        index: &Index,
+        llvm_index: &LlvmTypedIndex<'ink>,
+    ) {
+        let globals = unit
+            .global_vars
+            .iter()
+            .filter(|it| it.linkage != LinkageType::External)
+            .flat_map(|it| it.variables.iter())
+            .filter_map(|it| index.find_global_variable(&it.name));
+        let programs = unit
+            .units
+            .iter()
+            .filter(|it| it.pou_type == PouType::Program && it.linkage != LinkageType::External)
+            .filter_map(|it| match index.find_pou(&it.name) {
+                Some(PouIndexEntry::Program {
+                    instance_variable, ..
+                }) => Some(instance_variable),
+                _ => None,
+            });
+        for variable in globals.chain(programs) {
+            if let Some(global) = llvm_index.find_global_value(variable.get_qualified_name()) {
+                self.declare_global_variable(variable, global, index);
+            }
+        }
+    }
+
+    fn declare_global_variable(
+        &self,
+        variable: &VariableIndexEntry,
+        global: GlobalValue<'ink>,
+        index: &Index,
+    ) {
+        let debug_info = self.debug_info;
+        if let Some(variable_type) = debug_info.get_type(variable.get_type_name(), index) {
+            let expression = debug_info.builder.create_global_variable_expression(
+                self.file.as_debug_info_scope(),
+                variable.get_name(),
+                &global.get_name().to_string_lossy(),
+                self.file,
+                self.source.get_line(variable.source_location.get_start()),
+                variable_type,
+                false,
+                None,
+                None,
+                get_alignment_in_bits(variable_type) as u32,
+            );
+            global.set_metadata(
+                expression.as_metadata_value(debug_info.context),
+                debug_info.context.get_kind_id("dbg"),
+            );
+        }
+    }
+}
+
+/// returns the directory and the name of the given file
+fn split_path(path: &str) -> (&str, &str) {
+    let path = Path::new(path);
+    let directory = path.parent().and_then(Path::to_str).unwrap_or_default();
+    let file_name = path
+        .file_name()
+        .and_then(|it| it.to_str())
+        .unwrap_or_default();
+    (directory, file_name)
+}
+
+/// returns the alignment of the given type, basic types are aligned to their size
+fn get_alignment_in_bits(di_type: DIType) -> u64 {
+    match di_type.get_align_in_bits() {
+        0 => di_type.get_size_in_bits().clamp(8, POINTER_SIZE_IN_BITS),
+        alignment => alignment as u64,
+    }
+}
+
+/// returns the name of the given type as declared in ST, strings are named with their length
+/// (e.g. `STRING[80]`) and arrays with their bounds (e.g. `ARRAY[1..5] OF INT`)
+pub(crate) fn get_declared_type_name(type_name: &str, index: &Index) -> String {
+    match index.find_effective_type_info(type_name) {
+        //the terminator is not part of the declared length
+        Some(DataTypeInformation::String { size, encoding }) => {
+            let length = size.as_int_value(index).unwrap_or(1) - 1;
+            match encoding {
+                StringEncoding::Utf8 => format!("{}[{}]", STRING_TYPE, length),
+                StringEncoding::Utf16 => format!("{}[{}]", WSTRING_TYPE, length),
+            }
+        }
+        Some(DataTypeInformation::Array {
+            inner_type_name,
+            dimensions,
+            ..
+        }) => {
+            let bounds = dimensions
+                .iter()
+                .filter_map(|it| it.get_range_inclusive(index).ok())
+                .map(|it| format!("{}..{}", it.start(), it.end()))
+                .collect::<Vec<_>>();
+            format!(
+                "ARRAY[{}] OF {}",
+                bounds.join(", "),
+                get_declared_type_name(inner_type_name, index)
+            )
+        }
+        _ => type_name.to_string(),
+    }
+}
+
+fn align_to(offset: u64, alignment: u64) -> u64 {
+    (offset + alignment - 1) / alignment * alignment
+}
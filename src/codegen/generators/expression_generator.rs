@@ -1,11 +1,11 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 use crate::{
-    ast::{self, DirectAccessType, SourceRange},
+    ast::{self, DirectAccessType, PouType, SourceRange},
     codegen::llvm_typesystem,
     diagnostics::{Diagnostic, INTERNAL_LLVM_ERROR},
     index::{
         ArgumentType, ImplementationIndexEntry, Index, PouIndexEntry, VariableIndexEntry,
-        VariableType,
+        VariableType, SUPER_KEYWORD, VTABLE_MEMBER,
     },
     resolver::{AnnotationMap, AstAnnotations, StatementAnnotation},
     typesystem::{
@@ -14,14 +14,14 @@ use crate::{
 };
 use inkwell::{
     builder::Builder,
-    types::{BasicType, BasicTypeEnum},
+    types::{AnyTypeEnum, BasicType, BasicTypeEnum, FunctionType},
     values::{
-        ArrayValue, BasicMetadataValueEnum, BasicValue, BasicValueEnum, FloatValue, GlobalValue,
-        IntValue, PointerValue, StructValue, VectorValue,
+        BasicMetadataValueEnum, BasicValue, BasicValueEnum, CallableValue, FunctionValue,
+        GlobalValue, IntValue, PointerValue,
     },
     AddressSpace, FloatPredicate, IntPredicate,
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     ast::{flatten_expression_list, AstStatement, Operator},
@@ -29,14 +29,24 @@ use crate::{
         llvm_index::LlvmTypedIndex,
         llvm_typesystem::{cast_if_needed, get_llvm_int_type},
     },
-    typesystem::{DataType, DataTypeInformation},
+    typesystem::{DataType, DataTypeInformation, StructSource},
 };
 
-use super::{llvm::Llvm, statement_generator::FunctionContext};
+use super::{
+    bounds_check, checksums,
+    data_type_generator::get_const_array,
+    fast_math,
+    llvm::Llvm,
+    pou_generator, random, saturation,
+    statement_generator::FunctionContext,
+    string_conversions::{self, NumberKind, StringConversion},
+    vectorization,
+    vtable_generator::get_class_vtable_name,
+};
 
 /// the generator for expressions
 pub struct ExpressionCodeGenerator<'a, 'b> {
-    llvm: &'b Llvm<'a>,
+    pub llvm: &'b Llvm<'a>,
     index: &'b Index,
     annotations: &'b AstAnnotations,
     llvm_index: &'b LlvmTypedIndex<'a>,
@@ -50,6 +60,12 @@ pub struct ExpressionCodeGenerator<'a, 'b> {
 
     // the function on how to obtain the the length to use for the string
     string_len_provider: fn(type_length_declaration: usize, actual_length: usize) -> usize,
+
+    /// the copies of the arguments and the result of the inlined calls being generated, by the
+    /// variable's qualified name
+    inlined_variables: HashMap<String, PointerValue<'a>>,
+    /// the functions whose inlined calls are being generated, their nested calls are not inlined
+    inlined_functions: Vec<String>,
 }
 
 /// context information to generate a parameter
@@ -89,6 +105,8 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
             temp_variable_prefix: "load_".to_string(),
             temp_variable_suffix: "".to_string(),
             string_len_provider: |_, actual_length| actual_length, //when generating string-literals in a body, use the actual length
+            inlined_variables: HashMap::new(),
+            inlined_functions: Vec::new(),
         }
     }
 
@@ -114,6 +132,8 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
             temp_variable_prefix: "load_".to_string(),
             temp_variable_suffix: "".to_string(),
             string_len_provider: |type_length_declaration, _| type_length_declaration, //when generating string-literals in declarations, use the declared length
+            inlined_variables: HashMap::new(),
+            inlined_functions: Vec::new(),
         }
     }
 
@@ -180,6 +200,10 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
                 {
                     // constant propagation
                     self.generate_constant_expression(qualified_name, expression)
+                } else if self.is_instance_reference(expression) {
+                    // THIS is not stored anywhere, it is the instance passed to the current pou
+                    self.generate_instance_pointer(expression)
+                        .map(BasicValueEnum::PointerValue)
                 } else {
                     // general reference generation
                     let l_value = self.generate_element_pointer(expression)?;
@@ -288,7 +312,26 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
                 self.generate_expression(left)?,
                 self.generate_expression(right)?,
             ))
+        } else if let (
+            DataTypeInformation::FixedPoint {
+                size,
+                fraction_bits,
+                ..
+            },
+            true,
+        ) = (ltype, rtype.is_fixed_point())
+        {
+            self.generate_fixed_point_binary_expression(
+                operator,
+                left,
+                right,
+                *size,
+                *fraction_bits,
+            )
         } else if ltype.is_float() && rtype.is_float() {
+            if let Some(value) = self.generate_multiply_add(operator, left, right)? {
+                return Ok(value);
+            }
             Ok(self.create_llvm_float_binary_expression(
                 operator,
                 self.generate_expression(left)?,
@@ -301,11 +344,135 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
             self.create_llvm_binary_expression_for_pointer(
                 operator, left, ltype, right, rtype, expression,
             )
+        } else if ltype.is_string()
+            && rtype.is_string()
+            && operator.is_comparison_operator()
+            && !self.has_compare_functions(ltype.get_name(), operator)
+        {
+            self.generate_string_comparison(operator, left, right)
         } else {
             self.create_llvm_generic_binary_expression(operator, left, right, expression)
         }
     }
 
+    /// generates the arithmetic of two values of the same fixed-point type. Products and quotients
+    /// are computed with twice the bits, so the scaled intermediate result does not overflow
+    fn generate_fixed_point_binary_expression(
+        &self,
+        operator: &Operator,
+        left: &AstStatement,
+        right: &AstStatement,
+        size: u32,
+        fraction_bits: u32,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let left_value = self.generate_expression(left)?;
+        let right_value = self.generate_expression(right)?;
+        let fixed_type = get_llvm_int_type(self.llvm.context, size, "Fixed")?;
+        let wide_type = get_llvm_int_type(self.llvm.context, size * 2, "Fixed")?;
+        let fraction_bits = wide_type.const_int(fraction_bits as u64, false);
+        let widen = |it: BasicValueEnum<'ink>| {
+            builder.build_int_s_extend(it.into_int_value(), wide_type, "")
+        };
+
+        let wide_result = match operator {
+            Operator::Multiplication => {
+                let product = builder.build_int_mul(widen(left_value), widen(right_value), "");
+                builder.build_right_shift(product, fraction_bits, true, "")
+            }
+            Operator::Division => {
+                let dividend = builder.build_left_shift(widen(left_value), fraction_bits, "");
+                builder.build_int_signed_div(dividend, widen(right_value), "")
+            }
+            _ => {
+                return Ok(self.create_llvm_int_binary_expression(
+                    operator,
+                    left_value,
+                    right_value,
+                ))
+            }
+        };
+        Ok(builder
+            .build_int_truncate(wide_result, fixed_type, "tmpVar")
+            .into())
+    }
+
+    /// generates a product added to or subtracted from a value (`a * b + c`, `c + a * b` and
+    /// `a * b - c`) as `llvm.fmuladd` if the current function uses fast-math (see `fast_math`),
+    /// returns `None` for any other expression or if the operands are of different types
+    fn generate_multiply_add(
+        &self,
+        operator: &Operator,
+        left: &AstStatement,
+        right: &AstStatement,
+    ) -> Result<Option<BasicValueEnum<'ink>>, Diagnostic> {
+        if !self.function_context.map_or(false, |it| it.fast_math) {
+            return Ok(None);
+        }
+        let is_product = |it: &AstStatement| {
+            matches!(
+                it,
+                AstStatement::BinaryExpression {
+                    operator: Operator::Multiplication,
+                    ..
+                }
+            )
+        };
+        let (product, addend, subtracted) = match operator {
+            Operator::Plus if is_product(left) => (left, right, false),
+            Operator::Plus if is_product(right) => (right, left, false),
+            Operator::Minus if is_product(left) => (left, right, true),
+            _ => return Ok(None),
+        };
+        let (factor, multiplier) = match product {
+            AstStatement::BinaryExpression { left, right, .. } => (left.as_ref(), right.as_ref()),
+            _ => return Ok(None),
+        };
+
+        //all operands have to be computed in the same float type
+        let mut type_names = HashSet::new();
+        for operand in [product, factor, multiplier, addend] {
+            let type_hint = self.get_type_hint_for(operand)?;
+            type_names.insert(
+                self.index
+                    .get_intrinsic_type_by_name(type_hint.get_name())
+                    .get_name()
+                    .to_uppercase(),
+            );
+        }
+        let multiply_add = match type_names.iter().next() {
+            Some(type_name) if type_names.len() == 1 => fast_math::find_multiply_add(type_name)
+                .and_then(|it| self.llvm_index.find_associated_implementation(it)),
+            _ => None,
+        };
+        let multiply_add = match multiply_add {
+            Some(it) => it,
+            None => return Ok(None),
+        };
+
+        let factor = self.generate_expression(factor)?;
+        let multiplier = self.generate_expression(multiplier)?;
+        let mut addend = self.generate_expression(addend)?;
+        if subtracted {
+            addend = self
+                .llvm
+                .builder
+                .build_float_neg(addend.into_float_value(), "tmpVar")
+                .into();
+        }
+        let value = self
+            .llvm
+            .builder
+            .build_call(
+                multiply_add,
+                &[factor.into(), multiplier.into(), addend.into()],
+                "tmpVar",
+            )
+            .try_as_basic_value()
+            .left();
+        Ok(value)
+    }
+
     fn generate_directaccess(
         &self,
         elements: &[AstStatement],
@@ -512,6 +679,10 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
             );
         }
 
+        if let Some(value) = self.generate_inlined_call(pou, parameters)? {
+            return Ok(value);
+        }
+
         let function_name = implementation.get_call_name();
         let arguments_list = self.generate_pou_call_arguments_list(
             pou,
@@ -523,23 +694,56 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
         )?;
 
         let builder = &self.llvm.builder;
-        let function = self
+        let no_callable_implementation = || {
+            Diagnostic::codegen_error(
+                &format!(
+                    "No callable implementation associated to {:?}",
+                    function_name
+                ),
+                operator.get_location(),
+            )
+        };
+        //abstract methods have a function type but no implementation
+        let function_type = self
             .llvm_index
-            .find_associated_implementation(function_name) //using the non error option to control the output error
-            .ok_or_else(|| {
-                Diagnostic::codegen_error(
-                    &format!(
-                        "No callable implementation associated to {:?}",
-                        function_name
-                    ),
-                    operator.get_location(),
-                )
-            })?;
+            .find_associated_implementation_type(function_name) //using the non error option to control the output error
+            .ok_or_else(no_callable_implementation)?;
+        //methods of polymorphic classes are called through the vtable of the instance
+        let callable = match self.generate_virtual_method_pointer(
+            pou,
+            &arguments_list,
+            function_type,
+            operator,
+        )? {
+            Some(method) => CallableValue::try_from(method)
+                .map_err(|_| Diagnostic::cannot_generate_call_statement(operator))?,
+            None => self
+                .llvm_index
+                .find_associated_implementation(function_name)
+                .ok_or_else(no_callable_implementation)?
+                .into(),
+        };
+        let convention = self.index.find_calling_convention(function_name);
+        let (arguments_list, result) = if convention.is_some() {
+            let result = self.allocate_aggregate_result(function_name)?;
+            (
+                self.generate_indirect_arguments(arguments_list, result),
+                result,
+            )
+        } else {
+            (arguments_list, None)
+        };
         //If the target is a function, declare the struct locally
         //Assign all parameters into the struct values
-        let call_result = builder
-            .build_call(function, &arguments_list, "call")
-            .try_as_basic_value();
+        let call = builder.build_call(callable, &arguments_list, "call");
+        if let Some(convention) = convention {
+            call.set_call_convention(pou_generator::get_calling_convention_id(convention));
+        }
+        //the aggregate result was written to the memory passed by the caller
+        if let Some(result) = result {
+            return Ok(builder.build_load(result, ""));
+        }
+        let call_result = call.try_as_basic_value();
 
         // we return an uninitialized int pointer for void methods :-/
         // dont deref it!!
@@ -554,6 +758,201 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
         Ok(value)
     }
 
+    /// generates the expression of a function marked with `{attribute 'inline'}` in place of its
+    /// call, the expression reads copies of the arguments. Returns `None` if the call is not
+    /// inlined because the function is not inlined, is already being inlined or has aggregate
+    /// parameters or result
+    fn generate_inlined_call(
+        &self,
+        pou: &PouIndexEntry,
+        parameters: &Option<AstStatement>,
+    ) -> Result<Option<BasicValueEnum<'ink>>, Diagnostic> {
+        let function_name = pou.get_name().to_lowercase();
+        let expression = match self.index.find_inline_expression(&function_name) {
+            Some(expression) if !self.inlined_functions.contains(&function_name) => expression,
+            _ => return Ok(None),
+        };
+        let mut variables = Vec::new();
+        for member in self.index.get_container_members(&function_name) {
+            let llvm_type = self
+                .llvm_index
+                .get_associated_type(member.get_type_name())?;
+            if pou_generator::is_aggregate(llvm_type) {
+                return Ok(None);
+            }
+            variables.push((member, llvm_type));
+        }
+        let arguments = parameters
+            .as_ref()
+            .map(ast::flatten_expression_list)
+            .unwrap_or_default();
+        let declared_parameters = variables
+            .iter()
+            .map(|(member, _)| *member)
+            .filter(|it| it.is_parameter())
+            .collect::<Vec<_>>();
+        if arguments.len() != declared_parameters.len() {
+            return Ok(None);
+        }
+
+        let mut values = self
+            .generate_function_argument_values(pou, arguments, declared_parameters)?
+            .into_iter();
+        let mut inlined_variables = self.inlined_variables.clone();
+        for (member, llvm_type) in variables {
+            let copy = self.llvm.builder.build_alloca(llvm_type, member.get_name());
+            //the result may be read before it is assigned, like the one of a called function
+            let value = if member.is_parameter() {
+                values.next().unwrap_or_else(|| llvm_type.const_zero())
+            } else {
+                llvm_type.const_zero()
+            };
+            self.llvm.builder.build_store(copy, value);
+            inlined_variables.insert(member.get_qualified_name().to_lowercase(), copy);
+        }
+        let mut inlined_functions = self.inlined_functions.clone();
+        inlined_functions.push(function_name);
+
+        let generator = ExpressionCodeGenerator {
+            llvm: self.llvm,
+            index: self.index,
+            annotations: self.annotations,
+            llvm_index: self.llvm_index,
+            function_context: self.function_context,
+            temp_variable_prefix: self.temp_variable_prefix.clone(),
+            temp_variable_suffix: self.temp_variable_suffix.clone(),
+            string_len_provider: self.string_len_provider,
+            inlined_variables,
+            inlined_functions,
+        };
+        generator.generate_expression(expression).map(Some)
+    }
+
+    /// allocates the memory receiving the result of a function with a calling convention, `None`
+    /// if the function does not return an aggregate
+    fn allocate_aggregate_result(
+        &self,
+        function_name: &str,
+    ) -> Result<Option<PointerValue<'ink>>, Diagnostic> {
+        let result_type = match self.index.find_return_type(function_name) {
+            Some(data_type) => Some(self.llvm_index.get_associated_type(data_type.get_name())?),
+            None => None,
+        };
+        Ok(result_type
+            .filter(|it| pou_generator::is_aggregate(*it))
+            .map(|it| self.llvm.builder.build_alloca(it, "result")))
+    }
+
+    /// passes the aggregate arguments of a function with a calling convention as pointers to
+    /// copies, the pointer to the memory receiving an aggregate result is passed first
+    fn generate_indirect_arguments(
+        &self,
+        arguments: Vec<BasicMetadataValueEnum<'ink>>,
+        result: Option<PointerValue<'ink>>,
+    ) -> Vec<BasicMetadataValueEnum<'ink>> {
+        let builder = &self.llvm.builder;
+        let copy = |value: BasicValueEnum<'ink>| {
+            let copy = builder.build_alloca(value.get_type(), "");
+            builder.build_store(copy, value);
+            copy
+        };
+        let mut indirect = arguments
+            .into_iter()
+            .map(|it| match it {
+                BasicMetadataValueEnum::ArrayValue(value) => copy(value.into()).into(),
+                BasicMetadataValueEnum::StructValue(value) => copy(value.into()).into(),
+                it => it,
+            })
+            .collect::<Vec<BasicMetadataValueEnum>>();
+        if let Some(result) = result {
+            indirect.insert(0, result.into());
+        }
+        indirect
+    }
+
+    /// returns the pointer to the method to call if the given method belongs to a polymorphic
+    /// class, the pointer is loaded from the vtable of the instance the method is called on (the
+    /// first argument), so the method overridden by the instance's actual class is called.
+    /// Returns `None` if the method is called directly, like the base implementation called with
+    /// `SUPER^.method()`.
+    fn generate_virtual_method_pointer(
+        &self,
+        method: &PouIndexEntry,
+        arguments: &[BasicMetadataValueEnum<'ink>],
+        function_type: FunctionType<'ink>,
+        operator: &AstStatement,
+    ) -> Result<Option<PointerValue<'ink>>, Diagnostic> {
+        if let AstStatement::QualifiedReference { elements, .. } = operator {
+            if matches!(elements.first(), Some(AstStatement::PointerAccess { reference, .. })
+                if self.is_super_reference(reference))
+            {
+                return Ok(None);
+            }
+        }
+        let class_name = match method {
+            PouIndexEntry::Method {
+                parent_pou_name, ..
+            } if self
+                .index
+                .find_member(parent_pou_name, VTABLE_MEMBER)
+                .is_some() =>
+            {
+                parent_pou_name
+            }
+            _ => return Ok(None),
+        };
+        let method_name = ast::Pou::calc_return_name(method.get_name());
+        let slot = self
+            .index
+            .get_virtual_methods(class_name)
+            .iter()
+            .position(|it| {
+                ast::Pou::calc_return_name(it.get_name()).eq_ignore_ascii_case(method_name)
+            });
+        let vtable = self
+            .llvm_index
+            .find_global_value(&get_class_vtable_name(class_name));
+        let (slot, vtable, instance) = match (slot, vtable, arguments.first()) {
+            (Some(slot), Some(vtable), Some(BasicMetadataValueEnum::PointerValue(instance))) => {
+                (slot, vtable, *instance)
+            }
+            _ => return Ok(None),
+        };
+
+        let builder = &self.llvm.builder;
+        let cannot_access_vtable = |_: ()| {
+            Diagnostic::codegen_error(
+                &format!("Cannot access the vtable of '{}'", class_name),
+                operator.get_location(),
+            )
+        };
+        let vtable_member = builder
+            .build_struct_gep(instance, 0, VTABLE_MEMBER)
+            .map_err(cannot_access_vtable)?;
+        let vtable_ptr = builder.build_pointer_cast(
+            builder.build_load(vtable_member, "").into_pointer_value(),
+            vtable.as_pointer_value().get_type(),
+            "",
+        );
+        //an instance without a vtable (e.g. zero-initialized by its caller) uses its static type's
+        let vtable_ptr = builder
+            .build_select(
+                builder.build_is_null(vtable_ptr, ""),
+                vtable.as_pointer_value(),
+                vtable_ptr,
+                "",
+            )
+            .into_pointer_value();
+        let method_member = builder
+            .build_struct_gep(vtable_ptr, slot as u32, "")
+            .map_err(cannot_access_vtable)?;
+        Ok(Some(builder.build_pointer_cast(
+            builder.build_load(method_member, "").into_pointer_value(),
+            function_type.ptr_type(AddressSpace::Generic),
+            "",
+        )))
+    }
+
     /// generates the argument list for a call to a pou
     /// a call to a function returns a Vec with all parameters for the function,
     /// a call to a Program/Fb will return a Vec with a single struct carrying all parameters
@@ -588,8 +987,19 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
         } else {
             // no function
             let (class_ptr, call_ptr) = match pou {
-                PouIndexEntry::Method { .. } => {
-                    let class_ptr = self.generate_element_pointer(operator)?;
+                PouIndexEntry::Method {
+                    parent_pou_name, ..
+                } => {
+                    //the instance may be an instance of a class extending the method's class
+                    let class_type = self
+                        .llvm_index
+                        .get_associated_type(parent_pou_name)?
+                        .ptr_type(AddressSpace::Generic);
+                    let class_ptr = self.llvm.builder.build_pointer_cast(
+                        self.generate_element_pointer(operator)?,
+                        class_type,
+                        "",
+                    );
                     let call_ptr = self.allocate_function_struct_instance(
                         implementation.get_call_name(),
                         operator,
@@ -630,6 +1040,20 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
         arguments: Vec<&AstStatement>,
         declared_parameters: Vec<&VariableIndexEntry>,
     ) -> Result<Vec<BasicMetadataValueEnum<'ink>>, Diagnostic> {
+        Ok(self
+            .generate_function_argument_values(pou, arguments, declared_parameters)?
+            .into_iter()
+            .map(BasicValueEnum::into)
+            .collect::<Vec<BasicMetadataValueEnum>>())
+    }
+
+    /// generates the values of the given arguments in the order of the declared parameters
+    fn generate_function_argument_values(
+        &self,
+        pou: &PouIndexEntry,
+        arguments: Vec<&AstStatement>,
+        declared_parameters: Vec<&VariableIndexEntry>,
+    ) -> Result<Vec<BasicValueEnum<'ink>>, Diagnostic> {
         let mut result = Vec::new();
         for (idx, param_statement) in arguments.into_iter().enumerate() {
             let (location, param_statement) =
@@ -657,7 +1081,13 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
                     }
                 })?;
 
-            let argument: BasicValueEnum = if declaration_type.is_by_ref() {
+            let is_vla = self
+                .index
+                .find_effective_type_info(type_name)
+                .map_or(false, DataTypeInformation::is_vla);
+            let argument: BasicValueEnum = if is_vla {
+                self.generate_variable_length_array_argument(param_statement, type_name)?
+            } else if declaration_type.is_by_ref() {
                 self.generate_argument_by_ref(param_statement, type_name)?
             } else {
                 //pass by val
@@ -667,10 +1097,7 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
             result.push((location, argument));
         }
         result.sort_by(|(idx_a, _), (idx_b, _)| idx_a.cmp(idx_b));
-        Ok(result
-            .into_iter()
-            .map(|(_, v)| v.into())
-            .collect::<Vec<BasicMetadataValueEnum>>())
+        Ok(result.into_iter().map(|(_, v)| v).collect())
     }
 
     fn generate_argument_by_val(
@@ -682,10 +1109,76 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
             Some(type_info) if type_info.information.is_string() => {
                 self.generate_string_argument(type_info, param_statement)?
             }
+            //a REFERENCE TO parameter is bound to the argument
+            Some(DataType {
+                information:
+                    DataTypeInformation::Pointer {
+                        auto_deref: true, ..
+                    },
+                ..
+            }) => self.generate_argument_by_ref(param_statement, type_name)?,
             _ => self.generate_expression(param_statement)?,
         })
     }
 
+    /// generates the fat pointer passed for a variable-length array parameter, it points to the
+    /// argument's first element and holds the bounds of the argument's dimensions. A
+    /// variable-length array argument is passed on as it is
+    fn generate_variable_length_array_argument(
+        &self,
+        argument: &AstStatement,
+        type_name: &str,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let location = argument.get_location();
+        let dimensions = match self
+            .annotations
+            .get_type_or_void(argument, self.index)
+            .get_type_information()
+        {
+            DataTypeInformation::Array { dimensions, .. } => dimensions,
+            it if it.is_vla() => return self.generate_expression(argument),
+            it => {
+                return Err(Diagnostic::codegen_error(
+                    &format!("Cannot pass '{}' as a variable-length array", it.get_name()),
+                    location,
+                ))
+            }
+        };
+        let bounds = dimensions
+            .iter()
+            .flat_map(|it| [&it.start_offset, &it.end_offset])
+            .map(|it| {
+                it.as_int_value(self.index)
+                    .map(|bound| self.llvm.i32_type().const_int(bound as u64, true))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|it| Diagnostic::codegen_error(&it, location.clone()))?;
+
+        let fat_pointer_type = self
+            .llvm_index
+            .get_associated_type(type_name)?
+            .into_struct_type();
+        let data_type = fat_pointer_type
+            .get_field_type_at_index(0)
+            .ok_or_else(|| Diagnostic::unknown_type(type_name, location.clone()))?
+            .into_pointer_type();
+        let builder = &self.llvm.builder;
+        let fat_pointer = builder.build_alloca(fat_pointer_type, "");
+        let data =
+            builder.build_pointer_cast(self.generate_element_pointer(argument)?, data_type, "");
+        builder.build_store(
+            self.llvm
+                .get_member_pointer_from_struct(fat_pointer, 0, "data", &location)?,
+            data,
+        );
+        builder.build_store(
+            self.llvm
+                .get_member_pointer_from_struct(fat_pointer, 1, "dimensions", &location)?,
+            self.llvm.i32_type().const_array(&bounds),
+        );
+        Ok(builder.build_load(fat_pointer, ""))
+    }
+
     /// Before passing a string to a function, it is copied to a new string with the
     /// appropriate size for the called function
     fn generate_string_argument(
@@ -733,6 +1226,7 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
             Ok(self.llvm.builder.build_alloca(v_type, ""))
         } else {
             self.generate_element_pointer(argument)
+                .map(|it| self.cast_to_super_class(it, type_name))
                 .or_else::<Diagnostic, _>(|_| {
                     //passed a literal to byref parameter?
                     //TODO: find more defensive solution - check early
@@ -745,6 +1239,34 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
         .map(Into::into)
     }
 
+    /// casts the given pointer to the pointer type `pointer_type_name` if it points to a class, so
+    /// an instance of an extending class can be passed where an instance of its super class is
+    /// expected. Other pointers are returned unchanged.
+    fn cast_to_super_class(
+        &self,
+        pointer: PointerValue<'ink>,
+        pointer_type_name: &str,
+    ) -> PointerValue<'ink> {
+        let points_to_class = matches!(
+            self.index.find_effective_type_info(pointer_type_name),
+            Some(DataTypeInformation::Pointer { inner_type_name, .. })
+                if matches!(
+                    self.index.find_effective_type_info(inner_type_name),
+                    Some(DataTypeInformation::Struct {
+                        source: StructSource::Pou(PouType::Class),
+                        ..
+                    })
+                )
+        );
+        match self.llvm_index.find_associated_type(pointer_type_name) {
+            Some(BasicTypeEnum::PointerType(pointer_type)) if points_to_class => self
+                .llvm
+                .builder
+                .build_pointer_cast(pointer, pointer_type, ""),
+            _ => pointer,
+        }
+    }
+
     /// generates a new instance of a function called `function_name` and returns a PointerValue to it
     ///
     /// - `function_name` the name of the function as registered in the index
@@ -897,7 +1419,8 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
                         .build_alloca(temp_type, "empty_varinout")
                         .as_basic_value_enum()
                 } else {
-                    self.generate_element_pointer(expression)?
+                    let pointer = self.generate_element_pointer(expression)?;
+                    self.cast_to_super_class(pointer, parameter.get_name())
                         .as_basic_value_enum()
                 };
 
@@ -958,6 +1481,34 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
         }
     }
 
+    /// generates a pointer to the given `REFERENCE TO` variable itself instead of the variable it
+    /// refers to, so the reference can be bound to another variable (`r REF= x`)
+    pub fn generate_reference_pointer(
+        &self,
+        reference_statement: &AstStatement,
+    ) -> Result<PointerValue<'ink>, Diagnostic> {
+        let (qualifiers, reference) = match reference_statement {
+            AstStatement::QualifiedReference { elements, .. } => match elements.split_last() {
+                Some((last, qualifiers)) => (qualifiers, last),
+                None => (&[][..], reference_statement),
+            },
+            _ => (&[][..], reference_statement),
+        };
+        let mut qualifier: Option<PointerValue> = None;
+        for e in qualifiers {
+            qualifier = Some(self.do_generate_element_pointer(qualifier, e)?);
+        }
+        if let AstStatement::Reference { name, .. } = reference {
+            //the reference is not dereferenced
+            self.create_llvm_pointer_value_for_reference(qualifier.as_ref(), name, reference)
+        } else {
+            Err(Diagnostic::codegen_error(
+                &format!("Cannot bind {:?} with REF=", reference_statement),
+                reference_statement.get_location(),
+            ))
+        }
+    }
+
     pub fn generate_element_pointer_from_elements(
         &self,
         elements: &[AstStatement],
@@ -989,6 +1540,12 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
             AstStatement::ArrayAccess {
                 reference, access, ..
             } => self.generate_element_pointer_for_array(qualifier.as_ref(), reference, access),
+            //THIS^ and SUPER^ are the current instance itself
+            AstStatement::PointerAccess { reference, .. }
+                if self.is_instance_reference(reference) =>
+            {
+                self.generate_instance_pointer(reference)
+            }
             AstStatement::PointerAccess { reference, .. } => self
                 .do_generate_element_pointer(qualifier, reference)
                 .map(|it| self.deref(it)),
@@ -1062,13 +1619,62 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
         match self.annotations.get(context) {
             Some(StatementAnnotation::Variable { qualified_name, .. })
             | Some(StatementAnnotation::Program { qualified_name, .. }) => self
-                .llvm_index
-                .find_loaded_associated_variable_value(qualified_name)
+                .inlined_variables
+                .get(&qualified_name.to_lowercase())
+                .copied()
+                .or_else(|| {
+                    self.llvm_index
+                        .find_loaded_associated_variable_value(qualified_name)
+                })
                 .ok_or_else(|| Diagnostic::unresolved_reference(name, offset.clone())),
             _ => Err(Diagnostic::unresolved_reference(name, offset.clone())),
         }
     }
 
+    /// returns true if the given statement is the `SUPER` keyword of a method (and not a
+    /// variable called `SUPER`)
+    fn is_super_reference(&self, statement: &AstStatement) -> bool {
+        matches!(statement, AstStatement::Reference { name, .. } if name.eq_ignore_ascii_case(SUPER_KEYWORD))
+            && self.is_instance_reference(statement)
+    }
+
+    /// returns true if the given statement is `THIS` or `SUPER`
+    fn is_instance_reference(&self, statement: &AstStatement) -> bool {
+        matches!(
+            self.annotations.get(statement),
+            Some(StatementAnnotation::Instance { .. })
+        )
+    }
+
+    /// generates the pointer to the current instance (`THIS` or `SUPER`), the instance is the
+    /// first parameter of the current method or function block
+    fn generate_instance_pointer(
+        &self,
+        statement: &AstStatement,
+    ) -> Result<PointerValue<'ink>, Diagnostic> {
+        let function_context = self.get_function_context(statement)?;
+        let pointer_type = match self.annotations.get(statement) {
+            Some(StatementAnnotation::Instance { resulting_type }) => self
+                .llvm_index
+                .get_associated_type(resulting_type)?
+                .into_pointer_type(),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    &format!("Cannot generate an instance pointer for {:?}", statement),
+                    statement.get_location(),
+                ))
+            }
+        };
+        let instance = function_context
+            .function
+            .get_first_param()
+            .ok_or_else(|| Diagnostic::missing_function(statement.get_location()))?;
+        Ok(self
+            .llvm
+            .builder
+            .build_pointer_cast(instance.into_pointer_value(), pointer_type, ""))
+    }
+
     fn deref(&self, accessor_ptr: PointerValue<'ink>) -> PointerValue<'ink> {
         self.llvm
             .load_pointer(&accessor_ptr, "deref")
@@ -1122,6 +1728,37 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
             .map_err(|it| Diagnostic::codegen_error(&it, access_expression.get_location()))?;
 
         let access_value = self.generate_expression(access_expression)?;
+        if let Some(function_context) = self.get_bounds_checking_context(access_value) {
+            let end_offset = dimension
+                .end_offset
+                .as_int_value(self.index)
+                .map_err(|it| Diagnostic::codegen_error(&it, access_expression.get_location()))?;
+            let index = llvm_typesystem::cast_if_needed(
+                self.llvm,
+                self.index,
+                self.llvm_index,
+                self.index.get_type(DINT_TYPE)?,
+                access_value,
+                self.get_type_hint_for(access_expression)?,
+                access_expression,
+            )?;
+            let i32_type = self.llvm.i32_type();
+            let start = i32_type.const_int(start_offset as u64, true);
+            let end = i32_type.const_int(end_offset as u64, true);
+            let index = bounds_check::generate_bounds_check(
+                self.llvm,
+                self.llvm_index,
+                function_context.function,
+                index.into_int_value(),
+                (start, end),
+                &access_expression.get_location(),
+            )?;
+            return Ok(self
+                .llvm
+                .builder
+                .build_int_sub(index, start, "")
+                .as_basic_value_enum());
+        }
         //If start offset is not 0, adjust the current statement with an add operation
         let result = if start_offset != 0 {
             let access_int_value = access_value.into_int_value();
@@ -1146,6 +1783,22 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
         )
     }
 
+    /// returns the function context if the given index of an array access is checked at runtime,
+    /// constant indexes and the indexes in the `CheckBounds` function itself are not checked
+    fn get_bounds_checking_context(
+        &self,
+        index: BasicValueEnum<'ink>,
+    ) -> Option<&'b FunctionContext<'ink>> {
+        self.function_context.filter(|it| {
+            it.check_bounds
+                && matches!(index, BasicValueEnum::IntValue(it) if !it.is_const())
+                && !it
+                    .linking_context
+                    .get_call_name()
+                    .eq_ignore_ascii_case(bounds_check::BOUNDS_CHECK_FN)
+        })
+    }
+
     /// generates a gep statement for a array-reference with an optional qualifier
     ///
     /// - `qualifier` an optional qualifier for a reference (e.g. myStruct.x[2] where myStruct is the qualifier for x)
@@ -1160,6 +1813,11 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
         //Load the reference
         self.do_generate_element_pointer(qualifier.cloned(), reference)
             .and_then(|lvalue| {
+                if let Some((_, ndims)) =
+                    self.get_type_hint_info_for(reference)?.get_vla_dimensions()
+                {
+                    return self.generate_element_pointer_for_vla(lvalue, ndims, access);
+                }
                 if let DataTypeInformation::Array { dimensions, .. } =
                     self.get_type_hint_info_for(reference)?
                 {
@@ -1269,24 +1927,115 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
             })
     }
 
-    /// generates the result of an pointer binary-expression
+    /// generates a gep statement for an element of a variable-length array, the index into the
+    /// array's data is calculated from the bounds passed along with it
     ///
-    /// - `operator` the binary operator
-    /// - `left` the left side of the binary expression, needs to be an pointer/int-value
-    /// - `left_type` DataTypeInformation of the left side
-    /// - `right` the right side of the binary expression, needs to be an pointer/int-value
-    /// - `right_type` DataTypeInformation of the right side
-    /// - `expression` the binary expression
-    pub fn create_llvm_binary_expression_for_pointer(
+    /// - `fat_pointer` a pointer to the array's fat pointer
+    /// - `ndims` the array's number of dimensions
+    /// - `access` the accessor expression (the expression between the brackets: reference[access])
+    fn generate_element_pointer_for_vla(
         &self,
-        operator: &Operator,
-        left: &AstStatement,
-        left_type: &DataTypeInformation,
-        right: &AstStatement,
-        right_type: &DataTypeInformation,
-        expression: &AstStatement,
-    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
-        let left_expr = self.generate_expression(left)?;
+        fat_pointer: PointerValue<'ink>,
+        ndims: usize,
+        access: &AstStatement,
+    ) -> Result<PointerValue<'ink>, Diagnostic> {
+        let statements = access.get_as_list();
+        if statements.len() != ndims {
+            return Err(Diagnostic::codegen_error(
+                "Invalid array access",
+                access.get_location(),
+            ));
+        }
+        let location = access.get_location();
+        let builder = &self.llvm.builder;
+        let data = builder
+            .build_load(
+                self.llvm
+                    .get_member_pointer_from_struct(fat_pointer, 0, "data", &location)?,
+                "",
+            )
+            .into_pointer_value();
+        let dimensions =
+            self.llvm
+                .get_member_pointer_from_struct(fat_pointer, 1, "dimensions", &location)?;
+
+        // the elements are stored row by row, accessing [i, j] of an ARRAY[l1..u1, l2..u2]
+        // means to access (i - l1) * (u2 - l2 + 1) + (j - l2)
+        let i32_type = self.llvm.i32_type();
+        let mut index_access = i32_type.const_zero();
+        for (dimension, statement) in statements.iter().enumerate() {
+            let position = (2 * dimension) as u64;
+            let lower = self.load_vla_bound(dimensions, i32_type.const_int(position, false))?;
+            let upper = self.load_vla_bound(dimensions, i32_type.const_int(position + 1, false))?;
+            let length = builder.build_int_add(
+                builder.build_int_sub(upper, lower, ""),
+                i32_type.const_int(1, false),
+                "",
+            );
+            let access_value = cast_if_needed(
+                self.llvm,
+                self.index,
+                self.llvm_index,
+                self.index.get_type(DINT_TYPE)?,
+                self.generate_expression(statement)?,
+                self.get_type_hint_for(statement)?,
+                statement,
+            )?
+            .into_int_value();
+            let access_value = match self.get_bounds_checking_context(access_value.into()) {
+                Some(function_context) => bounds_check::generate_bounds_check(
+                    self.llvm,
+                    self.llvm_index,
+                    function_context.function,
+                    access_value,
+                    (lower, upper),
+                    &statement.get_location(),
+                )?,
+                None => access_value,
+            };
+            index_access = builder.build_int_add(
+                builder.build_int_mul(index_access, length, ""),
+                builder.build_int_sub(access_value, lower, ""),
+                "",
+            );
+        }
+        self.llvm
+            .load_array_element(data, &[index_access], "tmpVar")
+    }
+
+    /// loads the bound at the given position of a variable-length array's `dimensions`, the
+    /// lower and upper bound of every dimension are stored next to each other
+    fn load_vla_bound(
+        &self,
+        dimensions: PointerValue<'ink>,
+        position: IntValue<'ink>,
+    ) -> Result<IntValue<'ink>, Diagnostic> {
+        let bound = self.llvm.load_array_element(
+            dimensions,
+            &[self.llvm.i32_type().const_zero(), position],
+            "",
+        )?;
+        Ok(self.llvm.builder.build_load(bound, "").into_int_value())
+    }
+
+    /// generates the result of an pointer binary-expression
+    ///
+    /// - `operator` the binary operator
+    /// - `left` the left side of the binary expression, needs to be an pointer/int-value
+    /// - `left_type` DataTypeInformation of the left side
+    /// - `right` the right side of the binary expression, needs to be an pointer/int-value
+    /// - `right_type` DataTypeInformation of the right side
+    /// - `expression` the binary expression
+    pub fn create_llvm_binary_expression_for_pointer(
+        &self,
+        operator: &Operator,
+        left: &AstStatement,
+        left_type: &DataTypeInformation,
+        right: &AstStatement,
+        right_type: &DataTypeInformation,
+        expression: &AstStatement,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let left_expr = self.generate_expression(left)?;
         let right_expr = self.generate_expression(right)?;
 
         let result = match operator {
@@ -1800,6 +2549,13 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
             })
     }
 
+    /// returns the actual data type of the given statement, ignoring its type hint
+    pub fn get_type_info_for(&self, statement: &AstStatement) -> &DataTypeInformation {
+        self.annotations
+            .get_type_or_void(statement, self.index)
+            .get_type_information()
+    }
+
     /// generates a struct literal value with the given value assignments (ExpressionList)
     fn generate_literal_struct(
         &self,
@@ -1977,44 +2733,7 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
         }
 
         //TODO Validation: fail with compile-error if value cannot be converted into... correctly
-        let array_value = match llvm_type {
-            BasicTypeEnum::ArrayType(_) => llvm_type.into_array_type().const_array(
-                v.iter()
-                    .map(|it| it.into_array_value())
-                    .collect::<Vec<ArrayValue>>()
-                    .as_slice(),
-            ),
-            BasicTypeEnum::FloatType(_) => llvm_type.into_float_type().const_array(
-                v.iter()
-                    .map(|it| it.into_float_value())
-                    .collect::<Vec<FloatValue>>()
-                    .as_slice(),
-            ),
-            BasicTypeEnum::IntType(_) => llvm_type.into_int_type().const_array(
-                v.iter()
-                    .map(|it| it.into_int_value())
-                    .collect::<Vec<IntValue>>()
-                    .as_slice(),
-            ),
-            BasicTypeEnum::PointerType(_) => llvm_type.into_pointer_type().const_array(
-                v.iter()
-                    .map(|it| it.into_pointer_value())
-                    .collect::<Vec<PointerValue>>()
-                    .as_slice(),
-            ),
-            BasicTypeEnum::StructType(_) => llvm_type.into_struct_type().const_array(
-                v.iter()
-                    .map(|it| it.into_struct_value())
-                    .collect::<Vec<StructValue>>()
-                    .as_slice(),
-            ),
-            BasicTypeEnum::VectorType(_) => llvm_type.into_vector_type().const_array(
-                v.iter()
-                    .map(|it| it.into_vector_value())
-                    .collect::<Vec<VectorValue>>()
-                    .as_slice(),
-            ),
-        };
+        let array_value = get_const_array(llvm_type, &v);
         Ok(array_value.as_basic_value_enum())
     }
 
@@ -2142,6 +2861,1555 @@ impl<'ink, 'b> ExpressionCodeGenerator<'ink, 'b> {
         Ok(value)
     }
 
+    /// returns true if the EQUAL_XXX, LESS_XXX or GREATER_XXX functions the given comparison
+    /// would call are declared for the given type
+    fn has_compare_functions(&self, type_name: &str, operator: &Operator) -> bool {
+        let operators: &[Operator] = match operator {
+            Operator::NotEqual => &[Operator::Equal],
+            Operator::LessOrEqual => &[Operator::Equal, Operator::Less],
+            Operator::GreaterOrEqual => &[Operator::Equal, Operator::Greater],
+            _ => std::slice::from_ref(operator),
+        };
+        operators.iter().all(|it| {
+            crate::typesystem::get_equals_function_name_for(type_name, it)
+                .and_then(|name| self.index.find_pou_implementation(&name))
+                .is_some()
+        })
+    }
+
+    /// returns a pointer to the characters of the given STRING or WSTRING expression, values
+    /// that are not stored in a variable (e.g. a call's result) are copied into a temporary one
+    pub fn generate_string_pointer(
+        &self,
+        statement: &AstStatement,
+    ) -> Result<PointerValue<'ink>, Diagnostic> {
+        match statement {
+            AstStatement::Reference { .. }
+            | AstStatement::QualifiedReference { .. }
+            | AstStatement::ArrayAccess { .. }
+            | AstStatement::PointerAccess { .. } => self.generate_element_pointer(statement),
+            _ => match self.generate_expression(statement)? {
+                BasicValueEnum::PointerValue(pointer) => Ok(pointer),
+                value => {
+                    let pointer = self.llvm.builder.build_alloca(value.get_type(), "");
+                    self.llvm.builder.build_store(pointer, value);
+                    Ok(pointer)
+                }
+            },
+        }
+    }
+
+    /// compares two STRINGs or WSTRINGs character by character (unsigned) up to their first
+    /// 0-terminator. If one of the strings has no terminator, the characters following its end
+    /// count as 0, so strings of different lengths can be compared
+    fn generate_string_comparison(
+        &self,
+        operator: &Operator,
+        left: &AstStatement,
+        right: &AstStatement,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        // the comparison's result (-1, 0 or 1) is compared to 0 according to the operator
+        let predicate = match operator {
+            Operator::Equal => IntPredicate::EQ,
+            Operator::NotEqual => IntPredicate::NE,
+            Operator::Less => IntPredicate::SLT,
+            Operator::Greater => IntPredicate::SGT,
+            Operator::LessOrEqual => IntPredicate::SLE,
+            Operator::GreaterOrEqual => IntPredicate::SGE,
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    &format!("Invalid operator {} for strings", operator),
+                    left.get_location(),
+                ))
+            }
+        };
+        let builder = &self.llvm.builder;
+        let context = self.llvm.context;
+        let function = self.get_function_context(left)?.function;
+        let i32_type = context.i32_type();
+
+        let left_ptr = self.generate_string_pointer(left)?;
+        let right_ptr = self.generate_string_pointer(right)?;
+        let get_length = |pointer: PointerValue<'ink>, statement: &AstStatement| match pointer
+            .get_type()
+            .get_element_type()
+        {
+            AnyTypeEnum::ArrayType(array) => Ok(array.len()),
+            _ => Err(Diagnostic::codegen_error(
+                "Cannot compare a string without a length",
+                statement.get_location(),
+            )),
+        };
+        let left_length = get_length(left_ptr, left)?;
+        let right_length = get_length(right_ptr, right)?;
+        let load_char = |pointer: PointerValue<'ink>, index: IntValue<'ink>| {
+            let char_ptr = unsafe {
+                builder.build_in_bounds_gep(pointer, &[i32_type.const_zero(), index], "")
+            };
+            builder.build_load(char_ptr, "").into_int_value()
+        };
+
+        let start_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
+        let loop_block = context.append_basic_block(function, "");
+        let compare_block = context.append_basic_block(function, "");
+        let terminator_block = context.append_basic_block(function, "");
+        let next_block = context.append_basic_block(function, "");
+        let differ_block = context.append_basic_block(function, "");
+        let end_block = context.append_basic_block(function, "");
+        let result_block = context.append_basic_block(function, "");
+        builder.build_unconditional_branch(loop_block);
+
+        // loop over the characters both strings have
+        builder.position_at_end(loop_block);
+        let index = builder.build_phi(i32_type, "");
+        let current = index.as_basic_value().into_int_value();
+        let common_length = i32_type.const_int(left_length.min(right_length) as u64, false);
+        let is_end = builder.build_int_compare(IntPredicate::EQ, current, common_length, "");
+        builder.build_conditional_branch(is_end, end_block, compare_block);
+
+        builder.position_at_end(compare_block);
+        let left_char = load_char(left_ptr, current);
+        let right_char = load_char(right_ptr, current);
+        let is_equal = builder.build_int_compare(IntPredicate::EQ, left_char, right_char, "");
+        builder.build_conditional_branch(is_equal, terminator_block, differ_block);
+
+        // equal characters, both strings end if it is the terminator
+        builder.position_at_end(terminator_block);
+        let is_terminator = builder.build_int_compare(
+            IntPredicate::EQ,
+            left_char,
+            left_char.get_type().const_zero(),
+            "",
+        );
+        builder.build_conditional_branch(is_terminator, result_block, next_block);
+
+        builder.position_at_end(next_block);
+        let next = builder.build_int_add(current, i32_type.const_int(1, false), "");
+        builder.build_unconditional_branch(loop_block);
+        index.add_incoming(&[(&i32_type.const_zero(), start_block), (&next, next_block)]);
+
+        // the first different character decides
+        builder.position_at_end(differ_block);
+        let is_less = builder.build_int_compare(IntPredicate::ULT, left_char, right_char, "");
+        let differ_result = builder
+            .build_select(
+                is_less,
+                i32_type.const_all_ones(),
+                i32_type.const_int(1, false),
+                "",
+            )
+            .into_int_value();
+        builder.build_unconditional_branch(result_block);
+
+        // one of the strings ended without a terminator, the longer one is bigger unless its
+        // next character is the terminator
+        builder.position_at_end(end_block);
+        let end_result = if left_length == right_length {
+            i32_type.const_zero()
+        } else {
+            let (longer, bigger) = if left_length > right_length {
+                (left_ptr, i32_type.const_int(1, false))
+            } else {
+                (right_ptr, i32_type.const_all_ones())
+            };
+            let next_char = load_char(longer, common_length);
+            let is_terminator = builder.build_int_compare(
+                IntPredicate::EQ,
+                next_char,
+                next_char.get_type().const_zero(),
+                "",
+            );
+            builder
+                .build_select(is_terminator, i32_type.const_zero(), bigger, "")
+                .into_int_value()
+        };
+        let final_end_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
+        builder.build_unconditional_branch(result_block);
+
+        builder.position_at_end(result_block);
+        let result = builder.build_phi(i32_type, "");
+        result.add_incoming(&[
+            (&i32_type.const_zero(), terminator_block),
+            (&differ_result, differ_block),
+            (&end_result, final_end_block),
+        ]);
+        let comparison = builder.build_int_compare(
+            predicate,
+            result.as_basic_value().into_int_value(),
+            i32_type.const_zero(),
+            "",
+        );
+        Ok(comparison.as_basic_value_enum())
+    }
+
+    /// concatenates the given strings from left to right into a temporary string that is
+    /// big enough to hold all of them and returns a pointer to it
+    pub fn generate_string_concatenation(
+        &self,
+        operands: &[&AstStatement],
+    ) -> Result<PointerValue<'ink>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let context = self.llvm.context;
+        let i32_type = context.i32_type();
+
+        let mut strings = Vec::with_capacity(operands.len());
+        for operand in operands {
+            let pointer = self.generate_string_pointer(operand)?;
+            if let AnyTypeEnum::ArrayType(array) = pointer.get_type().get_element_type() {
+                strings.push((pointer, array, *operand));
+            } else {
+                return Err(Diagnostic::codegen_error(
+                    "Cannot concatenate a string without a length",
+                    operand.get_location(),
+                ));
+            }
+        }
+        let (_, first_type, first) = strings.first().ok_or_else(|| {
+            Diagnostic::codegen_error(
+                "Expected at least one string to concatenate",
+                SourceRange::undefined(),
+            )
+        })?;
+        let char_type = first_type.get_element_type().into_int_type();
+        if let Some((_, _, operand)) = strings
+            .iter()
+            .find(|(_, array, _)| array.get_element_type() != char_type.as_basic_type_enum())
+        {
+            return Err(Diagnostic::codegen_error(
+                "Cannot concatenate strings of different widths",
+                operand.get_location(),
+            ));
+        }
+        let function = self.get_function_context(first)?.function;
+
+        // every operand contributes at most its characters without the terminator
+        let length: u32 = strings.iter().map(|(_, array, _)| array.len() - 1).sum();
+        let result = builder.build_alloca(char_type.array_type(length + 1), "");
+        let char_at = |pointer: PointerValue<'ink>, index: IntValue<'ink>| unsafe {
+            builder.build_in_bounds_gep(pointer, &[i32_type.const_zero(), index], "")
+        };
+
+        let mut offset = i32_type.const_zero();
+        for (pointer, array, _) in strings {
+            let start_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
+            let loop_block = context.append_basic_block(function, "");
+            let check_block = context.append_basic_block(function, "");
+            let copy_block = context.append_basic_block(function, "");
+            let end_block = context.append_basic_block(function, "");
+            builder.build_unconditional_branch(loop_block);
+
+            // copy the characters up to the terminator or the operand's length
+            builder.position_at_end(loop_block);
+            let index = builder.build_phi(i32_type, "");
+            let target = builder.build_phi(i32_type, "");
+            let current = index.as_basic_value().into_int_value();
+            let current_target = target.as_basic_value().into_int_value();
+            let max_length = i32_type.const_int((array.len() - 1) as u64, false);
+            let is_end = builder.build_int_compare(IntPredicate::EQ, current, max_length, "");
+            builder.build_conditional_branch(is_end, end_block, check_block);
+
+            builder.position_at_end(check_block);
+            let character = builder
+                .build_load(char_at(pointer, current), "")
+                .into_int_value();
+            let is_terminator =
+                builder.build_int_compare(IntPredicate::EQ, character, char_type.const_zero(), "");
+            builder.build_conditional_branch(is_terminator, end_block, copy_block);
+
+            builder.position_at_end(copy_block);
+            builder.build_store(char_at(result, current_target), character);
+            let next = builder.build_int_add(current, i32_type.const_int(1, false), "");
+            let next_target =
+                builder.build_int_add(current_target, i32_type.const_int(1, false), "");
+            builder.build_unconditional_branch(loop_block);
+            index.add_incoming(&[(&i32_type.const_zero(), start_block), (&next, copy_block)]);
+            target.add_incoming(&[(&offset, start_block), (&next_target, copy_block)]);
+
+            // the next operand is appended where this one ended
+            builder.position_at_end(end_block);
+            offset = current_target;
+        }
+        builder.build_store(char_at(result, offset), char_type.const_zero());
+        Ok(result)
+    }
+
+    /// returns the conversion between numbers and strings named by the given builtin together
+    /// with the runtime's helper implementing it
+    fn get_string_conversion(
+        &self,
+        function_name: &str,
+        location: &SourceRange,
+    ) -> Result<(StringConversion, FunctionValue<'ink>), Diagnostic> {
+        let conversion = string_conversions::find_string_conversion(function_name, self.index)
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!(
+                        "{} is no conversion between a number and a string",
+                        function_name
+                    ),
+                    location.clone(),
+                )
+            })?;
+        let helper = self
+            .llvm_index
+            .find_associated_implementation(&conversion.helper)
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!("Cannot find the runtime's helper {}", conversion.helper),
+                    location.clone(),
+                )
+            })?;
+        Ok((conversion, helper))
+    }
+
+    /// converts a number into a STRING or WSTRING by calling the runtime's helper of the given
+    /// builtin (e.g. `INT_TO_STRING`), returns a pointer to the converted string
+    pub fn generate_number_to_string(
+        &self,
+        function_name: &str,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let i64_type = self.llvm.context.i64_type();
+        let (conversion, helper) = self.get_string_conversion(function_name, &location)?;
+        let number = ast::get_call_argument(parameters, "in", 0).ok_or_else(|| {
+            Diagnostic::codegen_error(
+                &format!("Expected a number to convert for {}", function_name),
+                location.clone(),
+            )
+        })?;
+
+        // the argument was already cast to the declared number type
+        let value = self.generate_expression(number)?;
+        let value: BasicMetadataValueEnum = match conversion.number {
+            NumberKind::Signed | NumberKind::DateAndTime | NumberKind::Time => builder
+                .build_int_s_extend_or_bit_cast(value.into_int_value(), i64_type, "")
+                .into(),
+            NumberKind::Unsigned => builder
+                .build_int_z_extend_or_bit_cast(value.into_int_value(), i64_type, "")
+                .into(),
+            NumberKind::Real | NumberKind::LReal => value.into(),
+        };
+
+        let string_type = self.get_return_type(function_name, &location)?;
+        let result = builder.build_alloca(string_type, "");
+        let length = match string_type {
+            BasicTypeEnum::ArrayType(array) => array.len(),
+            _ => unreachable!("the result of {} is a string", function_name),
+        };
+        let characters = unsafe {
+            builder.build_in_bounds_gep(result, &[i64_type.const_zero(), i64_type.const_zero()], "")
+        };
+        builder.build_call(
+            helper,
+            &[
+                value,
+                characters.into(),
+                self.llvm
+                    .context
+                    .i32_type()
+                    .const_int(length as u64, false)
+                    .into(),
+            ],
+            "",
+        );
+        Ok(result.as_basic_value_enum())
+    }
+
+    /// parses a STRING or WSTRING into a number by calling the runtime's helper of the given
+    /// builtin (e.g. `STRING_TO_INT`). The optional `error` output is set if the string is not a
+    /// number or does not fit into the result, the result is 0 then
+    pub fn generate_string_to_number(
+        &self,
+        function_name: &str,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let context = self.llvm.context;
+        let i32_type = context.i32_type();
+        let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+        let (conversion, helper) = self.get_string_conversion(function_name, &location)?;
+        let string = ast::get_call_argument(parameters, "in", 0).ok_or_else(|| {
+            Diagnostic::codegen_error(
+                &format!("Expected a string to convert for {}", function_name),
+                location.clone(),
+            )
+        })?;
+
+        let pointer = self.generate_string_pointer(string)?;
+        let length = match pointer.get_type().get_element_type() {
+            AnyTypeEnum::ArrayType(array) => array.len(),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Cannot convert a string without a length",
+                    string.get_location(),
+                ))
+            }
+        };
+        let characters = unsafe {
+            builder.build_in_bounds_gep(
+                pointer,
+                &[i32_type.const_zero(), i32_type.const_zero()],
+                "",
+            )
+        };
+        let error = match ast::get_call_argument(parameters, "error", 1) {
+            Some(error) => {
+                let error = self.generate_element_pointer(error)?;
+                builder.build_pointer_cast(error, i8_ptr_type, "")
+            }
+            None => i8_ptr_type.const_null(),
+        };
+
+        let number_type = self.get_return_type(function_name, &location)?;
+        let mut arguments: Vec<BasicMetadataValueEnum> = vec![
+            characters.into(),
+            i32_type.const_int(length as u64, false).into(),
+        ];
+        if conversion.is_integer() {
+            let bits = number_type.into_int_type().get_bit_width();
+            arguments.push(i32_type.const_int(bits as u64, false).into());
+        }
+        arguments.push(error.into());
+        let value = builder
+            .build_call(helper, &arguments, "")
+            .try_as_basic_value()
+            .either(Ok, |_| {
+                Err(Diagnostic::codegen_error(
+                    &format!("{} returned no value", conversion.helper),
+                    location.clone(),
+                ))
+            })?;
+
+        // the helpers return a LINT, ULINT or LREAL
+        Ok(match number_type {
+            BasicTypeEnum::IntType(int_type) => builder
+                .build_int_truncate_or_bit_cast(value.into_int_value(), int_type, "")
+                .as_basic_value_enum(),
+            BasicTypeEnum::FloatType(float_type) => builder
+                .build_float_cast(value.into_float_value(), float_type, "")
+                .as_basic_value_enum(),
+            _ => value,
+        })
+    }
+
+    /// calculates the checksum of a byte buffer by calling the runtime's helper of the given builtin
+    /// (e.g. `CRC16`), the buffer is passed as a pointer (or an address) and its length in bytes
+    pub fn generate_checksum(
+        &self,
+        function_name: &str,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let i8_ptr_type = self.llvm.context.i8_type().ptr_type(AddressSpace::Generic);
+        let helper = checksums::find_checksum_helper(function_name)
+            .and_then(|it| self.llvm_index.find_associated_implementation(it))
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!("Cannot find the runtime's helper of {}", function_name),
+                    location.clone(),
+                )
+            })?;
+        let (data, length) = match (
+            ast::get_call_argument(parameters, "data", 0),
+            ast::get_call_argument(parameters, "length", 1),
+        ) {
+            (Some(data), Some(length)) => (data, length),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    &format!("Expected a buffer and its length for {}", function_name),
+                    location,
+                ))
+            }
+        };
+
+        let data = match self.generate_expression(data)? {
+            BasicValueEnum::PointerValue(pointer) => {
+                builder.build_pointer_cast(pointer, i8_ptr_type, "")
+            }
+            BasicValueEnum::IntValue(address) => builder.build_int_to_ptr(address, i8_ptr_type, ""),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Expected the address of a buffer",
+                    data.get_location(),
+                ))
+            }
+        };
+        let length = self.generate_expression(length)?;
+        builder
+            .build_call(helper, &[data.into(), length.into()], "")
+            .try_as_basic_value()
+            .either(Ok, |_| {
+                Err(Diagnostic::codegen_error(
+                    &format!("{} returned no value", function_name),
+                    location,
+                ))
+            })
+    }
+
+    /// copies the bytes of `source` into `destination` (`MEM_COPY`), at most the size of the smaller
+    /// one, so neither is accessed beyond its end. Returns the number of copied bytes
+    pub fn generate_memory_copy(
+        &self,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let (destination, source) = match (
+            ast::get_call_argument(parameters, "destination", 0),
+            ast::get_call_argument(parameters, "source", 1),
+        ) {
+            (Some(destination), Some(source)) => (destination, source),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Expected a destination and a source for MEM_COPY",
+                    location,
+                ))
+            }
+        };
+        let destination_pointer = self.generate_element_pointer(destination)?;
+        let source_pointer = self.generate_element_pointer(source)?;
+        let size = self.generate_min_size(
+            self.get_size_of(destination_pointer, destination)?,
+            self.get_size_of(source_pointer, source)?,
+        );
+        self.generate_bytes_copy(destination_pointer, source_pointer, size, location)
+    }
+
+    /// copies the characters of a STRING up to its terminator into an ARRAY OF BYTE
+    /// (`STRING_TO_BYTES`), at most the size of the array. Returns the number of copied bytes
+    pub fn generate_string_to_bytes(
+        &self,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let (string, bytes) = match (
+            ast::get_call_argument(parameters, "in", 0),
+            ast::get_call_argument(parameters, "bytes", 1),
+        ) {
+            (Some(string), Some(bytes)) => (string, bytes),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Expected a string and an array of bytes for STRING_TO_BYTES",
+                    location,
+                ))
+            }
+        };
+        let string_pointer = self.generate_string_pointer(string)?;
+        let bytes_pointer = self.generate_element_pointer(bytes)?;
+        let length = self.generate_string_length(string_pointer, string)?;
+        let length = self
+            .llvm
+            .builder
+            .build_int_z_extend(length, self.llvm.context.i64_type(), "");
+        let size = self.generate_min_size(length, self.get_size_of(bytes_pointer, bytes)?);
+        self.generate_bytes_copy(bytes_pointer, string_pointer, size, location)
+    }
+
+    /// copies the first `length` bytes of an ARRAY OF BYTE into a new STRING (`BYTES_TO_STRING`),
+    /// at most the size of the array and the length of the STRING. Returns a pointer to the string
+    pub fn generate_bytes_to_string(
+        &self,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let i64_type = self.llvm.context.i64_type();
+        let (bytes, length) = match (
+            ast::get_call_argument(parameters, "in", 0),
+            ast::get_call_argument(parameters, "length", 1),
+        ) {
+            (Some(bytes), Some(length)) => (bytes, length),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Expected an array of bytes and a length for BYTES_TO_STRING",
+                    location,
+                ))
+            }
+        };
+        let bytes_pointer = self.generate_element_pointer(bytes)?;
+        let length = self.generate_expression(length)?.into_int_value();
+        let length = builder.build_int_z_extend_or_bit_cast(length, i64_type, "");
+
+        let string_type = self.get_return_type("BYTES_TO_STRING", &location)?;
+        let result = builder.build_alloca(string_type, "");
+        // the last character is reserved for the terminator
+        let capacity = match string_type {
+            BasicTypeEnum::ArrayType(array) => i64_type.const_int((array.len() - 1) as u64, false),
+            _ => unreachable!("the result of BYTES_TO_STRING is a string"),
+        };
+        let size = self.generate_min_size(length, self.get_size_of(bytes_pointer, bytes)?);
+        let size = self.generate_min_size(size, capacity);
+        self.generate_bytes_copy(result, bytes_pointer, size, location)?;
+        let terminator =
+            unsafe { builder.build_in_bounds_gep(result, &[i64_type.const_zero(), size], "") };
+        builder.build_store(terminator, self.llvm.context.i8_type().const_zero());
+        Ok(result.as_basic_value_enum())
+    }
+
+    /// returns the lower (`LOWER_BOUND`) or the upper (`UPPER_BOUND`) bound of a dimension of a
+    /// variable-length array, the dimensions are counted from 1
+    pub fn generate_array_bound(
+        &self,
+        function_name: &str,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let (array, dimension) = match (
+            ast::get_call_argument(parameters, "arr", 0),
+            ast::get_call_argument(parameters, "dim", 1),
+        ) {
+            (Some(array), Some(dimension)) => (array, dimension),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    &format!("Expected an array and a dimension for {}", function_name),
+                    location,
+                ))
+            }
+        };
+        let fat_pointer = self.generate_element_pointer(array)?;
+        let dimensions =
+            self.llvm
+                .get_member_pointer_from_struct(fat_pointer, 1, "dimensions", &location)?;
+        let dimension = cast_if_needed(
+            self.llvm,
+            self.index,
+            self.llvm_index,
+            self.index.get_type(DINT_TYPE)?,
+            self.generate_expression(dimension)?,
+            self.get_type_hint_for(dimension)?,
+            dimension,
+        )?
+        .into_int_value();
+
+        let builder = &self.llvm.builder;
+        let i32_type = self.llvm.i32_type();
+        let position = builder.build_int_mul(
+            builder.build_int_sub(dimension, i32_type.const_int(1, false), ""),
+            i32_type.const_int(2, false),
+            "",
+        );
+        let position = if function_name == "UPPER_BOUND" {
+            builder.build_int_add(position, i32_type.const_int(1, false), "")
+        } else {
+            position
+        };
+        self.load_vla_bound(dimensions, position)
+            .map(BasicValueEnum::from)
+    }
+
+    /// copies `size` bytes from `source` to `destination` (`MEMCPY`) and returns `size`
+    pub fn generate_memcpy(
+        &self,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let (destination, source, size) = match (
+            ast::get_call_argument(parameters, "destination", 0),
+            ast::get_call_argument(parameters, "source", 1),
+            ast::get_call_argument(parameters, "size", 2),
+        ) {
+            (Some(destination), Some(source), Some(size)) => (destination, source, size),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Expected a destination, a source and a size for MEMCPY",
+                    location,
+                ))
+            }
+        };
+        let destination = self.generate_memory_address(destination)?;
+        let source = self.generate_memory_address(source)?;
+        let size = self.generate_expression(size)?.into_int_value();
+        self.llvm
+            .builder
+            .build_memcpy(destination, 1, source, 1, size)
+            .map_err(|err| Diagnostic::codegen_error(err, location))?;
+        Ok(size.as_basic_value_enum())
+    }
+
+    /// sets `size` bytes of `destination` to `value` (`MEMSET`) and returns `size`
+    pub fn generate_memset(
+        &self,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let (destination, value, size) = match (
+            ast::get_call_argument(parameters, "destination", 0),
+            ast::get_call_argument(parameters, "value", 1),
+            ast::get_call_argument(parameters, "size", 2),
+        ) {
+            (Some(destination), Some(value), Some(size)) => (destination, value, size),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Expected a destination, a value and a size for MEMSET",
+                    location,
+                ))
+            }
+        };
+        let destination = self.generate_memory_address(destination)?;
+        let value = self.generate_expression(value)?.into_int_value();
+        let size = self.generate_expression(size)?.into_int_value();
+        self.llvm
+            .builder
+            .build_memset(destination, 1, value, size)
+            .map_err(|err| Diagnostic::codegen_error(err, location))?;
+        Ok(size.as_basic_value_enum())
+    }
+
+    /// compares the first `size` bytes of `left` and `right` (`MEMCMP`). Returns the difference of
+    /// the first bytes that differ (compared as unsigned values) or 0 if all bytes are equal
+    pub fn generate_memcmp(
+        &self,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let context = self.llvm.context;
+        let i32_type = context.i32_type();
+        let (left, right, size) = match (
+            ast::get_call_argument(parameters, "left", 0),
+            ast::get_call_argument(parameters, "right", 1),
+            ast::get_call_argument(parameters, "size", 2),
+        ) {
+            (Some(left), Some(right), Some(size)) => (left, right, size),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Expected two values and a size for MEMCMP",
+                    location,
+                ))
+            }
+        };
+        let left_pointer = self.generate_memory_address(left)?;
+        let right_pointer = self.generate_memory_address(right)?;
+        let size = self.generate_expression(size)?.into_int_value();
+        let size = builder.build_int_z_extend_or_bit_cast(size, i32_type, "");
+
+        let function = self.get_function_context(left)?.function;
+        let start_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
+        let loop_block = context.append_basic_block(function, "");
+        let check_block = context.append_basic_block(function, "");
+        let difference_block = context.append_basic_block(function, "");
+        let end_block = context.append_basic_block(function, "");
+        builder.build_unconditional_branch(loop_block);
+
+        builder.position_at_end(loop_block);
+        let index = builder.build_phi(i32_type, "");
+        let current = index.as_basic_value().into_int_value();
+        let is_end = builder.build_int_compare(IntPredicate::EQ, current, size, "");
+        builder.build_conditional_branch(is_end, end_block, check_block);
+
+        builder.position_at_end(check_block);
+        let load_byte = |pointer: PointerValue<'ink>| {
+            let byte = unsafe { builder.build_in_bounds_gep(pointer, &[current], "") };
+            builder.build_int_z_extend(builder.build_load(byte, "").into_int_value(), i32_type, "")
+        };
+        let left_byte = load_byte(left_pointer);
+        let right_byte = load_byte(right_pointer);
+        let is_equal = builder.build_int_compare(IntPredicate::EQ, left_byte, right_byte, "");
+        let next = builder.build_int_add(current, i32_type.const_int(1, false), "");
+        builder.build_conditional_branch(is_equal, loop_block, difference_block);
+        index.add_incoming(&[(&i32_type.const_zero(), start_block), (&next, check_block)]);
+
+        builder.position_at_end(difference_block);
+        let difference = builder.build_int_sub(left_byte, right_byte, "");
+        builder.build_unconditional_branch(end_block);
+
+        builder.position_at_end(end_block);
+        let result = builder.build_phi(i32_type, "");
+        result.add_incoming(&[
+            (&i32_type.const_zero(), loop_block),
+            (&difference, difference_block),
+        ]);
+        Ok(result.as_basic_value())
+    }
+
+    /// returns a byte pointer to the memory passed to a memory function. Pointers and addresses
+    /// (e.g. `ADR(frame)`) are used as they are, any other variable is passed by reference
+    fn generate_memory_address(
+        &self,
+        statement: &AstStatement,
+    ) -> Result<PointerValue<'ink>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let i8_ptr_type = self.llvm.context.i8_type().ptr_type(AddressSpace::Generic);
+        let is_address = matches!(statement, AstStatement::CallStatement { .. })
+            || matches!(
+                self.annotations
+                    .get_type_or_void(statement, self.index)
+                    .get_type_information(),
+                DataTypeInformation::Pointer {
+                    auto_deref: false,
+                    ..
+                }
+            );
+        let pointer = if is_address {
+            match self.do_generate_expression(statement)? {
+                BasicValueEnum::PointerValue(pointer) => pointer,
+                BasicValueEnum::IntValue(address) => {
+                    return Ok(builder.build_int_to_ptr(address, i8_ptr_type, ""))
+                }
+                _ => {
+                    return Err(Diagnostic::codegen_error(
+                        "Expected a variable or an address",
+                        statement.get_location(),
+                    ))
+                }
+            }
+        } else {
+            self.generate_element_pointer(statement)?
+        };
+        Ok(builder.build_pointer_cast(pointer, i8_ptr_type, ""))
+    }
+
+    /// generates `ARRAY_ADD` or `ARRAY_MUL`, storing the element-wise sum or product of `left` and
+    /// `right` into `destination`. Only as many elements as the shortest array holds are computed,
+    /// their number is returned
+    pub fn generate_array_operation(
+        &self,
+        function_name: &str,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let (destination, left, right) = match (
+            ast::get_call_argument(parameters, "destination", 0),
+            ast::get_call_argument(parameters, "left", 1),
+            ast::get_call_argument(parameters, "right", 2),
+        ) {
+            (Some(destination), Some(left), Some(right)) => (destination, left, right),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    &format!(
+                        "Expected a destination and two arrays for {}",
+                        function_name
+                    ),
+                    location,
+                ))
+            }
+        };
+        let operator = if function_name == "ARRAY_MUL" {
+            Operator::Multiplication
+        } else {
+            Operator::Plus
+        };
+        let (arrays, element_type, length) =
+            self.generate_numeric_arrays(function_name, &[destination, left, right])?;
+
+        let builder = &self.llvm.builder;
+        self.generate_vectorized_loop(destination, length, None, |index, _| {
+            let element = |array: PointerValue<'ink>| unsafe {
+                builder.build_in_bounds_gep(array, &[self.llvm.i32_type().const_zero(), index], "")
+            };
+            let left_value = builder.build_load(element(arrays[1]), "");
+            let right_value = builder.build_load(element(arrays[2]), "");
+            let value = if element_type.is_float() {
+                self.create_llvm_float_binary_expression(&operator, left_value, right_value)
+            } else {
+                self.create_llvm_int_binary_expression(&operator, left_value, right_value)
+            };
+            builder.build_store(element(arrays[0]), value);
+            Ok(None)
+        })?;
+        Ok(self.llvm.i32_type().const_int(length as u64, false).into())
+    }
+
+    /// generates `ARRAY_DOT`, the sum of the products of the elements of `left` and `right` as an
+    /// LREAL. Only as many elements as the shorter array holds are multiplied, integers are
+    /// summed up as LINT
+    pub fn generate_array_dot(
+        &self,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let (left, right) = match (
+            ast::get_call_argument(parameters, "left", 0),
+            ast::get_call_argument(parameters, "right", 1),
+        ) {
+            (Some(left), Some(right)) => (left, right),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Expected two arrays for ARRAY_DOT",
+                    location,
+                ))
+            }
+        };
+        let (arrays, element_type, length) =
+            self.generate_numeric_arrays("ARRAY_DOT", &[left, right])?;
+
+        let builder = &self.llvm.builder;
+        let context = self.llvm.context;
+        let element_llvm_type = arrays[0]
+            .get_type()
+            .get_element_type()
+            .into_array_type()
+            .get_element_type();
+        let is_signed = element_type.is_signed_int();
+        let sum = if element_type.is_float() {
+            element_llvm_type
+                .into_float_type()
+                .const_zero()
+                .as_basic_value_enum()
+        } else {
+            context.i64_type().const_zero().as_basic_value_enum()
+        };
+        let sum = self
+            .generate_vectorized_loop(left, length, Some(sum), |index, sum| {
+                let load = |array: PointerValue<'ink>| {
+                    let element = unsafe {
+                        builder.build_in_bounds_gep(
+                            array,
+                            &[context.i32_type().const_zero(), index],
+                            "",
+                        )
+                    };
+                    builder.build_load(element, "")
+                };
+                let (left_value, right_value) = (load(arrays[0]), load(arrays[1]));
+                let sum = sum.expect("the loop carries the sum");
+                if element_type.is_float() {
+                    let product = builder.build_float_mul(
+                        left_value.into_float_value(),
+                        right_value.into_float_value(),
+                        "",
+                    );
+                    Ok(Some(
+                        builder
+                            .build_float_add(sum.into_float_value(), product, "")
+                            .into(),
+                    ))
+                } else {
+                    let extend = |value: BasicValueEnum<'ink>| {
+                        if is_signed {
+                            builder.build_int_s_extend_or_bit_cast(
+                                value.into_int_value(),
+                                context.i64_type(),
+                                "",
+                            )
+                        } else {
+                            builder.build_int_z_extend_or_bit_cast(
+                                value.into_int_value(),
+                                context.i64_type(),
+                                "",
+                            )
+                        }
+                    };
+                    let product =
+                        builder.build_int_mul(extend(left_value), extend(right_value), "");
+                    Ok(Some(
+                        builder
+                            .build_int_add(sum.into_int_value(), product, "")
+                            .into(),
+                    ))
+                }
+            })?
+            .expect("the loop carries the sum");
+
+        let f64_type = context.f64_type();
+        let result = match sum {
+            BasicValueEnum::FloatValue(sum) => builder.build_float_cast(sum, f64_type, ""),
+            BasicValueEnum::IntValue(sum) if is_signed => {
+                builder.build_signed_int_to_float(sum, f64_type, "")
+            }
+            BasicValueEnum::IntValue(sum) => builder.build_unsigned_int_to_float(sum, f64_type, ""),
+            _ => unreachable!("the sum is a number"),
+        };
+        Ok(result.into())
+    }
+
+    /// generates `MATRIX_MUL`, storing the product of the matrix `left` and the matrix or vector
+    /// `right` into `destination`. A vector is a matrix with a single column. Returns the number
+    /// of computed elements
+    pub fn generate_matrix_multiplication(
+        &self,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let (destination, left, right) = match (
+            ast::get_call_argument(parameters, "destination", 0),
+            ast::get_call_argument(parameters, "left", 1),
+            ast::get_call_argument(parameters, "right", 2),
+        ) {
+            (Some(destination), Some(left), Some(right)) => (destination, left, right),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Expected a destination and two matrices for MATRIX_MUL",
+                    location,
+                ))
+            }
+        };
+        let (rows, inner) = self.get_matrix_shape("MATRIX_MUL", left)?;
+        let (right_rows, columns) = self.get_matrix_shape("MATRIX_MUL", right)?;
+        if right_rows != inner
+            || self.get_matrix_shape("MATRIX_MUL", destination)? != (rows, columns)
+        {
+            return Err(Diagnostic::codegen_error(
+                &format!(
+                    "Expected a {}x{} destination and a right matrix with {} rows for MATRIX_MUL",
+                    rows, columns, inner
+                ),
+                location,
+            ));
+        }
+        let (arrays, element_type, _) =
+            self.generate_numeric_arrays("MATRIX_MUL", &[destination, left, right])?;
+
+        let builder = &self.llvm.builder;
+        let i32_type = self.llvm.i32_type();
+        let zero: BasicValueEnum = match arrays[0]
+            .get_type()
+            .get_element_type()
+            .into_array_type()
+            .get_element_type()
+        {
+            BasicTypeEnum::FloatType(float_type) => float_type.const_zero().into(),
+            element => element.into_int_type().const_zero().into(),
+        };
+        let apply = |operator: &Operator, left, right| {
+            if element_type.is_float() {
+                self.create_llvm_float_binary_expression(operator, left, right)
+            } else {
+                self.create_llvm_int_binary_expression(operator, left, right)
+            }
+        };
+        self.generate_vectorized_loop(destination, rows, None, |row, _| {
+            self.generate_vectorized_loop(destination, columns, None, |column, _| {
+                let sum = self.generate_vectorized_loop(left, inner, Some(zero), |k, sum| {
+                    let left_value = self.load_matrix_element(arrays[1], row, k, inner);
+                    let right_value = self.load_matrix_element(arrays[2], k, column, columns);
+                    let product = apply(&Operator::Multiplication, left_value, right_value);
+                    Ok(Some(apply(
+                        &Operator::Plus,
+                        sum.expect("the loop carries the sum"),
+                        product,
+                    )))
+                })?;
+                let element = self.get_matrix_element(arrays[0], row, column, columns);
+                builder.build_store(element, sum.expect("the loop carries the sum"));
+                Ok(None)
+            })
+        })?;
+        Ok(i32_type.const_int((rows * columns) as u64, false).into())
+    }
+
+    /// generates `TRANSPOSE`, storing the transposed matrix `source` into `destination`. Returns
+    /// the number of transposed elements
+    pub fn generate_transpose(
+        &self,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let (destination, source) = match (
+            ast::get_call_argument(parameters, "destination", 0),
+            ast::get_call_argument(parameters, "source", 1),
+        ) {
+            (Some(destination), Some(source)) => (destination, source),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Expected a destination and a matrix for TRANSPOSE",
+                    location,
+                ))
+            }
+        };
+        let (rows, columns) = self.get_matrix_shape("TRANSPOSE", source)?;
+        if self.get_matrix_shape("TRANSPOSE", destination)? != (columns, rows) {
+            return Err(Diagnostic::codegen_error(
+                &format!("Expected a {}x{} destination for TRANSPOSE", columns, rows),
+                location,
+            ));
+        }
+        let (arrays, ..) = self.generate_numeric_arrays("TRANSPOSE", &[destination, source])?;
+
+        let builder = &self.llvm.builder;
+        self.generate_vectorized_loop(source, rows, None, |row, _| {
+            self.generate_vectorized_loop(source, columns, None, |column, _| {
+                let value = self.load_matrix_element(arrays[1], row, column, columns);
+                let element = self.get_matrix_element(arrays[0], column, row, rows);
+                builder.build_store(element, value);
+                Ok(None)
+            })
+        })?;
+        Ok(self
+            .llvm
+            .i32_type()
+            .const_int((rows * columns) as u64, false)
+            .into())
+    }
+
+    /// generates the conversion of a fixed-point number into the given type (e.g. `FIXED_TO_REAL`)
+    pub fn generate_fixed_point_conversion(
+        &self,
+        function_name: &str,
+        target_type: &str,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let value = ast::get_call_argument(parameters, "in", 0)
+            .map(|it| (it, self.annotations.get_type_or_void(it, self.index)))
+            .filter(|(_, it)| {
+                self.index
+                    .get_intrinsic_type_by_name(it.get_name())
+                    .get_type_information()
+                    .is_fixed_point()
+            });
+        let (value, value_type) = value.ok_or_else(|| {
+            Diagnostic::codegen_error(
+                &format!("Expected a fixed-point number for {}", function_name),
+                location,
+            )
+        })?;
+        cast_if_needed(
+            self.llvm,
+            self.index,
+            self.llvm_index,
+            self.index.get_type(target_type)?,
+            self.generate_expression(value)?,
+            value_type,
+            value,
+        )
+    }
+
+    /// returns the number of rows and columns of the given matrix, a vector is a matrix with a
+    /// single column
+    fn get_matrix_shape(
+        &self,
+        function_name: &str,
+        matrix: &AstStatement,
+    ) -> Result<(u32, u32), Diagnostic> {
+        let lengths = match self
+            .annotations
+            .get_type_or_void(matrix, self.index)
+            .get_type_information()
+        {
+            DataTypeInformation::Array { dimensions, .. } => dimensions
+                .iter()
+                .map(|it| it.get_length(self.index))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|message| Diagnostic::codegen_error(&message, matrix.get_location()))?,
+            _ => vec![],
+        };
+        match lengths.as_slice() {
+            [length] => Ok((*length, 1)),
+            [rows, columns] => Ok((*rows, *columns)),
+            _ => Err(Diagnostic::codegen_error(
+                &format!("Expected a vector or a matrix for {}", function_name),
+                matrix.get_location(),
+            )),
+        }
+    }
+
+    /// returns a pointer to the element of a matrix with the given number of columns
+    fn get_matrix_element(
+        &self,
+        matrix: PointerValue<'ink>,
+        row: IntValue<'ink>,
+        column: IntValue<'ink>,
+        columns: u32,
+    ) -> PointerValue<'ink> {
+        let builder = &self.llvm.builder;
+        let i32_type = self.llvm.i32_type();
+        let offset = builder.build_int_add(
+            builder.build_int_mul(row, i32_type.const_int(columns as u64, false), ""),
+            column,
+            "",
+        );
+        unsafe { builder.build_in_bounds_gep(matrix, &[i32_type.const_zero(), offset], "") }
+    }
+
+    fn load_matrix_element(
+        &self,
+        matrix: PointerValue<'ink>,
+        row: IntValue<'ink>,
+        column: IntValue<'ink>,
+        columns: u32,
+    ) -> BasicValueEnum<'ink> {
+        let element = self.get_matrix_element(matrix, row, column, columns);
+        self.llvm.builder.build_load(element, "")
+    }
+
+    /// returns pointers to the given arrays of the array builtins, the type of their elements
+    /// and the number of elements of the shortest array. The arrays have to hold numbers of the
+    /// same type
+    fn generate_numeric_arrays(
+        &self,
+        function_name: &str,
+        arrays: &[&AstStatement],
+    ) -> Result<(Vec<PointerValue<'ink>>, &'b DataTypeInformation, u32), Diagnostic> {
+        let mut element_types: Vec<&DataTypeInformation> = vec![];
+        let mut pointers = vec![];
+        let mut length = u32::MAX;
+        for array in arrays {
+            let element_type = match self
+                .annotations
+                .get_type_or_void(array, self.index)
+                .get_type_information()
+            {
+                DataTypeInformation::Array {
+                    inner_type_name, ..
+                } => Some(
+                    self.index
+                        .get_intrinsic_type_by_name(inner_type_name)
+                        .get_type_information(),
+                ),
+                _ => None,
+            }
+            .filter(|it| (it.is_int() && !it.is_bool()) || it.is_float())
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!("Expected an array of numbers for {}", function_name),
+                    array.get_location(),
+                )
+            })?;
+            if element_types
+                .first()
+                .map_or(false, |it| it.get_name() != element_type.get_name())
+            {
+                return Err(Diagnostic::codegen_error(
+                    &format!("Expected arrays of the same type for {}", function_name),
+                    array.get_location(),
+                ));
+            }
+            let pointer = self.generate_element_pointer(array)?;
+            length = length.min(
+                pointer
+                    .get_type()
+                    .get_element_type()
+                    .into_array_type()
+                    .len(),
+            );
+            element_types.push(element_type);
+            pointers.push(pointer);
+        }
+        Ok((pointers, element_types[0], length))
+    }
+
+    /// generates a loop over the indexes `0..length` that LLVM may vectorize. The body is
+    /// generated with the current index and accumulator, returning the next accumulator. The
+    /// body may generate nested loops. Returns the final accumulator
+    fn generate_vectorized_loop(
+        &self,
+        statement: &AstStatement,
+        length: u32,
+        accumulator: Option<BasicValueEnum<'ink>>,
+        body: impl Fn(
+            IntValue<'ink>,
+            Option<BasicValueEnum<'ink>>,
+        ) -> Result<Option<BasicValueEnum<'ink>>, Diagnostic>,
+    ) -> Result<Option<BasicValueEnum<'ink>>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let context = self.llvm.context;
+        let i32_type = context.i32_type();
+        let function = self.get_function_context(statement)?.function;
+        let start_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
+        let loop_block = context.append_basic_block(function, "");
+        let body_block = context.append_basic_block(function, "");
+        let end_block = context.append_basic_block(function, "");
+        builder.build_unconditional_branch(loop_block);
+
+        builder.position_at_end(loop_block);
+        let index = builder.build_phi(i32_type, "");
+        let current = index.as_basic_value().into_int_value();
+        let sum = accumulator.map(|it| {
+            let sum = builder.build_phi(it.get_type(), "");
+            sum.add_incoming(&[(&it, start_block)]);
+            sum
+        });
+        let is_end = builder.build_int_compare(
+            IntPredicate::UGE,
+            current,
+            i32_type.const_int(length as u64, false),
+            "",
+        );
+        builder.build_conditional_branch(is_end, end_block, body_block);
+
+        builder.position_at_end(body_block);
+        let next_sum = body(current, sum.map(|it| it.as_basic_value()))?;
+        let next = builder.build_int_add(current, i32_type.const_int(1, false), "");
+        let body_end = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
+        let latch = builder.build_unconditional_branch(loop_block);
+        vectorization::enable_vectorization(self.llvm, latch);
+        index.add_incoming(&[(&i32_type.const_zero(), start_block), (&next, body_end)]);
+        if let (Some(sum), Some(next_sum)) = (sum, next_sum) {
+            sum.add_incoming(&[(&next_sum, body_end)]);
+        }
+
+        builder.position_at_end(end_block);
+        Ok(sum.map(|it| it.as_basic_value()))
+    }
+
+    /// generates the given saturating builtin (`ADD_SAT`, `SUB_SAT`, `MUL_SAT`) as a call of the
+    /// LLVM intrinsic matching the size and the sign of the operands' integer type
+    pub fn generate_saturating_arithmetic(
+        &self,
+        function_name: &str,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let (in1, in2) = match (
+            ast::get_call_argument(parameters, "in1", 0),
+            ast::get_call_argument(parameters, "in2", 1),
+        ) {
+            (Some(in1), Some(in2)) => (in1, in2),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    &format!("Expected two operands for {}", function_name),
+                    location,
+                ))
+            }
+        };
+        // both operands are converted to T by the resolver's hints
+        let operand_type = self
+            .index
+            .get_intrinsic_type_by_name(self.get_type_hint_for(in1)?.get_name())
+            .get_type_information();
+        let intrinsic = Some(operand_type)
+            .filter(|it| it.is_int() && !it.is_bool())
+            .and_then(|it| {
+                saturation::find_saturating_intrinsic(
+                    function_name,
+                    it.is_signed_int(),
+                    it.get_size(),
+                )
+            })
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!("Expected integers for {}", function_name),
+                    location.clone(),
+                )
+            })?;
+        let function = self
+            .llvm_index
+            .find_associated_implementation(&intrinsic)
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!("Cannot find the intrinsic {}", intrinsic),
+                    location.clone(),
+                )
+            })?;
+
+        let mut arguments: Vec<BasicMetadataValueEnum> = vec![
+            self.generate_expression(in1)?.into(),
+            self.generate_expression(in2)?.into(),
+        ];
+        if saturation::is_fixed_point_intrinsic(&intrinsic) {
+            // a product of integers is a fixed-point product without fraction bits
+            arguments.push(self.llvm.i32_type().const_zero().into());
+        }
+        self.llvm
+            .builder
+            .build_call(function, &arguments, "")
+            .try_as_basic_value()
+            .left()
+            .ok_or_else(|| Diagnostic::codegen_error(INTERNAL_LLVM_ERROR, location))
+    }
+
+    /// calls the runtime's helper of the given random builtin (`RAND`, `SRAND`, `GUID`), the
+    /// helper of `GUID` writes into a new STRING. Returns the random number or the string
+    pub fn generate_random(
+        &self,
+        function_name: &str,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let context = self.llvm.context;
+        let helper = random::find_random_helper(function_name)
+            .and_then(|it| self.llvm_index.find_associated_implementation(it))
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!("Cannot find the runtime's helper of {}", function_name),
+                    location.clone(),
+                )
+            })?;
+        if function_name.eq_ignore_ascii_case("SRAND") {
+            let seed = ast::get_call_argument(parameters, "seed", 0).ok_or_else(|| {
+                Diagnostic::codegen_error("Expected a seed for SRAND", location.clone())
+            })?;
+            let seed = self.generate_expression(seed)?;
+            builder.build_call(helper, &[seed.into()], "");
+            // like other void functions, SRAND returns an uninitialized int pointer
+            return get_llvm_int_type(context, INT_SIZE, INT_TYPE).map(|int| {
+                int.ptr_type(AddressSpace::Const)
+                    .const_null()
+                    .as_basic_value_enum()
+            });
+        }
+        if function_name.eq_ignore_ascii_case("GUID") {
+            let i64_type = context.i64_type();
+            let string_type = self.get_return_type(function_name, &location)?;
+            let result = builder.build_alloca(string_type, "");
+            let length = match string_type {
+                BasicTypeEnum::ArrayType(array) => array.len(),
+                _ => unreachable!("the result of GUID is a string"),
+            };
+            let characters = unsafe {
+                builder.build_in_bounds_gep(
+                    result,
+                    &[i64_type.const_zero(), i64_type.const_zero()],
+                    "",
+                )
+            };
+            builder.build_call(
+                helper,
+                &[
+                    characters.into(),
+                    context.i32_type().const_int(length as u64, false).into(),
+                ],
+                "",
+            );
+            return Ok(result.as_basic_value_enum());
+        }
+        builder
+            .build_call(helper, &[], "")
+            .try_as_basic_value()
+            .either(Ok, |_| {
+                Err(Diagnostic::codegen_error(
+                    &format!("{} returned no value", function_name),
+                    location,
+                ))
+            })
+    }
+
+    /// generates the value of `__DEFINED` or `__OPTION` evaluated at compile time, `__OPTION`
+    /// returns its value in a new string
+    pub fn generate_define_query(
+        &self,
+        function_name: &str,
+        parameters: &[&AstStatement],
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let value = self
+            .index
+            .get_defines()
+            .evaluate_query(function_name, parameters, 0, location.clone())
+            .map_err(|message| Diagnostic::codegen_error(&message, location.clone()))?;
+        match value {
+            AstStatement::LiteralBool { value, .. } => self.llvm.create_const_bool(value),
+            AstStatement::LiteralString { value, .. } => {
+                let string_type = self.get_return_type(function_name, &location)?;
+                let length = match string_type {
+                    BasicTypeEnum::ArrayType(array) => array.len(),
+                    _ => unreachable!("the result of {} is a string", function_name),
+                };
+                let result = self.llvm.builder.build_alloca(string_type, "");
+                self.llvm.builder.build_store(
+                    result,
+                    self.llvm
+                        .create_const_utf8_string(&value, length as usize)?,
+                );
+                Ok(result.as_basic_value_enum())
+            }
+            _ => unreachable!("queries evaluate to a bool or a string"),
+        }
+    }
+
+    /// returns the size in bytes of the value the given pointer points to
+    fn get_size_of(
+        &self,
+        pointer: PointerValue<'ink>,
+        statement: &AstStatement,
+    ) -> Result<IntValue<'ink>, Diagnostic> {
+        BasicTypeEnum::try_from(pointer.get_type().get_element_type())
+            .ok()
+            .and_then(|it| it.size_of())
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    "Cannot determine the size of this value",
+                    statement.get_location(),
+                )
+            })
+    }
+
+    /// returns the smaller one of the two (unsigned) sizes
+    fn generate_min_size(&self, left: IntValue<'ink>, right: IntValue<'ink>) -> IntValue<'ink> {
+        let builder = &self.llvm.builder;
+        let is_left_smaller = builder.build_int_compare(IntPredicate::ULT, left, right, "");
+        builder
+            .build_select(is_left_smaller, left, right, "")
+            .into_int_value()
+    }
+
+    /// copies `size` bytes and returns the number of copied bytes as an UDINT
+    fn generate_bytes_copy(
+        &self,
+        destination: PointerValue<'ink>,
+        source: PointerValue<'ink>,
+        size: IntValue<'ink>,
+        location: SourceRange,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        builder
+            .build_memcpy(destination, 1, source, 1, size)
+            .map_err(|err| Diagnostic::codegen_error(err, location))?;
+        Ok(builder
+            .build_int_truncate_or_bit_cast(size, self.llvm.context.i32_type(), "")
+            .as_basic_value_enum())
+    }
+
+    /// returns the number of characters of the given string up to its terminator or its length
+    fn generate_string_length(
+        &self,
+        pointer: PointerValue<'ink>,
+        statement: &AstStatement,
+    ) -> Result<IntValue<'ink>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let context = self.llvm.context;
+        let i32_type = context.i32_type();
+        let array = match pointer.get_type().get_element_type() {
+            AnyTypeEnum::ArrayType(array) => array,
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Cannot determine the length of a string without a length",
+                    statement.get_location(),
+                ))
+            }
+        };
+        let function = self.get_function_context(statement)?.function;
+        let start_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
+        let loop_block = context.append_basic_block(function, "");
+        let check_block = context.append_basic_block(function, "");
+        let end_block = context.append_basic_block(function, "");
+        builder.build_unconditional_branch(loop_block);
+
+        builder.position_at_end(loop_block);
+        let index = builder.build_phi(i32_type, "");
+        let current = index.as_basic_value().into_int_value();
+        let max_length = i32_type.const_int(array.len() as u64, false);
+        let is_end = builder.build_int_compare(IntPredicate::EQ, current, max_length, "");
+        builder.build_conditional_branch(is_end, end_block, check_block);
+
+        builder.position_at_end(check_block);
+        let character =
+            unsafe { builder.build_in_bounds_gep(pointer, &[i32_type.const_zero(), current], "") };
+        let character = builder.build_load(character, "").into_int_value();
+        let is_terminator = builder.build_int_compare(
+            IntPredicate::EQ,
+            character,
+            character.get_type().const_zero(),
+            "",
+        );
+        let next = builder.build_int_add(current, i32_type.const_int(1, false), "");
+        builder.build_conditional_branch(is_terminator, end_block, loop_block);
+        index.add_incoming(&[(&i32_type.const_zero(), start_block), (&next, check_block)]);
+
+        builder.position_at_end(end_block);
+        Ok(current)
+    }
+
+    /// returns the llvm type of the given function's return type
+    fn get_return_type(
+        &self,
+        function_name: &str,
+        location: &SourceRange,
+    ) -> Result<BasicTypeEnum<'ink>, Diagnostic> {
+        let return_type = self.index.find_return_type(function_name).ok_or_else(|| {
+            Diagnostic::codegen_error(
+                &format!("Cannot find the return type of {}", function_name),
+                location.clone(),
+            )
+        })?;
+        self.llvm_index.get_associated_type(return_type.get_name())
+    }
+
     /// creates a binary expression (left op right) with generic
     /// left & right expressions (non-numerics)
     /// this function attempts to call optional
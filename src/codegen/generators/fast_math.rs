@@ -0,0 +1,68 @@
+//! fast floating point arithmetic for the POUs compiled with `--ffast-math` or marked with
+//! `{attribute 'fast_math'}`
+//!
+//! the functions of these POUs carry LLVM's fast-math attributes, so the optimizer and the code
+//! generator may reassociate float operations, assume there are no NaNs, infinities or signed
+//! zeros and use approximations (e.g. multiplying by a reciprocal instead of dividing). A product
+//! added to or subtracted from a value (`a * b + c`) is computed with `llvm.fmuladd`, which the
+//! code generator fuses to a single multiply-add instruction where the target provides one.
+use inkwell::{attributes::AttributeLoc, module::Module, values::FunctionValue};
+
+use super::llvm::Llvm;
+use crate::{
+    codegen::llvm_index::LlvmTypedIndex,
+    diagnostics::Diagnostic,
+    typesystem::{LREAL_TYPE, REAL_TYPE},
+};
+
+/// the function attributes relaxing the IEEE semantics of the function's float operations
+const FAST_MATH_ATTRIBUTES: [&str; 5] = [
+    "unsafe-fp-math",
+    "no-nans-fp-math",
+    "no-infs-fp-math",
+    "no-signed-zeros-fp-math",
+    "approx-func-fp-math",
+];
+
+/// returns the multiply-add intrinsic of the given float type or None if the type is no float
+pub fn find_multiply_add(type_name: &str) -> Option<&'static str> {
+    if type_name.eq_ignore_ascii_case(REAL_TYPE) {
+        Some("llvm.fmuladd.f32")
+    } else if type_name.eq_ignore_ascii_case(LREAL_TYPE) {
+        Some("llvm.fmuladd.f64")
+    } else {
+        None
+    }
+}
+
+/// allows the given function to compute its float operations with fast-math semantics
+pub fn add_fast_math_attributes(llvm: &Llvm, function: FunctionValue) {
+    for name in FAST_MATH_ATTRIBUTES {
+        let attribute = llvm.context.create_string_attribute(name, "true");
+        function.add_attribute(AttributeLoc::Function, attribute);
+    }
+}
+
+/// declares the multiply-add intrinsics used by the fast-math POUs
+/// Returns a new LLVM index containing the declared intrinsics
+pub fn declare_helpers<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+) -> Result<LlvmTypedIndex<'ink>, Diagnostic> {
+    let mut llvm_index = LlvmTypedIndex::default();
+    let intrinsics = [
+        ("llvm.fmuladd.f32", llvm.context.f32_type()),
+        ("llvm.fmuladd.f64", llvm.context.f64_type()),
+    ];
+    for (name, float_type) in intrinsics {
+        if module.get_function(name).is_none() {
+            let function_type = float_type.fn_type(
+                &[float_type.into(), float_type.into(), float_type.into()],
+                false,
+            );
+            let function = module.add_function(name, function_type, None);
+            llvm_index.associate_implementation(name, function)?;
+        }
+    }
+    Ok(llvm_index)
+}
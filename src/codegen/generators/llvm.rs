@@ -79,6 +79,31 @@ impl<'a> Llvm<'a> {
         global
     }
 
+    /// creates a private, zero-terminated string constant and returns a pointer to its first character
+    ///
+    /// - `name` the name of the constant
+    /// - `text` the constant's content
+    pub fn create_private_string_constant(
+        &self,
+        module: &Module<'a>,
+        name: &str,
+        text: &str,
+    ) -> Result<PointerValue<'a>, Diagnostic> {
+        let len = text.len() + 1;
+        let global = self.create_global_variable(
+            module,
+            name,
+            self.context.i8_type().array_type(len as u32).into(),
+        );
+        global.set_linkage(Linkage::Private);
+        global
+            .make_constant()
+            .set_initializer(&self.create_const_utf8_string(text, len)?);
+        Ok(global
+            .as_pointer_value()
+            .const_cast(self.context.i8_type().ptr_type(AddressSpace::Generic)))
+    }
+
     /// creates a local variable at the builder's location
     ///
     /// - `name` the name of the local variable
@@ -1,9 +1,12 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 use super::{
     data_type_generator::get_default_for,
+    debug_generator::DebugHooks,
+    debug_info::DebugFile,
     expression_generator::ExpressionCodeGenerator,
+    fast_math,
     llvm::{GlobalValueExt, Llvm},
-    statement_generator::{FunctionContext, StatementCodeGenerator},
+    statement_generator::{create_label_blocks, FunctionContext, StatementCodeGenerator},
 };
 use crate::{
     ast::Pou,
@@ -21,13 +24,14 @@ use crate::{
 use crate::index::{ImplementationIndexEntry, VariableIndexEntry};
 
 use crate::{
-    ast::{Implementation, PouType, SourceRange},
+    ast::{CallingConvention, Implementation, LinkageType, OptimizationHint, PouType, SourceRange},
     index::Index,
 };
 use inkwell::types::{BasicType, StructType};
 use inkwell::{
-    module::Module,
-    types::{BasicMetadataTypeEnum, BasicTypeEnum, FunctionType},
+    attributes::{Attribute, AttributeLoc},
+    module::{Linkage, Module},
+    types::{AnyType, BasicMetadataTypeEnum, BasicTypeEnum, FunctionType},
     values::{BasicValueEnum, FunctionValue},
     AddressSpace,
 };
@@ -37,6 +41,11 @@ pub struct PouGenerator<'ink, 'cg> {
     index: &'cg Index,
     annotations: &'cg AstAnnotations,
     llvm_index: &'cg LlvmTypedIndex<'ink>,
+    debug_hooks: Option<DebugHooks<'ink>>,
+    debug_file: Option<&'cg DebugFile<'ink, 'cg>>,
+    timing_annotations: bool,
+    fast_math: bool,
+    check_bounds: bool,
 }
 
 /// Creates opaque implementations for all callable items in the index
@@ -52,7 +61,12 @@ pub fn generate_implementation_stubs<'ink>(
     let pou_generator = PouGenerator::new(llvm, index, annotations, types_index);
     for (name, implementation) in index.get_implementations() {
         if let Some(pou) = index.find_pou(implementation.get_call_name()) {
-            if !pou.is_generic() {
+            //builtins are generated inline where they are called
+            if pou.is_abstract() {
+                //abstract methods get no implementation, they are called through the vtable
+                let function_type = pou_generator.create_function_type(implementation)?;
+                llvm_index.associate_abstract_implementation(name, function_type)?;
+            } else if !pou.is_generic() && pou.get_linkage() != &LinkageType::BuiltIn {
                 let curr_f = pou_generator.generate_implementation_stub(implementation, module)?;
                 llvm_index.associate_implementation(name, curr_f)?;
             }
@@ -62,6 +76,19 @@ pub fn generate_implementation_stubs<'ink>(
     Ok(llvm_index)
 }
 
+/// returns the id llvm knows the given calling convention by (see `llvm::CallingConv`)
+pub(crate) fn get_calling_convention_id(convention: CallingConvention) -> u32 {
+    match convention {
+        CallingConvention::C => 0,
+        CallingConvention::Stdcall => 64,
+    }
+}
+
+/// returns true for the types a calling convention passes through pointers
+pub(crate) fn is_aggregate(data_type: BasicTypeEnum) -> bool {
+    data_type.is_array_type() || data_type.is_struct_type()
+}
+
 ///Generates a global constant for each initialized pou member
 /// The given constant can then be used to initialize the variable using memcpy without re-evaluating the expression
 /// Retrieves the POUs from the index (implementation)
@@ -120,6 +147,9 @@ pub fn generate_global_constants_for_pou_members<'ink>(
                         .create_global_variable(module, &name, variable_type)
                         .make_constant()
                         .set_initial_value(Some(value), variable_type);
+                    if index.is_weak_pou(implementation.get_call_name()) {
+                        global_value.set_linkage(Linkage::WeakAny);
+                    }
                     local_llvm_index.associate_global(&name, global_value)?;
                 }
             }
@@ -143,41 +173,204 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
             index,
             annotations,
             llvm_index,
+            debug_hooks: None,
+            debug_file: None,
+            timing_annotations: false,
+            fast_math: false,
+            check_bounds: false,
         }
     }
 
+    /// instruments the generated implementations with calls to the given debug hooks
+    pub fn with_debug_hooks(mut self, debug_hooks: DebugHooks<'ink>) -> Self {
+        self.debug_hooks = Some(debug_hooks);
+        self
+    }
+
+    pub fn get_debug_hooks(&self) -> Option<&DebugHooks<'ink>> {
+        self.debug_hooks.as_ref()
+    }
+
+    /// describes the generated implementations in the debug information of the given file
+    pub fn with_debug_file(mut self, debug_file: &'cg DebugFile<'ink, 'cg>) -> Self {
+        self.debug_file = Some(debug_file);
+        self
+    }
+
+    pub fn get_debug_file(&self) -> Option<&'cg DebugFile<'ink, 'cg>> {
+        self.debug_file
+    }
+
+    /// annotates the generated statements and loops for WCET analyzers (see `timing_generator`)
+    pub fn with_timing_annotations(mut self) -> Self {
+        self.timing_annotations = true;
+        self
+    }
+
+    pub fn has_timing_annotations(&self) -> bool {
+        self.timing_annotations
+    }
+
+    /// computes the float operations of all POUs with fast-math semantics (see `fast_math`)
+    pub fn with_fast_math(mut self) -> Self {
+        self.fast_math = true;
+        self
+    }
+
+    /// checks the indexes of the array accesses of all POUs at runtime (see `bounds_check`)
+    pub fn with_bounds_check(mut self) -> Self {
+        self.check_bounds = true;
+        self
+    }
+
     /// generates an empty llvm function for the given implementation, including all parameters and the return type
     pub fn generate_implementation_stub(
         &self,
         implementation: &ImplementationIndexEntry,
         module: &Module<'ink>,
     ) -> Result<FunctionValue<'ink>, Diagnostic> {
-        let global_index = self.index;
-        //generate a function that takes a instance-struct parameter
         let pou_name = implementation.get_call_name();
+        let function_declaration = self.create_function_type(implementation)?;
+
+        //weak implementations may be replaced by another implementation at link time
+        let linkage = if self.index.is_weak_pou(pou_name) {
+            Some(Linkage::WeakAny)
+        } else {
+            None
+        };
+        let curr_f = module.add_function(pou_name, function_declaration, linkage);
+        if let Some(hint) = self.index.find_optimization_hint(pou_name) {
+            self.add_optimization_attributes(curr_f, hint);
+        }
+        if let Some(convention) = self.index.find_calling_convention(pou_name) {
+            self.add_calling_convention(curr_f, convention, implementation)?;
+        }
+        Ok(curr_f)
+    }
+
+    /// sets the calling convention requested by `{external '...'}` and marks the pointers passing
+    /// the function's aggregates: the pointer to the caller's copy of an aggregate parameter is
+    /// `byval`, the pointer to the caller's memory receiving an aggregate result is `sret`
+    fn add_calling_convention(
+        &self,
+        function: FunctionValue<'ink>,
+        convention: CallingConvention,
+        implementation: &ImplementationIndexEntry,
+    ) -> Result<(), Diagnostic> {
+        function.set_call_conventions(get_calling_convention_id(convention));
+        let context = self.llvm.context;
+        let type_attribute = |name: &str, aggregate: BasicTypeEnum<'ink>| {
+            context.create_type_attribute(
+                Attribute::get_named_enum_kind_id(name),
+                aggregate.as_any_type_enum(),
+            )
+        };
 
-        let parameters = self.create_parameters_for_implementation(implementation)?;
+        let mut first_parameter = 0;
+        if let Some(result) = self
+            .get_return_type(implementation)?
+            .filter(|it| is_aggregate(*it))
+        {
+            function.add_attribute(AttributeLoc::Param(0), type_attribute("sret", result));
+            first_parameter = 1;
+        }
+        for (index, parameter) in self
+            .create_parameters_for_implementation(implementation)?
+            .into_iter()
+            .enumerate()
+        {
+            let aggregate: BasicTypeEnum = match parameter {
+                BasicMetadataTypeEnum::ArrayType(it) => it.into(),
+                BasicMetadataTypeEnum::StructType(it) => it.into(),
+                _ => continue,
+            };
+            function.add_attribute(
+                AttributeLoc::Param(first_parameter + index as u32),
+                type_attribute("byval", aggregate),
+            );
+        }
+        Ok(())
+    }
 
-        let return_type = match global_index.find_return_type(implementation.get_type_name()) {
-            Some(r_type) => Some(self.llvm_index.get_associated_type(r_type.get_name())?),
-            None => None,
+    /// maps the optimization requested by `{attribute 'optimize' := '...'}` to the function's
+    /// attributes: `none` to `optnone` (which requires `noinline`) and `size` to `minsize` and
+    /// `optsize`. `speed` adds no attributes, the function is optimized at the level of the module
+    fn add_optimization_attributes(&self, function: FunctionValue<'ink>, hint: OptimizationHint) {
+        let attributes: &[&str] = match hint {
+            OptimizationHint::None => &["optnone", "noinline"],
+            OptimizationHint::Size => &["minsize", "optsize"],
+            OptimizationHint::Speed => &[],
         };
+        for name in attributes {
+            let attribute = self
+                .llvm
+                .context
+                .create_enum_attribute(Attribute::get_named_enum_kind_id(name), 0);
+            function.add_attribute(AttributeLoc::Function, attribute);
+        }
+    }
+
+    /// creates the llvm `FunctionType` of the given implementation, including all parameters and
+    /// the return type
+    pub fn create_function_type(
+        &self,
+        implementation: &ImplementationIndexEntry,
+    ) -> Result<FunctionType<'ink>, Diagnostic> {
+        let global_index = self.index;
+        //generate a function that takes a instance-struct parameter
+        let mut parameters = self.create_parameters_for_implementation(implementation)?;
+        let mut return_type = self.get_return_type(implementation)?;
+
+        //functions with a calling convention pass their aggregates through pointers
+        if global_index
+            .find_calling_convention(implementation.get_call_name())
+            .is_some()
+        {
+            parameters = parameters
+                .into_iter()
+                .map(|it| match it {
+                    BasicMetadataTypeEnum::ArrayType(aggregate) => {
+                        aggregate.ptr_type(AddressSpace::Generic).into()
+                    }
+                    BasicMetadataTypeEnum::StructType(aggregate) => {
+                        aggregate.ptr_type(AddressSpace::Generic).into()
+                    }
+                    it => it,
+                })
+                .collect();
+            if let Some(result) = return_type.filter(|it| is_aggregate(*it)) {
+                parameters.insert(0, result.ptr_type(AddressSpace::Generic).into());
+                return_type = None;
+            }
+        }
 
         let variadic = global_index
             .find_effective_type_info(implementation.get_type_name())
             .map(|it| it.is_variadic())
             .unwrap_or(false);
 
-        let function_declaration =
-            self.create_llvm_function_type(parameters, variadic, return_type)?;
+        self.create_llvm_function_type(parameters, variadic, return_type)
+    }
 
-        let curr_f = module.add_function(pou_name, function_declaration, None);
-        Ok(curr_f)
+    /// returns the llvm type of the given implementation's return value, `None` if it returns
+    /// nothing
+    fn get_return_type(
+        &self,
+        implementation: &ImplementationIndexEntry,
+    ) -> Result<Option<BasicTypeEnum<'ink>>, Diagnostic> {
+        match self.index.find_return_type(implementation.get_type_name()) {
+            Some(r_type) => Ok(Some(
+                self.llvm_index.get_associated_type(r_type.get_name())?,
+            )),
+            None => Ok(None),
+        }
     }
 
     /// creates and returns all parameters for the given implementation
     /// for functions, this method creates a full list of parameters, for other POUs
-    /// this method creates a single state-struct parameter
+    /// this method creates a single state-struct parameter. Variable-length arrays are passed
+    /// by value as their fat pointer (the array's data pointer and bounds), the array itself
+    /// stays with the caller
     fn create_parameters_for_implementation(
         &self,
         implementation: &ImplementationIndexEntry,
@@ -239,14 +432,29 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
                 )
             })?;
 
+        if let Some(debug_file) = self.debug_file {
+            debug_file.register_function(&self.llvm, current_function, implementation, self.index);
+        }
+        let uses_fast_math = self.fast_math || self.index.is_fast_math(pou_name);
+        if uses_fast_math {
+            fast_math::add_fast_math_attributes(&self.llvm, current_function);
+        }
+
         //generate the body
         let block = context.append_basic_block(current_function, "entry");
         self.llvm.builder.position_at_end(block);
+        if let Some(debug_hooks) = &self.debug_hooks {
+            let instance = match implementation.pou_type {
+                PouType::Function => None,
+                _ => current_function.get_nth_param(0),
+            };
+            debug_hooks.generate_enter(&self.llvm, pou_name, instance);
+        }
 
         let mut param_index = 0;
 
-        if let PouType::Method { .. } = implementation.pou_type {
-            let class_name = implementation.type_name.split('.').collect::<Vec<&str>>()[0];
+        if let PouType::Method { owner_class } = &implementation.pou_type {
+            let class_name = owner_class.as_str();
             let class_members = self.index.get_container_members(class_name);
             self.generate_local_struct_variable_accessors(
                 param_index,
@@ -278,9 +486,29 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
             )?;
         }
 
+        if let Some(debug_file) = self.debug_file {
+            for member in &pou_members {
+                if let Some(storage) =
+                    local_index.find_loaded_associated_variable_value(member.get_qualified_name())
+                {
+                    debug_file.declare_variable(
+                        &self.llvm,
+                        current_function,
+                        member,
+                        storage,
+                        self.index,
+                    );
+                }
+            }
+        }
+
         let function_context = FunctionContext {
             linking_context: implementation.into(),
             function: current_function,
+            labels: create_label_blocks(&self.llvm, current_function, &implementation.statements),
+            loop_bounds: implementation.loop_bounds.clone(),
+            fast_math: uses_fast_math,
+            check_bounds: self.check_bounds,
         };
         {
             //if this is a function, we need to initilialize the VAR-variables
@@ -309,7 +537,14 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
         }
 
         // generate return statement
+        if let Some(debug_file) = self.debug_file {
+            let end = implementation.location.get_end();
+            debug_file.set_location(&self.llvm, current_function, end);
+        }
         self.generate_return_statement(&function_context, &local_index)?;
+        if self.debug_file.is_some() {
+            self.llvm.builder.unset_current_debug_location();
+        }
 
         Ok(())
     }
@@ -486,6 +721,10 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
                     .find_associated_type(variable.get_type_name())
                     .and_then(|associated_type| associated_type.size_of())
                     .ok_or("Couldn't determine type size");
+                let declared_default = match right_stmt {
+                    Some(..) => None,
+                    None => self.get_declared_default(variable.get_type_name()),
+                };
                 //First try to get a saved global constant
                 let name = index::get_initializer_name(variable.get_qualified_name());
                 let type_init_name = index::get_initializer_name(variable.get_type_name());
@@ -507,6 +746,10 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
                     .map_err(|err| {
                         Diagnostic::codegen_error(err, variable.source_location.clone())
                     })?;
+                } else if let Some(value) = declared_default {
+                    //Without an initializer, the type's declared default (e.g. an array of
+                    //initialized structs) is stored
+                    self.llvm.builder.build_store(left, value);
                 } else if left.get_type().get_element_type().is_array_type() {
                     //If nothint was found see if this is an array to set its value to 0
                     size.and_then(|size| {
@@ -547,6 +790,16 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
         Ok(())
     }
 
+    /// returns the initial value of the given type if it (or one of its members) declares an
+    /// initial value, types without declared initial values default to zero
+    fn get_declared_default(&self, type_name: &str) -> Option<BasicValueEnum<'ink>> {
+        if self.index.has_declared_initial_value(type_name) {
+            self.llvm_index.find_associated_initial_value(type_name)
+        } else {
+            None
+        }
+    }
+
     /// generates the function's return statement only if the given pou_type is a `PouType::Function`
     ///
     /// a function returns the value of the local variable that has the function's name
@@ -555,6 +808,9 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
         function_context: &FunctionContext<'ink>,
         local_index: &LlvmTypedIndex<'ink>,
     ) -> Result<(), Diagnostic> {
+        if let Some(debug_hooks) = &self.debug_hooks {
+            debug_hooks.generate_leave(&self.llvm);
+        }
         if let Some(ret_v) = self
             .index
             .find_return_variable(function_context.linking_context.get_type_name())
@@ -0,0 +1,95 @@
+//! lays out the process images of the inputs (`%I`), outputs (`%Q`) and flags (`%M`) and locates
+//! the global variables declared at a hardware address (e.g. `lamp AT %QX1.0 : BOOL`) in them
+//!
+//! every image is a zero-initialized byte array (`__rusty_input_image`, `__rusty_output_image`
+//! and `__rusty_memory_image`) exchanged with the I/O drivers, it is as large as the highest
+//! address located in it. A located variable is a pointer into its image at the offset of its
+//! address (see `DirectAccessType::get_image_range`), no storage of its own is generated.
+//! Variables bound to a template (`%I*`) and the located variables of programs remain ordinary
+//! variables bound by the hardware configuration.
+use inkwell::{module::Module, types::BasicType, AddressSpace};
+
+use super::llvm::{GlobalValueExt, Llvm};
+use crate::{
+    ast::HardwareAccessType,
+    codegen::llvm_index::LlvmTypedIndex,
+    diagnostics::Diagnostic,
+    index::{Index, VariableIndexEntry},
+};
+
+pub const INPUT_IMAGE: &str = "__rusty_input_image";
+pub const OUTPUT_IMAGE: &str = "__rusty_output_image";
+pub const MEMORY_IMAGE: &str = "__rusty_memory_image";
+
+/// returns the name of the process image holding the addresses of the given direction
+pub fn get_image_name(direction: HardwareAccessType) -> &'static str {
+    match direction {
+        HardwareAccessType::Input => INPUT_IMAGE,
+        HardwareAccessType::Output => OUTPUT_IMAGE,
+        HardwareAccessType::Memory => MEMORY_IMAGE,
+    }
+}
+
+/// returns true if the given global variable is stored in a process image
+pub fn is_located(variable: &VariableIndexEntry, index: &Index) -> bool {
+    variable
+        .get_hardware_binding()
+        .and_then(|it| it.get_image_range(index))
+        .is_some()
+}
+
+/// generates the process images and returns the pointers to the located global variables
+pub fn generate_process_images<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    types_index: &LlvmTypedIndex<'ink>,
+) -> Result<LlvmTypedIndex<'ink>, Diagnostic> {
+    let mut llvm_index = LlvmTypedIndex::default();
+    let located = index
+        .get_globals()
+        .values()
+        .filter_map(|variable| {
+            let binding = variable.get_hardware_binding()?;
+            Some((variable, binding.direction, binding.get_image_range(index)?))
+        })
+        .collect::<Vec<_>>();
+
+    let i32_type = llvm.context.i32_type();
+    for direction in [
+        HardwareAccessType::Input,
+        HardwareAccessType::Output,
+        HardwareAccessType::Memory,
+    ] {
+        let variables = located
+            .iter()
+            .filter(|(_, it, _)| *it == direction)
+            .collect::<Vec<_>>();
+        let size = match variables.iter().map(|(_, _, range)| range.end).max() {
+            Some(size) => size,
+            None => continue,
+        };
+        let image_type = llvm.context.i8_type().array_type(size as u32);
+        let mut image =
+            llvm.create_global_variable(module, get_image_name(direction), image_type.into());
+        //the image is defined by the module declaring its variables
+        if variables.iter().all(|(it, ..)| it.is_external()) {
+            image = image.make_external();
+        } else {
+            image.set_initializer(&image_type.const_zero());
+        }
+
+        for (variable, _, range) in variables {
+            let variable_type = types_index.get_associated_type(variable.get_type_name())?;
+            let address = unsafe {
+                image.as_pointer_value().const_gep(&[
+                    i32_type.const_zero(),
+                    i32_type.const_int(range.start, false),
+                ])
+            }
+            .const_cast(variable_type.ptr_type(AddressSpace::Generic));
+            llvm_index.associate_located_global(variable.get_qualified_name(), address)?;
+        }
+    }
+    Ok(llvm_index)
+}
@@ -0,0 +1,80 @@
+//! declares the runtime's helpers behind the random builtins (`RAND`, `SRAND`, `GUID`)
+//!
+//! `RAND` returns a random LREAL in [0, 1), `SRAND` seeds the generator so the following numbers
+//! are reproducible and `GUID` writes a random GUID into a STRING. The helpers are provided by the
+//! runtime (`rusty-runtime`) and are only declared if the application calls one of the builtins.
+use inkwell::{
+    context::Context,
+    module::{Linkage, Module},
+    types::FunctionType,
+    AddressSpace,
+};
+
+use super::llvm::Llvm;
+use crate::{
+    codegen::llvm_index::LlvmTypedIndex, diagnostics::Diagnostic, index::Index,
+    resolver::AstAnnotations,
+};
+
+/// the random builtins with the runtime's helper implementing them
+const RANDOM_BUILTINS: [(&str, &str); 3] = [
+    ("RAND", "__rusty_rand"),
+    ("SRAND", "__rusty_srand"),
+    ("GUID", "__rusty_guid"),
+];
+
+/// returns the name of the runtime's helper implementing the given random builtin or None if
+/// the function is no random builtin
+pub fn find_random_helper(function_name: &str) -> Option<&'static str> {
+    RANDOM_BUILTINS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(function_name))
+        .map(|(_, helper)| *helper)
+}
+
+/// returns the signature of the given helper:
+/// - `double()` to draw a random number
+/// - `void(i64 seed)` to seed the generator
+/// - `void(char*, i32 length)` to write a GUID into a string
+fn get_helper_type<'ink>(context: &'ink Context, helper: &str) -> FunctionType<'ink> {
+    match helper {
+        "__rusty_rand" => context.f64_type().fn_type(&[], false),
+        "__rusty_srand" => context
+            .void_type()
+            .fn_type(&[context.i64_type().into()], false),
+        _ => context.void_type().fn_type(
+            &[
+                context.i8_type().ptr_type(AddressSpace::Generic).into(),
+                context.i32_type().into(),
+            ],
+            false,
+        ),
+    }
+}
+
+/// declares the helpers of the random builtins called by the application
+/// Returns a new LLVM index containing the declared helpers
+pub fn declare_helpers<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    annotations: &AstAnnotations,
+) -> Result<LlvmTypedIndex<'ink>, Diagnostic> {
+    let mut llvm_index = LlvmTypedIndex::default();
+    let called = annotations
+        .get_called_functions()
+        .filter(|it| index.get_builtin_function(it).is_some())
+        .collect::<Vec<_>>();
+    for (name, helper) in RANDOM_BUILTINS {
+        let is_called = called.iter().any(|it| it.eq_ignore_ascii_case(name));
+        if is_called && module.get_function(helper).is_none() {
+            let function = module.add_function(
+                helper,
+                get_helper_type(llvm.context, helper),
+                Some(Linkage::External),
+            );
+            llvm_index.associate_implementation(helper, function)?;
+        }
+    }
+    Ok(llvm_index)
+}
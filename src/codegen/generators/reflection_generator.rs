@@ -0,0 +1,276 @@
+//! generates reflection tables describing the layout of structs and POU instances, so runtimes
+//! and HMIs can enumerate and access the members of an instance by name
+//!
+//! every reflected type is described by a `__reflection_type` (name, size, member count and a
+//! pointer to its `__reflection_member`s) while every member is described by its name, its
+//! type's name, its offset and its size. The descriptors are accessible through the
+//! `__reflection_types` table and the following helper functions:
+//! - `__reflection_get_type_count() : DINT`
+//! - `__reflection_get_type(index : DINT) : POINTER TO __reflection_type` (null if out of bounds)
+//! - `__reflection_get_member_address(instance, member) : POINTER TO BYTE`
+use inkwell::{
+    module::{Linkage, Module},
+    types::{BasicType, StructType},
+    values::{BasicValueEnum, PointerValue},
+    AddressSpace, IntPredicate,
+};
+
+use super::llvm::{GlobalValueExt, Llvm};
+use crate::{
+    ast::{LinkageType, PouType, SourceRange},
+    diagnostics::Diagnostic,
+    index::Index,
+    typesystem::{DataType, DataTypeInformation, StructSource},
+};
+
+pub const REFLECTION_TYPES: &str = "__reflection_types";
+pub const GET_TYPE_COUNT: &str = "__reflection_get_type_count";
+pub const GET_TYPE: &str = "__reflection_get_type";
+pub const GET_MEMBER_ADDRESS: &str = "__reflection_get_member_address";
+
+pub fn generate_reflection<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+) -> Result<(), Diagnostic> {
+    let context = llvm.context;
+    let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    let i64_type = context.i64_type();
+    let i32_type = context.i32_type();
+
+    let member_type = llvm.create_struct_stub("__reflection_member");
+    member_type.set_body(
+        &[
+            i8_ptr_type.into(),
+            i8_ptr_type.into(),
+            i64_type.into(),
+            i64_type.into(),
+        ],
+        false,
+    );
+    let member_ptr_type = member_type.ptr_type(AddressSpace::Generic);
+    let type_type = llvm.create_struct_stub("__reflection_type");
+    type_type.set_body(
+        &[
+            i8_ptr_type.into(),
+            i64_type.into(),
+            i32_type.into(),
+            member_ptr_type.into(),
+        ],
+        false,
+    );
+
+    let mut descriptors = Vec::new();
+    for data_type in get_reflected_types(index) {
+        let struct_name = data_type.get_type_information().get_name();
+        let struct_type = match module.get_struct_type(struct_name) {
+            Some(struct_type) => struct_type,
+            None => continue,
+        };
+
+        let mut members = Vec::new();
+        for member in index
+            .get_container_members(data_type.get_name())
+            .into_iter()
+            .filter(|it| !it.is_temp() && !it.is_return())
+        {
+            let element = member.get_location_in_parent();
+            let member_name = create_string(module, llvm, member.get_name())?;
+            let type_name = create_string(module, llvm, member.get_type_name())?;
+            let offset = get_offset_of(struct_type, element, llvm);
+            let size = struct_type
+                .get_field_type_at_index(element)
+                .and_then(|it| it.size_of())
+                .ok_or_else(|| cannot_reflect(struct_name))?;
+            members.push(member_type.const_named_struct(&[
+                member_name.into(),
+                type_name.into(),
+                offset.into(),
+                size.into(),
+            ]));
+        }
+
+        let members_pointer = if members.is_empty() {
+            member_ptr_type.const_null()
+        } else {
+            let members_table = llvm.create_global_variable(
+                module,
+                format!("__reflection_members_{}", struct_name).as_str(),
+                member_type.array_type(members.len() as u32).into(),
+            );
+            members_table.set_linkage(Linkage::Private);
+            members_table
+                .make_constant()
+                .set_initializer(&member_type.const_array(&members));
+            members_table.as_pointer_value().const_cast(member_ptr_type)
+        };
+
+        let type_name = create_string(module, llvm, data_type.get_name())?;
+        let size = struct_type
+            .size_of()
+            .ok_or_else(|| cannot_reflect(struct_name))?;
+        descriptors.push(type_type.const_named_struct(&[
+            type_name.into(),
+            size.into(),
+            i32_type.const_int(members.len() as u64, false).into(),
+            members_pointer.into(),
+        ]));
+    }
+
+    let types_table = llvm.create_global_variable(
+        module,
+        REFLECTION_TYPES,
+        type_type.array_type(descriptors.len() as u32).into(),
+    );
+    types_table
+        .make_constant()
+        .set_initializer(&type_type.const_array(&descriptors));
+
+    generate_helper_functions(
+        module,
+        llvm,
+        types_table.as_pointer_value(),
+        descriptors.len() as u64,
+        type_type,
+        member_type,
+    );
+    Ok(())
+}
+
+/// returns the structs and the instance-structs of programs, function blocks and classes
+/// declared in this application
+pub fn get_reflected_types(index: &Index) -> Vec<&DataType> {
+    index
+        .get_types()
+        .values()
+        .chain(index.get_pou_types().values())
+        .filter(|it| !it.get_type_information().is_generic(index))
+        .filter(|it| match it.get_type_information() {
+            DataTypeInformation::Struct {
+                source: StructSource::OriginalDeclaration,
+                ..
+            } => true,
+            DataTypeInformation::Struct {
+                source:
+                    StructSource::Pou(PouType::Program | PouType::FunctionBlock | PouType::Class),
+                ..
+            } => index.find_pou(it.get_name()).map_or(false, |pou| {
+                !matches!(
+                    pou.get_linkage(),
+                    LinkageType::External | LinkageType::BuiltIn
+                )
+            }),
+            _ => false,
+        })
+        .collect()
+}
+
+/// returns the offset of the given element in the given struct as a constant expression
+pub(crate) fn get_offset_of<'ink>(
+    struct_type: StructType<'ink>,
+    element: u32,
+    llvm: &Llvm<'ink>,
+) -> BasicValueEnum<'ink> {
+    let i32_type = llvm.context.i32_type();
+    let element_pointer = unsafe {
+        struct_type
+            .ptr_type(AddressSpace::Generic)
+            .const_null()
+            .const_gep(&[
+                i32_type.const_zero(),
+                i32_type.const_int(element as u64, false),
+            ])
+    };
+    element_pointer.const_to_int(llvm.context.i64_type()).into()
+}
+
+fn create_string<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    text: &str,
+) -> Result<PointerValue<'ink>, Diagnostic> {
+    llvm.create_private_string_constant(module, "__reflection_name", text)
+}
+
+fn generate_helper_functions<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    types_table: PointerValue<'ink>,
+    type_count: u64,
+    type_type: StructType<'ink>,
+    member_type: StructType<'ink>,
+) {
+    let context = llvm.context;
+    let builder = &llvm.builder;
+    let i32_type = context.i32_type();
+    let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    let type_ptr_type = type_type.ptr_type(AddressSpace::Generic);
+
+    //__reflection_get_type_count() : DINT
+    let function = module.add_function(GET_TYPE_COUNT, i32_type.fn_type(&[], false), None);
+    builder.position_at_end(context.append_basic_block(function, "entry"));
+    builder.build_return(Some(&i32_type.const_int(type_count, false)));
+
+    //__reflection_get_type(index : DINT) : POINTER TO __reflection_type
+    let function = module.add_function(
+        GET_TYPE,
+        type_ptr_type.fn_type(&[i32_type.into()], false),
+        None,
+    );
+    builder.position_at_end(context.append_basic_block(function, "entry"));
+    let type_index = function
+        .get_nth_param(0)
+        .map(BasicValueEnum::into_int_value);
+    if let Some(type_index) = type_index {
+        let is_valid = builder.build_int_compare(
+            IntPredicate::ULT,
+            type_index,
+            i32_type.const_int(type_count, false),
+            "is_valid",
+        );
+        let descriptor = unsafe {
+            builder.build_gep(
+                types_table,
+                &[i32_type.const_zero(), type_index],
+                "descriptor",
+            )
+        };
+        let result =
+            builder.build_select(is_valid, descriptor, type_ptr_type.const_null(), "result");
+        builder.build_return(Some(&result));
+    }
+
+    //__reflection_get_member_address(instance : POINTER TO BYTE, member : POINTER TO __reflection_member) : POINTER TO BYTE
+    let function = module.add_function(
+        GET_MEMBER_ADDRESS,
+        i8_ptr_type.fn_type(
+            &[
+                i8_ptr_type.into(),
+                member_type.ptr_type(AddressSpace::Generic).into(),
+            ],
+            false,
+        ),
+        None,
+    );
+    builder.position_at_end(context.append_basic_block(function, "entry"));
+    if let (
+        Some(BasicValueEnum::PointerValue(instance)),
+        Some(BasicValueEnum::PointerValue(member)),
+    ) = (function.get_nth_param(0), function.get_nth_param(1))
+    {
+        if let Ok(offset_pointer) = builder.build_struct_gep(member, 2, "offset_ptr") {
+            let offset = builder
+                .build_load(offset_pointer, "offset")
+                .into_int_value();
+            let address = unsafe { builder.build_gep(instance, &[offset], "address") };
+            builder.build_return(Some(&address));
+        }
+    }
+}
+
+fn cannot_reflect(type_name: &str) -> Diagnostic {
+    Diagnostic::codegen_error(
+        format!("Cannot generate reflection data for {}", type_name).as_str(),
+        SourceRange::undefined(),
+    )
+}
@@ -0,0 +1,94 @@
+//! declares LLVM's saturating intrinsics behind the builtins `ADD_SAT`, `SUB_SAT` and `MUL_SAT`
+//!
+//! a saturating operation clamps its result to the range of the integer type instead of wrapping
+//! around (e.g. `ADD_SAT(INT#32000, INT#1000)` is `32767`), without branches. Products use the
+//! fixed-point multiplication intrinsics with a scale of 0. The intrinsics are only declared (for
+//! every integer size) if the application calls one of the builtins.
+use inkwell::{context::Context, module::Module, values::FunctionValue};
+
+use super::llvm::Llvm;
+use crate::{
+    codegen::llvm_index::LlvmTypedIndex, diagnostics::Diagnostic, index::Index,
+    resolver::AstAnnotations,
+};
+
+/// the saturating builtins with the signed and the unsigned intrinsic implementing them
+const SATURATING_BUILTINS: [(&str, &str, &str); 3] = [
+    ("ADD_SAT", "llvm.sadd.sat", "llvm.uadd.sat"),
+    ("SUB_SAT", "llvm.ssub.sat", "llvm.usub.sat"),
+    ("MUL_SAT", "llvm.smul.fix.sat", "llvm.umul.fix.sat"),
+];
+
+/// the sizes of the integers the intrinsics are declared for
+const INTEGER_SIZES: [u32; 4] = [8, 16, 32, 64];
+
+/// returns the name of the intrinsic implementing the given saturating builtin for integers of
+/// the given size (e.g. `llvm.sadd.sat.i16`) or None if the function is no saturating builtin
+pub fn find_saturating_intrinsic(function_name: &str, signed: bool, size: u32) -> Option<String> {
+    SATURATING_BUILTINS
+        .iter()
+        .find(|(name, ..)| name.eq_ignore_ascii_case(function_name))
+        .map(|(_, signed_intrinsic, unsigned_intrinsic)| {
+            let intrinsic = if signed {
+                signed_intrinsic
+            } else {
+                unsigned_intrinsic
+            };
+            format!("{}.i{}", intrinsic, size)
+        })
+}
+
+/// returns true if the given intrinsic takes the scale of a fixed-point product as third argument
+pub fn is_fixed_point_intrinsic(intrinsic: &str) -> bool {
+    intrinsic.contains(".fix.")
+}
+
+/// declares the intrinsic with the given name for integers of the given size
+fn declare_intrinsic<'ink>(
+    module: &Module<'ink>,
+    context: &'ink Context,
+    intrinsic: &str,
+    size: u32,
+) -> FunctionValue<'ink> {
+    let int_type = context.custom_width_int_type(size);
+    let function_type = if is_fixed_point_intrinsic(intrinsic) {
+        int_type.fn_type(
+            &[int_type.into(), int_type.into(), context.i32_type().into()],
+            false,
+        )
+    } else {
+        int_type.fn_type(&[int_type.into(), int_type.into()], false)
+    };
+    module.add_function(intrinsic, function_type, None)
+}
+
+/// declares the intrinsics of the saturating builtins called by the application
+/// Returns a new LLVM index containing the declared intrinsics
+pub fn declare_helpers<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    annotations: &AstAnnotations,
+) -> Result<LlvmTypedIndex<'ink>, Diagnostic> {
+    let mut llvm_index = LlvmTypedIndex::default();
+    let called = annotations
+        .get_called_functions()
+        .filter(|it| index.get_builtin_function(it).is_some())
+        .collect::<Vec<_>>();
+    for (name, ..) in SATURATING_BUILTINS {
+        if !called.iter().any(|it| it.eq_ignore_ascii_case(name)) {
+            continue;
+        }
+        for size in INTEGER_SIZES {
+            for signed in [true, false] {
+                let intrinsic = find_saturating_intrinsic(name, signed, size)
+                    .expect("the builtin is a saturating builtin");
+                if module.get_function(&intrinsic).is_none() {
+                    let function = declare_intrinsic(module, llvm.context, &intrinsic, size);
+                    llvm_index.associate_implementation(&intrinsic, function)?;
+                }
+            }
+        }
+    }
+    Ok(llvm_index)
+}
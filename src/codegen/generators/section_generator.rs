@@ -0,0 +1,106 @@
+//! places global variables into the data sections declared by the generated linker script
+//! (see `crate::linker_script`)
+use inkwell::{
+    module::Module,
+    targets::{TargetData, TargetTriple},
+    values::GlobalValue,
+};
+
+use super::process_image_generator::{INPUT_IMAGE, MEMORY_IMAGE, OUTPUT_IMAGE};
+use crate::{
+    abi_metadata,
+    diagnostics::Diagnostic,
+    index::Index,
+    linker_script::{
+        SectionEntry, SectionLayout, PERSISTENT_SECTION, PROCESS_IMAGE_SECTION, RETAIN_SECTION,
+    },
+};
+
+/// moves the process images and the global variables bound to hardware templates (`AT %I*`) into
+/// the process image section, the retained global variables into the retain section and the
+/// persistent global variables into the persistent section. Sections are only assigned for ELF
+/// targets, the returned layouts are empty for other targets
+pub fn assign_sections(
+    module: &Module,
+    index: &Index,
+    triple: &TargetTriple,
+) -> Result<Vec<SectionLayout>, Diagnostic> {
+    let mut process_image = SectionLayout::new(PROCESS_IMAGE_SECTION);
+    let mut retain = SectionLayout::new(RETAIN_SECTION);
+    let mut persistent = SectionLayout::new(PERSISTENT_SECTION);
+    let target = triple.as_str().to_string_lossy();
+    if !is_elf_target(&target) {
+        return Ok(vec![process_image, retain, persistent]);
+    }
+
+    let target_data = abi_metadata::create_target_machine(triple)?.get_target_data();
+    for variable in index.get_globals().values().filter(|it| !it.is_external()) {
+        let section = if variable.has_hardware_binding() {
+            &mut process_image
+        } else if variable.is_persistent() {
+            &mut persistent
+        } else if variable.is_retain() {
+            &mut retain
+        } else {
+            continue;
+        };
+        //the located variables are stored in the process images
+        if let Some(global) = module.get_global(variable.get_name()) {
+            place_into_section(
+                global,
+                variable.get_name(),
+                variable.get_type_name(),
+                section,
+                &target_data,
+            );
+        }
+    }
+    for name in [INPUT_IMAGE, OUTPUT_IMAGE, MEMORY_IMAGE] {
+        //images declared by another module have no initializer
+        if let Some(image) = module
+            .get_global(name)
+            .filter(|it| it.get_initializer().is_some())
+        {
+            let size = image
+                .as_pointer_value()
+                .get_type()
+                .get_element_type()
+                .into_array_type()
+                .len();
+            place_into_section(
+                image,
+                name,
+                &format!("ARRAY[0..{}] OF BYTE", size.saturating_sub(1)),
+                &mut process_image,
+                &target_data,
+            );
+        }
+    }
+    Ok(vec![process_image, retain, persistent])
+}
+
+fn place_into_section(
+    global: GlobalValue,
+    name: &str,
+    type_name: &str,
+    section: &mut SectionLayout,
+    target_data: &TargetData,
+) {
+    let variable_type = global.as_pointer_value().get_type().get_element_type();
+    global.set_section(&section.name);
+    section.alignment = section
+        .alignment
+        .max(target_data.get_abi_alignment(&variable_type));
+    section.entries.push(SectionEntry {
+        name: name.to_string(),
+        type_name: type_name.to_string(),
+        size: target_data.get_abi_size(&variable_type),
+    });
+}
+
+/// returns true if the given target-triple produces ELF objects
+fn is_elf_target(target: &str) -> bool {
+    !["apple", "darwin", "windows", "wasm"]
+        .iter()
+        .any(|it| target.contains(it))
+}
@@ -3,17 +3,21 @@ use super::{
     expression_generator::{to_i1, ExpressionCodeGenerator},
     llvm::Llvm,
     pou_generator::PouGenerator,
+    timing_generator,
 };
 use crate::{
-    ast::{flatten_expression_list, AstStatement, ConditionalBlock, Operator, SourceRange},
+    ast::{
+        flatten_expression_list, get_nested_bodies, AstId, AstStatement, ConditionalBlock,
+        Operator, SourceRange,
+    },
     codegen::llvm_typesystem,
     codegen::LlvmTypedIndex,
     diagnostics::{Diagnostic, INTERNAL_LLVM_ERROR},
     index::{ImplementationIndexEntry, Index},
-    resolver::AstAnnotations,
+    resolver::{AnnotationMap, AstAnnotations},
     typesystem::{
-        self, DataTypeInformation, RANGE_CHECK_LS_FN, RANGE_CHECK_LU_FN, RANGE_CHECK_S_FN,
-        RANGE_CHECK_U_FN,
+        self, DataTypeInformation, StringEncoding, RANGE_CHECK_LS_FN, RANGE_CHECK_LU_FN,
+        RANGE_CHECK_S_FN, RANGE_CHECK_U_FN,
     },
 };
 use inkwell::{
@@ -21,8 +25,9 @@ use inkwell::{
     builder::Builder,
     context::Context,
     values::{BasicValueEnum, FunctionValue},
+    IntPredicate,
 };
-use std::ops::Range;
+use std::{collections::HashMap, ops::Range};
 
 /// the full context when generating statements inside a POU
 pub struct FunctionContext<'a> {
@@ -30,6 +35,34 @@ pub struct FunctionContext<'a> {
     pub linking_context: ImplementationIndexEntry,
     /// the llvm function to generate statements into
     pub function: FunctionValue<'a>,
+    /// the blocks of the labels declared in the function's body by their lowercase name
+    pub labels: HashMap<String, BasicBlock<'a>>,
+    /// the bounds of the function's loops annotated with `{attribute 'loop_bound'}`
+    pub loop_bounds: HashMap<AstId, u32>,
+    /// compute the function's float operations with fast-math semantics
+    pub fast_math: bool,
+    /// check the indexes of the function's array accesses at runtime
+    pub check_bounds: bool,
+}
+
+/// creates a block for every label declared in the given statements (see `JMP`)
+pub fn create_label_blocks<'a>(
+    llvm: &Llvm<'a>,
+    function: FunctionValue<'a>,
+    statements: &[AstStatement],
+) -> HashMap<String, BasicBlock<'a>> {
+    let mut labels = HashMap::new();
+    for statement in statements {
+        if let AstStatement::LabelStatement { name, .. } = statement {
+            labels
+                .entry(name.to_lowercase())
+                .or_insert_with(|| llvm.context.append_basic_block(function, name));
+        }
+        for body in get_nested_bodies(statement) {
+            labels.extend(create_label_blocks(llvm, function, body));
+        }
+    }
+    labels
 }
 
 /// the StatementCodeGenerator is used to generate statements (For, If, etc.) or expressions (references, literals, etc.)
@@ -110,6 +143,78 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
     ///
     /// - `statement` the statement to be generated
     pub fn generate_statement(&self, statement: &AstStatement) -> Result<(), Diagnostic> {
+        let is_located = !matches!(
+            statement,
+            AstStatement::EmptyStatement { .. } | AstStatement::LabelStatement { .. }
+        );
+        if is_located {
+            let location = statement.get_location();
+            self.set_debug_location(&location);
+            if let Some(debug_hooks) = self.pou_generator.get_debug_hooks() {
+                debug_hooks.generate_statement(self.llvm, &location);
+            }
+        }
+        let start = self
+            .llvm
+            .builder
+            .get_insert_block()
+            .map(|block| (block, block.get_last_instruction()));
+        self.generate_statement_content(statement)?;
+
+        if is_located && self.pou_generator.has_timing_annotations() {
+            //the statement's first instruction follows the last one generated before it
+            let first_instruction = start.and_then(|(block, last)| match last {
+                Some(last) => last.get_next_instruction(),
+                None => block.get_first_instruction(),
+            });
+            if let Some(instruction) = first_instruction {
+                timing_generator::annotate_statement(
+                    self.llvm,
+                    instruction,
+                    statement.get_id(),
+                    statement.get_location().get_start(),
+                );
+            }
+        }
+
+        //every statement needs an instruction of its own to be located by a debugger
+        if let Some(debug_file) = self.pou_generator.get_debug_file() {
+            let end = self
+                .llvm
+                .builder
+                .get_insert_block()
+                .map(|block| (block, block.get_last_instruction()));
+            if is_located && start == end {
+                debug_file.generate_marker(self.llvm);
+            }
+        }
+        Ok(())
+    }
+
+    /// annotates the header of the given loop with its `{attribute 'loop_bound'}` if the
+    /// generated code is annotated for WCET analyzers
+    fn annotate_loop_bound(&self, statement: &AstStatement, header: BasicBlock) {
+        if !self.pou_generator.has_timing_annotations() {
+            return;
+        }
+        if let Some(bound) = self.function_context.loop_bounds.get(&statement.get_id()) {
+            timing_generator::annotate_loop_bound(self.llvm, header, *bound);
+        }
+    }
+
+    /// locates the following instructions at the given location if debug information is
+    /// generated
+    fn set_debug_location(&self, location: &SourceRange) {
+        if let Some(debug_file) = self.pou_generator.get_debug_file() {
+            debug_file.set_location(
+                self.llvm,
+                self.function_context.function,
+                location.get_start(),
+            );
+        }
+    }
+
+    fn generate_statement_content(&self, statement: &AstStatement) -> Result<(), Diagnostic> {
         match statement {
             AstStatement::EmptyStatement { .. } => {
                 //nothing to generate
@@ -117,6 +222,9 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
             AstStatement::Assignment { left, right, .. } => {
                 self.generate_assignment_statement(left, right)?;
             }
+            AstStatement::ReferenceAssignment { left, right, .. } => {
+                self.generate_reference_assignment(left, right)?;
+            }
             AstStatement::ForLoopStatement {
                 start,
                 end,
@@ -125,17 +233,20 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
                 by_step,
                 ..
             } => {
-                self.generate_for_statement(counter, start, end, by_step, body)?;
+                let header = self.generate_for_statement(counter, start, end, by_step, body)?;
+                self.annotate_loop_bound(statement, header);
             }
             AstStatement::RepeatLoopStatement {
                 condition, body, ..
             } => {
-                self.generate_repeat_statement(condition, body)?;
+                let header = self.generate_repeat_statement(condition, body)?;
+                self.annotate_loop_bound(statement, header);
             }
             AstStatement::WhileLoopStatement {
                 condition, body, ..
             } => {
-                self.generate_while_statement(condition, body)?;
+                let header = self.generate_while_statement(condition, body)?;
+                self.annotate_loop_bound(statement, header);
             }
             AstStatement::IfStatement {
                 blocks, else_block, ..
@@ -166,6 +277,19 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
                     ));
                 }
             }
+            AstStatement::JumpStatement {
+                label, location, ..
+            } => {
+                let target = self.get_label_block(label, location)?;
+                self.llvm.builder.build_unconditional_branch(target);
+                self.generate_buffer_block();
+            }
+            AstStatement::LabelStatement { name, location, .. } => {
+                //fall through into the label's block
+                let target = self.get_label_block(name, location)?;
+                self.llvm.builder.build_unconditional_branch(target);
+                self.llvm.builder.position_at_end(target);
+            }
             AstStatement::ContinueStatement { location, .. } => {
                 if let Some(cont_block) = &self.current_loop_continue {
                     self.llvm.builder.build_unconditional_branch(*cont_block);
@@ -185,6 +309,18 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         Ok(())
     }
 
+    fn get_label_block(
+        &self,
+        label: &str,
+        location: &SourceRange,
+    ) -> Result<BasicBlock<'a>, Diagnostic> {
+        self.function_context
+            .labels
+            .get(&label.to_lowercase())
+            .copied()
+            .ok_or_else(|| Diagnostic::unknown_label(label, location.clone()))
+    }
+
     /// generates an assignment statement _left_ := _right_
     ///
     /// `left_statement` the left side of the assignment
@@ -228,6 +364,25 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         Ok(())
     }
 
+    /// binds the `REFERENCE TO` variable `left_statement` to the variable `right_statement`
+    fn generate_reference_assignment(
+        &self,
+        left_statement: &AstStatement,
+        right_statement: &AstStatement,
+    ) -> Result<(), Diagnostic> {
+        let exp_gen = self.create_expr_generator();
+        let reference = exp_gen.generate_reference_pointer(left_statement)?;
+        let target = exp_gen.generate_element_pointer(right_statement)?;
+        //an instance of an extending class may be bound to a reference to its super class
+        let target = self.llvm.builder.build_pointer_cast(
+            target,
+            reference.get_type().get_element_type().into_pointer_type(),
+            "",
+        );
+        self.llvm.builder.build_store(reference, target);
+        Ok(())
+    }
+
     fn generate_direct_access_assignment(
         &self,
         left_statement: &AstStatement,
@@ -394,6 +549,8 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
     /// - `end` the value indicating the end of the for loop
     /// - `by_step` the step of the loop
     /// - `body` the statements inside the for-loop
+    ///
+    /// returns the loop's header checking the condition
     fn generate_for_statement(
         &self,
         counter: &AstStatement,
@@ -401,7 +558,7 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         end: &AstStatement,
         by_step: &Option<Box<AstStatement>>,
         body: &[AstStatement],
-    ) -> Result<(), Diagnostic> {
+    ) -> Result<BasicBlock<'a>, Diagnostic> {
         let (builder, current_function, context) = self.get_llvm_deps();
         self.generate_assignment_statement(counter, start)?;
         let condition_check = context.append_basic_block(current_function, "condition_check");
@@ -438,6 +595,8 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
             ..*self
         };
         body_generator.generate_body(body)?;
+        //the increment belongs to the loop's header, not to the body's last statement
+        self.set_debug_location(&counter.get_location());
         builder.build_unconditional_branch(increment_block);
 
         //Increment
@@ -469,7 +628,7 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         //Continue
         builder.position_at_end(continue_block);
 
-        Ok(())
+        Ok(condition_check)
     }
 
     fn generate_compare_expression(
@@ -541,6 +700,23 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         conditional_blocks: &[ConditionalBlock],
         else_body: &[AstStatement],
     ) -> Result<Option<BasicValueEnum<'a>>, Diagnostic> {
+        if let DataTypeInformation::String { size, encoding } = self
+            .annotations
+            .get_type_or_void(selector, self.index)
+            .get_type_information()
+        {
+            let length = size
+                .as_int_value(self.index)
+                .map_err(|msg| Diagnostic::codegen_error(msg.as_str(), selector.get_location()))?;
+            return self.generate_string_case_statement(
+                selector,
+                length as usize,
+                encoding,
+                conditional_blocks,
+                else_body,
+            );
+        }
+
         let (builder, current_function, context) = self.get_llvm_deps();
         //Continue
         let continue_block = context.append_basic_block(current_function, "continue");
@@ -558,6 +734,7 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
             let case_block = context.prepend_basic_block(else_block, "case");
 
             //flatten the expression list into a vector of expressions
+            self.set_debug_location(&conditional_block.condition.get_location());
             let expressions = flatten_expression_list(&*conditional_block.condition);
             for s in expressions {
                 if let AstStatement::RangeStatement { start, end, .. } = s {
@@ -594,12 +771,128 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
 
         // now that we collected all cases, go back to the initial block and generate the switch-statement
         builder.position_at_end(basic_block);
+        self.set_debug_location(&selector.get_location());
         builder.build_switch(selector_statement.into_int_value(), else_block, &cases);
 
         builder.position_at_end(continue_block);
         Ok(None)
     }
 
+    /// generates a CASE over a STRING or WSTRING selector of the given length (in characters,
+    /// including the terminating 0). A switch dispatches the selector's first character, the
+    /// labels starting with it are then compared character by character
+    fn generate_string_case_statement(
+        &self,
+        selector: &AstStatement,
+        length: usize,
+        encoding: &StringEncoding,
+        conditional_blocks: &[ConditionalBlock],
+        else_body: &[AstStatement],
+    ) -> Result<Option<BasicValueEnum<'a>>, Diagnostic> {
+        let (builder, current_function, context) = self.get_llvm_deps();
+        let else_block = context.append_basic_block(current_function, "else");
+        let continue_block = context.append_basic_block(current_function, "continue");
+
+        let selector_ptr = self
+            .create_expr_generator()
+            .generate_string_pointer(selector)?;
+        let char_type = match encoding {
+            StringEncoding::Utf8 => context.i8_type(),
+            StringEncoding::Utf16 => context.i16_type(),
+        };
+        let load_char = |index: usize| {
+            let char_ptr = unsafe {
+                builder.build_in_bounds_gep(
+                    selector_ptr,
+                    &[
+                        context.i32_type().const_zero(),
+                        context.i32_type().const_int(index as u64, false),
+                    ],
+                    "",
+                )
+            };
+            builder.build_load(char_ptr, "").into_int_value()
+        };
+        let basic_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
+        let first_char = load_char(0);
+
+        // the labels' characters (including the terminating 0) grouped by their first character
+        let mut labels_by_first_char: Vec<(u64, Vec<(Vec<u64>, BasicBlock)>)> = Vec::new();
+        for conditional_block in conditional_blocks {
+            let case_block = context.prepend_basic_block(else_block, "case");
+            for label in flatten_expression_list(&*conditional_block.condition) {
+                let value = self
+                    .annotations
+                    .get_constant_string(label, self.index)
+                    .ok_or_else(|| {
+                        Diagnostic::codegen_error(
+                            "Expected a string literal or constant as case label",
+                            label.get_location(),
+                        )
+                    })?;
+                let mut chars: Vec<u64> = match encoding {
+                    StringEncoding::Utf8 => value.bytes().map(u64::from).collect(),
+                    StringEncoding::Utf16 => value.encode_utf16().map(u64::from).collect(),
+                };
+                chars.push(0);
+                if chars.len() > length {
+                    // the selector cannot hold this label
+                    continue;
+                }
+                match labels_by_first_char
+                    .iter_mut()
+                    .find(|(first, _)| *first == chars[0])
+                {
+                    Some((_, labels)) => labels.push((chars, case_block)),
+                    None => labels_by_first_char.push((chars[0], vec![(chars, case_block)])),
+                }
+            }
+            builder.position_at_end(case_block);
+            self.generate_body(&conditional_block.body)?;
+            builder.build_unconditional_branch(continue_block);
+        }
+
+        //the comparisons belong to the selector, not to the last case's body
+        self.set_debug_location(&selector.get_location());
+        let mut cases = Vec::new();
+        for (first, labels) in labels_by_first_char {
+            let mut compare_block = context.prepend_basic_block(else_block, "compare");
+            cases.push((char_type.const_int(first, false), compare_block));
+            let last = labels.len() - 1;
+            for (i, (chars, case_block)) in labels.into_iter().enumerate() {
+                builder.position_at_end(compare_block);
+                // the first character already matched, the following ones (up to the label's 0) have to
+                let mut is_equal = context.bool_type().const_int(1, false);
+                for (index, expected) in chars.iter().enumerate().skip(1) {
+                    let char_is_equal = builder.build_int_compare(
+                        IntPredicate::EQ,
+                        load_char(index),
+                        char_type.const_int(*expected, false),
+                        "",
+                    );
+                    is_equal = builder.build_and(is_equal, char_is_equal, "");
+                }
+                compare_block = if i == last {
+                    else_block
+                } else {
+                    context.prepend_basic_block(else_block, "compare")
+                };
+                builder.build_conditional_branch(is_equal, case_block, compare_block);
+            }
+        }
+
+        builder.position_at_end(else_block);
+        self.generate_body(else_body)?;
+        builder.build_unconditional_branch(continue_block);
+
+        builder.position_at_end(basic_block);
+        self.set_debug_location(&selector.get_location());
+        builder.build_switch(first_char, else_block, &cases);
+
+        builder.position_at_end(continue_block);
+        Ok(None)
+    }
+
     /// returns the new block to use as else
     ///
     ///
@@ -656,11 +949,13 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
     ///
     /// - `condition` the while's condition
     /// - `body` the while's body statements
+    ///
+    /// returns the loop's header checking the condition
     fn generate_while_statement(
         &self,
         condition: &AstStatement,
         body: &[AstStatement],
-    ) -> Result<Option<BasicValueEnum<'a>>, Diagnostic> {
+    ) -> Result<BasicBlock<'a>, Diagnostic> {
         let builder = &self.llvm.builder;
         let basic_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
         let (condition_block, _) = self.generate_base_while_statement(condition, body)?;
@@ -671,7 +966,7 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         builder.build_unconditional_branch(condition_block);
 
         builder.position_at_end(continue_block);
-        Ok(None)
+        Ok(condition_block)
     }
 
     /// generates a repeat statement
@@ -683,11 +978,13 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
     ///
     /// - `condition` the repeat's condition
     /// - `body` the repeat's body statements
+    ///
+    /// returns the loop's header, the first block of its body
     fn generate_repeat_statement(
         &self,
         condition: &AstStatement,
         body: &[AstStatement],
-    ) -> Result<Option<BasicValueEnum<'a>>, Diagnostic> {
+    ) -> Result<BasicBlock<'a>, Diagnostic> {
         let builder = &self.llvm.builder;
         let basic_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
         let (_, while_block) = self.generate_base_while_statement(condition, body)?;
@@ -698,7 +995,7 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         builder.build_unconditional_branch(while_block);
 
         builder.position_at_end(continue_block);
-        Ok(None)
+        Ok(while_block)
     }
 
     /// utility method for while and repeat loops
@@ -714,6 +1011,7 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
 
         //Check loop condition
         builder.position_at_end(condition_check);
+        self.set_debug_location(&condition.get_location());
         let condition_value = self
             .create_expr_generator()
             .generate_expression(condition)?;
@@ -734,6 +1032,7 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         };
         body_generator.generate_body(body)?;
         //Loop back
+        self.set_debug_location(&condition.get_location());
         builder.build_unconditional_branch(condition_check);
 
         //Continue
@@ -773,6 +1072,7 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
 
             builder.position_at_end(then_block);
 
+            self.set_debug_location(&block.condition.get_location());
             let condition = self
                 .create_expr_generator()
                 .generate_expression(&block.condition)?;
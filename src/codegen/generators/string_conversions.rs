@@ -0,0 +1,168 @@
+//! declares the runtime's helpers behind the conversions between numbers and strings
+//! (e.g. `INT_TO_STRING`, `STRING_TO_REAL`)
+//!
+//! integers are passed to the helpers as LINT (ULINT if unsigned), strings are parsed into an
+//! LREAL and truncated if a REAL is requested. DTs (milliseconds) and TIMEs (nanoseconds) have
+//! helpers of their own to format and parse them like literals (e.g. `T#1h30m`). Parsing reports invalid strings through an optional
+//! `error` flag. The helpers are provided by the runtime (`rusty-runtime`) and are only declared if
+//! the application calls one of the conversions.
+use inkwell::{
+    context::Context,
+    module::{Linkage, Module},
+    types::{BasicMetadataTypeEnum, BasicTypeEnum, FunctionType},
+    AddressSpace,
+};
+
+use super::llvm::Llvm;
+use crate::{
+    codegen::llvm_index::LlvmTypedIndex,
+    diagnostics::Diagnostic,
+    index::Index,
+    resolver::AstAnnotations,
+    typesystem::{DataTypeInformation, StringEncoding, DATE_AND_TIME_TYPE, REAL_SIZE, TIME_TYPE},
+};
+
+/// the representation of a number passed to or returned from a helper
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NumberKind {
+    Signed,
+    Unsigned,
+    Real,
+    LReal,
+    DateAndTime,
+    Time,
+}
+
+/// a conversion between a number and a STRING or WSTRING
+#[derive(Clone, Debug, PartialEq)]
+pub struct StringConversion {
+    /// the name of the runtime's helper implementing the conversion
+    pub helper: String,
+    pub number: NumberKind,
+    pub encoding: StringEncoding,
+    /// true if the number is converted into a string, false if the string is parsed
+    pub to_string: bool,
+}
+
+impl StringConversion {
+    /// returns the signature of the helper:
+    /// - `void(number, char*, i32 length)` to convert a number into a string
+    /// - `number(char*, i32 length, i32 bits, i8* error)` to parse an integer
+    /// - `number(char*, i32 length, i8* error)` to parse a real, a DT or a TIME
+    pub fn get_helper_type<'ink>(&self, context: &'ink Context) -> FunctionType<'ink> {
+        let char_type = match self.encoding {
+            StringEncoding::Utf8 => context.i8_type(),
+            StringEncoding::Utf16 => context.i16_type(),
+        };
+        let string_type = char_type.ptr_type(AddressSpace::Generic).into();
+        let i32_type = context.i32_type().into();
+        let number_type: BasicTypeEnum = match self.number {
+            NumberKind::Signed
+            | NumberKind::Unsigned
+            | NumberKind::DateAndTime
+            | NumberKind::Time => context.i64_type().into(),
+            NumberKind::Real => context.f32_type().into(),
+            NumberKind::LReal => context.f64_type().into(),
+        };
+
+        if self.to_string {
+            context
+                .void_type()
+                .fn_type(&[number_type.into(), string_type, i32_type], false)
+        } else {
+            let mut parameters: Vec<BasicMetadataTypeEnum> = vec![string_type, i32_type];
+            if self.is_integer() {
+                parameters.push(i32_type);
+            }
+            parameters.push(context.i8_type().ptr_type(AddressSpace::Generic).into());
+            number_type.fn_type(&parameters, false)
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self.number, NumberKind::Signed | NumberKind::Unsigned)
+    }
+}
+
+/// returns the conversion named by the given function (e.g. `DINT_TO_WSTRING`) or None if the
+/// function does not convert between a number and a string
+pub fn find_string_conversion(function_name: &str, index: &Index) -> Option<StringConversion> {
+    let function_name = function_name.to_uppercase();
+    let (from, to) = function_name.split_once("_TO_")?;
+    let get_encoding = |name: &str| match name {
+        "STRING" => Some(StringEncoding::Utf8),
+        "WSTRING" => Some(StringEncoding::Utf16),
+        _ => None,
+    };
+    let (number, encoding, to_string) = match get_encoding(to) {
+        Some(encoding) => (from, encoding, true),
+        None => (to, get_encoding(from)?, false),
+    };
+
+    let number = match index.find_effective_type_info(number)? {
+        DataTypeInformation::Integer { name, .. } if name == DATE_AND_TIME_TYPE => {
+            NumberKind::DateAndTime
+        }
+        DataTypeInformation::Integer { name, .. } if name == TIME_TYPE => NumberKind::Time,
+        DataTypeInformation::Integer { signed: true, .. } => NumberKind::Signed,
+        DataTypeInformation::Integer { .. } => NumberKind::Unsigned,
+        // strings are always parsed into an LREAL
+        DataTypeInformation::Float {
+            size: REAL_SIZE, ..
+        } if to_string => NumberKind::Real,
+        DataTypeInformation::Float { .. } => NumberKind::LReal,
+        _ => return None,
+    };
+    let number_name = match number {
+        NumberKind::Signed => "lint",
+        NumberKind::Unsigned => "ulint",
+        NumberKind::Real => "real",
+        NumberKind::LReal => "lreal",
+        NumberKind::DateAndTime => "dt",
+        NumberKind::Time => "time",
+    };
+    let string_name = match encoding {
+        StringEncoding::Utf8 => "string",
+        StringEncoding::Utf16 => "wstring",
+    };
+    let helper = if to_string {
+        format!("__rusty_{}_to_{}", number_name, string_name)
+    } else {
+        format!("__rusty_{}_to_{}", string_name, number_name)
+    };
+    Some(StringConversion {
+        helper,
+        number,
+        encoding,
+        to_string,
+    })
+}
+
+/// declares the helpers of the builtin conversions called by the application
+/// Returns a new LLVM index containing the declared helpers
+pub fn declare_helpers<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    annotations: &AstAnnotations,
+) -> Result<LlvmTypedIndex<'ink>, Diagnostic> {
+    let mut llvm_index = LlvmTypedIndex::default();
+    //sort the helpers, so we get reproducable builds
+    let mut conversions = annotations
+        .get_called_functions()
+        .filter(|it| index.get_builtin_function(it).is_some())
+        .filter_map(|it| find_string_conversion(it, index))
+        .collect::<Vec<_>>();
+    conversions.sort_by(|a, b| a.helper.cmp(&b.helper));
+    for conversion in conversions {
+        if module.get_function(&conversion.helper).is_none() {
+            let helper = module.add_function(
+                &conversion.helper,
+                conversion.get_helper_type(llvm.context),
+                Some(Linkage::External),
+            );
+            llvm_index.associate_implementation(&conversion.helper, helper)?;
+        }
+    }
+    Ok(llvm_index)
+}
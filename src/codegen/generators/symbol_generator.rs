@@ -0,0 +1,224 @@
+//! generates the symbol table consumed by the runtime's `read_symbol` and `write_symbol` (see the
+//! `rusty-runtime` crate), so HMI or SCADA bridges can access variables by their instance path
+//! (e.g. `prg.fb1.sub.out`)
+//!
+//! the table is a trie of `__rusty_symbol`s (name, declared type, offset, size and the index and
+//! number of the node's children) stored in `__rusty_symbols` and counted by
+//! `__rusty_symbol_count`. The first node is the root, its children are the global variables and
+//! the programs. The children of a node are stored next to each other ordered by their lower case
+//! name, so they can be searched by bisection. The offset of a global variable or program is the
+//! address of its instance while the offset of a member is relative to its parent, the address
+//! of a path is the sum of the offsets along it. The members of a struct or POU are stored once
+//! and shared by all of its instances.
+use std::collections::HashMap;
+
+use inkwell::{
+    module::Module,
+    types::{BasicType, BasicTypeEnum, StructType},
+    values::BasicValueEnum,
+    AddressSpace,
+};
+
+use super::{debug_info, llvm::Llvm, reflection_generator, task_generator};
+use crate::{
+    diagnostics::Diagnostic,
+    index::{Index, PouIndexEntry},
+    typesystem::DataTypeInformation,
+};
+
+pub const SYMBOLS: &str = "__rusty_symbols";
+pub const SYMBOL_COUNT: &str = "__rusty_symbol_count";
+
+/// a node of the trie, `members` names the struct or POU whose members are the node's children
+struct SymbolNode<'ink> {
+    name: String,
+    type_name: String,
+    offset: BasicValueEnum<'ink>,
+    size: BasicValueEnum<'ink>,
+    members: Option<String>,
+    children: (usize, usize),
+}
+
+pub fn generate_symbol_table<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+) -> Result<(), Diagnostic> {
+    let context = llvm.context;
+    let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    let i64_type = context.i64_type();
+    let i32_type = context.i32_type();
+
+    let mut roots = get_roots(module, llvm, index);
+    roots.sort_by_key(|it| it.name.to_lowercase());
+    let mut nodes = vec![SymbolNode {
+        name: String::new(),
+        type_name: String::new(),
+        offset: i64_type.const_zero().into(),
+        size: i64_type.const_zero().into(),
+        members: None,
+        children: (1, roots.len()),
+    }];
+    nodes.extend(roots);
+
+    //the members of every type are appended once, the nodes appended are visited in turn
+    let mut member_ranges: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut node = 1;
+    while node < nodes.len() {
+        if let Some(type_name) = nodes[node].members.clone() {
+            let range = match member_ranges.get(&type_name) {
+                Some(range) => *range,
+                None => {
+                    let mut members = get_members(module, llvm, index, &type_name);
+                    members.sort_by_key(|it| it.name.to_lowercase());
+                    let range = (nodes.len(), members.len());
+                    nodes.extend(members);
+                    member_ranges.insert(type_name, range);
+                    range
+                }
+            };
+            nodes[node].children = range;
+        }
+        node += 1;
+    }
+
+    let symbol_type = llvm.create_struct_stub("__rusty_symbol");
+    symbol_type.set_body(
+        &[
+            i8_ptr_type.into(),
+            i8_ptr_type.into(),
+            i64_type.into(),
+            i64_type.into(),
+            i32_type.into(),
+            i32_type.into(),
+        ],
+        false,
+    );
+    let mut descriptors = Vec::new();
+    for node in &nodes {
+        let (first_child, child_count) = node.children;
+        descriptors.push(
+            symbol_type.const_named_struct(&[
+                llvm.create_private_string_constant(module, "__rusty_symbol_name", &node.name)?
+                    .into(),
+                llvm.create_private_string_constant(
+                    module,
+                    "__rusty_symbol_type",
+                    &node.type_name,
+                )?
+                .into(),
+                node.offset,
+                node.size,
+                i32_type.const_int(first_child as u64, false).into(),
+                i32_type.const_int(child_count as u64, false).into(),
+            ]),
+        );
+    }
+    task_generator::generate_table(
+        module,
+        llvm,
+        SYMBOLS,
+        SYMBOL_COUNT,
+        symbol_type,
+        &descriptors,
+    );
+    Ok(())
+}
+
+/// returns the global variables and the programs declared by this application, the constants are
+/// left out as they may have been replaced by their values
+fn get_roots<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+) -> Vec<SymbolNode<'ink>> {
+    let i64_type = llvm.context.i64_type();
+    let globals = index
+        .get_globals()
+        .values()
+        .filter(|it| !it.is_external() && !it.is_constant() && !it.get_name().starts_with("__"))
+        .map(|it| (it.get_name(), it.get_type_name(), it.get_name()));
+    let programs = index.get_pous().values().filter_map(|it| match it {
+        PouIndexEntry::Program {
+            name,
+            instance_variable,
+            ..
+        } if !instance_variable.is_external() => Some((
+            name.as_str(),
+            instance_variable.get_type_name(),
+            instance_variable.get_name(),
+        )),
+        _ => None,
+    });
+
+    globals
+        .chain(programs)
+        .filter_map(|(name, type_name, symbol)| {
+            let address = module.get_global(symbol)?.as_pointer_value();
+            let size = BasicTypeEnum::try_from(address.get_type().get_element_type())
+                .ok()?
+                .size_of()?;
+            Some(SymbolNode {
+                name: name.to_string(),
+                type_name: debug_info::get_declared_type_name(type_name, index),
+                offset: address.const_to_int(i64_type).into(),
+                size: size.into(),
+                members: get_struct_type(module, index, type_name)
+                    .map(|(name, _)| name.to_string()),
+                children: (0, 0),
+            })
+        })
+        .collect()
+}
+
+/// returns the members of the given struct or POU, the `VAR_IN_OUT` members and the members
+/// generated by the compiler (e.g. `__vtable`) are left out
+fn get_members<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    type_name: &str,
+) -> Vec<SymbolNode<'ink>> {
+    let struct_type = match get_struct_type(module, index, type_name) {
+        Some((_, struct_type)) => struct_type,
+        None => return vec![],
+    };
+    index
+        .get_container_members(type_name)
+        .into_iter()
+        .filter(|it| !it.is_temp() && !it.is_return())
+        .enumerate()
+        .filter(|(_, it)| {
+            !it.get_declaration_type().is_by_ref() && !it.get_name().starts_with("__")
+        })
+        .filter_map(|(element, member)| {
+            let size = struct_type
+                .get_field_type_at_index(element as u32)?
+                .size_of()?;
+            Some(SymbolNode {
+                name: member.get_name().to_string(),
+                type_name: debug_info::get_declared_type_name(member.get_type_name(), index),
+                offset: reflection_generator::get_offset_of(struct_type, element as u32, llvm),
+                size: size.into(),
+                members: get_struct_type(module, index, member.get_type_name())
+                    .map(|(name, _)| name.to_string()),
+                children: (0, 0),
+            })
+        })
+        .collect()
+}
+
+/// returns the name and the llvm struct of the given struct or POU, `None` for other types
+fn get_struct_type<'ink, 'idx>(
+    module: &Module<'ink>,
+    index: &'idx Index,
+    type_name: &str,
+) -> Option<(&'idx str, StructType<'ink>)> {
+    let data_type = index.find_effective_type(type_name)?;
+    match data_type.get_type_information() {
+        DataTypeInformation::Struct { name, .. } => {
+            Some((data_type.get_name(), module.get_struct_type(name)?))
+        }
+        _ => None,
+    }
+}
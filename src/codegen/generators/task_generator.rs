@@ -0,0 +1,345 @@
+//! generates the task table consumed by the runtime's scheduler (see the `rusty-runtime` crate)
+//!
+//! every task of the task configuration is described by a `__rusty_task` (name, priority, interval
+//! in milliseconds, an entry function calling the task's programs in order, the number of the
+//! external event executing the task or -1 for cyclic tasks and the core the task is executed on
+//! or -1 if it is not bound to a core), the descriptors are stored in
+//! `__rusty_tasks` and counted by `__rusty_task_count`. The entry of a task with a trigger is a
+//! wrapper, it compares the trigger with its value of the previous call (kept in
+//! `__rusty_task_<name>_trigger`) and only calls the programs on a rising edge, so the runtime
+//! executes it like a cyclic task. Every additional instance of a program gets a copy of the
+//! program's instance struct (`<name>_instance`, starting with the program's initial values) and a
+//! cycle function `<name>()` calling the program with it, a task executes the instance by calling
+//! this function. The retained variables are
+//! described by a `__rusty_retain_entry` (address and size), stored in `__rusty_retain` and
+//! counted by `__rusty_retain_count`, so the runtime can persist them across restarts.
+use inkwell::{
+    module::{Linkage, Module},
+    types::{BasicType, BasicTypeEnum, StructType},
+    values::{BasicValueEnum, PointerValue, StructValue},
+    AddressSpace, IntPredicate,
+};
+
+use super::{
+    llvm::{GlobalValueExt, Llvm},
+    telemetry_generator::{self, PathError},
+};
+use crate::{
+    diagnostics::Diagnostic,
+    index::{Index, PouIndexEntry},
+    task_configuration::{ProgramInstance, Task, TaskConfiguration},
+};
+
+pub const TASKS: &str = "__rusty_tasks";
+pub const TASK_COUNT: &str = "__rusty_task_count";
+pub const RETAIN: &str = "__rusty_retain";
+pub const RETAIN_COUNT: &str = "__rusty_retain_count";
+
+pub fn generate_task_table<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    tasks: &TaskConfiguration,
+) -> Result<(), Diagnostic> {
+    let context = llvm.context;
+    let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    let entry_type = context.void_type().fn_type(&[], false);
+
+    let task_type = llvm.create_struct_stub("__rusty_task");
+    task_type.set_body(
+        &[
+            i8_ptr_type.into(),
+            context.i32_type().into(),
+            context.i64_type().into(),
+            entry_type.ptr_type(AddressSpace::Generic).into(),
+            context.i32_type().into(),
+            context.i32_type().into(),
+        ],
+        false,
+    );
+
+    for instance in &tasks.instances {
+        generate_program_instance(module, llvm, index, instance)?;
+    }
+
+    let mut descriptors = Vec::new();
+    for task in &tasks.tasks {
+        let entry = generate_task_entry(module, llvm, index, tasks, task)?;
+        descriptors.push(
+            task_type.const_named_struct(&[
+                llvm.create_private_string_constant(module, "__rusty_task_name", &task.name)?
+                    .into(),
+                context
+                    .i32_type()
+                    .const_int(task.priority as u64, false)
+                    .into(),
+                context.i64_type().const_int(task.interval, false).into(),
+                entry.into(),
+                context
+                    .i32_type()
+                    .const_int(task.event.map_or(-1, i64::from) as u64, true)
+                    .into(),
+                context
+                    .i32_type()
+                    .const_int(task.core.map_or(-1, i64::from) as u64, true)
+                    .into(),
+            ]),
+        );
+    }
+    generate_table(module, llvm, TASKS, TASK_COUNT, task_type, &descriptors);
+
+    let retain_type = llvm.create_struct_stub("__rusty_retain_entry");
+    retain_type.set_body(&[i8_ptr_type.into(), context.i64_type().into()], false);
+    let retained = get_retained_addresses(module, llvm, index)
+        .into_iter()
+        .filter_map(|address| {
+            let size = BasicTypeEnum::try_from(address.get_type().get_element_type())
+                .ok()?
+                .size_of()?;
+            Some(
+                retain_type
+                    .const_named_struct(&[address.const_cast(i8_ptr_type).into(), size.into()]),
+            )
+        })
+        .collect::<Vec<_>>();
+    generate_table(module, llvm, RETAIN, RETAIN_COUNT, retain_type, &retained);
+    Ok(())
+}
+
+/// generates the instance struct `<name>_instance` of an additional program instance and its cycle
+/// function `<name>()`
+fn generate_program_instance<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    instance: &ProgramInstance,
+) -> Result<(), Diagnostic> {
+    let unknown_program = || {
+        Diagnostic::param_error(&format!(
+            "Program instance '{}' instantiates unknown program '{}'",
+            instance.name, instance.program
+        ))
+    };
+    let (program, program_instance) = match index.find_pou(&instance.program) {
+        Some(PouIndexEntry::Program {
+            name,
+            instance_variable,
+            ..
+        }) => (
+            module.get_function(name).ok_or_else(unknown_program)?,
+            module
+                .get_global(instance_variable.get_name())
+                .ok_or_else(unknown_program)?,
+        ),
+        _ => return Err(unknown_program()),
+    };
+    let instance_name = format!("{}_instance", instance.name);
+    if index.find_pou(&instance.name).is_some()
+        || index.find_global_variable(&instance.name).is_some()
+        || module.get_function(&instance.name).is_some()
+        || module.get_global(&instance_name).is_some()
+    {
+        return Err(Diagnostic::param_error(&format!(
+            "Program instance '{}' conflicts with a declaration of the same name",
+            instance.name
+        )));
+    }
+
+    let instance_type = program_instance.get_type().get_element_type();
+    let instance_type = BasicTypeEnum::try_from(instance_type).map_err(|_| unknown_program())?;
+    let instance_struct = llvm.create_global_variable(module, &instance_name, instance_type);
+    let initializer = program_instance
+        .get_initializer()
+        .unwrap_or_else(|| instance_type.const_zero());
+    instance_struct.set_initializer(&initializer);
+
+    let function = module.add_function(
+        &instance.name,
+        llvm.context.void_type().fn_type(&[], false),
+        None,
+    );
+    llvm.builder
+        .position_at_end(llvm.context.append_basic_block(function, "entry"));
+    llvm.builder
+        .build_call(program, &[instance_struct.as_pointer_value().into()], "");
+    llvm.builder.build_return(None);
+    Ok(())
+}
+
+/// generates `__rusty_task_<name>()`, which calls the task's programs with their instances (the
+/// cycle functions of additional program instances), if the task has a trigger only on its
+/// rising edge
+fn generate_task_entry<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    tasks: &TaskConfiguration,
+    task: &Task,
+) -> Result<PointerValue<'ink>, Diagnostic> {
+    let context = llvm.context;
+    let function = module.add_function(
+        format!("__rusty_task_{}", task.name).as_str(),
+        context.void_type().fn_type(&[], false),
+        None,
+    );
+    llvm.builder
+        .position_at_end(context.append_basic_block(function, "entry"));
+    let exit = context.append_basic_block(function, "exit");
+    if let Some(trigger) = &task.trigger {
+        let (address, type_name) = telemetry_generator::resolve_instance_path(
+            module, llvm, index, trigger,
+        )
+        .map_err(|err| {
+            let reason = match err {
+                PathError::Unknown => "it is unknown",
+                PathError::ByReference => "it is passed by reference",
+            };
+            Diagnostic::param_error(&format!(
+                "Task '{}' cannot be triggered by '{}', {}",
+                task.name, trigger, reason
+            ))
+        })?;
+        if !index
+            .find_effective_type_info(&type_name)
+            .map_or(false, |it| it.is_bool())
+        {
+            return Err(Diagnostic::param_error(&format!(
+                "Task '{}' cannot be triggered by '{}' of type {}, \
+                only BOOL variables can trigger a task",
+                task.name, trigger, type_name
+            )));
+        }
+        let value_type = address.get_type().get_element_type().into_int_type();
+        let previous = llvm.create_global_variable(
+            module,
+            &format!("__rusty_task_{}_trigger", task.name),
+            value_type.into(),
+        );
+        previous.set_linkage(Linkage::Private);
+        previous.set_initializer(&value_type.const_zero());
+
+        let value = llvm.builder.build_load(address, "trigger").into_int_value();
+        let last = llvm
+            .builder
+            .build_load(previous.as_pointer_value(), "previous")
+            .into_int_value();
+        llvm.builder.build_store(previous.as_pointer_value(), value);
+        let zero = value_type.const_zero();
+        let is_set = llvm
+            .builder
+            .build_int_compare(IntPredicate::NE, value, zero, "");
+        let was_set = llvm
+            .builder
+            .build_int_compare(IntPredicate::NE, last, zero, "");
+        let was_clear = llvm.builder.build_not(was_set, "");
+        let rising_edge = llvm.builder.build_and(is_set, was_clear, "rising_edge");
+        let execute = context.prepend_basic_block(exit, "execute");
+        llvm.builder
+            .build_conditional_branch(rising_edge, execute, exit);
+        llvm.builder.position_at_end(execute);
+    }
+    for program in &task.programs {
+        if let Some(instance) = tasks.find_instance(program) {
+            let cycle = module.get_function(&instance.name).ok_or_else(|| {
+                Diagnostic::param_error(&format!(
+                    "Task '{}' executes unknown program '{}'",
+                    task.name, program
+                ))
+            })?;
+            llvm.builder.build_call(cycle, &[], "");
+            continue;
+        }
+        let unknown_program = || {
+            Diagnostic::param_error(&format!(
+                "Task '{}' executes unknown program '{}'",
+                task.name, program
+            ))
+        };
+        let (name, instance) = match index.find_pou(program) {
+            Some(PouIndexEntry::Program {
+                name,
+                instance_variable,
+                ..
+            }) => (name, instance_variable),
+            _ => return Err(unknown_program()),
+        };
+        let program_function = module.get_function(name).ok_or_else(unknown_program)?;
+        let instance = module
+            .get_global(instance.get_name())
+            .ok_or_else(unknown_program)?;
+        llvm.builder
+            .build_call(program_function, &[instance.as_pointer_value().into()], "");
+    }
+    llvm.builder.build_unconditional_branch(exit);
+    llvm.builder.position_at_end(exit);
+    llvm.builder.build_return(None);
+    Ok(function.as_global_value().as_pointer_value())
+}
+
+/// returns the addresses of the retained global variables and program members
+fn get_retained_addresses<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+) -> Vec<PointerValue<'ink>> {
+    let i32_type = llvm.context.i32_type();
+    let globals = index
+        .get_globals()
+        .values()
+        .filter(|it| it.is_retain() && !it.is_external())
+        .filter_map(|it| module.get_global(it.get_name()))
+        .map(|it| it.as_pointer_value());
+
+    let members = index
+        .get_program_instances()
+        .into_iter()
+        .filter(|it| !it.is_external())
+        .filter_map(|instance| {
+            module
+                .get_global(instance.get_name())
+                .map(|global| (instance, global))
+        })
+        .flat_map(|(instance, global)| {
+            index
+                .get_container_members(instance.get_type_name())
+                .into_iter()
+                .filter(|it| !it.is_temp() && !it.is_return())
+                .enumerate()
+                .filter(|(_, it)| it.is_retain())
+                .map(move |(element, _)| unsafe {
+                    global.as_pointer_value().const_gep(&[
+                        i32_type.const_zero(),
+                        i32_type.const_int(element as u64, false),
+                    ])
+                })
+        });
+
+    globals.chain(members).collect()
+}
+
+/// generates a constant table holding the given descriptors and a constant holding their count
+pub(crate) fn generate_table<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    name: &str,
+    count_name: &str,
+    element_type: StructType<'ink>,
+    elements: &[StructValue<'ink>],
+) {
+    let table = llvm.create_global_variable(
+        module,
+        name,
+        element_type.array_type(elements.len() as u32).into(),
+    );
+    table
+        .make_constant()
+        .set_initializer(&element_type.const_array(elements));
+
+    let count_type = llvm.context.i32_type();
+    let count = llvm.create_global_variable(module, count_name, count_type.into());
+    count
+        .make_constant()
+        .set_initializer(&BasicValueEnum::IntValue(
+            count_type.const_int(elements.len() as u64, false),
+        ));
+}
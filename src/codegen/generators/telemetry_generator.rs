@@ -0,0 +1,309 @@
+//! generates the telemetry publisher called cyclically by the runtime (see the `rusty-runtime`
+//! crate), so the values of the configured variables can be pushed to MQTT or IoT brokers without
+//! hand-written marshaling
+//!
+//! `__rusty_telemetry(buffer, size)` serializes the variables of the task configuration's
+//! `telemetry` section into a JSON object keyed by their instance path (e.g.
+//! `{"prg.speed":12,"prg.name":"left"}`) and returns its length. The text is written by the
+//! runtime's `__rusty_json_*` helpers, every helper appends to the buffer and returns the new
+//! length. A length greater than the buffer's size means the object was truncated and has to be
+//! serialized again into a larger buffer. The publishing cycle in milliseconds is stored in
+//! `__rusty_telemetry_interval`.
+//!
+//! integers are passed to the helpers as LINT (ULINT if unsigned), enums as their numeric value
+//! and reals as LREAL. The date and time types are published as numbers (nanoseconds for `TIME`,
+//! milliseconds since 1970 for the dates). Arrays, structs and function blocks cannot be
+//! published as a whole, their elementary members have to be listed instead.
+use inkwell::{
+    module::{Linkage, Module},
+    types::BasicMetadataTypeEnum,
+    values::{BasicMetadataValueEnum, FunctionValue, IntValue, PointerValue},
+    AddressSpace,
+};
+
+use super::llvm::{GlobalValueExt, Llvm};
+use crate::{
+    diagnostics::Diagnostic,
+    index::{Index, PouIndexEntry},
+    task_configuration::Telemetry,
+    typesystem::{DataTypeInformation, StringEncoding},
+};
+
+pub const TELEMETRY: &str = "__rusty_telemetry";
+pub const TELEMETRY_INTERVAL: &str = "__rusty_telemetry_interval";
+
+/// the representation of a published variable passed to the runtime's helpers
+#[derive(Clone, Debug, PartialEq)]
+enum JsonKind {
+    Signed,
+    Unsigned,
+    Real,
+    Bool,
+    String(StringEncoding),
+}
+
+impl JsonKind {
+    fn get_helper_name(&self) -> &'static str {
+        match self {
+            JsonKind::Signed => "__rusty_json_lint",
+            JsonKind::Unsigned => "__rusty_json_ulint",
+            JsonKind::Real => "__rusty_json_lreal",
+            JsonKind::Bool => "__rusty_json_bool",
+            JsonKind::String(StringEncoding::Utf8) => "__rusty_json_string",
+            JsonKind::String(StringEncoding::Utf16) => "__rusty_json_wstring",
+        }
+    }
+}
+
+pub fn generate_telemetry<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    telemetry: &Telemetry,
+) -> Result<(), Diagnostic> {
+    let context = llvm.context;
+    let i8_ptr_type = context.i8_type().ptr_type(AddressSpace::Generic);
+    let i32_type = context.i32_type();
+    let function = module.add_function(
+        TELEMETRY,
+        i32_type.fn_type(&[i8_ptr_type.into(), i32_type.into()], false),
+        None,
+    );
+    llvm.builder
+        .position_at_end(context.append_basic_block(function, "entry"));
+    let buffer = function.get_nth_param(0).unwrap().into_pointer_value();
+    let size = function.get_nth_param(1).unwrap().into_int_value();
+
+    let mut position = i32_type.const_zero();
+    for (element, path) in telemetry.symbols.iter().enumerate() {
+        let (address, kind) = get_symbol(module, llvm, index, path)?;
+        let separator = if element == 0 { "{" } else { "," };
+        position = append_text(
+            module,
+            llvm,
+            buffer,
+            size,
+            position,
+            &format!("{}\"{}\":", separator, path),
+        )?;
+
+        let value = llvm.builder.build_load(address, "");
+        let mut arguments: Vec<BasicMetadataValueEnum> =
+            vec![buffer.into(), size.into(), position.into()];
+        match &kind {
+            JsonKind::Signed => arguments.push(
+                llvm.builder
+                    .build_int_s_extend_or_bit_cast(value.into_int_value(), context.i64_type(), "")
+                    .into(),
+            ),
+            JsonKind::Unsigned => arguments.push(
+                llvm.builder
+                    .build_int_z_extend_or_bit_cast(value.into_int_value(), context.i64_type(), "")
+                    .into(),
+            ),
+            JsonKind::Bool => arguments.push(
+                llvm.builder
+                    .build_int_z_extend_or_bit_cast(value.into_int_value(), context.i8_type(), "")
+                    .into(),
+            ),
+            JsonKind::Real => arguments.push(
+                llvm.builder
+                    .build_float_cast(value.into_float_value(), context.f64_type(), "")
+                    .into(),
+            ),
+            JsonKind::String(encoding) => {
+                let char_type = match encoding {
+                    StringEncoding::Utf8 => context.i8_type(),
+                    StringEncoding::Utf16 => context.i16_type(),
+                };
+                let length = address
+                    .get_type()
+                    .get_element_type()
+                    .into_array_type()
+                    .len();
+                arguments.push(
+                    llvm.builder
+                        .build_pointer_cast(address, char_type.ptr_type(AddressSpace::Generic), "")
+                        .into(),
+                );
+                arguments.push(i32_type.const_int(length as u64, false).into());
+            }
+        }
+        let helper = get_helper(module, llvm, kind.get_helper_name(), &arguments);
+        position = llvm
+            .builder
+            .build_call(helper, &arguments, "")
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_int_value();
+    }
+    let end = if telemetry.symbols.is_empty() {
+        "{}"
+    } else {
+        "}"
+    };
+    position = append_text(module, llvm, buffer, size, position, end)?;
+    llvm.builder.build_return(Some(&position));
+
+    let interval =
+        llvm.create_global_variable(module, TELEMETRY_INTERVAL, context.i64_type().into());
+    interval
+        .make_constant()
+        .set_initializer(&context.i64_type().const_int(telemetry.interval, false));
+    Ok(())
+}
+
+/// appends the given text unescaped with `__rusty_json_text` and returns the new length
+fn append_text<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    buffer: PointerValue<'ink>,
+    size: IntValue<'ink>,
+    position: IntValue<'ink>,
+    text: &str,
+) -> Result<IntValue<'ink>, Diagnostic> {
+    let text = llvm.create_private_string_constant(module, "__rusty_telemetry_text", text)?;
+    let arguments: Vec<BasicMetadataValueEnum> =
+        vec![buffer.into(), size.into(), position.into(), text.into()];
+    let helper = get_helper(module, llvm, "__rusty_json_text", &arguments);
+    Ok(llvm
+        .builder
+        .build_call(helper, &arguments, "")
+        .try_as_basic_value()
+        .left()
+        .unwrap()
+        .into_int_value())
+}
+
+/// returns the helper with the given name, it is declared as `i32(i8* buffer, i32 size,
+/// i32 position, ...)` taking the given arguments on its first use
+fn get_helper<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    name: &str,
+    arguments: &[BasicMetadataValueEnum<'ink>],
+) -> FunctionValue<'ink> {
+    module.get_function(name).unwrap_or_else(|| {
+        let parameters = arguments
+            .iter()
+            .map(|it| match it {
+                BasicMetadataValueEnum::IntValue(value) => value.get_type().into(),
+                BasicMetadataValueEnum::FloatValue(value) => value.get_type().into(),
+                BasicMetadataValueEnum::PointerValue(value) => value.get_type().into(),
+                _ => unreachable!("the helpers only take numbers and pointers"),
+            })
+            .collect::<Vec<BasicMetadataTypeEnum>>();
+        module.add_function(
+            name,
+            llvm.context.i32_type().fn_type(&parameters, false),
+            Some(Linkage::External),
+        )
+    })
+}
+
+/// the reason an instance path cannot be resolved to the address of a variable
+pub(super) enum PathError {
+    Unknown,
+    ByReference,
+}
+
+/// returns the address and the type name of the variable at the given instance path, the path
+/// starts with a global variable or a program followed by the names of struct, function block or
+/// program members
+pub(super) fn resolve_instance_path<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    path: &str,
+) -> Result<(PointerValue<'ink>, String), PathError> {
+    let mut names = path.split('.').map(str::trim);
+    let root = names.next().unwrap_or_default();
+    let (variable, type_name) = match index.find_pou(root) {
+        Some(PouIndexEntry::Program {
+            instance_variable, ..
+        }) => (
+            instance_variable.get_name(),
+            instance_variable.get_type_name(),
+        ),
+        _ => index
+            .find_global_variable(root)
+            .filter(|it| !it.is_constant())
+            .map(|it| (it.get_name(), it.get_type_name()))
+            .ok_or(PathError::Unknown)?,
+    };
+    let mut address = module
+        .get_global(variable)
+        .ok_or(PathError::Unknown)?
+        .as_pointer_value();
+    let mut type_name = type_name.to_string();
+
+    let i32_type = llvm.context.i32_type();
+    for name in names {
+        let (element, member) = index
+            .get_container_members(&type_name)
+            .into_iter()
+            .filter(|it| !it.is_temp() && !it.is_return())
+            .enumerate()
+            .find(|(_, it)| it.get_name().eq_ignore_ascii_case(name))
+            .ok_or(PathError::Unknown)?;
+        if member.get_declaration_type().is_by_ref() {
+            return Err(PathError::ByReference);
+        }
+        address = unsafe {
+            address.const_gep(&[
+                i32_type.const_zero(),
+                i32_type.const_int(element as u64, false),
+            ])
+        };
+        type_name = member.get_type_name().to_string();
+    }
+    Ok((address, type_name))
+}
+
+/// returns the address and the representation of the variable at the given instance path
+fn get_symbol<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    path: &str,
+) -> Result<(PointerValue<'ink>, JsonKind), Diagnostic> {
+    let (address, type_name) =
+        resolve_instance_path(module, llvm, index, path).map_err(|err| match err {
+            PathError::Unknown => {
+                Diagnostic::param_error(&format!("Cannot publish unknown symbol '{}'", path))
+            }
+            PathError::ByReference => Diagnostic::param_error(&format!(
+                "Cannot publish '{}', it is passed by reference",
+                path
+            )),
+        })?;
+
+    let kind = get_json_kind(index, &type_name).ok_or_else(|| {
+        Diagnostic::param_error(&format!(
+            "Cannot publish '{}' of type {}, only elementary variables can be published",
+            path, type_name
+        ))
+    })?;
+    Ok((address, kind))
+}
+
+fn get_json_kind(index: &Index, type_name: &str) -> Option<JsonKind> {
+    match index.find_effective_type_info(type_name)? {
+        it if it.is_bool() => Some(JsonKind::Bool),
+        DataTypeInformation::Integer { signed: true, .. } => Some(JsonKind::Signed),
+        DataTypeInformation::Integer { signed: false, .. } => Some(JsonKind::Unsigned),
+        DataTypeInformation::Float { .. } => Some(JsonKind::Real),
+        DataTypeInformation::String { encoding, .. } => Some(JsonKind::String(encoding.clone())),
+        DataTypeInformation::Enum {
+            referenced_type, ..
+        }
+        | DataTypeInformation::SubRange {
+            referenced_type, ..
+        }
+        | DataTypeInformation::Alias {
+            referenced_type, ..
+        } => get_json_kind(index, referenced_type),
+        _ => None,
+    }
+}
@@ -0,0 +1,45 @@
+//! annotates the generated code for worst-case execution time (WCET) analyzers
+//!
+//! the first instruction generated for a statement carries `!rusty.statement !{i64 id, i64 offset}`
+//! identifying the statement by its id and its offset in the source file. The first instruction of
+//! the header of a loop annotated with `{attribute 'loop_bound' := 'n'}` carries
+//! `!rusty.loop_bound !{i64 n}`. The bounds are also exported in AIS format (see `crate::wcet`),
+//! the annotation language understood by aiT and other analyzers.
+use inkwell::{basic_block::BasicBlock, values::InstructionValue};
+
+use super::llvm::Llvm;
+use crate::{ast::AstId, diagnostics::INTERNAL_LLVM_ERROR};
+
+pub const STATEMENT_METADATA: &str = "rusty.statement";
+pub const LOOP_BOUND_METADATA: &str = "rusty.loop_bound";
+
+/// identifies the statement the given instruction was generated for
+pub fn annotate_statement(llvm: &Llvm, instruction: InstructionValue, id: AstId, offset: usize) {
+    set_metadata(
+        llvm,
+        instruction,
+        STATEMENT_METADATA,
+        &[id as u64, offset as u64],
+    );
+}
+
+/// bounds the number of iterations of the loop starting with the given header
+pub fn annotate_loop_bound(llvm: &Llvm, header: BasicBlock, bound: u32) {
+    if let Some(instruction) = header.get_first_instruction() {
+        set_metadata(llvm, instruction, LOOP_BOUND_METADATA, &[bound as u64]);
+    }
+}
+
+fn set_metadata(llvm: &Llvm, instruction: InstructionValue, kind: &str, values: &[u64]) {
+    let i64_type = llvm.context.i64_type();
+    let values = values
+        .iter()
+        .map(|it| i64_type.const_int(*it, false).into())
+        .collect::<Vec<_>>();
+    instruction
+        .set_metadata(
+            llvm.context.metadata_node(&values),
+            llvm.context.get_kind_id(kind),
+        )
+        .expect(INTERNAL_LLVM_ERROR);
+}
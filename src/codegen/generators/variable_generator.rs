@@ -7,7 +7,10 @@ use crate::{
     index::{Index, PouIndexEntry},
     resolver::AstAnnotations,
 };
-use inkwell::{module::Module, values::GlobalValue};
+use inkwell::{
+    module::{Linkage, Module},
+    values::GlobalValue,
+};
 
 use crate::{codegen::llvm_index::LlvmTypedIndex, index::VariableIndexEntry};
 
@@ -15,6 +18,7 @@ use super::{
     data_type_generator::get_default_for,
     expression_generator::ExpressionCodeGenerator,
     llvm::{GlobalValueExt, Llvm},
+    process_image_generator,
 };
 
 pub fn generate_global_variables<'ctx, 'b>(
@@ -32,8 +36,12 @@ pub fn generate_global_variables<'ctx, 'b>(
         (kv.0.as_str(), kv.1)
     }
 
-    //all declared global variables
-    let globals = global_index.get_globals().iter().map(to_k_v);
+    //all declared global variables, the located ones are stored in the process images
+    let globals = global_index
+        .get_globals()
+        .iter()
+        .map(to_k_v)
+        .filter(|(_, it)| !process_image_generator::is_located(it, global_index));
     //all initializers
     let initializers = global_index.get_global_initializers().iter().map(to_k_v);
     //all enum-elements
@@ -68,6 +76,12 @@ pub fn generate_global_variables<'ctx, 'b>(
         })?;
         index.associate_global(name, global_variable)?
     }
+    index.merge(process_image_generator::generate_process_images(
+        module,
+        llvm,
+        global_index,
+        types_index,
+    )?);
     Ok(index)
 }
 
@@ -117,6 +131,9 @@ pub fn generate_global_variable<'ctx, 'b>(
             // 3rd try: get the compiler's default for the given type (zero-initializer)
             .or_else(|| index.find_associated_type(type_name).map(get_default_for));
         global_ir_variable.set_initial_value(initial_value, variable_type);
+        if global_variable.is_weak() {
+            global_ir_variable.set_linkage(Linkage::WeakAny);
+        }
         if global_variable.is_constant() {
             global_ir_variable = global_ir_variable.make_constant();
             if initial_value.is_none() {
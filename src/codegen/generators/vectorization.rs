@@ -0,0 +1,44 @@
+//! marks the loops generated for the array builtins (e.g. `ARRAY_ADD`) for LLVM's loop vectorizer
+//!
+//! a loop is identified by the distinct `!llvm.loop` node attached to the branch closing its body,
+//! the node refers to itself followed by the loop's hints. `llvm.loop.vectorize.enable` forces the
+//! vectorizer to also reorder float reductions (e.g. the sum of `ARRAY_DOT`). The loops are only
+//! vectorized when optimizing, the hints have no effect with `-Onone`.
+use inkwell::values::{AsValueRef, InstructionValue};
+use llvm_sys::{
+    core::{
+        LLVMGetTypeContext, LLVMMDNodeInContext2, LLVMMetadataAsValue, LLVMSetMetadata, LLVMTypeOf,
+        LLVMValueAsMetadata,
+    },
+    debuginfo::{LLVMMetadataReplaceAllUsesWith, LLVMTemporaryMDNode},
+    prelude::LLVMMetadataRef,
+};
+
+use super::llvm::Llvm;
+
+pub const LOOP_METADATA: &str = "llvm.loop";
+pub const VECTORIZE_ENABLE: &str = "llvm.loop.vectorize.enable";
+
+/// enables the vectorization of the loop closed by the given branch
+pub fn enable_vectorization(llvm: &Llvm, latch: InstructionValue) {
+    let context = llvm.context;
+    let hint = context.metadata_node(&[
+        context.metadata_string(VECTORIZE_ENABLE).into(),
+        context.bool_type().const_int(1, false).into(),
+    ]);
+    let kind = context.get_kind_id(LOOP_METADATA);
+    unsafe {
+        let context_ref = LLVMGetTypeContext(LLVMTypeOf(latch.as_value_ref()));
+        // the node refers to itself, so it is created with a placeholder replaced afterwards
+        let placeholder = LLVMTemporaryMDNode(context_ref, std::ptr::null_mut(), 0);
+        let mut operands: [LLVMMetadataRef; 2] =
+            [placeholder, LLVMValueAsMetadata(hint.as_value_ref())];
+        let node = LLVMMDNodeInContext2(context_ref, operands.as_mut_ptr(), operands.len());
+        LLVMMetadataReplaceAllUsesWith(placeholder, node);
+        LLVMSetMetadata(
+            latch.as_value_ref(),
+            kind,
+            LLVMMetadataAsValue(context_ref, node),
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! generates the vtables of the classes and function blocks implementing interfaces
+//!
+//! every interface `I` is described by a `__vtable_I` struct holding the address of each of its
+//! methods in declaration order. Every class or function block `C` implementing `I` gets a
+//! constant `__vtable_C_I` pointing to the methods of `C` implementing them, a method may be
+//! inherited from a super class of `C`.
+//!
+//! every polymorphic class `C` (an abstract class or a class extending or extended by another
+//! class) gets a constant `__vtable_C` holding the address of each of its virtual methods. Its
+//! instances point to it with their hidden `__vtable` member, so method calls are dispatched to
+//! the methods of the instance's actual class. The slots of abstract methods are null.
+use inkwell::{
+    module::Module,
+    types::{BasicTypeEnum, StructType},
+    values::BasicValueEnum,
+    AddressSpace,
+};
+
+use super::llvm::{GlobalValueExt, Llvm};
+use crate::{
+    ast::{LinkageType, Pou},
+    codegen::llvm_index::LlvmTypedIndex,
+    diagnostics::Diagnostic,
+    index::{Index, PouIndexEntry},
+};
+
+/// returns the name of the struct describing the methods of the given interface
+pub fn get_vtable_type_name(interface: &str) -> String {
+    format!("__vtable_{}", interface)
+}
+
+/// returns the name of the vtable of the given class implementing the given interface
+pub fn get_vtable_name(class: &str, interface: &str) -> String {
+    format!("__vtable_{}_{}", class, interface)
+}
+
+/// returns the name of the vtable of the given polymorphic class and the name of its type
+pub fn get_class_vtable_name(class: &str) -> String {
+    format!("__vtable_{}", class)
+}
+
+/// declares the vtables of all polymorphic classes and associates them in the given index, the
+/// vtables are initialized by `generate_vtables` once the methods are generated
+pub fn declare_class_vtables<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    llvm_index: &mut LlvmTypedIndex<'ink>,
+) -> Result<(), Diagnostic> {
+    for class in index
+        .get_pous()
+        .values()
+        .filter(|it| matches!(it, PouIndexEntry::Class { .. }))
+        .filter(|it| index.is_polymorphic(it.get_name()))
+    {
+        let name = get_class_vtable_name(class.get_name());
+        let method_count = index.get_virtual_methods(class.get_name()).len();
+        let vtable_type = get_vtable_type(module, llvm, &name, method_count);
+        let vtable =
+            llvm.create_global_variable(module, &name, BasicTypeEnum::StructType(vtable_type));
+        llvm_index.associate_global(&name, vtable)?;
+    }
+    Ok(())
+}
+
+pub fn generate_vtables<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    index: &Index,
+    llvm_index: &LlvmTypedIndex<'ink>,
+) -> Result<(), Diagnostic> {
+    let method_ptr_type = llvm.context.i8_type().ptr_type(AddressSpace::Generic);
+    let get_method_pointer = |method: Option<&PouIndexEntry>| -> BasicValueEnum<'ink> {
+        method
+            .and_then(|it| llvm_index.find_associated_implementation(it.get_name()))
+            .map(|it| {
+                it.as_global_value()
+                    .as_pointer_value()
+                    .const_cast(method_ptr_type)
+            })
+            .unwrap_or_else(|| method_ptr_type.const_null())
+            .into()
+    };
+
+    //the vtables of external classes are initialized by the module declaring them
+    for class in index
+        .get_pous()
+        .values()
+        .filter(|it| matches!(it, PouIndexEntry::Class { .. }))
+        .filter(|it| !matches!(it.get_linkage(), LinkageType::External))
+    {
+        let name = get_class_vtable_name(class.get_name());
+        if let Some(vtable) = llvm_index.find_global_value(&name) {
+            let methods = index
+                .get_virtual_methods(class.get_name())
+                .into_iter()
+                .map(|it| get_method_pointer(Some(it)))
+                .collect::<Vec<BasicValueEnum>>();
+            let vtable_type = get_vtable_type(module, llvm, &name, methods.len());
+            vtable
+                .make_constant()
+                .set_initializer(&vtable_type.const_named_struct(&methods));
+        }
+    }
+
+    let implementers = index
+        .get_pous()
+        .values()
+        .filter(|it| !it.get_interfaces().is_empty())
+        .filter(|it| !matches!(it.get_linkage(), LinkageType::External));
+    for class in implementers {
+        for interface in class
+            .get_interfaces()
+            .iter()
+            .filter_map(|it| index.find_pou(it))
+            .filter(|it| matches!(it, PouIndexEntry::Interface { .. }))
+        {
+            let prototypes = index.get_interface_methods(interface.get_name());
+            let vtable_type = get_vtable_type(
+                module,
+                llvm,
+                &get_vtable_type_name(interface.get_name()),
+                prototypes.len(),
+            );
+
+            //methods that are not implemented are reported by the validation
+            let methods = prototypes
+                .iter()
+                .map(|prototype| {
+                    get_method_pointer(index.find_method(
+                        class.get_name(),
+                        Pou::calc_return_name(prototype.get_name()),
+                    ))
+                })
+                .collect::<Vec<BasicValueEnum>>();
+
+            let vtable = llvm.create_global_variable(
+                module,
+                &get_vtable_name(class.get_name(), interface.get_name()),
+                BasicTypeEnum::StructType(vtable_type),
+            );
+            vtable
+                .make_constant()
+                .set_initializer(&vtable_type.const_named_struct(&methods));
+        }
+    }
+    Ok(())
+}
+
+/// returns the struct with the given name holding the given number of method pointers, creates
+/// it on first use
+fn get_vtable_type<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    name: &str,
+    method_count: usize,
+) -> StructType<'ink> {
+    module.get_struct_type(name).unwrap_or_else(|| {
+        let method_ptr_type = llvm.context.i8_type().ptr_type(AddressSpace::Generic);
+        let vtable_type = llvm.create_struct_stub(name);
+        vtable_type.set_body(&vec![method_ptr_type.into(); method_count], false);
+        vtable_type
+    })
+}
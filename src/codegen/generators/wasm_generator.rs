@@ -0,0 +1,191 @@
+//! adapts the generated module to WebAssembly targets (e.g. `wasm32-unknown-unknown`)
+//!
+//! the POUs are exported from the WebAssembly module under their name (`wasm-export-name`), so a
+//! host like a browser-based soft-PLC simulator can call them. Freestanding modules are not linked
+//! against a C library, so `memcpy`, `memmove` and `memset`, which the backend lowers the copies of
+//! strings, arrays and structs to, are generated as hidden byte loops. They are weak, a C library
+//! linked into the module replaces them.
+use inkwell::{
+    attributes::AttributeLoc,
+    module::{Linkage, Module},
+    targets::TargetTriple,
+    types::{FunctionType, IntType},
+    values::{FunctionValue, IntValue, PointerValue},
+    AddressSpace, GlobalVisibility, IntPredicate,
+};
+
+use super::llvm::Llvm;
+use crate::{abi_metadata, diagnostics::Diagnostic, index::Index};
+
+/// returns true if the given target is a WebAssembly target
+pub fn is_wasm_target(triple: &TargetTriple) -> bool {
+    let triple = triple.as_str().to_string_lossy();
+    triple.starts_with("wasm32") || triple.starts_with("wasm64")
+}
+
+/// exports the implemented POUs of the given module under their name
+pub fn export_pous(module: &Module, llvm: &Llvm, index: &Index) {
+    for function in module
+        .get_functions()
+        .filter(|it| it.count_basic_blocks() > 0)
+    {
+        let name = function.get_name().to_string_lossy().to_string();
+        if index.find_pou(&name).map_or(false, |it| !it.is_generic()) {
+            let attribute = llvm
+                .context
+                .create_string_attribute("wasm-export-name", &name);
+            function.add_attribute(AttributeLoc::Function, attribute);
+        }
+    }
+}
+
+/// generates `memcpy`, `memmove` and `memset` unless the module already declares them
+pub fn generate_memory_functions<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    triple: &TargetTriple,
+) -> Result<(), Diagnostic> {
+    let target_data = abi_metadata::create_target_machine(triple)?.get_target_data();
+    let size_type = llvm.context.ptr_sized_int_type(&target_data, None);
+    let byte_pointer = llvm.context.i8_type().ptr_type(AddressSpace::Generic);
+    let copy_type = byte_pointer.fn_type(
+        &[byte_pointer.into(), byte_pointer.into(), size_type.into()],
+        false,
+    );
+    let set_type = byte_pointer.fn_type(
+        &[
+            byte_pointer.into(),
+            llvm.context.i32_type().into(),
+            size_type.into(),
+        ],
+        false,
+    );
+
+    if let Some(function) = add_memory_function(module, llvm, "memcpy", copy_type) {
+        let (destination, source, length) = get_parameters(function);
+        build_byte_loop(llvm, function, size_type, length, false, |index| {
+            copy_byte(llvm, destination, source, index)
+        });
+        llvm.builder.build_return(Some(&destination));
+    }
+
+    if let Some(function) = add_memory_function(module, llvm, "memmove", copy_type) {
+        let (destination, source, length) = get_parameters(function);
+        //overlapping ranges are copied starting at the end if the destination follows the source
+        let forward = llvm.context.append_basic_block(function, "forward");
+        let backward = llvm.context.append_basic_block(function, "backward");
+        let is_before = llvm.builder.build_int_compare(
+            IntPredicate::ULT,
+            llvm.builder.build_ptr_to_int(destination, size_type, ""),
+            llvm.builder.build_ptr_to_int(source, size_type, ""),
+            "",
+        );
+        llvm.builder
+            .build_conditional_branch(is_before, forward, backward);
+        for (block, reverse) in [(forward, false), (backward, true)] {
+            llvm.builder.position_at_end(block);
+            build_byte_loop(llvm, function, size_type, length, reverse, |index| {
+                copy_byte(llvm, destination, source, index)
+            });
+            llvm.builder.build_return(Some(&destination));
+        }
+    }
+
+    if let Some(function) = add_memory_function(module, llvm, "memset", set_type) {
+        let destination = function.get_nth_param(0).unwrap().into_pointer_value();
+        let value = function.get_nth_param(1).unwrap().into_int_value();
+        let length = function.get_nth_param(2).unwrap().into_int_value();
+        let byte = llvm
+            .builder
+            .build_int_truncate(value, llvm.context.i8_type(), "byte");
+        build_byte_loop(llvm, function, size_type, length, false, |index| {
+            let address = unsafe { llvm.builder.build_in_bounds_gep(destination, &[index], "") };
+            llvm.builder.build_store(address, byte);
+        });
+        llvm.builder.build_return(Some(&destination));
+    }
+    Ok(())
+}
+
+/// adds the given memory function with an entry block the builder is positioned at, returns None
+/// if the module already declares the function
+fn add_memory_function<'ink>(
+    module: &Module<'ink>,
+    llvm: &Llvm<'ink>,
+    name: &str,
+    function_type: FunctionType<'ink>,
+) -> Option<FunctionValue<'ink>> {
+    if module.get_function(name).is_some() {
+        return None;
+    }
+    let function = module.add_function(name, function_type, Some(Linkage::WeakAny));
+    function
+        .as_global_value()
+        .set_visibility(GlobalVisibility::Hidden);
+    //keeps the optimizer from replacing the loops by calls to the function itself
+    let attribute = llvm.context.create_string_attribute("no-builtins", "");
+    function.add_attribute(AttributeLoc::Function, attribute);
+    llvm.builder
+        .position_at_end(llvm.context.append_basic_block(function, "entry"));
+    Some(function)
+}
+
+/// returns the destination, the source and the length of a copying memory function
+fn get_parameters(function: FunctionValue) -> (PointerValue, PointerValue, IntValue) {
+    (
+        function.get_nth_param(0).unwrap().into_pointer_value(),
+        function.get_nth_param(1).unwrap().into_pointer_value(),
+        function.get_nth_param(2).unwrap().into_int_value(),
+    )
+}
+
+/// copies the byte at the given index from the source to the destination
+fn copy_byte<'ink>(
+    llvm: &Llvm<'ink>,
+    destination: PointerValue<'ink>,
+    source: PointerValue<'ink>,
+    index: IntValue<'ink>,
+) {
+    let from = unsafe { llvm.builder.build_in_bounds_gep(source, &[index], "") };
+    let to = unsafe { llvm.builder.build_in_bounds_gep(destination, &[index], "") };
+    let byte = llvm.builder.build_load(from, "");
+    llvm.builder.build_store(to, byte);
+}
+
+/// builds a loop calling `body` with the indexes `0..length` (`length-1..=0` if `reverse`), the
+/// builder is positioned after the loop
+fn build_byte_loop<'ink>(
+    llvm: &Llvm<'ink>,
+    function: FunctionValue<'ink>,
+    size_type: IntType<'ink>,
+    length: IntValue<'ink>,
+    reverse: bool,
+    body: impl Fn(IntValue<'ink>),
+) {
+    let builder = &llvm.builder;
+    let start_block = builder.get_insert_block().unwrap();
+    let condition_block = llvm.context.append_basic_block(function, "condition");
+    let body_block = llvm.context.append_basic_block(function, "body");
+    let end_block = llvm.context.append_basic_block(function, "end");
+    builder.build_unconditional_branch(condition_block);
+
+    builder.position_at_end(condition_block);
+    let counter = builder.build_phi(size_type, "counter");
+    let current = counter.as_basic_value().into_int_value();
+    let is_end = builder.build_int_compare(IntPredicate::EQ, current, length, "");
+    builder.build_conditional_branch(is_end, end_block, body_block);
+
+    builder.position_at_end(body_block);
+    let one = size_type.const_int(1, false);
+    let index = if reverse {
+        builder.build_int_sub(builder.build_int_sub(length, current, ""), one, "")
+    } else {
+        current
+    };
+    body(index);
+    let next = builder.build_int_add(current, one, "");
+    builder.build_unconditional_branch(condition_block);
+    counter.add_incoming(&[(&size_type.const_zero(), start_block), (&next, body_block)]);
+
+    builder.position_at_end(end_block);
+}
@@ -1,7 +1,7 @@
 use crate::diagnostics::Diagnostic;
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 use crate::ast::SourceRange;
-use inkwell::types::BasicTypeEnum;
+use inkwell::types::{BasicTypeEnum, FunctionType};
 use inkwell::values::{BasicValueEnum, FunctionValue, GlobalValue, PointerValue};
 use std::collections::HashMap;
 
@@ -16,6 +16,7 @@ pub struct LlvmTypedIndex<'ink> {
     initial_value_associations: HashMap<String, BasicValueEnum<'ink>>,
     loaded_variable_associations: HashMap<String, PointerValue<'ink>>,
     implementations: HashMap<String, FunctionValue<'ink>>,
+    abstract_implementations: HashMap<String, FunctionType<'ink>>,
     constants: HashMap<String, BasicValueEnum<'ink>>,
     utf08_literals: HashMap<String, GlobalValue<'ink>>,
     utf16_literals: HashMap<String, GlobalValue<'ink>>,
@@ -31,6 +32,7 @@ impl<'ink> LlvmTypedIndex<'ink> {
             initial_value_associations: HashMap::new(),
             loaded_variable_associations: HashMap::new(),
             implementations: HashMap::new(),
+            abstract_implementations: HashMap::new(),
             constants: HashMap::new(),
             utf08_literals: HashMap::new(),
             utf16_literals: HashMap::new(),
@@ -59,6 +61,8 @@ impl<'ink> LlvmTypedIndex<'ink> {
         for (name, implementation) in other.implementations.drain() {
             self.implementations.insert(name, implementation);
         }
+        self.abstract_implementations
+            .extend(other.abstract_implementations);
         self.constants.extend(other.constants);
         self.utf08_literals.extend(other.utf08_literals);
         self.utf16_literals.extend(other.utf16_literals);
@@ -172,6 +176,18 @@ impl<'ink> LlvmTypedIndex<'ink> {
         Ok(())
     }
 
+    /// associates the given global variable with its address in a process image, the variable
+    /// has no global value of its own
+    pub fn associate_located_global(
+        &mut self,
+        variable_name: &str,
+        address: PointerValue<'ink>,
+    ) -> Result<(), Diagnostic> {
+        self.initial_value_associations
+            .insert(variable_name.to_lowercase(), address.into());
+        Ok(())
+    }
+
     pub fn associate_implementation(
         &mut self,
         callable_name: &str,
@@ -195,6 +211,37 @@ impl<'ink> LlvmTypedIndex<'ink> {
             })
     }
 
+    /// associates the function type of an abstract method, abstract methods have no
+    /// implementation and are only called through the vtable of the instance
+    pub fn associate_abstract_implementation(
+        &mut self,
+        callable_name: &str,
+        function_type: FunctionType<'ink>,
+    ) -> Result<(), Diagnostic> {
+        self.abstract_implementations
+            .insert(callable_name.to_lowercase(), function_type);
+        Ok(())
+    }
+
+    /// returns the function type of the given implementation or abstract method
+    pub fn find_associated_implementation_type(
+        &self,
+        callable_name: &str,
+    ) -> Option<FunctionType<'ink>> {
+        self.implementations
+            .get(&callable_name.to_lowercase())
+            .map(|it| it.get_type())
+            .or_else(|| {
+                self.abstract_implementations
+                    .get(&callable_name.to_lowercase())
+                    .copied()
+            })
+            .or_else(|| {
+                self.parent_index
+                    .and_then(|it| it.find_associated_implementation_type(callable_name))
+            })
+    }
+
     pub fn find_associated_variable_value(
         &self,
         qualified_name: &str,
@@ -213,6 +213,26 @@ pub fn cast_if_needed<'ctx>(
                         )
                         .into())
                 }
+                DataTypeInformation::FixedPoint {
+                    size: rsize,
+                    fraction_bits,
+                    ..
+                } => {
+                    // the fraction is truncated like a float's
+                    let fixed_type = get_llvm_int_type(llvm.context, *rsize, "Fixed")?;
+                    let integer = builder.build_int_signed_div(
+                        value.into_int_value(),
+                        fixed_type.const_int(1u64 << fraction_bits, false),
+                        "",
+                    );
+                    Ok(resize_int_value(
+                        llvm,
+                        integer,
+                        true,
+                        get_llvm_int_type(llvm.context, *lsize, "Integer")?,
+                    )
+                    .into())
+                }
                 DataTypeInformation::Pointer {
                     auto_deref: false, ..
                 } => Ok(llvm
@@ -275,12 +295,89 @@ pub fn cast_if_needed<'ctx>(
                     .map_err(|it| Diagnostic::relocate(it, statement.get_location()))
                 }
             }
+            DataTypeInformation::FixedPoint { fraction_bits, .. } => {
+                let float_type = get_llvm_float_type(llvm.context, *lsize, "Float")?;
+                let value =
+                    builder.build_signed_int_to_float(value.into_int_value(), float_type, "");
+                Ok(builder
+                    .build_float_mul(
+                        value,
+                        float_type.const_float(2f64.powi(-(*fraction_bits as i32))),
+                        "",
+                    )
+                    .into())
+            }
             _ => Err(Diagnostic::casting_error(
                 value_type.get_name(),
                 target_type.get_name(),
                 statement.get_location(),
             )),
         },
+        DataTypeInformation::FixedPoint {
+            size,
+            fraction_bits,
+            ..
+        } => {
+            let fixed_type = get_llvm_int_type(llvm.context, *size, "Fixed")?;
+            match value_type {
+                DataTypeInformation::Integer { signed, .. } => {
+                    let value = resize_int_value(llvm, value.into_int_value(), *signed, fixed_type);
+                    Ok(builder
+                        .build_left_shift(
+                            value,
+                            fixed_type.const_int(*fraction_bits as u64, false),
+                            "",
+                        )
+                        .into())
+                }
+                DataTypeInformation::Float { .. } => {
+                    let value = value.into_float_value();
+                    let scaled = builder.build_float_mul(
+                        value,
+                        value
+                            .get_type()
+                            .const_float(2f64.powi(*fraction_bits as i32)),
+                        "",
+                    );
+                    Ok(builder
+                        .build_float_to_signed_int(scaled, fixed_type, "")
+                        .into())
+                }
+                DataTypeInformation::FixedPoint {
+                    size: value_size,
+                    fraction_bits: value_fraction_bits,
+                    ..
+                } if (size, fraction_bits) != (value_size, value_fraction_bits) => {
+                    // rescaled in the wider of both types
+                    let wide_type =
+                        get_llvm_int_type(llvm.context, *size.max(value_size), "Fixed")?;
+                    let value = resize_int_value(llvm, value.into_int_value(), true, wide_type);
+                    let value = if fraction_bits > value_fraction_bits {
+                        builder.build_left_shift(
+                            value,
+                            wide_type
+                                .const_int((fraction_bits - value_fraction_bits) as u64, false),
+                            "",
+                        )
+                    } else {
+                        builder.build_right_shift(
+                            value,
+                            wide_type
+                                .const_int((value_fraction_bits - fraction_bits) as u64, false),
+                            true,
+                            "",
+                        )
+                    };
+                    Ok(resize_int_value(llvm, value, true, fixed_type).into())
+                }
+                DataTypeInformation::FixedPoint { .. } => Ok(value),
+                _ => Err(Diagnostic::casting_error(
+                    value_type.get_name(),
+                    target_type.get_name(),
+                    statement.get_location(),
+                )),
+            }
+        }
         DataTypeInformation::String { encoding, .. } => match value_type {
             DataTypeInformation::String {
                 encoding: value_encoding,
@@ -335,6 +432,25 @@ pub fn cast_if_needed<'ctx>(
     }
 }
 
+/// extends (respecting the sign) or truncates the given value to the given type
+fn resize_int_value<'ctx>(
+    llvm: &Llvm<'ctx>,
+    value: IntValue<'ctx>,
+    signed: bool,
+    target_type: IntType<'ctx>,
+) -> IntValue<'ctx> {
+    let width = value.get_type().get_bit_width();
+    if width > target_type.get_bit_width() {
+        llvm.builder.build_int_truncate(value, target_type, "")
+    } else if width == target_type.get_bit_width() {
+        value
+    } else if signed {
+        llvm.builder.build_int_s_extend(value, target_type, "")
+    } else {
+        llvm.builder.build_int_z_extend(value, target_type, "")
+    }
+}
+
 pub fn get_llvm_int_type<'a>(
     context: &'a Context,
     size: u32,
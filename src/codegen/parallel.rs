@@ -0,0 +1,162 @@
+//! generates the POUs of large projects on several threads (`--jobs`)
+//!
+//! an LLVM context must not be shared between threads, so every worker generates the types,
+//! globals and stubs of the whole project (see `CodeGen::generate_llvm_index`) into a module of
+//! its own context, followed by the bodies of its partition of the POUs. The partitions are
+//! consecutive runs of the POUs in the order of their declaration. A worker's module is passed
+//! back as bitcode after its globals have been turned into declarations, the main module (which
+//! generated the same index) defines them. The modules are linked into the main module in the
+//! order of the partitions, the linked functions take the places of their stubs, so the result
+//! does not depend on the number of threads or on their scheduling.
+use std::thread;
+
+use inkwell::{
+    context::Context,
+    memory_buffer::MemoryBuffer,
+    module::{Linkage, Module},
+    values::{AsValueRef, GlobalValue},
+};
+use llvm_sys::core::LLVMSetInitializer;
+
+use super::{generators::llvm::Llvm, generators::pou_generator::PouGenerator, CodeGen};
+use crate::{
+    ast::Implementation,
+    diagnostics::Diagnostic,
+    index::Index,
+    resolver::{AstAnnotations, StringLiterals},
+};
+
+/// returns the number of threads generating the POUs for the given `--jobs`, `0` uses every core
+pub fn get_thread_count(jobs: usize) -> usize {
+    if jobs == 0 {
+        thread::available_parallelism()
+            .map(usize::from)
+            .unwrap_or(1)
+    } else {
+        jobs
+    }
+}
+
+/// splits the given implementations into at most `count` consecutive partitions of similar size
+pub fn partition<T>(implementations: &[T], count: usize) -> Vec<&[T]> {
+    let count = count.max(1);
+    let size = (implementations.len() + count - 1) / count;
+    implementations.chunks(size.max(1)).collect()
+}
+
+/// generates the given partitions on one thread each and links them into the given code-generator's
+/// module, `literals` are the string literals of all units
+pub(super) fn generate_partitions(
+    code_generator: &CodeGen,
+    partitions: Vec<&[&Implementation]>,
+    annotations: &AstAnnotations,
+    literals: &StringLiterals,
+    global_index: &Index,
+) -> Result<(), Diagnostic> {
+    let (timing_annotations, fast_math, check_bounds) = (
+        code_generator.timing_annotations,
+        code_generator.fast_math,
+        code_generator.check_bounds,
+    );
+    let modules = thread::scope(|scope| {
+        partitions
+            .into_iter()
+            .map(|partition| {
+                scope.spawn(move || {
+                    generate_partition(
+                        partition,
+                        annotations,
+                        literals,
+                        global_index,
+                        timing_annotations,
+                        fast_math,
+                        check_bounds,
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect::<Result<Vec<_>, Diagnostic>>()
+    })?;
+
+    for (index, bitcode) in modules.iter().enumerate() {
+        let name = format!("partition_{}", index);
+        let buffer = MemoryBuffer::create_from_memory_range_copy(bitcode, &name);
+        let module = Module::parse_bitcode_from_buffer(&buffer, code_generator.context)
+            .map_err(|it| Diagnostic::llvm_error(&name, &it))?;
+        code_generator
+            .module
+            .link_in_module(module)
+            .map_err(|it| Diagnostic::llvm_error(&name, &it))?;
+    }
+    Ok(())
+}
+
+/// generates the given implementations into a module of a new context and returns its bitcode
+fn generate_partition(
+    implementations: &[&Implementation],
+    annotations: &AstAnnotations,
+    literals: &StringLiterals,
+    global_index: &Index,
+    timing_annotations: bool,
+    fast_math: bool,
+    check_bounds: bool,
+) -> Result<Vec<u8>, Diagnostic> {
+    let context = Context::create();
+    let mut code_generator = CodeGen::new(&context, "main");
+    code_generator.timing_annotations = timing_annotations;
+    code_generator.fast_math = fast_math;
+    code_generator.check_bounds = check_bounds;
+    let llvm_index =
+        code_generator.generate_llvm_index(annotations, literals.clone(), global_index)?;
+    let shared_globals = code_generator.module.get_globals().collect::<Vec<_>>();
+
+    let llvm = Llvm::new(&context, context.create_builder());
+    let pou_generator = code_generator.configure(PouGenerator::new(
+        llvm,
+        global_index,
+        annotations,
+        &llvm_index,
+    ));
+    for implementation in implementations {
+        pou_generator.generate_implementation(implementation)?;
+    }
+
+    shared_globals.into_iter().for_each(declare);
+    Ok(code_generator
+        .module
+        .write_bitcode_to_memory()
+        .as_slice()
+        .to_vec())
+}
+
+/// turns the definition of the given global into a declaration
+fn declare(global: GlobalValue) {
+    unsafe { LLVMSetInitializer(global.as_value_ref(), std::ptr::null_mut()) };
+    global.set_linkage(Linkage::External);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::partition;
+
+    #[test]
+    fn implementations_are_split_into_consecutive_partitions() {
+        let implementations = [1, 2, 3, 4, 5, 6, 7];
+        assert_eq!(
+            partition(&implementations, 3),
+            vec![&[1, 2, 3][..], &[4, 5, 6][..], &[7][..]]
+        );
+        assert_eq!(partition(&implementations, 1), vec![&implementations[..]]);
+        assert_eq!(
+            partition(&implementations[..2], 4),
+            vec![&[1][..], &[2][..]]
+        );
+        assert!(partition::<i32>(&[], 4).is_empty());
+    }
+}
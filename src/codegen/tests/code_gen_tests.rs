@@ -2957,3 +2957,260 @@ fn constant_expressions_in_ranged_type_declaration_are_propagated() {
 
     insta::assert_snapshot!(result);
 }
+
+#[test]
+fn classes_implementing_interfaces_get_a_vtable_per_interface() {
+    let result = codegen(
+        "
+    INTERFACE Drive
+        METHOD start : BOOL END_METHOD
+        METHOD stop END_METHOD
+    END_INTERFACE
+
+    CLASS Base
+        METHOD stop END_METHOD
+    END_CLASS
+
+    CLASS Motor EXTENDS Base IMPLEMENTS Drive
+        METHOD start : BOOL start := TRUE; END_METHOD
+    END_CLASS
+
+    FUNCTION_BLOCK Pump IMPLEMENTS Drive
+        METHOD start : BOOL END_METHOD
+        METHOD stop END_METHOD
+    END_FUNCTION_BLOCK
+        ",
+    );
+
+    //the methods are listed in the order of the interface's declaration
+    assert!(result.contains("%__vtable_Drive = type { i8*, i8* }"));
+    let motor = result
+        .lines()
+        .find(|it| it.starts_with("@__vtable_Motor_Drive = constant %__vtable_Drive"))
+        .unwrap();
+    assert!(motor.find("@Motor.start to i8*").unwrap() < motor.find("@Base.stop to i8*").unwrap());
+    let pump = result
+        .lines()
+        .find(|it| it.starts_with("@__vtable_Pump_Drive = constant %__vtable_Drive"))
+        .unwrap();
+    assert!(pump.contains("@Pump.start to i8*") && pump.contains("@Pump.stop to i8*"));
+    //the prototypes are not generated
+    assert!(!result.contains("@Drive.start"));
+}
+
+#[test]
+fn methods_of_polymorphic_classes_are_called_through_the_vtable() {
+    let result = codegen(
+        "
+    CLASS Shape
+        METHOD area : DINT END_METHOD
+    END_CLASS
+
+    CLASS Square EXTENDS Shape
+        METHOD OVERRIDE area : DINT END_METHOD
+    END_CLASS
+
+    CLASS Standalone
+        METHOD area : DINT END_METHOD
+    END_CLASS
+
+    FUNCTION area_of : DINT
+        VAR_IN_OUT shape : Shape; END_VAR
+        VAR standalone : Standalone; END_VAR
+        area_of := shape.area() + standalone.area();
+    END_FUNCTION
+        ",
+    );
+
+    //every class of the hierarchy gets a vtable, its instances are initialized to point to it
+    assert!(result.contains("%Shape_interface = type { i8* }"));
+    assert!(result.contains("@__vtable_Shape = constant %__vtable_Shape { i8* bitcast"));
+    assert!(result.contains("@__vtable_Square = constant %__vtable_Square { i8* bitcast"));
+    assert!(result.contains("@Square__init = unnamed_addr constant %Square_interface { i8* bitcast (%__vtable_Square* @__vtable_Square to i8*) }"));
+    //the overridden method is loaded from the instance's vtable
+    assert!(
+        result.contains("%__vtable = getelementptr inbounds %Shape_interface, %Shape_interface* %")
+    );
+    assert!(result.contains("call i32 %"));
+    //classes outside of a hierarchy are called directly
+    assert!(!result.contains("__vtable_Standalone"));
+    assert!(result.contains("call i32 @Standalone.area("));
+}
+
+#[test]
+fn methods_called_on_super_call_the_base_implementation_directly() {
+    let result = codegen(
+        "
+    CLASS Shape
+        METHOD area : DINT
+            area := 1;
+        END_METHOD
+    END_CLASS
+
+    CLASS Square EXTENDS Shape
+        METHOD OVERRIDE area : DINT
+            area := SUPER^.area() * 4;
+        END_METHOD
+    END_CLASS
+        ",
+    );
+
+    //the instance is passed as an instance of the super class, bypassing the vtable
+    assert!(result.contains("bitcast %Square_interface* %0 to %Shape_interface*"));
+    assert!(result.contains("call i32 @Shape.area(%Shape_interface*"));
+}
+
+#[test]
+fn abstract_methods_get_no_implementation() {
+    let result = codegen(
+        "
+    CLASS ABSTRACT Shape
+        METHOD ABSTRACT area : DINT
+        END_METHOD
+    END_CLASS
+
+    CLASS Square EXTENDS Shape
+        VAR side : DINT; END_VAR
+        METHOD OVERRIDE area : DINT
+            area := side * side;
+        END_METHOD
+    END_CLASS
+
+    FUNCTION area_of : DINT
+        VAR_IN_OUT shape : Shape; END_VAR
+        area_of := shape.area();
+    END_FUNCTION
+        ",
+    );
+
+    assert!(result.contains("store %fb_interface* %0, %fb_interface** %self"));
+    assert!(result
+        .contains("%x1 = getelementptr inbounds %fb_interface, %fb_interface* %0, i32 0, i32 1"));
+}
+
+#[test]
+fn this_is_the_instance_passed_to_methods_and_function_blocks() {
+    let result = codegen(
+        "
+    FUNCTION_BLOCK fb
+        VAR self : POINTER TO fb; x : INT; END_VAR
+        self := THIS;
+        THIS^.x := 1;
+    END_FUNCTION_BLOCK
+        ",
+    );
+
+    assert!(result.contains("define weak i16 @read_sensor()"));
+    assert!(result.contains("define weak void @motor(%motor_interface* %0)"));
+    assert!(result.contains("define weak void @motor.stop("));
+    assert!(result.contains("@motor__init = weak unnamed_addr constant %motor_interface"));
+    //pous without the attribute keep their linkage
+    assert!(result.contains("define i16 @main()"));
+}
+
+#[test]
+fn weak_pous_are_generated_with_weak_linkage() {
+    let result = codegen(
+        "
+    {attribute 'weak'}
+    FUNCTION read_sensor : INT
+        read_sensor := 7;
+    END_FUNCTION
+
+    {attribute 'weak'}
+    FUNCTION_BLOCK motor
+        VAR speed : INT := 5; END_VAR
+        METHOD stop END_METHOD
+    END_FUNCTION_BLOCK
+
+    FUNCTION main : INT
+        main := read_sensor();
+    END_FUNCTION
+        ",
+    );
+
+    //the abstract method is neither declared nor defined, its vtable slot stays empty
+    assert!(!result.contains("@Shape.area"));
+    assert!(result.contains("@__vtable_Shape = constant %__vtable_Shape { i8* null }"));
+    assert!(result.contains("@__vtable_Square = constant %__vtable_Square { i8* bitcast"));
+    //calls on a reference to the abstract class are dispatched through the vtable
+    assert!(result.contains("to i32 (%Shape_interface*, %Shape.area_interface*)*"));
+}
+
+#[test]
+fn pous_of_namespaces_are_generated_with_their_qualified_names() {
+    let result = codegen(
+        "
+    NAMESPACE plant
+        FUNCTION start : INT
+            start := 1;
+        END_FUNCTION
+
+        FUNCTION_BLOCK Motor
+            METHOD stop END_METHOD
+        END_FUNCTION_BLOCK
+    END_NAMESPACE
+
+    NAMESPACE util
+        FUNCTION start : INT
+            start := plant.start();
+        END_FUNCTION
+    END_NAMESPACE
+        ",
+    );
+
+    assert!(result.contains("define i16 @plant.start()"));
+    assert!(result.contains("define i16 @util.start()"));
+    assert!(result.contains("call i16 @plant.start()"));
+    assert!(result.contains("define void @plant.Motor.stop("));
+}
+
+#[test]
+fn reference_to_variables_are_bound_and_dereferenced() {
+    let result = codegen(
+        "
+    PROGRAM prg
+        VAR
+            r : REFERENCE TO INT;
+            x : INT;
+        END_VAR
+        r REF= x;
+        r := 5;
+    END_PROGRAM
+        ",
+    );
+
+    assert!(result.contains("store i16* %x, i16** %r"));
+    assert!(result.contains("%deref = load i16*, i16** %r"));
+    assert!(result.contains("store i16 5, i16* %deref"));
+}
+
+#[test]
+fn variable_length_arrays_are_passed_as_fat_pointers() {
+    let result = codegen(
+        "
+    FUNCTION sum : DINT
+        VAR_IN_OUT
+            values : ARRAY[*] OF INT;
+        END_VAR
+        sum := values[UPPER_BOUND(values, 1)];
+    END_FUNCTION
+
+    PROGRAM prg
+        VAR
+            ints : ARRAY[1..5] OF INT;
+            x : DINT;
+        END_VAR
+        x := sum(ints);
+    END_PROGRAM
+        ",
+    );
+
+    //the parameter is the fat pointer itself
+    assert!(result.contains("%__sum_values = type { i16*, [2 x i32] }"));
+    assert!(result.contains("define i32 @sum(%__sum_values %0)"));
+    //the caller passes a pointer to its array and the array's bounds
+    assert!(result.contains("store [2 x i32] [i32 1, i32 5]"));
+    //the element is accessed relative to the lower bound passed along
+    assert!(result.contains("%tmpVar = getelementptr inbounds i16, i16*"));
+}
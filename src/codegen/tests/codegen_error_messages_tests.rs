@@ -498,3 +498,27 @@ fn pointer_binary_expression_modulo() {
         panic!("expected code-gen error but got none")
     }
 }
+
+#[test]
+fn matrices_of_mismatching_sizes_cannot_be_multiplied() {
+    let result = codegen_without_unwrap(
+        "
+        PROGRAM prg
+        VAR
+            a : MATRIX[2, 3] OF REAL;
+            b : MATRIX[2, 3] OF REAL;
+            c : MATRIX[2, 3] OF REAL;
+        END_VAR
+            MATRIX_MUL(c, a, b);
+        END_PROGRAM
+        ",
+    );
+    if let Err(msg) = result {
+        assert_eq!(
+            msg.get_message(),
+            "Expected a 2x3 destination and a right matrix with 3 rows for MATRIX_MUL"
+        )
+    } else {
+        panic!("expected code-gen error but got none")
+    }
+}
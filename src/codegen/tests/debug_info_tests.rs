@@ -0,0 +1,142 @@
+use crate::test_utils::tests::codegen_with_debug_info;
+
+/// returns the line of the IR describing the given metadata, e.g. `!DISubprogram(name: "main"`
+fn find_metadata<'a>(ir: &'a str, metadata: &str) -> &'a str {
+    ir.lines()
+        .find(|it| it.contains(metadata))
+        .unwrap_or_else(|| panic!("{} not found in\n{}", metadata, ir))
+}
+
+const SOURCE: &str = "
+TYPE Point : STRUCT
+    x : INT;
+    y : REAL;
+END_STRUCT END_TYPE
+
+VAR_GLOBAL
+    origin : Point;
+END_VAR
+
+FUNCTION double : DINT
+VAR_INPUT
+    value : DINT;
+END_VAR
+    double := value * 2;
+END_FUNCTION
+
+PROGRAM main
+VAR
+    counter : DINT;
+    name : STRING;
+END_VAR
+    counter := double(counter);
+    origin.x := 1;
+END_PROGRAM
+";
+
+#[test]
+fn pous_are_described_by_subprograms_located_at_their_bodies() {
+    let result = codegen_with_debug_info(SOURCE);
+
+    assert!(result.contains(r#"!llvm.module.flags"#));
+    assert!(find_metadata(&result, "!DICompileUnit(").contains("emissionKind: FullDebug"));
+    assert!(find_metadata(&result, r#"!DIFile(filename: "main.st""#).contains("directory"));
+    assert!(find_metadata(&result, r#"!DISubprogram(name: "double""#).contains("line: 15"));
+    assert!(find_metadata(&result, r#"!DISubprogram(name: "main""#).contains("line: 23"));
+}
+
+#[test]
+fn statements_are_located_at_their_line_and_column() {
+    let result = codegen_with_debug_info(SOURCE);
+
+    assert!(result.contains("!DILocation(line: 15, column: 5,"));
+    assert!(result.contains("!DILocation(line: 23, column: 5,"));
+    assert!(result.contains("!DILocation(line: 24, column: 5,"));
+    assert!(find_metadata(&result, "call i32 @double(").contains("!dbg"));
+}
+
+#[test]
+fn variables_and_members_are_declared_with_their_types() {
+    let result = codegen_with_debug_info(SOURCE);
+
+    //locals of functions and members of POU instances
+    assert!(find_metadata(&result, r#"!DILocalVariable(name: "value""#).contains("line: 13"));
+    assert!(find_metadata(&result, r#"!DILocalVariable(name: "counter""#).contains("line: 20"));
+    assert!(result.contains("call void @llvm.dbg.declare(metadata i32* %value"));
+    assert!(find_metadata(&result, r#"!DIBasicType(name: "DINT""#).contains("DW_ATE_signed"));
+
+    //global variables and program instances
+    assert!(find_metadata(&result, r#"!DIGlobalVariable(name: "origin""#).contains("line: 8"));
+    assert!(result.contains(r#"!DIGlobalVariable(name: "main_instance""#));
+
+    //struct members are aligned to their natural alignment
+    let point = find_metadata(&result, r#"DW_TAG_structure_type, name: "Point""#);
+    assert!(point.contains("size: 64"));
+    assert!(find_metadata(&result, r#"DW_TAG_member, name: "y""#).contains("offset: 32"));
+    let name = find_metadata(&result, r#"DW_TAG_member, name: "name""#);
+    assert!(name.contains("size: 648") && name.contains("offset: 32"));
+}
+
+#[test]
+fn every_statement_is_located_at_an_instruction_of_its_own() {
+    let result = codegen_with_debug_info(
+        "
+FUNCTION sum : DINT
+VAR
+    i : DINT;
+END_VAR
+    5;
+    FOR i := 1 TO 3 DO
+        sum := sum + i;
+    END_FOR
+    WHILE sum > 10 DO
+        sum := sum - 1;
+    END_WHILE
+END_FUNCTION
+",
+    );
+
+    //statements without instructions of their own are located at a marker
+    assert!(find_metadata(&result, "call void @llvm.donothing()").contains("!dbg"));
+    assert!(result.contains("!DILocation(line: 6, column: 5,"));
+    //the loops' increments and conditions are located at the header, not at the body
+    assert!(result.contains("!DILocation(line: 7, column: 9,"));
+    assert!(result.contains("!DILocation(line: 10, column: 11,"));
+}
+
+#[test]
+fn st_types_are_described_in_st_terms() {
+    let result = codegen_with_debug_info(
+        "
+TYPE Color : (red, green := 5, blue); END_TYPE
+
+PROGRAM main
+VAR
+    color : Color;
+    name : STRING[10];
+    values : ARRAY[1..5] OF INT;
+    delay : TIME;
+    flag : BOOL;
+END_VAR
+END_PROGRAM
+",
+    );
+
+    //enums are described with their elements
+    assert!(result.contains(r#"DW_TAG_enumeration_type, name: "Color""#));
+    assert!(result.contains(r#"!DIEnumerator(name: "red", value: 0)"#));
+    assert!(result.contains(r#"!DIEnumerator(name: "green", value: 5)"#));
+    assert!(result.contains(r#"!DIEnumerator(name: "blue", value: 6)"#));
+
+    //strings and arrays are named as declared, including their length and bounds
+    assert!(result.contains(r#"DW_TAG_typedef, name: "STRING[10]""#));
+    assert!(result.contains(r#"DW_TAG_typedef, name: "ARRAY[1..5] OF INT""#));
+    assert!(result.contains("!DISubrange(count: 5, lowerBound: 1)"));
+
+    //the date and time types keep their names for the pretty printers
+    assert!(result.contains(r#"!DIBasicType(name: "TIME", size: 64"#));
+
+    //typedefs have no size of their own, the members following them are still aligned
+    let flag = find_metadata(&result, r#"DW_TAG_member, name: "flag""#);
+    assert!(flag.contains("offset: 320"));
+}
@@ -97,3 +97,30 @@ fn qualified_reference_assignment() {
     );
     insta::assert_snapshot!(prog);
 }
+
+#[test]
+fn located_globals_are_stored_in_the_process_images() {
+    let prog = codegen(
+        "
+        VAR_GLOBAL
+            start AT %IX1.2 : BOOL;
+            speed AT %QW2 : INT;
+        END_VAR
+
+        PROGRAM main
+        VAR
+            x : INT;
+        END_VAR
+        IF start THEN
+            x := speed;
+        END_IF
+        END_PROGRAM
+        ",
+    );
+
+    assert!(prog.contains("@__rusty_input_image = global [11 x i8] zeroinitializer"));
+    assert!(prog.contains("@__rusty_output_image = global [4 x i8] zeroinitializer"));
+    assert!(prog.contains("@__rusty_input_image, i32 0, i32 10)"));
+    assert!(prog.contains("@__rusty_output_image, i32 0, i32 2) to i16*"));
+    assert!(!prog.contains("@speed"));
+}
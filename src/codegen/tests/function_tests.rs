@@ -301,3 +301,275 @@ fn passing_arguments_to_functions_by_ref_and_val() {
     }
     "###);
 }
+
+#[test]
+fn optimize_attributes_are_mapped_to_function_attributes() {
+    let result = codegen(
+        "
+        {attribute 'optimize' := 'none'}
+        FUNCTION check : BOOL
+            check := TRUE;
+        END_FUNCTION
+
+        {attribute 'optimize' := 'size'}
+        FUNCTION_BLOCK fb
+        METHOD reset
+        END_METHOD
+        END_FUNCTION_BLOCK
+
+        {attribute 'optimize' := 'speed'}
+        FUNCTION calc : INT
+            calc := 1;
+        END_FUNCTION
+
+        FUNCTION plain : INT
+            plain := 1;
+        END_FUNCTION
+        ",
+    );
+
+    //returns the attributes of the group the given function refers to
+    let attributes_of = |function: &str| {
+        let definition = result
+            .lines()
+            .find(|it| it.starts_with("define") && it.contains(&format!(" @{}(", function)))
+            .unwrap();
+        let group = definition
+            .trim_end_matches(" {")
+            .rsplit(' ')
+            .next()
+            .unwrap();
+        result
+            .lines()
+            .find(|it| it.starts_with(&format!("attributes {} = ", group)))
+            .map(|it| it.split(" = ").nth(1).unwrap().to_string())
+    };
+
+    assert_eq!(attributes_of("check").unwrap(), "{ noinline optnone }");
+    assert_eq!(attributes_of("fb").unwrap(), "{ minsize optsize }");
+    //methods are optimized like their function block
+    assert_eq!(attributes_of("fb.reset").unwrap(), "{ minsize optsize }");
+    //speed is the optimization of the module, so it adds no attributes
+    assert_eq!(attributes_of("calc"), None);
+    assert_eq!(attributes_of("plain"), None);
+}
+
+#[test]
+fn functions_with_a_calling_convention_pass_aggregates_through_pointers() {
+    let result = codegen(
+        "
+        {external 'stdcall'}
+        FUNCTION MessageBoxA : DINT
+        VAR_INPUT text : STRING; flags : UDINT; END_VAR
+        END_FUNCTION
+
+        {external 'C'}
+        FUNCTION format_name : STRING
+        VAR_INPUT name : STRING; END_VAR
+        END_FUNCTION
+
+        PROGRAM main
+        VAR code : DINT; greeting : STRING; END_VAR
+            code := MessageBoxA('hello', 0);
+            greeting := format_name('world');
+        END_PROGRAM
+        ",
+    );
+
+    assert!(
+        result.contains("declare x86_stdcallcc i32 @MessageBoxA([81 x i8]* byval([81 x i8]), i32)")
+    );
+    assert!(result.contains(
+        "declare void @format_name([81 x i8]* sret([81 x i8]), [81 x i8]* byval([81 x i8]))"
+    ));
+    assert!(result.contains("call x86_stdcallcc i32 @MessageBoxA([81 x i8]* %"));
+    assert!(result.contains("call void @format_name([81 x i8]* %result, [81 x i8]* %"));
+}
+
+#[test]
+fn fast_math_functions_contract_products_to_multiply_adds() {
+    let result = codegen(
+        "
+        {attribute 'fast_math'}
+        FUNCTION filter : REAL
+        VAR_INPUT
+            x, y, k : REAL;
+            l : LREAL;
+        END_VAR
+            filter := x * k + y;
+            filter := y + x * k;
+            filter := x * k - y;
+            l := l * l + l;
+            filter := x / k + y;
+        END_FUNCTION
+
+        FUNCTION strict : REAL
+        VAR_INPUT
+            x, y, k : REAL;
+        END_VAR
+            strict := x * k + y;
+        END_FUNCTION
+        ",
+    );
+
+    let body_of = |function: &str| {
+        result
+            .split(&format!("@{}(", function))
+            .nth(1)
+            .and_then(|it| it.split("\n}").next())
+            .unwrap()
+            .to_string()
+    };
+    let filter = body_of("filter");
+    assert_eq!(filter.matches("call float @llvm.fmuladd.f32(").count(), 3);
+    assert_eq!(filter.matches("call double @llvm.fmuladd.f64(").count(), 1);
+    assert!(!filter.contains("fmul"));
+    assert!(filter.contains("fdiv float"));
+    assert!(filter.contains("fneg float"));
+    assert!(!body_of("strict").contains("fmuladd"));
+
+    let fast_math_attributes = result
+        .lines()
+        .find(|it| it.starts_with("attributes ") && it.contains("unsafe-fp-math"))
+        .unwrap();
+    for attribute in [
+        "\"unsafe-fp-math\"=\"true\"",
+        "\"no-nans-fp-math\"=\"true\"",
+        "\"no-infs-fp-math\"=\"true\"",
+        "\"no-signed-zeros-fp-math\"=\"true\"",
+        "\"approx-func-fp-math\"=\"true\"",
+    ] {
+        assert!(fast_math_attributes.contains(attribute));
+    }
+    let strict = result
+        .lines()
+        .find(|it| it.starts_with("define") && it.contains(" @strict("))
+        .unwrap();
+    assert!(strict.ends_with(") {"));
+}
+
+#[test]
+fn array_builtins_are_generated_as_vectorizable_loops() {
+    let result = codegen(
+        "
+        PROGRAM main
+        VAR
+            a, b : ARRAY[1..8] OF REAL;
+            c : ARRAY[0..5] OF REAL;
+            i : ARRAY[0..3] OF INT;
+            count : UDINT;
+            dot : LREAL;
+        END_VAR
+            count := ARRAY_ADD(c, a, b);
+            count := ARRAY_MUL(destination := a, left := a, right := b);
+            dot := ARRAY_DOT(i, i);
+        END_PROGRAM
+        ",
+    );
+
+    assert!(result.contains("store i32 6, i32* %count"));
+    assert!(result.contains("store i32 8, i32* %count"));
+    assert_eq!(result.matches("fadd float").count(), 1);
+    assert_eq!(result.matches("fmul float").count(), 1);
+    assert!(result.contains("sext i16"));
+    assert!(result.contains("sitofp i64"));
+    assert_eq!(result.matches(", !llvm.loop !").count(), 3);
+    assert!(result.contains("!{!\"llvm.loop.vectorize.enable\", i1 true}"));
+}
+
+#[test]
+fn matrix_builtins_are_generated_as_nested_loops() {
+    let result = codegen(
+        "
+        PROGRAM main
+        VAR
+            rotation : MATRIX[3, 3] OF LREAL;
+            position, rotated : VECTOR[3] OF LREAL;
+            transposed : MATRIX[3, 3] OF LREAL;
+            count : UDINT;
+        END_VAR
+            count := MATRIX_MUL(rotated, rotation, position);
+            count := TRANSPOSE(transposed, rotation);
+        END_PROGRAM
+        ",
+    );
+
+    assert!(result.contains("%rotation = getelementptr inbounds %main_interface"));
+    assert!(result.contains("[9 x double]"));
+    assert_eq!(result.matches("store i32 3, i32* %count").count(), 1);
+    assert_eq!(result.matches("store i32 9, i32* %count").count(), 1);
+    assert_eq!(result.matches("fmul double").count(), 1);
+    assert_eq!(result.matches("fadd double").count(), 1);
+    assert_eq!(result.matches(", !llvm.loop !").count(), 5);
+}
+
+#[test]
+fn saturating_builtins_call_the_intrinsic_of_the_operands_type() {
+    let result = codegen(
+        "
+        PROGRAM main
+        VAR
+            i : INT;
+            b : BYTE;
+            d : DINT;
+        END_VAR
+            i := ADD_SAT(i, INT#1000);
+            b := SUB_SAT(b, BYTE#10);
+            d := MUL_SAT(in1 := d, in2 := d);
+        END_PROGRAM
+        ",
+    );
+
+    assert!(result.contains("call i16 @llvm.sadd.sat.i16(i16 %load_i, i16 1000)"));
+    assert!(result.contains("call i8 @llvm.usub.sat.i8(i8 %load_b, i8 10)"));
+    assert!(result.contains("call i32 @llvm.smul.fix.sat.i32(i32 %load_d, i32 %load_d1, i32 0)"));
+    assert!(result.contains("declare i64 @llvm.umul.fix.sat.i64(i64, i64, i32)"));
+}
+
+#[test]
+fn calls_of_inline_functions_are_replaced_by_their_expression() {
+    let result = codegen(
+        "
+        {attribute 'inline'}
+        FUNCTION scaled : DINT
+        VAR_INPUT
+            x, factor : DINT;
+        END_VAR
+            scaled := x * factor + 1;
+        END_FUNCTION
+
+        {attribute 'inline'}
+        FUNCTION countdown : DINT
+        VAR_INPUT
+            n : DINT;
+        END_VAR
+            countdown := countdown(n - 1);
+        END_FUNCTION
+
+        PROGRAM main
+        VAR
+            a, b : DINT;
+            l : LINT;
+        END_VAR
+            a := scaled(b, 3);
+            l := scaled(factor := a, x := INT#2);
+            b := countdown(a);
+        END_PROGRAM
+        ",
+    );
+
+    let main = result
+        .split("@main(")
+        .nth(1)
+        .and_then(|it| it.split("\n}").next())
+        .unwrap();
+    assert!(!main.contains("@scaled("));
+    //the copies of the parameters and of the result
+    assert_eq!(main.matches(" = alloca i32").count(), 8);
+    assert_eq!(main.matches("mul i32").count(), 2);
+    assert!(main.contains("store i32 2, i32* %x"));
+    assert!(main.contains("sext i32"));
+    //a recursive call is only inlined once
+    assert_eq!(main.matches("call i32 @countdown(").count(), 1);
+    assert!(result.contains("define i32 @scaled("));
+}
@@ -337,3 +337,67 @@ fn struct_initializer_uses_fallback_to_field_default() {
 
     insta::assert_snapshot!(result);
 }
+
+#[test]
+fn struct_types_can_be_declared_after_their_first_use() {
+    let result = codegen(
+        "
+        TYPE Outer:
+        STRUCT
+          inner : Inner;
+          arr : ARRAY[0..1] OF Inner;
+        END_STRUCT
+        END_TYPE
+
+        TYPE Inner:
+        STRUCT
+          x : INT := 7;
+        END_STRUCT
+        END_TYPE
+
+        VAR_GLOBAL
+          o : Outer;
+        END_VAR
+        ",
+    );
+
+    // the members are generated before the struct containing them, regardless of declaration order
+    assert!(result.contains("%Outer = type { %Inner, [2 x %Inner] }"));
+    assert!(result.contains(
+        "@o = global %Outer { %Inner { i16 7 }, [2 x %Inner] [%Inner { i16 7 }, %Inner { i16 7 }] }"
+    ));
+}
+
+#[test]
+fn struct_and_function_block_types_are_generated_in_dependency_order() {
+    let result = codegen(
+        "
+        TYPE Wrapper:
+        STRUCT
+          counter : Counter;
+        END_STRUCT
+        END_TYPE
+
+        FUNCTION_BLOCK Counter
+        VAR
+          step : Step;
+        END_VAR
+        END_FUNCTION_BLOCK
+
+        TYPE Step:
+        STRUCT
+          increment : INT := 2;
+        END_STRUCT
+        END_TYPE
+
+        VAR_GLOBAL
+          w : Wrapper;
+        END_VAR
+        ",
+    );
+
+    // the function block is generated between the structs it contains and the struct containing it
+    assert!(result.contains("%Counter_interface = type { %Step }"));
+    assert!(result.contains("%Wrapper = type { %Counter_interface }"));
+    assert!(result.contains("@w = global %Wrapper { %Counter_interface { %Step { i16 2 } } }"));
+}
@@ -1,5 +1,8 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
-use crate::test_utils::tests::codegen;
+use crate::{
+    test_utils::tests::{codegen, codegen_with_bounds_check, codegen_with_timing_annotations},
+    Diagnostic,
+};
 
 #[test]
 fn bitaccess_generated_as_rsh_and_trunc_i1() {
@@ -154,3 +157,151 @@ fn function_result_assignment_on_aliased_string() {
 
     insta::assert_snapshot!(result);
 }
+
+#[test]
+fn statements_and_bounded_loops_are_annotated_for_wcet_analyzers() {
+    let src = "
+        PROGRAM main
+        VAR i : INT; END_VAR
+            {attribute 'loop_bound' := '10'}
+            WHILE i < 10 DO
+                i := i + 1;
+            END_WHILE
+        END_PROGRAM
+        ";
+    let result = codegen_with_timing_annotations(src);
+
+    //the header checking the condition is bounded
+    let header = result
+        .lines()
+        .skip_while(|it| !it.starts_with("condition_check:"))
+        .nth(1)
+        .unwrap();
+    assert!(header.contains("!rusty.loop_bound"), "{}", result);
+    assert!(result.contains("= !{i64 10}"), "{}", result);
+
+    //the loop and the assignment are identified by their offsets
+    assert_eq!(
+        result.matches(", !rusty.statement !").count(),
+        2,
+        "{}",
+        result
+    );
+    for statement in ["WHILE", "i := i + 1"] {
+        let offset = format!(", i64 {}}}", src.find(statement).unwrap());
+        assert!(result.contains(&offset), "{}", result);
+    }
+}
+
+#[test]
+fn code_is_not_annotated_by_default() {
+    let result = codegen(
+        "
+        PROGRAM main
+        VAR i : INT; END_VAR
+            {attribute 'loop_bound' := '10'}
+            WHILE i < 10 DO
+                i := i + 1;
+            END_WHILE
+        END_PROGRAM
+        ",
+    );
+    assert!(!result.contains("rusty.statement"));
+    assert!(!result.contains("rusty.loop_bound"));
+}
+
+#[test]
+fn array_accesses_with_variable_indexes_trap_out_of_bounds() {
+    let src = "
+        PROGRAM main
+        VAR
+            a : ARRAY[1..10] OF INT;
+            m : ARRAY[0..2, 0..3] OF INT;
+            i : INT;
+        END_VAR
+            a[i] := a[3];
+            m[i, 2] := 0;
+        END_PROGRAM
+        ";
+    let result = codegen_with_bounds_check(src).unwrap();
+
+    //the constant indexes are not checked
+    assert_eq!(result.matches("icmp sge i32").count(), 2, "{}", result);
+    assert_eq!(result.matches("icmp sle i32").count(), 2, "{}", result);
+    assert!(result.contains(", 10\n"), "{}", result);
+    assert_eq!(result.matches("call void @llvm.trap()").count(), 2);
+    assert!(result.contains("unreachable"));
+    assert!(!codegen(src).contains("out_of_bounds"));
+}
+
+#[test]
+fn indexes_out_of_bounds_are_passed_to_the_check_bounds_function() {
+    let result = codegen_with_bounds_check(
+        "
+        FUNCTION CheckBounds : DINT
+        VAR_INPUT
+            index, lower, upper : DINT;
+        END_VAR
+            CheckBounds := lower;
+        END_FUNCTION
+
+        FUNCTION sum : DINT
+        VAR_IN_OUT
+            values : ARRAY[*] OF DINT;
+        END_VAR
+        VAR_INPUT
+            i : DINT;
+        END_VAR
+            sum := values[i];
+        END_FUNCTION
+
+        PROGRAM main
+        VAR
+            a : ARRAY[1..10] OF INT;
+            i : INT;
+        END_VAR
+            a[i] := 1;
+        END_PROGRAM
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        result.matches("call i32 @CheckBounds(i32 %").count(),
+        2,
+        "{}",
+        result
+    );
+    assert!(result.contains("i32 1, i32 10)"), "{}", result);
+    assert_eq!(result.matches("phi i32").count(), 2);
+    assert!(!result.contains("call void @llvm.trap()"));
+}
+
+#[test]
+fn check_bounds_functions_with_other_parameters_are_reported() {
+    let src = "
+        FUNCTION CheckBounds : INT
+        VAR_INPUT
+            index : INT;
+        END_VAR
+            CheckBounds := index;
+        END_FUNCTION
+
+        PROGRAM main
+        VAR
+            a : ARRAY[1..10] OF INT;
+            i : INT;
+        END_VAR
+            a[i] := 1;
+        END_PROGRAM
+        ";
+    let offset = src.find("a[i]").unwrap() + 2;
+
+    assert_eq!(
+        codegen_with_bounds_check(src),
+        Err(Diagnostic::invalid_bounds_check_function(
+            "CheckBounds",
+            (offset..offset + 1).into()
+        ))
+    );
+}
@@ -254,3 +254,32 @@ fn int_bigger_than_byte_promoted_on_compare_statement() {
 
     insta::assert_snapshot!(result);
 }
+
+#[test]
+fn fixed_point_arithmetic_is_lowered_to_integer_operations() {
+    let result = codegen(
+        "
+        TYPE q16 : FIXED(16, 16); END_TYPE
+        PROGRAM main
+        VAR a, b : q16; i : DINT; r : REAL; END_VAR
+            a := 1.5;
+            b := a * b;
+            b := a / 2;
+            a := a + i;
+            i := FIXED_TO_DINT(a);
+            r := FIXED_TO_REAL(b);
+        END_PROGRAM
+        ",
+    );
+
+    assert!(result.contains("%main_interface = type { i32, i32, i32, float }"));
+    assert!(result.contains("store i32 98304, i32* %a"));
+    assert!(result.contains("mul i64"));
+    assert!(result.contains("ashr i64"));
+    assert!(result.contains("sdiv i64"));
+    assert_eq!(result.matches("trunc i64").count(), 2);
+    assert!(result.contains("shl i32"));
+    assert!(result.contains("sdiv i32"));
+    assert!(result.contains("sitofp i32"));
+    assert!(!result.contains("fadd"));
+}
@@ -0,0 +1,444 @@
+//! loads the initial values of the global variables declared in a `{attribute 'config_const'}`
+//! block from a JSON or TOML file, so machine variants can be built from the same sources with
+//! different parameter sets
+//!
+//! the file maps the names of the variables to their values: numbers and booleans for the
+//! elementary types, strings for `STRING`s and `WSTRING`s, arrays (flattened for
+//! multi-dimensional arrays) and tables or objects for structs. The values replace the
+//! initializers of the declarations before the sources are indexed, a variable without a value
+//! keeps its initializer.
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{
+    ast::{AstStatement, CompilationUnit, SourceRange},
+    codegen::generators::debug_info::get_declared_type_name,
+    config_file::{load_config, parse_config},
+    diagnostics::Diagnostic,
+    index::Index,
+    lexer::IdProvider,
+    typesystem::{DataTypeInformation, StructSource},
+    ConfigFormat,
+};
+
+/// the values of the configuration constants by the lower case name of their variable
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfigConstants {
+    values: HashMap<String, Value>,
+}
+
+impl ConfigConstants {
+    /// reads the configuration constants from the given file, the format is detected by its
+    /// extension
+    pub fn load(file: &str) -> Result<ConfigConstants, Diagnostic> {
+        load_config(file).map(ConfigConstants::new)
+    }
+
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<ConfigConstants, Diagnostic> {
+        parse_config(content, format).map(ConfigConstants::new)
+    }
+
+    /// creates the constants from the given values by the name of their variable
+    fn new(values: HashMap<String, Value>) -> ConfigConstants {
+        ConfigConstants {
+            values: values
+                .into_iter()
+                .map(|(name, value)| (name.to_lowercase(), value))
+                .collect(),
+        }
+    }
+
+    /// replaces the initializers of the unit's configuration constants with their values and
+    /// returns the names of the replaced variables
+    pub fn apply(&self, unit: &mut CompilationUnit, id_provider: &mut IdProvider) -> Vec<String> {
+        let mut applied = vec![];
+        for variable in unit
+            .global_vars
+            .iter_mut()
+            .filter(|it| it.config_const)
+            .flat_map(|it| it.variables.iter_mut())
+        {
+            if let Some(value) = self.values.get(&variable.name.to_lowercase()) {
+                variable.initializer = Some(to_statement(value, &variable.location, id_provider));
+                applied.push(variable.name.to_lowercase());
+            }
+        }
+        applied
+    }
+
+    /// reports the values of the given names which are not declared as configuration constants
+    pub fn check_declared(&self, applied: &[String]) -> Result<(), Diagnostic> {
+        let mut undeclared = self
+            .values
+            .keys()
+            .filter(|it| !applied.contains(it))
+            .collect::<Vec<_>>();
+        undeclared.sort();
+        match undeclared.first() {
+            Some(name) => Err(Diagnostic::param_error(&format!(
+                "Configuration constant '{}' is not declared in a {{attribute 'config_const'}} block",
+                name
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// reports the values of the unit's configuration constants not matching their variable's type
+    pub fn validate(&self, unit: &CompilationUnit, index: &Index) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        for variable in unit
+            .global_vars
+            .iter()
+            .filter(|it| it.config_const)
+            .flat_map(|it| it.variables.iter())
+        {
+            let value = self.values.get(&variable.name.to_lowercase());
+            let entry = index.find_global_variable(&variable.name);
+            if let Some((value, entry)) = value.zip(entry) {
+                if let Err((path, reason)) =
+                    validate_value(value, &variable.name, entry.get_type_name(), index)
+                {
+                    diagnostics.push(Diagnostic::invalid_config_constant(
+                        &path,
+                        &reason,
+                        variable.location.clone(),
+                    ));
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+/// converts the value into the literal initializing its variable
+fn to_statement(
+    value: &Value,
+    location: &SourceRange,
+    id_provider: &mut IdProvider,
+) -> AstStatement {
+    let location = location.clone();
+    match value {
+        Value::Bool(value) => AstStatement::LiteralBool {
+            value: *value,
+            location,
+            id: id_provider.next_id(),
+        },
+        Value::Number(number) => match number
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| number.as_u64().map(i128::from))
+        {
+            Some(value) => AstStatement::LiteralInteger {
+                value,
+                location,
+                id: id_provider.next_id(),
+            },
+            None => AstStatement::LiteralReal {
+                value: number.to_string(),
+                location,
+                id: id_provider.next_id(),
+            },
+        },
+        Value::String(value) => AstStatement::LiteralString {
+            value: value.clone(),
+            is_wide: false,
+            location,
+            id: id_provider.next_id(),
+        },
+        Value::Array(elements) => {
+            let mut elements = elements
+                .iter()
+                .map(|it| to_statement(it, &location, id_provider))
+                .collect::<Vec<_>>();
+            let elements = match elements.len() {
+                0 => None,
+                1 => elements.pop(),
+                _ => Some(AstStatement::ExpressionList {
+                    expressions: elements,
+                    id: id_provider.next_id(),
+                }),
+            };
+            AstStatement::LiteralArray {
+                elements: elements.map(Box::new),
+                location,
+                id: id_provider.next_id(),
+            }
+        }
+        Value::Object(members) => {
+            let mut assignments = members
+                .iter()
+                .map(|(name, value)| AstStatement::Assignment {
+                    left: Box::new(AstStatement::Reference {
+                        name: name.clone(),
+                        location: location.clone(),
+                        id: id_provider.next_id(),
+                    }),
+                    right: Box::new(to_statement(value, &location, id_provider)),
+                    id: id_provider.next_id(),
+                })
+                .collect::<Vec<_>>();
+            if assignments.len() == 1 {
+                assignments.remove(0)
+            } else {
+                AstStatement::ExpressionList {
+                    expressions: assignments,
+                    id: id_provider.next_id(),
+                }
+            }
+        }
+        Value::Null => AstStatement::EmptyStatement {
+            location,
+            id: id_provider.next_id(),
+        },
+    }
+}
+
+/// returns the path of the first mismatching value and the reason if the value does not match
+/// the given type
+fn validate_value(
+    value: &Value,
+    path: &str,
+    type_name: &str,
+    index: &Index,
+) -> Result<(), (String, String)> {
+    let expected = |description: &str| {
+        Err((
+            path.to_string(),
+            format!("expected {}, found {}", description, value),
+        ))
+    };
+    let type_info = match index.find_effective_type_info(type_name) {
+        Some(type_info) => type_info,
+        None => return Ok(()),
+    };
+    let declared_type = get_declared_type_name(type_name, index);
+    match type_info {
+        it if it.is_bool() => match value {
+            Value::Bool(_) => Ok(()),
+            _ => expected(&declared_type),
+        },
+        DataTypeInformation::Integer { signed, size, .. } => {
+            let (min, max) = if *signed {
+                (-(1_i128 << (size - 1)), (1_i128 << (size - 1)) - 1)
+            } else {
+                (0, (1_i128 << size) - 1)
+            };
+            let number = value
+                .as_i64()
+                .map(i128::from)
+                .or_else(|| value.as_u64().map(i128::from));
+            match number {
+                Some(number) if (min..=max).contains(&number) => Ok(()),
+                Some(_) => Err((
+                    path.to_string(),
+                    format!("{} exceeds the range of {}", value, declared_type),
+                )),
+                None => expected(&declared_type),
+            }
+        }
+        DataTypeInformation::Float { .. } => match value {
+            Value::Number(_) => Ok(()),
+            _ => expected(&declared_type),
+        },
+        DataTypeInformation::String { size, .. } => {
+            //the terminator is not part of the declared length
+            let length = size.as_int_value(index).unwrap_or(1) - 1;
+            match value {
+                Value::String(text) if text.chars().count() as i64 <= length => Ok(()),
+                Value::String(_) => Err((
+                    path.to_string(),
+                    format!("{} exceeds the length of {}", value, declared_type),
+                )),
+                _ => expected(&declared_type),
+            }
+        }
+        DataTypeInformation::Array {
+            inner_type_name,
+            dimensions,
+            ..
+        } => {
+            let length = dimensions
+                .iter()
+                .map(|it| it.get_length(index).unwrap_or(0) as usize)
+                .product::<usize>();
+            match value {
+                Value::Array(elements) if elements.len() <= length => elements
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(element, value)| {
+                        validate_value(
+                            value,
+                            &format!("{}[{}]", path, element),
+                            inner_type_name,
+                            index,
+                        )
+                    }),
+                Value::Array(elements) => Err((
+                    path.to_string(),
+                    format!(
+                        "{} values exceed the {} elements of {}",
+                        elements.len(),
+                        length,
+                        declared_type
+                    ),
+                )),
+                _ => expected(&declared_type),
+            }
+        }
+        DataTypeInformation::Struct {
+            name: struct_name,
+            source: StructSource::OriginalDeclaration,
+            ..
+        } => match value {
+            Value::Object(members) => members.iter().try_for_each(|(name, value)| {
+                let member_path = format!("{}.{}", path, name);
+                match index.find_member(struct_name, name) {
+                    Some(member) => {
+                        validate_value(value, &member_path, member.get_type_name(), index)
+                    }
+                    None => Err((
+                        member_path,
+                        format!("{} has no member '{}'", declared_type, name),
+                    )),
+                }
+            }),
+            _ => expected(&declared_type),
+        },
+        DataTypeInformation::Enum {
+            referenced_type, ..
+        }
+        | DataTypeInformation::SubRange {
+            referenced_type, ..
+        } => validate_value(value, path, referenced_type, index),
+        _ => Err((
+            path.to_string(),
+            format!("values of type {} cannot be configured", declared_type),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConfigConstants;
+    use crate::{
+        ast::{self, AstStatement, SourceRange},
+        builtins,
+        diagnostics::Diagnostic,
+        index::{self, Index},
+        lexer::IdProvider,
+        test_utils::tests::parse,
+        ConfigFormat,
+    };
+
+    #[test]
+    fn initializers_are_replaced_by_the_configured_values() {
+        let (mut unit, _) = parse(
+            "
+            {attribute 'config_const'}
+            VAR_GLOBAL CONSTANT
+                motorCount : INT := 1;
+                speeds : ARRAY[1..3] OF REAL;
+                name : STRING;
+            END_VAR
+            ",
+        );
+        let constants = ConfigConstants::parse(
+            r#"
+            MotorCount = 2
+            speeds = [1.5, 2.5]
+            "#,
+            ConfigFormat::TOML,
+        )
+        .unwrap();
+
+        let applied = constants.apply(&mut unit, &mut IdProvider::default());
+
+        assert_eq!(applied, vec!["motorcount", "speeds"]);
+        let variables = &unit.global_vars[0].variables;
+        assert!(matches!(
+            variables[0].initializer,
+            Some(AstStatement::LiteralInteger { value: 2, .. })
+        ));
+        assert_eq!(
+            format!("{:?}", variables[1].initializer),
+            "Some(LiteralArray { elements: Some(ExpressionList { expressions: [LiteralReal { value: \"1.5\" }, LiteralReal { value: \"2.5\" }] }) })"
+        );
+        assert_eq!(variables[2].initializer, None);
+        assert_eq!(constants.check_declared(&applied), Ok(()));
+        assert_eq!(
+            constants.check_declared(&["speeds".to_string()]),
+            Err(Diagnostic::param_error(
+                "Configuration constant 'motorcount' is not declared in a {attribute 'config_const'} block"
+            ))
+        );
+    }
+
+    #[test]
+    fn values_are_validated_against_the_type_of_their_variable() {
+        let src = "
+            TYPE Axis : STRUCT
+                id : USINT;
+                label : STRING[4];
+            END_STRUCT END_TYPE
+
+            {attribute 'config_const'}
+            VAR_GLOBAL CONSTANT
+                count : SINT;
+                enabled : BOOL;
+                axes : ARRAY[0..1] OF Axis;
+                gains : ARRAY[0..1] OF LREAL;
+            END_VAR
+            ";
+        let (mut unit, _) = parse(src);
+        let constants = ConfigConstants::parse(
+            r#"{
+                "count": 200,
+                "enabled": 1,
+                "axes": [ { "id": 1, "label": "x" }, { "id": 2, "label": "too long" } ],
+                "gains": [0.5, 1.5, 2.5]
+            }"#,
+            ConfigFormat::JSON,
+        )
+        .unwrap();
+        let id_provider = IdProvider::default();
+        constants.apply(&mut unit, &mut id_provider.clone());
+        ast::pre_process(&mut unit, id_provider.clone());
+        let mut index = Index::default();
+        index.import(index::visitor::visit(
+            &builtins::parse_built_ins(id_provider.clone()),
+            id_provider.clone(),
+        ));
+        index.import(index::visitor::visit(&unit, id_provider));
+
+        let location = |name: &str| {
+            let start = src.find(&format!("{} :", name)).unwrap();
+            SourceRange::new(start..start + name.len())
+        };
+        assert_eq!(
+            constants.validate(&unit, &index),
+            vec![
+                Diagnostic::invalid_config_constant(
+                    "count",
+                    "200 exceeds the range of SINT",
+                    location("count")
+                ),
+                Diagnostic::invalid_config_constant(
+                    "enabled",
+                    "expected BOOL, found 1",
+                    location("enabled")
+                ),
+                Diagnostic::invalid_config_constant(
+                    "axes[1].label",
+                    "\"too long\" exceeds the length of STRING[4]",
+                    location("axes")
+                ),
+                Diagnostic::invalid_config_constant(
+                    "gains",
+                    "3 values exceed the 2 elements of ARRAY[0..1] OF LREAL",
+                    location("gains")
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,36 @@
+//! reading the configuration files of an application (e.g. its task configuration) in the JSON
+//! or TOML format
+use serde::de::DeserializeOwned;
+
+use crate::{
+    diagnostics::{Diagnostic, ErrNo},
+    ConfigFormat,
+};
+
+/// reads the configuration from the given file, the format is detected by its extension
+pub fn load_config<T: DeserializeOwned>(file: &str) -> Result<T, Diagnostic> {
+    let format = crate::cli::get_config_format(file).ok_or_else(|| {
+        Diagnostic::param_error(&format!(
+            "Cannot identify format type for {}, valid extensions : \"json\", \"toml\"",
+            file
+        ))
+    })?;
+    let content = std::fs::read_to_string(file)
+        .map_err(|err| Diagnostic::io_read_error(file, err.to_string().as_str()))?;
+    parse_config(&content, format)
+}
+
+/// parses the configuration from the given content in the given format
+pub fn parse_config<T: DeserializeOwned>(
+    content: &str,
+    format: ConfigFormat,
+) -> Result<T, Diagnostic> {
+    match format {
+        ConfigFormat::JSON => serde_json::from_str(content).map_err(|e| e.to_string()),
+        ConfigFormat::TOML => toml::from_str(content).map_err(|e| e.to_string()),
+    }
+    .map_err(|message| Diagnostic::GeneralError {
+        message,
+        err_no: ErrNo::general__param_err,
+    })
+}
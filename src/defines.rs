@@ -0,0 +1,158 @@
+//! the symbols defined on the command line (`-D SIMULATION`, `-D AXES=4`) and the options of the
+//! compiler, queried in ST with `__DEFINED('SIMULATION')` and `__OPTION('target')`
+//!
+//! the queries take a literal name and are evaluated at compile time: the const evaluator replaces
+//! them in initializers and the builtins of the same name generate their constant value in bodies.
+//! `__DEFINED` returns whether a symbol was defined, `__OPTION` returns the value of a symbol or,
+//! if no such symbol was defined, the value of the compiler's option (`target`, `optimization`).
+//! Names are compared case-insensitively, unknown names evaluate to an empty string.
+use std::collections::HashMap;
+
+use crate::{
+    ast::{self, AstId, AstStatement, SourceRange},
+    diagnostics::Diagnostic,
+};
+
+pub const DEFINED: &str = "__DEFINED";
+pub const OPTION: &str = "__OPTION";
+
+/// the defined symbols and the compiler's options by their lower case name
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Defines {
+    symbols: HashMap<String, String>,
+    options: HashMap<String, String>,
+}
+
+impl Defines {
+    /// parses the given `NAME` or `NAME=VALUE` definitions, a symbol without value is defined as
+    /// an empty string
+    pub fn parse(definitions: &[String]) -> Result<Defines, Diagnostic> {
+        let mut symbols = HashMap::new();
+        for definition in definitions {
+            let (name, value) = definition
+                .split_once('=')
+                .unwrap_or((definition.as_str(), ""));
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(Diagnostic::param_error(&format!(
+                    "Invalid definition '{}', expected NAME or NAME=VALUE",
+                    definition
+                )));
+            }
+            symbols.insert(name.to_lowercase(), value.trim().to_string());
+        }
+        Ok(Defines {
+            symbols,
+            options: HashMap::new(),
+        })
+    }
+
+    /// adds the compiler's option with the given name and value
+    pub fn with_option(mut self, name: &str, value: &str) -> Self {
+        self.options.insert(name.to_lowercase(), value.to_string());
+        self
+    }
+
+    pub fn is_defined(&self, name: &str) -> bool {
+        self.symbols.contains_key(&name.to_lowercase())
+    }
+
+    /// returns the value of the given symbol or option, an empty string if neither exists
+    pub fn get_option(&self, name: &str) -> &str {
+        let name = name.to_lowercase();
+        self.symbols
+            .get(&name)
+            .or_else(|| self.options.get(&name))
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// returns whether the given function is `__DEFINED` or `__OPTION`
+    pub fn is_query(function_name: &str) -> bool {
+        function_name.eq_ignore_ascii_case(DEFINED) || function_name.eq_ignore_ascii_case(OPTION)
+    }
+
+    /// evaluates a call of `__DEFINED` or `__OPTION` with the given arguments to a `LiteralBool`
+    /// or `LiteralString` with the given id and location
+    pub fn evaluate_query(
+        &self,
+        function_name: &str,
+        arguments: &[&AstStatement],
+        id: AstId,
+        location: SourceRange,
+    ) -> Result<AstStatement, String> {
+        let name = match ast::get_call_argument(arguments, "name", 0) {
+            Some(AstStatement::LiteralString { value, .. }) if arguments.len() == 1 => value,
+            _ => {
+                return Err(format!(
+                    "Expected a literal name for {}",
+                    function_name.to_uppercase()
+                ))
+            }
+        };
+        if function_name.eq_ignore_ascii_case(DEFINED) {
+            Ok(AstStatement::LiteralBool {
+                value: self.is_defined(name),
+                location,
+                id,
+            })
+        } else {
+            Ok(AstStatement::LiteralString {
+                value: self.get_option(name).to_string(),
+                is_wide: false,
+                location,
+                id,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Defines;
+    use crate::ast::{AstStatement, SourceRange};
+
+    fn literal(value: &str) -> AstStatement {
+        AstStatement::LiteralString {
+            value: value.to_string(),
+            is_wide: false,
+            location: SourceRange::undefined(),
+            id: 0,
+        }
+    }
+
+    #[test]
+    fn definitions_are_parsed() {
+        let defines = Defines::parse(&["SIMULATION".to_string(), "Axes = 4".to_string()])
+            .unwrap()
+            .with_option("target", "x86_64-linux-gnu");
+
+        assert!(defines.is_defined("simulation"));
+        assert!(defines.is_defined("AXES"));
+        assert!(!defines.is_defined("target"));
+        assert_eq!(defines.get_option("axes"), "4");
+        assert_eq!(defines.get_option("Simulation"), "");
+        assert_eq!(defines.get_option("TARGET"), "x86_64-linux-gnu");
+        assert_eq!(defines.get_option("unknown"), "");
+        assert!(Defines::parse(&["=4".to_string()]).is_err());
+    }
+
+    #[test]
+    fn queries_are_evaluated_to_literals() {
+        let defines = Defines::parse(&["AXES=4".to_string()]).unwrap();
+        let axes = literal("axes");
+
+        assert!(matches!(
+            defines.evaluate_query("__defined", &[&axes], 0, SourceRange::undefined()),
+            Ok(AstStatement::LiteralBool { value: true, .. })
+        ));
+        assert!(matches!(
+            defines.evaluate_query("__OPTION", &[&axes], 0, SourceRange::undefined()),
+            Ok(AstStatement::LiteralString { value, .. }) if value == "4"
+        ));
+        assert_eq!(
+            defines.evaluate_query("__DEFINED", &[], 0, SourceRange::undefined()),
+            Err("Expected a literal name for __DEFINED".to_string())
+        );
+    }
+}
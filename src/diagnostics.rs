@@ -10,7 +10,8 @@ use codespan_reporting::{
 };
 use inkwell::support::LLVMString;
 
-use crate::ast::{DataTypeDeclaration, DiagnosticInfo, PouType, SourceRange};
+use crate::ast::{DataTypeDeclaration, DiagnosticInfo, Operator, PouType, SourceRange};
+use crate::dialect::Dialect;
 
 pub const INTERNAL_LLVM_ERROR: &str = "internal llvm codegen error";
 
@@ -20,6 +21,8 @@ pub enum Diagnostic {
         message: String,
         range: SourceRange,
         err_no: ErrNo,
+        /// the code-edits which fix the reported problem (e.g. to offer quick-fixes in an IDE)
+        edits: Vec<CodeEdit>,
     },
     GeneralError {
         message: String,
@@ -31,6 +34,42 @@ pub enum Diagnostic {
     },
 }
 
+/// a structured change to the source code, replacing the given range with the replacement
+/// an empty range inserts the replacement at the range's start
+#[derive(PartialEq, Debug, Clone)]
+pub struct CodeEdit {
+    pub range: SourceRange,
+    pub replacement: String,
+}
+
+impl CodeEdit {
+    /// creates an edit that inserts the given text at the given offset
+    pub fn insert(text: &str, offset: usize) -> CodeEdit {
+        CodeEdit {
+            range: SourceRange::new(offset..offset),
+            replacement: text.to_string(),
+        }
+    }
+
+    /// creates an edit that replaces the given range with the given text
+    pub fn replace(range: SourceRange, text: &str) -> CodeEdit {
+        CodeEdit {
+            range,
+            replacement: text.to_string(),
+        }
+    }
+}
+
+impl Display for CodeEdit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.range.get_start() == self.range.get_end() {
+            write!(f, "insert '{}'", self.replacement)
+        } else {
+            write!(f, "replace with '{}'", self.replacement)
+        }
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(PartialEq, Debug, Clone)]
 pub enum ErrNo {
@@ -39,6 +78,8 @@ pub enum ErrNo {
     //general
     general__io_err,
     general__param_err,
+    general__size_budget_exceeded,
+    general__profile_violation,
 
     //syntax
     syntax__generic_error,
@@ -52,6 +93,25 @@ pub enum ErrNo {
     pou__unsupported_return_type,
     pou__empty_variable_block,
     pou__missing_action_container,
+    pou__unknown_super_class,
+    pou__cyclic_inheritance,
+    pou__redeclared_inherited_member,
+    pou__unknown_interface,
+    pou__interface_method_with_body,
+    pou__missing_interface_method,
+    pou__interface_method_signature_mismatch,
+    pou__override_without_base_method,
+    pou__override_signature_mismatch,
+    pou__prototype_with_body,
+    pou__missing_prototype_implementation,
+    pou__duplicate_prototype_implementation,
+    pou__prototype_signature_mismatch,
+    pou__abstract_method_with_body,
+    pou__missing_abstract_method_implementation,
+    pou__abstract_class_instance,
+    pou__unknown_optimization,
+    pou__invalid_loop_bound,
+    pou__unknown_calling_convention,
 
     //variable related
     var__unresolved_constant,
@@ -59,6 +119,14 @@ pub enum ErrNo {
     var__invalid_constant,
     var__cannot_assign_to_const,
     var__invalid_assignment,
+    var__invalid_reference_binding,
+    var__invalid_vla_declaration,
+    var__invalid_config_constant,
+    var__invalid_hardware_address,
+    var__overlapping_hardware_address,
+    var__unmapped_template,
+    var__invalid_config_variable,
+    var__invalid_init_priority,
 
     //reference related
     reference__unresolved,
@@ -78,14 +146,75 @@ pub enum ErrNo {
     type__invalid_nature,
     type__unknown_nature,
     type__unresolved_generic,
+    type__mismatch,
+    type__implicit_bool_conversion,
+    type__bool_arithmetic,
+    type__signed_bit_operation,
+    type__comparison_chain,
+    type__invalid_case_label,
+    type__memory_size_exceeded,
+    type__circular_dependency,
+    type__invalid_vla_argument,
+
+    //jump related
+    jump__unknown_label,
+    jump__duplicate_label,
+    jump__into_block,
+
+    //sfc related
+    sfc__unknown_step,
+    sfc__duplicate_step,
+    sfc__invalid_initial_step,
+    sfc__unsupported_qualifier,
+    sfc__invalid_container,
+
+    //state machine related
+    state__unknown_state,
+    state__duplicate_state,
+    state__invalid_initial_state,
+    state__ambiguous_transition,
+    state__invalid_container,
+
+    //il related
+    il__missing_result,
+    il__missing_operand,
+    il__invalid_instruction,
+
+    //dialect related
+    dialect__unsupported_extension,
+
+    //profile related
+    profile__safety_violation,
 
     //codegen related
     codegen__general,
     codegen__missing_function,
     codegen__missing_compare_function,
+    codegen__invalid_bounds_check_function,
 
     //linker
     linker__generic_error,
+
+    //formatter
+    format__unsupported_source,
+    format__unformatted_source,
+
+    //documentation
+    doc__unsupported_source,
+
+    //migration
+    migration__conflicting_changes,
+
+    //library packages
+    package__invalid_package,
+    package__target_mismatch,
+    package__invalid_lock,
+    package__incompatible_version,
+    package__interface_changed,
+
+    //builtins
+    builtin__invalid_declaration,
+    builtin__duplicate_name,
 }
 
 impl Diagnostic {
@@ -94,6 +223,7 @@ impl Diagnostic {
             message: message.to_string(),
             range,
             err_no: ErrNo::syntax__generic_error,
+            edits: vec![],
         }
     }
 
@@ -105,6 +235,7 @@ impl Diagnostic {
             ),
             range,
             err_no: ErrNo::syntax__unexpected_token,
+            edits: vec![],
         }
     }
 
@@ -113,6 +244,7 @@ impl Diagnostic {
             message: "Return types cannot have a default value".into(),
             range,
             err_no: ErrNo::syntax__unexpected_token,
+            edits: vec![],
         }
     }
 
@@ -124,6 +256,7 @@ impl Diagnostic {
             ),
             range,
             err_no: ErrNo::pou__unexpected_return_type,
+            edits: vec![],
         }
     }
 
@@ -135,6 +268,7 @@ impl Diagnostic {
             ),
             range: data_type.get_location(),
             err_no: ErrNo::pou__unsupported_return_type,
+            edits: vec![],
         }
     }
 
@@ -143,6 +277,7 @@ impl Diagnostic {
             message: "Function Return type missing".into(),
             range,
             err_no: ErrNo::pou__missing_return_type,
+            edits: vec![],
         }
     }
 
@@ -151,6 +286,7 @@ impl Diagnostic {
             message: "Cannot generate code outside of function context.".into(),
             range: location,
             err_no: ErrNo::codegen__missing_function,
+            edits: vec![],
         }
     }
 
@@ -166,6 +302,19 @@ impl Diagnostic {
             ),
             range: location,
             err_no: ErrNo::codegen__missing_compare_function,
+            edits: vec![],
+        }
+    }
+
+    pub fn invalid_bounds_check_function(function_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid bounds check function, expected 'FUNCTION {} : DINT VAR_INPUT index, lower, upper : DINT; END_VAR ...'.",
+                function_name
+            ),
+            range: location,
+            err_no: ErrNo::codegen__invalid_bounds_check_function,
+            edits: vec![],
         }
     }
 
@@ -174,6 +323,7 @@ impl Diagnostic {
             message: format!("Missing expected Token {}", epxected_token),
             range,
             err_no: ErrNo::syntax__missing_token,
+            edits: vec![],
         }
     }
 
@@ -182,6 +332,7 @@ impl Diagnostic {
             message: "Missing Actions Container Name".to_string(),
             range,
             err_no: ErrNo::pou__missing_action_container,
+            edits: vec![],
         }
     }
 
@@ -190,6 +341,35 @@ impl Diagnostic {
             message: format!("Could not resolve reference to {:}", reference),
             range: location,
             err_no: ErrNo::reference__unresolved,
+            edits: vec![],
+        }
+    }
+
+    /// creates an unresolved-reference diagnostic that lists the given suggestions and offers
+    /// a quick-fix replacing the reference with the first (best) suggestion
+    pub fn unresolved_reference_with_suggestions(
+        reference: &str,
+        suggestions: &[String],
+        location: SourceRange,
+    ) -> Diagnostic {
+        if let Some(best) = suggestions.first() {
+            Diagnostic::SyntaxError {
+                message: format!(
+                    "Could not resolve reference to {:}. Did you mean {:}?",
+                    reference,
+                    suggestions
+                        .iter()
+                        .map(|it| format!("'{:}'", it))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                range: location.clone(),
+                err_no: ErrNo::reference__unresolved,
+                edits: vec![],
+            }
+            .with_edits(vec![CodeEdit::replace(location, best)])
+        } else {
+            Diagnostic::unresolved_reference(reference, location)
         }
     }
 
@@ -205,6 +385,7 @@ impl Diagnostic {
             ),
             range: location,
             err_no: ErrNo::type__unresolved_generic,
+            edits: vec![],
         }
     }
 
@@ -213,6 +394,7 @@ impl Diagnostic {
             message: format!("Unknown type: {:}", type_name),
             range: location,
             err_no: ErrNo::type__unknown_type,
+            edits: vec![],
         }
     }
 
@@ -221,6 +403,7 @@ impl Diagnostic {
             message: format!("Cannot cast from {:} to {:}", type_name, target_type),
             range: location,
             err_no: ErrNo::type__cast_error,
+            edits: vec![],
         }
     }
 
@@ -236,6 +419,7 @@ impl Diagnostic {
             ),
             range: location,
             err_no: ErrNo::type__incompatible_directaccess,
+            edits: vec![],
         }
     }
 
@@ -252,6 +436,7 @@ impl Diagnostic {
             ),
             range: location,
             err_no: ErrNo::type__incompatible_directaccess_range,
+            edits: vec![],
         }
     }
 
@@ -266,6 +451,7 @@ impl Diagnostic {
             ),
             range: location,
             err_no: ErrNo::type__incompatible_directaccess_variable,
+            edits: vec![],
         }
     }
 
@@ -280,281 +466,1373 @@ impl Diagnostic {
             ),
             range: location,
             err_no: ErrNo::type__incompatible_arrayaccess_range,
+            edits: vec![],
+        }
+    }
+
+    pub fn incompatible_array_access_variable(
+        access_type: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid type {} for array access. Only variables of Array types are allowed",
+                access_type
+            ),
+            range: location,
+            err_no: ErrNo::type__incompatible_arrayaccess_variable,
+            edits: vec![],
+        }
+    }
+
+    pub fn incompatible_array_access_type(access_type: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid type {} for array access. Only variables of Integer types are allowed to access an array",
+                access_type
+            ),
+            range: location,
+            err_no: ErrNo::type__incompatible_arrayaccess_variable,
+            edits: vec![],
+        }
+    }
+
+    pub fn incompatible_literal_cast(
+        cast_type: &str,
+        literal_type: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Literal {:} is not campatible to {:}",
+                literal_type, cast_type
+            ),
+            range: location,
+            err_no: ErrNo::type__incompatible_literal_cast,
+            edits: vec![],
+        }
+    }
+
+    pub fn literal_expected(location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: "Expected literal".into(),
+            range: location,
+            err_no: ErrNo::type__expected_literal,
+            edits: vec![],
+        }
+    }
+
+    pub fn literal_out_of_range(
+        literal: &str,
+        range_hint: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Literal {:} out of range ({})", literal, range_hint),
+            range: location,
+            err_no: ErrNo::type__literal_out_of_range,
+            edits: vec![],
+        }
+    }
+
+    pub fn literal_out_of_type_range(
+        literal: &str,
+        type_name: &str,
+        min: i128,
+        max: i128,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Literal {:} does not fit into {:}, the valid range is {:}..{:}",
+                literal, type_name, min, max
+            ),
+            range: location,
+            err_no: ErrNo::type__literal_out_of_range,
+            edits: vec![],
+        }
+    }
+
+    pub fn unsupported_in_dialect(
+        extension: &str,
+        dialect: Dialect,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "'{:}' is not supported by the {:} dialect",
+                extension, dialect
+            ),
+            range: location,
+            err_no: ErrNo::dialect__unsupported_extension,
+            edits: vec![],
+        }
+    }
+
+    pub fn unknown_super_class(super_class: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Unknown super class: {:}", super_class),
+            range: location,
+            err_no: ErrNo::pou__unknown_super_class,
+            edits: vec![],
+        }
+    }
+
+    pub fn cyclic_inheritance(class_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Class {:} cannot extend itself", class_name),
+            range: location,
+            err_no: ErrNo::pou__cyclic_inheritance,
+            edits: vec![],
+        }
+    }
+
+    pub fn redeclared_inherited_member(
+        member_name: &str,
+        super_class: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "{:} is already declared by the super class {:}",
+                member_name, super_class
+            ),
+            range: location,
+            err_no: ErrNo::pou__redeclared_inherited_member,
+            edits: vec![],
+        }
+    }
+
+    pub fn unknown_interface(interface: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Unknown interface: {:}", interface),
+            range: location,
+            err_no: ErrNo::pou__unknown_interface,
+            edits: vec![],
+        }
+    }
+
+    pub fn unknown_optimization(value: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Unknown optimization '{}', expected 'none', 'size' or 'speed'",
+                value
+            ),
+            range: location,
+            err_no: ErrNo::pou__unknown_optimization,
+            edits: vec![],
+        }
+    }
+
+    pub fn unknown_calling_convention(value: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Unknown calling convention '{}', expected 'C' or 'stdcall'",
+                value
+            ),
+            range: location,
+            err_no: ErrNo::pou__unknown_calling_convention,
+            edits: vec![],
+        }
+    }
+
+    pub fn invalid_loop_bound(value: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid loop bound '{}', expected the maximum number of iterations",
+                value
+            ),
+            range: location,
+            err_no: ErrNo::pou__invalid_loop_bound,
+            edits: vec![],
+        }
+    }
+
+    pub fn interface_method_with_body(method_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Interface method {:} cannot have an implementation",
+                method_name
+            ),
+            range: location,
+            err_no: ErrNo::pou__interface_method_with_body,
+            edits: vec![],
+        }
+    }
+
+    pub fn missing_interface_method(
+        method_name: &str,
+        interface: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Method {:} of interface {:} is not implemented",
+                method_name, interface
+            ),
+            range: location,
+            err_no: ErrNo::pou__missing_interface_method,
+            edits: vec![],
+        }
+    }
+
+    pub fn interface_method_signature_mismatch(
+        method_name: &str,
+        interface: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Method {:} does not match the signature declared by interface {:}",
+                method_name, interface
+            ),
+            range: location,
+            err_no: ErrNo::pou__interface_method_signature_mismatch,
+            edits: vec![],
+        }
+    }
+
+    pub fn override_without_base_method(method_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Method {:} is declared OVERRIDE but does not override a method of a super class",
+                method_name
+            ),
+            range: location,
+            err_no: ErrNo::pou__override_without_base_method,
+            edits: vec![],
+        }
+    }
+
+    pub fn override_signature_mismatch(
+        method_name: &str,
+        super_class: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Method {:} does not match the signature of the method it overrides in {:}",
+                method_name, super_class
+            ),
+            range: location,
+            err_no: ErrNo::pou__override_signature_mismatch,
+            edits: vec![],
+        }
+    }
+
+    pub fn prototype_with_body(pou_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Prototype {:} cannot have an implementation", pou_name),
+            range: location,
+            err_no: ErrNo::pou__prototype_with_body,
+            edits: vec![],
+        }
+    }
+
+    pub fn missing_prototype_implementation(pou_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Prototype {:} is not implemented", pou_name),
+            range: location,
+            err_no: ErrNo::pou__missing_prototype_implementation,
+            edits: vec![],
+        }
+    }
+
+    pub fn duplicate_prototype_implementation(
+        pou_name: &str,
+        count: usize,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Prototype {:} is implemented {:} times, expected exactly one implementation",
+                pou_name, count
+            ),
+            range: location,
+            err_no: ErrNo::pou__duplicate_prototype_implementation,
+            edits: vec![],
+        }
+    }
+
+    pub fn prototype_signature_mismatch(pou_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "{:} does not match the signature declared by its prototype",
+                pou_name
+            ),
+            range: location,
+            err_no: ErrNo::pou__prototype_signature_mismatch,
+            edits: vec![],
+        }
+    }
+
+    pub fn abstract_method_with_body(method_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Abstract method {:} cannot have an implementation",
+                method_name
+            ),
+            range: location,
+            err_no: ErrNo::pou__abstract_method_with_body,
+            edits: vec![],
+        }
+    }
+
+    pub fn missing_abstract_method_implementation(
+        class_name: &str,
+        method_names: &[&str],
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Class {:} must be declared ABSTRACT or implement the abstract methods: {:}",
+                class_name,
+                method_names.join(", ")
+            ),
+            range: location,
+            err_no: ErrNo::pou__missing_abstract_method_implementation,
+            edits: vec![],
+        }
+    }
+
+    pub fn abstract_class_instance(
+        variable_name: &str,
+        class_name: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Cannot declare {:} as an instance of the abstract class {:}",
+                variable_name, class_name
+            ),
+            range: location,
+            err_no: ErrNo::pou__abstract_class_instance,
+            edits: vec![],
+        }
+    }
+
+    pub fn empty_variable_block(location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: "Variable block is empty".into(),
+            range: location,
+            err_no: ErrNo::pou__empty_variable_block,
+            edits: vec![],
+        }
+    }
+
+    pub fn unresolved_constant(
+        constant_name: &str,
+        reason: Option<&str>,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Unresolved constant '{:}' variable{:}",
+                constant_name,
+                reason
+                    .map(|it| format!(": {:}", it))
+                    .unwrap_or_else(|| "".into()),
+            ),
+            range: location,
+            err_no: ErrNo::pou__empty_variable_block,
+            edits: vec![],
+        }
+    }
+
+    pub fn invalid_constant_block(location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: "This variable block does not support the CONSTANT modifier".to_string(),
+            range: location,
+            err_no: ErrNo::var__invalid_constant_block,
+            edits: vec![],
+        }
+    }
+
+    pub fn invalid_constant(constant_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Invalid constant {:} - Functionblock- and Class-instances cannot be delcared constant", constant_name),
+            range: location,
+            err_no: ErrNo::var__invalid_constant,
+            edits: vec![],
+        }
+    }
+
+    pub fn cannot_assign_to_constant(qualified_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Cannot assign to CONSTANT '{:}'", qualified_name),
+            range: location,
+            err_no: ErrNo::var__cannot_assign_to_const,
+            edits: vec![],
+        }
+    }
+
+    pub fn unknown_label(label: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Unknown label '{:}'", label),
+            range: location,
+            err_no: ErrNo::jump__unknown_label,
+            edits: vec![],
+        }
+    }
+
+    pub fn duplicate_label(label: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Duplicate label '{:}'", label),
+            range: location,
+            err_no: ErrNo::jump__duplicate_label,
+            edits: vec![],
+        }
+    }
+
+    pub fn jump_into_block(label: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Cannot jump to label '{:}', it is declared in a nested block",
+                label
+            ),
+            range: location,
+            err_no: ErrNo::jump__into_block,
+            edits: vec![],
+        }
+    }
+
+    pub fn unknown_step(step: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Unknown step '{:}'", step),
+            range: location,
+            err_no: ErrNo::sfc__unknown_step,
+            edits: vec![],
+        }
+    }
+
+    pub fn duplicate_step(step: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Duplicate step '{:}'", step),
+            range: location,
+            err_no: ErrNo::sfc__duplicate_step,
+            edits: vec![],
+        }
+    }
+
+    pub fn invalid_initial_steps(pou: &str, count: usize, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "The sequential function chart of '{:}' needs exactly one INITIAL_STEP, found {:}",
+                pou, count
+            ),
+            range: location,
+            err_no: ErrNo::sfc__invalid_initial_step,
+            edits: vec![],
+        }
+    }
+
+    pub fn unsupported_action_qualifier(qualifier: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Unsupported action qualifier '{:}', expected N, P1 or P0",
+                qualifier
+            ),
+            range: location,
+            err_no: ErrNo::sfc__unsupported_qualifier,
+            edits: vec![],
+        }
+    }
+
+    pub fn sfc_without_instance(pou: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Only programs and function blocks can contain a sequential function chart, '{:}' keeps no state between calls",
+                pou
+            ),
+            range: location,
+            err_no: ErrNo::sfc__invalid_container,
+            edits: vec![],
+        }
+    }
+
+    pub fn unknown_state(state: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Unknown state '{:}'", state),
+            range: location,
+            err_no: ErrNo::state__unknown_state,
+            edits: vec![],
+        }
+    }
+
+    pub fn duplicate_state(state: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Duplicate state '{:}'", state),
+            range: location,
+            err_no: ErrNo::state__duplicate_state,
+            edits: vec![],
+        }
+    }
+
+    pub fn invalid_initial_states(pou: &str, count: usize, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "The state machine of '{:}' needs exactly one INITIAL_STATE, found {:}",
+                pou, count
+            ),
+            range: location,
+            err_no: ErrNo::state__invalid_initial_state,
+            edits: vec![],
+        }
+    }
+
+    pub fn ambiguous_state_transition(location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: "A transition of a state machine leads to exactly one state".into(),
+            range: location,
+            err_no: ErrNo::state__ambiguous_transition,
+            edits: vec![],
+        }
+    }
+
+    pub fn state_machine_without_instance(pou: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Only programs and function blocks can contain a state machine, '{:}' keeps no state between calls",
+                pou
+            ),
+            range: location,
+            err_no: ErrNo::state__invalid_container,
+            edits: vec![],
+        }
+    }
+
+    pub fn missing_il_result(operator: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "'{:}' needs a current result, load one with LD first",
+                operator
+            ),
+            range: location,
+            err_no: ErrNo::il__missing_result,
+            edits: vec![],
+        }
+    }
+
+    pub fn missing_il_operand(operator: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("'{:}' needs an operand on the same line", operator),
+            range: location,
+            err_no: ErrNo::il__missing_operand,
+            edits: vec![],
+        }
+    }
+
+    pub fn invalid_il_instruction(message: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: message.into(),
+            range: location,
+            err_no: ErrNo::il__invalid_instruction,
+            edits: vec![],
+        }
+    }
+
+    pub fn cannot_generate_initializer(variable_name: &str, location: SourceRange) -> Diagnostic {
+        Self::codegen_error(
+            &format!(
+                "Cannot generate literal initializer for '{:}': Value can not be derived",
+                variable_name
+            ),
+            location,
+        )
+    }
+
+    pub fn codegen_error(message: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: message.into(),
+            range: location,
+            err_no: ErrNo::codegen__general,
+            edits: vec![],
+        }
+    }
+
+    pub fn cannot_generate_call_statement<T: DiagnosticInfo>(operator: &T) -> Diagnostic {
+        Diagnostic::codegen_error(
+            &format!(
+                "cannot generate call statement for {:?}",
+                operator.get_description()
+            ),
+            operator.get_location(),
+        )
+    }
+
+    pub fn io_read_error(file: &str, reason: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!("Cannot read file '{:}': {:}'", file, reason),
+            err_no: ErrNo::general__io_err,
+        }
+    }
+
+    pub fn io_write_error(file: &str, reason: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!("Cannot write file '{:}': {:}'", file, reason),
+            err_no: ErrNo::general__io_err,
+        }
+    }
+
+    pub fn param_error(reason: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: reason.to_string(),
+            err_no: ErrNo::general__param_err,
+        }
+    }
+
+    pub fn llvm_error(file: &str, llvm_error: &LLVMString) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!(
+                "{:}: Internal llvm error: {:}",
+                file,
+                llvm_error.to_string()
+            ),
+            err_no: ErrNo::general__io_err,
+        }
+    }
+
+    pub fn cannot_generate_from_empty_literal(
+        type_name: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::codegen_error(
+            format!("Cannot generate {} from empty literal", type_name).as_str(),
+            location,
+        )
+    }
+
+    pub fn cannot_generate_string_literal(type_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::codegen_error(
+            format!("Cannot generate String-Literal for type {}", type_name).as_str(),
+            location,
+        )
+    }
+
+    pub fn invalid_assignment(
+        right_type: &str,
+        left_type: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid assignment: cannot assign '{:}' to '{:}'",
+                right_type, left_type
+            ),
+            range: location,
+            err_no: ErrNo::var__invalid_assignment,
+            edits: vec![],
+        }
+    }
+
+    pub fn cannot_bind_reference(location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: "Only variables declared as REFERENCE TO can be bound with REF=".into(),
+            range: location,
+            err_no: ErrNo::var__invalid_reference_binding,
+            edits: vec![],
+        }
+    }
+
+    pub fn invalid_reference_binding(
+        reference_type: &str,
+        right_type: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid reference binding: a REFERENCE TO {:} can only be bound to a variable of type '{:}', found '{:}'",
+                reference_type, reference_type, right_type
+            ),
+            range: location,
+            err_no: ErrNo::var__invalid_reference_binding,
+            edits: vec![],
+        }
+    }
+
+    pub fn type_mismatch(
+        expected_type: &str,
+        actual_type: &str,
+        conversion: Option<&str>,
+        location: SourceRange,
+    ) -> Diagnostic {
+        let diagnostic = Diagnostic::SyntaxError {
+            message: format!(
+                "Type mismatch: expected '{:}' but found '{:}'{:}",
+                expected_type,
+                actual_type,
+                conversion
+                    .map(|it| format!(", use {:}(...) to convert", it))
+                    .unwrap_or_default()
+            ),
+            range: location.clone(),
+            err_no: ErrNo::type__mismatch,
+            edits: vec![],
+        };
+
+        if let Some(conversion) = conversion {
+            //wrap the expression into the conversion call
+            diagnostic.with_edits(vec![
+                CodeEdit::insert(&format!("{}(", conversion), location.get_start()),
+                CodeEdit::insert(")", location.get_end()),
+            ])
+        } else {
+            diagnostic
+        }
+    }
+
+    pub fn invalid_type_nature(type_name: &str, nature: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid type nature for generic argument. {} is no {}.",
+                type_name, nature
+            ),
+            range: location,
+            err_no: ErrNo::type__invalid_nature,
+            edits: vec![],
+        }
+    }
+
+    pub fn unknown_type_nature(nature: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Unknown type nature {}.", nature),
+            range: location,
+            err_no: ErrNo::type__unknown_nature,
+            edits: vec![],
+        }
+    }
+
+    pub fn size_budget_exceeded(kind: &str, size: u64, budget: u64, breakdown: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!(
+                "The {} of {} bytes exceeds the budget of {} bytes:{}",
+                kind, size, budget, breakdown
+            ),
+            err_no: ErrNo::general__size_budget_exceeded,
+        }
+    }
+
+    pub fn unformattable_source(file: &str, reason: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!("Cannot format '{}': {}", file, reason),
+            err_no: ErrNo::format__unsupported_source,
+        }
+    }
+
+    pub fn unformatted_sources(files: &[String]) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!(
+                "The following files are not formatted: {}",
+                files.join(", ")
+            ),
+            err_no: ErrNo::format__unformatted_source,
+        }
+    }
+
+    pub fn undocumentable_source(file: &str, reason: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!("Cannot document '{}': {}", file, reason),
+            err_no: ErrNo::doc__unsupported_source,
+        }
+    }
+
+    pub fn conflicting_migration_changes(first: &str, second: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!(
+                "Cannot migrate overlapping changes: {} and {}",
+                first, second
+            ),
+            err_no: ErrNo::migration__conflicting_changes,
+        }
+    }
+
+    pub fn invalid_library_package(file: &str, reason: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!("Cannot load library package '{}': {}", file, reason),
+            err_no: ErrNo::package__invalid_package,
+        }
+    }
+
+    pub fn library_target_mismatch(file: &str, library_target: &str, target: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!(
+                "Library package '{}' was compiled for '{}', it cannot be linked for '{}'",
+                file, library_target, target
+            ),
+            err_no: ErrNo::package__target_mismatch,
+        }
+    }
+
+    pub fn invalid_library_lock(file: &str, reason: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!("Cannot load library lock '{}': {}", file, reason),
+            err_no: ErrNo::package__invalid_lock,
+        }
+    }
+
+    pub fn incompatible_library_version(
+        library: &str,
+        version: &str,
+        recorded: &str,
+        lock_file: &str,
+    ) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!(
+                "Library '{}' {} is not compatible with the version {} recorded in '{}'",
+                library, version, recorded, lock_file
+            ),
+            err_no: ErrNo::package__incompatible_version,
+        }
+    }
+
+    /// reports the POUs of a library whose signature changed since it was recorded in the lock
+    /// file, a removed POU has no signature
+    pub fn library_interface_changed(
+        library: &str,
+        lock_file: &str,
+        changes: &[(String, Option<String>)],
+    ) -> Diagnostic {
+        let changes = changes
+            .iter()
+            .map(|(pou, signature)| match signature {
+                Some(signature) => format!("'{}' is now '{}'", pou, signature),
+                None => format!("'{}' was removed", pou),
+            })
+            .collect::<Vec<_>>();
+        Diagnostic::GeneralError {
+            message: format!(
+                "The interface of library '{}' changed since it was recorded in '{}': {}",
+                library,
+                lock_file,
+                changes.join(", ")
+            ),
+            err_no: ErrNo::package__interface_changed,
+        }
+    }
+
+    pub fn invalid_builtin_declaration(name: &str, reason: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!("Cannot register the builtin '{}': {}", name, reason),
+            err_no: ErrNo::builtin__invalid_declaration,
+        }
+    }
+
+    pub fn duplicate_builtin(name: &str, provider: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!(
+                "Cannot register the builtin '{}', it is already provided by '{}'",
+                name, provider
+            ),
+            err_no: ErrNo::builtin__duplicate_name,
+        }
+    }
+
+    pub fn profile_violation(profile: &str, violations: usize) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!(
+                "The sources violate the {} profile in {} place(s)",
+                profile, violations
+            ),
+            err_no: ErrNo::general__profile_violation,
+        }
+    }
+
+    pub fn safety_pointer_variable(name: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "The safety profile does not allow the pointer variable '{}'",
+                name
+            ),
+            range,
+            err_no: ErrNo::profile__safety_violation,
+            edits: vec![],
+        }
+    }
+
+    pub fn safety_address_of(function: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "The safety profile does not allow taking addresses with '{}'",
+                function
+            ),
+            range,
+            err_no: ErrNo::profile__safety_violation,
+            edits: vec![],
+        }
+    }
+
+    pub fn safety_dynamic_memory(function: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "The safety profile does not allow dynamic memory, '{}' allocates or releases memory",
+                function
+            ),
+            range,
+            err_no: ErrNo::profile__safety_violation,
+            edits: vec![],
+        }
+    }
+
+    pub fn safety_recursion(cycle: &[&str], range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "The safety profile does not allow recursion: {}",
+                cycle.join(" -> ")
+            ),
+            range,
+            err_no: ErrNo::profile__safety_violation,
+            edits: vec![],
+        }
+    }
+
+    pub fn safety_variadic(name: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "The safety profile does not allow the variadic POU '{}'",
+                name
+            ),
+            range,
+            err_no: ErrNo::profile__safety_violation,
+            edits: vec![],
+        }
+    }
+
+    pub fn safety_unbounded_loop(kind: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "The safety profile does not allow {} loops, their iterations are not bounded",
+                kind
+            ),
+            range,
+            err_no: ErrNo::profile__safety_violation,
+            edits: vec![],
+        }
+    }
+
+    pub fn link_error(error: &str) -> Diagnostic {
+        Diagnostic::GeneralError {
+            err_no: ErrNo::linker__generic_error,
+            message: error.to_string(),
+        }
+    }
+
+    /// attaches the given code-edits to this diagnostic, only syntax errors carry code-edits
+    pub fn with_edits(mut self, mut edits: Vec<CodeEdit>) -> Diagnostic {
+        if let Diagnostic::SyntaxError {
+            edits: existing, ..
+        } = &mut self
+        {
+            existing.append(&mut edits);
+        }
+        self
+    }
+
+    pub fn get_message(&self) -> &str {
+        match self {
+            Diagnostic::SyntaxError { message, .. }
+            | Diagnostic::ImprovementSuggestion { message, .. }
+            | Diagnostic::GeneralError { message, .. } => message.as_str(),
+        }
+    }
+
+    pub fn get_location(&self) -> SourceRange {
+        match self {
+            Diagnostic::SyntaxError { range, .. }
+            | Diagnostic::ImprovementSuggestion { range, .. } => range.clone(),
+            Diagnostic::GeneralError { .. } => SourceRange::undefined(),
+        }
+    }
+
+    pub fn get_type(&self) -> &ErrNo {
+        match self {
+            Diagnostic::SyntaxError { err_no, .. } | Diagnostic::GeneralError { err_no, .. } => {
+                err_no
+            }
+            Diagnostic::ImprovementSuggestion { .. } => &ErrNo::undefined,
+        }
+    }
+
+    /// returns the code-edits that fix this diagnostic (if any)
+    pub fn get_edits(&self) -> &[CodeEdit] {
+        match self {
+            Diagnostic::SyntaxError { edits, .. } => edits.as_slice(),
+            _ => &[],
+        }
+    }
+
+    /**
+     * relocates the given diagnostic to the given location if possible and returns it back
+     */
+    pub fn relocate(it: Diagnostic, new_location: SourceRange) -> Diagnostic {
+        match it {
+            Diagnostic::SyntaxError {
+                message,
+                err_no,
+                edits,
+                ..
+            } => Diagnostic::SyntaxError {
+                message,
+                range: new_location,
+                err_no,
+                edits,
+            },
+            Diagnostic::ImprovementSuggestion { message, .. } => {
+                Diagnostic::ImprovementSuggestion {
+                    message,
+                    range: new_location,
+                }
+            }
+            _ => it,
+        }
+    }
+
+    pub fn implicit_bool_conversion(type_name: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Condition of type '{}' is implicitly converted to BOOL, compare it explicitly (e.g. '<> 0')",
+                type_name
+            ),
+            range,
+            err_no: ErrNo::type__implicit_bool_conversion,
+            edits: vec![],
+        }
+    }
+
+    pub fn bool_arithmetic(operator: &Operator, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Arithmetic operation '{}' on a BOOL operand", operator),
+            range,
+            err_no: ErrNo::type__bool_arithmetic,
+            edits: vec![],
+        }
+    }
+
+    pub fn signed_bit_operation(
+        operator: &Operator,
+        type_name: &str,
+        range: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Bit operation '{}' on an operand of signed type '{}'",
+                operator, type_name
+            ),
+            range,
+            err_no: ErrNo::type__signed_bit_operation,
+            edits: vec![],
+        }
+    }
+
+    pub fn invalid_case_label(selector_type: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid case label for a selector of type '{:}', expected a string literal or constant",
+                selector_type
+            ),
+            range: location,
+            err_no: ErrNo::type__invalid_case_label,
+            edits: vec![],
+        }
+    }
+
+    /// creates a diagnostic for a comparison chain like `a < b < c` which is evaluated as
+    /// `(a < b) < c`, offering the given conjunction (e.g. `a < b AND b < c`) as a quick-fix
+    pub fn comparison_chain(suggestion: Option<&str>, range: SourceRange) -> Diagnostic {
+        let diagnostic = Diagnostic::SyntaxError {
+            message: format!(
+                "Chained comparison compares the BOOL result of the first comparison{:}",
+                suggestion
+                    .map(|it| format!(", did you mean '{:}'?", it))
+                    .unwrap_or_else(|| ", combine the comparisons using AND".to_string())
+            ),
+            range: range.clone(),
+            err_no: ErrNo::type__comparison_chain,
+            edits: vec![],
+        };
+
+        if let Some(suggestion) = suggestion {
+            diagnostic.with_edits(vec![CodeEdit::replace(range, suggestion)])
+        } else {
+            diagnostic
+        }
+    }
+
+    pub fn real_equality(operator: &Operator, range: SourceRange) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
+            message: format!(
+                "Comparing REAL values using '{}' may fail due to rounding errors, consider using ALMOST_EQUAL(a, b, epsilon)",
+                operator
+            ),
+            range,
+        }
+    }
+
+    pub fn write_only_variable(qualified_name: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
+            message: format!("Variable '{}' is written but never read", qualified_name),
+            range,
+        }
+    }
+
+    pub fn not_inlinable(function: &str, reason: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
+            message: format!(
+                "The calls of '{}' are not inlined because {}",
+                function, reason
+            ),
+            range,
         }
     }
 
-    pub fn incompatible_array_access_variable(
-        access_type: &str,
-        location: SourceRange,
-    ) -> Diagnostic {
-        Diagnostic::SyntaxError {
+    pub fn unreachable_output_assignment(qualified_name: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
             message: format!(
-                "Invalid type {} for array access. Only variables of Array types are allowed",
-                access_type
+                "Unreachable assignment to output '{}', the POU returns before and the output keeps its previous value",
+                qualified_name
             ),
-            range: location,
-            err_no: ErrNo::type__incompatible_arrayaccess_variable,
+            range,
         }
     }
 
-    pub fn incompatible_array_access_type(access_type: &str, location: SourceRange) -> Diagnostic {
-        Diagnostic::SyntaxError {
+    pub fn string_truncation(length: i64, target_length: i64, range: SourceRange) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
             message: format!(
-                "Invalid type {} for array access. Only variables of Integer types are allowed to access an array",
-                access_type
+                "The concatenated string can be up to {} characters long and may be truncated to the target's {} characters",
+                length, target_length
             ),
-            range: location,
-            err_no: ErrNo::type__incompatible_arrayaccess_variable,
+            range,
         }
     }
 
-    pub fn incompatible_literal_cast(
-        cast_type: &str,
-        literal_type: &str,
-        location: SourceRange,
-    ) -> Diagnostic {
+    pub fn memory_size_exceeded(size: i128, variable_size: u64, range: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
             message: format!(
-                "Literal {:} is not campatible to {:}",
-                literal_type, cast_type
+                "Cannot access {:} bytes of a variable with a size of {:} bytes",
+                size, variable_size
             ),
-            range: location,
-            err_no: ErrNo::type__incompatible_literal_cast,
+            range,
+            err_no: ErrNo::type__memory_size_exceeded,
+            edits: vec![],
         }
     }
 
-    pub fn literal_expected(location: SourceRange) -> Diagnostic {
+    pub fn circular_type_dependency(cycle: &[&str], range: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
-            message: "Expected literal".into(),
-            range: location,
-            err_no: ErrNo::type__expected_literal,
+            message: format!("Circular dependency between types: {:}", cycle.join(" -> ")),
+            range,
+            err_no: ErrNo::type__circular_dependency,
+            edits: vec![],
         }
     }
 
-    pub fn literal_out_of_range(
-        literal: &str,
-        range_hint: &str,
-        location: SourceRange,
-    ) -> Diagnostic {
+    pub fn invalid_vla_declaration(range: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
-            message: format!("Literal {:} out of range ({})", literal, range_hint),
-            range: location,
-            err_no: ErrNo::type__literal_out_of_range,
+            message: "Variable-length arrays can only be declared as VAR_INPUT or VAR_IN_OUT of a function".to_string(),
+            range,
+            err_no: ErrNo::var__invalid_vla_declaration,
+            edits: vec![],
         }
     }
 
-    pub fn empty_variable_block(location: SourceRange) -> Diagnostic {
+    pub fn invalid_config_constant(name: &str, reason: &str, range: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
-            message: "Variable block is empty".into(),
-            range: location,
-            err_no: ErrNo::pou__empty_variable_block,
+            message: format!("Invalid configuration constant '{}': {}", name, reason),
+            range,
+            err_no: ErrNo::var__invalid_config_constant,
+            edits: vec![],
         }
     }
 
-    pub fn unresolved_constant(
-        constant_name: &str,
-        reason: Option<&str>,
-        location: SourceRange,
-    ) -> Diagnostic {
+    pub fn invalid_hardware_address(name: &str, range: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
             message: format!(
-                "Unresolved constant '{:}' variable{:}",
-                constant_name,
-                reason
-                    .map(|it| format!(": {:}", it))
-                    .unwrap_or_else(|| "".into()),
+                "Invalid address of '{}', expected a byte offset or a bit address (<byte>.<bit>)",
+                name
             ),
-            range: location,
-            err_no: ErrNo::pou__empty_variable_block,
+            range,
+            err_no: ErrNo::var__invalid_hardware_address,
+            edits: vec![],
         }
     }
 
-    pub fn invalid_constant_block(location: SourceRange) -> Diagnostic {
+    pub fn hardware_address_size_mismatch(
+        name: &str,
+        type_name: &str,
+        width: u64,
+        range: SourceRange,
+    ) -> Diagnostic {
         Diagnostic::SyntaxError {
-            message: "This variable block does not support the CONSTANT modifier".to_string(),
-            range: location,
-            err_no: ErrNo::var__invalid_constant_block,
+            message: format!(
+                "'{}' of type {} does not fit its {}-bit address",
+                name, type_name, width
+            ),
+            range,
+            err_no: ErrNo::var__invalid_hardware_address,
+            edits: vec![],
         }
     }
 
-    pub fn invalid_constant(constant_name: &str, location: SourceRange) -> Diagnostic {
+    pub fn located_variable_initializer(name: &str, range: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
-            message: format!("Invalid constant {:} - Functionblock- and Class-instances cannot be delcared constant", constant_name),
-            range: location,
-            err_no: ErrNo::var__invalid_constant,
+            message: format!(
+                "'{}' is located in the process image and cannot have an initial value",
+                name
+            ),
+            range,
+            err_no: ErrNo::var__invalid_hardware_address,
+            edits: vec![],
         }
     }
 
-    pub fn cannot_assign_to_constant(qualified_name: &str, location: SourceRange) -> Diagnostic {
+    pub fn overlapping_hardware_address(name: &str, other: &str, range: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
-            message: format!("Cannot assign to CONSTANT '{:}'", qualified_name),
-            range: location,
-            err_no: ErrNo::var__cannot_assign_to_const,
-        }
-    }
-
-    pub fn cannot_generate_initializer(variable_name: &str, location: SourceRange) -> Diagnostic {
-        Self::codegen_error(
-            &format!(
-                "Cannot generate literal initializer for '{:}': Value can not be derived",
-                variable_name
+            message: format!(
+                "The address of '{}' overlaps the address of '{}'",
+                name, other
             ),
-            location,
-        )
-    }
-
-    pub fn codegen_error(message: &str, location: SourceRange) -> Diagnostic {
-        Diagnostic::SyntaxError {
-            message: message.into(),
-            range: location,
-            err_no: ErrNo::codegen__general,
+            range,
+            err_no: ErrNo::var__overlapping_hardware_address,
+            edits: vec![],
         }
     }
 
-    pub fn cannot_generate_call_statement<T: DiagnosticInfo>(operator: &T) -> Diagnostic {
-        Diagnostic::codegen_error(
-            &format!(
-                "cannot generate call statement for {:?}",
-                operator.get_description()
+    pub fn unmapped_template(name: &str, instance: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "The template address of '{}' is not configured for '{}', map it in a VAR_CONFIG block",
+                name, instance
             ),
-            operator.get_location(),
-        )
-    }
-
-    pub fn io_read_error(file: &str, reason: &str) -> Diagnostic {
-        Diagnostic::GeneralError {
-            message: format!("Cannot read file '{:}': {:}'", file, reason),
-            err_no: ErrNo::general__io_err,
-        }
-    }
-
-    pub fn io_write_error(file: &str, reason: &str) -> Diagnostic {
-        Diagnostic::GeneralError {
-            message: format!("Cannot write file '{:}': {:}'", file, reason),
-            err_no: ErrNo::general__io_err,
-        }
-    }
-
-    pub fn param_error(reason: &str) -> Diagnostic {
-        Diagnostic::GeneralError {
-            message: reason.to_string(),
-            err_no: ErrNo::general__param_err,
+            range,
+            err_no: ErrNo::var__unmapped_template,
+            edits: vec![],
         }
     }
 
-    pub fn llvm_error(file: &str, llvm_error: &LLVMString) -> Diagnostic {
-        Diagnostic::GeneralError {
+    pub fn unknown_config_variable(reference: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
             message: format!(
-                "{:}: Internal llvm error: {:}",
-                file,
-                llvm_error.to_string()
+                "Cannot configure '{}', no such instance variable",
+                reference
             ),
-            err_no: ErrNo::general__io_err,
+            range,
+            err_no: ErrNo::var__invalid_config_variable,
+            edits: vec![],
         }
     }
 
-    pub fn cannot_generate_from_empty_literal(
-        type_name: &str,
-        location: SourceRange,
-    ) -> Diagnostic {
-        Diagnostic::codegen_error(
-            format!("Cannot generate {} from empty literal", type_name).as_str(),
-            location,
-        )
-    }
-
-    pub fn cannot_generate_string_literal(type_name: &str, location: SourceRange) -> Diagnostic {
-        Diagnostic::codegen_error(
-            format!("Cannot generate String-Literal for type {}", type_name).as_str(),
-            location,
-        )
-    }
-
-    pub fn invalid_assignment(
-        right_type: &str,
-        left_type: &str,
-        location: SourceRange,
-    ) -> Diagnostic {
+    pub fn config_variable_without_template(reference: &str, range: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
             message: format!(
-                "Invalid assignment: cannot assign '{:}' to '{:}'",
-                right_type, left_type
+                "Cannot configure '{}', it is not declared with a template address (e.g. AT %I*)",
+                reference
             ),
-            range: location,
-            err_no: ErrNo::var__invalid_assignment,
+            range,
+            err_no: ErrNo::var__invalid_config_variable,
+            edits: vec![],
         }
     }
 
-    pub fn invalid_type_nature(type_name: &str, nature: &str, location: SourceRange) -> Diagnostic {
+    pub fn config_variable_mismatch(
+        reference: &str,
+        expected: &str,
+        found: &str,
+        range: SourceRange,
+    ) -> Diagnostic {
         Diagnostic::SyntaxError {
             message: format!(
-                "Invalid type nature for generic argument. {} is no {}.",
-                type_name, nature
+                "Invalid configuration of '{}': expected {}, found {}",
+                reference, expected, found
             ),
-            range: location,
-            err_no: ErrNo::type__invalid_nature,
+            range,
+            err_no: ErrNo::var__invalid_config_variable,
+            edits: vec![],
         }
     }
 
-    pub fn unknown_type_nature(nature: &str, location: SourceRange) -> Diagnostic {
+    pub fn invalid_vla_argument(
+        expected_type: &str,
+        argument_type: &str,
+        range: SourceRange,
+    ) -> Diagnostic {
         Diagnostic::SyntaxError {
-            message: format!("Unknown type nature {}.", nature),
-            range: location,
-            err_no: ErrNo::type__unknown_nature,
+            message: format!(
+                "Invalid argument: expected '{:}', found '{:}'",
+                expected_type, argument_type
+            ),
+            range,
+            err_no: ErrNo::type__invalid_vla_argument,
+            edits: vec![],
         }
     }
 
-    pub fn link_error(error: &str) -> Diagnostic {
-        Diagnostic::GeneralError {
-            err_no: ErrNo::linker__generic_error,
-            message: error.to_string(),
+    pub fn jump_statement(range: SourceRange) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
+            message: "JMP makes the control flow hard to follow, consider replacing it with IF, loops, EXIT or RETURN".to_string(),
+            range,
         }
     }
 
-    pub fn get_message(&self) -> &str {
-        match self {
-            Diagnostic::SyntaxError { message, .. }
-            | Diagnostic::ImprovementSuggestion { message, .. }
-            | Diagnostic::GeneralError { message, .. } => message.as_str(),
+    pub fn unsynchronized_global_access(
+        name: &str,
+        tasks: &[&str],
+        range: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
+            message: format!(
+                "Possible data race: '{}' is accessed by tasks '{}' and written by at least one of them, consider declaring it in a VAR_GLOBAL {{atomic}} block",
+                name,
+                tasks.join("', '")
+            ),
+            range,
         }
     }
 
-    pub fn get_location(&self) -> SourceRange {
-        match self {
-            Diagnostic::SyntaxError { range, .. }
-            | Diagnostic::ImprovementSuggestion { range, .. } => range.clone(),
-            Diagnostic::GeneralError { .. } => SourceRange::undefined(),
+    pub fn multiple_writers(name: &str, programs: &[&str], range: SourceRange) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
+            message: format!(
+                "'{}' is written by programs '{}', a global should only be written by a single \
+                program, consider declaring it in a VAR_GLOBAL {{shared}} block",
+                name,
+                programs.join("', '")
+            ),
+            range,
         }
     }
 
-    pub fn get_type(&self) -> &ErrNo {
-        match self {
-            Diagnostic::SyntaxError { err_no, .. } | Diagnostic::GeneralError { err_no, .. } => {
-                err_no
-            }
-            Diagnostic::ImprovementSuggestion { .. } => &ErrNo::undefined,
+    pub fn invalid_init_priority(value: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid init_priority '{}', expected a non-negative integer",
+                value
+            ),
+            range: location,
+            err_no: ErrNo::var__invalid_init_priority,
+            edits: vec![],
         }
     }
 
-    /**
-     * relocates the given diagnostic to the given location if possible and returns it back
-     */
-    pub fn relocate(it: Diagnostic, new_location: SourceRange) -> Diagnostic {
-        match it {
-            Diagnostic::SyntaxError {
-                message, err_no, ..
-            } => Diagnostic::SyntaxError {
-                message,
-                range: new_location,
-                err_no,
-            },
-            Diagnostic::ImprovementSuggestion { message, .. } => {
-                Diagnostic::ImprovementSuggestion {
-                    message,
-                    range: new_location,
-                }
-            }
-            _ => it,
+    /// the variable is initialized before the given dependency according to the priorities,
+    /// `None` is a variable without priority which is initialized after the prioritized ones
+    pub fn init_priority_conflict(
+        name: &str,
+        priority: u32,
+        dependency: &str,
+        dependency_priority: Option<u32>,
+        range: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "'{}' (init_priority {}) depends on '{}' which is initialized after it ({})",
+                name,
+                priority,
+                dependency,
+                dependency_priority
+                    .map(|it| format!("init_priority {}", it))
+                    .unwrap_or_else(|| "no init_priority".to_string())
+            ),
+            range,
+            err_no: ErrNo::var__invalid_init_priority,
+            edits: vec![],
         }
     }
 
@@ -607,7 +1885,7 @@ pub struct AssessedDiagnostic {
 
 impl DiagnosticAssessor for DefaultDiagnosticAssessor {
     fn assess(&self, d: Diagnostic) -> AssessedDiagnostic {
-        let severity = match d {
+        let severity = match &d {
             // improvements become warnings
             Diagnostic::ImprovementSuggestion { .. } => Severity::Warning,
             // everything else becomes an error
@@ -621,6 +1899,33 @@ impl DiagnosticAssessor for DefaultDiagnosticAssessor {
     }
 }
 
+/// an assessor for legacy code-bases that reports violations of IEC 61131-3's typing rules
+/// (e.g. integer conditions, arithmetic on BOOLs) as warnings and otherwise behaves like
+/// the default assessor
+#[derive(Default)]
+pub struct LegacyDiagnosticAssessor {
+    default: DefaultDiagnosticAssessor,
+}
+
+impl DiagnosticAssessor for LegacyDiagnosticAssessor {
+    fn assess(&self, d: Diagnostic) -> AssessedDiagnostic {
+        if matches!(
+            d.get_type(),
+            ErrNo::type__implicit_bool_conversion
+                | ErrNo::type__bool_arithmetic
+                | ErrNo::type__signed_bit_operation
+                | ErrNo::type__comparison_chain
+        ) {
+            AssessedDiagnostic {
+                diagnostic: d,
+                severity: Severity::Warning,
+            }
+        } else {
+            self.default.assess(d)
+        }
+    }
+}
+
 /// the DiagnosticReporter decides on the format and where to report the diagnostic to.
 /// possible implementations could print to either std-out, std-err or a file, etc.
 pub trait DiagnosticReporter {
@@ -684,7 +1989,13 @@ impl DiagnosticReporter for CodeSpanDiagnosticReporter {
                 .with_labels(vec![Label::primary(
                     file_id,
                     location.get_start()..location.get_end(),
-                )]);
+                )])
+                .with_notes(
+                    d.get_edits()
+                        .iter()
+                        .map(|it| format!("help: {}", it))
+                        .collect(),
+                );
             let result = codespan_reporting::term::emit(
                 &mut self.writer.lock(),
                 &self.config,
@@ -884,7 +2195,34 @@ impl Default for Diagnostician {
 mod diagnostics_tests {
     use codespan_reporting::files::{Location, SimpleFile};
 
-    use super::ClangFormatDiagnosticReporter;
+    use super::{
+        ClangFormatDiagnosticReporter, DefaultDiagnosticAssessor, Diagnostic, DiagnosticAssessor,
+        LegacyDiagnosticAssessor, Severity,
+    };
+    use crate::ast::{Operator, SourceRange};
+
+    #[test]
+    fn legacy_assessor_reports_iec_typing_violations_as_warnings() {
+        let violation = || Diagnostic::bool_arithmetic(&Operator::Plus, SourceRange::undefined());
+
+        let strict = DefaultDiagnosticAssessor::default().assess(violation());
+        assert!(matches!(strict.severity, Severity::Error));
+
+        let legacy = LegacyDiagnosticAssessor::default();
+        assert!(matches!(
+            legacy.assess(violation()).severity,
+            Severity::Warning
+        ));
+        assert!(matches!(
+            legacy
+                .assess(Diagnostic::unresolved_reference(
+                    "x",
+                    SourceRange::undefined()
+                ))
+                .severity,
+            Severity::Error
+        ));
+    }
 
     #[test]
     fn test_build_diagnostic_msg() {
@@ -0,0 +1,102 @@
+use std::fmt::{Display, Formatter};
+
+use clap::ArgEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{builtins, Conformance};
+
+/// the ST dialect a project is written in. The dialect pins the language surface accepted by the
+/// compiler: parser extensions, builtin functions and the treatment of implicit conversions
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ArgEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    /// IEC 61131-3 only, vendor extensions are reported as errors
+    Iec,
+    /// IEC 61131-3 plus the extensions offered by CODESYS (e.g. `POINTER TO`, `ADR`, `JMP`)
+    Codesys,
+    /// IEC 61131-3 plus the syntax of Siemens SCL (e.g. `#local` variables, `REGION`)
+    Scl,
+    /// accepts every supported extension (except SCL's syntax, whose markers would shadow
+    /// identifiers like `region`, and jumps, which have to be enabled by selecting the SCL or
+    /// CODESYS dialect) and reports violations tolerated by older code as warnings
+    Legacy,
+}
+
+/// a language construct that is not part of IEC 61131-3
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LanguageExtension {
+    /// `POINTER TO` instead of `REF_TO`
+    PointerTo,
+    /// keywords without the separating '_' (e.g. `ENDIF`)
+    UnseparatedKeywords,
+    /// builtin functions and function blocks of the runtime library that are not defined by the
+    /// standard (e.g. `ADR` or `FILE_OPEN`)
+    VendorBuiltIn,
+    /// SCL's `#` prefix of local variables (e.g. `#counter := 0;`)
+    LocalPrefix,
+    /// SCL's `REGION name` ... `END_REGION` markers and the `VERSION : x.y` block header
+    SclMarkers,
+    /// `JMP label` statements and `label:` jump targets
+    Jumps,
+    /// `IF c THEN a ELSE b END_IF` used as an expression, a shorthand for `SEL(c, b, a)`
+    ConditionalExpression,
+    /// `STATE` and `TRANSITION` blocks declaring a function block's body as a state machine
+    StateMachines,
+}
+
+impl Dialect {
+    /// returns true if the given extension is part of this dialect
+    pub fn allows(&self, extension: LanguageExtension) -> bool {
+        match self {
+            Dialect::Iec => false,
+            Dialect::Codesys => matches!(
+                extension,
+                LanguageExtension::PointerTo
+                    | LanguageExtension::VendorBuiltIn
+                    | LanguageExtension::Jumps
+            ),
+            Dialect::Scl => matches!(
+                extension,
+                LanguageExtension::LocalPrefix | LanguageExtension::SclMarkers
+            ),
+            Dialect::Legacy => !matches!(
+                extension,
+                LanguageExtension::LocalPrefix
+                    | LanguageExtension::SclMarkers
+                    | LanguageExtension::Jumps
+            ),
+        }
+    }
+
+    /// returns true if the builtin function with the given name can be called in this dialect
+    pub fn is_builtin_available(&self, name: &str) -> bool {
+        self.allows(LanguageExtension::VendorBuiltIn) || !builtins::is_vendor_extension(name)
+    }
+
+    /// returns the conformance level used to assess implicit conversions in this dialect
+    pub fn conformance(&self) -> Conformance {
+        match self {
+            Dialect::Iec | Dialect::Codesys | Dialect::Scl => Conformance::Strict,
+            Dialect::Legacy => Conformance::Legacy,
+        }
+    }
+}
+
+impl Default for Dialect {
+    /// without a selected dialect every supported extension is accepted
+    fn default() -> Self {
+        Dialect::Legacy
+    }
+}
+
+impl Display for Dialect {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let name = match self {
+            Dialect::Iec => "IEC 61131-3",
+            Dialect::Codesys => "CODESYS",
+            Dialect::Scl => "SCL",
+            Dialect::Legacy => "legacy",
+        };
+        write!(f, "{}", name)
+    }
+}
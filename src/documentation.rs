@@ -0,0 +1,598 @@
+//! generates the API documentation of Structured Text sources (`rustyc doc`)
+//!
+//! a documentation comment is a block comment starting with `(**` or a sequence of line comments
+//! starting with `///`. It documents the POU, data type or variable declared after it, only
+//! whitespace and pragmas may separate the two. A source is parsed and indexed on its own, the
+//! documentation of its POUs (with their `VAR_INPUT`, `VAR_OUTPUT` and `VAR_IN_OUT` interface and
+//! return type) and data types (with their members or elements) is then extracted from the index.
+//! The text of a documentation comment is copied verbatim into Markdown and escaped in HTML.
+use std::ops::Range;
+
+use clap::ArgEnum;
+
+use crate::{
+    ast::{self, Comment, CompilationUnit, LinkageType, PouType, UserTypeDeclaration},
+    diagnostics::{DefaultDiagnosticAssessor, DiagnosticAssessor, Severity},
+    dialect::Dialect,
+    index::{self, Index, VariableType},
+    lexer::{self, IdProvider},
+    parser,
+    typesystem::DataTypeInformation,
+};
+
+/// the format the documentation is written in
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ArgEnum)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+impl DocFormat {
+    /// the extension of the file the documentation is written to
+    pub fn get_extension(&self) -> &'static str {
+        match self {
+            DocFormat::Markdown => "md",
+            DocFormat::Html => "html",
+        }
+    }
+}
+
+/// the documentation of a project's POUs and data types in the order of their declaration
+#[derive(Debug, Default, PartialEq)]
+pub struct Documentation {
+    pub pous: Vec<ItemDocumentation>,
+    pub types: Vec<ItemDocumentation>,
+}
+
+/// a documented POU or data type
+#[derive(Debug, PartialEq)]
+pub struct ItemDocumentation {
+    pub name: String,
+    /// the keyword declaring the item (e.g. `FUNCTION_BLOCK` or `STRUCT`)
+    pub kind: String,
+    /// the declared type of a data type that is neither a struct nor an enum
+    /// (e.g. `INT(0..100)`) or the return type of a function
+    pub data_type: Option<String>,
+    pub docs: String,
+    /// the interface variables of a POU, the members of a struct or the elements of an enum
+    pub members: Vec<MemberDocumentation>,
+}
+
+/// a documented interface variable, struct member or enum element
+#[derive(Debug, PartialEq)]
+pub struct MemberDocumentation {
+    pub name: String,
+    /// the block declaring an interface variable (e.g. `VAR_INPUT`)
+    pub section: Option<&'static str>,
+    /// the type of a variable or member, enum elements have none
+    pub data_type: Option<String>,
+    pub docs: String,
+}
+
+/// returns the documentation of the given source or the reason it cannot be documented
+pub fn document(source: &str, dialect: Dialect) -> Result<Documentation, String> {
+    let id_provider = IdProvider::default();
+    let (mut unit, diagnostics) = parser::parse(
+        lexer::lex_with_dialect(source, id_provider.clone(), dialect),
+        LinkageType::Internal,
+    );
+    let assessor = DefaultDiagnosticAssessor::default();
+    if let Some(error) = diagnostics
+        .into_iter()
+        .find(|it| matches!(assessor.assess(it.clone()).severity, Severity::Error))
+    {
+        return Err(format!(
+            "the source contains syntax errors ({})",
+            error.get_message()
+        ));
+    }
+    //the inline types of the variables are named and indexed by the pre-processor
+    ast::pre_process(&mut unit, id_provider.clone());
+    let index = index::visitor::visit(&unit, id_provider);
+    Ok(extract(&unit, &index, source))
+}
+
+/// extracts the documentation of the given unit's POUs and data types, the interfaces, members and
+/// types are taken from the unit's index
+pub fn extract(unit: &CompilationUnit, index: &Index, source: &str) -> Documentation {
+    let extractor = Extractor {
+        unit,
+        index,
+        source,
+    };
+    let pous = unit
+        .units
+        .iter()
+        .filter(|it| !it.name.starts_with("__"))
+        .map(|pou| ItemDocumentation {
+            name: pou.name.clone(),
+            kind: get_pou_keyword(&pou.pou_type).into(),
+            data_type: index
+                .find_return_variable(&pou.name)
+                .map(|it| extractor.describe_type(it.get_type_name())),
+            docs: extractor.find_docs(pou.location.get_start()),
+            members: extractor.document_interface(&pou.name),
+        })
+        .collect();
+    let types = unit
+        .types
+        .iter()
+        .filter_map(|it| it.data_type.get_name().map(|name| (name, it)))
+        .filter(|(name, _)| !name.starts_with("__"))
+        .map(|(name, declaration)| extractor.document_type(name, declaration))
+        .collect();
+    Documentation { pous, types }
+}
+
+/// returns the keyword declaring a POU of the given type
+fn get_pou_keyword(pou_type: &PouType) -> &'static str {
+    match pou_type {
+        PouType::Program => "PROGRAM",
+        PouType::Function => "FUNCTION",
+        PouType::FunctionBlock => "FUNCTION_BLOCK",
+        PouType::Action => "ACTION",
+        PouType::Class => "CLASS",
+        PouType::Method { .. } => "METHOD",
+        PouType::Interface => "INTERFACE",
+    }
+}
+
+/// returns the block declaring interface variables of the given type
+fn get_section(variable_type: VariableType) -> Option<&'static str> {
+    match variable_type {
+        VariableType::Input => Some("VAR_INPUT"),
+        VariableType::Output => Some("VAR_OUTPUT"),
+        VariableType::InOut => Some("VAR_IN_OUT"),
+        _ => None,
+    }
+}
+
+struct Extractor<'a> {
+    unit: &'a CompilationUnit,
+    index: &'a Index,
+    source: &'a str,
+}
+
+impl<'a> Extractor<'a> {
+    /// documents the interface variables of the given POU in the order of their declaration
+    fn document_interface(&self, pou_name: &str) -> Vec<MemberDocumentation> {
+        self.index
+            .get_container_members(pou_name)
+            .into_iter()
+            .filter(|it| !it.get_name().starts_with("__"))
+            .filter_map(|variable| {
+                let section = get_section(variable.get_variable_type())?;
+                Some(MemberDocumentation {
+                    name: variable.get_name().into(),
+                    section: Some(section),
+                    data_type: Some(self.describe_type(variable.get_type_name())),
+                    docs: self.find_docs(variable.source_location.get_start()),
+                })
+            })
+            .collect()
+    }
+
+    /// documents the given user type, structs list their members and enums their elements
+    fn document_type(&self, name: &str, declaration: &UserTypeDeclaration) -> ItemDocumentation {
+        let (kind, data_type, members) = match self.index.find_effective_type_info(name) {
+            Some(DataTypeInformation::Struct { .. }) => {
+                let members = self
+                    .index
+                    .get_container_members(name)
+                    .into_iter()
+                    .map(|member| MemberDocumentation {
+                        name: member.get_name().into(),
+                        section: None,
+                        data_type: Some(self.describe_type(member.get_type_name())),
+                        docs: self.find_docs(member.source_location.get_start()),
+                    })
+                    .collect();
+                ("STRUCT", None, members)
+            }
+            Some(DataTypeInformation::Enum { elements, .. }) => {
+                let members = elements
+                    .iter()
+                    .map(|element| MemberDocumentation {
+                        name: element.clone(),
+                        section: None,
+                        data_type: None,
+                        docs: self
+                            .index
+                            .find_enum_element(name, element)
+                            .map(|it| self.find_docs(it.source_location.get_start()))
+                            .unwrap_or_default(),
+                    })
+                    .collect();
+                ("ENUM", None, members)
+            }
+            _ => {
+                //the declaration's location starts at the type's name
+                let definition = self
+                    .get_text(declaration.location.to_range())
+                    .as_deref()
+                    .and_then(|it| it.split_once(':'))
+                    .map(|(_, it)| it.trim().trim_end_matches(';').trim().to_string())
+                    .unwrap_or_else(|| name.into());
+                ("TYPE", Some(definition), vec![])
+            }
+        };
+        ItemDocumentation {
+            name: name.into(),
+            kind: kind.into(),
+            data_type,
+            docs: self.find_docs(declaration.location.get_start()),
+            members,
+        }
+    }
+
+    /// returns the given type's name or the declaration of the inline type the pre-processor
+    /// named (e.g. `ARRAY[0..9] OF INT` for `__main_values`)
+    fn describe_type(&self, type_name: &str) -> String {
+        if !type_name.starts_with("__") {
+            return type_name.into();
+        }
+        self.unit
+            .types
+            .iter()
+            .find(|it| it.data_type.get_name() == Some(type_name))
+            .and_then(|it| self.get_text(it.location.to_range()))
+            .unwrap_or_else(|| type_name.into())
+    }
+
+    /// returns the source in the given range with its whitespace collapsed
+    fn get_text(&self, range: Range<usize>) -> Option<String> {
+        let text = self.source.get(range)?;
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    }
+
+    /// returns the text of the documentation comments preceding the given offset of the source
+    fn find_docs(&self, start: usize) -> String {
+        let mut docs = vec![];
+        let mut end = start;
+        for comment in self.unit.comments.iter().rev() {
+            let range = comment.location.to_range();
+            //unknown pragmas are recorded as comments, they may separate the documentation
+            if range.end > end || comment.text.starts_with('{') {
+                continue;
+            }
+            let separated = self
+                .source
+                .get(range.end..end)
+                .map_or(true, |it| !is_blank(it));
+            match get_doc_text(comment) {
+                Some(text) if !separated => docs.push(text),
+                _ => break,
+            }
+            end = range.start;
+        }
+        docs.reverse();
+        docs.join("\n").trim().to_string()
+    }
+}
+
+/// returns the text of the given documentation comment without its delimiters or None if it is
+/// a regular comment
+fn get_doc_text(comment: &Comment) -> Option<String> {
+    let text = comment.text.trim_end();
+    if let Some(line) = text.strip_prefix("///") {
+        return Some(line.strip_prefix(' ').unwrap_or(line).to_string());
+    }
+    let text = text
+        .strip_prefix("(**")
+        .filter(|it| !it.starts_with(')'))?
+        .strip_suffix("*)")?;
+    let lines = text
+        .lines()
+        .map(|it| {
+            //a leading `*` continues the comment's frame
+            let line = it.trim_start();
+            line.strip_prefix("* ")
+                .or_else(|| line.strip_prefix('*'))
+                .unwrap_or(line)
+                .trim_end()
+        })
+        .collect::<Vec<_>>();
+    Some(lines.join("\n").trim().to_string())
+}
+
+/// returns true if the given text between a comment and a declaration contains nothing but
+/// whitespace, pragmas and the `TYPE` keyword opening a type declaration
+fn is_blank(text: &str) -> bool {
+    let mut remainder = text.trim_start();
+    loop {
+        if let Some(pragma) = remainder.strip_prefix('{') {
+            match pragma.split_once('}') {
+                Some((_, rest)) => remainder = rest.trim_start(),
+                None => return false,
+            }
+        } else if remainder
+            .get(..4)
+            .map_or(false, |it| it.eq_ignore_ascii_case("TYPE"))
+            && remainder[4..].starts_with(char::is_whitespace)
+        {
+            remainder = remainder[4..].trim_start();
+        } else {
+            return remainder.is_empty();
+        }
+    }
+}
+
+impl Documentation {
+    /// appends the documentation of another source
+    pub fn append(&mut self, other: &mut Documentation) {
+        self.pous.append(&mut other.pous);
+        self.types.append(&mut other.types);
+    }
+
+    /// renders the documentation in the given format
+    pub fn render(&self, format: DocFormat) -> String {
+        match format {
+            DocFormat::Markdown => self.to_markdown(),
+            DocFormat::Html => self.to_html(),
+        }
+    }
+
+    /// renders the documentation as one Markdown page
+    pub fn to_markdown(&self) -> String {
+        let mut page = String::from("# API Documentation\n");
+        for (title, items) in [("POUs", &self.pous), ("Data Types", &self.types)] {
+            if items.is_empty() {
+                continue;
+            }
+            page.push_str(&format!("\n## {}\n", title));
+            for item in items.iter() {
+                page.push_str(&format!("\n### {} `{}`\n\n", item.kind, item.name));
+                if let Some(data_type) = &item.data_type {
+                    page.push_str(&format!("Type: `{}`\n\n", data_type));
+                }
+                if !item.docs.is_empty() {
+                    page.push_str(&format!("{}\n\n", item.docs));
+                }
+                if !item.members.is_empty() {
+                    page.push_str("| Name | Section | Type | Description |\n");
+                    page.push_str("|------|---------|------|-------------|\n");
+                    for member in &item.members {
+                        page.push_str(&format!(
+                            "| `{}` | {} | {} | {} |\n",
+                            member.name,
+                            member.section.unwrap_or_default(),
+                            member
+                                .data_type
+                                .as_ref()
+                                .map(|it| format!("`{}`", it))
+                                .unwrap_or_default(),
+                            member.docs.replace('\n', " ").replace('|', "\\|")
+                        ));
+                    }
+                    page.push('\n');
+                }
+            }
+        }
+        page.trim_end().to_string() + "\n"
+    }
+
+    /// renders the documentation as one HTML page, the items are linked from a table of contents
+    pub fn to_html(&self) -> String {
+        let mut page = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+            <title>API Documentation</title>\n</head>\n<body>\n<h1>API Documentation</h1>\n",
+        );
+        for (title, items) in [("POUs", &self.pous), ("Data Types", &self.types)] {
+            if items.is_empty() {
+                continue;
+            }
+            page.push_str(&format!("<h2>{}</h2>\n<ul>\n", title));
+            for item in items.iter() {
+                page.push_str(&format!(
+                    "<li><a href=\"#{0}\">{0}</a></li>\n",
+                    escape(&item.name)
+                ));
+            }
+            page.push_str("</ul>\n");
+            for item in items.iter() {
+                page.push_str(&format!(
+                    "<h3 id=\"{0}\">{1} <code>{0}</code></h3>\n",
+                    escape(&item.name),
+                    item.kind
+                ));
+                if let Some(data_type) = &item.data_type {
+                    page.push_str(&format!(
+                        "<p>Type: <code>{}</code></p>\n",
+                        escape(data_type)
+                    ));
+                }
+                for paragraph in item.docs.split("\n\n").filter(|it| !it.trim().is_empty()) {
+                    page.push_str(&format!("<p>{}</p>\n", escape(paragraph.trim())));
+                }
+                if !item.members.is_empty() {
+                    page.push_str(
+                        "<table>\n<tr><th>Name</th><th>Section</th><th>Type</th>\
+                        <th>Description</th></tr>\n",
+                    );
+                    for member in &item.members {
+                        page.push_str(&format!(
+                            "<tr><td><code>{}</code></td><td>{}</td><td><code>{}</code></td>\
+                            <td>{}</td></tr>\n",
+                            escape(&member.name),
+                            member.section.unwrap_or_default(),
+                            escape(member.data_type.as_deref().unwrap_or_default()),
+                            escape(&member.docs)
+                        ));
+                    }
+                    page.push_str("</table>\n");
+                }
+            }
+        }
+        page.push_str("</body>\n</html>\n");
+        page
+    }
+}
+
+/// escapes the characters of the given text that are special in HTML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{document, DocFormat, ItemDocumentation, MemberDocumentation};
+    use crate::dialect::Dialect;
+    use pretty_assertions::*;
+
+    #[test]
+    fn pous_are_documented_with_their_interface() {
+        let source = "
+            (** Drives a motor.
+             *
+             * The motor stops if `enable` is FALSE. *)
+            FUNCTION_BLOCK Motor
+            VAR_INPUT
+                /// true to run the motor
+                enable : BOOL;
+                speeds : ARRAY[0..2] OF INT;
+            END_VAR
+            VAR_OUTPUT
+                (** the speed reached *) actual : INT;
+            END_VAR
+            VAR
+                (** not documented, a local variable *) state : INT;
+            END_VAR
+            END_FUNCTION_BLOCK
+
+            (* a regular comment *)
+            FUNCTION scale : REAL
+            VAR_IN_OUT value : REAL; END_VAR
+            END_FUNCTION";
+
+        let documentation = document(source, Dialect::Legacy).unwrap();
+
+        let member = |name: &str, section, data_type: &str, docs: &str| MemberDocumentation {
+            name: name.into(),
+            section: Some(section),
+            data_type: Some(data_type.into()),
+            docs: docs.into(),
+        };
+        assert_eq!(
+            documentation.pous,
+            vec![
+                ItemDocumentation {
+                    name: "Motor".into(),
+                    kind: "FUNCTION_BLOCK".into(),
+                    data_type: None,
+                    docs: "Drives a motor.\n\nThe motor stops if `enable` is FALSE.".into(),
+                    members: vec![
+                        member("enable", "VAR_INPUT", "BOOL", "true to run the motor"),
+                        member("speeds", "VAR_INPUT", "ARRAY[0..2] OF INT", ""),
+                        member("actual", "VAR_OUTPUT", "INT", "the speed reached"),
+                    ],
+                },
+                ItemDocumentation {
+                    name: "scale".into(),
+                    kind: "FUNCTION".into(),
+                    data_type: Some("REAL".into()),
+                    docs: "".into(),
+                    members: vec![member("value", "VAR_IN_OUT", "REAL", "")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn types_are_documented_with_their_members() {
+        let source = "
+            /// a point in the plane
+            /// in millimeters
+            {attribute 'pack'}
+            TYPE Point : STRUCT
+                /// the horizontal position
+                x : DINT;
+                y : DINT;
+            END_STRUCT END_TYPE
+
+            TYPE
+                (** the colors of a traffic light *)
+                Light : (
+                    /// stop
+                    red,
+                    green
+                );
+            END_TYPE
+
+            (** a percentage *)
+            TYPE Percent : INT(0..100); END_TYPE";
+
+        let documentation = document(source, Dialect::Legacy).unwrap();
+
+        let types = &documentation.types;
+        assert_eq!(types.len(), 3);
+        assert_eq!(types[0].kind, "STRUCT");
+        assert_eq!(types[0].docs, "a point in the plane\nin millimeters");
+        assert_eq!(types[0].members[0].docs, "the horizontal position");
+        assert_eq!(types[0].members[1].data_type.as_deref(), Some("DINT"));
+        assert_eq!(types[1].kind, "ENUM");
+        assert_eq!(types[1].docs, "the colors of a traffic light");
+        assert_eq!(types[1].members[0].name, "red");
+        assert_eq!(types[1].members[0].docs, "stop");
+        assert_eq!(types[1].members[1].docs, "");
+        assert_eq!(types[2].kind, "TYPE");
+        assert_eq!(types[2].data_type.as_deref(), Some("INT(0..100)"));
+        assert_eq!(types[2].docs, "a percentage");
+    }
+
+    #[test]
+    fn documentation_is_rendered_as_markdown_and_html() {
+        let source = "
+            (** adds <two> numbers *)
+            FUNCTION add : INT
+            VAR_INPUT
+                /// the first | summand
+                a : INT;
+            END_VAR
+            END_FUNCTION";
+
+        let documentation = document(source, Dialect::Legacy).unwrap();
+
+        assert_eq!(
+            documentation.render(DocFormat::Markdown),
+            "# API Documentation
+
+## POUs
+
+### FUNCTION `add`
+
+Type: `INT`
+
+adds <two> numbers
+
+| Name | Section | Type | Description |
+|------|---------|------|-------------|
+| `a` | VAR_INPUT | `INT` | the first \\| summand |
+"
+        );
+        let html = documentation.render(DocFormat::Html);
+        assert!(html.contains("<li><a href=\"#add\">add</a></li>"));
+        assert!(html.contains("<h3 id=\"add\">FUNCTION <code>add</code></h3>"));
+        assert!(html.contains("<p>adds &lt;two&gt; numbers</p>"));
+        assert!(html.contains(
+            "<tr><td><code>a</code></td><td>VAR_INPUT</td><td><code>INT</code></td>\
+            <td>the first | summand</td></tr>"
+        ));
+    }
+
+    #[test]
+    fn sources_with_syntax_errors_are_not_documented() {
+        let result = document(
+            "FUNCTION foo : INT VAR a : INT END_VAR END_FUNCTION",
+            Dialect::Legacy,
+        );
+        assert!(result.unwrap_err().contains("syntax errors"));
+    }
+}
@@ -0,0 +1,1534 @@
+//! prints Structured Text sources in a canonical layout (`rustyc format`)
+//!
+//! a source is parsed and its AST printed back with the configured indentation and keyword case.
+//! Literals are copied from the source, parentheses are only kept where the precedence of the
+//! operators requires them. A comment is printed before the declaration or statement following it,
+//! or behind the line it ends in the source. The parser lowers some constructs it cannot print
+//! back (instruction lists, sequential function charts, state machines, IF expressions, `VECTOR`
+//! and `MATRIX` types), so a source is only formatted if the result parses into the same AST,
+//! uses the same identifiers and keeps all comments of the original.
+use std::{collections::HashMap, ops::Range};
+
+use clap::ArgEnum;
+use logos::Logos;
+use regex::Regex;
+
+use crate::{
+    ast::*,
+    diagnostics::{DefaultDiagnosticAssessor, DiagnosticAssessor, Severity},
+    dialect::Dialect,
+    lexer::{self, IdProvider, Token},
+    parser,
+};
+
+const NOT_EQUIVALENT: &str =
+    "the formatted source would not be equivalent, it uses constructs the formatter cannot print";
+
+// the precedence of the expressions, an operand of lower precedence is put in parentheses
+const LIST: u8 = 0;
+const ASSIGNMENT: u8 = 1;
+const RANGE: u8 = 2;
+const OR: u8 = 3;
+const XOR: u8 = 4;
+const AND: u8 = 5;
+const EQUALITY: u8 = 6;
+const COMPARISON: u8 = 7;
+const ADDITION: u8 = 8;
+const MULTIPLICATION: u8 = 9;
+const UNARY: u8 = 10;
+const LEAF: u8 = 11;
+
+/// the case the keywords are printed in
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ArgEnum)]
+pub enum KeywordCase {
+    Upper,
+    Lower,
+}
+
+/// the layout of a formatted source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// the number of spaces a nested declaration or statement is indented by
+    pub indent: usize,
+    pub keyword_case: KeywordCase,
+    /// the arguments of a call exceeding the line width are printed on lines of their own
+    pub line_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent: 4,
+            keyword_case: KeywordCase::Upper,
+            line_width: 100,
+        }
+    }
+}
+
+/// returns the given source printed in the canonical layout or the reason it cannot be formatted
+pub fn format(source: &str, options: &FormatOptions, dialect: Dialect) -> Result<String, String> {
+    let unit = parse(source, dialect)?;
+    if !unit.namespaces.is_empty() {
+        return Err(
+            "NAMESPACE blocks and USING directives outside of POUs are not supported".into(),
+        );
+    }
+    let generated_variables = unit
+        .units
+        .iter()
+        .flat_map(|it| &it.variable_blocks)
+        .flat_map(|it| &it.variables)
+        .any(|it| it.name.starts_with("__"));
+    if generated_variables {
+        return Err(
+            "instruction lists, sequential function charts and state machines are not supported"
+                .into(),
+        );
+    }
+
+    let formatted = Printer::new(source, &unit, options).print(&unit);
+    let reparsed = parse(&formatted, dialect).map_err(|_| NOT_EQUIVALENT.to_string())?;
+    let comments = |unit: &CompilationUnit| {
+        unit.comments
+            .iter()
+            .map(|it| it.text.trim_end().to_string())
+            .collect::<Vec<_>>()
+    };
+    if get_identifiers(source) != get_identifiers(&formatted)
+        || describe(&unit) != describe(&reparsed)
+        || comments(&unit) != comments(&reparsed)
+    {
+        return Err(NOT_EQUIVALENT.into());
+    }
+    Ok(formatted)
+}
+
+/// parses the given source, sources with syntax errors are not formatted
+fn parse(source: &str, dialect: Dialect) -> Result<CompilationUnit, String> {
+    let (unit, diagnostics) = parser::parse(
+        lexer::lex_with_dialect(source, IdProvider::default(), dialect),
+        LinkageType::Internal,
+    );
+    let assessor = DefaultDiagnosticAssessor::default();
+    match diagnostics
+        .into_iter()
+        .find(|it| matches!(assessor.assess(it.clone()).severity, Severity::Error))
+    {
+        Some(error) => Err(format!(
+            "the source contains syntax errors ({})",
+            error.get_message()
+        )),
+        None => Ok(unit),
+    }
+}
+
+/// returns the identifiers of the given source in lower case, sorted by their names
+fn get_identifiers(source: &str) -> Vec<String> {
+    let mut lexer = Token::lexer(source);
+    let mut identifiers = vec![];
+    while let Some(token) = lexer.next() {
+        if token == Token::Identifier {
+            identifiers.push(lexer.slice().to_lowercase());
+        }
+    }
+    identifiers.sort();
+    identifiers
+}
+
+/// describes the declarations and statements of the given unit without their locations
+fn describe(unit: &CompilationUnit) -> String {
+    let mut description = format!(
+        "{:?}\n{:?}\n{:?}\n{:?}\n",
+        unit.units, unit.prototypes, unit.types, unit.global_vars
+    );
+    for pou in unit.units.iter().chain(&unit.prototypes) {
+        description += &format!("{} {:?} {:?}\n", pou.name, pou.linkage, pou.poly_mode);
+    }
+    let pou_blocks = unit
+        .units
+        .iter()
+        .chain(&unit.prototypes)
+        .flat_map(|it| &it.variable_blocks);
+    for block in pou_blocks.chain(&unit.global_vars) {
+        description += &format!(
+            "{:?} {} {} {} {} {} {} {:?} {:?}\n",
+            block.access,
+            block.constant,
+            block.retain,
+            block.persistent,
+            block.atomic,
+            block.shared,
+            block.config_const,
+            block.init_priority,
+            block.linkage
+        );
+    }
+    for variable in &unit.var_config {
+        description += &format!(
+            "{} {:?} {:?}\n",
+            variable.reference, variable.data_type, variable.address
+        );
+    }
+    for implementation in &unit.implementations {
+        let mut loop_bounds = implementation.loop_bounds.values().collect::<Vec<_>>();
+        loop_bounds.sort();
+        description += &format!(
+            "{} {} {:?} {:?} {:?} {} {:?} {:?}\n",
+            implementation.name,
+            implementation.type_name,
+            implementation.pou_type,
+            implementation.linkage,
+            implementation.access,
+            implementation.overriding,
+            loop_bounds,
+            implementation.statements
+        );
+    }
+    //hardware accesses are the only statements described with their location
+    Regex::new(r"location: SourceRange \{ range: \d+\.\.\d+ \}")
+        .expect("valid regex")
+        .replace_all(&description, "")
+        .to_string()
+}
+
+/// a declaration printed at the top level of a source
+enum Declaration<'a> {
+    Pou(&'a Pou),
+    Prototype(&'a Pou),
+    Type(&'a UserTypeDeclaration),
+    Globals(&'a VariableBlock),
+    Config(Vec<&'a ConfigVariable>),
+    Action(&'a Implementation),
+    /// the actions of an `ACTIONS` block and the location of the block's container
+    Actions(Vec<&'a Implementation>, Range<usize>),
+}
+
+struct Printer<'a> {
+    source: &'a str,
+    options: &'a FormatOptions,
+    /// the tokens of the source, they locate the keywords not represented in the AST
+    tokens: Vec<(Token, Range<usize>)>,
+    /// the comments not printed yet in reverse order
+    comments: Vec<&'a Comment>,
+    /// the loop bounds of the implementation being printed
+    loop_bounds: HashMap<AstId, u32>,
+    output: String,
+    level: usize,
+    /// the end of the source printed so far
+    position: usize,
+}
+
+impl<'a> Printer<'a> {
+    fn new(source: &'a str, unit: &'a CompilationUnit, options: &'a FormatOptions) -> Self {
+        Printer {
+            source,
+            options,
+            tokens: Token::lexer(source).spanned().collect(),
+            comments: unit.comments.iter().rev().collect(),
+            loop_bounds: HashMap::new(),
+            output: String::new(),
+            level: 0,
+            position: 0,
+        }
+    }
+
+    /// prints the declarations of the given unit in the order of the source
+    fn print(mut self, unit: &'a CompilationUnit) -> String {
+        for (_, declaration) in self.get_declarations(unit) {
+            if !self.output.is_empty() && !self.output.ends_with("\n\n") {
+                self.output.push('\n');
+            }
+            match declaration {
+                Declaration::Pou(pou) => self.print_pou(pou, unit, false),
+                Declaration::Prototype(pou) => self.print_pou(pou, unit, true),
+                Declaration::Type(declaration) => self.print_type(declaration),
+                Declaration::Globals(block) => self.print_variable_block(block),
+                Declaration::Config(variables) => self.print_var_config(&variables),
+                Declaration::Action(action) => self.print_action(action, true),
+                Declaration::Actions(actions, container) => self.print_actions(&actions, container),
+            }
+        }
+        self.flush_comments(usize::MAX, 0);
+        self.output
+    }
+
+    /// returns the top level declarations of the given unit by their offset in the source
+    fn get_declarations(&self, unit: &'a CompilationUnit) -> Vec<(usize, Declaration<'a>)> {
+        let mut declarations = vec![];
+        for pou in &unit.units {
+            if !matches!(pou.pou_type, PouType::Method { .. }) {
+                declarations.push((pou.location.get_start(), Declaration::Pou(pou)));
+            }
+        }
+        for pou in &unit.prototypes {
+            if !matches!(pou.pou_type, PouType::Method { .. }) {
+                declarations.push((pou.location.get_start(), Declaration::Prototype(pou)));
+            }
+        }
+        for declaration in &unit.types {
+            declarations.push((
+                declaration.location.get_start(),
+                Declaration::Type(declaration),
+            ));
+        }
+        for block in &unit.global_vars {
+            declarations.push((block.location.get_start(), Declaration::Globals(block)));
+        }
+
+        //the entries of a VAR_CONFIG block follow its keyword
+        let config_blocks = self.find_tokens(&Token::KeywordVarConfig);
+        let mut configs: Vec<(usize, Vec<&ConfigVariable>)> = vec![];
+        for variable in &unit.var_config {
+            let start = config_blocks
+                .iter()
+                .rev()
+                .find(|it| it.end <= variable.location.get_start())
+                .map_or(0, |it| it.start);
+            match configs.iter_mut().find(|(block, _)| *block == start) {
+                Some((_, variables)) => variables.push(variable),
+                None => configs.push((start, vec![variable])),
+            }
+        }
+        for (start, variables) in configs {
+            declarations.push((start, Declaration::Config(variables)));
+        }
+
+        //actions are either declared on their own or within an `ACTIONS` block
+        let action_blocks = self
+            .find_tokens(&Token::KeywordActions)
+            .into_iter()
+            .map(|it| it.start..self.find_token(&Token::KeywordEndActions, it.end).end)
+            .collect::<Vec<_>>();
+        let mut blocks: Vec<(usize, Vec<&Implementation>)> = vec![];
+        for action in &unit.implementations {
+            if action.pou_type != PouType::Action {
+                continue;
+            }
+            let start = action.location.get_start();
+            match action_blocks.iter().find(|it| it.contains(&start)) {
+                Some(block) => match blocks.iter_mut().find(|(it, _)| *it == block.start) {
+                    Some((_, actions)) => actions.push(action),
+                    None => blocks.push((block.start, vec![action])),
+                },
+                None => declarations.push((start, Declaration::Action(action))),
+            }
+        }
+        for (start, actions) in blocks {
+            let container = self.find_token(&Token::Identifier, start);
+            declarations.push((start, Declaration::Actions(actions, start..container.end)));
+        }
+
+        declarations.sort_by_key(|(start, _)| *start);
+        declarations
+    }
+
+    fn print_pou(&mut self, pou: &'a Pou, unit: &'a CompilationUnit, prototype: bool) {
+        let start = pou.location.get_start();
+        let implementation = unit
+            .implementations
+            .iter()
+            .find(|it| it.name == pou.name && it.pou_type != PouType::Action);
+        for pragma in get_pou_pragmas(pou) {
+            self.line(&pragma, start..start);
+        }
+        if prototype {
+            self.line("{prototype}", start..start);
+        }
+        let header_end = pou
+            .return_type
+            .as_ref()
+            .map_or(pou.name_location.get_end(), |it| {
+                it.get_location().get_end()
+            });
+        let header = self.get_pou_header(pou, implementation);
+        self.line(&header, start..header_end);
+
+        self.level += 1;
+        if !pou.usings.is_empty() {
+            let usings = format!("{} {};", self.keyword("USING"), pou.usings.join(", "));
+            self.line(&usings, header_end..header_end);
+        }
+        for block in &pou.variable_blocks {
+            self.print_variable_block(block);
+        }
+        let owner = Some(pou.name.clone());
+        for method in unit
+            .units
+            .iter()
+            .chain(&unit.prototypes)
+            .filter(|it| it.pou_type.get_optional_owner_class() == owner)
+        {
+            self.print_pou(method, unit, false);
+        }
+        if let Some(implementation) = implementation {
+            self.print_body(implementation);
+        }
+        self.level -= 1;
+
+        let (keyword, token) = match pou.pou_type {
+            PouType::Program => ("END_PROGRAM", Token::KeywordEndProgram),
+            PouType::Function => ("END_FUNCTION", Token::KeywordEndFunction),
+            PouType::FunctionBlock => ("END_FUNCTION_BLOCK", Token::KeywordEndFunctionBlock),
+            PouType::Class => ("END_CLASS", Token::KeywordEndClass),
+            PouType::Interface => ("END_INTERFACE", Token::KeywordEndInterface),
+            PouType::Method { .. } => ("END_METHOD", Token::KeywordEndMethod),
+            PouType::Action => ("END_ACTION", Token::KeywordEndAction),
+        };
+        self.end_line(keyword, &token, None);
+    }
+
+    /// returns the first line of the given POU (e.g. `FUNCTION_BLOCK ABSTRACT fb EXTENDS base`)
+    fn get_pou_header(&self, pou: &Pou, implementation: Option<&Implementation>) -> String {
+        let mut header = self.keyword(match pou.pou_type {
+            PouType::Program => "PROGRAM",
+            PouType::Function => "FUNCTION",
+            PouType::FunctionBlock => "FUNCTION_BLOCK",
+            PouType::Class => "CLASS",
+            PouType::Interface => "INTERFACE",
+            PouType::Method { .. } => "METHOD",
+            PouType::Action => "ACTION",
+        });
+        let access = implementation
+            .and_then(|it| it.access.as_ref())
+            .filter(|it| **it != AccessModifier::Protected);
+        if let Some(access) = access {
+            header = format!("{} {}", header, self.get_access_keyword(access));
+        }
+        match pou.poly_mode {
+            Some(PolymorphismMode::Abstract) => header += &self.keyword(" ABSTRACT"),
+            Some(PolymorphismMode::Final) => header += &self.keyword(" FINAL"),
+            _ => {}
+        }
+        if implementation.map_or(false, |it| it.overriding) {
+            header += &self.keyword(" OVERRIDE");
+        }
+        header = format!("{} {}", header, pou.get_return_name());
+        if !pou.generics.is_empty() {
+            let generics = pou
+                .generics
+                .iter()
+                .map(|it| format!("{}: {}", it.name, get_nature_name(&it.nature)))
+                .collect::<Vec<_>>();
+            header = format!("{}<{}>", header, generics.join(", "));
+        }
+        if let Some(super_class) = &pou.super_class {
+            header = format!("{} {} {}", header, self.keyword("EXTENDS"), super_class);
+        }
+        if !pou.interfaces.is_empty() {
+            let interfaces = pou.interfaces.join(", ");
+            header = format!("{} {} {}", header, self.keyword("IMPLEMENTS"), interfaces);
+        }
+        if let Some(return_type) = &pou.return_type {
+            header = format!(
+                "{} : {}",
+                header,
+                self.get_data_type_declaration(return_type)
+            );
+        }
+        header
+    }
+
+    fn get_access_keyword(&self, access: &AccessModifier) -> String {
+        self.keyword(match access {
+            AccessModifier::Private => "PRIVATE",
+            AccessModifier::Public => "PUBLIC",
+            AccessModifier::Protected => "PROTECTED",
+            AccessModifier::Internal => "INTERNAL",
+        })
+    }
+
+    fn print_body(&mut self, implementation: &'a Implementation) {
+        self.loop_bounds = implementation.loop_bounds.clone();
+        self.print_statements(&implementation.statements);
+    }
+
+    /// prints an `ACTION container.name` declared on its own or an `ACTION name` within an
+    /// `ACTIONS` block
+    fn print_action(&mut self, action: &'a Implementation, qualified: bool) {
+        let body_start = action.location.get_start();
+        let start = self
+            .find_token_before(&Token::KeywordAction, body_start)
+            .map_or(body_start, |it| it.start);
+        let name_end = self
+            .find_token_before(&Token::Identifier, body_start)
+            .map_or(start, |it| it.end);
+        if qualified {
+            if let Some(pragma) = get_linkage_pragma(action.linkage) {
+                self.line(pragma, start..start);
+            }
+        }
+        let name = if qualified {
+            action.name.as_str()
+        } else {
+            Pou::calc_return_name(&action.name)
+        };
+        let header = format!("{} {}", self.keyword("ACTION"), name);
+        self.line(&header, start..name_end);
+        self.level += 1;
+        self.print_body(action);
+        self.level -= 1;
+        self.end_line("END_ACTION", &Token::KeywordEndAction, None);
+    }
+
+    fn print_actions(&mut self, actions: &[&'a Implementation], container: Range<usize>) {
+        let start = container.start;
+        if let Some(pragma) = actions
+            .first()
+            .and_then(|it| get_linkage_pragma(it.linkage))
+        {
+            self.line(pragma, start..start);
+        }
+        let container_name = actions.first().map_or("", |it| it.type_name.as_str());
+        let header = format!("{} {}", self.keyword("ACTIONS"), container_name);
+        self.line(&header, container);
+        self.level += 1;
+        for action in actions {
+            self.print_action(action, false);
+        }
+        self.level -= 1;
+        self.end_line("END_ACTIONS", &Token::KeywordEndActions, None);
+    }
+
+    fn print_type(&mut self, declaration: &'a UserTypeDeclaration) {
+        let location = declaration.location.to_range();
+        let start = self
+            .find_token_before(&Token::KeywordType, location.start)
+            .map_or(location.start, |it| it.start);
+        let name = declaration.data_type.get_name().unwrap_or_default();
+        if let DataType::StructType { variables, .. } = &declaration.data_type {
+            let header = format!(
+                "{} {} : {}",
+                self.keyword("TYPE"),
+                name,
+                self.keyword("STRUCT")
+            );
+            let struct_end = self.find_token(&Token::KeywordStruct, location.start).end;
+            self.line(&header, start..struct_end);
+            self.level += 1;
+            self.print_variables(variables);
+            self.level -= 1;
+            self.end_line("END_STRUCT", &Token::KeywordEndStruct, None);
+        } else {
+            //the declaration starts with the type's name
+            let text = &self.source[location.clone()];
+            let text = text.split_once(':').map_or(text, |(_, it)| it).trim_start();
+            let initializer = declaration
+                .initializer
+                .as_ref()
+                .map(|it| format!(" := {}", self.get_initializer(it)))
+                .unwrap_or_default();
+            let line = format!(
+                "{} {} : {}{};",
+                self.keyword("TYPE"),
+                name,
+                self.get_data_type(&declaration.data_type, text),
+                initializer
+            );
+            self.line(&line, start..location.end);
+        }
+        self.end_line("END_TYPE", &Token::KeywordEndType, None);
+    }
+
+    fn print_variable_block(&mut self, block: &'a VariableBlock) {
+        let location = block.location.to_range();
+        if let Some(pragma) = get_linkage_pragma(block.linkage) {
+            self.line(pragma, location.start..location.start);
+        }
+        if block.config_const {
+            self.line("{attribute 'config_const'}", location.start..location.start);
+        }
+        if let Some(priority) = block.init_priority {
+            self.line(
+                &format!("{{attribute 'init_priority' := '{}'}}", priority),
+                location.start..location.start,
+            );
+        }
+        let mut header = self.keyword(match block.variable_block_type {
+            VariableBlockType::Local => "VAR",
+            VariableBlockType::Temp => "VAR_TEMP",
+            VariableBlockType::Input(_) => "VAR_INPUT",
+            VariableBlockType::Output => "VAR_OUTPUT",
+            VariableBlockType::Global => "VAR_GLOBAL",
+            VariableBlockType::InOut => "VAR_IN_OUT",
+        });
+        if block.variable_block_type == VariableBlockType::Input(ArgumentProperty::ByRef) {
+            header += " {ref}";
+        }
+        if block.atomic {
+            header += " {atomic}";
+        }
+        if block.shared {
+            header += " {shared}";
+        }
+        if block.constant {
+            header += &self.keyword(" CONSTANT");
+        }
+        if block.persistent {
+            header += &self.keyword(" PERSISTENT");
+        } else if block.retain {
+            header += &self.keyword(" RETAIN");
+        }
+        if block.access != AccessModifier::Protected {
+            header = format!("{} {}", header, self.get_access_keyword(&block.access));
+        }
+        self.line(&header, location);
+        self.level += 1;
+        self.print_variables(&block.variables);
+        self.level -= 1;
+        self.end_line("END_VAR", &Token::KeywordEndVar, None);
+    }
+
+    fn print_var_config(&mut self, variables: &[&'a ConfigVariable]) {
+        let start = variables.first().map_or(0, |it| it.location.get_start());
+        let keyword = self
+            .find_token_before(&Token::KeywordVarConfig, start)
+            .unwrap_or(start..start);
+        self.line(&self.keyword("VAR_CONFIG"), keyword);
+        self.level += 1;
+        for variable in variables {
+            let line = format!(
+                "{} {} {} : {};",
+                variable.reference,
+                self.keyword("AT"),
+                self.get_expression(&variable.address),
+                self.get_data_type_declaration(&variable.data_type)
+            );
+            let end = variable.data_type.get_location().get_end();
+            self.line(&line, variable.location.get_start()..end);
+        }
+        self.level -= 1;
+        self.end_line("END_VAR", &Token::KeywordEndVar, None);
+    }
+
+    /// prints the given variables, the variables declared on the same line stay on one line
+    fn print_variables(&mut self, variables: &'a [Variable]) {
+        let mut index = 0;
+        while index < variables.len() {
+            let first = &variables[index];
+            let count = variables[index..]
+                .iter()
+                .take_while(|it| {
+                    it.data_type.get_location() == first.data_type.get_location()
+                        && it.address == first.address
+                })
+                .count();
+            self.print_variable_line(&variables[index..index + count]);
+            index += count;
+        }
+    }
+
+    fn print_variable_line(&mut self, variables: &'a [Variable]) {
+        let first = &variables[0];
+        let start = first.location.get_start();
+        let mut declaration = variables
+            .iter()
+            .map(|it| it.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Some(address) = &first.address {
+            let address = self.get_expression(address);
+            declaration = format!("{} {} {}", declaration, self.keyword("AT"), address);
+        }
+
+        if let DataTypeDeclaration::DataTypeDefinition {
+            data_type: DataType::StructType { variables, .. },
+            ..
+        } = &first.data_type
+        {
+            let struct_end = self.find_token(&Token::KeywordStruct, start).end;
+            let header = format!("{} : {}", declaration, self.keyword("STRUCT"));
+            self.line(&header, start..struct_end);
+            self.level += 1;
+            self.print_variables(variables);
+            self.level -= 1;
+            self.end_line("END_STRUCT", &Token::KeywordEndStruct, None);
+            return;
+        }
+
+        let mut end = first.data_type.get_location().get_end();
+        let initializer = match &first.initializer {
+            Some(initializer) => {
+                end = end.max(initializer.get_location().get_end());
+                format!(" := {}", self.get_initializer(initializer))
+            }
+            None => String::new(),
+        };
+        let line = format!(
+            "{} : {}{};",
+            declaration,
+            self.get_data_type_declaration(&first.data_type),
+            initializer
+        );
+        self.line(&line, start..end);
+    }
+
+    fn print_statements(&mut self, statements: &'a [AstStatement]) {
+        let mut statements = statements.iter().peekable();
+        while let Some(statement) = statements.next() {
+            //the `;` following a statement not ending with one is parsed into an empty statement
+            let semicolon = match statements.peek() {
+                Some(AstStatement::EmptyStatement { location, .. })
+                    if !is_terminated(statement) =>
+                {
+                    statements.next();
+                    Some(location.get_end())
+                }
+                _ => None,
+            };
+            self.print_statement(statement, semicolon);
+        }
+    }
+
+    /// prints the given statements indented by another level
+    fn print_block(&mut self, statements: &'a [AstStatement]) {
+        self.level += 1;
+        self.print_statements(statements);
+        self.level -= 1;
+    }
+
+    /// prints the given statement, `semicolon` is the end of the `;` following a statement not
+    /// terminated by one
+    fn print_statement(&mut self, statement: &'a AstStatement, semicolon: Option<usize>) {
+        let location = statement.get_location().to_range();
+        let suffix = if semicolon.is_some() { ";" } else { "" };
+        let end = semicolon.unwrap_or(location.end);
+        match statement {
+            AstStatement::IfStatement {
+                blocks, else_block, ..
+            } => {
+                for (index, block) in blocks.iter().enumerate() {
+                    let (keyword, start) = if index == 0 {
+                        ("IF", location.start)
+                    } else {
+                        let start = self.find_token(&Token::KeywordElseIf, self.position).start;
+                        ("ELSIF", start)
+                    };
+                    let condition = block.condition.get_location().get_end();
+                    let then = self.find_token(&Token::KeywordThen, condition);
+                    let line = format!(
+                        "{} {} {}",
+                        self.keyword(keyword),
+                        self.get_expression(&block.condition),
+                        self.keyword("THEN")
+                    );
+                    if index == 0 {
+                        self.line(&line, start..then.end);
+                    } else {
+                        self.closing_line(&line, start..then.end);
+                    }
+                    self.print_block(&block.body);
+                }
+                if !else_block.is_empty() {
+                    let range = self.find_token(&Token::KeywordElse, self.position);
+                    self.closing_line(&self.keyword("ELSE"), range);
+                    self.print_block(else_block);
+                }
+                self.end_line("END_IF", &Token::KeywordEndIf, semicolon);
+            }
+            AstStatement::ForLoopStatement {
+                counter,
+                start,
+                end: last,
+                by_step,
+                body,
+                id,
+                ..
+            } => {
+                self.print_loop_bound(*id, location.start);
+                let mut line = format!(
+                    "{} {} := {} {} {}",
+                    self.keyword("FOR"),
+                    self.get_expression(counter),
+                    self.get_expression(start),
+                    self.keyword("TO"),
+                    self.get_expression(last)
+                );
+                let mut header_end = last.get_location().get_end();
+                if let Some(step) = by_step {
+                    line = format!(
+                        "{} {} {}",
+                        line,
+                        self.keyword("BY"),
+                        self.get_expression(step)
+                    );
+                    header_end = step.get_location().get_end();
+                }
+                let header_end = self.find_token(&Token::KeywordDo, header_end).end;
+                let line = format!("{} {}", line, self.keyword("DO"));
+                self.line(&line, location.start..header_end);
+                self.print_block(body);
+                self.end_line("END_FOR", &Token::KeywordEndFor, semicolon);
+            }
+            AstStatement::WhileLoopStatement {
+                condition,
+                body,
+                id,
+                ..
+            } => {
+                self.print_loop_bound(*id, location.start);
+                let header_end = self
+                    .find_token(&Token::KeywordDo, condition.get_location().get_end())
+                    .end;
+                let line = format!(
+                    "{} {} {}",
+                    self.keyword("WHILE"),
+                    self.get_expression(condition),
+                    self.keyword("DO")
+                );
+                self.line(&line, location.start..header_end);
+                self.print_block(body);
+                self.end_line("END_WHILE", &Token::KeywordEndWhile, semicolon);
+            }
+            AstStatement::RepeatLoopStatement {
+                condition,
+                body,
+                id,
+                ..
+            } => {
+                self.print_loop_bound(*id, location.start);
+                let repeat = self.find_token(&Token::KeywordRepeat, location.start);
+                self.line(&self.keyword("REPEAT"), repeat);
+                self.print_block(body);
+                if !matches!(**condition, AstStatement::EmptyStatement { .. }) {
+                    let until = self.find_token(&Token::KeywordUntil, self.position);
+                    let line = format!(
+                        "{} {}",
+                        self.keyword("UNTIL"),
+                        self.get_expression(condition)
+                    );
+                    self.closing_line(&line, until.start..condition.get_location().get_end());
+                }
+                self.end_line("END_REPEAT", &Token::KeywordEndRepeat, semicolon);
+            }
+            AstStatement::CaseStatement {
+                selector,
+                case_blocks,
+                else_block,
+                ..
+            } => {
+                let header_end = self
+                    .find_token(&Token::KeywordOf, selector.get_location().get_end())
+                    .end;
+                let line = format!(
+                    "{} {} {}",
+                    self.keyword("CASE"),
+                    self.get_expression(selector),
+                    self.keyword("OF")
+                );
+                self.line(&line, location.start..header_end);
+                self.level += 1;
+                for block in case_blocks {
+                    let condition = block.condition.get_location();
+                    let colon = self.find_token(&Token::KeywordColon, condition.get_end());
+                    let line = format!("{}:", self.get_expression(&block.condition));
+                    self.line(&line, condition.get_start()..colon.end);
+                    self.print_block(&block.body);
+                }
+                if !else_block.is_empty() {
+                    let range = self.find_token(&Token::KeywordElse, self.position);
+                    self.line(&self.keyword("ELSE"), range);
+                    self.print_block(else_block);
+                }
+                self.level -= 1;
+                self.end_line("END_CASE", &Token::KeywordEndCase, semicolon);
+            }
+            AstStatement::ReturnStatement { .. } => {
+                let line = format!("{}{}", self.keyword("RETURN"), suffix);
+                self.line(&line, location.start..end);
+            }
+            AstStatement::ExitStatement { .. } => {
+                let line = format!("{}{}", self.keyword("EXIT"), suffix);
+                self.line(&line, location.start..end);
+            }
+            AstStatement::ContinueStatement { .. } => {
+                let line = format!("{}{}", self.keyword("CONTINUE"), suffix);
+                self.line(&line, location.start..end);
+            }
+            AstStatement::JumpStatement { label, .. } => {
+                let line = format!("{} {}{}", self.keyword("JMP"), label, suffix);
+                self.line(&line, location.start..end);
+            }
+            AstStatement::LabelStatement { name, .. } => {
+                let colon = self.find_token(&Token::KeywordColon, location.end);
+                self.line(&format!("{}:", name), location.start..colon.end);
+            }
+            AstStatement::CaseCondition { condition, .. } => {
+                let colon = self.find_token(&Token::KeywordColon, location.end);
+                let line = format!("{}:", self.get_expression(condition));
+                self.line(&line, location.start..colon.end);
+            }
+            AstStatement::EmptyStatement { .. } => self.line(";", location),
+            _ => self.print_expression_statement(statement),
+        }
+    }
+
+    /// prints the `{attribute 'loop_bound' := 'n'}` of the loop with the given id
+    fn print_loop_bound(&mut self, id: AstId, start: usize) {
+        if let Some(bound) = self.loop_bounds.get(&id) {
+            let pragma = format!("{{attribute 'loop_bound' := '{}'}}", bound);
+            self.line(&pragma, start..start);
+        }
+    }
+
+    /// prints an expression followed by a `;`, the arguments of a call exceeding the line width
+    /// are printed on lines of their own
+    fn print_expression_statement(&mut self, statement: &'a AstStatement) {
+        let location = statement.get_location().to_range();
+        let line = format!("{};", self.get_expression(statement));
+        let (prefix, call) = match statement {
+            AstStatement::Assignment { left, right, .. } => (
+                format!("{} := ", self.get_operand(left, LEAF)),
+                right.as_ref(),
+            ),
+            _ => (String::new(), statement),
+        };
+        let exceeds_line =
+            self.get_indentation(self.level).len() + line.len() > self.options.line_width;
+        if let (
+            true,
+            AstStatement::CallStatement {
+                operator,
+                parameters,
+                ..
+            },
+        ) = (exceeds_line, call)
+        {
+            if let Some(parameters) = parameters.as_ref() {
+                let arguments = match parameters {
+                    AstStatement::ExpressionList { expressions, .. } => {
+                        expressions.iter().collect()
+                    }
+                    parameter => vec![parameter],
+                };
+                let first_line = format!("{}{}(", prefix, self.get_expression(operator));
+                self.line(&first_line, location.start..location.start);
+                self.level += 1;
+                for (index, argument) in arguments.iter().enumerate() {
+                    let separator = if index + 1 < arguments.len() { "," } else { "" };
+                    let line = format!("{}{}", self.get_operand(argument, ASSIGNMENT), separator);
+                    self.line(&line, argument.get_location().to_range());
+                }
+                self.level -= 1;
+                self.line(");", location.end..location.end);
+                return;
+            }
+        }
+        self.line(&line, location);
+    }
+
+    /// returns the given expression, the elements of an expression list are separated by commas
+    fn get_expression(&self, statement: &AstStatement) -> String {
+        match statement {
+            AstStatement::EmptyStatement { .. } => String::new(),
+            AstStatement::LiteralNull { .. } => self.keyword("NULL"),
+            AstStatement::LiteralBool { value, .. } => {
+                self.keyword(if *value { "TRUE" } else { "FALSE" })
+            }
+            AstStatement::LiteralInteger {
+                value, location, ..
+            } => {
+                //the location of a negative number with a radix (`-16#FF`) may exclude the sign
+                let text = self.get_source(location);
+                if *value < 0 && !text.starts_with('-') {
+                    format!("-{}", text)
+                } else {
+                    text.to_string()
+                }
+            }
+            AstStatement::LiteralDate { location, .. }
+            | AstStatement::LiteralDateAndTime { location, .. }
+            | AstStatement::LiteralTimeOfDay { location, .. }
+            | AstStatement::LiteralTime { location, .. }
+            | AstStatement::LiteralReal { location, .. }
+            | AstStatement::LiteralString { location, .. }
+            | AstStatement::DirectAccess { location, .. } => self.get_source(location).to_string(),
+            AstStatement::LiteralArray { elements, .. } => format!(
+                "[{}]",
+                elements
+                    .as_ref()
+                    .map(|it| self.get_expression(it))
+                    .unwrap_or_default()
+            ),
+            AstStatement::CastStatement {
+                target, type_name, ..
+            } => format!("{}#{}", type_name, self.get_expression(target)),
+            AstStatement::MultipliedStatement {
+                multiplier,
+                element,
+                ..
+            } => format!("{}({})", multiplier, self.get_expression(element)),
+            AstStatement::QualifiedReference { elements, .. } => elements
+                .iter()
+                .map(|it| self.get_expression(it))
+                .collect::<Vec<_>>()
+                .join("."),
+            AstStatement::Reference { name, .. } => name.clone(),
+            AstStatement::ArrayAccess {
+                reference, access, ..
+            } => format!(
+                "{}[{}]",
+                self.get_expression(reference),
+                self.get_expression(access)
+            ),
+            AstStatement::PointerAccess { reference, .. } => {
+                format!("{}^", self.get_expression(reference))
+            }
+            AstStatement::HardwareAccess {
+                direction,
+                access,
+                address,
+                ..
+            } => {
+                let direction = match direction {
+                    HardwareAccessType::Input => "I",
+                    HardwareAccessType::Output => "Q",
+                    HardwareAccessType::Memory => "M",
+                };
+                let access = match access {
+                    DirectAccessType::Bit => "X",
+                    DirectAccessType::Byte => "B",
+                    DirectAccessType::Word => "W",
+                    DirectAccessType::DWord => "D",
+                    DirectAccessType::Template => "*",
+                };
+                let address = address
+                    .iter()
+                    .map(|it| self.get_expression(it))
+                    .collect::<Vec<_>>();
+                format!("%{}{}{}", direction, access, address.join("."))
+            }
+            AstStatement::BinaryExpression {
+                operator,
+                left,
+                right,
+                ..
+            } => {
+                let precedence = get_precedence(statement);
+                format!(
+                    "{} {} {}",
+                    self.get_operand(left, precedence),
+                    self.keyword(&operator.to_string()),
+                    self.get_operand(right, precedence + 1)
+                )
+            }
+            AstStatement::UnaryExpression {
+                operator, value, ..
+            } => match operator {
+                Operator::Not => {
+                    format!("{} {}", self.keyword("NOT"), self.get_operand(value, LEAF))
+                }
+                _ => format!("{}{}", operator, self.get_operand(value, LEAF)),
+            },
+            AstStatement::ExpressionList { expressions, .. } => expressions
+                .iter()
+                .map(|it| self.get_operand(it, ASSIGNMENT))
+                .collect::<Vec<_>>()
+                .join(", "),
+            AstStatement::RangeStatement { start, end, .. } => format!(
+                "{}..{}",
+                self.get_operand(start, OR),
+                self.get_operand(end, OR)
+            ),
+            AstStatement::Assignment { left, right, .. } => format!(
+                "{} := {}",
+                self.get_operand(left, LEAF),
+                self.get_operand(right, RANGE)
+            ),
+            AstStatement::OutputAssignment { left, right, .. } => format!(
+                "{} => {}",
+                self.get_operand(left, LEAF),
+                self.get_operand(right, RANGE)
+            ),
+            AstStatement::ReferenceAssignment { left, right, .. } => format!(
+                "{} {} {}",
+                self.get_operand(left, LEAF),
+                self.keyword("REF="),
+                self.get_operand(right, RANGE)
+            ),
+            AstStatement::CallStatement {
+                operator,
+                parameters,
+                ..
+            } => format!(
+                "{}({})",
+                self.get_expression(operator),
+                parameters
+                    .as_ref()
+                    .as_ref()
+                    .map(|it| self.get_expression(it))
+                    .unwrap_or_default()
+            ),
+            AstStatement::CaseCondition { condition, .. } => self.get_expression(condition),
+            //statements are printed by `print_statement`
+            _ => String::new(),
+        }
+    }
+
+    /// returns the given operand, in parentheses if its precedence is lower than the given one
+    fn get_operand(&self, statement: &AstStatement, precedence: u8) -> String {
+        let expression = self.get_expression(statement);
+        if get_precedence(statement) < precedence {
+            format!("({})", expression)
+        } else {
+            expression
+        }
+    }
+
+    /// returns the initial value of a variable or type, struct initializers are put in parentheses
+    fn get_initializer(&self, statement: &AstStatement) -> String {
+        self.get_operand(statement, RANGE)
+    }
+
+    fn get_data_type_declaration(&self, declaration: &DataTypeDeclaration) -> String {
+        match declaration {
+            DataTypeDeclaration::DataTypeReference {
+                referenced_type, ..
+            } => referenced_type.clone(),
+            DataTypeDeclaration::DataTypeDefinition {
+                data_type,
+                location,
+                ..
+            } => self.get_data_type(data_type, self.get_source(location)),
+        }
+    }
+
+    /// returns the given data type, `text` is its declaration in the source
+    fn get_data_type(&self, data_type: &DataType, text: &str) -> String {
+        match data_type {
+            DataType::StructType { variables, .. } => {
+                let members = variables
+                    .iter()
+                    .map(|it| {
+                        format!(
+                            "{} : {};",
+                            it.name,
+                            self.get_data_type_declaration(&it.data_type)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                format!(
+                    "{} {} {}",
+                    self.keyword("STRUCT"),
+                    members.join(" "),
+                    self.keyword("END_STRUCT")
+                )
+            }
+            DataType::EnumType {
+                numeric_type,
+                elements,
+                ..
+            } => {
+                //the numeric type of an enum declared without one defaults to DINT
+                if text.starts_with('(') {
+                    format!("({})", self.get_expression(elements))
+                } else {
+                    format!("{}({})", numeric_type, self.get_expression(elements))
+                }
+            }
+            DataType::SubRangeType {
+                referenced_type,
+                bounds,
+                ..
+            } => match bounds {
+                Some(bounds) => format!("{}({})", referenced_type, self.get_expression(bounds)),
+                None => referenced_type.clone(),
+            },
+            DataType::ArrayType {
+                bounds,
+                referenced_type,
+                is_variable_length,
+                ..
+            } => {
+                let bounds = if *is_variable_length {
+                    let dimensions = match bounds {
+                        AstStatement::ExpressionList { expressions, .. } => expressions.len(),
+                        _ => 1,
+                    };
+                    vec!["*"; dimensions].join(", ")
+                } else {
+                    self.get_expression(bounds)
+                };
+                format!(
+                    "{}[{}] {} {}",
+                    self.keyword("ARRAY"),
+                    bounds,
+                    self.keyword("OF"),
+                    self.get_data_type_declaration(referenced_type)
+                )
+            }
+            DataType::PointerType {
+                referenced_type,
+                auto_deref,
+                ..
+            } => {
+                let keyword = if *auto_deref {
+                    "REFERENCE TO"
+                } else if text.to_uppercase().starts_with("POINTER") {
+                    "POINTER TO"
+                } else {
+                    "REF_TO"
+                };
+                format!(
+                    "{} {}",
+                    self.keyword(keyword),
+                    self.get_data_type_declaration(referenced_type)
+                )
+            }
+            DataType::StringType { is_wide, size, .. } => {
+                let keyword = self.keyword(if *is_wide { "WSTRING" } else { "STRING" });
+                match size {
+                    Some(size) => format!("{}[{}]", keyword, self.get_expression(size)),
+                    None => keyword,
+                }
+            }
+            DataType::VarArgs { referenced_type } => match referenced_type {
+                Some(referenced_type) => {
+                    format!("{}...", self.get_data_type_declaration(referenced_type))
+                }
+                None => "...".to_string(),
+            },
+            DataType::GenericType { name, .. } => name.clone(),
+            DataType::FixedPointType {
+                integer_bits,
+                fraction_bits,
+                ..
+            } => format!("FIXED({}, {})", integer_bits, fraction_bits),
+        }
+    }
+
+    /// prints a line of the given text representing the given range of the source. The comments
+    /// before the range are printed first, the comments following it on the same line of the
+    /// source are appended
+    fn line(&mut self, text: &str, range: Range<usize>) {
+        self.flush_comments(range.start, self.level);
+        self.keep_blank_line(range.start);
+        self.output += &self.get_indentation(self.level);
+        self.output += text;
+        self.position = self.position.max(range.end);
+        self.append_trailing_comments(range.end);
+        self.output.push('\n');
+    }
+
+    /// prints a line continuing or closing a block (e.g. `ELSE` or `END_IF`), the comments at the
+    /// end of the block are indented like its content
+    fn closing_line(&mut self, text: &str, range: Range<usize>) {
+        self.flush_comments(range.start, self.level + 1);
+        self.line(text, range);
+    }
+
+    /// prints the keyword closing a block (e.g. `END_IF`), located by the given token
+    fn end_line(&mut self, keyword: &str, token: &Token, semicolon: Option<usize>) {
+        let range = self.find_token(token, self.position);
+        let suffix = if semicolon.is_some() { ";" } else { "" };
+        let line = format!("{}{}", self.keyword(keyword), suffix);
+        self.closing_line(&line, range.start..semicolon.unwrap_or(range.end));
+    }
+
+    /// prints the comments starting before the given offset on lines of their own
+    fn flush_comments(&mut self, offset: usize, level: usize) {
+        while let Some(comment) = self
+            .comments
+            .last()
+            .copied()
+            .filter(|it| it.location.get_start() < offset)
+        {
+            self.comments.pop();
+            self.keep_blank_line(comment.location.get_start());
+            self.output += &self.get_indentation(level);
+            self.output += &comment.text;
+            self.output.push('\n');
+            self.position = self.position.max(comment.location.get_end());
+        }
+    }
+
+    /// appends the comments following the given offset on the same line of the source
+    fn append_trailing_comments(&mut self, mut end: usize) {
+        while let Some(comment) = self.comments.last().copied() {
+            let is_trailing =
+                self.source
+                    .get(end..comment.location.get_start())
+                    .map_or(false, |gap| {
+                        gap.chars()
+                            .all(|it| matches!(it, ' ' | '\t' | ';' | ',' | ')' | ']'))
+                    });
+            if !is_trailing {
+                break;
+            }
+            self.comments.pop();
+            self.output.push(' ');
+            self.output += &comment.text;
+            end = comment.location.get_end();
+            self.position = self.position.max(end);
+        }
+    }
+
+    /// keeps an empty line of the source between the printed part and the given offset
+    fn keep_blank_line(&mut self, offset: usize) {
+        let is_blank = self
+            .source
+            .get(self.position..offset)
+            .map_or(false, |gap| gap.matches('\n').count() > 1);
+        if is_blank && !self.output.is_empty() && !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+    }
+
+    fn get_indentation(&self, level: usize) -> String {
+        " ".repeat(level * self.options.indent)
+    }
+
+    fn keyword(&self, keyword: &str) -> String {
+        match self.options.keyword_case {
+            KeywordCase::Upper => keyword.to_string(),
+            KeywordCase::Lower => keyword.to_lowercase(),
+        }
+    }
+
+    fn get_source(&self, location: &SourceRange) -> &'a str {
+        self.source.get(location.to_range()).unwrap_or_default()
+    }
+
+    /// returns the range of the first given token starting at or after the given offset
+    fn find_token(&self, token: &Token, offset: usize) -> Range<usize> {
+        self.tokens
+            .iter()
+            .find(|(it, range)| it == token && range.start >= offset)
+            .map_or(offset..offset, |(_, range)| range.clone())
+    }
+
+    /// returns the range of the last given token ending at or before the given offset
+    fn find_token_before(&self, token: &Token, offset: usize) -> Option<Range<usize>> {
+        self.tokens
+            .iter()
+            .rev()
+            .find(|(it, range)| it == token && range.end <= offset)
+            .map(|(_, range)| range.clone())
+    }
+
+    fn find_tokens(&self, token: &Token) -> Vec<Range<usize>> {
+        self.tokens
+            .iter()
+            .filter(|(it, _)| it == token)
+            .map(|(_, range)| range.clone())
+            .collect()
+    }
+}
+
+/// returns true if the given statement ends with a `;` (e.g. an assignment) or a `:` (a label)
+fn is_terminated(statement: &AstStatement) -> bool {
+    !matches!(
+        statement,
+        AstStatement::IfStatement { .. }
+            | AstStatement::ForLoopStatement { .. }
+            | AstStatement::WhileLoopStatement { .. }
+            | AstStatement::RepeatLoopStatement { .. }
+            | AstStatement::CaseStatement { .. }
+            | AstStatement::ReturnStatement { .. }
+            | AstStatement::ExitStatement { .. }
+            | AstStatement::ContinueStatement { .. }
+            | AstStatement::JumpStatement { .. }
+    )
+}
+
+fn get_precedence(statement: &AstStatement) -> u8 {
+    match statement {
+        AstStatement::ExpressionList { .. } => LIST,
+        AstStatement::Assignment { .. }
+        | AstStatement::OutputAssignment { .. }
+        | AstStatement::ReferenceAssignment { .. } => ASSIGNMENT,
+        AstStatement::RangeStatement { .. } => RANGE,
+        AstStatement::BinaryExpression { operator, .. } => match operator {
+            Operator::Or => OR,
+            Operator::Xor => XOR,
+            Operator::And => AND,
+            Operator::Equal | Operator::NotEqual => EQUALITY,
+            Operator::Less
+            | Operator::Greater
+            | Operator::LessOrEqual
+            | Operator::GreaterOrEqual => COMPARISON,
+            Operator::Plus | Operator::Minus => ADDITION,
+            Operator::Multiplication | Operator::Division | Operator::Modulo => MULTIPLICATION,
+            Operator::Not | Operator::Address => UNARY,
+        },
+        AstStatement::UnaryExpression { .. } => UNARY,
+        _ => LEAF,
+    }
+}
+
+fn get_linkage_pragma(linkage: LinkageType) -> Option<&'static str> {
+    match linkage {
+        LinkageType::External => Some("{external}"),
+        LinkageType::Weak => Some("{attribute 'weak'}"),
+        LinkageType::Internal | LinkageType::BuiltIn => None,
+    }
+}
+
+/// returns the pragmas preceding the given POU
+fn get_pou_pragmas(pou: &Pou) -> Vec<String> {
+    let mut pragmas = vec![];
+    if let Some(convention) = pou.calling_convention {
+        //the calling convention implies the external linkage
+        pragmas.push(format!("{{external '{}'}}", convention.get_name()));
+    } else if let Some(pragma) = get_linkage_pragma(pou.linkage) {
+        if !matches!(pou.pou_type, PouType::Method { .. }) {
+            pragmas.push(pragma.to_string());
+        }
+    }
+    if let Some(optimization) = pou.optimization {
+        let name = match optimization {
+            OptimizationHint::None => "none",
+            OptimizationHint::Size => "size",
+            OptimizationHint::Speed => "speed",
+        };
+        pragmas.push(format!("{{attribute 'optimize' := '{}'}}", name));
+    }
+    if pou.fast_math {
+        pragmas.push("{attribute 'fast_math'}".to_string());
+    }
+    if pou.inline {
+        pragmas.push("{attribute 'inline'}".to_string());
+    }
+    pragmas
+}
+
+fn get_nature_name(nature: &TypeNature) -> &'static str {
+    match nature {
+        TypeNature::Any => "ANY",
+        TypeNature::Derived => "ANY_DERIVED",
+        TypeNature::Elementary => "ANY_ELEMENTARY",
+        TypeNature::Magnitude => "ANY_MAGNITUDE",
+        TypeNature::Num => "ANY_NUM",
+        TypeNature::Real => "ANY_REAL",
+        TypeNature::Int => "ANY_INT",
+        TypeNature::Signed => "ANY_SIGNED",
+        TypeNature::Unsigned => "ANY_UNSIGNED",
+        TypeNature::Duration => "ANY_DURATION",
+        TypeNature::Bit => "ANY_BIT",
+        TypeNature::Chars => "ANY_CHARS",
+        TypeNature::String => "ANY_STRING",
+        TypeNature::Char => "ANY_CHAR",
+        TypeNature::Date => "ANY_DATE",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, FormatOptions, KeywordCase};
+    use crate::dialect::Dialect;
+    use pretty_assertions::*;
+
+    fn format_source(source: &str) -> String {
+        format(source, &FormatOptions::default(), Dialect::Legacy).unwrap()
+    }
+
+    #[test]
+    fn sources_are_printed_in_canonical_layout() {
+        let source = "program  main\nvar x,y : INT := 2; END_VAR\n\
+            x:=(y+1)*2;if x>3 then y:=((x)); end_if\nEND_PROGRAM\n";
+
+        assert_eq!(
+            format_source(source),
+            "PROGRAM main
+    VAR
+        x, y : INT := 2;
+    END_VAR
+    x := (y + 1) * 2;
+    IF x > 3 THEN
+        y := x;
+    END_IF
+END_PROGRAM
+"
+        );
+    }
+
+    #[test]
+    fn comments_are_kept_before_and_behind_their_lines() {
+        let source = "(* header *)
+FUNCTION foo : INT // returns
+VAR_INPUT
+a : INT; // first
+END_VAR
+// body
+foo := a;
+END_FUNCTION
+";
+
+        assert_eq!(
+            format_source(source),
+            "(* header *)
+FUNCTION foo : INT // returns
+    VAR_INPUT
+        a : INT; // first
+    END_VAR
+    // body
+    foo := a;
+END_FUNCTION
+"
+        );
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let source = "TYPE color : (red, green := 2); END_TYPE
+FUNCTION_BLOCK fb VAR_OUTPUT q : ARRAY[0..3] OF color; END_VAR
+FOR i := 0 TO 3 BY 1 DO q[i] := red; END_FOR;
+CASE q[0] OF red: q[1] := green; ELSE RETURN; END_CASE
+END_FUNCTION_BLOCK";
+        let formatted = format_source(source);
+
+        assert_eq!(format_source(&formatted), formatted);
+    }
+
+    #[test]
+    fn keywords_are_printed_in_the_configured_case() {
+        let options = FormatOptions {
+            keyword_case: KeywordCase::Lower,
+            ..FormatOptions::default()
+        };
+        let source = "PROGRAM p VAR b : BOOL; END_VAR b := TRUE AND NOT b; END_PROGRAM";
+
+        assert_eq!(
+            format(source, &options, Dialect::Legacy).unwrap(),
+            "program p
+    var
+        b : BOOL;
+    end_var
+    b := true and not b;
+end_program
+"
+        );
+    }
+
+    #[test]
+    fn calls_exceeding_the_line_width_are_wrapped() {
+        let options = FormatOptions {
+            line_width: 30,
+            ..FormatOptions::default()
+        };
+        let source = "PROGRAM p x := foo(first := 1, second := 2); END_PROGRAM";
+
+        assert_eq!(
+            format(source, &options, Dialect::Legacy).unwrap(),
+            "PROGRAM p
+    x := foo(
+        first := 1,
+        second := 2
+    );
+END_PROGRAM
+"
+        );
+    }
+
+    #[test]
+    fn sources_that_cannot_be_printed_back_are_refused() {
+        let options = FormatOptions::default();
+
+        assert!(format("PROGRAM p x := 1;", &options, Dialect::Legacy).is_err());
+        assert!(format(
+            "NAMESPACE n PROGRAM p END_PROGRAM END_NAMESPACE",
+            &options,
+            Dialect::Legacy
+        )
+        .is_err());
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
     ast::{DirectAccessType, HardwareAccessType},
     diagnostics::{Diagnostic, ErrNo},
     expression_path::ExpressionPath,
-    index::Index,
+    index::{self, Index},
     ConfigFormat,
 };
 
@@ -85,23 +85,31 @@ pub struct HardwareBinding<'idx> {
     direction: HardwareAccessType,
     access_type: DirectAccessType,
     address: Vec<String>,
+    /// the instances bound to an address in a `VAR_CONFIG` block
+    configured: Vec<ExpandedHardwareBinding>,
 }
 impl<'idx> HardwareBinding<'idx> {
     fn expand(&self, index: &'idx Index) -> Vec<ExpandedHardwareBinding> {
         let names = self.qualifed_name.expand(index);
         names
             .iter()
-            .map(|it| ExpandedHardwareBinding {
-                name: it.clone(),
-                direction: self.direction,
-                access_type: self.access_type,
-                address: self.address.clone(),
+            .map(|it| {
+                self.configured
+                    .iter()
+                    .find(|configured| configured.name.eq_ignore_ascii_case(it))
+                    .cloned()
+                    .unwrap_or_else(|| ExpandedHardwareBinding {
+                        name: it.clone(),
+                        direction: self.direction,
+                        access_type: self.access_type,
+                        address: self.address.clone(),
+                    })
             })
             .collect()
     }
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 struct ExpandedHardwareBinding {
     name: String,
     #[serde(flatten)]
@@ -125,34 +133,59 @@ impl SerializeWithContext for HardwareBinding<'_> {
     }
 }
 
-/// Retrieves hardware bindings from all defined instances in the program
+/// resolves the constant entries of the given binding's address
+fn resolve_address(index: &Index, binding: &index::HardwareBinding) -> Result<Vec<String>, String> {
+    binding
+        .entries
+        .iter()
+        .map(|it| {
+            index
+                .get_const_expressions()
+                .get_constant_int_statement_value(it)
+        })
+        .map(|it| it.map(|it| it.to_string()))
+        .collect()
+}
+
+/// Retrieves hardware bindings from all defined instances in the program, the instances of
+/// function block templates configured in `VAR_CONFIG` blocks are bound to their configured address
 pub fn collect_hardware_configuration(index: &Index) -> Result<HardwareConfiguration, Diagnostic> {
-    let conf: Result<Vec<HardwareBinding>, String> = index
-        //Avoid arrays that are not representing structural types
-        .find_instances()
-        .filter(|(_, instance)| instance.has_hardware_binding())
-        .map(|(name, instance)| {
-            let binding = instance
-                .get_hardware_binding()
-                .expect("Instance should have a binding");
-            binding
-                .entries
-                .iter()
-                .map(|it| {
-                    index
-                        .get_const_expressions()
-                        .get_constant_int_statement_value(it)
-                })
-                .map(|it| it.map(|it| it.to_string()))
-                .collect::<Result<Vec<String>, String>>()
-                .map(|address| HardwareBinding {
+    let configured = index
+        .get_config_variables()
+        .values()
+        .map(|it| {
+            resolve_address(index, &it.binding).map(|address| ExpandedHardwareBinding {
+                name: it.reference.clone(),
+                direction: it.binding.direction,
+                access_type: it.binding.access,
+                address,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>();
+    let conf: Result<Vec<HardwareBinding>, String> = configured.and_then(|configured| {
+        index
+            //Avoid arrays that are not representing structural types
+            .find_instances()
+            .filter(|(_, instance)| instance.has_hardware_binding())
+            .map(|(name, instance)| {
+                let binding = instance
+                    .get_hardware_binding()
+                    .expect("Instance should have a binding");
+                let configured = if index.is_function_block_template(instance) {
+                    configured.clone()
+                } else {
+                    vec![]
+                };
+                resolve_address(index, binding).map(|address| HardwareBinding {
                     qualifed_name: name,
                     access_type: binding.access,
                     address,
                     direction: binding.direction,
+                    configured,
                 })
-        })
-        .collect();
+            })
+            .collect()
+    });
 
     conf.map(|hardware_binding| HardwareConfiguration {
         index,
@@ -187,7 +220,11 @@ pub fn generate_hardware_configuration(
 #[cfg(test)]
 mod tests {
     use crate::{
-        hardware_binding::{collect_hardware_configuration, generate_hardware_configuration},
+        ast::{DirectAccessType, HardwareAccessType},
+        hardware_binding::{
+            collect_hardware_configuration, generate_hardware_configuration,
+            ExpandedHardwareBinding,
+        },
         test_utils::tests::index,
         ConfigFormat,
     };
@@ -312,4 +349,40 @@ mod tests {
         let res = generate_hardware_configuration(&config, ConfigFormat::TOML).unwrap();
         insta::assert_snapshot!(res);
     }
+
+    #[test]
+    fn hardware_configured_fb_instances() {
+        let (_, index) = index(
+            "
+        FUNCTION_BLOCK fb
+        VAR
+            a AT %I*: BOOL;
+        END_VAR
+        END_FUNCTION_BLOCK
+        VAR_GLOBAL
+            aFb : ARRAY[0..1] OF fb;
+        END_VAR
+        VAR_CONFIG
+            aFb[1].a AT %IX1.2 : BOOL;
+        END_VAR",
+        );
+        let config = collect_hardware_configuration(&index).unwrap();
+        assert_eq!(
+            config.hardware_binding[0].expand(&index),
+            vec![
+                ExpandedHardwareBinding {
+                    name: "aFb[0].a".into(),
+                    direction: HardwareAccessType::Input,
+                    access_type: DirectAccessType::Template,
+                    address: vec![],
+                },
+                ExpandedHardwareBinding {
+                    name: "aFb[1].a".into(),
+                    direction: HardwareAccessType::Input,
+                    access_type: DirectAccessType::Bit,
+                    address: vec!["1".into(), "2".into()],
+                },
+            ]
+        );
+    }
 }
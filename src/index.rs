@@ -1,12 +1,15 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+use std::{collections::HashSet, ops::Range};
+
 use indexmap::IndexMap;
 
 use crate::{
     ast::{
-        AstStatement, DirectAccessType, GenericBinding, HardwareAccessType, Implementation,
-        LinkageType, PouType, SourceRange, TypeNature,
+        AstStatement, CallingConvention, DirectAccessType, GenericBinding, HardwareAccessType,
+        Implementation, LinkageType, OptimizationHint, PouType, SourceRange, TypeNature,
     },
     builtins::{self, BuiltIn},
+    defines::Defines,
     diagnostics::Diagnostic,
     typesystem::{self, *},
 };
@@ -22,6 +25,25 @@ mod instance_iterator;
 mod tests;
 pub mod visitor;
 
+/// the name of the hidden member of polymorphic classes pointing to their vtable
+pub const VTABLE_MEMBER: &str = "__vtable";
+
+/// the keyword referring to the instance as an instance of its super class (`SUPER^.method()`)
+pub const SUPER_KEYWORD: &str = "SUPER";
+
+/// the keyword referring to the current instance of a method or function block (`THIS^`)
+pub const THIS_KEYWORD: &str = "THIS";
+
+/// an instance specific address configured in a `VAR_CONFIG` block
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConfigVariableEntry {
+    /// the path to the configured variable (e.g. `main.fbs[1].sensor`)
+    pub reference: String,
+    /// the type the configured variable is declared with in the `VAR_CONFIG` block
+    pub data_type_name: String,
+    pub binding: HardwareBinding,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct VariableIndexEntry {
     /// the name of this variable (e.g. 'x' for 'PLC_PRG.x')
@@ -34,6 +56,11 @@ pub struct VariableIndexEntry {
     pub variable_type: ArgumentType,
     /// true if this variable is a compile-time-constant
     is_constant: bool,
+    /// true if this variable keeps its value across restarts (declared in a `RETAIN` block)
+    is_retain: bool,
+    /// true if this variable also keeps its value across downloads (declared in a `PERSISTENT`
+    /// block)
+    is_persistent: bool,
     /// the variable's datatype
     pub data_type_name: String,
     /// the index of the member-variable in it's container (e.g. struct). defautls to 0 (Single variables)
@@ -87,6 +114,28 @@ impl HardwareBinding {
             None
         }
     }
+
+    /// returns true if the binding is located at an address (e.g. `%IX1.2`) instead of being a
+    /// template (`%I*`) whose address is configured later
+    pub fn is_located(&self) -> bool {
+        self.access != DirectAccessType::Template
+    }
+
+    /// returns the bytes of the process image occupied by the bound variable, `None` for
+    /// templates and malformed addresses (see `DirectAccessType::get_image_range`)
+    pub fn get_image_range(&self, index: &Index) -> Option<Range<u64>> {
+        let address = self
+            .entries
+            .iter()
+            .map(|it| {
+                index
+                    .get_const_expressions()
+                    .get_constant_int_statement_value(it)
+                    .ok()
+            })
+            .collect::<Option<Vec<_>>>()?;
+        self.access.get_image_range(&address)
+    }
 }
 
 #[derive(Debug)]
@@ -97,6 +146,7 @@ pub struct MemberInfo<'b> {
     variable_type_name: &'b str,
     binding: Option<HardwareBinding>,
     is_constant: bool,
+    is_retain: bool,
 }
 
 impl VariableIndexEntry {
@@ -114,6 +164,8 @@ impl VariableIndexEntry {
             initial_value: None,
             variable_type,
             is_constant: false,
+            is_retain: false,
+            is_persistent: false,
             data_type_name: data_type_name.to_string(),
             location_in_parent,
             linkage: LinkageType::Internal,
@@ -134,6 +186,8 @@ impl VariableIndexEntry {
             initial_value: None,
             variable_type: ArgumentType::ByVal(VariableType::Global),
             is_constant: false,
+            is_retain: false,
+            is_persistent: false,
             data_type_name: data_type_name.to_string(),
             location_in_parent: 0,
             linkage: LinkageType::Internal,
@@ -157,6 +211,16 @@ impl VariableIndexEntry {
         self
     }
 
+    pub fn set_retain(mut self, is_retain: bool) -> Self {
+        self.is_retain = is_retain;
+        self
+    }
+
+    pub fn set_persistent(mut self, is_persistent: bool) -> Self {
+        self.is_persistent = is_persistent;
+        self
+    }
+
     pub fn set_hardware_binding(mut self, binding: Option<HardwareBinding>) -> Self {
         self.binding = binding;
         self
@@ -209,10 +273,23 @@ impl VariableIndexEntry {
         self.is_constant
     }
 
+    pub fn is_retain(&self) -> bool {
+        self.is_retain
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.is_persistent
+    }
+
     pub fn is_external(&self) -> bool {
         self.linkage == LinkageType::External
     }
 
+    /// returns true if this variable may be replaced by another definition at link time
+    pub fn is_weak(&self) -> bool {
+        self.linkage == LinkageType::Weak
+    }
+
     pub fn get_variable_type(&self) -> VariableType {
         self.variable_type.get_variable_type()
     }
@@ -298,6 +375,7 @@ pub enum ImplementationType {
     Action,
     Class,
     Method,
+    Interface,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -346,6 +424,7 @@ impl From<&PouType> for ImplementationType {
             PouType::Action => ImplementationType::Action,
             PouType::Class => ImplementationType::Class,
             PouType::Method { .. } => ImplementationType::Method,
+            PouType::Interface => ImplementationType::Interface,
         }
     }
 }
@@ -362,6 +441,7 @@ pub enum PouIndexEntry {
         name: String,
         instance_struct_name: String,
         linkage: LinkageType,
+        interfaces: Vec<String>,
     },
     Function {
         name: String,
@@ -375,6 +455,9 @@ pub enum PouIndexEntry {
         name: String,
         instance_struct_name: String,
         linkage: LinkageType,
+        super_class: Option<String>,
+        interfaces: Vec<String>,
+        is_abstract: bool,
     },
     Method {
         name: String,
@@ -382,6 +465,7 @@ pub enum PouIndexEntry {
         return_type: String,
         instance_struct_name: String,
         linkage: LinkageType,
+        is_abstract: bool,
     },
     Action {
         name: String,
@@ -389,6 +473,11 @@ pub enum PouIndexEntry {
         instance_struct_name: String,
         linkage: LinkageType,
     },
+    Interface {
+        name: String,
+        instance_struct_name: String,
+        linkage: LinkageType,
+    },
 }
 
 impl PouIndexEntry {
@@ -413,11 +502,17 @@ impl PouIndexEntry {
     /// # Arguments
     /// - `name` the name of the FunctionBlock
     /// - `linkage` the linkage type of the pou
-    pub fn create_function_block_entry(pou_name: &str, linkage: LinkageType) -> PouIndexEntry {
+    /// - `interfaces` the names of the interfaces the FunctionBlock implements
+    pub fn create_function_block_entry(
+        pou_name: &str,
+        linkage: LinkageType,
+        interfaces: &[String],
+    ) -> PouIndexEntry {
         PouIndexEntry::FunctionBlock {
             name: pou_name.into(),
             instance_struct_name: pou_name.into(),
             linkage,
+            interfaces: interfaces.to_vec(),
         }
     }
 
@@ -462,11 +557,34 @@ impl PouIndexEntry {
     /// creates a new Class-PouIndexEntry
     /// # Arguments
     /// - `name` the name of the Class
-    pub fn create_class_entry(pou_name: &str, linkage: LinkageType) -> PouIndexEntry {
+    /// - `super_class` the name of the class this class extends
+    /// - `interfaces` the names of the interfaces the Class implements
+    /// - `is_abstract` whether the Class is declared `ABSTRACT`
+    pub fn create_class_entry(
+        pou_name: &str,
+        linkage: LinkageType,
+        super_class: Option<&str>,
+        interfaces: &[String],
+        is_abstract: bool,
+    ) -> PouIndexEntry {
         PouIndexEntry::Class {
             name: pou_name.into(),
             instance_struct_name: pou_name.into(),
             linkage,
+            super_class: super_class.map(Into::into),
+            interfaces: interfaces.to_vec(),
+            is_abstract,
+        }
+    }
+
+    /// creates a new Interface-PouIndexEntry
+    /// # Arguments
+    /// - `name` the name of the Interface
+    pub fn create_interface_entry(pou_name: &str, linkage: LinkageType) -> PouIndexEntry {
+        PouIndexEntry::Interface {
+            name: pou_name.into(),
+            instance_struct_name: pou_name.into(),
+            linkage,
         }
     }
 
@@ -475,11 +593,13 @@ impl PouIndexEntry {
     /// - `name` the name of the method (without the pou-qualifier)
     /// - `return_type` the name of the method's return type
     /// - `owner_class` the name of the parent pou
+    /// - `is_abstract` whether the method is declared `ABSTRACT`
     pub fn create_method_entry(
         name: &str,
         return_type: &str,
         owner_class: &str,
         linkage: LinkageType,
+        is_abstract: bool,
     ) -> PouIndexEntry {
         PouIndexEntry::Method {
             name: name.into(),
@@ -487,6 +607,7 @@ impl PouIndexEntry {
             instance_struct_name: name.into(),
             return_type: return_type.into(),
             linkage,
+            is_abstract,
         }
     }
 
@@ -498,7 +619,8 @@ impl PouIndexEntry {
             | PouIndexEntry::Function { name, .. }
             | PouIndexEntry::Method { name, .. }
             | PouIndexEntry::Action { name, .. }
-            | PouIndexEntry::Class { name, .. } => name,
+            | PouIndexEntry::Class { name, .. }
+            | PouIndexEntry::Interface { name, .. } => name,
         }
     }
 
@@ -525,6 +647,10 @@ impl PouIndexEntry {
             | PouIndexEntry::Class {
                 instance_struct_name,
                 ..
+            }
+            | PouIndexEntry::Interface {
+                instance_struct_name,
+                ..
             } => Some(instance_struct_name.as_str()),
             _ => None, //functions have no struct type
         }
@@ -555,6 +681,7 @@ impl PouIndexEntry {
             PouIndexEntry::Program { .. }
             | PouIndexEntry::FunctionBlock { .. }
             | PouIndexEntry::Class { .. }
+            | PouIndexEntry::Interface { .. }
             | PouIndexEntry::Function { .. } => self.get_name(),
             PouIndexEntry::Action {
                 parent_pou_name, ..
@@ -573,6 +700,25 @@ impl PouIndexEntry {
         index.find_implementation_by_name(self.get_name())
     }
 
+    /// returns the name of the class this class extends or `None` if this is no class or it
+    /// extends no other class
+    pub fn get_super_class(&self) -> Option<&str> {
+        if let PouIndexEntry::Class { super_class, .. } = self {
+            super_class.as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// returns the names of the interfaces implemented by this class or function block
+    pub fn get_interfaces(&self) -> &[String] {
+        match self {
+            PouIndexEntry::Class { interfaces, .. }
+            | PouIndexEntry::FunctionBlock { interfaces, .. } => interfaces.as_slice(),
+            _ => &[],
+        }
+    }
+
     /// returns the linkage type of this pou
     pub fn get_linkage(&self) -> &LinkageType {
         match self {
@@ -581,10 +727,25 @@ impl PouIndexEntry {
             | PouIndexEntry::Function { linkage, .. }
             | PouIndexEntry::Method { linkage, .. }
             | PouIndexEntry::Action { linkage, .. }
-            | PouIndexEntry::Class { linkage, .. } => linkage,
+            | PouIndexEntry::Class { linkage, .. }
+            | PouIndexEntry::Interface { linkage, .. } => linkage,
         }
     }
 
+    /// returns true if this pou is a class or method declared `ABSTRACT`, otherwise false
+    pub fn is_abstract(&self) -> bool {
+        matches!(
+            self,
+            PouIndexEntry::Class {
+                is_abstract: true,
+                ..
+            } | PouIndexEntry::Method {
+                is_abstract: true,
+                ..
+            }
+        )
+    }
+
     /// returns true if this pou is a function with generic parameters, otherwise false
     pub fn is_generic(&self) -> bool {
         if let PouIndexEntry::Function { generics, .. } = self {
@@ -669,14 +830,19 @@ impl TypeIndex {
         &'ret self,
         data_type: &'ret DataType,
     ) -> Option<&'ret DataType> {
-        match data_type.get_type_information() {
-            DataTypeInformation::Alias {
-                referenced_type, ..
-            } => self
-                .find_type(referenced_type)
-                .and_then(|it| self.find_effective_type(it)),
-            _ => Some(data_type),
+        //aliases referring to each other are reported by the validation, stop once we went around
+        let mut visited = HashSet::new();
+        let mut current = data_type;
+        while let DataTypeInformation::Alias {
+            referenced_type, ..
+        } = current.get_type_information()
+        {
+            if !visited.insert(current.get_name()) {
+                return None;
+            }
+            current = self.find_type(referenced_type)?;
         }
+        Some(current)
     }
 }
 
@@ -710,6 +876,25 @@ pub struct Index {
     type_index: TypeIndex,
 
     constant_expressions: ConstExpressions,
+
+    /// the symbols defined on the command line and the compiler's options
+    defines: Defines,
+
+    /// the optimizations requested by `{attribute 'optimize' := '...'}`, by the POU's name
+    optimization_hints: IndexMap<String, OptimizationHint>,
+
+    /// the POUs requesting fast floating point arithmetic with `{attribute 'fast_math'}`
+    fast_math_pous: HashSet<String>,
+
+    /// the expressions replacing the calls of the functions marked with `{attribute 'inline'}`, by
+    /// the function's name
+    inline_expressions: IndexMap<String, AstStatement>,
+
+    /// the calling conventions requested by `{external '...'}`, by the function's name
+    calling_conventions: IndexMap<String, CallingConvention>,
+
+    /// the addresses configured in `VAR_CONFIG` blocks, by the path of the configured variable
+    config_variables: IndexMap<String, ConfigVariableEntry>,
 }
 
 impl Index {
@@ -786,6 +971,16 @@ impl Index {
 
         //pous
         self.pous.extend(other.pous);
+        self.optimization_hints.extend(other.optimization_hints);
+        self.fast_math_pous.extend(other.fast_math_pous);
+        self.inline_expressions.extend(other.inline_expressions);
+        self.calling_conventions.extend(other.calling_conventions);
+
+        //config variables
+        for (reference, mut e) in other.config_variables.drain(..) {
+            e.binding = self.transfer_binding(&e.binding, &mut other.constant_expressions);
+            self.config_variables.insert(reference, e);
+        }
 
         //Constant expressions are intentionally not imported
         // self.constant_expressions.import(other.constant_expressions)
@@ -798,31 +993,31 @@ impl Index {
     ) -> VariableIndexEntry {
         variable.initial_value = self.maybe_import_const_expr(import_from, &variable.initial_value);
 
-        let binding = if let Some(HardwareBinding {
-            direction,
-            access,
-            entries,
-            location,
-        }) = variable.get_hardware_binding()
-        {
-            let mut new_entries = vec![];
-            for entry in entries {
-                if let Some(e) = self.maybe_import_const_expr(import_from, &Some(*entry)) {
-                    new_entries.push(e);
-                }
-            }
-            Some(HardwareBinding {
-                direction: *direction,
-                access: *access,
-                entries: new_entries,
-                location: location.clone(),
-            })
-        } else {
-            None
-        };
+        let binding = variable
+            .get_hardware_binding()
+            .map(|it| self.transfer_binding(it, import_from));
         variable.set_hardware_binding(binding)
     }
 
+    fn transfer_binding(
+        &mut self,
+        binding: &HardwareBinding,
+        import_from: &mut ConstExpressions,
+    ) -> HardwareBinding {
+        let mut new_entries = vec![];
+        for entry in &binding.entries {
+            if let Some(e) = self.maybe_import_const_expr(import_from, &Some(*entry)) {
+                new_entries.push(e);
+            }
+        }
+        HardwareBinding {
+            direction: binding.direction,
+            access: binding.access,
+            entries: new_entries,
+            location: binding.location.clone(),
+        }
+    }
+
     /// imports the corresponding const-expression (according to the given initializer-id) from the given ConstExpressions
     /// into self's const-expressions and returns the new Id
     fn maybe_import_const_expr(
@@ -917,6 +1112,14 @@ impl Index {
         &self,
         fully_qualified_name: &str,
     ) -> Option<&VariableIndexEntry> {
+        //the container may be qualified with its namespace (e.g. "plant.PLC_PRG.x")
+        let member = fully_qualified_name
+            .rsplit_once('.')
+            .and_then(|(container, name)| self.find_member(container, name))
+            .or_else(|| self.find_global_variable(fully_qualified_name));
+        if member.is_some() {
+            return member;
+        }
         let segments: Vec<&str> = fully_qualified_name.split('.').collect();
         let (q, segments) = if segments.len() > 1 {
             (
@@ -971,6 +1174,258 @@ impl Index {
             .get(&qualified_name.to_lowercase())
     }
 
+    /// returns the names of the given class' super classes, starting with the class it extends.
+    /// The hierarchy ends at a class extending no (or an unknown) class or before a class that is
+    /// already part of it (cyclic inheritance)
+    pub fn get_super_classes(&self, class_name: &str) -> Vec<&str> {
+        let mut super_classes: Vec<&str> = vec![];
+        let mut super_class = self
+            .find_pou(class_name)
+            .and_then(PouIndexEntry::get_super_class)
+            .and_then(|it| self.find_pou(it))
+            .filter(|it| matches!(it, PouIndexEntry::Class { .. }));
+        while let Some(class) = super_class {
+            let name = class.get_name();
+            if name.eq_ignore_ascii_case(class_name)
+                || super_classes.iter().any(|it| it.eq_ignore_ascii_case(name))
+            {
+                break;
+            }
+            super_classes.push(name);
+            super_class = class
+                .get_super_class()
+                .and_then(|it| self.find_pou(it))
+                .filter(|it| matches!(it, PouIndexEntry::Class { .. }));
+        }
+        super_classes
+    }
+
+    /// returns whether the given pou (or the pou declaring the given method or action) is marked
+    /// `{attribute 'weak'}`, so its implementation may be replaced by another one at link time
+    pub fn is_weak_pou(&self, pou_name: &str) -> bool {
+        self.find_pou(pou_name).map_or(false, |pou| {
+            std::iter::once(pou)
+                .chain(self.find_pou(pou.get_container()))
+                .any(|it| it.get_linkage() == &LinkageType::Weak)
+        })
+    }
+
+    /// returns the name of the class or function block `THIS` points to inside of the given pou:
+    /// the function block itself or the class or function block declaring the given method or
+    /// action
+    pub fn find_type_of_this(&self, pou_name: &str) -> Option<&str> {
+        self.find_pou(pou_name)
+            .and_then(|it| self.find_pou(it.get_container()))
+            .filter(|it| {
+                matches!(
+                    it,
+                    PouIndexEntry::FunctionBlock { .. } | PouIndexEntry::Class { .. }
+                )
+            })
+            .map(PouIndexEntry::get_name)
+    }
+
+    /// returns the name of the class extended by the class declaring the given method, this is
+    /// the class `SUPER` refers to inside of the method
+    pub fn find_super_class_of_method(&self, method_name: &str) -> Option<&str> {
+        self.find_pou(method_name)
+            .filter(|it| matches!(it, PouIndexEntry::Method { .. }))
+            .and_then(|it| self.get_super_classes(it.get_container()).first().copied())
+    }
+
+    /// returns the method `method_name` of the given class or, if the class does not declare
+    /// it, the method inherited from the closest super class
+    pub fn find_method(&self, class_name: &str, method_name: &str) -> Option<&PouIndexEntry> {
+        std::iter::once(class_name)
+            .chain(self.get_super_classes(class_name))
+            .find_map(|class| self.find_pou(&format!("{}.{}", class, method_name)))
+    }
+
+    /// returns the method prototypes declared by the given interface in their declaration order
+    pub fn get_interface_methods(&self, interface_name: &str) -> Vec<&PouIndexEntry> {
+        self.get_methods(interface_name)
+    }
+
+    /// returns the methods declared by the given class, function block or interface
+    pub fn get_methods(&self, container_name: &str) -> Vec<&PouIndexEntry> {
+        self.pous
+            .values()
+            .filter(|it| {
+                matches!(it, PouIndexEntry::Method { parent_pou_name, .. }
+                    if parent_pou_name.eq_ignore_ascii_case(container_name))
+            })
+            .collect()
+    }
+
+    /// returns whether the given class is abstract, extends or is extended by another class. The
+    /// methods of these classes are called through the vtable of the instance, so a call on a
+    /// reference to a super class calls the methods overridden by the instance's actual class
+    pub fn is_polymorphic(&self, class_name: &str) -> bool {
+        self.find_pou(class_name)
+            .map_or(false, PouIndexEntry::is_abstract)
+            || !self.get_super_classes(class_name).is_empty()
+            || self.pous.values().any(|it| {
+                matches!(it, PouIndexEntry::Class { .. })
+                    && !it.get_name().eq_ignore_ascii_case(class_name)
+                    && self
+                        .get_super_classes(it.get_name())
+                        .iter()
+                        .any(|super_class| super_class.eq_ignore_ascii_case(class_name))
+            })
+    }
+
+    /// returns the methods in the vtable of the given class. The methods of the super classes
+    /// come first, so the vtable of a super class is a prefix of the vtable of the extending
+    /// class. An overriding method takes the place of the method it overrides.
+    pub fn get_virtual_methods(&self, class_name: &str) -> Vec<&PouIndexEntry> {
+        let mut hierarchy = self.get_super_classes(class_name);
+        hierarchy.reverse();
+        hierarchy.push(class_name);
+
+        let mut method_names: Vec<&str> = vec![];
+        for class in hierarchy {
+            for method in self.get_methods(class) {
+                let name = crate::ast::Pou::calc_return_name(method.get_name());
+                if !method_names.iter().any(|it| it.eq_ignore_ascii_case(name)) {
+                    method_names.push(name);
+                }
+            }
+        }
+        method_names
+            .into_iter()
+            .filter_map(|it| self.find_method(class_name, it))
+            .collect()
+    }
+
+    /// merges the members of the super classes into the containers of the classes extending
+    /// them. The inherited members precede the class' own members, so an instance of the super
+    /// class is a prefix of an instance of the extending class. A member redeclared by the
+    /// extending class is ignored in favor of the inherited one.
+    pub fn merge_inherited_members(&mut self) {
+        self.add_vtable_members();
+
+        let classes: Vec<String> = self
+            .pous
+            .values()
+            .filter(|it| it.get_super_class().is_some())
+            .map(|it| it.get_name().to_string())
+            .collect();
+
+        //collect all merged containers before replacing any, so only declared members are merged
+        let merged_containers: Vec<(String, IndexMap<String, VariableIndexEntry>)> = classes
+            .iter()
+            .map(|class| {
+                let mut hierarchy = self.get_super_classes(class);
+                hierarchy.reverse();
+                hierarchy.push(class.as_str());
+
+                let mut members = IndexMap::new();
+                for container in hierarchy {
+                    for member in self.get_container_members(container) {
+                        let name = member.get_name().to_lowercase();
+                        if !members.contains_key(&name) {
+                            let location_in_parent = members.len() as u32;
+                            let entry = VariableIndexEntry {
+                                qualified_name: format!("{}.{}", class, member.get_name()),
+                                location_in_parent,
+                                ..member.clone()
+                            };
+                            members.insert(name, entry);
+                        }
+                    }
+                }
+                (class.to_lowercase(), members)
+            })
+            .collect();
+
+        for (class, members) in merged_containers {
+            if let Some(DataTypeInformation::Struct { member_names, .. }) = self
+                .type_index
+                .pou_types
+                .get_mut(&class)
+                .map(|it| &mut it.information)
+            {
+                *member_names = members
+                    .values()
+                    .map(|it| it.get_name().to_string())
+                    .collect();
+            }
+            self.member_variables.insert(class, members);
+        }
+    }
+
+    /// adds the hidden vtable member as the first member of every polymorphic class that extends
+    /// no other class, the classes extending it inherit the member
+    fn add_vtable_members(&mut self) {
+        let root_classes: Vec<String> = self
+            .pous
+            .values()
+            .filter(|it| matches!(it, PouIndexEntry::Class { .. }))
+            .map(|it| it.get_name())
+            .filter(|it| self.get_super_classes(it).is_empty() && self.is_polymorphic(it))
+            .map(|it| it.to_lowercase())
+            .collect();
+        if root_classes.is_empty() {
+            return;
+        }
+
+        self.register_type(DataType {
+            name: VTABLE_POINTER_TYPE.into(),
+            initial_value: None,
+            information: DataTypeInformation::Pointer {
+                name: VTABLE_POINTER_TYPE.into(),
+                inner_type_name: BYTE_TYPE.into(),
+                auto_deref: false,
+            },
+            nature: TypeNature::Any,
+        });
+
+        for class in root_classes {
+            let declared_members = self.member_variables.remove(&class).unwrap_or_default();
+            if declared_members.contains_key(VTABLE_MEMBER) {
+                self.member_variables.insert(class, declared_members);
+                continue;
+            }
+
+            let class_name = self
+                .find_pou(&class)
+                .map(|it| it.get_name().to_string())
+                .unwrap_or_else(|| class.clone());
+            let mut members = IndexMap::new();
+            members.insert(
+                VTABLE_MEMBER.to_string(),
+                VariableIndexEntry::new(
+                    VTABLE_MEMBER,
+                    &format!("{}.{}", class_name, VTABLE_MEMBER),
+                    VTABLE_POINTER_TYPE,
+                    ArgumentType::ByVal(VariableType::Local),
+                    0,
+                    SourceRange::undefined(),
+                ),
+            );
+            for (name, member) in declared_members {
+                let location_in_parent = member.location_in_parent + 1;
+                members.insert(
+                    name,
+                    VariableIndexEntry {
+                        location_in_parent,
+                        ..member
+                    },
+                );
+            }
+
+            if let Some(DataTypeInformation::Struct { member_names, .. }) = self
+                .type_index
+                .pou_types
+                .get_mut(&class)
+                .map(|it| &mut it.information)
+            {
+                member_names.insert(0, VTABLE_MEMBER.to_string());
+            }
+            self.member_variables.insert(class, members);
+        }
+    }
+
     /// returns all member variables of the given container (e.g. FUNCTION, PROGRAM, STRUCT, etc.)
     pub fn get_container_members(&self, container_name: &str) -> Vec<&VariableIndexEntry> {
         self.member_variables
@@ -1045,6 +1500,38 @@ impl Index {
         }
     }
 
+    /// returns true if the type with the given name or any of its members (recursively)
+    /// declares an initial value, the default value of all other types is zero
+    pub fn has_declared_initial_value(&self, type_name: &str) -> bool {
+        let data_type = match self.type_index.find_type(type_name) {
+            Some(data_type) => data_type,
+            None => return false,
+        };
+        if data_type.initial_value.is_some() {
+            return true;
+        }
+        match data_type.get_type_information() {
+            DataTypeInformation::Struct { .. } => self
+                .get_container_members(data_type.get_name())
+                .iter()
+                .filter(|it| !it.is_temp())
+                .any(|it| {
+                    it.initial_value.is_some()
+                        || self.has_declared_initial_value(it.get_type_name())
+                }),
+            DataTypeInformation::Array {
+                inner_type_name, ..
+            } => self.has_declared_initial_value(inner_type_name),
+            DataTypeInformation::Alias {
+                referenced_type, ..
+            }
+            | DataTypeInformation::SubRange {
+                referenced_type, ..
+            } => self.has_declared_initial_value(referenced_type),
+            _ => false,
+        }
+    }
+
     pub fn get_type(&self, type_name: &str) -> Result<&DataType, Diagnostic> {
         self.type_index.get_type(type_name)
     }
@@ -1091,6 +1578,82 @@ impl Index {
         &self.global_variables
     }
 
+    /// returns the global variables and the variables of programs located at a hardware address
+    /// (e.g. `AT %QW4`) with their binding and the bytes they occupy in their process image
+    pub fn find_located_variables(
+        &self,
+    ) -> Vec<(&VariableIndexEntry, &HardwareBinding, Range<u64>)> {
+        let program_variables = self
+            .pous
+            .values()
+            .filter(|it| matches!(it, PouIndexEntry::Program { .. }))
+            .flat_map(|it| self.get_container_members(it.get_name()));
+        self.global_variables
+            .values()
+            .chain(program_variables)
+            .filter_map(|variable| {
+                let binding = variable.get_hardware_binding()?;
+                let range = binding.get_image_range(self)?;
+                Some((variable, binding, range))
+            })
+            .collect()
+    }
+
+    pub fn register_config_variable(&mut self, entry: ConfigVariableEntry) {
+        self.config_variables
+            .insert(entry.reference.to_lowercase(), entry);
+    }
+
+    /// returns the address configured for the instance variable with the given path
+    pub fn find_config_variable(&self, reference: &str) -> Option<&ConfigVariableEntry> {
+        self.config_variables.get(&reference.to_lowercase())
+    }
+
+    pub fn get_config_variables(&self) -> &IndexMap<String, ConfigVariableEntry> {
+        &self.config_variables
+    }
+
+    /// returns the instance variable with the given path (e.g. `main.fbs[1].sensor`)
+    pub fn find_instance_variable(&self, reference: &str) -> Option<&VariableIndexEntry> {
+        self.find_instances()
+            .find(|(path, _)| {
+                path.expand(self)
+                    .iter()
+                    .any(|it| it.eq_ignore_ascii_case(reference))
+            })
+            .map(|(_, variable)| variable)
+    }
+
+    /// returns the instances of function block variables declared with a template address
+    /// (e.g. `AT %I*`) which are not configured in a `VAR_CONFIG` block, by their path
+    ///
+    /// the templates of global and program variables are bound by the hardware configuration
+    pub fn find_unbound_templates(&self) -> Vec<(String, &VariableIndexEntry)> {
+        self.find_instances()
+            .filter(|(_, variable)| self.is_function_block_template(variable))
+            .flat_map(|(path, variable)| {
+                path.expand(self).into_iter().map(move |it| (it, variable))
+            })
+            .filter(|(path, _)| self.find_config_variable(path).is_none())
+            .collect()
+    }
+
+    /// returns true if the given variable is a member of a function block declared with a
+    /// template address (e.g. `AT %I*`)
+    pub fn is_function_block_template(&self, variable: &VariableIndexEntry) -> bool {
+        let is_template = variable
+            .get_hardware_binding()
+            .map_or(false, |it| !it.is_located());
+        is_template
+            && variable
+                .get_qualified_name()
+                .rsplit_once('.')
+                .and_then(|(container, _)| self.find_pou(container))
+                .map_or(false, |it| {
+                    matches!(it, PouIndexEntry::FunctionBlock { .. })
+                })
+    }
+
     pub fn get_program_instances(&self) -> Vec<&VariableIndexEntry> {
         self.pous
             .values()
@@ -1209,6 +1772,7 @@ impl Index {
             source_location,
         )
         .set_constant(member_info.is_constant)
+        .set_retain(member_info.is_retain)
         .set_initial_value(initial_value)
         .set_hardware_binding(member_info.binding);
 
@@ -1289,6 +1853,76 @@ impl Index {
         &self.constant_expressions
     }
 
+    /// returns the symbols defined on the command line and the compiler's options queried by
+    /// `__DEFINED` and `__OPTION`
+    pub fn get_defines(&self) -> &Defines {
+        &self.defines
+    }
+
+    pub fn set_defines(&mut self, defines: Defines) {
+        self.defines = defines;
+    }
+
+    pub fn register_optimization_hint(&mut self, pou_name: &str, hint: OptimizationHint) {
+        self.optimization_hints
+            .insert(pou_name.to_lowercase(), hint);
+    }
+
+    /// returns the optimization requested for the given pou, methods and actions are optimized
+    /// like the pou declaring them unless they request an optimization of their own
+    pub fn find_optimization_hint(&self, pou_name: &str) -> Option<OptimizationHint> {
+        self.optimization_hints
+            .get(&pou_name.to_lowercase())
+            .or_else(|| {
+                let container = self.find_pou(pou_name)?.get_container();
+                self.optimization_hints.get(&container.to_lowercase())
+            })
+            .copied()
+    }
+
+    pub fn register_calling_convention(&mut self, pou_name: &str, convention: CallingConvention) {
+        self.calling_conventions
+            .insert(pou_name.to_lowercase(), convention);
+    }
+
+    /// returns the calling convention of the given external function, `None` if it is called like
+    /// the functions compiled by rusty
+    pub fn find_calling_convention(&self, pou_name: &str) -> Option<CallingConvention> {
+        self.calling_conventions
+            .get(&pou_name.to_lowercase())
+            .copied()
+    }
+
+    pub fn register_fast_math(&mut self, pou_name: &str) {
+        self.fast_math_pous.insert(pou_name.to_lowercase());
+    }
+
+    /// returns true if the given pou requested fast floating point arithmetic, methods and actions
+    /// compute like the pou declaring them
+    pub fn is_fast_math(&self, pou_name: &str) -> bool {
+        self.fast_math_pous.contains(&pou_name.to_lowercase())
+            || self.find_pou(pou_name).map_or(false, |it| {
+                self.fast_math_pous
+                    .contains(&it.get_container().to_lowercase())
+            })
+    }
+
+    /// returns true if any pou requested fast floating point arithmetic
+    pub fn has_fast_math(&self) -> bool {
+        !self.fast_math_pous.is_empty()
+    }
+
+    pub fn register_inline_expression(&mut self, pou_name: &str, expression: AstStatement) {
+        self.inline_expressions
+            .insert(pou_name.to_lowercase(), expression);
+    }
+
+    /// returns the expression replacing the calls of the given function, `None` if it is not
+    /// inlined
+    pub fn find_inline_expression(&self, pou_name: &str) -> Option<&AstStatement> {
+        self.inline_expressions.get(&pou_name.to_lowercase())
+    }
+
     /// returns the intrinsic (built-in) type represented by the given type-information
     /// this will return the built-in type behind alias and range-types
     pub fn find_intrinsic_type<'idx>(
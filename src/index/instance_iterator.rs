@@ -38,16 +38,8 @@ impl<'idx> InstanceIterator<'idx> {
                     .iter()
                     .map(|(_, it)| it)
                     .chain(index.get_program_instances().into_iter())
-                    .map(|it| {
-                        (
-                            it.get_qualified_name()
-                                .split('.')
-                                .last()
-                                .expect("Variable needs a name")
-                                .into(),
-                            it,
-                        )
-                    }),
+                    //the names of globals and programs may be qualified with their namespace
+                    .map(|it| (it.get_qualified_name().into(), it)),
             )) as Box<dyn Iterator<Item = InstanceEntry<'idx>>>,
             inner: None,
             filter: |_, _| true,
@@ -67,16 +59,8 @@ impl<'idx> InstanceIterator<'idx> {
                     .iter()
                     .map(|(_, it)| it)
                     .chain(index.get_program_instances().into_iter())
-                    .map(|it| {
-                        (
-                            it.get_qualified_name()
-                                .split('.')
-                                .last()
-                                .expect("Variable needs a name")
-                                .into(),
-                            it,
-                        )
-                    }),
+                    //the names of globals and programs may be qualified with their namespace
+                    .map(|it| (it.get_qualified_name().into(), it)),
             )) as Box<dyn Iterator<Item = InstanceEntry<'idx>>>,
 
             inner: None,
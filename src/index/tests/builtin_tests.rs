@@ -20,3 +20,35 @@ fn test_indexer_has_builtins() {
     assert!(index.find_implementation_by_name("ADR").is_some());
     assert!(index.find_implementation_by_name("REF").is_some());
 }
+
+#[test]
+fn string_conversions_are_builtins() {
+    let (_, index) = index("");
+    assert!(index.get_builtin_function("INT_TO_STRING").is_some());
+    assert!(index.get_builtin_function("wstring_to_lreal").is_some());
+    assert!(index.find_member("STRING_TO_DINT", "error").is_some());
+    assert_eq!(
+        index
+            .find_return_type("REAL_TO_WSTRING")
+            .map(|it| it.get_name()),
+        Some("WSTRING")
+    );
+}
+
+#[test]
+fn builtins_are_declared_in_the_order_of_their_names() {
+    let first = builtins::parse_built_ins(IdProvider::default());
+    let second = builtins::parse_built_ins(IdProvider::default());
+
+    let names = |unit: &crate::ast::CompilationUnit| {
+        unit.units
+            .iter()
+            .map(|it| it.name.clone())
+            .collect::<Vec<_>>()
+    };
+    let mut sorted = names(&first);
+    sorted.sort();
+    assert_eq!(names(&first), sorted);
+    // the ids of the declarations do not depend on the process
+    assert_eq!(first, second);
+}
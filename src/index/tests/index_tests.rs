@@ -210,6 +210,169 @@ fn class_methods_are_indexed() {
     }
 }
 
+#[test]
+fn inherited_members_precede_the_members_of_an_extending_class() {
+    let (_, index) = index(
+        r#"
+        CLASS Motor EXTENDS Drive
+            VAR rpm : DINT; END_VAR
+        END_CLASS
+
+        CLASS Drive EXTENDS Device
+            VAR speed : INT; enabled : BOOL; END_VAR
+        END_CLASS
+
+        CLASS Device
+            VAR id : UDINT; END_VAR
+        END_CLASS
+    "#,
+    );
+
+    assert_eq!(vec!["Drive", "Device"], index.get_super_classes("Motor"));
+    let members: Vec<(&str, u32)> = index
+        .get_container_members("Motor")
+        .iter()
+        .map(|it| (it.get_qualified_name(), it.get_location_in_parent()))
+        .collect();
+    assert_eq!(
+        vec![
+            ("Motor.__vtable", 0),
+            ("Motor.id", 1),
+            ("Motor.speed", 2),
+            ("Motor.enabled", 3),
+            ("Motor.rpm", 4)
+        ],
+        members
+    );
+    assert_eq!(
+        "INT",
+        index.find_member("Motor", "speed").unwrap().get_type_name()
+    );
+    if let crate::typesystem::DataTypeInformation::Struct { member_names, .. } =
+        index.get_type("Motor").unwrap().get_type_information()
+    {
+        assert_eq!(
+            &vec!["__vtable", "id", "speed", "enabled", "rpm"],
+            member_names
+        );
+    }
+
+    //the super class only consists of its own and its inherited members
+    let members: Vec<&str> = index
+        .get_container_members("Drive")
+        .iter()
+        .map(|it| it.get_name())
+        .collect();
+    assert_eq!(vec!["__vtable", "id", "speed", "enabled"], members);
+}
+
+#[test]
+fn overriding_methods_take_the_vtable_slot_of_the_overridden_method() {
+    let (_, index) = index(
+        r#"
+        CLASS Drive
+            VAR speed : INT; END_VAR
+            METHOD start END_METHOD
+            METHOD stop END_METHOD
+        END_CLASS
+
+        CLASS Motor EXTENDS Drive
+            METHOD run END_METHOD
+            METHOD OVERRIDE stop END_METHOD
+        END_CLASS
+
+        CLASS Standalone
+            VAR speed : INT; END_VAR
+            METHOD stop END_METHOD
+        END_CLASS
+    "#,
+    );
+
+    //the methods of the super class come first
+    let methods: Vec<&str> = index
+        .get_virtual_methods("Motor")
+        .iter()
+        .map(|it| it.get_name())
+        .collect();
+    assert_eq!(vec!["Drive.start", "Motor.stop", "Motor.run"], methods);
+    let methods: Vec<&str> = index
+        .get_virtual_methods("Drive")
+        .iter()
+        .map(|it| it.get_name())
+        .collect();
+    assert_eq!(vec!["Drive.start", "Drive.stop"], methods);
+
+    //only classes of a hierarchy point to a vtable
+    assert!(index.is_polymorphic("Drive") && index.is_polymorphic("Motor"));
+    assert!(!index.is_polymorphic("Standalone"));
+    assert_eq!(
+        "__VTABLE_POINTER",
+        index
+            .find_member("Drive", "__vtable")
+            .unwrap()
+            .get_type_name()
+    );
+    assert!(index.find_member("Standalone", "__vtable").is_none());
+}
+
+#[test]
+fn cyclic_inheritance_ends_the_class_hierarchy() {
+    let (_, index) = index(
+        r#"
+        CLASS a EXTENDS b VAR x : INT; END_VAR END_CLASS
+        CLASS b EXTENDS a VAR y : INT; END_VAR END_CLASS
+    "#,
+    );
+
+    assert_eq!(vec!["b"], index.get_super_classes("a"));
+    assert_eq!(2, index.get_container_members("a").len());
+}
+
+#[test]
+fn interfaces_and_their_implementers_are_indexed() {
+    let (_, index) = index(
+        r#"
+        INTERFACE Drive
+            METHOD start : BOOL END_METHOD
+            METHOD stop END_METHOD
+        END_INTERFACE
+
+        CLASS Motor IMPLEMENTS Drive
+            METHOD start : BOOL END_METHOD
+            METHOD stop END_METHOD
+        END_CLASS
+
+        FUNCTION_BLOCK Pump IMPLEMENTS Drive
+        END_FUNCTION_BLOCK
+    "#,
+    );
+
+    assert_eq!(
+        Some(&PouIndexEntry::Interface {
+            name: "Drive".into(),
+            instance_struct_name: "Drive".into(),
+            linkage: LinkageType::Internal,
+        }),
+        index.find_pou("Drive")
+    );
+    let prototypes: Vec<&str> = index
+        .get_interface_methods("drive")
+        .iter()
+        .map(|it| it.get_name())
+        .collect();
+    assert_eq!(vec!["Drive.start", "Drive.stop"], prototypes);
+    assert_eq!(
+        &["Drive".to_string()],
+        index.find_pou("Motor").unwrap().get_interfaces()
+    );
+    assert_eq!(
+        &["Drive".to_string()],
+        index.find_pou("Pump").unwrap().get_interfaces()
+    );
+    //the prototypes are not implemented
+    assert!(index.find_pou_implementation("Drive.start").is_none());
+}
+
 #[test]
 fn function_is_indexed() {
     let (_, index) = index(
@@ -816,6 +979,7 @@ fn pre_processing_generates_inline_pointers() {
     let expected = &UserTypeDeclaration {
         data_type: DataType::PointerType {
             name: Some("__foo_inline_pointer".to_string()),
+            auto_deref: false,
             referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
                 referenced_type: "INT".to_string(),
                 location: SourceRange::undefined(),
@@ -853,6 +1017,7 @@ fn pre_processing_generates_pointer_to_pointer_type() {
     let expected = &UserTypeDeclaration {
         data_type: DataType::PointerType {
             name: Some("__pointer_to_pointer".to_string()),
+            auto_deref: false,
             referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
                 referenced_type: "INT".to_string(),
                 location: SourceRange::undefined(),
@@ -869,6 +1034,7 @@ fn pre_processing_generates_pointer_to_pointer_type() {
     let expected = &UserTypeDeclaration {
         data_type: DataType::PointerType {
             name: Some("pointer_to_pointer".to_string()),
+            auto_deref: false,
             referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
                 referenced_type: "__pointer_to_pointer".to_string(),
                 location: SourceRange::undefined(),
@@ -901,6 +1067,7 @@ fn pre_processing_generates_inline_pointer_to_pointer() {
     let expected = &UserTypeDeclaration {
         data_type: DataType::PointerType {
             name: Some("__foo_inline_pointer_".to_string()),
+            auto_deref: false,
             referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
                 referenced_type: "INT".to_string(),
                 location: SourceRange::undefined(),
@@ -917,6 +1084,7 @@ fn pre_processing_generates_inline_pointer_to_pointer() {
     let expected = &UserTypeDeclaration {
         data_type: DataType::PointerType {
             name: Some("__foo_inline_pointer".to_string()),
+            auto_deref: false,
             referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
                 referenced_type: "__foo_inline_pointer_".to_string(),
                 location: SourceRange::undefined(),
@@ -974,6 +1142,7 @@ fn pre_processing_generates_inline_arrays() {
                 referenced_type: "INT".to_string(),
                 location: SourceRange::undefined(),
             }),
+            is_variable_length: false,
         },
         initializer: None,
         location: (59..77).into(),
@@ -1029,6 +1198,7 @@ fn pre_processing_generates_inline_array_of_array() {
                 referenced_type: "INT".to_string(),
                 location: SourceRange::undefined(),
             }),
+            is_variable_length: false,
         },
         initializer: None,
         location: (59..92).into(),
@@ -1058,6 +1228,7 @@ fn pre_processing_generates_inline_array_of_array() {
                 referenced_type: "__foo_inline_array_".to_string(),
                 location: SourceRange::undefined(),
             }),
+            is_variable_length: false,
         },
         initializer: None,
         location: (59..92).into(),
@@ -1098,6 +1269,7 @@ fn pre_processing_generates_array_of_array_type() {
                 referenced_type: "INT".to_string(),
                 location: SourceRange::undefined(),
             }),
+            is_variable_length: false,
         },
         location: SourceRange::undefined(),
         initializer: None,
@@ -1119,6 +1291,7 @@ fn pre_processing_generates_array_of_array_type() {
                 referenced_type: "__arr_arr".to_string(),
                 location: SourceRange::undefined(),
             }),
+            is_variable_length: false,
         },
         location: SourceRange::undefined(),
         initializer: None,
@@ -1192,6 +1365,7 @@ fn pre_processing_nested_array_in_struct() {
                 referenced_type: "INT".to_string(),
                 location: SourceRange::undefined(),
             }),
+            is_variable_length: false,
         },
         initializer: None,
         location: (59..77).into(),
@@ -1237,6 +1411,7 @@ fn pre_processing_generates_inline_array_of_array_of_array() {
                 referenced_type: "INT".to_string(),
                 location: SourceRange::undefined(),
             }),
+            is_variable_length: false,
         },
         initializer: None,
         location: (74..107).into(),
@@ -1266,6 +1441,7 @@ fn pre_processing_generates_inline_array_of_array_of_array() {
                 referenced_type: "__foo_inline_array__".to_string(),
                 location: SourceRange::undefined(),
             }),
+            is_variable_length: false,
         },
         initializer: None,
         location: (59..107).into(),
@@ -1295,6 +1471,7 @@ fn pre_processing_generates_inline_array_of_array_of_array() {
                 referenced_type: "__foo_inline_array_".to_string(),
                 location: SourceRange::undefined(),
             }),
+            is_variable_length: false,
         },
         initializer: None,
         location: (59..107).into(),
@@ -1913,6 +2090,8 @@ fn a_program_pou_is_indexed() {
                 initial_value: None,
                 variable_type: ArgumentType::ByVal(VariableType::Global),
                 is_constant: false,
+                is_retain: false,
+                is_persistent: false,
                 data_type_name: "myProgram".into(),
                 location_in_parent: 0,
                 linkage: LinkageType::Internal,
@@ -1943,7 +2122,8 @@ fn a_program_pou_is_indexed() {
         Some(&PouIndexEntry::FunctionBlock {
             name: "myFunctionBlock".into(),
             linkage: LinkageType::Internal,
-            instance_struct_name: "myFunctionBlock".into()
+            instance_struct_name: "myFunctionBlock".into(),
+            interfaces: vec![],
         }),
         index.find_pou("myFunctionBlock"),
     );
@@ -1952,7 +2132,10 @@ fn a_program_pou_is_indexed() {
         Some(&PouIndexEntry::Class {
             name: "myClass".into(),
             linkage: LinkageType::Internal,
-            instance_struct_name: "myClass".into()
+            instance_struct_name: "myClass".into(),
+            super_class: None,
+            interfaces: vec![],
+            is_abstract: false,
         }),
         index.find_pou("myClass"),
     );
@@ -1967,3 +2150,120 @@ fn a_program_pou_is_indexed() {
         index.find_pou("myProgram.act"),
     );
 }
+
+#[test]
+fn retain_blocks_are_registered_in_the_index() {
+    let (_, index) = index(
+        "
+        VAR_GLOBAL RETAIN
+            total : LREAL;
+        END_VAR
+        VAR_GLOBAL
+            count : DINT;
+        END_VAR
+
+        PROGRAM prg
+            VAR RETAIN
+                x : INT;
+            END_VAR
+            VAR
+                y : INT;
+            END_VAR
+        END_PROGRAM
+    ",
+    );
+
+    assert!(index.find_global_variable("total").unwrap().is_retain());
+    assert!(!index.find_global_variable("count").unwrap().is_retain());
+    assert!(index.find_member("prg", "x").unwrap().is_retain());
+    assert!(!index.find_member("prg", "y").unwrap().is_retain());
+}
+
+#[test]
+fn variable_length_arrays_are_registered_as_fat_pointers() {
+    let (_, index) = index(
+        "
+        FUNCTION sum : DINT
+            VAR_IN_OUT
+                matrix : ARRAY[*, *] OF INT;
+            END_VAR
+        END_FUNCTION
+    ",
+    );
+
+    //the parameter is passed as the fat pointer, not as a pointer to it
+    let parameter = index.find_member("sum", "matrix").unwrap();
+    assert_eq!(parameter.get_type_name(), "__sum_matrix");
+    assert_eq!(
+        index
+            .find_effective_type_info("__sum_matrix")
+            .and_then(DataTypeInformation::get_vla_dimensions),
+        Some(("INT", 2))
+    );
+
+    let members = index.get_container_members("__sum_matrix");
+    assert_eq!(members[0].get_name(), "data");
+    assert_eq!(
+        index.find_effective_type_info(members[0].get_type_name()),
+        Some(&DataTypeInformation::Pointer {
+            name: "__sum_matrix__data".into(),
+            inner_type_name: "INT".into(),
+            auto_deref: false,
+        })
+    );
+    assert_eq!(members[1].get_name(), "dimensions");
+    if let Some(DataTypeInformation::Array {
+        inner_type_name,
+        dimensions,
+        ..
+    }) = index.find_effective_type_info(members[1].get_type_name())
+    {
+        assert_eq!(inner_type_name, "DINT");
+        assert_eq!(dimensions[0].get_length(&index), Ok(4));
+    } else {
+        panic!("expected an array of bounds");
+    }
+}
+
+#[test]
+fn unbound_function_block_templates_are_recorded() {
+    let (_, index) = index(
+        "
+        FUNCTION_BLOCK sensor
+        VAR
+            value AT %I* : BOOL;
+        END_VAR
+        END_FUNCTION_BLOCK
+
+        PROGRAM main
+        VAR
+            sensors : ARRAY[0..2] OF sensor;
+            alarm AT %Q* : BOOL;
+        END_VAR
+        END_PROGRAM
+
+        VAR_CONFIG
+            main.sensors[1].value AT %IX2.1 : BOOL;
+        END_VAR
+        ",
+    );
+
+    let configured = index.find_config_variable("MAIN.SENSORS[1].VALUE").unwrap();
+    assert_eq!(configured.data_type_name, "BOOL");
+    assert_eq!(configured.binding.access, DirectAccessType::Bit);
+    assert_eq!(configured.binding.direction, HardwareAccessType::Input);
+
+    //the template of the program is bound by the hardware configuration
+    let unbound = index
+        .find_unbound_templates()
+        .into_iter()
+        .map(|(path, variable)| (path, variable.get_qualified_name()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        unbound,
+        vec![
+            ("main.sensors[0].value".to_string(), "sensor.value"),
+            ("main.sensors[2].value".to_string(), "sensor.value"),
+        ]
+    );
+}
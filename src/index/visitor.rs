@@ -1,9 +1,11 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
-use super::{HardwareBinding, PouIndexEntry, VariableIndexEntry, VariableType};
+use super::{
+    ConfigVariableEntry, HardwareBinding, PouIndexEntry, VariableIndexEntry, VariableType,
+};
 use crate::ast::{
     self, ArgumentProperty, AstStatement, CompilationUnit, DataType, DataTypeDeclaration,
-    Implementation, Pou, PouType, SourceRange, TypeNature, UserTypeDeclaration, VariableBlock,
-    VariableBlockType,
+    Implementation, LinkageType, PolymorphismMode, Pou, PouType, SourceRange, TypeNature,
+    UserTypeDeclaration, VariableBlock, VariableBlockType,
 };
 use crate::diagnostics::Diagnostic;
 use crate::index::{ArgumentType, Index, MemberInfo};
@@ -28,6 +30,11 @@ pub fn visit(unit: &CompilationUnit, mut id_provider: IdProvider) -> Index {
         visit_global_var_block(&mut index, global_vars);
     }
 
+    //Record the addresses configured for instance variables
+    for config_variable in &unit.var_config {
+        visit_config_variable(&mut index, config_variable);
+    }
+
     //Create types and variables for POUs
     for pou in &unit.units {
         visit_pou(&mut index, pou);
@@ -36,11 +43,32 @@ pub fn visit(unit: &CompilationUnit, mut id_provider: IdProvider) -> Index {
     for implementation in &unit.implementations {
         visit_implementation(&mut index, implementation);
     }
+
+    //Record the expressions replacing the calls of inlined functions
+    for pou in unit.units.iter().filter(|it| it.inline) {
+        if let Some(expression) = unit
+            .implementations
+            .iter()
+            .find(|it| it.name == pou.name)
+            .and_then(|it| pou.get_inline_expression(it).ok())
+        {
+            index.register_inline_expression(&pou.name, expression.clone());
+        }
+    }
     index
 }
 
 pub fn visit_pou(index: &mut Index, pou: &Pou) {
     let interface_name = format!("{}_interface", &pou.name);
+    if let Some(hint) = pou.optimization {
+        index.register_optimization_hint(&pou.name, hint);
+    }
+    if pou.fast_math {
+        index.register_fast_math(&pou.name);
+    }
+    if let Some(convention) = pou.calling_convention {
+        index.register_calling_convention(&pou.name, convention);
+    }
 
     let mut member_names = vec![];
 
@@ -66,7 +94,11 @@ pub fn visit_pou(index: &mut Index, pou: &Pou) {
             member_names.push(var.name.clone());
 
             let var_type_name = var.data_type.get_name().expect("named datatype");
-            let type_name = if block_type.is_by_ref() {
+            //variable-length arrays are passed as a struct pointing to the array
+            let is_vla = index
+                .find_effective_type_info(var_type_name)
+                .map_or(false, DataTypeInformation::is_vla);
+            let type_name = if block_type.is_by_ref() && !is_vla {
                 //register a pointer type for argument
                 register_byref_pointer_type_for(index, var_type_name)
             } else {
@@ -92,6 +124,7 @@ pub fn visit_pou(index: &mut Index, pou: &Pou) {
                     variable_linkage: block_type,
                     variable_type_name: &type_name,
                     is_constant: block.constant,
+                    is_retain: block.retain,
                     binding,
                 },
                 initial_value,
@@ -118,6 +151,7 @@ pub fn visit_pou(index: &mut Index, pou: &Pou) {
                 variable_linkage: ArgumentType::ByVal(VariableType::Return),
                 variable_type_name: return_type_name,
                 is_constant: false, //return variables are not constants
+                is_retain: false,
                 binding: None,
             },
             None,
@@ -152,11 +186,13 @@ pub fn visit_pou(index: &mut Index, pou: &Pou) {
                 &pou.name,
                 pou.location.clone(),
             )
-            .set_constant(true);
+            .set_constant(true)
+            .set_linkage(get_initializer_linkage(pou.linkage));
             index.register_global_initializer(&global_struct_name, variable);
             index.register_pou(PouIndexEntry::create_function_block_entry(
                 &pou.name,
                 pou.linkage,
+                &pou.interfaces,
             ));
             index.register_pou_type(datatype);
         }
@@ -168,9 +204,23 @@ pub fn visit_pou(index: &mut Index, pou: &Pou) {
                 &pou.name,
                 pou.location.clone(),
             )
-            .set_constant(true);
+            .set_constant(true)
+            .set_linkage(get_initializer_linkage(pou.linkage));
             index.register_global_initializer(&global_struct_name, variable);
-            index.register_pou(PouIndexEntry::create_class_entry(&pou.name, pou.linkage));
+            index.register_pou(PouIndexEntry::create_class_entry(
+                &pou.name,
+                pou.linkage,
+                pou.super_class.as_deref(),
+                &pou.interfaces,
+                pou.poly_mode == Some(PolymorphismMode::Abstract),
+            ));
+            index.register_pou_type(datatype);
+        }
+        PouType::Interface => {
+            index.register_pou(PouIndexEntry::create_interface_entry(
+                &pou.name,
+                pou.linkage,
+            ));
             index.register_pou_type(datatype);
         }
         PouType::Function => {
@@ -189,6 +239,7 @@ pub fn visit_pou(index: &mut Index, pou: &Pou) {
                 return_type_name,
                 owner_class,
                 pou.linkage,
+                pou.poly_mode == Some(PolymorphismMode::Abstract),
             ));
             index.register_pou_type(datatype);
         }
@@ -196,6 +247,16 @@ pub fn visit_pou(index: &mut Index, pou: &Pou) {
     };
 }
 
+/// returns the linkage of the initializer of a pou with the given linkage, the initializer of a
+/// weak pou is replaced together with the pou
+fn get_initializer_linkage(pou_linkage: LinkageType) -> LinkageType {
+    if pou_linkage == LinkageType::Weak {
+        LinkageType::Weak
+    } else {
+        LinkageType::Internal
+    }
+}
+
 /// returns the declaration type (ByRef or ByVal) for the given VariableBlock (VAR_INPUT, VAR_OUTPUT, VAR_INOUT, etc.)
 fn get_declaration_type_for(block: &VariableBlock) -> ArgumentType {
     if matches!(
@@ -259,6 +320,96 @@ fn register_byref_pointer_type_for(index: &mut Index, inner_type_name: &str) ->
     type_name
 }
 
+/// registers an `ARRAY[*] OF ...` as the struct passed for it, a pointer to the array's first
+/// element (`data`) and the lower and upper bound of each dimension (`dimensions`)
+fn visit_variable_length_array(
+    index: &mut Index,
+    name: &str,
+    referenced_type: &DataTypeDeclaration,
+    bounds: &AstStatement,
+) {
+    let inner_type_name = referenced_type.get_name().expect("named datatype");
+    let ndims = bounds.get_as_list().len();
+
+    //IEC identifiers cannot contain two consecutive underscores
+    let data_type_name = format!("{}__data", name);
+    index.register_type(typesystem::DataType {
+        name: data_type_name.clone(),
+        initial_value: None,
+        information: DataTypeInformation::Pointer {
+            name: data_type_name.clone(),
+            inner_type_name: inner_type_name.to_string(),
+            auto_deref: false,
+        },
+        nature: TypeNature::Any,
+    });
+    let dimensions_type_name = format!("{}__dimensions", name);
+    index.register_type(typesystem::DataType {
+        name: dimensions_type_name.clone(),
+        initial_value: None,
+        information: DataTypeInformation::Array {
+            name: dimensions_type_name.clone(),
+            inner_type_name: DINT_TYPE.to_string(),
+            dimensions: vec![Dimension {
+                start_offset: TypeSize::from_literal(0),
+                end_offset: TypeSize::from_literal(2 * ndims as i64 - 1),
+            }],
+        },
+        nature: TypeNature::Any,
+    });
+
+    index.register_type(typesystem::DataType {
+        name: name.to_string(),
+        initial_value: None,
+        information: DataTypeInformation::Struct {
+            name: name.to_string(),
+            member_names: vec!["data".to_string(), "dimensions".to_string()],
+            varargs: None,
+            source: StructSource::Internal(InternalType::VariableLengthArray {
+                inner_type_name: inner_type_name.to_string(),
+                ndims,
+            }),
+        },
+        nature: TypeNature::Any,
+    });
+    for (count, (member, member_type)) in [
+        ("data", data_type_name),
+        ("dimensions", dimensions_type_name),
+    ]
+    .iter()
+    .enumerate()
+    {
+        index.register_member_variable(
+            MemberInfo {
+                container_name: name,
+                variable_name: member,
+                variable_linkage: ArgumentType::ByVal(VariableType::Local),
+                variable_type_name: member_type,
+                is_constant: false,
+                is_retain: false,
+                binding: None,
+            },
+            None,
+            SourceRange::undefined(),
+            count as u32,
+        );
+    }
+}
+
+fn visit_config_variable(index: &mut Index, config_variable: &ast::ConfigVariable) {
+    if let Some(binding) = HardwareBinding::from_statement(index, &config_variable.address, None) {
+        index.register_config_variable(ConfigVariableEntry {
+            reference: config_variable.reference.clone(),
+            data_type_name: config_variable
+                .data_type
+                .get_name()
+                .unwrap_or_default()
+                .to_string(),
+            binding,
+        });
+    }
+}
+
 fn visit_global_var_block(index: &mut Index, block: &VariableBlock) {
     let linkage = block.linkage;
     for var in &block.variables {
@@ -274,6 +425,8 @@ fn visit_global_var_block(index: &mut Index, block: &VariableBlock) {
         )
         .set_initial_value(initializer)
         .set_constant(block.constant)
+        .set_retain(block.retain)
+        .set_persistent(block.persistent)
         .set_linkage(linkage)
         .set_hardware_binding(
             var.address
@@ -384,6 +537,7 @@ fn visit_data_type(
                         variable_linkage: ArgumentType::ByVal(VariableType::Local),
                         variable_type_name: member_type,
                         is_constant: false, //struct members are not constants //TODO thats probably not true (you can define a struct in an CONST-block?!)
+                        is_retain: false,
                         binding,
                     },
                     init,
@@ -478,6 +632,13 @@ fn visit_data_type(
             name: Some(name),
             referenced_type,
             bounds,
+            is_variable_length: true,
+        } => visit_variable_length_array(index, name, referenced_type, bounds),
+        DataType::ArrayType {
+            name: Some(name),
+            referenced_type,
+            bounds,
+            ..
         } => {
             let dimensions: Result<Vec<Dimension>, Diagnostic> = bounds
                 .get_as_list()
@@ -556,13 +717,13 @@ fn visit_data_type(
         DataType::PointerType {
             name: Some(name),
             referenced_type,
-            ..
+            auto_deref,
         } => {
             let inner_type_name = referenced_type.get_name().expect("named datatype");
             let information = DataTypeInformation::Pointer {
                 name: name.clone(),
                 inner_type_name: inner_type_name.into(),
-                auto_deref: false,
+                auto_deref: *auto_deref,
             };
 
             let init = index
@@ -634,6 +795,30 @@ fn visit_data_type(
                 nature: TypeNature::String,
             });
         }
+        DataType::FixedPointType {
+            name: Some(name),
+            integer_bits,
+            fraction_bits,
+        } => {
+            let information = DataTypeInformation::FixedPoint {
+                name: name.clone(),
+                size: integer_bits + fraction_bits,
+                fraction_bits: *fraction_bits,
+            };
+            let init = index
+                .get_mut_const_expressions()
+                .maybe_add_constant_expression(
+                    type_declaration.initializer.clone(),
+                    name,
+                    scope.clone(),
+                );
+            index.register_type(typesystem::DataType {
+                name: name.to_string(),
+                initial_value: init,
+                information,
+                nature: TypeNature::Any,
+            });
+        }
         DataType::VarArgs { .. } => {} //Varargs are not indexed,
         DataType::GenericType {
             name,
@@ -0,0 +1,279 @@
+//! the order in which the global variables and program instances are initialized
+//!
+//! a variable is initialized after the variables referenced by its initializer (a program instance
+//! after the variables referenced by the initializers of its members). Among the variables whose
+//! dependencies are initialized, the one with the lowest `{attribute 'init_priority' := 'n'}` is
+//! initialized first, the variables without priority follow in the order of their declaration.
+//! A priority that contradicts the dependencies of its variable is reported.
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter, Result},
+};
+
+use crate::{
+    ast::{AstStatement, CompilationUnit, LinkageType, PouType},
+    index::Index,
+    resolver::const_evaluator::collect_referenced_constants,
+    Diagnostic,
+};
+
+/// a global variable or program instance and the variables its initializer depends on
+#[derive(Debug, PartialEq)]
+pub struct Initialization {
+    /// the name of the global variable or of the program
+    pub name: String,
+    pub priority: Option<u32>,
+    pub is_program: bool,
+    /// the names of the global variables and programs referenced by the initializer
+    pub dependencies: Vec<String>,
+}
+
+/// the global variables and program instances in the order of their initialization
+#[derive(Debug, Default)]
+pub struct InitializationOrder {
+    initializations: Vec<Initialization>,
+}
+
+impl InitializationOrder {
+    /// orders the global variables and program instances declared in the given units
+    pub fn new(units: &[CompilationUnit], index: &Index) -> InitializationOrder {
+        let mut pending = Vec::new();
+        for unit in units {
+            for block in unit
+                .global_vars
+                .iter()
+                .filter(|it| it.linkage != LinkageType::External)
+            {
+                for variable in &block.variables {
+                    pending.push(Initialization {
+                        name: variable.name.clone(),
+                        priority: block.init_priority,
+                        is_program: false,
+                        dependencies: get_dependencies(variable.initializer.iter(), None, index),
+                    });
+                }
+            }
+            for program in unit
+                .units
+                .iter()
+                .filter(|it| it.pou_type == PouType::Program && it.linkage != LinkageType::External)
+            {
+                let initializers = program
+                    .variable_blocks
+                    .iter()
+                    .flat_map(|it| &it.variables)
+                    .filter_map(|it| it.initializer.as_ref());
+                pending.push(Initialization {
+                    name: program.name.clone(),
+                    priority: None,
+                    is_program: true,
+                    dependencies: get_dependencies(initializers, Some(&program.name), index),
+                });
+            }
+        }
+
+        //only the dependencies on the ordered variables are relevant, members of programs are
+        //initialized with their program
+        let names = pending
+            .iter()
+            .map(|it| (it.name.to_lowercase(), it.name.clone()))
+            .collect::<HashMap<_, _>>();
+        for initialization in &mut pending {
+            let own_name = initialization.name.to_lowercase();
+            let mut dependencies = Vec::new();
+            for dependency in &initialization.dependencies {
+                let dependency = dependency.to_lowercase();
+                let dependency = dependency.split('.').next().unwrap_or_default();
+                if let Some(name) = names.get(dependency).filter(|_| dependency != own_name) {
+                    if !dependencies.contains(name) {
+                        dependencies.push(name.clone());
+                    }
+                }
+            }
+            initialization.dependencies = dependencies;
+        }
+
+        let mut initializations = Vec::new();
+        let mut initialized = HashSet::new();
+        while !pending.is_empty() {
+            //the variables of a circular dependency (reported by the constant evaluation) keep
+            //the order of their declaration
+            let next = pending
+                .iter()
+                .enumerate()
+                .filter(|(_, it)| {
+                    it.dependencies
+                        .iter()
+                        .all(|it| initialized.contains(&it.to_lowercase()))
+                })
+                .min_by_key(|(position, it)| (it.priority.is_none(), it.priority, *position))
+                .map(|(position, _)| position)
+                .unwrap_or_default();
+            let initialization = pending.remove(next);
+            initialized.insert(initialization.name.to_lowercase());
+            initializations.push(initialization);
+        }
+        InitializationOrder { initializations }
+    }
+
+    pub fn get_initializations(&self) -> &[Initialization] {
+        &self.initializations
+    }
+
+    /// returns an error for every prioritized global variable declared in the given unit that
+    /// depends on a variable initialized after it according to the priorities
+    pub fn diagnostics_for(&self, unit: &CompilationUnit) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for block in &unit.global_vars {
+            let priority = match block.init_priority {
+                Some(priority) => priority,
+                None => continue,
+            };
+            for variable in &block.variables {
+                let dependencies = self
+                    .find_initialization(&variable.name)
+                    .map(|it| it.dependencies.as_slice())
+                    .unwrap_or_default();
+                for dependency in dependencies {
+                    let dependency_priority = self
+                        .find_initialization(dependency)
+                        .and_then(|it| it.priority);
+                    if dependency_priority.map_or(true, |it| it > priority) {
+                        diagnostics.push(Diagnostic::init_priority_conflict(
+                            &variable.name,
+                            priority,
+                            dependency,
+                            dependency_priority,
+                            variable.location.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+        diagnostics
+    }
+
+    fn find_initialization(&self, name: &str) -> Option<&Initialization> {
+        self.initializations
+            .iter()
+            .find(|it| it.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// lists the variables in the order of their initialization with their priority and dependencies
+impl Display for InitializationOrder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(
+            f,
+            "# initialization order generated by rusty {}",
+            env!("CARGO_PKG_VERSION")
+        )?;
+        for (position, initialization) in self.initializations.iter().enumerate() {
+            write!(f, "{}. ", position + 1)?;
+            if initialization.is_program {
+                write!(f, "PROGRAM ")?;
+            }
+            write!(f, "{}", initialization.name)?;
+            if let Some(priority) = initialization.priority {
+                write!(f, " (init_priority {})", priority)?;
+            }
+            if !initialization.dependencies.is_empty() {
+                write!(f, ", after {}", initialization.dependencies.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// returns the qualified names of the initialized variables referenced by the given initializers
+fn get_dependencies<'a>(
+    initializers: impl Iterator<Item = &'a AstStatement>,
+    scope: Option<&str>,
+    index: &Index,
+) -> Vec<String> {
+    let mut references = Vec::new();
+    for initializer in initializers {
+        collect_referenced_constants(initializer, scope, index, &mut references);
+    }
+    references.into_iter().map(|(name, _)| name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InitializationOrder;
+    use crate::{test_utils::tests::index, Diagnostic};
+
+    #[test]
+    fn variables_are_initialized_after_their_dependencies_by_priority() {
+        let (unit, index) = index(
+            "
+            VAR_GLOBAL CONSTANT
+                speed : INT := limit / 2;
+                offset : INT := 3;
+            END_VAR
+            {attribute 'init_priority' := '1'}
+            VAR_GLOBAL CONSTANT
+                limit : INT := 100;
+            END_VAR
+            PROGRAM main
+            VAR
+                x : INT := speed;
+            END_VAR
+            END_PROGRAM
+            ",
+        );
+
+        let order = InitializationOrder::new(std::slice::from_ref(&unit), &index);
+
+        assert_eq!(order.diagnostics_for(&unit), vec![]);
+        assert_eq!(
+            order.to_string(),
+            format!(
+                "# initialization order generated by rusty {}\n\
+                1. limit (init_priority 1)\n\
+                2. speed, after limit\n\
+                3. offset\n\
+                4. PROGRAM main, after speed\n",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn priorities_contradicting_the_dependencies_are_reported() {
+        let (unit, index) = index(
+            "
+            {attribute 'init_priority' := '1'}
+            VAR_GLOBAL CONSTANT
+                speed : INT := limit / 2;
+            END_VAR
+            {attribute 'init_priority' := '2'}
+            VAR_GLOBAL CONSTANT
+                limit : INT := 100;
+            END_VAR
+            ",
+        );
+
+        let order = InitializationOrder::new(std::slice::from_ref(&unit), &index);
+
+        assert_eq!(
+            order.diagnostics_for(&unit),
+            vec![Diagnostic::init_priority_conflict(
+                "speed",
+                1,
+                "limit",
+                Some(2),
+                (96..101).into()
+            )]
+        );
+        assert_eq!(
+            order
+                .get_initializations()
+                .iter()
+                .map(|it| it.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["limit", "speed"]
+        );
+    }
+}
@@ -3,15 +3,19 @@ use core::ops::Range;
 use logos::Filter;
 use logos::Lexer;
 use logos::Logos;
+use std::collections::HashMap;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 pub use tokens::Token;
 
 use crate::ast::AstId;
+use crate::ast::Comment;
 use crate::ast::DirectAccessType;
 use crate::ast::HardwareAccessType;
 use crate::ast::SourceRange;
+use crate::diagnostics::CodeEdit;
+use crate::dialect::{Dialect, LanguageExtension};
 use crate::Diagnostic;
 
 #[cfg(test)]
@@ -30,6 +34,13 @@ pub struct ParseSession<'a> {
     pub parse_progress: usize,
     id_provider: IdProvider,
     pub scope: Option<String>,
+    /// the dialect deciding which language extensions are accepted
+    pub dialect: Dialect,
+    /// the iteration bounds of the loops annotated with `{attribute 'loop_bound'}` in the body
+    /// being parsed, by the loop's id
+    pub loop_bounds: HashMap<AstId, u32>,
+    /// the comments and unknown pragmas skipped so far
+    pub comments: Vec<Comment>,
 }
 
 #[macro_export]
@@ -47,7 +58,7 @@ macro_rules! expect_token {
 }
 
 impl<'a> ParseSession<'a> {
-    pub fn new(l: Lexer<'a, Token>, id_provider: IdProvider) -> ParseSession<'a> {
+    pub fn new(l: Lexer<'a, Token>, id_provider: IdProvider, dialect: Dialect) -> ParseSession<'a> {
         let mut lexer = ParseSession {
             lexer: l,
             token: Token::KeywordBy,
@@ -58,6 +69,9 @@ impl<'a> ParseSession<'a> {
             parse_progress: 0,
             id_provider,
             scope: None,
+            dialect,
+            loop_bounds: HashMap::new(),
+            comments: vec![],
         };
         lexer.advance();
         lexer
@@ -115,14 +129,16 @@ impl<'a> ParseSession<'a> {
 
     pub fn advance(&mut self) {
         self.last_range = self.range();
-        self.last_token =
-            std::mem::replace(&mut self.token, self.lexer.next().unwrap_or(Token::End));
+        let token = self.next_token();
+        self.last_token = std::mem::replace(&mut self.token, token);
         self.parse_progress += 1;
+        self.skip_scl_syntax();
 
         match self.token {
             Token::KeywordVarInput
             | Token::KeywordVarOutput
             | Token::KeywordVarGlobal
+            | Token::KeywordVarConfig
             | Token::KeywordVarInOut
             | Token::KeywordRef
             | Token::KeywordVarTemp
@@ -140,25 +156,115 @@ impl<'a> ParseSession<'a> {
             | Token::KeywordEndFor
             | Token::KeywordEndRepeat
             | Token::KeywordEndMethod
-            | Token::KeywordEndClass => {
+            | Token::KeywordEndClass
+            | Token::KeywordEndNamespace => {
                 if !self.slice().to_string().contains('_') {
-                    self.accept_diagnostic(Diagnostic::ImprovementSuggestion {
-                        message: format!(
-                            "the words in {} should be separated by a '_'",
-                            self.slice()
-                        ),
-                        range: self.location(),
-                    });
+                    if self.dialect.allows(LanguageExtension::UnseparatedKeywords) {
+                        self.accept_diagnostic(Diagnostic::ImprovementSuggestion {
+                            message: format!(
+                                "the words in {} should be separated by a '_'",
+                                self.slice()
+                            ),
+                            range: self.location(),
+                        });
+                    } else {
+                        self.accept_diagnostic(Diagnostic::unsupported_in_dialect(
+                            self.slice(),
+                            self.dialect,
+                            self.location(),
+                        ));
+                    }
                 }
             }
             _ => {}
         }
     }
 
+    /// skips SCL syntax that has no representation in the AST: the `#` prefix of local
+    /// variables, `REGION`/`END_REGION` markers and the `VERSION : x.y` header, the latter two
+    /// including the rest of their line. Attributes (`{ S7_Optimized_Access := 'TRUE' }`)
+    /// are skipped like any other pragma
+    fn skip_scl_syntax(&mut self) {
+        loop {
+            if self.token == Token::LocalPrefix {
+                if !self.dialect.allows(LanguageExtension::LocalPrefix) {
+                    self.accept_diagnostic(Diagnostic::unsupported_in_dialect(
+                        self.slice(),
+                        self.dialect,
+                        self.location(),
+                    ));
+                }
+            } else if self.token == Token::Identifier
+                && self.dialect.allows(LanguageExtension::SclMarkers)
+                && is_scl_marker(self.slice(), self.lexer.remainder())
+            {
+                let remainder = self.lexer.remainder();
+                self.lexer
+                    .bump(remainder.find('\n').unwrap_or(remainder.len()));
+            } else {
+                return;
+            }
+            self.token = self.next_token();
+        }
+    }
+
+    /// returns the lexer's next token and records the comments skipped before it
+    fn next_token(&mut self) -> Token {
+        let start = self.lexer.span().end;
+        let token = self.lexer.next().unwrap_or(Token::End);
+        let end = if token == Token::End {
+            self.lexer.source().len()
+        } else {
+            self.lexer.span().start
+        };
+        if start < end {
+            let last_end = self.comments.last().map_or(0, |it| it.location.get_end());
+            let comments = find_comments(&self.lexer.source()[start..end], start);
+            self.comments.extend(
+                comments
+                    .into_iter()
+                    .filter(|it| it.location.get_start() >= last_end),
+            );
+        }
+        token
+    }
+
     pub fn slice(&self) -> &str {
         self.lexer.slice()
     }
 
+    /// returns true if the current token starts an instruction list
+    pub fn starts_il(&self) -> bool {
+        self.token == Token::Identifier && starts_il(self.slice(), self.lexer.remainder())
+    }
+
+    /// returns true if the current token starts a `JMP label` statement
+    pub fn is_jump(&self) -> bool {
+        self.token == Token::Identifier && is_jump(self.slice(), self.lexer.remainder())
+    }
+
+    /// returns true if the current token starts a `VECTOR[n]` or `MATRIX[rows, columns]` type
+    pub fn is_vector_or_matrix(&self) -> bool {
+        self.token == Token::Identifier && is_vector_or_matrix(self.slice(), self.lexer.remainder())
+    }
+
+    /// returns true if the current token starts a `STEP name :` of a sequential function chart
+    pub fn is_step(&self) -> bool {
+        self.token == Token::Identifier && is_step(self.slice(), self.lexer.remainder())
+    }
+
+    /// returns true if the current token starts a `STATE name` of a state machine
+    pub fn is_state(&self) -> bool {
+        self.token == Token::Identifier && is_state(self.slice(), self.lexer.remainder())
+    }
+
+    /// returns true if the current token starts the `ENTRY:`, `DURING:` or `EXIT:` section of a
+    /// state
+    pub fn is_state_section(&self) -> bool {
+        matches!(self.token, Token::Identifier | Token::KeywordExit)
+            && is_state_section(self.slice(), self.lexer.remainder())
+    }
+
     pub fn location(&self) -> SourceRange {
         SourceRange::new(self.range())
     }
@@ -210,6 +316,8 @@ impl<'a> ParseSession<'a> {
         let mut hit = self.get_close_region_level(&self.token);
         let start = self.location();
         let mut end = self.location().get_end();
+        let last_end = self.last_range.end;
+        let starts_with_identifier = self.token == Token::Identifier;
         while self.token != Token::End && hit.is_none() {
             end = self.location().get_end();
             self.advance();
@@ -222,7 +330,7 @@ impl<'a> ParseSession<'a> {
         //Did we recover in the while loop above?
         if start.get_end() != self.location().get_end() {
             let range = start.get_start()..end;
-            self.accept_diagnostic(Diagnostic::unexpected_token_found(
+            let diagnostic = Diagnostic::unexpected_token_found(
                 format!(
                     "{:?}",
                     self.closing_keywords
@@ -233,7 +341,26 @@ impl<'a> ParseSession<'a> {
                 .as_str(),
                 format!("'{}'", self.slice_region(range.clone())).as_str(),
                 SourceRange::new(range),
-            ));
+            );
+
+            // a statement followed by a new statement on the next line most likely misses its ';'
+            let expects_semicolon = self
+                .closing_keywords
+                .last()
+                .map_or(false, |it| it.contains(&Token::KeywordSemicolon));
+            if expects_semicolon
+                && starts_with_identifier
+                && last_end <= start.get_start()
+                && self
+                    .slice_region(last_end..start.get_start())
+                    .contains('\n')
+            {
+                self.accept_diagnostic(
+                    diagnostic.with_edits(vec![CodeEdit::insert(";", last_end)]),
+                );
+            } else {
+                self.accept_diagnostic(diagnostic);
+            }
         }
 
         if let Some(hit) = hit {
@@ -252,6 +379,98 @@ impl<'a> ParseSession<'a> {
     }
 }
 
+/// returns true if the given identifier starts a line that only carries SCL meta-information
+pub(crate) fn is_scl_marker(identifier: &str, remainder: &str) -> bool {
+    if identifier.eq_ignore_ascii_case("REGION") || identifier.eq_ignore_ascii_case("END_REGION") {
+        return true;
+    }
+    //`VERSION : 0.1`, but neither `version := 1` nor `version : INT`
+    identifier.eq_ignore_ascii_case("VERSION")
+        && remainder
+            .trim_start()
+            .strip_prefix(':')
+            .map_or(false, |it| {
+                it.trim_start().starts_with(|c: char| c.is_ascii_digit())
+            })
+}
+
+/// a body is an instruction list if it starts with loading or calling an operand (`LD x`,
+/// `LDN x`, `CAL fb`), optionally labeled (`start: LD x`). Neither `ld := 1`, `ld.x := 1` nor
+/// `cal(x)` start one, `JMP` starts a jump in structured text
+pub(crate) fn starts_il(identifier: &str, remainder: &str) -> bool {
+    if let Some(instruction) = remainder
+        .trim_start_matches([' ', '\t'])
+        .strip_prefix(':')
+        .filter(|it| !it.starts_with('='))
+    {
+        let instruction = instruction.trim_start();
+        let end = instruction
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(instruction.len());
+        let (identifier, remainder) = instruction.split_at(end);
+        return is_load_or_call(identifier, remainder);
+    }
+    is_load_or_call(identifier, remainder)
+}
+
+fn is_load_or_call(identifier: &str, remainder: &str) -> bool {
+    ["LD", "LDN", "CAL"]
+        .iter()
+        .any(|it| identifier.eq_ignore_ascii_case(it))
+        && remainder.starts_with([' ', '\t'])
+        && remainder
+            .trim_start_matches([' ', '\t'])
+            .starts_with(|c: char| c.is_ascii_alphanumeric() || "_-'\"%".contains(c))
+}
+
+/// `JMP` is no keyword, it only starts a jump if it is followed by a label, so identifiers named
+/// `jmp` stay valid (`JMP target`, but neither `jmp := 1` nor `jmp;`)
+pub(crate) fn is_jump(identifier: &str, remainder: &str) -> bool {
+    identifier.eq_ignore_ascii_case("JMP")
+        && remainder.starts_with(char::is_whitespace)
+        && remainder
+            .trim_start()
+            .starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+}
+
+/// `STEP` is no keyword, it only starts a step if it is followed by the step's name, so
+/// identifiers named `step` stay valid (`STEP fill :`, but neither `step := 1` nor `step.x`)
+pub(crate) fn is_step(identifier: &str, remainder: &str) -> bool {
+    identifier.eq_ignore_ascii_case("STEP")
+        && remainder.starts_with(char::is_whitespace)
+        && remainder
+            .trim_start()
+            .starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+}
+
+/// `STATE` is no keyword, it only starts a state if it is followed by the state's name, so
+/// identifiers named `state` stay valid (`STATE idle`, but neither `state := 1` nor `state.x`)
+pub(crate) fn is_state(identifier: &str, remainder: &str) -> bool {
+    identifier.eq_ignore_ascii_case("STATE")
+        && remainder.starts_with(char::is_whitespace)
+        && remainder
+            .trim_start()
+            .starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+}
+
+/// `ENTRY` and `DURING` are no keywords, like `EXIT` they only start a section of a state if they
+/// are followed by a colon (`ENTRY:`, but neither `entry := 1` nor `EXIT;`)
+pub(crate) fn is_state_section(identifier: &str, remainder: &str) -> bool {
+    let remainder = remainder.trim_start();
+    ["ENTRY", "DURING", "EXIT"]
+        .iter()
+        .any(|it| it.eq_ignore_ascii_case(identifier))
+        && remainder.starts_with(':')
+        && !remainder.starts_with(":=")
+}
+
+/// `VECTOR` and `MATRIX` are no keywords, they only start a type if they are followed by its size,
+/// so identifiers named `vector` stay valid (`VECTOR[3] OF REAL`, but not `vector : INT`)
+pub(crate) fn is_vector_or_matrix(identifier: &str, remainder: &str) -> bool {
+    (identifier.eq_ignore_ascii_case("VECTOR") || identifier.eq_ignore_ascii_case("MATRIX"))
+        && remainder.trim_start().starts_with('[')
+}
+
 fn parse_pragma(lexer: &mut Lexer<Token>) -> Filter<()> {
     let remainder = lexer.remainder();
     let chars = remainder.chars();
@@ -295,6 +514,53 @@ fn parse_comments(lexer: &mut Lexer<Token>) -> Filter<()> {
     Filter::Emit(())
 }
 
+/// returns the comments and the unknown pragmas in the given text skipped by the lexer, the text
+/// starts at the given offset of the source
+fn find_comments(text: &str, offset: usize) -> Vec<Comment> {
+    let mut comments = vec![];
+    let mut position = 0;
+    while let Some(rest) = text.get(position..).filter(|it| !it.is_empty()) {
+        let length = if rest.starts_with("//") {
+            rest.find(['\r', '\n']).unwrap_or(rest.len())
+        } else if rest.starts_with("(*") || rest.starts_with("/*") {
+            find_comment_end(rest)
+        } else if rest.starts_with('{') {
+            rest.find('}').map_or(rest.len(), |it| it + 1)
+        } else {
+            position += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+        comments.push(Comment {
+            text: rest[..length].to_string(),
+            location: SourceRange::new(offset + position..offset + position + length),
+        });
+        position += length;
+    }
+    comments
+}
+
+/// returns the length of the (nested) comment the given text starts with, see `parse_comments`
+fn find_comment_end(comment: &str) -> usize {
+    let (open, close) = get_closing_tag(&comment[..2]);
+    let mut unclosed = 1;
+    let mut prev = ' ';
+    for (index, c) in comment.char_indices().skip(2) {
+        if c == '*' && prev == open {
+            unclosed += 1;
+            prev = ' ';
+        } else if c == close && prev == '*' {
+            unclosed -= 1;
+            prev = c;
+        } else {
+            prev = c;
+        }
+        if unclosed == 0 {
+            return index + c.len_utf8();
+        }
+    }
+    comment.len()
+}
+
 fn get_closing_tag(open_tag: &str) -> (char, char) {
     match open_tag {
         "(*" => ('(', ')'),
@@ -375,11 +641,19 @@ impl Default for IdProvider {
 
 #[cfg(test)]
 pub fn lex(source: &str) -> ParseSession {
-    ParseSession::new(Token::lexer(source), IdProvider::default())
+    ParseSession::new(
+        Token::lexer(source),
+        IdProvider::default(),
+        Dialect::default(),
+    )
 }
 
 pub fn lex_with_ids(source: &str, id_provider: IdProvider) -> ParseSession {
-    ParseSession::new(Token::lexer(source), id_provider)
+    lex_with_dialect(source, id_provider, Dialect::default())
+}
+
+pub fn lex_with_dialect(source: &str, id_provider: IdProvider, dialect: Dialect) -> ParseSession {
+    ParseSession::new(Token::lexer(source), id_provider, dialect)
 }
 
 #[cfg(test)]
@@ -3,7 +3,8 @@ use pretty_assertions::{assert_eq, assert_ne};
 
 use crate::{
     ast::{DirectAccessType, HardwareAccessType, SourceRange},
-    lexer::{lex, Token::*},
+    dialect::Dialect,
+    lexer::{lex, lex_with_dialect, IdProvider, Token::*},
 };
 
 #[test]
@@ -69,12 +70,23 @@ fn undefined_pragmas_are_ignored_by_the_lexer() {
 #[test]
 fn registered_pragmas_parsed() {
     let mut lexer = lex(r"
-        {external}{ref}{not_registerd}
+        {external}{ref}{atomic}{attribute 'weak'}{attribute 'config_const'}{not_registerd}
         ");
     assert_eq!(lexer.token, PropertyExternal, "Token : {}", lexer.slice());
     lexer.advance();
     assert_eq!(lexer.token, PropertyByRef, "Token : {}", lexer.slice());
     lexer.advance();
+    assert_eq!(lexer.token, PropertyAtomic, "Token : {}", lexer.slice());
+    lexer.advance();
+    assert_eq!(lexer.token, PropertyWeak, "Token : {}", lexer.slice());
+    lexer.advance();
+    assert_eq!(
+        lexer.token,
+        PropertyConfigConst,
+        "Token : {}",
+        lexer.slice()
+    );
+    lexer.advance();
 }
 
 #[test]
@@ -828,12 +840,13 @@ fn lowercase_keywords_accepted() {
     let mut result = lex(r###"
         program class end_class endclass var_input varinput var_output
         varoutput var abstract final method constant retain non_retain 
-        nonretain var_temp vartemp end_method endmethod
+        nonretain persistent var_temp vartemp end_method endmethod
         public private internal protected override
-        var_global varglobal var_in_out varinout end_var endvar
+        var_global varglobal var_config varconfig var_in_out varinout end_var endvar
         end_program endprogram end_function endfunction end_function_block endfunctionblock
         type struct end_type endtype end_struct endstruct 
         actions action end_action endaction end_actions endactions 
+        initial_step end_step transition end_transition
         if then elsif else endif end_if
         for to by do end_for endfor
         while end_while endwhile repeat until endrepeat end_repeat
@@ -849,3 +862,99 @@ fn lowercase_keywords_accepted() {
         result.advance();
     }
 }
+
+#[test]
+fn namespace_keywords() {
+    let mut lexer = lex("NAMESPACE plant USING util; END_NAMESPACE ENDNAMESPACE");
+
+    assert_eq!(lexer.token, KeywordNamespace);
+    lexer.advance();
+    assert_eq!(lexer.slice(), "plant");
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordUsing);
+    lexer.advance();
+    assert_eq!(lexer.slice(), "util");
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordSemicolon);
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordEndNamespace);
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordEndNamespace);
+    assert_eq!(
+        lexer.diagnostics.first().map(|it| it.get_message()),
+        Some("the words in ENDNAMESPACE should be separated by a '_'")
+    );
+    lexer.advance();
+    assert_eq!(lexer.token, End);
+}
+
+#[test]
+fn reference_to_and_ref_assignment() {
+    let mut lexer = lex("REFERENCE TO x reference\n\tto y r REF= x ref_x := reference");
+
+    assert_eq!(lexer.token, KeywordReferenceTo);
+    lexer.advance();
+    assert_eq!(lexer.slice(), "x");
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordReferenceTo);
+    lexer.advance();
+    assert_eq!(lexer.slice(), "y");
+    lexer.advance();
+    assert_eq!(lexer.slice(), "r");
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordReferenceAssignment);
+    lexer.advance();
+    assert_eq!(lexer.slice(), "x");
+    lexer.advance();
+    //REFERENCE and REF are no keywords on their own
+    assert_eq!((lexer.token, lexer.slice()), (Identifier, "ref_x"));
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordAssignment);
+    lexer.advance();
+    assert_eq!((lexer.token, lexer.slice()), (Identifier, "reference"));
+    lexer.advance();
+    assert_eq!(lexer.token, End);
+}
+
+#[test]
+fn local_prefix_is_skipped() {
+    let mut lexer = lex_with_dialect("#x := #y;", IdProvider::default(), Dialect::Scl);
+
+    assert_eq!(lexer.token, Identifier);
+    assert_eq!(lexer.slice(), "x");
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordAssignment);
+    lexer.advance();
+    assert_eq!(lexer.token, Identifier);
+    assert_eq!(lexer.slice(), "y");
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordSemicolon);
+    assert!(lexer.diagnostics.is_empty());
+}
+
+#[test]
+fn skipped_comments_are_recorded() {
+    let source = "(* a (* nested *) comment *) x // line\r\n{ unknown pragma } /* c */ y";
+    let mut lexer = lex(source);
+    while lexer.token != End {
+        lexer.advance();
+    }
+
+    let comments = lexer
+        .comments
+        .iter()
+        .map(|it| (it.text.as_str(), &source[it.location.to_range()]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        comments,
+        vec![
+            (
+                "(* a (* nested *) comment *)",
+                "(* a (* nested *) comment *)"
+            ),
+            ("// line", "// line"),
+            ("{ unknown pragma }", "{ unknown pragma }"),
+            ("/* c */", "/* c */"),
+        ]
+    );
+}
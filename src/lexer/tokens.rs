@@ -16,9 +16,42 @@ pub enum Token {
     #[token("{external}")]
     PropertyExternal,
 
+    #[regex(r"\{external\s+'[^'}]*'\s*\}")]
+    PropertyCallingConvention,
+
     #[token("{ref}")]
     PropertyByRef,
 
+    #[token("{prototype}")]
+    PropertyPrototype,
+
+    #[token("{atomic}")]
+    PropertyAtomic,
+
+    #[token("{shared}")]
+    PropertyShared,
+
+    #[regex(r"\{attribute\s+'weak'\}")]
+    PropertyWeak,
+
+    #[regex(r"\{attribute\s+'config_const'\}")]
+    PropertyConfigConst,
+
+    #[regex(r"\{attribute\s+'init_priority'\s*:=\s*[^}]*\}")]
+    PropertyInitPriority,
+
+    #[regex(r"\{attribute\s+'optimize'\s*:=\s*'[^'}]*'\s*\}")]
+    PropertyOptimize,
+
+    #[regex(r"\{attribute\s+'fast_math'\s*\}")]
+    PropertyFastMath,
+
+    #[regex(r"\{attribute\s+'inline'\s*\}")]
+    PropertyInline,
+
+    #[regex(r"\{attribute\s+'loop_bound'\s*:=\s*'[^'}]*'\s*\}")]
+    PropertyLoopBound,
+
     #[token("PROGRAM", ignore(case))]
     KeywordProgram,
 
@@ -59,6 +92,9 @@ pub enum Token {
     #[token("NONRETAIN", ignore(case))]
     KeywordNonRetain,
 
+    #[token("PERSISTENT", ignore(case))]
+    KeywordPersistent,
+
     #[token("VAR_TEMP", ignore(case))]
     #[token("VARTEMP", ignore(case))]
     KeywordVarTemp,
@@ -82,10 +118,37 @@ pub enum Token {
     #[token("OVERRIDE", ignore(case))]
     KeywordOverride,
 
+    #[token("EXTENDS", ignore(case))]
+    KeywordExtends,
+
+    #[token("IMPLEMENTS", ignore(case))]
+    KeywordImplements,
+
+    #[token("INTERFACE", ignore(case))]
+    KeywordInterface,
+
+    #[token("END_INTERFACE", ignore(case))]
+    #[token("ENDINTERFACE", ignore(case))]
+    KeywordEndInterface,
+
+    #[token("NAMESPACE", ignore(case))]
+    KeywordNamespace,
+
+    #[token("END_NAMESPACE", ignore(case))]
+    #[token("ENDNAMESPACE", ignore(case))]
+    KeywordEndNamespace,
+
+    #[token("USING", ignore(case))]
+    KeywordUsing,
+
     #[token("VAR_GLOBAL", ignore(case))]
     #[token("VARGLOBAL", ignore(case))]
     KeywordVarGlobal,
 
+    #[token("VAR_CONFIG", ignore(case))]
+    #[token("VARCONFIG", ignore(case))]
+    KeywordVarConfig,
+
     #[token("VAR_IN_OUT", ignore(case))]
     #[token("VARINOUT", ignore(case))]
     KeywordVarInOut,
@@ -141,6 +204,24 @@ pub enum Token {
     #[token("ENDACTIONS", ignore(case))]
     KeywordEndActions,
 
+    #[token("INITIAL_STEP", ignore(case))]
+    KeywordInitialStep,
+
+    #[token("END_STEP", ignore(case))]
+    KeywordEndStep,
+
+    #[token("TRANSITION", ignore(case))]
+    KeywordTransition,
+
+    #[token("END_TRANSITION", ignore(case))]
+    KeywordEndTransition,
+
+    #[token("INITIAL_STATE", ignore(case))]
+    KeywordInitialState,
+
+    #[token("END_STATE", ignore(case))]
+    KeywordEndState,
+
     #[token(":")]
     KeywordColon,
 
@@ -153,6 +234,9 @@ pub enum Token {
     #[token("=>")]
     KeywordOutputAssignment,
 
+    #[token("REF=", ignore(case))]
+    KeywordReferenceAssignment,
+
     #[token("(")]
     KeywordParensOpen,
 
@@ -246,6 +330,9 @@ pub enum Token {
     #[token("REFTO", ignore(case))]
     KeywordRef,
 
+    #[regex(r"REFERENCE\s+TO", ignore(case))]
+    KeywordReferenceTo,
+
     #[token("ARRAY", ignore(case))]
     KeywordArray,
 
@@ -302,6 +389,10 @@ pub enum Token {
     #[token("^")]
     OperatorDeref,
 
+    /// the `#` prefix of local variables in SCL (e.g. `#counter`)
+    #[token("#")]
+    LocalPrefix,
+
     #[token("MOD", ignore(case))]
     OperatorModulo,
 
@@ -22,52 +22,88 @@ use std::io::Write;
 use std::str::FromStr;
 
 use clap::ArgEnum;
-use codegen::CodeGen;
+use codegen::{generators::wasm_generator, CodeGen};
 use glob::glob;
 use inkwell::passes::PassBuilderOptions;
 use std::path::Path;
 
+use abi_metadata::AbiMetadata;
 use ast::{LinkageType, PouType, SourceRange};
-use cli::CompileParameters;
+use cli::{
+    BuildParameters, CompileParameters, DocParameters, FormatParameters, InspectParameters,
+    MigrateParameters, PackageParameters, RunParameters, SymbolicateParameters, TestParameters,
+};
+use config_constants::ConfigConstants;
+use defines::Defines;
 use diagnostics::Diagnostic;
+use dialect::Dialect;
 use encoding_rs::Encoding;
 use encoding_rs_io::DecodeReaderBytesBuilder;
 use index::Index;
+use init_order::InitializationOrder;
 use inkwell::context::Context;
-use inkwell::targets::{
-    CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine, TargetTriple,
-};
+use inkwell::targets::{FileType, RelocMode, TargetMachine, TargetTriple};
 use lexer::IdProvider;
+use library_package::{LibraryLock, LibraryManifest, LibraryPackage, LockViolation};
+use linker_script::SectionLayout;
+use profile::{ConformanceReport, Profile};
+use project::ProjectConfiguration;
 use resolver::{AstAnnotations, StringLiterals};
+use simulation::symbols::SourceFile;
+use size_budget::SizeReport;
 use std::{fs::File, io::Read};
-use validation::Validator;
+use task_configuration::TaskConfiguration;
+use validation::{PrototypeValidator, RaceValidator, SafetyValidator, UsageValidator, Validator};
 
 use crate::ast::CompilationUnit;
-use crate::diagnostics::Diagnostician;
+use crate::diagnostics::{Diagnostician, LegacyDiagnosticAssessor};
 use crate::resolver::{AnnotationMapImpl, TypeAnnotator};
+pub mod abi_metadata;
 mod ast;
-mod builtins;
+pub mod builtins;
+pub mod c_header;
 pub mod cli;
 mod codegen;
+pub mod config_constants;
+mod config_file;
+pub mod defines;
 pub mod diagnostics;
+pub mod dialect;
+pub mod documentation;
 pub mod expression_path;
+pub mod formatter;
 mod hardware_binding;
 pub mod index;
+mod init_order;
 mod lexer;
+pub mod library_package;
 mod linker;
+pub mod linker_script;
+mod llvm_options;
+pub mod lsp;
+pub mod migration;
 mod parser;
+pub mod pretty_printers;
+pub mod profile;
+pub mod project;
 mod resolver;
+pub mod simulation;
+pub mod size_budget;
+pub mod symbolication;
+pub mod task_configuration;
 mod test_utils;
 
 pub mod runner;
 mod typesystem;
 mod validation;
+mod wcet;
 
 #[macro_use]
 #[cfg(test)]
 extern crate pretty_assertions;
 
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Debug, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum FormatOption {
     Static,
     PIC,
@@ -77,6 +113,26 @@ pub enum FormatOption {
     IR,
 }
 
+/// the kinds of output selected with `--output-type`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ArgEnum)]
+pub enum OutputType {
+    /// an object file, the linker is not run
+    Obj,
+    /// a shared library, e.g. loaded by the runtime
+    Shared,
+    /// an executable the object is statically linked into
+    Static,
+}
+
+impl From<OutputType> for FormatOption {
+    fn from(val: OutputType) -> Self {
+        match val {
+            OutputType::Obj | OutputType::Static => FormatOption::Static,
+            OutputType::Shared => FormatOption::Shared,
+        }
+    }
+}
+
 #[derive(PartialEq, Debug, Clone, Copy, ArgEnum)]
 pub enum ConfigFormat {
     JSON,
@@ -100,12 +156,35 @@ pub struct CompileOptions {
     pub output: String,
     pub target: Option<String>,
     pub optimization: OptimizationLevel,
+    /// emit reflection tables describing the members of structs and POU instances
+    pub reflection: bool,
+    /// emit DWARF debug information, so the application can be debugged at the source level
+    pub debug: bool,
+    /// the options passed to LLVM's internal command line parser (see `llvm_options`)
+    pub llvm_args: Vec<String>,
+    /// keep calls in tail position from being replaced by jumps
+    pub disable_tail_calls: bool,
+    /// compute float operations without strict IEEE semantics (see `codegen::generators::fast_math`)
+    pub fast_math: bool,
+    /// the number of threads generating the POUs, `0` uses every core (see `codegen::parallel`)
+    pub jobs: usize,
+    /// the CPU to generate code for (e.g. `cortex-a53`), `generic` if not set
+    pub cpu: Option<String>,
+    /// the features of the CPU to enable or disable (e.g. `+neon,-fp16`)
+    pub features: Option<String>,
+    /// the objects hold bitcode optimized and compiled together with the other units by the
+    /// linker (link-time optimization)
+    pub lto: bool,
+    /// check the indexes of array accesses at runtime (see `codegen::generators::bounds_check`)
+    pub check_bounds: bool,
 }
 
 pub struct LinkOptions {
     pub libraries: Vec<String>,
     pub library_pathes: Vec<String>,
     pub sysroot: Option<String>,
+    /// the location of the map file written by the linker
+    pub map_file: Option<String>,
 }
 
 struct ConfigurationOptions {
@@ -113,20 +192,105 @@ struct ConfigurationOptions {
     output: String,
 }
 
+/// the language profile the sources have to conform to
+pub struct ProfileOptions {
+    pub profile: Profile,
+    /// the file the conformance report is written to
+    pub report: Option<String>,
+}
+
+/// the options of the compilation of sources into a module (see `compile_module_with_options`)
+pub struct ModuleOptions<'a> {
+    /// the task configuration used to detect possible data races
+    pub tasks: Option<&'a TaskConfiguration>,
+    /// replace the initial values of the sources' `{attribute 'config_const'}` global variables
+    pub constants: Option<&'a ConfigConstants>,
+    /// the symbols queried by `__DEFINED` and `__OPTION`
+    pub defines: Option<&'a Defines>,
+    /// the ST dialect the sources are written in
+    pub dialect: Dialect,
+    /// the profile the sources have to conform to, the compilation fails if they violate it
+    pub profile: Option<&'a ProfileOptions>,
+    /// annotate the generated code for WCET analyzers and write the bounds of the annotated loops
+    /// to the given file (see `wcet`)
+    pub wcet_annotations: Option<&'a str>,
+    /// the file the initialization order of the global variables is written to (see `init_order`)
+    pub init_order: Option<&'a str>,
+    /// compute the float operations of all POUs with fast-math semantics
+    pub fast_math: bool,
+    /// check the indexes of array accesses at runtime
+    pub check_bounds: bool,
+    /// instrument the generated POUs with calls to the simulation's debugger, the hooks identify a
+    /// source by its index in the sources followed by the includes
+    pub debug_hooks: bool,
+    /// describe the generated POUs and variables in DWARF debug information
+    pub debug_info: bool,
+    /// declare the function blocks implemented by the runtime of hosted targets (e.g. `FILE_OPEN`)
+    pub runtime_library: bool,
+    /// the number of threads generating the POUs unless they are instrumented or described in
+    /// debug information
+    pub jobs: usize,
+}
+
+impl Default for ModuleOptions<'_> {
+    fn default() -> Self {
+        ModuleOptions {
+            tasks: None,
+            constants: None,
+            defines: None,
+            dialect: Dialect::default(),
+            profile: None,
+            wcet_annotations: None,
+            init_order: None,
+            fast_math: false,
+            check_bounds: false,
+            debug_hooks: false,
+            debug_info: false,
+            runtime_library: false,
+            jobs: 1,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, ArgEnum)]
 pub enum ErrorFormat {
     Rich,
     Clang,
 }
 
+/// the IEC 61131-3 conformance level used to assess diagnostics
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ArgEnum)]
+pub enum Conformance {
+    /// violations of IEC 61131-3's typing rules are reported as errors
+    Strict,
+    /// violations tolerated by legacy code-bases (e.g. `IF int_var THEN`) are reported as warnings
+    Legacy,
+}
+
+/// the errors detected by checks generated into the application (`--check`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ArgEnum)]
+pub enum RuntimeCheck {
+    /// the indexes of array accesses are checked against the bounds of the array
+    Bounds,
+}
+
+/// the optimization levels, also given like clang's `-O0` to `-O3` and `-Os`
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum)]
 pub enum OptimizationLevel {
+    #[clap(alias = "0")]
     None,
+    #[clap(alias = "1")]
     Less,
+    #[clap(alias = "2")]
     Default,
+    #[clap(alias = "3")]
     Aggressive,
+    /// optimizes like `default` without the optimizations growing the code
+    #[clap(alias = "s")]
+    Size,
 }
 
+/// maps the level to the level of the target machine generating the code
 impl From<OptimizationLevel> for inkwell::OptimizationLevel {
     fn from(val: OptimizationLevel) -> Self {
         match val {
@@ -134,17 +298,55 @@ impl From<OptimizationLevel> for inkwell::OptimizationLevel {
             OptimizationLevel::Less => inkwell::OptimizationLevel::Less,
             OptimizationLevel::Default => inkwell::OptimizationLevel::Default,
             OptimizationLevel::Aggressive => inkwell::OptimizationLevel::Aggressive,
+            //the code generator has no level of its own for size, like clang's `-Os` it
+            //generates code at `default`, the size is reduced by the pass pipeline (`default<Os>`)
+            OptimizationLevel::Size => inkwell::OptimizationLevel::Default,
         }
     }
 }
 
 impl OptimizationLevel {
+    /// the name of the level as given on the command line, e.g. `aggressive`
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            OptimizationLevel::None => "none",
+            OptimizationLevel::Less => "less",
+            OptimizationLevel::Default => "default",
+            OptimizationLevel::Aggressive => "aggressive",
+            OptimizationLevel::Size => "size",
+        }
+    }
+
+    /// the pass pipeline of the new pass manager run before the code is emitted
     fn opt_params(&self) -> &str {
         match self {
             OptimizationLevel::None => "default<O0>",
             OptimizationLevel::Less => "default<O1>",
             OptimizationLevel::Default => "default<O2>",
             OptimizationLevel::Aggressive => "default<O3>",
+            OptimizationLevel::Size => "default<Os>",
+        }
+    }
+
+    /// the passes run on a unit before it is optimized with the others at link time
+    fn lto_pre_link_params(&self) -> &str {
+        match self {
+            OptimizationLevel::None => "lto-pre-link<O0>",
+            OptimizationLevel::Less => "lto-pre-link<O1>",
+            OptimizationLevel::Default => "lto-pre-link<O2>",
+            OptimizationLevel::Aggressive => "lto-pre-link<O3>",
+            OptimizationLevel::Size => "lto-pre-link<Os>",
+        }
+    }
+
+    /// the level of the linker's link-time optimization (e.g. `--lto-O2`)
+    fn get_lto_level(&self) -> u32 {
+        match self {
+            OptimizationLevel::None => 0,
+            OptimizationLevel::Less => 1,
+            OptimizationLevel::Default => 2,
+            OptimizationLevel::Aggressive => 3,
+            OptimizationLevel::Size => 2,
         }
     }
 }
@@ -154,6 +356,12 @@ impl OptimizationLevel {
 pub struct CompileResult {
     pub index: Index,
     pub objects: Vec<FilePath>,
+    /// the data sections the generated variables were placed in
+    pub sections: Vec<SectionLayout>,
+    /// the size of the generated code and variables
+    pub sizes: SizeReport,
+    /// the ABI metadata embedded into the generated code
+    pub abi: AbiMetadata,
 }
 
 /// SourceContainers offer source-code to be compiled via the load_source function.
@@ -280,28 +488,13 @@ fn persist_to_obj(
     triple: &TargetTriple,
     optimization: OptimizationLevel,
 ) -> Result<(), Diagnostic> {
-    let initialization_config = &InitializationConfig::default();
-    Target::initialize_all(initialization_config);
-
-    let target = Target::from_triple(triple).map_err(|it| {
-        Diagnostic::codegen_error(
-            &format!("Invalid target-tripple '{:}' - {:?}", triple, it),
-            SourceRange::undefined(),
-        )
-    })?;
-    let machine = target
-        .create_target_machine(
-            triple,
-            //TODO : Add cpu features as optionals
-            "generic", //TargetMachine::get_host_cpu_name().to_string().as_str(),
-            "",        //TargetMachine::get_host_cpu_features().to_string().as_str(),
-            optimization.into(),
-            reloc,
-            CodeModel::Default,
-        )
-        .ok_or_else(|| {
-            Diagnostic::codegen_error("Cannot create target machine.", SourceRange::undefined())
-        });
+    let machine = abi_metadata::create_configured_target_machine(
+        triple,
+        codegen.get_target_cpu(),
+        codegen.get_target_features(),
+        optimization.into(),
+        reloc,
+    );
 
     ////Run the passes
     machine.and_then(|it| {
@@ -372,6 +565,60 @@ pub fn persist_to_shared_object(
     )
 }
 
+/// Runs the pass pipeline of the given optimization on the LLVM module before it is persisted as
+/// IR or bitcode, without optimization the module is kept as generated
+fn optimize(
+    codegen: &CodeGen,
+    output: &str,
+    triple: &TargetTriple,
+    optimization: OptimizationLevel,
+) -> Result<(), Diagnostic> {
+    if optimization == OptimizationLevel::None {
+        return Ok(());
+    }
+    let machine = abi_metadata::create_configured_target_machine(
+        triple,
+        codegen.get_target_cpu(),
+        codegen.get_target_features(),
+        optimization.into(),
+        RelocMode::Default,
+    )?;
+    codegen
+        .module
+        .run_passes(
+            optimization.opt_params(),
+            &machine,
+            PassBuilderOptions::create(),
+        )
+        .map_err(|it| Diagnostic::llvm_error(output, &it))
+}
+
+/// Runs the optimizations preceding link-time optimization on the given LLVM module and saves it
+/// as bitcode, the linker optimizes it together with the other units and generates their code
+fn persist_for_lto(
+    codegen: CodeGen,
+    output: &str,
+    triple: &TargetTriple,
+    optimization: OptimizationLevel,
+) -> Result<(), Diagnostic> {
+    let machine = abi_metadata::create_configured_target_machine(
+        triple,
+        codegen.get_target_cpu(),
+        codegen.get_target_features(),
+        optimization.into(),
+        RelocMode::PIC,
+    )?;
+    codegen
+        .module
+        .run_passes(
+            optimization.lto_pre_link_params(),
+            &machine,
+            PassBuilderOptions::create(),
+        )
+        .map_err(|it| Diagnostic::llvm_error(output, &it))?;
+    persist_to_bitcode(codegen, output)
+}
+
 ///
 /// Persists the given LLVM module into a bitcode file
 ///
@@ -413,12 +660,82 @@ pub fn persist_to_ir(codegen: CodeGen, output: &str) -> Result<(), Diagnostic> {
 /// * `sources` - the source to be compiled
 /// * `encoding` - The encoding to parse the files, None for UTF-8
 pub fn compile_module<'c, T: SourceContainer>(
+    context: &'c Context,
+    sources: Vec<T>,
+    includes: Vec<T>,
+    encoding: Option<&'static Encoding>,
+    diagnostician: Diagnostician,
+) -> Result<(Index, CodeGen<'c>), Diagnostic> {
+    compile_module_with_tasks(
+        context,
+        sources,
+        includes,
+        encoding,
+        diagnostician,
+        None,
+        Dialect::default(),
+    )
+}
+
+///
+/// Compiles the given source into a `codegen::CodeGen` using the provided context and
+/// checks the access to global variables across the given tasks
+///
+/// # Arguments
+///
+/// * `context` - the LLVM Context to be used for the compilation
+/// * `sources` - the source to be compiled
+/// * `encoding` - The encoding to parse the files, None for UTF-8
+/// * `tasks` - the task configuration used to detect possible data races
+/// * `dialect` - the ST dialect the sources are written in
+pub fn compile_module_with_tasks<'c, T: SourceContainer>(
+    context: &'c Context,
+    sources: Vec<T>,
+    includes: Vec<T>,
+    encoding: Option<&'static Encoding>,
+    diagnostician: Diagnostician,
+    tasks: Option<&TaskConfiguration>,
+    dialect: Dialect,
+) -> Result<(Index, CodeGen<'c>), Diagnostic> {
+    compile_module_with_options(
+        context,
+        sources,
+        includes,
+        encoding,
+        diagnostician,
+        &ModuleOptions {
+            tasks,
+            dialect,
+            ..ModuleOptions::default()
+        },
+    )
+}
+
+/// Compiles the given source like `compile_module_with_tasks` with the given options (see
+/// `ModuleOptions`)
+pub(crate) fn compile_module_with_options<'c, T: SourceContainer>(
     context: &'c Context,
     sources: Vec<T>,
     includes: Vec<T>,
     encoding: Option<&'static Encoding>,
     mut diagnostician: Diagnostician,
+    options: &ModuleOptions,
 ) -> Result<(Index, CodeGen<'c>), Diagnostic> {
+    let ModuleOptions {
+        tasks,
+        constants,
+        defines,
+        dialect,
+        profile,
+        wcet_annotations,
+        init_order,
+        fast_math,
+        check_bounds,
+        debug_hooks,
+        debug_info,
+        runtime_library,
+        jobs,
+    } = *options;
     let mut full_index = Index::default();
     let mut id_provider = IdProvider::default();
 
@@ -426,27 +743,59 @@ pub fn compile_module<'c, T: SourceContainer>(
 
     // ### PHASE 1 ###
     // parse & index everything
-    let (index, mut units) = parse_and_index(
+    let mut units = parse_units(
         sources,
         encoding,
         &id_provider,
         &mut diagnostician,
         LinkageType::Internal,
+        dialect,
     )?;
-    full_index.import(index);
-    all_units.append(&mut units);
-
-    let (includes_index, mut includes_units) = parse_and_index(
+    let mut includes_units = parse_units(
         includes,
         encoding,
         &id_provider,
         &mut diagnostician,
         LinkageType::External,
+        dialect,
     )?;
-    full_index.import(includes_index);
+
+    //the configured values replace the initializers before they are indexed
+    if let Some(constants) = constants {
+        let applied = units
+            .iter_mut()
+            .flat_map(|(_, _, unit, _)| constants.apply(unit, &mut id_provider))
+            .collect::<Vec<_>>();
+        constants.check_declared(&applied)?;
+    }
+
+    //the declarations of a namespace may be referenced from every unit
+    ast::resolve_namespaces(
+        units
+            .iter_mut()
+            .chain(includes_units.iter_mut())
+            .map(|(_, _, unit, _)| unit)
+            .collect(),
+    );
+
+    full_index.import(index_units(&mut units, &id_provider));
+    all_units.append(&mut units);
+    full_index.import(index_units(&mut includes_units, &id_provider));
     all_units.append(&mut includes_units);
 
-    // ### PHASE 1.1 resolve constant literal values
+    if runtime_library {
+        let runtime_library = builtins::parse_runtime_library(id_provider.clone());
+        full_index.import(index::visitor::visit(&runtime_library, id_provider.clone()));
+    }
+
+    // ### PHASE 1.1 merge the members of super classes into the classes extending them
+    full_index.merge_inherited_members();
+
+    if let Some(defines) = defines {
+        full_index.set_defines(defines.clone());
+    }
+
+    // ### PHASE 1.2 resolve constant literal values
     let (mut full_index, _unresolvables) =
         resolver::const_evaluator::evaluate_constants(full_index);
 
@@ -455,74 +804,208 @@ pub fn compile_module<'c, T: SourceContainer>(
     let mut annotated_units: Vec<CompilationUnit> = Vec::new();
     let mut all_annotations = AnnotationMapImpl::default();
     let mut all_literals = StringLiterals::default();
-    for (file_id, syntax_errors, unit) in all_units.into_iter() {
+    let mut usage_validator = UsageValidator::new();
+    let mut prototype_validator = PrototypeValidator::new();
+    let mut race_validator = RaceValidator::new();
+    let mut safety_validator = SafetyValidator::new();
+    let mut conformance = profile.map(|it| ConformanceReport::new(it.profile));
+    let mut file_ids = Vec::new();
+    let mut source_files = Vec::new();
+    for (file_id, syntax_errors, unit, source_file) in all_units.into_iter() {
         let (annotations, string_literals) = TypeAnnotator::visit_unit(&full_index, &unit);
 
-        let mut validator = Validator::new();
+        let mut validator = Validator::with_dialect(dialect);
         validator.visit_unit(&annotations, &full_index, &unit);
         //log errors
         diagnostician.handle(syntax_errors, file_id);
         diagnostician.handle(validator.diagnostics(), file_id);
+        if let Some(constants) = constants {
+            diagnostician.handle(constants.validate(&unit, &full_index), file_id);
+        }
+        usage_validator.visit_unit(&annotations, &unit);
+        prototype_validator.visit_unit(&unit);
+        race_validator.visit_unit(&annotations, &full_index, &unit);
+        if let Some(conformance) = conformance.as_mut() {
+            let violations = safety_validator.visit_unit(&annotations, &full_index, &unit);
+            conformance.add(&source_file, &violations);
+            diagnostician.handle(violations.into_iter().map(|(_, it)| it).collect(), file_id);
+        }
 
+        file_ids.push(file_id);
+        source_files.push(source_file);
         annotated_units.push(unit);
         all_annotations.import(annotations);
         all_literals.import(string_literals);
     }
 
+    //variables may be read, prototypes implemented and POUs called in a different unit, so these
+    //are reported once all units are visited
+    let initialization_order = InitializationOrder::new(&annotated_units, &full_index);
+    for ((file_id, unit), source_file) in file_ids
+        .iter()
+        .zip(annotated_units.iter())
+        .zip(source_files.iter())
+    {
+        diagnostician.handle(usage_validator.diagnostics_for(unit), *file_id);
+        diagnostician.handle(prototype_validator.diagnostics_for(unit), *file_id);
+        diagnostician.handle(race_validator.single_writer_diagnostics_for(unit), *file_id);
+        diagnostician.handle(initialization_order.diagnostics_for(unit), *file_id);
+        if let Some(tasks) = tasks {
+            diagnostician.handle(race_validator.diagnostics_for(tasks, unit), *file_id);
+        }
+        if let Some(conformance) = conformance.as_mut() {
+            let recursions = safety_validator.find_recursions(unit);
+            conformance.add(source_file, &recursions);
+            diagnostician.handle(recursions.into_iter().map(|(_, it)| it).collect(), *file_id);
+        }
+    }
+
+    if let (Some(profile), Some(conformance)) = (profile, conformance) {
+        if let Some(report) = &profile.report {
+            File::create(report)
+                .and_then(|mut it| it.write_all(conformance.to_string().as_bytes()))
+                .map_err(|it| Diagnostic::GeneralError {
+                    err_no: diagnostics::ErrNo::general__io_err,
+                    message: it.to_string(),
+                })?;
+        }
+        if !conformance.is_conformant() {
+            return Err(Diagnostic::profile_violation(
+                &profile.profile.to_string(),
+                conformance.get_violation_count(),
+            ));
+        }
+    }
+
+    if let Some(wcet_annotations) = wcet_annotations {
+        File::create(wcet_annotations)
+            .and_then(|mut it| {
+                it.write_all(wcet::generate_ais_annotations(&annotated_units).as_bytes())
+            })
+            .map_err(|it| Diagnostic::GeneralError {
+                err_no: diagnostics::ErrNo::general__io_err,
+                message: it.to_string(),
+            })?;
+    }
+
+    if let Some(init_order) = init_order {
+        File::create(init_order)
+            .and_then(|mut it| it.write_all(initialization_order.to_string().as_bytes()))
+            .map_err(|it| Diagnostic::GeneralError {
+                err_no: diagnostics::ErrNo::general__io_err,
+                message: it.to_string(),
+            })?;
+    }
+
     //Merge the new indices with the full index
     full_index.import(std::mem::take(&mut all_annotations.new_index));
 
     // ### PHASE 3 ###
     // - codegen
-    let code_generator = codegen::CodeGen::new(context, "main");
+    let mut code_generator = codegen::CodeGen::new(context, "main");
+    if debug_info {
+        if let Some(main_file) = source_files.first() {
+            code_generator = code_generator.with_debug_info(main_file);
+        }
+    }
+    if wcet_annotations.is_some() {
+        code_generator = code_generator.with_timing_annotations();
+    }
+    if fast_math {
+        code_generator = code_generator.with_fast_math();
+    }
+    if check_bounds {
+        code_generator = code_generator.with_bounds_check();
+    }
 
     let annotations = AstAnnotations::new(all_annotations, id_provider.next_id());
+    let jobs = codegen::parallel::get_thread_count(jobs);
+    if jobs > 1 && !debug_hooks && !debug_info {
+        //every thread generates its own index into a module of its own context
+        let literals = all_literals.clone();
+        code_generator.generate_llvm_index(&annotations, all_literals, &full_index)?;
+        code_generator.generate_in_parallel(
+            &annotated_units,
+            &annotations,
+            &literals,
+            &full_index,
+            jobs,
+        )?;
+        return Ok((full_index, code_generator));
+    }
     //Associate the index type with LLVM types
     let llvm_index = code_generator.generate_llvm_index(&annotations, all_literals, &full_index)?;
-    for unit in annotated_units {
-        code_generator.generate(&unit, &annotations, &full_index, &llvm_index)?;
+    for (file, (unit, source_file)) in annotated_units.iter().zip(source_files.iter()).enumerate() {
+        if debug_hooks {
+            code_generator.generate_with_debug_hooks(
+                unit,
+                file as u32,
+                &annotations,
+                &full_index,
+                &llvm_index,
+            )?;
+        } else if debug_info {
+            code_generator.generate_with_debug_info(
+                unit,
+                source_file,
+                &annotations,
+                &full_index,
+                &llvm_index,
+            )?;
+        } else {
+            code_generator.generate(unit, &annotations, &full_index, &llvm_index)?;
+        }
     }
+    code_generator.finalize_debug_info();
 
     Ok((full_index, code_generator))
 }
 
-type Units = Vec<(usize, Vec<Diagnostic>, CompilationUnit)>;
-fn parse_and_index<T: SourceContainer>(
+type Units = Vec<(usize, Vec<Diagnostic>, CompilationUnit, SourceFile)>;
+fn parse_units<T: SourceContainer>(
     source: Vec<T>,
     encoding: Option<&'static Encoding>,
     id_provider: &IdProvider,
     diagnostician: &mut Diagnostician,
     linkage: LinkageType,
-) -> Result<(Index, Units), Diagnostic> {
-    let mut index = Index::default();
-
+    dialect: Dialect,
+) -> Result<Units, Diagnostic> {
     let mut units = Vec::new();
 
-    //parse the builtins into the index
-    let builtins = builtins::parse_built_ins(id_provider.clone());
-    index.import(index::visitor::visit(&builtins, id_provider.clone()));
-
     for container in source {
         let location: String = container.get_location().into();
         let e = container
             .load_source(encoding)
             .map_err(|err| Diagnostic::io_read_error(location.as_str(), err.as_str()))?;
 
-        let (mut parse_result, diagnostics) = parser::parse(
-            lexer::lex_with_ids(e.source.as_str(), id_provider.clone()),
+        let (parse_result, diagnostics) = parser::parse(
+            lexer::lex_with_dialect(e.source.as_str(), id_provider.clone(), dialect),
             linkage,
         );
 
-        //pre-process the ast (create inlined types)
-        ast::pre_process(&mut parse_result, id_provider.clone());
-        //index the pou
-        index.import(index::visitor::visit(&parse_result, id_provider.clone()));
-
+        //the line table maps the offsets of the generated code's debug information
+        let source_file = SourceFile::new(&location, &e.source);
         //register the file with the diagnstician, so diagnostics are later able to show snippets from the code
         let file_id = diagnostician.register_file(location.clone(), e.source);
-        units.push((file_id, diagnostics, parse_result));
+        units.push((file_id, diagnostics, parse_result, source_file));
+    }
+    Ok(units)
+}
+
+fn index_units(units: &mut Units, id_provider: &IdProvider) -> Index {
+    let mut index = Index::default();
+
+    //parse the builtins into the index
+    let builtins = builtins::parse_built_ins(id_provider.clone());
+    index.import(index::visitor::visit(&builtins, id_provider.clone()));
+
+    for (_, _, unit, _) in units.iter_mut() {
+        //pre-process the ast (create inlined types)
+        ast::pre_process(unit, id_provider.clone());
+        //index the pou
+        index.import(index::visitor::visit(unit, id_provider.clone()));
     }
-    Ok((index, units))
+    index
 }
 
 fn create_file_paths(inputs: &[String]) -> Result<Vec<FilePath>, Diagnostic> {
@@ -556,12 +1039,34 @@ fn create_file_paths(inputs: &[String]) -> Result<Vec<FilePath>, Diagnostic> {
 /// Links any provided libraries
 /// Returns the location of the output file
 pub fn build_with_params(parameters: CompileParameters) -> Result<(), Diagnostic> {
-    let files = create_file_paths(&parameters.input)?;
-    let includes = if parameters.includes.is_empty() {
+    let mut files = create_file_paths(&parameters.input)?;
+    let mut includes = if parameters.includes.is_empty() {
         vec![]
     } else {
         create_file_paths(&parameters.includes)?
     };
+    let target = get_target_triple(parameters.target.as_deref());
+    //the declarations of a library package are included, its object is linked like an input file
+    let mut lock = parameters
+        .library_lock
+        .as_deref()
+        .map(LibraryLock::load)
+        .transpose()?;
+    let recorded_libraries = lock.as_ref().map(|it| it.libraries.len());
+    //the extracted packages are removed when the directory is dropped at the end of the build
+    let package_directory = tempfile::tempdir().map_err(|it| Diagnostic::GeneralError {
+        err_no: diagnostics::ErrNo::general__io_err,
+        message: it.to_string(),
+    })?;
+    for package in &parameters.library_packages {
+        let library = load_library_package(package, &target)?;
+        if let (Some(lock), Some(lock_file)) = (&mut lock, &parameters.library_lock) {
+            verify_library(lock, &library.manifest, lock_file)?;
+        }
+        let (declarations, object) = library.extract(package_directory.path())?;
+        includes.push(declarations.into());
+        files.push(object.into());
+    }
     let output = parameters
         .output_name()
         .ok_or_else(|| Diagnostic::param_error("Missing parameter: output-name"))?;
@@ -577,31 +1082,92 @@ pub fn build_with_params(parameters: CompileParameters) -> Result<(), Diagnostic
             output: config.to_owned(),
         });
 
+    let tasks = parameters
+        .task_config
+        .as_deref()
+        .map(TaskConfiguration::load)
+        .transpose()?;
+
+    let constants = parameters
+        .config_constants
+        .as_deref()
+        .map(ConfigConstants::load)
+        .transpose()?;
+
+    let defines = Defines::parse(&parameters.defines)?;
+
+    let profile = parameters.profile.map(|profile| ProfileOptions {
+        profile,
+        report: parameters.profile_report.clone(),
+    });
+
+    let project = parameters
+        .project
+        .as_deref()
+        .map(ProjectConfiguration::load)
+        .transpose()?
+        .unwrap_or_default();
+    //without a selected conformance level the violations are assessed like in the project's dialect
+    let conformance = parameters
+        .conformance
+        .unwrap_or_else(|| project.dialect.unwrap_or_default().conformance());
+
     let compile_options = CompileOptions {
         output,
         target: parameters.target,
         format: out_format,
-        optimization: parameters.optimization,
+        optimization: parameters.optimization_or_default(),
+        reflection: parameters.reflection,
+        debug: parameters.debug,
+        llvm_args: llvm_options::collect_llvm_args(&parameters.llvm_args),
+        disable_tail_calls: parameters.disable_tail_calls,
+        fast_math: parameters.fast_math,
+        jobs: parameters.jobs,
+        cpu: parameters.cpu,
+        features: parameters.target_features,
+        lto: parameters.lto,
+        check_bounds: parameters.checks.contains(&RuntimeCheck::Bounds),
     };
 
-    let link_options = if !parameters.skip_linking {
+    let link_options = if !parameters.is_linking_skipped() {
         Some(LinkOptions {
             libraries: parameters.libraries,
             library_pathes: parameters.library_pathes,
             sysroot: parameters.sysroot,
+            map_file: parameters.map_file,
         })
     } else {
         None
     };
 
-    let target = get_target_triple(compile_options.target.as_deref());
-    let compile_result = build(
+    let compile_result = build_with_tasks(
         files,
         includes,
         &compile_options,
         parameters.encoding,
-        &parameters.error_format,
+        create_diagnostician(&parameters.error_format, conformance),
         &target,
+        ModuleOptions {
+            tasks: tasks.as_ref(),
+            constants: constants.as_ref(),
+            defines: Some(&defines),
+            dialect: project.dialect.unwrap_or_default(),
+            profile: profile.as_ref(),
+            wcet_annotations: parameters.wcet_annotations.as_deref(),
+            init_order: parameters.init_order.as_deref(),
+            ..ModuleOptions::default()
+        },
+    )?;
+
+    size_budget::check_budget(
+        "code size",
+        compile_result.sizes.code.as_ref(),
+        parameters.max_code_size,
+    )?;
+    size_budget::check_budget(
+        "RAM usage",
+        compile_result.sizes.ram.as_ref(),
+        parameters.max_ram,
     )?;
 
     if let Some(link_options) = link_options {
@@ -613,6 +1179,8 @@ pub fn build_with_params(parameters: CompileParameters) -> Result<(), Diagnostic
             link_options.libraries,
             &target,
             link_options.sysroot,
+            link_options.map_file.as_deref(),
+            compile_options.lto.then(|| compile_options.optimization),
         )?;
     }
 
@@ -629,9 +1197,467 @@ pub fn build_with_params(parameters: CompileParameters) -> Result<(), Diagnostic
             })?;
     }
 
+    if let Some(linker_script) = parameters.linker_script {
+        let script = linker_script::generate_linker_script(
+            &compile_result.sections,
+            &target.as_str().to_string_lossy(),
+        );
+        File::create(linker_script)
+            .and_then(|mut it| it.write_all(script.as_bytes()))
+            .map_err(|it| Diagnostic::GeneralError {
+                err_no: diagnostics::ErrNo::general__io_err,
+                message: it.to_string(),
+            })?;
+    }
+
+    if let Some(header) = parameters.header {
+        let content = c_header::generate_header(
+            &compile_result.index,
+            &compile_result.abi,
+            &c_header::get_include_guard(&header),
+        );
+        File::create(header)
+            .and_then(|mut it| it.write_all(content.as_bytes()))
+            .map_err(|it| Diagnostic::GeneralError {
+                err_no: diagnostics::ErrNo::general__io_err,
+                message: it.to_string(),
+            })?;
+    }
+
+    if compile_options.debug {
+        let printers = [
+            (
+                pretty_printers::get_gdb_script_name(&compile_options.output),
+                pretty_printers::generate_gdb_printers(&compile_result.index),
+            ),
+            (
+                pretty_printers::get_lldb_script_name(&compile_options.output),
+                pretty_printers::generate_lldb_printers(&compile_result.index),
+            ),
+        ];
+        for (script_name, script) in printers {
+            File::create(script_name)
+                .and_then(|mut it| it.write_all(script.as_bytes()))
+                .map_err(|it| Diagnostic::GeneralError {
+                    err_no: diagnostics::ErrNo::general__io_err,
+                    message: it.to_string(),
+                })?;
+        }
+    }
+
+    //libraries consumed for the first time are recorded once the build succeeded
+    if let (Some(lock), Some(lock_file)) = (&lock, &parameters.library_lock) {
+        if recorded_libraries != Some(lock.libraries.len()) {
+            lock.write(lock_file)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The driver function for `rusty build`
+/// Reads the given project file (or the one of the current directory)
+/// Compiles the listed sources with the project's options and links them into one output
+pub fn build_project_with_params(parameters: BuildParameters) -> Result<(), Diagnostic> {
+    let project_file = match parameters.project {
+        Some(project_file) => project_file,
+        None => project::find_project_file()?,
+    };
+    let arguments =
+        ProjectConfiguration::load(&project_file)?.get_compile_arguments(&project_file)?;
+    let parameters = CompileParameters::parse(arguments).map_err(|err| {
+        Diagnostic::param_error(&format!("Invalid project {}: {}", project_file, err))
+    })?;
+    build_with_params(parameters)
+}
+
+/// The driver function for `rusty migrate`
+/// Converts the vendor constructs of the given sources into portable IEC 61131-3 ST
+/// Writes the migrated sources and a change report into the output directory
+pub fn migrate_with_params(parameters: MigrateParameters) -> Result<(), Diagnostic> {
+    let files = create_file_paths(&parameters.input)?;
+    let migrated = migration::migrate(files, parameters.encoding, parameters.dialect)?;
+    migration::write_migration(&migrated, &parameters.output)
+}
+
+/// The driver function for `rusty format`
+/// Rewrites the given sources in the canonical layout, with `--check` the sources are left
+/// untouched and the ones that are not formatted are reported
+pub fn format_with_params(parameters: FormatParameters) -> Result<(), Diagnostic> {
+    let options = formatter::FormatOptions {
+        indent: parameters.indent,
+        keyword_case: parameters.keyword_case,
+        line_width: parameters.line_width,
+    };
+    let mut unformatted = vec![];
+    for container in create_file_paths(&parameters.input)? {
+        let location = container.get_location().to_string();
+        let source = container
+            .load_source(parameters.encoding)
+            .map_err(|err| Diagnostic::io_read_error(location.as_str(), err.as_str()))?;
+        let formatted = formatter::format(&source.source, &options, parameters.dialect)
+            .map_err(|reason| Diagnostic::unformattable_source(&location, &reason))?;
+        if formatted == source.source {
+            continue;
+        }
+        if parameters.check {
+            unformatted.push(location);
+        } else {
+            fs::write(&location, formatted)
+                .map_err(|err| Diagnostic::io_write_error(&location, err.to_string().as_str()))?;
+        }
+    }
+    if unformatted.is_empty() {
+        Ok(())
+    } else {
+        Err(Diagnostic::unformatted_sources(&unformatted))
+    }
+}
+
+/// The driver function for `rusty doc`
+/// Writes the API documentation of the given sources' POUs and data types to the output directory
+pub fn doc_with_params(parameters: DocParameters) -> Result<(), Diagnostic> {
+    let mut documentation = documentation::Documentation::default();
+    for container in create_file_paths(&parameters.input)? {
+        let location = container.get_location().to_string();
+        let source = container
+            .load_source(parameters.encoding)
+            .map_err(|err| Diagnostic::io_read_error(location.as_str(), err.as_str()))?;
+        let mut file_documentation = documentation::document(&source.source, parameters.dialect)
+            .map_err(|reason| Diagnostic::undocumentable_source(&location, &reason))?;
+        documentation.append(&mut file_documentation);
+    }
+    let output = Path::new(&parameters.output);
+    let file = output.join(format!("index.{}", parameters.format.get_extension()));
+    let file_name = file.to_string_lossy();
+    fs::create_dir_all(output)
+        .and_then(|_| fs::write(&file, documentation.render(parameters.format)))
+        .map_err(|err| Diagnostic::io_write_error(&file_name, err.to_string().as_str()))
+}
+
+/// The driver function for `rusty package`
+/// Compiles the given sources into a position independent object and writes it with the
+/// declarations and the documentation of the sources into a library package (see `library_package`)
+pub fn package_with_params(parameters: PackageParameters) -> Result<(), Diagnostic> {
+    let files = create_file_paths(&parameters.input)?;
+    let includes = if parameters.includes.is_empty() {
+        vec![]
+    } else {
+        create_file_paths(&parameters.includes)?
+    };
+    let name = parameters.library_name();
+    let target = get_target_triple(parameters.target.as_deref());
+    let object = std::env::temp_dir()
+        .join(format!("rusty-package-{}-{}.o", std::process::id(), name))
+        .to_string_lossy()
+        .to_string();
+
+    let compile_options = CompileOptions {
+        output: object.clone(),
+        target: parameters.target.clone(),
+        format: FormatOption::PIC,
+        optimization: parameters.optimization,
+        reflection: false,
+        debug: false,
+        llvm_args: vec![],
+        disable_tail_calls: false,
+        fast_math: false,
+        jobs: 1,
+        cpu: None,
+        features: None,
+        lto: false,
+        check_bounds: false,
+    };
+    build_with_tasks(
+        files.clone(),
+        includes,
+        &compile_options,
+        parameters.encoding,
+        Diagnostician::default(),
+        &target,
+        ModuleOptions {
+            dialect: parameters.dialect,
+            ..ModuleOptions::default()
+        },
+    )?;
+    let object_code =
+        fs::read(&object).map_err(|err| Diagnostic::io_read_error(&object, &err.to_string()))?;
+    fs::remove_file(&object)
+        .map_err(|err| Diagnostic::io_write_error(&object, &err.to_string()))?;
+
+    let mut declarations = String::new();
+    let mut documentation = documentation::Documentation::default();
+    for container in files {
+        let location = container.get_location().to_string();
+        let source = container
+            .load_source(parameters.encoding)
+            .map_err(|err| Diagnostic::io_read_error(location.as_str(), err.as_str()))?;
+        declarations += &library_package::strip_implementations(&source.source, parameters.dialect);
+        declarations.push('\n');
+        let mut file_documentation = documentation::document(&source.source, parameters.dialect)
+            .map_err(|reason| Diagnostic::undocumentable_source(&location, &reason))?;
+        documentation.append(&mut file_documentation);
+    }
+
+    let interface = documentation
+        .pous
+        .iter()
+        .map(|it| (it.name.clone(), library_package::describe_signature(it)))
+        .collect();
+    LibraryPackage {
+        manifest: LibraryManifest {
+            name,
+            version: parameters.version,
+            target: target.as_str().to_string_lossy().to_string(),
+            compiler: env!("CARGO_PKG_VERSION").to_string(),
+            interface,
+        },
+        declarations,
+        documentation: documentation.to_markdown(),
+        object: object_code,
+    }
+    .write(&parameters.output_name())
+}
+
+/// loads the given library package, which has to be compiled for the given target
+fn load_library_package(
+    package: &str,
+    target: &TargetTriple,
+) -> Result<LibraryPackage, Diagnostic> {
+    let library = LibraryPackage::load(package)?;
+    let target = target.as_str().to_string_lossy();
+    if library.manifest.target != target {
+        return Err(Diagnostic::library_target_mismatch(
+            package,
+            &library.manifest.target,
+            &target,
+        ));
+    }
+    Ok(library)
+}
+
+/// verifies the library described by the given manifest against the version and interface
+/// recorded in the lock, records it if it is not recorded yet
+fn verify_library(
+    lock: &mut LibraryLock,
+    manifest: &LibraryManifest,
+    lock_file: &str,
+) -> Result<(), Diagnostic> {
+    lock.verify(manifest).map_err(|violation| match violation {
+        LockViolation::Version { recorded } => Diagnostic::incompatible_library_version(
+            &manifest.name,
+            &manifest.version.to_string(),
+            &recorded.to_string(),
+            lock_file,
+        ),
+        LockViolation::Interface(changes) => {
+            Diagnostic::library_interface_changed(&manifest.name, lock_file, &changes)
+        }
+    })
+}
+
+/// The driver function for `rusty inspect`
+/// Prints the exported POUs, instance layouts, retained area and ABI metadata of the given artifact
+pub fn inspect_with_params(parameters: InspectParameters) -> Result<(), Diagnostic> {
+    let records = abi_metadata::read_metadata(&parameters.input)?;
+    if records.is_empty() {
+        return Err(Diagnostic::param_error(&format!(
+            "{} contains no ABI metadata",
+            parameters.input
+        )));
+    }
+    for metadata in records {
+        println!("{}\n", metadata);
+    }
+    Ok(())
+}
+
+/// The driver function for `rusty symbolicate`
+/// Prints the POU, the source location and the surrounding source of every given address
+pub fn symbolicate_with_params(parameters: SymbolicateParameters) -> Result<(), Diagnostic> {
+    let map = symbolication::MapFile::load(&parameters.map_file)?;
+    let lines = parameters
+        .debug_info
+        .as_deref()
+        .map(symbolication::LineTable::load)
+        .transpose()?;
+    let mut addresses = parameters.addresses;
+    if let Some(backtrace) = &parameters.backtrace {
+        let text = fs::read_to_string(backtrace)
+            .map_err(|err| Diagnostic::io_read_error(backtrace, &err.to_string()))?;
+        addresses.extend(symbolication::find_addresses(&text));
+    }
+    let load_address = parameters.load_address.unwrap_or_default();
+    for (number, address) in addresses.into_iter().enumerate() {
+        let frame =
+            symbolication::symbolicate(address.wrapping_sub(load_address), &map, lines.as_ref());
+        let source = frame
+            .location
+            .as_ref()
+            .and_then(|it| fs::read_to_string(&it.file).ok());
+        println!(
+            "{}",
+            symbolication::format_frame(number, &frame, source.as_deref(), parameters.context)
+        );
+    }
+    Ok(())
+}
+
+/// The driver function for `rusty run`
+/// Executes the tasks of the given sources cyclically in the JIT, pacing the simulated time in real time
+/// Reloads changed sources between two cycles and serves a debugger if requested
+pub fn run_with_params(parameters: RunParameters) -> Result<(), Diagnostic> {
+    let sources = create_simulation_sources(
+        &parameters.input,
+        &parameters.includes,
+        parameters.encoding,
+        parameters.dialect,
+        //the hooks let the budget report the time spent per POU
+        parameters.debug_port.is_some() || parameters.budget,
+    )?;
+    let tasks = TaskConfiguration::load(&parameters.task_config)?;
+    let mut simulation = simulation::Simulation::new(sources, tasks, parameters.tick)?;
+    if let Some(seed) = parameters.seed {
+        simulation::seed_random(seed);
+    }
+    for force in &parameters.forces {
+        let (expression, value) = force.split_once(":=").ok_or_else(|| {
+            Diagnostic::param_error(&format!(
+                "Invalid force '{}', expected '<symbol> := <value>'",
+                force
+            ))
+        })?;
+        simulation.force(expression, value)?;
+    }
+    if let Some(file) = &parameters.restore_state {
+        let report = simulation.restore_snapshot(&simulation::Snapshot::load(file)?);
+        println!("{}", report);
+    }
+    if let Some(port) = parameters.debug_port {
+        println!("Waiting for a debugger on port {}", port);
+        let adapter = simulation::dap::DebugAdapter::listen(port)?;
+        simulation.attach_debugger(Box::new(adapter))?;
+    }
+    let tick = std::time::Duration::from_millis(simulation.get_tick());
+    while parameters
+        .cycles
+        .map_or(true, |it| simulation.get_cycle() < it)
+    {
+        let started = std::time::Instant::now();
+        simulation.run_cycle();
+        if parameters.budget {
+            for overrun in simulation
+                .get_task_times()
+                .iter()
+                .filter(|it| it.is_overrun())
+            {
+                println!("Cycle {}: {}", simulation.get_cycle(), overrun);
+            }
+        }
+        if let (Some(file), Some(interval)) = (&parameters.save_state, parameters.save_every) {
+            if simulation.get_cycle() % interval.max(1) == 0 {
+                simulation.save_snapshot().save(file)?;
+            }
+        }
+        if parameters.watch {
+            //a failed compilation was reported already, the current version keeps running
+            match simulation.reload_if_changed() {
+                Ok(Some(report)) => println!("{}", report),
+                Ok(None) => {}
+                Err(err) => println!("Reload failed: {:?}", err),
+            }
+        }
+        std::thread::sleep(tick.saturating_sub(started.elapsed()));
+    }
+    if let Some(file) = &parameters.save_state {
+        simulation.save_snapshot().save(file)?;
+    }
     Ok(())
 }
 
+/// runs the test functions and every scenario in a fresh simulation and reports the results,
+/// fails if a test fails
+pub fn test_with_params(parameters: TestParameters) -> Result<(), Diagnostic> {
+    let mut settings = simulation::properties::PropertySettings {
+        cases: parameters.cases,
+        ..Default::default()
+    };
+    if let Some(seed) = parameters.seed {
+        settings.seed = seed;
+    }
+    //the tests are reproduced by the reported seed, including the application's random numbers
+    simulation::seed_random(settings.seed);
+    let properties = simulation::properties::run_tests(
+        create_simulation_sources(
+            &parameters.input,
+            &parameters.includes,
+            parameters.encoding,
+            parameters.dialect,
+            false,
+        )?,
+        &settings,
+    )?;
+    if properties.is_empty() && parameters.scenarios.is_empty() {
+        return Err(Diagnostic::param_error(
+            "No tests found, declare test functions with {test} or pass a --scenario",
+        ));
+    }
+    let mut failed = 0;
+    for result in &properties {
+        println!("{}", result);
+        if !result.is_passed() {
+            failed += 1;
+        }
+    }
+    for file in &parameters.scenarios {
+        let scenario = simulation::scenario::Scenario::load(file)?;
+        let sources = create_simulation_sources(
+            &parameters.input,
+            &parameters.includes,
+            parameters.encoding,
+            parameters.dialect,
+            false,
+        )?;
+        let tasks = TaskConfiguration::load(&parameters.task_config)?;
+        let mut simulation = simulation::Simulation::new(sources, tasks, parameters.tick)?;
+        simulation::seed_random(settings.seed);
+        let result = scenario.run(&mut simulation)?;
+        println!("{}", result);
+        if !result.is_passed() {
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(Diagnostic::param_error(&format!(
+            "{} of {} tests failed",
+            failed,
+            properties.len() + parameters.scenarios.len()
+        )));
+    }
+    Ok(())
+}
+
+fn create_simulation_sources(
+    input: &[String],
+    includes: &[String],
+    encoding: Option<&'static Encoding>,
+    dialect: Dialect,
+    debug_hooks: bool,
+) -> Result<simulation::SimulationSources, Diagnostic> {
+    Ok(simulation::SimulationSources {
+        sources: create_file_paths(input)?,
+        includes: if includes.is_empty() {
+            vec![]
+        } else {
+            create_file_paths(includes)?
+        },
+        generated: vec![],
+        encoding,
+        dialect,
+        debug_hooks,
+    })
+}
+
 /// The builder function for the compilation
 /// Sorts files that need compilation
 /// Parses, validates and generates code for the given source files
@@ -645,6 +1671,51 @@ pub fn build(
     error_format: &ErrorFormat,
     target: &TargetTriple,
 ) -> Result<CompileResult, Diagnostic> {
+    build_with_tasks(
+        files,
+        includes,
+        compile_options,
+        encoding,
+        create_diagnostician(error_format, Dialect::default().conformance()),
+        target,
+        ModuleOptions::default(),
+    )
+}
+
+/// returns true if the given target has no operating system (e.g. `thumbv7em-none-eabihf`), so
+/// the runtime's function blocks accessing files are not available
+fn is_freestanding(target: &TargetTriple) -> bool {
+    let triple = target.as_str().to_string_lossy();
+    triple.split('-').skip(1).any(|it| it == "none") || triple.ends_with("-unknown-unknown")
+}
+
+/// creates a diagnostician reporting in the given format and assessing with the given conformance
+fn create_diagnostician(error_format: &ErrorFormat, conformance: Conformance) -> Diagnostician {
+    let mut diagnostician = match error_format {
+        ErrorFormat::Rich => Diagnostician::default(),
+        ErrorFormat::Clang => Diagnostician::clang_format_diagnostician(),
+    };
+    if conformance == Conformance::Legacy {
+        diagnostician.assessor = Box::new(LegacyDiagnosticAssessor::default());
+    }
+    diagnostician
+}
+
+/// The builder function for the compilation of an application with the given module options (e.g.
+/// the task configuration and dialect), see `build`. The options generating the code (e.g.
+/// `fast_math` or `jobs`) are taken from the `compile_options` and the target
+pub fn build_with_tasks(
+    files: Vec<FilePath>,
+    includes: Vec<FilePath>,
+    compile_options: &CompileOptions,
+    encoding: Option<&'static Encoding>,
+    diagnostician: Diagnostician,
+    target: &TargetTriple,
+    options: ModuleOptions,
+) -> Result<CompileResult, Diagnostic> {
+    //the options have to be known to LLVM before the target machine is created
+    llvm_options::apply_llvm_args(&compile_options.llvm_args)?;
+
     let mut objects = vec![];
     let mut sources = vec![];
     files.into_iter().for_each(|it| {
@@ -655,21 +1726,86 @@ pub fn build(
         }
     });
 
+    //the options of the compilation can be queried like the defined symbols
+    let defines = options
+        .defines
+        .cloned()
+        .unwrap_or_default()
+        .with_option("target", &target.as_str().to_string_lossy())
+        .with_option("optimization", compile_options.optimization.get_name());
+
     let context = Context::create();
-    let diagnostician = match error_format {
-        ErrorFormat::Rich => Diagnostician::default(),
-        ErrorFormat::Clang => Diagnostician::clang_format_diagnostician(),
-    };
-    let (index, codegen) = compile_module(&context, sources, includes, encoding, diagnostician)?;
+    let (index, mut codegen) = compile_module_with_options(
+        &context,
+        sources,
+        includes,
+        encoding,
+        diagnostician,
+        &ModuleOptions {
+            defines: Some(&defines),
+            fast_math: compile_options.fast_math,
+            check_bounds: compile_options.check_bounds,
+            debug_info: compile_options.debug,
+            runtime_library: !is_freestanding(target),
+            jobs: compile_options.jobs,
+            ..options
+        },
+    )?;
+    if compile_options.reflection {
+        codegen.generate_reflection(&index)?;
+    }
+    if let Some(tasks) = options.tasks {
+        codegen.generate_task_table(&index, tasks)?;
+        codegen.generate_symbol_table(&index)?;
+        if let Some(telemetry) = &tasks.telemetry {
+            codegen.generate_telemetry(&index, telemetry)?;
+        }
+    }
+    let abi = codegen.generate_abi_metadata(&index, options.dialect, target)?;
+    if compile_options.disable_tail_calls {
+        codegen.disable_tail_calls();
+    }
+    //unoptimized code stays debuggable when it is optimized later (e.g. at link time)
+    if compile_options.debug && compile_options.optimization == OptimizationLevel::None {
+        codegen.disable_optimization();
+    }
+    codegen.set_target(
+        target,
+        compile_options.cpu.as_deref(),
+        compile_options.features.as_deref(),
+    )?;
+    if wasm_generator::is_wasm_target(target) {
+        codegen.prepare_for_wasm(&index, target, is_freestanding(target))?;
+    }
+    let sections = codegen.assign_sections(&index, target)?;
+    //the variables cannot be laid out for targets LLVM was built without
+    let ram = size_budget::measure_ram(&codegen.module, &index, target).ok();
+    let functions = size_budget::get_implemented_functions(&codegen.module);
     objects.push(persist(
         codegen,
         &compile_options.output,
         compile_options.format,
         target,
         compile_options.optimization,
+        compile_options.lto,
     )?);
+    //only objects contain machine code, IR and bitcode are compiled later
+    let code = match compile_options.format {
+        FormatOption::IR | FormatOption::Bitcode => None,
+        _ if compile_options.lto => None,
+        _ => Some(size_budget::measure_code(
+            &compile_options.output,
+            &functions,
+        )?),
+    };
 
-    Ok(CompileResult { index, objects })
+    Ok(CompileResult {
+        index,
+        objects,
+        sections,
+        sizes: SizeReport { code, ram },
+        abi,
+    })
 }
 
 pub fn persist(
@@ -678,20 +1814,27 @@ pub fn persist(
     out_format: FormatOption,
     target: &TargetTriple,
     optimization: OptimizationLevel,
+    lto: bool,
 ) -> Result<FilePath, Diagnostic> {
     match out_format {
+        FormatOption::Bitcode => optimize(&input, output, target, optimization)
+            .and_then(|_| persist_to_bitcode(input, output)),
+        FormatOption::IR => optimize(&input, output, target, optimization)
+            .and_then(|_| persist_to_ir(input, output)),
+        _ if lto => persist_for_lto(input, output, target, optimization),
         FormatOption::Static | FormatOption::Relocatable => {
             persist_as_static_obj(input, output, target, optimization)
         }
         FormatOption::Shared => persist_to_shared_object(input, output, target, optimization),
         FormatOption::PIC => persist_to_shared_pic_object(input, output, target, optimization),
-        FormatOption::Bitcode => persist_to_bitcode(input, output),
-        FormatOption::IR => persist_to_ir(input, output),
     }?;
 
     Ok(output.into())
 }
 
+/// Links the given objects and libraries into the output, writes a map file of the linked output
+/// if `map_file` is set. Objects holding bitcode are optimized together at the `lto` level
+#[allow(clippy::too_many_arguments)]
 pub fn link(
     output: &str,
     out_format: FormatOption,
@@ -700,6 +1843,8 @@ pub fn link(
     libraries: Vec<String>,
     target: &TargetTriple,
     sysroot: Option<String>,
+    map_file: Option<&str>,
+    lto: Option<OptimizationLevel>,
 ) -> Result<(), Diagnostic> {
     let linkable_formats = vec![
         FormatOption::Static,
@@ -730,6 +1875,14 @@ pub fn link(
             linker.add_sysroot(sysroot);
         }
 
+        if let Some(map_file) = map_file {
+            linker.add_map_file(map_file);
+        }
+
+        if let Some(lto) = lto {
+            linker.set_lto_level(lto.get_lto_level());
+        }
+
         match out_format {
             FormatOption::Static => linker.build_exectuable(Path::new(&output))?,
             FormatOption::Relocatable => linker.build_relocatable(Path::new(&output))?,
@@ -747,7 +1900,7 @@ mod tests {
 
     use inkwell::targets::TargetMachine;
 
-    use crate::{create_source_code, get_target_triple};
+    use crate::{create_source_code, get_target_triple, is_freestanding};
 
     #[test]
     fn test_get_target_triple() {
@@ -764,6 +1917,28 @@ mod tests {
         assert_eq!(triple.as_str().to_str().unwrap(), "x86_64-pc-linux-gnu");
     }
 
+    #[test]
+    fn targets_without_operating_system_are_freestanding() {
+        assert!(!is_freestanding(&get_target_triple(Some(
+            "x86_64-pc-linux-gnu"
+        ))));
+        assert!(!is_freestanding(&get_target_triple(Some(
+            "aarch64-apple-darwin"
+        ))));
+        assert!(!is_freestanding(&get_target_triple(Some(
+            "x86_64-pc-windows-msvc"
+        ))));
+        assert!(is_freestanding(&get_target_triple(Some(
+            "thumbv7em-none-eabihf"
+        ))));
+        assert!(is_freestanding(&get_target_triple(Some(
+            "riscv32imac-unknown-none-elf"
+        ))));
+        assert!(is_freestanding(&get_target_triple(Some(
+            "wasm32-unknown-unknown"
+        ))));
+    }
+
     #[test]
     fn windows_encoded_file_content_read() {
         let expected = r"PROGRAM ä
@@ -0,0 +1,572 @@
+//! the `.plclib` package of a compiled ST library (`rustyc package`)
+//!
+//! a package bundles the object file of the library, the declarations of its POUs, data types and
+//! global variables without their implementations, its API documentation and a manifest naming the
+//! library, the target it was compiled for and the compiler it was built with. An application
+//! using the package (`--lib <file>.plclib`) is compiled against the declarations like an include
+//! (`-i`) and linked with the object, so the library can be distributed without its sources.
+//!
+//! the package starts with the magic `PLCLIB` and the version of the format, followed by its named
+//! entries. Every entry is stored as the length of its name (u32), the name, the length of its
+//! content (u64) and the content, the lengths in little endian.
+//!
+//! the manifest records the semantic version of the library and the signatures of its POUs. A
+//! project can record the versions and the hashes of the signatures of the libraries it is built
+//! with in a lock file (`--lib-lock`), later builds verify that the consumed libraries are
+//! compatible with the recorded versions and that the recorded signatures did not change.
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    fs,
+    path::Path,
+    str::FromStr,
+};
+
+use logos::Logos;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    abi_metadata,
+    ast::LinkageType,
+    dialect::Dialect,
+    documentation::ItemDocumentation,
+    lexer::{self, IdProvider, Token},
+    parser, Diagnostic,
+};
+
+/// the extension of library packages
+pub const EXTENSION: &str = "plclib";
+
+const MAGIC: &[u8] = b"PLCLIB";
+const FORMAT_VERSION: u8 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const DECLARATIONS: &str = "declarations.st";
+const DOCUMENTATION: &str = "documentation.md";
+const OBJECT: &str = "library.o";
+
+/// describes the library of a package
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct LibraryManifest {
+    pub name: String,
+    /// the version of packages written before versions were recorded is `0.0.0`
+    #[serde(default)]
+    pub version: Version,
+    /// the target-triple the library's object was compiled for
+    pub target: String,
+    /// the version of the compiler the library was built with
+    pub compiler: String,
+    /// the signatures of the library's POUs by their name (see [`describe_signature`])
+    #[serde(default)]
+    pub interface: BTreeMap<String, String>,
+}
+
+/// a semantic version (`major.minor.patch`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// returns true if a library of this version can replace a library of the given version,
+    /// which is a later version with the same major version (and the same minor version before
+    /// `1.0.0`)
+    pub fn is_compatible_with(&self, recorded: &Version) -> bool {
+        self >= recorded
+            && self.major == recorded.major
+            && (self.major > 0 || self.minor == recorded.minor)
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "'{}' is not a semantic version (major.minor.patch)",
+                version
+            )
+        };
+        let parts = version
+            .split('.')
+            .map(|it| it.parse::<u64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        match parts.as_slice() {
+            [major, minor, patch] => Ok(Version {
+                major: *major,
+                minor: *minor,
+                patch: *patch,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<String> for Version {
+    type Error = String;
+
+    fn try_from(version: String) -> Result<Self, Self::Error> {
+        version.parse()
+    }
+}
+
+impl From<Version> for String {
+    fn from(version: Version) -> Self {
+        version.to_string()
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// a compiled library with its declarations and documentation
+#[derive(Debug, PartialEq)]
+pub struct LibraryPackage {
+    pub manifest: LibraryManifest,
+    /// the sources of the library without the bodies of their POUs and actions
+    pub declarations: String,
+    /// the API documentation in markdown
+    pub documentation: String,
+    /// the position independent object file of the library
+    pub object: Vec<u8>,
+}
+
+impl LibraryPackage {
+    /// reads the package from the given file
+    pub fn load(file: &str) -> Result<LibraryPackage, Diagnostic> {
+        let content =
+            fs::read(file).map_err(|err| Diagnostic::io_read_error(file, &err.to_string()))?;
+        LibraryPackage::from_bytes(&content)
+            .map_err(|reason| Diagnostic::invalid_library_package(file, &reason))
+    }
+
+    /// writes the package to the given file
+    pub fn write(&self, file: &str) -> Result<(), Diagnostic> {
+        fs::write(file, self.to_bytes())
+            .map_err(|err| Diagnostic::io_write_error(file, &err.to_string()))
+    }
+
+    /// returns the content of the package's file
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let manifest = serde_json::to_string_pretty(&self.manifest).unwrap_or_default();
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(FORMAT_VERSION);
+        for (name, content) in [
+            (MANIFEST, manifest.as_bytes()),
+            (DECLARATIONS, self.declarations.as_bytes()),
+            (DOCUMENTATION, self.documentation.as_bytes()),
+            (OBJECT, self.object.as_slice()),
+        ] {
+            bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.extend_from_slice(&(content.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(content);
+        }
+        bytes
+    }
+
+    /// reads the package from the content of its file, returns the reason if it is malformed
+    pub fn from_bytes(bytes: &[u8]) -> Result<LibraryPackage, String> {
+        let mut reader = bytes
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| "it is not a library package".to_string())?;
+        match take(&mut reader, 1)? {
+            [FORMAT_VERSION] => {}
+            [version] => return Err(format!("its format version {} is not supported", version)),
+            _ => unreachable!("one byte was taken"),
+        }
+
+        let (mut manifest, mut declarations, mut documentation, mut object) =
+            (None, None, None, None);
+        while !reader.is_empty() {
+            let name_length = u32::from_le_bytes(take_array(&mut reader)?) as usize;
+            let name = String::from_utf8_lossy(take(&mut reader, name_length)?).to_string();
+            let length = u64::from_le_bytes(take_array(&mut reader)?) as usize;
+            let content = take(&mut reader, length)?;
+            let text = || String::from_utf8(content.to_vec()).map_err(|err| err.to_string());
+            match name.as_str() {
+                MANIFEST => {
+                    manifest = Some(serde_json::from_slice(content).map_err(|e| e.to_string())?)
+                }
+                DECLARATIONS => declarations = Some(text()?),
+                DOCUMENTATION => documentation = Some(text()?),
+                OBJECT => object = Some(content.to_vec()),
+                //entries of later versions are skipped
+                _ => {}
+            }
+        }
+        let missing = |entry: &str| format!("the entry '{}' is missing", entry);
+        Ok(LibraryPackage {
+            manifest: manifest.ok_or_else(|| missing(MANIFEST))?,
+            declarations: declarations.ok_or_else(|| missing(DECLARATIONS))?,
+            documentation: documentation.unwrap_or_default(),
+            object: object.ok_or_else(|| missing(OBJECT))?,
+        })
+    }
+
+    /// writes the declarations and the object of the package to the given directory, returns the
+    /// paths of the declarations and the object
+    pub fn extract(&self, directory: &Path) -> Result<(String, String), Diagnostic> {
+        let directory = directory.join(&self.manifest.name);
+        let declarations = directory.join(DECLARATIONS).to_string_lossy().to_string();
+        let object = directory.join(OBJECT).to_string_lossy().to_string();
+        fs::create_dir_all(&directory)
+            .and_then(|_| fs::write(&declarations, &self.declarations))
+            .map_err(|err| Diagnostic::io_write_error(&declarations, &err.to_string()))?;
+        fs::write(&object, &self.object)
+            .map_err(|err| Diagnostic::io_write_error(&object, &err.to_string()))?;
+        Ok((declarations, object))
+    }
+}
+
+/// the versions and the hashes of the POU signatures of the libraries a project is built with,
+/// stored as JSON by the name of the library
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LibraryLock {
+    pub libraries: BTreeMap<String, LockedLibrary>,
+}
+
+/// the recorded version and interface of a library
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct LockedLibrary {
+    pub version: Version,
+    /// the hashes of the signatures of the library's POUs by their name
+    pub interface: BTreeMap<String, String>,
+}
+
+/// the reason a library does not match the library recorded in a lock file
+#[derive(Debug, PartialEq)]
+pub enum LockViolation {
+    /// the library's version is not compatible with the recorded version
+    Version { recorded: Version },
+    /// the signatures of the recorded POUs that changed by their name, a POU that was removed has
+    /// no signature
+    Interface(Vec<(String, Option<String>)>),
+}
+
+impl LibraryLock {
+    /// reads the lock from the given file, a missing file records no library
+    pub fn load(file: &str) -> Result<LibraryLock, Diagnostic> {
+        if !Path::new(file).exists() {
+            return Ok(LibraryLock::default());
+        }
+        let content = fs::read_to_string(file)
+            .map_err(|err| Diagnostic::io_read_error(file, &err.to_string()))?;
+        serde_json::from_str(&content)
+            .map_err(|err| Diagnostic::invalid_library_lock(file, &err.to_string()))
+    }
+
+    /// writes the lock to the given file
+    pub fn write(&self, file: &str) -> Result<(), Diagnostic> {
+        let content = serde_json::to_string_pretty(self).unwrap_or_default();
+        fs::write(file, content + "\n")
+            .map_err(|err| Diagnostic::io_write_error(file, &err.to_string()))
+    }
+
+    /// verifies that the library described by the given manifest is compatible with the version
+    /// recorded for it and that the signatures of the recorded POUs did not change. A library
+    /// that is not recorded yet is recorded
+    pub fn verify(&mut self, manifest: &LibraryManifest) -> Result<(), LockViolation> {
+        let locked = match self.libraries.get(&manifest.name) {
+            Some(locked) => locked,
+            None => {
+                let interface = manifest
+                    .interface
+                    .iter()
+                    .map(|(name, signature)| (name.clone(), hash_signature(signature)))
+                    .collect();
+                self.libraries.insert(
+                    manifest.name.clone(),
+                    LockedLibrary {
+                        version: manifest.version,
+                        interface,
+                    },
+                );
+                return Ok(());
+            }
+        };
+
+        if !manifest.version.is_compatible_with(&locked.version) {
+            return Err(LockViolation::Version {
+                recorded: locked.version,
+            });
+        }
+        let changes = locked
+            .interface
+            .iter()
+            .filter_map(|(name, hash)| match manifest.interface.get(name) {
+                Some(signature) if hash_signature(signature) == *hash => None,
+                signature => Some((name.clone(), signature.cloned())),
+            })
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            Ok(())
+        } else {
+            Err(LockViolation::Interface(changes))
+        }
+    }
+}
+
+/// describes the interface of the given documented POU, e.g.
+/// `FUNCTION ramp : INT (VAR_INPUT target : INT; VAR_INPUT rate : REAL)`
+pub fn describe_signature(pou: &ItemDocumentation) -> String {
+    let mut signature = format!("{} {}", pou.kind, pou.name);
+    if let Some(return_type) = &pou.data_type {
+        signature = format!("{} : {}", signature, return_type);
+    }
+    let parameters = pou
+        .members
+        .iter()
+        .map(|it| {
+            format!(
+                "{} {} : {}",
+                it.section.unwrap_or_default(),
+                it.name,
+                it.data_type.as_deref().unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("{} ({})", signature, parameters.join("; "))
+}
+
+/// returns the hash of the given signature recorded in a lock file
+fn hash_signature(signature: &str) -> String {
+    format!(
+        "{:016x}",
+        abi_metadata::hash_layouts(std::iter::once(signature))
+    )
+}
+
+/// takes the given number of bytes from the reader
+fn take<'a>(reader: &mut &'a [u8], length: usize) -> Result<&'a [u8], String> {
+    if reader.len() < length {
+        return Err("it is truncated".into());
+    }
+    let (taken, rest) = reader.split_at(length);
+    *reader = rest;
+    Ok(taken)
+}
+
+fn take_array<const N: usize>(reader: &mut &[u8]) -> Result<[u8; N], String> {
+    let mut array = [0; N];
+    array.copy_from_slice(take(reader, N)?);
+    Ok(array)
+}
+
+/// returns the given source without the statements of its POUs and actions, the comments within
+/// the bodies are removed as well
+pub fn strip_implementations(source: &str, dialect: Dialect) -> String {
+    let (unit, _) = parser::parse(
+        lexer::lex_with_dialect(source, IdProvider::default(), dialect),
+        LinkageType::Internal,
+    );
+    let tokens = Token::lexer(source)
+        .spanned()
+        .map(|(_, span)| span)
+        .collect::<Vec<_>>();
+    let mut bodies = unit
+        .implementations
+        .iter()
+        .filter_map(|implementation| {
+            let location = implementation.location.get_start()..implementation.location.get_end();
+            //the location of a body ends with the END_ keyword of its POU
+            let end_keyword = tokens
+                .iter()
+                .rev()
+                .find(|it| location.start <= it.start && it.end <= location.end)?;
+            if !source[end_keyword.clone()]
+                .to_uppercase()
+                .starts_with("END_")
+            {
+                return None;
+            }
+            //the comments preceding the first statement belong to the body
+            let start = tokens
+                .iter()
+                .rev()
+                .find(|it| it.end <= location.start)
+                .map_or(0, |it| it.end);
+            Some(start..end_keyword.start)
+        })
+        .collect::<Vec<_>>();
+    bodies.sort_by_key(|it| it.start);
+
+    let mut declarations = String::new();
+    let mut position = 0;
+    for body in bodies {
+        if body.start < position {
+            continue;
+        }
+        declarations.push_str(&source[position..body.start]);
+        //the END_ keyword keeps its line if it is the only token on it
+        let removed = &source[body.clone()];
+        match removed
+            .rfind('\n')
+            .filter(|line| removed[*line..].trim().is_empty())
+        {
+            Some(line) => position = body.start + line,
+            None => {
+                declarations.push('\n');
+                position = body.end;
+            }
+        }
+    }
+    declarations.push_str(&source[position..]);
+    declarations
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{
+        strip_implementations, LibraryLock, LibraryManifest, LibraryPackage, LockViolation, Version,
+    };
+    use crate::dialect::Dialect;
+
+    fn manifest(version: &str, interface: &[(&str, &str)]) -> LibraryManifest {
+        LibraryManifest {
+            name: "motion".into(),
+            version: version.parse().unwrap(),
+            target: "x86_64-pc-linux-gnu".into(),
+            compiler: "0.2.0".into(),
+            interface: interface
+                .iter()
+                .map(|(name, signature)| (name.to_string(), signature.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn packages_can_be_written_and_read() {
+        let package = LibraryPackage {
+            manifest: manifest("1.2.0", &[("ramp", "FUNCTION ramp : INT ()")]),
+            declarations: "FUNCTION ramp : INT END_FUNCTION".into(),
+            documentation: "# ramp".into(),
+            object: vec![0x7f, b'E', b'L', b'F'],
+        };
+
+        let bytes = package.to_bytes();
+
+        assert!(bytes.starts_with(b"PLCLIB\x01"));
+        assert_eq!(LibraryPackage::from_bytes(&bytes), Ok(package));
+        assert_eq!(
+            LibraryPackage::from_bytes(&bytes[..bytes.len() - 1]),
+            Err("it is truncated".to_string())
+        );
+        assert_eq!(
+            LibraryPackage::from_bytes(b"!<arch>\n"),
+            Err("it is not a library package".to_string())
+        );
+    }
+
+    #[test]
+    fn implementations_are_stripped_from_the_declarations() {
+        let declarations = strip_implementations(
+            "
+            (** ramps up to the target *)
+            FUNCTION ramp : INT
+            VAR_INPUT
+                target : INT;
+            END_VAR
+                // the secret sauce
+                ramp := target / 2;
+            END_FUNCTION
+
+            FUNCTION_BLOCK axis
+            VAR
+                position : LREAL;
+            END_VAR
+                position := position + 1.0;
+            END_FUNCTION_BLOCK
+            ",
+            Dialect::default(),
+        );
+
+        assert!(declarations.contains("(** ramps up to the target *)"));
+        assert!(
+            declarations.contains("target : INT;\n            END_VAR\n            END_FUNCTION")
+        );
+        assert!(declarations
+            .contains("position : LREAL;\n            END_VAR\n            END_FUNCTION_BLOCK"));
+        assert!(!declarations.contains("secret sauce"));
+        assert!(!declarations.contains(":="));
+    }
+
+    #[test]
+    fn later_versions_with_the_same_major_version_are_compatible() {
+        let version = |it: &str| it.parse::<Version>().unwrap();
+
+        assert!(version("1.3.1").is_compatible_with(&version("1.2.0")));
+        assert!(version("1.2.0").is_compatible_with(&version("1.2.0")));
+        assert!(!version("1.1.9").is_compatible_with(&version("1.2.0")));
+        assert!(!version("2.0.0").is_compatible_with(&version("1.2.0")));
+        assert!(version("0.2.5").is_compatible_with(&version("0.2.1")));
+        assert!(!version("0.3.0").is_compatible_with(&version("0.2.1")));
+        assert_eq!(
+            "1.2".parse::<Version>(),
+            Err("'1.2' is not a semantic version (major.minor.patch)".to_string())
+        );
+    }
+
+    #[test]
+    fn libraries_are_verified_against_the_lock() {
+        let mut lock = LibraryLock::default();
+        let recorded = manifest(
+            "1.2.0",
+            &[
+                ("ramp", "FUNCTION ramp : INT (VAR_INPUT target : INT)"),
+                ("stop", "FUNCTION stop : BOOL ()"),
+            ],
+        );
+        //an unknown library is recorded
+        assert_eq!(lock.verify(&recorded), Ok(()));
+        assert_eq!(lock.libraries["motion"].interface.len(), 2);
+        assert_eq!(lock.verify(&recorded), Ok(()));
+
+        //new POUs may be added
+        let extended = manifest(
+            "1.3.0",
+            &[
+                ("ramp", "FUNCTION ramp : INT (VAR_INPUT target : INT)"),
+                ("stop", "FUNCTION stop : BOOL ()"),
+                ("jog", "FUNCTION jog : BOOL ()"),
+            ],
+        );
+        assert_eq!(lock.verify(&extended), Ok(()));
+
+        let changed = manifest(
+            "1.3.0",
+            &[("ramp", "FUNCTION ramp : INT (VAR_INPUT target : REAL)")],
+        );
+        assert_eq!(
+            lock.verify(&changed),
+            Err(LockViolation::Interface(vec![
+                (
+                    "ramp".into(),
+                    Some("FUNCTION ramp : INT (VAR_INPUT target : REAL)".into())
+                ),
+                ("stop".into(), None)
+            ]))
+        );
+        assert_eq!(
+            lock.verify(&manifest("2.0.0", &[])),
+            Err(LockViolation::Version {
+                recorded: "1.2.0".parse().unwrap()
+            })
+        );
+
+        let json = serde_json::to_string(&lock).unwrap();
+        assert!(json.starts_with(r#"{"motion":{"version":"1.2.0","interface":{"ramp":""#));
+        assert_eq!(serde_json::from_str::<LibraryLock>(&json).unwrap(), lock);
+        assert!(serde_json::from_str::<BTreeMap<String, Version>>(r#"{"v":"1.x.0"}"#).is_err());
+    }
+}
@@ -15,6 +15,8 @@ trait LinkerInterface {
     fn add_lib(&mut self, path: &str);
     fn add_lib_path(&mut self, path: &str);
     fn add_sysroot(&mut self, path: &str);
+    fn add_map_file(&mut self, path: &str);
+    fn set_lto_level(&mut self, level: u32);
     fn build_shared_object(&mut self, path: &str);
     fn build_exectuable(&mut self, path: &str);
     fn build_relocatable(&mut self, path: &str);
@@ -24,11 +26,16 @@ trait LinkerInterface {
 impl Linker {
     pub fn new(target: &str) -> Result<Linker, LinkerError> {
         let target_os = target.split('-').collect::<Vec<&str>>()[2];
-        let linker = match target_os {
-            "linux" => Ok(Box::new(LdLinker::new())),
-            //"win32" | "windows" => Ok(Box::new(MsvcLinker::new())),
-            _ => Err(LinkerError::Target(target_os.into())),
-        }?;
+        let linker: Box<dyn LinkerInterface> = match target_os {
+            _ if target.starts_with("wasm32") || target.starts_with("wasm64") => {
+                Box::new(WasmLinker::new())
+            }
+            "linux" => Box::new(LdLinker::new()),
+            //bare-metal targets (e.g. armv7r-none-eabihf) are linked into ELF files as well
+            _ if target.split('-').nth(1) == Some("none") => Box::new(LdLinker::new()),
+            //"win32" | "windows" => Box::new(MsvcLinker::new()),
+            _ => return Err(LinkerError::Target(target_os.into())),
+        };
         Ok(Linker {
             errors: Vec::default(),
             linker,
@@ -59,6 +66,19 @@ impl Linker {
         self
     }
 
+    /// Write a map file listing the sections and symbols of the output with their addresses and sizes
+    pub fn add_map_file<'a>(&'a mut self, path: &str) -> &'a mut Self {
+        self.linker.add_map_file(path);
+        self
+    }
+
+    /// Optimize the objects holding bitcode together at the given level (0-3) before generating
+    /// their code
+    pub fn set_lto_level<'a>(&'a mut self, level: u32) -> &'a mut Self {
+        self.linker.set_lto_level(level);
+        self
+    }
+
     /// Set the output file and run the linker to generate a shared object
     pub fn build_shared_obj(&mut self, path: &Path) -> Result<(), LinkerError> {
         if let Some(file) = self.get_str_from_path(path) {
@@ -129,6 +149,14 @@ impl LinkerInterface for LdLinker {
         self.args.push(format!("--sysroot={}", path));
     }
 
+    fn add_map_file(&mut self, path: &str) {
+        self.args.push(format!("-Map={}", path));
+    }
+
+    fn set_lto_level(&mut self, level: u32) {
+        self.args.push(format!("--lto-O{}", level));
+    }
+
     fn build_shared_object(&mut self, path: &str) {
         self.args.push("--shared".into());
         self.args.push("-o".into());
@@ -153,6 +181,87 @@ impl LinkerInterface for LdLinker {
     }
 }
 
+/// links WebAssembly modules without entry point (reactors), the POUs and global variables are
+/// exported and the undefined functions (e.g. external functions) are imported from the host
+struct WasmLinker {
+    args: Vec<String>,
+    map_file: Option<String>,
+}
+
+impl WasmLinker {
+    fn new() -> WasmLinker {
+        WasmLinker {
+            args: vec![
+                "--no-entry".into(),
+                "--export-dynamic".into(),
+                "--allow-undefined".into(),
+            ],
+            map_file: None,
+        }
+    }
+}
+
+impl LinkerInterface for WasmLinker {
+    fn get_platform(&self) -> String {
+        "WebAssembly".into()
+    }
+
+    fn add_obj(&mut self, path: &str) {
+        self.args.push(path.into());
+    }
+
+    fn add_lib_path(&mut self, path: &str) {
+        self.args.push(format!("-L{}", path));
+    }
+
+    fn add_lib(&mut self, path: &str) {
+        self.args.push(format!("-l{}", path));
+    }
+
+    fn add_sysroot(&mut self, path: &str) {
+        self.args.push(format!("-L{}/lib", path));
+    }
+
+    fn add_map_file(&mut self, path: &str) {
+        self.map_file = Some(path.into());
+    }
+
+    fn set_lto_level(&mut self, level: u32) {
+        self.args.push(format!("--lto-O{}", level));
+    }
+
+    //a WebAssembly module can be instantiated by every host, so shared objects are linked like
+    //executables
+    fn build_shared_object(&mut self, path: &str) {
+        self.build_exectuable(path);
+    }
+
+    fn build_exectuable(&mut self, path: &str) {
+        self.args.push("-o".into());
+        self.args.push(path.into());
+    }
+
+    fn build_relocatable(&mut self, path: &str) {
+        //objects for further linking have neither exports nor imports yet
+        self.args.retain(|it| !it.starts_with("--"));
+        self.args.push("--relocatable".into());
+        self.args.push("-o".into());
+        self.args.push(path.into());
+    }
+
+    fn finalize(&mut self) -> Result<(), LinkerError> {
+        if let Some(map_file) = &self.map_file {
+            return Err(LinkerError::Link(format!(
+                "Cannot write the map file '{}', map files are not supported for WebAssembly",
+                map_file
+            )));
+        }
+        lld_rs::link(lld_rs::LldFlavor::Wasm, &self.args)
+            .ok()
+            .map_err(LinkerError::Link)
+    }
+}
+
 /* TODO: Implement Windows linker
 
 struct MsvcLinker {
@@ -218,6 +327,12 @@ fn creation_test() {
     let linker = Linker::new("x86_64-pc-linux-gnu").unwrap();
     assert_eq!(linker.linker.get_platform(), "Linux");
 
+    let linker = Linker::new("wasm32-unknown-unknown").unwrap();
+    assert_eq!(linker.linker.get_platform(), "WebAssembly");
+
+    let linker = Linker::new("armv7r-none-eabihf").unwrap();
+    assert_eq!(linker.linker.get_platform(), "Linux");
+
     if let Err(tgt) = Linker::new("x86_64-pc-redox-abc") {
         assert_eq!(tgt, LinkerError::Target("redox".into()));
     } else {
@@ -0,0 +1,130 @@
+//! generates a GNU ld script fragment declaring the sections rusty places its data in, so firmware
+//! integrators can include it into their linker script instead of maintaining the section
+//! definitions by hand
+
+/// the section holding the process images and the global variables bound to hardware templates
+/// (`AT %I*`)
+pub const PROCESS_IMAGE_SECTION: &str = ".process_image";
+/// the section holding the global variables declared in `RETAIN` blocks
+pub const RETAIN_SECTION: &str = ".retain";
+/// the section holding the global variables declared in `PERSISTENT` blocks
+pub const PERSISTENT_SECTION: &str = ".persistent";
+
+/// the variables placed into a data section, sized for the compilation's target
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionLayout {
+    pub name: String,
+    pub alignment: u32,
+    pub entries: Vec<SectionEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionEntry {
+    pub name: String,
+    pub type_name: String,
+    pub size: u64,
+}
+
+impl SectionLayout {
+    pub fn new(name: &str) -> Self {
+        SectionLayout {
+            name: name.to_string(),
+            alignment: 1,
+            entries: vec![],
+        }
+    }
+
+    /// the number of bytes occupied by the section's variables, not counting padding
+    pub fn get_size(&self) -> u64 {
+        self.entries.iter().map(|it| it.size).sum()
+    }
+}
+
+/// returns the symbol marking the start (`suffix = "start"`) or end of the given section
+fn get_marker(section: &str, suffix: &str) -> String {
+    format!("__{}_{}", section.trim_start_matches('.'), suffix)
+}
+
+/// generates the `SECTIONS` fragment for the given data sections and the generated code
+pub fn generate_linker_script(sections: &[SectionLayout], target: &str) -> String {
+    let mut script = format!(
+        "/* generated by rusty {} for {} */\nSECTIONS\n{{\n",
+        env!("CARGO_PKG_VERSION"),
+        target
+    );
+    script.push_str("    .text : { *(.text .text.*) }\n");
+    for section in sections {
+        script.push_str(&format!(
+            "\n    /* {} bytes in {} variables */\n",
+            section.get_size(),
+            section.entries.len()
+        ));
+        for entry in &section.entries {
+            script.push_str(&format!(
+                "    /*   {} : {} ({} bytes) */\n",
+                entry.name, entry.type_name, entry.size
+            ));
+        }
+        script.push_str(&format!(
+            "    {name} (NOLOAD) : ALIGN({alignment})\n    {{\n        {start} = .;\n        KEEP(*({name} {name}.*))\n        {end} = .;\n    }}\n",
+            name = section.name,
+            alignment = section.alignment,
+            start = get_marker(&section.name, "start"),
+            end = get_marker(&section.name, "end"),
+        ));
+        script.push_str(&format!(
+            "    ASSERT(SIZEOF({name}) >= {size}, \"{name} is smaller than the generated variables\")\n",
+            name = section.name,
+            size = section.get_size(),
+        ));
+    }
+    script.push_str("}\n");
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_linker_script, SectionEntry, SectionLayout, RETAIN_SECTION};
+
+    #[test]
+    fn sections_are_declared_with_their_sizes() {
+        let mut retain = SectionLayout::new(RETAIN_SECTION);
+        retain.alignment = 8;
+        retain.entries.push(SectionEntry {
+            name: "total".into(),
+            type_name: "LREAL".into(),
+            size: 8,
+        });
+        retain.entries.push(SectionEntry {
+            name: "count".into(),
+            type_name: "DINT".into(),
+            size: 4,
+        });
+
+        let script = generate_linker_script(&[retain], "x86_64-unknown-linux-gnu");
+
+        assert_eq!(
+            script,
+            format!(
+                r#"/* generated by rusty {} for x86_64-unknown-linux-gnu */
+SECTIONS
+{{
+    .text : {{ *(.text .text.*) }}
+
+    /* 12 bytes in 2 variables */
+    /*   total : LREAL (8 bytes) */
+    /*   count : DINT (4 bytes) */
+    .retain (NOLOAD) : ALIGN(8)
+    {{
+        __retain_start = .;
+        KEEP(*(.retain .retain.*))
+        __retain_end = .;
+    }}
+    ASSERT(SIZEOF(.retain) >= 12, ".retain is smaller than the generated variables")
+}}
+"#,
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! passes options to LLVM's internal command line parser (e.g. `--llvm-arg=-unroll-threshold=50`)
+//!
+//! the options are taken from the `RUSTY_LLVM_ARGS` environment variable (separated by whitespace)
+//! followed by the ones given with `--llvm-arg`. LLVM parses its options once per process, options
+//! unknown to LLVM are ignored instead of terminating the compiler.
+use std::{ffi::CString, os::raw::c_char, sync::Once};
+
+use llvm_sys::support::LLVMParseCommandLineOptions;
+
+use crate::diagnostics::Diagnostic;
+
+/// the environment variable holding the options passed to LLVM before the given ones
+pub const LLVM_ARGS_VARIABLE: &str = "RUSTY_LLVM_ARGS";
+
+/// returns the options of the `RUSTY_LLVM_ARGS` environment variable followed by the given ones
+pub fn collect_llvm_args(args: &[String]) -> Vec<String> {
+    std::env::var(LLVM_ARGS_VARIABLE)
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_string)
+        .chain(args.iter().cloned())
+        .collect()
+}
+
+/// checks that every argument is an option, LLVM would take positional arguments as input files
+pub fn validate_llvm_args(args: &[String]) -> Result<(), Diagnostic> {
+    match args
+        .iter()
+        .find(|it| !it.starts_with('-') || it.len() < 2 || it.contains('\0'))
+    {
+        Some(arg) => Err(Diagnostic::param_error(&format!(
+            "Invalid LLVM option '{}', expected an option like '-unroll-threshold=50'",
+            arg
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// passes the given options to LLVM, the options of later calls are ignored
+pub fn apply_llvm_args(args: &[String]) -> Result<(), Diagnostic> {
+    static PARSED: Once = Once::new();
+    validate_llvm_args(args)?;
+    if args.is_empty() {
+        return Ok(());
+    }
+
+    let args = std::iter::once("rustyc")
+        .chain(args.iter().map(String::as_str))
+        .map(|it| CString::new(it).expect("validated options contain no nul bytes"))
+        .collect::<Vec<_>>();
+    let argv = args
+        .iter()
+        .map(|it| it.as_ptr())
+        .collect::<Vec<*const c_char>>();
+    let overview = CString::default();
+    PARSED.call_once(|| unsafe {
+        LLVMParseCommandLineOptions(argv.len() as i32, argv.as_ptr(), overview.as_ptr())
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_llvm_args, validate_llvm_args, LLVM_ARGS_VARIABLE};
+
+    #[test]
+    fn options_of_the_environment_precede_the_given_ones() {
+        std::env::set_var(
+            LLVM_ARGS_VARIABLE,
+            " -unroll-threshold=50  -enable-misched ",
+        );
+        let args = collect_llvm_args(&["-unroll-threshold=100".to_string()]);
+        std::env::remove_var(LLVM_ARGS_VARIABLE);
+
+        assert_eq!(
+            args,
+            vec![
+                "-unroll-threshold=50",
+                "-enable-misched",
+                "-unroll-threshold=100"
+            ]
+        );
+    }
+
+    #[test]
+    fn only_options_are_passed_to_llvm() {
+        assert!(validate_llvm_args(&["-unroll-threshold=50".to_string()]).is_ok());
+        assert!(validate_llvm_args(&["--x86-asm-syntax=intel".to_string()]).is_ok());
+
+        for arg in ["input.ll", "-", "-a\0b"] {
+            assert!(
+                validate_llvm_args(&[arg.to_string()]).is_err(),
+                "{} was accepted",
+                arg
+            );
+        }
+    }
+}
@@ -0,0 +1,1169 @@
+//! a language server providing the diagnostics, quick-fixes, go-to-definition, hover types and
+//! document symbols of ST files to editors using the Language Server Protocol
+//! (https://microsoft.github.io/language-server-protocol/)
+//!
+//! the server keeps the analysis of every document opened by the client. The documents are
+//! analyzed as one project, so references between the open files are resolved, but a change only
+//! re-analyzes the changed document and the documents depending on it (the documents using a name
+//! declared by a re-analyzed document), the other documents keep their analysis. The analysis runs
+//! the compiler's front-end (parser, index, resolver and validator) without generating code in the
+//! dialect of the project file found in the client's workspace (see `DEFAULT_PROJECT_FILES`).
+//! Messages are framed like the debug adapter's (see `simulation::dap`).
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    io::{BufRead, Write},
+    path::Path,
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    ast::{
+        self, AstStatement, CompilationUnit, DataType, DataTypeDeclaration, LinkageType, PouType,
+        SourceRange, Variable,
+    },
+    builtins,
+    diagnostics::{
+        CodeEdit, DefaultDiagnosticAssessor, Diagnostic, DiagnosticAssessor,
+        LegacyDiagnosticAssessor, Severity,
+    },
+    dialect::Dialect,
+    index::{self, Index},
+    lexer::{self, IdProvider, Token},
+    parser,
+    project::{ProjectConfiguration, DEFAULT_PROJECT_FILES},
+    resolver::{
+        const_evaluator::evaluate_constants, AnnotationMap, AnnotationMapImpl, StatementAnnotation,
+        TypeAnnotator,
+    },
+    simulation::dap::read_message,
+    Conformance, Validator,
+};
+
+/// the JSON-RPC error code of requests the server does not implement
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// the symbol kinds of the protocol's `DocumentSymbol`
+mod symbol_kind {
+    pub const MODULE: i64 = 2;
+    pub const CLASS: i64 = 5;
+    pub const METHOD: i64 = 6;
+    pub const ENUM: i64 = 10;
+    pub const INTERFACE: i64 = 11;
+    pub const FUNCTION: i64 = 12;
+    pub const VARIABLE: i64 = 13;
+    pub const STRUCT: i64 = 23;
+    pub const TYPE_PARAMETER: i64 = 26;
+}
+
+/// an open document and its analysis
+struct Document {
+    text: String,
+    unit: CompilationUnit,
+    annotations: AnnotationMapImpl,
+    /// the syntax and validation errors of the unit
+    diagnostics: Vec<Diagnostic>,
+    /// the lower case identifiers used in the document
+    identifiers: HashSet<String>,
+    /// the lower case names of the POUs, types, global variables and enum elements declared in the
+    /// document, see `get_declarations`
+    declarations: HashSet<String>,
+}
+
+impl Document {
+    /// parses the given text, the unit is resolved and validated by `LanguageServer::analyze`
+    fn parse(text: &str, id_provider: IdProvider, dialect: Dialect) -> Document {
+        let (unit, diagnostics) = parser::parse(
+            lexer::lex_with_dialect(text, id_provider.clone(), dialect),
+            LinkageType::Internal,
+        );
+        let mut identifiers = HashSet::new();
+        let mut lexer = lexer::lex_with_dialect(text, id_provider, dialect);
+        while lexer.token != Token::End {
+            if lexer.token == Token::Identifier {
+                identifiers.insert(lexer.slice().to_lowercase());
+            }
+            lexer.advance();
+        }
+        Document {
+            text: text.to_string(),
+            declarations: get_declarations(&unit),
+            unit,
+            annotations: AnnotationMapImpl::default(),
+            diagnostics,
+            identifiers,
+        }
+    }
+}
+
+pub struct LanguageServer {
+    writer: Box<dyn Write>,
+    /// the open documents by their uri
+    documents: BTreeMap<String, Document>,
+    /// the declarations of the builtins, indexed with the documents
+    builtins: CompilationUnit,
+    id_provider: IdProvider,
+    /// the dialect of the project, selected by the project file of the client's workspace
+    dialect: Dialect,
+    /// set once the client requested the shutdown, the server exits with `0` afterwards
+    shut_down: bool,
+}
+
+impl LanguageServer {
+    /// creates a server writing its messages to `writer`
+    pub fn new(writer: Box<dyn Write>) -> LanguageServer {
+        let id_provider = IdProvider::default();
+        LanguageServer {
+            writer,
+            documents: BTreeMap::new(),
+            builtins: builtins::parse_built_ins(id_provider.clone()),
+            id_provider,
+            dialect: Dialect::default(),
+            shut_down: false,
+        }
+    }
+
+    /// handles the client's messages read from `reader` until it sends `exit` or closes the stream
+    /// and returns the server's exit code
+    pub fn run(&mut self, reader: &mut impl BufRead) -> i32 {
+        while let Some(message) = read_message(reader) {
+            if message["method"] == "exit" {
+                break;
+            }
+            self.handle(&message);
+        }
+        if self.shut_down {
+            0
+        } else {
+            1
+        }
+    }
+
+    fn send(&mut self, message: Value) {
+        let content = message.to_string();
+        //a client that is gone will be noticed when reading its next message
+        let _ = write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        )
+        .and_then(|_| self.writer.flush());
+    }
+
+    fn respond(&mut self, id: &Value, result: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+    }
+
+    fn handle(&mut self, message: &Value) {
+        let params = &message["params"];
+        let result = match message["method"].as_str().unwrap_or_default() {
+            "initialize" => {
+                self.dialect = self.load_dialect(params);
+                json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "definitionProvider": true,
+                        "hoverProvider": true,
+                        "documentSymbolProvider": true,
+                        "codeActionProvider": true,
+                    },
+                    "serverInfo": { "name": "rusty-lsp", "version": env!("CARGO_PKG_VERSION") },
+                })
+            }
+            "shutdown" => {
+                self.shut_down = true;
+                Value::Null
+            }
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                self.update(&document["uri"], document["text"].as_str());
+                return;
+            }
+            "textDocument/didChange" => {
+                //the documents are synchronized in full, the last change holds the whole text
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|it| it.last())
+                    .and_then(|it| it["text"].as_str());
+                self.update(&params["textDocument"]["uri"], text);
+                return;
+            }
+            "textDocument/didClose" => {
+                self.close(&params["textDocument"]["uri"]);
+                return;
+            }
+            "textDocument/definition" => self.find_definition(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/documentSymbol" => self.find_symbols(params),
+            "textDocument/codeAction" => self.find_code_actions(params),
+            method => {
+                if !message["id"].is_null() {
+                    self.send(json!({
+                        "jsonrpc": "2.0",
+                        "id": message["id"],
+                        "error": {
+                            "code": METHOD_NOT_FOUND,
+                            "message": format!("Unsupported method: {}", method),
+                        },
+                    }));
+                }
+                return;
+            }
+        };
+        self.respond(&message["id"], result);
+    }
+
+    /// returns the dialect of the project file in the root of the client's workspace (given by the
+    /// `InitializeParams`), the default dialect if there is none
+    fn load_dialect(&mut self, params: &Value) -> Dialect {
+        let project_file = params["rootUri"]
+            .as_str()
+            .and_then(|it| it.strip_prefix("file://"))
+            .and_then(|root| {
+                DEFAULT_PROJECT_FILES
+                    .iter()
+                    .map(|it| Path::new(root).join(it))
+                    .find(|it| it.exists())
+            });
+        match project_file.map(|it| ProjectConfiguration::load(&it.to_string_lossy())) {
+            Some(Ok(project)) => project.dialect.unwrap_or_default(),
+            Some(Err(error)) => {
+                self.send(json!({
+                    "jsonrpc": "2.0",
+                    "method": "window/showMessage",
+                    "params": { "type": 1, "message": error.get_message() },
+                }));
+                Dialect::default()
+            }
+            None => Dialect::default(),
+        }
+    }
+
+    /// replaces the text of the given document and re-analyzes it
+    fn update(&mut self, uri: &Value, text: Option<&str>) {
+        if let (Some(uri), Some(text)) = (uri.as_str(), text) {
+            let document = Document::parse(text, self.id_provider.clone(), self.dialect);
+            let previous = self.documents.insert(uri.to_string(), document);
+            self.analyze(uri, previous.map(|it| it.declarations).unwrap_or_default());
+        }
+    }
+
+    /// forgets the given document, clears its diagnostics and re-analyzes the documents depending
+    /// on it
+    fn close(&mut self, uri: &Value) {
+        if let Some((uri, document)) = uri
+            .as_str()
+            .and_then(|uri| Some((uri, self.documents.remove(uri)?)))
+        {
+            self.send(json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/publishDiagnostics",
+                "params": { "uri": uri, "diagnostics": [] },
+            }));
+            self.analyze(uri, document.declarations);
+        }
+    }
+
+    /// analyzes the given document, which was just parsed or closed, and the documents depending
+    /// on it and publishes their diagnostics. The declarations of all documents are indexed, but
+    /// the other documents are neither parsed, resolved nor validated again
+    fn analyze(&mut self, uri: &str, previous_declarations: HashSet<String>) {
+        //a document using a name the document declared before or declares now depends on it,
+        //the documents depending on a dependent are analyzed as well
+        let mut declarations = previous_declarations;
+        let mut analyzed = BTreeSet::new();
+        let mut next = vec![uri.to_string()];
+        while !next.is_empty() {
+            for it in next {
+                if let Some(document) = self.documents.get(&it) {
+                    declarations.extend(document.declarations.iter().cloned());
+                    analyzed.insert(it);
+                }
+            }
+            next = self
+                .documents
+                .iter()
+                .filter(|(it, document)| {
+                    !analyzed.contains(*it) && !document.identifiers.is_disjoint(&declarations)
+                })
+                .map(|(it, _)| it.clone())
+                .collect();
+        }
+        //the dependents' units were resolved against the old declarations, they are parsed again
+        for it in analyzed.iter().filter(|it| it.as_str() != uri) {
+            let document = Document::parse(
+                &self.documents[it].text,
+                self.id_provider.clone(),
+                self.dialect,
+            );
+            self.documents.insert(it.clone(), document);
+        }
+
+        let (mut parsed, resolved): (Vec<_>, Vec<_>) = self
+            .documents
+            .iter_mut()
+            .partition(|(it, _)| analyzed.contains(*it));
+        ast::resolve_namespaces_with(
+            parsed.iter_mut().map(|(_, it)| &mut it.unit).collect(),
+            resolved.iter().map(|(_, it)| &it.unit).collect(),
+        );
+        for (_, document) in parsed.iter_mut() {
+            ast::pre_process(&mut document.unit, self.id_provider.clone());
+        }
+
+        let mut index = Index::default();
+        index.import(index::visitor::visit(
+            &self.builtins,
+            self.id_provider.clone(),
+        ));
+        for document in self.documents.values() {
+            index.import(index::visitor::visit(
+                &document.unit,
+                self.id_provider.clone(),
+            ));
+        }
+        index.merge_inherited_members();
+        let (index, _) = evaluate_constants(index);
+
+        for it in analyzed.iter() {
+            let document = self.documents.get_mut(it).expect("analyzed document");
+            let (annotations, _) = TypeAnnotator::visit_unit(&index, &document.unit);
+            let mut validator = Validator::with_dialect(self.dialect);
+            validator.visit_unit(&annotations, &index, &document.unit);
+            document.diagnostics.append(&mut validator.diagnostics());
+            document.annotations = annotations;
+        }
+
+        let assessor = get_assessor(self.dialect);
+        let notifications = analyzed
+            .iter()
+            .map(|uri| {
+                let document = &self.documents[uri];
+                let diagnostics = document
+                    .diagnostics
+                    .iter()
+                    .map(|it| to_diagnostic(&document.text, assessor.as_ref(), it))
+                    .collect::<Vec<_>>();
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/publishDiagnostics",
+                    "params": { "uri": uri, "diagnostics": diagnostics },
+                })
+            })
+            .collect::<Vec<_>>();
+        notifications.into_iter().for_each(|it| self.send(it));
+    }
+
+    /// returns the document and the offset of the position of the given
+    /// `TextDocumentPositionParams`
+    fn find_position(&self, params: &Value) -> Option<(&Document, usize)> {
+        let document = self
+            .documents
+            .get(params["textDocument"]["uri"].as_str()?)?;
+        let position = &params["position"];
+        let offset = to_offset(
+            &document.text,
+            position["line"].as_u64()? as usize,
+            position["character"].as_u64()? as usize,
+        );
+        Some((document, offset))
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        self.find_position(params)
+            .and_then(|(document, offset)| {
+                let statement = find_statement(&document.unit, offset)?;
+                let annotation = document.annotations.get(statement)?;
+                let signature = match annotation {
+                    StatementAnnotation::Value { resulting_type }
+                    | StatementAnnotation::Instance { resulting_type } => resulting_type.clone(),
+                    StatementAnnotation::Variable {
+                        resulting_type,
+                        qualified_name,
+                        ..
+                    } => format!("{} : {}", qualified_name, resulting_type),
+                    StatementAnnotation::Function {
+                        return_type,
+                        qualified_name,
+                    } => format!("FUNCTION {} : {}", qualified_name, return_type),
+                    StatementAnnotation::Program { qualified_name } => {
+                        format!("PROGRAM {}", qualified_name)
+                    }
+                    StatementAnnotation::Type { type_name } => format!("TYPE {}", type_name),
+                };
+                let contents = format!("```st\n{}\n```", signature);
+                Some(json!({
+                    "contents": { "kind": "markdown", "value": contents },
+                    "range": to_range(&document.text, &statement.get_location()),
+                }))
+            })
+            .unwrap_or(Value::Null)
+    }
+
+    fn find_definition(&self, params: &Value) -> Value {
+        self.find_position(params)
+            .and_then(|(document, offset)| {
+                let statement = find_statement(&document.unit, offset)?;
+                let (uri, location) =
+                    find_declaration(&self.documents, document.annotations.get(statement)?)?;
+                Some(json!({
+                    "uri": uri,
+                    "range": to_range(&self.documents[uri].text, &location),
+                }))
+            })
+            .unwrap_or(Value::Null)
+    }
+
+    fn find_symbols(&self, params: &Value) -> Value {
+        let symbols = params["textDocument"]["uri"]
+            .as_str()
+            .and_then(|uri| self.documents.get(uri))
+            .map(|it| get_symbols(&it.text, &it.unit))
+            .unwrap_or_default();
+        Value::Array(symbols)
+    }
+
+    /// offers the edits of the diagnostics overlapping the range of the given `CodeActionParams`
+    /// as quick-fixes
+    fn find_code_actions(&self, params: &Value) -> Value {
+        let actions = params["textDocument"]["uri"]
+            .as_str()
+            .and_then(|uri| {
+                let document = self.documents.get(uri)?;
+                let offset_of = |position: &Value| {
+                    Some(to_offset(
+                        &document.text,
+                        position["line"].as_u64()? as usize,
+                        position["character"].as_u64()? as usize,
+                    ))
+                };
+                let start = offset_of(&params["range"]["start"])?;
+                let end = offset_of(&params["range"]["end"])?;
+                let assessor = get_assessor(self.dialect);
+                let actions = document
+                    .diagnostics
+                    .iter()
+                    .filter(|it| {
+                        let location = it.get_location();
+                        location.get_start() <= end && start <= location.get_end()
+                    })
+                    .filter(|it| !it.get_edits().is_empty())
+                    .map(|it| {
+                        let title = it
+                            .get_edits()
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        json!({
+                            "title": title,
+                            "kind": "quickfix",
+                            "diagnostics": [to_diagnostic(&document.text, assessor.as_ref(), it)],
+                            "edit": {
+                                "changes": { uri: to_text_edits(&document.text, it.get_edits()) },
+                            },
+                        })
+                    })
+                    .collect();
+                Some(actions)
+            })
+            .unwrap_or_default();
+        Value::Array(actions)
+    }
+}
+
+/// returns the assessor of the diagnostics in the given dialect
+fn get_assessor(dialect: Dialect) -> Box<dyn DiagnosticAssessor> {
+    match dialect.conformance() {
+        Conformance::Strict => Box::new(DefaultDiagnosticAssessor::default()),
+        Conformance::Legacy => Box::new(LegacyDiagnosticAssessor::default()),
+    }
+}
+
+/// returns the protocol's `Diagnostic` of the given diagnostic, the edits fixing the problem are
+/// passed as the `edits` of its `data`
+fn to_diagnostic(text: &str, assessor: &dyn DiagnosticAssessor, diagnostic: &Diagnostic) -> Value {
+    let severity = match assessor.assess(diagnostic.clone()).severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+        Severity::_Info => 3,
+    };
+    let mut result = json!({
+        "range": to_range(text, &diagnostic.get_location()),
+        "severity": severity,
+        "source": "rusty",
+        "message": diagnostic.get_message(),
+    });
+    if !diagnostic.get_edits().is_empty() {
+        result["data"] = json!({ "edits": to_text_edits(text, diagnostic.get_edits()) });
+    }
+    result
+}
+
+/// returns the protocol's `TextEdit`s of the given edits
+fn to_text_edits(text: &str, edits: &[CodeEdit]) -> Vec<Value> {
+    edits
+        .iter()
+        .map(|it| json!({ "range": to_range(text, &it.range), "newText": it.replacement }))
+        .collect()
+}
+
+/// returns the lower case names of the POUs, types, global variables and enum elements declared
+/// in the given unit, the names declared in namespaces without their namespace
+fn get_declarations(unit: &CompilationUnit) -> HashSet<String> {
+    //enums declared inline (e.g. `state : (idle, running)`) declare their elements as well
+    let inline_types = unit
+        .units
+        .iter()
+        .flat_map(|it| &it.variable_blocks)
+        .chain(&unit.global_vars)
+        .flat_map(|it| &it.variables)
+        .filter_map(|it| match &it.data_type {
+            DataTypeDeclaration::DataTypeDefinition { data_type, .. } => Some(data_type),
+            _ => None,
+        });
+    let enum_elements = unit
+        .types
+        .iter()
+        .map(|it| &it.data_type)
+        .chain(inline_types)
+        .filter_map(|it| match it {
+            DataType::EnumType { elements, .. } => Some(ast::get_enum_element_names(elements)),
+            _ => None,
+        })
+        .flatten();
+    unit.units
+        .iter()
+        .map(|it| it.name.clone())
+        .chain(
+            unit.types
+                .iter()
+                .filter_map(|it| it.data_type.get_name())
+                .map(str::to_string),
+        )
+        .chain(
+            unit.global_vars
+                .iter()
+                .flat_map(|it| &it.variables)
+                .map(|it| it.name.clone()),
+        )
+        .chain(enum_elements)
+        .map(|it| it.rsplit('.').next().unwrap_or_default().to_lowercase())
+        .collect()
+}
+
+/// returns the (0-based) line and UTF-16 character of the given offset
+fn to_position(text: &str, offset: usize) -> Value {
+    let offset = offset.min(text.len());
+    let line_start = text[..offset].rfind('\n').map_or(0, |it| it + 1);
+    json!({
+        "line": text[..line_start].matches('\n').count(),
+        "character": text[line_start..offset].encode_utf16().count(),
+    })
+}
+
+fn to_range(text: &str, location: &SourceRange) -> Value {
+    json!({
+        "start": to_position(text, location.get_start()),
+        "end": to_position(text, location.get_end()),
+    })
+}
+
+/// returns the offset of the given (0-based) line and UTF-16 character
+fn to_offset(text: &str, line: usize, character: usize) -> usize {
+    let line_start = match line {
+        0 => 0,
+        _ => match text.match_indices('\n').nth(line - 1) {
+            Some((it, _)) => it + 1,
+            None => return text.len(),
+        },
+    };
+    let mut units = 0;
+    text[line_start..]
+        .char_indices()
+        .find(|(_, it)| {
+            units += it.len_utf16();
+            *it == '\n' || units > character
+        })
+        .map_or(text.len(), |(it, _)| line_start + it)
+}
+
+/// returns the expressions and statements nested in the given statement
+fn get_children(statement: &AstStatement) -> Vec<&AstStatement> {
+    match statement {
+        AstStatement::LiteralArray {
+            elements: Some(it), ..
+        }
+        | AstStatement::CastStatement { target: it, .. }
+        | AstStatement::MultipliedStatement { element: it, .. }
+        | AstStatement::PointerAccess { reference: it, .. }
+        | AstStatement::DirectAccess { index: it, .. }
+        | AstStatement::UnaryExpression { value: it, .. }
+        | AstStatement::CaseCondition { condition: it, .. } => vec![it.as_ref()],
+        AstStatement::QualifiedReference { elements, .. }
+        | AstStatement::HardwareAccess {
+            address: elements, ..
+        }
+        | AstStatement::ExpressionList {
+            expressions: elements,
+            ..
+        } => elements.iter().collect(),
+        AstStatement::ArrayAccess {
+            reference: left,
+            access: right,
+            ..
+        }
+        | AstStatement::BinaryExpression { left, right, .. }
+        | AstStatement::RangeStatement {
+            start: left,
+            end: right,
+            ..
+        }
+        | AstStatement::Assignment { left, right, .. }
+        | AstStatement::OutputAssignment { left, right, .. }
+        | AstStatement::ReferenceAssignment { left, right, .. } => {
+            vec![left.as_ref(), right.as_ref()]
+        }
+        AstStatement::CallStatement {
+            operator,
+            parameters,
+            ..
+        } => std::iter::once(operator.as_ref())
+            .chain((**parameters).as_ref())
+            .collect(),
+        AstStatement::IfStatement {
+            blocks, else_block, ..
+        } => blocks
+            .iter()
+            .flat_map(|it| std::iter::once(it.condition.as_ref()).chain(&it.body))
+            .chain(else_block)
+            .collect(),
+        AstStatement::CaseStatement {
+            selector,
+            case_blocks,
+            else_block,
+            ..
+        } => std::iter::once(selector.as_ref())
+            .chain(
+                case_blocks
+                    .iter()
+                    .flat_map(|it| std::iter::once(it.condition.as_ref()).chain(&it.body)),
+            )
+            .chain(else_block)
+            .collect(),
+        AstStatement::ForLoopStatement {
+            counter,
+            start,
+            end,
+            by_step,
+            body,
+            ..
+        } => [counter, start, end]
+            .into_iter()
+            .map(|it| it.as_ref())
+            .chain(by_step.as_deref())
+            .chain(body)
+            .collect(),
+        AstStatement::WhileLoopStatement {
+            condition, body, ..
+        }
+        | AstStatement::RepeatLoopStatement {
+            condition, body, ..
+        } => std::iter::once(condition.as_ref()).chain(body).collect(),
+        _ => vec![],
+    }
+}
+
+/// returns the innermost expression of the unit's implementations containing the given offset
+fn find_statement(unit: &CompilationUnit, offset: usize) -> Option<&AstStatement> {
+    let contains = |it: &AstStatement| {
+        let location = it.get_location();
+        location.get_start() <= offset && offset <= location.get_end()
+    };
+    let mut candidates = unit
+        .implementations
+        .iter()
+        .flat_map(|it| it.statements.iter())
+        .collect::<Vec<_>>();
+    let mut found = None;
+    while let Some(statement) = candidates.iter().copied().find(|it| contains(it)) {
+        found = Some(statement);
+        candidates = get_children(statement);
+    }
+    found
+}
+
+/// returns the location of the declaration of the variable with the given name
+fn find_variable<'a>(
+    mut variables: impl Iterator<Item = &'a Variable>,
+    name: &str,
+) -> Option<SourceRange> {
+    variables
+        .find(|it| it.name.eq_ignore_ascii_case(name))
+        .map(|it| it.location.clone())
+}
+
+/// returns the uri of the document and the location of the declaration of the given reference
+fn find_declaration<'d>(
+    documents: &'d BTreeMap<String, Document>,
+    annotation: &StatementAnnotation,
+) -> Option<(&'d str, SourceRange)> {
+    documents.iter().find_map(|(uri, document)| {
+        let unit = &document.unit;
+        let find_pou = |name: &str| {
+            unit.units
+                .iter()
+                .find(|it| it.name.eq_ignore_ascii_case(name))
+        };
+        let find_type = |name: &str| {
+            unit.types.iter().find(|it| {
+                it.data_type
+                    .get_name()
+                    .map_or(false, |it| it.eq_ignore_ascii_case(name))
+            })
+        };
+        let location = match annotation {
+            StatementAnnotation::Variable { qualified_name, .. } => {
+                match qualified_name.rsplit_once('.') {
+                    Some((container, name)) => find_pou(container)
+                        .and_then(|pou| {
+                            let variables = pou.variable_blocks.iter().flat_map(|it| &it.variables);
+                            find_variable(variables, name)
+                        })
+                        .or_else(|| match find_type(container).map(|it| &it.data_type) {
+                            Some(DataType::StructType { variables, .. }) => {
+                                find_variable(variables.iter(), name)
+                            }
+                            _ => None,
+                        }),
+                    None => find_variable(
+                        unit.global_vars.iter().flat_map(|it| &it.variables),
+                        qualified_name,
+                    ),
+                }
+            }
+            StatementAnnotation::Function { qualified_name, .. }
+            | StatementAnnotation::Program { qualified_name } => {
+                find_pou(qualified_name).map(|it| it.name_location.clone())
+            }
+            StatementAnnotation::Type { type_name } => find_pou(type_name)
+                .map(|it| it.name_location.clone())
+                .or_else(|| find_type(type_name).map(|it| it.location.clone())),
+            _ => None,
+        };
+        location.map(|it| (uri.as_str(), it))
+    })
+}
+
+fn to_symbol(
+    text: &str,
+    name: &str,
+    kind: i64,
+    location: &SourceRange,
+    children: Vec<Value>,
+) -> Value {
+    json!({
+        "name": name,
+        "kind": kind,
+        "range": to_range(text, location),
+        "selectionRange": to_range(text, location),
+        "children": children,
+    })
+}
+
+fn to_variable_symbols<'a>(
+    text: &str,
+    variables: impl Iterator<Item = &'a Variable>,
+) -> Vec<Value> {
+    variables
+        .map(|it| to_symbol(text, &it.name, symbol_kind::VARIABLE, &it.location, vec![]))
+        .collect()
+}
+
+/// returns the POUs, types and global variables declared in the given unit
+fn get_symbols(text: &str, unit: &CompilationUnit) -> Vec<Value> {
+    let pous = unit
+        .units
+        .iter()
+        .filter(|it| !it.name.starts_with("__"))
+        .map(|pou| {
+            let kind = match pou.pou_type {
+                PouType::Program => symbol_kind::MODULE,
+                PouType::Function => symbol_kind::FUNCTION,
+                PouType::FunctionBlock | PouType::Class => symbol_kind::CLASS,
+                PouType::Action | PouType::Method { .. } => symbol_kind::METHOD,
+                PouType::Interface => symbol_kind::INTERFACE,
+            };
+            let mut symbol = to_symbol(
+                text,
+                &pou.name,
+                kind,
+                &pou.location,
+                to_variable_symbols(
+                    text,
+                    pou.variable_blocks.iter().flat_map(|it| &it.variables),
+                ),
+            );
+            symbol["selectionRange"] = to_range(text, &pou.name_location);
+            symbol
+        });
+    //the types generated for inline declarations (e.g. `__main_x`) are not declared by the user
+    let types = unit
+        .types
+        .iter()
+        .filter_map(|it| Some((it, it.data_type.get_name()?)))
+        .filter(|(_, name)| !name.starts_with("__"))
+        .map(|(it, name)| match &it.data_type {
+            DataType::StructType { variables, .. } => to_symbol(
+                text,
+                name,
+                symbol_kind::STRUCT,
+                &it.location,
+                to_variable_symbols(text, variables.iter()),
+            ),
+            DataType::EnumType { .. } => {
+                to_symbol(text, name, symbol_kind::ENUM, &it.location, vec![])
+            }
+            _ => to_symbol(
+                text,
+                name,
+                symbol_kind::TYPE_PARAMETER,
+                &it.location,
+                vec![],
+            ),
+        });
+    let globals = to_variable_symbols(text, unit.global_vars.iter().flat_map(|it| &it.variables));
+    pous.chain(types).chain(globals).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        io::{BufReader, Write},
+        rc::Rc,
+    };
+
+    use serde_json::{json, Value};
+
+    use super::{to_offset, to_position, LanguageServer};
+    use crate::simulation::dap::read_message;
+
+    /// collects the messages written by the server
+    #[derive(Clone, Default)]
+    struct Output(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// runs a server receiving the given messages and returns its exit code and messages
+    fn run(messages: &[Value]) -> (i32, Vec<Value>) {
+        let input = messages
+            .iter()
+            .map(|it| {
+                let content = it.to_string();
+                format!("Content-Length: {}\r\n\r\n{}", content.len(), content)
+            })
+            .collect::<String>();
+        let output = Output::default();
+        let mut server = LanguageServer::new(Box::new(output.clone()));
+        let exit_code = server.run(&mut BufReader::new(input.as_bytes()));
+        let written = output.0.borrow().clone();
+        let mut reader = BufReader::new(written.as_slice());
+        (
+            exit_code,
+            std::iter::from_fn(|| read_message(&mut reader)).collect(),
+        )
+    }
+
+    fn open(uri: &str, text: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": { "uri": uri, "languageId": "st", "version": 1, "text": text },
+            },
+        })
+    }
+
+    fn request(id: i64, method: &str, uri: &str, line: usize, character: usize) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": {
+                "textDocument": { "uri": uri },
+                "position": { "line": line, "character": character },
+            },
+        })
+    }
+
+    fn find_response(messages: &[Value], id: i64) -> &Value {
+        &messages.iter().find(|it| it["id"] == id).unwrap()["result"]
+    }
+
+    /// returns the diagnostics published for the given document, the latest ones last
+    fn find_published<'m>(messages: &'m [Value], uri: &str) -> Vec<&'m Value> {
+        messages
+            .iter()
+            .filter(|it| it["method"] == "textDocument/publishDiagnostics")
+            .filter(|it| it["params"]["uri"] == uri)
+            .map(|it| &it["params"]["diagnostics"])
+            .collect()
+    }
+
+    const LIB: &str = "TYPE Point : STRUCT x, y : DINT; END_STRUCT END_TYPE
+FUNCTION double : DINT
+VAR_INPUT value : DINT; END_VAR
+    double := value * 2;
+END_FUNCTION
+";
+
+    const MAIN: &str = "PROGRAM main
+VAR
+    p : Point;
+    result : DINT;
+END_VAR
+    result := double(p.x);
+    result := unknown;
+END_PROGRAM
+";
+
+    #[test]
+    fn positions_count_utf16_characters_per_line() {
+        let text = "ab\n€x\n\ny";
+        assert_eq!(to_position(text, 1), json!({"line": 0, "character": 1}));
+        assert_eq!(to_position(text, 6), json!({"line": 1, "character": 1}));
+        assert_eq!(to_position(text, 9), json!({"line": 3, "character": 0}));
+        assert_eq!(to_offset(text, 1, 1), 6);
+        assert_eq!(to_offset(text, 2, 5), 8);
+        assert_eq!(to_offset(text, 3, 0), 9);
+        assert_eq!(to_offset(text, 7, 0), text.len());
+    }
+
+    #[test]
+    fn diagnostics_are_published_for_the_open_documents() {
+        let (exit_code, messages) = run(&[
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+            open("file:///lib.st", LIB),
+            open("file:///main.st", MAIN),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "shutdown"}),
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+        ]);
+
+        assert_eq!(exit_code, 0);
+        assert_eq!(
+            find_response(&messages, 1)["capabilities"]["hoverProvider"],
+            true
+        );
+        let last_published = |uri: &str| {
+            messages
+                .iter()
+                .filter(|it| it["params"]["uri"] == uri)
+                .last()
+                .unwrap()["params"]["diagnostics"]
+                .clone()
+        };
+        assert_eq!(last_published("file:///lib.st"), json!([]));
+        let diagnostics = last_published("file:///main.st");
+        let unresolved = diagnostics
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|it| it["message"].as_str().unwrap().contains("unknown"))
+            .unwrap();
+        assert_eq!(
+            unresolved["range"],
+            json!({"start": {"line": 6, "character": 14}, "end": {"line": 6, "character": 21}})
+        );
+        assert_eq!(unresolved["severity"], 1);
+    }
+
+    #[test]
+    fn references_are_resolved_across_documents() {
+        let (_, messages) = run(&[
+            open("file:///lib.st", LIB),
+            open("file:///main.st", MAIN),
+            request(1, "textDocument/hover", "file:///main.st", 5, 23),
+            request(2, "textDocument/definition", "file:///main.st", 5, 16),
+            request(3, "textDocument/definition", "file:///main.st", 5, 23),
+            request(4, "textDocument/hover", "file:///main.st", 0, 0),
+        ]);
+
+        assert_eq!(
+            find_response(&messages, 1)["contents"]["value"],
+            "```st\nPoint.x : DINT\n```"
+        );
+        assert_eq!(
+            find_response(&messages, 2),
+            &json!({
+                "uri": "file:///lib.st",
+                "range": {
+                    "start": {"line": 1, "character": 9},
+                    "end": {"line": 1, "character": 15},
+                },
+            })
+        );
+        assert_eq!(find_response(&messages, 3)["uri"], "file:///lib.st");
+        assert_eq!(
+            find_response(&messages, 3)["range"]["start"],
+            json!({"line": 0, "character": 20})
+        );
+        assert_eq!(find_response(&messages, 4), &Value::Null);
+    }
+
+    #[test]
+    fn only_the_changed_document_and_its_dependents_are_analyzed() {
+        let (_, messages) = run(&[
+            open("file:///lib.st", LIB),
+            open("file:///main.st", MAIN),
+            open(
+                "file:///other.st",
+                "PROGRAM other\nVAR x : INT; END_VAR\n    x := 1;\nEND_PROGRAM\n",
+            ),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didChange",
+                "params": {
+                    "textDocument": { "uri": "file:///lib.st", "version": 2 },
+                    "contentChanges": [{ "text": LIB.replace("double", "twice") }],
+                },
+            }),
+        ]);
+
+        assert_eq!(find_published(&messages, "file:///lib.st").len(), 2);
+        assert_eq!(find_published(&messages, "file:///other.st").len(), 1);
+        //main calls the renamed function
+        let published = find_published(&messages, "file:///main.st");
+        assert_eq!(published.len(), 2);
+        let messages = published[1]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|it| it["message"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert!(messages.iter().any(|it| it.contains("double")));
+    }
+
+    #[test]
+    fn quick_fixes_are_offered_as_code_actions() {
+        let (_, messages) = run(&[
+            open(
+                "file:///main.st",
+                "PROGRAM main\nVAR\n    speed : DINT;\nEND_VAR\n    speed := sped;\nEND_PROGRAM\n",
+            ),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "textDocument/codeAction",
+                "params": {
+                    "textDocument": { "uri": "file:///main.st" },
+                    "range": {
+                        "start": {"line": 4, "character": 14},
+                        "end": {"line": 4, "character": 14},
+                    },
+                    "context": { "diagnostics": [] },
+                },
+            }),
+        ]);
+
+        let edits = json!([{
+            "range": {"start": {"line": 4, "character": 13}, "end": {"line": 4, "character": 17}},
+            "newText": "speed",
+        }]);
+        let published = find_published(&messages, "file:///main.st");
+        assert_eq!(published[0][0]["data"]["edits"], edits);
+        let actions = find_response(&messages, 1).as_array().unwrap();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0]["title"], "replace with 'speed'");
+        assert_eq!(actions[0]["kind"], "quickfix");
+        assert_eq!(actions[0]["diagnostics"][0], published[0][0]);
+        assert_eq!(actions[0]["edit"]["changes"]["file:///main.st"], edits);
+    }
+
+    #[test]
+    fn diagnostics_are_reported_in_the_dialect_of_the_project() {
+        const SOURCE: &str = "PROGRAM main
+VAR
+    x : INT;
+    a : LWORD;
+END_VAR
+    a := ADR(x);
+END_PROGRAM
+";
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(project.path().join("plc.json"), r#"{ "dialect": "iec" }"#).unwrap();
+        let root = format!("file://{}", project.path().display());
+
+        let (_, messages) = run(&[
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"rootUri": root}}),
+            open("file:///main.st", SOURCE),
+        ]);
+        assert_eq!(
+            find_published(&messages, "file:///main.st")[0],
+            &json!([{
+                "range": {
+                    "start": {"line": 5, "character": 9},
+                    "end": {"line": 5, "character": 12},
+                },
+                "severity": 1,
+                "source": "rusty",
+                "message": "'ADR' is not supported by the IEC 61131-3 dialect",
+            }])
+        );
+
+        //without a project every extension is accepted
+        let (_, messages) = run(&[
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+            open("file:///main.st", SOURCE),
+        ]);
+        assert_eq!(find_published(&messages, "file:///main.st")[0], &json!([]));
+    }
+
+    #[test]
+    fn document_symbols_list_pous_types_and_variables() {
+        let (_, messages) = run(&[
+            open("file:///lib.st", LIB),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "textDocument/documentSymbol",
+                "params": { "textDocument": { "uri": "file:///lib.st" } },
+            }),
+        ]);
+
+        let symbols = find_response(&messages, 1).as_array().unwrap();
+        let names = symbols
+            .iter()
+            .map(|it| {
+                let children = it["children"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|it| it["name"].as_str().unwrap())
+                    .collect::<Vec<_>>();
+                format!(
+                    "{} {} {:?}",
+                    it["kind"],
+                    it["name"].as_str().unwrap(),
+                    children
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                r#"12 double ["value"]"#.to_string(),
+                r#"23 Point ["x", "y"]"#.to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! The language server of RuSTy
+//!
+//! Editors start `rusty-lsp` and talk to it over stdin and stdout using the Language Server
+//! Protocol, the server reports the diagnostics of the open ST files while they are typed and
+//! answers code action (quick-fix), go-to-definition, hover and document symbol requests.
+use std::io::stdin;
+
+use rusty::lsp::LanguageServer;
+
+fn main() {
+    let mut server = LanguageServer::new(Box::new(std::io::stdout()));
+    let exit_code = server.run(&mut stdin().lock());
+    std::process::exit(exit_code);
+}
@@ -18,12 +18,137 @@
 //! [`IEC61131-3`]: https://en.wikipedia.org/wiki/IEC_61131-3
 //! [`IR`]: https://llvm.org/docs/LangRef.html
 
-use rusty::build_with_params;
-use rusty::cli::{CompileParameters, ParameterError};
+use rusty::cli::{
+    BuildParameters, CompileParameters, DocParameters, FormatParameters, InspectParameters,
+    MigrateParameters, PackageParameters, ParameterError, RunParameters, SymbolicateParameters,
+    TestParameters,
+};
+use rusty::{
+    build_project_with_params, build_with_params, doc_with_params, format_with_params,
+    inspect_with_params, migrate_with_params, package_with_params, run_with_params,
+    symbolicate_with_params, test_with_params,
+};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
+    if args.get(1).map(String::as_str) == Some("build") {
+        match BuildParameters::parse(args[1..].to_vec()) {
+            Ok(bp) => {
+                if let Err(msg) = build_project_with_params(bp) {
+                    println!("Error: {:?}", msg);
+                    std::process::exit(1);
+                }
+            }
+            Err(err) => err.exit(),
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("migrate") {
+        match MigrateParameters::parse(args[1..].to_vec()) {
+            Ok(mp) => {
+                if let Err(msg) = migrate_with_params(mp) {
+                    println!("Error: {:?}", msg);
+                    std::process::exit(1);
+                }
+            }
+            Err(err) => err.exit(),
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("format") {
+        match FormatParameters::parse(args[1..].to_vec()) {
+            Ok(fp) => {
+                if let Err(msg) = format_with_params(fp) {
+                    println!("Error: {:?}", msg);
+                    std::process::exit(1);
+                }
+            }
+            Err(err) => err.exit(),
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("doc") {
+        match DocParameters::parse(args[1..].to_vec()) {
+            Ok(dp) => {
+                if let Err(msg) = doc_with_params(dp) {
+                    println!("Error: {:?}", msg);
+                    std::process::exit(1);
+                }
+            }
+            Err(err) => err.exit(),
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("package") {
+        match PackageParameters::parse(args[1..].to_vec()) {
+            Ok(pp) => {
+                if let Err(msg) = package_with_params(pp) {
+                    println!("Error: {:?}", msg);
+                    std::process::exit(1);
+                }
+            }
+            Err(err) => err.exit(),
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("inspect") {
+        match InspectParameters::parse(args[1..].to_vec()) {
+            Ok(ip) => {
+                if let Err(msg) = inspect_with_params(ip) {
+                    println!("Error: {:?}", msg);
+                    std::process::exit(1);
+                }
+            }
+            Err(err) => err.exit(),
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("symbolicate") {
+        match SymbolicateParameters::parse(args[1..].to_vec()) {
+            Ok(sp) => {
+                if let Err(msg) = symbolicate_with_params(sp) {
+                    println!("Error: {:?}", msg);
+                    std::process::exit(1);
+                }
+            }
+            Err(err) => err.exit(),
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("run") {
+        match RunParameters::parse(args[1..].to_vec()) {
+            Ok(rp) => {
+                if let Err(msg) = run_with_params(rp) {
+                    println!("Error: {:?}", msg);
+                    std::process::exit(1);
+                }
+            }
+            Err(err) => err.exit(),
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("test") {
+        match TestParameters::parse(args[1..].to_vec()) {
+            Ok(tp) => {
+                if let Err(msg) = test_with_params(tp) {
+                    println!("Error: {:?}", msg);
+                    std::process::exit(1);
+                }
+            }
+            Err(err) => err.exit(),
+        }
+        return;
+    }
+
     let compile_parameters: Result<CompileParameters, ParameterError> =
         CompileParameters::parse(args);
     match compile_parameters {
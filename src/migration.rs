@@ -0,0 +1,423 @@
+use std::path::{Component, Path};
+
+use encoding_rs::Encoding;
+use logos::Logos;
+
+use crate::{
+    ast::{self, LinkageType, SourceRange},
+    builtins,
+    diagnostics::{CodeEdit, Diagnostic, ErrNo},
+    dialect::{Dialect, LanguageExtension},
+    index::{self, Index},
+    lexer::{self, IdProvider, Token},
+    parser,
+    resolver::{const_evaluator::evaluate_constants, TypeAnnotator},
+    SourceContainer, Validator,
+};
+
+/// the name of the change report written next to the migrated sources
+pub const REPORT_FILE_NAME: &str = "migration-report.txt";
+
+/// keywords written without their separating '_' and their IEC 61131-3 spelling
+const SEPARATED_KEYWORDS: &[(&str, &str)] = &[
+    ("VARINPUT", "VAR_INPUT"),
+    ("VAROUTPUT", "VAR_OUTPUT"),
+    ("VARGLOBAL", "VAR_GLOBAL"),
+    ("VARINOUT", "VAR_IN_OUT"),
+    ("VARTEMP", "VAR_TEMP"),
+    ("NONRETAIN", "NON_RETAIN"),
+    ("ENDVAR", "END_VAR"),
+    ("REFTO", "REF_TO"),
+    ("ENDPROGRAM", "END_PROGRAM"),
+    ("ENDFUNCTION", "END_FUNCTION"),
+    ("FUNCTIONBLOCK", "FUNCTION_BLOCK"),
+    ("ENDFUNCTIONBLOCK", "END_FUNCTION_BLOCK"),
+    ("ENDCLASS", "END_CLASS"),
+    ("ENDMETHOD", "END_METHOD"),
+    ("ENDTYPE", "END_TYPE"),
+    ("ENDSTRUCT", "END_STRUCT"),
+    ("ENDACTION", "END_ACTION"),
+    ("ENDACTIONS", "END_ACTIONS"),
+    ("ENDIF", "END_IF"),
+    ("ENDFOR", "END_FOR"),
+    ("ENDWHILE", "END_WHILE"),
+    ("ENDREPEAT", "END_REPEAT"),
+    ("ENDCASE", "END_CASE"),
+];
+
+/// a single change applied to a source file during the migration
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub description: String,
+    pub location: SourceRange,
+    pub edits: Vec<CodeEdit>,
+}
+
+/// a source file converted into portable IEC 61131-3 ST
+pub struct MigratedSource {
+    /// the location the original source was loaded from
+    pub path: String,
+    pub original: String,
+    pub source: String,
+    /// the changes applied to the original source
+    pub changes: Vec<Change>,
+    /// vendor constructs without a portable replacement that need to be migrated by hand
+    pub open_issues: Vec<Diagnostic>,
+}
+
+/// converts the known vendor constructs of the given dialect (keyword spellings, pragma
+/// spellings, SCL syntax and implicit conversions) found in the given sources into portable
+/// IEC 61131-3 ST
+pub fn migrate<T: SourceContainer>(
+    sources: Vec<T>,
+    encoding: Option<&'static Encoding>,
+    dialect: Dialect,
+) -> Result<Vec<MigratedSource>, Diagnostic> {
+    let id_provider = IdProvider::default();
+    let mut full_index = Index::default();
+    let builtins = builtins::parse_built_ins(id_provider.clone());
+    full_index.import(index::visitor::visit(&builtins, id_provider.clone()));
+
+    let mut parsed = Vec::new();
+    for container in sources {
+        let location = container.get_location().to_string();
+        let code = container
+            .load_source(encoding)
+            .map_err(|err| Diagnostic::io_read_error(location.as_str(), err.as_str()))?;
+        let (mut unit, _) = parser::parse(
+            lexer::lex_with_dialect(code.source.as_str(), id_provider.clone(), dialect),
+            LinkageType::Internal,
+        );
+        ast::pre_process(&mut unit, id_provider.clone());
+        full_index.import(index::visitor::visit(&unit, id_provider.clone()));
+        parsed.push((location, code.source, unit));
+    }
+    full_index.merge_inherited_members();
+    let (full_index, _) = evaluate_constants(full_index);
+
+    let mut migrated = Vec::new();
+    for (path, source, unit) in parsed {
+        let (mut changes, mut open_issues) = migrate_syntax(source.as_str(), dialect);
+
+        let (annotations, _) = TypeAnnotator::visit_unit(&full_index, &unit);
+        let mut validator = Validator::with_dialect(Dialect::Iec);
+        validator.visit_unit(&annotations, &full_index, &unit);
+        for diagnostic in validator.diagnostics() {
+            match diagnostic.get_type() {
+                ErrNo::type__implicit_bool_conversion | ErrNo::type__comparison_chain
+                    if !diagnostic.get_edits().is_empty() =>
+                {
+                    changes.push(Change {
+                        description: diagnostic.get_message().to_string(),
+                        location: diagnostic.get_location(),
+                        edits: diagnostic.get_edits().to_vec(),
+                    })
+                }
+                ErrNo::type__implicit_bool_conversion
+                | ErrNo::type__comparison_chain
+                | ErrNo::dialect__unsupported_extension => open_issues.push(diagnostic),
+                _ => {}
+            }
+        }
+
+        changes.sort_by_key(|it| it.location.get_start());
+        migrated.push(MigratedSource {
+            source: apply_changes(source.as_str(), &changes)?,
+            original: source,
+            path,
+            changes,
+            open_issues,
+        });
+    }
+    Ok(migrated)
+}
+
+/// collects the changes needed to replace the vendor syntax of the given dialect in the given
+/// source and the vendor syntax that cannot be replaced automatically
+///
+/// `POINTER TO` is only reported: unlike a `REF_TO` a pointer supports arithmetic, so
+/// replacing it may change the meaning of the code using it
+pub fn migrate_syntax(source: &str, dialect: Dialect) -> (Vec<Change>, Vec<Diagnostic>) {
+    let mut changes = Vec::new();
+    let mut open_issues = Vec::new();
+    let mut lexer = Token::lexer(source);
+    let mut pointer_start = None;
+    while let Some(token) = lexer.next() {
+        let range = lexer.span();
+        let slice = lexer.slice();
+        let replacement = match token {
+            Token::KeywordPointer => {
+                pointer_start = Some(range.start);
+                continue;
+            }
+            Token::KeywordTo => match pointer_start.take() {
+                Some(start) => {
+                    open_issues.push(Diagnostic::unsupported_in_dialect(
+                        "POINTER TO",
+                        Dialect::Iec,
+                        SourceRange::new(start..range.end),
+                    ));
+                    continue;
+                }
+                None => None,
+            },
+            Token::PropertyExternal if slice.starts_with('@') => Some("{external}".to_string()),
+            Token::LocalPrefix => Some("".to_string()),
+            Token::Identifier => {
+                if dialect.allows(LanguageExtension::SclMarkers)
+                    && lexer::is_scl_marker(slice, lexer.remainder())
+                {
+                    changes.push(Change {
+                        description: format!("commented out SCL marker '{}'", slice),
+                        location: SourceRange::new(range.clone()),
+                        edits: vec![CodeEdit::insert("// ", range.start)],
+                    });
+                }
+                None
+            }
+            _ => SEPARATED_KEYWORDS
+                .iter()
+                .find(|(keyword, _)| keyword.eq_ignore_ascii_case(slice))
+                .map(|(_, separated)| separated.to_string()),
+        };
+        pointer_start = None;
+        if let Some(replacement) = replacement {
+            changes.push(replacement_change(slice, replacement.as_str(), range));
+        }
+    }
+    (changes, open_issues)
+}
+
+fn replacement_change(original: &str, replacement: &str, range: std::ops::Range<usize>) -> Change {
+    let description = if replacement.is_empty() {
+        format!("removed '{}'", original)
+    } else {
+        format!("replaced '{}' with '{}'", original, replacement)
+    };
+    Change {
+        description,
+        location: SourceRange::new(range.clone()),
+        edits: vec![CodeEdit::replace(SourceRange::new(range), replacement)],
+    }
+}
+
+/// applies the edits of all given changes to the given source, fails if the edits of two
+/// changes overlap
+pub fn apply_changes(source: &str, changes: &[Change]) -> Result<String, Diagnostic> {
+    let mut edits = changes
+        .iter()
+        .flat_map(|change| change.edits.iter().map(move |edit| (edit, change)))
+        .collect::<Vec<_>>();
+    edits.sort_by_key(|(edit, _)| (edit.range.get_start(), edit.range.get_end()));
+    for pair in edits.windows(2) {
+        let ((previous, previous_change), (next, next_change)) = (pair[0], pair[1]);
+        if next.range.get_start() < previous.range.get_end() {
+            return Err(Diagnostic::conflicting_migration_changes(
+                &previous_change.description,
+                &next_change.description,
+            ));
+        }
+    }
+
+    //apply from back to front so the offsets of the remaining edits stay valid
+    let mut result = source.to_string();
+    for (edit, _) in edits.into_iter().rev() {
+        result.replace_range(
+            edit.range.get_start()..edit.range.get_end(),
+            &edit.replacement,
+        );
+    }
+    Ok(result)
+}
+
+/// writes the migrated sources and the change report into the given directory
+pub fn write_migration(migrated: &[MigratedSource], output_dir: &str) -> Result<(), Diagnostic> {
+    let mut report = String::new();
+    for source in migrated {
+        //keep the relative location of the source inside the output directory
+        let relative_path = Path::new(source.path.as_str())
+            .components()
+            .filter(|it| matches!(it, Component::Normal(_)))
+            .collect::<std::path::PathBuf>();
+        let target = Path::new(output_dir).join(relative_path);
+        write_file(&target, source.source.as_str())?;
+
+        for change in &source.changes {
+            report.push_str(&format!(
+                "{}:{}: {}\n",
+                source.path,
+                get_line_number(&source.original, &change.location),
+                change.description
+            ));
+        }
+        for issue in &source.open_issues {
+            report.push_str(&format!(
+                "{}:{}: needs manual migration: {}\n",
+                source.path,
+                get_line_number(&source.original, &issue.get_location()),
+                issue.get_message()
+            ));
+        }
+    }
+    write_file(
+        &Path::new(output_dir).join(REPORT_FILE_NAME),
+        report.as_str(),
+    )
+}
+
+/// returns the 1-based line of the given location in the given source
+fn get_line_number(source: &str, location: &SourceRange) -> usize {
+    source[..location.get_start().min(source.len())]
+        .matches('\n')
+        .count()
+        + 1
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), Diagnostic> {
+    let location = path.to_string_lossy();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| Diagnostic::io_write_error(&location, err.to_string().as_str()))?;
+    }
+    std::fs::write(path, content)
+        .map_err(|err| Diagnostic::io_write_error(&location, err.to_string().as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_changes, migrate, migrate_syntax, Change};
+    use crate::{
+        diagnostics::{CodeEdit, Diagnostic},
+        dialect::Dialect,
+        SourceCode,
+    };
+    use pretty_assertions::*;
+
+    #[test]
+    fn vendor_keywords_and_pragmas_are_migrated() {
+        let src = "@EXTERNAL FUNCTION foo : INT VARINPUT p : POINTER TO INT; ENDVAR ENDFUNCTION";
+        let (changes, open_issues) = migrate_syntax(src, Dialect::Codesys);
+
+        assert_eq!(
+            apply_changes(src, &changes).unwrap(),
+            "{external} FUNCTION foo : INT VAR_INPUT p : POINTER TO INT; END_VAR END_FUNCTION"
+        );
+        assert_eq!(
+            changes
+                .iter()
+                .map(|it| it.description.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "replaced '@EXTERNAL' with '{external}'",
+                "replaced 'VARINPUT' with 'VAR_INPUT'",
+                "replaced 'ENDVAR' with 'END_VAR'",
+                "replaced 'ENDFUNCTION' with 'END_FUNCTION'",
+            ]
+        );
+        //pointers support arithmetic, so they are not replaced with references
+        assert_eq!(
+            open_issues,
+            vec![Diagnostic::unsupported_in_dialect(
+                "POINTER TO",
+                Dialect::Iec,
+                (42..52).into()
+            )]
+        );
+    }
+
+    #[test]
+    fn scl_syntax_is_migrated() {
+        let src = "
+        PROGRAM prg
+        VERSION : 0.1
+        VAR x : INT; END_VAR
+        REGION init
+            #x := 0;
+        END_REGION
+        END_PROGRAM";
+
+        assert_eq!(
+            apply_changes(src, &migrate_syntax(src, Dialect::Scl).0).unwrap(),
+            "
+        PROGRAM prg
+        // VERSION : 0.1
+        VAR x : INT; END_VAR
+        // REGION init
+            x := 0;
+        // END_REGION
+        END_PROGRAM"
+        );
+    }
+
+    #[test]
+    fn implicit_conversions_are_migrated() {
+        let src = "
+        PROGRAM prg
+        VAR x : INT; y : INT; END_VAR
+        IF x THEN END_IF
+        WHILE x + y DO ENDWHILE
+        END_PROGRAM";
+
+        let migrated = migrate(vec![SourceCode::from(src)], None, Dialect::Legacy).unwrap();
+
+        assert_eq!(
+            migrated[0].source,
+            "
+        PROGRAM prg
+        VAR x : INT; y : INT; END_VAR
+        IF x <> 0 THEN END_IF
+        WHILE (x + y) <> 0 DO END_WHILE
+        END_PROGRAM"
+        );
+        assert!(migrated[0].open_issues.is_empty());
+    }
+
+    #[test]
+    fn constructs_without_a_portable_replacement_are_reported() {
+        let src = "
+        PROGRAM prg
+        VAR x : INT; a : LWORD; END_VAR
+        a := ADR(x);
+        END_PROGRAM";
+
+        let migrated = migrate(vec![SourceCode::from(src)], None, Dialect::Codesys).unwrap();
+
+        assert_eq!(migrated[0].source, src);
+        assert_eq!(migrated[0].open_issues.len(), 1);
+    }
+
+    #[test]
+    fn overlapping_changes_are_rejected() {
+        let src = "x := a + b;";
+        let change = |description: &str, edit: CodeEdit| Change {
+            description: description.to_string(),
+            location: edit.range.clone(),
+            edits: vec![edit],
+        };
+        let changes = vec![
+            change("first", CodeEdit::replace((5..10).into(), "(a + b)")),
+            change("second", CodeEdit::replace((9..10).into(), "c")),
+        ];
+
+        assert_eq!(
+            apply_changes(src, &changes),
+            Err(Diagnostic::conflicting_migration_changes("first", "second"))
+        );
+    }
+
+    #[test]
+    fn adjacent_changes_are_applied_in_order() {
+        let src = "x := a + b;";
+        let changes = vec![Change {
+            description: "wrapped 'a + b'".to_string(),
+            location: (5..10).into(),
+            edits: vec![
+                CodeEdit::insert("(", 5),
+                CodeEdit::replace((5..6).into(), "c"),
+                CodeEdit::insert(") <> 0", 10),
+            ],
+        }];
+
+        assert_eq!(apply_changes(src, &changes).unwrap(), "x := (c + b) <> 0;");
+    }
+}
@@ -1,9 +1,10 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 use crate::{
     ast::*,
+    dialect::LanguageExtension,
     expect_token, lexer,
     lexer::{ParseSession, Token, Token::*},
-    typesystem::DINT_TYPE,
+    typesystem::{DINT_TYPE, FIXED_TYPE},
     Diagnostic,
 };
 
@@ -14,6 +15,9 @@ use self::{
 
 mod control_parser;
 mod expressions_parser;
+mod il_parser;
+mod sfc_parser;
+mod state_machine_parser;
 
 #[cfg(test)]
 pub mod tests;
@@ -21,8 +25,41 @@ pub type ParsedAst = (CompilationUnit, Vec<Diagnostic>);
 
 pub fn parse(mut lexer: ParseSession, lnk: LinkageType) -> ParsedAst {
     let mut unit = CompilationUnit::default();
+    let mut usings = vec![];
+    while parse_declarations(&mut lexer, &mut unit, &mut usings, lnk) == KeywordEndNamespace {
+        lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+            "StartKeyword",
+            lexer.slice(),
+            lexer.location(),
+        ));
+        lexer.advance();
+    }
+    if !usings.is_empty() {
+        //the USING directives outside of a namespace apply to the global namespace
+        unit.namespaces.push(Namespace {
+            name: "".into(),
+            usings,
+            location: SourceRange::undefined(),
+        });
+    }
+    unit.comments = std::mem::take(&mut lexer.comments);
+    (unit, lexer.diagnostics)
+}
 
+/// parses the declarations into the given unit until the end of the file or of the current
+/// namespace, the namespaces imported by `USING` directives are added to `usings`
+/// returns the token the declarations ended with
+fn parse_declarations(
+    lexer: &mut ParseSession,
+    unit: &mut CompilationUnit,
+    usings: &mut Vec<String>,
+    lnk: LinkageType,
+) -> Token {
     let mut linkage = lnk;
+    let mut optimization = None;
+    let mut fast_math = None;
+    let mut inline = None;
+    let mut calling_convention = None;
     loop {
         match lexer.token {
             PropertyExternal => {
@@ -31,25 +68,126 @@ pub fn parse(mut lexer: ParseSession, lnk: LinkageType) -> ParsedAst {
                 //Don't reset linkage
                 continue;
             }
-            KeywordVarGlobal => unit
-                .global_vars
-                .push(parse_variable_block(&mut lexer, linkage)),
-            KeywordProgram | KeywordClass | KeywordFunction | KeywordFunctionBlock => {
+            PropertyCallingConvention => {
+                linkage = LinkageType::External;
+                calling_convention = Some((parse_calling_convention(lexer), lexer.location()));
+                lexer.advance();
+                continue;
+            }
+            PropertyWeak => {
+                linkage = LinkageType::Weak;
+                lexer.advance();
+                //Don't reset linkage
+                continue;
+            }
+            PropertyOptimize => {
+                optimization = Some((parse_optimization(lexer), lexer.location()));
+                lexer.advance();
+                continue;
+            }
+            PropertyFastMath => {
+                fast_math = Some(lexer.location());
+                lexer.advance();
+                continue;
+            }
+            PropertyInline => {
+                inline = Some(lexer.location());
+                lexer.advance();
+                continue;
+            }
+            PropertyPrototype => {
+                lexer.advance();
+                let params = match lexer.token {
+                    KeywordFunction => (PouType::Function, KeywordEndFunction),
+                    KeywordFunctionBlock => (PouType::FunctionBlock, KeywordEndFunctionBlock),
+                    _ => {
+                        lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+                            "FUNCTION or FUNCTION_BLOCK",
+                            lexer.slice(),
+                            lexer.location(),
+                        ));
+                        continue;
+                    }
+                };
+
+                //a prototype only declares the interface of a POU implemented elsewhere
+                let (mut pous, implementations) = parse_pou(lexer, params.0, linkage, params.1);
+                for implementation in implementations {
+                    if let Some(statement) = implementation.statements.first() {
+                        lexer.accept_diagnostic(Diagnostic::prototype_with_body(
+                            &implementation.name,
+                            statement.get_location(),
+                        ));
+                    }
+                }
+                unit.prototypes.append(&mut pous);
+            }
+            KeywordVarGlobal => unit.global_vars.push(parse_variable_block(lexer, linkage)),
+            KeywordVarConfig => unit.var_config.append(&mut parse_var_config(lexer)),
+            PropertyConfigConst | PropertyInitPriority => {
+                let (config_const, init_priority) = parse_global_block_attributes(lexer);
+                if lexer.token == KeywordVarGlobal {
+                    let mut block = parse_variable_block(lexer, linkage);
+                    block.config_const = config_const;
+                    block.init_priority = init_priority;
+                    unit.global_vars.push(block);
+                } else {
+                    lexer.accept_diagnostic(Diagnostic::invalid_pragma_location(
+                        if config_const {
+                            "Only VAR_GLOBAL blocks support the config_const attribute"
+                        } else {
+                            "Only VAR_GLOBAL blocks support the init_priority attribute"
+                        },
+                        lexer.location(),
+                    ));
+                }
+            }
+            KeywordProgram | KeywordClass | KeywordInterface | KeywordFunction
+            | KeywordFunctionBlock => {
                 let params = match lexer.token {
                     KeywordProgram => (PouType::Program, KeywordEndProgram),
                     KeywordClass => (PouType::Class, KeywordEndClass),
+                    KeywordInterface => (PouType::Interface, KeywordEndInterface),
                     KeywordFunction => (PouType::Function, KeywordEndFunction),
                     _ => (PouType::FunctionBlock, KeywordEndFunctionBlock),
                 };
 
-                let (mut pou, mut implementation) =
-                    parse_pou(&mut lexer, params.0, linkage, params.1);
+                let (mut pou, mut implementation) = parse_pou(lexer, params.0, linkage, params.1);
+                if let (Some(pou), Some((hint, _))) = (pou.first_mut(), optimization.take()) {
+                    pou.optimization = hint;
+                }
+                if let (Some(pou), Some(_)) = (pou.first_mut(), fast_math.take()) {
+                    pou.fast_math = true;
+                }
+                if let Some(pou) = pou
+                    .first_mut()
+                    .filter(|it| it.pou_type == PouType::Function)
+                {
+                    if let Some((convention, _)) = calling_convention.take() {
+                        pou.calling_convention = convention;
+                    }
+                    if inline.take().is_some() {
+                        pou.inline = true;
+                        if let Err(reason) = implementation
+                            .first()
+                            .map_or(Err("it is not implemented"), |it| {
+                                pou.get_inline_expression(it)
+                            })
+                        {
+                            lexer.accept_diagnostic(Diagnostic::not_inlinable(
+                                &pou.name,
+                                reason,
+                                pou.name_location.clone(),
+                            ));
+                        }
+                    }
+                }
 
                 unit.units.append(&mut pou);
                 unit.implementations.append(&mut implementation);
             }
             KeywordAction => {
-                if let Some(implementation) = parse_action(&mut lexer, linkage, None) {
+                if let Some(implementation) = parse_action(lexer, linkage, None) {
                     unit.implementations.push(implementation);
                 }
             }
@@ -59,15 +197,17 @@ pub fn parse(mut lexer: ParseSession, lnk: LinkageType) -> ParsedAst {
                     .last()
                     .map(|it| it.name.as_str())
                     .unwrap_or("__unknown__");
-                let mut actions = parse_actions(&mut lexer, linkage, last_pou);
+                let mut actions = parse_actions(lexer, linkage, last_pou);
                 unit.implementations.append(&mut actions);
             }
             KeywordType => {
-                if let Some(unit_type) = parse_type(&mut lexer) {
+                if let Some(unit_type) = parse_type(lexer) {
                     unit.types.push(unit_type);
                 }
             }
-            KeywordEndActions | End => return (unit, lexer.diagnostics),
+            KeywordNamespace => unit.import(parse_namespace(lexer, linkage)),
+            KeywordUsing => usings.append(&mut parse_using_directive(lexer)),
+            KeywordEndActions | KeywordEndNamespace | End => return lexer.token.clone(),
             _ => {
                 lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
                     "StartKeyword",
@@ -78,10 +218,101 @@ pub fn parse(mut lexer: ParseSession, lnk: LinkageType) -> ParsedAst {
             }
         };
         linkage = lnk;
+        if let Some((_, location)) = optimization.take() {
+            lexer.accept_diagnostic(Diagnostic::invalid_pragma_location(
+                "Only POUs support the optimize attribute",
+                location,
+            ));
+        }
+        if let Some(location) = fast_math.take() {
+            lexer.accept_diagnostic(Diagnostic::invalid_pragma_location(
+                "Only POUs support the fast_math attribute",
+                location,
+            ));
+        }
+        if let Some(location) = inline.take() {
+            lexer.accept_diagnostic(Diagnostic::invalid_pragma_location(
+                "Only FUNCTIONs support the inline attribute",
+                location,
+            ));
+        }
+        if let Some((_, location)) = calling_convention.take() {
+            lexer.accept_diagnostic(Diagnostic::invalid_pragma_location(
+                "Only FUNCTIONs support a calling convention",
+                location,
+            ));
+        }
     }
     //the match in the loop will always return
 }
 
+/// parses the value of the current `{attribute 'optimize' := '...'}` pragma
+fn parse_optimization(lexer: &mut ParseSession) -> Option<OptimizationHint> {
+    let value = lexer.slice().rsplit('\'').nth(1).unwrap_or_default();
+    let hint = OptimizationHint::from_name(value);
+    if hint.is_none() {
+        lexer.accept_diagnostic(Diagnostic::unknown_optimization(value, lexer.location()));
+    }
+    hint
+}
+
+/// parses the calling convention of the current `{external '...'}` pragma
+fn parse_calling_convention(lexer: &mut ParseSession) -> Option<CallingConvention> {
+    let value = lexer.slice().rsplit('\'').nth(1).unwrap_or_default();
+    let convention = CallingConvention::from_name(value);
+    if convention.is_none() {
+        lexer.accept_diagnostic(Diagnostic::unknown_calling_convention(
+            value,
+            lexer.location(),
+        ));
+    }
+    convention
+}
+
+/// parses a `NAMESPACE name ... END_NAMESPACE` block, the names of its declarations (including
+/// the ones of nested namespaces) are qualified with the namespace's name
+fn parse_namespace(lexer: &mut ParseSession, linkage: LinkageType) -> CompilationUnit {
+    let start = lexer.range().start;
+    lexer.advance(); //Consume NAMESPACE
+    let name = parse_qualified_identifier(lexer)
+        .map(|(name, _)| name)
+        .unwrap_or_default();
+
+    let mut unit = CompilationUnit::default();
+    let mut usings = vec![];
+    if parse_declarations(lexer, &mut unit, &mut usings, linkage) == KeywordEndNamespace {
+        lexer.advance();
+    } else {
+        lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+            "END_NAMESPACE",
+            lexer.slice(),
+            lexer.location(),
+        ));
+    }
+
+    unit.qualify_names(&name);
+    unit.namespaces.push(Namespace {
+        name,
+        usings,
+        location: SourceRange::new(start..lexer.last_range.end),
+    });
+    unit
+}
+
+/// parses a `USING a, b.c;` directive and returns the names of the imported namespaces
+fn parse_using_directive(lexer: &mut ParseSession) -> Vec<String> {
+    lexer.advance(); //Consume USING
+    let mut usings = vec![];
+    while let Some((name, _)) = parse_qualified_identifier(lexer) {
+        usings.push(name);
+        if !lexer.allow(&KeywordComma) {
+            break;
+        }
+    }
+    lexer.consume_or_report(KeywordSemicolon);
+    usings
+}
+
 fn parse_actions(
     lexer: &mut ParseSession,
     linkage: LinkageType,
@@ -158,23 +389,42 @@ fn parse_pou(
         let generics = parse_generics(lexer);
 
         with_scope(lexer, name.clone(), |lexer| {
-            // TODO: Parse USING directives
-            // TODO: Parse EXTENDS specifier
-            // TODO: Parse IMPLEMENTS specifier
+            let mut usings = vec![];
+            while lexer.token == KeywordUsing {
+                usings.append(&mut parse_using_directive(lexer));
+            }
+            let super_class = if pou_type == PouType::Class && lexer.allow(&KeywordExtends) {
+                parse_qualified_identifier(lexer).map(|(name, _)| name)
+            } else {
+                None
+            };
+            let interfaces = if matches!(pou_type, PouType::Class | PouType::FunctionBlock)
+                && lexer.allow(&KeywordImplements)
+            {
+                parse_interface_names(lexer)
+            } else {
+                vec![]
+            };
 
-            let return_type = if pou_type != PouType::Class {
+            let return_type = if !matches!(pou_type, PouType::Class | PouType::Interface) {
                 // parse an optional return type
                 parse_return_type(lexer, &pou_type)
             } else {
-                // classes do not have a return type
+                // classes and interfaces do not have a return type
                 None
             };
+            //the USING directives of a function follow its return type
+            while lexer.token == KeywordUsing {
+                usings.append(&mut parse_using_directive(lexer));
+            }
 
             // parse variable declarations. note that var in/out/inout
-            // blocks are not allowed inside of class declarations.
+            // blocks are not allowed inside of class declarations and
+            // interfaces declare no variables at all.
             let mut variable_blocks = vec![];
             let allowed_var_types = match pou_type {
                 PouType::Class => vec![KeywordVar],
+                PouType::Interface => vec![],
                 _ => vec![
                     KeywordVar,
                     KeywordVarInput,
@@ -197,13 +447,74 @@ fn parse_pou(
                 // declarations before their implementation.
                 while lexer.token == KeywordMethod {
                     if let Some((pou, implementation)) = parse_method(lexer, &name, linkage) {
+                        // abstract methods are implemented by the extending classes
+                        if pou.poly_mode == Some(PolymorphismMode::Abstract) {
+                            if let Some(statement) = implementation.statements.first() {
+                                lexer.accept_diagnostic(Diagnostic::abstract_method_with_body(
+                                    &pou.name,
+                                    statement.get_location(),
+                                ));
+                            }
+                        }
                         impl_pous.push(pou);
                         implementations.push(implementation);
                     }
                 }
             }
-            if pou_type != PouType::Class {
-                // a class may not contain an implementation
+            if pou_type == PouType::Interface {
+                // interfaces only declare the prototypes of their methods, the implementations
+                // are provided by the classes and function blocks implementing the interface
+                while lexer.token == KeywordMethod {
+                    if let Some((pou, implementation)) = parse_method(lexer, &name, linkage) {
+                        if let Some(statement) = implementation.statements.first() {
+                            lexer.accept_diagnostic(Diagnostic::interface_method_with_body(
+                                &pou.name,
+                                statement.get_location(),
+                            ));
+                        }
+                        impl_pous.push(pou);
+                    }
+                }
+            }
+            if !matches!(pou_type, PouType::Class | PouType::Interface)
+                && state_machine_parser::starts_state_machine(lexer)
+            {
+                // a state machine is lowered to a CASE over the current state, its entry and exit
+                // sections to actions of the POU
+                let mut state_machine =
+                    state_machine_parser::parse_state_machine(lexer, &pou_type, &name, linkage);
+                variable_blocks.append(&mut state_machine.variables);
+                implementations.push(state_machine.implementation);
+                implementations.append(&mut state_machine.actions);
+            } else if !matches!(pou_type, PouType::Class | PouType::Interface)
+                && sfc_parser::starts_sfc(lexer)
+            {
+                // a sequential function chart is lowered to the POU's implementation, its steps
+                // are declared as variables of the POU
+                let mut sfc = sfc_parser::parse_sfc(lexer, &pou_type, &name, linkage);
+                variable_blocks.append(&mut sfc.variables);
+                implementations.push(sfc.implementation);
+                implementations.append(&mut sfc.actions);
+            } else if !matches!(pou_type, PouType::Class | PouType::Interface) && lexer.starts_il()
+            {
+                // an instruction list is lowered to the statements of the POU's implementation
+                let start = lexer.range().start;
+                let mut il = il_parser::parse_il(lexer);
+                variable_blocks.append(&mut il.variables);
+                implementations.push(Implementation {
+                    name: name.clone(),
+                    type_name: name.clone(),
+                    linkage,
+                    pou_type: pou_type.clone(),
+                    statements: il.statements,
+                    location: SourceRange::new(start..lexer.range().end),
+                    overriding: false,
+                    generic: !generics.is_empty(),
+                    access: None,
+                    loop_bounds: std::mem::take(&mut lexer.loop_bounds),
+                });
+            } else if !matches!(pou_type, PouType::Class | PouType::Interface) {
+                // classes and interfaces may not contain an implementation
                 implementations.push(parse_implementation(
                     lexer,
                     linkage,
@@ -224,6 +535,13 @@ fn parse_pou(
                 poly_mode,
                 generics,
                 linkage,
+                super_class,
+                interfaces,
+                usings,
+                optimization: None,
+                fast_math: false,
+                inline: false,
+                calling_convention: None,
             }];
             pous.append(&mut impl_pous);
 
@@ -242,6 +560,28 @@ fn parse_pou(
     pou
 }
 
+/// parses the comma-separated names of the interfaces following `IMPLEMENTS`
+fn parse_interface_names(lexer: &mut ParseSession) -> Vec<String> {
+    let mut interfaces = vec![];
+    loop {
+        match parse_qualified_identifier(lexer) {
+            Some((name, _)) => interfaces.push(name),
+            None => {
+                lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+                    "Identifier",
+                    lexer.slice(),
+                    lexer.location(),
+                ));
+                break;
+            }
+        }
+        if !lexer.allow(&KeywordComma) {
+            break;
+        }
+    }
+    interfaces
+}
+
 fn parse_generics(lexer: &mut ParseSession) -> Vec<GenericBinding> {
     if lexer.allow(&Token::OperatorLess) {
         parse_any_in_region(lexer, vec![Token::OperatorGreater], |lexer| {
@@ -424,6 +764,13 @@ fn parse_method(
                 poly_mode,
                 generics,
                 linkage,
+                super_class: None,
+                interfaces: vec![],
+                usings: vec![],
+                optimization: None,
+                fast_math: false,
+                inline: false,
+                calling_convention: None,
             },
             implementation,
         ))
@@ -444,6 +791,18 @@ fn parse_access_modifier(lexer: &mut ParseSession) -> AccessModifier {
     }
 }
 
+/// parse a name qualified with the namespaces it is declared in (e.g. `plant.conveyor.Motor`)
+/// returns the name as a String and the SourceRange of the parsed name
+fn parse_qualified_identifier(lexer: &mut ParseSession) -> Option<(String, SourceRange)> {
+    let (mut name, location) = parse_identifier(lexer)?;
+    let start = location.get_start();
+    while lexer.allow(&KeywordDot) {
+        let (segment, _) = parse_identifier(lexer)?;
+        name = format!("{}.{}", name, segment);
+    }
+    Some((name, SourceRange::new(start..lexer.last_range.end)))
+}
+
 /// parse identifier and advance if successful
 /// returns the identifier as a String and the SourceRange of the parsed name
 fn parse_identifier(lexer: &mut ParseSession) -> Option<(String, SourceRange)> {
@@ -481,6 +840,7 @@ fn parse_implementation(
         overriding: false,
         generic,
         access: None,
+        loop_bounds: std::mem::take(&mut lexer.loop_bounds),
     }
 }
 
@@ -623,23 +983,35 @@ fn parse_data_type_definition(
     } else if lexer.allow(&KeywordPointer) {
         let start_pos = lexer.last_range.start;
         //Report wrong keyword
-        lexer.accept_diagnostic(Diagnostic::ImprovementSuggestion {
-            message: "'POINTER TO' is not a standard keyword, use REF_TO instead".to_string(),
-            range: lexer.last_location(),
-        });
+        if lexer.dialect.allows(LanguageExtension::PointerTo) {
+            lexer.accept_diagnostic(Diagnostic::ImprovementSuggestion {
+                message: "'POINTER TO' is not a standard keyword, use REF_TO instead".to_string(),
+                range: lexer.last_location(),
+            });
+        } else {
+            lexer.accept_diagnostic(Diagnostic::unsupported_in_dialect(
+                "POINTER TO",
+                lexer.dialect,
+                lexer.last_location(),
+            ));
+        }
         if let Err(diag) = lexer.expect(KeywordTo) {
             lexer.accept_diagnostic(diag);
         } else {
             lexer.advance();
         }
-        parse_pointer_definition(lexer, name, start_pos)
+        parse_pointer_definition(lexer, name, start_pos, false)
     } else if lexer.allow(&KeywordRef) {
-        parse_pointer_definition(lexer, name, lexer.last_range.start)
+        parse_pointer_definition(lexer, name, lexer.last_range.start, false)
+    } else if lexer.allow(&KeywordReferenceTo) {
+        parse_pointer_definition(lexer, name, lexer.last_range.start, true)
     } else if lexer.allow(&KeywordParensOpen) {
         //enum without datatype
         parse_enum_type_definition(lexer, name)
     } else if lexer.token == KeywordString || lexer.token == KeywordWideString {
         parse_string_type_definition(lexer, name)
+    } else if lexer.is_vector_or_matrix() {
+        parse_vector_or_matrix_type_definition(lexer, name)
     } else if lexer.token == Identifier {
         parse_type_reference_type_definition(lexer, name)
     } else {
@@ -657,6 +1029,7 @@ fn parse_pointer_definition(
     lexer: &mut ParseSession,
     name: Option<String>,
     start_pos: usize,
+    auto_deref: bool,
 ) -> Option<(DataTypeDeclaration, Option<AstStatement>)> {
     parse_data_type_definition(lexer, None).map(|(decl, initializer)| {
         (
@@ -664,6 +1037,7 @@ fn parse_pointer_definition(
                 data_type: DataType::PointerType {
                     name,
                     referenced_type: Box::new(decl),
+                    auto_deref,
                 },
                 location: (start_pos..lexer.last_range.end).into(),
                 scope: lexer.scope.clone(),
@@ -679,7 +1053,14 @@ fn parse_type_reference_type_definition(
 ) -> Option<(DataTypeDeclaration, Option<AstStatement>)> {
     let start = lexer.location().get_start();
     //Subrange
-    let referenced_type = lexer.slice_and_advance();
+    let mut referenced_type = lexer.slice_and_advance();
+    //a type declared in a namespace (e.g. `plant.Motor`)
+    while lexer.token == KeywordDot {
+        lexer.advance();
+        if let Some((segment, _)) = parse_identifier(lexer) {
+            referenced_type = format!("{}.{}", referenced_type, segment);
+        }
+    }
 
     let bounds = if lexer.allow(&KeywordParensOpen) {
         // INT (..) :=
@@ -698,6 +1079,24 @@ fn parse_type_reference_type_definition(
     };
 
     let end = lexer.last_range.end;
+    if referenced_type.eq_ignore_ascii_case(FIXED_TYPE) {
+        if let Some(bounds) = bounds {
+            let location = SourceRange::new(start..end);
+            let (integer_bits, fraction_bits) = parse_fixed_point_bits(lexer, &bounds, &location);
+            return Some((
+                DataTypeDeclaration::DataTypeDefinition {
+                    data_type: DataType::FixedPointType {
+                        name,
+                        integer_bits,
+                        fraction_bits,
+                    },
+                    location,
+                    scope: lexer.scope.clone(),
+                },
+                initial_value,
+            ));
+        }
+    }
     if name.is_some() || bounds.is_some() {
         let data_type = match bounds {
             Some(AstStatement::ExpressionList { expressions, id }) => {
@@ -747,6 +1146,39 @@ fn parse_type_reference_type_definition(
     }
 }
 
+/// returns the integer and fraction bits of `FIXED(integer_bits, fraction_bits)`, the number
+/// is stored in an integer of 8, 16, 32 or 64 bits including the sign. Invalid bits are reported
+/// and replaced by `FIXED(16, 16)`
+fn parse_fixed_point_bits(
+    lexer: &mut ParseSession,
+    bounds: &AstStatement,
+    location: &SourceRange,
+) -> (u32, u32) {
+    let bits = match bounds {
+        AstStatement::ExpressionList { expressions, .. } => match expressions.as_slice() {
+            [AstStatement::LiteralInteger { value: integer, .. }, AstStatement::LiteralInteger {
+                value: fraction, ..
+            }] => u32::try_from(*integer)
+                .ok()
+                .zip(u32::try_from(*fraction).ok()),
+            _ => None,
+        },
+        _ => None,
+    }
+    // the sign takes one of the integer bits
+    .filter(|(integer, fraction)| {
+        *integer > 0 && matches!(integer.checked_add(*fraction), Some(8 | 16 | 32 | 64))
+    });
+
+    bits.unwrap_or_else(|| {
+        lexer.accept_diagnostic(Diagnostic::syntax_error(
+            "A FIXED type is declared with at least one integer bit and the fraction bits adding up to 8, 16, 32 or 64 (e.g. FIXED(16, 16))",
+            location.clone(),
+        ));
+        (16, 16)
+    })
+}
+
 fn parse_string_size_expression(lexer: &mut ParseSession) -> Option<AstStatement> {
     let opening_token = lexer.token.clone();
     if lexer.allow(&KeywordSquareParensOpen) || lexer.allow(&KeywordParensOpen) {
@@ -835,13 +1267,17 @@ fn parse_array_type_definition(
     name: Option<String>,
 ) -> Option<(DataTypeDeclaration, Option<AstStatement>)> {
     let start = lexer.last_range.start;
-    let range = parse_any_in_region(lexer, vec![KeywordOf], |lexer| {
+    let (range, is_variable_length) = parse_any_in_region(lexer, vec![KeywordOf], |lexer| {
         // Parse Array range
 
         expect_token!(lexer, KeywordSquareParensOpen, None);
         lexer.advance();
 
-        let range_statement = parse_expression(lexer);
+        let range_statement = if lexer.token == OperatorMultiplication {
+            (parse_variable_length_dimensions(lexer), true)
+        } else {
+            (parse_expression(lexer), false)
+        };
 
         expect_token!(lexer, KeywordSquareParensClose, None);
         lexer.advance();
@@ -858,6 +1294,82 @@ fn parse_array_type_definition(
                     name,
                     bounds: range,
                     referenced_type: Box::new(reference),
+                    is_variable_length,
+                },
+                location,
+                scope: lexer.scope.clone(),
+            },
+            initializer,
+        )
+    })
+}
+
+/// `VECTOR[n] OF <type>` and `MATRIX[rows, columns] OF <type>` are arrays indexed from 0, like
+/// `ARRAY[0..n-1] OF <type>` and `ARRAY[0..rows-1, 0..columns-1] OF <type>`
+fn parse_vector_or_matrix_type_definition(
+    lexer: &mut ParseSession,
+    name: Option<String>,
+) -> Option<(DataTypeDeclaration, Option<AstStatement>)> {
+    let start = lexer.location().get_start();
+    let is_matrix = lexer.slice_and_advance().eq_ignore_ascii_case("MATRIX");
+    let sizes = parse_any_in_region(lexer, vec![KeywordOf], |lexer| {
+        expect_token!(lexer, KeywordSquareParensOpen, None);
+        lexer.advance();
+        let sizes = parse_expression(lexer);
+        expect_token!(lexer, KeywordSquareParensClose, None);
+        lexer.advance();
+        Some(sizes)
+    })?;
+
+    let sizes = match sizes {
+        AstStatement::ExpressionList { expressions, .. } => expressions,
+        size => vec![size],
+    };
+    if sizes.len() != if is_matrix { 2 } else { 1 } {
+        lexer.accept_diagnostic(Diagnostic::syntax_error(
+            if is_matrix {
+                "A MATRIX is declared with its rows and columns (e.g. MATRIX[3, 3])"
+            } else {
+                "A VECTOR is declared with its size (e.g. VECTOR[3])"
+            },
+            SourceRange::new(start..lexer.last_range.end),
+        ));
+    }
+    let mut bounds = sizes
+        .into_iter()
+        .map(|size| {
+            let location = size.get_location();
+            AstStatement::RangeStatement {
+                start: Box::new(create_literal_int(0, &location, lexer.next_id())),
+                end: Box::new(create_binary_expression(
+                    size,
+                    Operator::Minus,
+                    create_literal_int(1, &location, lexer.next_id()),
+                    lexer.next_id(),
+                )),
+                id: lexer.next_id(),
+            }
+        })
+        .collect::<Vec<_>>();
+    let bounds = if bounds.len() == 1 {
+        bounds.remove(0)
+    } else {
+        AstStatement::ExpressionList {
+            expressions: bounds,
+            id: lexer.next_id(),
+        }
+    };
+
+    let inner_type_defintion = parse_data_type_definition(lexer, None);
+    inner_type_defintion.map(|(reference, initializer)| {
+        let location = SourceRange::new(start..reference.get_location().get_end());
+        (
+            DataTypeDeclaration::DataTypeDefinition {
+                data_type: DataType::ArrayType {
+                    name,
+                    bounds,
+                    referenced_type: Box::new(reference),
+                    is_variable_length: false,
                 },
                 location,
                 scope: lexer.scope.clone(),
@@ -867,12 +1379,51 @@ fn parse_array_type_definition(
     })
 }
 
+/// parses the dimensions of a variable-length array (e.g. `*, *`), every dimension is represented
+/// by an EmptyStatement
+fn parse_variable_length_dimensions(lexer: &mut ParseSession) -> AstStatement {
+    let mut dimensions = vec![];
+    loop {
+        dimensions.push(AstStatement::EmptyStatement {
+            location: lexer.location(),
+            id: lexer.next_id(),
+        });
+        if !lexer.allow(&OperatorMultiplication) {
+            lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+                "*",
+                lexer.slice(),
+                lexer.location(),
+            ));
+        }
+        if !lexer.allow(&KeywordComma) {
+            break;
+        }
+    }
+    if dimensions.len() == 1 {
+        dimensions.remove(0)
+    } else {
+        AstStatement::ExpressionList {
+            expressions: dimensions,
+            id: lexer.next_id(),
+        }
+    }
+}
+
 /// parse a body and recovers until the given `end_keywords`
 fn parse_body_in_region(lexer: &mut ParseSession, end_keywords: Vec<Token>) -> Vec<AstStatement> {
     parse_any_in_region(lexer, end_keywords, parse_body_standalone)
 }
 
 fn parse_body_standalone(lexer: &mut ParseSession) -> Vec<AstStatement> {
+    parse_statement_list(lexer)
+        .into_iter()
+        .map(|it| into_label(lexer, it))
+        .collect()
+}
+
+/// parses the statements until the current region closes, `name:` is parsed as a
+/// `CaseCondition` (see `parse_body_standalone` for bodies outside of a `CASE`)
+fn parse_statement_list(lexer: &mut ParseSession) -> Vec<AstStatement> {
     let mut statements = Vec::new();
     while !lexer.closes_open_region(&lexer.token) {
         statements.push(parse_control(lexer));
@@ -880,6 +1431,29 @@ fn parse_body_standalone(lexer: &mut ParseSession) -> Vec<AstStatement> {
     statements
 }
 
+/// outside of a `CASE`, a condition consisting of a single name is a jump's label (`name:`)
+fn into_label(lexer: &mut ParseSession, statement: AstStatement) -> AstStatement {
+    match statement {
+        AstStatement::CaseCondition { condition, id } => match *condition {
+            AstStatement::Reference { name, location, .. } => {
+                if !lexer.dialect.allows(LanguageExtension::Jumps) {
+                    lexer.accept_diagnostic(Diagnostic::unsupported_in_dialect(
+                        &format!("{}:", name),
+                        lexer.dialect,
+                        location.clone(),
+                    ));
+                }
+                AstStatement::LabelStatement { name, location, id }
+            }
+            condition => AstStatement::CaseCondition {
+                condition: Box::new(condition),
+                id,
+            },
+        },
+        statement => statement,
+    }
+}
+
 /// parses a statement ending with a ';'
 fn parse_statement(lexer: &mut ParseSession) -> AstStatement {
     let result = parse_any_in_region(lexer, vec![KeywordSemicolon, KeywordColon], |lexer| {
@@ -967,13 +1541,52 @@ fn parse_variable_block_type(lexer: &mut ParseSession) -> VariableBlockType {
     }
 }
 
+/// parses the attributes of a `VAR_GLOBAL` block in any order, `{attribute 'config_const'}` and
+/// `{attribute 'init_priority' := 'n'}`
+fn parse_global_block_attributes(lexer: &mut ParseSession) -> (bool, Option<u32>) {
+    let (mut config_const, mut init_priority) = (false, None);
+    loop {
+        match lexer.token {
+            PropertyConfigConst => config_const = true,
+            PropertyInitPriority => {
+                let value = lexer
+                    .slice()
+                    .split(":=")
+                    .nth(1)
+                    .unwrap_or_default()
+                    .trim_end_matches('}')
+                    .trim()
+                    .trim_matches('\'')
+                    .trim()
+                    .to_string();
+                match value.parse::<u32>() {
+                    Ok(priority) => init_priority = Some(priority),
+                    Err(_) => lexer.accept_diagnostic(Diagnostic::invalid_init_priority(
+                        &value,
+                        lexer.location(),
+                    )),
+                }
+            }
+            _ => return (config_const, init_priority),
+        }
+        lexer.advance();
+    }
+}
+
 fn parse_variable_block(lexer: &mut ParseSession, linkage: LinkageType) -> VariableBlock {
     let location = lexer.location();
     let variable_block_type = parse_variable_block_type(lexer);
+    //`{atomic}` and `{shared}` may be combined in any order
+    let atomic = lexer.allow(&PropertyAtomic);
+    let shared = lexer.allow(&PropertyShared);
+    let atomic = lexer.allow(&PropertyAtomic) || atomic;
 
     let constant = lexer.allow(&KeywordConstant);
 
     let retain = lexer.allow(&KeywordRetain);
+    let persistent = lexer.allow(&KeywordPersistent);
+    //persistent variables are retained as well, `PERSISTENT RETAIN` equals `RETAIN PERSISTENT`
+    let retain = lexer.allow(&KeywordRetain) || retain || persistent;
     lexer.allow(&KeywordNonRetain);
 
     let access = parse_access_modifier(lexer);
@@ -985,6 +1598,11 @@ fn parse_variable_block(lexer: &mut ParseSession, linkage: LinkageType) -> Varia
         access,
         constant,
         retain,
+        persistent,
+        atomic,
+        shared,
+        config_const: false,
+        init_priority: None,
         variables,
         variable_block_type,
         linkage,
@@ -1024,49 +1642,7 @@ fn parse_variable_line(lexer: &mut ParseSession) -> Vec<Variable> {
 
     //See if there's an AT keyword
     let address = if lexer.allow(&KeywordAt) {
-        let start_location = lexer.last_range.start;
-        //Look for a hardware address
-        if let HardwareAccess((direction, access_type)) = lexer.token {
-            lexer.advance();
-            //Folowed by an integer
-            if access_type == DirectAccessType::Template || lexer.token == LiteralInteger {
-                let mut address = vec![];
-                if lexer.token == LiteralInteger {
-                    loop {
-                        let int = expressions_parser::parse_strict_literal_integer(lexer);
-                        match int {
-                            Ok(statement) => address.push(statement),
-                            Err(err) => {
-                                lexer.accept_diagnostic(err);
-                                break;
-                            }
-                        }
-                        if !lexer.allow(&KeywordDot) {
-                            break;
-                        }
-                    }
-                }
-                Some(AstStatement::HardwareAccess {
-                    access: access_type,
-                    direction,
-                    address,
-                    location: (start_location..lexer.last_range.end).into(),
-                    id: lexer.next_id(),
-                })
-            } else {
-                lexer.accept_diagnostic(Diagnostic::missing_token(
-                    "LiteralInteger",
-                    lexer.location(),
-                ));
-                None
-            }
-        } else {
-            lexer.accept_diagnostic(Diagnostic::missing_token(
-                "Hardware Access",
-                lexer.location(),
-            ));
-            None
-        }
+        parse_hardware_access(lexer)
     } else {
         None
     };
@@ -1094,3 +1670,127 @@ fn parse_variable_line(lexer: &mut ParseSession) -> Vec<Variable> {
     }
     variables
 }
+
+/// parses a `VAR_CONFIG ... END_VAR` block, every entry binds the template address of an
+/// instance's variable (e.g. `main.fb.sensor AT %IX1.2 : BOOL;`)
+fn parse_var_config(lexer: &mut ParseSession) -> Vec<ConfigVariable> {
+    lexer.advance(); //Consume VAR_CONFIG
+    parse_any_in_region(lexer, vec![KeywordEndVar], |lexer| {
+        let mut variables = vec![];
+        while lexer.token == Identifier {
+            if let Some(variable) = parse_config_variable(lexer) {
+                variables.push(variable);
+            }
+        }
+        variables
+    })
+}
+
+fn parse_config_variable(lexer: &mut ParseSession) -> Option<ConfigVariable> {
+    let (reference, location) = parse_instance_path(lexer)?;
+    let address = if lexer.allow(&KeywordAt) {
+        parse_hardware_access(lexer)
+    } else {
+        lexer.accept_diagnostic(Diagnostic::missing_token(
+            format!("{:?}", KeywordAt).as_str(),
+            lexer.location(),
+        ));
+        None
+    };
+
+    if !lexer.allow(&KeywordColon) {
+        lexer.accept_diagnostic(Diagnostic::missing_token(
+            format!("{:?}", KeywordColon).as_str(),
+            lexer.location(),
+        ));
+    }
+    let (data_type, _) = parse_full_data_type_definition(lexer, None)?;
+    Some(ConfigVariable {
+        reference,
+        data_type,
+        address: address?,
+        location,
+    })
+}
+
+/// parses the path to an instance's variable, array elements are accessed with integer literals
+/// (e.g. `main.fbs[1,2].sensor`)
+fn parse_instance_path(lexer: &mut ParseSession) -> Option<(String, SourceRange)> {
+    let (mut path, location) = parse_identifier(lexer)?;
+    let start = location.get_start();
+    loop {
+        if lexer.allow(&KeywordDot) {
+            let (name, _) = parse_identifier(lexer)?;
+            path = format!("{}.{}", path, name);
+        } else if lexer.allow(&KeywordSquareParensOpen) {
+            let mut indices = vec![];
+            loop {
+                let sign = if lexer.allow(&OperatorMinus) { "-" } else { "" };
+                if lexer.token != LiteralInteger {
+                    lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+                        "LiteralInteger",
+                        lexer.slice(),
+                        lexer.location(),
+                    ));
+                    return None;
+                }
+                indices.push(format!("{}{}", sign, lexer.slice_and_advance()));
+                if !lexer.allow(&KeywordComma) {
+                    break;
+                }
+            }
+            lexer.consume_or_report(KeywordSquareParensClose);
+            path = format!("{}[{}]", path, indices.join(","));
+        } else {
+            break;
+        }
+    }
+    Some((path, SourceRange::new(start..lexer.last_range.end)))
+}
+
+/// parses the hardware address following an `AT` keyword (e.g. `%IX1.2` or `%I*`)
+fn parse_hardware_access(lexer: &mut ParseSession) -> Option<AstStatement> {
+    let start_location = lexer.last_range.start;
+    //Look for a hardware address
+    if let HardwareAccess((direction, access_type)) = lexer.token {
+        lexer.advance();
+        //Folowed by an integer
+        if access_type == DirectAccessType::Template || lexer.token == LiteralInteger {
+            let mut address = vec![];
+            if lexer.token == LiteralInteger {
+                loop {
+                    let int = expressions_parser::parse_strict_literal_integer(lexer);
+                    match int {
+                        Ok(statement) => address.push(statement),
+                        Err(err) => {
+                            lexer.accept_diagnostic(err);
+                            break;
+                        }
+                    }
+                    if !lexer.allow(&KeywordDot) {
+                        break;
+                    }
+                }
+            }
+            Some(AstStatement::HardwareAccess {
+                access: access_type,
+                direction,
+                address,
+                location: (start_location..lexer.last_range.end).into(),
+                id: lexer.next_id(),
+            })
+        } else {
+            lexer.accept_diagnostic(Diagnostic::missing_token(
+                "LiteralInteger",
+                lexer.location(),
+            ));
+            None
+        }
+    } else {
+        lexer.accept_diagnostic(Diagnostic::missing_token(
+            "Hardware Access",
+            lexer.location(),
+        ));
+        None
+    }
+}
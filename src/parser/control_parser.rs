@@ -1,9 +1,10 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 use crate::{
     ast::*,
+    dialect::LanguageExtension,
     expect_token,
     lexer::Token::*,
-    parser::{parse_any_in_region, parse_body_in_region},
+    parser::{parse_any_in_region, parse_body_in_region, parse_statement_list},
     Diagnostic,
 };
 
@@ -20,10 +21,76 @@ pub fn parse_control_statement(lexer: &mut ParseSession) -> AstStatement {
         KeywordReturn => parse_return_statement(lexer),
         KeywordContinue => parse_continue_statement(lexer),
         KeywordExit => parse_exit_statement(lexer),
+        Identifier if lexer.is_jump() => parse_jump_statement(lexer),
+        PropertyLoopBound => parse_loop_bound(lexer),
         _ => parse_statement(lexer),
     }
 }
 
+/// parses `{attribute 'loop_bound' := 'n'}` followed by the loop it bounds, the bound is recorded
+/// by the loop's id (see `Implementation::loop_bounds`)
+fn parse_loop_bound(lexer: &mut ParseSession) -> AstStatement {
+    let value = lexer
+        .slice()
+        .rsplit('\'')
+        .nth(1)
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let location = lexer.location();
+    lexer.advance();
+    let bound = value.parse::<u32>().ok();
+    if bound.is_none() {
+        lexer.accept_diagnostic(Diagnostic::invalid_loop_bound(&value, location.clone()));
+    }
+
+    let statement = parse_control_statement(lexer);
+    let is_loop = matches!(
+        statement,
+        AstStatement::ForLoopStatement { .. }
+            | AstStatement::WhileLoopStatement { .. }
+            | AstStatement::RepeatLoopStatement { .. }
+    );
+    if !is_loop {
+        lexer.accept_diagnostic(Diagnostic::invalid_pragma_location(
+            "Only loops support the loop_bound attribute",
+            location,
+        ));
+    } else if let Some(bound) = bound {
+        lexer.loop_bounds.insert(statement.get_id(), bound);
+    }
+    statement
+}
+
+/// parses `JMP label`, jumps are only accepted by dialects allowing `LanguageExtension::Jumps`
+fn parse_jump_statement(lexer: &mut ParseSession) -> AstStatement {
+    let start = lexer.range().start;
+    if !lexer.dialect.allows(LanguageExtension::Jumps) {
+        lexer.accept_diagnostic(Diagnostic::unsupported_in_dialect(
+            "JMP",
+            lexer.dialect,
+            lexer.location(),
+        ));
+    }
+    lexer.advance(); // JMP
+    expect_token!(
+        lexer,
+        Identifier,
+        AstStatement::EmptyStatement {
+            location: lexer.location(),
+            id: lexer.next_id()
+        }
+    );
+    let label = lexer.slice().to_string();
+    let end = lexer.range().end;
+    lexer.advance();
+    AstStatement::JumpStatement {
+        label,
+        location: SourceRange::new(start..end),
+        id: lexer.next_id(),
+    }
+}
+
 fn parse_return_statement(lexer: &mut ParseSession) -> AstStatement {
     let location = lexer.location();
     lexer.advance();
@@ -197,7 +264,11 @@ fn parse_case_statement(lexer: &mut ParseSession) -> AstStatement {
 
     let mut case_blocks = Vec::new();
     if lexer.token != KeywordEndCase && lexer.token != KeywordElse {
-        let body = parse_body_in_region(lexer, vec![KeywordEndCase, KeywordElse]);
+        let body = parse_any_in_region(
+            lexer,
+            vec![KeywordEndCase, KeywordElse],
+            parse_statement_list,
+        );
 
         let mut current_condition = None;
         let mut current_body = vec![];
@@ -2,6 +2,7 @@
 
 use crate::{
     ast::*,
+    dialect::LanguageExtension,
     lexer::Token::*,
     lexer::{ParseSession, Token},
     parser::parse_any_in_region,
@@ -208,7 +209,7 @@ fn to_operator(token: &Token) -> Option<Operator> {
 }
 
 // Literals, Identifiers, etc.
-fn parse_leaf_expression(lexer: &mut ParseSession) -> AstStatement {
+pub(crate) fn parse_leaf_expression(lexer: &mut ParseSession) -> AstStatement {
     //see if there's a cast
     let literal_cast = if lexer.token == TypeCastPrefix {
         let location = lexer.location();
@@ -250,6 +251,7 @@ fn parse_leaf_expression(lexer: &mut ParseSession) -> AstStatement {
             LiteralFalse => parse_bool_literal(lexer, false),
             LiteralNull => parse_null_literal(lexer),
             KeywordSquareParensOpen => parse_array_literal(lexer),
+            KeywordIf => parse_if_expression(lexer),
             _ => Err(Diagnostic::unexpected_token_found(
                 "Literal",
                 lexer.slice(),
@@ -291,6 +293,13 @@ fn parse_leaf_expression(lexer: &mut ParseSession) -> AstStatement {
                     right: Box::new(parse_range_statement(lexer)),
                     id: lexer.next_id(),
                 }
+            } else if lexer.token == KeywordReferenceAssignment {
+                lexer.advance();
+                AstStatement::ReferenceAssignment {
+                    left: Box::new(statement),
+                    right: Box::new(parse_range_statement(lexer)),
+                    id: lexer.next_id(),
+                }
             } else {
                 statement
             }
@@ -323,6 +332,65 @@ fn parse_array_literal(lexer: &mut ParseSession) -> Result<AstStatement, Diagnos
 
 #[allow(clippy::unnecessary_wraps)]
 //Allowing the unnecessary wrap here because this method is used along other methods that need to return Results
+/// parses `IF c THEN a ELSIF d THEN b ELSE e END_IF` used as an expression into the
+/// corresponding `SEL(c, SEL(d, e, b), a)` calls
+fn parse_if_expression(lexer: &mut ParseSession) -> Result<AstStatement, Diagnostic> {
+    let start = lexer.range().start;
+    if !lexer
+        .dialect
+        .allows(LanguageExtension::ConditionalExpression)
+    {
+        lexer.accept_diagnostic(Diagnostic::unsupported_in_dialect(
+            "IF expression",
+            lexer.dialect,
+            lexer.location(),
+        ));
+    }
+    let operator_location = lexer.location();
+    let expect = |lexer: &mut ParseSession, token: Token| {
+        if lexer.token == token {
+            lexer.advance();
+            Ok(())
+        } else {
+            Err(Diagnostic::unexpected_token_found(
+                format!("{:?}", token).as_str(),
+                lexer.slice(),
+                lexer.location(),
+            ))
+        }
+    };
+
+    let mut branches = vec![];
+    while lexer.token == KeywordIf || lexer.token == KeywordElseIf {
+        lexer.advance();
+        let condition = parse_range_statement(lexer);
+        expect(lexer, KeywordThen)?;
+        branches.push((condition, parse_range_statement(lexer)));
+    }
+    // an expression needs a value for every case, so the ELSE is required
+    expect(lexer, KeywordElse)?;
+    let mut result = parse_range_statement(lexer);
+    expect(lexer, KeywordEndIf)?;
+
+    let location = SourceRange::new(start..lexer.last_range.end);
+    for (condition, value) in branches.into_iter().rev() {
+        result = AstStatement::CallStatement {
+            operator: Box::new(AstStatement::Reference {
+                name: "SEL".to_string(),
+                location: operator_location.clone(),
+                id: lexer.next_id(),
+            }),
+            parameters: Box::new(Some(AstStatement::ExpressionList {
+                expressions: vec![condition, result, value],
+                id: lexer.next_id(),
+            })),
+            location: location.clone(),
+            id: lexer.next_id(),
+        };
+    }
+    Ok(result)
+}
+
 fn parse_bool_literal(lexer: &mut ParseSession, value: bool) -> Result<AstStatement, Diagnostic> {
     let location = lexer.location();
     lexer.advance();
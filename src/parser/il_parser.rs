@@ -0,0 +1,466 @@
+//! parses the Instruction List (IL) body of a POU and lowers it to statements
+//!
+//! IL computes with a current result (CR): `LD x` loads `x`, operators like `ADD y` combine the
+//! CR with their operand and `ST z` stores it. The lowering tracks the CR as an expression, so
+//! `LD a ADD b ST c` becomes `c := a + b;`. `S`/`R` and the conditional jumps, calls and returns
+//! become `IF` statements guarded by the CR, a deferred operation (`AND( b OR c )`) combines the
+//! CR with the result of the parenthesized instructions and any other operator calls the function
+//! of its name with the CR as its first argument (`LD x LIMIT 0, 10`). A CR guarding several
+//! instructions is kept in the temporary `__il_cr` unless it is a variable. Instructions are
+//! separated by line breaks, an operand has to follow its operator on the same line.
+use crate::{ast::*, lexer::Token::*, typesystem::BOOL_TYPE, Diagnostic};
+
+use super::expressions_parser::parse_leaf_expression;
+use super::ParseSession;
+
+/// the temporary keeping a CR that guards several instructions
+const CURRENT_RESULT: &str = "__il_cr";
+
+/// the lowered instruction list
+pub struct Il {
+    /// the temporaries the lowering declares in the POU
+    pub variables: Vec<VariableBlock>,
+    pub statements: Vec<AstStatement>,
+}
+
+/// the statements lowered so far and the CR following them
+#[derive(Default)]
+struct IlBody {
+    statements: Vec<AstStatement>,
+    result: Option<AstStatement>,
+    keeps_result: bool,
+}
+
+/// parses the instructions until the end of the POU's body
+pub fn parse_il(lexer: &mut ParseSession) -> Il {
+    let start = lexer.range().start;
+    let mut body = IlBody::default();
+    while !lexer.closes_open_region(&lexer.token) {
+        parse_instruction(lexer, &mut body);
+    }
+
+    let location = SourceRange::new(start..lexer.range().start);
+    let variables = if body.keeps_result {
+        vec![VariableBlock {
+            access: AccessModifier::Protected,
+            constant: false,
+            retain: false,
+            persistent: false,
+            atomic: false,
+            shared: false,
+            config_const: false,
+            init_priority: None,
+            variables: vec![Variable {
+                name: CURRENT_RESULT.into(),
+                data_type: DataTypeDeclaration::DataTypeReference {
+                    referenced_type: BOOL_TYPE.into(),
+                    location: location.clone(),
+                },
+                initializer: None,
+                address: None,
+                location: location.clone(),
+            }],
+            variable_block_type: VariableBlockType::Temp,
+            linkage: LinkageType::Internal,
+            location,
+        }]
+    } else {
+        vec![]
+    };
+    Il {
+        variables,
+        statements: body.statements,
+    }
+}
+
+/// parses a single instruction (or label) and lowers it into the given body
+fn parse_instruction(lexer: &mut ParseSession, body: &mut IlBody) {
+    let location = lexer.location();
+    let name = lexer.slice().to_string();
+    let is_operator = matches!(
+        lexer.token,
+        Identifier
+            | OperatorAnd
+            | OperatorAmp
+            | OperatorOr
+            | OperatorXor
+            | OperatorModulo
+            | OperatorNot
+    );
+    lexer.advance();
+    if !is_operator {
+        lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+            "IL operator",
+            &name,
+            location,
+        ));
+        return;
+    }
+
+    if lexer.token == KeywordColon && !is_on_new_line(lexer) {
+        //the CR of a jump target depends on the jump, it has to be loaded again
+        lexer.advance();
+        body.result = None;
+        body.statements.push(AstStatement::LabelStatement {
+            name,
+            location,
+            id: lexer.next_id(),
+        });
+        return;
+    }
+
+    let operator = name.to_uppercase();
+    match operator.as_str() {
+        "LD" | "LDN" => {
+            let operand = parse_operand(lexer, &name, &location);
+            body.result = Some(negate(lexer, operand, operator == "LDN"));
+        }
+        "ST" | "STN" => {
+            let target = parse_operand(lexer, &name, &location);
+            let result = take_result(lexer, body, &name, &location);
+            let value = negate(lexer, result, operator == "STN");
+            //the stored variable holds the CR from now on
+            body.result =
+                copy_variable(lexer, &target).map(|it| negate(lexer, it, operator == "STN"));
+            body.statements.push(AstStatement::Assignment {
+                left: Box::new(target),
+                right: Box::new(value),
+                id: lexer.next_id(),
+            });
+        }
+        "S" | "R" => {
+            let target = parse_operand(lexer, &name, &location);
+            let condition = share_result(lexer, body, &name, &location);
+            let value = AstStatement::LiteralBool {
+                value: operator == "S",
+                location: location.clone(),
+                id: lexer.next_id(),
+            };
+            let set = AstStatement::Assignment {
+                left: Box::new(target),
+                right: Box::new(value),
+                id: lexer.next_id(),
+            };
+            let statement = if_then(lexer, condition, set, &location);
+            body.statements.push(statement);
+        }
+        "JMP" | "JMPC" | "JMPCN" => {
+            let label = if has_operand(lexer) && lexer.token == Identifier {
+                lexer.slice_and_advance()
+            } else {
+                lexer.accept_diagnostic(Diagnostic::missing_il_operand(&name, location.clone()));
+                String::new()
+            };
+            let jump = AstStatement::JumpStatement {
+                label,
+                location: SourceRange::new(location.get_start()..lexer.last_range.end),
+                id: lexer.next_id(),
+            };
+            push_conditionally(lexer, body, jump, &operator, &name, &location);
+            if operator == "JMP" {
+                //the following instruction is only reached by a jump to its label
+                body.result = None;
+            }
+        }
+        "CAL" | "CALC" | "CALCN" => {
+            let call = match parse_operand(lexer, &name, &location) {
+                call @ AstStatement::CallStatement { .. } => call,
+                reference => AstStatement::CallStatement {
+                    location: reference.get_location(),
+                    operator: Box::new(reference),
+                    parameters: Box::new(None),
+                    id: lexer.next_id(),
+                },
+            };
+            push_conditionally(lexer, body, call, &operator, &name, &location);
+        }
+        "RET" | "RETC" | "RETCN" => {
+            let statement = AstStatement::ReturnStatement {
+                location: location.clone(),
+                id: lexer.next_id(),
+            };
+            push_conditionally(lexer, body, statement, &operator, &name, &location);
+            if operator == "RET" {
+                body.result = None;
+            }
+        }
+        "NOT" => {
+            let result = take_result(lexer, body, &name, &location);
+            body.result = Some(negate(lexer, result, true));
+        }
+        _ => {
+            if let Some((operator, negated)) = to_operator(&operator) {
+                let operand = if lexer.token == KeywordParensOpen {
+                    parse_deferred(lexer)
+                } else {
+                    parse_operand(lexer, &name, &location)
+                };
+                let right = negate(lexer, operand, negated);
+                let left = take_result(lexer, body, &name, &location);
+                body.result = Some(AstStatement::BinaryExpression {
+                    operator,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    id: lexer.next_id(),
+                });
+            } else {
+                //a function called with the CR as its first argument
+                let mut arguments = vec![take_result(lexer, body, &name, &location)];
+                while has_operand(lexer) {
+                    arguments.push(parse_leaf_expression(lexer));
+                    if !lexer.allow(&KeywordComma) {
+                        break;
+                    }
+                }
+                let parameters = if arguments.len() == 1 {
+                    arguments.remove(0)
+                } else {
+                    AstStatement::ExpressionList {
+                        expressions: arguments,
+                        id: lexer.next_id(),
+                    }
+                };
+                let function = create_reference(&name, &location, lexer.next_id());
+                body.result = Some(AstStatement::CallStatement {
+                    operator: Box::new(function),
+                    parameters: Box::new(Some(parameters)),
+                    location: SourceRange::new(location.get_start()..lexer.last_range.end),
+                    id: lexer.next_id(),
+                });
+            }
+        }
+    }
+}
+
+/// the binary operator of the given IL operator and whether it negates its operand (e.g. `ANDN`)
+fn to_operator(operator: &str) -> Option<(Operator, bool)> {
+    let (operator, negated) = match operator {
+        "AND" | "&" => (Operator::And, false),
+        "ANDN" => (Operator::And, true),
+        "OR" | "ORN" => (Operator::Or, operator == "ORN"),
+        "XOR" | "XORN" => (Operator::Xor, operator == "XORN"),
+        "ADD" => (Operator::Plus, false),
+        "SUB" => (Operator::Minus, false),
+        "MUL" => (Operator::Multiplication, false),
+        "DIV" => (Operator::Division, false),
+        "MOD" => (Operator::Modulo, false),
+        "GT" => (Operator::Greater, false),
+        "GE" => (Operator::GreaterOrEqual, false),
+        "EQ" => (Operator::Equal, false),
+        "NE" => (Operator::NotEqual, false),
+        "LE" => (Operator::LessOrEqual, false),
+        "LT" => (Operator::Less, false),
+        _ => return None,
+    };
+    Some((operator, negated))
+}
+
+/// parses `( [operand] instructions )`, the parenthesized instructions compute the deferred
+/// operation's operand starting with the given operand as their CR
+fn parse_deferred(lexer: &mut ParseSession) -> AstStatement {
+    let location = lexer.location();
+    lexer.advance(); // (
+    let mut nested = IlBody::default();
+    if has_operand(lexer) {
+        nested.result = Some(parse_leaf_expression(lexer));
+    }
+    while lexer.token != KeywordParensClose && !lexer.closes_open_region(&lexer.token) {
+        let instruction = lexer.location();
+        let statements = nested.statements.len();
+        parse_instruction(lexer, &mut nested);
+        if nested.statements.len() > statements {
+            lexer.accept_diagnostic(Diagnostic::invalid_il_instruction(
+                "Parenthesized instructions may only compute an operand",
+                instruction,
+            ));
+        }
+    }
+    if !lexer.allow(&KeywordParensClose) {
+        lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+            ")",
+            lexer.slice(),
+            lexer.location(),
+        ));
+    }
+
+    nested.result.unwrap_or_else(|| {
+        lexer.accept_diagnostic(Diagnostic::invalid_il_instruction(
+            "Parenthesized instructions have to load an operand",
+            location.clone(),
+        ));
+        AstStatement::EmptyStatement {
+            location,
+            id: lexer.next_id(),
+        }
+    })
+}
+
+/// pushes the given statement, guarded by the CR for the conditional operators (e.g. `JMPC`) and
+/// by the negated CR for the negated ones (e.g. `JMPCN`)
+fn push_conditionally(
+    lexer: &mut ParseSession,
+    body: &mut IlBody,
+    statement: AstStatement,
+    operator: &str,
+    name: &str,
+    location: &SourceRange,
+) {
+    let statement = if operator.ends_with('C') || operator.ends_with("CN") {
+        let condition = share_result(lexer, body, name, location);
+        let condition = negate(lexer, condition, operator.ends_with('N'));
+        if_then(lexer, condition, statement, location)
+    } else {
+        statement
+    };
+    body.statements.push(statement);
+}
+
+/// parses the operand following the operator on the same line
+fn parse_operand(lexer: &mut ParseSession, operator: &str, location: &SourceRange) -> AstStatement {
+    if has_operand(lexer) {
+        parse_leaf_expression(lexer)
+    } else {
+        lexer.accept_diagnostic(Diagnostic::missing_il_operand(operator, location.clone()));
+        AstStatement::EmptyStatement {
+            location: location.clone(),
+            id: lexer.next_id(),
+        }
+    }
+}
+
+fn has_operand(lexer: &ParseSession) -> bool {
+    !lexer.closes_open_region(&lexer.token)
+        && lexer.token != KeywordParensClose
+        && !is_on_new_line(lexer)
+}
+
+/// returns true if a line break separates the current token from the previous one
+fn is_on_new_line(lexer: &ParseSession) -> bool {
+    lexer
+        .slice_region(lexer.last_range.end..lexer.range().start)
+        .contains('\n')
+}
+
+/// takes the CR consumed by the given operator
+fn take_result(
+    lexer: &mut ParseSession,
+    body: &mut IlBody,
+    operator: &str,
+    location: &SourceRange,
+) -> AstStatement {
+    body.result.take().unwrap_or_else(|| {
+        lexer.accept_diagnostic(Diagnostic::missing_il_result(operator, location.clone()));
+        AstStatement::EmptyStatement {
+            location: location.clone(),
+            id: lexer.next_id(),
+        }
+    })
+}
+
+/// returns the CR for an operator that leaves it unchanged, a CR that is no variable is stored
+/// in `__il_cr` so it is only evaluated once
+fn share_result(
+    lexer: &mut ParseSession,
+    body: &mut IlBody,
+    operator: &str,
+    location: &SourceRange,
+) -> AstStatement {
+    let result = take_result(lexer, body, operator, location);
+    if let Some(copy) = copy_variable(lexer, &result) {
+        body.result = Some(copy);
+        return result;
+    }
+    body.keeps_result = true;
+    let current_result = create_reference(CURRENT_RESULT, location, lexer.next_id());
+    body.statements.push(AstStatement::Assignment {
+        left: Box::new(current_result),
+        right: Box::new(result),
+        id: lexer.next_id(),
+    });
+    body.result = Some(create_reference(CURRENT_RESULT, location, lexer.next_id()));
+    create_reference(CURRENT_RESULT, location, lexer.next_id())
+}
+
+/// copies the given variable (or constant) with new ids, returns `None` for any other expression
+fn copy_variable(lexer: &mut ParseSession, statement: &AstStatement) -> Option<AstStatement> {
+    let copy = match statement {
+        AstStatement::Reference { name, location, .. } => {
+            create_reference(name, location, lexer.next_id())
+        }
+        AstStatement::QualifiedReference { elements, .. } => AstStatement::QualifiedReference {
+            elements: elements
+                .iter()
+                .map(|it| copy_variable(lexer, it))
+                .collect::<Option<Vec<_>>>()?,
+            id: lexer.next_id(),
+        },
+        AstStatement::ArrayAccess {
+            reference, access, ..
+        } => AstStatement::ArrayAccess {
+            reference: Box::new(copy_variable(lexer, reference)?),
+            access: Box::new(copy_variable(lexer, access)?),
+            id: lexer.next_id(),
+        },
+        AstStatement::PointerAccess { reference, .. } => AstStatement::PointerAccess {
+            reference: Box::new(copy_variable(lexer, reference)?),
+            id: lexer.next_id(),
+        },
+        AstStatement::DirectAccess {
+            access,
+            index,
+            location,
+            ..
+        } => AstStatement::DirectAccess {
+            access: access.clone(),
+            index: Box::new(copy_variable(lexer, index)?),
+            location: location.clone(),
+            id: lexer.next_id(),
+        },
+        AstStatement::LiteralInteger {
+            value, location, ..
+        } => AstStatement::LiteralInteger {
+            value: *value,
+            location: location.clone(),
+            id: lexer.next_id(),
+        },
+        AstStatement::LiteralBool {
+            value, location, ..
+        } => AstStatement::LiteralBool {
+            value: *value,
+            location: location.clone(),
+            id: lexer.next_id(),
+        },
+        _ => return None,
+    };
+    Some(copy)
+}
+
+fn negate(lexer: &mut ParseSession, value: AstStatement, negated: bool) -> AstStatement {
+    if negated {
+        AstStatement::UnaryExpression {
+            operator: Operator::Not,
+            location: value.get_location(),
+            value: Box::new(value),
+            id: lexer.next_id(),
+        }
+    } else {
+        value
+    }
+}
+
+fn if_then(
+    lexer: &mut ParseSession,
+    condition: AstStatement,
+    statement: AstStatement,
+    location: &SourceRange,
+) -> AstStatement {
+    AstStatement::IfStatement {
+        blocks: vec![ConditionalBlock {
+            condition: Box::new(condition),
+            body: vec![statement],
+        }],
+        else_block: vec![],
+        location: location.clone(),
+        id: lexer.next_id(),
+    }
+}
@@ -0,0 +1,581 @@
+//! parses the textual sequential function chart (SFC) of a program's or function block's body and
+//! lowers it to a state machine executed by the POU's implementation
+//!
+//! every step becomes a variable named after the step (`fill.X` is true while the step is
+//! active, `fill.T` is the time passed since it was activated), the POU's body activates the
+//! entered steps, executes the actions associated with the active steps and fires the enabled
+//! transitions. The transitions fire in the order of their declaration, a step left by a
+//! transition cannot be left by a later one in the same cycle and a step entered in a cycle is
+//! left at the earliest in the following cycle.
+use crate::{
+    ast::*,
+    expect_token,
+    lexer::Token::*,
+    parser::{parse_any_in_region, parse_identifier, parse_implementation},
+    typesystem::{BOOL_TYPE, TIME_TYPE},
+    Diagnostic,
+};
+
+use super::parse_expression;
+use super::ParseSession;
+use std::collections::HashMap;
+
+/// the runtime's monotonic clock in nanoseconds, the time base of the steps' elapsed times
+pub const CLOCK_FUNCTION: &str = "__rusty_time";
+/// the temporary variable holding the time of the current cycle
+const NOW: &str = "__sfc_now";
+/// true if the step was activated since the last cycle
+const ENTERED: &str = "__entered";
+/// the time the step was activated at
+const START: &str = "__start";
+/// true if the step was active in the last cycle, used to detect the step's activation and
+/// deactivation
+const WAS_ACTIVE: &str = "__was_active";
+
+/// the qualifier deciding when an action associated with a step is executed
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Qualifier {
+    /// executed in every cycle the step is active
+    N,
+    /// executed once, in the first cycle the step is active
+    P1,
+    /// executed once, in the first cycle after the step was deactivated
+    P0,
+}
+
+impl Qualifier {
+    fn from_name(name: &str) -> Option<Qualifier> {
+        match name.to_uppercase().as_str() {
+            "N" => Some(Qualifier::N),
+            "P1" => Some(Qualifier::P1),
+            "P0" => Some(Qualifier::P0),
+            _ => None,
+        }
+    }
+}
+
+/// `name(qualifier);` executes the action `name` according to the qualifier
+struct ActionAssociation {
+    name: String,
+    qualifier: Qualifier,
+    location: SourceRange,
+}
+
+struct Step {
+    name: String,
+    location: SourceRange,
+    initial: bool,
+    actions: Vec<ActionAssociation>,
+}
+
+/// `TRANSITION FROM sources TO targets := condition; END_TRANSITION`, the transitions of state
+/// machines share the syntax
+pub(super) struct Transition {
+    pub sources: Vec<(String, SourceRange)>,
+    pub targets: Vec<(String, SourceRange)>,
+    pub condition: AstStatement,
+    pub location: SourceRange,
+}
+
+/// the lowered chart, the step variables are added to the POU's variables
+pub struct Sfc {
+    pub variables: Vec<VariableBlock>,
+    pub implementation: Implementation,
+    pub actions: Vec<Implementation>,
+}
+
+/// returns true if the current token starts a sequential function chart
+pub fn starts_sfc(lexer: &ParseSession) -> bool {
+    matches!(lexer.token, KeywordInitialStep | KeywordTransition) || lexer.is_step()
+}
+
+/// parses the steps, transitions and actions of the given POU's body until the end of the POU
+pub fn parse_sfc(
+    lexer: &mut ParseSession,
+    pou_type: &PouType,
+    pou_name: &str,
+    linkage: LinkageType,
+) -> Sfc {
+    let start = lexer.location();
+    let mut steps = vec![];
+    let mut transitions = vec![];
+    let mut actions = vec![];
+    while !lexer.closes_open_region(&lexer.token) {
+        match lexer.token {
+            KeywordInitialStep => steps.extend(parse_step(lexer, true)),
+            Identifier if lexer.is_step() => steps.extend(parse_step(lexer, false)),
+            KeywordTransition => transitions.extend(parse_transition(lexer)),
+            KeywordAction => actions.extend(parse_sfc_action(lexer, pou_name, linkage)),
+            _ => {
+                lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+                    "STEP, TRANSITION or ACTION",
+                    lexer.slice(),
+                    lexer.location(),
+                ));
+                lexer.advance();
+            }
+        }
+    }
+    let location = SourceRange::new(start.get_start()..lexer.last_range.end);
+
+    if !matches!(pou_type, PouType::Program | PouType::FunctionBlock) {
+        lexer.accept_diagnostic(Diagnostic::sfc_without_instance(pou_name, location.clone()));
+    }
+    validate_network(lexer, pou_name, &steps, &transitions, &location);
+
+    let statements = lower_network(lexer, &steps, &transitions);
+    Sfc {
+        variables: declare_steps(lexer, &steps, &location),
+        implementation: Implementation {
+            name: pou_name.into(),
+            type_name: pou_name.into(),
+            linkage,
+            pou_type: pou_type.clone(),
+            statements,
+            location,
+            overriding: false,
+            generic: false,
+            access: None,
+            loop_bounds: HashMap::new(),
+        },
+        actions,
+    }
+}
+
+/// parses `[INITIAL_]STEP name : action(qualifier); ... END_STEP`
+fn parse_step(lexer: &mut ParseSession, initial: bool) -> Option<Step> {
+    lexer.advance(); //Consume STEP or INITIAL_STEP
+    parse_any_in_region(lexer, vec![KeywordEndStep], |lexer| {
+        let (name, location) = parse_identifier(lexer)?;
+        lexer.consume_or_report(KeywordColon);
+        let mut actions = vec![];
+        while lexer.token == Identifier {
+            actions.push(parse_action_association(lexer));
+        }
+        Some(Step {
+            name,
+            location,
+            initial,
+            actions,
+        })
+    })
+}
+
+/// parses `name;` or `name(qualifier);`, actions without qualifier are executed in every cycle
+fn parse_action_association(lexer: &mut ParseSession) -> ActionAssociation {
+    let location = lexer.location();
+    let name = lexer.slice_and_advance();
+    let mut qualifier = Qualifier::N;
+    if lexer.allow(&KeywordParensOpen) {
+        let text = lexer.slice().to_string();
+        match Qualifier::from_name(&text) {
+            Some(it) => qualifier = it,
+            None => lexer.accept_diagnostic(Diagnostic::unsupported_action_qualifier(
+                &text,
+                lexer.location(),
+            )),
+        }
+        lexer.advance();
+        lexer.consume_or_report(KeywordParensClose);
+    }
+    lexer.consume_or_report(KeywordSemicolon);
+    ActionAssociation {
+        name,
+        qualifier,
+        location,
+    }
+}
+
+/// parses `TRANSITION [name] FROM sources TO targets := condition; END_TRANSITION`, sources and
+/// targets are a step or a parenthesized list of steps
+pub(super) fn parse_transition(lexer: &mut ParseSession) -> Option<Transition> {
+    let start = lexer.range().start;
+    lexer.advance(); //Consume TRANSITION
+    parse_any_in_region(lexer, vec![KeywordEndTransition], |lexer| {
+        if lexer.token == Identifier && !lexer.slice().eq_ignore_ascii_case("FROM") {
+            //the transition's name is only documentation
+            lexer.advance();
+        }
+        if !lexer.slice().eq_ignore_ascii_case("FROM") {
+            lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+                "FROM",
+                lexer.slice(),
+                lexer.location(),
+            ));
+            return None;
+        }
+        lexer.advance();
+        let sources = parse_step_names(lexer)?;
+        expect_token!(lexer, KeywordTo, None);
+        lexer.advance();
+        let targets = parse_step_names(lexer)?;
+        expect_token!(lexer, KeywordAssignment, None);
+        lexer.advance();
+        let condition = parse_expression(lexer);
+        let location = SourceRange::new(start..lexer.range().end);
+        lexer.consume_or_report(KeywordSemicolon);
+        Some(Transition {
+            sources,
+            targets,
+            condition,
+            location,
+        })
+    })
+}
+
+/// parses `step` or `(step1, step2, ...)`
+fn parse_step_names(lexer: &mut ParseSession) -> Option<Vec<(String, SourceRange)>> {
+    if !lexer.allow(&KeywordParensOpen) {
+        return parse_identifier(lexer).map(|it| vec![it]);
+    }
+    let mut names = vec![parse_identifier(lexer)?];
+    while lexer.allow(&KeywordComma) {
+        names.push(parse_identifier(lexer)?);
+    }
+    lexer.consume_or_report(KeywordParensClose);
+    Some(names)
+}
+
+/// parses `ACTION name : ... END_ACTION`, the action belongs to the chart's POU
+fn parse_sfc_action(
+    lexer: &mut ParseSession,
+    pou_name: &str,
+    linkage: LinkageType,
+) -> Option<Implementation> {
+    lexer.advance(); //Consume ACTION
+    parse_any_in_region(lexer, vec![KeywordEndAction], |lexer| {
+        let (name, _) = parse_identifier(lexer)?;
+        lexer.allow(&KeywordColon);
+        Some(parse_implementation(
+            lexer,
+            linkage,
+            PouType::Action,
+            &format!("{}.{}", pou_name, name),
+            pou_name,
+            false,
+        ))
+    })
+}
+
+/// reports duplicate steps, transitions between unknown steps and charts without exactly one
+/// initial step
+fn validate_network(
+    lexer: &mut ParseSession,
+    pou_name: &str,
+    steps: &[Step],
+    transitions: &[Transition],
+    location: &SourceRange,
+) {
+    for (i, step) in steps.iter().enumerate() {
+        if steps[..i]
+            .iter()
+            .any(|it| it.name.eq_ignore_ascii_case(&step.name))
+        {
+            lexer.accept_diagnostic(Diagnostic::duplicate_step(
+                &step.name,
+                step.location.clone(),
+            ));
+        }
+    }
+    let initial_steps = steps.iter().filter(|it| it.initial).count();
+    if initial_steps != 1 {
+        lexer.accept_diagnostic(Diagnostic::invalid_initial_steps(
+            pou_name,
+            initial_steps,
+            location.clone(),
+        ));
+    }
+    for (name, location) in transitions
+        .iter()
+        .flat_map(|it| it.sources.iter().chain(it.targets.iter()))
+    {
+        if !steps.iter().any(|it| it.name.eq_ignore_ascii_case(name)) {
+            lexer.accept_diagnostic(Diagnostic::unknown_step(name, location.clone()));
+        }
+    }
+}
+
+/// declares a variable per step and the temporary variable holding the cycle's time, the initial
+/// step is entered in the first cycle
+fn declare_steps(
+    lexer: &mut ParseSession,
+    steps: &[Step],
+    location: &SourceRange,
+) -> Vec<VariableBlock> {
+    let members = [
+        ("X", BOOL_TYPE),
+        ("T", TIME_TYPE),
+        (ENTERED, BOOL_TYPE),
+        (START, TIME_TYPE),
+        (WAS_ACTIVE, BOOL_TYPE),
+    ];
+    let mut variables = vec![];
+    for step in steps {
+        let location = &step.location;
+        let initializer = if step.initial {
+            let mut expressions = vec![];
+            for name in ["X", ENTERED] {
+                let flag = reference(lexer, name, location);
+                let value = literal(lexer, true, location);
+                expressions.push(assign(flag, value, lexer.next_id()));
+            }
+            Some(AstStatement::ExpressionList {
+                expressions,
+                id: lexer.next_id(),
+            })
+        } else {
+            None
+        };
+        variables.push(Variable {
+            name: step.name.clone(),
+            data_type: DataTypeDeclaration::DataTypeDefinition {
+                data_type: DataType::StructType {
+                    name: None,
+                    variables: members
+                        .iter()
+                        .map(|(name, type_name)| declare(name, type_name, location))
+                        .collect(),
+                },
+                location: location.clone(),
+                scope: lexer.scope.clone(),
+            },
+            initializer,
+            address: None,
+            location: location.clone(),
+        });
+    }
+    vec![
+        block(variables, VariableBlockType::Local, location),
+        block(
+            vec![declare(NOW, TIME_TYPE, location)],
+            VariableBlockType::Temp,
+            location,
+        ),
+    ]
+}
+
+pub(super) fn declare(name: &str, type_name: &str, location: &SourceRange) -> Variable {
+    Variable {
+        name: name.into(),
+        data_type: DataTypeDeclaration::DataTypeReference {
+            referenced_type: type_name.into(),
+            location: location.clone(),
+        },
+        initializer: None,
+        address: None,
+        location: location.clone(),
+    }
+}
+
+pub(super) fn block(
+    variables: Vec<Variable>,
+    variable_block_type: VariableBlockType,
+    location: &SourceRange,
+) -> VariableBlock {
+    VariableBlock {
+        access: AccessModifier::Protected,
+        constant: false,
+        retain: false,
+        persistent: false,
+        atomic: false,
+        shared: false,
+        config_const: false,
+        init_priority: None,
+        variables,
+        variable_block_type,
+        linkage: LinkageType::Internal,
+        location: location.clone(),
+    }
+}
+
+/// lowers the chart to the statements of the POU's body:
+/// ```text
+/// __sfc_now := __rusty_time();
+/// IF step.__entered THEN step.__entered := FALSE; step.__start := __sfc_now; END_IF
+/// IF step.X THEN step.T := __sfc_now - step.__start; END_IF
+/// IF step.X THEN action(); END_IF                                  (N)
+/// IF step.X AND NOT step.__was_active THEN action(); END_IF        (P1)
+/// IF NOT step.X AND step.__was_active THEN action(); END_IF        (P0)
+/// step.__was_active := step.X;
+/// IF source.X AND NOT source.__entered AND (condition) THEN
+///     source.X := FALSE; target.X := TRUE; target.__entered := TRUE;
+/// END_IF
+/// ```
+fn lower_network(
+    lexer: &mut ParseSession,
+    steps: &[Step],
+    transitions: &[Transition],
+) -> Vec<AstStatement> {
+    let mut statements = vec![];
+    let location = steps
+        .first()
+        .map(|it| it.location.clone())
+        .unwrap_or_else(SourceRange::undefined);
+    let now = reference(lexer, NOW, &location);
+    let clock = call(lexer, CLOCK_FUNCTION, &location);
+    statements.push(assign(now, clock, lexer.next_id()));
+
+    for step in steps {
+        let location = &step.location;
+        let entered = member(lexer, &step.name, ENTERED, location);
+        let value = literal(lexer, false, location);
+        let leave = assign(entered, value, lexer.next_id());
+        let start = member(lexer, &step.name, START, location);
+        let now = reference(lexer, NOW, location);
+        let start = assign(start, now, lexer.next_id());
+        let condition = member(lexer, &step.name, ENTERED, location);
+        statements.push(if_then(
+            condition,
+            vec![leave, start],
+            location,
+            lexer.next_id(),
+        ));
+
+        let now = reference(lexer, NOW, location);
+        let start = member(lexer, &step.name, START, location);
+        let elapsed = create_binary_expression(now, Operator::Minus, start, lexer.next_id());
+        let time = member(lexer, &step.name, "T", location);
+        let update = assign(time, elapsed, lexer.next_id());
+        let condition = member(lexer, &step.name, "X", location);
+        statements.push(if_then(condition, vec![update], location, lexer.next_id()));
+    }
+
+    for step in steps {
+        for action in &step.actions {
+            let location = &action.location;
+            let active = member(lexer, &step.name, "X", location);
+            let was_active = member(lexer, &step.name, WAS_ACTIVE, location);
+            let condition = match action.qualifier {
+                Qualifier::N => active,
+                Qualifier::P1 => {
+                    let activated = not(was_active, location, lexer.next_id());
+                    create_binary_expression(active, Operator::And, activated, lexer.next_id())
+                }
+                Qualifier::P0 => {
+                    let inactive = not(active, location, lexer.next_id());
+                    create_binary_expression(inactive, Operator::And, was_active, lexer.next_id())
+                }
+            };
+            let execute = call(lexer, &action.name, location);
+            statements.push(if_then(condition, vec![execute], location, lexer.next_id()));
+        }
+    }
+    for step in steps {
+        let location = &step.location;
+        let was_active = member(lexer, &step.name, WAS_ACTIVE, location);
+        let active = member(lexer, &step.name, "X", location);
+        statements.push(assign(was_active, active, lexer.next_id()));
+    }
+
+    for transition in transitions {
+        let mut condition = transition.condition.clone();
+        for (source, location) in transition.sources.iter().rev() {
+            let entered = member(lexer, source, ENTERED, location);
+            let not_entered = not(entered, location, lexer.next_id());
+            condition =
+                create_binary_expression(not_entered, Operator::And, condition, lexer.next_id());
+            let active = member(lexer, source, "X", location);
+            condition = create_binary_expression(active, Operator::And, condition, lexer.next_id());
+        }
+        let mut body = vec![];
+        for (source, location) in &transition.sources {
+            let active = member(lexer, source, "X", location);
+            let value = literal(lexer, false, location);
+            body.push(assign(active, value, lexer.next_id()));
+        }
+        for (target, location) in &transition.targets {
+            for name in ["X", ENTERED] {
+                let flag = member(lexer, target, name, location);
+                let value = literal(lexer, true, location);
+                body.push(assign(flag, value, lexer.next_id()));
+            }
+        }
+        statements.push(if_then(
+            condition,
+            body,
+            &transition.location,
+            lexer.next_id(),
+        ));
+    }
+    statements
+}
+
+/// `name()`
+pub(super) fn call(lexer: &mut ParseSession, name: &str, location: &SourceRange) -> AstStatement {
+    AstStatement::CallStatement {
+        operator: Box::new(reference(lexer, name, location)),
+        parameters: Box::new(None),
+        location: location.clone(),
+        id: lexer.next_id(),
+    }
+}
+
+pub(super) fn reference(
+    lexer: &mut ParseSession,
+    name: &str,
+    location: &SourceRange,
+) -> AstStatement {
+    create_reference(name, location, lexer.next_id())
+}
+
+/// `step.member`
+fn member(
+    lexer: &mut ParseSession,
+    step: &str,
+    member: &str,
+    location: &SourceRange,
+) -> AstStatement {
+    AstStatement::QualifiedReference {
+        elements: vec![
+            reference(lexer, step, location),
+            reference(lexer, member, location),
+        ],
+        id: lexer.next_id(),
+    }
+}
+
+pub(super) fn literal(
+    lexer: &mut ParseSession,
+    value: bool,
+    location: &SourceRange,
+) -> AstStatement {
+    AstStatement::LiteralBool {
+        value,
+        location: location.clone(),
+        id: lexer.next_id(),
+    }
+}
+
+pub(super) fn assign(left: AstStatement, right: AstStatement, id: AstId) -> AstStatement {
+    AstStatement::Assignment {
+        left: Box::new(left),
+        right: Box::new(right),
+        id,
+    }
+}
+
+pub(super) fn not(value: AstStatement, location: &SourceRange, id: AstId) -> AstStatement {
+    AstStatement::UnaryExpression {
+        operator: Operator::Not,
+        value: Box::new(value),
+        location: location.clone(),
+        id,
+    }
+}
+
+pub(super) fn if_then(
+    condition: AstStatement,
+    body: Vec<AstStatement>,
+    location: &SourceRange,
+    id: AstId,
+) -> AstStatement {
+    AstStatement::IfStatement {
+        blocks: vec![ConditionalBlock {
+            condition: Box::new(condition),
+            body,
+        }],
+        else_block: vec![],
+        location: location.clone(),
+        id,
+    }
+}
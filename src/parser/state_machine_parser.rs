@@ -0,0 +1,391 @@
+//! parses the states and transitions of a program's or function block's body declared as a state
+//! machine and lowers them to a `CASE` over the current state
+//!
+//! the states become the elements of an enum generated for the POU's `__state` variable, the
+//! initial state is its first element, so a new instance starts in it. A state's `ENTRY:` and
+//! `EXIT:` sections are lowered to actions of the POU, its `DURING:` section (or the statements
+//! before the first section) is executed in every cycle the state is active. The transitions
+//! leaving a state are evaluated after its `DURING:` section in the order of their declaration,
+//! the first enabled one executes the state's exit action and changes the state. The entry action
+//! of the new state is executed at the beginning of the following cycle, so the state changes at
+//! most once per cycle.
+use crate::{
+    ast::*,
+    dialect::LanguageExtension,
+    lexer::Token::*,
+    parser::{into_label, parse_any_in_region, parse_control, parse_identifier},
+    typesystem::{BOOL_TYPE, DINT_TYPE},
+    Diagnostic,
+};
+
+use super::{
+    sfc_parser::{self, assign, block, call, declare, literal, not, reference, Transition},
+    ParseSession,
+};
+use std::collections::HashMap;
+
+/// the variable holding the current state
+const STATE: &str = "__state";
+/// true once the entry action of the current state was executed
+const ENTERED: &str = "__entered";
+
+struct State {
+    name: String,
+    location: SourceRange,
+    initial: bool,
+    entry: Section,
+    during: Section,
+    exit: Section,
+}
+
+/// the statements of one of a state's sections and the bounds of their loops
+#[derive(Default)]
+struct Section {
+    statements: Vec<AstStatement>,
+    loop_bounds: HashMap<AstId, u32>,
+}
+
+/// the lowered state machine, the state variables are added to the POU's variables
+pub struct StateMachine {
+    pub variables: Vec<VariableBlock>,
+    pub implementation: Implementation,
+    pub actions: Vec<Implementation>,
+}
+
+/// returns true if the current token starts a state machine
+pub fn starts_state_machine(lexer: &ParseSession) -> bool {
+    lexer.token == KeywordInitialState || lexer.is_state()
+}
+
+/// returns the name of the enum generated for the states of the given POU, the name the
+/// preprocessor gives the inline type of the `__state` variable
+fn get_state_type_name(pou_name: &str) -> String {
+    format!("__{}_{}", pou_name, STATE)
+}
+
+/// parses the states and transitions of the given POU's body until the end of the POU
+pub fn parse_state_machine(
+    lexer: &mut ParseSession,
+    pou_type: &PouType,
+    pou_name: &str,
+    linkage: LinkageType,
+) -> StateMachine {
+    let start = lexer.location();
+    if !lexer.dialect.allows(LanguageExtension::StateMachines) {
+        lexer.accept_diagnostic(Diagnostic::unsupported_in_dialect(
+            "STATE",
+            lexer.dialect,
+            start.clone(),
+        ));
+    }
+    let mut states = vec![];
+    let mut transitions = vec![];
+    while !lexer.closes_open_region(&lexer.token) {
+        match lexer.token {
+            KeywordInitialState => states.extend(parse_state(lexer, true)),
+            Identifier if lexer.is_state() => states.extend(parse_state(lexer, false)),
+            KeywordTransition => transitions.extend(sfc_parser::parse_transition(lexer)),
+            _ => {
+                lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+                    "STATE or TRANSITION",
+                    lexer.slice(),
+                    lexer.location(),
+                ));
+                lexer.advance();
+            }
+        }
+    }
+    let location = SourceRange::new(start.get_start()..lexer.last_range.end);
+
+    if !matches!(pou_type, PouType::Program | PouType::FunctionBlock) {
+        lexer.accept_diagnostic(Diagnostic::state_machine_without_instance(
+            pou_name,
+            location.clone(),
+        ));
+    }
+    validate_states(lexer, pou_name, &states, &transitions, &location);
+    //the initial state is the enum's first element and therefore the default of `__state`
+    states.sort_by_key(|it| !it.initial);
+
+    let mut actions = vec![];
+    let mut loop_bounds = HashMap::new();
+    for state in &mut states {
+        loop_bounds.extend(state.during.loop_bounds.drain());
+        for (kind, section) in [("entry", &mut state.entry), ("exit", &mut state.exit)] {
+            if section.statements.is_empty() {
+                continue;
+            }
+            actions.push(Implementation {
+                name: format!("{}.{}", pou_name, get_action_name(kind, &state.name)),
+                type_name: pou_name.into(),
+                linkage,
+                pou_type: PouType::Action,
+                statements: std::mem::take(&mut section.statements),
+                location: state.location.clone(),
+                overriding: false,
+                generic: false,
+                access: None,
+                loop_bounds: std::mem::take(&mut section.loop_bounds),
+            });
+        }
+    }
+
+    let statements = vec![lower_states(
+        lexer,
+        pou_name,
+        &mut states,
+        &transitions,
+        &actions,
+    )];
+    StateMachine {
+        variables: vec![declare_state(lexer, &states, &location)],
+        implementation: Implementation {
+            name: pou_name.into(),
+            type_name: pou_name.into(),
+            linkage,
+            pou_type: pou_type.clone(),
+            statements,
+            location,
+            overriding: false,
+            generic: false,
+            access: None,
+            loop_bounds,
+        },
+        actions,
+    }
+}
+
+/// parses `[INITIAL_]STATE name ENTRY: ... DURING: ... EXIT: ... END_STATE`, the sections are
+/// optional and the statements before the first section belong to `DURING:`
+fn parse_state(lexer: &mut ParseSession, initial: bool) -> Option<State> {
+    lexer.advance(); //Consume STATE or INITIAL_STATE
+    parse_any_in_region(lexer, vec![KeywordEndState], |lexer| {
+        let (name, location) = parse_identifier(lexer)?;
+        lexer.allow(&KeywordColon);
+        let mut sections: [Section; 3] = Default::default();
+        let mut current = 1;
+        loop {
+            if lexer.is_state_section() {
+                current = match lexer.slice().to_uppercase().as_str() {
+                    "ENTRY" => 0,
+                    "DURING" => 1,
+                    _ => 2,
+                };
+                lexer.advance();
+                lexer.advance(); //Consume the colon
+            } else if lexer.closes_open_region(&lexer.token) {
+                break;
+            } else {
+                let statement = parse_control(lexer);
+                let section = &mut sections[current];
+                section.statements.push(into_label(lexer, statement));
+                section.loop_bounds.extend(lexer.loop_bounds.drain());
+            }
+        }
+        let [entry, during, exit] = sections;
+        Some(State {
+            name,
+            location,
+            initial,
+            entry,
+            during,
+            exit,
+        })
+    })
+}
+
+/// reports duplicate states, transitions between unknown states, transitions into several states
+/// and state machines without exactly one initial state
+fn validate_states(
+    lexer: &mut ParseSession,
+    pou_name: &str,
+    states: &[State],
+    transitions: &[Transition],
+    location: &SourceRange,
+) {
+    for (i, state) in states.iter().enumerate() {
+        if states[..i]
+            .iter()
+            .any(|it| it.name.eq_ignore_ascii_case(&state.name))
+        {
+            lexer.accept_diagnostic(Diagnostic::duplicate_state(
+                &state.name,
+                state.location.clone(),
+            ));
+        }
+    }
+    let initial_states = states.iter().filter(|it| it.initial).count();
+    if initial_states != 1 {
+        lexer.accept_diagnostic(Diagnostic::invalid_initial_states(
+            pou_name,
+            initial_states,
+            location.clone(),
+        ));
+    }
+    for transition in transitions {
+        if transition.targets.len() != 1 {
+            lexer.accept_diagnostic(Diagnostic::ambiguous_state_transition(
+                transition.location.clone(),
+            ));
+        }
+        for (name, location) in transition.sources.iter().chain(transition.targets.iter()) {
+            if !states.iter().any(|it| it.name.eq_ignore_ascii_case(name)) {
+                lexer.accept_diagnostic(Diagnostic::unknown_state(name, location.clone()));
+            }
+        }
+    }
+}
+
+/// returns the name of the action generated for the given section of a state (e.g.
+/// `__entry_idle`)
+fn get_action_name(kind: &str, state: &str) -> String {
+    format!("__{}_{}", kind, state)
+}
+
+/// declares the state variable as an enum of the states and the flag of the executed entry action
+fn declare_state(
+    lexer: &mut ParseSession,
+    states: &[State],
+    location: &SourceRange,
+) -> VariableBlock {
+    let expressions = states
+        .iter()
+        .map(|it| reference(lexer, &it.name, &it.location))
+        .collect();
+    let elements = AstStatement::ExpressionList {
+        expressions,
+        id: lexer.next_id(),
+    };
+    let state = Variable {
+        name: STATE.into(),
+        data_type: DataTypeDeclaration::DataTypeDefinition {
+            data_type: DataType::EnumType {
+                name: None,
+                numeric_type: DINT_TYPE.into(),
+                elements,
+            },
+            location: location.clone(),
+            scope: lexer.scope.clone(),
+        },
+        initializer: None,
+        address: None,
+        location: location.clone(),
+    };
+    block(
+        vec![state, declare(ENTERED, BOOL_TYPE, location)],
+        VariableBlockType::Local,
+        location,
+    )
+}
+
+/// lowers the states to the statements of the POU's body:
+/// ```text
+/// CASE __state OF
+/// __pou___state#idle:
+///     IF NOT __entered THEN __entered := TRUE; __entry_idle(); END_IF
+///     (* DURING: statements *)
+///     IF condition THEN
+///         __exit_idle(); __state := __pou___state#target; __entered := FALSE;
+///     ELSIF ...
+///     END_IF
+/// ...
+/// END_CASE
+/// ```
+fn lower_states(
+    lexer: &mut ParseSession,
+    pou_name: &str,
+    states: &mut [State],
+    transitions: &[Transition],
+    actions: &[Implementation],
+) -> AstStatement {
+    let type_name = get_state_type_name(pou_name);
+    let has_action = |kind: &str, state: &str| {
+        let name = format!("{}.{}", pou_name, get_action_name(kind, state));
+        actions.iter().any(|it| it.name == name)
+    };
+    let mut case_blocks = vec![];
+    for state in states.iter_mut() {
+        let location = &state.location;
+        let mut body = vec![];
+        if has_action("entry", &state.name) {
+            let entered = reference(lexer, ENTERED, location);
+            let value = literal(lexer, true, location);
+            let enter = assign(entered, value, lexer.next_id());
+            let entry = call(lexer, &get_action_name("entry", &state.name), location);
+            let entered = reference(lexer, ENTERED, location);
+            let condition = not(entered, location, lexer.next_id());
+            body.push(sfc_parser::if_then(
+                condition,
+                vec![enter, entry],
+                location,
+                lexer.next_id(),
+            ));
+        }
+        body.append(&mut state.during.statements);
+
+        let mut blocks = vec![];
+        let leaving = transitions.iter().filter(|transition| {
+            transition
+                .sources
+                .iter()
+                .any(|(it, _)| it.eq_ignore_ascii_case(&state.name))
+        });
+        for transition in leaving {
+            let (target, target_location) = match transition.targets.as_slice() {
+                [target] => target,
+                _ => continue,
+            };
+            let mut change = vec![];
+            if has_action("exit", &state.name) {
+                change.push(call(lexer, &get_action_name("exit", &state.name), location));
+            }
+            let current = reference(lexer, STATE, target_location);
+            let target = state_literal(lexer, &type_name, target, target_location);
+            change.push(assign(current, target, lexer.next_id()));
+            let entered = reference(lexer, ENTERED, target_location);
+            let value = literal(lexer, false, target_location);
+            change.push(assign(entered, value, lexer.next_id()));
+            blocks.push(ConditionalBlock {
+                condition: Box::new(transition.condition.clone()),
+                body: change,
+            });
+        }
+        if !blocks.is_empty() {
+            body.push(AstStatement::IfStatement {
+                blocks,
+                else_block: vec![],
+                location: location.clone(),
+                id: lexer.next_id(),
+            });
+        }
+
+        case_blocks.push(ConditionalBlock {
+            condition: Box::new(state_literal(lexer, &type_name, &state.name, location)),
+            body,
+        });
+    }
+
+    let location = states
+        .first()
+        .map(|it| it.location.clone())
+        .unwrap_or_else(SourceRange::undefined);
+    AstStatement::CaseStatement {
+        selector: Box::new(reference(lexer, STATE, &location)),
+        case_blocks,
+        else_block: vec![],
+        location,
+        id: lexer.next_id(),
+    }
+}
+
+/// `__pou___state#name`, the state's element qualified with the enum so it cannot be shadowed by
+/// a variable of the same name
+fn state_literal(
+    lexer: &mut ParseSession,
+    type_name: &str,
+    state: &str,
+    location: &SourceRange,
+) -> AstStatement {
+    let element = reference(lexer, state, location);
+    create_cast_statement(type_name, element, location, lexer.next_id())
+}
@@ -4,13 +4,18 @@ use crate::ast::{AstStatement, SourceRange};
 mod class_parser_tests;
 mod container_parser_tests;
 mod control_parser_tests;
+mod dialect_parser_tests;
 mod expressions_parser_tests;
 mod function_parser_tests;
+mod il_parser_tests;
 mod initializer_parser_tests;
 mod misc_parser_tests;
+mod namespace_parser_tests;
 mod parse_errors;
 mod parse_generics;
 mod program_parser_tests;
+mod sfc_parser_tests;
+mod state_machine_parser_tests;
 mod statement_parser_tests;
 mod type_parser_tests;
 mod variable_parser_tests;
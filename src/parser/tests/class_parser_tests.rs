@@ -1,4 +1,4 @@
-use crate::{ast::*, test_utils::tests::parse};
+use crate::{ast::*, test_utils::tests::parse, Diagnostic};
 
 #[test]
 fn simple_class_with_defaults_can_be_parsed() {
@@ -39,6 +39,18 @@ fn simple_class2_can_be_parsed() {
     assert_eq!(unit.implementations.len(), 0);
 }
 
+#[test]
+fn class_extending_a_super_class_can_be_parsed() {
+    let src = "CLASS FINAL Motor EXTENDS Drive END_CLASS CLASS Drive END_CLASS";
+    let unit = parse(src).0;
+
+    let class = &unit.units[0];
+    assert_eq!(class.name, "Motor");
+    assert_eq!(class.poly_mode, Some(PolymorphismMode::Final));
+    assert_eq!(class.super_class, Some("Drive".to_string()));
+    assert_eq!(unit.units[1].super_class, None);
+}
+
 #[test]
 fn method_with_defaults_can_be_parsed() {
     let src = "CLASS MyClass METHOD testMethod END_METHOD END_CLASS";
@@ -323,3 +335,90 @@ fn fb_method_with_return_type_can_be_parsed() {
     assert_ne!(method_pou.return_type, None);
     assert_eq!(method.overriding, true);
 }
+
+#[test]
+fn interface_with_method_prototypes_can_be_parsed() {
+    let src = "
+        INTERFACE Drive
+            METHOD start : BOOL
+            VAR_INPUT speed : INT; END_VAR
+            END_METHOD
+            METHOD stop END_METHOD
+        END_INTERFACE";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    let interface = &unit.units[0];
+    assert_eq!(interface.pou_type, PouType::Interface);
+    assert_eq!(interface.name, "Drive");
+    assert_eq!(interface.variable_blocks.len(), 0);
+
+    let start = &unit.units[1];
+    assert_eq!(start.name, "Drive.start");
+    assert_eq!(
+        start.pou_type,
+        PouType::Method {
+            owner_class: "Drive".into()
+        }
+    );
+    assert_ne!(start.return_type, None);
+    assert_eq!(start.variable_blocks[0].variables[0].name, "speed");
+    assert_eq!(unit.units[2].name, "Drive.stop");
+    //the prototypes have no implementations
+    assert_eq!(unit.implementations.len(), 0);
+}
+
+#[test]
+fn classes_and_function_blocks_implementing_interfaces_can_be_parsed() {
+    let src = "
+        CLASS Motor EXTENDS Drive IMPLEMENTS Startable, Stoppable END_CLASS
+        FUNCTION_BLOCK Pump IMPLEMENTS Startable END_FUNCTION_BLOCK";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(unit.units[0].super_class, Some("Drive".to_string()));
+    assert_eq!(
+        unit.units[0].interfaces,
+        vec!["Startable".to_string(), "Stoppable".to_string()]
+    );
+    assert_eq!(unit.units[1].interfaces, vec!["Startable".to_string()]);
+}
+
+#[test]
+fn interface_method_with_a_body_is_reported() {
+    let src = "
+        INTERFACE Drive
+            METHOD start
+                start := 1;
+            END_METHOD
+        END_INTERFACE";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(unit.implementations.len(), 0);
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::interface_method_with_body(
+            "Drive.start",
+            (66..76).into()
+        )]
+    );
+}
+
+#[test]
+fn abstract_method_with_a_body_is_reported() {
+    let src = "
+        CLASS ABSTRACT Shape
+            METHOD ABSTRACT area : DINT
+                area := 0;
+            END_METHOD
+        END_CLASS";
+    let (_, diagnostics) = parse(src);
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::abstract_method_with_body(
+            "Shape.area",
+            (86..95).into()
+        )]
+    );
+}
@@ -1,7 +1,9 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
-use crate::ast::AstStatement;
+use crate::ast::{AstStatement, SourceRange};
 use crate::test_utils::tests::parse;
+use crate::Diagnostic;
 use pretty_assertions::*;
+use std::collections::HashMap;
 
 #[test]
 fn if_statement() {
@@ -961,3 +963,49 @@ fn call_stmnt_location_test() {
         );
     }
 }
+
+#[test]
+fn loops_can_be_bounded() {
+    let src = "
+        PROGRAM exp
+        VAR i : INT; END_VAR
+        {attribute 'loop_bound' := '10'}
+        FOR i := 1 TO 10 DO
+            {attribute 'loop_bound' := ' 5 '}
+            WHILE i > 0 DO
+                i := i - 1;
+            END_WHILE
+        END_FOR
+        {attribute 'loop_bound' := 'many'}
+        REPEAT
+            i := i + 1;
+        UNTIL i > 3
+        END_REPEAT
+        {attribute 'loop_bound' := '2'}
+        i := 0;
+        END_PROGRAM
+        ";
+    let (result, diagnostics) = parse(src);
+
+    let implementation = &result.implementations[0];
+    let for_loop = &implementation.statements[0];
+    let while_loop = match for_loop {
+        AstStatement::ForLoopStatement { body, .. } => &body[0],
+        _ => panic!("expected a FOR loop, found {:?}", for_loop),
+    };
+    assert_eq!(
+        implementation.loop_bounds,
+        HashMap::from([(for_loop.get_id(), 10), (while_loop.get_id(), 5)])
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_loop_bound("many", SourceRange::new(266..300)),
+            Diagnostic::invalid_pragma_location(
+                "Only loops support the loop_bound attribute",
+                SourceRange::new(387..418)
+            ),
+        ]
+    );
+}
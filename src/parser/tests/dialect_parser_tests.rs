@@ -0,0 +1,197 @@
+use crate::{
+    ast::{AstStatement, CompilationUnit, LinkageType},
+    dialect::Dialect,
+    lexer::{lex_with_dialect, IdProvider},
+    parser::parse,
+    Diagnostic,
+};
+use pretty_assertions::*;
+
+fn parse_scl(src: &str) -> (CompilationUnit, Vec<Diagnostic>) {
+    parse(
+        lex_with_dialect(src, IdProvider::default(), Dialect::Scl),
+        LinkageType::Internal,
+    )
+}
+
+#[test]
+fn scl_sources_are_mapped_onto_the_iec_ast() {
+    let (scl, diagnostics) = parse_scl(
+        "
+        FUNCTION_BLOCK motor
+        { S7_Optimized_Access := 'TRUE' }
+        VERSION : 0.1
+        VAR
+            speed { ExternalAccessible := 'False' } : INT;
+        END_VAR
+        REGION initialization
+            #speed := 0;
+        END_REGION
+        REGION control
+            IF #speed < 100 THEN
+                #speed := #speed + 1;
+            END_IF;
+        END_REGION
+        END_FUNCTION_BLOCK
+        ",
+    );
+    let (iec, ..) = crate::test_utils::tests::parse(
+        "
+        FUNCTION_BLOCK motor
+        VAR
+            speed : INT;
+        END_VAR
+            speed := 0;
+            IF speed < 100 THEN
+                speed := speed + 1;
+            END_IF;
+        END_FUNCTION_BLOCK
+        ",
+    );
+
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(format!("{:?}", scl.units), format!("{:?}", iec.units));
+    assert_eq!(
+        format!("{:?}", scl.implementations[0].statements),
+        format!("{:?}", iec.implementations[0].statements)
+    );
+}
+
+#[test]
+fn scl_markers_do_not_shadow_identifiers() {
+    let (unit, diagnostics) = parse_scl(
+        "
+        PROGRAM prg
+        VAR
+            version : INT;
+        END_VAR
+            version := 1;
+        END_PROGRAM
+        ",
+    );
+
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(unit.implementations[0].statements.len(), 1);
+}
+
+#[test]
+fn local_prefix_is_reported_outside_of_scl() {
+    let (_, diagnostics) = parse(
+        lex_with_dialect(
+            "PROGRAM prg VAR x : INT; END_VAR #x := 1; END_PROGRAM",
+            IdProvider::default(),
+            Dialect::Iec,
+        ),
+        LinkageType::Internal,
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unsupported_in_dialect(
+            "#",
+            Dialect::Iec,
+            (33..34).into()
+        )]
+    );
+}
+
+#[test]
+fn local_prefix_is_reported_in_the_default_dialect() {
+    let (_, diagnostics) = parse(
+        lex_with_dialect(
+            "PROGRAM prg VAR x : INT; END_VAR #x := 1; END_PROGRAM",
+            IdProvider::default(),
+            Dialect::default(),
+        ),
+        LinkageType::Internal,
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unsupported_in_dialect(
+            "#",
+            Dialect::Legacy,
+            (33..34).into()
+        )]
+    );
+}
+
+#[test]
+fn jumps_and_labels_are_parsed_in_the_codesys_dialect() {
+    let (unit, diagnostics) = parse(
+        lex_with_dialect(
+            "
+            PROGRAM prg
+            VAR
+                jmp : INT;
+            END_VAR
+            start:
+                jmp := jmp + 1;
+                CASE jmp OF
+                    other: JMP start;
+                END_CASE
+            END_PROGRAM
+            ",
+            IdProvider::default(),
+            Dialect::Codesys,
+        ),
+        LinkageType::Internal,
+    );
+
+    assert_eq!(diagnostics, vec![]);
+    let statements = &unit.implementations[0].statements;
+    assert_eq!(
+        format!("{:?}", statements[0]),
+        r#"LabelStatement { name: "start" }"#
+    );
+    assert!(matches!(statements[1], AstStatement::Assignment { .. }));
+    // a name followed by ':' stays a case-condition inside a CASE
+    if let AstStatement::CaseStatement { case_blocks, .. } = &statements[2] {
+        assert_eq!(
+            format!("{:?}", case_blocks[0].condition),
+            r#"Reference { name: "other" }"#
+        );
+        assert_eq!(
+            format!("{:?}", case_blocks[0].body),
+            r#"[JumpStatement { label: "start" }, EmptyStatement]"#
+        );
+    } else {
+        panic!("expected a CASE statement, found {:?}", statements[2]);
+    }
+}
+
+#[test]
+fn if_expressions_are_parsed_into_sel_calls() {
+    let parse_body = |body: &str, dialect: Dialect| {
+        let (unit, diagnostics) = parse(
+            lex_with_dialect(
+                &format!("PROGRAM prg {} END_PROGRAM", body),
+                IdProvider::default(),
+                dialect,
+            ),
+            LinkageType::Internal,
+        );
+        (
+            format!("{:?}", unit.implementations[0].statements),
+            diagnostics,
+        )
+    };
+
+    let (sel, _) = parse_body("x := SEL(a, SEL(b, 3, 2), 1);", Dialect::Legacy);
+    let (if_expression, diagnostics) = parse_body(
+        "x := IF a THEN 1 ELSIF b THEN 2 ELSE 3 END_IF;",
+        Dialect::Legacy,
+    );
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(if_expression, sel);
+
+    let (_, diagnostics) = parse_body("x := IF a THEN 1 ELSE 2 END_IF;", Dialect::Iec);
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unsupported_in_dialect(
+            "IF expression",
+            Dialect::Iec,
+            (17..19).into()
+        )]
+    );
+}
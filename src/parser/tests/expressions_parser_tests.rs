@@ -2168,6 +2168,7 @@ fn arrays_can_be_parsed() {
                             size: None,
                         },
                     },
+                    is_variable_length: false,
                 },
             },
         },
@@ -2254,8 +2255,10 @@ fn nested_arrays_can_be_parsed() {
                                     size: None,
                                 },
                             },
+                            is_variable_length: false,
                         },
                     },
+                    is_variable_length: false,
                 },
             },
         },
@@ -2353,6 +2356,7 @@ fn multidim_arrays_can_be_parsed() {
                             size: None,
                         },
                     },
+                    is_variable_length: false,
                 },
             },
         },
@@ -2703,6 +2707,13 @@ fn sized_string_as_function_return() {
         name_location: SourceRange::undefined(),
         generics: vec![],
         linkage: crate::ast::LinkageType::Internal,
+        super_class: None,
+        interfaces: vec![],
+        usings: vec![],
+        optimization: None,
+        fast_math: false,
+        inline: false,
+        calling_convention: None,
     };
 
     assert_eq!(format!("{:?}", ast.units[0]), format!("{:?}", expected));
@@ -2742,6 +2753,7 @@ fn array_type_as_function_return() {
                     id: 0,
                 },
                 name: None,
+                is_variable_length: false,
             },
             location: SourceRange::undefined(),
             scope: Some("foo".into()),
@@ -2751,6 +2763,13 @@ fn array_type_as_function_return() {
         name_location: SourceRange::undefined(),
         generics: vec![],
         linkage: crate::ast::LinkageType::Internal,
+        super_class: None,
+        interfaces: vec![],
+        usings: vec![],
+        optimization: None,
+        fast_math: false,
+        inline: false,
+        calling_convention: None,
     };
 
     assert_eq!(format!("{:?}", ast.units[0]), format!("{:?}", expected));
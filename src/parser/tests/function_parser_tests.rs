@@ -130,6 +130,11 @@ fn varargs_parameters_can_be_parsed() {
             constant: false,
             access: AccessModifier::Protected,
             retain: false,
+            persistent: false,
+            atomic: false,
+            shared: false,
+            config_const: false,
+            init_priority: None,
             variable_block_type: VariableBlockType::Input(ArgumentProperty::ByVal),
             location: SourceRange::undefined(),
             linkage: LinkageType::Internal,
@@ -172,6 +177,13 @@ fn varargs_parameters_can_be_parsed() {
         poly_mode: None,
         generics: vec![],
         linkage: crate::ast::LinkageType::Internal,
+        super_class: None,
+        interfaces: vec![],
+        usings: vec![],
+        optimization: None,
+        fast_math: false,
+        inline: false,
+        calling_convention: None,
     };
     assert_eq!(format!("{:#?}", expected), format!("{:#?}", x).as_str());
 }
@@ -432,3 +444,66 @@ fn var_input_by_ref_parsed() {
     }
     "###)
 }
+
+#[test]
+fn prototypes_of_functions_and_function_blocks_can_be_parsed() {
+    let src = "
+        {prototype}
+        FUNCTION foo : INT
+            VAR_INPUT a : INT; END_VAR
+        END_FUNCTION
+
+        {prototype}
+        FUNCTION_BLOCK bar
+            x := 1;
+        END_FUNCTION_BLOCK
+
+        {prototype}
+        PROGRAM baz
+        END_PROGRAM
+        ";
+    let (result, diagnostics) = parse(src);
+
+    //prototypes are not implemented
+    let prototypes: Vec<(&str, &PouType)> = result
+        .prototypes
+        .iter()
+        .map(|it| (it.name.as_str(), &it.pou_type))
+        .collect();
+    assert_eq!(
+        vec![
+            ("foo", &PouType::Function),
+            ("bar", &PouType::FunctionBlock)
+        ],
+        prototypes
+    );
+    assert_eq!(1, result.prototypes[0].variable_blocks.len());
+    assert_eq!(
+        vec!["baz"],
+        result
+            .units
+            .iter()
+            .map(|it| it.name.as_str())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec!["baz"],
+        result
+            .implementations
+            .iter()
+            .map(|it| it.name.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        vec![
+            Diagnostic::prototype_with_body("bar", (168..174).into()),
+            Diagnostic::unexpected_token_found(
+                "FUNCTION or FUNCTION_BLOCK",
+                "PROGRAM",
+                (232..239).into()
+            ),
+        ],
+        diagnostics
+    );
+}
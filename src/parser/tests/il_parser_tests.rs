@@ -0,0 +1,198 @@
+use crate::{ast::*, lexer, test_utils::tests::parse, Diagnostic};
+
+/// parses the bodies of the given programs, expecting no diagnostics
+fn parse_bodies(src: &str) -> Vec<String> {
+    let (unit, diagnostics) = parse(src);
+    assert_eq!(diagnostics, vec![]);
+    unit.implementations
+        .iter()
+        .map(|it| format!("{:#?}", it.statements))
+        .collect()
+}
+
+#[test]
+fn il_is_lowered_to_the_equivalent_statements() {
+    let bodies = parse_bodies(
+        "
+        PROGRAM il
+        VAR a, b, c : INT; x, y : BOOL; END_VAR
+            LD a
+            ADD b
+            MUL 2
+            ST c
+            LDN x
+            ANDN y
+            OR( a
+                GT b
+            )
+            ST x
+            S y
+            LD c
+            LIMIT 0, 10
+            ST b
+        END_PROGRAM
+
+        PROGRAM st
+        VAR a, b, c : INT; x, y : BOOL; END_VAR
+            c := (a + b) * 2;
+            x := NOT x AND NOT y OR a > b;
+            IF x THEN
+                y := TRUE;
+            END_IF
+            b := LIMIT(c, 0, 10);
+        END_PROGRAM
+        ",
+    );
+
+    assert_eq!(bodies[0], bodies[1]);
+}
+
+#[test]
+fn a_computed_result_guarding_several_instructions_is_kept() {
+    let bodies = parse_bodies(
+        "
+        FUNCTION_BLOCK il
+        VAR a, b : INT; x, y : BOOL; END_VAR
+            LD a
+            GT b
+            S x
+            R y
+            RETC
+            LD x
+            RETCN
+        END_FUNCTION_BLOCK
+
+        FUNCTION_BLOCK st
+        VAR a, b : INT; x, y : BOOL; END_VAR
+        VAR_TEMP __il_cr : BOOL; END_VAR
+            __il_cr := a > b;
+            IF __il_cr THEN
+                x := TRUE;
+            END_IF
+            IF __il_cr THEN
+                y := FALSE;
+            END_IF
+            IF __il_cr THEN
+                RETURN;
+            END_IF
+            IF NOT x THEN
+                RETURN;
+            END_IF
+        END_FUNCTION_BLOCK
+        ",
+    );
+    assert_eq!(bodies[0], bodies[1]);
+
+    let (unit, _) = parse(
+        "
+        FUNCTION_BLOCK il
+        VAR a, b : INT; x, y : BOOL; END_VAR
+            LD a
+            GT b
+            S x
+        END_FUNCTION_BLOCK
+        ",
+    );
+    let temp = unit.units[0].variable_blocks.last().unwrap();
+    assert_eq!(temp.variable_block_type, VariableBlockType::Temp);
+    assert_eq!(temp.variables[0].name, "__il_cr");
+}
+
+#[test]
+fn jumps_labels_and_calls_are_lowered() {
+    let (unit, diagnostics) = parse(
+        "
+        PROGRAM prg
+        VAR x : BOOL; t : TON; END_VAR
+        loop: LD x
+            JMPC done
+            CAL t(IN := x, PT := T#1s)
+            CALCN t
+            JMP loop
+        done:
+            LD t.Q
+            ST x
+        END_PROGRAM
+        ",
+    );
+    assert_eq!(diagnostics, vec![]);
+
+    let statements = &unit.implementations[0].statements;
+    let lowered = statements
+        .iter()
+        .map(|it| match it {
+            AstStatement::LabelStatement { name, .. } => format!("label {}", name),
+            AstStatement::JumpStatement { label, .. } => format!("jump {}", label),
+            AstStatement::IfStatement { blocks, .. } => match &blocks[0].body[0] {
+                AstStatement::JumpStatement { label, .. } => format!("if jump {}", label),
+                AstStatement::CallStatement { .. } => "if call".to_string(),
+                other => format!("if {:?}", other),
+            },
+            AstStatement::CallStatement { .. } => "call".to_string(),
+            AstStatement::Assignment { .. } => "assignment".to_string(),
+            other => format!("{:?}", other),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lowered,
+        vec![
+            "label loop",
+            "if jump done",
+            "call",
+            "if call",
+            "jump loop",
+            "label done",
+            "assignment",
+        ]
+    );
+}
+
+#[test]
+fn il_is_only_detected_in_instruction_bodies() {
+    assert!(lexer::starts_il("LD", " x\n"));
+    assert!(lexer::starts_il("ldn", "\t%IX1.0\n"));
+    assert!(lexer::starts_il("CAL", " fb(IN := x)\n"));
+    assert!(lexer::starts_il("start", ": LD x\n"));
+
+    assert!(!lexer::starts_il("ld", " := 1;"));
+    assert!(!lexer::starts_il("ld", ".x := 1;"));
+    assert!(!lexer::starts_il("cal", "(x);"));
+    assert!(!lexer::starts_il("start", ": ld := 1;"));
+    assert!(!lexer::starts_il("JMP", " start;"));
+}
+
+#[test]
+fn invalid_instructions_are_reported() {
+    let src = "
+        PROGRAM prg
+        VAR x : BOOL; END_VAR
+            LD x
+            ST
+            AND x
+            LD x
+            OR(
+                LD x
+                ST x
+            )
+            AND(
+            )
+        END_PROGRAM
+        ";
+    let (_, diagnostics) = parse(src);
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::missing_il_operand("ST", (80..82).into()),
+            Diagnostic::missing_il_result("AND", (95..98).into()),
+            Diagnostic::invalid_il_instruction(
+                "Parenthesized instructions may only compute an operand",
+                (171..173).into()
+            ),
+            Diagnostic::invalid_il_instruction(
+                "Parenthesized instructions have to load an operand",
+                (205..206).into()
+            ),
+        ]
+    );
+}
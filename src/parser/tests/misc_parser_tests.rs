@@ -1,7 +1,7 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 use crate::Diagnostic;
 use core::panic;
-use std::ops::Range;
+use std::{collections::HashMap, ops::Range};
 
 use crate::{ast::*, parser::tests::empty_stmt, test_utils::tests::parse};
 use pretty_assertions::*;
@@ -47,6 +47,11 @@ fn exponent_literals_parsed_as_variables() {
             access: AccessModifier::Internal,
             constant: false,
             retain: false,
+            persistent: false,
+            atomic: false,
+            shared: false,
+            config_const: false,
+            init_priority: None,
             location: SourceRange::undefined(),
             linkage: LinkageType::Internal,
             variables: vec![Variable {
@@ -64,6 +69,13 @@ fn exponent_literals_parsed_as_variables() {
         name_location: SourceRange::undefined(),
         generics: vec![],
         linkage: crate::ast::LinkageType::Internal,
+        super_class: None,
+        interfaces: vec![],
+        usings: vec![],
+        optimization: None,
+        fast_math: false,
+        inline: false,
+        calling_convention: None,
     };
     assert_eq!(format!("{:#?}", expected), format!("{:#?}", pou).as_str());
     let implementation = &parse_result.implementations[0];
@@ -89,6 +101,7 @@ fn exponent_literals_parsed_as_variables() {
         overriding: false,
         generic: false,
         location: (105..142).into(),
+        loop_bounds: HashMap::new(),
     };
     assert_eq!(
         format!("{:#?}", expected),
@@ -1046,3 +1059,142 @@ fn location_implementation_for_all_statements() {
         (1..5).into()
     );
 }
+
+#[test]
+fn optimize_attributes_are_attached_to_the_following_pou() {
+    let src = "
+    {attribute 'optimize' := 'none'}
+    FUNCTION check : BOOL END_FUNCTION
+    {attribute 'optimize':='Size'} PROGRAM prg END_PROGRAM
+    FUNCTION_BLOCK fb END_FUNCTION_BLOCK
+    {attribute 'optimize' := 'fast'} FUNCTION calc : INT END_FUNCTION
+    {attribute 'optimize' := 'speed'} VAR_GLOBAL END_VAR
+    ";
+    let (result, diagnostics) = parse(src);
+
+    let hints = result
+        .units
+        .iter()
+        .map(|it| (it.name.as_str(), it.optimization))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        hints,
+        vec![
+            ("check", Some(OptimizationHint::None)),
+            ("prg", Some(OptimizationHint::Size)),
+            ("fb", None),
+            ("calc", None),
+        ]
+    );
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unknown_optimization("fast", SourceRange::new(181..213)),
+            Diagnostic::invalid_pragma_location(
+                "Only POUs support the optimize attribute",
+                SourceRange::new(251..284)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn calling_conventions_are_attached_to_the_following_external_function() {
+    let src = "
+    {external 'stdcall'} FUNCTION MessageBoxA : DINT END_FUNCTION
+    {external 'C'} FUNCTION puts : DINT END_FUNCTION
+    {external 'fastcall'} FUNCTION memcpy : DINT END_FUNCTION
+    {external 'C'} FUNCTION_BLOCK fb END_FUNCTION_BLOCK
+    ";
+    let (result, diagnostics) = parse(src);
+
+    let conventions = result
+        .units
+        .iter()
+        .map(|it| (it.name.as_str(), it.linkage, it.calling_convention))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        conventions,
+        vec![
+            (
+                "MessageBoxA",
+                LinkageType::External,
+                Some(CallingConvention::Stdcall)
+            ),
+            ("puts", LinkageType::External, Some(CallingConvention::C)),
+            ("memcpy", LinkageType::External, None),
+            ("fb", LinkageType::External, None),
+        ]
+    );
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unknown_calling_convention("fastcall", SourceRange::new(124..145)),
+            Diagnostic::invalid_pragma_location(
+                "Only FUNCTIONs support a calling convention",
+                SourceRange::new(186..200)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn fast_math_attributes_are_attached_to_the_following_pou() {
+    let src = "
+    {attribute 'fast_math'}
+    FUNCTION filter : REAL END_FUNCTION
+    FUNCTION_BLOCK pid END_FUNCTION_BLOCK
+    {attribute 'fast_math'} VAR_GLOBAL END_VAR
+    ";
+    let (result, diagnostics) = parse(src);
+
+    let fast_math = result
+        .units
+        .iter()
+        .map(|it| (it.name.as_str(), it.fast_math))
+        .collect::<Vec<_>>();
+    assert_eq!(fast_math, vec![("filter", true), ("pid", false)]);
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::invalid_pragma_location(
+            "Only POUs support the fast_math attribute",
+            SourceRange::new(115..138)
+        )]
+    );
+}
+
+#[test]
+fn inline_attributes_are_attached_to_the_following_function() {
+    let src = "
+    {attribute 'inline'}
+    FUNCTION twice : INT VAR_INPUT x : INT; END_VAR twice := x * 2; END_FUNCTION
+    {attribute 'inline'}
+    FUNCTION count : INT VAR i : INT; END_VAR count := i; END_FUNCTION
+    {attribute 'inline'} PROGRAM main END_PROGRAM
+    ";
+    let (result, diagnostics) = parse(src);
+
+    let inline = result
+        .units
+        .iter()
+        .map(|it| (it.name.as_str(), it.inline))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        inline,
+        vec![("twice", true), ("count", true), ("main", false)]
+    );
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::not_inlinable(
+                "count",
+                "it declares variables other than VAR_INPUT parameters passed by value",
+                SourceRange::new(145..150)
+            ),
+            Diagnostic::invalid_pragma_location(
+                "Only FUNCTIONs support the inline attribute",
+                SourceRange::new(207..227)
+            )
+        ]
+    );
+}
@@ -0,0 +1,155 @@
+use crate::{ast::*, test_utils::tests::parse, Diagnostic};
+
+#[test]
+fn declarations_in_namespaces_are_qualified() {
+    let src = "
+        NAMESPACE plant
+            NAMESPACE conveyor
+                FUNCTION_BLOCK Motor
+                    METHOD start END_METHOD
+                END_FUNCTION_BLOCK
+            END_NAMESPACE
+
+            TYPE Point : STRUCT x, y : INT; END_STRUCT END_TYPE
+
+            VAR_GLOBAL speed : INT; END_VAR
+
+            PROGRAM Main END_PROGRAM
+            ACTIONS Main
+                ACTION reset END_ACTION
+            END_ACTIONS
+        END_NAMESPACE
+
+        FUNCTION foo : INT END_FUNCTION
+        ";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    let pous = unit
+        .units
+        .iter()
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        pous,
+        vec![
+            "plant.conveyor.Motor",
+            "plant.conveyor.Motor.start",
+            "plant.Main",
+            "foo"
+        ]
+    );
+    assert_eq!(
+        unit.units[1].pou_type,
+        PouType::Method {
+            owner_class: "plant.conveyor.Motor".into()
+        }
+    );
+    let implementations = unit
+        .implementations
+        .iter()
+        .map(|it| (it.name.as_str(), it.type_name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        implementations,
+        vec![
+            ("plant.conveyor.Motor.start", "plant.conveyor.Motor.start"),
+            ("plant.conveyor.Motor", "plant.conveyor.Motor"),
+            ("plant.Main", "plant.Main"),
+            ("plant.Main.reset", "plant.Main"),
+            ("foo", "foo"),
+        ]
+    );
+    assert_eq!(unit.types[0].data_type.get_name(), Some("plant.Point"));
+    assert_eq!(unit.global_vars[0].variables[0].name, "plant.speed");
+    let namespaces = unit
+        .namespaces
+        .iter()
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(namespaces, vec!["plant.conveyor", "plant"]);
+}
+
+#[test]
+fn using_directives_are_parsed() {
+    let src = "
+        USING util;
+        NAMESPACE plant.conveyor
+            USING plant.sensors, util.math;
+            FUNCTION foo : INT USING plant.actors; END_FUNCTION
+            FUNCTION_BLOCK Motor USING drives; END_FUNCTION_BLOCK
+        END_NAMESPACE
+        ";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(unit.units[0].usings, vec!["plant.actors"]);
+    assert_eq!(unit.units[1].usings, vec!["drives"]);
+    let namespaces = unit
+        .namespaces
+        .iter()
+        .map(|it| (it.name.as_str(), it.usings.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        namespaces,
+        vec![
+            (
+                "plant.conveyor",
+                vec!["plant.sensors".to_string(), "util.math".to_string()]
+            ),
+            ("", vec!["util".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn types_can_be_referenced_with_their_namespace() {
+    let src = "
+        CLASS Drive EXTENDS plant.Motor IMPLEMENTS plant.IStart, IStop
+        VAR
+            position : plant.Point;
+            points : ARRAY[0..2] OF plant.Point;
+        END_VAR
+        END_CLASS
+        ";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    let class = &unit.units[0];
+    assert_eq!(class.super_class, Some("plant.Motor".into()));
+    assert_eq!(class.interfaces, vec!["plant.IStart", "IStop"]);
+    assert_eq!(
+        class.variable_blocks[0].variables[0].data_type.get_name(),
+        Some("plant.Point")
+    );
+    if let DataTypeDeclaration::DataTypeDefinition {
+        data_type: DataType::ArrayType {
+            referenced_type, ..
+        },
+        ..
+    } = &class.variable_blocks[0].variables[1].data_type
+    {
+        assert_eq!(referenced_type.get_name(), Some("plant.Point"));
+    } else {
+        panic!("unexpected data type");
+    }
+}
+
+#[test]
+fn unterminated_namespace_is_reported() {
+    let src = "
+        NAMESPACE plant
+            FUNCTION foo : INT END_FUNCTION
+        ";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(unit.units[0].name, "plant.foo");
+    let messages = diagnostics
+        .iter()
+        .map(Diagnostic::get_message)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec!["Unexpected token: expected END_NAMESPACE but found "]
+    );
+}
@@ -255,6 +255,11 @@ fn unclosed_var_container() {
                 constant: false,
                 access: AccessModifier::Protected,
                 retain: false,
+                persistent: false,
+                atomic: false,
+                shared: false,
+                config_const: false,
+                init_priority: None,
                 variable_block_type: VariableBlockType::Local,
                 location: SourceRange::undefined(),
                 variables: vec![Variable {
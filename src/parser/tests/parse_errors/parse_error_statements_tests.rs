@@ -1,6 +1,7 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 use crate::{
     ast::*,
+    diagnostics::CodeEdit,
     lexer::Token,
     parser::tests::{empty_stmt, ref_to},
     test_utils::tests::parse,
@@ -31,9 +32,10 @@ fn missing_semicolon_after_call() {
 
     let (compilation_unit, diagnostics) = parse(src);
     //expected end of statement (e.g. ;), but found KeywordEndProgram at line: 1 offset: 14..25"
-    //Expecting a missing semicolon message
+    //Expecting a missing semicolon message with a quick-fix inserting the ';' after buz()
     let expected =
-        Diagnostic::unexpected_token_found("KeywordSemicolon", "'foo()'", SourceRange::new(76..81));
+        Diagnostic::unexpected_token_found("KeywordSemicolon", "'foo()'", SourceRange::new(76..81))
+            .with_edits(vec![CodeEdit::insert(";", 55)]);
     assert_eq!(diagnostics[0], expected);
 
     let pou = &compilation_unit.implementations[0];
@@ -272,6 +274,11 @@ fn invalid_variable_name_error_recovery() {
                 constant: false,
                 access: AccessModifier::Protected,
                 retain: false,
+                persistent: false,
+                atomic: false,
+                shared: false,
+                config_const: false,
+                init_priority: None,
                 location: SourceRange::undefined(),
                 variables: vec![Variable {
                     name: "c".into(),
@@ -1028,6 +1035,7 @@ fn pointer_type_without_to_test() {
     let expected = UserTypeDeclaration {
         data_type: DataType::PointerType {
             name: Some("SamplePointer".into()),
+            auto_deref: false,
             referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
                 referenced_type: "INT".to_string(),
                 location: SourceRange::undefined(),
@@ -1066,6 +1074,7 @@ fn pointer_type_with_wrong_keyword_to_test() {
     let expected = UserTypeDeclaration {
         data_type: DataType::PointerType {
             name: Some("SamplePointer".into()),
+            auto_deref: false,
             referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
                 referenced_type: "tu".to_string(),
                 location: SourceRange::undefined(),
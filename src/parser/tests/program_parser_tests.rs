@@ -24,6 +24,17 @@ fn external_simple_foo_program_can_be_parsed() {
     assert_eq!(prg.linkage, LinkageType::External);
 }
 
+#[test]
+fn weak_function_can_be_parsed() {
+    let src = "{attribute 'weak'} FUNCTION foo : INT END_FUNCTION PROGRAM bar END_PROGRAM";
+    let result = parse(src).0;
+
+    assert_eq!(result.units[0].linkage, LinkageType::Weak);
+    assert_eq!(result.implementations[0].linkage, LinkageType::Weak);
+    //the attribute only applies to the next pou
+    assert_eq!(result.units[1].linkage, LinkageType::Internal);
+}
+
 #[test]
 fn simple_program_with_variable_can_be_parsed() {
     let src = "PROGRAM buz VAR x : INT; END_VAR END_PROGRAM";
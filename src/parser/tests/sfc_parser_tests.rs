@@ -0,0 +1,171 @@
+use crate::{ast::*, test_utils::tests::parse, Diagnostic};
+
+#[test]
+fn sfc_steps_are_declared_as_variables() {
+    let src = "
+        PROGRAM prg
+        VAR
+            start : BOOL;
+        END_VAR
+            INITIAL_STEP idle :
+            END_STEP
+            STEP fill :
+                pump(N);
+                open(P1);
+                close(P0);
+            END_STEP
+            TRANSITION FROM idle TO fill := start;
+            END_TRANSITION
+            TRANSITION FROM fill TO idle := fill.T >= T#5s;
+            END_TRANSITION
+            ACTION pump :
+                start := FALSE;
+            END_ACTION
+        END_PROGRAM
+        ";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    let variables = unit.units[0]
+        .variable_blocks
+        .iter()
+        .map(|block| {
+            (
+                block.variable_block_type,
+                block
+                    .variables
+                    .iter()
+                    .map(|it| it.name.as_str())
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        variables,
+        vec![
+            (VariableBlockType::Local, vec!["start"]),
+            (VariableBlockType::Local, vec!["idle", "fill"]),
+            (VariableBlockType::Temp, vec!["__sfc_now"]),
+        ]
+    );
+
+    // the initial step is entered in the first cycle
+    let idle = &unit.units[0].variable_blocks[1].variables[0];
+    let fill = &unit.units[0].variable_blocks[1].variables[1];
+    assert!(idle.initializer.is_some());
+    assert!(fill.initializer.is_none());
+    if let DataTypeDeclaration::DataTypeDefinition {
+        data_type: DataType::StructType { variables, .. },
+        ..
+    } = &fill.data_type
+    {
+        let members = variables
+            .iter()
+            .map(|it| it.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            members,
+            vec!["X", "T", "__entered", "__start", "__was_active"]
+        );
+    } else {
+        panic!("expected an inline struct, found {:?}", fill.data_type);
+    }
+}
+
+#[test]
+fn sfc_is_lowered_to_the_pou_body() {
+    let src = "
+        FUNCTION_BLOCK fb
+            INITIAL_STEP idle :
+            END_STEP
+            STEP fill :
+                pump(N);
+                open(P1);
+            END_STEP
+            TRANSITION FROM idle TO fill := TRUE;
+            END_TRANSITION
+            ACTION pump : END_ACTION
+            ACTION open : END_ACTION
+        END_FUNCTION_BLOCK
+        ";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    let names = unit
+        .implementations
+        .iter()
+        .map(|it| (it.name.as_str(), it.type_name.as_str(), it.pou_type.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            ("fb", "fb", PouType::FunctionBlock),
+            ("fb.pump", "fb", PouType::Action),
+            ("fb.open", "fb", PouType::Action),
+        ]
+    );
+
+    // the clock, entering and timing every step, an if per action, remembering the active
+    // steps and an if per transition
+    let statements = &unit.implementations[0].statements;
+    assert_eq!(statements.len(), 1 + 2 * 2 + 2 + 2 + 1);
+    if let AstStatement::IfStatement { blocks, .. } = &statements[9] {
+        let body = format!("{:?}", blocks[0].body);
+        assert_eq!(body.matches("LiteralBool { value: false }").count(), 1);
+        assert_eq!(body.matches("LiteralBool { value: true }").count(), 2);
+        assert!(body.contains(r#"Reference { name: "__entered" }"#));
+    } else {
+        panic!("expected the transition, found {:?}", statements[9]);
+    }
+}
+
+#[test]
+fn step_is_no_keyword() {
+    let src = "
+        PROGRAM prg
+        VAR
+            step : INT;
+            transitions : INT;
+        END_VAR
+            step := step + 1;
+            FOR transitions := 0 TO 10 BY step DO
+            END_FOR
+        END_PROGRAM
+        ";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(unit.implementations[0].statements.len(), 2);
+}
+
+#[test]
+fn invalid_sfc_networks_are_reported() {
+    let src = "
+        PROGRAM prg
+            STEP fill :
+                pump(S);
+            END_STEP
+            STEP fill :
+            END_STEP
+            TRANSITION FROM fill TO drain := TRUE;
+            END_TRANSITION
+        END_PROGRAM
+
+        FUNCTION foo : INT
+            INITIAL_STEP idle :
+            END_STEP
+        END_FUNCTION
+        ";
+    let (_, diagnostics) = parse(src);
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unsupported_action_qualifier("S", (66..67).into()),
+            Diagnostic::duplicate_step("fill", (108..112).into()),
+            Diagnostic::invalid_initial_steps("prg", 0, (33..213).into()),
+            Diagnostic::unknown_step("drain", (172..177).into()),
+            Diagnostic::sfc_without_instance("foo", (274..314).into()),
+        ]
+    );
+}
@@ -0,0 +1,180 @@
+use crate::{ast::*, test_utils::tests::parse, Diagnostic};
+
+#[test]
+fn states_are_declared_as_an_enum_starting_with_the_initial_state() {
+    let src = "
+        FUNCTION_BLOCK door
+        VAR_INPUT
+            open : BOOL;
+        END_VAR
+            STATE opening
+                ENTRY:
+                    open := FALSE;
+            END_STATE
+            INITIAL_STATE closed
+            END_STATE
+            TRANSITION FROM closed TO opening := open;
+            END_TRANSITION
+        END_FUNCTION_BLOCK
+        ";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    let block = &unit.units[0].variable_blocks[1];
+    let names = block
+        .variables
+        .iter()
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["__state", "__entered"]);
+    if let DataTypeDeclaration::DataTypeDefinition {
+        data_type: DataType::EnumType { elements, .. },
+        ..
+    } = &block.variables[0].data_type
+    {
+        let elements = flatten_expression_list(elements)
+            .into_iter()
+            .map(|it| format!("{:?}", it))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            elements,
+            vec![
+                r#"Reference { name: "closed" }"#,
+                r#"Reference { name: "opening" }"#
+            ]
+        );
+    } else {
+        panic!(
+            "expected an inline enum, found {:?}",
+            block.variables[0].data_type
+        );
+    }
+}
+
+#[test]
+fn state_machine_is_lowered_to_a_case_over_the_states() {
+    let src = "
+        PROGRAM prg
+        VAR
+            go, done : BOOL;
+            ticks : INT;
+        END_VAR
+            INITIAL_STATE idle
+                ticks := 0;
+            END_STATE
+            STATE busy
+                ENTRY:
+                    ticks := 1;
+                DURING:
+                    ticks := ticks + 1;
+                EXIT:
+                    done := TRUE;
+            END_STATE
+            TRANSITION FROM idle TO busy := go;
+            END_TRANSITION
+            TRANSITION FROM busy TO idle := ticks > 10;
+            END_TRANSITION
+            TRANSITION abort FROM (idle, busy) TO idle := NOT go;
+            END_TRANSITION
+        END_PROGRAM
+        ";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    let names = unit
+        .implementations
+        .iter()
+        .map(|it| (it.name.as_str(), it.type_name.as_str(), it.pou_type.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            ("prg", "prg", PouType::Program),
+            ("prg.__entry_busy", "prg", PouType::Action),
+            ("prg.__exit_busy", "prg", PouType::Action),
+        ]
+    );
+
+    let statements = &unit.implementations[0].statements;
+    assert_eq!(statements.len(), 1);
+    if let AstStatement::CaseStatement { case_blocks, .. } = &statements[0] {
+        assert_eq!(case_blocks.len(), 2);
+        assert_eq!(
+            format!("{:?}", case_blocks[1].condition),
+            r#"CastStatement { type_name: "__prg___state", target: Reference { name: "busy" } }"#
+        );
+        // idle: the DURING statement and its two transitions
+        let idle = &case_blocks[0].body;
+        assert_eq!(idle.len(), 2);
+        assert!(matches!(&idle[1], AstStatement::IfStatement { blocks, .. } if blocks.len() == 2));
+        // busy: the entry action, the DURING statement and its two transitions leaving through
+        // the exit action
+        let busy = &case_blocks[1].body;
+        assert_eq!(busy.len(), 3);
+        let entry = format!("{:?}", busy[0]);
+        assert!(entry.contains(r#"Reference { name: "__entry_busy" }"#));
+        if let AstStatement::IfStatement { blocks, .. } = &busy[2] {
+            assert_eq!(blocks.len(), 2);
+            let body = format!("{:?}", blocks[0].body);
+            assert!(body.contains(r#"Reference { name: "__exit_busy" }"#));
+            assert!(body.contains(r#"Reference { name: "idle" }"#));
+            assert!(body.contains("LiteralBool { value: false }"));
+        } else {
+            panic!("expected the transitions, found {:?}", busy[2]);
+        }
+    } else {
+        panic!("expected a case statement, found {:?}", statements[0]);
+    }
+}
+
+#[test]
+fn state_and_its_sections_are_no_keywords() {
+    let src = "
+        PROGRAM prg
+        VAR
+            state : INT;
+            entry : INT;
+            during : INT;
+        END_VAR
+            state := entry + during;
+            WHILE state > 0 DO
+                EXIT;
+            END_WHILE
+        END_PROGRAM
+        ";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(unit.implementations[0].statements.len(), 2);
+}
+
+#[test]
+fn invalid_state_machines_are_reported() {
+    let src = "
+        PROGRAM prg
+            STATE idle
+            END_STATE
+            STATE idle
+            END_STATE
+            TRANSITION FROM idle TO (busy, idle) := TRUE;
+            END_TRANSITION
+        END_PROGRAM
+
+        FUNCTION foo : INT
+            INITIAL_STATE idle
+            END_STATE
+        END_FUNCTION
+        ";
+    let (_, diagnostics) = parse(src);
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::duplicate_state("idle", (84..88).into()),
+            Diagnostic::invalid_initial_states("prg", 0, (33..195).into()),
+            Diagnostic::ambiguous_state_transition((123..168).into()),
+            Diagnostic::unknown_state("busy", (148..152).into()),
+            Diagnostic::state_machine_without_instance("foo", (256..296).into()),
+        ]
+    );
+}
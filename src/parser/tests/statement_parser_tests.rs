@@ -262,3 +262,32 @@ fn string_variable_declaration_can_be_parsed() {
 }"#;
     assert_eq!(expected, format!("{:#?}", x).as_str());
 }
+
+#[test]
+fn reference_assignment_is_parsed() {
+    let src = "PROGRAM buz r REF= x; s.r REF= y; END_PROGRAM ";
+    let result = parse(src).0;
+
+    let prg = &result.implementations[0];
+    assert_eq!(
+        format!("{:?}", prg.statements),
+        format!(
+            "{:?}",
+            vec![
+                AstStatement::ReferenceAssignment {
+                    left: Box::new(ref_to("r")),
+                    right: Box::new(ref_to("x")),
+                    id: 0
+                },
+                AstStatement::ReferenceAssignment {
+                    left: Box::new(AstStatement::QualifiedReference {
+                        elements: vec![ref_to("s"), ref_to("r")],
+                        id: 0
+                    }),
+                    right: Box::new(ref_to("y")),
+                    id: 0
+                },
+            ]
+        ),
+    );
+}
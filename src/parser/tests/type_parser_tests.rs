@@ -181,6 +181,7 @@ fn array_type_can_be_parsed_test() {
                     referenced_type: "INT".to_string(),
                     location: SourceRange::undefined(),
                 }),
+                is_variable_length: false,
             },
             initializer: None,
             location: SourceRange::undefined(),
@@ -351,6 +352,7 @@ fn struct_with_inline_array_can_be_parsed() {
                         referenced_type: DataTypeReference {
                             referenced_type: "INT",
                         },
+                        is_variable_length: false,
                     },
                 },
             },
@@ -375,6 +377,7 @@ fn pointer_type_test() {
     let expected = UserTypeDeclaration {
         data_type: DataType::PointerType {
             name: Some("SamplePointer".into()),
+            auto_deref: false,
             referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
                 referenced_type: "INT".to_string(),
                 location: SourceRange::undefined(),
@@ -396,6 +399,36 @@ fn pointer_type_test() {
     assert_eq!(diagnostics[0], diagnostic);
 }
 
+#[test]
+fn reference_to_type_test() {
+    let (result, diagnostics) = parse(
+        r#"
+        TYPE SampleReference :
+            REFERENCE TO INT;
+        END_TYPE
+        "#,
+    );
+    let reference_type = &result.types[0];
+    let expected = UserTypeDeclaration {
+        data_type: DataType::PointerType {
+            name: Some("SampleReference".into()),
+            auto_deref: true,
+            referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
+                referenced_type: "INT".to_string(),
+                location: SourceRange::undefined(),
+            }),
+        },
+        location: SourceRange::undefined(),
+        initializer: None,
+        scope: None,
+    };
+    assert_eq!(
+        format!("{:#?}", expected),
+        format!("{:#?}", reference_type).as_str()
+    );
+    assert_eq!(diagnostics.len(), 0)
+}
+
 #[test]
 fn ref_type_test() {
     let (result, diagnostics) = parse(
@@ -409,6 +442,7 @@ fn ref_type_test() {
     let expected = UserTypeDeclaration {
         data_type: DataType::PointerType {
             name: Some("SampleReference".into()),
+            auto_deref: false,
             referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
                 referenced_type: "INT".to_string(),
                 location: SourceRange::undefined(),
@@ -441,6 +475,7 @@ fn global_pointer_declaration() {
         data_type: DataTypeDeclaration::DataTypeDefinition {
             data_type: DataType::PointerType {
                 name: None,
+                auto_deref: false,
                 referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
                     referenced_type: "INT".to_string(),
                     location: SourceRange::undefined(),
@@ -463,6 +498,7 @@ fn global_pointer_declaration() {
         data_type: DataTypeDeclaration::DataTypeDefinition {
             data_type: DataType::PointerType {
                 name: None,
+                auto_deref: false,
                 referenced_type: Box::new(DataTypeDeclaration::DataTypeReference {
                     referenced_type: "INT".to_string(),
                     location: SourceRange::undefined(),
@@ -486,3 +522,129 @@ fn global_pointer_declaration() {
     };
     assert_eq!(diagnostics[0], diagnostic);
 }
+
+#[test]
+fn variable_length_arrays_can_be_parsed() {
+    let (result, diagnostics) = parse(
+        r#"
+        FUNCTION sum : DINT
+            VAR_INPUT
+                values : ARRAY[*] OF INT;
+            END_VAR
+            VAR_IN_OUT
+                matrix : ARRAY[*, *] OF REAL;
+            END_VAR
+        END_FUNCTION
+        "#,
+    );
+
+    assert_eq!(diagnostics, vec![]);
+    let get_array = |block: usize| {
+        if let DataTypeDeclaration::DataTypeDefinition {
+            data_type:
+                DataType::ArrayType {
+                    bounds,
+                    is_variable_length,
+                    ..
+                },
+            ..
+        } = &result.units[0].variable_blocks[block].variables[0].data_type
+        {
+            (
+                *is_variable_length,
+                flatten_expression_list(bounds)
+                    .iter()
+                    .all(|it| matches!(it, AstStatement::EmptyStatement { .. })),
+                flatten_expression_list(bounds).len(),
+            )
+        } else {
+            panic!("expected an array");
+        }
+    };
+    assert_eq!(get_array(0), (true, true, 1));
+    assert_eq!(get_array(1), (true, true, 2));
+}
+
+#[test]
+fn vectors_and_matrices_are_arrays_indexed_from_zero() {
+    let types_of = |src: &str| {
+        let (result, diagnostics) = parse(src);
+        assert_eq!(diagnostics, vec![]);
+        format!("{:#?}", result.types)
+    };
+    assert_eq!(
+        types_of(
+            "
+            TYPE position : VECTOR[3] OF LREAL; END_TYPE
+            TYPE rotation : MATRIX[3, AXES] OF LREAL; END_TYPE
+            "
+        ),
+        types_of(
+            "
+            TYPE position : ARRAY[0..3 - 1] OF LREAL; END_TYPE
+            TYPE rotation : ARRAY[0..3 - 1, 0..AXES - 1] OF LREAL; END_TYPE
+            "
+        )
+    );
+
+    //identifiers named vector or matrix stay valid
+    let (_, diagnostics) = parse(
+        "
+        PROGRAM prg
+        VAR vector : INT; matrix : ARRAY[0..1] OF INT; END_VAR
+            vector := matrix[0];
+        END_PROGRAM
+        ",
+    );
+    assert_eq!(diagnostics, vec![]);
+
+    let (_, diagnostics) = parse("TYPE rotation : MATRIX[3] OF LREAL; END_TYPE");
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::syntax_error(
+            "A MATRIX is declared with its rows and columns (e.g. MATRIX[3, 3])",
+            (16..28).into()
+        )]
+    );
+}
+
+#[test]
+fn fixed_point_types_are_declared_with_integer_and_fraction_bits() {
+    let (result, diagnostics) = parse(
+        "
+        TYPE q16 : FIXED(16, 16); END_TYPE
+        PROGRAM prg
+        VAR x : fixed(1, 15) := 0.5; END_VAR
+        END_PROGRAM
+        ",
+    );
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(
+        result.types[0].data_type,
+        DataType::FixedPointType {
+            name: Some("q16".to_string()),
+            integer_bits: 16,
+            fraction_bits: 16,
+        }
+    );
+    assert!(matches!(
+        &result.units[0].variable_blocks[0].variables[0].data_type,
+        DataTypeDeclaration::DataTypeDefinition {
+            data_type: DataType::FixedPointType {
+                integer_bits: 1,
+                fraction_bits: 15,
+                ..
+            },
+            ..
+        }
+    ));
+
+    let (_, diagnostics) = parse("TYPE q : FIXED(4, 5); END_TYPE");
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::syntax_error(
+            "A FIXED type is declared with at least one integer bit and the fraction bits adding up to 8, 16, 32 or 64 (e.g. FIXED(16, 16))",
+            (9..20).into()
+        )]
+    );
+}
@@ -1,6 +1,7 @@
 use crate::{
-    ast::{LinkageType, VariableBlock},
+    ast::{LinkageType, SourceRange, VariableBlock},
     test_utils::tests::parse,
+    Diagnostic,
 };
 
 #[test]
@@ -17,6 +18,72 @@ fn empty_global_vars_can_be_parsed() {
     assert_eq!(ast_string, expected_ast)
 }
 
+#[test]
+fn atomic_global_vars_can_be_parsed() {
+    let src = "VAR_GLOBAL {atomic} x : INT; END_VAR VAR_GLOBAL y : INT; END_VAR";
+    let (result, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    assert!(result.global_vars[0].atomic);
+    assert!(!result.global_vars[1].atomic);
+}
+
+#[test]
+fn config_const_global_vars_can_be_parsed() {
+    let src = "{attribute 'config_const'} VAR_GLOBAL CONSTANT x : INT; END_VAR VAR_GLOBAL y : INT; END_VAR";
+    let (result, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    assert!(result.global_vars[0].config_const);
+    assert!(result.global_vars[0].constant);
+    assert!(!result.global_vars[1].config_const);
+}
+
+#[test]
+fn config_const_attribute_is_only_supported_for_global_vars() {
+    let src = "{attribute 'config_const'} PROGRAM prg END_PROGRAM";
+    let (result, diagnostics) = parse(src);
+
+    assert_eq!(result.units.len(), 1);
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::invalid_pragma_location(
+            "Only VAR_GLOBAL blocks support the config_const attribute",
+            SourceRange::new(27..34)
+        )]
+    );
+}
+
+#[test]
+fn init_priorities_of_global_vars_can_be_parsed() {
+    let src = "{attribute 'init_priority' := '2'} {attribute 'config_const'}
+    VAR_GLOBAL CONSTANT x : INT; END_VAR
+    {attribute 'init_priority' := 1} VAR_GLOBAL y : INT; END_VAR
+    VAR_GLOBAL z : INT; END_VAR";
+    let (result, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(result.global_vars[0].init_priority, Some(2));
+    assert!(result.global_vars[0].config_const);
+    assert_eq!(result.global_vars[1].init_priority, Some(1));
+    assert_eq!(result.global_vars[2].init_priority, None);
+}
+
+#[test]
+fn invalid_init_priorities_are_reported() {
+    let src = "{attribute 'init_priority' := 'first'} VAR_GLOBAL x : INT; END_VAR";
+    let (result, diagnostics) = parse(src);
+
+    assert_eq!(result.global_vars[0].init_priority, None);
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::invalid_init_priority(
+            "first",
+            SourceRange::new(0..38)
+        )]
+    );
+}
+
 #[test]
 fn global_vars_can_be_parsed() {
     let src = "VAR_GLOBAL x : INT; y : BOOL; END_VAR";
@@ -223,3 +290,76 @@ fn struct_with_address() {
     assert_eq!(diag, vec![]);
     insta::assert_snapshot!(format!("{:?}", result));
 }
+
+#[test]
+fn persistent_global_vars_are_retained() {
+    let src = "
+    VAR_GLOBAL RETAIN END_VAR
+    VAR_GLOBAL PERSISTENT END_VAR
+    VAR_GLOBAL RETAIN PERSISTENT END_VAR
+    VAR_GLOBAL PERSISTENT RETAIN END_VAR
+    ";
+    let (result, diag) = parse(src);
+
+    assert_eq!(diag, vec![]);
+    let flags = result
+        .global_vars
+        .iter()
+        .map(|it| (it.retain, it.persistent))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        flags,
+        vec![(true, false), (true, true), (true, true), (true, true)]
+    );
+}
+
+#[test]
+fn var_config_can_be_parsed() {
+    let src = "
+    VAR_CONFIG
+        main.fb.sensor AT %IX1.2 : BOOL;
+        main.fbs[1,-2].valve AT %QW4 : WORD;
+    END_VAR
+    ";
+    let (result, diag) = parse(src);
+
+    assert_eq!(diag, vec![]);
+    let entries = result
+        .var_config
+        .iter()
+        .map(|it| {
+            (
+                it.reference.as_str(),
+                it.data_type.get_name().unwrap(),
+                it.location.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        vec![
+            ("main.fb.sensor", "BOOL", SourceRange::new(24..38)),
+            ("main.fbs[1,-2].valve", "WORD", SourceRange::new(65..85)),
+        ]
+    );
+    assert_eq!(
+        format!("{:?}", result.var_config[0].address),
+        "HardwareAccess { direction: Input, access: Bit, address: [LiteralInteger { value: 1 }, LiteralInteger { value: 2 }], location: SourceRange { range: 39..48 } }"
+    );
+}
+
+#[test]
+fn var_config_requires_an_address() {
+    let src = "
+    VAR_CONFIG
+        main.fb.sensor : BOOL;
+    END_VAR
+    ";
+    let (result, diag) = parse(src);
+
+    assert_eq!(
+        diag,
+        vec![Diagnostic::missing_token("KeywordAt", (39..40).into())]
+    );
+    assert_eq!(result.var_config, vec![]);
+}
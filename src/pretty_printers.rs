@@ -0,0 +1,138 @@
+//! generates the python pretty printers for GDB and LLDB that accompany a debug build, they show
+//! strings and the date and time types as ST literals and the instances of programs, function
+//! blocks and classes with their declared members only
+
+use std::path::Path;
+
+use crate::index::{Index, PouIndexEntry};
+
+const FORMATS: &str = include_str!("pretty_printers/formats.py");
+const GDB_PRINTERS: &str = include_str!("pretty_printers/gdb.py");
+const LLDB_PRINTERS: &str = include_str!("pretty_printers/lldb.py");
+
+/// returns the name GDB auto-loads the printers of the given application from (`<application>-gdb.py`)
+pub fn get_gdb_script_name(output: &str) -> String {
+    format!("{}-gdb.py", output)
+}
+
+/// returns the name of the LLDB printers of the given application, the file's stem is imported as
+/// a python module and may therefore only contain letters, digits and underscores
+pub fn get_lldb_script_name(output: &str) -> String {
+    let path = Path::new(output);
+    let stem: String = path
+        .file_stem()
+        .map(|it| it.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .map(|it| if it.is_ascii_alphanumeric() { it } else { '_' })
+        .collect();
+    path.with_file_name(format!("{}_lldb.py", stem))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// generates the printers for GDB, to be auto-loaded or `source`d
+pub fn generate_gdb_printers(index: &Index) -> String {
+    generate_printers(index, GDB_PRINTERS)
+}
+
+/// generates the printers for LLDB, to be loaded with `command script import`
+pub fn generate_lldb_printers(index: &Index) -> String {
+    generate_printers(index, LLDB_PRINTERS)
+}
+
+fn generate_printers(index: &Index, printers: &str) -> String {
+    format!(
+        "# generated by rusty {}\n{}\n{}\n{}",
+        env!("CARGO_PKG_VERSION"),
+        FORMATS,
+        generate_instances(index),
+        printers
+    )
+}
+
+/// describes the instance structs as `INSTANCES = { struct: (kind, name, [(member, is_reference)]) }`,
+/// the members generated by the compiler (e.g. `__vtable`) and the temporary variables are left out
+fn generate_instances(index: &Index) -> String {
+    let mut instances = String::from("INSTANCES = {\n");
+    for pou in index.get_pous().values() {
+        let kind = match pou {
+            PouIndexEntry::Program { .. } => "PROGRAM",
+            PouIndexEntry::FunctionBlock { .. } => "FUNCTION_BLOCK",
+            PouIndexEntry::Class { .. } => "CLASS",
+            _ => continue,
+        };
+        let struct_name = pou
+            .get_instance_struct_type_name()
+            .unwrap_or_else(|| pou.get_name());
+        let mut members = index
+            .get_container_members(pou.get_name())
+            .into_iter()
+            .filter(|it| !it.is_temp() && !it.is_return() && !it.get_name().starts_with("__"))
+            .collect::<Vec<_>>();
+        members.sort_by_key(|it| it.get_location_in_parent());
+        let members = members
+            .iter()
+            .map(|it| {
+                let is_reference = if it.get_declaration_type().is_by_ref() {
+                    "True"
+                } else {
+                    "False"
+                };
+                format!("({:?}, {})", it.get_name(), is_reference)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        instances.push_str(&format!(
+            "    {:?}: ({:?}, {:?}, [{}]),\n",
+            struct_name.to_lowercase(),
+            kind,
+            pou.get_name(),
+            members
+        ));
+    }
+    instances.push_str("}\n");
+    instances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_instances, get_gdb_script_name, get_lldb_script_name};
+    use crate::test_utils::tests::index;
+
+    #[test]
+    fn instances_are_described_with_their_visible_members() {
+        let (_, index) = index(
+            "
+            FUNCTION_BLOCK Motor
+            VAR_INPUT speed : INT; END_VAR
+            VAR_IN_OUT limit : INT; END_VAR
+            VAR_TEMP scratch : INT; END_VAR
+            END_FUNCTION_BLOCK
+
+            PROGRAM mainProg
+            VAR drive : Motor; END_VAR
+            END_PROGRAM
+
+            FUNCTION foo : INT
+            VAR x : INT; END_VAR
+            END_FUNCTION
+            ",
+        );
+
+        let instances = generate_instances(&index);
+        assert!(instances.contains(
+            r#"    "motor": ("FUNCTION_BLOCK", "Motor", [("speed", False), ("limit", True)]),"#
+        ));
+        assert!(
+            instances.contains(r#"    "mainprog": ("PROGRAM", "mainProg", [("drive", False)]),"#)
+        );
+        assert!(!instances.contains(r#""foo""#));
+    }
+
+    #[test]
+    fn script_names_follow_the_debuggers_conventions() {
+        assert_eq!(get_gdb_script_name("out/my-app"), "out/my-app-gdb.py");
+        assert_eq!(get_lldb_script_name("out/my-app.elf"), "out/my_app_lldb.py");
+    }
+}
@@ -0,0 +1,98 @@
+# renders the values of the ST types as ST literals, shared by the GDB and LLDB pretty printers
+import datetime
+import re
+
+# the units of a TIME (nanoseconds) from the largest to the smallest
+_TIME_UNITS = [
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+]
+
+_EPOCH = datetime.datetime(1970, 1, 1)
+
+# the described names of strings, e.g. `STRING[80]` or `WSTRING[10]`
+_STRING_TYPE = re.compile(r"^(W?)STRING\[\d+\]$", re.IGNORECASE)
+
+
+def format_time(nanos):
+    if nanos == 0:
+        return "T#0s"
+    sign = "-" if nanos < 0 else ""
+    nanos = abs(nanos)
+    parts = []
+    for unit, size in _TIME_UNITS:
+        if nanos >= size:
+            parts.append("%d%s" % (nanos // size, unit))
+            nanos %= size
+    return "T#" + sign + "".join(parts)
+
+
+def _to_date_time(millis):
+    return _EPOCH + datetime.timedelta(milliseconds=millis)
+
+
+def _format_milliseconds(date_time):
+    millis = date_time.microsecond // 1000
+    return ".%03d" % millis if millis else ""
+
+
+def format_date(millis):
+    return "D#" + _to_date_time(millis).strftime("%Y-%m-%d")
+
+
+def format_time_of_day(millis):
+    time_of_day = _to_date_time(millis)
+    return "TOD#" + time_of_day.strftime("%H:%M:%S") + _format_milliseconds(time_of_day)
+
+
+def format_date_and_time(millis):
+    date_time = _to_date_time(millis)
+    return "DT#" + date_time.strftime("%Y-%m-%d-%H:%M:%S") + _format_milliseconds(date_time)
+
+
+# the formats of the date and time types by the name of the described type, the aliases (e.g.
+# `LTIME` or `TOD`) refer to them
+NUMBER_FORMATS = {
+    "TIME": format_time,
+    "DATE": format_date,
+    "TIME_OF_DAY": format_time_of_day,
+    "DATE_AND_TIME": format_date_and_time,
+}
+
+
+def format_number(type_name, value):
+    """returns the given value of a date or time type as ST literal, None for other types"""
+    format = NUMBER_FORMATS.get(type_name.upper()) if type_name else None
+    if format is None:
+        return None
+    try:
+        return format(value)
+    except (OverflowError, ValueError):
+        return str(value)
+
+
+def is_wide_string(type_name):
+    """returns True for a WSTRING, False for a STRING and None for other types"""
+    match = _STRING_TYPE.match(type_name) if type_name else None
+    return bool(match.group(1)) if match else None
+
+
+def format_string(characters, wide):
+    """returns the given characters up to the terminator as ST literal"""
+    characters = list(characters)
+    if 0 in characters:
+        characters = characters[: characters.index(0)]
+    if wide:
+        units = b"".join(it.to_bytes(2, "little") for it in characters)
+        return '"%s"' % units.decode("utf-16-le", "replace")
+    return "'%s'" % bytes(it & 0xFF for it in characters).decode("utf-8", "replace")
+
+
+def find_instance(type_name):
+    """returns the POU kind and the visible members of the given instance struct"""
+    return INSTANCES.get(type_name.lower()) if type_name else None
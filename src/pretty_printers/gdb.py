@@ -0,0 +1,64 @@
+# registers the GDB pretty printers, GDB loads them from `<application>-gdb.py`
+import gdb
+
+
+class _LiteralPrinter:
+    def __init__(self, literal):
+        self.literal = literal
+
+    def to_string(self):
+        return self.literal
+
+
+class _StringPrinter:
+    def __init__(self, value, wide):
+        self.value = value
+        self.wide = wide
+
+    def to_string(self):
+        array = self.value.type.strip_typedefs()
+        low, high = array.range()
+        characters = (int(self.value[it]) for it in range(low, high + 1))
+        return format_string(characters, self.wide)
+
+
+class _InstancePrinter:
+    """shows the members of a POU instance, VAR_IN_OUT members are dereferenced and the
+    members generated by the compiler are hidden"""
+
+    def __init__(self, value, instance):
+        self.value = value
+        self.kind, self.name, self.members = instance
+
+    def to_string(self):
+        return "%s %s" % (self.kind, self.name)
+
+    def children(self):
+        for member, is_reference in self.members:
+            value = self.value[member]
+            if is_reference and int(value) != 0:
+                value = value.dereference()
+            yield member, value
+
+
+def _lookup(value):
+    type_name = value.type.name
+    wide = is_wide_string(type_name)
+    if wide is not None:
+        return _StringPrinter(value, wide)
+
+    resolved = value.type.strip_typedefs()
+    literal = None
+    if resolved.code == gdb.TYPE_CODE_INT:
+        literal = format_number(resolved.name, int(value))
+    if literal is not None:
+        return _LiteralPrinter(literal)
+
+    if resolved.code == gdb.TYPE_CODE_STRUCT:
+        instance = find_instance(resolved.tag or resolved.name)
+        if instance is not None:
+            return _InstancePrinter(value, instance)
+    return None
+
+
+gdb.pretty_printers.append(_lookup)
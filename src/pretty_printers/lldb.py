@@ -0,0 +1,68 @@
+# registers the LLDB summaries and synthetic children, load them with
+# `command script import <application>_lldb.py`
+import lldb
+
+
+def number_summary(value, internal_dict):
+    type_name = value.GetType().GetCanonicalType().GetName()
+    literal = format_number(type_name, value.GetValueAsSigned())
+    return literal if literal is not None else value.GetValue()
+
+
+def string_summary(value, internal_dict):
+    wide = bool(is_wide_string(value.GetTypeName()))
+    characters = (
+        value.GetChildAtIndex(it).GetValueAsUnsigned() for it in range(value.GetNumChildren())
+    )
+    return format_string(characters, wide)
+
+
+def instance_summary(value, internal_dict):
+    instance = find_instance(value.GetType().GetCanonicalType().GetName())
+    return "%s %s" % (instance[0], instance[1]) if instance else ""
+
+
+class InstanceProvider:
+    """shows the members of a POU instance, VAR_IN_OUT members are dereferenced and the
+    members generated by the compiler are hidden"""
+
+    def __init__(self, value, internal_dict):
+        self.value = value
+        self.members = []
+
+    def update(self):
+        instance = find_instance(self.value.GetType().GetCanonicalType().GetName())
+        self.members = instance[2] if instance else []
+        return False
+
+    def num_children(self):
+        return len(self.members)
+
+    def get_child_index(self, name):
+        names = [member for member, _ in self.members]
+        return names.index(name) if name in names else -1
+
+    def get_child_at_index(self, index):
+        if index < 0 or index >= len(self.members):
+            return None
+        member, is_reference = self.members[index]
+        child = self.value.GetChildMemberWithName(member)
+        if is_reference and child.GetValueAsUnsigned() != 0:
+            child = child.Dereference()
+        return child
+
+
+def __lldb_init_module(debugger, internal_dict):
+    module = __name__
+    for type_name in NUMBER_FORMATS:
+        debugger.HandleCommand(
+            "type summary add -F %s.number_summary %s" % (module, type_name)
+        )
+    debugger.HandleCommand(
+        "type summary add -x '^W?STRING\\[[0-9]+\\]$' -F %s.string_summary" % module
+    )
+    for kind, name, _ in INSTANCES.values():
+        debugger.HandleCommand("type summary add -F %s.instance_summary %s" % (module, name))
+        debugger.HandleCommand(
+            "type synthetic add -l %s.InstanceProvider %s" % (module, name)
+        )
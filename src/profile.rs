@@ -0,0 +1,135 @@
+//! language profiles restrict the accepted language to a subset, e.g. the safety profile rejects
+//! the constructs that cannot be assessed by the certification of safety related runtimes.
+//!
+//! the violations are reported as errors and listed in a conformance report, the report states
+//! for every rule of the profile whether the sources conform to it
+use std::fmt::{Display, Formatter};
+
+use clap::ArgEnum;
+
+use crate::{simulation::symbols::SourceFile, Diagnostic};
+
+/// a subset of the language the sources have to conform to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ArgEnum)]
+pub enum Profile {
+    /// rejects pointers, dynamic memory, recursion, variadic POUs and unbounded loops
+    Safety,
+}
+
+impl Profile {
+    /// returns the rules the sources have to conform to
+    pub fn get_rules(&self) -> &'static [SafetyRule] {
+        match self {
+            Profile::Safety => &[
+                SafetyRule::Pointers,
+                SafetyRule::DynamicMemory,
+                SafetyRule::Recursion,
+                SafetyRule::Variadics,
+                SafetyRule::UnboundedLoops,
+            ],
+        }
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Profile::Safety => write!(f, "safety"),
+        }
+    }
+}
+
+/// a rule of the safety profile
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SafetyRule {
+    /// pointer variables (`REF_TO`, `POINTER TO`) and taking addresses with `REF` or `ADR`
+    Pointers,
+    /// calls to the allocator (`malloc`, `calloc`, `realloc` and `free`)
+    DynamicMemory,
+    /// POUs calling themselves, directly or through other POUs
+    Recursion,
+    /// POUs declaring or calling with a variable number of arguments (`...`)
+    Variadics,
+    /// `WHILE` and `REPEAT` loops, whose number of iterations is not bounded
+    UnboundedLoops,
+}
+
+impl SafetyRule {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            SafetyRule::Pointers => "pointers",
+            SafetyRule::DynamicMemory => "dynamic memory",
+            SafetyRule::Recursion => "recursion",
+            SafetyRule::Variadics => "variadics",
+            SafetyRule::UnboundedLoops => "unbounded loops",
+        }
+    }
+}
+
+/// a violation of a rule, located in a source file
+struct Violation {
+    rule: SafetyRule,
+    location: String,
+    message: String,
+}
+
+/// the result of checking the sources against a profile
+pub struct ConformanceReport {
+    profile: Profile,
+    violations: Vec<Violation>,
+}
+
+impl ConformanceReport {
+    pub fn new(profile: Profile) -> ConformanceReport {
+        ConformanceReport {
+            profile,
+            violations: vec![],
+        }
+    }
+
+    /// records the given violations of the given file
+    pub fn add(&mut self, file: &SourceFile, violations: &[(SafetyRule, Diagnostic)]) {
+        for (rule, diagnostic) in violations {
+            let line = file.get_line(diagnostic.get_location().get_start());
+            self.violations.push(Violation {
+                rule: *rule,
+                location: format!("{}:{}", file.path, line),
+                message: diagnostic.get_message().to_string(),
+            });
+        }
+    }
+
+    /// returns true if the sources violate none of the profile's rules
+    pub fn is_conformant(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn get_violation_count(&self) -> usize {
+        self.violations.len()
+    }
+}
+
+impl Display for ConformanceReport {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "Conformance to the {} profile", self.profile)?;
+        for rule in self.profile.get_rules() {
+            let count = self.violations.iter().filter(|it| it.rule == *rule).count();
+            let result = if count == 0 {
+                "passed".to_string()
+            } else {
+                format!("failed, {} violation(s)", count)
+            };
+            writeln!(f, "    {:<20} {}", rule.get_name(), result)?;
+        }
+        for violation in &self.violations {
+            writeln!(
+                f,
+                "{}: {}: {}",
+                violation.location,
+                violation.rule.get_name(),
+                violation.message
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{
+    config_file::{load_config, parse_config},
+    diagnostics::Diagnostic,
+    dialect::Dialect,
+    ConfigFormat, FormatOption,
+};
+
+/// the project files looked up in the current directory by `rusty build`
+pub const DEFAULT_PROJECT_FILES: &[&str] = &["plc.json", "rusty.toml"];
+
+/// the project-wide settings of a plc-application. The paths are relative to the project file
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct ProjectConfiguration {
+    /// the dialect the project's sources are written in, every extension is accepted if omitted
+    #[serde(default)]
+    pub dialect: Option<Dialect>,
+    /// the sources compiled by `rusty build`, may be glob expressions like `src/**/*.st`
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// the sources declaring external functions, they are not compiled
+    #[serde(default)]
+    pub includes: Vec<String>,
+    #[serde(default)]
+    pub library_paths: Vec<String>,
+    /// the libraries linked with the compiled sources
+    #[serde(default)]
+    pub libraries: Vec<String>,
+    /// the output file, named after the first source if omitted
+    #[serde(default)]
+    pub output: Option<String>,
+    /// the type of the output (`static`, `shared`, `pic`, `relocatable`, `bitcode` or `ir`)
+    #[serde(default)]
+    pub format: Option<FormatOption>,
+    /// further options passed as on the command line (e.g. `["-O", "none", "-g"]`)
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+impl ProjectConfiguration {
+    /// reads the project file, the format is detected by its extension
+    pub fn load(file: &str) -> Result<ProjectConfiguration, Diagnostic> {
+        load_config(file)
+    }
+
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<ProjectConfiguration, Diagnostic> {
+        parse_config(content, format)
+    }
+
+    /// returns the command line compiling the given project file, its paths are resolved against
+    /// the directory of the project file
+    pub fn get_compile_arguments(&self, project_file: &str) -> Result<Vec<String>, Diagnostic> {
+        if self.files.is_empty() {
+            return Err(Diagnostic::param_error(&format!(
+                "The project {} lists no files",
+                project_file
+            )));
+        }
+        let directory = Path::new(project_file)
+            .parent()
+            .unwrap_or_else(|| Path::new(""));
+        let resolve = |path: &String| directory.join(path).to_string_lossy().to_string();
+
+        let mut arguments = vec!["rustyc".to_string()];
+        arguments.extend(self.files.iter().map(resolve));
+        for (option, values, is_path) in [
+            ("--include", &self.includes, true),
+            ("--library-path", &self.library_paths, true),
+            ("--library", &self.libraries, false),
+        ] {
+            for value in values {
+                arguments.push(option.to_string());
+                arguments.push(if is_path {
+                    resolve(value)
+                } else {
+                    value.to_string()
+                });
+            }
+        }
+        if let Some(output) = &self.output {
+            arguments.push("--output".to_string());
+            arguments.push(resolve(output));
+        }
+        if let Some(format) = self.format {
+            arguments.push(
+                match format {
+                    FormatOption::Static => "--static",
+                    FormatOption::PIC => "--pic",
+                    FormatOption::Shared => "--shared",
+                    FormatOption::Relocatable => "--relocatable",
+                    FormatOption::Bitcode => "--bc",
+                    FormatOption::IR => "--ir",
+                }
+                .to_string(),
+            );
+        }
+        arguments.push(format!("--project={}", project_file));
+        arguments.extend(self.options.iter().cloned());
+        Ok(arguments)
+    }
+}
+
+/// returns the project file of the current directory (see `DEFAULT_PROJECT_FILES`)
+pub fn find_project_file() -> Result<String, Diagnostic> {
+    DEFAULT_PROJECT_FILES
+        .iter()
+        .find(|it| Path::new(it).exists())
+        .map(|it| it.to_string())
+        .ok_or_else(|| {
+            Diagnostic::param_error(&format!(
+                "No project file given and none of {} found",
+                DEFAULT_PROJECT_FILES.join(", ")
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProjectConfiguration;
+    use crate::{dialect::Dialect, ConfigFormat, FormatOption};
+
+    #[test]
+    fn dialect_can_be_selected_in_the_project_file() {
+        let json =
+            ProjectConfiguration::parse(r#"{ "dialect": "codesys" }"#, ConfigFormat::JSON).unwrap();
+        assert_eq!(json.dialect, Some(Dialect::Codesys));
+
+        let toml = ProjectConfiguration::parse(r#"dialect = "iec""#, ConfigFormat::TOML).unwrap();
+        assert_eq!(toml.dialect, Some(Dialect::Iec));
+
+        let empty = ProjectConfiguration::parse("", ConfigFormat::TOML).unwrap();
+        assert_eq!(empty.dialect, None);
+    }
+
+    #[test]
+    fn projects_are_compiled_with_the_listed_sources_and_options() {
+        let toml = ProjectConfiguration::parse(
+            r#"
+            files = ["src/**/*.st"]
+            includes = ["include/io.st"]
+            library_paths = ["lib"]
+            libraries = ["iec61131std"]
+            output = "build/plant.so"
+            format = "shared"
+            options = ["-O", "none", "-g"]
+            "#,
+            ConfigFormat::TOML,
+        )
+        .unwrap();
+        let json = ProjectConfiguration::parse(
+            r#"{
+                "files": ["src/**/*.st"],
+                "includes": ["include/io.st"],
+                "library_paths": ["lib"],
+                "libraries": ["iec61131std"],
+                "output": "build/plant.so",
+                "format": "shared",
+                "options": ["-O", "none", "-g"]
+            }"#,
+            ConfigFormat::JSON,
+        )
+        .unwrap();
+        assert_eq!(toml, json);
+        assert_eq!(toml.format, Some(FormatOption::Shared));
+
+        assert_eq!(
+            toml.get_compile_arguments("plant/plc.toml").unwrap(),
+            vec![
+                "rustyc",
+                "plant/src/**/*.st",
+                "--include",
+                "plant/include/io.st",
+                "--library-path",
+                "plant/lib",
+                "--library",
+                "iec61131std",
+                "--output",
+                "plant/build/plant.so",
+                "--shared",
+                "--project=plant/plc.toml",
+                "-O",
+                "none",
+                "-g",
+            ]
+        );
+    }
+
+    #[test]
+    fn projects_without_sources_cannot_be_compiled() {
+        let project =
+            ProjectConfiguration::parse(r#"dialect = "iec""#, ConfigFormat::TOML).unwrap();
+        assert!(project.get_compile_arguments("plc.toml").is_err());
+    }
+
+    #[test]
+    fn unknown_dialects_are_rejected() {
+        assert!(
+            ProjectConfiguration::parse(r#"{ "dialect": "pascal" }"#, ConfigFormat::JSON).is_err()
+        );
+    }
+}
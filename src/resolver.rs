@@ -16,7 +16,8 @@ use crate::{
         self, AstId, AstStatement, CompilationUnit, DataType, DataTypeDeclaration, GenericBinding,
         LinkageType, Operator, Pou, TypeNature, UserTypeDeclaration, Variable,
     },
-    index::{Index, PouIndexEntry, VariableIndexEntry},
+    builtins::{self, BuiltIn},
+    index::{Index, PouIndexEntry, VariableIndexEntry, SUPER_KEYWORD, THIS_KEYWORD},
     typesystem::{
         self, get_bigger_type, DataTypeInformation, StringEncoding, BOOL_TYPE, BYTE_TYPE,
         DATE_AND_TIME_TYPE, DATE_TYPE, DINT_TYPE, DWORD_TYPE, LINT_TYPE, REAL_TYPE,
@@ -141,6 +142,9 @@ pub enum StatementAnnotation {
     Type { type_name: String },
     /// a reference to a program call or reference (e.g. `PLC_PRG`)
     Program { qualified_name: String },
+    /// the pointer to the current instance inside of a method or function block
+    /// (e.g. `THIS` --> `POINTER_TO_fb`)
+    Instance { resulting_type: String },
 }
 
 impl StatementAnnotation {
@@ -185,6 +189,9 @@ impl From<&PouIndexEntry> for StatementAnnotation {
             PouIndexEntry::Action { name, .. } => StatementAnnotation::Program {
                 qualified_name: name.to_string(),
             },
+            PouIndexEntry::Interface { name, .. } => StatementAnnotation::Type {
+                type_name: name.to_string(),
+            },
         }
     }
 }
@@ -196,6 +203,7 @@ fn get_type_for_annotation<'a>(
     match annotation {
         StatementAnnotation::Value { resulting_type } => Some(resulting_type.as_str()),
         StatementAnnotation::Variable { resulting_type, .. } => Some(resulting_type.as_str()),
+        StatementAnnotation::Instance { resulting_type } => Some(resulting_type.as_str()),
         StatementAnnotation::Function { .. } => None,
         StatementAnnotation::Type { .. } => None,
         StatementAnnotation::Program { .. } => None,
@@ -244,6 +252,31 @@ pub trait AnnotationMap {
             .and_then(|it| get_type_for_annotation(index, it))
     }
 
+    /// returns the value of the given string literal or of the referenced constant, if it is
+    /// initialized with a string literal (e.g. the labels of a CASE over strings)
+    fn get_constant_string<'i>(&'i self, s: &'i AstStatement, index: &'i Index) -> Option<&'i str> {
+        let statement = match self.get(s) {
+            Some(StatementAnnotation::Variable {
+                qualified_name,
+                constant: true,
+                ..
+            }) => index
+                .find_fully_qualified_variable(qualified_name)
+                .and_then(|it| it.initial_value)
+                .and_then(|it| {
+                    index
+                        .get_const_expressions()
+                        .get_resolved_constant_statement(&it)
+                })?,
+            _ => s,
+        };
+        if let AstStatement::LiteralString { value, .. } = statement {
+            Some(value.as_str())
+        } else {
+            None
+        }
+    }
+
     /// returns the name of the callable that is refered by the given statemt
     /// or none if this thing may not be callable
     fn get_call_name(&self, s: &AstStatement) -> Option<&str> {
@@ -299,6 +332,19 @@ impl AstAnnotations {
     pub fn get_bool_id(&self) -> AstId {
         self.bool_id
     }
+
+    /// returns the qualified names of all functions referenced by the annotated statements
+    pub fn get_called_functions(&self) -> impl Iterator<Item = &str> {
+        self.annotation_map
+            .type_map
+            .values()
+            .filter_map(|it| match it {
+                StatementAnnotation::Function { qualified_name, .. } => {
+                    Some(qualified_name.as_str())
+                }
+                _ => None,
+            })
+    }
 }
 
 #[derive(Default)]
@@ -378,7 +424,7 @@ impl AnnotationMap for AnnotationMapImpl {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct StringLiterals {
     pub utf08: HashSet<String>,
     pub utf16: HashSet<String>,
@@ -818,19 +864,18 @@ impl<'i> TypeAnnotator<'i> {
                     .annotation_map
                     .get_type_or_void(reference, self.index)
                     .get_type_information();
-                let inner_type_name = if let DataTypeInformation::Array {
-                    inner_type_name, ..
-                } = array_type
-                {
-                    Some(
-                        self.index
-                            .get_effective_type_by_name(inner_type_name)
-                            .get_name()
-                            .to_string(),
-                    )
-                } else {
-                    None
-                };
+                let inner_type_name = match array_type {
+                    DataTypeInformation::Array {
+                        inner_type_name, ..
+                    } => Some(inner_type_name.as_str()),
+                    _ => array_type.get_vla_dimensions().map(|(it, _)| it),
+                }
+                .map(|it| {
+                    self.index
+                        .get_effective_type_by_name(it)
+                        .get_name()
+                        .to_string()
+                });
 
                 if let Some(inner_type_name) = inner_type_name {
                     self.annotation_map
@@ -944,6 +989,30 @@ impl<'i> TypeAnnotator<'i> {
                             }
                         }
 
+                        Some(target_name)
+                    } else if left_type.get_type_information().is_fixed_point()
+                        || right_type.get_type_information().is_fixed_point()
+                    {
+                        // the other operand is converted into the (left) fixed-point type
+                        let fixed_type = if left_type.get_type_information().is_fixed_point() {
+                            left_type
+                        } else {
+                            right_type
+                        };
+                        let target_name = if operator.is_bool_type() {
+                            BOOL_TYPE.to_string()
+                        } else {
+                            fixed_type.get_name().to_string()
+                        };
+                        let (fixed_is_left, fixed_is_right) =
+                            (fixed_type != right_type, fixed_type != left_type);
+                        let fixed_type = fixed_type.clone();
+                        if fixed_is_left {
+                            self.update_expected_types(&fixed_type, right);
+                        }
+                        if fixed_is_right {
+                            self.update_expected_types(&fixed_type, left);
+                        }
                         Some(target_name)
                     } else if operator.is_bool_type() {
                         Some(BOOL_TYPE.to_string())
@@ -1012,15 +1081,22 @@ impl<'i> TypeAnnotator<'i> {
                         .find_member(qualifier, name)
                         // 2nd try an enum-element qualifier#name
                         .or_else(|| self.index.find_enum_element(qualifier, name.as_str()))
-                        // 3rd try - look for a method qualifier.name
+                        // 3rd try - look for a method qualifier.name (or one inherited by the qualifier)
                         .map_or_else(
-                            || {
-                                self.index
-                                    .find_pou(format!("{}.{}", qualifier, name).as_str())
-                                    .map(|it| it.into())
-                            },
+                            || self.index.find_method(qualifier, name).map(|it| it.into()),
                             |v| Some(to_variable_annotation(v, self.index, ctx.constant)),
                         )
+                } else if let Some(instance_type) = ctx
+                    .pou
+                    .and_then(|pou| get_instance_type(self.index, pou, name))
+                {
+                    // THIS (or SUPER) is a pointer to the current instance (as its super class)
+                    Some(StatementAnnotation::Instance {
+                        resulting_type: add_pointer_type(
+                            &mut self.annotation_map.new_index,
+                            instance_type.to_string(),
+                        ),
+                    })
                 } else {
                     // if we see no qualifier, we try some strategies ...
                     ctx.pou
@@ -1054,13 +1130,8 @@ impl<'i> TypeAnnotator<'i> {
                             // ... then try if we find a scoped-pou with that name (maybe it's a call to a local method or action?)
                             ctx.pou
                                 .and_then(|pou_name| self.index.find_pou(pou_name))
-                                .and_then(|it| {
-                                    self.index
-                                        .find_pou(
-                                            format!("{}.{}", it.get_container(), name).as_str(),
-                                        )
-                                        .map(Into::into)
-                                })
+                                .and_then(|it| self.index.find_method(it.get_container(), name))
+                                .map(Into::into)
                         })
                         .or_else(|| {
                             // ... then try if we find a global-pou with that name (maybe it's a call to a function or program?)
@@ -1089,7 +1160,8 @@ impl<'i> TypeAnnotator<'i> {
                         .annotation_map
                         .get(s)
                         .map(|it| match it {
-                            StatementAnnotation::Value { resulting_type } => {
+                            StatementAnnotation::Value { resulting_type }
+                            | StatementAnnotation::Instance { resulting_type } => {
                                 (resulting_type.as_str(), false)
                             }
                             StatementAnnotation::Variable {
@@ -1144,6 +1216,10 @@ impl<'i> TypeAnnotator<'i> {
                 }
                 self.update_right_hand_side_expected_type(left, right);
             }
+            AstStatement::ReferenceAssignment { left, right, .. } => {
+                //the reference is bound to the right side's variable, its value is not converted
+                visit_all_statements!(self, ctx, left, right);
+            }
             AstStatement::CallStatement {
                 parameters,
                 operator,
@@ -1206,6 +1282,29 @@ impl<'i> TypeAnnotator<'i> {
                                 }
                             }
                         }
+                        //the arguments of a generic variadic parameter (e.g. `args : T...`) are candidates as well
+                        let index = self.index;
+                        if let Some(DataTypeInformation::Generic {
+                            generic_symbol,
+                            nature,
+                            ..
+                        }) = index
+                            .find_pou_type(&operator_qualifier)
+                            .and_then(|it| it.get_type_information().get_variadic_type())
+                            .and_then(|it| index.find_effective_type_info(it))
+                        {
+                            let declared_parameters =
+                                all_members.iter().filter(|it| it.is_parameter()).count();
+                            for p in parameters.iter().skip(declared_parameters) {
+                                self.annotation_map.add_generic_nature(p, *nature);
+                                if let Some(candidate) = self.annotation_map.get_type(p, index) {
+                                    generics_candidates
+                                        .entry(generic_symbol.to_string())
+                                        .or_insert_with(std::vec::Vec::new)
+                                        .push(candidate.get_name().to_string());
+                                }
+                            }
+                        }
                     }
                 }
                 for (p, name) in params {
@@ -1224,7 +1323,13 @@ impl<'i> TypeAnnotator<'i> {
                 if let Some(StatementAnnotation::Function { return_type, .. }) =
                     self.annotation_map.get(operator)
                 {
-                    if let Some(return_type) = self.index.find_effective_type(return_type) {
+                    if let Some(return_type) =
+                        self.index.find_effective_type(return_type).or_else(|| {
+                            self.annotation_map
+                                .new_index
+                                .find_effective_type(return_type)
+                        })
+                    {
                         self.annotation_map.annotate(
                             statement,
                             StatementAnnotation::value(return_type.get_name()),
@@ -1318,10 +1423,46 @@ impl<'i> TypeAnnotator<'i> {
         ctx: VisitorContext,
     ) {
         if let Some(PouIndexEntry::Function {
-            generics, linkage, ..
+            generics,
+            linkage,
+            return_type,
+            ..
         }) = self.index.find_pou(implementation_name)
         {
-            if linkage != &LinkageType::BuiltIn && !generics.is_empty() {
+            if linkage == &LinkageType::BuiltIn {
+                //Builtins are not specialized, but a generic result (e.g. SEL) takes the derived type
+                //unless the builtin annotates its calls itself (e.g. CONCAT)
+                if let Some(annotate) =
+                    builtins::get_builtin(implementation_name).and_then(BuiltIn::get_annotation)
+                {
+                    let parameters = parameters
+                        .as_ref()
+                        .map(ast::flatten_expression_list)
+                        .unwrap_or_default();
+                    annotate(self, operator, &parameters);
+                } else if let DataTypeInformation::Generic { generic_symbol, .. } =
+                    self.index.get_type_information_or_void(return_type)
+                {
+                    let generic_map = &self.derive_generic_types(generics, generics_candidates);
+                    if let Some(real_type) = generic_map.get(generic_symbol) {
+                        self.annotation_map.annotate(
+                            operator,
+                            StatementAnnotation::Function {
+                                qualified_name: implementation_name.to_string(),
+                                return_type: real_type.to_string(),
+                            },
+                        );
+                    }
+                    //the generic parameters are converted to the derived type
+                    if let Some(s) = parameters.as_ref() {
+                        self.update_generic_function_parameters(
+                            s,
+                            implementation_name,
+                            generic_map,
+                        );
+                    }
+                }
+            } else if !generics.is_empty() {
                 let generic_map = &self.derive_generic_types(generics, generics_candidates);
                 //Annotate the statement with the new function call
                 if let Some(StatementAnnotation::Function {
@@ -1365,6 +1506,43 @@ impl<'i> TypeAnnotator<'i> {
         }
     }
 
+    /// annotates a call to CONCAT with a string type that can hold all of its operands
+    /// e.g. `CONCAT(STRING[10], 'abc')` results in a STRING[13]
+    pub(crate) fn annotate_concat_result(
+        &mut self,
+        implementation_name: &str,
+        operator: &AstStatement,
+        operands: &[&AstStatement],
+    ) {
+        let mut is_wide = None;
+        let mut length = 0;
+        for operand in operands.iter().copied() {
+            if let Some(DataTypeInformation::String { size, encoding }) = self
+                .annotation_map
+                .get_type(operand, self.index)
+                .map(typesystem::DataType::get_type_information)
+            {
+                //the first string decides the width, mixed widths are reported by the validator
+                is_wide.get_or_insert(*encoding == StringEncoding::Utf16);
+                length += size
+                    .as_int_value(self.index)
+                    .map(|it| it - 1)
+                    .unwrap_or(typesystem::DEFAULT_STRING_LEN as i64);
+            }
+        }
+        if let Some(is_wide) = is_wide {
+            let return_type =
+                register_string_type(&mut self.annotation_map.new_index, is_wide, length as usize);
+            self.annotation_map.annotate(
+                operator,
+                StatementAnnotation::Function {
+                    qualified_name: implementation_name.to_string(),
+                    return_type,
+                },
+            );
+        }
+    }
+
     /// douplicates the given generic_function under the `new_name` (e.g. foo__INT__REAL)using the
     /// real datatypes for the generics as given in `generics` (e.g. { T=INT, U=REAL})
     pub fn register_generic_pou_entries(
@@ -1674,7 +1852,7 @@ fn register_string_type(index: &mut Index, is_wide: bool, len: usize) -> String
         index.register_type(crate::typesystem::DataType {
             name: new_type_name.clone(),
             initial_value: None,
-            nature: TypeNature::Chars,
+            nature: TypeNature::String,
             information: crate::typesystem::DataTypeInformation::String {
                 encoding: if is_wide {
                     StringEncoding::Utf16
@@ -1688,6 +1866,18 @@ fn register_string_type(index: &mut Index, is_wide: bool, len: usize) -> String
     new_type_name
 }
 
+/// returns the type `THIS` or `SUPER` point to inside of the given pou or `None` if the given name
+/// is neither of them or the pou has no such instance
+fn get_instance_type<'i>(index: &'i Index, pou_name: &str, name: &str) -> Option<&'i str> {
+    if name.eq_ignore_ascii_case(THIS_KEYWORD) {
+        index.find_type_of_this(pou_name)
+    } else if name.eq_ignore_ascii_case(SUPER_KEYWORD) {
+        index.find_super_class_of_method(pou_name)
+    } else {
+        None
+    }
+}
+
 /// adds a pointer to the given inner_type to the given index and return's its name
 fn add_pointer_type(index: &mut Index, inner_type_name: String) -> String {
     let new_type_name = format!("POINTER_TO_{}", inner_type_name.as_str());
@@ -1,7 +1,8 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use crate::{
     ast::{AstStatement, Operator, SourceRange},
+    defines::Defines,
     index::{
         const_expressions::{ConstExpression, ConstId},
         Index,
@@ -134,6 +135,22 @@ impl UnresolvableConstant {
     pub fn no_initial_value(id: &ConstId) -> Self {
         UnresolvableConstant::new(*id, "No initial value")
     }
+
+    /// the constant is part of the given cycle of constants, e.g. `[a, b, a]`
+    pub fn circular_dependency(id: &ConstId, cycle: &[&str]) -> Self {
+        UnresolvableConstant::new(
+            *id,
+            format!("Circular dependency: {}", cycle.join(" -> ")).as_str(),
+        )
+    }
+
+    /// the constant depends on the given constant that is part of a circular dependency
+    pub fn depends_on_circular_dependency(id: &ConstId, constant: &str) -> Self {
+        UnresolvableConstant::new(
+            *id,
+            format!("Depends on the circular dependency of '{}'", constant).as_str(),
+        )
+    }
 }
 
 /// returns true, if the given expression needs to be evaluated.
@@ -273,16 +290,170 @@ pub fn evaluate_constants(mut index: Index) -> (Index, Vec<UnresolvableConstant>
         }
     }
 
-    //import all constants that were note resolved in the loop above
-    unresolvable.extend(
-        remaining_constants
-            .iter()
-            .map(UnresolvableConstant::incomplete_initialzation),
-    );
+    //import all constants that were note resolved in the loop above, the ones that are part of
+    //(or depend on) a circular dependency are reported with the constants involved
+    let remaining_constants: Vec<ConstId> = remaining_constants.into_iter().collect();
+    let dependencies = get_dependencies(&remaining_constants, &index);
+    for candidate in &remaining_constants {
+        let unresolved = match find_cycle(candidate, &dependencies) {
+            Some(cycle) => UnresolvableConstant::circular_dependency(
+                candidate,
+                &cycle.iter().map(String::as_str).collect::<Vec<_>>(),
+            ),
+            None => match find_dependency_in_cycle(candidate, &dependencies) {
+                Some(constant) => {
+                    UnresolvableConstant::depends_on_circular_dependency(candidate, &constant)
+                }
+                None => {
+                    unresolvable.push(UnresolvableConstant::incomplete_initialzation(candidate));
+                    continue;
+                }
+            },
+        };
+        index
+            .get_mut_const_expressions()
+            .mark_unresolvable(candidate, unresolved.reason.as_str())
+            .expect("unknown id for const-expression"); //panic if we dont know the id
+        unresolvable.push(unresolved);
+    }
 
     (index, unresolvable)
 }
 
+/// the constants referenced by a constant, by their qualified name
+type Dependencies = HashMap<ConstId, Vec<(String, ConstId)>>;
+
+/// returns the constants referenced by each of the given constants, limited to the given constants
+fn get_dependencies(constants: &[ConstId], index: &Index) -> Dependencies {
+    let candidates: HashSet<&ConstId> = constants.iter().collect();
+    constants
+        .iter()
+        .map(|id| {
+            let mut references = vec![];
+            if let Some(expression) = index.get_const_expressions().find_const_expression(id) {
+                collect_referenced_constants(
+                    expression.get_statement(),
+                    expression.get_qualifier(),
+                    index,
+                    &mut references,
+                );
+            }
+            references.retain(|(_, it)| candidates.contains(it));
+            (*id, references)
+        })
+        .collect()
+}
+
+/// collects the constants referenced by the given const-expression
+pub(crate) fn collect_referenced_constants(
+    statement: &AstStatement,
+    scope: Option<&str>,
+    index: &Index,
+    references: &mut Vec<(String, ConstId)>,
+) {
+    let variable = match statement {
+        AstStatement::Reference { name, .. } => {
+            index.find_variable(scope, std::slice::from_ref(&name.as_str()))
+        }
+        AstStatement::QualifiedReference { elements, .. } => match elements.as_slice() {
+            [AstStatement::Reference { name: pou_name, .. }, AstStatement::Reference { name, .. }] => {
+                index.find_member(pou_name, name)
+            }
+            _ => None,
+        },
+        AstStatement::BinaryExpression { left, right, .. }
+        | AstStatement::RangeStatement {
+            start: left,
+            end: right,
+            ..
+        } => {
+            collect_referenced_constants(left, scope, index, references);
+            collect_referenced_constants(right, scope, index, references);
+            None
+        }
+        AstStatement::UnaryExpression { value, .. }
+        | AstStatement::MultipliedStatement { element: value, .. }
+        | AstStatement::Assignment { right: value, .. } => {
+            collect_referenced_constants(value, scope, index, references);
+            None
+        }
+        AstStatement::ExpressionList { expressions, .. } => {
+            for expression in expressions {
+                collect_referenced_constants(expression, scope, index, references);
+            }
+            None
+        }
+        AstStatement::LiteralArray {
+            elements: Some(elements),
+            ..
+        } => {
+            collect_referenced_constants(elements, scope, index, references);
+            None
+        }
+        _ => None,
+    };
+    if let Some((variable, initial_value)) =
+        variable.and_then(|it| it.initial_value.map(|initial_value| (it, initial_value)))
+    {
+        references.push((variable.get_qualified_name().to_string(), initial_value));
+    }
+}
+
+/// returns the names of the constants of the shortest cycle leading from the given constant back
+/// to itself, starting and ending with the given constant (e.g. `[a, b, a]`)
+fn find_cycle(constant: &ConstId, dependencies: &Dependencies) -> Option<Vec<String>> {
+    //the name of a constant is known from the constants referencing it
+    let name_of = |id: &ConstId| {
+        dependencies
+            .values()
+            .flatten()
+            .find(|(_, it)| it == id)
+            .map(|(name, _)| name.clone())
+    };
+    let mut predecessors: HashMap<ConstId, ConstId> = HashMap::new();
+    let mut queue = VecDeque::from([*constant]);
+    while let Some(current) = queue.pop_front() {
+        for (_, dependency) in dependencies.get(&current).into_iter().flatten() {
+            if dependency == constant {
+                let mut path = vec![];
+                let mut step = current;
+                while step != *constant {
+                    path.push(name_of(&step)?);
+                    step = predecessors[&step];
+                }
+                let name = name_of(constant)?;
+                let mut cycle = vec![name.clone()];
+                cycle.extend(path.into_iter().rev());
+                cycle.push(name);
+                return Some(cycle);
+            }
+            if !predecessors.contains_key(dependency) {
+                predecessors.insert(*dependency, current);
+                queue.push_back(*dependency);
+            }
+        }
+    }
+    None
+}
+
+/// returns the name of the first constant that is part of a circular dependency and that is
+/// referenced by the given constant, directly or indirectly
+fn find_dependency_in_cycle(constant: &ConstId, dependencies: &Dependencies) -> Option<String> {
+    let mut visited = HashSet::from([*constant]);
+    let mut queue = VecDeque::from([*constant]);
+    while let Some(current) = queue.pop_front() {
+        for (name, dependency) in dependencies.get(&current).into_iter().flatten() {
+            if visited.insert(*dependency) {
+                if find_cycle(dependency, dependencies).is_some() {
+                    return Some(name.clone());
+                }
+                queue.push_back(*dependency);
+            }
+        }
+    }
+    None
+}
+
 /// transforms the given literal to better fit the datatype of the candidate
 /// effectively this casts an IntLiteral to a RealLiteral if necessary
 fn cast_if_necessary(
@@ -593,6 +764,26 @@ pub fn evaluate(
                 }
             })
         }
+        AstStatement::CallStatement {
+            operator,
+            parameters,
+            id,
+            location,
+        } => match operator.as_ref() {
+            AstStatement::Reference { name, .. } if Defines::is_query(name) => {
+                let arguments = parameters
+                    .as_ref()
+                    .as_ref()
+                    .map(AstStatement::get_as_list)
+                    .unwrap_or_default();
+                Some(
+                    index
+                        .get_defines()
+                        .evaluate_query(name, &arguments, *id, location.clone())?,
+                )
+            }
+            _ => return Err(format!("Cannot resolve constant: {:#?}", initial)),
+        },
         _ => return Err(format!("Cannot resolve constant: {:#?}", initial)),
     };
     Ok(literal)
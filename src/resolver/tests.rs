@@ -3,3 +3,4 @@ mod resolve_control_statments;
 mod resolve_expressions_tests;
 mod resolve_generic_calls;
 mod resolve_literals_tests;
+mod resolve_namespaces_tests;
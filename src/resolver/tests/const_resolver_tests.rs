@@ -1,4 +1,5 @@
 use crate::ast::{AstStatement, SourceRange};
+use crate::defines::Defines;
 use crate::index::const_expressions::ConstExpression;
 use crate::index::Index;
 
@@ -1103,13 +1104,13 @@ fn evaluating_constants_can_handle_recursion() {
     // WHEN compile-time evaluation is applied
     let (index, unresolvable) = evaluate_constants(index);
 
-    // THEN a,b,c,d could not be resolved (ciruclar dependency)
+    // THEN a,b,c,d could not be resolved (a and d form a cycle, b and c depend on it)
     debug_assert_eq!(
         vec![
-            UnresolvableConstant::incomplete_initialzation(&global!(index, "a")),
-            UnresolvableConstant::incomplete_initialzation(&global!(index, "b")),
-            UnresolvableConstant::incomplete_initialzation(&global!(index, "c")),
-            UnresolvableConstant::incomplete_initialzation(&global!(index, "d")),
+            UnresolvableConstant::circular_dependency(&global!(index, "a"), &["a", "d", "a"]),
+            UnresolvableConstant::depends_on_circular_dependency(&global!(index, "b"), "a"),
+            UnresolvableConstant::depends_on_circular_dependency(&global!(index, "c"), "a"),
+            UnresolvableConstant::circular_dependency(&global!(index, "d"), &["d", "a", "d"]),
         ],
         unresolvable
     );
@@ -1490,3 +1491,54 @@ fn nested_array_literals_multiplied_statement_type_resolving() {
         unreachable!()
     }
 }
+
+#[test]
+fn defines_are_queried_at_compile_time() {
+    // GIVEN constants initialized with the defined symbols and the compiler's options
+    let (_, mut index) = index(
+        "VAR_GLOBAL CONSTANT
+            simulation : BOOL := __DEFINED('SIMULATION');
+            hardware : BOOL := NOT __DEFINED('Simulation');
+            axes : STRING := __OPTION(name := 'axes');
+            target : STRING := __OPTION('TARGET');
+            unknown : STRING := __OPTION('unknown');
+            debug : BOOL := __DEFINED('debug');
+        END_VAR
+        ",
+    );
+    index.set_defines(
+        Defines::parse(&["SIMULATION".to_string(), "AXES=4".to_string()])
+            .unwrap()
+            .with_option("target", "x86_64-linux-gnu"),
+    );
+
+    // WHEN compile-time evaluation is applied
+    let (index, unresolvable) = evaluate_constants(index);
+
+    // THEN the queries were replaced by their values
+    debug_assert_eq!(EMPTY, unresolvable);
+    debug_assert_eq!(
+        &create_bool_literal(true),
+        find_connstant_value(&index, "simulation").unwrap()
+    );
+    debug_assert_eq!(
+        &create_bool_literal(false),
+        find_connstant_value(&index, "hardware").unwrap()
+    );
+    debug_assert_eq!(
+        &create_string_literal("4", false),
+        find_connstant_value(&index, "axes").unwrap()
+    );
+    debug_assert_eq!(
+        &create_string_literal("x86_64-linux-gnu", false),
+        find_connstant_value(&index, "target").unwrap()
+    );
+    debug_assert_eq!(
+        &create_string_literal("", false),
+        find_connstant_value(&index, "unknown").unwrap()
+    );
+    debug_assert_eq!(
+        &create_bool_literal(false),
+        find_connstant_value(&index, "debug").unwrap()
+    );
+}
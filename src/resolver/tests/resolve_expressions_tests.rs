@@ -2522,6 +2522,174 @@ fn class_method_gets_annotated() {
     assert_type_and_hint!(&annotations, &index, &body[3], "SINT", None);
 }
 
+#[test]
+fn inherited_methods_and_members_get_annotated() {
+    let (unit, mut index) = index(
+        "
+    CLASS Drive
+        VAR speed : INT; END_VAR
+        METHOD stop : BOOL
+            speed := 0;
+        END_METHOD
+    END_CLASS
+
+    CLASS Motor EXTENDS Drive
+        VAR rpm : DINT; END_VAR
+        METHOD run
+            speed;
+            stop();
+        END_METHOD
+    END_CLASS
+
+    PROGRAM prg
+        VAR m : Motor; END_VAR
+        m.speed;
+        m.stop();
+    END_PROGRAM
+        ",
+    );
+
+    let annotations = annotate(&unit, &mut index);
+
+    //inside of the extending class' method
+    let body = &unit.implementations[1].statements;
+    assert_eq!(
+        Some(&StatementAnnotation::Variable {
+            resulting_type: "INT".into(),
+            qualified_name: "Motor.speed".into(),
+            constant: false,
+            is_auto_deref: false,
+        }),
+        annotations.get(&body[0])
+    );
+    if let AstStatement::CallStatement { operator, .. } = &body[1] {
+        assert_eq!(
+            Some(&StatementAnnotation::Function {
+                return_type: "BOOL".into(),
+                qualified_name: "Drive.stop".into(),
+            }),
+            annotations.get(operator)
+        );
+    } else {
+        unreachable!()
+    }
+
+    //on an instance of the extending class
+    let body = &unit.implementations[2].statements;
+    assert_type_and_hint!(&annotations, &index, &body[0], "INT", None);
+    if let AstStatement::CallStatement { operator, .. } = &body[1] {
+        assert_eq!(
+            Some(&StatementAnnotation::Function {
+                return_type: "BOOL".into(),
+                qualified_name: "Drive.stop".into(),
+            }),
+            annotations.get(operator)
+        );
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn super_refers_to_the_instance_as_its_super_class() {
+    let (unit, mut index) = index(
+        "
+    CLASS Drive
+        METHOD stop : BOOL END_METHOD
+    END_CLASS
+
+    CLASS Motor EXTENDS Drive
+        METHOD OVERRIDE stop : BOOL
+            SUPER^.stop();
+        END_METHOD
+    END_CLASS
+
+    PROGRAM prg
+        VAR super : INT; END_VAR
+        SUPER^.stop();
+    END_PROGRAM
+        ",
+    );
+
+    let annotations = annotate(&unit, &mut index);
+
+    //inside of the overriding method SUPER^ is the Drive part of the instance
+    let body = &unit.implementations[1].statements;
+    if let AstStatement::CallStatement { operator, .. } = &body[0] {
+        assert_eq!(
+            Some(&StatementAnnotation::Function {
+                return_type: "BOOL".into(),
+                qualified_name: "Drive.stop".into(),
+            }),
+            annotations.get(operator)
+        );
+        if let AstStatement::QualifiedReference { elements, .. } = operator.as_ref() {
+            assert_eq!(
+                Some(&StatementAnnotation::value("Drive")),
+                annotations.get(&elements[0])
+            );
+        } else {
+            unreachable!()
+        }
+    } else {
+        unreachable!()
+    }
+
+    //outside of a method SUPER is an ordinary reference
+    let body = &unit.implementations[2].statements;
+    if let AstStatement::CallStatement { operator, .. } = &body[0] {
+        assert_eq!(None, annotations.get(operator));
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn this_points_to_the_current_instance() {
+    let (unit, mut index) = index(
+        "
+    FUNCTION_BLOCK fb
+        VAR x : INT; END_VAR
+        THIS;
+        THIS^.x;
+    END_FUNCTION_BLOCK
+
+    CLASS cls
+        METHOD m
+            THIS;
+        END_METHOD
+    END_CLASS
+
+    FUNCTION foo : INT
+        THIS;
+    END_FUNCTION
+        ",
+    );
+
+    let annotations = annotate(&unit, &mut index);
+
+    let body = &unit.implementations[0].statements;
+    assert_eq!(
+        Some(&StatementAnnotation::Instance {
+            resulting_type: "POINTER_TO_fb".into()
+        }),
+        annotations.get(&body[0])
+    );
+    assert_type_and_hint!(&annotations, &index, &body[1], "INT", None);
+
+    let body = &unit.implementations[1].statements;
+    assert_eq!(
+        Some(&StatementAnnotation::Instance {
+            resulting_type: "POINTER_TO_cls".into()
+        }),
+        annotations.get(&body[0])
+    );
+
+    //functions have no instance
+    let body = &unit.implementations[2].statements;
+    assert_eq!(None, annotations.get(&body[0]));
+}
+
 #[test]
 fn nested_bitwise_access_resolves_correctly() {
     let (unit, mut index) = index(
@@ -0,0 +1,138 @@
+use crate::{
+    ast::AstStatement,
+    resolver::{AnnotationMap, StatementAnnotation},
+    test_utils::tests::{annotate, index},
+};
+
+fn reference_name(statement: &AstStatement) -> &str {
+    match statement {
+        AstStatement::Reference { name, .. } => name.as_str(),
+        AstStatement::Assignment { left, .. } => reference_name(left),
+        AstStatement::CallStatement { operator, .. } => reference_name(operator),
+        _ => panic!("unexpected statement {:?}", statement),
+    }
+}
+
+#[test]
+fn references_are_resolved_within_their_namespace() {
+    let (unit, _) = index(
+        "
+        NAMESPACE plant
+            VAR_GLOBAL speed : INT; END_VAR
+            FUNCTION start : INT END_FUNCTION
+            PROGRAM Main
+                speed := start();
+            END_PROGRAM
+        END_NAMESPACE
+        ",
+    );
+
+    let statements = &unit.implementations[1].statements;
+    assert_eq!(reference_name(&statements[0]), "plant.speed");
+    if let AstStatement::Assignment { right, .. } = &statements[0] {
+        assert_eq!(reference_name(right), "plant.start");
+    } else {
+        panic!("expected an assignment, found {:?}", statements[0]);
+    }
+}
+
+#[test]
+fn qualified_references_are_collapsed_into_the_declared_name() {
+    let (unit, _) = index(
+        "
+        NAMESPACE plant
+            PROGRAM Main VAR x : INT; END_VAR END_PROGRAM
+        END_NAMESPACE
+        PROGRAM other
+            plant.Main.x;
+        END_PROGRAM
+        ",
+    );
+
+    let statements = &unit.implementations[1].statements;
+    if let AstStatement::QualifiedReference { elements, .. } = &statements[0] {
+        let names = elements.iter().map(reference_name).collect::<Vec<_>>();
+        assert_eq!(names, vec!["plant.Main", "x"]);
+    } else {
+        panic!("expected a qualified reference, found {:?}", statements[0]);
+    }
+}
+
+#[test]
+fn using_directives_make_declarations_visible() {
+    let (unit, _) = index(
+        "
+        NAMESPACE util
+            FUNCTION start : INT END_FUNCTION
+        END_NAMESPACE
+        NAMESPACE plant
+            FUNCTION run : INT END_FUNCTION
+        END_NAMESPACE
+        USING plant;
+        PROGRAM Main
+        USING util;
+            start();
+            run();
+        END_PROGRAM
+        ",
+    );
+
+    let statements = &unit.implementations[2].statements;
+    assert_eq!(reference_name(&statements[0]), "util.start");
+    assert_eq!(reference_name(&statements[1]), "plant.run");
+}
+
+#[test]
+fn local_variables_shadow_namespace_declarations() {
+    let (unit, _) = index(
+        "
+        NAMESPACE plant
+            VAR_GLOBAL speed : INT; END_VAR
+            FUNCTION_BLOCK Motor
+            VAR speed : INT; END_VAR
+                speed;
+            END_FUNCTION_BLOCK
+        END_NAMESPACE
+        ",
+    );
+
+    let statements = &unit.implementations[0].statements;
+    assert_eq!(reference_name(&statements[0]), "speed");
+}
+
+#[test]
+fn namespaced_types_and_calls_are_annotated() {
+    let (unit, mut index) = index(
+        "
+        NAMESPACE plant
+            TYPE Point : STRUCT x : INT; END_STRUCT END_TYPE
+            FUNCTION start : INT END_FUNCTION
+        END_NAMESPACE
+        PROGRAM Main
+        VAR p : plant.Point; END_VAR
+            p;
+            plant.start();
+        END_PROGRAM
+        ",
+    );
+    let annotations = annotate(&unit, &mut index);
+
+    let statements = &unit.implementations[1].statements;
+    assert_eq!(
+        annotations
+            .get_type_or_void(&statements[0], &index)
+            .get_name(),
+        "plant.Point"
+    );
+    if let AstStatement::CallStatement { operator, .. } = &statements[1] {
+        assert_eq!(
+            annotations.get(operator),
+            Some(&StatementAnnotation::Function {
+                return_type: "INT".into(),
+                qualified_name: "plant.start".into(),
+            })
+        );
+    } else {
+        panic!("expected a call, found {:?}", statements[1]);
+    }
+}
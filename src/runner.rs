@@ -3,7 +3,10 @@ use inkwell::{
     execution_engine::{ExecutionEngine, JitFunction},
 };
 
-use crate::{compile_module, diagnostics::Diagnostician, SourceCode, SourceContainer};
+use crate::{
+    compile_module_with_options, diagnostics::Diagnostician, ModuleOptions, SourceCode,
+    SourceContainer,
+};
 
 type MainFunction<T, U> = unsafe extern "C" fn(*mut T) -> U;
 type MainEmptyFunction<U> = unsafe extern "C" fn() -> U;
@@ -87,18 +90,30 @@ pub fn run_no_param<U>(exec_engine: &ExecutionEngine, name: &str) -> U {
 ///
 pub fn compile<T: Compilable>(context: &Context, source: T) -> ExecutionEngine {
     let source = source.containers();
-    let (_, code_gen) = compile_module(
+    //the JIT runs on the host, so the runtime's function blocks are available
+    let (_, code_gen) = compile_module_with_options(
         context,
         source,
         vec![],
         None,
         Diagnostician::null_diagnostician(),
+        &ModuleOptions {
+            runtime_library: true,
+            ..ModuleOptions::default()
+        },
     )
     .unwrap();
-    code_gen
+    let engine = code_gen
         .module
         .create_jit_execution_engine(inkwell::OptimizationLevel::None)
-        .unwrap()
+        .unwrap();
+    //builtins like the string conversions call the runtime's helpers
+    for (name, address) in rusty_runtime::get_helpers() {
+        if let Some(helper) = code_gen.module.get_function(name) {
+            engine.add_global_mapping(&helper, address);
+        }
+    }
+    engine
 }
 
 ///
@@ -0,0 +1,463 @@
+//! runs an application in the JIT for development: the tasks of the task configuration are
+//! executed cyclically in simulated time (see `rusty run`)
+//!
+//! a simulation can be reloaded between two cycles, the sources are then recompiled and the tasks
+//! continue with the new code. Global variables and program instances whose memory layout did
+//! not change keep their values, so logic can be changed while the application is running.
+//!
+//! symbols can be watched and forced to a value (see `force`) while the simulation runs. The
+//! execution time of every task is measured against its interval, see `profiler`. The state of a
+//! simulation can be saved as `Snapshot` and restored later, e.g. to replay a fault state.
+//!
+//! acceptance tests describe the inputs and expected outputs per cycle, see `scenario`. Test
+//! functions are run with generated inputs, see `properties`.
+//!
+//! a simulation compiled with debug hooks can be debugged on source level, see `debugger`.
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use encoding_rs::Encoding;
+use inkwell::{
+    context::Context,
+    execution_engine::{ExecutionEngine, JitFunction},
+    OptimizationLevel,
+};
+
+use crate::{
+    codegen::generators::abi_generator,
+    compile_module_with_options,
+    diagnostics::{Diagnostic, Diagnostician},
+    dialect::Dialect,
+    index::Index,
+    task_configuration::TaskConfiguration,
+    FilePath, ModuleOptions, SourceCode, SourceContainer,
+};
+
+pub mod dap;
+pub mod debugger;
+pub mod force;
+mod profiler;
+pub mod properties;
+mod reload;
+pub mod scenario;
+mod snapshot;
+pub mod symbols;
+
+pub use profiler::TaskTime;
+pub use reload::ReloadReport;
+pub use snapshot::{RestoreReport, Snapshot, SnapshotVariable};
+
+type TaskEntry = unsafe extern "C" fn();
+
+/// a compiled version of the simulated application
+struct Application {
+    symbols: Rc<symbols::SymbolTable>,
+    /// the entry functions of the tasks, by the task's name
+    entries: HashMap<String, JitFunction<'static, TaskEntry>>,
+    //dropped last, the entries and symbols point into the engine's memory
+    _engine: ExecutionEngine<'static>,
+}
+
+/// a task of the task configuration and the simulated time it is released next
+struct SimulatedTask {
+    name: String,
+    priority: u32,
+    interval: u64,
+    /// the external event releasing the task instead of its interval
+    event: Option<u32>,
+    next_release: u64,
+}
+
+/// the sources, includes and settings an application is compiled from
+pub struct SimulationSources {
+    pub sources: Vec<FilePath>,
+    pub includes: Vec<FilePath>,
+    /// sources generated by a tool (e.g. a test harness), compiled after the sources
+    pub generated: Vec<SourceCode>,
+    pub encoding: Option<&'static Encoding>,
+    pub dialect: Dialect,
+    /// instruments the code with the hooks required by the debugger
+    pub debug_hooks: bool,
+}
+
+pub struct Simulation {
+    sources: SimulationSources,
+    configuration: TaskConfiguration,
+    application: Application,
+    tasks: Vec<SimulatedTask>,
+    watcher: reload::SourceWatcher,
+    forces: Rc<RefCell<force::ForceTable>>,
+    /// the execution times of the tasks executed by the last cycle
+    task_times: Vec<TaskTime>,
+    /// the simulated time advanced by every cycle, in milliseconds
+    tick: u64,
+    /// the simulated time in milliseconds
+    time: u64,
+    cycle: u64,
+}
+
+impl Application {
+    fn compile(
+        sources: &SimulationSources,
+        configuration: &TaskConfiguration,
+    ) -> Result<Application, Diagnostic> {
+        //the named types of a context cannot be redefined, so every version is compiled into its
+        //own context which is kept alive for the rest of the session
+        let context: &'static Context = Box::leak(Box::new(Context::create()));
+        let mut units = load_sources(&sources.sources, sources.encoding)?;
+        units.extend(sources.generated.iter().cloned());
+        let includes = load_sources(&sources.includes, sources.encoding)?;
+        let (index, codegen) = compile_module_with_options(
+            context,
+            units.clone(),
+            includes.clone(),
+            sources.encoding,
+            Diagnostician::default(),
+            &ModuleOptions {
+                tasks: Some(configuration),
+                dialect: sources.dialect,
+                debug_hooks: sources.debug_hooks,
+                runtime_library: true,
+                ..ModuleOptions::default()
+            },
+        )?;
+        codegen.generate_task_table(&index, configuration)?;
+        let engine = codegen
+            .module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .map_err(|err| Diagnostic::llvm_error("simulation", &err))?;
+        //the hooks have to be mapped before the engine compiles the module
+        for (name, address) in debugger::get_hooks() {
+            if let Some(hook) = codegen.module.get_function(name) {
+                engine.add_global_mapping(&hook, address);
+            }
+        }
+        //builtins like the string conversions call the runtime's helpers
+        for (name, address) in rusty_runtime::get_helpers() {
+            if let Some(helper) = codegen.module.get_function(name) {
+                engine.add_global_mapping(&helper, address);
+            }
+        }
+
+        let mut entries = HashMap::new();
+        for task in &configuration.tasks {
+            let entry = unsafe { engine.get_function(&format!("__rusty_task_{}", task.name)) }
+                .map_err(|err| {
+                    Diagnostic::param_error(&format!(
+                        "Cannot load the entry of task '{}': {}",
+                        task.name, err
+                    ))
+                })?;
+            entries.insert(task.name.clone(), entry);
+        }
+        let variables = reload::get_state_variables(&codegen.module, &engine, &index);
+        let layouts =
+            abi_generator::get_instance_layouts(&codegen.module, &index, engine.get_target_data());
+        let files = units
+            .iter()
+            .chain(includes.iter())
+            .map(|it| symbols::SourceFile::new(&it.path, &it.source))
+            .collect();
+
+        Ok(Application {
+            symbols: Rc::new(symbols::SymbolTable::new(index, layouts, variables, files)),
+            entries,
+            _engine: engine,
+        })
+    }
+}
+
+/// loads the given files, so the line tables are built from the compiled text
+fn load_sources(
+    files: &[FilePath],
+    encoding: Option<&'static Encoding>,
+) -> Result<Vec<SourceCode>, Diagnostic> {
+    files
+        .iter()
+        .map(|file| {
+            file.clone()
+                .load_source(encoding)
+                .map_err(|err| Diagnostic::io_read_error(file.get_location(), &err))
+        })
+        .collect()
+}
+
+/// seeds the runtime's generator behind `RAND` and `GUID`, so the random numbers of the following
+/// cycles only depend on the seed. The generator is shared by all simulations of the process
+pub fn seed_random(seed: u64) {
+    rusty_runtime::random::seed(seed)
+}
+
+impl Simulation {
+    /// compiles the given sources and prepares the tasks of the configuration, `tick` is the
+    /// simulated time in milliseconds advanced by every cycle
+    pub fn new(
+        sources: SimulationSources,
+        configuration: TaskConfiguration,
+        tick: u64,
+    ) -> Result<Simulation, Diagnostic> {
+        let application = Application::compile(&sources, &configuration)?;
+        let mut tasks = configuration
+            .tasks
+            .iter()
+            .map(|task| SimulatedTask {
+                name: task.name.clone(),
+                priority: task.priority,
+                interval: task.interval,
+                event: task.event,
+                next_release: 0,
+            })
+            .collect::<Vec<_>>();
+        tasks.sort_by_key(|it| it.priority);
+        let watcher = reload::SourceWatcher::new(
+            sources
+                .sources
+                .iter()
+                .chain(sources.includes.iter())
+                .map(|it| it.path.as_str()),
+        );
+
+        Ok(Simulation {
+            sources,
+            configuration,
+            application,
+            tasks,
+            watcher,
+            forces: Rc::new(RefCell::new(force::ForceTable::default())),
+            task_times: vec![],
+            tick: tick.max(1),
+            time: 0,
+            cycle: 0,
+        })
+    }
+
+    /// executes the due tasks in the order of their priority, advances the simulated time by one
+    /// tick and returns the names of the executed tasks
+    pub fn run_cycle(&mut self) -> Vec<String> {
+        debugger::poll();
+        self.task_times.clear();
+        let mut executed = vec![];
+        for task in self.tasks.iter_mut() {
+            let released = match task.event {
+                Some(event) => rusty_runtime::events::take(event),
+                None => self.time >= task.next_release,
+            };
+            if !released {
+                continue;
+            }
+            if let Some(entry) = self.application.entries.get(&task.name) {
+                //not borrowed during the call, the debugger may force symbols while it is stopped
+                self.forces.borrow().apply();
+                profiler::start();
+                let started = Instant::now();
+                unsafe { entry.call() };
+                let elapsed = started.elapsed();
+                let pous = profiler::finish();
+                //a task stopped by the debugger is not measured
+                if !debugger::take_stopped() {
+                    self.task_times.push(TaskTime {
+                        task: task.name.clone(),
+                        elapsed,
+                        budget: Duration::from_millis(task.interval.max(self.tick)),
+                        pous,
+                    });
+                }
+            }
+            //a task without interval runs in every cycle
+            task.next_release += task.interval.max(self.tick);
+            executed.push(task.name.clone());
+        }
+        //the forced values are observed between the cycles too
+        self.forces.borrow().apply();
+        self.time += self.tick;
+        self.cycle += 1;
+        executed
+    }
+
+    /// reloads the application if one of its sources changed since the last check, a failed
+    /// compilation keeps the current version running
+    pub fn reload_if_changed(&mut self) -> Result<Option<ReloadReport>, Diagnostic> {
+        if self.watcher.has_changed() {
+            self.reload().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// recompiles the sources and continues the tasks with the new version, the values of global
+    /// variables and program instances whose layout did not change are carried over, as are the
+    /// values of the members which kept their name and layout
+    pub fn reload(&mut self) -> Result<ReloadReport, Diagnostic> {
+        let application = Application::compile(&self.sources, &self.configuration)?;
+        let report = reload::transfer_state(
+            self.application.symbols.get_variables(),
+            application.symbols.get_variables(),
+        );
+        self.forces.borrow_mut().resolve(&application.symbols);
+        debugger::set_symbols(application.symbols.clone());
+        self.application = application;
+        Ok(report)
+    }
+
+    /// attaches the given handler to the debugger, the sources have to be compiled with debug
+    /// hooks. The handler is attached to the current thread, the thread running the cycles.
+    pub fn attach_debugger(
+        &mut self,
+        handler: Box<dyn debugger::DebugHandler>,
+    ) -> Result<(), Diagnostic> {
+        if !self.sources.debug_hooks {
+            return Err(Diagnostic::param_error(
+                "The simulation was compiled without debug hooks",
+            ));
+        }
+        debugger::attach(
+            handler,
+            self.application.symbols.clone(),
+            self.forces.clone(),
+        );
+        Ok(())
+    }
+
+    /// detaches the debugger from the current thread
+    pub fn detach_debugger(&mut self) {
+        debugger::detach();
+    }
+
+    /// returns the memory of the given global variable or program instance (e.g. `prg_instance`)
+    pub fn get_variable(&self, symbol: &str) -> Option<&[u8]> {
+        self.application
+            .symbols
+            .get_variables()
+            .get(symbol)
+            .map(|it| unsafe { std::slice::from_raw_parts(it.address as *const u8, it.size) })
+    }
+
+    /// raises the given external event, its tasks are executed in the next cycle. The events are
+    /// shared by all simulations of the process (and the application's `RAISE_EVENT`)
+    pub fn raise_event(&mut self, event: u32) {
+        rusty_runtime::events::raise(event)
+    }
+
+    /// executes the given task once, outside of its schedule and without advancing the simulated
+    /// time. Returns false if there is no such task
+    pub fn run_task(&mut self, name: &str) -> bool {
+        match self.application.entries.get(name) {
+            Some(entry) => {
+                self.forces.borrow().apply();
+                unsafe { entry.call() };
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// captures the simulated time and the memory of the global variables and program instances
+    /// which hold no pointers
+    pub fn save_snapshot(&self) -> Snapshot {
+        Snapshot {
+            time: self.time,
+            cycle: self.cycle,
+            releases: self
+                .tasks
+                .iter()
+                .map(|it| (it.name.clone(), it.next_release))
+                .collect(),
+            variables: snapshot::capture(self.application.symbols.get_variables()),
+        }
+    }
+
+    /// continues the simulation from the given snapshot, variables which are not part of the
+    /// snapshot or changed their layout keep their current values
+    pub fn restore_snapshot(&mut self, snapshot: &Snapshot) -> RestoreReport {
+        let report = snapshot::restore(
+            &snapshot.variables,
+            self.application.symbols.get_variables(),
+        );
+        self.time = snapshot.time;
+        self.cycle = snapshot.cycle;
+        for task in self.tasks.iter_mut() {
+            task.next_release = snapshot
+                .releases
+                .get(&task.name)
+                .copied()
+                .unwrap_or(snapshot.time);
+        }
+        self.forces.borrow().apply();
+        report
+    }
+
+    /// returns the current value of the given watch expression (e.g. `prg.counter`) as ST literal
+    pub fn watch(&self, expression: &str) -> Result<String, Diagnostic> {
+        let symbols = &self.application.symbols;
+        symbols
+            .resolve(expression)
+            .map(|it| symbols.format_value(&it))
+            .ok_or_else(|| {
+                Diagnostic::param_error(&format!("Unknown symbol '{}'", expression.trim()))
+            })
+    }
+
+    /// writes the given ST literal to the symbol of the given expression once
+    pub fn write(&mut self, expression: &str, value: &str) -> Result<(), Diagnostic> {
+        let (symbol, value) = force::convert(&self.application.symbols, expression, value)
+            .map_err(|err| Diagnostic::param_error(&err))?;
+        symbols::write_memory(&symbol, &value);
+        Ok(())
+    }
+
+    /// holds the symbol of the given expression at the given ST literal until it is released
+    pub fn force(&mut self, expression: &str, value: &str) -> Result<(), Diagnostic> {
+        self.forces
+            .borrow_mut()
+            .force(&self.application.symbols, expression, value)
+            .map_err(|err| Diagnostic::param_error(&err))
+    }
+
+    /// releases the given forced symbol, returns false if it was not forced
+    pub fn release(&mut self, expression: &str) -> bool {
+        self.forces.borrow_mut().release(expression)
+    }
+
+    pub fn release_all(&mut self) {
+        self.forces.borrow_mut().release_all();
+    }
+
+    /// returns the forced expressions and their values
+    pub fn get_forced(&self) -> Vec<(String, String)> {
+        self.forces.borrow().get_forced()
+    }
+
+    /// the execution times of the tasks executed by the last cycle, see `TaskTime::is_overrun`
+    pub fn get_task_times(&self) -> &[TaskTime] {
+        &self.task_times
+    }
+
+    /// the symbols of the running version
+    pub fn get_symbols(&self) -> &symbols::SymbolTable {
+        &self.application.symbols
+    }
+
+    /// the index of the running version
+    pub fn get_index(&self) -> &Index {
+        self.application.symbols.get_index()
+    }
+
+    /// the simulated time in milliseconds
+    pub fn get_time(&self) -> u64 {
+        self.time
+    }
+
+    /// the number of executed cycles
+    pub fn get_cycle(&self) -> u64 {
+        self.cycle
+    }
+
+    /// the simulated time advanced by every cycle, in milliseconds
+    pub fn get_tick(&self) -> u64 {
+        self.tick
+    }
+}
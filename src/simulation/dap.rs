@@ -0,0 +1,475 @@
+//! a debug adapter connecting an IDE to the simulation's debugger using the Debug Adapter
+//! Protocol (https://microsoft.github.io/debug-adapter-protocol/)
+//!
+//! the adapter supports breakpoints on source lines, stepping over, into and out of POUs and the
+//! inspection of the current POU's members and the global variables. Watch expressions like
+//! `prg.counter` are evaluated against the symbol table and variables can be changed, the debug
+//! console additionally accepts the commands `force <expression> := <value>`,
+//! `release [<expression>]` and `forced`. The client's messages are read on a separate thread,
+//! so requests like `pause` are received while the simulation runs.
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::mpsc::{channel, Receiver},
+};
+
+use serde_json::{json, Value};
+
+use super::{
+    debugger::{DebugHandler, DebugState, Resume, StopReason},
+    symbols::{write_memory, Symbol},
+};
+use crate::diagnostics::Diagnostic;
+
+/// the only thread reported to the client, the simulation executes all tasks on one thread
+const THREAD_ID: i64 = 1;
+
+/// a container of variables handed out to the client while the execution is stopped
+enum VariablesReference {
+    Locals(usize),
+    Globals,
+    Members(Symbol),
+}
+
+pub struct DebugAdapter {
+    requests: Receiver<Value>,
+    writer: Box<dyn Write>,
+    sequence: i64,
+    references: Vec<VariablesReference>,
+    /// set once the client disconnected, the execution is no longer stopped
+    disconnected: bool,
+}
+
+impl DebugAdapter {
+    /// creates an adapter reading the client's messages from `reader` and writing to `writer`
+    pub fn new(reader: impl Read + Send + 'static, writer: Box<dyn Write>) -> DebugAdapter {
+        let (sender, requests) = channel();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            while let Some(message) = read_message(&mut reader) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        DebugAdapter {
+            requests,
+            writer,
+            sequence: 0,
+            references: vec![],
+            disconnected: false,
+        }
+    }
+
+    /// waits for a client connecting to the given port on the local host
+    pub fn listen(port: u16) -> Result<DebugAdapter, Diagnostic> {
+        let io_error = |err: std::io::Error| {
+            Diagnostic::param_error(&format!(
+                "Cannot accept a debugger on port {}: {}",
+                port, err
+            ))
+        };
+        let listener = TcpListener::bind(("127.0.0.1", port)).map_err(io_error)?;
+        let (stream, _) = listener.accept().map_err(io_error)?;
+        let reader = stream.try_clone().map_err(io_error)?;
+        Ok(DebugAdapter::new(reader, Box::new(stream)))
+    }
+
+    fn send(&mut self, mut message: Value) {
+        self.sequence += 1;
+        message["seq"] = json!(self.sequence);
+        let content = message.to_string();
+        //a client that went away is noticed by the reader, the simulation keeps running
+        let _ = write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        )
+        .and_then(|_| self.writer.flush());
+    }
+
+    fn respond(&mut self, request: &Value, success: bool, body: Value) {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": success,
+            "body": body,
+        });
+        if !success {
+            response["message"] = body["error"].clone();
+        }
+        self.send(response);
+    }
+
+    fn send_event(&mut self, event: &str, body: Value) {
+        self.send(json!({ "type": "event", "event": event, "body": body }));
+    }
+
+    /// handles the given request, returns how to continue if the request resumes the execution
+    fn handle(&mut self, state: &mut DebugState, request: &Value) -> Option<Resume> {
+        let arguments = &request["arguments"];
+        match request["command"].as_str().unwrap_or_default() {
+            "initialize" => {
+                self.respond(
+                    request,
+                    true,
+                    json!({
+                        "supportsConfigurationDoneRequest": true,
+                        "supportsEvaluateForHovers": true,
+                        "supportsSetVariable": true,
+                        "supportsTerminateRequest": false,
+                    }),
+                );
+                self.send_event("initialized", json!({}));
+            }
+            "launch" | "attach" | "setExceptionBreakpoints" => {
+                self.respond(request, true, json!({}))
+            }
+            "configurationDone" => {
+                self.respond(request, true, json!({}));
+                return Some(Resume::Continue);
+            }
+            "setBreakpoints" => {
+                let path = arguments["source"]["path"].as_str().unwrap_or_default();
+                let lines = arguments["breakpoints"]
+                    .as_array()
+                    .map(|it| {
+                        it.iter()
+                            .filter_map(|it| it["line"].as_u64())
+                            .map(|it| it as u32)
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let file = state.get_symbols().find_source(path);
+                if let Some(file) = file {
+                    state.set_breakpoints(file, lines.iter().copied());
+                }
+                let breakpoints = lines
+                    .iter()
+                    .map(|line| json!({ "verified": file.is_some(), "line": line }))
+                    .collect::<Vec<_>>();
+                self.respond(request, true, json!({ "breakpoints": breakpoints }));
+            }
+            "threads" => self.respond(
+                request,
+                true,
+                json!({ "threads": [{ "id": THREAD_ID, "name": "simulation" }] }),
+            ),
+            "stackTrace" => {
+                let frames = state
+                    .get_frames()
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .map(|(id, frame)| {
+                        let path = state
+                            .get_symbols()
+                            .get_source(frame.file)
+                            .map(|it| it.path.clone())
+                            .unwrap_or_default();
+                        json!({
+                            "id": id,
+                            "name": frame.pou,
+                            "line": frame.line,
+                            "column": 1,
+                            "source": { "path": path },
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let total = frames.len();
+                self.respond(
+                    request,
+                    true,
+                    json!({ "stackFrames": frames, "totalFrames": total }),
+                );
+            }
+            "scopes" => {
+                let frame = arguments["frameId"].as_u64().unwrap_or_default() as usize;
+                let locals = self.add_reference(VariablesReference::Locals(frame));
+                let globals = self.add_reference(VariablesReference::Globals);
+                self.respond(
+                    request,
+                    true,
+                    json!({ "scopes": [
+                        { "name": "Locals", "variablesReference": locals, "expensive": false },
+                        { "name": "Globals", "variablesReference": globals, "expensive": false },
+                    ]}),
+                );
+            }
+            "variables" => {
+                let reference = arguments["variablesReference"].as_u64().unwrap_or_default();
+                let symbols = self.get_variables(state, reference as usize);
+                let variables = symbols
+                    .into_iter()
+                    .map(|symbol| {
+                        let value = state.get_symbols().format_value(&symbol);
+                        let children = if state.get_symbols().is_structured(&symbol) {
+                            self.add_reference(VariablesReference::Members(symbol.clone()))
+                        } else {
+                            0
+                        };
+                        json!({
+                            "name": symbol.name,
+                            "value": value,
+                            "type": symbol.type_name,
+                            "variablesReference": children,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                self.respond(request, true, json!({ "variables": variables }));
+            }
+            "evaluate" => {
+                let expression = arguments["expression"].as_str().unwrap_or_default();
+                let result = if arguments["context"] == "repl" {
+                    execute_command(state, expression)
+                } else {
+                    None
+                };
+                match result.unwrap_or_else(|| self.evaluate(state, expression)) {
+                    Ok(body) => self.respond(request, true, body),
+                    Err(err) => self.respond(request, false, json!({ "error": err })),
+                }
+            }
+            "setVariable" => {
+                let reference = arguments["variablesReference"].as_u64().unwrap_or_default();
+                let name = arguments["name"].as_str().unwrap_or_default();
+                let value = arguments["value"].as_str().unwrap_or_default();
+                let symbol = self
+                    .get_variables(state, reference as usize)
+                    .into_iter()
+                    .find(|it| it.name == name);
+                let result = match symbol {
+                    Some(symbol) => state
+                        .get_symbols()
+                        .parse_value(&symbol, value)
+                        .map(|value| {
+                            write_memory(&symbol, &value);
+                            symbol
+                        }),
+                    None => Err(format!("Unknown variable '{}'", name)),
+                };
+                match result {
+                    Ok(symbol) => self.respond(
+                        request,
+                        true,
+                        json!({
+                            "value": state.get_symbols().format_value(&symbol),
+                            "type": symbol.type_name,
+                        }),
+                    ),
+                    Err(err) => self.respond(request, false, json!({ "error": err })),
+                }
+            }
+            "continue" => {
+                self.respond(request, true, json!({ "allThreadsContinued": true }));
+                return Some(Resume::Continue);
+            }
+            "next" => {
+                self.respond(request, true, json!({}));
+                return Some(Resume::StepOver);
+            }
+            "stepIn" => {
+                self.respond(request, true, json!({}));
+                return Some(Resume::StepIn);
+            }
+            "stepOut" => {
+                self.respond(request, true, json!({}));
+                return Some(Resume::StepOut);
+            }
+            "pause" => {
+                state.request_pause();
+                self.respond(request, true, json!({}));
+            }
+            "disconnect" => {
+                state.clear_breakpoints();
+                self.disconnected = true;
+                self.respond(request, true, json!({}));
+                return Some(Resume::Continue);
+            }
+            command => self.respond(
+                request,
+                false,
+                json!({ "error": format!("Unsupported request '{}'", command) }),
+            ),
+        }
+        None
+    }
+
+    /// evaluates the given watch expression
+    fn evaluate(&mut self, state: &DebugState, expression: &str) -> Result<Value, String> {
+        let symbols = state.get_symbols();
+        let symbol = symbols
+            .resolve(expression)
+            .ok_or_else(|| format!("Unknown symbol '{}'", expression.trim()))?;
+        let children = if symbols.is_structured(&symbol) {
+            self.add_reference(VariablesReference::Members(symbol.clone()))
+        } else {
+            0
+        };
+        Ok(json!({
+            "result": symbols.format_value(&symbol),
+            "type": symbol.type_name,
+            "variablesReference": children,
+        }))
+    }
+
+    /// registers the given container and returns its (1-based) reference
+    fn add_reference(&mut self, reference: VariablesReference) -> usize {
+        self.references.push(reference);
+        self.references.len()
+    }
+
+    fn get_variables(&self, state: &DebugState, reference: usize) -> Vec<Symbol> {
+        let symbols = state.get_symbols();
+        match reference
+            .checked_sub(1)
+            .and_then(|it| self.references.get(it))
+        {
+            Some(VariablesReference::Locals(frame)) => state
+                .get_frames()
+                .get(*frame)
+                .map(|frame| symbols.get_locals(&frame.pou, frame.instance))
+                .unwrap_or_default(),
+            Some(VariablesReference::Globals) => symbols.get_globals(),
+            Some(VariablesReference::Members(symbol)) => symbols.get_members(symbol),
+            None => vec![],
+        }
+    }
+}
+
+impl DebugHandler for DebugAdapter {
+    fn attached(&mut self, state: &mut DebugState) {
+        //the breakpoints are configured before the application starts
+        while let Ok(request) = self.requests.recv() {
+            if self.handle(state, &request).is_some() {
+                return;
+            }
+        }
+        self.disconnected = true;
+    }
+
+    fn poll(&mut self, state: &mut DebugState) {
+        while let Ok(request) = self.requests.try_recv() {
+            self.handle(state, &request);
+        }
+    }
+
+    fn stopped(&mut self, state: &mut DebugState, reason: StopReason) -> Resume {
+        if self.disconnected {
+            return Resume::Continue;
+        }
+        let reason = match reason {
+            StopReason::Breakpoint => "breakpoint",
+            StopReason::Step => "step",
+            StopReason::Pause => "pause",
+        };
+        self.send_event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        );
+        let resume = loop {
+            match self.requests.recv() {
+                Ok(request) => {
+                    if let Some(resume) = self.handle(state, &request) {
+                        break resume;
+                    }
+                }
+                Err(_) => {
+                    self.disconnected = true;
+                    break Resume::Continue;
+                }
+            }
+        };
+        //the references are only valid while the execution is stopped
+        self.references.clear();
+        resume
+    }
+}
+
+/// executes a command entered in the debug console, returns `None` if the input is no command
+fn execute_command(state: &mut DebugState, input: &str) -> Option<Result<Value, String>> {
+    let input = input.trim();
+    let (command, arguments) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    let arguments = arguments.trim();
+    let result = match command.to_lowercase().as_str() {
+        "force" => {
+            let (expression, value) = arguments.split_once(":=")?;
+            state
+                .force(expression, value)
+                .map(|_| format!("{} forced to {}", expression.trim(), value.trim()))
+        }
+        "release" if arguments.is_empty() => {
+            state.release_all();
+            Ok("all symbols released".to_string())
+        }
+        "release" => {
+            if state.release(arguments) {
+                Ok(format!("{} released", arguments))
+            } else {
+                Err(format!("'{}' is not forced", arguments))
+            }
+        }
+        "forced" if arguments.is_empty() => Ok(state
+            .get_forced()
+            .iter()
+            .map(|(expression, value)| format!("{} := {}", expression, value))
+            .collect::<Vec<_>>()
+            .join("\n")),
+        _ => return None,
+    };
+    Some(result.map(|it| json!({ "result": it, "variablesReference": 0 })))
+}
+
+/// reads the next `Content-Length` framed message, returns `None` once the stream ended
+pub(crate) fn read_message(reader: &mut impl BufRead) -> Option<Value> {
+    loop {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).ok()? == 0 {
+                return None;
+            }
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+        if let Some(length) = length {
+            let mut content = vec![0; length];
+            reader.read_exact(&mut content).ok()?;
+            if let Ok(message) = serde_json::from_slice(&content) {
+                return Some(message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::read_message;
+
+    #[test]
+    fn messages_are_read_by_their_content_length() {
+        let first = r#"{"seq":1,"type":"request","command":"initialize"}"#;
+        let second = r#"{"seq":2,"type":"request","command":"threads"}"#;
+        let stream = format!(
+            "Content-Length: {}\r\n\r\n{}Content-Length: {}\r\n\r\n{}",
+            first.len(),
+            first,
+            second.len(),
+            second
+        );
+        let mut reader = BufReader::new(stream.as_bytes());
+
+        assert_eq!(read_message(&mut reader).unwrap()["command"], "initialize");
+        assert_eq!(read_message(&mut reader).unwrap()["command"], "threads");
+        assert!(read_message(&mut reader).is_none());
+    }
+}
@@ -0,0 +1,296 @@
+//! a source-level debugger for the simulation
+//!
+//! code compiled with debug hooks (see `codegen::generators::debug_generator`) reports the POUs it
+//! enters and the statements it executes. The debugger keeps the stack of active POUs, matches
+//! the statements against the breakpoints and step requests and asks the attached `DebugHandler`
+//! how to continue whenever the execution stops. The hooks run on the simulation's thread, so the
+//! debugger is stored per thread.
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ffi::CStr,
+    os::raw::c_char,
+    rc::Rc,
+};
+
+use super::{force::ForceTable, profiler, symbols::SymbolTable};
+use crate::codegen::generators::debug_generator::{DEBUG_ENTER, DEBUG_LEAVE, DEBUG_STATEMENT};
+
+thread_local! {
+    static DEBUGGER: RefCell<Option<Debugger>> = RefCell::new(None);
+}
+
+/// how the execution continues after a stop
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resume {
+    Continue,
+    /// stops at the next statement of the current POU or a POU it returns to
+    StepOver,
+    /// stops at the next statement, including the statements of called POUs
+    StepIn,
+    /// stops at the next statement of the POU the current POU returns to
+    StepOut,
+}
+
+/// why the execution stopped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    Breakpoint,
+    Step,
+    Pause,
+}
+
+/// an active POU and the location of the statement it currently executes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub pou: String,
+    /// the address of the POU's instance, 0 for functions
+    pub instance: usize,
+    /// the number of the source file, see `SymbolTable::get_source`
+    pub file: usize,
+    /// the 1-based line of the current statement, 0 before the first statement
+    pub line: u32,
+}
+
+/// a pending step and the location it started at
+struct Step {
+    resume: Resume,
+    depth: usize,
+    file: usize,
+    line: u32,
+}
+
+/// the breakpoints, the stack, the symbols and the forced symbols of the debugged application
+pub struct DebugState {
+    /// the breakpoint lines by source file
+    breakpoints: HashMap<usize, HashSet<u32>>,
+    step: Option<Step>,
+    pause_requested: bool,
+    /// the active POUs, the innermost one last
+    frames: Vec<Frame>,
+    symbols: Rc<SymbolTable>,
+    forces: Rc<RefCell<ForceTable>>,
+}
+
+/// the interface between the debugger and a client (e.g. an IDE)
+pub trait DebugHandler {
+    /// called when the handler is attached, before the first cycle runs
+    fn attached(&mut self, _state: &mut DebugState) {}
+
+    /// called between two cycles to process the client's requests
+    fn poll(&mut self, _state: &mut DebugState) {}
+
+    /// called when the execution stops, returns how to continue
+    fn stopped(&mut self, state: &mut DebugState, reason: StopReason) -> Resume;
+}
+
+struct Debugger {
+    state: DebugState,
+    handler: Box<dyn DebugHandler>,
+    /// set when the execution stopped, see `take_stopped`
+    stopped: bool,
+}
+
+impl DebugState {
+    /// replaces the breakpoints of the given source file
+    pub fn set_breakpoints(&mut self, file: usize, lines: impl Iterator<Item = u32>) {
+        self.breakpoints.insert(file, lines.collect());
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// stops the execution at the next statement
+    pub fn request_pause(&mut self) {
+        self.pause_requested = true;
+    }
+
+    /// the active POUs, the innermost one last
+    pub fn get_frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn get_symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    /// holds the symbol of the given expression at the given value until it is released
+    pub fn force(&mut self, expression: &str, value: &str) -> Result<(), String> {
+        self.forces
+            .borrow_mut()
+            .force(&self.symbols, expression, value)
+    }
+
+    /// releases the given forced symbol, returns false if it was not forced
+    pub fn release(&mut self, expression: &str) -> bool {
+        self.forces.borrow_mut().release(expression)
+    }
+
+    pub fn release_all(&mut self) {
+        self.forces.borrow_mut().release_all();
+    }
+
+    /// returns the forced expressions and their values
+    pub fn get_forced(&self) -> Vec<(String, String)> {
+        self.forces.borrow().get_forced()
+    }
+
+    /// returns the reason to stop at the given location of the innermost frame, if any
+    fn get_stop_reason(&self, file: usize, line: u32, line_changed: bool) -> Option<StopReason> {
+        if self.pause_requested {
+            return Some(StopReason::Pause);
+        }
+        if line_changed
+            && self
+                .breakpoints
+                .get(&file)
+                .map_or(false, |it| it.contains(&line))
+        {
+            return Some(StopReason::Breakpoint);
+        }
+        let step = self.step.as_ref()?;
+        let depth = self.frames.len();
+        let moved = file != step.file || line != step.line;
+        let stop = match step.resume {
+            Resume::Continue => false,
+            Resume::StepIn => depth != step.depth || moved,
+            Resume::StepOver => depth < step.depth || (depth == step.depth && moved),
+            Resume::StepOut => depth < step.depth,
+        };
+        if stop {
+            Some(StopReason::Step)
+        } else {
+            None
+        }
+    }
+}
+
+impl Debugger {
+    fn enter(&mut self, pou: String, instance: usize) {
+        self.state.frames.push(Frame {
+            pou,
+            instance,
+            file: 0,
+            line: 0,
+        });
+    }
+
+    fn leave(&mut self) {
+        self.state.frames.pop();
+    }
+
+    fn statement(&mut self, file: usize, offset: usize) {
+        let line = self
+            .state
+            .symbols
+            .get_source(file)
+            .map_or(0, |it| it.get_line(offset));
+        let line_changed = match self.state.frames.last_mut() {
+            Some(frame) => {
+                let changed = frame.file != file || frame.line != line;
+                frame.file = file;
+                frame.line = line;
+                changed
+            }
+            None => return,
+        };
+        if let Some(reason) = self.state.get_stop_reason(file, line, line_changed) {
+            self.state.pause_requested = false;
+            self.stopped = true;
+            let resume = self.handler.stopped(&mut self.state, reason);
+            self.state.step = match resume {
+                Resume::Continue => None,
+                resume => Some(Step {
+                    resume,
+                    depth: self.state.frames.len(),
+                    file,
+                    line,
+                }),
+            };
+        }
+    }
+}
+
+/// attaches the given handler to the code running on this thread
+pub(super) fn attach(
+    handler: Box<dyn DebugHandler>,
+    symbols: Rc<SymbolTable>,
+    forces: Rc<RefCell<ForceTable>>,
+) {
+    let mut debugger = Debugger {
+        state: DebugState {
+            breakpoints: HashMap::new(),
+            step: None,
+            pause_requested: false,
+            frames: vec![],
+            symbols,
+            forces,
+        },
+        handler,
+        stopped: false,
+    };
+    debugger.handler.attached(&mut debugger.state);
+    DEBUGGER.with(|it| *it.borrow_mut() = Some(debugger));
+}
+
+/// detaches the handler attached to this thread
+pub(super) fn detach() {
+    DEBUGGER.with(|it| *it.borrow_mut() = None);
+}
+
+/// lets the attached handler process its requests
+pub(super) fn poll() {
+    with_debugger(|debugger| debugger.handler.poll(&mut debugger.state));
+}
+
+/// replaces the symbols after the application was reloaded
+pub(super) fn set_symbols(symbols: Rc<SymbolTable>) {
+    with_debugger(|debugger| {
+        debugger.state.symbols = symbols;
+        debugger.state.frames.clear();
+    });
+}
+
+/// returns true if the execution stopped since the last call, its timing is then meaningless
+pub(super) fn take_stopped() -> bool {
+    let mut stopped = false;
+    with_debugger(|debugger| stopped = std::mem::take(&mut debugger.stopped));
+    stopped
+}
+
+fn with_debugger(action: impl FnOnce(&mut Debugger)) {
+    DEBUGGER.with(|it| {
+        if let Some(debugger) = it.borrow_mut().as_mut() {
+            action(debugger);
+        }
+    });
+}
+
+/// the hooks called by the instrumented code and their symbols, they serve the debugger and the
+/// profiler
+pub(super) fn get_hooks() -> [(&'static str, usize); 3] {
+    [
+        (DEBUG_ENTER, debug_enter as usize),
+        (DEBUG_STATEMENT, debug_statement as usize),
+        (DEBUG_LEAVE, debug_leave as usize),
+    ]
+}
+
+extern "C" fn debug_enter(pou: *const c_char, instance: *const u8) {
+    if pou.is_null() {
+        return;
+    }
+    let pou = unsafe { CStr::from_ptr(pou) }.to_string_lossy().to_string();
+    profiler::enter(&pou);
+    with_debugger(|debugger| debugger.enter(pou, instance as usize));
+}
+
+extern "C" fn debug_statement(file: i32, offset: i32) {
+    with_debugger(|debugger| debugger.statement(file as usize, offset as usize));
+}
+
+extern "C" fn debug_leave() {
+    profiler::leave();
+    with_debugger(Debugger::leave);
+}
@@ -0,0 +1,88 @@
+//! forced variables: a forced symbol is overwritten with its value before and after every task
+//! until it is released, so neither the application nor its I/O can change it while the system
+//! is commissioned
+use super::symbols::{self, Symbol, SymbolTable};
+
+struct Force {
+    expression: String,
+    /// the value as entered, it is converted again for every reloaded version
+    value: String,
+    /// the symbol and the converted value in the running version, `None` if the expression
+    /// cannot be resolved in it
+    target: Option<(Symbol, Vec<u8>)>,
+}
+
+/// the forced symbols of a simulation, shared with its debugger
+#[derive(Default)]
+pub struct ForceTable {
+    forces: Vec<Force>,
+}
+
+impl ForceTable {
+    /// forces the symbol of the given expression to the given value and writes the value
+    /// immediately, forcing a forced symbol again replaces its value
+    pub fn force(
+        &mut self,
+        symbols: &SymbolTable,
+        expression: &str,
+        value: &str,
+    ) -> Result<(), String> {
+        let target = convert(symbols, expression, value)?;
+        symbols::write_memory(&target.0, &target.1);
+        self.release(expression);
+        self.forces.push(Force {
+            expression: expression.trim().to_string(),
+            value: value.trim().to_string(),
+            target: Some(target),
+        });
+        Ok(())
+    }
+
+    /// releases the given symbol, returns false if it was not forced
+    pub fn release(&mut self, expression: &str) -> bool {
+        let len = self.forces.len();
+        self.forces
+            .retain(|it| !it.expression.eq_ignore_ascii_case(expression.trim()));
+        self.forces.len() != len
+    }
+
+    pub fn release_all(&mut self) {
+        self.forces.clear();
+    }
+
+    /// returns the forced expressions and their values
+    pub fn get_forced(&self) -> Vec<(String, String)> {
+        self.forces
+            .iter()
+            .map(|it| (it.expression.clone(), it.value.clone()))
+            .collect()
+    }
+
+    /// writes the forced values
+    pub(super) fn apply(&self) {
+        for (symbol, value) in self.forces.iter().filter_map(|it| it.target.as_ref()) {
+            symbols::write_memory(symbol, value);
+        }
+    }
+
+    /// resolves the forced expressions in a reloaded version, forces that cannot be resolved
+    /// are suspended until a later version declares their symbol again
+    pub(super) fn resolve(&mut self, symbols: &SymbolTable) {
+        for force in self.forces.iter_mut() {
+            force.target = convert(symbols, &force.expression, &force.value).ok();
+        }
+    }
+}
+
+/// resolves the given expression and converts the value into the symbol's representation
+pub(super) fn convert(
+    symbols: &SymbolTable,
+    expression: &str,
+    value: &str,
+) -> Result<(Symbol, Vec<u8>), String> {
+    let symbol = symbols
+        .resolve(expression)
+        .ok_or_else(|| format!("Unknown symbol '{}'", expression.trim()))?;
+    let value = symbols.parse_value(&symbol, value)?;
+    Ok((symbol, value))
+}
@@ -0,0 +1,115 @@
+//! measures the execution time of the simulated tasks against their cycle time
+//!
+//! the time of a whole task is always measured. Code compiled with debug hooks additionally
+//! reports the POUs it enters and leaves, the time spent in every POU (excluding the POUs it
+//! calls) is then attributed to it, so an overrun can be traced to the most expensive POUs. The
+//! hooks themselves add some overhead to the measured times.
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    time::{Duration, Instant},
+};
+
+thread_local! {
+    static PROFILER: RefCell<Option<Profiler>> = RefCell::new(None);
+}
+
+/// the number of POUs reported with an overrun
+const REPORTED_POUS: usize = 3;
+
+/// the measured execution time of a task in one cycle
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskTime {
+    pub task: String,
+    pub elapsed: Duration,
+    /// the task's interval, or the simulation's tick for a task running in every cycle
+    pub budget: Duration,
+    /// the time spent in the task's POUs (excluding their callees), the most expensive first.
+    /// Empty if the code was compiled without debug hooks
+    pub pous: Vec<(String, Duration)>,
+}
+
+impl TaskTime {
+    pub fn is_overrun(&self) -> bool {
+        self.elapsed > self.budget
+    }
+}
+
+impl Display for TaskTime {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "task '{}' took {:?} of its {:?} budget",
+            self.task, self.elapsed, self.budget
+        )?;
+        if !self.pous.is_empty() {
+            let pous = self
+                .pous
+                .iter()
+                .take(REPORTED_POUS)
+                .map(|(pou, elapsed)| format!("{} {:?}", pou, elapsed))
+                .collect::<Vec<_>>();
+            write!(f, ", most expensive: {}", pous.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// a POU entered but not yet left
+struct Call {
+    pou: String,
+    entered: Instant,
+    /// the time spent in the POUs it called
+    callees: Duration,
+}
+
+#[derive(Default)]
+struct Profiler {
+    calls: Vec<Call>,
+    times: HashMap<String, Duration>,
+}
+
+/// starts attributing the execution time to the POUs entered on this thread
+pub(super) fn start() {
+    PROFILER.with(|it| *it.borrow_mut() = Some(Profiler::default()));
+}
+
+/// stops the profiling and returns the time spent per POU, the most expensive first
+pub(super) fn finish() -> Vec<(String, Duration)> {
+    let profiler = PROFILER.with(|it| it.borrow_mut().take());
+    let mut times = profiler
+        .map(|it| it.times.into_iter().collect::<Vec<_>>())
+        .unwrap_or_default();
+    times.sort_by(|(a_pou, a), (b_pou, b)| b.cmp(a).then_with(|| a_pou.cmp(b_pou)));
+    times
+}
+
+/// called by the debug hook when a POU is entered
+pub(super) fn enter(pou: &str) {
+    PROFILER.with(|it| {
+        if let Some(profiler) = it.borrow_mut().as_mut() {
+            profiler.calls.push(Call {
+                pou: pou.to_string(),
+                entered: Instant::now(),
+                callees: Duration::ZERO,
+            });
+        }
+    });
+}
+
+/// called by the debug hook when a POU returns
+pub(super) fn leave() {
+    PROFILER.with(|it| {
+        if let Some(profiler) = it.borrow_mut().as_mut() {
+            if let Some(call) = profiler.calls.pop() {
+                let elapsed = call.entered.elapsed();
+                *profiler.times.entry(call.pou).or_default() +=
+                    elapsed.saturating_sub(call.callees);
+                if let Some(caller) = profiler.calls.last_mut() {
+                    caller.callees += elapsed;
+                }
+            }
+        }
+    });
+}
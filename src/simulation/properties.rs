@@ -0,0 +1,607 @@
+//! property-based tests: test functions returning `BOOL` are called with generated inputs
+//! (see `rusty test`), a failing case is shrunk to a minimal counterexample
+//!
+//! a function is marked as test by the `{test}` pragma or by `{test_range}` pragmas limiting
+//! the values generated for its inputs, the pragmas precede the function:
+//!
+//! ```text
+//! {test_range speed := 0..100}
+//! {test_range factor := -1.5..1.5}
+//! FUNCTION scaled_speed_is_limited : BOOL
+//! VAR_INPUT speed : INT; factor : REAL; active : BOOL; END_VAR
+//!     scaled_speed_is_limited := ABS(scale(speed, factor)) <= 150;
+//! END_FUNCTION
+//! ```
+//!
+//! the ranges include their bounds, integer and `BOOL` inputs without range cover their type's
+//! range. Every test is called by a generated program executed as its own task in the JIT.
+use std::{
+    fmt::{Display, Formatter},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use regex::{Captures, Regex};
+
+use super::{Simulation, SimulationSources};
+use crate::{
+    ast::LinkageType,
+    diagnostics::{Diagnostic, Diagnostician},
+    index::{Index, VariableType},
+    lexer::IdProvider,
+    task_configuration::{Task, TaskConfiguration},
+    typesystem::DataTypeInformation,
+    SourceCode, SourceContainer,
+};
+
+/// the prefix of the generated programs calling the tests
+const HARNESS_PREFIX: &str = "__property_";
+/// the generated program's variable receiving the test's result
+const RESULT: &str = "__result";
+/// the number of evaluations spent to shrink a failing case
+const SHRINK_BUDGET: usize = 1000;
+
+/// the inclusive range of values generated for an input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Range {
+    Integer(i128, i128),
+    Real(f64, f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Integer(i128),
+    Real(f64),
+}
+
+/// a test function and the ranges of its inputs, as declared by its pragmas
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyTest {
+    pub name: String,
+    pub ranges: Vec<(String, Range)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertySettings {
+    /// the number of cases generated per test
+    pub cases: usize,
+    /// the seed of the generated values, a failure is reproduced by the same seed
+    pub seed: u64,
+}
+
+impl Default for PropertySettings {
+    fn default() -> Self {
+        PropertySettings {
+            cases: 100,
+            seed: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |it| it.as_nanos() as u64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyResult {
+    pub name: String,
+    /// the number of cases run until the test failed or passed
+    pub cases: usize,
+    pub seed: u64,
+    /// the shrunk inputs the test failed for and their values
+    pub counterexample: Option<Vec<(String, String)>>,
+}
+
+impl PropertyResult {
+    pub fn is_passed(&self) -> bool {
+        self.counterexample.is_none()
+    }
+}
+
+impl Display for PropertyResult {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match &self.counterexample {
+            None => write!(f, "property '{}' passed ({} cases)", self.name, self.cases),
+            Some(inputs) => {
+                let inputs = inputs
+                    .iter()
+                    .map(|(input, value)| format!("{} := {}", input, value))
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    "property '{}' failed after {} cases (seed {}) for ({})",
+                    self.name,
+                    self.cases,
+                    self.seed,
+                    inputs.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Value::Integer(value) => write!(f, "{}", value),
+            //always with decimal point, so the value reads as REAL literal
+            Value::Real(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+impl Range {
+    /// parses a range like `0..100` or `-0.5..0.5`
+    fn parse(text: &str) -> Option<Range> {
+        let (start, end) = text.split_once("..")?;
+        let (start, end) = (start.trim(), end.trim());
+        let range = match (start.parse::<i128>(), end.parse::<i128>()) {
+            (Ok(start), Ok(end)) => Range::Integer(start, end),
+            _ => Range::Real(start.parse().ok()?, end.parse().ok()?),
+        };
+        match range {
+            Range::Integer(start, end) if start > end => None,
+            Range::Real(start, end) if start.is_nan() || end.is_nan() || start > end => None,
+            range => Some(range),
+        }
+    }
+
+    /// the values every test starts with: the bounds and zero
+    fn get_edges(&self) -> Vec<Value> {
+        let mut edges = vec![];
+        for edge in [self.get_start(), self.get_end(), self.get_origin()] {
+            if !edges.contains(&edge) {
+                edges.push(edge);
+            }
+        }
+        edges
+    }
+
+    fn get_start(&self) -> Value {
+        match self {
+            Range::Integer(start, _) => Value::Integer(*start),
+            Range::Real(start, _) => Value::Real(*start),
+        }
+    }
+
+    fn get_end(&self) -> Value {
+        match self {
+            Range::Integer(_, end) => Value::Integer(*end),
+            Range::Real(_, end) => Value::Real(*end),
+        }
+    }
+
+    /// the value failing cases are shrunk to, zero or the bound closest to it
+    fn get_origin(&self) -> Value {
+        match self {
+            Range::Integer(start, end) => Value::Integer(0.clamp(*start, *end)),
+            Range::Real(start, end) => Value::Real(0f64.clamp(*start, *end)),
+        }
+    }
+
+    fn generate(&self, random: &mut Random) -> Value {
+        match self {
+            Range::Integer(start, end) => {
+                let length = (end - start) as u128 + 1;
+                Value::Integer(start + (random.next_value() as u128 % length) as i128)
+            }
+            Range::Real(start, end) => {
+                let fraction = (random.next_value() >> 11) as f64 / (1u64 << 53) as f64;
+                Value::Real(start + (end - start) * fraction)
+            }
+        }
+    }
+
+    /// returns the candidates to shrink the given value to, the smallest first
+    fn shrink(&self, value: Value) -> Vec<Value> {
+        let mut candidates = vec![];
+        match (self.get_origin(), value) {
+            (Value::Integer(origin), Value::Integer(value)) => {
+                let mut distance = value - origin;
+                while distance != 0 {
+                    candidates.push(Value::Integer(value - distance));
+                    distance /= 2;
+                }
+            }
+            (Value::Real(origin), Value::Real(value)) => {
+                let truncated = value.trunc();
+                if truncated != value && self.contains(truncated) {
+                    candidates.push(Value::Real(truncated));
+                }
+                let mut distance = value - origin;
+                for _ in 0..32 {
+                    if distance == 0.0 {
+                        break;
+                    }
+                    candidates.push(Value::Real(value - distance));
+                    distance /= 2.0;
+                }
+            }
+            _ => {}
+        }
+        candidates
+    }
+
+    fn contains(&self, value: f64) -> bool {
+        match self {
+            Range::Integer(start, end) => *start as f64 <= value && value <= *end as f64,
+            Range::Real(start, end) => *start <= value && value <= *end,
+        }
+    }
+}
+
+/// a xorshift generator, reproducible for a seed
+struct Random(u64);
+
+impl Random {
+    fn next_value(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// finds the test functions declared in the given source by their pragmas
+pub fn find_tests(source: &str) -> Result<Vec<PropertyTest>, String> {
+    let declarations = Regex::new(
+        r"(?i)\{\s*(test_range|test)\b([^}]*)\}|\b(FUNCTION_BLOCK|PROGRAM|CLASS|FUNCTION)\s+([A-Za-z_][A-Za-z0-9_]*)",
+    )
+    .expect("valid regex");
+    let mut tests = vec![];
+    let mut pending: Option<Vec<(String, Range)>> = None;
+    for captures in declarations.captures_iter(source) {
+        match get_group(&captures, 1).to_lowercase().as_str() {
+            "test" => {
+                pending.get_or_insert_with(Vec::new);
+            }
+            "test_range" => {
+                let declaration = get_group(&captures, 2);
+                let range = declaration
+                    .split_once(":=")
+                    .and_then(|(input, range)| Some((input.trim(), Range::parse(range)?)))
+                    .filter(|(input, _)| !input.is_empty())
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid test range '{}', expected '<input> := <start>..<end>'",
+                            declaration.trim()
+                        )
+                    })?;
+                pending
+                    .get_or_insert_with(Vec::new)
+                    .push((range.0.to_string(), range.1));
+            }
+            //the pragmas belong to the following POU
+            _ => {
+                let ranges = pending.take();
+                if let Some(ranges) =
+                    ranges.filter(|_| get_group(&captures, 3).eq_ignore_ascii_case("FUNCTION"))
+                {
+                    tests.push(PropertyTest {
+                        name: get_group(&captures, 4).to_string(),
+                        ranges,
+                    });
+                }
+            }
+        }
+    }
+    Ok(tests)
+}
+
+fn get_group<'a>(captures: &Captures<'a>, group: usize) -> &'a str {
+    captures.get(group).map_or("", |it| it.as_str())
+}
+
+/// runs the test functions declared in the given sources, returns no results if the sources
+/// declare no tests
+pub fn run_tests(
+    mut sources: SimulationSources,
+    settings: &PropertySettings,
+) -> Result<Vec<PropertyResult>, Diagnostic> {
+    let mut tests = vec![];
+    let mut units = vec![];
+    for file in &sources.sources {
+        let unit = file
+            .clone()
+            .load_source(sources.encoding)
+            .map_err(|err| Diagnostic::io_read_error(file.get_location(), &err))?;
+        tests.extend(find_tests(&unit.source).map_err(|err| {
+            Diagnostic::param_error(&format!("{}: {}", file.get_location(), err))
+        })?);
+        units.push(unit);
+    }
+    if tests.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let (index, _) = crate::parse_and_index(
+        units,
+        sources.encoding,
+        &IdProvider::default(),
+        &mut Diagnostician::null_diagnostician(),
+        LinkageType::Internal,
+        sources.dialect,
+    )?;
+    let tests = tests
+        .into_iter()
+        .map(|test| complete_ranges(&index, test))
+        .collect::<Result<Vec<_>, _>>()?;
+    sources.generated.push(SourceCode {
+        source: tests
+            .iter()
+            .map(|it| generate_harness(&index, it))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        path: "<property tests>".to_string(),
+    });
+    let configuration = TaskConfiguration {
+        tasks: tests
+            .iter()
+            .map(|it| Task {
+                name: it.name.clone(),
+                priority: 0,
+                interval: 0,
+                programs: vec![format!("{}{}", HARNESS_PREFIX, it.name)],
+                ..Default::default()
+            })
+            .collect(),
+        instances: vec![],
+        telemetry: None,
+    };
+    let mut simulation = Simulation::new(sources, configuration, 1)?;
+    tests
+        .iter()
+        .map(|test| run_test(&mut simulation, test, settings))
+        .collect()
+}
+
+/// checks the test's signature and adds the default ranges of the inputs without range
+fn complete_ranges(index: &Index, test: PropertyTest) -> Result<PropertyTest, Diagnostic> {
+    let error =
+        |message: String| Diagnostic::param_error(&format!("Test '{}': {}", test.name, message));
+    let returns_bool = index
+        .find_return_type(&test.name)
+        .map_or(false, |it| it.get_type_information().is_bool());
+    if !returns_bool {
+        return Err(error(
+            "A test has to be a function returning BOOL".to_string(),
+        ));
+    }
+    let inputs = get_inputs(index, &test.name);
+    if let Some((input, _)) = test
+        .ranges
+        .iter()
+        .find(|(input, _)| !inputs.iter().any(|(it, _)| it.eq_ignore_ascii_case(input)))
+    {
+        return Err(error(format!("Unknown input '{}'", input)));
+    }
+    let mut ranges = vec![];
+    for (input, type_name) in inputs {
+        let declared = test
+            .ranges
+            .iter()
+            .find(|(it, _)| it.eq_ignore_ascii_case(&input))
+            .map(|(_, range)| *range);
+        let range = declared
+            .or_else(|| get_type_range(index, &type_name))
+            .ok_or_else(|| error(format!("The input '{}' requires a test_range", input)))?;
+        ranges.push((input, range));
+    }
+    Ok(PropertyTest {
+        name: test.name,
+        ranges,
+    })
+}
+
+/// returns the names and types of the given function's inputs
+fn get_inputs(index: &Index, function: &str) -> Vec<(String, String)> {
+    index
+        .get_container_members(function)
+        .into_iter()
+        .filter(|it| it.get_variable_type() == VariableType::Input)
+        .map(|it| (it.get_name().to_string(), it.get_type_name().to_string()))
+        .collect()
+}
+
+/// returns the range of the given integer or `BOOL` type
+fn get_type_range(index: &Index, type_name: &str) -> Option<Range> {
+    let type_information = index.find_effective_type_info(type_name)?;
+    match type_information {
+        _ if type_information.is_bool() => Some(Range::Integer(0, 1)),
+        DataTypeInformation::Integer { signed, size, .. } if *size <= 64 => Some(if *signed {
+            Range::Integer(-(1i128 << (size - 1)), (1i128 << (size - 1)) - 1)
+        } else {
+            Range::Integer(0, (1i128 << size) - 1)
+        }),
+        _ => None,
+    }
+}
+
+/// generates the program calling the given test with its instance's variables
+fn generate_harness(index: &Index, test: &PropertyTest) -> String {
+    let inputs = get_inputs(index, &test.name);
+    let variables = inputs
+        .iter()
+        .map(|(input, type_name)| format!("    {} : {};\n", input, type_name))
+        .collect::<String>();
+    let arguments = inputs
+        .iter()
+        .map(|(input, _)| format!("{0} := {0}", input))
+        .collect::<Vec<_>>();
+    format!(
+        "PROGRAM {prefix}{test}\nVAR\n{variables}    {result} : BOOL;\nEND_VAR\n    {result} := {test}({arguments});\nEND_PROGRAM\n",
+        prefix = HARNESS_PREFIX,
+        test = test.name,
+        variables = variables,
+        result = RESULT,
+        arguments = arguments.join(", ")
+    )
+}
+
+fn run_test(
+    simulation: &mut Simulation,
+    test: &PropertyTest,
+    settings: &PropertySettings,
+) -> Result<PropertyResult, Diagnostic> {
+    //every test has its own sequence, so adding a test does not change the others' cases
+    let mut random = Random(
+        test.name
+            .bytes()
+            .fold(settings.seed ^ 0x9e37_79b9_7f4a_7c15, |hash, it| {
+                (hash ^ it as u64).wrapping_mul(0x0100_0000_01b3)
+            })
+            .max(1),
+    );
+    let edges = test
+        .ranges
+        .iter()
+        .map(|(_, range)| range.get_edges())
+        .collect::<Vec<_>>();
+    let mut result = PropertyResult {
+        name: test.name.clone(),
+        cases: 0,
+        seed: settings.seed,
+        counterexample: None,
+    };
+    for case in 0..settings.cases.max(1) {
+        let values = test
+            .ranges
+            .iter()
+            .zip(edges.iter())
+            .map(|((_, range), edges)| {
+                edges
+                    .get(case)
+                    .copied()
+                    .unwrap_or_else(|| range.generate(&mut random))
+            })
+            .collect::<Vec<_>>();
+        result.cases += 1;
+        if !evaluate(simulation, test, &values)? {
+            let values = shrink(simulation, test, values)?;
+            result.counterexample = Some(
+                test.ranges
+                    .iter()
+                    .zip(values.iter())
+                    .map(|((input, _), value)| {
+                        let symbol = format!("{}{}.{}", HARNESS_PREFIX, test.name, input);
+                        simulation.write(&symbol, &value.to_string())?;
+                        Ok((input.clone(), simulation.watch(&symbol)?))
+                    })
+                    .collect::<Result<_, Diagnostic>>()?,
+            );
+            break;
+        }
+        //a test without inputs has a single case
+        if test.ranges.is_empty() {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+/// calls the test with the given values, returns its result
+fn evaluate(
+    simulation: &mut Simulation,
+    test: &PropertyTest,
+    values: &[Value],
+) -> Result<bool, Diagnostic> {
+    let harness = format!("{}{}", HARNESS_PREFIX, test.name);
+    for ((input, _), value) in test.ranges.iter().zip(values) {
+        simulation.write(&format!("{}.{}", harness, input), &value.to_string())?;
+    }
+    simulation.run_task(&test.name);
+    Ok(simulation.watch(&format!("{}.{}", harness, RESULT))? == "TRUE")
+}
+
+/// moves the values of a failing case towards zero as long as the test keeps failing
+fn shrink(
+    simulation: &mut Simulation,
+    test: &PropertyTest,
+    mut values: Vec<Value>,
+) -> Result<Vec<Value>, Diagnostic> {
+    let mut budget = SHRINK_BUDGET;
+    let mut shrunk = true;
+    while shrunk && budget > 0 {
+        shrunk = false;
+        for (input, (_, range)) in test.ranges.iter().enumerate() {
+            for candidate in range.shrink(values[input]) {
+                if budget == 0 {
+                    break;
+                }
+                budget -= 1;
+                let mut case = values.clone();
+                case[input] = candidate;
+                if !evaluate(simulation, test, &case)? {
+                    values = case;
+                    shrunk = true;
+                    break;
+                }
+            }
+        }
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_tests, PropertyTest, Range, Value};
+
+    #[test]
+    fn tests_are_found_by_their_pragmas() {
+        let tests = find_tests(
+            "{test}
+            FUNCTION plain : BOOL plain := TRUE; END_FUNCTION
+            FUNCTION helper : INT END_FUNCTION
+            {test_range x := -5..5}
+            {TEST_RANGE factor := 0.5 .. 1.5}
+            FUNCTION ranged : BOOL
+            VAR_INPUT x : INT; factor : REAL; END_VAR
+            END_FUNCTION
+            {test}
+            FUNCTION_BLOCK not_a_test END_FUNCTION_BLOCK
+            FUNCTION after_block : BOOL END_FUNCTION",
+        )
+        .unwrap();
+
+        assert_eq!(
+            tests,
+            vec![
+                PropertyTest {
+                    name: "plain".to_string(),
+                    ranges: vec![]
+                },
+                PropertyTest {
+                    name: "ranged".to_string(),
+                    ranges: vec![
+                        ("x".to_string(), Range::Integer(-5, 5)),
+                        ("factor".to_string(), Range::Real(0.5, 1.5))
+                    ]
+                },
+            ]
+        );
+        assert!(find_tests("{test_range x := 5..1} FUNCTION f : BOOL END_FUNCTION").is_err());
+        assert!(find_tests("{test_range 0..1} FUNCTION f : BOOL END_FUNCTION").is_err());
+    }
+
+    #[test]
+    fn failing_values_are_shrunk_towards_zero() {
+        let range = Range::Integer(-10, 100);
+        assert_eq!(
+            range.shrink(Value::Integer(40)),
+            vec![
+                Value::Integer(0),
+                Value::Integer(20),
+                Value::Integer(30),
+                Value::Integer(35),
+                Value::Integer(38),
+                Value::Integer(39)
+            ]
+        );
+        assert_eq!(Range::Integer(5, 10).get_origin(), Value::Integer(5));
+        assert_eq!(
+            Range::Real(-2.0, 2.0).shrink(Value::Real(1.5))[..3],
+            [Value::Real(1.0), Value::Real(0.0), Value::Real(0.75)]
+        );
+        assert_eq!(
+            Range::Integer(0, 1).get_edges(),
+            vec![Value::Integer(0), Value::Integer(1)]
+        );
+    }
+}
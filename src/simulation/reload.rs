@@ -0,0 +1,405 @@
+//! detects changed sources and carries the state of a replaced version over to its successor
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    fs,
+    time::SystemTime,
+};
+
+use inkwell::{
+    execution_engine::ExecutionEngine,
+    module::Module,
+    targets::TargetData,
+    types::{AnyType, AnyTypeEnum, StructType},
+};
+
+use super::symbols::{StateMember, StateVariable};
+use crate::index::Index;
+
+/// the outcome of a reload
+#[derive(Debug, Default, PartialEq)]
+pub struct ReloadReport {
+    /// the variables which kept their values
+    pub preserved: Vec<String>,
+    /// the variables whose layout changed, only their members which kept their name and layout
+    /// kept their values
+    pub migrated: Vec<String>,
+    /// the variables which were reset to their initial values because their layout changed
+    pub reinitialized: Vec<String>,
+}
+
+impl Display for ReloadReport {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "reloaded, {} variables preserved, {} migrated, {} reinitialized",
+            self.preserved.len(),
+            self.migrated.len(),
+            self.reinitialized.len()
+        )?;
+        if !self.reinitialized.is_empty() {
+            write!(f, " ({})", self.reinitialized.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// watches the modification of a set of source files
+pub(super) struct SourceWatcher {
+    files: Vec<(String, Option<(SystemTime, u64)>)>,
+}
+
+impl SourceWatcher {
+    pub fn new<'a>(files: impl Iterator<Item = &'a str>) -> Self {
+        SourceWatcher {
+            files: files
+                .map(|it| (it.to_string(), get_modification(it)))
+                .collect(),
+        }
+    }
+
+    /// returns true if one of the files was modified since the watcher was created or last asked
+    pub fn has_changed(&mut self) -> bool {
+        let mut changed = false;
+        for (file, last_modification) in self.files.iter_mut() {
+            let modification = get_modification(file);
+            if modification != *last_modification {
+                *last_modification = modification;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+fn get_modification(file: &str) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(file).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// collects the mutable global variables and program instances defined by the given module
+pub(super) fn get_state_variables(
+    module: &Module,
+    engine: &ExecutionEngine,
+    index: &Index,
+) -> HashMap<String, StateVariable> {
+    let target_data = engine.get_target_data();
+    module
+        .get_globals()
+        .filter(|it| !it.is_constant() && !it.is_declaration())
+        .filter_map(|global| {
+            let name = global.get_name().to_str().ok()?;
+            if name.starts_with("llvm.") {
+                return None;
+            }
+            //the jit resolves the symbols of variables the same way as the symbols of functions
+            let address = engine.get_function_address(name).ok()?;
+            let data_type = global.as_pointer_value().get_type().get_element_type();
+            Some((
+                name.to_string(),
+                StateVariable {
+                    address,
+                    size: target_data.get_abi_size(&data_type) as usize,
+                    layout: describe_layout(data_type, index),
+                    members: get_members(data_type, index, target_data),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// returns the names of the fields of the given struct as declared by the struct or POU of the
+/// same name, empty if the struct is not declared in the sources (e.g. a generated type)
+fn get_field_names(struct_type: StructType, index: &Index) -> Vec<String> {
+    let names = struct_type
+        .get_name()
+        .and_then(|it| it.to_str().ok())
+        .map(|it| index.get_container_members(it))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|it| !it.is_temp() && !it.is_return())
+        .map(|it| it.get_name().to_string())
+        .collect::<Vec<_>>();
+    if names.len() == struct_type.count_fields() as usize {
+        names
+    } else {
+        vec![]
+    }
+}
+
+/// describes the given type down to its primitive members, so structs are compared by the names
+/// and content of their members rather than their name. Returns `None` for types containing
+/// pointers.
+fn describe_layout(data_type: AnyTypeEnum, index: &Index) -> Option<String> {
+    match data_type {
+        AnyTypeEnum::StructType(struct_type) => {
+            let names = get_field_names(struct_type, index);
+            let fields = struct_type
+                .get_field_types()
+                .into_iter()
+                .enumerate()
+                .map(|(field, it)| {
+                    let layout = describe_layout(it.as_any_type_enum(), index)?;
+                    Some(match names.get(field) {
+                        Some(name) => format!("{}: {}", name, layout),
+                        None => layout,
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("{{ {} }}", fields.join(", ")))
+        }
+        AnyTypeEnum::ArrayType(array_type) => Some(format!(
+            "[{} x {}]",
+            array_type.len(),
+            describe_layout(array_type.get_element_type().as_any_type_enum(), index)?
+        )),
+        AnyTypeEnum::PointerType(_) | AnyTypeEnum::FunctionType(_) => None,
+        _ => Some(data_type.print_to_string().to_string()),
+    }
+}
+
+/// returns the members of a struct or POU instance by their lower case name, empty for other
+/// types
+fn get_members(
+    data_type: AnyTypeEnum,
+    index: &Index,
+    target_data: &TargetData,
+) -> HashMap<String, StateMember> {
+    match data_type {
+        AnyTypeEnum::StructType(struct_type) => get_field_names(struct_type, index)
+            .into_iter()
+            .zip(struct_type.get_field_types())
+            .enumerate()
+            .filter_map(|(field, (name, field_type))| {
+                let member = StateMember {
+                    offset: target_data.offset_of_element(&struct_type, field as u32)? as usize,
+                    size: target_data.get_abi_size(&field_type) as usize,
+                    layout: describe_layout(field_type.as_any_type_enum(), index),
+                };
+                Some((name.to_lowercase(), member))
+            })
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
+
+/// returns true if a value of the `previous` layout and size can be copied into the `new` one, i.e.
+/// both are the same and hold no pointers
+fn is_transferable(new: (&Option<String>, usize), previous: (&Option<String>, usize)) -> bool {
+    new.0.is_some() && new == previous
+}
+
+/// copies the values of the replaced version's variables into the variables of the new version
+/// that kept their name and layout. The values of a struct or POU instance whose layout changed
+/// are mapped by the names of its members, the members which kept their name and layout keep their
+/// values
+pub(super) fn transfer_state(
+    replaced: &HashMap<String, StateVariable>,
+    new: &HashMap<String, StateVariable>,
+) -> ReloadReport {
+    let mut report = ReloadReport::default();
+    for (name, variable) in new {
+        let previous = match replaced.get(name) {
+            Some(it) => it,
+            None => continue,
+        };
+        if is_transferable(
+            (&variable.layout, variable.size),
+            (&previous.layout, previous.size),
+        ) {
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    previous.address as *const u8,
+                    variable.address as *mut u8,
+                    variable.size,
+                )
+            };
+            report.preserved.push(name.clone());
+            continue;
+        }
+        let mut is_migrated = false;
+        for (member_name, member) in &variable.members {
+            if let Some(previous_member) = previous
+                .members
+                .get(member_name)
+                .filter(|it| is_transferable((&member.layout, member.size), (&it.layout, it.size)))
+            {
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        (previous.address + previous_member.offset) as *const u8,
+                        (variable.address + member.offset) as *mut u8,
+                        member.size,
+                    )
+                };
+                is_migrated = true;
+            }
+        }
+        if is_migrated {
+            report.migrated.push(name.clone());
+        } else {
+            report.reinitialized.push(name.clone());
+        }
+    }
+    report.preserved.sort();
+    report.migrated.sort();
+    report.reinitialized.sort();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use inkwell::{context::Context, types::AnyType};
+
+    use super::{describe_layout, transfer_state, SourceWatcher};
+    use crate::{
+        simulation::symbols::{StateMember, StateVariable},
+        test_utils::tests::index,
+    };
+
+    fn variable(memory: &mut [u8], layout: Option<&str>) -> StateVariable {
+        StateVariable {
+            address: memory.as_mut_ptr() as usize,
+            size: memory.len(),
+            layout: layout.map(str::to_string),
+            members: HashMap::new(),
+        }
+    }
+
+    /// an instance of the given `INT` members, located in the given memory in their order
+    fn instance(memory: &mut [u8], members: &[&str]) -> StateVariable {
+        let layout = members
+            .iter()
+            .map(|it| format!("{}: i16", it))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut instance = variable(memory, Some(&format!("{{ {} }}", layout)));
+        instance.members = members
+            .iter()
+            .enumerate()
+            .map(|(position, it)| {
+                let member = StateMember {
+                    offset: position * 2,
+                    size: 2,
+                    layout: Some("i16".to_string()),
+                };
+                (it.to_string(), member)
+            })
+            .collect();
+        instance
+    }
+
+    #[test]
+    fn values_are_only_carried_over_for_unchanged_layouts() {
+        let mut old_counter = [1u8, 2, 3, 4];
+        let mut old_instance = [5u8, 6, 7, 8];
+        let mut old_pointer = [9u8; 4];
+        let mut new_counter = [0u8; 4];
+        let mut new_instance = [0u8; 4];
+        let mut new_pointer = [0u8; 4];
+        let replaced = HashMap::from([
+            (
+                "counter".to_string(),
+                variable(&mut old_counter, Some("i32")),
+            ),
+            (
+                "prg_instance".to_string(),
+                variable(&mut old_instance, Some("{ i16, i16 }")),
+            ),
+            ("ptr".to_string(), variable(&mut old_pointer, None)),
+        ]);
+        let new = HashMap::from([
+            (
+                "counter".to_string(),
+                variable(&mut new_counter, Some("i32")),
+            ),
+            (
+                "prg_instance".to_string(),
+                variable(&mut new_instance, Some("{ i32 }")),
+            ),
+            ("ptr".to_string(), variable(&mut new_pointer, None)),
+        ]);
+
+        let report = transfer_state(&replaced, &new);
+
+        assert_eq!(report.preserved, vec!["counter"]);
+        assert_eq!(report.reinitialized, vec!["prg_instance", "ptr"]);
+        assert_eq!(new_counter, [1, 2, 3, 4]);
+        assert_eq!(new_instance, [0; 4]);
+        assert_eq!(new_pointer, [0; 4]);
+    }
+
+    #[test]
+    fn layouts_name_the_members_of_instances() {
+        let context = Context::create();
+        let instance = context.opaque_struct_type("prg");
+        instance.set_body(
+            &[context.i16_type().into(), context.i16_type().into()],
+            false,
+        );
+
+        let (_, declared) = index("PROGRAM prg VAR a, b : INT; END_VAR END_PROGRAM");
+        assert_eq!(
+            describe_layout(instance.as_any_type_enum(), &declared),
+            Some("{ a: i16, b: i16 }".to_string())
+        );
+        //swapped members of the same type change the layout
+        let (_, swapped) = index("PROGRAM prg VAR b, a : INT; END_VAR END_PROGRAM");
+        assert_eq!(
+            describe_layout(instance.as_any_type_enum(), &swapped),
+            Some("{ b: i16, a: i16 }".to_string())
+        );
+    }
+
+    #[test]
+    fn values_of_changed_instances_are_mapped_by_member_name() {
+        let mut old_swapped = [1u8, 0, 2, 0];
+        let mut old_changed = [3u8, 0, 4, 0];
+        let mut new_swapped = [0u8; 4];
+        let mut new_changed = [0u8; 6];
+        let replaced = HashMap::from([
+            (
+                "swapped".to_string(),
+                instance(&mut old_swapped, &["a", "b"]),
+            ),
+            (
+                "changed".to_string(),
+                instance(&mut old_changed, &["a", "b"]),
+            ),
+        ]);
+        let mut changed = instance(&mut new_changed, &["a", "c", "b"]);
+        //`a` changed its type
+        changed.members.get_mut("a").unwrap().layout = Some("i32".to_string());
+        let new = HashMap::from([
+            (
+                "swapped".to_string(),
+                instance(&mut new_swapped, &["b", "a"]),
+            ),
+            ("changed".to_string(), changed),
+        ]);
+
+        let report = transfer_state(&replaced, &new);
+
+        assert_eq!(report.migrated, vec!["changed", "swapped"]);
+        assert!(report.preserved.is_empty());
+        assert!(report.reinitialized.is_empty());
+        assert_eq!(new_swapped, [2, 0, 1, 0]);
+        assert_eq!(new_changed, [0, 0, 0, 0, 4, 0]);
+    }
+
+    #[test]
+    fn modified_sources_are_detected() {
+        let directory = tempfile::tempdir().unwrap();
+        let file = directory.path().join("source_watcher.st");
+        let file = file.to_string_lossy().to_string();
+        std::fs::write(&file, "PROGRAM prg END_PROGRAM").unwrap();
+
+        let mut watcher = SourceWatcher::new([file.as_str()].into_iter());
+        assert!(!watcher.has_changed());
+
+        std::fs::write(&file, "PROGRAM prg VAR x : INT; END_VAR END_PROGRAM").unwrap();
+        assert!(watcher.has_changed());
+        assert!(!watcher.has_changed());
+    }
+}
@@ -0,0 +1,397 @@
+//! acceptance test scenarios executed against a simulation (see `rusty test --scenario`)
+//!
+//! a scenario is a CSV file. Its header names the cycle column followed by the inputs written
+//! before and the outputs checked after a cycle, every following row describes one cycle:
+//!
+//! ```text
+//! # the cycles run without a row keep their inputs and are not checked
+//! cycle, in prg.start, in level, out prg.valve, out prg.flow +- 0.5
+//! 1,     TRUE,         10,       TRUE,          0.0
+//! 5,     ,             80,       FALSE,         12.5 +- 1
+//! ```
+//!
+//! an empty cell neither writes nor checks the column. Outputs are compared by their memory, or
+//! numerically if the column or the cell defines a tolerance (`+-` or `±`).
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+};
+
+use super::{symbols, Simulation};
+use crate::diagnostics::Diagnostic;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Input,
+    Output,
+}
+
+#[derive(Debug, PartialEq)]
+struct Column {
+    direction: Direction,
+    expression: String,
+    tolerance: Option<f64>,
+}
+
+/// the values of one cycle, `None` for an empty cell
+#[derive(Debug, PartialEq)]
+struct Step {
+    cycle: u64,
+    line: usize,
+    values: Vec<Option<String>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Scenario {
+    pub name: String,
+    columns: Vec<Column>,
+    steps: Vec<Step>,
+}
+
+/// an output which did not match its expected value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub cycle: u64,
+    pub expression: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioResult {
+    pub name: String,
+    pub cycles: u64,
+    pub checks: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl ScenarioResult {
+    pub fn is_passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "cycle {}: {} is {}, expected {}",
+            self.cycle, self.expression, self.actual, self.expected
+        )
+    }
+}
+
+impl Display for ScenarioResult {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "scenario '{}' {} ({} cycles, {} checks",
+            self.name,
+            if self.is_passed() { "passed" } else { "failed" },
+            self.cycles,
+            self.checks
+        )?;
+        if !self.is_passed() {
+            write!(f, ", {} mismatches", self.mismatches.len())?;
+        }
+        write!(f, ")")?;
+        for mismatch in &self.mismatches {
+            write!(f, "\n    {}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+impl Scenario {
+    pub fn load(path: &str) -> Result<Scenario, Diagnostic> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| Diagnostic::io_read_error(path, &err.to_string()))?;
+        Scenario::parse(path, &content)
+    }
+
+    /// parses the given CSV content, `name` is used to report errors and results
+    pub fn parse(name: &str, content: &str) -> Result<Scenario, Diagnostic> {
+        let error = |line: usize, message: &str| {
+            Diagnostic::param_error(&format!("{}:{}: {}", name, line, message))
+        };
+        let mut rows = content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (line, header) = rows.next().ok_or_else(|| error(1, "Missing header"))?;
+        let mut header = split_row(header).into_iter();
+        if !header
+            .next()
+            .map_or(false, |it| it.eq_ignore_ascii_case("cycle"))
+        {
+            return Err(error(line, "The first column has to be 'cycle'"));
+        }
+        let columns = header
+            .map(|it| {
+                parse_column(&it).ok_or_else(|| {
+                    error(
+                        line,
+                        &format!(
+                            "Invalid column '{}', expected 'in <symbol>' or 'out <symbol>'",
+                            it
+                        ),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut steps: Vec<Step> = vec![];
+        for (line, row) in rows {
+            let mut cells = split_row(row).into_iter();
+            let cycle = cells
+                .next()
+                .and_then(|it| it.parse::<u64>().ok())
+                .filter(|it| *it > 0)
+                .ok_or_else(|| error(line, "Invalid cycle"))?;
+            if steps.last().map_or(false, |it| it.cycle >= cycle) {
+                return Err(error(line, "The cycles have to be ascending"));
+            }
+            let values = cells
+                .map(|it| if it.is_empty() { None } else { Some(it) })
+                .collect::<Vec<_>>();
+            if values.len() > columns.len() {
+                return Err(error(line, "More cells than columns"));
+            }
+            steps.push(Step {
+                cycle,
+                line,
+                values,
+            });
+        }
+        Ok(Scenario {
+            name: name.to_string(),
+            columns,
+            steps,
+        })
+    }
+
+    /// runs the scenario's cycles in the given simulation, fails if a symbol of the scenario is
+    /// unknown or a value cannot be converted
+    pub fn run(&self, simulation: &mut Simulation) -> Result<ScenarioResult, Diagnostic> {
+        let mut result = ScenarioResult {
+            name: self.name.clone(),
+            cycles: 0,
+            checks: 0,
+            mismatches: vec![],
+        };
+        let error = |step: &Step, err: Diagnostic| {
+            Diagnostic::param_error(&format!(
+                "{}:{}: {}",
+                self.name,
+                step.line,
+                err.get_message()
+            ))
+        };
+        for step in &self.steps {
+            while result.cycles + 1 < step.cycle {
+                simulation.run_cycle();
+                result.cycles += 1;
+            }
+            for (column, value) in self.get_values(step, Direction::Input) {
+                simulation
+                    .write(&column.expression, value)
+                    .map_err(|err| error(step, err))?;
+            }
+            simulation.run_cycle();
+            result.cycles += 1;
+            for (column, expected) in self.get_values(step, Direction::Output) {
+                result.checks += 1;
+                let actual = check(simulation, column, expected).map_err(|err| error(step, err))?;
+                if let Some(actual) = actual {
+                    result.mismatches.push(Mismatch {
+                        cycle: step.cycle,
+                        expression: column.expression.clone(),
+                        expected: expected.to_string(),
+                        actual,
+                    });
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    fn get_values<'a>(
+        &'a self,
+        step: &'a Step,
+        direction: Direction,
+    ) -> impl Iterator<Item = (&'a Column, &'a str)> {
+        self.columns
+            .iter()
+            .zip(step.values.iter())
+            .filter(move |(column, _)| column.direction == direction)
+            .filter_map(|(column, value)| value.as_deref().map(|it| (column, it)))
+    }
+}
+
+/// compares the given output against its expected value, returns the actual value if it does
+/// not match
+fn check(
+    simulation: &Simulation,
+    column: &Column,
+    expected: &str,
+) -> Result<Option<String>, Diagnostic> {
+    let symbols = simulation.get_symbols();
+    let symbol = symbols.resolve(&column.expression).ok_or_else(|| {
+        Diagnostic::param_error(&format!("Unknown symbol '{}'", column.expression))
+    })?;
+    let actual = symbols.format_value(&symbol);
+    let (expected, tolerance) = match split_tolerance(expected) {
+        Some((value, tolerance)) => (value, Some(tolerance)),
+        None => (expected, column.tolerance),
+    };
+    let matches = match tolerance {
+        Some(tolerance) => {
+            let expected = expected.parse::<f64>().map_err(|_| {
+                Diagnostic::param_error(&format!(
+                    "Invalid value {} for '{}', a tolerance requires a number",
+                    expected, column.expression
+                ))
+            })?;
+            actual
+                .parse::<f64>()
+                .map_or(false, |actual| (actual - expected).abs() <= tolerance)
+        }
+        None => {
+            let expected = symbols
+                .parse_value(&symbol, expected)
+                .map_err(|err| Diagnostic::param_error(&err))?;
+            symbols::read_memory(&symbol) == expected.as_slice()
+        }
+    };
+    Ok(if matches { None } else { Some(actual) })
+}
+
+/// parses a column header like `in prg.start` or `out prg.flow +- 0.5`
+fn parse_column(header: &str) -> Option<Column> {
+    let (direction, expression) = header.split_once(char::is_whitespace)?;
+    let direction = match direction.to_lowercase().as_str() {
+        "in" => Direction::Input,
+        "out" => Direction::Output,
+        _ => return None,
+    };
+    let (expression, tolerance) = match split_tolerance(expression) {
+        Some((expression, tolerance)) => (expression, Some(tolerance)),
+        None => (expression.trim(), None),
+    };
+    if expression.is_empty() || (direction == Direction::Input && tolerance.is_some()) {
+        return None;
+    }
+    Some(Column {
+        direction,
+        expression: expression.to_string(),
+        tolerance,
+    })
+}
+
+/// splits `value +- tolerance` into its parts, returns `None` if there is no valid tolerance
+fn split_tolerance(text: &str) -> Option<(&str, f64)> {
+    let (value, tolerance) = text.split_once("+-").or_else(|| text.split_once('±'))?;
+    let tolerance = tolerance.trim().parse::<f64>().ok()?;
+    Some((value.trim(), tolerance.abs()))
+}
+
+/// splits a CSV row into its trimmed cells, cells may be quoted with `"` (`""` for a quote)
+fn split_row(row: &str) -> Vec<String> {
+    let mut cells = vec![];
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut characters = row.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if quoted && characters.peek() == Some(&'"') => {
+                cell.push('"');
+                characters.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(character),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_row, Column, Direction, Scenario};
+
+    #[test]
+    fn rows_are_split_into_cells() {
+        assert_eq!(
+            split_row(r#" 1, TRUE ,, "a, ""b""", 'c'"#),
+            vec!["1", "TRUE", "", r#"a, "b""#, "'c'"]
+        );
+    }
+
+    #[test]
+    fn scenarios_are_parsed() {
+        let scenario = Scenario::parse(
+            "valve.csv",
+            "# a comment\n\
+            cycle, in prg.start, out prg.flow +- 0.5\n\
+            1, TRUE\n\
+            \n\
+            4, , 12.5 ± 1\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            scenario.columns,
+            vec![
+                Column {
+                    direction: Direction::Input,
+                    expression: "prg.start".to_string(),
+                    tolerance: None
+                },
+                Column {
+                    direction: Direction::Output,
+                    expression: "prg.flow".to_string(),
+                    tolerance: Some(0.5)
+                }
+            ]
+        );
+        assert_eq!(scenario.steps.len(), 2);
+        assert_eq!(scenario.steps[0].values, vec![Some("TRUE".to_string())]);
+        assert_eq!(scenario.steps[1].cycle, 4);
+        assert_eq!(scenario.steps[1].line, 5);
+        assert_eq!(
+            scenario.steps[1].values,
+            vec![None, Some("12.5 ± 1".to_string())]
+        );
+    }
+
+    #[test]
+    fn invalid_scenarios_report_their_line() {
+        let error = |content: &str| {
+            Scenario::parse("test.csv", content)
+                .unwrap_err()
+                .get_message()
+                .to_string()
+        };
+
+        assert_eq!(
+            error("step, in a"),
+            "test.csv:1: The first column has to be 'cycle'"
+        );
+        assert_eq!(
+            error("cycle, a"),
+            "test.csv:1: Invalid column 'a', expected 'in <symbol>' or 'out <symbol>'"
+        );
+        assert_eq!(
+            error("cycle, in a\n2, 1\n2, 1"),
+            "test.csv:3: The cycles have to be ascending"
+        );
+        assert_eq!(
+            error("cycle, in a\n1, 1, 2"),
+            "test.csv:2: More cells than columns"
+        );
+        assert_eq!(error("cycle, in a\nx, 1"), "test.csv:2: Invalid cycle");
+    }
+}
@@ -0,0 +1,185 @@
+//! snapshots of a simulation's state: the memory of its global variables and program instances
+//! and its simulated time. A snapshot can be restored into a later version of the application,
+//! variables whose layout changed since the snapshot was taken keep their current values.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{Display, Formatter},
+    fs,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::symbols::StateVariable;
+use crate::diagnostics::Diagnostic;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// the simulated time in milliseconds
+    pub time: u64,
+    pub cycle: u64,
+    /// the simulated time every task is released next, by the task's name
+    pub releases: BTreeMap<String, u64>,
+    /// the global variables and program instances by their symbol
+    pub variables: BTreeMap<String, SnapshotVariable>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotVariable {
+    /// the memory layout the variable had, see `StateVariable::layout`
+    pub layout: String,
+    /// the variable's memory as hex string
+    pub memory: String,
+}
+
+/// the outcome of restoring a snapshot
+#[derive(Debug, Default, PartialEq)]
+pub struct RestoreReport {
+    pub restored: Vec<String>,
+    /// the variables of the snapshot which no longer exist or changed their layout
+    pub skipped: Vec<String>,
+}
+
+impl Display for RestoreReport {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} variables restored, {} skipped",
+            self.restored.len(),
+            self.skipped.len()
+        )?;
+        if !self.skipped.is_empty() {
+            write!(f, " ({})", self.skipped.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Snapshot {
+    pub fn save(&self, path: &str) -> Result<(), Diagnostic> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|err| Diagnostic::io_write_error(path, &err.to_string()))?;
+        fs::write(path, content).map_err(|err| Diagnostic::io_write_error(path, &err.to_string()))
+    }
+
+    pub fn load(path: &str) -> Result<Snapshot, Diagnostic> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| Diagnostic::io_read_error(path, &err.to_string()))?;
+        serde_json::from_str(&content)
+            .map_err(|err| Diagnostic::io_read_error(path, &err.to_string()))
+    }
+}
+
+/// copies the memory of the given variables, variables holding pointers are left out since their
+/// targets cannot be restored
+pub(super) fn capture(
+    variables: &HashMap<String, StateVariable>,
+) -> BTreeMap<String, SnapshotVariable> {
+    variables
+        .iter()
+        .filter_map(|(name, variable)| {
+            let layout = variable.layout.clone()?;
+            let memory =
+                unsafe { std::slice::from_raw_parts(variable.address as *const u8, variable.size) };
+            Some((
+                name.clone(),
+                SnapshotVariable {
+                    layout,
+                    memory: memory.iter().map(|it| format!("{:02x}", it)).collect(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// writes the captured memory into the variables that kept their name and layout
+pub(super) fn restore(
+    captured: &BTreeMap<String, SnapshotVariable>,
+    variables: &HashMap<String, StateVariable>,
+) -> RestoreReport {
+    let mut report = RestoreReport::default();
+    for (name, snapshot) in captured {
+        let memory = variables
+            .get(name)
+            .filter(|it| it.layout.as_ref() == Some(&snapshot.layout))
+            .zip(decode(&snapshot.memory))
+            .filter(|(variable, memory)| variable.size == memory.len());
+        match memory {
+            Some((variable, memory)) => {
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        memory.as_ptr(),
+                        variable.address as *mut u8,
+                        variable.size,
+                    )
+                };
+                report.restored.push(name.clone());
+            }
+            None => report.skipped.push(name.clone()),
+        }
+    }
+    report
+}
+
+fn decode(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|it| u8::from_str_radix(hex.get(it..it + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{capture, restore};
+    use crate::simulation::symbols::StateVariable;
+
+    fn variable(memory: &mut [u8], layout: Option<&str>) -> StateVariable {
+        StateVariable {
+            address: memory.as_mut_ptr() as usize,
+            size: memory.len(),
+            layout: layout.map(str::to_string),
+            members: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn captured_memory_is_restored_into_unchanged_layouts() {
+        let mut counter = [1u8, 2, 3, 0xff];
+        let mut instance = [5u8, 6, 7, 8];
+        let mut pointer = [9u8; 4];
+        let variables = HashMap::from([
+            ("counter".to_string(), variable(&mut counter, Some("i32"))),
+            (
+                "prg_instance".to_string(),
+                variable(&mut instance, Some("{ i16, i16 }")),
+            ),
+            ("ptr".to_string(), variable(&mut pointer, None)),
+        ]);
+        let captured = capture(&variables);
+        assert_eq!(captured["counter"].memory, "010203ff");
+        assert!(!captured.contains_key("ptr"));
+
+        let mut new_counter = [0u8; 4];
+        let mut new_instance = [0u8; 4];
+        let new = HashMap::from([
+            (
+                "counter".to_string(),
+                variable(&mut new_counter, Some("i32")),
+            ),
+            (
+                "prg_instance".to_string(),
+                variable(&mut new_instance, Some("{ i32 }")),
+            ),
+        ]);
+        let report = restore(&captured, &new);
+
+        assert_eq!(report.restored, vec!["counter"]);
+        assert_eq!(report.skipped, vec!["prg_instance"]);
+        assert_eq!(new_counter, [1, 2, 3, 0xff]);
+        assert_eq!(new_instance, [0; 4]);
+    }
+}
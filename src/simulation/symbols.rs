@@ -0,0 +1,502 @@
+//! the symbols of a compiled version: its index, the memory layout of its structs and POU
+//! instances, the addresses of its variables and the line tables of its sources
+use std::{collections::HashMap, fs};
+
+use crate::{
+    abi_metadata::InstanceLayout,
+    index::Index,
+    typesystem::{DataTypeInformation, StringEncoding},
+};
+
+/// a global variable or program instance in the memory of a compiled version
+pub struct StateVariable {
+    pub address: usize,
+    pub size: usize,
+    /// describes the variable's memory layout, `None` if it holds pointers which would point into
+    /// the replaced version's memory
+    pub layout: Option<String>,
+    /// the members of a struct or POU instance by their lower case name, empty for other types
+    pub members: HashMap<String, StateMember>,
+}
+
+/// a member of a struct or POU instance held by a `StateVariable`
+pub struct StateMember {
+    /// the offset of the member inside the variable
+    pub offset: usize,
+    pub size: usize,
+    /// see `StateVariable::layout`
+    pub layout: Option<String>,
+}
+
+/// a variable or member located in the simulation's memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub type_name: String,
+    pub address: usize,
+    pub size: usize,
+}
+
+/// a source file and the offsets its lines start at
+pub struct SourceFile {
+    /// the canonical path of the file, as reported to debuggers
+    pub path: String,
+    line_starts: Vec<usize>,
+}
+
+pub struct SymbolTable {
+    index: Index,
+    /// the layouts of structs and POU instances by their lower case name
+    layouts: HashMap<String, InstanceLayout>,
+    /// the global variables and program instances by their symbol
+    variables: HashMap<String, StateVariable>,
+    /// the sources followed by the includes, as numbered by the debug hooks
+    sources: Vec<SourceFile>,
+}
+
+impl SourceFile {
+    pub fn new(path: &str, text: &str) -> SourceFile {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        SourceFile {
+            path: canonicalize(path),
+            line_starts,
+        }
+    }
+
+    /// returns the (1-based) line containing the given offset
+    pub fn get_line(&self, offset: usize) -> u32 {
+        self.line_starts.partition_point(|it| *it <= offset) as u32
+    }
+
+    /// returns the (1-based) column of the given offset in its line
+    pub fn get_column(&self, offset: usize) -> u32 {
+        let line_start = self.line_starts[self.get_line(offset) as usize - 1];
+        (offset - line_start + 1) as u32
+    }
+}
+
+/// returns the absolute path of the given file, or the path itself if it cannot be resolved
+fn canonicalize(path: &str) -> String {
+    fs::canonicalize(path)
+        .map(|it| it.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+impl SymbolTable {
+    pub(super) fn new(
+        index: Index,
+        layouts: Vec<InstanceLayout>,
+        variables: HashMap<String, StateVariable>,
+        sources: Vec<SourceFile>,
+    ) -> SymbolTable {
+        SymbolTable {
+            index,
+            layouts: layouts
+                .into_iter()
+                .map(|it| (it.name.to_lowercase(), it))
+                .collect(),
+            variables,
+            sources,
+        }
+    }
+
+    pub fn get_index(&self) -> &Index {
+        &self.index
+    }
+
+    pub(super) fn get_variables(&self) -> &HashMap<String, StateVariable> {
+        &self.variables
+    }
+
+    /// returns the global variable or program instance with the given symbol
+    pub fn find_variable(&self, symbol: &str) -> Option<Symbol> {
+        let variable = self.variables.get(symbol)?;
+        let type_name = self
+            .index
+            .find_global_variable(symbol)
+            .or_else(|| {
+                self.index
+                    .get_program_instances()
+                    .into_iter()
+                    .find(|it| it.get_name() == symbol)
+            })
+            .map(|it| it.get_type_name().to_string())
+            .unwrap_or_default();
+        Some(Symbol {
+            name: symbol.to_string(),
+            type_name,
+            address: variable.address,
+            size: variable.size,
+        })
+    }
+
+    /// returns the global variables declared by the application
+    pub fn get_globals(&self) -> Vec<Symbol> {
+        self.index
+            .get_globals()
+            .values()
+            .filter_map(|it| self.find_variable(it.get_name()))
+            .collect()
+    }
+
+    pub fn get_source(&self, file: usize) -> Option<&SourceFile> {
+        self.sources.get(file)
+    }
+
+    /// returns the number of the given source file
+    pub fn find_source(&self, path: &str) -> Option<usize> {
+        let path = canonicalize(path);
+        self.sources.iter().position(|it| it.path == path)
+    }
+
+    /// resolves a watch expression like `prg.counter`, `values[2]` or `prg.fb.state.out`, the
+    /// first segment names a global variable or a program
+    pub fn resolve(&self, expression: &str) -> Option<Symbol> {
+        let mut segments = split_expression(expression)?.into_iter();
+        let root = segments.next()?;
+        let instance = format!("{}_instance", root);
+        let name = self
+            .variables
+            .keys()
+            .find(|it| it.eq_ignore_ascii_case(&root))
+            .or_else(|| {
+                self.variables
+                    .keys()
+                    .find(|it| it.eq_ignore_ascii_case(&instance))
+            })?;
+        let mut symbol = self.find_variable(name)?;
+        for segment in segments {
+            symbol = self
+                .get_members(&symbol)
+                .into_iter()
+                .find(|it| it.name.eq_ignore_ascii_case(&segment))?;
+        }
+        symbol.name = expression.trim().to_string();
+        Some(symbol)
+    }
+
+    /// returns the members of the instance of the given POU located at the given address
+    pub fn get_locals(&self, pou: &str, instance: usize) -> Vec<Symbol> {
+        if instance == 0 {
+            return vec![];
+        }
+        let type_name = self
+            .index
+            .find_pou_implementation(pou)
+            .map_or(pou, |it| it.get_type_name());
+        self.get_layout_members(type_name, instance)
+    }
+
+    /// returns the members of the given struct, array or instance
+    pub fn get_members(&self, symbol: &Symbol) -> Vec<Symbol> {
+        match self
+            .index
+            .get_intrinsic_type_by_name(&symbol.type_name)
+            .get_type_information()
+        {
+            DataTypeInformation::Struct { .. } => {
+                self.get_layout_members(&symbol.type_name, symbol.address)
+            }
+            DataTypeInformation::Array {
+                inner_type_name,
+                dimensions,
+                ..
+            } => {
+                let lengths = dimensions
+                    .iter()
+                    .map(|it| it.get_length(&self.index).unwrap_or(0) as usize)
+                    .collect::<Vec<_>>();
+                let count = lengths.iter().product::<usize>();
+                if count == 0 {
+                    return vec![];
+                }
+                let start = dimensions
+                    .first()
+                    .and_then(|it| it.start_offset.as_int_value(&self.index).ok())
+                    .filter(|_| dimensions.len() == 1)
+                    .unwrap_or(0);
+                let element_size = symbol.size / count;
+                (0..count)
+                    .map(|element| Symbol {
+                        name: format!("[{}]", start + element as i64),
+                        type_name: inner_type_name.clone(),
+                        address: symbol.address + element * element_size,
+                        size: element_size,
+                    })
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    fn get_layout_members(&self, type_name: &str, address: usize) -> Vec<Symbol> {
+        self.layouts
+            .get(&type_name.to_lowercase())
+            .map(|layout| {
+                layout
+                    .members
+                    .iter()
+                    .map(|member| Symbol {
+                        name: member.name.clone(),
+                        type_name: member.type_name.clone(),
+                        address: address + member.offset as usize,
+                        size: member.size as usize,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// returns true if the given symbol has members
+    pub fn is_structured(&self, symbol: &Symbol) -> bool {
+        matches!(
+            self.index
+                .get_intrinsic_type_by_name(&symbol.type_name)
+                .get_type_information(),
+            DataTypeInformation::Struct { .. } | DataTypeInformation::Array { .. }
+        )
+    }
+
+    /// renders the current value of the given symbol as ST literal
+    pub fn format_value(&self, symbol: &Symbol) -> String {
+        let memory = read_memory(symbol);
+        let type_information = self
+            .index
+            .get_intrinsic_type_by_name(&symbol.type_name)
+            .get_type_information();
+        match type_information {
+            _ if type_information.is_bool() => {
+                if memory.iter().any(|it| *it != 0) {
+                    "TRUE".to_string()
+                } else {
+                    "FALSE".to_string()
+                }
+            }
+            DataTypeInformation::Integer { signed, .. } => {
+                let mut bytes = [0u8; 8];
+                let len = memory.len().min(8);
+                bytes[..len].copy_from_slice(&memory[..len]);
+                let value = u64::from_le_bytes(bytes);
+                if *signed && len > 0 && len < 8 {
+                    //sign-extend the value
+                    let shift = 64 - len * 8;
+                    (((value << shift) as i64) >> shift).to_string()
+                } else if *signed {
+                    (value as i64).to_string()
+                } else {
+                    value.to_string()
+                }
+            }
+            DataTypeInformation::Float { size: 32, .. } if memory.len() >= 4 => {
+                f32::from_le_bytes([memory[0], memory[1], memory[2], memory[3]]).to_string()
+            }
+            DataTypeInformation::Float { .. } if memory.len() >= 8 => {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(&memory[..8]);
+                f64::from_le_bytes(bytes).to_string()
+            }
+            DataTypeInformation::String {
+                encoding: StringEncoding::Utf8,
+                ..
+            } => {
+                let end = memory
+                    .iter()
+                    .position(|it| *it == 0)
+                    .unwrap_or(memory.len());
+                format!("'{}'", String::from_utf8_lossy(&memory[..end]))
+            }
+            DataTypeInformation::String {
+                encoding: StringEncoding::Utf16,
+                ..
+            } => {
+                let characters = memory
+                    .chunks_exact(2)
+                    .map(|it| u16::from_le_bytes([it[0], it[1]]))
+                    .take_while(|it| *it != 0)
+                    .collect::<Vec<_>>();
+                format!("\"{}\"", String::from_utf16_lossy(&characters))
+            }
+            DataTypeInformation::Pointer { .. } if memory.len() >= 8 => {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(&memory[..8]);
+                format!("16#{:X}", u64::from_le_bytes(bytes))
+            }
+            _ => symbol.type_name.clone(),
+        }
+    }
+
+    /// converts the given ST literal into the memory representation of the given symbol
+    pub fn parse_value(&self, symbol: &Symbol, value: &str) -> Result<Vec<u8>, String> {
+        let value = value.trim();
+        let invalid = || {
+            format!(
+                "Invalid value {} for '{}' of type {}",
+                value, symbol.name, symbol.type_name
+            )
+        };
+        let type_information = self
+            .index
+            .get_intrinsic_type_by_name(&symbol.type_name)
+            .get_type_information();
+        let mut memory = match type_information {
+            _ if type_information.is_bool() => match value.to_uppercase().as_str() {
+                "TRUE" | "1" => vec![1],
+                "FALSE" | "0" => vec![0],
+                _ => return Err(invalid()),
+            },
+            DataTypeInformation::Integer { signed, size, .. } => {
+                let number = parse_integer(value).ok_or_else(invalid)?;
+                let bits = (*size).min(64);
+                let (min, max) = if *signed {
+                    (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+                } else {
+                    (0, (1i128 << bits) - 1)
+                };
+                if number < min || number > max {
+                    return Err(invalid());
+                }
+                number.to_le_bytes().to_vec()
+            }
+            DataTypeInformation::Float { size: 32, .. } => value
+                .parse::<f32>()
+                .map_err(|_| invalid())?
+                .to_le_bytes()
+                .to_vec(),
+            DataTypeInformation::Float { .. } => value
+                .parse::<f64>()
+                .map_err(|_| invalid())?
+                .to_le_bytes()
+                .to_vec(),
+            DataTypeInformation::String {
+                encoding: StringEncoding::Utf8,
+                ..
+            } => value
+                .strip_prefix('\'')
+                .and_then(|it| it.strip_suffix('\''))
+                .ok_or_else(invalid)?
+                .as_bytes()
+                .to_vec(),
+            DataTypeInformation::String {
+                encoding: StringEncoding::Utf16,
+                ..
+            } => value
+                .strip_prefix('"')
+                .and_then(|it| it.strip_suffix('"'))
+                .ok_or_else(invalid)?
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes)
+                .collect(),
+            _ => {
+                return Err(format!(
+                    "Cannot assign a value to '{}' of type {}",
+                    symbol.name, symbol.type_name
+                ))
+            }
+        };
+        if matches!(type_information, DataTypeInformation::String { .. }) {
+            //the string has to fit including its terminator
+            if memory.len() >= symbol.size {
+                return Err(invalid());
+            }
+        }
+        memory.resize(symbol.size, 0);
+        Ok(memory)
+    }
+}
+
+/// splits a watch expression into its member names and array subscripts like `[2]`
+fn split_expression(expression: &str) -> Option<Vec<String>> {
+    let mut segments = vec![];
+    for part in expression.trim().split('.') {
+        let (name, subscripts) = part.split_at(part.find('[').unwrap_or(part.len()));
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        segments.push(name.to_string());
+        let mut subscripts = subscripts.trim();
+        while !subscripts.is_empty() {
+            let end = subscripts.find(']')?;
+            let index = subscripts.strip_prefix('[')?[..end - 1].trim();
+            segments.push(format!("[{}]", parse_integer(index)?));
+            subscripts = subscripts[end + 1..].trim();
+        }
+    }
+    Some(segments)
+}
+
+/// parses an integer literal like `-12`, `16#FF` or `2#1010_1010`
+fn parse_integer(literal: &str) -> Option<i128> {
+    let literal = literal.replace('_', "");
+    let (negative, literal) = match literal.strip_prefix('-') {
+        Some(it) => (true, it.to_string()),
+        None => (false, literal.trim_start_matches('+').to_string()),
+    };
+    let number = match literal.split_once('#') {
+        Some((radix, digits)) => i128::from_str_radix(digits, radix.parse().ok()?).ok()?,
+        None => literal.parse::<i128>().ok()?,
+    };
+    Some(if negative { -number } else { number })
+}
+
+/// overwrites the memory of the given symbol with the given value
+pub(super) fn write_memory(symbol: &Symbol, value: &[u8]) {
+    if symbol.address == 0 {
+        return;
+    }
+    let len = value.len().min(symbol.size);
+    unsafe { std::ptr::copy_nonoverlapping(value.as_ptr(), symbol.address as *mut u8, len) };
+}
+
+/// returns the memory of the given symbol
+pub(super) fn read_memory(symbol: &Symbol) -> &[u8] {
+    if symbol.address == 0 {
+        return &[];
+    }
+    unsafe { std::slice::from_raw_parts(symbol.address as *const u8, symbol.size) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_integer, split_expression, SourceFile};
+
+    #[test]
+    fn offsets_are_mapped_to_lines() {
+        let file = SourceFile::new("prg.st", "PROGRAM prg\nx := 1;\n\ny := 2;\nEND_PROGRAM");
+
+        assert_eq!(file.get_line(0), 1);
+        assert_eq!(file.get_line(11), 1);
+        assert_eq!(file.get_line(12), 2);
+        assert_eq!(file.get_line(20), 3);
+        assert_eq!(file.get_line(21), 4);
+        assert_eq!(file.get_line(40), 5);
+
+        assert_eq!(file.get_column(0), 1);
+        assert_eq!(file.get_column(11), 12);
+        assert_eq!(file.get_column(14), 3);
+        assert_eq!(file.get_column(21), 1);
+    }
+
+    #[test]
+    fn watch_expressions_are_split_into_members_and_subscripts() {
+        assert_eq!(
+            split_expression(" prg.values[ 16#2 ][-1].x "),
+            Some(vec![
+                "prg".to_string(),
+                "values".to_string(),
+                "[2]".to_string(),
+                "[-1]".to_string(),
+                "x".to_string()
+            ])
+        );
+        assert_eq!(split_expression("prg..x"), None);
+        assert_eq!(split_expression("values[1"), None);
+
+        assert_eq!(parse_integer("-1_000"), Some(-1000));
+        assert_eq!(parse_integer("2#1010"), Some(10));
+        assert_eq!(parse_integer("16#FF"), Some(255));
+        assert_eq!(parse_integer("x"), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! measures the code and the data of a compilation and enforces the budgets given with
+//! `--max-code-size` and `--max-ram`, so an application outgrowing its controller fails to build
+//! instead of failing to flash
+//!
+//! the code size is the size of the executable sections of the emitted object (`.text*`), broken
+//! down by the size of the functions' symbols and grouped by their POU (methods and actions count
+//! for their container). The RAM is the size of the mutable global variables laid out for the
+//! target, the instance of a program counts for the program. Constants are not counted, they are
+//! placed into read-only sections.
+use std::{cmp::Reverse, collections::HashMap, path::Path};
+
+use inkwell::{memory_buffer::MemoryBuffer, module::Module, targets::TargetTriple};
+
+use crate::{
+    abi_metadata,
+    diagnostics::Diagnostic,
+    index::{Index, PouIndexEntry},
+};
+
+/// the size of a POU's code or of a variable's data
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeEntry {
+    pub name: String,
+    pub size: u64,
+}
+
+/// the measured size and its breakdown, ordered from the largest to the smallest entry
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Usage {
+    pub total: u64,
+    pub entries: Vec<SizeEntry>,
+}
+
+impl Usage {
+    /// sums up the sizes of the entries with the same name
+    fn from_sizes(total: u64, sizes: impl Iterator<Item = (String, u64)>) -> Self {
+        let mut grouped: HashMap<String, u64> = HashMap::new();
+        for (name, size) in sizes {
+            *grouped.entry(name).or_default() += size;
+        }
+        let mut entries = grouped
+            .into_iter()
+            .map(|(name, size)| SizeEntry { name, size })
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| (Reverse(a.size), &a.name).cmp(&(Reverse(b.size), &b.name)));
+        Usage { total, entries }
+    }
+}
+
+/// the code and the data of a compilation, `None` if it could not be measured (e.g. the code of
+/// IR or bitcode outputs)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SizeReport {
+    pub code: Option<Usage>,
+    pub ram: Option<Usage>,
+}
+
+/// returns the names of the functions implemented in the given module
+pub fn get_implemented_functions(module: &Module) -> Vec<String> {
+    module
+        .get_functions()
+        .filter(|it| it.count_basic_blocks() > 0)
+        .map(|it| it.get_name().to_string_lossy().to_string())
+        .collect()
+}
+
+/// measures the mutable global variables of the given module laid out for the given target
+pub fn measure_ram(
+    module: &Module,
+    index: &Index,
+    triple: &TargetTriple,
+) -> Result<Usage, Diagnostic> {
+    let target_data = abi_metadata::create_target_machine(triple)?.get_target_data();
+    let programs = index
+        .get_pous()
+        .values()
+        .filter_map(|it| match it {
+            PouIndexEntry::Program {
+                name,
+                instance_variable,
+                ..
+            } => Some((instance_variable.get_name().to_lowercase(), name.as_str())),
+            _ => None,
+        })
+        .collect::<HashMap<_, _>>();
+
+    let sizes = module
+        .get_globals()
+        .filter(|it| !it.is_constant() && it.get_initializer().is_some())
+        .map(|global| {
+            let name = global.get_name().to_string_lossy().to_string();
+            let size =
+                target_data.get_abi_size(&global.as_pointer_value().get_type().get_element_type());
+            let name = programs
+                .get(&name.to_lowercase())
+                .map(|it| it.to_string())
+                .unwrap_or(name);
+            (name, size)
+        })
+        .collect::<Vec<_>>();
+    let total = sizes.iter().map(|(_, size)| size).sum();
+    Ok(Usage::from_sizes(total, sizes.into_iter()))
+}
+
+/// measures the executable sections of the given object file and the given functions in it
+pub fn measure_code(path: &str, functions: &[String]) -> Result<Usage, Diagnostic> {
+    let object = MemoryBuffer::create_from_file(Path::new(path))
+        .map_err(|err| Diagnostic::io_read_error(path, &err.to_string()))?
+        .create_object_file()
+        .map_err(|_| Diagnostic::io_read_error(path, "not an object file"))?;
+
+    let total = object
+        .get_sections()
+        .filter(|it| {
+            it.get_name()
+                .map(|name| is_code_section(&name.to_string_lossy()))
+                .unwrap_or_default()
+        })
+        .map(|it| it.size())
+        .sum();
+    let sizes = object.get_symbols().filter_map(|symbol| {
+        let name = symbol.get_name()?.to_string_lossy();
+        //symbols of Mach-O objects start with an underscore
+        let function = functions
+            .iter()
+            .find(|it| **it == name || Some(it.as_str()) == name.strip_prefix('_'))?;
+        Some((get_pou_name(function).to_string(), symbol.size()))
+    });
+    Ok(Usage::from_sizes(total, sizes))
+}
+
+fn is_code_section(name: &str) -> bool {
+    name.starts_with(".text") || name == "__text"
+}
+
+/// returns the POU a function belongs to, methods and actions (`fb.method`) belong to their
+/// container
+fn get_pou_name(function: &str) -> &str {
+    function.split('.').next().unwrap_or(function)
+}
+
+/// fails if the given usage exceeds the given budget, the error lists the entries of the usage
+pub fn check_budget(
+    kind: &str,
+    usage: Option<&Usage>,
+    budget: Option<u64>,
+) -> Result<(), Diagnostic> {
+    let budget = match budget {
+        Some(budget) => budget,
+        None => return Ok(()),
+    };
+    let usage = usage.ok_or_else(|| {
+        Diagnostic::param_error(&format!(
+            "The {} cannot be measured for this output, emit an object file instead",
+            kind
+        ))
+    })?;
+    if usage.total <= budget {
+        return Ok(());
+    }
+    let breakdown = usage
+        .entries
+        .iter()
+        .map(|it| format!("\n    {:<32} {:>10} bytes", it.name, it.size))
+        .collect::<String>();
+    Err(Diagnostic::size_budget_exceeded(
+        kind,
+        usage.total,
+        budget,
+        &breakdown,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_are_grouped_by_pou() {
+        let usage = Usage::from_sizes(
+            40,
+            vec![
+                ("fb".to_string(), 8),
+                ("main".to_string(), 20),
+                ("fb".to_string(), 4),
+                ("init".to_string(), 4),
+            ]
+            .into_iter(),
+        );
+
+        assert_eq!(
+            usage.entries,
+            vec![
+                SizeEntry {
+                    name: "main".into(),
+                    size: 20
+                },
+                SizeEntry {
+                    name: "fb".into(),
+                    size: 12
+                },
+                SizeEntry {
+                    name: "init".into(),
+                    size: 4
+                },
+            ]
+        );
+        assert_eq!(get_pou_name("fb.method"), "fb");
+    }
+
+    #[test]
+    fn exceeded_budgets_are_reported_with_a_breakdown() {
+        let usage = Usage::from_sizes(40, vec![("main".to_string(), 40)].into_iter());
+
+        assert_eq!(check_budget("code size", Some(&usage), Some(40)), Ok(()));
+        assert_eq!(check_budget("code size", None, None), Ok(()));
+        assert!(check_budget("code size", None, Some(40)).is_err());
+        let message = check_budget("code size", Some(&usage), Some(32))
+            .unwrap_err()
+            .get_message()
+            .to_string();
+        assert!(message.starts_with("The code size of 40 bytes exceeds the budget of 32 bytes"));
+        assert!(message.contains("main"));
+    }
+}
@@ -0,0 +1,667 @@
+//! translates the addresses of a crash on a deployed controller into the POUs and source lines
+//! they belong to, used by `rusty symbolicate` for post-mortem analysis
+//!
+//! the POUs are looked up in the map file written by the linker (see `--map`), the source lines in
+//! the DWARF line table (`.debug_line`) of the application compiled with `-g`. Only the map files
+//! written by lld and little-endian ELF binaries are supported.
+use std::fmt::Write;
+
+use regex::Regex;
+
+use crate::diagnostics::Diagnostic;
+
+/// a symbol of the linked application, e.g. the function generated for a POU
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub address: u64,
+    /// the distance to the next symbol or the end of the symbol's section
+    pub size: u64,
+}
+
+/// the symbols listed in a map file, sorted by their address
+#[derive(Debug, Default)]
+pub struct MapFile {
+    symbols: Vec<Symbol>,
+}
+
+/// a position in the sources
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// a row of the DWARF line table, the instructions from its address up to the next row's
+/// address belong to its location
+#[derive(Debug)]
+struct LineRow {
+    address: u64,
+    file: usize,
+    line: u32,
+    column: u32,
+}
+
+/// a contiguous range of instructions described by the line table, ends at `end`
+#[derive(Debug)]
+struct LineSequence {
+    rows: Vec<LineRow>,
+    end: u64,
+}
+
+/// the source locations of the instructions as described by the `.debug_line` section
+#[derive(Debug, Default)]
+pub struct LineTable {
+    files: Vec<String>,
+    sequences: Vec<LineSequence>,
+}
+
+/// a symbolicated address
+#[derive(Debug, PartialEq)]
+pub struct Frame {
+    pub address: u64,
+    /// the symbol containing the address and the address' offset into it
+    pub symbol: Option<(String, u64)>,
+    pub location: Option<SourceLocation>,
+}
+
+impl MapFile {
+    pub fn load(path: &str) -> Result<MapFile, Diagnostic> {
+        std::fs::read_to_string(path)
+            .map(|it| MapFile::parse(&it))
+            .map_err(|err| Diagnostic::io_read_error(path, &err.to_string()))
+    }
+
+    /// parses a map file written by lld, its header names the columns:
+    /// `VMA LMA Size Align Out In Symbol`, the column a line's name starts in tells output
+    /// sections, input sections and symbols apart
+    pub fn parse(text: &str) -> MapFile {
+        let mut lines = text.lines();
+        let columns = lines
+            .next()
+            .and_then(|header| Some((header.find(" In ")? + 1, header.find("Symbol")?)));
+        let (in_column, symbol_column) = match columns {
+            Some(it) => it,
+            None => return MapFile::default(),
+        };
+
+        let mut symbols = vec![];
+        //the symbols of the current input section and the section's end
+        let mut pending: Vec<(String, u64)> = vec![];
+        let mut section_end = 0;
+        for line in lines {
+            let (numbers, name_column, name) = match split_map_line(line) {
+                Some(it) => it,
+                None => continue,
+            };
+            if name_column >= symbol_column {
+                pending.push((name.to_string(), numbers[0]));
+                continue;
+            }
+            flush_symbols(&mut pending, section_end, &mut symbols);
+            if name_column >= in_column {
+                section_end = numbers[0] + numbers[2];
+            }
+        }
+        flush_symbols(&mut pending, section_end, &mut symbols);
+        symbols.sort_by_key(|it| it.address);
+        MapFile { symbols }
+    }
+
+    /// returns the symbol containing the given address
+    pub fn find_symbol(&self, address: u64) -> Option<&Symbol> {
+        let index = self.symbols.partition_point(|it| it.address <= address);
+        self.symbols[..index]
+            .iter()
+            .rev()
+            .find(|it| address < it.address + it.size.max(1))
+    }
+}
+
+/// splits a line of the map file into its numbers (VMA, LMA, Size and Align), the column the
+/// name starts in and the name
+fn split_map_line(line: &str) -> Option<([u64; 4], usize, &str)> {
+    let mut numbers = [0; 4];
+    let mut rest = line;
+    for number in numbers.iter_mut() {
+        let trimmed = rest.trim_start();
+        let end = trimmed.find(char::is_whitespace)?;
+        *number = u64::from_str_radix(&trimmed[..end], 16).ok()?;
+        rest = &trimmed[end..];
+    }
+    let name = rest.trim_start();
+    if name.is_empty() {
+        return None;
+    }
+    Some((numbers, line.len() - name.len(), name.trim_end()))
+}
+
+/// sizes the pending symbols of an input section by the distance to their successor
+fn flush_symbols(pending: &mut Vec<(String, u64)>, section_end: u64, symbols: &mut Vec<Symbol>) {
+    pending.sort_by_key(|(_, address)| *address);
+    let ends = pending
+        .iter()
+        .skip(1)
+        .map(|(_, address)| *address)
+        .chain(std::iter::once(section_end))
+        .collect::<Vec<_>>();
+    for ((name, address), end) in pending.drain(..).zip(ends) {
+        symbols.push(Symbol {
+            name,
+            address,
+            size: end.saturating_sub(address),
+        });
+    }
+}
+
+impl LineTable {
+    /// reads the line table of the given ELF binary
+    pub fn load(path: &str) -> Result<LineTable, Diagnostic> {
+        let binary =
+            std::fs::read(path).map_err(|err| Diagnostic::io_read_error(path, &err.to_string()))?;
+        find_elf_section(&binary, ".debug_line")
+            .and_then(|section| {
+                section.ok_or_else(|| "no debug information, compile it with -g".to_string())
+            })
+            .and_then(LineTable::parse)
+            .map_err(|message| Diagnostic::param_error(&format!("{}: {}", path, message)))
+    }
+
+    /// parses the line programs (DWARF 2 to 4) of a `.debug_line` section
+    pub fn parse(section: &[u8]) -> Result<LineTable, String> {
+        let mut table = LineTable::default();
+        let mut reader = Reader::new(section);
+        while reader.position < section.len() {
+            table
+                .parse_unit(&mut reader)
+                .ok_or_else(|| "malformed .debug_line section".to_string())?;
+        }
+        table.sequences.sort_by_key(|it| it.end);
+        Ok(table)
+    }
+
+    fn parse_unit(&mut self, reader: &mut Reader) -> Option<()> {
+        let (unit_length, offset_size) = match reader.u32()? {
+            0xffff_ffff => (reader.u64()?, 8),
+            length => (length as u64, 4),
+        };
+        let unit_end = reader.position + unit_length as usize;
+        let version = reader.u16()?;
+        if !(2..=4).contains(&version) {
+            //later versions describe their files differently
+            reader.position = unit_end;
+            return Some(());
+        }
+        let header_length = reader.offset(offset_size)?;
+        let program_start = reader.position + header_length as usize;
+        let minimum_instruction_length = reader.u8()? as u64;
+        if version >= 4 {
+            //the maximum operations per instruction only matter for VLIW architectures
+            reader.u8()?;
+        }
+        //whether a row starts a statement is not needed to locate an instruction
+        reader.u8()?;
+        let line_base = reader.u8()? as i8 as i64;
+        let line_range = reader.u8()?;
+        if line_range == 0 {
+            return None;
+        }
+        let opcode_base = reader.u8()?;
+        let mut standard_opcode_lengths = vec![];
+        for _ in 1..opcode_base {
+            standard_opcode_lengths.push(reader.u8()?);
+        }
+        let mut directories = vec![String::new()];
+        loop {
+            let directory = reader.string()?;
+            if directory.is_empty() {
+                break;
+            }
+            directories.push(directory);
+        }
+        //the file numbers are 1-based and local to the unit
+        let mut files = vec![self.files.len()];
+        while reader.position < program_start {
+            let name = reader.string()?;
+            if name.is_empty() {
+                break;
+            }
+            files.push(self.add_file(&name, reader, &directories)?);
+        }
+
+        reader.position = program_start;
+        let mut row = LineRow {
+            address: 0,
+            file: 1,
+            line: 1,
+            column: 0,
+        };
+        let mut rows = vec![];
+        while reader.position < unit_end {
+            let opcode = reader.u8()?;
+            if opcode >= opcode_base {
+                let adjusted = opcode - opcode_base;
+                row.address += (adjusted / line_range) as u64 * minimum_instruction_length;
+                row.line = (row.line as i64 + line_base + (adjusted % line_range) as i64) as u32;
+                rows.push(self.create_row(&row, &files));
+                continue;
+            }
+            match opcode {
+                0 => {
+                    let length = reader.uleb()? as usize;
+                    let end = reader.position + length;
+                    match reader.u8()? {
+                        //end of sequence
+                        1 => {
+                            self.sequences.push(LineSequence {
+                                rows: std::mem::take(&mut rows),
+                                end: row.address,
+                            });
+                            row = LineRow {
+                                address: 0,
+                                file: 1,
+                                line: 1,
+                                column: 0,
+                            };
+                        }
+                        //set address
+                        2 => row.address = reader.offset(length.checked_sub(1)?)?,
+                        //define file
+                        3 => {
+                            let name = reader.string()?;
+                            files.push(self.add_file(&name, reader, &directories)?);
+                        }
+                        _ => {}
+                    }
+                    reader.position = end;
+                }
+                //copy
+                1 => rows.push(self.create_row(&row, &files)),
+                //advance pc
+                2 => row.address += reader.uleb()? * minimum_instruction_length,
+                //advance line
+                3 => row.line = (row.line as i64 + reader.sleb()?) as u32,
+                //set file
+                4 => row.file = reader.uleb()? as usize,
+                //set column
+                5 => row.column = reader.uleb()? as u32,
+                //constant add pc
+                8 => {
+                    row.address +=
+                        ((255 - opcode_base) / line_range) as u64 * minimum_instruction_length
+                }
+                //fixed advance pc
+                9 => row.address += reader.u16()? as u64,
+                //the remaining opcodes do not affect the location, their operands are skipped
+                _ => {
+                    for _ in 0..standard_opcode_lengths[opcode as usize - 1] {
+                        reader.uleb()?;
+                    }
+                }
+            }
+        }
+        reader.position = unit_end;
+        Some(())
+    }
+
+    fn add_file(
+        &mut self,
+        name: &str,
+        reader: &mut Reader,
+        directories: &[String],
+    ) -> Option<usize> {
+        let directory = reader.uleb()? as usize;
+        //the modification time and length are not used
+        reader.uleb()?;
+        reader.uleb()?;
+        let path = match directories.get(directory) {
+            Some(directory) if !directory.is_empty() && !name.starts_with('/') => {
+                format!("{}/{}", directory, name)
+            }
+            _ => name.to_string(),
+        };
+        self.files.push(path);
+        Some(self.files.len() - 1)
+    }
+
+    /// copies the given row, replacing the unit's file number by the table's
+    fn create_row(&self, row: &LineRow, files: &[usize]) -> LineRow {
+        LineRow {
+            address: row.address,
+            file: files.get(row.file).copied().unwrap_or(usize::MAX),
+            line: row.line,
+            column: row.column,
+        }
+    }
+
+    /// returns the location of the instruction at the given address
+    pub fn find_location(&self, address: u64) -> Option<SourceLocation> {
+        let sequence = self
+            .sequences
+            .iter()
+            .filter(|it| address < it.end)
+            .find(|it| it.rows.first().map_or(false, |row| row.address <= address))?;
+        let index = sequence.rows.partition_point(|it| it.address <= address);
+        let row = &sequence.rows[index - 1];
+        Some(SourceLocation {
+            file: self
+                .files
+                .get(row.file)
+                .cloned()
+                .unwrap_or_else(|| "??".to_string()),
+            line: row.line,
+            column: row.column,
+        })
+    }
+}
+
+/// returns the content of the section with the given name, None if the binary has no such section
+fn find_elf_section<'b>(binary: &'b [u8], name: &str) -> Result<Option<&'b [u8]>, String> {
+    let unsupported = || "only little-endian ELF binaries are supported".to_string();
+    if !binary.starts_with(b"\x7fELF") || binary.get(5) != Some(&1) {
+        return Err(unsupported());
+    }
+    let is_64_bit = match binary.get(4) {
+        Some(1) => false,
+        Some(2) => true,
+        _ => return Err(unsupported()),
+    };
+    let malformed = || "malformed ELF binary".to_string();
+    let mut reader = Reader::new(binary);
+    //the offsets of the section headers and the number of the section holding their names
+    let (header_offset, header_size) = if is_64_bit {
+        reader.position = 0x28;
+        (reader.u64().ok_or_else(malformed)?, 0x40)
+    } else {
+        reader.position = 0x20;
+        (reader.u32().ok_or_else(malformed)? as u64, 0x28)
+    };
+    reader.position = if is_64_bit { 0x3c } else { 0x30 };
+    let count = reader.u16().ok_or_else(malformed)? as usize;
+    let names_index = reader.u16().ok_or_else(malformed)? as usize;
+
+    let read_header = |index: usize| -> Option<(usize, &'b [u8])> {
+        let mut reader = Reader::new(binary);
+        reader.position = header_offset as usize + index * header_size;
+        let name = reader.u32()? as usize;
+        let (offset, size) = if is_64_bit {
+            reader.position += 20;
+            (reader.u64()?, reader.u64()?)
+        } else {
+            reader.position += 12;
+            (reader.u32()? as u64, reader.u32()? as u64)
+        };
+        let content = binary.get(offset as usize..offset.checked_add(size)? as usize)?;
+        Some((name, content))
+    };
+    let (_, names) = read_header(names_index).ok_or_else(malformed)?;
+    for index in 0..count {
+        let (name_offset, content) = read_header(index).ok_or_else(malformed)?;
+        let section_name = names
+            .get(name_offset..)
+            .and_then(|it| it.split(|it| *it == 0).next())
+            .ok_or_else(malformed)?;
+        if section_name == name.as_bytes() {
+            return Ok(Some(content));
+        }
+    }
+    Ok(None)
+}
+
+/// reads the little-endian values of a binary
+struct Reader<'b> {
+    data: &'b [u8],
+    position: usize,
+}
+
+impl<'b> Reader<'b> {
+    fn new(data: &'b [u8]) -> Self {
+        Reader { data, position: 0 }
+    }
+
+    /// reads an unsigned value of the given number of bytes
+    fn offset(&mut self, size: usize) -> Option<u64> {
+        let bytes = self.data.get(self.position..self.position + size)?;
+        self.position += size;
+        Some(
+            bytes
+                .iter()
+                .rev()
+                .fold(0, |value, byte| (value << 8) | *byte as u64),
+        )
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.offset(1).map(|it| it as u8)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.offset(2).map(|it| it as u16)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.offset(4).map(|it| it as u32)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.offset(8)
+    }
+
+    fn uleb(&mut self) -> Option<u64> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= ((byte & 0x7f) as u64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+    }
+
+    fn sleb(&mut self) -> Option<i64> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= ((byte & 0x7f) as i64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+                return Some(value);
+            }
+        }
+    }
+
+    /// reads a 0-terminated string
+    fn string(&mut self) -> Option<String> {
+        let rest = self.data.get(self.position..)?;
+        let length = rest.iter().position(|it| *it == 0)?;
+        self.position += length + 1;
+        Some(String::from_utf8_lossy(&rest[..length]).to_string())
+    }
+}
+
+/// returns the hexadecimal addresses (e.g. `0x4011a2`) of a backtrace or crash log
+pub fn find_addresses(text: &str) -> Vec<u64> {
+    let pattern = Regex::new(r"\b0[xX]([0-9a-fA-F]+)\b").expect("valid pattern");
+    pattern
+        .captures_iter(text)
+        .filter_map(|it| u64::from_str_radix(&it[1], 16).ok())
+        .collect()
+}
+
+/// looks up the symbol and the source location of the given address
+pub fn symbolicate(address: u64, map: &MapFile, lines: Option<&LineTable>) -> Frame {
+    Frame {
+        address,
+        symbol: map
+            .find_symbol(address)
+            .map(|it| (it.name.clone(), address - it.address)),
+        location: lines.and_then(|it| it.find_location(address)),
+    }
+}
+
+/// describes the given frame, followed by the `context` lines surrounding its location if the
+/// source is available
+pub fn format_frame(number: usize, frame: &Frame, source: Option<&str>, context: usize) -> String {
+    let mut result = format!("#{} {:#018x} in ", number, frame.address);
+    match &frame.symbol {
+        Some((name, 0)) => result.push_str(name),
+        Some((name, offset)) => {
+            let _ = write!(result, "{} + {:#x}", name, offset);
+        }
+        None => result.push_str("??"),
+    }
+    result.push('\n');
+    if let Some(location) = &frame.location {
+        let _ = writeln!(
+            result,
+            "    at {}:{}:{}",
+            location.file, location.line, location.column
+        );
+        if let Some(source) = source.filter(|_| location.line > 0) {
+            let line = location.line as usize;
+            let first = line.saturating_sub(context).max(1);
+            let last = line + context;
+            let width = last.to_string().len();
+            for (number, text) in source
+                .lines()
+                .enumerate()
+                .map(|(index, text)| (index + 1, text))
+                .skip(first - 1)
+                .take(last - first + 1)
+            {
+                let marker = if number == line { ">" } else { " " };
+                let text = format!("  {} {:>width$} | {}", marker, number, text, width = width);
+                result.push_str(text.trim_end());
+                result.push('\n');
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_addresses, format_frame, symbolicate, LineTable, MapFile, SourceLocation};
+
+    const MAP: &str = "             VMA              LMA     Size Align Out     In      Symbol
+          200200           200200       10     8 .rodata
+          200200           200200       10     8         main.o:(.rodata)
+          201210           201210       60    16 .text
+          201210           201210       60    16         main.o:(.text)
+          201210           201210        0     1                 mainProg
+          201240           201240        0     1                 Motor.stop
+          202270           202270        8     8 .data
+          202270           202270        8     8         main.o:(.data)
+          202270           202270        0     1                 mainProg_instance
+";
+
+    /// a line program for the instructions 0x201210 to 0x201250 of `/plc/main.st`
+    fn create_line_section() -> Vec<u8> {
+        let mut header = vec![
+            1,    //minimum instruction length
+            1,    //maximum operations per instruction
+            1,    //default is statement
+            0xfb, //line base (-5)
+            14,   //line range
+            13,   //opcode base
+            0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1, //standard opcode lengths
+        ];
+        header.extend(b"/plc\0\0");
+        header.extend(b"main.st\0\x01\0\0\0");
+        let program = vec![
+            0, 9, 2, 0x10, 0x12, 0x20, 0, 0, 0, 0, 0, //set address 0x201210
+            3, 4, //advance line to 5
+            5, 5, //set column 5
+            1, //copy
+            2, 0x12, //advance pc by 0x12
+            3, 2, //advance line to 7
+            1, //copy
+            2, 0x1e, //advance pc to 0x201240
+            3, 5, //advance line to 12
+            1, //copy
+            2, 0x10, //advance pc to 0x201250
+            0, 1, 1, //end sequence
+        ];
+        let mut unit = vec![4, 0]; //version
+        unit.extend((header.len() as u32).to_le_bytes());
+        unit.extend(header);
+        unit.extend(program);
+        let mut section = (unit.len() as u32).to_le_bytes().to_vec();
+        section.extend(unit);
+        section
+    }
+
+    #[test]
+    fn symbols_are_found_by_their_address_range() {
+        let map = MapFile::parse(MAP);
+
+        let symbol = map.find_symbol(0x201222).unwrap();
+        assert_eq!((symbol.name.as_str(), symbol.size), ("mainProg", 0x30));
+        let symbol = map.find_symbol(0x20126f).unwrap();
+        assert_eq!((symbol.name.as_str(), symbol.size), ("Motor.stop", 0x30));
+        assert_eq!(map.find_symbol(0x202270).unwrap().name, "mainProg_instance");
+        //sections without symbols
+        assert_eq!(map.find_symbol(0x200208), None);
+        assert_eq!(map.find_symbol(0x201270), None);
+    }
+
+    #[test]
+    fn locations_are_read_from_the_line_program() {
+        let lines = LineTable::parse(&create_line_section()).unwrap();
+
+        let location = |line, column| {
+            Some(SourceLocation {
+                file: "/plc/main.st".to_string(),
+                line,
+                column,
+            })
+        };
+        assert_eq!(lines.find_location(0x201210), location(5, 5));
+        assert_eq!(lines.find_location(0x201223), location(7, 5));
+        assert_eq!(lines.find_location(0x20124f), location(12, 5));
+        assert_eq!(lines.find_location(0x201250), None);
+        assert_eq!(lines.find_location(0x201200), None);
+    }
+
+    #[test]
+    fn frames_show_the_pou_and_the_surrounding_source() {
+        let map = MapFile::parse(MAP);
+        let lines = LineTable::parse(&create_line_section()).unwrap();
+        let source = "PROGRAM mainProg\nVAR x, y : INT; END_VAR\n\nx := 1;\n    x := 2;\n\n    y := x / 0;\nEND_PROGRAM\n";
+
+        let frame = symbolicate(0x201230, &map, Some(&lines));
+        assert_eq!(
+            format_frame(0, &frame, Some(source), 1),
+            "#0 0x0000000000201230 in mainProg + 0x20
+    at /plc/main.st:7:5
+    6 |
+  > 7 |     y := x / 0;
+    8 | END_PROGRAM
+"
+        );
+
+        let frame = symbolicate(0x300000, &map, None);
+        assert_eq!(
+            format_frame(1, &frame, None, 1),
+            "#1 0x0000000000300000 in ??\n"
+        );
+    }
+
+    #[test]
+    fn addresses_are_found_in_backtraces() {
+        let backtrace =
+            "Segmentation fault at 0x0000000000201230\n#1 0x201240 in ?? ()\nstack: 0xzz 1234";
+        assert_eq!(find_addresses(backtrace), vec![0x201230, 0x201240]);
+    }
+}
@@ -0,0 +1,337 @@
+use rusty_runtime::events::EVENT_COUNT;
+use serde::Deserialize;
+
+use crate::{
+    config_file::{load_config, parse_config},
+    diagnostics::Diagnostic,
+    ConfigFormat,
+};
+
+/// the tasks of a plc-application and the programs they execute
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct TaskConfiguration {
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    /// additional instances of programs, executed by listing their name in a task's programs
+    #[serde(default)]
+    pub instances: Vec<ProgramInstance>,
+    /// the variables serialized to JSON for the runtime's telemetry, if any
+    #[serde(default)]
+    pub telemetry: Option<Telemetry>,
+}
+
+/// a task executing a list of programs, either cyclically or triggered by an event
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct Task {
+    pub name: String,
+    #[serde(default)]
+    pub priority: u32,
+    /// the task's cycle time in milliseconds, a task without interval runs in every scheduler cycle
+    #[serde(default)]
+    pub interval: u64,
+    #[serde(default)]
+    pub programs: Vec<String>,
+    /// the instance path of a BOOL variable (e.g. `io.start`), the task's programs are only
+    /// executed in the cycles its rising edge is detected. The variable is sampled by the task's
+    /// interval
+    #[serde(default)]
+    pub trigger: Option<String>,
+    /// the number (0 to 63) of an external event (e.g. an interrupt), the task is only executed
+    /// after the event was raised, by the runtime's `events::raise` or the application's
+    /// `RAISE_EVENT`
+    #[serde(default)]
+    pub event: Option<u32>,
+    /// the processor core the runtime executes the task on, tasks without core share the
+    /// runtime's main thread
+    #[serde(default)]
+    pub core: Option<u32>,
+}
+
+impl Task {
+    /// returns true if the task is executed after an external event instead of cyclically
+    pub fn is_event_task(&self) -> bool {
+        self.event.is_some()
+    }
+}
+
+/// an additional instance of a program (e.g. one per identical machine station), it gets its own
+/// global instance struct `<name>_instance` and a cycle function `<name>()` calling the program
+/// with it
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ProgramInstance {
+    pub name: String,
+    pub program: String,
+}
+
+/// the variables published cyclically by the runtime (e.g. to an MQTT broker)
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Telemetry {
+    /// the publishing cycle in milliseconds, without interval the values are published after every
+    /// scheduler cycle
+    #[serde(default)]
+    pub interval: u64,
+    /// the instance paths of the published variables (e.g. `prg.fb1.out`)
+    #[serde(default)]
+    pub symbols: Vec<String>,
+}
+
+impl TaskConfiguration {
+    /// reads the task configuration from the given file, the format is detected by its extension
+    pub fn load(file: &str) -> Result<TaskConfiguration, Diagnostic> {
+        load_config(file).and_then(TaskConfiguration::validate)
+    }
+
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<TaskConfiguration, Diagnostic> {
+        parse_config(content, format).and_then(TaskConfiguration::validate)
+    }
+
+    /// returns the given configuration if its tasks and instances are valid
+    fn validate(configuration: TaskConfiguration) -> Result<TaskConfiguration, Diagnostic> {
+        if let Some(task) = configuration
+            .tasks
+            .iter()
+            .find(|it| it.event.map_or(false, |event| event >= EVENT_COUNT))
+        {
+            return Err(Diagnostic::param_error(&format!(
+                "Task '{}' is executed by unknown event {}, events are numbered from 0 to {}",
+                task.name,
+                task.event.unwrap_or_default(),
+                EVENT_COUNT - 1
+            )));
+        }
+        //an external event releases a task at any time, it cannot be sampled by an interval
+        if let Some(task) = configuration
+            .tasks
+            .iter()
+            .find(|it| it.is_event_task() && (it.interval > 0 || it.trigger.is_some()))
+        {
+            return Err(Diagnostic::param_error(&format!(
+                "Task '{}' is executed by an event, it cannot have an interval or a trigger",
+                task.name
+            )));
+        }
+        let instances = &configuration.instances;
+        if let Some((_, instance)) = instances.iter().enumerate().find(|(position, it)| {
+            instances[..*position]
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(&it.name))
+        }) {
+            return Err(Diagnostic::param_error(&format!(
+                "Program instance '{}' is declared more than once",
+                instance.name
+            )));
+        }
+        Ok(configuration)
+    }
+
+    /// returns the additional program instance with the given name, if any
+    pub fn find_instance(&self, name: &str) -> Option<&ProgramInstance> {
+        self.instances
+            .iter()
+            .find(|it| it.name.eq_ignore_ascii_case(name))
+    }
+
+    /// returns the program executed by the given entry of a task's programs, the program of an
+    /// additional instance or the entry itself
+    pub fn get_program_of<'a>(&'a self, name: &'a str) -> &'a str {
+        self.find_instance(name)
+            .map_or(name, |it| it.program.as_str())
+    }
+
+    /// returns the names of all tasks executing the given program
+    pub fn get_tasks_of(&self, program: &str) -> Vec<&str> {
+        self.tasks
+            .iter()
+            .filter(|task| {
+                task.programs
+                    .iter()
+                    .any(|it| it.eq_ignore_ascii_case(program))
+            })
+            .map(|task| task.name.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProgramInstance, Task, TaskConfiguration, Telemetry};
+    use crate::{diagnostics::Diagnostic, ConfigFormat};
+
+    #[test]
+    fn task_configuration_can_be_parsed_from_json() {
+        let config = TaskConfiguration::parse(
+            r#"{ "tasks": [
+                { "name": "fast", "priority": 1, "interval": 10, "programs": ["motion"] },
+                { "name": "slow", "programs": ["hmi", "logging"] }
+            ]}"#,
+            ConfigFormat::JSON,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            TaskConfiguration {
+                tasks: vec![
+                    Task {
+                        name: "fast".into(),
+                        priority: 1,
+                        interval: 10,
+                        programs: vec!["motion".into()],
+                        ..Default::default()
+                    },
+                    Task {
+                        name: "slow".into(),
+                        priority: 0,
+                        interval: 0,
+                        programs: vec!["hmi".into(), "logging".into()],
+                        ..Default::default()
+                    },
+                ],
+                instances: vec![],
+                telemetry: None,
+            }
+        );
+        assert_eq!(config.get_tasks_of("HMI"), vec!["slow"]);
+    }
+
+    #[test]
+    fn task_configuration_can_be_parsed_from_toml() {
+        let config = TaskConfiguration::parse(
+            r#"
+            [[tasks]]
+            name = "fast"
+            programs = ["motion"]
+            "#,
+            ConfigFormat::TOML,
+        )
+        .unwrap();
+
+        assert_eq!(config.get_tasks_of("motion"), vec!["fast"]);
+    }
+
+    #[test]
+    fn event_tasks_can_be_parsed() {
+        let config = TaskConfiguration::parse(
+            r#"{ "tasks": [
+                { "name": "start", "trigger": "io.start", "interval": 5, "programs": ["sequence"] },
+                { "name": "alarm", "priority": 1, "event": 3, "programs": ["alarm"] }
+            ]}"#,
+            ConfigFormat::JSON,
+        )
+        .unwrap();
+
+        assert_eq!(config.tasks[0].trigger.as_deref(), Some("io.start"));
+        assert!(!config.tasks[0].is_event_task());
+        assert_eq!(config.tasks[1].event, Some(3));
+        assert!(config.tasks[1].is_event_task());
+    }
+
+    #[test]
+    fn task_cores_can_be_parsed() {
+        let config = TaskConfiguration::parse(
+            r#"{ "tasks": [
+                { "name": "motion", "interval": 1, "core": 1, "programs": ["motion"] },
+                { "name": "hmi", "interval": 100, "programs": ["hmi"] }
+            ]}"#,
+            ConfigFormat::JSON,
+        )
+        .unwrap();
+
+        assert_eq!(config.tasks[0].core, Some(1));
+        assert_eq!(config.tasks[1].core, None);
+    }
+
+    #[test]
+    fn program_instances_can_be_parsed() {
+        let config = TaskConfiguration::parse(
+            r#"
+            [[tasks]]
+            name = "stations"
+            programs = ["station_a", "station_b"]
+
+            [[instances]]
+            name = "station_a"
+            program = "station"
+
+            [[instances]]
+            name = "station_b"
+            program = "station"
+            "#,
+            ConfigFormat::TOML,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.find_instance("STATION_B"),
+            Some(&ProgramInstance {
+                name: "station_b".into(),
+                program: "station".into()
+            })
+        );
+        assert_eq!(config.get_program_of("station_a"), "station");
+        assert_eq!(config.get_program_of("hmi"), "hmi");
+    }
+
+    #[test]
+    fn duplicated_program_instances_are_reported() {
+        let result = TaskConfiguration::parse(
+            r#"{ "instances": [
+                { "name": "station_a", "program": "station" },
+                { "name": "Station_A", "program": "station" }
+            ]}"#,
+            ConfigFormat::JSON,
+        );
+
+        assert_eq!(
+            result,
+            Err(Diagnostic::param_error(
+                "Program instance 'Station_A' is declared more than once"
+            ))
+        );
+    }
+
+    #[test]
+    fn event_tasks_with_an_interval_are_reported() {
+        let result = TaskConfiguration::parse(
+            r#"
+            [[tasks]]
+            name = "alarm"
+            interval = 10
+            event = 3
+            "#,
+            ConfigFormat::TOML,
+        );
+
+        assert_eq!(
+            result,
+            Err(Diagnostic::param_error(
+                "Task 'alarm' is executed by an event, it cannot have an interval or a trigger"
+            ))
+        );
+    }
+
+    #[test]
+    fn telemetry_can_be_parsed_from_toml() {
+        let config = TaskConfiguration::parse(
+            r#"
+            [[tasks]]
+            name = "fast"
+            programs = ["motion"]
+
+            [telemetry]
+            interval = 500
+            symbols = ["motion.speed", "counter"]
+            "#,
+            ConfigFormat::TOML,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.telemetry,
+            Some(Telemetry {
+                interval: 500,
+                symbols: vec!["motion.speed".into(), "counter".into()]
+            })
+        );
+    }
+}
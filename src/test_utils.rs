@@ -8,13 +8,17 @@ pub mod tests {
         ast::{self, CompilationUnit},
         builtins,
         diagnostics::{Diagnostic, Diagnostician},
+        dialect::Dialect,
         index::{self, Index},
         lexer::{self, IdProvider},
         parser,
         resolver::{
-            const_evaluator::evaluate_constants, AnnotationMapImpl, AstAnnotations, TypeAnnotator,
+            const_evaluator::evaluate_constants, AnnotationMapImpl, AstAnnotations, StringLiterals,
+            TypeAnnotator,
         },
-        SourceContainer, Validator,
+        task_configuration::TaskConfiguration,
+        ModuleOptions, PrototypeValidator, RaceValidator, SafetyValidator, SourceCode,
+        SourceContainer, UsageValidator, Validator,
     };
 
     pub fn parse(src: &str) -> (CompilationUnit, Vec<Diagnostic>) {
@@ -44,8 +48,10 @@ pub mod tests {
             lexer::lex_with_ids(src, id_provider.clone()),
             ast::LinkageType::Internal,
         );
+        ast::resolve_namespaces(vec![&mut unit]);
         ast::pre_process(&mut unit, id_provider.clone());
         index.import(index::visitor::visit(&unit, id_provider));
+        index.merge_inherited_members();
         (unit, index)
     }
 
@@ -54,6 +60,18 @@ pub mod tests {
         do_index(src, id_provider)
     }
 
+    /// evaluates the constants of the given index and annotates the unit, the types created by
+    /// the annotation are imported into the returned index
+    fn annotate_and_index(
+        unit: &CompilationUnit,
+        index: Index,
+    ) -> (Index, AnnotationMapImpl, StringLiterals) {
+        let (mut index, ..) = evaluate_constants(index);
+        let (mut annotations, literals) = TypeAnnotator::visit_unit(&index, unit);
+        index.import(std::mem::take(&mut annotations.new_index));
+        (index, annotations, literals)
+    }
+
     pub fn annotate(parse_result: &CompilationUnit, index: &mut Index) -> AnnotationMapImpl {
         let (mut annotations, _) = TypeAnnotator::visit_unit(index, parse_result);
         index.import(std::mem::take(&mut annotations.new_index));
@@ -62,35 +80,166 @@ pub mod tests {
 
     pub fn parse_and_validate(src: &str) -> Vec<Diagnostic> {
         let (unit, index) = index(src);
-
-        let (mut index, ..) = evaluate_constants(index);
-        let (mut annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
-        index.import(std::mem::take(&mut annotations.new_index));
+        let (index, annotations, _) = annotate_and_index(&unit, index);
 
         let mut validator = Validator::new();
         validator.visit_unit(&annotations, &index, &unit);
         validator.diagnostics()
     }
 
-    pub fn codegen_without_unwrap(src: &str) -> Result<String, Diagnostic> {
+    /// returns the syntax- and validation-diagnostics of the given source in the given dialect,
+    /// the source can use the runtime library of hosted targets
+    pub fn parse_and_validate_with_dialect(src: &str, dialect: Dialect) -> Vec<Diagnostic> {
+        let id_provider = IdProvider::default();
+        let mut index = Index::default();
+        let builtins = builtins::parse_built_ins(id_provider.clone());
+        index.import(index::visitor::visit(&builtins, id_provider.clone()));
+        let runtime_library = builtins::parse_runtime_library(id_provider.clone());
+        index.import(index::visitor::visit(&runtime_library, id_provider.clone()));
+
+        let (mut unit, mut diagnostics) = parser::parse(
+            lexer::lex_with_dialect(src, id_provider.clone(), dialect),
+            ast::LinkageType::Internal,
+        );
+        ast::resolve_namespaces(vec![&mut unit]);
+        ast::pre_process(&mut unit, id_provider.clone());
+        index.import(index::visitor::visit(&unit, id_provider));
+        index.merge_inherited_members();
+        let (index, annotations, _) = annotate_and_index(&unit, index);
+
+        let mut validator = Validator::with_dialect(dialect);
+        validator.visit_unit(&annotations, &index, &unit);
+        diagnostics.append(&mut validator.diagnostics());
+        diagnostics
+    }
+
+    pub fn parse_and_validate_usage(src: &str) -> Vec<Diagnostic> {
+        let (unit, index) = index(src);
+        let (_, annotations, _) = annotate_and_index(&unit, index);
+
+        let mut validator = UsageValidator::new();
+        validator.visit_unit(&annotations, &unit);
+        validator.diagnostics_for(&unit)
+    }
+
+    /// returns the diagnostics of the prototypes of the given units, each source is parsed as
+    /// its own compilation unit
+    pub fn parse_and_validate_prototypes(sources: &[&str]) -> Vec<Vec<Diagnostic>> {
+        let units: Vec<CompilationUnit> = sources.iter().map(|it| parse(it).0).collect();
+        let mut validator = PrototypeValidator::new();
+        units.iter().for_each(|it| validator.visit_unit(it));
+        units
+            .iter()
+            .map(|it| validator.diagnostics_for(it))
+            .collect()
+    }
+
+    pub fn parse_and_validate_races(src: &str, tasks: &TaskConfiguration) -> Vec<Diagnostic> {
+        let (unit, index) = index(src);
+        let (index, annotations, _) = annotate_and_index(&unit, index);
+
+        let mut validator = RaceValidator::new();
+        validator.visit_unit(&annotations, &index, &unit);
+        validator.diagnostics_for(tasks, &unit)
+    }
+
+    pub fn parse_and_validate_single_writers(src: &str) -> Vec<Diagnostic> {
+        let (unit, index) = index(src);
+        let (index, annotations, _) = annotate_and_index(&unit, index);
+
+        let mut validator = RaceValidator::new();
+        validator.visit_unit(&annotations, &index, &unit);
+        validator.single_writer_diagnostics_for(&unit)
+    }
+
+    pub fn parse_and_validate_safety(src: &str) -> Vec<Diagnostic> {
+        let (unit, index) = index(src);
+        let (index, annotations, _) = annotate_and_index(&unit, index);
+
+        let mut validator = SafetyValidator::new();
+        let mut violations = validator.visit_unit(&annotations, &index, &unit);
+        violations.extend(validator.find_recursions(&unit));
+        violations
+            .into_iter()
+            .map(|(_, diagnostic)| diagnostic)
+            .collect()
+    }
+
+    /// generates the given source with the options generating code (`wcet_annotations`,
+    /// `fast_math` and `check_bounds`), no file is written
+    pub fn codegen_with_options(src: &str, options: &ModuleOptions) -> Result<String, Diagnostic> {
         let mut id_provider = IdProvider::default();
         let (unit, index) = do_index(src, id_provider.clone());
-
-        let (mut index, ..) = evaluate_constants(index);
-        let (mut annotations, literals) = TypeAnnotator::visit_unit(&index, &unit);
-        index.import(std::mem::take(&mut annotations.new_index));
+        let (index, annotations, literals) = annotate_and_index(&unit, index);
 
         let context = inkwell::context::Context::create();
-        let code_generator = crate::codegen::CodeGen::new(&context, "main");
+        let mut code_generator = crate::codegen::CodeGen::new(&context, "main");
+        if options.wcet_annotations.is_some() {
+            code_generator = code_generator.with_timing_annotations();
+        }
+        if options.fast_math {
+            code_generator = code_generator.with_fast_math();
+        }
+        if options.check_bounds {
+            code_generator = code_generator.with_bounds_check();
+        }
         let annotations = AstAnnotations::new(annotations, id_provider.next_id());
         let llvm_index = code_generator.generate_llvm_index(&annotations, literals, &index)?;
         code_generator.generate(&unit, &annotations, &index, &llvm_index)
     }
 
+    pub fn codegen_without_unwrap(src: &str) -> Result<String, Diagnostic> {
+        codegen_with_options(src, &ModuleOptions::default())
+    }
+
     pub fn codegen(src: &str) -> String {
         codegen_without_unwrap(src).unwrap()
     }
 
+    /// generates the given source annotated for WCET analyzers (see `timing_generator`)
+    pub fn codegen_with_timing_annotations(src: &str) -> String {
+        let options = ModuleOptions {
+            wcet_annotations: Some("main.wcet.json"),
+            ..ModuleOptions::default()
+        };
+        codegen_with_options(src, &options).unwrap()
+    }
+
+    /// generates the given source checking the indexes of array accesses (see `bounds_check`)
+    pub fn codegen_with_bounds_check(src: &str) -> Result<String, Diagnostic> {
+        let options = ModuleOptions {
+            check_bounds: true,
+            ..ModuleOptions::default()
+        };
+        codegen_with_options(src, &options)
+    }
+
+    /// compiles the given source with DWARF debug information, verifies the module and returns the
+    /// generated IR
+    pub fn codegen_with_debug_info(src: &str) -> String {
+        let context = Context::create();
+        let source = SourceCode {
+            path: "main.st".into(),
+            source: src.into(),
+        };
+        let (_, code_generator) = crate::compile_module_with_options(
+            &context,
+            vec![source],
+            vec![],
+            None,
+            Diagnostician::default(),
+            &ModuleOptions {
+                debug_info: true,
+                ..ModuleOptions::default()
+            },
+        )
+        .unwrap();
+        if let Err(message) = code_generator.module.verify() {
+            panic!("{}", message.to_string());
+        }
+        code_generator.module.print_to_string().to_string()
+    }
+
     pub fn generate_with_empty_program(src: &str) -> String {
         let source = format!("{} {}", "PROGRAM main END_PROGRAM", src);
         codegen(source.as_str())
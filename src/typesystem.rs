@@ -74,6 +74,10 @@ pub const WSTRING_TYPE: &str = "WSTRING";
 pub const CHAR_TYPE: &str = "CHAR";
 pub const WCHAR_TYPE: &str = "WCHAR";
 pub const VOID_TYPE: &str = "VOID";
+/// the fixed-point type family, declared as `FIXED(integer_bits, fraction_bits)`
+pub const FIXED_TYPE: &str = "FIXED";
+/// the type of the hidden member pointing to the vtable of a polymorphic class' instance
+pub const VTABLE_POINTER_TYPE: &str = "__VTABLE_POINTER";
 
 #[cfg(test)]
 mod tests;
@@ -166,6 +170,18 @@ impl TypeSize {
 pub enum StructSource {
     OriginalDeclaration,
     Pou(PouType),
+    Internal(InternalType),
+}
+
+/// structs generated by the compiler
+#[derive(Debug, Clone, PartialEq)]
+pub enum InternalType {
+    /// the fat pointer passed for an `ARRAY[*] OF ...` parameter: a pointer to the array's first
+    /// element (`data`) and the lower and upper bound of each of its dimensions (`dimensions`)
+    VariableLengthArray {
+        inner_type_name: TypeId,
+        ndims: usize,
+    },
 }
 
 type TypeId = String;
@@ -205,6 +221,12 @@ pub enum DataTypeInformation {
         name: TypeId,
         size: u32,
     },
+    /// a signed number stored as an integer of `size` bits scaled by 2^fraction_bits
+    FixedPoint {
+        name: TypeId,
+        size: u32,
+        fraction_bits: u32,
+    },
     String {
         size: TypeSize,
         encoding: StringEncoding,
@@ -234,6 +256,7 @@ impl DataTypeInformation {
             | DataTypeInformation::Pointer { name, .. }
             | DataTypeInformation::Integer { name, .. }
             | DataTypeInformation::Float { name, .. }
+            | DataTypeInformation::FixedPoint { name, .. }
             | DataTypeInformation::SubRange { name, .. }
             | DataTypeInformation::Alias { name, .. }
             | DataTypeInformation::Enum { name, .. }
@@ -295,6 +318,10 @@ impl DataTypeInformation {
         matches!(self, DataTypeInformation::Float { .. })
     }
 
+    pub fn is_fixed_point(&self) -> bool {
+        matches!(self, DataTypeInformation::FixedPoint { .. })
+    }
+
     pub fn is_struct(&self) -> bool {
         matches!(self, DataTypeInformation::Struct { .. })
     }
@@ -303,6 +330,25 @@ impl DataTypeInformation {
         matches!(self, DataTypeInformation::Array { .. })
     }
 
+    pub fn is_vla(&self) -> bool {
+        self.get_vla_dimensions().is_some()
+    }
+
+    /// returns the inner type and the number of dimensions of a variable-length array
+    pub fn get_vla_dimensions(&self) -> Option<(&str, usize)> {
+        match self {
+            DataTypeInformation::Struct {
+                source:
+                    StructSource::Internal(InternalType::VariableLengthArray {
+                        inner_type_name,
+                        ndims,
+                    }),
+                ..
+            } => Some((inner_type_name.as_str(), *ndims)),
+            _ => None,
+        }
+    }
+
     pub fn is_numerical(&self) -> bool {
         matches!(
             self,
@@ -370,6 +416,7 @@ impl DataTypeInformation {
         match self {
             DataTypeInformation::Integer { size, .. } => *size,
             DataTypeInformation::Float { size, .. } => *size,
+            DataTypeInformation::FixedPoint { size, .. } => *size,
             DataTypeInformation::String { .. } => unimplemented!("string"),
             DataTypeInformation::Struct { .. } => 0, //TODO : Should we fill in the struct members here for size calculation or save the struct size.
             DataTypeInformation::Array { .. } => unimplemented!("array"), //Propably length * inner type size
@@ -382,6 +429,75 @@ impl DataTypeInformation {
         }
     }
 
+    /// returns the number of bytes used to store this type or None if its size is not known at
+    /// compile time (e.g. pointers and generics)
+    pub fn get_size_in_bytes(&self, index: &Index) -> Option<u64> {
+        self.get_size_and_alignment(index).map(|(size, _)| size)
+    }
+
+    /// returns the size and the alignment of this type in bytes, members are aligned to their size
+    fn get_size_and_alignment(&self, index: &Index) -> Option<(u64, u64)> {
+        let get_inner = |name: &str| {
+            index
+                .find_effective_type_info(name)?
+                .get_size_and_alignment(index)
+        };
+        match self {
+            DataTypeInformation::Integer { size, .. }
+            | DataTypeInformation::Float { size, .. }
+            | DataTypeInformation::FixedPoint { size, .. } => {
+                let bytes = ((*size + 7) / 8) as u64;
+                Some((bytes, bytes))
+            }
+            DataTypeInformation::String { size, encoding } => {
+                let width = match encoding {
+                    StringEncoding::Utf8 => 1,
+                    StringEncoding::Utf16 => 2,
+                };
+                let length = size.as_int_value(index).ok()? as u64;
+                Some((length * width, width))
+            }
+            DataTypeInformation::Array {
+                inner_type_name,
+                dimensions,
+                ..
+            } => {
+                let (inner_size, alignment) = get_inner(inner_type_name)?;
+                dimensions
+                    .iter()
+                    .map(|it| it.get_length(index).ok())
+                    .try_fold(inner_size, |size, length| Some(size * length? as u64))
+                    .map(|size| (size, alignment))
+            }
+            DataTypeInformation::Struct {
+                name,
+                source: StructSource::OriginalDeclaration,
+                ..
+            } => {
+                let mut members = index.get_container_members(name);
+                members.sort_by_key(|it| it.get_location_in_parent());
+                let mut size = 0;
+                let mut alignment = 1;
+                for member in members {
+                    let (member_size, member_alignment) = get_inner(member.get_type_name())?;
+                    size = align_to(size, member_alignment) + member_size;
+                    alignment = alignment.max(member_alignment);
+                }
+                Some((align_to(size, alignment), alignment))
+            }
+            DataTypeInformation::Enum {
+                referenced_type, ..
+            }
+            | DataTypeInformation::SubRange {
+                referenced_type, ..
+            }
+            | DataTypeInformation::Alias {
+                referenced_type, ..
+            } => get_inner(referenced_type),
+            _ => None,
+        }
+    }
+
     pub fn get_alignment(&self) -> u32 {
         match self {
             DataTypeInformation::String { encoding, .. } if encoding == &StringEncoding::Utf8 => 1,
@@ -802,6 +918,11 @@ fn get_rank(type_information: &DataTypeInformation, index: &Index) -> u32 {
     }
 }
 
+/// rounds the given offset up to the next multiple of the given alignment
+fn align_to(offset: u64, alignment: u64) -> u64 {
+    (offset + alignment - 1) / alignment * alignment
+}
+
 /// Returns true if provided types have the same type nature
 /// i.e. Both are numeric or both are floats
 pub fn is_same_type_class(
@@ -878,6 +999,44 @@ pub fn get_signed_type<'t>(
     Some(data_type)
 }
 
+/// returns the name of the conversion function that converts `from` into `to`
+/// (e.g. `INT_TO_DINT`) or `None` if there is no such conversion
+/// (e.g. for structs, arrays, pointers or equal types)
+pub fn get_conversion_function_name(
+    from: &DataTypeInformation,
+    to: &DataTypeInformation,
+    index: &Index,
+) -> Option<String> {
+    let from = get_elementary_type_name(from, index)?;
+    let to = get_elementary_type_name(to, index)?;
+    if from != to {
+        Some(format!("{}_TO_{}", from, to))
+    } else {
+        None
+    }
+}
+
+/// returns the name of the elementary type behind the given type (e.g. `STRING` for a
+/// `STRING[20]` or `INT` for an alias or a sub-range of `INT`) or `None` if it is no
+/// elementary type
+fn get_elementary_type_name<'idx>(
+    type_information: &'idx DataTypeInformation,
+    index: &'idx Index,
+) -> Option<&'idx str> {
+    match type_information {
+        DataTypeInformation::Integer { .. }
+        | DataTypeInformation::Float { .. }
+        | DataTypeInformation::String { .. } => Some(type_information.get_name()),
+        DataTypeInformation::SubRange {
+            referenced_type, ..
+        }
+        | DataTypeInformation::Alias {
+            referenced_type, ..
+        } => get_elementary_type_name(index.find_effective_type_info(referenced_type)?, index),
+        _ => None,
+    }
+}
+
 /**
  * returns the compare-function name for the given type and operator.
  * Returns None if the given operator is no comparison operator
@@ -3,10 +3,11 @@ use crate::{
     index::{visitor::visit, Index},
     lexer::IdProvider,
     typesystem::{
-        self, get_equals_function_name_for, get_signed_type, Dimension, BOOL_TYPE, BYTE_TYPE,
-        CHAR_TYPE, DATE_AND_TIME_TYPE, DATE_TYPE, DINT_TYPE, DWORD_TYPE, INT_TYPE, LINT_TYPE,
-        LREAL_TYPE, LWORD_TYPE, REAL_TYPE, SINT_TYPE, STRING_TYPE, TIME_OF_DAY_TYPE, TIME_TYPE,
-        UDINT_TYPE, UINT_TYPE, ULINT_TYPE, USINT_TYPE, WCHAR_TYPE, WORD_TYPE, WSTRING_TYPE,
+        self, get_conversion_function_name, get_equals_function_name_for, get_signed_type,
+        Dimension, BOOL_TYPE, BYTE_TYPE, CHAR_TYPE, DATE_AND_TIME_TYPE, DATE_TYPE, DINT_TYPE,
+        DWORD_TYPE, INT_TYPE, LINT_TYPE, LREAL_TYPE, LWORD_TYPE, REAL_TYPE, SINT_TYPE, STRING_TYPE,
+        TIME_OF_DAY_TYPE, TIME_TYPE, UDINT_TYPE, UINT_TYPE, ULINT_TYPE, USINT_TYPE, WCHAR_TYPE,
+        WORD_TYPE, WSTRING_TYPE,
     },
 };
 
@@ -610,3 +611,106 @@ fn any_date_type_test() {
     assert!(date_time.has_nature(TypeNature::Any, &index));
     assert!(tod.has_nature(TypeNature::Any, &index));
 }
+
+#[test]
+fn conversion_function_names() {
+    let index = get_index();
+    let int = index.get_type_information_or_void(INT_TYPE);
+    let dint = index.get_type_information_or_void(DINT_TYPE);
+    let real = index.get_type_information_or_void(REAL_TYPE);
+    let string = index.get_type_information_or_void(STRING_TYPE);
+
+    assert_eq!(
+        get_conversion_function_name(int, dint, &index),
+        Some("INT_TO_DINT".to_string())
+    );
+    assert_eq!(
+        get_conversion_function_name(real, string, &index),
+        Some("REAL_TO_STRING".to_string())
+    );
+    assert_eq!(get_conversion_function_name(int, int, &index), None);
+    assert_eq!(
+        get_conversion_function_name(int, index.get_void_type().get_type_information(), &index),
+        None
+    );
+}
+
+#[test]
+fn conversion_function_names_use_the_elementary_types() {
+    let (_, index) = crate::test_utils::tests::index(
+        "
+        TYPE MyInt : INT; END_TYPE
+        TYPE Percent : INT(0..100); END_TYPE
+        TYPE Point : STRUCT x : INT; END_STRUCT END_TYPE
+        VAR_GLOBAL
+            name : STRING[20];
+            wide_name : WSTRING[20];
+        END_VAR
+        ",
+    );
+    let type_of = |name: &str| {
+        let type_name = index
+            .find_global_variable(name)
+            .map(|it| it.get_type_name())
+            .unwrap_or(name);
+        index.get_type_information_or_void(type_name)
+    };
+
+    assert_eq!(
+        get_conversion_function_name(type_of("name"), type_of(INT_TYPE), &index),
+        Some("STRING_TO_INT".to_string())
+    );
+    assert_eq!(
+        get_conversion_function_name(type_of("MyInt"), type_of("name"), &index),
+        Some("INT_TO_STRING".to_string())
+    );
+    assert_eq!(
+        get_conversion_function_name(type_of("Percent"), type_of(DINT_TYPE), &index),
+        Some("INT_TO_DINT".to_string())
+    );
+    assert_eq!(
+        get_conversion_function_name(type_of("name"), type_of("wide_name"), &index),
+        Some("STRING_TO_WSTRING".to_string())
+    );
+    assert_eq!(
+        get_conversion_function_name(type_of("MyInt"), type_of(INT_TYPE), &index),
+        None
+    );
+    assert_eq!(
+        get_conversion_function_name(type_of("Point"), type_of(INT_TYPE), &index),
+        None
+    );
+}
+
+#[test]
+fn sizes_in_bytes() {
+    let (_, index) = crate::test_utils::tests::index(
+        "
+        TYPE Header : STRUCT
+            kind : BYTE;
+            length : DINT;
+            flag : BOOL;
+        END_STRUCT END_TYPE
+        TYPE Frame : STRUCT
+            header : Header;
+            data : ARRAY[1..3, 0..1] OF WORD;
+            name : STRING[10];
+            wide_name : WSTRING[3];
+        END_STRUCT END_TYPE
+        TYPE IntReference : REF_TO INT; END_TYPE
+        ",
+    );
+    let get_size = |name: &str| {
+        index
+            .get_type_information_or_void(name)
+            .get_size_in_bytes(&index)
+    };
+
+    assert_eq!(get_size(LREAL_TYPE), Some(8));
+    assert_eq!(get_size(BOOL_TYPE), Some(1));
+    // kind, 3 bytes padding, length, flag, 3 bytes padding
+    assert_eq!(get_size("Header"), Some(12));
+    // header, data (12), name (11), wide_name (8), 1 byte padding
+    assert_eq!(get_size("Frame"), Some(44));
+    assert_eq!(get_size("IntReference"), None);
+}
@@ -3,8 +3,10 @@ use crate::{
         AstStatement, CompilationUnit, DataType, DataTypeDeclaration, Pou, SourceRange,
         UserTypeDeclaration, Variable, VariableBlock,
     },
+    dialect::Dialect,
     index::Index,
-    resolver::AnnotationMapImpl,
+    resolver::{AnnotationMap, AnnotationMapImpl, StatementAnnotation},
+    typesystem::VOID_TYPE,
     Diagnostic,
 };
 
@@ -12,9 +14,18 @@ use self::{
     pou_validator::PouValidator, stmt_validator::StatementValidator,
     variable_validator::VariableValidator,
 };
+pub use prototype_validator::PrototypeValidator;
+pub use race_validator::RaceValidator;
+pub use safety_validator::SafetyValidator;
+pub use usage_validator::UsageValidator;
 
+mod access_visitor;
 mod pou_validator;
+mod prototype_validator;
+mod race_validator;
+mod safety_validator;
 mod stmt_validator;
+mod usage_validator;
 mod variable_validator;
 
 #[cfg(test)]
@@ -31,10 +42,16 @@ macro_rules! visit_all_statements {
      };
    }
 
+#[derive(Clone, Copy)]
 pub struct ValidationContext<'s> {
     ast_annotation: &'s AnnotationMapImpl,
     index: &'s Index,
     qualifier: Option<&'s str>,
+    /// the type of the qualifying element when validating the members of a qualified reference
+    /// (e.g. `a`'s type when validating `b` in `a.b`)
+    member_of: Option<&'s str>,
+    /// the dialect deciding which builtins are available
+    dialect: Dialect,
 }
 
 pub struct Validator {
@@ -42,14 +59,21 @@ pub struct Validator {
     pou_validator: PouValidator,
     variable_validator: VariableValidator,
     stmt_validator: StatementValidator,
+    dialect: Dialect,
 }
 
 impl Validator {
     pub fn new() -> Validator {
+        Validator::with_dialect(Dialect::default())
+    }
+
+    /// creates a validator that reports the use of extensions not supported by the given dialect
+    pub fn with_dialect(dialect: Dialect) -> Validator {
         Validator {
             pou_validator: PouValidator::new(),
             variable_validator: VariableValidator::new(),
             stmt_validator: StatementValidator::new(),
+            dialect,
         }
     }
 
@@ -74,6 +98,8 @@ impl Validator {
                     ast_annotation: annotations,
                     index,
                     qualifier: Some(pou.name.as_str()),
+                    member_of: None,
+                    dialect: self.dialect,
                 },
             );
         }
@@ -82,6 +108,8 @@ impl Validator {
             ast_annotation: annotations,
             index,
             qualifier: None,
+            member_of: None,
+            dialect: self.dialect,
         };
         for t in &unit.types {
             self.visit_user_type_declaration(t, no_context);
@@ -91,15 +119,28 @@ impl Validator {
             self.visit_variable_container(no_context, gv);
         }
 
+        for config_variable in &unit.var_config {
+            self.variable_validator
+                .validate_config_variable(config_variable, no_context);
+        }
+
         for i in &unit.implementations {
             let context = ValidationContext {
                 ast_annotation: annotations,
                 index,
                 qualifier: Some(i.name.as_str()),
+                member_of: None,
+                dialect: self.dialect,
             };
+            if let Some(pou) = unit.units.iter().find(|it| it.name == i.name) {
+                self.pou_validator.validate_override(pou, i, &context);
+            }
             i.statements
                 .iter()
                 .for_each(|s| self.visit_statement(s, &context));
+            self.stmt_validator
+                .validate_unreachable_assignments(&i.statements, &context);
+            self.stmt_validator.validate_jumps(&i.statements, &context);
         }
     }
 
@@ -108,6 +149,11 @@ impl Validator {
         user_data_type: &UserTypeDeclaration,
         context: &ValidationContext,
     ) {
+        self.variable_validator.validate_type_cycle(
+            &user_data_type.data_type,
+            &user_data_type.location,
+            context,
+        );
         self.visit_data_type(context, &user_data_type.data_type, &user_data_type.location);
     }
 
@@ -186,13 +232,27 @@ impl Validator {
             AstStatement::MultipliedStatement { element, .. } => {
                 self.visit_statement(element, context)
             }
-            AstStatement::QualifiedReference { elements, .. } => elements
-                .iter()
-                .for_each(|e| self.visit_statement(e, context)),
+            AstStatement::QualifiedReference { elements, .. } => {
+                let mut member_context = ValidationContext {
+                    member_of: None,
+                    ..*context
+                };
+                for e in elements {
+                    self.visit_statement(e, &member_context);
+                    // the next element is a member of this element's type
+                    member_context.member_of = Some(get_qualifying_type_name(e, context));
+                }
+            }
             AstStatement::ArrayAccess {
                 reference, access, ..
             } => {
-                visit_all_statements!(self, context, reference, access);
+                self.visit_statement(reference, context);
+                // the access is never a member of the qualifier (e.g. `i` in `a.b[i]`)
+                let access_context = ValidationContext {
+                    member_of: None,
+                    ..*context
+                };
+                self.visit_statement(access, &access_context);
             }
             AstStatement::BinaryExpression { left, right, .. } => {
                 visit_all_statements!(self, context, left, right);
@@ -212,6 +272,10 @@ impl Validator {
                 self.visit_statement(left, context);
                 self.visit_statement(right, context);
             }
+            AstStatement::ReferenceAssignment { left, right, .. } => {
+                self.visit_statement(left, context);
+                self.visit_statement(right, context);
+            }
             AstStatement::CallStatement {
                 parameters,
                 operator,
@@ -283,3 +347,19 @@ impl Validator {
         self.stmt_validator.validate_statement(statement, context);
     }
 }
+
+/// returns the name of the type, whose members can be accessed via the given statement
+/// (e.g. `a`'s type in `a.b`)
+fn get_qualifying_type_name<'s>(
+    statement: &AstStatement,
+    context: &ValidationContext<'s>,
+) -> &'s str {
+    match context.ast_annotation.get(statement) {
+        Some(StatementAnnotation::Value { resulting_type })
+        | Some(StatementAnnotation::Variable { resulting_type, .. })
+        | Some(StatementAnnotation::Instance { resulting_type }) => resulting_type.as_str(),
+        Some(StatementAnnotation::Type { type_name }) => type_name.as_str(),
+        Some(StatementAnnotation::Program { qualified_name }) => qualified_name.as_str(),
+        Some(StatementAnnotation::Function { .. }) | None => VOID_TYPE,
+    }
+}
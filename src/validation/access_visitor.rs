@@ -0,0 +1,151 @@
+use crate::ast::AstStatement;
+
+/// the way a reference is accessed by a statement
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Access {
+    Read,
+    Write,
+    Call,
+}
+
+/// walks the given statement and reports every `Reference` together with the way
+/// it is accessed (e.g. the left side of an assignment is written, the operator of a
+/// call statement is called, everything else is read)
+pub fn visit_accesses<F>(statement: &AstStatement, access: Access, visitor: &mut F)
+where
+    F: FnMut(&AstStatement, Access),
+{
+    match statement {
+        AstStatement::Reference { .. } => visitor(statement, access),
+        AstStatement::QualifiedReference { elements, .. } => {
+            // writing to `a.b` or `a.%X1` also writes to `a`, calling `a.b()` only reads `a`
+            let last = elements.len().saturating_sub(1);
+            for (i, e) in elements.iter().enumerate() {
+                let element_access = if i < last && access == Access::Call {
+                    Access::Read
+                } else {
+                    access
+                };
+                visit_accesses(e, element_access, visitor);
+            }
+        }
+        AstStatement::ArrayAccess {
+            reference,
+            access: index,
+            ..
+        } => {
+            visit_accesses(reference, access, visitor);
+            visit_accesses(index, Access::Read, visitor);
+        }
+        AstStatement::DirectAccess { index, .. } => visit_accesses(index, Access::Read, visitor),
+        AstStatement::PointerAccess { reference, .. } => {
+            visit_accesses(reference, Access::Read, visitor)
+        }
+        AstStatement::CastStatement { target, .. } => visit_accesses(target, access, visitor),
+        AstStatement::MultipliedStatement { element, .. } => {
+            visit_accesses(element, Access::Read, visitor)
+        }
+        AstStatement::LiteralArray {
+            elements: Some(elements),
+            ..
+        } => visit_accesses(elements, Access::Read, visitor),
+        AstStatement::BinaryExpression { left, right, .. } => {
+            visit_accesses(left, Access::Read, visitor);
+            visit_accesses(right, Access::Read, visitor);
+        }
+        AstStatement::UnaryExpression { value, .. } => visit_accesses(value, Access::Read, visitor),
+        AstStatement::ExpressionList { expressions, .. } => expressions
+            .iter()
+            .for_each(|it| visit_accesses(it, Access::Read, visitor)),
+        AstStatement::RangeStatement { start, end, .. } => {
+            visit_accesses(start, Access::Read, visitor);
+            visit_accesses(end, Access::Read, visitor);
+        }
+        AstStatement::Assignment { left, right, .. } => {
+            visit_accesses(left, Access::Write, visitor);
+            visit_accesses(right, Access::Read, visitor);
+        }
+        AstStatement::ReferenceAssignment { left, right, .. } => {
+            // `r REF= x` binds r to x, x may be written through r afterwards
+            visit_accesses(left, Access::Write, visitor);
+            visit_accesses(right, Access::Write, visitor);
+        }
+        AstStatement::OutputAssignment { left, right, .. } => {
+            // `out => x` reads the callee's output and writes it to x
+            visit_accesses(left, Access::Read, visitor);
+            visit_accesses(right, Access::Write, visitor);
+        }
+        AstStatement::CallStatement {
+            operator,
+            parameters,
+            ..
+        } => {
+            visit_accesses(operator, Access::Call, visitor);
+            if let Some(parameters) = parameters.as_ref() {
+                visit_accesses(parameters, Access::Read, visitor);
+            }
+        }
+        AstStatement::IfStatement {
+            blocks, else_block, ..
+        } => {
+            for b in blocks {
+                visit_accesses(&b.condition, Access::Read, visitor);
+                visit_all_accesses(&b.body, visitor);
+            }
+            visit_all_accesses(else_block, visitor);
+        }
+        AstStatement::ForLoopStatement {
+            counter,
+            start,
+            end,
+            by_step,
+            body,
+            ..
+        } => {
+            // the loop itself reads the counter to check the end-condition
+            visit_accesses(counter, Access::Read, visitor);
+            visit_accesses(start, Access::Read, visitor);
+            visit_accesses(end, Access::Read, visitor);
+            if let Some(by_step) = by_step {
+                visit_accesses(by_step, Access::Read, visitor);
+            }
+            visit_all_accesses(body, visitor);
+        }
+        AstStatement::WhileLoopStatement {
+            condition, body, ..
+        }
+        | AstStatement::RepeatLoopStatement {
+            condition, body, ..
+        } => {
+            visit_accesses(condition, Access::Read, visitor);
+            visit_all_accesses(body, visitor);
+        }
+        AstStatement::CaseStatement {
+            selector,
+            case_blocks,
+            else_block,
+            ..
+        } => {
+            visit_accesses(selector, Access::Read, visitor);
+            for b in case_blocks {
+                visit_accesses(&b.condition, Access::Read, visitor);
+                visit_all_accesses(&b.body, visitor);
+            }
+            visit_all_accesses(else_block, visitor);
+        }
+        AstStatement::CaseCondition { condition, .. } => {
+            visit_accesses(condition, Access::Read, visitor)
+        }
+        _ => {}
+    }
+}
+
+/// visits all accesses of the given list of statements
+pub fn visit_all_accesses<F>(statements: &[AstStatement], visitor: &mut F)
+where
+    F: FnMut(&AstStatement, Access),
+{
+    statements
+        .iter()
+        .for_each(|it| visit_accesses(it, Access::Read, visitor));
+}
@@ -1,5 +1,9 @@
 use super::ValidationContext;
-use crate::{ast::Pou, Diagnostic, PouType};
+use crate::{
+    ast::{Implementation, Pou},
+    index::PouIndexEntry,
+    Diagnostic, PouType,
+};
 
 /// validates POUs
 pub struct PouValidator {
@@ -17,6 +21,91 @@ impl PouValidator {
         if pou.pou_type == PouType::Function {
             self.validate_function(pou, context);
         };
+        if let Some(super_class) = &pou.super_class {
+            self.validate_super_class(pou, super_class, context);
+        }
+        for interface in &pou.interfaces {
+            self.validate_interface(pou, interface, context);
+        }
+        if let PouType::Method { owner_class } = &pou.pou_type {
+            self.validate_overridden_method(pou, owner_class, context);
+        }
+        if pou.pou_type == PouType::Class {
+            self.validate_abstract_methods(pou, context);
+        }
+    }
+
+    /// a class that is not declared `ABSTRACT` must implement all of its abstract methods,
+    /// including the abstract methods inherited from its super classes
+    pub fn validate_abstract_methods(&mut self, pou: &Pou, context: &ValidationContext) {
+        if context
+            .index
+            .find_pou(&pou.name)
+            .map_or(true, PouIndexEntry::is_abstract)
+        {
+            return;
+        }
+
+        let missing_methods = context
+            .index
+            .get_virtual_methods(&pou.name)
+            .into_iter()
+            .filter(|it| it.is_abstract())
+            .map(|it| Pou::calc_return_name(it.get_name()))
+            .collect::<Vec<_>>();
+        if !missing_methods.is_empty() {
+            self.diagnostics
+                .push(Diagnostic::missing_abstract_method_implementation(
+                    &pou.name,
+                    &missing_methods,
+                    pou.name_location.to_owned(),
+                ));
+        }
+    }
+
+    /// a method overriding a method of a super class is called in its place, so it must have
+    /// the same signature
+    pub fn validate_overridden_method(
+        &mut self,
+        pou: &Pou,
+        owner_class: &str,
+        context: &ValidationContext,
+    ) {
+        let method_name = Pou::calc_return_name(&pou.name);
+        if let (Some(method), Some(overridden)) = (
+            context.index.find_pou(&pou.name),
+            find_overridden_method(owner_class, method_name, context),
+        ) {
+            if !has_same_signature(method, overridden, context) {
+                self.diagnostics
+                    .push(Diagnostic::override_signature_mismatch(
+                        method_name,
+                        overridden.get_container(),
+                        pou.name_location.to_owned(),
+                    ));
+            }
+        }
+    }
+
+    /// a method declared `OVERRIDE` must override a method of a super class
+    pub fn validate_override(
+        &mut self,
+        pou: &Pou,
+        implementation: &Implementation,
+        context: &ValidationContext,
+    ) {
+        if let PouType::Method { owner_class } = &pou.pou_type {
+            let method_name = Pou::calc_return_name(&pou.name);
+            if implementation.overriding
+                && find_overridden_method(owner_class, method_name, context).is_none()
+            {
+                self.diagnostics
+                    .push(Diagnostic::override_without_base_method(
+                        method_name,
+                        pou.name_location.to_owned(),
+                    ));
+            }
+        }
     }
 
     pub fn validate_function(&mut self, pou: &Pou, context: &ValidationContext) {
@@ -28,4 +117,138 @@ impl PouValidator {
             ));
         }
     }
+
+    /// a class must extend a known class, must not extend itself (directly or indirectly) and
+    /// must not redeclare an inherited member
+    pub fn validate_super_class(
+        &mut self,
+        pou: &Pou,
+        super_class: &str,
+        context: &ValidationContext,
+    ) {
+        if !matches!(
+            context.index.find_pou(super_class),
+            Some(PouIndexEntry::Class { .. })
+        ) {
+            self.diagnostics.push(Diagnostic::unknown_super_class(
+                super_class,
+                pou.name_location.to_owned(),
+            ));
+            return;
+        }
+
+        //the hierarchy stops before a class that is already part of it
+        let super_classes = context.index.get_super_classes(&pou.name);
+        let is_cyclic = context
+            .index
+            .find_pou(super_classes.last().copied().unwrap_or(&pou.name))
+            .and_then(PouIndexEntry::get_super_class)
+            .map_or(false, |it| it.eq_ignore_ascii_case(&pou.name));
+        if is_cyclic {
+            self.diagnostics.push(Diagnostic::cyclic_inheritance(
+                &pou.name,
+                pou.name_location.to_owned(),
+            ));
+            return;
+        }
+
+        for variable in pou
+            .variable_blocks
+            .iter()
+            .flat_map(|it| it.variables.iter())
+        {
+            if context
+                .index
+                .find_member(super_class, &variable.name)
+                .is_some()
+            {
+                self.diagnostics
+                    .push(Diagnostic::redeclared_inherited_member(
+                        &variable.name,
+                        super_class,
+                        variable.location.to_owned(),
+                    ));
+            }
+        }
+    }
+
+    /// a class or function block must implement every method of a known interface with the
+    /// interface's signature, the methods may be inherited from a super class
+    pub fn validate_interface(&mut self, pou: &Pou, interface: &str, context: &ValidationContext) {
+        let interface_name = match context.index.find_pou(interface) {
+            Some(PouIndexEntry::Interface { name, .. }) => name,
+            _ => {
+                self.diagnostics.push(Diagnostic::unknown_interface(
+                    interface,
+                    pou.name_location.to_owned(),
+                ));
+                return;
+            }
+        };
+
+        for prototype in context.index.get_interface_methods(interface_name) {
+            let method_name = Pou::calc_return_name(prototype.get_name());
+            match context.index.find_method(&pou.name, method_name) {
+                Some(method) if !has_same_signature(method, prototype, context) => self
+                    .diagnostics
+                    .push(Diagnostic::interface_method_signature_mismatch(
+                        method_name,
+                        interface_name,
+                        pou.name_location.to_owned(),
+                    )),
+                Some(_) => {}
+                None => self.diagnostics.push(Diagnostic::missing_interface_method(
+                    method_name,
+                    interface_name,
+                    pou.name_location.to_owned(),
+                )),
+            }
+        }
+    }
+}
+
+/// returns the method of the given class' closest super class declaring a method with the given
+/// name
+fn find_overridden_method<'idx>(
+    class_name: &str,
+    method_name: &str,
+    context: &ValidationContext<'idx>,
+) -> Option<&'idx PouIndexEntry> {
+    context
+        .index
+        .get_super_classes(class_name)
+        .into_iter()
+        .find_map(|it| context.index.find_pou(&format!("{}.{}", it, method_name)))
+}
+
+/// returns true if both methods have the same return type and the same parameters, declared in
+/// the same order, with the same names, types and directions
+fn has_same_signature(
+    method: &PouIndexEntry,
+    prototype: &PouIndexEntry,
+    context: &ValidationContext,
+) -> bool {
+    let return_type_name = |pou: &PouIndexEntry| {
+        context
+            .index
+            .find_return_type(pou.get_name())
+            .map(|it| it.get_name().to_lowercase())
+    };
+    let parameters = |pou: &PouIndexEntry| {
+        context
+            .index
+            .get_container_members(pou.get_name())
+            .into_iter()
+            .filter(|it| it.is_parameter())
+            .map(|it| {
+                (
+                    it.get_name().to_lowercase(),
+                    it.get_type_name().to_lowercase(),
+                    it.get_declaration_type(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    return_type_name(method) == return_type_name(prototype)
+        && parameters(method) == parameters(prototype)
 }
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::{CompilationUnit, Pou, PouType, VariableBlockType},
+    Diagnostic,
+};
+
+/// the type, the return type and the parameters (name, type and block) of a POU
+type Signature = (
+    PouType,
+    Option<String>,
+    Vec<(String, Option<String>, VariableBlockType)>,
+);
+
+/// checks that every POU declared as `{prototype}` is implemented by exactly one function or
+/// function block with the prototype's signature
+///
+/// since a prototype is usually implemented in a different compilation unit than the one
+/// declaring it, all units need to be visited before asking for diagnostics
+#[derive(Default)]
+pub struct PrototypeValidator {
+    /// the signatures of all functions and function blocks by their name
+    implementations: HashMap<String, Vec<Signature>>,
+    prototypes: HashMap<String, Signature>,
+}
+
+impl PrototypeValidator {
+    pub fn new() -> PrototypeValidator {
+        PrototypeValidator::default()
+    }
+
+    /// records the prototypes and the functions and function blocks of the given unit
+    pub fn visit_unit(&mut self, unit: &CompilationUnit) {
+        for pou in unit.units.iter().filter(|it| can_implement_prototype(it)) {
+            self.implementations
+                .entry(pou.name.to_lowercase())
+                .or_default()
+                .push(get_signature(pou));
+        }
+        for prototype in unit
+            .prototypes
+            .iter()
+            .filter(|it| can_implement_prototype(it))
+        {
+            self.prototypes
+                .insert(prototype.name.to_lowercase(), get_signature(prototype));
+        }
+    }
+
+    /// returns an error for every prototype of the given unit that is not implemented and for
+    /// every implementation of a prototype in the given unit that does not match the
+    /// prototype's signature or is not the only implementation
+    pub fn diagnostics_for(&self, unit: &CompilationUnit) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for prototype in unit
+            .prototypes
+            .iter()
+            .filter(|it| can_implement_prototype(it))
+        {
+            if !self
+                .implementations
+                .contains_key(&prototype.name.to_lowercase())
+            {
+                diagnostics.push(Diagnostic::missing_prototype_implementation(
+                    &prototype.name,
+                    prototype.name_location.clone(),
+                ));
+            }
+        }
+
+        for pou in unit.units.iter().filter(|it| can_implement_prototype(it)) {
+            let name = pou.name.to_lowercase();
+            if let Some(prototype) = self.prototypes.get(&name) {
+                let count = self.implementations.get(&name).map_or(0, Vec::len);
+                if count > 1 {
+                    diagnostics.push(Diagnostic::duplicate_prototype_implementation(
+                        &pou.name,
+                        count,
+                        pou.name_location.clone(),
+                    ));
+                } else if &get_signature(pou) != prototype {
+                    diagnostics.push(Diagnostic::prototype_signature_mismatch(
+                        &pou.name,
+                        pou.name_location.clone(),
+                    ));
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+fn can_implement_prototype(pou: &Pou) -> bool {
+    matches!(pou.pou_type, PouType::Function | PouType::FunctionBlock)
+}
+
+/// returns the signature of the given pou, types are compared by their name
+fn get_signature(pou: &Pou) -> Signature {
+    let parameters = pou
+        .variable_blocks
+        .iter()
+        .filter(|it| {
+            matches!(
+                it.variable_block_type,
+                VariableBlockType::Input(..) | VariableBlockType::Output | VariableBlockType::InOut
+            )
+        })
+        .flat_map(|block| {
+            block.variables.iter().map(move |it| {
+                (
+                    it.name.to_lowercase(),
+                    it.data_type.get_name().map(str::to_lowercase),
+                    block.variable_block_type,
+                )
+            })
+        })
+        .collect();
+    (
+        pou.pou_type.clone(),
+        pou.return_type
+            .as_ref()
+            .and_then(|it| it.get_name())
+            .map(str::to_lowercase),
+        parameters,
+    )
+}
@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+
+use super::access_visitor::{visit_all_accesses, Access};
+use crate::{
+    ast::{AstStatement, CompilationUnit, PouType},
+    index::Index,
+    resolver::{AnnotationMap, AnnotationMapImpl, StatementAnnotation},
+    task_configuration::TaskConfiguration,
+    Diagnostic,
+};
+
+/// the global variables accessed and the pous called by a single implementation
+#[derive(Default)]
+struct PouAccesses {
+    reads: HashSet<String>,
+    writes: HashSet<String>,
+    calls: HashSet<String>,
+}
+
+/// finds global variables that are shared between tasks without synchronization
+///
+/// a global is reported if it is written by more than one task, or if it is written by
+/// one task and read by another one, unless it is declared in a `VAR_GLOBAL {atomic}` block.
+/// Independent of the tasks, a global written by more than one program is reported unless it
+/// is declared in a `VAR_GLOBAL {shared}` block (single writer rule)
+#[derive(Default)]
+pub struct RaceValidator {
+    pous: HashMap<String, PouAccesses>,
+    /// the programs (lowercase) in the order of their implementations
+    programs: Vec<String>,
+}
+
+impl RaceValidator {
+    pub fn new() -> RaceValidator {
+        RaceValidator::default()
+    }
+
+    /// records the global accesses and calls of all implementations of the given unit
+    pub fn visit_unit(
+        &mut self,
+        annotations: &AnnotationMapImpl,
+        index: &Index,
+        unit: &CompilationUnit,
+    ) {
+        for implementation in &unit.implementations {
+            if implementation.pou_type == PouType::Program {
+                self.programs.push(implementation.name.to_lowercase());
+            }
+            let accesses = self
+                .pous
+                .entry(implementation.name.to_lowercase())
+                .or_default();
+            visit_all_accesses(&implementation.statements, &mut |statement, access| {
+                record(accesses, annotations, index, statement, access)
+            });
+        }
+    }
+
+    /// returns a warning for every global variable declared in the given unit that is
+    /// accessed by more than one task of the given configuration and written by at least one of them
+    pub fn diagnostics_for(
+        &self,
+        tasks: &TaskConfiguration,
+        unit: &CompilationUnit,
+    ) -> Vec<Diagnostic> {
+        let task_accesses = tasks
+            .tasks
+            .iter()
+            .map(|task| {
+                let programs = task
+                    .programs
+                    .iter()
+                    .map(|it| tasks.get_program_of(it).to_string())
+                    .collect::<Vec<_>>();
+                let pous = self.get_reachable_pous(&programs);
+                let (mut reads, mut writes) = (HashSet::new(), HashSet::new());
+                for accesses in pous.iter().filter_map(|it| self.pous.get(*it)) {
+                    reads.extend(accesses.reads.iter().map(String::as_str));
+                    writes.extend(accesses.writes.iter().map(String::as_str));
+                }
+                (task.name.as_str(), reads, writes)
+            })
+            .collect::<Vec<_>>();
+
+        let mut diagnostics = Vec::new();
+        for block in unit
+            .global_vars
+            .iter()
+            .filter(|it| !it.constant && !it.atomic)
+        {
+            for variable in &block.variables {
+                let name = variable.name.to_lowercase();
+                let writers = task_accesses
+                    .iter()
+                    .filter(|(_, _, writes)| writes.contains(name.as_str()))
+                    .count();
+                let accessing_tasks = task_accesses
+                    .iter()
+                    .filter(|(_, reads, writes)| {
+                        reads.contains(name.as_str()) || writes.contains(name.as_str())
+                    })
+                    .map(|(task, ..)| *task)
+                    .collect::<Vec<_>>();
+
+                if writers > 0 && accessing_tasks.len() > 1 {
+                    diagnostics.push(Diagnostic::unsynchronized_global_access(
+                        variable.name.as_str(),
+                        accessing_tasks.as_slice(),
+                        variable.location.clone(),
+                    ));
+                }
+            }
+        }
+        diagnostics
+    }
+
+    /// returns a warning for every global variable declared in the given unit that is
+    /// written by more than one program, unless it is declared in a `VAR_GLOBAL {shared}` block
+    ///
+    /// a write is attributed to the program whose body (or one of the non-program pous it calls)
+    /// contains it, a program calling another program does not write the other one's globals
+    pub fn single_writer_diagnostics_for(&self, unit: &CompilationUnit) -> Vec<Diagnostic> {
+        let program_writes = self
+            .programs
+            .iter()
+            .map(|program| {
+                let pous = self.collect_reachable_pous(&[program.clone()], |pou| {
+                    pou == program.as_str() || !self.programs.iter().any(|it| it == pou)
+                });
+                let writes = pous
+                    .iter()
+                    .filter_map(|it| self.pous.get(*it))
+                    .flat_map(|it| it.writes.iter().map(String::as_str))
+                    .collect::<HashSet<_>>();
+                (program.as_str(), writes)
+            })
+            .collect::<Vec<_>>();
+
+        let mut diagnostics = Vec::new();
+        for block in unit
+            .global_vars
+            .iter()
+            .filter(|it| !it.constant && !it.shared)
+        {
+            for variable in &block.variables {
+                let name = variable.name.to_lowercase();
+                let writers = program_writes
+                    .iter()
+                    .filter(|(_, writes)| writes.contains(name.as_str()))
+                    .map(|(program, _)| *program)
+                    .collect::<Vec<_>>();
+
+                if writers.len() > 1 {
+                    diagnostics.push(Diagnostic::multiple_writers(
+                        variable.name.as_str(),
+                        writers.as_slice(),
+                        variable.location.clone(),
+                    ));
+                }
+            }
+        }
+        diagnostics
+    }
+
+    /// returns all pous (lowercase) executed when calling the given programs
+    fn get_reachable_pous<'a>(&'a self, programs: &[String]) -> HashSet<&'a str> {
+        self.collect_reachable_pous(programs, |_| true)
+    }
+
+    /// returns all pous (lowercase) executed when calling the given pous, the calls of a pou
+    /// are only followed if `enter` accepts it
+    fn collect_reachable_pous<'a>(
+        &'a self,
+        pous: &[String],
+        enter: impl Fn(&str) -> bool,
+    ) -> HashSet<&'a str> {
+        let mut reachable = HashSet::new();
+        let mut pending = pous.iter().map(|it| it.to_lowercase()).collect::<Vec<_>>();
+        while let Some(pou) = pending.pop() {
+            if !enter(pou.as_str()) {
+                continue;
+            }
+            if let Some((name, accesses)) = self.pous.get_key_value(&pou) {
+                if reachable.insert(name.as_str()) {
+                    pending.extend(accesses.calls.iter().cloned());
+                }
+            }
+        }
+        reachable
+    }
+}
+
+fn record(
+    accesses: &mut PouAccesses,
+    annotations: &AnnotationMapImpl,
+    index: &Index,
+    statement: &AstStatement,
+    access: Access,
+) {
+    match (annotations.get(statement), access) {
+        // calling a function-block instance executes the function-block's body
+        (Some(StatementAnnotation::Variable { resulting_type, .. }), Access::Call) => {
+            accesses.calls.insert(resulting_type.to_lowercase());
+        }
+        (Some(StatementAnnotation::Function { qualified_name, .. }), Access::Call)
+        | (Some(StatementAnnotation::Program { qualified_name }), Access::Call) => {
+            accesses.calls.insert(qualified_name.to_lowercase());
+        }
+        (Some(StatementAnnotation::Variable { qualified_name, .. }), _)
+            if index.find_global_variable(qualified_name).is_some() =>
+        {
+            let name = qualified_name.to_lowercase();
+            if access == Access::Write {
+                accesses.writes.insert(name);
+            } else {
+                accesses.reads.insert(name);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::access_visitor::{visit_all_accesses, Access};
+use crate::{
+    ast::{AstStatement, CompilationUnit, DataType, DataTypeDeclaration, LinkageType, Variable},
+    index::{Index, VariableIndexEntry},
+    profile::SafetyRule,
+    resolver::{AnnotationMap, AnnotationMapImpl, StatementAnnotation},
+    typesystem::DataTypeInformation,
+    Diagnostic,
+};
+
+/// the functions of the C allocator, calling them allocates or releases memory at runtime
+const ALLOCATOR_FUNCTIONS: &[&str] = &["malloc", "calloc", "realloc", "free"];
+
+/// the builtins taking the address of a variable
+const ADDRESS_FUNCTIONS: &[&str] = &["ADR", "REF"];
+
+type Violations = Vec<(SafetyRule, Diagnostic)>;
+
+/// finds the constructs rejected by the safety profile
+///
+/// a POU may call itself through POUs of other compilation units, so all units need to be
+/// visited before asking for recursions
+#[derive(Default)]
+pub struct SafetyValidator {
+    /// the POUs (lowercase) called by every implementation
+    calls: HashMap<String, HashSet<String>>,
+    /// the names of the implementations by their lowercase name
+    names: HashMap<String, String>,
+}
+
+impl SafetyValidator {
+    pub fn new() -> SafetyValidator {
+        SafetyValidator::default()
+    }
+
+    /// records the calls of the given unit's implementations and returns the unit's pointers,
+    /// allocations, variadic POUs and unbounded loops
+    pub fn visit_unit(
+        &mut self,
+        annotations: &AnnotationMapImpl,
+        index: &Index,
+        unit: &CompilationUnit,
+    ) -> Violations {
+        let mut violations = Violations::new();
+
+        for pou in unit
+            .units
+            .iter()
+            .filter(|it| it.linkage != LinkageType::External)
+        {
+            for block in &pou.variable_blocks {
+                for variable in &block.variables {
+                    if is_variadic(variable) {
+                        violations.push((
+                            SafetyRule::Variadics,
+                            Diagnostic::safety_variadic(&pou.name, variable.location.clone()),
+                        ));
+                    }
+                    let entry = index.find_member(&pou.name, &variable.name);
+                    check_pointer(variable, entry, index, &mut violations);
+                }
+            }
+        }
+        for block in unit
+            .global_vars
+            .iter()
+            .filter(|it| it.linkage != LinkageType::External)
+        {
+            for variable in &block.variables {
+                let entry = index.find_global_variable(&variable.name);
+                check_pointer(variable, entry, index, &mut violations);
+            }
+        }
+        for user_type in &unit.types {
+            if let DataType::StructType {
+                name: Some(name),
+                variables,
+            } = &user_type.data_type
+            {
+                for variable in variables {
+                    let entry = index.find_member(name, &variable.name);
+                    check_pointer(variable, entry, index, &mut violations);
+                }
+            }
+        }
+
+        for implementation in unit
+            .implementations
+            .iter()
+            .filter(|it| it.linkage != LinkageType::External)
+        {
+            let name = implementation.name.to_lowercase();
+            self.names.insert(name.clone(), implementation.name.clone());
+            let calls = self.calls.entry(name).or_default();
+            visit_all_accesses(&implementation.statements, &mut |statement, access| {
+                if access == Access::Call {
+                    record_call(calls, annotations, index, statement, &mut violations);
+                }
+            });
+            find_unbounded_loops(&implementation.statements, &mut violations);
+        }
+        violations
+    }
+
+    /// returns the POUs of the given unit calling themselves, directly or through other POUs
+    pub fn find_recursions(&self, unit: &CompilationUnit) -> Violations {
+        unit.units
+            .iter()
+            .filter_map(|pou| {
+                let cycle = self.find_cycle(&pou.name.to_lowercase())?;
+                Some((
+                    SafetyRule::Recursion,
+                    Diagnostic::safety_recursion(&cycle, pou.name_location.clone()),
+                ))
+            })
+            .collect()
+    }
+
+    /// returns the shortest chain of calls leading from the given POU back to itself
+    fn find_cycle(&self, pou: &str) -> Option<Vec<&str>> {
+        let pou = self.calls.get_key_value(pou)?.0.as_str();
+        let mut callers: HashMap<&str, &str> = HashMap::new();
+        let mut pending = VecDeque::from([pou]);
+        while let Some(current) = pending.pop_front() {
+            for callee in self.calls.get(current).into_iter().flatten() {
+                if callee == pou {
+                    let mut cycle = vec![current];
+                    let mut it = current;
+                    while it != pou {
+                        it = callers[it];
+                        cycle.push(it);
+                    }
+                    cycle.reverse();
+                    cycle.push(pou);
+                    return Some(
+                        cycle
+                            .into_iter()
+                            .map(|it| self.names.get(it).map_or(it, String::as_str))
+                            .collect(),
+                    );
+                }
+                if !callers.contains_key(callee.as_str()) {
+                    callers.insert(callee, current);
+                    pending.push_back(callee);
+                }
+            }
+        }
+        None
+    }
+}
+
+fn is_variadic(variable: &Variable) -> bool {
+    matches!(
+        variable.data_type,
+        DataTypeDeclaration::DataTypeDefinition {
+            data_type: DataType::VarArgs { .. },
+            ..
+        }
+    )
+}
+
+fn check_pointer(
+    variable: &Variable,
+    entry: Option<&VariableIndexEntry>,
+    index: &Index,
+    violations: &mut Violations,
+) {
+    if entry.map_or(false, |it| is_pointer(it.get_type_name(), index)) {
+        violations.push((
+            SafetyRule::Pointers,
+            Diagnostic::safety_pointer_variable(&variable.name, variable.location.clone()),
+        ));
+    }
+}
+
+/// returns true if the given type is a pointer or an array of pointers, references passed
+/// implicitly (e.g. `VAR_IN_OUT`) are no pointers
+fn is_pointer(type_name: &str, index: &Index) -> bool {
+    match index.find_effective_type_info(type_name) {
+        Some(DataTypeInformation::Pointer { auto_deref, .. }) => !auto_deref,
+        Some(DataTypeInformation::Array {
+            inner_type_name, ..
+        }) => is_pointer(inner_type_name, index),
+        _ => false,
+    }
+}
+
+fn record_call(
+    calls: &mut HashSet<String>,
+    annotations: &AnnotationMapImpl,
+    index: &Index,
+    statement: &AstStatement,
+    violations: &mut Violations,
+) {
+    let location = statement.get_location();
+    match annotations.get(statement) {
+        // calling a function-block instance executes the function-block's body
+        Some(StatementAnnotation::Variable { resulting_type, .. }) => {
+            calls.insert(resulting_type.to_lowercase());
+        }
+        Some(StatementAnnotation::Program { qualified_name }) => {
+            calls.insert(qualified_name.to_lowercase());
+        }
+        Some(StatementAnnotation::Function { qualified_name, .. }) => {
+            calls.insert(qualified_name.to_lowercase());
+            //the calls of generic functions are annotated with their implementation (e.g. `ADR__INT`)
+            let name = match statement {
+                AstStatement::Reference { name, .. } => name.as_str(),
+                _ => qualified_name.as_str(),
+            };
+            let is_any_of = |names: &[&str]| names.iter().any(|it| it.eq_ignore_ascii_case(name));
+            if is_any_of(ADDRESS_FUNCTIONS) {
+                violations.push((
+                    SafetyRule::Pointers,
+                    Diagnostic::safety_address_of(name, location),
+                ));
+            } else if is_any_of(ALLOCATOR_FUNCTIONS) {
+                violations.push((
+                    SafetyRule::DynamicMemory,
+                    Diagnostic::safety_dynamic_memory(name, location),
+                ));
+            } else if index.find_pou(qualified_name).map_or(false, |it| {
+                it.is_variadic() && it.get_linkage() != &LinkageType::BuiltIn
+            }) {
+                violations.push((
+                    SafetyRule::Variadics,
+                    Diagnostic::safety_variadic(qualified_name, location),
+                ));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// reports the `WHILE` and `REPEAT` loops in the given statements
+fn find_unbounded_loops(statements: &[AstStatement], violations: &mut Violations) {
+    for statement in statements {
+        match statement {
+            AstStatement::WhileLoopStatement { body, location, .. } => {
+                violations.push((
+                    SafetyRule::UnboundedLoops,
+                    Diagnostic::safety_unbounded_loop("WHILE", location.clone()),
+                ));
+                find_unbounded_loops(body, violations);
+            }
+            AstStatement::RepeatLoopStatement { body, location, .. } => {
+                violations.push((
+                    SafetyRule::UnboundedLoops,
+                    Diagnostic::safety_unbounded_loop("REPEAT", location.clone()),
+                ));
+                find_unbounded_loops(body, violations);
+            }
+            AstStatement::ForLoopStatement { body, .. } => find_unbounded_loops(body, violations),
+            AstStatement::IfStatement {
+                blocks, else_block, ..
+            } => {
+                for block in blocks {
+                    find_unbounded_loops(&block.body, violations);
+                }
+                find_unbounded_loops(else_block, violations);
+            }
+            AstStatement::CaseStatement {
+                case_blocks,
+                else_block,
+                ..
+            } => {
+                for block in case_blocks {
+                    find_unbounded_loops(&block.body, violations);
+                }
+                find_unbounded_loops(else_block, violations);
+            }
+            _ => {}
+        }
+    }
+}
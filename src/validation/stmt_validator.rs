@@ -2,13 +2,19 @@ use std::{convert::TryInto, mem::discriminant};
 
 use super::ValidationContext;
 use crate::{
-    ast::{AstStatement, DirectAccessType, Operator, SourceRange},
-    index::{ArgumentType, VariableIndexEntry, VariableType},
+    ast::{
+        flatten_expression_list, get_call_argument, get_nested_bodies, AstStatement,
+        ConditionalBlock, DirectAccessType, LinkageType, Operator, SourceRange,
+    },
+    diagnostics::CodeEdit,
+    dialect::LanguageExtension,
+    index::{ArgumentType, PouIndexEntry, VariableIndexEntry, VariableType},
     resolver::{AnnotationMap, StatementAnnotation},
     typesystem::{
-        DataType, DataTypeInformation, Dimension, BOOL_TYPE, DATE_AND_TIME_TYPE, DATE_TYPE,
-        DINT_TYPE, INT_TYPE, LINT_TYPE, LREAL_TYPE, SINT_TYPE, STRING_TYPE, TIME_OF_DAY_TYPE,
-        TIME_TYPE, UDINT_TYPE, UINT_TYPE, ULINT_TYPE, USINT_TYPE, VOID_TYPE, WSTRING_TYPE,
+        get_conversion_function_name, DataType, DataTypeInformation, Dimension, BOOL_TYPE,
+        DATE_AND_TIME_TYPE, DATE_TYPE, DINT_TYPE, INT_TYPE, LINT_TYPE, LREAL_TYPE, SINT_TYPE,
+        STRING_TYPE, TIME_OF_DAY_TYPE, TIME_TYPE, UDINT_TYPE, UINT_TYPE, ULINT_TYPE, USINT_TYPE,
+        VOID_TYPE, WSTRING_TYPE,
     },
     Diagnostic,
 };
@@ -56,7 +62,9 @@ impl StatementValidator {
                     .get_type_or_void(reference, context.index)
                     .get_type_information();
 
-                if let DataTypeInformation::Array { dimensions, .. } = target_type {
+                if target_type.is_vla() {
+                    //the bounds of a variable-length array are only known at runtime
+                } else if let DataTypeInformation::Array { dimensions, .. } = target_type {
                     if let AstStatement::ExpressionList { expressions, .. } = access.as_ref() {
                         for (i, exp) in expressions.iter().enumerate() {
                             self.validate_array_access(exp, dimensions, i, context);
@@ -173,9 +181,33 @@ impl StatementValidator {
                             l_effective_type.get_name(),
                             statement.get_location(),
                         ));
+                    } else if !is_assignable(l_effective_type, r_effective_type) {
+                        // report the expected and the actual type, along with the conversion
+                        // that would make this assignment valid (e.g. STRING_TO_INT)
+                        self.diagnostics.push(Diagnostic::type_mismatch(
+                            l_effective_type.get_name(),
+                            r_effective_type.get_name(),
+                            get_conversion_function_name(
+                                r_effective_type,
+                                l_effective_type,
+                                context.index,
+                            )
+                            .as_deref(),
+                            right.get_location(),
+                        ));
+                    } else if let AstStatement::LiteralInteger { value, .. } = right.as_ref() {
+                        // the literal would be truncated when stored (e.g. byte_var := 300)
+                        self.diagnostics.extend(validate_literal_range(
+                            *value,
+                            l_effective_type,
+                            right,
+                        ));
                     }
                 }
             }
+            AstStatement::ReferenceAssignment { left, right, .. } => {
+                self.validate_reference_assignment(left, right, context);
+            }
             AstStatement::BinaryExpression {
                 operator,
                 left,
@@ -227,9 +259,585 @@ impl StatementValidator {
                 }
                 _ => self.validate_binary_expression(context, operator, left, right, statement),
             },
+            AstStatement::CaseStatement {
+                selector,
+                case_blocks,
+                ..
+            } => self.validate_string_case_labels(selector, case_blocks, context),
             _ => (),
         }
+        self.validate_bool_int_mixing(statement, context);
+        self.validate_real_equality(statement, context);
+        self.validate_string_widths(statement, context);
+        self.validate_string_truncation(statement, context);
         self.validate_type_nature(statement, context);
+        self.validate_builtin_availability(statement, context);
+        self.validate_memory_sizes(statement, context);
+        self.validate_vla_arguments(statement, context);
+        self.validate_argument_ranges(statement, context);
+    }
+
+    /// reports assignments to the POU's outputs which follow an unconditional RETURN in the same
+    /// statement list. A RETURN ends the POU (for programs and function blocks the current
+    /// cycle), its outputs keep the values assigned before the RETURN
+    pub fn validate_unreachable_assignments(
+        &mut self,
+        statements: &[AstStatement],
+        context: &ValidationContext,
+    ) {
+        let mut returned = false;
+        for statement in statements {
+            if returned {
+                self.report_unreachable_output_assignments(statement, context);
+            } else if matches!(statement, AstStatement::ReturnStatement { .. }) {
+                returned = true;
+            } else {
+                for body in get_nested_bodies(statement) {
+                    self.validate_unreachable_assignments(body, context);
+                }
+            }
+        }
+    }
+
+    fn report_unreachable_output_assignments(
+        &mut self,
+        statement: &AstStatement,
+        context: &ValidationContext,
+    ) {
+        if let AstStatement::Assignment { left, .. } = statement {
+            if let Some(StatementAnnotation::Variable { qualified_name, .. }) =
+                context.ast_annotation.get(left.as_ref())
+            {
+                let is_own_output = context
+                    .index
+                    .find_fully_qualified_variable(qualified_name)
+                    .filter(|it| {
+                        matches!(
+                            it.get_variable_type(),
+                            VariableType::Output | VariableType::Return
+                        )
+                    })
+                    .zip(context.qualifier)
+                    .and_then(|(variable, pou)| context.index.find_member(pou, variable.get_name()))
+                    .map_or(false, |it| it.get_qualified_name() == qualified_name);
+                if is_own_output {
+                    self.diagnostics
+                        .push(Diagnostic::unreachable_output_assignment(
+                            qualified_name,
+                            statement.get_location(),
+                        ));
+                }
+            }
+        }
+        for body in get_nested_bodies(statement) {
+            body.iter()
+                .for_each(|it| self.report_unreachable_output_assignments(it, context));
+        }
+    }
+
+    /// validates the labels and `JMP` statements of a POU's body: labels have to be unique and a
+    /// jump can only leave nested blocks, its label has to be declared in the jump's block or in
+    /// one of its enclosing blocks. Every jump is reported as a warning to ease its migration
+    pub fn validate_jumps(&mut self, statements: &[AstStatement], context: &ValidationContext) {
+        let mut labels: Vec<String> = vec![];
+        collect_labels(statements, &mut |name, location| {
+            let label_name = name.to_lowercase();
+            if labels.contains(&label_name) {
+                self.diagnostics
+                    .push(Diagnostic::duplicate_label(name, location.clone()));
+            } else {
+                labels.push(label_name);
+            }
+        });
+        self.validate_jump_targets(statements, &mut vec![], &labels, context);
+    }
+
+    fn validate_jump_targets(
+        &mut self,
+        statements: &[AstStatement],
+        visible_labels: &mut Vec<String>,
+        labels: &[String],
+        context: &ValidationContext,
+    ) {
+        let outer_labels = visible_labels.len();
+        visible_labels.extend(statements.iter().filter_map(|it| match it {
+            AstStatement::LabelStatement { name, .. } => Some(name.to_lowercase()),
+            _ => None,
+        }));
+        for statement in statements {
+            if let AstStatement::JumpStatement {
+                label, location, ..
+            } = statement
+            {
+                let label_name = label.to_lowercase();
+                if visible_labels.contains(&label_name) {
+                    if context.dialect.allows(LanguageExtension::Jumps) {
+                        self.diagnostics
+                            .push(Diagnostic::jump_statement(location.clone()));
+                    }
+                } else if labels.contains(&label_name) {
+                    self.diagnostics
+                        .push(Diagnostic::jump_into_block(label, location.clone()));
+                } else {
+                    self.diagnostics
+                        .push(Diagnostic::unknown_label(label, location.clone()));
+                }
+            }
+            for body in get_nested_bodies(statement) {
+                self.validate_jump_targets(body, visible_labels, labels, context);
+            }
+        }
+        visible_labels.truncate(outer_labels);
+    }
+
+    /// reports calls to builtin functions and to instances of the runtime library's function
+    /// blocks that are not part of the selected dialect
+    fn validate_builtin_availability(
+        &mut self,
+        statement: &AstStatement,
+        context: &ValidationContext,
+    ) {
+        if let AstStatement::CallStatement { operator, .. } = statement {
+            let pou_name = match context.ast_annotation.get(operator) {
+                Some(StatementAnnotation::Function { qualified_name, .. }) => qualified_name,
+                Some(StatementAnnotation::Variable { resulting_type, .. }) => resulting_type,
+                _ => return,
+            };
+            //only the compiler's and the runtime's POUs are builtins, not the user's
+            let is_builtin = context.index.find_pou(pou_name).map_or(false, |it| {
+                matches!(
+                    it.get_linkage(),
+                    LinkageType::BuiltIn | LinkageType::External
+                )
+            });
+            if is_builtin && !context.dialect.is_builtin_available(pou_name) {
+                self.diagnostics.push(Diagnostic::unsupported_in_dialect(
+                    pou_name,
+                    context.dialect,
+                    operator.get_location(),
+                ));
+            }
+        }
+    }
+
+    /// reports calls to MEMCPY, MEMSET and MEMCMP with a literal size that exceeds one of the
+    /// passed variables. Pointers and addresses (e.g. `ADR(frame)`) are not checked
+    fn validate_memory_sizes(&mut self, statement: &AstStatement, context: &ValidationContext) {
+        if let AstStatement::CallStatement {
+            operator,
+            parameters: Some(parameters),
+            ..
+        } = statement
+        {
+            let memory_parameters: &[&str] = if is_builtin(operator, "MEMCPY", context) {
+                &["destination", "source"]
+            } else if is_builtin(operator, "MEMCMP", context) {
+                &["left", "right"]
+            } else if is_builtin(operator, "MEMSET", context) {
+                &["destination"]
+            } else {
+                return;
+            };
+            let parameters = flatten_expression_list(parameters);
+            let size = match get_call_argument(&parameters, "size", 2) {
+                Some(AstStatement::LiteralInteger { value, .. }) => *value,
+                _ => return,
+            };
+            for (position, name) in memory_parameters.iter().enumerate() {
+                let memory = match get_call_argument(&parameters, name, position) {
+                    Some(memory) if !matches!(memory, AstStatement::CallStatement { .. }) => memory,
+                    _ => continue,
+                };
+                let memory_type = context
+                    .ast_annotation
+                    .get_type_or_void(memory, context.index)
+                    .get_type_information();
+                if let Some(memory_size) = memory_type.get_size_in_bytes(context.index) {
+                    if size > memory_size as i128 {
+                        self.diagnostics.push(Diagnostic::memory_size_exceeded(
+                            size,
+                            memory_size,
+                            memory.get_location(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// reports arguments of variable-length array parameters that are no arrays of the parameter's
+    /// element type and number of dimensions, as well as calls to LOWER_BOUND and UPPER_BOUND
+    /// with something else than a variable-length array
+    fn validate_vla_arguments(&mut self, statement: &AstStatement, context: &ValidationContext) {
+        let (operator, arguments) = match statement {
+            AstStatement::CallStatement {
+                operator,
+                parameters: Some(parameters),
+                ..
+            } => (operator, flatten_expression_list(parameters)),
+            _ => return,
+        };
+        let get_type = |it: &AstStatement| {
+            context
+                .ast_annotation
+                .get_type_or_void(it, context.index)
+                .get_type_information()
+        };
+
+        if is_builtin(operator, "LOWER_BOUND", context)
+            || is_builtin(operator, "UPPER_BOUND", context)
+        {
+            if let Some(array) = get_call_argument(&arguments, "arr", 0) {
+                if !get_type(array).is_vla() {
+                    self.diagnostics.push(Diagnostic::invalid_vla_argument(
+                        "ARRAY[*]",
+                        get_type(array).get_name(),
+                        array.get_location(),
+                    ));
+                }
+            }
+            return;
+        }
+
+        let pou_name = match context.ast_annotation.get(operator) {
+            Some(StatementAnnotation::Function { qualified_name, .. }) => qualified_name,
+            _ => return,
+        };
+        let is_same_type = |left: &str, right: &str| {
+            context
+                .index
+                .get_effective_type_by_name(left)
+                .get_name()
+                .eq_ignore_ascii_case(context.index.get_effective_type_by_name(right).get_name())
+        };
+        let parameters = context
+            .index
+            .get_container_members(pou_name)
+            .into_iter()
+            .filter(|it| it.is_parameter());
+        for (position, parameter) in parameters.enumerate() {
+            let (inner_type_name, ndims) = match context
+                .index
+                .find_effective_type_info(parameter.get_type_name())
+                .and_then(DataTypeInformation::get_vla_dimensions)
+            {
+                Some(vla) => vla,
+                None => continue,
+            };
+            let argument = match get_call_argument(&arguments, parameter.get_name(), position) {
+                Some(argument) => argument,
+                None => continue,
+            };
+            let argument_type = get_type(argument);
+            let is_valid = match argument_type {
+                DataTypeInformation::Array {
+                    inner_type_name: argument_inner_type,
+                    dimensions,
+                    ..
+                } => {
+                    dimensions.len() == ndims && is_same_type(argument_inner_type, inner_type_name)
+                }
+                _ => argument_type.get_vla_dimensions().map_or(
+                    false,
+                    |(argument_inner_type, argument_ndims)| {
+                        argument_ndims == ndims
+                            && is_same_type(argument_inner_type, inner_type_name)
+                    },
+                ),
+            };
+            if !is_valid {
+                self.diagnostics.push(Diagnostic::invalid_vla_argument(
+                    &format!(
+                        "ARRAY[{}] OF {}",
+                        vec!["*"; ndims].join(", "),
+                        inner_type_name
+                    ),
+                    argument_type.get_name(),
+                    argument.get_location(),
+                ));
+            }
+        }
+    }
+
+    /// validates the IEC-conformant use of BOOLs and integers (e.g. no `IF int_var THEN`)
+    fn validate_bool_int_mixing(&mut self, statement: &AstStatement, context: &ValidationContext) {
+        match statement {
+            AstStatement::IfStatement { blocks, .. } => blocks
+                .iter()
+                .for_each(|b| self.validate_condition(&b.condition, context)),
+            AstStatement::WhileLoopStatement { condition, .. }
+            | AstStatement::RepeatLoopStatement { condition, .. } => {
+                self.validate_condition(condition, context)
+            }
+            AstStatement::UnaryExpression {
+                operator, value, ..
+            } => self.validate_operand_types(operator, &[value.as_ref()], context),
+            AstStatement::BinaryExpression {
+                operator,
+                left,
+                right,
+                ..
+            } => {
+                self.validate_operand_types(operator, &[left.as_ref(), right.as_ref()], context);
+                self.validate_comparison_chain(statement, context);
+            }
+            _ => (),
+        }
+    }
+
+    /// validates that comparisons are not chained (e.g. `a < b < c`), since such a chain is
+    /// evaluated as `(a < b) < c` which compares the BOOL result with `c`
+    fn validate_comparison_chain(&mut self, statement: &AstStatement, context: &ValidationContext) {
+        if let AstStatement::BinaryExpression {
+            operator,
+            left,
+            right,
+            ..
+        } = statement
+        {
+            if let AstStatement::BinaryExpression {
+                operator: inner_operator,
+                left: first,
+                right: second,
+                ..
+            } = left.as_ref()
+            {
+                let right_type = context
+                    .ast_annotation
+                    .get_type_or_void(right, context.index)
+                    .get_type_information();
+                if operator.is_comparison_operator()
+                    && inner_operator.is_comparison_operator()
+                    && right_type.is_numerical()
+                    && !right_type.is_bool()
+                {
+                    let suggestion = get_expression_text(first)
+                        .zip(get_expression_text(second))
+                        .zip(get_expression_text(right))
+                        .map(|((first, second), third)| {
+                            format!(
+                                "{} {} {} AND {} {} {}",
+                                first, inner_operator, second, second, operator, third
+                            )
+                        });
+                    self.diagnostics.push(Diagnostic::comparison_chain(
+                        suggestion.as_deref(),
+                        statement.get_location(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// warns about `=` and `<>` comparisons of REAL/LREAL values, since rounding errors
+    /// may prevent such comparisons from ever being true
+    fn validate_real_equality(&mut self, statement: &AstStatement, context: &ValidationContext) {
+        if let AstStatement::BinaryExpression {
+            operator: operator @ (Operator::Equal | Operator::NotEqual),
+            left,
+            right,
+            ..
+        } = statement
+        {
+            let is_float = |it: &AstStatement| {
+                context
+                    .ast_annotation
+                    .get_type_or_void(it, context.index)
+                    .get_type_information()
+                    .is_float()
+            };
+            if is_float(left) || is_float(right) {
+                self.diagnostics.push(Diagnostic::real_equality(
+                    operator,
+                    statement.get_location(),
+                ));
+            }
+        }
+    }
+
+    /// validates that the labels of a CASE over a STRING or WSTRING are string literals or
+    /// constants, the labels are compared with the selector's characters
+    fn validate_string_case_labels(
+        &mut self,
+        selector: &AstStatement,
+        case_blocks: &[ConditionalBlock],
+        context: &ValidationContext,
+    ) {
+        let selector_type = context
+            .ast_annotation
+            .get_type_or_void(selector, context.index);
+        if !selector_type.get_type_information().is_string() {
+            return;
+        }
+        for label in case_blocks
+            .iter()
+            .flat_map(|it| flatten_expression_list(it.condition.as_ref()))
+        {
+            if context
+                .ast_annotation
+                .get_constant_string(label, context.index)
+                .is_none()
+            {
+                self.diagnostics.push(Diagnostic::invalid_case_label(
+                    selector_type.get_name(),
+                    label.get_location(),
+                ));
+            }
+        }
+    }
+
+    /// validates that `left REF= right` binds a `REFERENCE TO` variable to a variable of the
+    /// referenced type
+    fn validate_reference_assignment(
+        &mut self,
+        left: &AstStatement,
+        right: &AstStatement,
+        context: &ValidationContext,
+    ) {
+        let reference_type = match context.ast_annotation.get(left) {
+            Some(StatementAnnotation::Variable {
+                qualified_name,
+                resulting_type,
+                is_auto_deref: true,
+                ..
+            }) if !context
+                .index
+                .find_fully_qualified_variable(qualified_name)
+                .map_or(false, |it| it.get_declaration_type().is_by_ref()) =>
+            {
+                resulting_type
+            }
+            //unresolved references are reported already
+            None => return,
+            //VAR_IN_OUT parameters are dereferenced implicitly too, but cannot be bound
+            _ => {
+                self.diagnostics
+                    .push(Diagnostic::cannot_bind_reference(left.get_location()));
+                return;
+            }
+        };
+        let reference_type = context.index.get_effective_type_by_name(reference_type);
+        match context.ast_annotation.get(right) {
+            Some(StatementAnnotation::Variable { resulting_type, .. })
+                if context.index.get_effective_type_by_name(resulting_type) == reference_type => {}
+            None => {}
+            _ => self.diagnostics.push(Diagnostic::invalid_reference_binding(
+                reference_type.get_name(),
+                context
+                    .ast_annotation
+                    .get_type_or_void(right, context.index)
+                    .get_name(),
+                right.get_location(),
+            )),
+        }
+    }
+
+    /// validates that the integer literals passed implicitly to a call fit into the types of their
+    /// parameters, the explicit arguments (`x := 300`) are validated as assignments
+    fn validate_argument_ranges(&mut self, statement: &AstStatement, context: &ValidationContext) {
+        let (operator, arguments) = match statement {
+            AstStatement::CallStatement {
+                operator,
+                parameters: Some(parameters),
+                ..
+            } => (operator, flatten_expression_list(parameters)),
+            _ => return,
+        };
+        let pou_name = match context.ast_annotation.get(operator) {
+            Some(StatementAnnotation::Function { qualified_name, .. })
+            | Some(StatementAnnotation::Program { qualified_name }) => qualified_name,
+            //a call of a function block's instance
+            Some(StatementAnnotation::Variable { resulting_type, .. }) => resulting_type,
+            _ => return,
+        };
+        let parameters = context
+            .index
+            .get_container_members(pou_name)
+            .into_iter()
+            .filter(|it| it.is_parameter());
+        for (parameter, argument) in parameters.zip(arguments) {
+            if let AstStatement::LiteralInteger { value, .. } = argument {
+                if let Some(parameter_type) = context
+                    .index
+                    .find_effective_type_info(parameter.get_type_name())
+                {
+                    self.diagnostics.extend(validate_literal_range(
+                        *value,
+                        parameter_type,
+                        argument,
+                    ));
+                }
+            }
+        }
+    }
+
+    /// validates that conditions are BOOLs and not implicitly converted integers
+    fn validate_condition(&mut self, condition: &AstStatement, context: &ValidationContext) {
+        let condition_type = context
+            .ast_annotation
+            .get_type_or_void(condition, context.index)
+            .get_type_information();
+        if condition_type.is_numerical() && !condition_type.is_bool() {
+            let location = condition.get_location();
+            //compound conditions need parentheses, since `<>` binds stronger than e.g. `OR`
+            let edits = if matches!(
+                condition,
+                AstStatement::Reference { .. }
+                    | AstStatement::QualifiedReference { .. }
+                    | AstStatement::ArrayAccess { .. }
+                    | AstStatement::CallStatement { .. }
+            ) {
+                vec![CodeEdit::insert(" <> 0", location.get_end())]
+            } else {
+                vec![
+                    CodeEdit::insert("(", location.get_start()),
+                    CodeEdit::insert(") <> 0", location.get_end()),
+                ]
+            };
+            self.diagnostics.push(
+                Diagnostic::implicit_bool_conversion(condition_type.get_name(), location)
+                    .with_edits(edits),
+            );
+        }
+    }
+
+    /// validates that arithmetic operations are not applied to BOOLs and bit operations are
+    /// not applied to signed integers. Only the declared types of variables and literals are
+    /// checked, since intermediate results are implicitly promoted to DINT.
+    fn validate_operand_types(
+        &mut self,
+        operator: &Operator,
+        operands: &[&AstStatement],
+        context: &ValidationContext,
+    ) {
+        for operand in operands {
+            let operand_type = match operand {
+                AstStatement::LiteralBool { .. } => context
+                    .index
+                    .get_effective_type_by_name(BOOL_TYPE)
+                    .get_type_information(),
+                _ => match context.ast_annotation.get(operand) {
+                    Some(StatementAnnotation::Variable { resulting_type, .. }) => context
+                        .index
+                        .get_effective_type_by_name(resulting_type)
+                        .get_type_information(),
+                    _ => continue,
+                },
+            };
+
+            if operator.is_arithmetic_operator() && operand_type.is_bool() {
+                self.diagnostics.push(Diagnostic::bool_arithmetic(
+                    operator,
+                    operand.get_location(),
+                ));
+            } else if operator.is_bit_operator() && operand_type.is_signed_int() {
+                self.diagnostics.push(Diagnostic::signed_bit_operation(
+                    operator,
+                    operand_type.get_name(),
+                    operand.get_location(),
+                ));
+            }
+        }
     }
 
     /// Validates that the assigned type and type hint are compatible with the nature for this
@@ -344,7 +952,11 @@ impl StatementValidator {
     ) {
         if !context.ast_annotation.has_type_annotation(id) {
             self.diagnostics
-                .push(Diagnostic::unresolved_reference(ref_name, location.clone()));
+                .push(Diagnostic::unresolved_reference_with_suggestions(
+                    ref_name,
+                    &find_suggestions(ref_name, context),
+                    location.clone(),
+                ));
         }
     }
 
@@ -480,6 +1092,108 @@ impl StatementValidator {
         }
     }
 
+    /// reports comparisons and concatenations of a STRING with a WSTRING, strings are processed
+    /// character by character so one of them has to be converted
+    fn validate_string_widths(&mut self, statement: &AstStatement, context: &ValidationContext) {
+        match statement {
+            AstStatement::BinaryExpression {
+                operator,
+                left,
+                right,
+                ..
+            } if operator.is_comparison_operator() => {
+                self.validate_same_string_width(left, right, context)
+            }
+            AstStatement::CallStatement {
+                operator,
+                parameters,
+                ..
+            } if is_builtin(operator, "CONCAT", context) => {
+                if let Some(parameters) = parameters.as_ref() {
+                    let operands = flatten_expression_list(parameters);
+                    if let Some((first, others)) = operands.split_first() {
+                        for other in others {
+                            self.validate_same_string_width(first, other, context);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn validate_same_string_width(
+        &mut self,
+        left: &AstStatement,
+        right: &AstStatement,
+        context: &ValidationContext,
+    ) {
+        let left_type = context
+            .ast_annotation
+            .get_type_or_void(left, context.index)
+            .get_type_information();
+        let right_type = context
+            .ast_annotation
+            .get_type_or_void(right, context.index)
+            .get_type_information();
+        if let (
+            DataTypeInformation::String {
+                encoding: left_encoding,
+                ..
+            },
+            DataTypeInformation::String {
+                encoding: right_encoding,
+                ..
+            },
+        ) = (left_type, right_type)
+        {
+            if left_encoding != right_encoding {
+                self.diagnostics.push(Diagnostic::type_mismatch(
+                    left_type.get_name(),
+                    right_type.get_name(),
+                    get_conversion_function_name(right_type, left_type, context.index).as_deref(),
+                    right.get_location(),
+                ));
+            }
+        }
+    }
+
+    /// warns if the result of a CONCAT may not fit into the string it is assigned to
+    fn validate_string_truncation(
+        &mut self,
+        statement: &AstStatement,
+        context: &ValidationContext,
+    ) {
+        if let AstStatement::Assignment { left, right, .. } = statement {
+            if let AstStatement::CallStatement { operator, .. } = right.as_ref() {
+                if !is_builtin(operator, "CONCAT", context) {
+                    return;
+                }
+                let get_length = |it: &AstStatement| match context
+                    .ast_annotation
+                    .get_type_or_void(it, context.index)
+                    .get_type_information()
+                {
+                    DataTypeInformation::String { size, .. } => {
+                        size.as_int_value(context.index).ok().map(|it| it - 1)
+                    }
+                    _ => None,
+                };
+                if let Some((target_length, result_length)) =
+                    get_length(left).zip(get_length(right))
+                {
+                    if result_length > target_length {
+                        self.diagnostics.push(Diagnostic::string_truncation(
+                            result_length,
+                            target_length,
+                            right.get_location(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
     /// checks if the given binary expression is valid
     fn validate_binary_expression(
         &mut self,
@@ -498,7 +1212,33 @@ impl StatementValidator {
             .get_type_or_void(right, context.index)
             .get_type_information();
 
-        if std::mem::discriminant(left_type) == std::mem::discriminant(right_type)
+        if let (
+            DataTypeInformation::String {
+                encoding: left_encoding,
+                ..
+            },
+            DataTypeInformation::String {
+                encoding: right_encoding,
+                ..
+            },
+        ) = (left_type, right_type)
+        {
+            // mixed widths are reported by `validate_string_widths`
+            if left_encoding == right_encoding
+                && operator.is_comparison_operator()
+                && compare_function_declared(left_type.get_name(), operator, context)
+                && !compare_function_exists(left_type.get_name(), operator, context)
+            {
+                // strings are compared by the compiler, unless a compare-function is declared
+                self.diagnostics.push(Diagnostic::missing_compare_function(
+                    crate::typesystem::get_equals_function_name_for(left_type.get_name(), operator)
+                        .unwrap_or_default()
+                        .as_str(),
+                    left_type.get_name(),
+                    binary_statement.get_location(),
+                ));
+            }
+        } else if std::mem::discriminant(left_type) == std::mem::discriminant(right_type)
             && !(left_type.is_numerical() || left_type.is_pointer())
         {
             //see if we have the right compare-function (non-numbers are compared using user-defined callback-functions)
@@ -517,6 +1257,55 @@ impl StatementValidator {
     }
 }
 
+/// calls the given function for every label in the given statements and their nested blocks
+fn collect_labels<'s>(statements: &'s [AstStatement], f: &mut dyn FnMut(&'s str, &'s SourceRange)) {
+    for statement in statements {
+        if let AstStatement::LabelStatement { name, location, .. } = statement {
+            f(name, location);
+        }
+        for body in get_nested_bodies(statement) {
+            collect_labels(body, f);
+        }
+    }
+}
+
+/// returns the source-text of simple expressions (references and integer literals)
+fn get_expression_text(statement: &AstStatement) -> Option<String> {
+    match statement {
+        AstStatement::Reference { name, .. } => Some(name.clone()),
+        AstStatement::LiteralInteger { value, .. } => Some(value.to_string()),
+        AstStatement::QualifiedReference { elements, .. } => elements
+            .iter()
+            .map(get_expression_text)
+            .collect::<Option<Vec<_>>>()
+            .map(|it| it.join(".")),
+        _ => None,
+    }
+}
+
+/// returns true if the given operator calls the builtin of the given name (and not a
+/// user-declared one)
+fn is_builtin(operator: &AstStatement, name: &str, context: &ValidationContext) -> bool {
+    matches!(
+        context.ast_annotation.get(operator),
+        Some(StatementAnnotation::Function { qualified_name, .. })
+            if qualified_name.eq_ignore_ascii_case(name)
+                && context.index.get_builtin_function(qualified_name).is_some()
+    )
+}
+
+/// returns true if a compare function for the given operator and type is declared, regardless of
+/// its signature
+fn compare_function_declared(
+    type_name: &str,
+    operator: &Operator,
+    context: &ValidationContext,
+) -> bool {
+    crate::typesystem::get_equals_function_name_for(type_name, operator)
+        .and_then(|function_name| context.index.find_pou_implementation(&function_name))
+        .is_some()
+}
+
 /// returns true if the index contains a compare function for the given operator and type
 fn compare_function_exists(
     type_name: &str,
@@ -568,6 +1357,150 @@ fn compare_function_exists(
     false
 }
 
+/// returns names that are similar to the given (unresolved) name, best matches first
+///
+/// suggestions are ...
+/// - names with a small edit-distance that are visible from the current context, a name only
+///   differing in case counts as one typo
+/// - program variables with the same name that are missing their program's qualifier
+///
+/// the names generated by the compiler (containing `__`, which is not allowed in identifiers) are
+/// never suggested
+fn find_suggestions(name: &str, context: &ValidationContext) -> Vec<String> {
+    // allow roughly one typo per four characters, but at least one
+    let max_distance = (name.len() / 4).max(1);
+    let mut candidates: Vec<(usize, String)> = Vec::new();
+    let mut add_similar = |candidate: &str| {
+        if candidate == name || candidate.contains("__") {
+            return;
+        }
+        let distance = get_edit_distance(name, candidate).max(1);
+        if distance <= max_distance {
+            candidates.push((distance, candidate.to_string()));
+        }
+    };
+
+    if let Some(member_of) = context.member_of {
+        // members of a qualified reference can only be members of the qualifier's type
+        context
+            .index
+            .get_container_members(member_of)
+            .iter()
+            .for_each(|it| add_similar(it.get_name()));
+    } else {
+        // local variables of the current pou (and its container, e.g. for actions)
+        let containers = context.qualifier.into_iter().chain(
+            context
+                .qualifier
+                .and_then(|it| context.index.find_pou(it))
+                .map(PouIndexEntry::get_container),
+        );
+        containers
+            .flat_map(|it| context.index.get_container_members(it))
+            .for_each(|it| add_similar(it.get_name()));
+        context
+            .index
+            .get_globals()
+            .values()
+            .for_each(|it| add_similar(it.get_name()));
+        context
+            .index
+            .get_pous()
+            .values()
+            .for_each(|it| add_similar(it.get_name()));
+
+        // maybe this is a program's variable that misses its qualifier
+        context
+            .index
+            .get_pous()
+            .values()
+            .filter(|it| matches!(it, PouIndexEntry::Program { .. }))
+            .filter(|it| !it.get_name().contains("__"))
+            .filter_map(|it| {
+                context
+                    .index
+                    .find_member(it.get_name(), name)
+                    .map(|v| format!("{}.{}", it.get_name(), v.get_name()))
+            })
+            .for_each(|it| candidates.push((0, it)));
+    }
+
+    candidates.sort_by_key(|(distance, _)| *distance);
+    let mut suggestions: Vec<String> = Vec::new();
+    for (_, candidate) in candidates {
+        if !suggestions
+            .iter()
+            .any(|it| it.eq_ignore_ascii_case(&candidate))
+        {
+            suggestions.push(candidate);
+        }
+    }
+    suggestions.truncate(3);
+    suggestions
+}
+
+/// returns the (case insensitive) levenshtein distance between the two given names
+fn get_edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// returns false if a value of type `right` can never be assigned to a variable of type `left`
+/// without an explicit conversion (e.g. a STRING to an INT)
+fn is_assignable(left: &DataTypeInformation, right: &DataTypeInformation) -> bool {
+    match (left, right) {
+        // the arguments of variable-length arrays are validated with their call
+        _ if left.is_vla() => true,
+        // strings and numbers need an explicit conversion
+        (DataTypeInformation::String { .. }, _) => !right.is_numerical(),
+        (_, DataTypeInformation::String { .. }) => !left.is_numerical(),
+        // structs can only be assigned to the same struct
+        (
+            DataTypeInformation::Struct { name: l, .. },
+            DataTypeInformation::Struct { name: r, .. },
+        ) => l == r,
+        _ => true,
+    }
+}
+
+/// returns the diagnostic of an integer literal which does not fit into the given integer type
+pub(super) fn validate_literal_range(
+    value: i128,
+    target_type: &DataTypeInformation,
+    literal: &AstStatement,
+) -> Option<Diagnostic> {
+    if !matches!(target_type, DataTypeInformation::Integer { .. })
+        || is_date_or_time_type(target_type)
+    {
+        return None;
+    }
+    let bits = target_type.get_semantic_size();
+    let (min, max) = if target_type.is_signed_int() {
+        (-(1_i128 << (bits - 1)), (1_i128 << (bits - 1)) - 1)
+    } else {
+        (0, (1_i128 << bits) - 1)
+    };
+    (value < min || value > max).then(|| {
+        Diagnostic::literal_out_of_type_range(
+            value.to_string().as_str(),
+            target_type.get_name(),
+            min,
+            max,
+            literal.get_location(),
+        )
+    })
+}
+
 fn is_date_or_time_type(cast_type: &crate::typesystem::DataTypeInformation) -> bool {
     return cast_type.get_name() == DATE_TYPE
         || cast_type.get_name() == DATE_AND_TIME_TYPE
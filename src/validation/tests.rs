@@ -1,9 +1,14 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 mod array_access_validation_test;
 mod bitaccess_validation_test;
+mod dialect_validation_tests;
 mod generic_validation_tests;
 mod literals_validation_tests;
 mod pou_validation_tests;
+mod prototype_validation_tests;
+mod race_validation_tests;
 mod reference_resolve_tests;
+mod safety_validation_tests;
 mod statement_validation_tests;
+mod usage_validation_tests;
 mod variable_validation_tests;
@@ -0,0 +1,297 @@
+use crate::{dialect::Dialect, test_utils::tests::parse_and_validate_with_dialect, Diagnostic};
+
+const SRC: &str = "
+    TYPE PtrToInt : POINTER TO INT; END_TYPE
+
+    PROGRAM prg
+    VAR
+        x : INT;
+        a : LWORD;
+    END_VAR
+        IF x > 0 THEN
+            a := ADR(x);
+        ENDIF;
+    END_PROGRAM
+    ";
+
+#[test]
+fn vendor_extensions_are_reported_in_the_iec_dialect() {
+    let diagnostics = parse_and_validate_with_dialect(SRC, Dialect::Iec);
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unsupported_in_dialect("POINTER TO", Dialect::Iec, (21..28).into()),
+            Diagnostic::unsupported_in_dialect("ENDIF", Dialect::Iec, (174..179).into()),
+            Diagnostic::unsupported_in_dialect("ADR", Dialect::Iec, (158..161).into()),
+        ]
+    );
+}
+
+#[test]
+fn codesys_extensions_are_accepted_in_the_codesys_dialect() {
+    let diagnostics = parse_and_validate_with_dialect(SRC, Dialect::Codesys);
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::ImprovementSuggestion {
+                message: "'POINTER TO' is not a standard keyword, use REF_TO instead".into(),
+                range: (21..28).into(),
+            },
+            Diagnostic::unsupported_in_dialect("ENDIF", Dialect::Codesys, (174..179).into()),
+        ]
+    );
+}
+
+#[test]
+fn all_extensions_are_accepted_in_the_legacy_dialect() {
+    let diagnostics = parse_and_validate_with_dialect(SRC, Dialect::Legacy);
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::ImprovementSuggestion {
+                message: "'POINTER TO' is not a standard keyword, use REF_TO instead".into(),
+                range: (21..28).into(),
+            },
+            Diagnostic::ImprovementSuggestion {
+                message: "the words in ENDIF should be separated by a '_'".into(),
+                range: (174..179).into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn jumps_are_only_accepted_in_the_codesys_dialect() {
+    let diagnostics = parse_and_validate_with_dialect(
+        "
+    PROGRAM prg
+    start:
+        JMP start;
+    END_PROGRAM
+    ",
+        Dialect::Legacy,
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unsupported_in_dialect("JMP", Dialect::Legacy, (36..39).into()),
+            Diagnostic::unsupported_in_dialect("start:", Dialect::Legacy, (21..26).into()),
+        ]
+    );
+}
+
+#[test]
+fn jumps_are_reported_and_their_labels_are_validated() {
+    let diagnostics = parse_and_validate_with_dialect(
+        "
+    PROGRAM prg
+    VAR
+        i : INT;
+    END_VAR
+    start:
+        i := i + 1;
+        IF i < 10 THEN
+            JMP start;
+        END_IF
+        JMP inner;
+        JMP unknown;
+        WHILE i > 0 DO
+        inner:
+            i := i - 1;
+        END_WHILE
+    start:
+    END_PROGRAM
+    ",
+        Dialect::Codesys,
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::duplicate_label("start", (270..275).into()),
+            Diagnostic::jump_statement((120..129).into()),
+            Diagnostic::jump_into_block("inner", (154..163).into()),
+            Diagnostic::unknown_label("unknown", (173..184).into()),
+        ]
+    );
+}
+
+#[test]
+fn comparing_reals_with_a_tolerance_is_a_vendor_builtin() {
+    let src = "
+    PROGRAM prg
+    VAR
+        r : REAL;
+        x : BOOL;
+    END_VAR
+        x := ALMOST_EQUAL(r, r, r);
+    END_PROGRAM
+    ";
+
+    assert_eq!(
+        parse_and_validate_with_dialect(src, Dialect::Iec),
+        vec![Diagnostic::unsupported_in_dialect(
+            "ALMOST_EQUAL",
+            Dialect::Iec,
+            (86..98).into()
+        ),]
+    );
+    assert!(parse_and_validate_with_dialect(src, Dialect::Codesys).is_empty());
+}
+
+#[test]
+fn checksums_are_vendor_builtins() {
+    let src = "
+    PROGRAM prg
+    VAR
+        data : LWORD;
+        length : UDINT;
+        w : WORD;
+        d : DWORD;
+    END_VAR
+        w := CRC16(data, length);
+        d := CRC32(data, length);
+    END_PROGRAM
+    ";
+
+    assert_eq!(
+        parse_and_validate_with_dialect(src, Dialect::Iec),
+        vec![
+            Diagnostic::unsupported_in_dialect("CRC16", Dialect::Iec, (133..138).into()),
+            Diagnostic::unsupported_in_dialect("CRC32", Dialect::Iec, (167..172).into()),
+        ]
+    );
+    assert!(parse_and_validate_with_dialect(src, Dialect::Codesys).is_empty());
+}
+
+#[test]
+fn byte_copies_are_vendor_builtins() {
+    let src = "
+    PROGRAM prg
+    VAR
+        bytes : ARRAY[0..9] OF BYTE;
+        copy : ARRAY[0..9] OF BYTE;
+        s : STRING;
+        n : UDINT;
+    END_VAR
+        n := MEM_COPY(copy, bytes);
+        n := STRING_TO_BYTES(s, bytes);
+        s := BYTES_TO_STRING(bytes, n);
+    END_PROGRAM
+    ";
+
+    assert_eq!(
+        parse_and_validate_with_dialect(src, Dialect::Iec),
+        vec![
+            Diagnostic::unsupported_in_dialect("MEM_COPY", Dialect::Iec, (162..170).into()),
+            Diagnostic::unsupported_in_dialect("STRING_TO_BYTES", Dialect::Iec, (198..213).into()),
+            Diagnostic::unsupported_in_dialect("BYTES_TO_STRING", Dialect::Iec, (238..253).into()),
+        ]
+    );
+    assert!(parse_and_validate_with_dialect(src, Dialect::Codesys).is_empty());
+}
+
+#[test]
+fn file_function_blocks_are_vendor_builtins() {
+    let src = "
+    PROGRAM prg
+    VAR
+        closer : FILE_CLOSE;
+        handle : DINT;
+    END_VAR
+        closer(execute := TRUE, handle := handle);
+    END_PROGRAM
+    ";
+
+    assert_eq!(
+        parse_and_validate_with_dialect(src, Dialect::Iec),
+        vec![Diagnostic::unsupported_in_dialect(
+            "FILE_CLOSE",
+            Dialect::Iec,
+            (97..103).into()
+        ),]
+    );
+    assert!(parse_and_validate_with_dialect(src, Dialect::Codesys).is_empty());
+}
+
+#[test]
+fn array_operations_are_vendor_builtins() {
+    let src = "
+    PROGRAM prg
+    VAR
+        a : ARRAY[0..3] OF REAL;
+        b : ARRAY[0..3] OF REAL;
+        sum : ARRAY[0..3] OF REAL;
+        n : UDINT;
+        dot : LREAL;
+    END_VAR
+        n := ARRAY_ADD(sum, a, b);
+        n := ARRAY_MUL(sum, a, b);
+        dot := ARRAY_DOT(a, b);
+    END_PROGRAM
+    ";
+
+    assert_eq!(
+        parse_and_validate_with_dialect(src, Dialect::Iec),
+        vec![
+            Diagnostic::unsupported_in_dialect("ARRAY_ADD", Dialect::Iec, (191..200).into()),
+            Diagnostic::unsupported_in_dialect("ARRAY_MUL", Dialect::Iec, (226..235).into()),
+            Diagnostic::unsupported_in_dialect("ARRAY_DOT", Dialect::Iec, (263..272).into()),
+        ]
+    );
+    assert!(parse_and_validate_with_dialect(src, Dialect::Codesys).is_empty());
+}
+
+#[test]
+fn matrix_operations_are_vendor_builtins() {
+    let src = "
+    PROGRAM prg
+    VAR
+        a : ARRAY[0..1, 0..1] OF REAL;
+        b : ARRAY[0..1, 0..1] OF REAL;
+        product : ARRAY[0..1, 0..1] OF REAL;
+        n : UDINT;
+    END_VAR
+        n := MATRIX_MUL(product, a, b);
+        n := TRANSPOSE(product, a);
+    END_PROGRAM
+    ";
+
+    assert_eq!(
+        parse_and_validate_with_dialect(src, Dialect::Iec),
+        vec![
+            Diagnostic::unsupported_in_dialect("MATRIX_MUL", Dialect::Iec, (192..202).into()),
+            Diagnostic::unsupported_in_dialect("TRANSPOSE", Dialect::Iec, (232..241).into()),
+        ]
+    );
+    assert!(parse_and_validate_with_dialect(src, Dialect::Codesys).is_empty());
+}
+
+#[test]
+fn saturating_arithmetic_is_a_vendor_builtin() {
+    let src = "
+    PROGRAM prg
+    VAR
+        a : INT;
+        b : INT;
+    END_VAR
+        a := ADD_SAT(a, b);
+        a := SUB_SAT(a, b);
+        a := MUL_SAT(a, b);
+    END_PROGRAM
+    ";
+
+    assert_eq!(
+        parse_and_validate_with_dialect(src, Dialect::Iec),
+        vec![
+            Diagnostic::unsupported_in_dialect("ADD_SAT", Dialect::Iec, (84..91).into()),
+            Diagnostic::unsupported_in_dialect("SUB_SAT", Dialect::Iec, (112..119).into()),
+            Diagnostic::unsupported_in_dialect("MUL_SAT", Dialect::Iec, (140..147).into()),
+        ]
+    );
+    assert!(parse_and_validate_with_dialect(src, Dialect::Codesys).is_empty());
+}
@@ -223,3 +223,108 @@ fn char_cast_validate() {
         ]
     );
 }
+
+#[test]
+fn assigned_literals_are_validated_against_the_target_type() {
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR
+                b : BYTE;
+                s : SINT;
+                x : BOOL;
+                d : DINT;
+            END_VAR
+            b := 255;
+            b := 300;
+            s := -128;
+            s := -129;
+            x := 2;
+            d := 16#7FFF_FFFF;
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::literal_out_of_type_range("300", "BYTE", 0, 255, (200..203).into()),
+            Diagnostic::literal_out_of_type_range("-129", "SINT", -128, 127, (245..249).into()),
+            Diagnostic::literal_out_of_type_range("2", "BOOL", 0, 1, (268..269).into()),
+        ]
+    );
+}
+
+#[test]
+fn initial_literals_are_validated_against_the_variable_type() {
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL
+            g : USINT := 256;
+        END_VAR
+        PROGRAM prg
+            VAR
+                b : BYTE := 300;
+                s : SINT := -128;
+                i : INT := -32769;
+                ok : BYTE := 255;
+            END_VAR
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::literal_out_of_type_range("300", "BYTE", 0, 255, (130..133).into()),
+            Diagnostic::literal_out_of_type_range(
+                "-32769",
+                "INT",
+                -32768,
+                32767,
+                (196..202).into()
+            ),
+            Diagnostic::literal_out_of_type_range("256", "USINT", 0, 255, (45..48).into()),
+        ]
+    );
+}
+
+#[test]
+fn literal_arguments_are_validated_against_the_parameter_type() {
+    let diagnostics = parse_and_validate(
+        "
+        FUNCTION foo : INT
+            VAR_INPUT
+                a : BYTE;
+                b : SINT;
+            END_VAR
+        END_FUNCTION
+
+        FUNCTION_BLOCK fb
+            VAR_INPUT
+                c : USINT;
+            END_VAR
+        END_FUNCTION_BLOCK
+
+        PROGRAM prg
+            VAR
+                instance : fb;
+            END_VAR
+            foo(255, -128);
+            foo(256, -129);
+            foo(a := 300, b := 1);
+            instance(1000);
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::literal_out_of_type_range("256", "BYTE", 0, 255, (398..401).into()),
+            Diagnostic::literal_out_of_type_range("-129", "SINT", -128, 127, (403..407).into()),
+            Diagnostic::literal_out_of_type_range("300", "BYTE", 0, 255, (431..434).into()),
+            Diagnostic::literal_out_of_type_range("1000", "USINT", 0, 255, (466..470).into()),
+        ]
+    );
+}
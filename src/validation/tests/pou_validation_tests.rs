@@ -11,3 +11,180 @@ fn function_no_return_unsupported() {
         vec![Diagnostic::function_return_missing((9..12).into())]
     );
 }
+
+#[test]
+fn classes_can_only_extend_known_classes() {
+    let diagnostics = parse_and_validate(
+        "CLASS a EXTENDS unknown END_CLASS FUNCTION_BLOCK fb END_FUNCTION_BLOCK CLASS b EXTENDS fb END_CLASS",
+    );
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unknown_super_class("unknown", (6..7).into()),
+            Diagnostic::unknown_super_class("fb", (77..78).into()),
+        ]
+    );
+}
+
+#[test]
+fn classes_cannot_extend_themselves() {
+    let diagnostics = parse_and_validate(
+        "CLASS a EXTENDS b END_CLASS CLASS b EXTENDS a END_CLASS CLASS c EXTENDS c END_CLASS",
+    );
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::cyclic_inheritance("a", (6..7).into()),
+            Diagnostic::cyclic_inheritance("b", (34..35).into()),
+            Diagnostic::cyclic_inheritance("c", (62..63).into()),
+        ]
+    );
+}
+
+#[test]
+fn inherited_members_cannot_be_redeclared() {
+    let diagnostics = parse_and_validate(
+        "CLASS a VAR x : INT; END_VAR END_CLASS CLASS b EXTENDS a VAR x : DINT; y : INT; END_VAR END_CLASS",
+    );
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::redeclared_inherited_member(
+            "x",
+            "a",
+            (61..62).into()
+        )]
+    );
+}
+
+#[test]
+fn classes_and_function_blocks_can_only_implement_known_interfaces() {
+    let diagnostics = parse_and_validate(
+        "CLASS a IMPLEMENTS unknown END_CLASS CLASS b END_CLASS FUNCTION_BLOCK fb IMPLEMENTS b END_FUNCTION_BLOCK",
+    );
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unknown_interface("unknown", (6..7).into()),
+            Diagnostic::unknown_interface("b", (70..72).into()),
+        ]
+    );
+}
+
+#[test]
+fn interface_methods_must_be_implemented_with_their_signature() {
+    let diagnostics = parse_and_validate(
+        "INTERFACE i METHOD start : BOOL VAR_INPUT speed : INT; END_VAR END_METHOD METHOD stop END_METHOD METHOD reset END_METHOD END_INTERFACE CLASS base METHOD reset END_METHOD END_CLASS CLASS c EXTENDS base IMPLEMENTS i METHOD start : BOOL VAR_INPUT speed : DINT; END_VAR END_METHOD END_CLASS FUNCTION_BLOCK d IMPLEMENTS i METHOD start : BOOL VAR_INPUT speed : INT; END_VAR END_METHOD METHOD stop END_METHOD METHOD reset END_METHOD END_FUNCTION_BLOCK",
+    );
+    //reset is inherited from the super class of c
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::interface_method_signature_mismatch("start", "i", (186..187).into()),
+            Diagnostic::missing_interface_method("stop", "i", (186..187).into()),
+        ]
+    );
+}
+
+#[test]
+fn overriding_methods_must_override_a_method_with_the_same_signature() {
+    let diagnostics = parse_and_validate(
+        "
+        CLASS Drive
+            METHOD stop : BOOL
+                VAR_INPUT force : BOOL; END_VAR
+            END_METHOD
+        END_CLASS
+
+        CLASS Motor EXTENDS Drive
+            METHOD OVERRIDE stop : INT
+                VAR_INPUT force : BOOL; END_VAR
+            END_METHOD
+            METHOD OVERRIDE start END_METHOD
+        END_CLASS
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::override_signature_mismatch("stop", "Drive", (204..208).into()),
+            Diagnostic::override_without_base_method("start", (314..319).into()),
+        ]
+    );
+}
+
+#[test]
+fn concrete_classes_must_implement_all_abstract_methods() {
+    let diagnostics = parse_and_validate(
+        "
+        CLASS ABSTRACT Shape
+            METHOD ABSTRACT area : DINT END_METHOD
+            METHOD ABSTRACT scale VAR_INPUT factor : DINT; END_VAR END_METHOD
+        END_CLASS
+
+        CLASS ABSTRACT Rectangle EXTENDS Shape
+            METHOD OVERRIDE area : DINT END_METHOD
+        END_CLASS
+
+        CLASS Square EXTENDS Rectangle
+        END_CLASS
+
+        CLASS Circle EXTENDS Shape
+            METHOD OVERRIDE area : DINT END_METHOD
+            METHOD OVERRIDE scale VAR_INPUT factor : DINT; END_VAR END_METHOD
+        END_CLASS
+
+        CLASS Point
+            METHOD ABSTRACT move END_METHOD
+        END_CLASS
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::missing_abstract_method_implementation(
+                "Square",
+                &["scale"],
+                (309..315).into()
+            ),
+            Diagnostic::missing_abstract_method_implementation(
+                "Point",
+                &["move"],
+                (550..555).into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn abstract_classes_cannot_be_instantiated() {
+    let diagnostics = parse_and_validate(
+        "
+        CLASS ABSTRACT Shape
+        END_CLASS
+
+        CLASS Square EXTENDS Shape
+        END_CLASS
+
+        VAR_GLOBAL
+            shape : Shape;
+            square : Square;
+        END_VAR
+
+        FUNCTION area_of : DINT
+            VAR_IN_OUT reference : Shape; END_VAR
+            VAR_INPUT copy : Shape; END_VAR
+            VAR pointer : REF_TO Shape; END_VAR
+        END_FUNCTION
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::abstract_class_instance("copy", "Shape", (299..303).into()),
+            Diagnostic::abstract_class_instance("shape", "Shape", (134..139).into()),
+        ]
+    );
+}
@@ -0,0 +1,94 @@
+use crate::{test_utils::tests::parse_and_validate_prototypes, Diagnostic};
+
+#[test]
+fn implemented_prototypes_are_not_reported() {
+    let diagnostics = parse_and_validate_prototypes(&[
+        "
+        {prototype}
+        FUNCTION_BLOCK Sensor
+            VAR_OUTPUT value : REAL; END_VAR
+        END_FUNCTION_BLOCK
+        ",
+        "
+        FUNCTION_BLOCK Sensor
+            VAR_OUTPUT value : REAL; END_VAR
+            value := 1.0;
+        END_FUNCTION_BLOCK
+        ",
+    ]);
+
+    assert_eq!(diagnostics, vec![vec![], vec![]]);
+}
+
+#[test]
+fn prototypes_without_implementation_or_with_a_different_signature_are_reported() {
+    let diagnostics = parse_and_validate_prototypes(&[
+        "
+        {prototype}
+        FUNCTION scale : DINT
+            VAR_INPUT value : DINT; factor : INT; END_VAR
+        END_FUNCTION
+
+        {prototype}
+        FUNCTION missing : BOOL
+        END_FUNCTION
+        ",
+        "
+        FUNCTION scale : DINT
+            VAR_INPUT value : DINT; factor : DINT; END_VAR
+            scale := value * factor;
+        END_FUNCTION
+        ",
+    ]);
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            vec![Diagnostic::missing_prototype_implementation(
+                "missing",
+                (168..175).into()
+            )],
+            vec![Diagnostic::prototype_signature_mismatch(
+                "scale",
+                (18..23).into()
+            )],
+        ]
+    );
+}
+
+#[test]
+fn prototypes_implemented_more_than_once_are_reported() {
+    let implementation = "
+        FUNCTION_BLOCK Sensor
+            VAR_OUTPUT value : REAL; END_VAR
+            value := 1.0;
+        END_FUNCTION_BLOCK
+        ";
+    let diagnostics = parse_and_validate_prototypes(&[
+        "
+        {prototype}
+        FUNCTION_BLOCK Sensor
+            VAR_OUTPUT value : REAL; END_VAR
+        END_FUNCTION_BLOCK
+        ",
+        implementation,
+        implementation,
+    ]);
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            vec![],
+            vec![Diagnostic::duplicate_prototype_implementation(
+                "Sensor",
+                2,
+                (24..30).into()
+            )],
+            vec![Diagnostic::duplicate_prototype_implementation(
+                "Sensor",
+                2,
+                (24..30).into()
+            )],
+        ]
+    );
+}
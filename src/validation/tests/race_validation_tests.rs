@@ -0,0 +1,248 @@
+use crate::{
+    task_configuration::{ProgramInstance, Task, TaskConfiguration},
+    test_utils::tests::{parse_and_validate_races, parse_and_validate_single_writers},
+    Diagnostic,
+};
+
+fn tasks(tasks: &[(&str, &[&str])]) -> TaskConfiguration {
+    TaskConfiguration {
+        tasks: tasks
+            .iter()
+            .map(|(name, programs)| Task {
+                name: name.to_string(),
+                priority: 0,
+                interval: 0,
+                programs: programs.iter().map(|it| it.to_string()).collect(),
+                ..Default::default()
+            })
+            .collect(),
+        instances: vec![],
+        telemetry: None,
+    }
+}
+
+#[test]
+fn globals_written_by_multiple_tasks_are_reported() {
+    let diagnostics = parse_and_validate_races(
+        "
+        VAR_GLOBAL
+            shared : INT;
+            owned : INT;
+        END_VAR
+
+        PROGRAM fast
+            shared := 1;
+            owned := 2;
+        END_PROGRAM
+
+        PROGRAM slow
+            shared := 3;
+        END_PROGRAM
+       ",
+        &tasks(&[("FastTask", &["fast"]), ("SlowTask", &["slow"])]),
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unsynchronized_global_access(
+            "shared",
+            &["FastTask", "SlowTask"],
+            (32..38).into()
+        )]
+    );
+}
+
+#[test]
+fn globals_written_through_called_pous_are_reported() {
+    let diagnostics = parse_and_validate_races(
+        "
+        VAR_GLOBAL
+            speed : INT;
+        END_VAR
+
+        FUNCTION_BLOCK regulator
+            speed := speed + 1;
+        END_FUNCTION_BLOCK
+
+        PROGRAM control
+        VAR
+            reg : regulator;
+        END_VAR
+            reg();
+        END_PROGRAM
+
+        PROGRAM hmi
+        VAR
+            display : INT;
+        END_VAR
+            display := speed;
+        END_PROGRAM
+       ",
+        &tasks(&[("Control", &["control"]), ("Hmi", &["hmi"])]),
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unsynchronized_global_access(
+            "speed",
+            &["Control", "Hmi"],
+            (32..37).into()
+        )]
+    );
+}
+
+#[test]
+fn globals_written_by_instances_of_a_program_in_multiple_tasks_are_reported() {
+    let mut configuration = tasks(&[("StationA", &["station_a"]), ("StationB", &["station_b"])]);
+    configuration.instances = ["station_a", "station_b"]
+        .iter()
+        .map(|name| ProgramInstance {
+            name: name.to_string(),
+            program: "station".into(),
+        })
+        .collect();
+    let diagnostics = parse_and_validate_races(
+        "
+        VAR_GLOBAL
+            parts : INT;
+        END_VAR
+
+        PROGRAM station
+            parts := parts + 1;
+        END_PROGRAM
+       ",
+        &configuration,
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unsynchronized_global_access(
+            "parts",
+            &["StationA", "StationB"],
+            (32..37).into()
+        )]
+    );
+}
+
+#[test]
+fn atomic_and_read_only_globals_are_not_reported() {
+    let diagnostics = parse_and_validate_races(
+        "
+        VAR_GLOBAL {atomic}
+            counter : DINT;
+        END_VAR
+        VAR_GLOBAL
+            setpoint : INT;
+        END_VAR
+
+        PROGRAM fast
+        VAR
+            x : INT;
+        END_VAR
+            counter := counter + 1;
+            x := setpoint;
+        END_PROGRAM
+
+        PROGRAM slow
+        VAR
+            y : INT;
+        END_VAR
+            counter := 0;
+            y := setpoint;
+        END_PROGRAM
+       ",
+        &tasks(&[("FastTask", &["fast"]), ("SlowTask", &["slow"])]),
+    );
+
+    assert_eq!(diagnostics, vec![]);
+}
+
+#[test]
+fn globals_of_programs_in_the_same_task_are_not_reported() {
+    let diagnostics = parse_and_validate_races(
+        "
+        VAR_GLOBAL
+            shared : INT;
+        END_VAR
+
+        PROGRAM a
+            shared := 1;
+        END_PROGRAM
+
+        PROGRAM b
+            shared := 2;
+        END_PROGRAM
+       ",
+        &tasks(&[("MainTask", &["a", "b"])]),
+    );
+
+    assert_eq!(diagnostics, vec![]);
+}
+
+#[test]
+fn globals_written_by_multiple_programs_are_reported() {
+    let diagnostics = parse_and_validate_single_writers(
+        "
+        VAR_GLOBAL
+            mode : INT;
+            owned : INT;
+        END_VAR
+
+        FUNCTION_BLOCK switcher
+            mode := 2;
+        END_FUNCTION_BLOCK
+
+        PROGRAM a
+            mode := 1;
+            owned := 1;
+        END_PROGRAM
+
+        PROGRAM b
+        VAR
+            s : switcher;
+        END_VAR
+            s();
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::multiple_writers(
+            "mode",
+            &["a", "b"],
+            (32..36).into()
+        )]
+    );
+}
+
+#[test]
+fn shared_globals_and_globals_written_by_called_programs_are_not_reported() {
+    let diagnostics = parse_and_validate_single_writers(
+        "
+        VAR_GLOBAL {shared}
+            mode : INT;
+        END_VAR
+        VAR_GLOBAL {atomic} {shared}
+            counter : DINT;
+        END_VAR
+        VAR_GLOBAL
+            step : INT;
+        END_VAR
+
+        PROGRAM a
+            mode := 1;
+            counter := counter + 1;
+            b();
+        END_PROGRAM
+
+        PROGRAM b
+            mode := 2;
+            counter := 0;
+            step := step + 1;
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(diagnostics, vec![]);
+}
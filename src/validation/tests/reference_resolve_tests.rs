@@ -1,5 +1,5 @@
 use crate::test_utils::tests::parse_and_validate;
-use crate::Diagnostic;
+use crate::{diagnostics::CodeEdit, Diagnostic};
 
 /// tests wheter simple local and global variables can be resolved and
 /// errors are reported properly
@@ -26,8 +26,16 @@ fn resolve_simple_variable_references() {
     assert_eq!(
         diagnostics,
         vec![
-            Diagnostic::unresolved_reference("b", (168..169).into()),
-            Diagnostic::unresolved_reference("gb", (207..209).into()),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "b",
+                &["a".to_string()],
+                (168..169).into()
+            ),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "gb",
+                &["ga".to_string()],
+                (207..209).into()
+            ),
         ]
     );
 }
@@ -56,10 +64,26 @@ fn resolve_function_calls_and_parameters() {
     assert_eq!(
         diagnostics,
         vec![
-            Diagnostic::unresolved_reference("boo", (101..104).into()),
-            Diagnostic::unresolved_reference("c", (105..106).into()),
-            Diagnostic::unresolved_reference("c", (163..164).into()),
-            Diagnostic::unresolved_reference("y", (187..188).into()),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "boo",
+                &["foo".to_string()],
+                (101..104).into()
+            ),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "c",
+                &["a".to_string()],
+                (105..106).into()
+            ),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "c",
+                &["a".to_string()],
+                (163..164).into()
+            ),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "y",
+                &["a".to_string()],
+                (187..188).into()
+            ),
         ]
     );
 }
@@ -116,12 +140,48 @@ fn resole_struct_member_access() {
     assert_eq!(
         diagnostics,
         vec![
-            Diagnostic::unresolved_reference("field10", (694..701).into()),
-            Diagnostic::unresolved_reference("field20", (721..728).into()),
-            Diagnostic::unresolved_reference("field30", (748..755).into()),
-            Diagnostic::unresolved_reference("subfield10", (955..965).into()),
-            Diagnostic::unresolved_reference("subfield20", (989..999).into()),
-            Diagnostic::unresolved_reference("subfield30", (1023..1033).into()),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "field10",
+                &["field1".to_string()],
+                (694..701).into()
+            ),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "field20",
+                &["field2".to_string()],
+                (721..728).into()
+            ),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "field30",
+                &["field3".to_string()],
+                (748..755).into()
+            ),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "subfield10",
+                &[
+                    "subfield1".to_string(),
+                    "subfield2".to_string(),
+                    "subfield3".to_string()
+                ],
+                (955..965).into()
+            ),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "subfield20",
+                &[
+                    "subfield2".to_string(),
+                    "subfield1".to_string(),
+                    "subfield3".to_string()
+                ],
+                (989..999).into()
+            ),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "subfield30",
+                &[
+                    "subfield3".to_string(),
+                    "subfield1".to_string(),
+                    "subfield2".to_string()
+                ],
+                (1023..1033).into()
+            ),
         ]
     );
 }
@@ -194,11 +254,23 @@ fn resolve_function_block_calls_in_structs_and_field_access() {
     assert_eq!(
         diagnostics,
         vec![
-            Diagnostic::unresolved_reference("fb3", (650..653).into()),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "fb3",
+                &["fb1".to_string(), "fb2".to_string()],
+                (650..653).into()
+            ),
             Diagnostic::unresolved_reference("a", (654..655).into()),
-            Diagnostic::unresolved_reference("fb3", (664..667).into()),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "fb3",
+                &["fb1".to_string(), "fb2".to_string()],
+                (664..667).into()
+            ),
             Diagnostic::unresolved_reference("b", (668..669).into()),
-            Diagnostic::unresolved_reference("fb3", (678..681).into()),
+            Diagnostic::unresolved_reference_with_suggestions(
+                "fb3",
+                &["fb1".to_string(), "fb2".to_string()],
+                (678..681).into()
+            ),
             Diagnostic::unresolved_reference("c", (682..683).into()),
         ]
     );
@@ -236,3 +308,101 @@ fn resolve_function_members_via_qualifier() {
         ]
     );
 }
+
+/// tests whether unresolved references offer a quick-fix to the closest
+/// visible name
+#[test]
+fn unresolved_references_suggest_closest_match() {
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR motorSpeed : INT; END_VAR
+            motorSped := 1;
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unresolved_reference_with_suggestions(
+            "motorSped",
+            &["motorSpeed".to_string()],
+            (75..84).into()
+        )]
+    );
+}
+
+/// tests whether the suggestions offer a quick-fix to the best match
+#[test]
+fn unresolved_reference_suggestions_offer_quick_fix() {
+    let diagnostic = Diagnostic::unresolved_reference_with_suggestions(
+        "iMotorSped",
+        &["iMotorSpeed".to_string(), "iMotorSpeeds".to_string()],
+        (1..11).into(),
+    );
+
+    assert_eq!(
+        diagnostic.get_message(),
+        "Could not resolve reference to iMotorSped. Did you mean 'iMotorSpeed', 'iMotorSpeeds'?"
+    );
+    assert_eq!(
+        diagnostic.get_edits(),
+        &[CodeEdit::replace((1..11).into(), "iMotorSpeed")]
+    );
+}
+
+/// tests whether program variables used without the program's qualifier are suggested
+#[test]
+fn unresolved_references_suggest_missing_qualifier() {
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM mainProg
+            VAR counter : INT; END_VAR
+        END_PROGRAM
+
+        PROGRAM otherProg
+            counter;
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unresolved_reference_with_suggestions(
+            "counter",
+            &["mainProg.counter".to_string()],
+            (124..131).into()
+        )]
+    );
+}
+
+/// tests whether short names are offered the names differing by a single typo and whether the
+/// names generated by the compiler are never suggested
+#[test]
+fn unresolved_references_suggest_short_names_but_no_generated_names() {
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL
+            __retries : INT;
+        END_VAR
+
+        PROGRAM prg
+            VAR i : INT; END_VAR
+            j := 1;
+            _retries := 0;
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unresolved_reference_with_suggestions(
+                "j",
+                &["i".to_string()],
+                (131..132).into()
+            ),
+            Diagnostic::unresolved_reference("_retries", (151..159).into()),
+        ]
+    );
+}
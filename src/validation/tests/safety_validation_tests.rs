@@ -0,0 +1,219 @@
+use crate::{test_utils::tests::parse_and_validate_safety, Diagnostic};
+
+#[test]
+fn pointer_variables_and_addresses_are_reported() {
+    let diagnostics = parse_and_validate_safety(
+        "
+        TYPE Node : STRUCT
+            next : REF_TO Node;
+        END_STRUCT END_TYPE
+
+        VAR_GLOBAL
+            g : POINTER TO INT;
+        END_VAR
+
+        PROGRAM prg
+        VAR
+            x : INT;
+            p : REF_TO INT;
+            refs : ARRAY[0..1] OF REF_TO INT;
+        END_VAR
+        VAR_IN_OUT
+            io : INT;
+        END_VAR
+            p := REF(x);
+            g := ADR(x);
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::safety_pointer_variable("p", (222..223).into()),
+            Diagnostic::safety_pointer_variable("refs", (250..254).into()),
+            Diagnostic::safety_pointer_variable("g", (120..121).into()),
+            Diagnostic::safety_pointer_variable("next", (40..44).into()),
+            Diagnostic::safety_address_of("REF", (374..377).into()),
+            Diagnostic::safety_address_of("ADR", (399..402).into()),
+        ]
+    );
+}
+
+#[test]
+fn allocator_calls_are_reported() {
+    let diagnostics = parse_and_validate_safety(
+        "
+        @EXTERNAL FUNCTION malloc : LWORD
+        VAR_INPUT
+            size : ULINT;
+        END_VAR
+        END_FUNCTION
+
+        @EXTERNAL FUNCTION free : BOOL
+        VAR_INPUT
+            address : LWORD;
+        END_VAR
+        END_FUNCTION
+
+        PROGRAM prg
+        VAR
+            address : LWORD;
+        END_VAR
+            address := malloc(8);
+            free(address);
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::safety_dynamic_memory("malloc", (349..355).into()),
+            Diagnostic::safety_dynamic_memory("free", (372..376).into()),
+        ]
+    );
+}
+
+#[test]
+fn recursive_pous_are_reported() {
+    let diagnostics = parse_and_validate_safety(
+        "
+        FUNCTION fact : DINT
+        VAR_INPUT
+            n : DINT;
+        END_VAR
+            IF n > 1 THEN
+                fact := n * fact(n - 1);
+            ELSE
+                fact := 1;
+            END_IF
+        END_FUNCTION
+
+        FUNCTION ping : DINT
+            ping := pong();
+        END_FUNCTION
+
+        FUNCTION pong : DINT
+            pong := ping();
+        END_FUNCTION
+
+        FUNCTION leaf : DINT
+            leaf := 1;
+        END_FUNCTION
+
+        PROGRAM prg
+        VAR
+            x : DINT;
+        END_VAR
+            x := fact(3) + ping() + leaf();
+        END_PROGRAM
+       ",
+    );
+
+    // callers of recursive POUs are not recursive themselves
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::safety_recursion(&["fact", "fact"], (18..22).into()),
+            Diagnostic::safety_recursion(&["ping", "pong", "ping"], (255..259).into()),
+            Diagnostic::safety_recursion(&["pong", "ping", "pong"], (334..338).into()),
+        ]
+    );
+}
+
+#[test]
+fn variadic_pous_and_their_calls_are_reported() {
+    let diagnostics = parse_and_validate_safety(
+        "
+        @EXTERNAL FUNCTION printf : DINT
+        VAR_INPUT
+            format : STRING;
+            args : ...;
+        END_VAR
+        END_FUNCTION
+
+        FUNCTION sum : DINT
+        VAR_INPUT
+            values : ...;
+        END_VAR
+        END_FUNCTION
+
+        PROGRAM prg
+            printf('%d', 1);
+            sum(1, 2, 3);
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::safety_variadic("sum", (209..215).into()),
+            Diagnostic::safety_variadic("printf", (293..299).into()),
+            Diagnostic::safety_variadic("sum", (322..325).into()),
+        ]
+    );
+}
+
+#[test]
+fn unbounded_loops_are_reported() {
+    let diagnostics = parse_and_validate_safety(
+        "
+        PROGRAM prg
+        VAR
+            i : INT;
+        END_VAR
+            WHILE i < 10 DO
+                i := i + 1;
+            END_WHILE
+            IF i > 5 THEN
+                REPEAT
+                    i := i - 1;
+                UNTIL i = 0
+                END_REPEAT;
+            END_IF
+            FOR i := 0 TO 10 DO
+                i := i;
+            END_FOR
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::safety_unbounded_loop("WHILE", (82..147).into()),
+            Diagnostic::safety_unbounded_loop("REPEAT", (190..283).into()),
+        ]
+    );
+}
+
+#[test]
+fn conforming_sources_are_not_reported() {
+    let diagnostics = parse_and_validate_safety(
+        "
+        FUNCTION add : INT
+        VAR_INPUT
+            a, b : INT;
+        END_VAR
+        VAR_IN_OUT
+            result : INT;
+        END_VAR
+            FOR a := 0 TO b DO
+                result := result + a;
+            END_FOR
+            add := result;
+        END_FUNCTION
+
+        PROGRAM prg
+        VAR
+            x : INT;
+        END_VAR
+            add(1, 2, x);
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(diagnostics, vec![]);
+}
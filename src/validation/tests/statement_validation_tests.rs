@@ -1,5 +1,5 @@
 use crate::test_utils::tests::parse_and_validate;
-use crate::Diagnostic;
+use crate::{ast::Operator, diagnostics::CodeEdit, Diagnostic};
 
 #[test]
 fn assignment_to_constants_result_in_an_error() {
@@ -144,36 +144,24 @@ fn invalid_char_assignments() {
 }
 
 #[test]
-fn missing_string_compare_function_causes_error() {
-    // GIVEN assignment statements to constants, some to writable variables
+fn strings_are_compared_without_compare_functions() {
+    // GIVEN STRING comparisons without STRING_EQUAL, STRING_LESS or STRING_GREATER functions
     // WHEN it is validated
     let diagnostics = parse_and_validate(
         "
         PROGRAM prg
-            'a' =  'b'; // missing compare function :-(
-            'a' <> 'b'; // missing compare function :-(
-            'a' <  'b'; // missing compare function :-(
-            'a' >  'b'; // missing compare function :-(
-            'a' <= 'b'; // missing compare function :-(
-            'a' >= 'b'; // missing compare function :-(
+            'a' =  'b';
+            'a' <> 'b';
+            'a' <  'b';
+            'a' >  'b';
+            'a' <= 'b';
+            'a' >= 'b';
         END_PROGRAM
       ",
     );
 
-    // THEN everything but VAR and VAR_GLOBALS are reported
-    assert_eq!(
-        diagnostics,
-        vec![
-            Diagnostic::missing_compare_function("STRING_EQUAL", "STRING", (33..43).into()),
-            Diagnostic::missing_compare_function("STRING_EQUAL", "STRING", (89..99).into()),
-            Diagnostic::missing_compare_function("STRING_LESS", "STRING", (145..155).into()),
-            Diagnostic::missing_compare_function("STRING_GREATER", "STRING", (201..211).into()),
-            Diagnostic::missing_compare_function("STRING_LESS", "STRING", (257..267).into()),
-            Diagnostic::missing_compare_function("STRING_EQUAL", "STRING", (257..267).into()),
-            Diagnostic::missing_compare_function("STRING_GREATER", "STRING", (313..323).into()),
-            Diagnostic::missing_compare_function("STRING_EQUAL", "STRING", (313..323).into()),
-        ]
-    );
+    // THEN the compiler generates the comparisons
+    assert_eq!(diagnostics, vec![]);
 }
 
 #[test]
@@ -227,36 +215,24 @@ fn string_compare_function_with_wrong_signature_causes_error() {
 }
 
 #[test]
-fn missing_wstring_compare_function_causes_error() {
-    // GIVEN assignment statements to constants, some to writable variables
+fn wstrings_are_compared_without_compare_functions() {
+    // GIVEN WSTRING comparisons without WSTRING_EQUAL, WSTRING_LESS or WSTRING_GREATER functions
     // WHEN it is validated
     let diagnostics = parse_and_validate(
         r#"
         PROGRAM prg
-            "a" =  "b"; // missing compare function :-(
-            "a" <> "b"; // missing compare function :-(
-            "a" <  "b"; // missing compare function :-(
-            "a" >  "b"; // missing compare function :-(
-            "a" <= "b"; // missing compare function :-(
-            "a" >= "b"; // missing compare function :-(
+            "a" =  "b";
+            "a" <> "b";
+            "a" <  "b";
+            "a" >  "b";
+            "a" <= "b";
+            "a" >= "b";
         END_PROGRAM
       "#,
     );
 
-    // THEN everything but VAR and VAR_GLOBALS are reported
-    assert_eq!(
-        diagnostics,
-        vec![
-            Diagnostic::missing_compare_function("WSTRING_EQUAL", "WSTRING", (33..43).into()),
-            Diagnostic::missing_compare_function("WSTRING_EQUAL", "WSTRING", (89..99).into()),
-            Diagnostic::missing_compare_function("WSTRING_LESS", "WSTRING", (145..155).into()),
-            Diagnostic::missing_compare_function("WSTRING_GREATER", "WSTRING", (201..211).into()),
-            Diagnostic::missing_compare_function("WSTRING_LESS", "WSTRING", (257..267).into()),
-            Diagnostic::missing_compare_function("WSTRING_EQUAL", "WSTRING", (257..267).into()),
-            Diagnostic::missing_compare_function("WSTRING_GREATER", "WSTRING", (313..323).into()),
-            Diagnostic::missing_compare_function("WSTRING_EQUAL", "WSTRING", (313..323).into()),
-        ]
-    );
+    // THEN the compiler generates the comparisons
+    assert_eq!(diagnostics, vec![]);
 }
 
 #[test]
@@ -283,3 +259,477 @@ fn wstring_compare_function_cause_no_error_if_functions_exist() {
     // THEN everything but VAR and VAR_GLOBALS are reported
     assert_eq!(diagnostics, vec![]);
 }
+
+#[test]
+fn assignment_of_incompatible_types_reports_type_mismatch() {
+    // GIVEN assignments between strings, numbers and structs
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        r#"
+        TYPE MyStruct : STRUCT a : INT; END_STRUCT END_TYPE
+        TYPE OtherStruct : STRUCT a : INT; END_STRUCT END_TYPE
+
+        PROGRAM prg
+            VAR
+                i : INT;
+                r : REAL;
+                s : STRING;
+                ms : MyStruct;
+                os : OtherStruct;
+            END_VAR
+            i := s; // invalid
+            s := r; // invalid
+            s := 'ABC';
+            ms := os; // invalid
+            ms := ms;
+            r := i;
+        END_PROGRAM
+      "#,
+    );
+
+    // THEN the expected and the actual type are reported together with the conversion
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::type_mismatch("INT", "STRING", Some("STRING_TO_INT"), (342..343).into()),
+            Diagnostic::type_mismatch("STRING", "REAL", Some("REAL_TO_STRING"), (373..374).into()),
+            Diagnostic::type_mismatch("MyStruct", "OtherStruct", None, (429..431).into()),
+        ]
+    );
+}
+
+#[test]
+fn type_mismatches_of_sized_strings_and_aliases_suggest_elementary_conversions() {
+    let diagnostics = parse_and_validate(
+        r#"
+        TYPE MyInt : INT; END_TYPE
+
+        PROGRAM prg
+            VAR
+                i : MyInt;
+                s : STRING[20];
+            END_VAR
+            i := s;
+            s := i;
+        END_PROGRAM
+      "#,
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::type_mismatch("INT", "STRING", Some("STRING_TO_INT"), (169..170).into()),
+            Diagnostic::type_mismatch("STRING", "INT", Some("INT_TO_STRING"), (189..190).into()),
+        ]
+    );
+}
+
+#[test]
+fn integer_conditions_are_reported() {
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR
+                i : INT;
+                b : BOOL;
+            END_VAR
+            IF i THEN END_IF
+            IF b THEN END_IF
+            WHILE i DO END_WHILE
+            REPEAT UNTIL i > 0 END_REPEAT
+            IF i + 1 THEN END_IF
+        END_PROGRAM
+      ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::implicit_bool_conversion("INT", (123..124).into())
+                .with_edits(vec![CodeEdit::insert(" <> 0", 124)]),
+            Diagnostic::implicit_bool_conversion("INT", (184..185).into())
+                .with_edits(vec![CodeEdit::insert(" <> 0", 185)]),
+            Diagnostic::implicit_bool_conversion("DINT", (256..261).into()).with_edits(vec![
+                CodeEdit::insert("(", 256),
+                CodeEdit::insert(") <> 0", 261)
+            ]),
+        ]
+    );
+}
+
+#[test]
+fn arithmetic_on_bools_is_reported() {
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR
+                i : INT;
+                b : BOOL;
+            END_VAR
+            i := b + 1;
+            i := i + TRUE;
+            i := i + 1;
+        END_PROGRAM
+      ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::bool_arithmetic(&Operator::Plus, (125..126).into()),
+            Diagnostic::bool_arithmetic(&Operator::Plus, (153..157).into()),
+        ]
+    );
+}
+
+#[test]
+fn bit_operations_on_signed_types_are_reported() {
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR
+                i : INT;
+                w : WORD;
+            END_VAR
+            i := i AND 16#FF;
+            w := w AND 16#FF;
+            w := NOT w;
+            i := NOT i;
+        END_PROGRAM
+      ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::signed_bit_operation(&Operator::And, "INT", (125..126).into()),
+            Diagnostic::signed_bit_operation(&Operator::Not, "INT", (213..214).into()),
+        ]
+    );
+}
+
+#[test]
+fn comparison_chains_are_reported() {
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR
+                a, b, c : INT;
+                x : BOOL;
+            END_VAR
+            x := a < b < c;
+            x := 0 <= a <= 10;
+            x := a < b = x;
+        END_PROGRAM
+      ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::comparison_chain(Some("a < b AND b < c"), (131..140).into()),
+            Diagnostic::comparison_chain(Some("0 <= a AND a <= 10"), (159..171).into()),
+        ]
+    );
+    assert_eq!(
+        diagnostics[0].get_edits(),
+        &[CodeEdit::replace((131..140).into(), "a < b AND b < c")]
+    );
+}
+
+#[test]
+fn equality_comparisons_of_reals_are_reported() {
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR
+                r : REAL;
+                l : LREAL;
+                i : INT;
+                x : BOOL;
+            END_VAR
+            x := r = 0.1;
+            x := l <> r;
+            x := i = 1;
+            x := r < 0.1;
+            x := ALMOST_EQUAL(r, 0.1, 0.001);
+        END_PROGRAM
+      ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::real_equality(&Operator::Equal, (178..185).into()),
+            Diagnostic::real_equality(&Operator::NotEqual, (204..210).into()),
+        ]
+    );
+}
+
+#[test]
+fn output_assignments_after_return_are_reported() {
+    let diagnostics = parse_and_validate(
+        "
+        FUNCTION_BLOCK fb
+            VAR_INPUT
+                enable : BOOL;
+            END_VAR
+            VAR_OUTPUT
+                running : BOOL;
+            END_VAR
+            VAR
+                count : INT;
+            END_VAR
+            IF NOT enable THEN
+                RETURN;
+                running := FALSE;
+            END_IF
+            running := TRUE;
+            RETURN;
+            count := count + 1;
+            IF enable THEN
+                running := FALSE;
+            END_IF
+        END_FUNCTION_BLOCK
+
+        FUNCTION foo : INT
+            RETURN;
+            foo := 1;
+        END_FUNCTION
+      ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unreachable_output_assignment("fb.running", (311..327).into()),
+            Diagnostic::unreachable_output_assignment("fb.running", (472..488).into()),
+            Diagnostic::unreachable_output_assignment("foo.foo", (596..604).into()),
+        ]
+    );
+}
+
+#[test]
+fn string_case_labels_have_to_be_string_literals_or_constants() {
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL CONSTANT
+            START : STRING := 'start';
+            LIMIT : INT := 10;
+        END_VAR
+
+        PROGRAM prg
+            VAR
+                cmd : STRING;
+                other : STRING;
+                i : INT;
+            END_VAR
+            CASE cmd OF
+                START, 'stop': i := 1;
+                other: i := 2;
+                LIMIT, 'a'..'c': i := 3;
+            END_CASE
+            CASE i OF
+                LIMIT: i := 4;
+            END_CASE
+        END_PROGRAM
+      ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_case_label("STRING", (338..343).into()),
+            Diagnostic::invalid_case_label("STRING", (369..374).into()),
+            Diagnostic::invalid_case_label("STRING", (376..384).into()),
+        ]
+    );
+}
+
+#[test]
+fn comparing_strings_of_different_widths_causes_error() {
+    let diagnostics = parse_and_validate(
+        r#"
+        PROGRAM prg
+            VAR
+                s : STRING;
+                ws : WSTRING;
+            END_VAR
+            s = ws;
+            ws <= s;
+        END_PROGRAM
+      "#,
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::type_mismatch(
+                "STRING",
+                "WSTRING",
+                Some("WSTRING_TO_STRING"),
+                (131..133).into()
+            ),
+            Diagnostic::type_mismatch(
+                "WSTRING",
+                "STRING",
+                Some("STRING_TO_WSTRING"),
+                (153..154).into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn concat_operands_have_to_be_strings_of_the_same_width() {
+    let diagnostics = parse_and_validate(
+        r#"
+        PROGRAM prg
+            VAR
+                s : STRING[10];
+                ws : WSTRING;
+                i : INT;
+            END_VAR
+            s := CONCAT(s, 'abc');
+            s := CONCAT('a', s, ws);
+            s := CONCAT(s, i);
+        END_PROGRAM
+      "#,
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::string_truncation(13, 10, (161..177).into()),
+            Diagnostic::type_mismatch(
+                "STRING",
+                "WSTRING",
+                Some("WSTRING_TO_STRING"),
+                (211..213).into()
+            ),
+            Diagnostic::string_truncation(91, 10, (196..214).into()),
+            Diagnostic::invalid_type_nature("INT", "String", (243..244).into()),
+        ]
+    );
+}
+
+#[test]
+fn memory_functions_cannot_access_variables_beyond_their_size() {
+    let diagnostics = parse_and_validate(
+        r#"
+        TYPE Header : STRUCT
+            kind : BYTE;
+            length : DINT;
+        END_STRUCT END_TYPE
+
+        PROGRAM prg
+            VAR
+                header : Header;
+                frame : ARRAY[0..5] OF BYTE;
+                name : STRING[10];
+                p : REF_TO BYTE;
+                n : UDINT;
+                d : DINT;
+            END_VAR
+            MEMCPY(header, frame, 6);
+            MEMCPY(header, frame, 8);
+            MEMCPY(ADR(header), frame, n);
+            MEMSET(name, 0, 12);
+            MEMSET(p, 0, 100);
+            d := MEMCMP(header, ADR(frame), 9);
+        END_PROGRAM
+      "#,
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::memory_size_exceeded(8, 6, (431..436).into()),
+            Diagnostic::memory_size_exceeded(12, 11, (504..508).into()),
+            Diagnostic::memory_size_exceeded(9, 8, (573..579).into()),
+        ]
+    );
+}
+
+#[test]
+fn reference_bindings_are_validated() {
+    let diagnostics = parse_and_validate(
+        "
+        FUNCTION_BLOCK fb
+            VAR_IN_OUT
+                io : INT;
+            END_VAR
+            VAR
+                r : REFERENCE TO INT;
+                i : INT;
+                d : DINT;
+            END_VAR
+            r REF= i;
+            r REF= d;
+            i REF= i;
+            io REF= i;
+            r REF= 5;
+        END_FUNCTION_BLOCK
+      ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_reference_binding("INT", "DINT", (262..263).into()),
+            Diagnostic::cannot_bind_reference((277..278).into()),
+            Diagnostic::cannot_bind_reference((299..301).into()),
+            Diagnostic::invalid_reference_binding("INT", "DINT", (329..330).into()),
+        ]
+    );
+}
+
+#[test]
+fn arguments_of_variable_length_arrays_are_validated() {
+    // GIVEN arrays and other variables passed to a variable-length array
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        FUNCTION sum : DINT
+            VAR_IN_OUT
+                values : ARRAY[*] OF INT;
+            END_VAR
+            sum := LOWER_BOUND(values, 1) + UPPER_BOUND(values, 1);
+        END_FUNCTION
+
+        FUNCTION forward : DINT
+            VAR_IN_OUT
+                values : ARRAY[*] OF INT;
+            END_VAR
+            forward := sum(values);
+        END_FUNCTION
+
+        PROGRAM prg
+            VAR
+                ints : ARRAY[1..5] OF INT;
+                matrix : ARRAY[1..2, 1..2] OF INT;
+                reals : ARRAY[1..5] OF REAL;
+                x : INT;
+            END_VAR
+            sum(ints);
+            sum(values := ints);
+            sum(matrix);
+            sum(reals);
+            sum(x);
+            x := LOWER_BOUND(ints, 1);
+        END_PROGRAM
+      ",
+    );
+
+    // THEN arrays of another element type or number of dimensions are reported, as well as
+    // bounds of arrays that are not passed as variable-length arrays
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_vla_argument("ARRAY[*] OF INT", "__prg_matrix", (671..677).into()),
+            Diagnostic::invalid_vla_argument("ARRAY[*] OF INT", "__prg_reals", (696..701).into()),
+            Diagnostic::invalid_vla_argument("ARRAY[*] OF INT", "INT", (720..721).into()),
+            Diagnostic::invalid_vla_argument("ARRAY[*]", "__prg_ints", (753..757).into()),
+        ]
+    );
+}
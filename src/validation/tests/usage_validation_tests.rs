@@ -0,0 +1,115 @@
+use crate::{test_utils::tests::parse_and_validate_usage, Diagnostic};
+
+#[test]
+fn locals_written_but_never_read_are_reported() {
+    let diagnostics = parse_and_validate_usage(
+        "
+        PROGRAM prg
+        VAR
+            a : INT;
+            b : INT;
+            c : ARRAY[0..2] OF INT;
+        END_VAR
+        VAR_TEMP
+            t : INT;
+        END_VAR
+            a := 1;
+            b := a;
+            c[a] := 2;
+            t := 3;
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::write_only_variable("prg.b", (66..67).into()),
+            Diagnostic::write_only_variable("prg.c", (87..88).into()),
+            Diagnostic::write_only_variable("prg.t", (156..157).into()),
+        ]
+    );
+}
+
+#[test]
+fn outputs_read_through_call_outputs_are_not_reported() {
+    let diagnostics = parse_and_validate_usage(
+        "
+        FUNCTION_BLOCK fb
+        VAR_OUTPUT
+            wired : BOOL;
+            unwired : BOOL;
+        END_VAR
+            wired := TRUE;
+            unwired := TRUE;
+        END_FUNCTION_BLOCK
+
+        PROGRAM prg
+        VAR
+            inst : fb;
+            x : BOOL;
+        END_VAR
+            inst(wired => x);
+            IF x THEN
+                x := FALSE;
+            END_IF
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::write_only_variable(
+            "fb.unwired",
+            (84..91).into()
+        )]
+    );
+}
+
+#[test]
+fn variables_at_direct_addresses_are_not_reported() {
+    let diagnostics = parse_and_validate_usage(
+        "
+        PROGRAM prg
+        VAR
+            lamp AT %QX1.0 : BOOL;
+            flags : BYTE;
+        END_VAR
+            lamp := TRUE;
+            flags.%X1 := TRUE;
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::write_only_variable(
+            "prg.flags",
+            (80..85).into()
+        )]
+    );
+}
+
+#[test]
+fn variables_read_from_other_pous_are_not_reported() {
+    let diagnostics = parse_and_validate_usage(
+        "
+        PROGRAM producer
+        VAR_OUTPUT
+            value : INT;
+        END_VAR
+            value := 42;
+        END_PROGRAM
+
+        PROGRAM consumer
+        VAR
+            i : INT;
+        END_VAR
+            FOR i := 0 TO producer.value DO
+            END_FOR
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(diagnostics, vec![]);
+}
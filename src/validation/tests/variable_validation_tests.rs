@@ -173,3 +173,188 @@ fn constant_fb_instances_are_illegal() {
         ]
     );
 }
+
+#[test]
+fn circular_constant_dependencies_are_reported() {
+    // GIVEN two constants initialized with each other
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL CONSTANT
+            a : INT := b;
+            b : INT := a;
+        END_VAR
+      ",
+    );
+
+    // THEN both constants report the cycle
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unresolved_constant(
+                "a",
+                Some("Circular dependency: a -> b -> a"),
+                (41..42).into()
+            ),
+            Diagnostic::unresolved_constant(
+                "b",
+                Some("Circular dependency: b -> a -> b"),
+                (67..68).into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn circular_type_dependencies_are_reported() {
+    // GIVEN aliases referring to each other and structs containing each other
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        TYPE A : B; END_TYPE
+        TYPE B : A; END_TYPE
+        TYPE S1 : STRUCT x : S2; END_STRUCT END_TYPE
+        TYPE S2 : STRUCT y : S1; z : REF_TO S2; END_STRUCT END_TYPE
+        TYPE C : S1; END_TYPE
+      ",
+    );
+
+    // THEN every type in a cycle is reported, references do not form a cycle
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::circular_type_dependency(&["A", "B", "A"], (14..20).into()),
+            Diagnostic::circular_type_dependency(&["B", "A", "B"], (43..49).into()),
+            Diagnostic::circular_type_dependency(&["S1", "S2", "S1"], (72..102).into()),
+            Diagnostic::circular_type_dependency(&["S2", "S1", "S2"], (125..170).into()),
+        ]
+    );
+}
+
+#[test]
+fn variable_length_arrays_can_only_be_declared_as_function_parameters() {
+    // GIVEN variable-length arrays declared as parameters of a function and elsewhere
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL
+            g : ARRAY[*] OF INT;
+        END_VAR
+
+        FUNCTION foo : INT
+            VAR_INPUT
+                a : ARRAY[*] OF INT;
+            END_VAR
+            VAR_IN_OUT
+                b : ARRAY[*, *] OF INT;
+            END_VAR
+            VAR
+                c : ARRAY[*] OF INT;
+            END_VAR
+        END_FUNCTION
+
+        FUNCTION_BLOCK fb
+            VAR_INPUT
+                d : ARRAY[*] OF INT;
+            END_VAR
+        END_FUNCTION_BLOCK
+      ",
+    );
+
+    // THEN only the function's inputs and in-outs are accepted
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_vla_declaration((32..33).into()),
+            Diagnostic::invalid_vla_declaration((291..292).into()),
+            Diagnostic::invalid_vla_declaration((418..419).into()),
+        ]
+    );
+}
+
+#[test]
+fn located_global_variables_are_validated() {
+    // GIVEN global variables located in the process images
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL
+            start AT %IX1.0 : BOOL;
+            speed AT %QW2 : INT;
+            level AT %QB1 : INT;
+            mode AT %QB3 : BYTE;
+            limit AT %MD0 : DINT := 5;
+            fault AT %IX1.9 : BOOL;
+        END_VAR
+      ",
+    );
+
+    // THEN mismatching types, initial values, overlaps and invalid bits are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::overlapping_hardware_address("speed", "mode", (74..81).into()),
+            Diagnostic::hardware_address_size_mismatch("level", "INT", 8, (107..114).into()),
+            Diagnostic::overlapping_hardware_address("mode", "speed", (139..146).into()),
+            Diagnostic::located_variable_initializer("limit", (173..180).into()),
+            Diagnostic::invalid_hardware_address("fault", (212..221).into()),
+        ]
+    );
+}
+
+#[test]
+fn var_config_entries_are_validated() {
+    // GIVEN a function block with template addresses configured per instance
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        FUNCTION_BLOCK valve
+        VAR
+            open AT %Q* : BOOL;
+            position AT %I* : INT;
+        END_VAR
+        END_FUNCTION_BLOCK
+
+        PROGRAM main
+        VAR
+            valves : ARRAY[1..2] OF valve;
+            count : INT;
+        END_VAR
+        END_PROGRAM
+
+        VAR_CONFIG
+            main.valves[1].open AT %QX0.0 : BOOL;
+            main.valves[2].open AT %IX0.1 : BOOL;
+            main.valves[1].position AT %IW2 : DINT;
+            main.count AT %MW0 : INT;
+            main.pump.open AT %QX0.2 : BOOL;
+        END_VAR
+      ",
+    );
+
+    // THEN unmapped templates and invalid configurations are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unmapped_template(
+                "valve.position",
+                "main.valves[2].position",
+                (95..101).into()
+            ),
+            Diagnostic::config_variable_mismatch(
+                "main.valves[2].open",
+                "Output address",
+                "Input address",
+                (372..391).into()
+            ),
+            Diagnostic::config_variable_mismatch(
+                "main.valves[1].position",
+                "type 'INT'",
+                "type 'DINT'",
+                (422..445).into()
+            ),
+            Diagnostic::config_variable_without_template("main.count", (474..484).into()),
+            Diagnostic::unknown_config_variable("main.pump.open", (512..526).into()),
+        ]
+    );
+}
@@ -0,0 +1,83 @@
+use std::collections::HashSet;
+
+use super::access_visitor::{visit_all_accesses, Access};
+use crate::{
+    ast::{AstStatement, CompilationUnit, LinkageType, VariableBlockType},
+    resolver::{AnnotationMap, AnnotationMapImpl, StatementAnnotation},
+    Diagnostic,
+};
+
+/// collects reads and writes of variables across all compilation units to
+/// find variables that are written but never read (dead stores)
+///
+/// since a variable may be read in a different compilation unit than the one
+/// that declares it, all units need to be visited before asking for diagnostics
+#[derive(Default)]
+pub struct UsageValidator {
+    reads: HashSet<String>,
+    writes: HashSet<String>,
+}
+
+impl UsageValidator {
+    pub fn new() -> UsageValidator {
+        UsageValidator::default()
+    }
+
+    /// records all variable accesses in the given unit's implementations
+    pub fn visit_unit(&mut self, annotations: &AnnotationMapImpl, unit: &CompilationUnit) {
+        for implementation in &unit.implementations {
+            visit_all_accesses(&implementation.statements, &mut |statement, access| {
+                self.record(annotations, statement, access)
+            });
+        }
+    }
+
+    /// returns a warning for every local, temp or output variable declared in the given unit
+    /// that is written but never read. Variables located at a direct address (`AT %Q...`)
+    /// are written for their side-effect and are therefore ignored.
+    pub fn diagnostics_for(&self, unit: &CompilationUnit) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for pou in unit
+            .units
+            .iter()
+            .filter(|it| it.linkage != LinkageType::External)
+        {
+            for block in pou.variable_blocks.iter().filter(|it| {
+                matches!(
+                    it.variable_block_type,
+                    VariableBlockType::Local | VariableBlockType::Temp | VariableBlockType::Output
+                )
+            }) {
+                for variable in block.variables.iter().filter(|it| it.address.is_none()) {
+                    let qualified_name = format!("{}.{}", pou.name, variable.name).to_lowercase();
+                    if self.writes.contains(&qualified_name)
+                        && !self.reads.contains(&qualified_name)
+                    {
+                        diagnostics.push(Diagnostic::write_only_variable(
+                            format!("{}.{}", pou.name, variable.name).as_str(),
+                            variable.location.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+        diagnostics
+    }
+
+    fn record(
+        &mut self,
+        annotations: &AnnotationMapImpl,
+        statement: &AstStatement,
+        access: Access,
+    ) {
+        if let Some(StatementAnnotation::Variable { qualified_name, .. }) =
+            annotations.get(statement)
+        {
+            let qualified_name = qualified_name.to_lowercase();
+            match access {
+                Access::Read | Access::Call => self.reads.insert(qualified_name),
+                Access::Write => self.writes.insert(qualified_name),
+            };
+        }
+    }
+}
@@ -1,14 +1,19 @@
+use std::collections::{HashMap, VecDeque};
+
 use crate::{
     ast::{
-        AstStatement, DataType, DataTypeDeclaration, PouType, SourceRange, Variable, VariableBlock,
-        VariableBlockType,
+        AstStatement, ConfigVariable, DataType, DataTypeDeclaration, DirectAccessType, PouType,
+        SourceRange, Variable, VariableBlock, VariableBlockType,
+    },
+    index::{
+        const_expressions::ConstExpression, HardwareBinding, Index, PouIndexEntry,
+        VariableIndexEntry, VariableType,
     },
-    index::{const_expressions::ConstExpression, Index},
     typesystem::{DataTypeInformation, StructSource},
     Diagnostic,
 };
 
-use super::ValidationContext;
+use super::{stmt_validator::validate_literal_range, ValidationContext};
 
 /// validates variables & datatypes
 
@@ -75,6 +80,39 @@ impl VariableValidator {
                 _ => {}
             }
 
+            //variable-length arrays are passed to functions, they cannot be instantiated
+            let is_vla = context
+                .index
+                .find_effective_type_info(v_entry.get_type_name())
+                .map_or(false, DataTypeInformation::is_vla);
+            let is_function_parameter = matches!(
+                v_entry.get_variable_type(),
+                VariableType::Input | VariableType::InOut
+            ) && context
+                .qualifier
+                .and_then(|it| context.index.find_pou(it))
+                .map_or(false, |it| matches!(it, PouIndexEntry::Function { .. }));
+            if is_vla && !is_function_parameter {
+                self.diagnostics.push(Diagnostic::invalid_vla_declaration(
+                    variable.location.clone(),
+                ));
+            }
+
+            //abstract classes cannot be instantiated, they can only be referenced
+            if let Some(class) = context
+                .index
+                .find_pou(v_entry.get_type_name())
+                .filter(|it| matches!(it, PouIndexEntry::Class { .. }) && it.is_abstract())
+            {
+                if !v_entry.get_declaration_type().is_by_ref() {
+                    self.diagnostics.push(Diagnostic::abstract_class_instance(
+                        v_entry.get_name(),
+                        class.get_name(),
+                        variable.location.clone(),
+                    ));
+                }
+            }
+
             //check if we declared a constant fb-instance or class-instance
             if v_entry.is_constant()
                 && data_type_is_fb_or_class_instance(v_entry.get_type_name(), context.index)
@@ -84,11 +122,212 @@ impl VariableValidator {
                     variable.location.clone(),
                 ));
             }
+
+            if let Some(binding) = v_entry.get_hardware_binding().filter(|it| it.is_located()) {
+                self.validate_hardware_address(v_entry, binding, context);
+            }
+
+            //the initial value would be truncated when stored (e.g. `b : BYTE := 300`)
+            if let Some(initializer @ AstStatement::LiteralInteger { value, .. }) =
+                variable.initializer.as_ref()
+            {
+                if let Some(variable_type) = context
+                    .index
+                    .find_effective_type_info(v_entry.get_type_name())
+                {
+                    self.diagnostics.extend(validate_literal_range(
+                        *value,
+                        variable_type,
+                        initializer,
+                    ));
+                }
+            }
+
+            //every instance of a function block's template has to be configured in VAR_CONFIG
+            if let Some(binding) = v_entry
+                .get_hardware_binding()
+                .filter(|_| context.index.is_function_block_template(v_entry))
+            {
+                for (instance, _) in context
+                    .index
+                    .find_unbound_templates()
+                    .iter()
+                    .filter(|(_, it)| it.get_qualified_name() == v_entry.get_qualified_name())
+                {
+                    self.diagnostics.push(Diagnostic::unmapped_template(
+                        v_entry.get_qualified_name(),
+                        instance,
+                        binding.location.clone(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// validates the address of a variable located in a process image (e.g. `AT %QW4`): the
+    /// variable's type has to fit the address and the address must not overlap other addresses
+    fn validate_hardware_address(
+        &mut self,
+        variable: &VariableIndexEntry,
+        binding: &HardwareBinding,
+        context: &ValidationContext,
+    ) {
+        let location = binding.location.clone();
+        let range = match binding.get_image_range(context.index) {
+            Some(range) => range,
+            None => {
+                self.diagnostics.push(Diagnostic::invalid_hardware_address(
+                    variable.get_name(),
+                    location,
+                ));
+                return;
+            }
+        };
+
+        if !fits_address(variable.get_type_name(), binding.access, context.index) {
+            self.diagnostics
+                .push(Diagnostic::hardware_address_size_mismatch(
+                    variable.get_name(),
+                    variable.get_type_name(),
+                    binding.access.get_bit_width(),
+                    location.clone(),
+                ));
+        }
+
+        //global variables are stored in the zero-initialized process image
+        if context.qualifier.is_none() && variable.initial_value.is_some() {
+            self.diagnostics
+                .push(Diagnostic::located_variable_initializer(
+                    variable.get_name(),
+                    location.clone(),
+                ));
+        }
+
+        if let Some((other, ..)) = context.index.find_located_variables().into_iter().find(
+            |(other, other_binding, other_range)| {
+                other_binding.direction == binding.direction
+                    && other_range.start < range.end
+                    && range.start < other_range.end
+                    && !other
+                        .get_qualified_name()
+                        .eq_ignore_ascii_case(variable.get_qualified_name())
+            },
+        ) {
+            self.diagnostics
+                .push(Diagnostic::overlapping_hardware_address(
+                    variable.get_qualified_name(),
+                    other.get_qualified_name(),
+                    location,
+                ));
+        }
+    }
+
+    /// validates an entry of a `VAR_CONFIG` block: it has to configure the template address of a
+    /// function block's variable with an address and a type matching the variable's declaration
+    pub fn validate_config_variable(
+        &mut self,
+        config_variable: &ConfigVariable,
+        context: &ValidationContext,
+    ) {
+        let reference = config_variable.reference.as_str();
+        let location = config_variable.location.clone();
+        let variable = match context.index.find_instance_variable(reference) {
+            Some(variable) => variable,
+            None => {
+                self.diagnostics
+                    .push(Diagnostic::unknown_config_variable(reference, location));
+                return;
+            }
+        };
+        let template = match variable
+            .get_hardware_binding()
+            .filter(|_| context.index.is_function_block_template(variable))
+        {
+            Some(template) => template,
+            None => {
+                self.diagnostics
+                    .push(Diagnostic::config_variable_without_template(
+                        reference, location,
+                    ));
+                return;
+            }
+        };
+
+        let binding = match context
+            .index
+            .find_config_variable(reference)
+            .map(|it| &it.binding)
+            .filter(|it| it.get_image_range(context.index).is_some())
+        {
+            Some(binding) => binding,
+            None => {
+                self.diagnostics
+                    .push(Diagnostic::invalid_hardware_address(reference, location));
+                return;
+            }
+        };
+        if binding.direction != template.direction {
+            self.diagnostics.push(Diagnostic::config_variable_mismatch(
+                reference,
+                format!("{:?} address", template.direction).as_str(),
+                format!("{:?} address", binding.direction).as_str(),
+                location.clone(),
+            ));
+        }
+
+        let type_name = config_variable.data_type.get_name().unwrap_or_default();
+        let is_same_type = context
+            .index
+            .find_effective_type_by_name(type_name)
+            .zip(
+                context
+                    .index
+                    .find_effective_type_by_name(variable.get_type_name()),
+            )
+            .map_or(false, |(configured, declared)| {
+                configured
+                    .get_name()
+                    .eq_ignore_ascii_case(declared.get_name())
+            });
+        if !is_same_type {
+            self.diagnostics.push(Diagnostic::config_variable_mismatch(
+                reference,
+                format!("type '{}'", variable.get_type_name()).as_str(),
+                format!("type '{}'", type_name).as_str(),
+                location,
+            ));
+        } else if !fits_address(variable.get_type_name(), binding.access, context.index) {
+            self.diagnostics
+                .push(Diagnostic::hardware_address_size_mismatch(
+                    reference,
+                    variable.get_type_name(),
+                    binding.access.get_bit_width(),
+                    location,
+                ));
         }
     }
 
     pub fn validate_data_type_declaration(&self, _declaration: &DataTypeDeclaration) {}
 
+    /// reports user types containing themselves, e.g. aliases referring to each other or structs
+    /// containing each other by value
+    pub fn validate_type_cycle(
+        &mut self,
+        declaration: &DataType,
+        location: &SourceRange,
+        context: &ValidationContext,
+    ) {
+        if let Some(cycle) = declaration
+            .get_name()
+            .and_then(|it| find_type_cycle(it, context.index))
+        {
+            self.diagnostics.push(Diagnostic::circular_type_dependency(
+                &cycle,
+                location.clone(),
+            ));
+        }
+    }
+
     pub fn validate_data_type(&mut self, declaration: &DataType, location: &SourceRange) {
         match declaration {
             DataType::StructType { variables, .. } => {
@@ -109,6 +348,61 @@ impl VariableValidator {
     }
 }
 
+/// returns the path leading from the given type back to itself if the type contains itself
+fn find_type_cycle<'idx>(type_name: &str, index: &'idx Index) -> Option<Vec<&'idx str>> {
+    let start = index.get_type(type_name).ok()?.get_name();
+    let mut predecessors: HashMap<String, &str> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        for contained in get_contained_types(current, index) {
+            let contained = match index.get_type(contained) {
+                Ok(it) => it.get_name(),
+                Err(_) => continue,
+            };
+            if contained.eq_ignore_ascii_case(start) {
+                let mut cycle = vec![start];
+                let mut step = current;
+                while !step.eq_ignore_ascii_case(start) {
+                    cycle.push(step);
+                    step = predecessors[&step.to_lowercase()];
+                }
+                cycle.push(start);
+                cycle.reverse();
+                return Some(cycle);
+            }
+            if !predecessors.contains_key(&contained.to_lowercase()) {
+                predecessors.insert(contained.to_lowercase(), current);
+                queue.push_back(contained);
+            }
+        }
+    }
+    None
+}
+
+/// returns the types stored inside a value of the given type, pointers do not store their target
+fn get_contained_types<'idx>(type_name: &str, index: &'idx Index) -> Vec<&'idx str> {
+    match index
+        .get_type(type_name)
+        .map(crate::typesystem::DataType::get_type_information)
+    {
+        Ok(DataTypeInformation::Alias {
+            referenced_type, ..
+        })
+        | Ok(DataTypeInformation::SubRange {
+            referenced_type, ..
+        }) => vec![referenced_type.as_str()],
+        Ok(DataTypeInformation::Array {
+            inner_type_name, ..
+        }) => vec![inner_type_name.as_str()],
+        Ok(DataTypeInformation::Struct { name, .. }) => index
+            .get_container_members(name)
+            .into_iter()
+            .map(|it| it.get_type_name())
+            .collect(),
+        _ => vec![],
+    }
+}
+
 /// returns whether this data_type is a function block, a class or an array/pointer of/to these
 fn data_type_is_fb_or_class_instance(type_name: &str, index: &Index) -> bool {
     let data_type = index.find_effective_type(type_name).map_or_else(
@@ -151,6 +445,21 @@ fn data_type_is_fb_or_class_instance(type_name: &str, index: &Index) -> bool {
     }
 }
 
+/// returns true if a variable of the given type can be located at an address of the given size
+fn fits_address(type_name: &str, access: DirectAccessType, index: &Index) -> bool {
+    index
+        .find_effective_type_info(type_name)
+        .map_or(false, |it| match access {
+            DirectAccessType::Bit => it.is_bool(),
+            access => {
+                matches!(
+                    it,
+                    DataTypeInformation::Integer { .. } | DataTypeInformation::Float { .. }
+                ) && it.get_size() as u64 == access.get_bit_width()
+            }
+        })
+}
+
 #[cfg(test)]
 mod variable_validator_tests {
     use crate::test_utils::tests::parse_and_validate;
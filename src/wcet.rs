@@ -0,0 +1,93 @@
+//! exports the bounds of the loops annotated with `{attribute 'loop_bound' := 'n'}` in AIS, the
+//! annotation language of aiT and other worst-case execution time (WCET) analyzers
+//!
+//! a loop is addressed by the function of its POU and its position among the function's loops in
+//! the order of their declaration (e.g. `loop "main" + 2 loops max 10;`). The analyzers count the
+//! loops of the machine code, so the positions only match if the optimizer does not remove,
+//! unroll or reorder loops (e.g. when compiling with `-Onone`).
+use crate::ast::{get_nested_bodies, AstId, AstStatement, CompilationUnit};
+
+/// generates the AIS annotations of the bounded loops implemented by the given units
+pub fn generate_ais_annotations(units: &[CompilationUnit]) -> String {
+    let mut annotations = format!("# generated by rusty {}\n", env!("CARGO_PKG_VERSION"));
+    for implementation in units
+        .iter()
+        .flat_map(|it| it.implementations.iter())
+        .filter(|it| !it.generic)
+    {
+        let mut loops = vec![];
+        collect_loops(&implementation.statements, &mut loops);
+        for (position, id) in loops.iter().enumerate() {
+            if let Some(bound) = implementation.loop_bounds.get(id) {
+                annotations.push_str(&format!(
+                    "loop \"{}\" + {} {} max {};\n",
+                    implementation.name,
+                    position + 1,
+                    if position == 0 { "loop" } else { "loops" },
+                    bound
+                ));
+            }
+        }
+    }
+    annotations
+}
+
+/// collects the ids of the given statements' loops (including the nested ones) in the order of
+/// their declaration
+fn collect_loops(statements: &[AstStatement], loops: &mut Vec<AstId>) {
+    for statement in statements {
+        if matches!(
+            statement,
+            AstStatement::ForLoopStatement { .. }
+                | AstStatement::WhileLoopStatement { .. }
+                | AstStatement::RepeatLoopStatement { .. }
+        ) {
+            loops.push(statement.get_id());
+        }
+        for body in get_nested_bodies(statement) {
+            collect_loops(body, loops);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate_ais_annotations;
+    use crate::test_utils::tests::parse;
+
+    #[test]
+    fn loops_are_addressed_by_their_position_in_the_function() {
+        let (unit, diagnostics) = parse(
+            "
+            PROGRAM main
+            VAR i, j : INT; END_VAR
+                {attribute 'loop_bound' := '10'}
+                FOR i := 1 TO 10 DO
+                    WHILE j > 0 DO
+                        j := j - 1;
+                    END_WHILE
+                END_FOR
+                IF i > 0 THEN
+                    {attribute 'loop_bound' := '3'}
+                    REPEAT
+                        i := i - 1;
+                    UNTIL i = 0
+                    END_REPEAT
+                END_IF
+            END_PROGRAM
+            ",
+        );
+        assert_eq!(diagnostics, vec![]);
+
+        assert_eq!(
+            generate_ais_annotations(&[unit]),
+            format!(
+                r#"# generated by rusty {}
+loop "main" + 1 loop max 10;
+loop "main" + 3 loops max 3;
+"#,
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+}
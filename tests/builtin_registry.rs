@@ -0,0 +1,119 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+//the builtin registry is shared by all compilations of a process, so the builtins registered by
+//these tests are kept out of the other tests' binary
+use inkwell::{
+    context::Context,
+    targets::{InitializationConfig, Target},
+    values::BasicValue,
+};
+use rusty::{
+    builtins, compile_module,
+    diagnostics::{Diagnostic, Diagnostician},
+    runner::run_no_param,
+    SourceCode,
+};
+
+extern "C" fn acme_scale(val: i32) -> i32 {
+    val * 3
+}
+
+#[test]
+fn registered_builtins_are_generated_in_place_or_called_externally() {
+    builtins::register_builtin(
+        "acme",
+        "ACME_ANSWER",
+        "FUNCTION ACME_ANSWER : DINT END_FUNCTION",
+        |generator, _, _| {
+            Ok(generator
+                .llvm
+                .context
+                .i32_type()
+                .const_int(42, false)
+                .as_basic_value_enum())
+        },
+    )
+    .unwrap();
+    builtins::register_extern_builtin(
+        "acme",
+        "acme_scale",
+        "FUNCTION ACME_SCALE : DINT
+        VAR_INPUT
+            val : DINT;
+        END_VAR
+        END_FUNCTION",
+    )
+    .unwrap();
+    assert_eq!(
+        builtins::get_builtin_names("acme"),
+        vec!["ACME_ANSWER".to_string(), "ACME_SCALE".to_string()]
+    );
+    assert!(builtins::get_builtin_names(builtins::STANDARD_PROVIDER).contains(&"SEL".into()));
+
+    let prog = "
+    FUNCTION main : DINT
+        main := ACME_SCALE(ACME_ANSWER());
+    END_FUNCTION
+    ";
+
+    Target::initialize_native(&InitializationConfig::default()).unwrap();
+    let context: Context = Context::create();
+    let source = SourceCode {
+        path: "builtins.st".to_string(),
+        source: prog.to_string(),
+    };
+    let (index, code_gen) = compile_module(
+        &context,
+        vec![source],
+        vec![],
+        None,
+        Diagnostician::default(),
+    )
+    .unwrap();
+    assert!(index.get_builtin_function("acme_answer").is_some());
+    assert!(index.get_builtin_function("ACME_SCALE").is_none());
+
+    let exec_engine = code_gen
+        .module
+        .create_jit_execution_engine(inkwell::OptimizationLevel::None)
+        .unwrap();
+    let fn_value = code_gen.module.get_function("ACME_SCALE").unwrap();
+    exec_engine.add_global_mapping(&fn_value, acme_scale as usize);
+
+    let res: i32 = run_no_param(&exec_engine, "main");
+    assert_eq!(res, 126)
+}
+
+#[test]
+fn conflicting_and_invalid_builtins_are_rejected() {
+    assert_eq!(
+        builtins::register_extern_builtin("acme", "sel", "FUNCTION SEL : INT END_FUNCTION"),
+        Err(Diagnostic::duplicate_builtin("sel", "std"))
+    );
+    assert_eq!(
+        builtins::register_extern_builtin(
+            "acme",
+            "ACME_RESET",
+            "FUNCTION ACME_REBOOT : INT END_FUNCTION"
+        ),
+        Err(Diagnostic::invalid_builtin_declaration(
+            "ACME_RESET",
+            "it does not declare the POU 'ACME_RESET' only"
+        ))
+    );
+    assert_eq!(
+        builtins::register_builtin(
+            "acme",
+            "ACME_TIMER",
+            "FUNCTION_BLOCK ACME_TIMER END_FUNCTION_BLOCK",
+            |generator, _, _| Ok(generator.llvm.context.bool_type().const_zero().into()),
+        ),
+        Err(Diagnostic::invalid_builtin_declaration(
+            "ACME_TIMER",
+            "builtins generated by a callback have to be functions"
+        ))
+    );
+    assert!(builtins::get_builtin_names("acme")
+        .iter()
+        .all(|it| it != "ACME_RESET" && it != "ACME_TIMER"));
+}
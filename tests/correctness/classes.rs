@@ -54,3 +54,259 @@ fn class_reference_in_pou() {
     let _: i32 = compile_and_run(source, &mut m);
     assert_eq!(m.x, 10);
 }
+
+#[test]
+fn extending_class_inherits_members_and_methods() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Motor {
+        vtable: usize,
+        speed: i16,
+        rpm: i32,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        m: Motor,
+        rpm: i32,
+        doubled: i16,
+        stopped: bool,
+    }
+
+    let source = "
+        CLASS Drive
+            VAR
+                speed : INT;
+            END_VAR
+
+            METHOD accelerate
+                VAR_INPUT delta : INT; END_VAR
+                speed := speed + delta;
+            END_METHOD
+
+            METHOD stop : BOOL
+                speed := 0;
+                stop := TRUE;
+            END_METHOD
+        END_CLASS
+
+        CLASS Motor EXTENDS Drive
+            VAR
+                rpm : DINT;
+            END_VAR
+
+            METHOD update
+                rpm := speed * 10;
+            END_METHOD
+        END_CLASS
+
+        FUNCTION double_speed : INT
+            VAR_IN_OUT drive : Drive; END_VAR
+            drive.speed := drive.speed * 2;
+            double_speed := drive.speed;
+        END_FUNCTION
+
+        PROGRAM main
+        VAR
+            m : Motor;
+            rpm : DINT;
+            doubled : INT;
+            stopped : BOOL;
+        END_VAR
+            m.accelerate(delta := 5);
+            m.accelerate(4);
+            m.update();
+            rpm := m.rpm;
+            doubled := double_speed(m);
+            stopped := m.stop();
+        END_PROGRAM
+        ";
+
+    let mut main = MainType {
+        m: Motor {
+            vtable: 0,
+            speed: 0,
+            rpm: 0,
+        },
+        rpm: 0,
+        doubled: 0,
+        stopped: false,
+    };
+    let _: i32 = compile_and_run(source, &mut main);
+    assert_eq!(main.rpm, 90);
+    assert_eq!(main.doubled, 18);
+    assert!(main.stopped);
+    assert_eq!(main.m.speed, 0);
+}
+
+#[test]
+fn overridden_methods_are_called_through_a_super_class() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        square_area: i32,
+        shape_area: i32,
+        direct_area: i32,
+    }
+
+    let source = "
+        CLASS Shape
+            METHOD area : DINT
+                area := -1;
+            END_METHOD
+        END_CLASS
+
+        CLASS Square EXTENDS Shape
+            VAR
+                side : DINT;
+            END_VAR
+
+            METHOD OVERRIDE area : DINT
+                area := side * side;
+            END_METHOD
+        END_CLASS
+
+        FUNCTION area_of : DINT
+            VAR_IN_OUT shape : Shape; END_VAR
+            area_of := shape.area();
+        END_FUNCTION
+
+        VAR_GLOBAL
+            square : Square;
+            shape : Shape;
+        END_VAR
+
+        PROGRAM main
+        VAR
+            square_area : DINT;
+            shape_area : DINT;
+            direct_area : DINT;
+        END_VAR
+            square.side := 3;
+            square_area := area_of(square);
+            shape_area := area_of(shape);
+            direct_area := square.area();
+        END_PROGRAM
+        ";
+
+    let mut main = MainType {
+        square_area: 0,
+        shape_area: 0,
+        direct_area: 0,
+    };
+    let _: i32 = compile_and_run(source, &mut main);
+    assert_eq!(main.square_area, 9);
+    assert_eq!(main.shape_area, -1);
+    assert_eq!(main.direct_area, 9);
+}
+
+#[test]
+fn super_calls_the_implementation_of_the_super_class() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        area: i32,
+    }
+
+    let source = "
+        CLASS Shape
+            VAR
+                side : DINT;
+            END_VAR
+
+            METHOD area : DINT
+                area := side * side;
+            END_METHOD
+        END_CLASS
+
+        CLASS Cube EXTENDS Shape
+            METHOD OVERRIDE area : DINT
+                area := SUPER^.area() * 6;
+            END_METHOD
+        END_CLASS
+
+        FUNCTION area_of : DINT
+            VAR_IN_OUT shape : Shape; END_VAR
+            area_of := shape.area();
+        END_FUNCTION
+
+        VAR_GLOBAL
+            cube : Cube;
+        END_VAR
+
+        PROGRAM main
+        VAR
+            area : DINT;
+        END_VAR
+            cube.side := 2;
+            area := area_of(cube);
+        END_PROGRAM
+        ";
+
+    let mut main = MainType { area: 0 };
+    let _: i32 = compile_and_run(source, &mut main);
+    assert_eq!(main.area, 24);
+}
+
+#[test]
+fn abstract_methods_are_called_on_the_implementing_class() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        square_area: i32,
+        circle_area: i32,
+    }
+
+    let source = "
+        CLASS ABSTRACT Shape
+            VAR
+                size : DINT;
+            END_VAR
+
+            METHOD ABSTRACT area : DINT
+            END_METHOD
+        END_CLASS
+
+        CLASS Square EXTENDS Shape
+            METHOD OVERRIDE area : DINT
+                area := size * size;
+            END_METHOD
+        END_CLASS
+
+        CLASS Circle EXTENDS Shape
+            METHOD OVERRIDE area : DINT
+                area := 3 * size * size;
+            END_METHOD
+        END_CLASS
+
+        FUNCTION area_of : DINT
+            VAR_IN_OUT shape : Shape; END_VAR
+            area_of := shape.area();
+        END_FUNCTION
+
+        VAR_GLOBAL
+            square : Square;
+            circle : Circle;
+        END_VAR
+
+        PROGRAM main
+        VAR
+            square_area : DINT;
+            circle_area : DINT;
+        END_VAR
+            square.size := 3;
+            circle.size := 2;
+            square_area := area_of(square);
+            circle_area := area_of(circle);
+        END_PROGRAM
+        ";
+
+    let mut main = MainType {
+        square_area: 0,
+        circle_area: 0,
+    };
+    let _: i32 = compile_and_run(source, &mut main);
+    assert_eq!(main.square_area, 9);
+    assert_eq!(main.circle_area, 12);
+}
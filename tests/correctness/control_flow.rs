@@ -738,3 +738,338 @@ fn case_statement() {
     let _: i32 = compile_and_run(function.to_string(), p);
     assert_eq!(p.ret, 7);
 }
+
+#[test]
+fn jumps_are_taken_in_the_codesys_dialect() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        i: i16,
+        ret: i16,
+    }
+    let function = r#"
+    PROGRAM main
+    VAR_INPUT
+        i : INT;
+        ret : INT;
+    END_VAR
+    ret := 0;
+    next:
+        ret := ret + 1;
+        IF ret < i THEN
+            JMP next;
+        END_IF
+        IF ret > 100 THEN
+            JMP done;
+        END_IF
+        ret := ret * 2;
+    done:
+    END_PROGRAM
+    "#;
+
+    let context = Context::create();
+    let (_, code_gen) = compile_module_with_tasks(
+        &context,
+        vec![SourceCode::from(function)],
+        vec![],
+        None,
+        Diagnostician::null_diagnostician(),
+        None,
+        dialect::Dialect::Codesys,
+    )
+    .unwrap();
+    let engine = code_gen
+        .module
+        .create_jit_execution_engine(inkwell::OptimizationLevel::None)
+        .unwrap();
+
+    let p = &mut MainType { i: 5, ret: 0 };
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!(p.ret, 10);
+
+    let p = &mut MainType { i: 200, ret: 0 };
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!(p.ret, 200);
+}
+
+#[test]
+fn instruction_lists_are_compiled_with_structured_text() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        i: i16,
+        ret: i16,
+    }
+    let function = r#"
+    FUNCTION double : INT
+    VAR_INPUT
+        x : INT;
+    END_VAR
+        double := x * 2;
+    END_FUNCTION
+
+    PROGRAM main
+    VAR_INPUT
+        i : INT;
+        ret : INT;
+    END_VAR
+        LD 0
+        ST ret
+    next:
+        LD ret
+        ADD 1
+        ST ret
+        LT i
+        JMPC next
+        LD ret
+        GT 100
+        RETC
+        LD ret
+        double
+        ST ret
+    END_PROGRAM
+    "#;
+
+    let p = &mut MainType { i: 5, ret: 0 };
+    let _: i32 = compile_and_run(function.to_string(), p);
+    assert_eq!(p.ret, 10);
+
+    let p = &mut MainType { i: 200, ret: 0 };
+    let _: i32 = compile_and_run(function.to_string(), p);
+    assert_eq!(p.ret, 200);
+}
+
+#[test]
+fn case_statement_over_strings() {
+    #[repr(C)]
+    struct MainType {
+        cmd: [u8; 11],
+        wcmd: [u16; 11],
+        ret: i16,
+        wret: i16,
+    }
+
+    fn new_main(cmd: &str) -> MainType {
+        let mut main = MainType {
+            cmd: [0; 11],
+            wcmd: [0; 11],
+            ret: 0,
+            wret: 0,
+        };
+        main.cmd[..cmd.len()].copy_from_slice(cmd.as_bytes());
+        for (i, c) in cmd.encode_utf16().enumerate() {
+            main.wcmd[i] = c;
+        }
+        main
+    }
+
+    let function = r#"
+    VAR_GLOBAL CONSTANT
+        STOP_CMD : STRING := 'stop';
+    END_VAR
+    PROGRAM main
+    VAR_INPUT
+        cmd : STRING[10];
+        wcmd : WSTRING[10];
+        ret : INT;
+        wret : INT;
+    END_VAR
+    CASE cmd OF
+        'start', 'go': ret := 1;
+        STOP_CMD, 'halt': ret := 2;
+        'st': ret := 3;
+        '': ret := 4;
+        'far too long for cmd': ret := 5;
+        ELSE ret := 9;
+    END_CASE
+    CASE wcmd OF
+        "start": wret := 1;
+        "stop": wret := 2;
+        ELSE wret := 9;
+    END_CASE
+    END_PROGRAM
+    "#;
+
+    let context = Context::create();
+    let (_, code_gen) = compile_module_with_tasks(
+        &context,
+        vec![SourceCode::from(function)],
+        vec![],
+        None,
+        Diagnostician::null_diagnostician(),
+        None,
+        dialect::Dialect::default(),
+    )
+    .unwrap();
+    let engine = code_gen
+        .module
+        .create_jit_execution_engine(inkwell::OptimizationLevel::None)
+        .unwrap();
+
+    for (cmd, ret, wret) in [
+        ("start", 1, 1),
+        ("go", 1, 9),
+        ("stop", 2, 2),
+        ("halt", 2, 9),
+        ("st", 3, 9),
+        ("", 4, 9),
+        ("starts", 9, 9),
+        ("s", 9, 9),
+    ] {
+        let p = &mut new_main(cmd);
+        let _: i32 = run(&engine, "main", p);
+        assert_eq!((p.ret, p.wret), (ret, wret), "{}", cmd);
+    }
+}
+
+#[test]
+fn sfc_steps_are_activated_by_their_transitions() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct Step {
+        x: bool,
+        t: i64,
+        entered: bool,
+        start: i64,
+        was_active: bool,
+    }
+
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        start: bool,
+        filled: i16,
+        entered: i16,
+        left: i16,
+        idle: Step,
+        fill: Step,
+    }
+
+    let function = r#"
+    PROGRAM main
+    VAR
+        start : BOOL;
+        filled : INT;
+        entered : INT;
+        left : INT;
+    END_VAR
+        INITIAL_STEP idle :
+        END_STEP
+        STEP fill :
+            pump(N);
+            enter(P1);
+            leave(P0);
+        END_STEP
+        TRANSITION FROM idle TO fill := start;
+        END_TRANSITION
+        TRANSITION FROM fill TO idle := filled >= 3;
+        END_TRANSITION
+        ACTION pump :
+            filled := filled + 1;
+        END_ACTION
+        ACTION enter :
+            entered := entered + 1;
+        END_ACTION
+        ACTION leave :
+            left := left + 1;
+        END_ACTION
+    END_PROGRAM
+    "#;
+
+    let context = Context::create();
+    let engine = compile(&context, function);
+    //the instance is passed by the test, so it carries the initial step's initial value
+    let p = &mut MainType {
+        idle: Step {
+            x: true,
+            entered: true,
+            ..Step::default()
+        },
+        ..MainType::default()
+    };
+
+    let _: i32 = run(&engine, "main", p);
+    assert!(p.idle.x && !p.fill.x);
+
+    // the transition fires, the entered step's actions are executed from the next cycle on
+    p.start = true;
+    let _: i32 = run(&engine, "main", p);
+    assert!(!p.idle.x && p.fill.x);
+    assert_eq!((p.filled, p.entered), (0, 0));
+
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!((p.filled, p.entered, p.fill.t), (1, 1, 0));
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!((p.filled, p.entered), (2, 1));
+    assert!(p.fill.t >= 2_000_000);
+
+    // the third cycle leaves the step, the idle step is left again in the following cycle
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!((p.filled, p.left), (3, 0));
+    assert!(p.idle.x && !p.fill.x);
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!((p.filled, p.entered, p.left), (3, 1, 1));
+    assert!(!p.idle.x && p.fill.x);
+}
+
+#[test]
+fn state_machine_changes_states_by_its_transitions() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        go: bool,
+        ticks: i16,
+        entries: i16,
+        exits: i16,
+        state: i32,
+        entered: bool,
+    }
+
+    let function = r#"
+    PROGRAM main
+    VAR
+        go : BOOL;
+        ticks : INT;
+        entries : INT;
+        exits : INT;
+    END_VAR
+        INITIAL_STATE idle
+        END_STATE
+        STATE busy
+            ENTRY:
+                entries := entries + 1;
+            DURING:
+                ticks := ticks + 1;
+            EXIT:
+                exits := exits + 1;
+        END_STATE
+        TRANSITION FROM idle TO busy := go;
+        END_TRANSITION
+        TRANSITION FROM busy TO idle := ticks >= 2;
+        END_TRANSITION
+    END_PROGRAM
+    "#;
+
+    let context = Context::create();
+    let engine = compile(&context, function);
+    let p = &mut MainType::default();
+
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!(p.state, 0);
+
+    // the transition fires, the entered state's entry action is executed in the next cycle
+    p.go = true;
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!((p.state, p.entries, p.ticks), (1, 0, 0));
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!((p.state, p.entries, p.ticks), (1, 1, 1));
+
+    // the state is left through its exit action
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!((p.state, p.ticks, p.exits), (0, 2, 1));
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!(p.state, 1);
+    let _: i32 = run(&engine, "main", p);
+    assert_eq!((p.state, p.entries, p.ticks, p.exits), (0, 2, 3, 2));
+}
@@ -379,3 +379,48 @@ fn using_arrays() {
         assert_eq!(i as i32, *j);
     }
 }
+
+#[test]
+fn fixed_point_numbers_are_computed_as_scaled_integers() {
+    #[repr(C)]
+    #[derive(Debug, Default)]
+    struct MainType {
+        a: i32,
+        product: i32,
+        quotient: i32,
+        narrow: i16,
+        integer: i32,
+        real: f32,
+        negative: bool,
+    }
+
+    let function = "
+        TYPE q16 : FIXED(16, 16); END_TYPE
+        PROGRAM main
+        VAR
+            a, product, quotient : q16;
+            narrow : FIXED(8, 8);
+            integer : DINT;
+            real : REAL;
+            negative : BOOL;
+        END_VAR
+            a := 1.5;
+            product := a * -2.25;
+            quotient := 3 / a;
+            narrow := product;
+            integer := FIXED_TO_DINT(product);
+            real := FIXED_TO_REAL(quotient + 0.25);
+            negative := product < 0;
+        END_PROGRAM
+    ";
+
+    let mut main = MainType::default();
+    let _: i32 = compile_and_run(function, &mut main);
+    assert_eq!(main.a, 3 << 15);
+    assert_eq!(main.product, -(27 << 13));
+    assert_eq!(main.quotient, 2 << 16);
+    assert_eq!(main.narrow, -(27 << 5));
+    assert_eq!(main.integer, -3);
+    assert_eq!(main.real, 2.25);
+    assert!(main.negative);
+}
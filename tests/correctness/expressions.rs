@@ -222,3 +222,46 @@ fn enums_can_be_compared() {
     let _: i32 = compile_and_run(function, &mut main);
     assert_eq!([true, true, true], [main.a, main.b, main.c]);
 }
+
+#[test]
+fn strings_are_compared_character_by_character() {
+    #[repr(C)]
+    struct Main {
+        results: [bool; 12],
+    }
+
+    let mut main = Main {
+        results: [false; 12],
+    };
+
+    let function = r#"
+            PROGRAM main
+            VAR
+                results : ARRAY[0..11] OF BOOL;
+            END_VAR
+            VAR_TEMP
+                long : STRING[10] := 'abc';
+                short : STRING[3] := 'abc';
+                prefix : STRING[2] := 'ab';
+                wide : WSTRING := "abc";
+            END_VAR
+                results[0] := 'abc' = 'abc';
+                results[1] := long = short;
+                results[2] := long <> short;
+                results[3] := long < 'abd';
+                results[4] := long > prefix;
+                results[5] := prefix < long;
+                results[6] := long <= short;
+                results[7] := long >= 'abcd';
+                results[8] := 'é' > 'z';
+                results[9] := wide = "abc";
+                results[10] := wide < "abd";
+                results[11] := wide > "abcd";
+           END_PROGRAM
+    "#;
+    let _: i32 = compile_and_run(function, &mut main);
+    assert_eq!(
+        [true, true, false, true, true, true, true, false, true, true, true, false],
+        main.results
+    );
+}
@@ -700,3 +700,624 @@ fn by_ref_and_by_val_mixed_function_call() {
     let res: i32 = compile_and_run(function.to_string(), &mut MainType::default());
     assert_eq!(res, 11_110)
 }
+
+#[test]
+fn almost_equal_compares_reals_with_tolerance() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        close: bool,
+        far: bool,
+        mixed: bool,
+    }
+
+    let function = "
+        PROGRAM main
+        VAR
+            close : BOOL;
+            far : BOOL;
+            mixed : BOOL;
+        END_VAR
+        VAR_TEMP
+            r : REAL := 0.1;
+            l : LREAL := 0.3;
+        END_VAR
+            close := ALMOST_EQUAL(r + 0.2, 0.3, 0.0001);
+            far := ALMOST_EQUAL(r, 0.3, 0.0001);
+            mixed := ALMOST_EQUAL(l, r * 3, 0.0001);
+        END_PROGRAM
+    ";
+
+    let mut main = MainType::default();
+    let _: i32 = compile_and_run(function, &mut main);
+    assert!(main.close);
+    assert!(!main.far);
+    assert!(main.mixed);
+}
+
+#[test]
+fn almost_equal_compares_integers_with_reals() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        close: bool,
+        far: bool,
+        negative: bool,
+    }
+
+    let function = "
+        PROGRAM main
+        VAR
+            close : BOOL;
+            far : BOOL;
+            negative : BOOL;
+        END_VAR
+        VAR_TEMP
+            i : INT := 3;
+            n : INT := -3;
+            r : REAL := 3.05;
+        END_VAR
+            close := ALMOST_EQUAL(i, r, 0.1);
+            far := ALMOST_EQUAL(r, i, 0.01);
+            negative := ALMOST_EQUAL(n, -r, 0.1);
+        END_PROGRAM
+    ";
+
+    let mut main = MainType::default();
+    let _: i32 = compile_and_run(function, &mut main);
+    assert!(main.close);
+    assert!(!main.far);
+    assert!(main.negative);
+}
+
+#[test]
+fn sel_and_if_expressions_select_a_value() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        first: i16,
+        second: i32,
+        mixed: f64,
+        nested: i16,
+    }
+
+    let function = "
+        PROGRAM main
+        VAR
+            first : INT;
+            second : DINT;
+            mixed : LREAL;
+            nested : INT;
+        END_VAR
+        VAR_TEMP
+            i : INT := 7;
+            r : REAL := 1.5;
+        END_VAR
+            first := SEL(FALSE, i, 9);
+            second := SEL(i > 5, 1, i * 10000);
+            mixed := IF i = 7 THEN r ELSE i END_IF;
+            nested := IF i < 0 THEN 1 ELSIF i < 5 THEN 2 ELSE 3 END_IF;
+        END_PROGRAM
+    ";
+
+    let mut main = MainType::default();
+    let _: i32 = compile_and_run(function, &mut main);
+    assert_eq!(main.first, 7);
+    assert_eq!(main.second, 70_000);
+    assert_eq!(main.mixed, 1.5);
+    assert_eq!(main.nested, 3);
+}
+
+#[test]
+fn checksums_of_byte_buffers() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        crc16: u16,
+        crc32: u32,
+        sum: u8,
+        parity: u8,
+        partial: u16,
+    }
+
+    let function = "
+        PROGRAM main
+        VAR
+            crc16 : WORD;
+            crc32 : DWORD;
+            sum : BYTE;
+            parity : BYTE;
+            partial : WORD;
+        END_VAR
+        VAR_TEMP
+            digits : STRING[9] := '123456789';
+            frame : ARRAY[0..2] OF BYTE := [16#F0, 16#20, 16#01];
+        END_VAR
+            crc16 := CRC16(ADR(digits), 9);
+            crc32 := CRC32(data := ADR(digits), length := 9);
+            sum := CHECKSUM_SUM(ADR(frame), 3);
+            parity := CHECKSUM_XOR(ADR(frame), 3);
+            partial := CRC16(ADR(frame), 0);
+        END_PROGRAM
+    ";
+
+    let mut main = MainType::default();
+    let _: i32 = compile_and_run(function, &mut main);
+    assert_eq!(main.crc16, 0x4B37);
+    assert_eq!(main.crc32, 0xCBF4_3926);
+    assert_eq!(main.sum, 0x11);
+    assert_eq!(main.parity, 0xD1);
+    assert_eq!(main.partial, 0xFFFF);
+}
+
+#[test]
+fn memory_is_copied_between_strings_byte_arrays_and_structs() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct Header {
+        id: u16,
+        length: u16,
+    }
+
+    #[repr(C)]
+    struct MainType {
+        header: Header,
+        frame: [u8; 6],
+        text: [u8; 81],
+        copied_header: u32,
+        copied_text: u32,
+        copied_frame: u32,
+    }
+
+    let function = "
+        TYPE Header : STRUCT
+            id : UINT;
+            length : UINT;
+        END_STRUCT END_TYPE
+
+        PROGRAM main
+        VAR
+            header : Header;
+            frame : ARRAY[0..5] OF BYTE;
+            text : STRING;
+            copied_header : UDINT;
+            copied_text : UDINT;
+            copied_frame : UDINT;
+        END_VAR
+        VAR_TEMP
+            raw : ARRAY[0..7] OF BYTE := [16#34, 16#12, 16#02, 16#00, 16#FF, 16#FF, 16#FF, 16#FF];
+        END_VAR
+            copied_header := MEM_COPY(header, raw);
+            copied_text := STRING_TO_BYTES('hello world', frame);
+            copied_frame := STRING_TO_BYTES('hi', frame);
+            text := BYTES_TO_STRING(frame, 100);
+        END_PROGRAM
+    ";
+
+    let mut main = MainType {
+        header: Header::default(),
+        frame: [0; 6],
+        text: [0; 81],
+        copied_header: 0,
+        copied_text: 0,
+        copied_frame: 0,
+    };
+    let _: i32 = compile_and_run(function, &mut main);
+    assert_eq!(main.header.id, 0x1234);
+    assert_eq!(main.header.length, 2);
+    assert_eq!(main.copied_header, 4);
+    assert_eq!(main.copied_text, 6);
+    assert_eq!(main.copied_frame, 2);
+    assert_eq!(&main.frame, b"hillo ");
+    assert_eq!(&main.text[..7], b"hillo \0");
+}
+
+#[test]
+fn memory_is_copied_set_and_compared_by_the_memory_functions() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        source: [u8; 4],
+        destination: [u8; 4],
+        copied: u32,
+        set: u32,
+        equal: i32,
+        less: i32,
+        greater: i32,
+    }
+
+    let function = "
+        PROGRAM main
+        VAR
+            source : ARRAY[0..3] OF BYTE;
+            destination : ARRAY[0..3] OF BYTE;
+            copied : UDINT;
+            set : UDINT;
+            equal : DINT;
+            less : DINT;
+            greater : DINT;
+        END_VAR
+            copied := MEMCPY(destination, ADR(source), 4);
+            equal := MEMCMP(source, destination, 4);
+            set := MEMSET(ADR(destination[2]), 16#FF, 2);
+            less := MEMCMP(source, destination, 4);
+            greater := MEMCMP(left := destination, right := source, size := 4);
+        END_PROGRAM
+    ";
+
+    let mut main = MainType {
+        source: [1, 2, 3, 4],
+        ..MainType::default()
+    };
+    let _: i32 = compile_and_run(function, &mut main);
+    assert_eq!(main.copied, 4);
+    assert_eq!(main.set, 2);
+    assert_eq!(main.destination, [1, 2, 0xFF, 0xFF]);
+    assert_eq!(main.equal, 0);
+    assert_eq!(main.less, 3 - 0xFF);
+    assert_eq!(main.greater, 0xFF - 3);
+}
+
+#[test]
+fn array_builtins_compute_element_wise_results_and_dot_products() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        left: [f32; 4],
+        right: [f32; 3],
+        sum: [f32; 4],
+        product: [f32; 4],
+        counts: [i32; 4],
+        added: u32,
+        multiplied: u32,
+        dot: f64,
+        int_dot: f64,
+    }
+
+    let function = "
+        PROGRAM main
+        VAR
+            left : ARRAY[0..3] OF REAL;
+            right : ARRAY[1..3] OF REAL;
+            sum : ARRAY[0..3] OF REAL;
+            product : ARRAY[0..3] OF REAL;
+            counts : ARRAY[0..3] OF DINT;
+            added : UDINT;
+            multiplied : UDINT;
+            dot : LREAL;
+            int_dot : LREAL;
+        END_VAR
+            added := ARRAY_ADD(sum, left, right);
+            multiplied := ARRAY_MUL(destination := product, left := left, right := left);
+            dot := ARRAY_DOT(left, right);
+            int_dot := ARRAY_DOT(counts, counts);
+        END_PROGRAM
+    ";
+
+    let mut main = MainType {
+        left: [1.0, 2.0, 3.0, 4.0],
+        right: [0.5, 0.25, 2.0],
+        counts: [-3, 4, 100_000, 0],
+        ..MainType::default()
+    };
+    let _: i32 = compile_and_run(function, &mut main);
+    assert_eq!(main.added, 3);
+    assert_eq!(main.sum, [1.5, 2.25, 5.0, 0.0]);
+    assert_eq!(main.multiplied, 4);
+    assert_eq!(main.product, [1.0, 4.0, 9.0, 16.0]);
+    assert_eq!(main.dot, 7.0);
+    assert_eq!(main.int_dot, 10_000_000_025.0);
+}
+
+#[test]
+fn matrices_are_multiplied_and_transposed() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        left: [[f64; 3]; 2],
+        right: [[f64; 2]; 3],
+        vector: [f64; 3],
+        product: [[f64; 2]; 2],
+        image: [f64; 2],
+        transposed: [[f64; 2]; 3],
+        multiplied: u32,
+        count: u32,
+    }
+
+    let function = "
+        PROGRAM main
+        VAR
+            left : MATRIX[2, 3] OF LREAL;
+            right : MATRIX[3, 2] OF LREAL;
+            vector : VECTOR[3] OF LREAL;
+            product : MATRIX[2, 2] OF LREAL;
+            image : VECTOR[2] OF LREAL;
+            transposed : MATRIX[3, 2] OF LREAL;
+            multiplied : UDINT;
+            count : UDINT;
+        END_VAR
+            multiplied := MATRIX_MUL(product, left, right);
+            MATRIX_MUL(destination := image, left := left, right := vector);
+            count := TRANSPOSE(transposed, left);
+        END_PROGRAM
+    ";
+
+    let mut main = MainType {
+        left: [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]],
+        right: [[7.0, 8.0], [9.0, 10.0], [11.0, 12.0]],
+        vector: [1.0, 0.0, -1.0],
+        ..MainType::default()
+    };
+    let _: i32 = compile_and_run(function, &mut main);
+    assert_eq!(main.multiplied, 4);
+    assert_eq!(main.product, [[58.0, 64.0], [139.0, 154.0]]);
+    assert_eq!(main.image, [-2.0, -2.0]);
+    assert_eq!(main.count, 6);
+    assert_eq!(main.transposed, [[1.0, 4.0], [2.0, 5.0], [3.0, 6.0]]);
+}
+
+#[test]
+fn seeded_random_numbers_and_guids_are_reproducible() {
+    #[repr(C)]
+    struct MainType {
+        first: f64,
+        second: f64,
+        repeated: f64,
+        id: [u8; 81],
+        other_id: [u8; 81],
+    }
+
+    let function = "
+        PROGRAM main
+        VAR
+            first : LREAL;
+            second : LREAL;
+            repeated : LREAL;
+            id : STRING;
+            other_id : STRING;
+        END_VAR
+            SRAND(42);
+            first := RAND();
+            second := RAND();
+            SRAND(seed := 42);
+            repeated := RAND();
+            id := GUID();
+            other_id := GUID();
+        END_PROGRAM
+    ";
+
+    let mut main = MainType {
+        first: 0.0,
+        second: 0.0,
+        repeated: 0.0,
+        id: [0; 81],
+        other_id: [0; 81],
+    };
+    let _: i32 = compile_and_run(function, &mut main);
+    assert!((0.0..1.0).contains(&main.first));
+    assert_ne!(main.first, main.second);
+    assert_eq!(main.first, main.repeated);
+    assert_eq!(main.id[36], 0);
+    assert_eq!(main.id[8], b'-');
+    assert_eq!(main.id[14], b'4');
+    assert_ne!(main.id, main.other_id);
+}
+
+#[test]
+fn files_are_written_and_read_by_the_runtime_function_blocks() {
+    #[repr(C)]
+    struct MainType {
+        written: u32,
+        count: u32,
+        missing_error: bool,
+        loaded: [u8; 10],
+        instances: [u8; 2048],
+    }
+
+    let path = std::env::temp_dir().join(format!("rusty_recipe_{}.dat", std::process::id()));
+    let path = path.to_str().unwrap();
+    let function = format!(
+        "
+        PROGRAM main
+        VAR
+            written : UDINT;
+            count : UDINT;
+            missing_error : BOOL;
+            loaded : ARRAY[0..9] OF BYTE;
+            recipe : STRING;
+            open_writer : FILE_OPEN;
+            write_recipe : FILE_WRITE;
+            close_writer : FILE_CLOSE;
+            open_reader : FILE_OPEN;
+            read_recipe : FILE_READ;
+            close_reader : FILE_CLOSE;
+            missing : FILE_OPEN;
+        END_VAR
+            recipe := 'speed=42';
+            open_writer(execute := TRUE, path := '{path}', mode := FILE_MODE_WRITE);
+            write_recipe(execute := TRUE, handle := open_writer.handle, buffer := ADR(recipe), size := 8);
+            written := write_recipe.count;
+            close_writer(execute := TRUE, handle := open_writer.handle);
+
+            open_reader(execute := TRUE, path := '{path}', mode := FILE_MODE_READ);
+            read_recipe(execute := TRUE, handle := open_reader.handle, buffer := ADR(loaded), size := 10);
+            count := read_recipe.count;
+            close_reader(execute := TRUE, handle := open_reader.handle);
+
+            missing(execute := TRUE, path := '{path}.missing', mode := FILE_MODE_READ);
+            missing_error := missing.error;
+        END_PROGRAM
+    ",
+        path = path
+    );
+
+    let mut main = MainType {
+        written: 0,
+        count: 0,
+        missing_error: false,
+        loaded: [0; 10],
+        instances: [0; 2048],
+    };
+    let _: i32 = compile_and_run(function, &mut main);
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(main.written, 8);
+    assert_eq!(main.count, 8);
+    assert_eq!(&main.loaded[..8], b"speed=42");
+    assert!(main.missing_error);
+}
+
+#[cfg(feature = "communication")]
+#[test]
+fn udp_datagrams_are_exchanged_by_the_communication_function_blocks() {
+    #[repr(C)]
+    struct MainType {
+        opened: bool,
+        sent: u32,
+        received: u32,
+        message: [u8; 10],
+        instances: [u8; 2048],
+    }
+
+    let port = std::net::UdpSocket::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let function = format!(
+        "
+        PROGRAM main
+        VAR
+            opened : BOOL;
+            sent : UDINT;
+            received : UDINT;
+            message : ARRAY[0..9] OF BYTE;
+            greeting : STRING;
+            receiver : UDP_OPEN;
+            sender : UDP_OPEN;
+            send_greeting : UDP_SEND;
+            receive_greeting : UDP_RECEIVE;
+        END_VAR
+            greeting := 'hello';
+            receiver(execute := TRUE, port := {port});
+            sender(execute := TRUE);
+            opened := receiver.done AND sender.done;
+            send_greeting(execute := TRUE, handle := sender.handle, host := '127.0.0.1', port := {port}, buffer := ADR(greeting), size := 5);
+            sent := send_greeting.count;
+            receive_greeting(enable := received = 0, handle := receiver.handle, buffer := ADR(message), size := 10);
+            received := received + receive_greeting.count;
+        END_PROGRAM
+    ",
+        port = port
+    );
+
+    let context: Context = Context::create();
+    let engine = compile(&context, function);
+    let mut main = MainType {
+        opened: false,
+        sent: 0,
+        received: 0,
+        message: [0; 10],
+        instances: [0; 2048],
+    };
+    //the datagram is received in one of the next cycles without blocking the cycles before
+    for _ in 0..1000 {
+        let _: i32 = run(&engine, "main", &mut main);
+        if main.received > 0 {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    assert!(main.opened);
+    assert_eq!(main.sent, 5);
+    assert_eq!(main.received, 5);
+    assert_eq!(&main.message[..5], b"hello");
+}
+
+#[test]
+fn saturating_builtins_clamp_to_the_range_of_the_type() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        sum: i16,
+        difference: u8,
+        product: i32,
+        negative_product: i32,
+        in_range: i16,
+    }
+
+    let function = "
+        PROGRAM main
+        VAR
+            sum : INT;
+            difference : BYTE;
+            product, negative_product : DINT;
+            in_range : INT := -20;
+        END_VAR
+            sum := ADD_SAT(INT#32000, INT#1000);
+            difference := SUB_SAT(BYTE#10, BYTE#20);
+            product := MUL_SAT(DINT#100000, DINT#100000);
+            negative_product := MUL_SAT(DINT#100000, -100000);
+            in_range := ADD_SAT(in_range, INT#5);
+        END_PROGRAM
+    ";
+
+    let mut main = MainType::default();
+    let _: i32 = compile_and_run(function, &mut main);
+    assert_eq!(main.sum, i16::MAX);
+    assert_eq!(main.difference, 0);
+    assert_eq!(main.product, i32::MAX);
+    assert_eq!(main.negative_product, i32::MIN);
+    assert_eq!(main.in_range, -15);
+}
+
+#[test]
+fn inlined_functions_compute_like_called_ones() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        inlined: i32,
+        called: i32,
+        nested: i64,
+    }
+
+    let function = "
+        {attribute 'inline'}
+        FUNCTION scaled : DINT
+        VAR_INPUT
+            x, factor : DINT;
+        END_VAR
+            scaled := x * factor + 1;
+        END_FUNCTION
+
+        FUNCTION multiplied : DINT
+        VAR_INPUT
+            x, factor : DINT;
+        END_VAR
+            multiplied := x * factor + 1;
+        END_FUNCTION
+
+        {attribute 'inline'}
+        FUNCTION offset : DINT
+        VAR_INPUT
+            x : DINT;
+        END_VAR
+            offset := scaled(factor := x, x := 10) - x;
+        END_FUNCTION
+
+        PROGRAM main
+        VAR
+            inlined, called : DINT;
+            nested : LINT;
+        END_VAR
+            inlined := scaled(6, 7);
+            called := multiplied(6, 7);
+            nested := offset(inlined);
+        END_PROGRAM
+    ";
+
+    let mut main = MainType::default();
+    let _: i32 = compile_and_run(function, &mut main);
+    assert_eq!(main.inlined, 43);
+    assert_eq!(main.called, 43);
+    assert_eq!(main.nested, 431 - 43);
+}
@@ -1294,3 +1294,66 @@ fn intial_values_diverge_from_type() {
     assert_eq!([5, 6, 7, 8], maintype.arr);
     assert_eq!(5, maintype.my_int);
 }
+
+#[test]
+fn uninitialized_arrays_use_the_initial_values_of_their_elements() {
+    let function = "
+    TYPE point : STRUCT x : DINT := 1; y : DINT := 2; END_STRUCT END_TYPE
+
+    FUNCTION other : DINT
+    VAR
+        points : ARRAY[0..1] OF point;
+    END_VAR
+        other := points[1].x * 10 + points[1].y;
+    END_FUNCTION
+
+    PROGRAM main
+    VAR
+        points : ARRAY[0..1] OF point;
+        from_function : DINT;
+    END_VAR
+        from_function := other();
+    END_PROGRAM
+    ";
+    #[allow(dead_code)]
+    struct MainType {
+        points: [i32; 4],
+        from_function: i32,
+    }
+    let mut maintype = MainType {
+        points: [0; 4],
+        from_function: 0,
+    };
+    let _: i32 = compile_and_run(function.to_string(), &mut maintype);
+    assert_eq!([1, 2, 1, 2], maintype.points);
+    assert_eq!(12, maintype.from_function);
+}
+
+#[test]
+fn uninitialized_function_variables_use_the_initial_value_of_their_type() {
+    let function = "
+    TYPE myInt : DINT := 4; END_TYPE
+
+    FUNCTION other : DINT
+    VAR
+        i : myInt;
+    END_VAR
+        other := i;
+    END_FUNCTION
+
+    PROGRAM main
+    VAR
+        i : DINT;
+    END_VAR
+        i := other();
+    END_PROGRAM
+    ";
+    let mut maintype = FourInts {
+        a: 0,
+        b: 0,
+        c: 0,
+        d: 0,
+    };
+    let _: i32 = compile_and_run(function.to_string(), &mut maintype);
+    assert_eq!(4, maintype.a);
+}
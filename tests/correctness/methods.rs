@@ -63,3 +63,41 @@ END_CLASS
     //Expecting it not to fail
     assert_eq!(res, 42);
 }
+
+#[test]
+fn methods_can_pass_their_instance_with_this() {
+    let src = "
+FUNCTION main : DINT
+    prg();
+    main := prg.y;
+END_FUNCTION
+
+FUNCTION increment
+    VAR_INPUT counter : POINTER TO myFB; END_VAR
+    counter^.x := counter^.x + 1;
+END_FUNCTION
+
+PROGRAM prg
+    VAR x : myFB; END_VAR
+    VAR_OUTPUT y : DINT; END_VAR
+    x();
+    y := x.test(32);
+END_PROGRAM
+
+FUNCTION_BLOCK myFB
+    VAR
+        x : INT := 8;
+    END_VAR
+    increment(THIS);
+
+    METHOD test : DINT
+        VAR_INPUT a : DINT; END_VAR
+        increment(THIS);
+        test := THIS^.x + a;
+    END_METHOD
+END_FUNCTION_BLOCK
+    ";
+
+    let res: i32 = compile_and_run(src, &mut MainType::default());
+    assert_eq!(res, 42);
+}
@@ -0,0 +1,106 @@
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+use inkwell::execution_engine::JitFunction;
+
+use super::super::*;
+
+#[repr(C)]
+struct ReflectionMember {
+    name: *const c_char,
+    type_name: *const c_char,
+    offset: u64,
+    size: u64,
+}
+
+#[repr(C)]
+struct ReflectionType {
+    name: *const c_char,
+    size: u64,
+    member_count: u32,
+    members: *const ReflectionMember,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct Motor {
+    speed: i32,
+    position: f64,
+    enable: bool,
+}
+
+type GetTypeCount = unsafe extern "C" fn() -> i32;
+type GetType = unsafe extern "C" fn(i32) -> *const ReflectionType;
+type GetMemberAddress = unsafe extern "C" fn(*mut u8, *const ReflectionMember) -> *mut u8;
+
+fn to_str<'a>(text: *const c_char) -> &'a str {
+    unsafe { CStr::from_ptr(text) }.to_str().unwrap()
+}
+
+#[test]
+fn members_of_function_blocks_can_be_enumerated_and_accessed() {
+    let src = "
+        FUNCTION_BLOCK motor
+        VAR
+            speed : DINT;
+            position : LREAL;
+            enable : BOOL;
+        END_VAR
+        END_FUNCTION_BLOCK
+    ";
+
+    let context = Context::create();
+    let (index, code_gen) = compile_module(
+        &context,
+        vec![SourceCode::from(src)],
+        vec![],
+        None,
+        Diagnostician::null_diagnostician(),
+    )
+    .unwrap();
+    code_gen.generate_reflection(&index).unwrap();
+    let engine = code_gen
+        .module
+        .create_jit_execution_engine(inkwell::OptimizationLevel::None)
+        .unwrap();
+
+    unsafe {
+        let get_type_count: JitFunction<GetTypeCount> =
+            engine.get_function("__reflection_get_type_count").unwrap();
+        let get_type: JitFunction<GetType> = engine.get_function("__reflection_get_type").unwrap();
+        let get_member_address: JitFunction<GetMemberAddress> = engine
+            .get_function("__reflection_get_member_address")
+            .unwrap();
+
+        let count = get_type_count.call();
+        assert!(get_type.call(count).is_null());
+        let motor = (0..count)
+            .map(|i| &*get_type.call(i))
+            .find(|it| to_str(it.name) == "motor")
+            .unwrap();
+        assert_eq!(motor.size, std::mem::size_of::<Motor>() as u64);
+
+        let members = std::slice::from_raw_parts(motor.members, motor.member_count as usize);
+        let described = members
+            .iter()
+            .map(|it| (to_str(it.name), to_str(it.type_name), it.offset, it.size))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            described,
+            vec![
+                ("speed", "DINT", 0, 4),
+                ("position", "LREAL", 8, 8),
+                ("enable", "BOOL", 16, 1),
+            ]
+        );
+
+        let mut instance = Motor {
+            speed: 0,
+            position: 0.0,
+            enable: false,
+        };
+        let address = get_member_address.call(&mut instance as *mut Motor as *mut u8, &members[1]);
+        *(address as *mut f64) = 1.5;
+        assert_eq!(instance.position, 1.5);
+    }
+}
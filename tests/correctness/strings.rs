@@ -605,3 +605,155 @@ fn wstring_as_function_parameters_cast() {
     let res = String::from_utf16_lossy(&main_type.res[..5]);
     assert_eq!(res, "hello");
 }
+
+#[test]
+fn concat_appends_any_number_of_strings() {
+    let src = r#"
+        PROGRAM main
+            VAR
+                x : STRING[20];
+                y : WSTRING[20];
+                short : STRING[4];
+            END_VAR
+            VAR_TEMP
+                name : STRING[10] := 'rusty';
+                empty : STRING;
+            END_VAR
+            x := CONCAT('hello ', name, empty, '!');
+            y := CONCAT("a", "bc", "def");
+            short := CONCAT(CONCAT('ab', 'cd'), 'ef');
+        END_PROGRAM
+    "#;
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        x: [u8; 21],
+        y: [u16; 21],
+        short: [u8; 5],
+    }
+    let mut main_type = MainType {
+        x: [0; 21],
+        y: [0; 21],
+        short: [0; 5],
+    };
+
+    let _: i32 = compile_and_run(src, &mut main_type);
+    assert_eq!("hello rusty!\0".as_bytes(), &main_type.x[..13]);
+    assert_eq!("abcdef", String::from_utf16_lossy(&main_type.y[..6]));
+    assert_eq!(0, main_type.y[6]);
+    assert_eq!("abcd".as_bytes(), &main_type.short[..4]);
+}
+
+#[test]
+fn numbers_are_converted_to_strings_and_back() {
+    let src = r#"
+        PROGRAM main
+            VAR
+                i : STRING;
+                r : STRING;
+                w : WSTRING;
+                parsed : INT;
+                hex : UDINT;
+                real : REAL;
+                invalid : SINT;
+                invalid_error : BOOL;
+                valid_error : BOOL;
+            END_VAR
+            i := INT_TO_STRING(-1234);
+            r := LREAL_TO_STRING(2.5);
+            w := UDINT_TO_WSTRING(4000000000);
+            parsed := STRING_TO_INT(' 42 ', error => valid_error);
+            hex := WSTRING_TO_UDINT("16#FF");
+            real := STRING_TO_REAL('-0.75');
+            invalid := STRING_TO_SINT('300', invalid_error);
+        END_PROGRAM
+    "#;
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        i: [u8; 81],
+        r: [u8; 81],
+        w: [u16; 81],
+        parsed: i16,
+        hex: u32,
+        real: f32,
+        invalid: i8,
+        invalid_error: bool,
+        valid_error: bool,
+    }
+    let mut main_type = MainType {
+        i: [0; 81],
+        r: [0; 81],
+        w: [0; 81],
+        parsed: 0,
+        hex: 0,
+        real: 0.0,
+        invalid: 1,
+        invalid_error: false,
+        valid_error: true,
+    };
+
+    let _: i32 = compile_and_run(src, &mut main_type);
+    assert_eq!("-1234\0".as_bytes(), &main_type.i[..6]);
+    assert_eq!("2.5\0".as_bytes(), &main_type.r[..4]);
+    assert_eq!("4000000000", String::from_utf16_lossy(&main_type.w[..10]));
+    assert_eq!(0, main_type.w[10]);
+    assert_eq!(42, main_type.parsed);
+    assert!(!main_type.valid_error);
+    assert_eq!(255, main_type.hex);
+    assert_eq!(-0.75, main_type.real);
+    assert_eq!(0, main_type.invalid);
+    assert!(main_type.invalid_error);
+}
+
+#[test]
+fn date_and_times_and_durations_are_converted_to_strings_and_back() {
+    let src = r#"
+        PROGRAM main
+            VAR
+                dt_text : STRING;
+                time_text : WSTRING;
+                timestamp : DT;
+                duration : TIME;
+                error : BOOL;
+            END_VAR
+            dt_text := DT_TO_STRING(DT#2021-05-02-14:20:10.25);
+            time_text := TIME_TO_WSTRING(T#1h30m);
+            timestamp := STRING_TO_DT('2021-05-02T14:20:10Z');
+            duration := STRING_TO_TIME('T#-2.5s', error => error);
+        END_PROGRAM
+    "#;
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        dt_text: [u8; 81],
+        time_text: [u16; 81],
+        timestamp: i64,
+        duration: i64,
+        error: bool,
+    }
+    let mut main_type = MainType {
+        dt_text: [0; 81],
+        time_text: [0; 81],
+        timestamp: 0,
+        duration: 0,
+        error: true,
+    };
+
+    let _: i32 = compile_and_run(src, &mut main_type);
+    assert_eq!(
+        "DT#2021-05-02-14:20:10.250\0".as_bytes(),
+        &main_type.dt_text[..27]
+    );
+    assert_eq!(
+        "T#1h30m",
+        String::from_utf16_lossy(&main_type.time_text[..7])
+    );
+    assert_eq!(0, main_type.time_text[7]);
+    assert_eq!(1_619_965_210_000, main_type.timestamp);
+    assert_eq!(-2_500_000_000, main_type.duration);
+    assert!(!main_type.error);
+}
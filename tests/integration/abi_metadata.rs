@@ -0,0 +1,118 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+use std::{env, fs};
+
+use rusty::{
+    abi_metadata::{read_metadata, MemberLayout, RetainedVariable, ABI_VERSION},
+    build, get_target_triple, CompileOptions, ErrorFormat, FilePath, FormatOption,
+};
+
+use crate::get_test_file;
+
+fn build_artifact(file: &str, format: FormatOption, name: &str) -> String {
+    let mut out = env::temp_dir();
+    out.push(name);
+    let out = out.into_os_string().into_string().unwrap();
+    let target = get_target_triple(None);
+    build(
+        vec![FilePath {
+            path: get_test_file(file),
+        }],
+        vec![],
+        &CompileOptions {
+            format,
+            output: out.clone(),
+            target: None,
+            optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
+        },
+        None,
+        &ErrorFormat::Rich,
+        &target,
+    )
+    .unwrap();
+    out
+}
+
+#[test]
+fn abi_metadata_is_embedded_into_objects_and_bitcode() {
+    let target = get_target_triple(None);
+    for (format, name) in [
+        (FormatOption::Static, "abi_metadata.o"),
+        (FormatOption::Bitcode, "abi_metadata.bc"),
+    ] {
+        let out = build_artifact("test_file.st", format, name);
+        let records = read_metadata(&out).unwrap();
+        fs::remove_file(&out).unwrap();
+
+        assert_eq!(records.len(), 1);
+        let metadata = &records[0];
+        assert_eq!(metadata.abi_version, ABI_VERSION);
+        assert_eq!(metadata.compiler_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metadata.target, target.as_str().to_string_lossy());
+        assert_eq!(metadata.exports, vec!["a"]);
+    }
+}
+
+#[test]
+fn instance_layouts_and_retained_variables_are_embedded() {
+    let out = build_artifact("abi_metadata.st", FormatOption::Static, "abi_layouts.o");
+    let records = read_metadata(&out).unwrap();
+    fs::remove_file(&out).unwrap();
+
+    let metadata = &records[0];
+    assert_eq!(metadata.exports, vec!["motor", "prg"]);
+
+    let motor = metadata
+        .layouts
+        .iter()
+        .find(|it| it.name == "motor")
+        .unwrap();
+    assert_eq!(motor.size, 16);
+    assert_eq!(
+        motor.members,
+        vec![
+            MemberLayout {
+                name: "speed".into(),
+                type_name: "DINT".into(),
+                offset: 0,
+                size: 4,
+            },
+            MemberLayout {
+                name: "hours".into(),
+                type_name: "LREAL".into(),
+                offset: 8,
+                size: 8,
+            },
+        ]
+    );
+
+    assert_eq!(
+        metadata.retained,
+        vec![
+            RetainedVariable {
+                name: "total".into(),
+                type_name: "LREAL".into(),
+                container: "total".into(),
+                offset: 0,
+                size: 8,
+            },
+            RetainedVariable {
+                name: "motor.hours".into(),
+                type_name: "LREAL".into(),
+                container: "motor".into(),
+                offset: 8,
+                size: 8,
+            },
+        ]
+    );
+}
@@ -0,0 +1,37 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+use std::fs;
+
+use rusty::{build_with_params, cli::CompileParameters};
+
+use crate::get_test_file;
+
+/// compiles `off_by_one.st` to IR with the given options and returns the IR
+fn compile_to_ir(options: &[&str]) -> String {
+    let directory = tempfile::tempdir().unwrap();
+    let output = directory
+        .path()
+        .join("off_by_one.ll")
+        .to_string_lossy()
+        .to_string();
+    let mut args = vec![
+        "rustyc".to_string(),
+        get_test_file("bounds_check/off_by_one.st"),
+        "--ir".to_string(),
+        "-o".to_string(),
+        output.clone(),
+    ];
+    args.extend(options.iter().map(|it| it.to_string()));
+    build_with_params(CompileParameters::parse(args).unwrap()).unwrap();
+    fs::read_to_string(&output).unwrap()
+}
+
+#[test]
+fn array_accesses_call_the_check_bounds_function_out_of_bounds() {
+    let ir = compile_to_ir(&["-O0", "--check", "bounds"]);
+    assert!(ir.contains("call i32 @CheckBounds(i32 %"));
+    assert!(ir.contains("i32 0, i32 7)"));
+
+    let ir = compile_to_ir(&["-O0"]);
+    assert!(!ir.contains("call i32 @CheckBounds("));
+}
@@ -0,0 +1,51 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+use std::{env, fs};
+
+use rusty::{build, get_target_triple, CompileOptions, ErrorFormat, FilePath, FormatOption};
+
+use crate::get_test_file;
+
+static TARGET: Option<&str> = Some("armv7a-none-eabihf");
+
+#[test]
+fn module_is_generated_for_the_given_cpu_and_features() {
+    let mut out = env::temp_dir();
+    out.push("cross_compilation.ll");
+    let out = out.into_os_string().into_string().unwrap();
+    let triple = get_target_triple(TARGET);
+
+    build(
+        vec![FilePath {
+            path: get_test_file("test_file.st"),
+        }],
+        vec![],
+        &CompileOptions {
+            output: out.clone(),
+            format: FormatOption::IR,
+            target: TARGET.map(String::from),
+            optimization: rusty::OptimizationLevel::None,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: Some("cortex-a9".into()),
+            features: Some("+neon".into()),
+            lto: false,
+            check_bounds: false,
+        },
+        None,
+        &ErrorFormat::Rich,
+        &triple,
+    )
+    .unwrap();
+    let ir = fs::read_to_string(&out).unwrap();
+    fs::remove_file(&out).unwrap();
+
+    assert!(ir.contains(r#"target triple = "armv7a-none-eabihf""#));
+    assert!(ir.contains(r#"target datalayout = "e-m:e-p:32:32"#));
+    assert!(ir.contains(r#""target-cpu"="cortex-a9""#));
+    assert!(ir.contains(r#""target-features"="+neon""#));
+}
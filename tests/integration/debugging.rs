@@ -0,0 +1,340 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+use std::{
+    cell::RefCell,
+    env, fs,
+    io::{Read, Write},
+    rc::Rc,
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+use rusty::{
+    dialect::Dialect,
+    simulation::{
+        dap::DebugAdapter,
+        debugger::{DebugHandler, DebugState, Resume, StopReason},
+        Simulation, SimulationSources,
+    },
+    task_configuration::TaskConfiguration,
+    ConfigFormat, FilePath,
+};
+use serde_json::{json, Value};
+
+const SOURCE: &str = "VAR_GLOBAL
+    total : DINT;
+END_VAR
+FUNCTION double : DINT
+VAR_INPUT x : DINT; END_VAR
+    double := x * 2;
+END_FUNCTION
+PROGRAM prg
+VAR count : INT; END_VAR
+    count := count + 1;
+    total := double(count);
+    total := total + 1;
+END_PROGRAM
+";
+
+fn start_debug_simulation(name: &str) -> (Simulation, String) {
+    let mut file = env::temp_dir();
+    file.push(name);
+    let file = file.into_os_string().into_string().unwrap();
+    fs::write(&file, SOURCE).unwrap();
+    let tasks = TaskConfiguration::parse(
+        r#"{ "tasks": [ { "name": "main", "programs": ["prg"] } ] }"#,
+        ConfigFormat::JSON,
+    )
+    .unwrap();
+    let simulation = Simulation::new(
+        SimulationSources {
+            sources: vec![FilePath::from(file.as_str())],
+            includes: vec![],
+            generated: vec![],
+            encoding: None,
+            dialect: Dialect::default(),
+            debug_hooks: true,
+        },
+        tasks,
+        10,
+    )
+    .unwrap();
+    (simulation, file)
+}
+
+/// a stop observed by the `ScriptedHandler`
+#[derive(Debug, PartialEq)]
+struct Stop {
+    reason: StopReason,
+    pou: String,
+    line: u32,
+    /// the locals and globals with their values
+    variables: Vec<String>,
+}
+
+/// stops at the given breakpoints and resumes as scripted
+struct ScriptedHandler {
+    file: String,
+    breakpoints: Vec<u32>,
+    resumes: Vec<Resume>,
+    stops: Rc<RefCell<Vec<Stop>>>,
+}
+
+impl DebugHandler for ScriptedHandler {
+    fn attached(&mut self, state: &mut DebugState) {
+        let file = state.get_symbols().find_source(&self.file).unwrap();
+        state.set_breakpoints(file, self.breakpoints.iter().copied());
+    }
+
+    fn stopped(&mut self, state: &mut DebugState, reason: StopReason) -> Resume {
+        let frame = state.get_frames().last().unwrap();
+        let symbols = state.get_symbols();
+        let variables = symbols
+            .get_locals(&frame.pou, frame.instance)
+            .into_iter()
+            .chain(symbols.get_globals())
+            .map(|it| format!("{} = {}", it.name, symbols.format_value(&it)))
+            .collect();
+        self.stops.borrow_mut().push(Stop {
+            reason,
+            pou: frame.pou.clone(),
+            line: frame.line,
+            variables,
+        });
+        if self.resumes.is_empty() {
+            Resume::Continue
+        } else {
+            self.resumes.remove(0)
+        }
+    }
+}
+
+fn stop(reason: StopReason, pou: &str, line: u32, variables: &[&str]) -> Stop {
+    Stop {
+        reason,
+        pou: pou.into(),
+        line,
+        variables: variables.iter().map(|it| it.to_string()).collect(),
+    }
+}
+
+#[test]
+fn breakpoints_and_steps_stop_on_source_lines() {
+    let (mut simulation, file) = start_debug_simulation("debugging_steps.st");
+    let stops = Rc::new(RefCell::new(vec![]));
+    simulation
+        .attach_debugger(Box::new(ScriptedHandler {
+            file: file.clone(),
+            breakpoints: vec![11],
+            resumes: vec![Resume::StepIn, Resume::StepOver, Resume::Continue],
+            stops: stops.clone(),
+        }))
+        .unwrap();
+
+    simulation.run_cycle();
+    simulation.run_cycle();
+    simulation.detach_debugger();
+
+    assert_eq!(
+        *stops.borrow(),
+        vec![
+            stop(
+                StopReason::Breakpoint,
+                "prg",
+                11,
+                &["count = 1", "total = 0"]
+            ),
+            stop(StopReason::Step, "double", 6, &["total = 0"]),
+            stop(StopReason::Step, "prg", 12, &["count = 1", "total = 2"]),
+            stop(
+                StopReason::Breakpoint,
+                "prg",
+                11,
+                &["count = 2", "total = 3"]
+            ),
+        ]
+    );
+    fs::remove_file(&file).unwrap();
+}
+
+/// the client's end of the pipe to the adapter
+struct ChannelReader {
+    receiver: Receiver<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.buffer.is_empty() {
+            match self.receiver.recv() {
+                Ok(content) => self.buffer = content,
+                Err(_) => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.buffer.len());
+        buf[..len].copy_from_slice(&self.buffer[..len]);
+        self.buffer.drain(..len);
+        Ok(len)
+    }
+}
+
+/// sends every flushed message to the client
+struct ChannelWriter {
+    sender: Sender<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let _ = self.sender.send(std::mem::take(&mut self.buffer));
+        Ok(())
+    }
+}
+
+/// a scripted DAP client
+struct Client {
+    requests: Sender<Vec<u8>>,
+    messages: Receiver<Vec<u8>>,
+    sequence: i64,
+}
+
+impl Client {
+    fn send(&mut self, command: &str, arguments: Value) {
+        self.sequence += 1;
+        let content = json!({
+            "seq": self.sequence,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+        self.requests
+            .send(format!("Content-Length: {}\r\n\r\n{}", content.len(), content).into_bytes())
+            .unwrap();
+    }
+
+    /// returns the next message matching the given type and command or event
+    fn receive(&mut self, message_type: &str, name: &str) -> Value {
+        loop {
+            let message = self.messages.recv().unwrap();
+            let message = String::from_utf8(message).unwrap();
+            let (_, content) = message.split_once("\r\n\r\n").unwrap();
+            let message: Value = serde_json::from_str(content).unwrap();
+            let key = if message_type == "event" {
+                "event"
+            } else {
+                "command"
+            };
+            if message["type"] == message_type && message[key] == name {
+                return message;
+            }
+        }
+    }
+
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        self.send(command, arguments);
+        self.receive("response", command)
+    }
+}
+
+#[test]
+fn debug_adapter_reports_stops_and_variables() {
+    let (mut simulation, file) = start_debug_simulation("debugging_adapter.st");
+    let (requests, reader) = channel();
+    let (writer, messages) = channel();
+
+    let path = file.clone();
+    let client = std::thread::spawn(move || {
+        let mut client = Client {
+            requests,
+            messages,
+            sequence: 0,
+        };
+        client.request("initialize", json!({ "adapterID": "rusty" }));
+        client.receive("event", "initialized");
+        let breakpoints = client.request(
+            "setBreakpoints",
+            json!({ "source": { "path": path }, "breakpoints": [{ "line": 12 }] }),
+        );
+        client.request("configurationDone", json!({}));
+
+        let stopped = client.receive("event", "stopped");
+        let stack = client.request("stackTrace", json!({ "threadId": 1 }));
+        let frame = stack["body"]["stackFrames"][0]["id"].clone();
+        let scopes = client.request("scopes", json!({ "frameId": frame }));
+        let locals = scopes["body"]["scopes"][0]["variablesReference"].clone();
+        let variables = client.request("variables", json!({ "variablesReference": locals }));
+        let changed = client.request(
+            "setVariable",
+            json!({ "variablesReference": locals, "name": "count", "value": "16#1" }),
+        );
+        let watched = client.request(
+            "evaluate",
+            json!({ "expression": "prg.count", "context": "watch" }),
+        );
+        let commands = ["force total := 100", "forced", "release total", "count"]
+            .iter()
+            .map(|command| {
+                client.request(
+                    "evaluate",
+                    json!({ "expression": command, "context": "repl" }),
+                )
+            })
+            .collect::<Vec<_>>();
+        client.request("disconnect", json!({}));
+        (
+            breakpoints,
+            stopped,
+            stack,
+            variables,
+            changed,
+            watched,
+            commands,
+        )
+    });
+
+    let adapter = DebugAdapter::new(
+        ChannelReader {
+            receiver: reader,
+            buffer: vec![],
+        },
+        Box::new(ChannelWriter {
+            sender: writer,
+            buffer: vec![],
+        }),
+    );
+    simulation.attach_debugger(Box::new(adapter)).unwrap();
+    simulation.run_cycle();
+    simulation.run_cycle();
+    simulation.detach_debugger();
+    let (breakpoints, stopped, stack, variables, changed, watched, commands) =
+        client.join().unwrap();
+
+    assert_eq!(breakpoints["body"]["breakpoints"][0]["verified"], true);
+    assert_eq!(stopped["body"]["reason"], "breakpoint");
+    assert_eq!(stack["body"]["stackFrames"][0]["name"], "prg");
+    assert_eq!(stack["body"]["stackFrames"][0]["line"], 12);
+    assert_eq!(
+        variables["body"]["variables"][0],
+        json!({ "name": "count", "value": "1", "type": "INT", "variablesReference": 0 })
+    );
+    assert_eq!(changed["body"]["value"], "1");
+    assert_eq!(watched["body"]["result"], "1");
+    assert_eq!(commands[0]["body"]["result"], "total forced to 100");
+    assert_eq!(commands[1]["body"]["result"], "total := 100");
+    assert_eq!(commands[2]["body"]["result"], "total released");
+    assert_eq!(
+        commands[3]["success"], false,
+        "count is no global or program"
+    );
+    assert_eq!(
+        simulation.get_variable("total").map(|it| it[0]),
+        Some(5),
+        "the second cycle runs after the client disconnected"
+    );
+    fs::remove_file(&file).unwrap();
+}
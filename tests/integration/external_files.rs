@@ -22,6 +22,16 @@ fn compile_all(name: &str, encoding: Option<&'static Encoding>) {
             output: out.clone(),
             target: None,
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         encoding,
         &ErrorFormat::Rich,
@@ -37,6 +47,16 @@ fn compile_all(name: &str, encoding: Option<&'static Encoding>) {
             output: out.clone(),
             target: None,
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         encoding,
         &ErrorFormat::Rich,
@@ -52,6 +72,16 @@ fn compile_all(name: &str, encoding: Option<&'static Encoding>) {
             output: out.clone(),
             target: None,
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         encoding,
         &ErrorFormat::Rich,
@@ -67,6 +97,16 @@ fn compile_all(name: &str, encoding: Option<&'static Encoding>) {
             output: out.clone(),
             target: None,
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         encoding,
         &ErrorFormat::Rich,
@@ -82,6 +122,16 @@ fn compile_all(name: &str, encoding: Option<&'static Encoding>) {
             output: out.clone(),
             target: None,
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         encoding,
         &ErrorFormat::Rich,
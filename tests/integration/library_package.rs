@@ -0,0 +1,136 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+use std::{env, fs};
+
+use rusty::{
+    build_with_params,
+    cli::{CompileParameters, PackageParameters},
+    library_package::LibraryPackage,
+    package_with_params,
+};
+
+use crate::get_test_file;
+
+#[test]
+fn applications_are_compiled_against_the_declarations_of_a_library_package() {
+    let mut directory = env::temp_dir();
+    directory.push("library_package");
+    fs::create_dir_all(&directory).unwrap();
+    let package = directory
+        .join("motion.plclib")
+        .to_string_lossy()
+        .to_string();
+    let output = directory.join("app.ll").to_string_lossy().to_string();
+
+    let parameters = PackageParameters::parse(vec![
+        "package".to_string(),
+        get_test_file("library/motion.st"),
+        "-o".to_string(),
+        package.clone(),
+    ])
+    .unwrap();
+    package_with_params(parameters).unwrap();
+
+    let library = LibraryPackage::load(&package).unwrap();
+    assert_eq!(library.manifest.name, "motion");
+    assert!(library.declarations.contains("FUNCTION ramp : INT"));
+    assert!(!library.declarations.contains("secret sauce"));
+    assert!(library
+        .documentation
+        .contains("ramps the speed up to the target"));
+    assert!(!library.object.is_empty());
+
+    let parameters = CompileParameters::parse(vec![
+        "rustyc".to_string(),
+        get_test_file("library/app.st"),
+        "--lib".to_string(),
+        package,
+        "--ir".to_string(),
+        "-o".to_string(),
+        output.clone(),
+    ])
+    .unwrap();
+    build_with_params(parameters).unwrap();
+
+    let ir = fs::read_to_string(&output).unwrap();
+    assert!(ir.contains("declare i16 @ramp("));
+    assert!(ir.contains("call i16 @ramp("));
+}
+
+/// packages the given source as the library `motion` of the given version into the directory
+fn package_motion(source: &str, version: &str, directory: &std::path::Path) -> String {
+    let package = directory
+        .join("motion.plclib")
+        .to_string_lossy()
+        .to_string();
+    let parameters = PackageParameters::parse(vec![
+        "package".to_string(),
+        get_test_file(source),
+        "--library-version".to_string(),
+        version.to_string(),
+        "-o".to_string(),
+        package.clone(),
+    ])
+    .unwrap();
+    package_with_params(parameters).unwrap();
+    package
+}
+
+#[test]
+fn consumed_libraries_are_verified_against_the_lock() {
+    let mut directory = env::temp_dir();
+    directory.push("library_lock");
+    fs::create_dir_all(&directory).unwrap();
+    let lock = directory.join("plc.lock").to_string_lossy().to_string();
+    let _ = fs::remove_file(&lock);
+    let output = directory.join("app.ll").to_string_lossy().to_string();
+    let build = |package: &str| {
+        let parameters = CompileParameters::parse(vec![
+            "rustyc".to_string(),
+            get_test_file("library/app.st"),
+            "--lib".to_string(),
+            package.to_string(),
+            "--lib-lock".to_string(),
+            lock.clone(),
+            "--ir".to_string(),
+            "-o".to_string(),
+            output.clone(),
+        ])
+        .unwrap();
+        build_with_params(parameters)
+    };
+
+    //the first build records the library
+    let package = package_motion("library/motion.st", "1.2.0", &directory);
+    assert_eq!(
+        LibraryPackage::load(&package).unwrap().manifest.interface["ramp"],
+        "FUNCTION ramp : INT (VAR_INPUT target : INT)"
+    );
+    build(&package).unwrap();
+    assert!(fs::read_to_string(&lock)
+        .unwrap()
+        .contains(r#""version": "1.2.0""#));
+
+    //a compatible version with the same interface
+    let package = package_motion("library/motion.st", "1.3.0", &directory);
+    build(&package).unwrap();
+
+    let package = package_motion("library/motion_changed.st", "1.4.0", &directory);
+    assert_eq!(
+        build(&package).unwrap_err().get_message(),
+        format!(
+            "The interface of library 'motion' changed since it was recorded in '{}': \
+            'ramp' is now 'FUNCTION ramp : INT (VAR_INPUT target : REAL)'",
+            lock
+        )
+    );
+
+    let package = package_motion("library/motion.st", "2.0.0", &directory);
+    assert_eq!(
+        build(&package).unwrap_err().get_message(),
+        format!(
+            "Library 'motion' 2.0.0 is not compatible with the version 1.2.0 recorded in '{}'",
+            lock
+        )
+    );
+}
@@ -34,6 +34,16 @@ fn link_as_shared_object() {
             format: FormatOption::Shared,
             target: TARGET.map(String::from),
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         None,
         &ErrorFormat::Rich,
@@ -50,6 +60,16 @@ fn link_as_shared_object() {
             format: FormatOption::Shared,
             target: TARGET.map(String::from),
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         None,
         &ErrorFormat::Rich,
@@ -65,6 +85,8 @@ fn link_as_shared_object() {
         vec![],
         &triple,
         None,
+        None,
+        None,
     )
     .unwrap();
 
@@ -100,6 +122,16 @@ fn link_as_pic_object() {
             format: FormatOption::PIC,
             target: TARGET.map(String::from),
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         None,
         &ErrorFormat::Rich,
@@ -116,6 +148,16 @@ fn link_as_pic_object() {
             format: FormatOption::PIC,
             target: TARGET.map(String::from),
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         None,
         &ErrorFormat::Rich,
@@ -131,6 +173,8 @@ fn link_as_pic_object() {
         vec![],
         &triple,
         None,
+        None,
+        None,
     )
     .unwrap();
 
@@ -166,6 +210,16 @@ fn link_as_static_object() {
             format: FormatOption::Static,
             target: TARGET.map(String::from),
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         None,
         &ErrorFormat::Rich,
@@ -182,6 +236,16 @@ fn link_as_static_object() {
             format: FormatOption::Static,
             target: TARGET.map(String::from),
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         None,
         &ErrorFormat::Rich,
@@ -197,6 +261,8 @@ fn link_as_static_object() {
         vec![],
         &triple,
         None,
+        None,
+        None,
     )
     .unwrap();
 
@@ -232,6 +298,16 @@ fn link_as_relocatable_object() {
             format: FormatOption::Static,
             target: TARGET.map(String::from),
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         None,
         &ErrorFormat::Rich,
@@ -248,6 +324,16 @@ fn link_as_relocatable_object() {
             format: FormatOption::Relocatable,
             target: TARGET.map(String::from),
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         None,
         &ErrorFormat::Rich,
@@ -263,6 +349,8 @@ fn link_as_relocatable_object() {
         vec![],
         &triple,
         None,
+        None,
+        None,
     )
     .unwrap();
 
@@ -289,6 +377,16 @@ fn link_missing_file() {
             format: FormatOption::Static,
             target: TARGET.map(String::from),
             optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
         },
         None,
         &ErrorFormat::Rich,
@@ -304,6 +402,8 @@ fn link_missing_file() {
         vec![],
         &triple,
         None,
+        None,
+        None,
     );
 
     match res {
@@ -316,3 +416,128 @@ fn link_missing_file() {
     //Delete it
     fs::remove_file(&out).unwrap();
 }
+
+#[test]
+fn link_with_map_file() {
+    let file = FilePath {
+        path: get_test_file("linking/file2.st"),
+    };
+
+    let mut out = env::temp_dir();
+    out.push("mapped.so");
+    let out = out.into_os_string().into_string().unwrap();
+    let mut map = env::temp_dir();
+    map.push("mapped.map");
+    let map = map.into_os_string().into_string().unwrap();
+    let triple = get_target_triple(TARGET);
+
+    let res = build(
+        vec![file],
+        vec![],
+        &CompileOptions {
+            output: out.clone(),
+            format: FormatOption::Shared,
+            target: TARGET.map(String::from),
+            optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
+        },
+        None,
+        &ErrorFormat::Rich,
+        &triple,
+    )
+    .unwrap();
+
+    link(
+        &out,
+        FormatOption::Shared,
+        &res.objects,
+        vec![],
+        vec![],
+        &triple,
+        None,
+        Some(&map),
+        None,
+    )
+    .unwrap();
+
+    //the map file lists the output's sections and symbols
+    let content = fs::read_to_string(&map).unwrap();
+    assert!(content.contains(".text"));
+    assert!(content.contains("func2"));
+
+    //Delete it
+    fs::remove_file(&out).unwrap();
+    fs::remove_file(&map).unwrap();
+}
+
+#[test]
+fn link_with_lto() {
+    let file1 = FilePath {
+        path: get_test_file("linking/file1.st"),
+    };
+    let file2 = FilePath {
+        path: get_test_file("linking/file2.st"),
+    };
+
+    let mut out = env::temp_dir();
+    out.push("lto.so");
+    let out = out.into_os_string().into_string().unwrap();
+    let triple = get_target_triple(TARGET);
+
+    let res = build(
+        vec![file1, file2],
+        vec![],
+        &CompileOptions {
+            output: out.clone(),
+            format: FormatOption::Shared,
+            target: TARGET.map(String::from),
+            optimization: rusty::OptimizationLevel::Default,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: true,
+            check_bounds: false,
+        },
+        None,
+        &ErrorFormat::Rich,
+        &triple,
+    )
+    .unwrap();
+
+    //the objects hold bitcode which is optimized and compiled by the linker
+    for object in &res.objects {
+        assert!(fs::read(&object.path).unwrap().starts_with(b"BC"));
+    }
+
+    link(
+        &out,
+        FormatOption::Shared,
+        &res.objects,
+        vec![],
+        vec![],
+        &triple,
+        None,
+        None,
+        Some(rusty::OptimizationLevel::Default),
+    )
+    .unwrap();
+
+    assert!(fs::read(&out).unwrap().starts_with(b"\x7fELF"));
+
+    //Delete it
+    fs::remove_file(&out).unwrap();
+}
@@ -1,4 +1,10 @@
-use rusty::FilePath;
+use std::{env, fs};
+
+use rusty::{
+    build_project_with_params, build_with_params,
+    cli::{BuildParameters, CompileParameters},
+    FilePath,
+};
 
 use crate::{compile_and_run, get_test_file};
 
@@ -14,3 +20,65 @@ fn sources_accross_multiple_files_compiled() {
     let res: i32 = compile_and_run(vec![file1, file2], &mut ());
     assert_eq!(42, res);
 }
+
+#[test]
+fn sources_listed_in_a_project_file_are_built_into_one_output() {
+    let mut directory = env::temp_dir();
+    directory.push("multi_files_project");
+    fs::create_dir_all(&directory).unwrap();
+    let project_file = directory.join("plc.json");
+    fs::write(
+        &project_file,
+        format!(
+            r#"{{
+                "files": [{:?}, {:?}],
+                "output": "multi.ll",
+                "format": "ir",
+                "options": ["-O", "none"]
+            }}"#,
+            get_test_file("multi/func.st"),
+            get_test_file("multi/prog.st")
+        ),
+    )
+    .unwrap();
+
+    let parameters = BuildParameters::parse(vec![
+        "build".to_string(),
+        project_file.to_string_lossy().to_string(),
+    ])
+    .unwrap();
+    build_project_with_params(parameters).unwrap();
+
+    let ir = fs::read_to_string(directory.join("multi.ll")).unwrap();
+    assert!(ir.contains("define i16 @main()"));
+    assert!(ir.contains("define void @mainProg("));
+}
+
+#[test]
+fn pous_generated_in_parallel_are_linked_like_the_sequentially_generated_ones() {
+    let mut directory = env::temp_dir();
+    directory.push("multi_files_parallel");
+    fs::create_dir_all(&directory).unwrap();
+    let build = |jobs: &str| {
+        let output = directory.join(format!("multi_{}.ll", jobs));
+        let parameters = CompileParameters::parse(vec![
+            "rustyc".to_string(),
+            get_test_file("multi/func.st"),
+            get_test_file("multi/prog.st"),
+            "--ir".to_string(),
+            "-o".to_string(),
+            output.to_string_lossy().to_string(),
+            "-j".to_string(),
+            jobs.to_string(),
+        ])
+        .unwrap();
+        build_with_params(parameters).unwrap();
+        fs::read_to_string(output).unwrap()
+    };
+
+    let sequential = build("1");
+    assert!(sequential.contains("define i16 @main()"));
+    assert!(sequential.contains("define void @mainProg("));
+    assert_eq!(build("2"), sequential);
+    assert_eq!(build("4"), sequential);
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+use std::fs;
+
+use rusty::{build_with_params, cli::CompileParameters};
+
+use crate::get_test_file;
+
+/// compiles `twice.st` to IR with the given options and returns the IR
+fn compile_to_ir(options: &[&str]) -> String {
+    let directory = tempfile::tempdir().unwrap();
+    let output = directory
+        .path()
+        .join("twice.ll")
+        .to_string_lossy()
+        .to_string();
+    let mut args = vec![
+        "rustyc".to_string(),
+        get_test_file("optimization/twice.st"),
+        "--ir".to_string(),
+        "-o".to_string(),
+        output.clone(),
+    ];
+    args.extend(options.iter().map(|it| it.to_string()));
+    build_with_params(CompileParameters::parse(args).unwrap()).unwrap();
+    fs::read_to_string(&output).unwrap()
+}
+
+#[test]
+fn ir_is_optimized_by_the_pass_pipeline_of_the_level() {
+    let unoptimized = compile_to_ir(&["-O0"]);
+    assert!(unoptimized.contains("call i32 @twice("));
+    assert!(!unoptimized.contains("optnone"));
+
+    for level in ["-O1", "-O2", "-O3", "-Os"] {
+        let optimized = compile_to_ir(&[level]);
+        assert!(!optimized.contains("call i32 @twice("), "{}", level);
+        assert!(optimized.contains("ret i32 42"), "{}", level);
+    }
+}
+
+#[test]
+fn ir_is_not_optimized_without_a_level() {
+    let ir = compile_to_ir(&[]);
+    assert!(ir.contains("call i32 @twice("));
+}
+
+#[test]
+fn unoptimized_functions_are_excluded_from_optimization_when_debugging() {
+    let ir = compile_to_ir(&["-O0", "-g"]);
+    assert!(ir.contains("call i32 @twice("));
+    assert!(ir.contains("optnone"));
+    assert!(ir.contains("noinline"));
+}
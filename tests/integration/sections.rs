@@ -0,0 +1,97 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+use std::{env, fs};
+
+use rusty::{
+    build, get_target_triple,
+    linker_script::{
+        generate_linker_script, SectionEntry, PERSISTENT_SECTION, PROCESS_IMAGE_SECTION,
+        RETAIN_SECTION,
+    },
+    CompileOptions, ErrorFormat, FilePath, FormatOption,
+};
+
+use crate::get_test_file;
+
+static TARGET: Option<&str> = Some("x86_64-unknown-linux-gnu");
+
+#[test]
+fn bound_retained_and_persistent_globals_are_placed_into_sections() {
+    let mut out = env::temp_dir();
+    out.push("sections.ll");
+    let out = out.into_os_string().into_string().unwrap();
+    let triple = get_target_triple(TARGET);
+
+    let result = build(
+        vec![FilePath {
+            path: get_test_file("sections.st"),
+        }],
+        vec![],
+        &CompileOptions {
+            output: out.clone(),
+            format: FormatOption::IR,
+            target: TARGET.map(String::from),
+            optimization: rusty::OptimizationLevel::None,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
+        },
+        None,
+        &ErrorFormat::Rich,
+        &triple,
+    )
+    .unwrap();
+    let ir = fs::read_to_string(&out).unwrap();
+    fs::remove_file(&out).unwrap();
+
+    //the located variables are stored in the process images
+    assert!(ir.contains(
+        r#"@__rusty_input_image = global [9 x i8] zeroinitializer, section ".process_image""#
+    ));
+    assert!(ir.contains(
+        r#"@__rusty_output_image = global [4 x i8] zeroinitializer, section ".process_image""#
+    ));
+    assert!(!ir.contains("@start ="));
+    assert!(ir.contains(r#"@total = global double 0.000000e+00, section ".retain""#));
+    assert!(ir.contains(r#"@hours = global double 0.000000e+00, section ".persistent""#));
+    assert!(ir.contains("@count = global i32 0\n"));
+
+    let names = |section: &str| {
+        result
+            .sections
+            .iter()
+            .find(|it| it.name == section)
+            .map(|it| it.entries.clone())
+            .unwrap()
+    };
+    assert_eq!(
+        names(PROCESS_IMAGE_SECTION),
+        vec![
+            SectionEntry {
+                name: "__rusty_input_image".into(),
+                type_name: "ARRAY[0..8] OF BYTE".into(),
+                size: 9,
+            },
+            SectionEntry {
+                name: "__rusty_output_image".into(),
+                type_name: "ARRAY[0..3] OF BYTE".into(),
+                size: 4,
+            },
+        ]
+    );
+    assert_eq!(names(RETAIN_SECTION).len(), 1);
+    assert_eq!(names(PERSISTENT_SECTION).len(), 1);
+
+    let script = generate_linker_script(&result.sections, TARGET.unwrap());
+    assert!(script.contains("ASSERT(SIZEOF(.process_image) >= 13"));
+    assert!(script.contains("ASSERT(SIZEOF(.retain) >= 8"));
+    assert!(script.contains("__persistent_start = .;"));
+    assert!(script.contains("ASSERT(SIZEOF(.persistent) >= 8"));
+}
@@ -0,0 +1,358 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+use std::{env, fs};
+
+use rusty::{
+    dialect::Dialect,
+    simulation::{
+        properties::{self, PropertySettings},
+        scenario::{Mismatch, Scenario},
+        Simulation, SimulationSources, Snapshot,
+    },
+    task_configuration::TaskConfiguration,
+    ConfigFormat, FilePath,
+};
+
+fn write_source(name: &str, program: &str) -> String {
+    let mut file = env::temp_dir();
+    file.push(name);
+    let file = file.into_os_string().into_string().unwrap();
+    fs::write(
+        &file,
+        format!("VAR_GLOBAL counter : DINT; END_VAR\n{}", program),
+    )
+    .unwrap();
+    file
+}
+
+fn start_simulation(file: &str) -> Simulation {
+    start_simulation_with_tasks(
+        file,
+        r#"{ "tasks": [
+            { "name": "fast", "priority": 1, "programs": ["prg"] },
+            { "name": "slow", "priority": 2, "interval": 30, "programs": ["prg"] }
+        ]}"#,
+    )
+}
+
+fn start_simulation_with_tasks(file: &str, tasks: &str) -> Simulation {
+    let tasks = TaskConfiguration::parse(tasks, ConfigFormat::JSON).unwrap();
+    Simulation::new(
+        SimulationSources {
+            sources: vec![FilePath::from(file)],
+            includes: vec![],
+            generated: vec![],
+            encoding: None,
+            dialect: Dialect::default(),
+            debug_hooks: false,
+        },
+        tasks,
+        10,
+    )
+    .unwrap()
+}
+
+fn get_dint(simulation: &Simulation, symbol: &str) -> i32 {
+    let memory = simulation.get_variable(symbol).unwrap();
+    i32::from_ne_bytes(memory[..4].try_into().unwrap())
+}
+
+#[test]
+fn tasks_are_executed_by_their_interval() {
+    let file = write_source(
+        "simulation_intervals.st",
+        "PROGRAM prg counter := counter + 1; END_PROGRAM",
+    );
+    let mut simulation = start_simulation(&file);
+
+    assert_eq!(simulation.run_cycle(), vec!["fast", "slow"]);
+    assert_eq!(simulation.run_cycle(), vec!["fast"]);
+    assert_eq!(simulation.run_cycle(), vec!["fast"]);
+    assert_eq!(simulation.run_cycle(), vec!["fast", "slow"]);
+    assert_eq!(simulation.get_time(), 40);
+    assert_eq!(get_dint(&simulation, "counter"), 6);
+    fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn event_tasks_are_executed_by_rising_edges_and_raised_events() {
+    let file = write_source(
+        "simulation_events.st",
+        "VAR_GLOBAL start : BOOL; alarms : DINT; END_VAR
+        PROGRAM prg counter := counter + 1; END_PROGRAM
+        PROGRAM alarm alarms := alarms + 1; END_PROGRAM",
+    );
+    let mut simulation = start_simulation_with_tasks(
+        &file,
+        r#"{ "tasks": [
+            { "name": "edge", "priority": 1, "trigger": "start", "programs": ["prg"] },
+            { "name": "alarm", "priority": 2, "event": 5, "programs": ["alarm"] }
+        ]}"#,
+    );
+
+    //the task with a trigger is polled in every cycle, its programs only run on a rising edge
+    assert_eq!(simulation.run_cycle(), vec!["edge"]);
+    simulation.write("start", "TRUE").unwrap();
+    simulation.run_cycle();
+    simulation.run_cycle();
+    assert_eq!(get_dint(&simulation, "counter"), 1);
+    simulation.write("start", "FALSE").unwrap();
+    simulation.run_cycle();
+    simulation.write("start", "TRUE").unwrap();
+    simulation.run_cycle();
+    assert_eq!(get_dint(&simulation, "counter"), 2);
+
+    simulation.raise_event(5);
+    assert_eq!(simulation.run_cycle(), vec!["edge", "alarm"]);
+    assert_eq!(simulation.run_cycle(), vec!["edge"]);
+    assert_eq!(get_dint(&simulation, "alarms"), 1);
+    fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn reloaded_code_continues_with_the_preserved_state() {
+    let file = write_source(
+        "simulation_reload.st",
+        "PROGRAM prg VAR calls : DINT; END_VAR calls := calls + 1; counter := counter + 1; END_PROGRAM",
+    );
+    let mut simulation = start_simulation(&file);
+    simulation.run_cycle();
+    simulation.run_cycle();
+    assert_eq!(get_dint(&simulation, "counter"), 3);
+
+    //changed logic, unchanged layout
+    write_source(
+        "simulation_reload.st",
+        "PROGRAM prg VAR calls : DINT; END_VAR calls := calls + 1; counter := counter + 100; END_PROGRAM",
+    );
+    let report = simulation.reload_if_changed().unwrap().unwrap();
+    assert_eq!(report.preserved, vec!["counter", "prg_instance"]);
+    assert!(report.reinitialized.is_empty());
+    simulation.run_cycle();
+    assert_eq!(get_dint(&simulation, "counter"), 103);
+    assert_eq!(get_dint(&simulation, "prg_instance"), 4);
+    assert_eq!(simulation.reload_if_changed(), Ok(None));
+
+    //a changed instance layout resets the instance
+    write_source(
+        "simulation_reload.st",
+        "PROGRAM prg VAR calls : LINT; END_VAR calls := calls + 1; counter := counter + 1; END_PROGRAM",
+    );
+    let report = simulation.reload().unwrap();
+    assert_eq!(report.preserved, vec!["counter"]);
+    assert_eq!(report.reinitialized, vec!["prg_instance"]);
+    simulation.run_cycle();
+    assert_eq!(get_dint(&simulation, "counter"), 105);
+    assert_eq!(get_dint(&simulation, "prg_instance"), 2);
+    fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn forced_symbols_are_held_until_released() {
+    let file = write_source(
+        "simulation_force.st",
+        "PROGRAM prg VAR calls : DINT; END_VAR calls := calls + 1; counter := counter + 1; END_PROGRAM",
+    );
+    let mut simulation = start_simulation(&file);
+    simulation.force("counter", " 42").unwrap();
+    assert_eq!(
+        simulation.get_forced(),
+        vec![("counter".to_string(), "42".to_string())]
+    );
+
+    //both tasks increment the forced value, it is restored after every task
+    simulation.run_cycle();
+    assert_eq!(simulation.watch("counter"), Ok("42".to_string()));
+    assert_eq!(simulation.watch("PRG.calls"), Ok("2".to_string()));
+
+    simulation.write("prg.calls", "16#10").unwrap();
+    assert!(simulation.release("counter"));
+    assert!(!simulation.release("counter"));
+    simulation.run_cycle();
+    assert_eq!(simulation.watch("counter"), Ok("43".to_string()));
+    assert_eq!(simulation.watch("prg.calls"), Ok("17".to_string()));
+
+    assert!(simulation.force("prg.unknown", "1").is_err());
+    assert!(simulation.force("counter", "TRUE").is_err());
+    assert!(simulation.watch("unknown").is_err());
+    fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn tasks_exceeding_their_interval_report_their_most_expensive_pous() {
+    let file = write_source(
+        "simulation_budget.st",
+        "FUNCTION busy : DINT
+        VAR_INPUT n : DINT; END_VAR
+        VAR i : DINT; END_VAR
+            FOR i := 1 TO n DO busy := busy + 1; END_FOR
+        END_FUNCTION
+        PROGRAM expensive counter := busy(200000); END_PROGRAM
+        PROGRAM cheap counter := counter + 1; END_PROGRAM",
+    );
+    let tasks = TaskConfiguration::parse(
+        r#"{ "tasks": [
+            { "name": "control", "priority": 1, "interval": 1, "programs": ["expensive"] },
+            { "name": "logging", "priority": 2, "interval": 1000, "programs": ["cheap"] }
+        ]}"#,
+        ConfigFormat::JSON,
+    )
+    .unwrap();
+    let mut simulation = Simulation::new(
+        SimulationSources {
+            sources: vec![FilePath::from(file.as_str())],
+            includes: vec![],
+            generated: vec![],
+            encoding: None,
+            dialect: Dialect::default(),
+            debug_hooks: true,
+        },
+        tasks,
+        1,
+    )
+    .unwrap();
+
+    simulation.run_cycle();
+    let times = simulation.get_task_times();
+    assert_eq!(times.len(), 2);
+    assert_eq!(times[0].task, "control");
+    assert!(times[0].is_overrun(), "{}", times[0]);
+    assert_eq!(times[0].pous[0].0, "busy");
+    assert!(times[0].to_string().contains("most expensive: busy"));
+    assert_eq!(times[1].task, "logging");
+    assert!(!times[1].is_overrun(), "{}", times[1]);
+
+    simulation.run_cycle();
+    assert_eq!(simulation.get_task_times().len(), 1);
+    fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn restored_snapshots_continue_from_the_saved_state() {
+    let file = write_source(
+        "simulation_snapshot.st",
+        "PROGRAM prg VAR calls : DINT; END_VAR calls := calls + 1; counter := counter + 1; END_PROGRAM",
+    );
+    let mut snapshot_file = env::temp_dir();
+    snapshot_file.push("simulation_snapshot.json");
+    let snapshot_file = snapshot_file.into_os_string().into_string().unwrap();
+    let mut simulation = start_simulation(&file);
+    simulation.run_cycle();
+    simulation.run_cycle();
+    simulation.save_snapshot().save(&snapshot_file).unwrap();
+    simulation.run_cycle();
+    simulation.run_cycle();
+    assert_eq!(get_dint(&simulation, "counter"), 6);
+
+    let report = simulation.restore_snapshot(&Snapshot::load(&snapshot_file).unwrap());
+    assert_eq!(report.restored, vec!["counter", "prg_instance"]);
+    assert!(report.skipped.is_empty());
+    assert_eq!(get_dint(&simulation, "counter"), 3);
+    assert_eq!(get_dint(&simulation, "prg_instance"), 3);
+    assert_eq!(simulation.get_time(), 20);
+    assert_eq!(simulation.get_cycle(), 2);
+
+    //the slow task is due again at 30ms
+    assert_eq!(simulation.run_cycle(), vec!["fast"]);
+    assert_eq!(simulation.run_cycle(), vec!["fast", "slow"]);
+    assert_eq!(get_dint(&simulation, "counter"), 6);
+    fs::remove_file(&file).unwrap();
+    fs::remove_file(&snapshot_file).unwrap();
+}
+
+#[test]
+fn scenarios_write_inputs_and_check_outputs_per_cycle() {
+    let file = write_source(
+        "simulation_scenario.st",
+        "PROGRAM prg
+        VAR_INPUT start : BOOL; level : INT; END_VAR
+        VAR open : BOOL; flow : REAL; END_VAR
+            open := start AND level < 50;
+            IF open THEN flow := flow + 2.5; ELSE flow := 0.0; END_IF
+        END_PROGRAM",
+    );
+    let mut simulation = start_simulation(&file);
+    let scenario = Scenario::parse(
+        "valve.csv",
+        "cycle, in prg.start, in prg.level, out prg.open, out prg.flow +- 0.1
+        1, TRUE, 10, TRUE, 5.0
+        # the fast task runs alone in the cycles 2 and 3
+        3, , , TRUE, 10.02
+        4, , 80, FALSE, 1 +- 0.5",
+    )
+    .unwrap();
+
+    let result = scenario.run(&mut simulation).unwrap();
+    assert_eq!(result.cycles, 4);
+    assert_eq!(result.checks, 6);
+    assert_eq!(
+        result.mismatches,
+        vec![Mismatch {
+            cycle: 4,
+            expression: "prg.flow".to_string(),
+            expected: "1 +- 0.5".to_string(),
+            actual: "0".to_string(),
+        }]
+    );
+    assert!(!result.is_passed());
+    assert!(
+        Scenario::parse("unknown.csv", "cycle, in prg.unknown\n1, 1")
+            .unwrap()
+            .run(&mut simulation)
+            .is_err()
+    );
+    fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn test_functions_are_run_with_generated_and_shrunk_inputs() {
+    let file = write_source(
+        "simulation_properties.st",
+        "{test_range x := -100..100}
+        FUNCTION doubled_is_even : BOOL
+        VAR_INPUT x : INT; END_VAR
+            doubled_is_even := x * 2 MOD 2 = 0;
+        END_FUNCTION
+        {test_range x := 0..1000}
+        FUNCTION small_values_only : BOOL
+        VAR_INPUT x : DINT; flag : BOOL; END_VAR
+            small_values_only := x < 37 OR flag;
+        END_FUNCTION
+        {test}
+        FUNCTION always : BOOL
+            always := TRUE;
+        END_FUNCTION",
+    );
+    let results = properties::run_tests(
+        SimulationSources {
+            sources: vec![FilePath::from(file.as_str())],
+            includes: vec![],
+            generated: vec![],
+            encoding: None,
+            dialect: Dialect::default(),
+            debug_hooks: false,
+        },
+        &PropertySettings {
+            cases: 200,
+            seed: 7,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].name, "doubled_is_even");
+    assert!(results[0].is_passed());
+    assert_eq!(results[0].cases, 200);
+    assert_eq!(results[1].name, "small_values_only");
+    assert_eq!(
+        results[1].counterexample,
+        Some(vec![
+            ("x".to_string(), "37".to_string()),
+            ("flag".to_string(), "FALSE".to_string())
+        ])
+    );
+    assert_eq!(results[2].name, "always");
+    assert!(results[2].is_passed());
+    assert_eq!(results[2].cases, 1);
+    fs::remove_file(&file).unwrap();
+}
@@ -0,0 +1,74 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+use std::{env, fs};
+
+use rusty::{
+    build, get_target_triple,
+    size_budget::{check_budget, SizeEntry},
+    CompileOptions, ErrorFormat, FilePath, FormatOption,
+};
+
+use crate::get_test_file;
+
+static TARGET: Option<&str> = Some("x86_64-unknown-linux-gnu");
+
+#[test]
+fn code_and_ram_are_measured_per_pou() {
+    let mut out = env::temp_dir();
+    out.push("size_budget.o");
+    let out = out.into_os_string().into_string().unwrap();
+
+    let result = build(
+        vec![FilePath {
+            path: get_test_file("abi_metadata.st"),
+        }],
+        vec![],
+        &CompileOptions {
+            output: out.clone(),
+            format: FormatOption::Static,
+            target: TARGET.map(String::from),
+            optimization: rusty::OptimizationLevel::None,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
+        },
+        None,
+        &ErrorFormat::Rich,
+        &get_target_triple(TARGET),
+    )
+    .unwrap();
+    fs::remove_file(&out).unwrap();
+
+    let code = result.sizes.code.unwrap();
+    assert!(code.total > 0);
+    let code_size = |name: &str| {
+        code.entries
+            .iter()
+            .find(|it| it.name == name)
+            .map(|it| it.size)
+            .unwrap_or_default()
+    };
+    assert!(code_size("prg") > 0);
+    assert!(code_size("motor") > 0);
+    assert!(code.entries.iter().map(|it| it.size).sum::<u64>() <= code.total);
+
+    let ram = result.sizes.ram.unwrap();
+    assert!(ram.entries.contains(&SizeEntry {
+        name: "prg".into(),
+        size: 24,
+    }));
+    assert!(ram.entries.contains(&SizeEntry {
+        name: "total".into(),
+        size: 8,
+    }));
+
+    assert!(check_budget("RAM usage", Some(&ram), Some(ram.total)).is_ok());
+    assert!(check_budget("RAM usage", Some(&ram), Some(ram.total - 1)).is_err());
+}
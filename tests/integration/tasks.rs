@@ -0,0 +1,207 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+use std::{env, fs};
+
+use rusty::{
+    build_with_tasks,
+    diagnostics::{Diagnostic, Diagnostician},
+    get_target_triple,
+    task_configuration::TaskConfiguration,
+    CompileOptions, ConfigFormat, FilePath, FormatOption, ModuleOptions,
+};
+
+use crate::get_test_file;
+
+fn build_with_task_config(name: &str, tasks: &str) -> Result<String, Diagnostic> {
+    build_with_task_config_from("tasks.st", name, tasks)
+}
+
+fn build_with_task_config_from(
+    source: &str,
+    name: &str,
+    tasks: &str,
+) -> Result<String, Diagnostic> {
+    let mut out = env::temp_dir();
+    out.push(name);
+    let out = out.into_os_string().into_string().unwrap();
+    let tasks = TaskConfiguration::parse(tasks, ConfigFormat::JSON).unwrap();
+
+    build_with_tasks(
+        vec![FilePath {
+            path: get_test_file(source),
+        }],
+        vec![],
+        &CompileOptions {
+            output: out.clone(),
+            format: FormatOption::IR,
+            target: None,
+            optimization: rusty::OptimizationLevel::None,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
+        },
+        None,
+        Diagnostician::default(),
+        &get_target_triple(None),
+        ModuleOptions {
+            tasks: Some(&tasks),
+            ..ModuleOptions::default()
+        },
+    )?;
+    let ir = fs::read_to_string(&out).unwrap();
+    fs::remove_file(&out).unwrap();
+    Ok(ir)
+}
+
+#[test]
+fn task_table_is_generated_for_the_task_configuration() {
+    let ir = build_with_task_config(
+        "task_table.ll",
+        r#"{ "tasks": [
+            { "name": "fast", "priority": 1, "interval": 10, "programs": ["motion", "hmi"] }
+        ]}"#,
+    )
+    .unwrap();
+
+    assert!(ir.contains("@__rusty_task_count = unnamed_addr constant i32 1"));
+    assert!(ir.contains("@__rusty_retain_count = unnamed_addr constant i32 1"));
+    assert!(ir.contains("define void @__rusty_task_fast()"));
+    assert!(ir.contains("call void @motion(%motion_interface* @motion_instance)"));
+    assert!(ir.contains("call void @hmi(%hmi_interface* @hmi_instance)"));
+}
+
+#[test]
+fn event_tasks_are_registered_with_their_trigger_and_event() {
+    let ir = build_with_task_config(
+        "event_tasks.ll",
+        r#"{ "tasks": [
+            { "name": "edge", "trigger": "motion.position", "programs": ["hmi"] },
+            { "name": "alarm", "event": 3, "programs": ["motion"] }
+        ]}"#,
+    );
+    //only BOOL variables can trigger a task
+    assert_eq!(
+        ir.err(),
+        Some(Diagnostic::param_error(
+            "Task 'edge' cannot be triggered by 'motion.position' of type LREAL, \
+            only BOOL variables can trigger a task"
+        ))
+    );
+
+    let ir = build_with_task_config_from(
+        "event_tasks.st",
+        "event_tasks.ll",
+        r#"{ "tasks": [
+            { "name": "edge", "trigger": "io.start", "programs": ["hmi"] },
+            { "name": "alarm", "event": 3, "core": 1, "programs": ["motion"] }
+        ]}"#,
+    )
+    .unwrap();
+    assert!(ir.contains("@__rusty_task_edge_trigger = private global i8 0"));
+    assert!(ir.contains("br i1 %rising_edge, label %execute, label %exit"));
+    assert!(ir.contains("void ()* @__rusty_task_edge, i32 -1, i32 -1 }"));
+    assert!(ir.contains("void ()* @__rusty_task_alarm, i32 3, i32 1 }"));
+}
+
+#[test]
+fn symbol_table_is_generated_for_the_task_configuration() {
+    let ir = build_with_task_config(
+        "symbol_table.ll",
+        r#"{ "tasks": [ { "name": "fast", "programs": ["motion"] } ] }"#,
+    )
+    .unwrap();
+
+    //the root, the programs hmi and motion and the members of motion
+    assert!(ir.contains("@__rusty_symbol_count = unnamed_addr constant i32 5"));
+    assert!(ir.contains("i64 ptrtoint (%motion_interface* @motion_instance to i64)"));
+    assert!(ir.contains("i32 3, i32 2 }"));
+}
+
+#[test]
+fn telemetry_is_serialized_by_the_generated_publisher() {
+    let ir = build_with_task_config(
+        "telemetry.ll",
+        r#"{ "tasks": [ { "name": "fast", "programs": ["motion"] } ],
+             "telemetry": { "interval": 500, "symbols": ["motion.speed", "motion.position"] } }"#,
+    )
+    .unwrap();
+
+    assert!(ir.contains("@__rusty_telemetry_interval = unnamed_addr constant i64 500"));
+    assert!(ir.contains("define i32 @__rusty_telemetry(i8* %0, i32 %1)"));
+    assert!(ir.contains("c\"{\\22motion.speed\\22:\\00\""));
+    assert!(ir.contains("call i32 @__rusty_json_lint(i8* %0, i32 %1, i32 %"));
+    assert!(ir.contains("call i32 @__rusty_json_lreal(i8* %0, i32 %1, i32 %"));
+}
+
+#[test]
+fn unknown_telemetry_symbols_are_reported() {
+    let result = build_with_task_config(
+        "telemetry_unknown.ll",
+        r#"{ "tasks": [ { "name": "fast", "programs": ["motion"] } ],
+             "telemetry": { "symbols": ["motion.torque"] } }"#,
+    );
+
+    assert_eq!(
+        result.err(),
+        Some(Diagnostic::param_error(
+            "Cannot publish unknown symbol 'motion.torque'"
+        ))
+    );
+}
+
+#[test]
+fn unknown_programs_in_tasks_are_reported() {
+    let result = build_with_task_config(
+        "task_table_unknown.ll",
+        r#"{ "tasks": [ { "name": "fast", "programs": ["conveyor"] } ] }"#,
+    );
+
+    assert_eq!(
+        result.err(),
+        Some(Diagnostic::param_error(
+            "Task 'fast' executes unknown program 'conveyor'"
+        ))
+    );
+}
+
+#[test]
+fn program_instances_get_their_own_instance_struct_and_cycle_function() {
+    let ir = build_with_task_config(
+        "program_instances.ll",
+        r#"{ "tasks": [ { "name": "stations", "programs": ["motion_a", "motion_b", "hmi"] } ],
+             "instances": [
+                { "name": "motion_a", "program": "motion" },
+                { "name": "motion_b", "program": "motion" }
+             ] }"#,
+    )
+    .unwrap();
+
+    assert!(ir.contains("@motion_a_instance = global %motion_interface zeroinitializer"));
+    assert!(ir.contains("@motion_b_instance = global %motion_interface zeroinitializer"));
+    assert!(ir.contains("define void @motion_a()"));
+    assert!(ir.contains("call void @motion(%motion_interface* @motion_b_instance)"));
+    assert!(ir.contains("call void @motion_a()"));
+    assert!(ir.contains("call void @hmi(%hmi_interface* @hmi_instance)"));
+}
+
+#[test]
+fn program_instances_of_unknown_programs_are_reported() {
+    let result = build_with_task_config(
+        "program_instances_unknown.ll",
+        r#"{ "instances": [ { "name": "conveyor_a", "program": "conveyor" } ] }"#,
+    );
+
+    assert_eq!(
+        result.err(),
+        Some(Diagnostic::param_error(
+            "Program instance 'conveyor_a' instantiates unknown program 'conveyor'"
+        ))
+    );
+}
@@ -0,0 +1,55 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+use std::{env, fs};
+
+use rusty::{build, get_target_triple, CompileOptions, ErrorFormat, FilePath, FormatOption};
+
+use crate::get_test_file;
+
+static TARGET: Option<&str> = Some("wasm32-unknown-unknown");
+
+#[test]
+fn pous_are_exported_and_memory_functions_generated_for_wasm() {
+    let mut out = env::temp_dir();
+    out.push("wasm.ll");
+    let out = out.into_os_string().into_string().unwrap();
+    let triple = get_target_triple(TARGET);
+
+    build(
+        vec![FilePath {
+            path: get_test_file("wasm.st"),
+        }],
+        vec![],
+        &CompileOptions {
+            output: out.clone(),
+            format: FormatOption::IR,
+            target: TARGET.map(String::from),
+            optimization: rusty::OptimizationLevel::None,
+            reflection: false,
+            debug: false,
+            llvm_args: vec![],
+            disable_tail_calls: false,
+            fast_math: false,
+            jobs: 1,
+            cpu: None,
+            features: None,
+            lto: false,
+            check_bounds: false,
+        },
+        None,
+        &ErrorFormat::Rich,
+        &triple,
+    )
+    .unwrap();
+    let ir = fs::read_to_string(&out).unwrap();
+    fs::remove_file(&out).unwrap();
+
+    //the module is laid out with the 32 bit pointers of the target
+    assert!(ir.contains(r#"target triple = "wasm32-unknown-unknown""#));
+    assert!(ir.contains(r#"target datalayout = "e-m:e-p:32:32"#));
+    assert!(ir.contains(r#""wasm-export-name"="main""#));
+    assert!(ir.contains(r#""wasm-export-name"="greet""#));
+    assert!(ir.contains("define weak hidden i8* @memcpy(i8* %0, i8* %1, i32 %2)"));
+    assert!(ir.contains("define weak hidden i8* @memmove(i8* %0, i8* %1, i32 %2)"));
+    assert!(ir.contains("define weak hidden i8* @memset(i8* %0, i32 %1, i32 %2)"));
+}
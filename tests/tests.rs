@@ -24,6 +24,7 @@ mod correctness {
     mod initial_values;
     mod methods;
     mod pointers;
+    mod reflection;
     mod strings;
     mod sub_range_types;
     mod math_operators {
@@ -36,9 +37,20 @@ mod correctness {
 }
 
 mod integration {
+    mod abi_metadata;
+    mod bounds_check;
+    mod cross_compilation;
+    mod debugging;
     mod external_files;
+    mod library_package;
     mod linking;
     mod multi_files;
+    mod optimization;
+    mod sections;
+    mod simulation;
+    mod size_budget;
+    mod tasks;
+    mod wasm;
 }
 
 #[macro_export]